@@ -21,7 +21,7 @@ use {json, Error, crypto};
 use crypto::Keccak256;
 use random::Random;
 use smallvec::SmallVec;
-use account::{Cipher, Kdf, Aes128Ctr, Pbkdf2, Prf};
+use account::{Cipher, Kdf, KdfParams, Aes128Ctr, Pbkdf2, Scrypt, Prf};
 
 /// Encrypted data
 #[derive(Debug, PartialEq, Clone)]
@@ -78,15 +78,27 @@ impl Crypto {
 		Crypto::with_plain(secret.as_ref(), password, iterations)
 	}
 
+	/// Encrypt account secret using given key derivation parameters
+	pub fn with_secret_kdf(secret: &Secret, password: &Password, kdf: KdfParams) -> Result<Self, crypto::Error> {
+		Crypto::with_plain_kdf(secret.as_ref(), password, kdf)
+	}
+
 	/// Encrypt custom plain data
 	pub fn with_plain(plain: &[u8], password: &Password, iterations: u32) -> Result<Self, crypto::Error> {
+		Crypto::with_plain_kdf(plain, password, KdfParams::Pbkdf2 { iterations })
+	}
+
+	/// Encrypt custom plain data using given key derivation parameters
+	pub fn with_plain_kdf(plain: &[u8], password: &Password, kdf: KdfParams) -> Result<Self, crypto::Error> {
 		let salt: [u8; 32] = Random::random();
 		let iv: [u8; 16] = Random::random();
 
 		// two parts of derived key
 		// DK = [ DK[0..15] DK[16..31] ] = [derived_left_bits, derived_right_bits]
-		let (derived_left_bits, derived_right_bits) =
-			crypto::derive_key_iterations(password.as_bytes(), &salt, iterations);
+		let (derived_left_bits, derived_right_bits) = match kdf {
+			KdfParams::Pbkdf2 { iterations } => crypto::derive_key_iterations(password.as_bytes(), &salt, iterations),
+			KdfParams::Scrypt { n, r, p } => crypto::scrypt::derive_key(password.as_bytes(), &salt, n, p, r)?,
+		};
 
 		// preallocated (on-stack in case of `Secret`) buffer to hold cipher
 		// length = length(plain) as we are using CTR-approach
@@ -104,12 +116,21 @@ impl Crypto {
 				iv: iv,
 			}),
 			ciphertext: ciphertext.into_vec(),
-			kdf: Kdf::Pbkdf2(Pbkdf2 {
-				dklen: crypto::KEY_LENGTH as u32,
-				salt: salt.to_vec(),
-				c: iterations,
-				prf: Prf::HmacSha256,
-			}),
+			kdf: match kdf {
+				KdfParams::Pbkdf2 { iterations } => Kdf::Pbkdf2(Pbkdf2 {
+					dklen: crypto::KEY_LENGTH as u32,
+					salt: salt.to_vec(),
+					c: iterations,
+					prf: Prf::HmacSha256,
+				}),
+				KdfParams::Scrypt { n, r, p } => Kdf::Scrypt(Scrypt {
+					dklen: crypto::KEY_LENGTH as u32,
+					salt: salt.to_vec(),
+					n: n,
+					r: r,
+					p: p,
+				}),
+			},
 			mac: mac,
 		})
 	}
@@ -161,6 +182,7 @@ impl Crypto {
 mod tests {
 	use crypto::publickey::{Generator, Random};
 	use super::{Crypto, Error};
+	use account::{Kdf, KdfParams};
 
 	#[test]
 	fn crypto_with_secret_create() {
@@ -204,4 +226,15 @@ mod tests {
 		let decrypted_data = crypto.decrypt(&passwd).unwrap();
 		assert_eq!(&original_data, &decrypted_data);
 	}
+
+	#[test]
+	fn crypto_with_secret_scrypt() {
+		let keypair = Random.generate();
+		let passwd = "this is sparta".into();
+		let kdf = KdfParams::Scrypt { n: 1024, r: 8, p: 1 };
+		let crypto = Crypto::with_secret_kdf(keypair.secret(), &passwd, kdf).unwrap();
+		assert_matches!(crypto.kdf, Kdf::Scrypt(_));
+		assert!(kdf.matches(&crypto.kdf));
+		assert_eq!(keypair.secret(), &crypto.secret(&passwd).unwrap());
+	}
 }
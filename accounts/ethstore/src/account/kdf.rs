@@ -44,6 +44,58 @@ pub enum Kdf {
 	Scrypt(Scrypt),
 }
 
+/// Key derivation parameters used when encrypting new key files.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KdfParams {
+	/// PBKDF2 with HMAC-SHA256 and given number of iterations
+	Pbkdf2 {
+		/// Number of iterations
+		iterations: u32,
+	},
+	/// Scrypt with given cost parameters
+	Scrypt {
+		/// CPU/memory cost parameter, must be a power of two
+		n: u32,
+		/// Block size
+		r: u32,
+		/// Parallelization parameter
+		p: u32,
+	},
+}
+
+/// Highest accepted number of PBKDF2 iterations.
+pub const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+/// Highest accepted memory cost of scrypt (`128 * n * r` bytes).
+pub const MAX_SCRYPT_MEMORY: u64 = 1024 * 1024 * 1024;
+/// Highest accepted scrypt parallelization parameter.
+pub const MAX_SCRYPT_P: u32 = 16;
+
+impl KdfParams {
+	/// Checks that the parameters can be used to derive keys in a bounded time and memory.
+	pub fn validate(&self) -> Result<(), String> {
+		match *self {
+			KdfParams::Pbkdf2 { iterations } if iterations == 0 || iterations > MAX_PBKDF2_ITERATIONS =>
+				Err(format!("PBKDF2 iterations must be between 1 and {}", MAX_PBKDF2_ITERATIONS)),
+			KdfParams::Scrypt { n, .. } if n < 2 || !n.is_power_of_two() =>
+				Err("scrypt parameter `n` must be a power of two".into()),
+			KdfParams::Scrypt { n, r, .. } if r == 0 || (n as u64).saturating_mul(r as u64).saturating_mul(128) > MAX_SCRYPT_MEMORY =>
+				Err(format!("scrypt parameters `n` and `r` must use at most {} bytes of memory", MAX_SCRYPT_MEMORY)),
+			KdfParams::Scrypt { p, .. } if p == 0 || p > MAX_SCRYPT_P =>
+				Err(format!("scrypt parameter `p` must be between 1 and {}", MAX_SCRYPT_P)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Returns true if `kdf` was produced with these parameters.
+	pub fn matches(&self, kdf: &Kdf) -> bool {
+		match (*self, kdf) {
+			(KdfParams::Pbkdf2 { iterations }, &Kdf::Pbkdf2(ref params)) => params.c == iterations,
+			(KdfParams::Scrypt { n, r, p }, &Kdf::Scrypt(ref params)) => params.n == n && params.r == r && params.p == p,
+			_ => false,
+		}
+	}
+}
+
 impl From<json::Prf> for Prf {
 	fn from(json: json::Prf) -> Self {
 		match json {
@@ -18,7 +18,7 @@ use crypto::publickey::{KeyPair, sign, Address, Signature, Message, Public, Secr
 use ethkey::Password;
 use crypto::publickey::ecdh::agree;
 use {json, Error};
-use account::Version;
+use account::{KdfParams, Version};
 use crypto;
 use super::crypto::Crypto;
 
@@ -60,14 +60,14 @@ impl SafeAccount {
 		keypair: &KeyPair,
 		id: [u8; 16],
 		password: &Password,
-		iterations: u32,
+		kdf: KdfParams,
 		name: String,
 		meta: String
 	) -> Result<Self, crypto::Error> {
 		Ok(SafeAccount {
 			id: id,
 			version: Version::V3,
-			crypto: Crypto::with_secret(keypair.secret(), password, iterations)?,
+			crypto: Crypto::with_secret_kdf(keypair.secret(), password, kdf)?,
 			address: keypair.address(),
 			filename: None,
 			name: name,
@@ -178,12 +178,12 @@ impl SafeAccount {
 	}
 
 	/// Change account's password.
-	pub fn change_password(&self, old_password: &Password, new_password: &Password, iterations: u32) -> Result<Self, Error> {
+	pub fn change_password(&self, old_password: &Password, new_password: &Password, kdf: KdfParams) -> Result<Self, Error> {
 		let secret = self.crypto.secret(old_password)?;
 		let result = SafeAccount {
 			id: self.id.clone(),
 			version: self.version.clone(),
-			crypto: Crypto::with_secret(&secret, new_password, iterations)?,
+			crypto: Crypto::with_secret_kdf(&secret, new_password, kdf)?,
 			address: self.address.clone(),
 			filename: self.filename.clone(),
 			name: self.name.clone(),
@@ -192,6 +192,16 @@ impl SafeAccount {
 		Ok(result)
 	}
 
+	/// Re-encrypt account's secret with the same password and new key derivation parameters.
+	pub fn reencrypt(&self, password: &Password, kdf: KdfParams) -> Result<Self, Error> {
+		let secret = self.crypto.secret(password)?;
+		let result = SafeAccount {
+			crypto: Crypto::with_secret_kdf(&secret, password, kdf)?,
+			..self.clone()
+		};
+		Ok(result)
+	}
+
 	/// Check if password matches the account.
 	pub fn check_password(&self, password: &Password) -> bool {
 		self.crypto.secret(password).is_ok()
@@ -202,13 +212,14 @@ impl SafeAccount {
 mod tests {
 	use crypto::publickey::{Generator, Random, verify_public};
 	use super::SafeAccount;
+	use account::KdfParams;
 
 	#[test]
 	fn sign_and_verify_public() {
 		let keypair = Random.generate();
 		let password = "hello world".into();
 		let message = [1u8; 32].into();
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, 10240, "Test".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, KdfParams::Pbkdf2 { iterations: 10240 }, "Test".to_owned(), "{}".to_owned());
 		let signature = account.unwrap().sign(&password, &message).unwrap();
 		assert!(verify_public(keypair.public(), &signature, &message).unwrap());
 	}
@@ -218,7 +229,7 @@ mod tests {
 		let keypair = Random.generate();
 		let first_password = "hello world".into();
 		let sec_password = "this is sparta".into();
-		let i = 10240;
+		let i = KdfParams::Pbkdf2 { iterations: 10240 };
 		let message = [1u8; 32].into();
 		let account = SafeAccount::create(&keypair, [0u8; 16], &first_password, i, "Test".to_owned(), "{}".to_owned()).unwrap();
 		let new_account = account.change_password(&first_password, &sec_password, i).unwrap();
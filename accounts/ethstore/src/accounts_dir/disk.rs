@@ -355,7 +355,7 @@ mod test {
 
 	use std::{env, fs};
 	use super::{KeyDirectory, RootDiskDirectory, VaultKey};
-	use account::SafeAccount;
+	use account::{SafeAccount, KdfParams};
 	use crypto::publickey::{Random, Generator};
 	use self::tempfile::TempDir;
 
@@ -369,7 +369,7 @@ mod test {
 		let directory = RootDiskDirectory::create(dir.clone()).unwrap();
 
 		// when
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, 1024, "Test".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, KdfParams::Pbkdf2 { iterations: 1024 }, "Test".to_owned(), "{}".to_owned());
 		let res = directory.insert(account.unwrap());
 
 		// then
@@ -390,7 +390,7 @@ mod test {
 		let directory = RootDiskDirectory::create(dir.clone()).unwrap();
 
 		// when
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, 1024, "Test".to_owned(), "{}".to_owned()).unwrap();
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, KdfParams::Pbkdf2 { iterations: 1024 }, "Test".to_owned(), "{}".to_owned()).unwrap();
 		let filename = "test".to_string();
 		let dedup = true;
 
@@ -474,7 +474,7 @@ mod test {
 
 		let keypair = Random.generate();
 		let password = "test pass".into();
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, 1024, "Test".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, KdfParams::Pbkdf2 { iterations: 1024 }, "Test".to_owned(), "{}".to_owned());
 		directory.insert(account.unwrap()).expect("Account should be inserted ok");
 
 		let new_hash = directory.files_hash().expect("New files hash should be calculated ok");
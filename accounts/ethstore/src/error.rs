@@ -52,6 +52,8 @@ pub enum Error {
 	EthPublicKeyCrypto(EthPublicKeyCryptoError),
 	/// Derivation error
 	Derivation(DerivationError),
+	/// Key derivation parameters are out of bounds
+	InvalidKdfParams(String),
 	/// Custom error
 	Custom(String),
 }
@@ -74,6 +76,7 @@ impl fmt::Display for Error {
 			Error::EthCrypto(ref err) => err.to_string(),
 			Error::EthPublicKeyCrypto(ref err) => err.to_string(),
 			Error::Derivation(ref err) => format!("Derivation error: {:?}", err),
+			Error::InvalidKdfParams(ref reason) => format!("Invalid key derivation parameters: {}", reason),
 			Error::Custom(ref s) => s.clone(),
 		};
 
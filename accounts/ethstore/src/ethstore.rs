@@ -24,10 +24,13 @@ use random::Random;
 use crypto::publickey::{Signature, Address, Message, Secret, Public, KeyPair, ExtendedKeyPair};
 use ethkey::Password;
 use accounts_dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
-use account::SafeAccount;
+use account::{SafeAccount, Crypto, KdfParams};
 use presale::PresaleWallet;
 use json::{self, Uuid, OpaqueKeyFile};
-use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret};
+use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret, Reencryption};
+
+/// Version of the keystore archive format produced by `export_vault`.
+pub const ARCHIVE_VERSION: u32 = 1;
 
 /// Accounts store.
 pub struct EthStore {
@@ -42,8 +45,13 @@ impl EthStore {
 
 	/// Open a new account store with given key directory backend and custom number of iterations.
 	pub fn open_with_iterations(directory: Box<dyn KeyDirectory>, iterations: u32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfParams::Pbkdf2 { iterations })
+	}
+
+	/// Open a new account store with given key directory backend and custom key derivation parameters for new keys.
+	pub fn open_with_kdf(directory: Box<dyn KeyDirectory>, kdf: KdfParams) -> Result<Self, Error> {
 		Ok(EthStore {
-			store: EthMultiStore::open_with_iterations(directory, iterations)?,
+			store: EthMultiStore::open_with_kdf(directory, kdf)?,
 		})
 	}
 
@@ -152,6 +160,20 @@ impl SimpleSecretStore for EthStore {
 	fn set_vault_meta(&self, name: &str, meta: &str) -> Result<(), Error> {
 		self.store.set_vault_meta(name, meta)
 	}
+
+	fn export_vault(&self, vault: SecretVaultRef, archive_password: &Password) -> Result<Vec<u8>, Error> {
+		self.store.export_vault(vault, archive_password)
+	}
+
+	fn import_vault(&self, vault: SecretVaultRef, archive: &[u8], archive_password: &Password) -> Result<Vec<StoreAccountRef>, Error> {
+		self.store.import_vault(vault, archive, archive_password)
+	}
+
+	fn reencrypt_accounts(&self, vault: SecretVaultRef, passwords: &[Password], kdf: KdfParams, progress: &dyn Fn(usize, usize))
+		-> Result<Reencryption, Error>
+	{
+		self.store.reencrypt_accounts(vault, passwords, kdf, progress)
+	}
 }
 
 impl SecretStore for EthStore {
@@ -257,7 +279,10 @@ impl SecretStore for EthStore {
 /// Similar to `EthStore` but may store many accounts (with different passwords) for the same `Address`
 pub struct EthMultiStore {
 	dir: Box<dyn KeyDirectory>,
+	// PBKDF2 iterations for vault and archive files
 	iterations: u32,
+	// key derivation parameters for account key files
+	kdf: KdfParams,
 	// order lock: cache, then vaults
 	cache: RwLock<BTreeMap<StoreAccountRef, Vec<SafeAccount>>>,
 	vaults: Mutex<HashMap<String, Box<dyn VaultKeyDirectory>>>,
@@ -278,10 +303,20 @@ impl EthMultiStore {
 
 	/// Open new multi-accounts store with given key directory backend and custom number of iterations for new keys.
 	pub fn open_with_iterations(directory: Box<dyn KeyDirectory>, iterations: u32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfParams::Pbkdf2 { iterations })
+	}
+
+	/// Open new multi-accounts store with given key directory backend and custom key derivation parameters for new keys.
+	pub fn open_with_kdf(directory: Box<dyn KeyDirectory>, kdf: KdfParams) -> Result<Self, Error> {
+		let iterations = match kdf {
+			KdfParams::Pbkdf2 { iterations } => iterations,
+			KdfParams::Scrypt { .. } => KEY_ITERATIONS as u32,
+		};
 		let store = EthMultiStore {
 			dir: directory,
 			vaults: Mutex::new(HashMap::new()),
 			iterations: iterations,
+			kdf: kdf,
 			cache: Default::default(),
 			timestamp: Mutex::new(Timestamp {
 				dir_hash: None,
@@ -436,6 +471,22 @@ impl EthMultiStore {
 		return Ok(());
 	}
 
+	fn vault_accounts(&self, vault: &SecretVaultRef) -> Result<Vec<(StoreAccountRef, SafeAccount)>, Error> {
+		if let SecretVaultRef::Vault(ref vault_name) = *vault {
+			if !self.vaults.lock().contains_key(vault_name) {
+				return Err(Error::VaultNotFound);
+			}
+		}
+
+		self.reload_if_changed()?;
+		let cache = self.cache.read();
+		Ok(cache.iter()
+			.filter(|&(account_ref, _)| account_ref.vault == *vault)
+			.flat_map(|(account_ref, accounts)| accounts.iter().map(move |account| (account_ref.clone(), account.clone())))
+			.collect()
+		)
+	}
+
 	fn generate(&self, secret: Secret, derivation: Derivation) -> Result<ExtendedKeyPair, Error> {
 		let mut extended = ExtendedKeyPair::new(secret);
 		match derivation {
@@ -458,7 +509,7 @@ impl SimpleSecretStore for EthMultiStore {
 	fn insert_account(&self, vault: SecretVaultRef, secret: Secret, password: &Password) -> Result<StoreAccountRef, Error> {
 		let keypair = KeyPair::from_secret(secret).map_err(|_| Error::CreationFailed)?;
 		let id: [u8; 16] = Random::random();
-		let account = SafeAccount::create(&keypair, id, password, self.iterations, "".to_owned(), "{}".to_owned())?;
+		let account = SafeAccount::create(&keypair, id, password, self.kdf, "".to_owned(), "{}".to_owned())?;
 		self.import(vault, account)
 	}
 
@@ -537,7 +588,7 @@ impl SimpleSecretStore for EthMultiStore {
 
 		for account in accounts {
 			// Change password
-			let new_account = account.change_password(old_password, new_password, self.iterations)?;
+			let new_account = account.change_password(old_password, new_password, self.kdf)?;
 			self.update(account_ref, account, new_account)?;
 		}
 
@@ -683,6 +734,100 @@ impl SimpleSecretStore for EthMultiStore {
 			.ok_or(Error::VaultNotFound)
 			.and_then(|v| v.set_meta(meta))
 	}
+
+	fn export_vault(&self, vault: SecretVaultRef, archive_password: &Password) -> Result<Vec<u8>, Error> {
+		let accounts = self.vault_accounts(&vault)?;
+		let (vault_name, vault_meta) = match vault {
+			SecretVaultRef::Root => (None, None),
+			SecretVaultRef::Vault(name) => {
+				let meta = self.get_vault_meta(&name)?;
+				(Some(name), Some(meta))
+			},
+		};
+
+		let keys = accounts.into_iter()
+			.map(|(_, mut account)| {
+				if vault_name.is_some() {
+					account.meta = json::remove_vault_name_from_json_meta(&account.meta)
+						.map_err(|err| Error::Custom(format!("{:?}", err)))?;
+				}
+				Ok(account.into())
+			})
+			.collect::<Result<Vec<json::KeyFile>, Error>>()?;
+		let keys_count = keys.len();
+
+		let contents = json::ArchiveContents {
+			meta: vault_meta,
+			keys: keys,
+		}.to_vec().map_err(|e| Error::Custom(format!("{:?}", e)))?;
+
+		let archive = json::Archive {
+			version: ARCHIVE_VERSION,
+			vault: vault_name,
+			crypto: Crypto::with_plain(&contents, archive_password, self.iterations)?.into(),
+		};
+
+		let mut result = Vec::new();
+		archive.write(&mut result).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		info!(target: "ethstore", "Exported {} key(s) to keystore archive", keys_count);
+		Ok(result)
+	}
+
+	fn import_vault(&self, vault: SecretVaultRef, archive: &[u8], archive_password: &Password) -> Result<Vec<StoreAccountRef>, Error> {
+		let archive = json::Archive::load(archive).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		if archive.version != ARCHIVE_VERSION {
+			return Err(Error::Custom(format!("Unsupported keystore archive version: {}", archive.version)));
+		}
+
+		let crypto: Crypto = archive.crypto.into();
+		let contents = crypto.decrypt(archive_password)?;
+		let contents = json::ArchiveContents::load(&contents).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+
+		let mut imported = Vec::with_capacity(contents.keys.len());
+		for key in contents.keys {
+			let account = SafeAccount::from_file(key, None, &None)?;
+			let account_ref = StoreAccountRef::new(vault.clone(), account.address);
+			let exists = self.get_accounts(&account_ref)
+				.map(|accounts| accounts.iter().any(|acc| acc.id == account.id && acc.crypto == account.crypto))
+				.unwrap_or(false);
+			if exists {
+				continue;
+			}
+
+			imported.push(self.import(vault.clone(), account)?);
+		}
+
+		info!(target: "ethstore", "Imported {} key(s) from keystore archive", imported.len());
+		Ok(imported)
+	}
+
+	fn reencrypt_accounts(&self, vault: SecretVaultRef, passwords: &[Password], kdf: KdfParams, progress: &dyn Fn(usize, usize))
+		-> Result<Reencryption, Error>
+	{
+		kdf.validate().map_err(Error::InvalidKdfParams)?;
+		let accounts = self.vault_accounts(&vault)?;
+		let total = accounts.len();
+		let mut result = Reencryption::default();
+
+		for (index, (account_ref, account)) in accounts.into_iter().enumerate() {
+			if kdf.matches(&account.crypto.kdf) {
+				result.unchanged.push(account_ref);
+			} else {
+				match passwords.iter().find(|password| account.check_password(password)) {
+					Some(password) => {
+						let new_account = account.reencrypt(password, kdf)?;
+						self.update(&account_ref, account, new_account)?;
+						result.reencrypted.push(account_ref);
+					},
+					None => result.locked.push(account_ref),
+				}
+			}
+
+			progress(index + 1, total);
+		}
+
+		Ok(result)
+	}
 }
 
 #[cfg(test)]
@@ -693,6 +838,8 @@ mod tests {
 	use crypto::publickey::{Random, Generator, KeyPair};
 	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
 	use super::{EthStore, EthMultiStore};
+	use account::KdfParams;
+	use std::cell::Cell;
 	use self::tempfile::TempDir;
 	use ethereum_types::H256;
 
@@ -1127,4 +1274,76 @@ mod tests {
 		// then
 		assert!(exported.is_ok(), "Should export single account: {:?}", exported);
 	}
+
+	#[test]
+	fn should_export_and_import_vault_archive() {
+		// given
+		let mut dir = RootDiskDirectoryGuard::new();
+		let store = EthStore::open(dir.key_dir.take().unwrap()).unwrap();
+		let name = "vault"; let vault_password = "vault_password".into();
+		let archive_password = "archive_password".into();
+		store.create_vault(name, &vault_password).unwrap();
+		let account1 = store.insert_account(SecretVaultRef::Vault(name.to_owned()), keypair().secret().clone(), &"password1".into()).unwrap();
+		let account2 = store.insert_account(SecretVaultRef::Vault(name.to_owned()), keypair().secret().clone(), &"password2".into()).unwrap();
+		store.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"password3".into()).unwrap();
+
+		// when
+		let archive = store.export_vault(SecretVaultRef::Vault(name.to_owned()), &archive_password).unwrap();
+		let other_store = multi_store();
+
+		// then
+		assert!(other_store.import_vault(SecretVaultRef::Root, &archive, &"wrong".into()).is_err());
+		let mut imported = other_store.import_vault(SecretVaultRef::Root, &archive, &archive_password).unwrap();
+		imported.sort();
+		let mut expected = vec![StoreAccountRef::root(account1.address), StoreAccountRef::root(account2.address)];
+		expected.sort();
+		assert_eq!(imported, expected);
+		assert!(other_store.sign(&StoreAccountRef::root(account1.address), &"password1".into(), &Default::default()).is_ok());
+		assert!(other_store.sign(&StoreAccountRef::root(account2.address), &"password2".into(), &Default::default()).is_ok());
+
+		// importing the same archive twice is a no-op
+		assert!(other_store.import_vault(SecretVaultRef::Root, &archive, &archive_password).unwrap().is_empty());
+		assert_eq!(other_store.accounts().unwrap().len(), 2);
+	}
+
+	#[test]
+	fn should_create_accounts_with_configured_kdf() {
+		// given
+		let kdf = KdfParams::Scrypt { n: 1024, r: 8, p: 1 };
+		let store = EthStore::open_with_kdf(Box::new(MemoryDirectory::default()), kdf).unwrap();
+
+		// when
+		let account = store.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"test".into()).unwrap();
+		store.change_password(&account, &"test".into(), &"test2".into()).unwrap();
+
+		// then
+		let safe_account = store.get(&account).unwrap();
+		assert!(kdf.matches(&safe_account.crypto.kdf));
+		assert!(store.test_password(&account, &"test2".into()).unwrap());
+	}
+
+	#[test]
+	fn should_reencrypt_accounts_with_new_kdf() {
+		// given
+		let store = store();
+		let kdf = KdfParams::Scrypt { n: 1024, r: 8, p: 1 };
+		let account1 = store.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"password1".into()).unwrap();
+		let account2 = store.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"password2".into()).unwrap();
+		let progress = Cell::new((0, 0));
+
+		// when
+		let result = store.reencrypt_accounts(SecretVaultRef::Root, &["password1".into()], kdf, &|done, total| progress.set((done, total))).unwrap();
+
+		// then
+		assert_eq!(result.reencrypted, vec![account1.clone()]);
+		assert_eq!(result.locked, vec![account2]);
+		assert!(result.unchanged.is_empty());
+		assert_eq!(progress.get(), (2, 2));
+		assert!(store.test_password(&account1, &"password1".into()).unwrap());
+
+		// already re-encrypted accounts are left alone
+		let result = store.reencrypt_accounts(SecretVaultRef::Root, &["password1".into()], kdf, &|_, _| {}).unwrap();
+		assert_eq!(result.unchanged, vec![account1]);
+		assert!(result.reencrypted.is_empty());
+	}
 }
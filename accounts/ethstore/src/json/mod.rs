@@ -16,6 +16,7 @@
 
 //! Contract interface specification.
 
+mod archive;
 mod bytes;
 mod cipher;
 mod crypto;
@@ -29,6 +30,7 @@ mod vault_file;
 mod vault_key_file;
 mod version;
 
+pub use self::archive::{Archive, ArchiveContents};
 pub use self::bytes::Bytes;
 pub use self::cipher::{Cipher, CipherSer, CipherSerParams, Aes128Ctr};
 pub use self::crypto::{Crypto, CipherText};
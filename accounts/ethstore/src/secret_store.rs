@@ -21,6 +21,7 @@ use crypto::publickey::{Address, Message, Signature, Secret, Public};
 use ethkey::Password;
 use Error;
 use json::{Uuid, OpaqueKeyFile};
+use account::KdfParams;
 use ethereum_types::H256;
 use OpaqueSecret;
 
@@ -101,6 +102,28 @@ pub trait SimpleSecretStore: Send + Sync {
 	fn get_vault_meta(&self, name: &str) -> Result<String, Error>;
 	/// Set vault metadata string.
 	fn set_vault_meta(&self, name: &str, meta: &str) -> Result<(), Error>;
+	/// Export all accounts of given vault to an archive encrypted with `archive_password`.
+	/// Key files keep their own encryption, so the archive can be imported without knowing account passwords.
+	fn export_vault(&self, vault: SecretVaultRef, archive_password: &Password) -> Result<Vec<u8>, Error>;
+	/// Import accounts from an archive created by `export_vault` into given vault.
+	/// Accounts already present in the vault are skipped.
+	fn import_vault(&self, vault: SecretVaultRef, archive: &[u8], archive_password: &Password) -> Result<Vec<StoreAccountRef>, Error>;
+	/// Re-encrypt every account of given vault with new key derivation parameters.
+	/// Each account is unlocked with the first matching password of `passwords`;
+	/// `progress` is called with the number of processed and total accounts after each account.
+	fn reencrypt_accounts(&self, vault: SecretVaultRef, passwords: &[Password], kdf: KdfParams, progress: &dyn Fn(usize, usize))
+		-> Result<Reencryption, Error>;
+}
+
+/// Outcome of `SimpleSecretStore::reencrypt_accounts`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Reencryption {
+	/// Accounts encrypted with new parameters
+	pub reencrypted: Vec<StoreAccountRef>,
+	/// Accounts which were already using requested parameters
+	pub unchanged: Vec<StoreAccountRef>,
+	/// Accounts none of the passwords could unlock
+	pub locked: Vec<StoreAccountRef>,
 }
 
 /// Secret Store API
@@ -18,7 +18,7 @@
 
 use std::{
 	collections::HashMap,
-	time::Instant,
+	time::{Duration, Instant},
 };
 
 use parity_crypto::publickey::Address;
@@ -43,6 +43,25 @@ pub enum Unlock {
 pub struct AccountData {
 	pub unlock: Unlock,
 	pub password: Password,
+	/// When the account was unlocked.
+	pub unlocked_at: Instant,
+	/// When the account was last used to sign or decrypt.
+	pub last_used: Instant,
+}
+
+/// Active unlock session of an account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnlockSession {
+	/// The unlocked account.
+	pub address: Address,
+	/// Whether the account will be locked after the first usage.
+	pub one_time: bool,
+	/// Time elapsed since the account was unlocked.
+	pub unlocked_for: Duration,
+	/// Time elapsed since the account was last used.
+	pub idle_for: Duration,
+	/// Time left until the account is relocked, `None` if it stays unlocked until restart.
+	pub expires_in: Option<Duration>,
 }
 
 /// Collected account metadata
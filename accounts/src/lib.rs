@@ -25,9 +25,13 @@ mod stores;
 use self::account_data::{Unlock, AccountData};
 use self::stores::AddressBook;
 
+use std::cmp;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Instant, Duration};
 
+use crossbeam_utils::thread;
+
 use ethkey::Password;
 use parity_crypto::publickey::{Address, Message, Public, Secret, Random, Generator, Signature};
 use ethstore::accounts_dir::MemoryDirectory;
@@ -36,11 +40,11 @@ use ethstore::{
 	random_string, SecretVaultRef, StoreAccountRef, OpaqueSecret,
 };
 use log::warn;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
-pub use ethstore::{Derivation, IndexDerivation, KeyFile, Error};
+pub use ethstore::{Derivation, IndexDerivation, KeyFile, KdfParams, Reencryption, Error};
 
-pub use self::account_data::AccountMeta;
+pub use self::account_data::{AccountMeta, UnlockSession};
 pub use self::error::SignError;
 
 type AccountToken = Password;
@@ -52,8 +56,21 @@ pub struct AccountProviderSettings {
 	pub unlock_keep_secret: bool,
 	/// Disallowed accounts.
 	pub blacklisted_accounts: Vec<Address>,
+	/// Duration of an unlock session when no explicit duration is requested.
+	/// Defaults to `DEFAULT_UNLOCK_SESSION`.
+	pub unlock_session_duration: Option<Duration>,
+	/// Durations of unlock sessions of particular accounts, overriding `unlock_session_duration`.
+	pub unlock_session_durations: HashMap<Address, Duration>,
+	/// Relock unlocked accounts, including permanently unlocked ones, which were not used for this long.
+	pub unlock_idle_timeout: Option<Duration>,
 }
 
+/// Default duration of an unlock session.
+pub const DEFAULT_UNLOCK_SESSION: Duration = Duration::from_secs(300);
+
+/// Maximum number of key files decrypted at once when unlocking many accounts.
+pub const MAX_UNLOCK_THREADS: usize = 4;
+
 /// Account management.
 /// Responsible for unlocking accounts.
 pub struct AccountProvider {
@@ -72,6 +89,12 @@ pub struct AccountProvider {
 	unlock_keep_secret: bool,
 	/// Disallowed accounts.
 	blacklisted_accounts: Vec<Address>,
+	/// Default duration of an unlock session.
+	unlock_session_duration: Duration,
+	/// Durations of unlock sessions of particular accounts.
+	unlock_session_durations: HashMap<Address, Duration>,
+	/// Idle timeout of unlock sessions.
+	unlock_idle_timeout: Option<Duration>,
 }
 
 fn transient_sstore() -> EthMultiStore {
@@ -102,6 +125,9 @@ impl AccountProvider {
 			transient_sstore: transient_sstore(),
 			unlock_keep_secret: settings.unlock_keep_secret,
 			blacklisted_accounts: settings.blacklisted_accounts,
+			unlock_session_duration: settings.unlock_session_duration.unwrap_or(DEFAULT_UNLOCK_SESSION),
+			unlock_session_durations: settings.unlock_session_durations,
+			unlock_idle_timeout: settings.unlock_idle_timeout,
 		}
 	}
 
@@ -115,6 +141,9 @@ impl AccountProvider {
 			transient_sstore: transient_sstore(),
 			unlock_keep_secret: false,
 			blacklisted_accounts: vec![],
+			unlock_session_duration: DEFAULT_UNLOCK_SESSION,
+			unlock_session_durations: HashMap::new(),
+			unlock_idle_timeout: None,
 		}
 	}
 
@@ -278,13 +307,14 @@ impl AccountProvider {
 		let account = self.sstore.account_ref(&address)?;
 
 		// check if account is already unlocked permanently, if it is, do nothing
-		let mut unlocked = self.unlocked.write();
-		if let Some(data) = unlocked.get(&account) {
+		if let Some(data) = self.unlocked.read().get(&account) {
 			if let Unlock::Perm = data.unlock {
 				return Ok(())
 			}
 		}
 
+		// key derivation is slow, so the password is verified without holding the lock
+		// to let many accounts be unlocked concurrently
 		if self.unlock_keep_secret && unlock == Unlock::Perm {
 			// verify password and get the secret
 			let secret = self.sstore.raw_secret(&account, &password)?;
@@ -296,25 +326,42 @@ impl AccountProvider {
 			let _ = self.sstore.sign(&account, &password, &dummy_msg)?;
 		}
 
-		let data = AccountData { unlock, password };
+		let now = Instant::now();
+		let data = AccountData { unlock, password, unlocked_at: now, last_used: now };
 
-		unlocked.insert(account, data);
+		let mut unlocked = self.unlocked.write();
+		match unlocked.get(&account) {
+			// unlocked permanently in the meantime
+			Some(data) if data.unlock == Unlock::Perm => {},
+			_ => { unlocked.insert(account, data); },
+		}
 		Ok(())
 	}
 
 	fn password(&self, account: &StoreAccountRef) -> Result<Password, SignError> {
+		let now = Instant::now();
 		let mut unlocked = self.unlocked.write();
-		let data = unlocked.get(account).ok_or(SignError::NotUnlocked)?.clone();
+		let data = unlocked.get_mut(account).ok_or(SignError::NotUnlocked)?;
+		if self.session_end(data).map_or(false, |end| now >= end) {
+			unlocked.remove(account).expect("data exists: so key must exist: qed");
+			return Err(SignError::NotUnlocked);
+		}
+		data.last_used = now;
+		let password = data.password.clone();
 		if let Unlock::OneTime = data.unlock {
 			unlocked.remove(account).expect("data exists: so key must exist: qed");
 		}
-		if let Unlock::Timed(ref end) = data.unlock {
-			if Instant::now() > *end {
-				unlocked.remove(account).expect("data exists: so key must exist: qed");
-				return Err(SignError::NotUnlocked);
-			}
+		Ok(password)
+	}
+
+	/// Returns the moment the unlock session ends, `None` if it never does.
+	fn session_end(&self, data: &AccountData) -> Option<Instant> {
+		let idle_end = self.unlock_idle_timeout.map(|timeout| data.last_used + timeout);
+		match data.unlock {
+			Unlock::Perm => idle_end,
+			Unlock::OneTime => idle_end,
+			Unlock::Timed(end) => Some(idle_end.map_or(end, |idle_end| cmp::min(end, idle_end))),
 		}
-		Ok(data.password)
 	}
 
 	/// Unlocks account permanently.
@@ -322,6 +369,34 @@ impl AccountProvider {
 		self.unlock_account(account, password, Unlock::Perm)
 	}
 
+	/// Unlocks given accounts permanently, trying each of the passwords in turn.
+	/// Key files are decrypted on up to `threads` threads, but never more than `MAX_UNLOCK_THREADS`
+	/// since each scrypt derivation may need hundreds of megabytes of memory.
+	/// Returns accounts which could not be unlocked with any of the passwords.
+	pub fn unlock_accounts_permanently(&self, accounts: &[Address], passwords: &[Password], threads: usize) -> Vec<Address> {
+		let next = AtomicUsize::new(0);
+		let failed = Mutex::new(Vec::new());
+		let threads = cmp::max(1, cmp::min(cmp::min(threads, MAX_UNLOCK_THREADS), accounts.len()));
+
+		thread::scope(|scope| {
+			for _ in 0..threads {
+				scope.spawn(|_| loop {
+					let account = match accounts.get(next.fetch_add(1, Ordering::SeqCst)) {
+						Some(account) => *account,
+						None => break,
+					};
+
+					if !passwords.iter().any(|p| self.unlock_account_permanently(account, p.clone()).is_ok()) {
+						failed.lock().push(account);
+					}
+				});
+			}
+		}).expect("unlocking threads never panic; qed");
+
+		let failed = failed.into_inner();
+		accounts.iter().filter(|a| failed.contains(a)).cloned().collect()
+	}
+
 	/// Unlocks account temporarily (for one signing).
 	pub fn unlock_account_temporarily(&self, account: Address, password: Password) -> Result<(), Error> {
 		self.unlock_account(account, password, Unlock::OneTime)
@@ -332,12 +407,59 @@ impl AccountProvider {
 		self.unlock_account(account, password, Unlock::Timed(Instant::now() + duration))
 	}
 
+	/// Unlocks account for a session of the duration configured for it, or the default one.
+	pub fn unlock_account_session(&self, account: Address, password: Password) -> Result<(), Error> {
+		let duration = self.unlock_session_durations.get(&account).cloned().unwrap_or(self.unlock_session_duration);
+		self.unlock_account_timed(account, password, duration)
+	}
+
+	/// Returns active unlock sessions.
+	pub fn unlock_sessions(&self) -> Vec<UnlockSession> {
+		let now = Instant::now();
+		let unlocked = self.unlocked.read();
+		let mut sessions: Vec<_> = unlocked.iter()
+			.filter_map(|(account, data)| {
+				let end = self.session_end(data);
+				if end.map_or(false, |end| now >= end) {
+					return None;
+				}
+				Some(UnlockSession {
+					address: account.address,
+					one_time: data.unlock == Unlock::OneTime,
+					unlocked_for: now.duration_since(data.unlocked_at),
+					idle_for: now.duration_since(data.last_used),
+					expires_in: end.map(|end| end.duration_since(now)),
+				})
+			})
+			.collect();
+		sessions.sort_by_key(|session| session.address);
+		sessions
+	}
+
+	/// Locks accounts whose unlock session has expired. Returns relocked accounts.
+	pub fn relock_expired(&self) -> Vec<Address> {
+		let now = Instant::now();
+		let mut unlocked = self.unlocked.write();
+		let expired: Vec<_> = unlocked.iter()
+			.filter(|(_, data)| self.session_end(data).map_or(false, |end| now >= end))
+			.map(|(account, _)| account.clone())
+			.collect();
+		for account in &expired {
+			unlocked.remove(account);
+		}
+		expired.into_iter().map(|account| account.address).collect()
+	}
+
 	/// Checks if given account is unlocked
 	pub fn is_unlocked(&self, address: &Address) -> bool {
+		let now = Instant::now();
 		let unlocked = self.unlocked.read();
 		let unlocked_secrets = self.unlocked_secrets.read();
 		self.sstore.account_ref(address)
-			.map(|r| unlocked.get(&r).is_some() || unlocked_secrets.get(&r).is_some())
+			.map(|r| {
+				let active = unlocked.get(&r).map_or(false, |data| self.session_end(data).map_or(true, |end| now < end));
+				active || unlocked_secrets.get(&r).is_some()
+			})
 			.unwrap_or(false)
 	}
 
@@ -498,6 +620,28 @@ impl AccountProvider {
 		self.sstore.set_vault_meta(name, meta)
 			.map_err(Into::into)
 	}
+
+	/// Export accounts of the given vault (or the root keystore if `vault` is empty) to an encrypted archive.
+	pub fn export_vault(&self, vault: &str, archive_password: &Password) -> Result<Vec<u8>, Error> {
+		self.sstore.export_vault(vault_ref(vault), archive_password)
+	}
+
+	/// Import accounts from an encrypted archive into the given vault (or the root keystore if `vault` is empty).
+	pub fn import_vault(&self, vault: &str, archive: &[u8], archive_password: &Password) -> Result<Vec<Address>, Error> {
+		self.sstore.import_vault(vault_ref(vault), archive, archive_password)
+			.map(|accounts| accounts.into_iter().map(|a| a.address).collect())
+	}
+
+	/// Re-encrypt accounts of the given vault (or the root keystore if `vault` is empty) with new key derivation parameters.
+	pub fn reencrypt_accounts<F>(&self, vault: &str, passwords: &[Password], kdf: KdfParams, progress: F) -> Result<Reencryption, Error>
+		where F: Fn(usize, usize)
+	{
+		self.sstore.reencrypt_accounts(vault_ref(vault), passwords, kdf, &progress)
+	}
+}
+
+fn vault_ref(vault: &str) -> SecretVaultRef {
+	if vault.is_empty() { SecretVaultRef::Root } else { SecretVaultRef::Vault(vault.to_owned()) }
 }
 
 #[cfg(test)]
@@ -604,6 +748,24 @@ mod tests {
 		assert!(ap.sign(kp.address(), None, dummy_msg).is_ok());
 	}
 
+	#[test]
+	fn unlock_accounts_in_parallel() {
+		let ap = AccountProvider::transient_provider();
+		let accounts: Vec<_> = (0..8).map(|i| {
+			let kp = Random.generate();
+			let password = if i == 3 { "other" } else { "test" };
+			ap.insert_account(kp.secret().clone(), &password.into()).unwrap();
+			kp.address()
+		}).collect();
+
+		let failed = ap.unlock_accounts_permanently(&accounts, &["test1".into(), "test".into()], 4);
+
+		assert_eq!(failed, vec![accounts[3]]);
+		for (i, account) in accounts.iter().enumerate() {
+			assert_eq!(ap.is_unlocked_permanently(account), i != 3);
+		}
+	}
+
 	#[test]
 	fn unlock_account_timer() {
 		let kp = Random.generate();
@@ -618,6 +780,73 @@ mod tests {
 		assert!(ap.sign(kp.address(), None, dummy_msg).is_err());
 	}
 
+	#[test]
+	fn should_relock_idle_session() {
+		let kp = Random.generate();
+		let mut ap = AccountProvider::transient_provider();
+		ap.unlock_idle_timeout = Some(Duration::from_secs(60));
+		let dummy_msg = [1u8; 32].into();
+
+		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.unlock_account_session(kp.address(), "test".into()).is_ok());
+		assert!(ap.sign(kp.address(), None, dummy_msg).is_ok());
+		assert!(ap.relock_expired().is_empty());
+
+		// the session is idle for longer than the timeout
+		ap.unlock_idle_timeout = Some(Duration::from_secs(0));
+		assert!(!ap.is_unlocked(&kp.address()));
+		assert_eq!(ap.relock_expired(), vec![kp.address()]);
+		assert!(ap.sign(kp.address(), None, dummy_msg).is_err());
+
+		// permanent unlocks are subject to the idle timeout as well
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		assert!(!ap.is_unlocked(&kp.address()));
+		assert_eq!(ap.relock_expired(), vec![kp.address()]);
+	}
+
+	#[test]
+	fn should_use_session_duration_of_account() {
+		let kp1 = Random.generate();
+		let kp2 = Random.generate();
+		let mut ap = AccountProvider::transient_provider();
+		ap.unlock_session_durations.insert(kp1.address(), Duration::from_secs(10));
+		assert!(ap.insert_account(kp1.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.insert_account(kp2.secret().clone(), &"test".into()).is_ok());
+
+		assert!(ap.unlock_account_session(kp1.address(), "test".into()).is_ok());
+		assert!(ap.unlock_account_session(kp2.address(), "test".into()).is_ok());
+		let sessions = ap.unlock_sessions();
+		let session = sessions.iter().find(|s| s.address == kp1.address()).unwrap();
+		assert!(session.expires_in.unwrap() <= Duration::from_secs(10));
+		let session = sessions.iter().find(|s| s.address == kp2.address()).unwrap();
+		assert!(session.expires_in.unwrap() > Duration::from_secs(10));
+	}
+
+	#[test]
+	fn should_list_unlock_sessions() {
+		let kp1 = Random.generate();
+		let kp2 = Random.generate();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp1.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.insert_account(kp2.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.unlock_sessions().is_empty());
+
+		assert!(ap.unlock_account_session(kp1.address(), "test".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp2.address(), "test".into()).is_ok());
+		let sessions = ap.unlock_sessions();
+		assert_eq!(sessions.len(), 2);
+
+		let session = sessions.iter().find(|s| s.address == kp1.address()).unwrap();
+		assert!(!session.one_time);
+		assert!(session.expires_in.unwrap() <= super::DEFAULT_UNLOCK_SESSION);
+		let session = sessions.iter().find(|s| s.address == kp2.address()).unwrap();
+		assert_eq!(session.expires_in, None);
+
+		ap.unlocked.write().get_mut(&StoreAccountRef::root(kp1.address())).unwrap().unlock = Unlock::Timed(Instant::now());
+		assert_eq!(ap.unlock_sessions().len(), 1);
+		assert_eq!(ap.relock_expired(), vec![kp1.address()]);
+	}
+
 	#[test]
 	fn should_sign_and_return_token() {
 		// given
@@ -63,6 +63,8 @@ pub use compute::{ProofOfWork, quick_get_difficulty, slow_hash_block_number};
 pub use seed_compute::SeedHashCompute;
 pub use shared::ETHASH_EPOCH_LENGTH;
 
+use shared::get_cache_size;
+
 use common_types::engines::OptimizeFor;
 use compute::Light;
 use ethereum_types::{BigEndianHash, U256, U512};
@@ -73,6 +75,29 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use std::convert::TryFrom;
 use std::sync::Arc;
+use std::thread;
+
+/// Ethash cache and verification settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EthashConfig {
+	/// Number of epochs following the current one whose caches are generated in the background.
+	pub pregenerate_epochs: u64,
+	/// Upper bound of the memory used by the caches kept in memory, in bytes.
+	/// The cache of the most recent epoch is always kept.
+	pub max_cache_memory: usize,
+	/// Blocks below this number only get the seal of a sample of them fully verified.
+	pub light_verification_below: u64,
+}
+
+impl Default for EthashConfig {
+	fn default() -> Self {
+		EthashConfig {
+			pregenerate_epochs: 1,
+			max_cache_memory: 512 * 1024 * 1024,
+			light_verification_below: 0,
+		}
+	}
+}
 
 struct LightCache {
 	recent_epoch: Option<u64>,
@@ -87,11 +112,26 @@ pub struct EthashManager {
 	cache: Mutex<LightCache>,
 	cache_dir: PathBuf,
 	progpow_transition: u64,
+	config: EthashConfig,
+	// held while a cache file is being generated, so that it is never read half-written.
+	generating: Arc<Mutex<()>>,
+	// highest epoch whose cache generation has been started in the background.
+	pregenerated: Mutex<Option<u64>>,
 }
 
 impl EthashManager {
 	/// Create a new new instance of ethash manager
 	pub fn new<T: Into<Option<OptimizeFor>>>(cache_dir: &Path, optimize_for: T, progpow_transition: u64) -> EthashManager {
+		Self::with_config(cache_dir, optimize_for, progpow_transition, EthashConfig::default())
+	}
+
+	/// Create a new instance of ethash manager with the given cache settings
+	pub fn with_config<T: Into<Option<OptimizeFor>>>(
+		cache_dir: &Path,
+		optimize_for: T,
+		progpow_transition: u64,
+		config: EthashConfig,
+	) -> EthashManager {
 		EthashManager {
 			cache_dir: cache_dir.to_path_buf(),
 			nodecache_builder: NodeCacheBuilder::new(optimize_for.into().unwrap_or_default(), progpow_transition),
@@ -102,9 +142,17 @@ impl EthashManager {
 				prev_epoch: None,
 				prev: None,
 			}),
+			config,
+			generating: Arc::new(Mutex::new(())),
+			pregenerated: Mutex::new(None),
 		}
 	}
 
+	/// The cache and verification settings.
+	pub fn config(&self) -> &EthashConfig {
+		&self.config
+	}
+
 	/// Calculate the light client data
 	/// `block_number` - Block number to check
 	/// `light` - The light client handler
@@ -143,6 +191,7 @@ impl EthashManager {
 
 			match light {
 				None => {
+					let _generating = self.generating.lock();
 					let light = match self.nodecache_builder.light_from_file(
 						&self.cache_dir,
 						block_number,
@@ -162,13 +211,67 @@ impl EthashManager {
 					};
 					lights.prev_epoch = mem::replace(&mut lights.recent_epoch, Some(epoch));
 					lights.prev = mem::replace(&mut lights.recent, Some(light.clone()));
+
+					let cache_memory = lights.recent_epoch.into_iter().chain(lights.prev_epoch)
+						.map(|e| get_cache_size(e * ETHASH_EPOCH_LENGTH))
+						.sum::<usize>();
+					if cache_memory > self.config.max_cache_memory {
+						trace!(target: "ethash", "Dropping cache of epoch {:?} to stay within memory bound", lights.prev_epoch);
+						lights.prev_epoch = None;
+						lights.prev = None;
+					}
 					light
 				}
 				Some(light) => light,
 			}
 		};
+		self.pregenerate(epoch);
 		light.compute(header_hash, nonce, block_number)
 	}
+
+	// generate the cache files of the epochs following `epoch` in the background,
+	// so that verification doesn't stall when the epoch changes.
+	fn pregenerate(&self, epoch: u64) {
+		if self.config.pregenerate_epochs == 0 {
+			return;
+		}
+
+		let last = epoch.saturating_add(self.config.pregenerate_epochs);
+		let first = {
+			let mut pregenerated = self.pregenerated.lock();
+			let first = match *pregenerated {
+				Some(e) if e >= last => return,
+				Some(e) if e > epoch => e + 1,
+				_ => epoch + 1,
+			};
+			*pregenerated = Some(last);
+			first
+		};
+
+		let builder = self.nodecache_builder.clone();
+		let cache_dir = self.cache_dir.clone();
+		let generating = self.generating.clone();
+		let spawned = thread::Builder::new()
+			.name("Ethash cache".into())
+			.spawn(move || {
+				for epoch in first..=last {
+					let block_number = epoch * ETHASH_EPOCH_LENGTH;
+					let _generating = generating.lock();
+					if builder.light_from_file(&cache_dir, block_number).is_ok() {
+						continue;
+					}
+
+					debug!(target: "ethash", "Generating cache for epoch {}", epoch);
+					if let Err(e) = builder.light(&cache_dir, block_number).to_file() {
+						warn!("Light cache file write error: {}", e);
+					}
+				}
+			});
+
+		if let Err(e) = spawned {
+			warn!("Error starting Ethash cache generation thread: {}", e);
+		}
+	}
 }
 
 /// Convert an Ethash boundary to its original difficulty. Basically just `f(x) = 2^256 / x`.
@@ -213,6 +316,25 @@ fn test_lru() {
 	assert_eq!(ethash.cache.lock().prev_epoch.unwrap(), 0);
 }
 
+#[test]
+fn test_cache_memory_bound() {
+	use tempfile::TempDir;
+
+	let tempdir = TempDir::new().unwrap();
+	let config = EthashConfig {
+		pregenerate_epochs: 0,
+		max_cache_memory: get_cache_size(ETHASH_EPOCH_LENGTH) + 1,
+		..EthashConfig::default()
+	};
+	let ethash = EthashManager::with_config(tempdir.path(), None, u64::max_value(), config);
+	let hash = [0u8; 32];
+	ethash.compute_light(1, &hash, 1);
+	ethash.compute_light(50000, &hash, 1);
+	assert_eq!(ethash.cache.lock().recent_epoch, Some(1));
+	assert_eq!(ethash.cache.lock().prev_epoch, None);
+	assert!(ethash.cache.lock().prev.is_none());
+}
+
 #[test]
 fn test_difficulty_to_boundary() {
 	use ethereum_types::{H256, BigEndianHash};
@@ -436,6 +436,18 @@ impl Account {
 	/// Determine whether there are any un-`commit()`-ed storage-setting operations.
 	pub fn storage_is_clean(&self) -> bool { self.storage_changes.is_empty() }
 
+	/// Keys of the storage entries cached since the account was loaded.
+	pub fn cached_storage_keys(&self) -> Vec<H256> {
+		self.storage_cache.borrow().iter().map(|(key, _)| *key).collect()
+	}
+
+	/// Approximate memory used by the cached and changed storage entries, in bytes.
+	pub fn storage_cache_size(&self) -> usize {
+		let original = self.original_storage_cache.as_ref().map_or(0, |(_, cache)| cache.borrow().len());
+		let entries = self.storage_cache.borrow().len() + original + self.storage_changes.len();
+		entries * 2 * ::std::mem::size_of::<H256>()
+	}
+
 	/// Check if account has zero nonce, balance, no code and no storage.
 	///
 	/// NOTE: Will panic if `!self.storage_is_clean()`
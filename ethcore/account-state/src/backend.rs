@@ -31,7 +31,7 @@ use memory_db::{HashKey, MemoryDB};
 use parking_lot::Mutex;
 use keccak_hasher::KeccakHasher;
 
-use crate::account::Account;
+use crate::{account::Account, fork::ForkSource};
 
 /// State backend. See module docs for more details.
 pub trait Backend: Send {
@@ -62,6 +62,9 @@ pub trait Backend: Send {
 
 	/// Get cached code based on hash.
 	fn get_cached_code(&self, hash: &H256) -> Option<Arc<Vec<u8>>>;
+
+	/// Remote state the chain is forked off, read for the accounts and storage missing locally.
+	fn fork(&self) -> Option<&dyn ForkSource> { None }
 }
 
 /// A raw backend used to check proofs of execution.
@@ -0,0 +1,67 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chains forked off a remote network at a given block, which read the accounts and storage they
+//! don't have from the remote state on first access.
+//!
+//! A value missing from the local state is either untouched since the fork, in which case it is
+//! read from the remote state, or deleted by the local chain. The latter are recorded as
+//! tombstones in the storage of a dedicated account, so that they follow checkpoints, block
+//! reorganisations and historical states like the rest of the state.
+
+use ethereum_types::{Address, H256, U256};
+use keccak_hash::keccak;
+use parity_bytes::Bytes;
+
+/// Account of the remote state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteAccount {
+	/// Balance.
+	pub balance: U256,
+	/// Nonce.
+	pub nonce: U256,
+	/// Code, empty for basic accounts.
+	pub code: Bytes,
+}
+
+/// State of the remote network at the fork block. Being at a fixed block, the values it returns
+/// never change and may be cached by implementations.
+pub trait ForkSource: Send + Sync {
+	/// Account at `address`, `None` if it doesn't exist.
+	fn account(&self, address: &Address) -> Result<Option<RemoteAccount>, String>;
+
+	/// Storage value of `key` of the account at `address`.
+	fn storage_at(&self, address: &Address, key: &H256) -> Result<H256, String>;
+}
+
+/// Account holding the tombstones of the local state, never read from the remote state.
+pub fn tombstones() -> Address {
+	Address::from_slice(&keccak(b"openethereum:fork:tombstones")[12..])
+}
+
+/// Tombstone of an account removed or created by the local chain. Its storage isn't read from the
+/// remote state either.
+pub fn account_tombstone(address: &Address) -> H256 {
+	keccak(address)
+}
+
+/// Tombstone of a storage value cleared by the local chain.
+pub fn storage_tombstone(address: &Address, key: &H256) -> H256 {
+	let mut preimage = [0u8; 52];
+	preimage[..20].copy_from_slice(address.as_bytes());
+	preimage[20..].copy_from_slice(key.as_bytes());
+	keccak(&preimage[..])
+}
@@ -24,10 +24,12 @@
 
 pub mod account;
 pub mod backend;
+pub mod fork;
 pub mod state;
 
 pub use {
 	account::Account,
 	backend::Backend,
-	state::{State, CleanupMode},
+	fork::ForkSource,
+	state::{State, StateReads, CleanupMode},
 };
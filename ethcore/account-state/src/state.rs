@@ -49,6 +49,7 @@ use trie_db::{Trie, TrieError, Recorder};
 use crate::{
 	account::Account,
 	backend::Backend,
+	fork,
 };
 
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
@@ -155,6 +156,15 @@ impl AccountEntry {
 	}
 }
 
+/// Accounts and storage keys looked up in a state, see `State::track_reads`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateReads {
+	/// Accounts whose balance, nonce, code or existence was looked up, or which were changed.
+	pub accounts: BTreeSet<Address>,
+	/// Storage keys looked up, with their account.
+	pub storage: BTreeSet<(Address, H256)>,
+}
+
 /// Representation of the entire state of all accounts in the system.
 ///
 /// `State` can work together with `StateDB` to share account cache.
@@ -206,6 +216,13 @@ pub struct State<B> {
 	cache: RefCell<HashMap<Address, AccountEntry>>,
 	// The original account is preserved in
 	checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
+	// Accounts and storage keys looked up since `track_reads`, including those answered by the
+	// global cache.
+	reads: RefCell<Option<StateReads>>,
+	// Accounts and storage keys written by `commit` since `track_writes`.
+	writes: Option<BTreeMap<Address, BTreeSet<H256>>>,
+	// Accounts whose balance, nonce or code was changed by `commit` since `track_writes`.
+	changed_accounts: Option<BTreeSet<Address>>,
 	account_start_nonce: U256,
 	factories: Factories,
 }
@@ -268,6 +285,9 @@ impl<B: Backend> State<B> {
 			root,
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
+			reads: RefCell::new(None),
+			writes: None,
+			changed_accounts: None,
 			account_start_nonce,
 			factories,
 		}
@@ -284,6 +304,9 @@ impl<B: Backend> State<B> {
 			root,
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
+			reads: RefCell::new(None),
+			writes: None,
+			changed_accounts: None,
 			account_start_nonce,
 			factories,
 		};
@@ -390,6 +413,66 @@ impl<B: Backend> State<B> {
 		&self.root
 	}
 
+	/// Addresses of the accounts loaded into the local cache, in order. This includes every
+	/// account modified since the state was opened as well as those only read.
+	pub fn cached_addresses(&self) -> Vec<Address> {
+		let mut addresses: Vec<_> = self.cache.borrow().keys().cloned().collect();
+		addresses.sort();
+		addresses
+	}
+
+	/// Start recording the accounts and storage keys looked up in this state, see `reads`.
+	pub fn track_reads(&mut self) {
+		*self.reads.get_mut() = Some(StateReads::default());
+	}
+
+	/// Accounts and storage keys looked up since `track_reads`, including those answered by the
+	/// global cache. Empty unless reads are tracked.
+	pub fn reads(&self) -> StateReads {
+		self.reads.borrow().clone().unwrap_or_default()
+	}
+
+	fn note_read(&self, address: &Address) {
+		if let Some(ref mut reads) = *self.reads.borrow_mut() {
+			reads.accounts.insert(*address);
+		}
+	}
+
+	fn note_storage_read(&self, address: &Address, key: &H256) {
+		if let Some(ref mut reads) = *self.reads.borrow_mut() {
+			reads.storage.insert((*address, *key));
+		}
+	}
+
+	/// Start recording the accounts and storage keys written by `commit`, see `written_storage`
+	/// and `changed_accounts`.
+	pub fn track_writes(&mut self) {
+		self.writes = Some(BTreeMap::new());
+		self.changed_accounts = Some(BTreeSet::new());
+	}
+
+	/// Accounts whose balance, nonce or code was changed by every `commit` since `track_writes`,
+	/// including created and removed ones. Empty unless writes are tracked.
+	pub fn changed_accounts(&self) -> BTreeSet<Address> {
+		self.changed_accounts.clone().unwrap_or_default()
+	}
+
+	/// Accounts written by every `commit` since `track_writes`, with the storage keys written
+	/// to each of them. Empty unless writes are tracked.
+	pub fn written_storage(&self) -> BTreeMap<Address, BTreeSet<H256>> {
+		self.writes.clone().unwrap_or_default()
+	}
+
+	/// Addresses of the accounts written to the trie by `commit`, in order.
+	pub fn committed_addresses(&self) -> Vec<Address> {
+		let mut addresses: Vec<_> = self.cache.borrow().iter()
+			.filter(|&(_, a)| a.state == AccountState::Committed)
+			.map(|(address, _)| *address)
+			.collect();
+		addresses.sort();
+		addresses
+	}
+
 	/// Create a new contract at address `contract`. If there is already an account at the address
 	/// it will have its code reset, ready for `init_code()`.
 	pub fn new_contract(&mut self, contract: &Address, balance: U256, nonce_offset: U256, version: U256) -> TrieResult<()> {
@@ -399,12 +482,21 @@ impl<B: Backend> State<B> {
 			return Err(Box::new(TrieError::DecoderError(H256::from(*contract), rlp::DecoderError::Custom("Nonce overflow".into()))));
 		}
 		self.insert_cache(contract, AccountEntry::new_dirty(Some(Account::new_contract(balance, nonce, version, original_storage_root))));
+		if self.db.fork().is_some() {
+			// the storage of contracts created locally isn't read from the remote state
+			self.tombstone(fork::account_tombstone(contract))?;
+		}
 		Ok(())
 	}
 
 	/// Remove an existing account.
 	pub fn kill_account(&mut self, account: &Address) {
 		self.insert_cache(account, AccountEntry::new_dirty(None));
+		if self.db.fork().is_some() {
+			if let Err(e) = self.tombstone(fork::account_tombstone(account)) {
+				warn!(target: "state", "Failed to record the removal of {} from the forked state: {}", account, e);
+			}
+		}
 	}
 
 	/// Determine whether an account exists.
@@ -539,6 +631,7 @@ impl<B: Backend> State<B> {
 		// 2. If there's an entry for the account in the global cache check for the key or load it into that account.
 		// 3. If account is missing in the global cache load it into the local cache and cache the key there.
 
+		self.note_storage_read(address, key);
 		{
 			// check local cache first without updating
 			let local_cache = self.cache.borrow_mut();
@@ -580,10 +673,7 @@ impl<B: Backend> State<B> {
 		}
 
 		// account is not found in the global cache, get from the DB and insert into local
-		let db = &self.db.as_hash_db();
-		let db = self.factories.trie.readonly(db, &self.root).expect(SEC_TRIE_DB_UNWRAP_STR);
-		let from_rlp = |b: &[u8]| Account::from_rlp(b).expect("decoding db value failed");
-		let maybe_acc = db.get_with(address.as_bytes(), from_rlp)?;
+		let maybe_acc = self.load_account(address)?;
 		let r = maybe_acc.as_ref().map_or(Ok(H256::zero()), |a| {
 			let account_db = self.factories.accountdb.readonly(self.db.as_hash_db(), a.address_hash(address));
 			f_at(a, account_db.as_hash_db(), key)
@@ -594,22 +684,72 @@ impl<B: Backend> State<B> {
 
 	/// Mutate storage of account `address` so that it is `value` for `key`.
 	pub fn storage_at(&self, address: &Address, key: &H256) -> TrieResult<H256> {
-		self.storage_at_inner(
+		let value = self.storage_at_inner(
 			address,
 			key,
 			|account, key| { account.cached_storage_at(key) },
 			|account, db, key| { account.storage_at(db, key) },
-		)
+		)?;
+		self.fork_storage_at(address, key, value)
 	}
 
 	/// Get the value of storage after last state commitment.
 	pub fn original_storage_at(&self, address: &Address, key: &H256) -> TrieResult<H256> {
-		self.storage_at_inner(
+		let value = self.storage_at_inner(
 			address,
 			key,
 			|account, key| { account.cached_original_storage_at(key) },
 			|account, db, key| { account.original_storage_at(db, key) },
-		)
+		)?;
+		self.fork_storage_at(address, key, value)
+	}
+
+	/// Read the account from the trie or, if the chain is forked and the account was never
+	/// touched locally, from the remote state.
+	fn load_account(&self, a: &Address) -> TrieResult<Option<Account>> {
+		let db = &self.db.as_hash_db();
+		let db = self.factories.trie.readonly(db, &self.root).expect(SEC_TRIE_DB_UNWRAP_STR);
+		let from_rlp = |b: &[u8]| Account::from_rlp(b).expect("decoding db value failed");
+		let maybe_acc = db.get_with(a.as_bytes(), from_rlp)?;
+		match self.db.fork() {
+			Some(remote) if maybe_acc.is_none() && *a != fork::tombstones() => {
+				if self.is_tombstoned(fork::account_tombstone(a))? {
+					return Ok(None);
+				}
+				let remote = remote.account(a).map_err(|e| {
+					warn!(target: "state", "Failed to read account {} from the forked state: {}", a, e);
+					Box::new(TrieError::IncompleteDatabase(H256::from(*a)))
+				})?;
+				Ok(remote.map(|remote| Account::new(remote.balance, remote.nonce, HashMap::new(), remote.code, U256::zero())))
+			},
+			_ => Ok(maybe_acc),
+		}
+	}
+
+	/// Read a storage value missing from the trie from the remote state, if the chain is forked
+	/// and the value was never cleared locally.
+	fn fork_storage_at(&self, address: &Address, key: &H256, value: H256) -> TrieResult<H256> {
+		let remote = match self.db.fork() {
+			Some(remote) if value.is_zero() && *address != fork::tombstones() => remote,
+			_ => return Ok(value),
+		};
+		if self.is_tombstoned(fork::account_tombstone(address))? || self.is_tombstoned(fork::storage_tombstone(address, key))? {
+			return Ok(value);
+		}
+		remote.storage_at(address, key).map_err(|e| {
+			warn!(target: "state", "Failed to read storage {:?} of {} from the forked state: {}", key, address, e);
+			Box::new(TrieError::IncompleteDatabase(H256::from(*address)))
+		})
+	}
+
+	fn is_tombstoned(&self, tombstone: H256) -> TrieResult<bool> {
+		Ok(!self.storage_at(&fork::tombstones(), &tombstone)?.is_zero())
+	}
+
+	/// Record that the local chain removed a value of the remote state.
+	fn tombstone(&mut self, tombstone: H256) -> TrieResult<()> {
+		self.require(&fork::tombstones(), false)?.set_storage(tombstone, H256::from_low_u64_be(1));
+		Ok(())
 	}
 
 	/// Get accounts' code.
@@ -679,7 +819,10 @@ impl<B: Backend> State<B> {
 	pub fn set_storage(&mut self, a: &Address, key: H256, value: H256) -> TrieResult<()> {
 		trace!(target: "state", "set_storage({}:{:x} to {:x})", a, key, value);
 		if self.storage_at(a, &key)? != value {
-			self.require(a, false)?.set_storage(key, value)
+			self.require(a, false)?.set_storage(key, value);
+			if value.is_zero() && self.db.fork().is_some() {
+				self.tombstone(fork::storage_tombstone(a, &key))?;
+			}
 		}
 
 		Ok(())
@@ -708,6 +851,14 @@ impl<B: Backend> State<B> {
 		assert!(self.checkpoints.borrow().is_empty());
 		// first, commit the sub trees.
 		let mut accounts = self.cache.borrow_mut();
+		if let Some(ref mut writes) = self.writes {
+			for (address, a) in accounts.iter().filter(|&(_, a)| a.is_dirty()) {
+				let keys = writes.entry(*address).or_default();
+				if let Some(ref account) = a.account {
+					keys.extend(account.storage_changes().keys().cloned());
+				}
+			}
+		}
 		for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
 			if let Some(ref mut account) = a.account {
 				let addr_hash = account.address_hash(address);
@@ -723,14 +874,27 @@ impl<B: Backend> State<B> {
 			let mut trie = self.factories.trie.from_existing(self.db.as_hash_db_mut(), &mut self.root)?;
 			for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
 				a.state = AccountState::Committed;
-				match a.account {
+				let previous = match a.account {
 					Some(ref mut account) => {
-						trie.insert(address.as_bytes(), &account.rlp())?;
+						trie.insert(address.as_bytes(), &account.rlp())?
 					},
 					None => {
-						trie.remove(address.as_bytes())?;
+						trie.remove(address.as_bytes())?
 					},
 				};
+				if let Some(ref mut changed) = self.changed_accounts {
+					let same = match (previous, &a.account) {
+						(Some(previous), &Some(ref account)) => Account::from_rlp(&previous).map_or(false, |previous| {
+							previous.balance() == account.balance() && previous.nonce() == account.nonce()
+								&& previous.code_hash() == account.code_hash() && previous.code_version() == account.code_version()
+						}),
+						(None, &None) => true,
+						_ => false,
+					};
+					if !same {
+						changed.insert(*address);
+					}
+				}
 			}
 		}
 
@@ -962,6 +1126,7 @@ impl<B: Backend> State<B> {
 	/// Populates local cache if nothing found.
 	fn ensure_cached<F, U>(&self, a: &Address, require: RequireCache, f: F) -> TrieResult<U>
 		where F: Fn(Option<&Account>) -> U {
+		self.note_read(a);
 		// check local cache first
 		if let Some(ref mut maybe_acc) = self.cache.borrow_mut().get_mut(a) {
 			if let Some(ref mut account) = maybe_acc.account {
@@ -988,10 +1153,7 @@ impl<B: Backend> State<B> {
 			Some(r) => Ok(r?),
 			None => {
 				// not found in the global cache, get from the DB and insert into local
-				let db = &self.db.as_hash_db();
-				let db = self.factories.trie.readonly(db, &self.root)?;
-				let from_rlp = |b: &[u8]| Account::from_rlp(b).expect("decoding db value failed");
-				let mut maybe_acc = db.get_with(a.as_bytes(), from_rlp)?;
+				let mut maybe_acc = self.load_account(a)?;
 				if let Some(ref mut account) = maybe_acc.as_mut() {
 					let accountdb = self.factories.accountdb.readonly(self.db.as_hash_db(), account.address_hash(a));
 					if !Self::update_account_cache(require, account, &self.db, accountdb.as_hash_db()) {
@@ -1015,15 +1177,14 @@ impl<B: Backend> State<B> {
 	pub fn require_or_from<F, G>(&self, a: &Address, require_code: bool, default: F, not_default: G) -> TrieResult<RefMut<Account>>
 		where F: FnOnce() -> Account, G: FnOnce(&mut Account),
 	{
+		// the account is changed depending on its current value, e.g. its nonce.
+		self.note_read(a);
 		let contains_key = self.cache.borrow().contains_key(a);
 		if !contains_key {
 			match self.db.get_cached_account(a) {
 				Some(acc) => self.insert_cache(a, AccountEntry::new_clean_cached(acc)),
 				None => {
-					let db = &self.db.as_hash_db();
-					let db = self.factories.trie.readonly(db, &self.root)?;
-					let from_rlp = |b:&[u8]| { Account::from_rlp(b).expect("decoding db value failed") };
-					let maybe_account = db.get_with(a.as_bytes(), from_rlp)?;
+					let maybe_account = self.load_account(a)?;
 					self.insert_cache(a, AccountEntry::new_clean(maybe_account));
 				}
 			}
@@ -1144,6 +1305,9 @@ impl<B: Backend + Clone> Clone for State<B> {
 			root: self.root.clone(),
 			cache: RefCell::new(cache),
 			checkpoints: RefCell::new(Vec::new()),
+			reads: RefCell::new(self.reads.borrow().clone()),
+			writes: self.writes.clone(),
+			changed_accounts: self.changed_accounts.clone(),
 			account_start_nonce: self.account_start_nonce.clone(),
 			factories: self.factories.clone(),
 		}
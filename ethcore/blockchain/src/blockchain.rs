@@ -40,8 +40,9 @@ use common_types::{
 	views::{BlockView, HeaderView},
 };
 use ethcore_db::cache_manager::CacheManager;
+use ethcore_db::compression::{ChainCompression, CompressedKind, DEFAULT_LEVEL};
 use ethcore_db::keys::{BlockReceipts, BlockDetails, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions};
-use ethcore_db::{self as db, Writable, Readable, CacheUpdatePolicy};
+use ethcore_db::{self as db, Key, Writable, Readable, CacheUpdatePolicy};
 use ethereum_types::{H256, Bloom, BloomRef, U256};
 use util_mem::{MallocSizeOf, allocators::new_malloc_size_ops};
 use itertools::Itertools;
@@ -57,6 +58,13 @@ use crate::best_block::{BestBlock, BestAncientBlock};
 use crate::update::{ExtrasUpdate, ExtrasInsert};
 use crate::{CacheSize, Config};
 
+/// Number of recent canonical blocks sampled to train the compression dictionaries.
+const DICTIONARY_SAMPLE_BLOCKS: u64 = 2048;
+/// Minimal number of samples needed to train a compression dictionary.
+const DICTIONARY_MIN_SAMPLES: usize = 256;
+/// Database key of the number of the next block to be recompressed.
+const RECOMPRESS_KEY: &[u8] = b"recompress";
+
 /// Database backing `BlockChain`.
 pub trait BlockChainDB: Send + Sync {
 	/// Generic key value store.
@@ -239,6 +247,7 @@ pub struct BlockChain {
 	block_receipts: RwLock<HashMap<H256, BlockReceipts>>,
 
 	db: Arc<dyn BlockChainDB>,
+	compression: ChainCompression,
 
 	cache_man: Mutex<CacheManager<CacheId>>,
 
@@ -327,6 +336,7 @@ impl BlockProvider for BlockChain {
 		let b = self.db.key_value().get(db::COL_BODIES, hash.as_bytes())
 			.expect("Low level database error when fetching block body data. Some issue with disk?")?;
 
+		let b = self.compression.decompress(CompressedKind::Bodies, &b);
 		let body = encoded::Body::new(decompress(&b, blocks_swapper()).into_vec());
 		let mut write = self.block_bodies.write();
 		write.insert(*hash, body.clone());
@@ -358,9 +368,26 @@ impl BlockProvider for BlockChain {
 
 	/// Get receipts of block with given hash.
 	fn block_receipts(&self, hash: &H256) -> Option<BlockReceipts> {
-		let result = self.db.key_value().read_with_cache(db::COL_EXTRA, &self.block_receipts, hash)?;
+		// Check cache first
+		{
+			let read = self.block_receipts.read();
+			if let Some(v) = read.get(hash) {
+				return Some(v.clone());
+			}
+		}
+
+		// Read from DB and populate cache
+		let key = Key::<BlockReceipts>::key(hash);
+		let b = self.db.key_value().get(db::COL_EXTRA, key.as_ref())
+			.expect("Low level database error when fetching block receipts. Some issue with disk?")?;
+
+		let receipts: BlockReceipts = ::rlp::decode(&self.compression.decompress(CompressedKind::Receipts, &b))
+			.expect("decode error: the db is corrupted or the data structure has changed");
+		let mut write = self.block_receipts.write();
+		write.insert(*hash, receipts.clone());
+
 		self.cache_man.lock().note_used(CacheId::BlockReceipts(*hash));
-		Some(result)
+		Some(receipts)
 	}
 
 	/// Returns numbers of blocks containing given bloom.
@@ -564,6 +591,7 @@ impl BlockChain {
 			transaction_addresses: RwLock::new(HashMap::new()),
 			block_receipts: RwLock::new(HashMap::new()),
 			db: db.clone(),
+			compression: ChainCompression::new(config.compression, DEFAULT_LEVEL),
 			cache_man: Mutex::new(cache_man),
 			pending_best_ancient_block: RwLock::new(None),
 			pending_best_block: RwLock::new(None),
@@ -572,6 +600,8 @@ impl BlockChain {
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
 		};
 
+		bc.compression.load(&**bc.db.key_value());
+
 		// load best block
 		let best_block_hash = match bc.db.key_value().get(db::COL_EXTRA, b"best")
 			.expect("Low-level database error when fetching 'best' block. Some issue with disk?")
@@ -808,7 +838,7 @@ impl BlockChain {
 		assert!(self.pending_best_block.read().is_none());
 
 		let compressed_header = compress(block.header_view().rlp().as_raw(), blocks_swapper());
-		let compressed_body = compress(&Self::block_to_body(block.raw()), blocks_swapper());
+		let compressed_body = self.compress_body(block.raw());
 
 		// store block in db
 		batch.put(db::COL_HEADERS, hash.as_bytes(), &compressed_header);
@@ -1125,7 +1155,7 @@ impl BlockChain {
 		assert!(self.pending_best_block.read().is_none());
 
 		let compressed_header = compress(block.header_view().rlp().as_raw(), blocks_swapper());
-		let compressed_body = compress(&Self::block_to_body(block.raw()), blocks_swapper());
+		let compressed_body = self.compress_body(block.raw());
 
 		// store block in db
 		batch.put(db::COL_HEADERS, hash.as_bytes(), &compressed_header);
@@ -1155,6 +1185,61 @@ impl BlockChain {
 		ImportRoute::from(info)
 	}
 
+	/// Makes an already inserted block the best block, moving its branch into the canon chain.
+	/// Used when the head of the chain is chosen externally rather than by total difficulty.
+	/// Returns `None` if the block is unknown, is the best block or one of its ancestors,
+	/// or if switching to it would retract a finalized block.
+	pub fn set_best_block(&self, batch: &mut DBTransaction, hash: H256) -> Option<ImportRoute> {
+		let best_hash = self.best_block_hash();
+		let details = self.block_details(&hash)?;
+		let route = self.tree_route(best_hash, hash)?;
+		if route.index == route.blocks.len() || route.is_from_route_finalized {
+			return None;
+		}
+
+		if self.pending_best_block.read().is_some() {
+			warn!(target: "blockchain", "Not setting the best block to {} before the pending best block is committed", hash);
+			return None;
+		}
+
+		let block = self.block(&hash)?;
+		let route = self.tree_route(best_hash, details.parent)?;
+		let info = BlockInfo {
+			hash,
+			number: details.number,
+			total_difficulty: details.total_difficulty,
+			location: match route.blocks.len() {
+				0 => BlockLocation::CanonChain,
+				_ => BlockLocation::BranchBecomingCanonChain(BranchBecomingCanonChainData {
+					ancestor: route.ancestor,
+					enacted: route.blocks[route.index..].to_vec(),
+					retracted: route.blocks[..route.index].to_vec(),
+				}),
+			},
+		};
+
+		if let BlockLocation::BranchBecomingCanonChain(ref d) = info.location {
+			info!(target: "reorg", "Head set to {} ({} {} {})",
+				Colour::Yellow.bold().paint(format!("#{} {}", info.number, info.hash)),
+				Colour::Red.paint(d.retracted.iter().join(" ")),
+				Colour::White.paint(format!("#{} {}", self.block_details(&d.ancestor).expect("`ancestor` is in the route; qed").number, d.ancestor)),
+				Colour::Green.paint(d.enacted.iter().join(" "))
+			);
+		}
+
+		self.prepare_update(batch, ExtrasUpdate {
+			block_hashes: self.prepare_block_hashes_update(&info),
+			block_details: HashMap::new(),
+			block_receipts: HashMap::new(),
+			blocks_blooms: self.prepare_block_blooms_update(block.header_view().log_bloom(), &info),
+			transactions_addresses: self.prepare_transaction_addresses_update(block.view().transaction_hashes(), &info),
+			info: info.clone(),
+			block,
+		}, true);
+
+		Some(ImportRoute::from(info))
+	}
+
 	/// Get inserted block info which is critical to prepare extras updates.
 	fn block_info(&self, header: &HeaderView, route: TreeRoute, extras: &ExtrasInsert) -> BlockInfo {
 		let hash = header.hash();
@@ -1214,7 +1299,10 @@ impl BlockChain {
 
 		{
 			let mut write_receipts = self.block_receipts.write();
-			batch.extend_with_cache(db::COL_EXTRA, &mut *write_receipts, update.block_receipts, CacheUpdatePolicy::Remove);
+			for (hash, receipts) in update.block_receipts {
+				self.write_receipts(batch, &hash, &receipts);
+				write_receipts.remove(&hash);
+			}
 		}
 
 		if let Some((block, blooms)) = update.blocks_blooms {
@@ -1582,6 +1670,119 @@ impl BlockChain {
 		});
 	}
 
+	/// Encode the body of a block for storage.
+	fn compress_body(&self, block: &[u8]) -> Vec<u8> {
+		let body = compress(&Self::block_to_body(block), blocks_swapper());
+		self.compression.compress(CompressedKind::Bodies, &body)
+	}
+
+	/// Write receipts of a block, compressed if enabled.
+	fn write_receipts(&self, batch: &mut DBTransaction, hash: &H256, receipts: &BlockReceipts) {
+		let key = Key::<BlockReceipts>::key(hash);
+		let value = self.compression.compress(CompressedKind::Receipts, &::rlp::encode(receipts));
+		batch.put(db::COL_EXTRA, key.as_ref(), &value);
+	}
+
+	/// Rewrite stored bodies and receipts of up to `max_blocks` canonical blocks so they match
+	/// the current compression settings, continuing where the previous call stopped.
+	/// Trains the compression dictionaries first if there are none yet.
+	/// Returns the number of rewritten values.
+	pub fn recompress(&self, max_blocks: u64) -> usize {
+		if !self.compression.is_enabled() {
+			return 0;
+		}
+
+		let mut batch = DBTransaction::new();
+		let stored: Option<BlockNumber> = self.db.key_value().get(db::COL_EXTRA, RECOMPRESS_KEY)
+			.expect("Low level database error when fetching recompression progress. Some issue with disk?")
+			.map(|n| ::rlp::decode(&n).expect("recompression progress is stored as rlp-encoded number; qed"));
+		let mut from = stored.unwrap_or(0);
+
+		if self.train_dictionaries(&mut batch) {
+			// previously written values use stale dictionaries, start over.
+			from = 0;
+		}
+
+		let best = self.best_block_number();
+		let to = ::std::cmp::min(best.saturating_add(1), from.saturating_add(max_blocks));
+		let mut rewritten = 0;
+		for number in from..to {
+			let hash = match self.block_hash(number) {
+				Some(hash) => hash,
+				// blocks in the gap of a warp-synced database.
+				None => continue,
+			};
+
+			let body = self.db.key_value().get(db::COL_BODIES, hash.as_bytes())
+				.expect("Low level database error when fetching block body data. Some issue with disk?");
+			if let Some(body) = body {
+				if self.compression.needs_recompression(CompressedKind::Bodies, &body) {
+					let raw = self.compression.decompress(CompressedKind::Bodies, &body);
+					batch.put(db::COL_BODIES, hash.as_bytes(), &self.compression.compress(CompressedKind::Bodies, &raw));
+					rewritten += 1;
+				}
+			}
+
+			let key = Key::<BlockReceipts>::key(&hash);
+			let receipts = self.db.key_value().get(db::COL_EXTRA, key.as_ref())
+				.expect("Low level database error when fetching block receipts. Some issue with disk?");
+			if let Some(receipts) = receipts {
+				if self.compression.needs_recompression(CompressedKind::Receipts, &receipts) {
+					let raw = self.compression.decompress(CompressedKind::Receipts, &receipts);
+					batch.put(db::COL_EXTRA, key.as_ref(), &self.compression.compress(CompressedKind::Receipts, &raw));
+					rewritten += 1;
+				}
+			}
+		}
+
+		// once caught up with the head, ticks find nothing to rewrite and leave the database alone.
+		if stored != Some(to) {
+			batch.put(db::COL_EXTRA, RECOMPRESS_KEY, &::rlp::encode(&to));
+		}
+		if !batch.ops.is_empty() {
+			self.db.key_value().write(batch).expect("Low level database error when writing recompressed data. Some issue with disk?");
+		}
+
+		if rewritten > 0 {
+			debug!(target: "blockchain", "Recompressed {} values of blocks #{}..#{}", rewritten, from, to);
+		}
+		rewritten
+	}
+
+	/// Train compression dictionaries which don't exist yet from the most recent canonical blocks.
+	/// Returns true if any dictionary was trained.
+	fn train_dictionaries(&self, batch: &mut DBTransaction) -> bool {
+		let mut trained = false;
+		for kind in &[CompressedKind::Bodies, CompressedKind::Receipts] {
+			if self.compression.active_dictionary(*kind).is_some() {
+				continue;
+			}
+
+			let best = self.best_block_number();
+			let samples = (best.saturating_sub(DICTIONARY_SAMPLE_BLOCKS)..=best)
+				.filter_map(|number| self.block_hash(number))
+				.filter_map(|hash| match *kind {
+					CompressedKind::Bodies => self.db.key_value().get(db::COL_BODIES, hash.as_bytes()),
+					CompressedKind::Receipts => self.db.key_value().get(db::COL_EXTRA, Key::<BlockReceipts>::key(&hash).as_ref()),
+				}.expect("Low level database error when sampling block data. Some issue with disk?"))
+				.map(|value| self.compression.decompress(*kind, &value).into_owned())
+				.collect::<Vec<_>>();
+
+			if samples.len() < DICTIONARY_MIN_SAMPLES {
+				continue;
+			}
+
+			match self.compression.train(*kind, &samples, batch) {
+				Ok(id) => {
+					info!(target: "blockchain", "Trained compression dictionary #{} for {:?} from {} samples", id, kind, samples.len());
+					trained = true;
+				},
+				Err(e) => warn!(target: "blockchain", "Failed to train compression dictionary for {:?}: {}", kind, e),
+			}
+		}
+		trained
+	}
+
 	/// Create a block body from a block.
 	pub fn block_to_body(block: &[u8]) -> Bytes {
 		let mut body = RlpStream::new_list(2);
@@ -1874,6 +2075,60 @@ mod tests {
 		assert_eq!(bc.transaction_address(&t1_hash), None);
 	}
 
+	#[test]
+	fn set_best_block_moves_branch_into_canon_chain() {
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: "601080600c6000396000f3006000355415600957005b60203560003555".from_hex().unwrap(),
+		}.sign(&secret(), None);
+
+		let t1_hash = t1.hash();
+
+		let genesis = BlockBuilder::genesis();
+		let b1a = genesis.add_block_with_transactions(iter::once(t1));
+		let b1b = genesis.add_block_with_difficulty(9);
+		let b2 = b1b.add_block();
+
+		let genesis_hash = genesis.last().hash();
+		let b1a_hash = b1a.last().hash();
+		let b1b_hash = b1b.last().hash();
+		let b2_hash = b2.last().hash();
+
+		let db = new_db();
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+
+		let mut batch = db.key_value().transaction();
+		let _ = insert_block_batch(&mut batch, &bc, b1a.last().encoded(), vec![]);
+		bc.commit();
+		let _ = insert_block_batch(&mut batch, &bc, b1b.last().encoded(), vec![]);
+		bc.commit();
+		let _ = bc.insert_block(&mut batch, b2.last().encoded(), vec![], ExtrasInsert {
+			fork_choice: common_types::engines::ForkChoice::Old,
+			is_finalized: false,
+		});
+		bc.commit();
+		db.key_value().write(batch).unwrap();
+		assert_eq!(bc.best_block_hash(), b1a_hash);
+
+		let mut batch = db.key_value().transaction();
+		assert!(bc.set_best_block(&mut batch, b1a_hash).is_none());
+		assert!(bc.set_best_block(&mut batch, genesis_hash).is_none());
+		let route = bc.set_best_block(&mut batch, b2_hash).unwrap();
+		bc.commit();
+		db.key_value().write(batch).unwrap();
+
+		assert_eq!(route.enacted, vec![b1b_hash, b2_hash]);
+		assert_eq!(route.retracted, vec![b1a_hash]);
+		assert_eq!(bc.best_block_hash(), b2_hash);
+		assert_eq!(bc.block_hash(1), Some(b1b_hash));
+		assert_eq!(bc.block_hash(2), Some(b2_hash));
+		assert_eq!(bc.transaction_address(&t1_hash), None);
+	}
+
 	#[test]
 	fn test_overwriting_transaction_addresses() {
 		let t1 = Transaction {
@@ -2429,6 +2684,43 @@ mod tests {
 		assert_eq!(bc.best_block_number(), 5);
 	}
 
+	#[test]
+	fn recompress_existing_bodies_and_receipts() {
+		let genesis = BlockBuilder::genesis();
+		let blocks = genesis.add_blocks(3);
+		let receipt = Receipt {
+			outcome: TransactionOutcome::StateRoot(H256::zero()),
+			gas_used: 10_000.into(),
+			log_bloom: Default::default(),
+			logs: vec![],
+		};
+
+		let db = new_db();
+		{
+			let bc = new_chain(genesis.last().encoded(), db.clone());
+			for block in BlockGenerator::new(iter::once(blocks.clone())) {
+				insert_block(&db, &bc, block.encoded(), vec![receipt.clone()]);
+			}
+		}
+
+		let config = Config { compression: true, ..Config::default() };
+		let bc = BlockChain::new(config.clone(), genesis.last().encoded().raw(), db.clone());
+		let best_hash = bc.best_block_hash();
+		let stored_body = || db.key_value().get(db::COL_BODIES, best_hash.as_bytes()).unwrap().unwrap();
+		assert!(stored_body()[0] >= 0xc0);
+
+		// genesis and the 3 inserted blocks have a body, only the latter have receipts.
+		assert_eq!(bc.recompress(16), 7);
+		assert_eq!(bc.recompress(16), 0);
+		assert!(stored_body()[0] < 0xc0);
+
+		let bc = BlockChain::new(config, genesis.last().encoded().raw(), db.clone());
+		for block in BlockGenerator::new(iter::once(blocks)) {
+			assert_eq!(bc.block_body(&block.hash()).unwrap().transactions_count(), 0);
+			assert_eq!(bc.block_receipts(&block.hash()).unwrap().receipts, vec![receipt.clone()]);
+		}
+	}
+
 	#[test]
 	fn epoch_transitions_iter() {
 		use common_types::engines::epoch::Transition as EpochTransition;
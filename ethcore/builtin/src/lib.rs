@@ -44,6 +44,10 @@ use eth_pairings::public_interface::eip2537::{
 	SCALAR_BYTE_LENGTH
 };
 
+mod wasm;
+
+pub use crate::wasm::WasmBuiltin;
+
 /// Native implementation of a built-in contract.
 pub trait Implementation: Send + Sync {
 	/// execute this built-in on the given input, writing to the given output.
@@ -82,6 +86,7 @@ enum Pricing {
 	AltBn128ConstOperations(AltBn128ConstOperations),
 	Blake2F(Blake2FPricer),
 	Linear(Linear),
+	Quadratic(Quadratic),
 	Modexp(ModexpPricer),
 	Bls12Pairing(Bls12PairingPricer),
 	Bls12ConstOperations(Bls12ConstOperations),
@@ -96,6 +101,7 @@ impl Pricer for Pricing {
 			Pricing::AltBn128ConstOperations(inner) => inner.cost(input),
 			Pricing::Blake2F(inner) => inner.cost(input),
 			Pricing::Linear(inner) => inner.cost(input),
+			Pricing::Quadratic(inner) => inner.cost(input),
 			Pricing::Modexp(inner) => inner.cost(input),
 			Pricing::Bls12Pairing(inner) => inner.cost(input),
 			Pricing::Bls12ConstOperations(inner) => inner.cost(input),
@@ -112,6 +118,15 @@ struct Linear {
 	word: u64,
 }
 
+/// A linear pricing model with an added quadratic term, priced like memory expansion:
+/// `base + word * words + words^2 / quad_divisor`.
+#[derive(Debug)]
+struct Quadratic {
+	base: u64,
+	word: u64,
+	quad_divisor: u64,
+}
+
 /// A special pricing model for modular exponentiation.
 #[derive(Debug)]
 struct ModexpPricer {
@@ -124,6 +139,13 @@ impl Pricer for Linear {
 	}
 }
 
+impl Pricer for Quadratic {
+	fn cost(&self, input: &[u8]) -> U256 {
+		let words = U256::from((input.len() + 31) / 32);
+		U256::from(self.base) + U256::from(self.word) * words + words * words / U256::from(self.quad_divisor)
+	}
+}
+
 /// alt_bn128 pairing price
 #[derive(Debug, Copy, Clone)]
 struct AltBn128PairingPrice {
@@ -377,7 +399,17 @@ impl TryFrom<ethjson::spec::builtin::Builtin> for Builtin {
 	type Error = EthcoreError;
 
 	fn try_from(b: ethjson::spec::builtin::Builtin) -> Result<Self, Self::Error> {
-		let native = EthereumBuiltin::from_str(&b.name)?;
+		let native = match (b.name.as_str(), b.wasm) {
+			("wasm", Some(module)) => {
+				let code: Vec<u8> = module.code
+					.ok_or_else(|| EthcoreError::Msg("WASM builtin module not loaded".into()))?
+					.into();
+				EthereumBuiltin::Wasm(WasmBuiltin::new(&code, module.budget).map_err(EthcoreError::Msg)?)
+			},
+			("wasm", None) => return Err(EthcoreError::Msg("WASM builtin without a module".into())),
+			(name, Some(_)) => return Err(EthcoreError::Msg(format!("builtin {} doesn't take a WASM module", name))),
+			(name, None) => EthereumBuiltin::from_str(name)?,
+		};
 		let mut pricer = BTreeMap::new();
 
 		for (activate_at, p) in b.pricing {
@@ -400,6 +432,18 @@ impl From<ethjson::spec::builtin::Pricing> for Pricing {
 					word: linear.word,
 				})
 			}
+			ethjson::spec::builtin::Pricing::Quadratic(quadratic) => {
+				Pricing::Quadratic(Quadratic {
+					base: quadratic.base,
+					word: quadratic.word,
+					quad_divisor: if quadratic.quad_divisor == 0 {
+						warn!(target: "builtin", "Zero quadratic divisor specified. Falling back to default: 512.");
+						512
+					} else {
+						quadratic.quad_divisor
+					},
+				})
+			}
 			ethjson::spec::builtin::Pricing::Modexp(exp) => {
 				Pricing::Modexp(ModexpPricer {
 					divisor: if exp.divisor == 0 {
@@ -497,6 +541,8 @@ enum EthereumBuiltin {
 	Bls12MapFpToG1(Bls12MapFpToG1),
 	/// bls12_381 fp2 to g2 mapping
 	Bls12MapFp2ToG2(Bls12MapFp2ToG2),
+	/// Contract implemented by a WASM module of the chain spec
+	Wasm(WasmBuiltin),
 }
 
 impl FromStr for EthereumBuiltin {
@@ -548,6 +594,7 @@ impl Implementation for EthereumBuiltin {
 			EthereumBuiltin::Bls12Pairing(inner) => inner.execute(input, output),
 			EthereumBuiltin::Bls12MapFpToG1(inner) => inner.execute(input, output),
 			EthereumBuiltin::Bls12MapFp2ToG2(inner) => inner.execute(input, output),
+			EthereumBuiltin::Wasm(inner) => inner.execute(input, output),
 		}
 	}
 }
@@ -1124,9 +1171,10 @@ mod tests {
 	use parity_bytes::BytesRef;
 	use super::{
 		Builtin, EthereumBuiltin, FromStr, Implementation, Linear,
-		ModexpPricer, Pricing,
+		ModexpPricer, Pricing, Quadratic,
 		Bls12ConstOperations,
-		Bls12PairingPrice,Bls12PairingPricer
+		Bls12PairingPrice,Bls12PairingPricer,
+		G1Marker, PointScalarLength, BLS12_G1_AND_G2_PAIR_LEN,
 	};
 
 	#[test]
@@ -1674,10 +1722,25 @@ mod tests {
 		assert_eq!(i, o);
 	}
 
+	#[test]
+	fn quadratic_pricing() {
+		let pricer = Pricing::Quadratic(Quadratic { base: 10, word: 3, quad_divisor: 512 });
+		let b = Builtin {
+			pricer: btreemap![0 => pricer],
+			native: EthereumBuiltin::from_str("identity").unwrap(),
+		};
+
+		assert_eq!(b.cost(&[0; 0], 0), U256::from(10));
+		assert_eq!(b.cost(&[0; 32], 0), U256::from(13));
+		// 1024 words: 10 + 3 * 1024 + 1024 * 1024 / 512
+		assert_eq!(b.cost(&[0; 32 * 1024], 0), U256::from(10 + 3072 + 2048));
+	}
+
 	#[test]
 	fn from_json() {
 		let b = Builtin::try_from(ethjson::spec::Builtin {
 			name: "identity".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				0 => PricingAt {
 					info: None,
@@ -1701,6 +1764,7 @@ mod tests {
 	fn bn128_pairing_eip1108_transition() {
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "alt_bn128_pairing".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				10 => PricingAt {
 					info: None,
@@ -1727,6 +1791,7 @@ mod tests {
 	fn bn128_add_eip1108_transition() {
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "alt_bn128_add".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				10 => PricingAt {
 					info: None,
@@ -1753,6 +1818,7 @@ mod tests {
 	fn bn128_mul_eip1108_transition() {
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "alt_bn128_mul".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				10 => PricingAt {
 					info: None,
@@ -1780,6 +1846,7 @@ mod tests {
 	fn multimap_use_most_recent_on_activate() {
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "alt_bn128_mul".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				10 => PricingAt {
 					info: None,
@@ -1818,6 +1885,7 @@ mod tests {
 	fn multimap_use_last_with_same_activate_at() {
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "alt_bn128_mul".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				1 => PricingAt {
 					info: None,
@@ -2201,6 +2269,7 @@ mod tests {
 
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "bls12_381_g1_multiexp".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				10000000 => PricingAt {
 					info: None,
@@ -2225,6 +2294,7 @@ mod tests {
 
 		let b = Builtin::try_from(JsonBuiltin {
 			name: "bls12_381_g2_multiexp".to_owned(),
+			wasm: None,
 			pricing: btreemap![
 				10000000 => PricingAt {
 					info: None,
@@ -2242,4 +2312,49 @@ mod tests {
 			}
 		}
 	}
+
+	#[test]
+	fn bls12_381_multiexp_pricing() {
+		use ethjson::spec::builtin::{Pricing, Bls12G1Multiexp};
+
+		let b = Builtin::try_from(JsonBuiltin {
+			name: "bls12_381_g1_multiexp".to_owned(),
+			wasm: None,
+			pricing: btreemap![
+				0 => PricingAt {
+					info: None,
+					price: Pricing::Bls12G1Multiexp(Bls12G1Multiexp {
+						base: 12000,
+					}),
+				}
+			],
+		}).unwrap();
+
+		let pair_len = G1Marker::LENGTH;
+		assert_eq!(b.cost(&[], 0), U256::zero());
+		assert_eq!(b.cost(&vec![0; pair_len], 0), U256::from(14_400), "12 000 * 1 * 1200 / 1000 == 14 400");
+		assert_eq!(b.cost(&vec![0; pair_len * 2 + 1], 0), U256::from(21_312), "12 000 * 2 * 888 / 1000 == 21 312");
+		assert_eq!(b.cost(&vec![0; pair_len * 200], 0), U256::from(417_600), "12 000 * 200 * 174 / 1000 == 417 600");
+	}
+
+	#[test]
+	fn bls12_381_pairing_pricing() {
+		use ethjson::spec::builtin::{Pricing, Bls12Pairing};
+
+		let b = Builtin::try_from(JsonBuiltin {
+			name: "bls12_381_pairing".to_owned(),
+			wasm: None,
+			pricing: btreemap![
+				0 => PricingAt {
+					info: None,
+					price: Pricing::Bls12Pairing(Bls12Pairing {
+						base: 115_000,
+						pair: 23_000,
+					}),
+				}
+			],
+		}).unwrap();
+
+		assert_eq!(b.cost(&vec![0; BLS12_G1_AND_G2_PAIR_LEN * 3], 0), U256::from(184_000), "23 000 * 3 + 115 000 == 184 000");
+	}
 }
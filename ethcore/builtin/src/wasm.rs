@@ -0,0 +1,266 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Built-in contracts implemented by WASM modules shipped with the chain spec.
+//!
+//! A module imports its memory and the following functions from `env`:
+//!
+//! - `input_length() -> i32`: length of the call input;
+//! - `fetch_input(ptr: i32)`: copy the call input to `ptr`;
+//! - `ret(ptr: i32, len: i32)`: set the call output to the `len` bytes at `ptr`.
+//!
+//! and exports a `call()` function executing the contract. The module is metered: every
+//! instruction consumes a unit of its budget and running out of it fails the call, so a module
+//! can't stall the block processing however it is called.
+
+use std::fmt;
+
+use parity_bytes::BytesRef;
+use parity_wasm::elements::{self, Deserialize};
+use pwasm_utils::{self as wasm_utils, rules};
+use wasmi::{
+	self, Externals, FuncInstance, FuncRef, ImportsBuilder, MemoryDescriptor, MemoryInstance, MemoryRef,
+	ModuleImportResolver, ModuleInstance, RuntimeArgs, RuntimeValue, Signature, Trap, ValueType, memory_units,
+};
+
+use crate::Implementation;
+
+/// Maximal memory of a module, in 64KiB pages.
+const MAX_MEMORY_PAGES: u32 = 16;
+/// Maximal height of the value stack of a module.
+const MAX_STACK_HEIGHT: u32 = 64 * 1024;
+
+const INPUT_LENGTH_FUNC: usize = 0;
+const FETCH_INPUT_FUNC: usize = 1;
+const RET_FUNC: usize = 2;
+const GAS_FUNC: usize = 3;
+
+/// Built-in contract executing a WASM module.
+pub struct WasmBuiltin {
+	module: wasmi::Module,
+	budget: u64,
+}
+
+impl fmt::Debug for WasmBuiltin {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("WasmBuiltin").field("budget", &self.budget).finish()
+	}
+}
+
+impl WasmBuiltin {
+	/// Validate the module `code` and inject its metering, allowing `budget` instructions per call.
+	pub fn new(code: &[u8], budget: u64) -> Result<Self, String> {
+		let module = elements::Module::deserialize(&mut &code[..])
+			.map_err(|e| format!("Invalid WASM module: {:?}", e))?;
+		if module.memory_section().map_or(false, |memory| !memory.entries().is_empty()) {
+			return Err("Invalid WASM module: the memory must be imported from env".into());
+		}
+
+		let rules = rules::Set::default().with_forbidden_floats();
+		let module = wasm_utils::inject_gas_counter(module, &rules)
+			.map_err(|_| "Invalid WASM module: failed to inject the metering".to_owned())?;
+		let module = wasm_utils::stack_height::inject_limiter(module, MAX_STACK_HEIGHT)
+			.map_err(|_| "Invalid WASM module: failed to inject the stack limiter".to_owned())?;
+		let module = wasmi::Module::from_parity_wasm_module(module)
+			.map_err(|e| format!("Invalid WASM module: {}", e))?;
+
+		Ok(WasmBuiltin { module, budget })
+	}
+
+	fn run(&self, input: &[u8]) -> Result<Vec<u8>, wasmi::Error> {
+		let resolver = Resolver::default();
+		let instance = ModuleInstance::new(&self.module, &ImportsBuilder::new().with_resolver("env", &resolver))?;
+		let mut host = Host {
+			memory: resolver.memory.borrow().clone(),
+			input,
+			output: Vec::new(),
+			budget: self.budget,
+		};
+		let instance = instance.run_start(&mut host)?;
+		instance.invoke_export("call", &[], &mut host)?;
+		Ok(host.output)
+	}
+}
+
+impl Implementation for WasmBuiltin {
+	fn execute(&self, input: &[u8], output: &mut BytesRef) -> Result<(), &'static str> {
+		match self.run(input) {
+			Ok(result) => {
+				output.write(0, &result);
+				Ok(())
+			},
+			Err(e) => {
+				log::trace!(target: "builtin", "WASM builtin failed: {}", e);
+				Err("WASM builtin failed")
+			},
+		}
+	}
+}
+
+/// Failure of a host function, trapping the module.
+#[derive(Debug)]
+enum HostError {
+	OutOfBudget,
+	NoMemory,
+}
+
+impl fmt::Display for HostError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HostError::OutOfBudget => write!(f, "out of budget"),
+			HostError::NoMemory => write!(f, "memory not imported"),
+		}
+	}
+}
+
+impl wasmi::HostError for HostError {}
+
+/// Functions and memory imported by the module.
+#[derive(Default)]
+struct Resolver {
+	memory: ::std::cell::RefCell<Option<MemoryRef>>,
+}
+
+impl ModuleImportResolver for Resolver {
+	fn resolve_func(&self, field_name: &str, _signature: &Signature) -> Result<FuncRef, wasmi::Error> {
+		let (params, result, index): (&[ValueType], _, _) = match field_name {
+			"input_length" => (&[], Some(ValueType::I32), INPUT_LENGTH_FUNC),
+			"fetch_input" => (&[ValueType::I32], None, FETCH_INPUT_FUNC),
+			"ret" => (&[ValueType::I32, ValueType::I32], None, RET_FUNC),
+			"gas" => (&[ValueType::I32], None, GAS_FUNC),
+			_ => return Err(wasmi::Error::Instantiation(format!("Export {} not found", field_name))),
+		};
+		Ok(FuncInstance::alloc_host(Signature::new(params, result), index))
+	}
+
+	fn resolve_memory(&self, field_name: &str, descriptor: &MemoryDescriptor) -> Result<MemoryRef, wasmi::Error> {
+		if field_name != "memory" {
+			return Err(wasmi::Error::Instantiation("Memory imported under unknown name".into()));
+		}
+		let maximum = descriptor.maximum().unwrap_or(MAX_MEMORY_PAGES);
+		if descriptor.initial() > MAX_MEMORY_PAGES || maximum > MAX_MEMORY_PAGES {
+			return Err(wasmi::Error::Instantiation("Module requested too much memory".into()));
+		}
+		let memory = MemoryInstance::alloc(
+			memory_units::Pages(descriptor.initial() as usize),
+			Some(memory_units::Pages(maximum as usize)),
+		)?;
+		*self.memory.borrow_mut() = Some(memory.clone());
+		Ok(memory)
+	}
+}
+
+/// State of a call.
+struct Host<'a> {
+	memory: Option<MemoryRef>,
+	input: &'a [u8],
+	output: Vec<u8>,
+	budget: u64,
+}
+
+impl<'a> Host<'a> {
+	fn memory(&self) -> Result<&MemoryRef, Trap> {
+		self.memory.as_ref().ok_or_else(|| HostError::NoMemory.into())
+	}
+}
+
+impl<'a> Externals for Host<'a> {
+	fn invoke_index(&mut self, index: usize, args: RuntimeArgs) -> Result<Option<RuntimeValue>, Trap> {
+		match index {
+			INPUT_LENGTH_FUNC => Ok(Some(RuntimeValue::I32(self.input.len() as i32))),
+			FETCH_INPUT_FUNC => {
+				let ptr: u32 = args.nth_checked(0)?;
+				self.memory()?.set(ptr, self.input)?;
+				Ok(None)
+			},
+			RET_FUNC => {
+				let ptr: u32 = args.nth_checked(0)?;
+				let len: u32 = args.nth_checked(1)?;
+				self.output = self.memory()?.get(ptr, len as usize)?;
+				Ok(None)
+			},
+			GAS_FUNC => {
+				let amount: u32 = args.nth_checked(0)?;
+				match self.budget.checked_sub(amount as u64) {
+					Some(budget) => {
+						self.budget = budget;
+						Ok(None)
+					},
+					None => Err(HostError::OutOfBudget.into()),
+				}
+			},
+			_ => panic!("Only functions resolved by Resolver are imported; qed"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hex_literal::hex;
+	use parity_bytes::BytesRef;
+	use crate::Implementation;
+	use super::WasmBuiltin;
+
+	/// Module returning its input reversed, in the text format:
+	///
+	/// ```text
+	/// (module
+	///   (import "env" "memory" (memory 1 1))
+	///   (import "env" "input_length" (func $input_length (result i32)))
+	///   (import "env" "fetch_input" (func $fetch_input (param i32)))
+	///   (import "env" "ret" (func $ret (param i32 i32)))
+	///   (func (export "call") (local $len i32) (local $i i32)
+	///     (set_local $len (call $input_length))
+	///     (call $fetch_input (i32.const 0))
+	///     (block (loop
+	///       (br_if 1 (i32.ge_u (get_local $i) (get_local $len)))
+	///       (i32.store8 (i32.add (get_local $len) (get_local $i))
+	///         (i32.load8_u (i32.sub (i32.sub (get_local $len) (get_local $i)) (i32.const 1))))
+	///       (set_local $i (i32.add (get_local $i) (i32.const 1)))
+	///       (br 0)))
+	///     (call $ret (get_local $len) (get_local $len))))
+	/// ```
+	const REVERSE: &[u8] = &hex!("
+		0061736d0100000001110460000060017f0060027f7f006000017f023f0403656e76066d656d6f72790201010103656e
+		760c696e7075745f6c656e677468000303656e760b66657463685f696e707574000103656e7603726574000203020100
+		0708010463616c6c00030a3d013b01027f100021004100100102400340200120004f0d01200020016a200020016b4101
+		6b2d00003a0000200141016a21010c000b0b2000200010020b
+	");
+
+	fn reverse(budget: u64) -> WasmBuiltin {
+		WasmBuiltin::new(REVERSE, budget).unwrap()
+	}
+
+	#[test]
+	fn executes_the_module() {
+		let builtin = reverse(1_000_000);
+		let mut output = vec![0u8; 4];
+		builtin.execute(&[1, 2, 3, 4], &mut BytesRef::Fixed(&mut output[..])).unwrap();
+		assert_eq!(output, vec![4, 3, 2, 1]);
+	}
+
+	#[test]
+	fn fails_when_out_of_budget() {
+		let builtin = reverse(10);
+		let mut output = vec![0u8; 4];
+		assert!(builtin.execute(&[1, 2, 3, 4], &mut BytesRef::Fixed(&mut output[..])).is_err());
+	}
+
+	#[test]
+	fn rejects_invalid_modules() {
+		assert!(WasmBuiltin::new(&[0, 1, 2, 3], 1_000).is_err());
+	}
+}
@@ -17,6 +17,7 @@
 use std::{
 	collections::BTreeMap,
 	sync::Arc,
+	time::Duration,
 };
 
 use account_state::state::StateInfo;
@@ -25,35 +26,42 @@ use bytes::Bytes;
 use call_contract::CallContract;
 use registrar::RegistrarClient;
 use common_types::{
+	ancestry_proof::AncestryProof,
 	basic_account::BasicAccount,
 	block_status::BlockStatus,
 	blockchain_info::BlockChainInfo,
 	BlockNumber,
 	call_analytics::CallAnalytics,
 	chain_notify::{NewBlocks, ChainMessageType},
-	client_types::Mode,
+	client_types::{Mode, StateWrites},
+	confirmation_status::ConfirmationStatus,
 	encoded,
-	engines::{epoch::Transition as EpochTransition, machine::Executed},
+	engines::{epoch::{EpochValidators, Transition as EpochTransition}, machine::Executed},
 	errors::{EthcoreError, EthcoreResult},
-	filter::Filter,
+	filter::{Filter, ReconstructLogsError},
+	gas_stats::{BlockGasStats, GasStats},
 	header::Header,
 	ids::{BlockId, TransactionId, TraceId, UncleId},
 	log_entry::LocalizedLogEntry,
 	pruning_info::PruningInfo,
-	receipt::LocalizedReceipt,
+	receipt::{LocalizedReceipt, ReceiptProof},
+	state_diff::StateDiff,
+	threat_status::ThreatStatus,
 	trace_filter::Filter as TraceFilter,
 	transaction::{self, Action, LocalizedTransaction, CallError, SignedTransaction, UnverifiedTransaction},
 	tree_route::TreeRoute,
 	verification::{VerificationQueueInfo, Unverified},
 };
-use ethereum_types::{Address, H256, U256};
+use ethereum_types::{Address, H256, H520, U256};
 use ethcore_db::keys::BlockReceipts;
 use ethcore_miner::pool::VerifiedTransaction;
 use kvdb::DBValue;
 use stats;
 use trace::{
 	FlatTrace,
+	GasProfile,
 	localized::LocalizedTrace,
+	StructLogger,
 	VMTrace,
 };
 use common_types::{
@@ -105,6 +113,17 @@ pub trait Balance {
 	/// Returns None if and only if the block's root hash has been pruned from the DB.
 	fn balance(&self, address: &Address, state: StateOrBlock) -> Option<U256>;
 
+	/// Get balances of several addresses at the given block's state, all read from the same state.
+	///
+	/// Returns None if and only if the block's root hash has been pruned from the DB, and an error
+	/// if the state couldn't be read.
+	fn balances(&self, addresses: &[Address], state: StateOrBlock) -> Option<Result<Vec<U256>, String>> {
+		match state {
+			StateOrBlock::State(s) => Some(addresses.iter().map(|address| s.balance(address).map_err(|e| e.to_string())).collect()),
+			StateOrBlock::Block(id) => addresses.iter().map(|address| self.balance(address, id.into())).collect::<Option<_>>().map(Ok),
+		}
+	}
+
 	/// Get address balance at the latest block's state.
 	fn latest_balance(&self, address: &Address) -> U256 {
 		self.balance(address, BlockId::Latest.into())
@@ -216,13 +235,46 @@ impl Tick for () {}
 pub trait BadBlocks {
 	/// Returns a list of blocks that were recently not imported because they were invalid.
 	fn bad_blocks(&self) -> Vec<(Unverified, String)>;
+
+	/// Returns the diagnostics gathered on a recent bad block, e.g. the state it changed before
+	/// failing the state root check, as JSON.
+	fn bad_block_diagnostics(&self, _hash: &H256) -> Option<String> {
+		None
+	}
 }
 
 
+/// A block which may be included as an uncle in the next authored block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UncleCandidate {
+	/// Hash of the uncle.
+	pub hash: H256,
+	/// Number of the uncle.
+	pub number: BlockNumber,
+	/// Author of the uncle.
+	pub author: Address,
+	/// Number of generations between the uncle and the next block.
+	pub age: u64,
+	/// Whether the uncle was authored by the block author.
+	pub own: bool,
+	/// Whether the uncle selection policy includes the uncle in the next block.
+	pub included: bool,
+	/// Increase of the block author's balance when the uncle is included,
+	/// including the uncle reward if the uncle is authored by the block author.
+	pub expected_reward: U256,
+}
+
+/// Provides the uncles which may be included in the next authored block.
+pub trait UncleCandidates {
+	/// Returns the possible uncles of a block authored by `author` on top of the best block,
+	/// in the order the uncle selection policy considers them.
+	fn uncle_candidates(&self, author: &Address) -> Vec<UncleCandidate>;
+}
+
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
 pub trait BlockChainClient:
 	Sync + Send + AccountData + BlockChain + CallContract + RegistrarClient
-	+ ImportBlock + IoClient + BadBlocks
+	+ ImportBlock + IoClient + BadBlocks + UncleCandidates
 {
 	/// Look up the block number for the given block ID.
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber>;
@@ -237,6 +289,9 @@ pub trait BlockChainClient:
 	/// Get block total difficulty.
 	fn block_total_difficulty(&self, id: BlockId) -> Option<U256>;
 
+	/// Whether the engine has finalized the given block, `None` if the block is unknown.
+	fn block_finalized(&self, id: BlockId) -> Option<bool>;
+
 	/// Attempt to get address storage root at given block.
 	/// May not fail on BlockId::Latest.
 	fn storage_root(&self, address: &Address, id: BlockId) -> Option<H256>;
@@ -269,6 +324,23 @@ pub trait BlockChainClient:
 	/// Returns None if and only if the block's root hash has been pruned from the DB.
 	fn storage_at(&self, address: &Address, position: &H256, state: StateOrBlock) -> Option<H256>;
 
+	/// Get values of the storage at several positions at the given block's state, all read from
+	/// the same state.
+	///
+	/// Returns None if and only if the block's root hash has been pruned from the DB, and an error
+	/// if the state couldn't be read.
+	fn storage_at_batch(&self, positions: &[(Address, H256)], state: StateOrBlock) -> Option<Result<Vec<H256>, String>> {
+		match state {
+			StateOrBlock::State(s) => Some(positions.iter()
+				.map(|(address, position)| s.storage_at(address, position).map_err(|e| e.to_string()))
+				.collect()),
+			StateOrBlock::Block(id) => positions.iter()
+				.map(|(address, position)| self.storage_at(address, position, id.into()))
+				.collect::<Option<_>>()
+				.map(Ok),
+		}
+	}
+
 	/// Get value of the storage at given position at the latest block's state.
 	fn latest_storage_at(&self, address: &Address, position: &H256) -> H256 {
 		self.storage_at(address, position, BlockId::Latest.into())
@@ -293,6 +365,10 @@ pub trait BlockChainClient:
 	/// Get transaction receipt with given hash.
 	fn transaction_receipt(&self, id: TransactionId) -> Option<LocalizedReceipt>;
 
+	/// Get the confirmation status of a transaction in the canonical chain: how deep it is,
+	/// the heaviest known branch which would revert it and whether it has been finalized.
+	fn confirmation_status(&self, id: TransactionId) -> Option<ConfirmationStatus>;
+
 	/// Get localized receipts for all transaction in given block.
 	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>>;
 
@@ -320,12 +396,33 @@ pub trait BlockChainClient:
 	/// Returns logs matching given filter. If one of the filtering block cannot be found, returns the block id that caused the error.
 	fn logs(&self, filter: Filter) -> Result<Vec<LocalizedLogEntry>, BlockId>;
 
+	/// Returns logs matching given filter from the blocks whose receipts are no longer stored, re-executing
+	/// these blocks on top of their parent state. Fails if the parent state of one of them is pruned.
+	fn reconstructed_logs(&self, filter: Filter) -> Result<Vec<LocalizedLogEntry>, ReconstructLogsError>;
+
 	/// Replays a given transaction for inspection.
 	fn replay(&self, t: TransactionId, analytics: CallAnalytics) -> Result<Executed<FlatTrace, VMTrace>, CallError>;
 
+	/// Replays a given transaction, streaming its executed VM steps through the given logger.
+	/// Returns the number of steps sent.
+	fn replay_struct_logs(&self, t: TransactionId, logger: StructLogger) -> Result<usize, CallError>;
+
+	/// Replays a given transaction, returning the gas spent by every call site.
+	fn replay_gas_profile(&self, t: TransactionId) -> Result<Vec<GasProfile>, CallError>;
+
 	/// Replays all the transactions in a given block for inspection.
 	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Box<dyn Iterator<Item = (H256, Executed<FlatTrace, VMTrace>)>>, CallError>;
 
+	/// Re-executes a block on top of its parent's state, returning the trie nodes and
+	/// code read from the parent's state, ordered by hash.
+	fn execution_witness(&self, block: BlockId) -> Result<Vec<Bytes>, CallError>;
+
+	/// Returns the changes to accounts between the states of two blocks, looking at no more than
+	/// `count` candidate accounts following `after` in address order, together with the address
+	/// to continue from if there are more. The tries are compared directly when fat DB is enabled,
+	/// otherwise the canonical blocks in between are replayed to find the touched accounts.
+	fn state_diff(&self, from: BlockId, to: BlockId, after: Option<&Address>, count: u64) -> Result<(StateDiff, Option<Address>), CallError>;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -367,6 +464,30 @@ pub trait BlockChainClient:
 		corpus.into()
 	}
 
+	/// Gas statistics of a single block, computed from its receipts.
+	fn block_gas_stats(&self, hash: &H256) -> Option<BlockGasStats> {
+		let block = self.block(BlockId::Hash(*hash))?;
+		let receipts = self.block_receipts(hash)?;
+		Some(BlockGasStats::new(&block, &receipts.receipts))
+	}
+
+	/// Gas statistics aggregated over the canon blocks `from..=to`, with at most `top` contracts.
+	/// Returns `None` if any block of the range is unknown or lacks receipts.
+	fn gas_stats(&self, from: BlockNumber, to: BlockNumber, top: usize) -> Option<GasStats> {
+		let blocks = (from..=to)
+			.map(|number| self.block_hash(BlockId::Number(number)).and_then(|hash| self.block_gas_stats(&hash)))
+			.collect::<Option<Vec<_>>>()?;
+		GasStats::aggregate(&blocks, top)
+	}
+
+	/// State written by the import of a recent block, if the writes were recorded.
+	fn state_writes(&self, _hash: &H256) -> Option<StateWrites> {
+		None
+	}
+
+	/// Start or stop recording the state written by imported blocks for `state_writes`.
+	fn record_state_writes(&self, _record: bool) {}
+
 	/// Get the preferred chain ID to sign on
 	fn signing_chain_id(&self) -> Option<u64>;
 
@@ -382,6 +503,31 @@ pub trait BlockChainClient:
 	/// Set the chain via a spec name.
 	fn set_spec_name(&self, spec_name: String) -> Result<(), ()>;
 
+	/// Apply a signed update of the consensus engine parameters and verify the queued blocks again.
+	/// The update is stored in the database and applied again when the client restarts.
+	fn reload_engine_params(&self, update: &[u8], signature: H520) -> Result<(), EthcoreError>;
+
+	/// Signs of a majority attack in the recently imported blocks.
+	fn threat_status(&self) -> ThreatStatus;
+
+	/// Switch to the branch containing the given block which was held back for reorganizing the
+	/// chain deeper than allowed, and resume the block import paused meanwhile. Fails once the
+	/// state of the branch is pruned.
+	fn accept_reorg(&self, hash: H256) -> Result<(), String>;
+
+	/// Run the background rewrites of the database now, even outside of the configured
+	/// rewrite window, until they catch up.
+	fn rewrite_database(&self) -> Result<(), String>;
+
+	/// Stop importing blocks once the import in progress finishes, and write the buffered changes
+	/// to the database, so that the head stays put and the files are consistent. The import resumes
+	/// with `resume_import` or after `timeout`; pausing again moves the deadline.
+	fn pause_import(&self, timeout: Duration) -> Result<(), String>;
+
+	/// Resume the paused block import, keeping the current head if a reorganization is held back.
+	/// Returns false if it wasn't paused.
+	fn resume_import(&self) -> bool;
+
 	/// Disable the client from importing blocks. This cannot be undone in this session and indicates
 	/// that a subsystem has reason to believe this executable incapable of syncing the chain.
 	fn disable(&self);
@@ -395,6 +541,11 @@ pub trait BlockChainClient:
 	/// Returns information about pruning/data availability.
 	fn pruning_info(&self) -> PruningInfo;
 
+	/// Returns the validator set that governs the given block, along with the proof of the epoch
+	/// transition that introduced it. `None` if the block is unknown or the engine has no
+	/// validator set.
+	fn validator_set_at(&self, id: BlockId) -> Option<EpochValidators>;
+
 	/// Returns a transaction signed with the key configured in the engine signer.
 	fn create_transaction(&self, tx_request: TransactionRequest) -> Result<SignedTransaction, transaction::Error>;
 
@@ -462,10 +613,20 @@ pub trait BlockChainReset {
 	/// reset to best_block - n
 	fn reset(&self, num: u32) -> Result<(), String>;
 
+	/// reset to best_block - n while the client is running, reloading the chain and dropping
+	/// the pending block
+	fn rewind(&self, num: u32) -> Result<(), String>;
+
 	/// Number of eras kept in a journal before they are pruned
 	fn pruning_history(&self) -> u64;
 }
 
+/// Moves the head of the chain when it is chosen externally, e.g. by a proof-of-stake consensus client.
+pub trait ForkChoiceClient {
+	/// Make an imported block the best block, optionally marking one of its ancestors as finalized.
+	fn set_head(&self, head: H256, finalized: Option<H256>) -> Result<(), String>;
+}
+
 
 /// Provides `latest_schedule` method
 pub trait ScheduleInfo {
@@ -507,6 +668,15 @@ pub trait ProvingBlockChainClient: BlockChainClient {
 	/// to reproduce it.
 	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<(Bytes, Vec<DBValue>)>;
 
+	/// Prove inclusion of a canonical transaction and its receipt in the block's
+	/// transactions and receipts tries.
+	fn prove_receipt(&self, hash: H256) -> Option<ReceiptProof>;
+
+	/// Prove that the canonical block `number` is an ancestor of (or is) the canonical block `head`
+	/// against the accumulator of the block hashes up to `head`. `None` while the accumulator
+	/// is catching up with a long chain.
+	fn prove_ancestry(&self, number: BlockNumber, head: BlockNumber) -> Option<AncestryProof>;
+
 	/// Get an epoch change signal by block hash.
 	fn epoch_signal(&self, hash: H256) -> Option<Vec<u8>>;
 }
@@ -549,6 +719,12 @@ pub trait ChainNotify: Send + Sync {
 	fn transactions_received(&self, _txs: &[UnverifiedTransaction], _peer_id: usize) {
 		// does nothing by default
 	}
+
+	/// fires when a branch is kept out of the canon chain because switching to it
+	/// would retract more blocks than allowed
+	fn reorg_held(&self, _head: &H256, _number: BlockNumber, _depth: u64) {
+		// does nothing by default
+	}
 }
 
 /// Provides a method for importing/exporting blocks
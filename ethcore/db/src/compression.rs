@@ -0,0 +1,298 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dictionary compression of chain data.
+//!
+//! Block bodies and receipts are compressed with zstd, using a dictionary trained
+//! separately for each kind of data. Compressed values are prefixed with a marker byte
+//! which can never start a plain RLP list, so values written before compression was
+//! enabled are still read transparently.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use kvdb::{DBTransaction, KeyValueDB};
+use parking_lot::RwLock;
+use zstd_safe::{CDict, DDict};
+
+use crate::db::COL_NODE_INFO;
+
+/// Marker prefixed to every compressed value. Bodies and receipts are RLP lists, so their
+/// encoding always starts with a byte `>= 0xc0`.
+const COMPRESSED_MARKER: u8 = 0x01;
+/// Dictionary id used for values compressed without a dictionary.
+const NO_DICTIONARY: u8 = 0;
+/// Prefix of the keys under which trained dictionaries are stored.
+const DICTIONARY_KEY_PREFIX: &[u8] = b"zstd-dict-";
+/// Default zstd compression level.
+pub const DEFAULT_LEVEL: i32 = 3;
+/// Maximal size of a trained dictionary in bytes.
+pub const MAX_DICTIONARY_SIZE: usize = 112 * 1024;
+
+/// Kind of compressed chain data. Every kind has its own dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressedKind {
+	/// Block bodies.
+	Bodies,
+	/// Block receipts.
+	Receipts,
+}
+
+impl CompressedKind {
+	fn tag(&self) -> u8 {
+		match *self {
+			CompressedKind::Bodies => b'b',
+			CompressedKind::Receipts => b'r',
+		}
+	}
+
+	fn from_tag(tag: u8) -> Option<Self> {
+		match tag {
+			b'b' => Some(CompressedKind::Bodies),
+			b'r' => Some(CompressedKind::Receipts),
+			_ => None,
+		}
+	}
+
+	fn dictionary_key(&self, id: u8) -> Vec<u8> {
+		let mut key = DICTIONARY_KEY_PREFIX.to_vec();
+		key.push(self.tag());
+		key.push(id);
+		key
+	}
+}
+
+/// Digested dictionary. Both halves hold their own copy of the trained bytes.
+struct Dictionary {
+	encoder: CDict<'static>,
+	decoder: DDict<'static>,
+}
+
+/// Compressor of chain data columns.
+pub struct ChainCompression {
+	enabled: bool,
+	level: i32,
+	dictionaries: RwLock<HashMap<(CompressedKind, u8), Arc<Dictionary>>>,
+	active: RwLock<HashMap<CompressedKind, u8>>,
+}
+
+impl ChainCompression {
+	/// Create a new compressor. When `enabled` is false values are written uncompressed,
+	/// but compressed values are still read.
+	pub fn new(enabled: bool, level: i32) -> Self {
+		ChainCompression {
+			enabled,
+			level,
+			dictionaries: RwLock::new(HashMap::new()),
+			active: RwLock::new(HashMap::new()),
+		}
+	}
+
+	/// Load all the dictionaries stored in the database. The most recently trained
+	/// dictionary of each kind becomes the active one.
+	pub fn load(&self, db: &dyn KeyValueDB) {
+		let mut dictionaries = self.dictionaries.write();
+		let mut active = self.active.write();
+		for (key, value) in db.iter_from_prefix(COL_NODE_INFO, DICTIONARY_KEY_PREFIX) {
+			if !key.starts_with(DICTIONARY_KEY_PREFIX) || key.len() != DICTIONARY_KEY_PREFIX.len() + 2 {
+				continue;
+			}
+			let kind = match CompressedKind::from_tag(key[DICTIONARY_KEY_PREFIX.len()]) {
+				Some(kind) => kind,
+				None => continue,
+			};
+			let id = key[DICTIONARY_KEY_PREFIX.len() + 1];
+			dictionaries.insert((kind, id), Arc::new(self.prepare(&value)));
+			let current = active.entry(kind).or_insert(id);
+			if *current < id {
+				*current = id;
+			}
+		}
+	}
+
+	/// Returns true if newly written values are compressed.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+
+	/// Returns id of the dictionary currently used to compress the given kind of data.
+	pub fn active_dictionary(&self, kind: CompressedKind) -> Option<u8> {
+		self.active.read().get(&kind).cloned()
+	}
+
+	/// Returns true if a value should be rewritten to match the current compression settings.
+	pub fn needs_recompression(&self, kind: CompressedKind, value: &[u8]) -> bool {
+		if !self.enabled {
+			return false;
+		}
+
+		let active = self.active_dictionary(kind).unwrap_or(NO_DICTIONARY);
+		match value {
+			[COMPRESSED_MARKER, id, ..] => *id != active,
+			_ => true,
+		}
+	}
+
+	/// Compress the value. Returns the value unchanged if compression is disabled.
+	pub fn compress(&self, kind: CompressedKind, raw: &[u8]) -> Vec<u8> {
+		if !self.enabled {
+			return raw.to_vec();
+		}
+
+		let id = self.active_dictionary(kind).unwrap_or(NO_DICTIONARY);
+		let mut out = vec![COMPRESSED_MARKER, id];
+		let result = match self.dictionary(kind, id) {
+			Some(dictionary) => {
+				let mut buffer = vec![0u8; zstd_safe::compress_bound(raw.len())];
+				zstd_safe::compress_using_cdict(&mut zstd_safe::create_cctx(), &mut buffer, raw, &dictionary.encoder)
+					.map(|written| out.extend_from_slice(&buffer[..written]))
+					.map_err(zstd_error)
+			},
+			None => zstd::stream::copy_encode(raw, &mut out, self.level),
+		};
+
+		result.expect("writing to an in-memory buffer never fails; qed");
+		out
+	}
+
+	/// Decompress the value. Values which were not compressed are returned as they are.
+	pub fn decompress<'a>(&self, kind: CompressedKind, value: &'a [u8]) -> Cow<'a, [u8]> {
+		match value {
+			[COMPRESSED_MARKER, id, payload @ ..] => {
+				let decoded = self.decode(kind, *id, payload)
+					.unwrap_or_else(|e| panic!("Failed to decompress {:?} stored with dictionary {}: {}. Database corrupt?", kind, id, e));
+				Cow::Owned(decoded)
+			},
+			_ => Cow::Borrowed(value),
+		}
+	}
+
+	/// Train a new dictionary from the given samples, make it the active one
+	/// and write it to the database. Returns the id of the new dictionary.
+	pub fn train<S: AsRef<[u8]>>(
+		&self,
+		kind: CompressedKind,
+		samples: &[S],
+		batch: &mut DBTransaction,
+	) -> io::Result<u8> {
+		let raw = zstd::dict::from_samples(samples, MAX_DICTIONARY_SIZE)?;
+		let id = match self.active_dictionary(kind) {
+			Some(id) => id.checked_add(1)
+				.ok_or_else(|| io::Error::new(io::ErrorKind::Other, "dictionary ids exhausted"))?,
+			None => NO_DICTIONARY + 1,
+		};
+
+		batch.put(COL_NODE_INFO, &kind.dictionary_key(id), &raw);
+		self.dictionaries.write().insert((kind, id), Arc::new(self.prepare(&raw)));
+		self.active.write().insert(kind, id);
+		Ok(id)
+	}
+
+	fn prepare(&self, raw: &[u8]) -> Dictionary {
+		Dictionary {
+			encoder: zstd_safe::create_cdict(raw, self.level),
+			decoder: zstd_safe::create_ddict(raw),
+		}
+	}
+
+	fn dictionary(&self, kind: CompressedKind, id: u8) -> Option<Arc<Dictionary>> {
+		match id {
+			NO_DICTIONARY => None,
+			id => self.dictionaries.read().get(&(kind, id)).cloned(),
+		}
+	}
+
+	fn decode(&self, kind: CompressedKind, id: u8, payload: &[u8]) -> io::Result<Vec<u8>> {
+		let mut out = Vec::new();
+		match id {
+			NO_DICTIONARY => zstd::stream::copy_decode(payload, &mut out)?,
+			id => {
+				let dictionary = self.dictionary(kind, id)
+					.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unknown dictionary"))?;
+				// frames compressed with a digested dictionary always record their content size
+				let size = match zstd_safe::get_frame_content_size(payload) {
+					zstd_safe::CONTENTSIZE_UNKNOWN | zstd_safe::CONTENTSIZE_ERROR =>
+						return Err(io::Error::new(io::ErrorKind::InvalidData, "missing frame content size")),
+					size => size as usize,
+				};
+				out.resize(size, 0);
+				let written = zstd_safe::decompress_using_ddict(&mut zstd_safe::create_dctx(), &mut out, payload, &dictionary.decoder)
+					.map_err(zstd_error)?;
+				out.truncate(written);
+			},
+		}
+		Ok(out)
+	}
+}
+
+fn zstd_error(code: usize) -> io::Error {
+	io::Error::new(io::ErrorKind::Other, zstd_safe::get_error_name(code))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample(i: u8) -> Vec<u8> {
+		let mut s = vec![0xf8, 0x80];
+		s.extend((0..128u8).map(|x| x.wrapping_mul(i) % 7));
+		s
+	}
+
+	#[test]
+	fn disabled_compression_keeps_values() {
+		let compression = ChainCompression::new(false, DEFAULT_LEVEL);
+		let value = sample(3);
+		assert_eq!(compression.compress(CompressedKind::Bodies, &value), value);
+		assert!(!compression.needs_recompression(CompressedKind::Bodies, &value));
+	}
+
+	#[test]
+	fn reads_uncompressed_values() {
+		let compression = ChainCompression::new(true, DEFAULT_LEVEL);
+		let value = sample(5);
+		assert_eq!(&*compression.decompress(CompressedKind::Receipts, &value), &value[..]);
+		assert!(compression.needs_recompression(CompressedKind::Receipts, &value));
+	}
+
+	#[test]
+	fn roundtrip_with_trained_dictionary() {
+		let db = kvdb_memorydb::create(crate::NUM_COLUMNS);
+		let compression = ChainCompression::new(true, DEFAULT_LEVEL);
+		let plain = compression.compress(CompressedKind::Bodies, &sample(1));
+		assert_eq!(&*compression.decompress(CompressedKind::Bodies, &plain), &sample(1)[..]);
+
+		let samples = (0..255u8).map(sample).collect::<Vec<_>>();
+		let mut batch = DBTransaction::new();
+		let id = compression.train(CompressedKind::Bodies, &samples, &mut batch).unwrap();
+		db.write(batch).unwrap();
+		assert_eq!(id, 1);
+
+		let compressed = compression.compress(CompressedKind::Bodies, &sample(2));
+		assert!(compression.needs_recompression(CompressedKind::Bodies, &plain));
+		assert!(!compression.needs_recompression(CompressedKind::Bodies, &compressed));
+
+		// a fresh instance loads the dictionary from the database.
+		let reloaded = ChainCompression::new(true, DEFAULT_LEVEL);
+		reloaded.load(&db);
+		assert_eq!(reloaded.active_dictionary(CompressedKind::Bodies), Some(1));
+		assert_eq!(reloaded.active_dictionary(CompressedKind::Receipts), None);
+		assert_eq!(&*reloaded.decompress(CompressedKind::Bodies, &compressed), &sample(2)[..]);
+		assert_eq!(&*reloaded.decompress(CompressedKind::Bodies, &plain), &sample(1)[..]);
+	}
+}
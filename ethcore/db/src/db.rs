@@ -44,8 +44,12 @@ pub const COL_NODE_INFO: u32 = 6;
 pub const COL_LIGHT_CHAIN: u32 = 7;
 /// Column for the private transactions state.
 pub const COL_PRIVATE_TRANSACTIONS_STATE: u32 = 8;
+/// Column for the copies of the state at checkpoint blocks.
+pub const COL_STATE_CHECKPOINTS: u32 = 9;
+/// Column for the most recent bad blocks.
+pub const COL_BAD_BLOCKS: u32 = 10;
 /// Number of columns in DB
-pub const NUM_COLUMNS: u32 = 9;
+pub const NUM_COLUMNS: u32 = 11;
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
@@ -25,5 +25,6 @@ mod db;
 
 pub mod keys;
 pub mod cache_manager;
+pub mod compression;
 
 pub use self::db::*;
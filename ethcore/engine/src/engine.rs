@@ -25,7 +25,8 @@ use common_types::{
 	ancestry_action::AncestryAction,
 	header::{Header, ExtendedHeader},
 	engines::{
-		Seal, SealingState, Headers, PendingTransitionStore,
+		ForkChoice, Seal, SealingState, Headers, PendingTransitionStore,
+		epoch::EpochValidators,
 		params::CommonParams,
 		machine as machine_types,
 	},
@@ -33,6 +34,7 @@ use common_types::{
 	receipt::Receipt,
 	snapshot::Snapshotting,
 	transaction::{self, SignedTransaction, UnverifiedTransaction},
+	validator_status::ValidatorStatus,
 };
 use client_traits::EngineClient;
 
@@ -44,6 +46,7 @@ use machine::{
 };
 use vm::{EnvInfo, Schedule, ActionType, ActionValue};
 
+use crate::sealing_journal::SealingJournal;
 use crate::signer::EngineSigner;
 
 /// A system-calling closure. Enacts calls on a block's state from the system address.
@@ -131,6 +134,19 @@ impl<'a> ConstructedVerifier<'a> {
 	}
 }
 
+/// Decode the validator set proven by an epoch transition stored in the chain.
+///
+/// Stored transitions have been confirmed on import, so the finality proof is not checked again.
+pub fn epoch_validators(engine: &dyn Engine, header: &Header, proof: Vec<u8>) -> Option<EpochValidators> {
+	let validators = engine.epoch_verifier(header, &proof).known_confirmed().ok()?.validators()?;
+	Some(EpochValidators {
+		transition_hash: header.hash(),
+		transition_number: header.number(),
+		proof,
+		validators,
+	})
+}
+
 /// Results of a query of whether an epoch change occurred at the given block.
 pub enum EpochChange {
 	/// Cannot determine until more data is passed.
@@ -141,6 +157,58 @@ pub enum EpochChange {
 	Yes(Proof),
 }
 
+/// Manual control over block production, offered by development engines.
+pub trait DevMining: Send + Sync {
+	/// Whether a block is sealed as soon as there are transactions to include.
+	fn automine(&self) -> bool;
+	/// Enable or disable sealing on every transaction.
+	fn set_automine(&self, automine: bool);
+	/// Seal the next block even if it has no transactions.
+	fn force_seal(&self);
+	/// Whether the next block is sealed even if it has no transactions.
+	fn is_seal_forced(&self) -> bool;
+	/// Move the timestamp of the next blocks forward by `seconds`. Returns the total offset in seconds,
+	/// or `None` if it would overflow.
+	fn increase_time(&self, seconds: u64) -> Option<u64>;
+	/// The offset in seconds added to the timestamp of the next blocks.
+	fn time_offset(&self) -> u64;
+	/// Replace the offset added to the timestamp of the next blocks, e.g. when reverting to a snapshot.
+	fn set_time_offset(&self, offset: u64);
+	/// Allow sealing again on top of `best_block`, after the chain was rewound to it.
+	fn rewound(&self, best_block: BlockNumber);
+}
+
+/// Signer set voting, offered by engines whose signers vote on adding and removing signers.
+pub trait SignerVoting: Send + Sync {
+	/// The signers authorized after the block with the given header.
+	fn signers(&self, header: &Header) -> Result<Vec<Address>, Error>;
+	/// The proposals the local signer votes on, `true` to authorize the address and `false` to remove it.
+	fn proposals(&self) -> BTreeMap<Address, bool>;
+	/// Start voting on authorizing or removing the given address in the blocks sealed locally.
+	fn propose(&self, address: Address, authorize: bool);
+	/// Stop voting on the given address. Returns false if there was no such proposal.
+	fn discard(&self, address: &Address) -> bool;
+}
+
+/// Updates of consensus parameters while the node is running, offered by engines whose parameters
+/// may change without a restart.
+pub trait ParamsReload: Send + Sync {
+	/// Verify the signature over an update of the engine parameters and apply it. Only parameters
+	/// taking effect after `best_block` may change.
+	fn reload_params(&self, update: &[u8], signature: &Signature, best_block: BlockNumber) -> Result<(), Error>;
+
+	/// Apply an update accepted by `reload_params` before the node was restarted again. The checks
+	/// against the chain and the clock are skipped, since they passed when it was accepted.
+	fn restore_params(&self, update: &[u8], signature: &Signature) -> Result<(), Error>;
+}
+
+/// Tracking of the steps the local signer is expected to seal, offered by engines with a fixed
+/// sealing schedule.
+pub trait ValidatorDuties: Send + Sync {
+	/// The sealing duties of the local signer since the node started.
+	fn validator_status(&self) -> ValidatorStatus;
+}
+
 /// A consensus mechanism for the chain. Generally either proof-of-work or proof-of-stake-based.
 /// Provides hooks into each of the major parts of block import.
 pub trait Engine: Sync + Send {
@@ -311,6 +379,14 @@ pub trait Engine: Sync + Send {
 	/// Register a component which signs consensus messages.
 	fn set_signer(&self, _signer: Option<Box<dyn EngineSigner>>) {}
 
+	/// Whether the engine journals the blocks it signs, see `set_sealing_journal`.
+	fn uses_sealing_journal(&self) -> bool { false }
+
+	/// Journal the blocks signed by the local signer, so that no conflicting block is ever signed
+	/// in the same slot. Only engines which sign blocks use it. Set once, for all the signers set
+	/// later on.
+	fn set_sealing_journal(&self, _journal: Arc<SealingJournal>) {}
+
 	/// Sign using the EngineSigner, to be used for consensus tx signing.
 	fn sign(&self, _hash: H256) -> Result<Signature, Error> { unimplemented!() }
 
@@ -320,6 +396,23 @@ pub trait Engine: Sync + Send {
 	/// Trigger next step of the consensus engine.
 	fn step(&self) {}
 
+	/// Manual mining controls, if the engine is a development engine.
+	fn dev_mining(&self) -> Option<&dyn DevMining> { None }
+
+	/// Signer set voting, if the engine's signers vote on the signer set.
+	fn signer_voting(&self) -> Option<&dyn SignerVoting> { None }
+
+	/// Runtime parameter updates, if the engine's parameters may change without a restart.
+	/// Blocks queued for import must be verified again after an update.
+	fn params_reload(&self) -> Option<&dyn ParamsReload> { None }
+
+	/// Mix hash and difficulty reached by a proof-of-work nonce for the given block number and pow hash,
+	/// if the engine is proof-of-work. Used to check mining pool shares below the block difficulty.
+	fn proof_of_work(&self, _block_number: BlockNumber, _pow_hash: &H256, _nonce: u64) -> Option<(H256, U256)> { None }
+
+	/// Sealing duties of the local signer, if the engine assigns steps to validators.
+	fn validator_duties(&self) -> Option<&dyn ValidatorDuties> { None }
+
 	/// Snapshot mode for the engine: Unsupported, PoW or PoA
 	fn snapshot_mode(&self) -> Snapshotting { Snapshotting::Unsupported }
 
@@ -353,6 +446,23 @@ pub trait Engine: Sync + Send {
 		None
 	}
 
+	/// Overrides the total difficulty fork choice rule for a newly imported block. Engines
+	/// whose canonical head is chosen externally return `Some(ForkChoice::Old)` here.
+	fn fork_choice(&self, _header: &Header) -> Option<ForkChoice> {
+		None
+	}
+
+	/// The EIP-4399 `prevRandao` value of a proof-of-stake block, which the `DIFFICULTY` opcode returns
+	/// instead of the difficulty.
+	fn prev_randao(&self, _header: &Header) -> Option<H256> {
+		None
+	}
+
+	/// The EIP-1559 base fee per gas of the block, if it carries one.
+	fn base_fee(&self, _header: &Header) -> Option<U256> {
+		None
+	}
+
 	/// Get the general parameters of the chain.
 	fn params(&self) -> &CommonParams;
 
@@ -429,6 +539,11 @@ pub trait EpochVerifier: Send + Sync {
 	fn check_finality_proof(&self, _proof: &[u8]) -> Option<Vec<H256>> {
 		None
 	}
+
+	/// The validators allowed to seal blocks of this epoch, if the engine has a validator set.
+	fn validators(&self) -> Option<Vec<Address>> {
+		None
+	}
 }
 
 /// Special "no-op" verifier for stateless, epoch-less engines.
@@ -17,6 +17,7 @@
 //! This crate defines the Engine trait and related types.
 
 mod engine;
+pub mod sealing_journal;
 pub mod signer;
 
 pub use crate::engine::{
@@ -24,12 +25,17 @@ pub use crate::engine::{
 	EpochVerifier,
 	StateDependentProof,
 	ConstructedVerifier,
+	DevMining,
 	EpochChange,
+	ParamsReload,
 	Proof,
+	SignerVoting,
 	SystemCall,
 	SystemOrCodeCall,
 	SystemOrCodeCallKind,
+	ValidatorDuties,
 	default_system_or_code_call,
+	epoch_validators,
 };
 
 #[cfg(any(test, feature = "test-helpers"))]
@@ -0,0 +1,268 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Journal of the blocks sealed by the local signer.
+//!
+//! Before a block is signed, its slot (the step or the block number, depending on the engine) and
+//! the signed hash are appended to the journal and synced to disk. A block conflicting with a
+//! recorded one in the same slot is never signed, even after a restart or after the database was
+//! rolled back. Only the most recent slots of each signer are kept, and older slots are refused.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use common_types::errors::EngineError;
+use ethereum_types::{Address, H256};
+use parking_lot::Mutex;
+
+/// Number of most recent slots of each signer kept in the journal.
+const RETAINED_SLOTS: u64 = 16_384;
+/// Minimal number of appended records before the journal is compacted.
+const MIN_RECORDS_BEFORE_COMPACTION: usize = 1_024;
+
+struct State {
+	file: Option<File>,
+	slots: HashMap<Address, BTreeMap<u64, H256>>,
+	records: usize,
+}
+
+impl State {
+	fn insert(&mut self, signer: Address, slot: u64, hash: H256) {
+		let slots = self.slots.entry(signer).or_default();
+		slots.insert(slot, hash);
+		let highest = *slots.keys().next_back().expect("a slot was just inserted; qed");
+		*slots = slots.split_off(&highest.saturating_sub(RETAINED_SLOTS));
+	}
+
+	fn len(&self) -> usize {
+		self.slots.values().map(BTreeMap::len).sum()
+	}
+
+	fn append(&mut self, signer: &Address, slot: u64, hash: &H256) -> io::Result<()> {
+		if let Some(ref mut file) = self.file {
+			file.write_all(record(signer, slot, hash).as_bytes())?;
+			file.sync_data()?;
+		}
+		self.records += 1;
+		Ok(())
+	}
+}
+
+fn record(signer: &Address, slot: u64, hash: &H256) -> String {
+	format!("{:x} {} {:x}\n", signer, slot, hash)
+}
+
+fn parse_record(line: &str) -> Option<(Address, u64, H256)> {
+	let mut fields = line.split_whitespace();
+	let signer = Address::from_str(fields.next()?).ok()?;
+	let slot = fields.next()?.parse().ok()?;
+	let hash = H256::from_str(fields.next()?).ok()?;
+	match fields.next() {
+		Some(_) => None,
+		None => Some((signer, slot, hash)),
+	}
+}
+
+/// Slots sealed by the local signers, persisted in a file.
+pub struct SealingJournal {
+	path: Option<PathBuf>,
+	state: Mutex<State>,
+}
+
+impl SealingJournal {
+	/// Open the journal at given path, replaying and compacting existing records.
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref().to_owned();
+		let mut state = State { file: None, slots: HashMap::new(), records: 0 };
+
+		if path.exists() {
+			let lines = BufReader::new(File::open(&path)?).lines().collect::<Result<Vec<_>, _>>()?;
+			for (i, line) in lines.iter().enumerate() {
+				if line.trim().is_empty() {
+					continue;
+				}
+				match parse_record(line) {
+					Some((signer, slot, hash)) => state.insert(signer, slot, hash),
+					// the last record might have been only partially written before a crash.
+					None if i + 1 == lines.len() => {},
+					None => return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						format!("invalid sealing journal record at line {}: {}", i + 1, line),
+					)),
+				}
+			}
+		}
+
+		state.file = Some(OpenOptions::new().create(true).append(true).open(&path)?);
+		let journal = SealingJournal { path: Some(path), state: Mutex::new(state) };
+		journal.compact()?;
+
+		Ok(journal)
+	}
+
+	/// A journal which is not persisted.
+	pub fn in_memory() -> Self {
+		SealingJournal {
+			path: None,
+			state: Mutex::new(State { file: None, slots: HashMap::new(), records: 0 }),
+		}
+	}
+
+	/// Record that `signer` is about to sign `hash` in `slot`. Fails without recording anything if
+	/// the signer signed a different hash in the same slot, or if the slot is too old to tell.
+	/// Signing the same hash in the same slot again is allowed.
+	pub fn record(&self, signer: Address, slot: u64, hash: H256) -> Result<(), EngineError> {
+		{
+			let mut state = self.state.lock();
+			if let Some(slots) = state.slots.get(&signer) {
+				match slots.get(&slot) {
+					Some(signed) if *signed == hash => return Ok(()),
+					Some(signed) => return Err(EngineError::SealRefused(
+						format!("{} already signed {} in slot {}", signer, signed, slot)
+					)),
+					None => {},
+				}
+				let highest = *slots.keys().next_back().expect("signers are only present with a slot; qed");
+				if slot.saturating_add(RETAINED_SLOTS) < highest {
+					return Err(EngineError::SealRefused(
+						format!("slot {} is older than the slots journaled for {}", slot, signer)
+					));
+				}
+			}
+
+			state.append(&signer, slot, &hash)
+				.map_err(|e| EngineError::SealRefused(format!("could not write the sealing journal: {}", e)))?;
+			state.insert(signer, slot, hash);
+		}
+
+		self.maybe_compact()
+			.map_err(|e| EngineError::SealRefused(format!("could not compact the sealing journal: {}", e)))
+	}
+
+	/// The hash `signer` signed in `slot`, if it's journaled.
+	pub fn signed(&self, signer: &Address, slot: u64) -> Option<H256> {
+		self.state.lock().slots.get(signer)?.get(&slot).cloned()
+	}
+
+	/// Rewrite the journal file with only the retained slots.
+	pub fn compact(&self) -> io::Result<()> {
+		let path = match self.path {
+			Some(ref path) => path,
+			None => return Ok(()),
+		};
+		let mut state = self.state.lock();
+		let tmp_path = path.with_extension("tmp");
+
+		{
+			let mut tmp = File::create(&tmp_path)?;
+			for (signer, slots) in &state.slots {
+				for (slot, hash) in slots {
+					tmp.write_all(record(signer, *slot, hash).as_bytes())?;
+				}
+			}
+			tmp.sync_all()?;
+		}
+
+		fs::rename(&tmp_path, path)?;
+		state.file = Some(OpenOptions::new().append(true).open(path)?);
+		state.records = state.len();
+
+		Ok(())
+	}
+
+	fn maybe_compact(&self) -> io::Result<()> {
+		let should_compact = {
+			let state = self.state.lock();
+			state.records >= MIN_RECORDS_BEFORE_COMPACTION && state.records > 2 * state.len()
+		};
+
+		if should_compact {
+			self.compact()
+		} else {
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use common_types::errors::EngineError;
+	use ethereum_types::{Address, H256};
+	use tempfile::TempDir;
+
+	use super::{SealingJournal, RETAINED_SLOTS};
+
+	#[test]
+	fn refuses_conflicting_seals_after_reopening() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("sealing.journal");
+		let (signer, other) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+		let (hash1, hash2) = (H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+
+		{
+			let journal = SealingJournal::open(&path).unwrap();
+			journal.record(signer, 10, hash1).unwrap();
+			journal.record(signer, 11, hash2).unwrap();
+		}
+
+		let journal = SealingJournal::open(&path).unwrap();
+		assert_eq!(journal.signed(&signer, 10), Some(hash1));
+		// the same block may be sealed again.
+		assert!(journal.record(signer, 10, hash1).is_ok());
+		match journal.record(signer, 10, hash2) {
+			Err(EngineError::SealRefused(_)) => {},
+			other => panic!("conflicting seal accepted: {:?}", other),
+		}
+		assert_eq!(journal.signed(&signer, 10), Some(hash1));
+		// other signers have their own slots.
+		assert!(journal.record(other, 10, hash2).is_ok());
+	}
+
+	#[test]
+	fn refuses_slots_older_than_the_window() {
+		let journal = SealingJournal::in_memory();
+		let signer = Address::from_low_u64_be(1);
+
+		journal.record(signer, 5, H256::from_low_u64_be(1)).unwrap();
+		journal.record(signer, RETAINED_SLOTS + 10, H256::from_low_u64_be(2)).unwrap();
+		assert_eq!(journal.signed(&signer, 5), None);
+		assert!(journal.record(signer, 9, H256::from_low_u64_be(3)).is_err());
+		assert!(journal.record(signer, 10, H256::from_low_u64_be(3)).is_ok());
+	}
+
+	#[test]
+	fn skips_truncated_last_record() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("sealing.journal");
+		let signer = Address::from_low_u64_be(1);
+
+		SealingJournal::open(&path).unwrap().record(signer, 10, H256::from_low_u64_be(1)).unwrap();
+		{
+			let mut file = ::std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+			file.write_all(b"0000000000000000000000000000000000000001 11 00").unwrap();
+		}
+
+		let journal = SealingJournal::open(&path).unwrap();
+		assert_eq!(journal.signed(&signer, 10), Some(H256::from_low_u64_be(1)));
+		assert_eq!(journal.signed(&signer, 11), None);
+	}
+}
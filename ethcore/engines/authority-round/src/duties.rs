@@ -0,0 +1,168 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Tracking of the steps the local validator is expected to seal.
+
+use std::collections::VecDeque;
+
+use common_types::validator_status::{DutySlot, SlotOutcome};
+use parking_lot::Mutex;
+
+/// Number of expected steps kept for reporting.
+const RECENT_SLOTS: usize = 64;
+
+/// Outcomes of the steps assigned to the local signer since it was set.
+#[derive(Default)]
+pub struct DutyTracker {
+	inner: Mutex<Duties>,
+}
+
+/// Totals of the expected steps, by outcome.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DutyCounts {
+	/// Steps the local signer was expected to seal.
+	pub expected: u64,
+	/// Steps sealed in time, including empty steps.
+	pub sealed: u64,
+	/// Steps sealed late.
+	pub late: u64,
+	/// Steps which passed without a seal.
+	pub missed: u64,
+}
+
+#[derive(Default)]
+struct Duties {
+	slots: VecDeque<DutySlot>,
+	counts: DutyCounts,
+}
+
+impl Duties {
+	fn assign(&mut self, step: u64) {
+		if self.slots.back().map_or(false, |slot| slot.step >= step) {
+			return;
+		}
+		self.slots.push_back(DutySlot { step, outcome: SlotOutcome::Pending });
+		self.counts.expected += 1;
+		while self.slots.len() > RECENT_SLOTS {
+			self.slots.pop_front();
+		}
+	}
+}
+
+impl DutyTracker {
+	/// Register a step the local signer is expected to seal.
+	pub fn assign(&self, step: u64) {
+		self.inner.lock().assign(step);
+	}
+
+	/// Record that the local signer sealed the given step. Steps which were sealed already keep
+	/// their first outcome.
+	pub fn seal(&self, step: u64, outcome: SlotOutcome) {
+		let mut duties = self.inner.lock();
+		duties.assign(step);
+		let Duties { slots, counts } = &mut *duties;
+		if let Some(slot) = slots.iter_mut().rev().find(|slot| slot.step == step) {
+			if slot.outcome != SlotOutcome::Pending {
+				return;
+			}
+			slot.outcome = outcome;
+			match outcome {
+				SlotOutcome::Sealed | SlotOutcome::EmptyStep => counts.sealed += 1,
+				SlotOutcome::Late(_) => counts.late += 1,
+				SlotOutcome::Missed => counts.missed += 1,
+				SlotOutcome::Pending => {},
+			}
+		}
+	}
+
+	/// Mark the unsealed steps before `step` as missed. Returns the newly missed steps.
+	pub fn close_before(&self, step: u64) -> Vec<u64> {
+		let mut duties = self.inner.lock();
+		let Duties { slots, counts } = &mut *duties;
+		let mut missed = Vec::new();
+		for slot in slots.iter_mut().take_while(|slot| slot.step < step) {
+			if slot.outcome == SlotOutcome::Pending {
+				slot.outcome = SlotOutcome::Missed;
+				counts.missed += 1;
+				missed.push(slot.step);
+			}
+		}
+		missed
+	}
+
+	/// Forget all steps, e.g. after the local signer changed.
+	pub fn reset(&self) {
+		*self.inner.lock() = Duties::default();
+	}
+
+	/// The totals of the expected steps and the most recent ones, oldest first.
+	pub fn snapshot(&self) -> (DutyCounts, Vec<DutySlot>) {
+		let duties = self.inner.lock();
+		(duties.counts, duties.slots.iter().cloned().collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common_types::validator_status::{DutySlot, SlotOutcome};
+	use super::{DutyCounts, DutyTracker, RECENT_SLOTS};
+
+	#[test]
+	fn tracks_outcomes_of_expected_steps() {
+		let tracker = DutyTracker::default();
+		tracker.assign(3);
+		tracker.seal(3, SlotOutcome::Sealed);
+		tracker.assign(6);
+		tracker.seal(9, SlotOutcome::Late(4));
+		tracker.assign(12);
+		tracker.seal(12, SlotOutcome::EmptyStep);
+		// a second seal in the same step doesn't change the outcome.
+		tracker.seal(12, SlotOutcome::Sealed);
+		tracker.assign(15);
+
+		assert_eq!(tracker.close_before(15), vec![6]);
+		assert_eq!(tracker.close_before(16), vec![15]);
+		assert!(tracker.close_before(16).is_empty());
+
+		let (counts, slots) = tracker.snapshot();
+		assert_eq!(counts, DutyCounts { expected: 5, sealed: 2, late: 1, missed: 2 });
+		assert_eq!(slots, vec![
+			DutySlot { step: 3, outcome: SlotOutcome::Sealed },
+			DutySlot { step: 6, outcome: SlotOutcome::Missed },
+			DutySlot { step: 9, outcome: SlotOutcome::Late(4) },
+			DutySlot { step: 12, outcome: SlotOutcome::EmptyStep },
+			DutySlot { step: 15, outcome: SlotOutcome::Missed },
+		]);
+	}
+
+	#[test]
+	fn keeps_only_recent_steps() {
+		let tracker = DutyTracker::default();
+		for step in 0..(RECENT_SLOTS as u64 + 10) {
+			tracker.assign(step);
+			// assigning the same step again is a no-op.
+			tracker.assign(step);
+		}
+		let (counts, slots) = tracker.snapshot();
+		assert_eq!(counts.expected, RECENT_SLOTS as u64 + 10);
+		assert_eq!(slots.len(), RECENT_SLOTS);
+		assert_eq!(slots[0].step, 10);
+
+		tracker.reset();
+		assert_eq!(tracker.snapshot(), (DutyCounts::default(), Vec::new()));
+	}
+}
@@ -51,6 +51,7 @@ use machine::{
 use keccak_hash::keccak;
 use log::{info, debug, error, trace, warn};
 use lru_cache::LruCache;
+use engine::sealing_journal::SealingJournal;
 use engine::signer::EngineSigner;
 use parity_crypto::publickey::Signature;
 use io::{IoContext, IoHandler, TimerToken, IoService};
@@ -78,14 +79,17 @@ use common_types::{
 	receipt::Receipt,
 	snapshot::Snapshotting,
 	transaction::SignedTransaction,
+	validator_status::{SlotOutcome, ValidatorStatus},
 };
 use unexpected::{Mismatch, OutOfBounds};
 use validator_set::{ValidatorSet, SimpleList, new_validator_set_posdao};
 
+mod duties;
 mod finality;
 mod randomness;
 pub(crate) mod util;
 
+use self::duties::DutyTracker;
 use self::finality::RollingFinality;
 
 /// `AuthorityRound` params.
@@ -131,6 +135,9 @@ pub struct AuthorityRoundParams {
 	/// If set, this is the block number at which the consensus engine switches from AuRa to AuRa
 	/// with POSDAO modifications.
 	pub posdao_transition: Option<BlockNumber>,
+	/// If set, updates of the validator set, step durations and transition blocks signed by this
+	/// account are applied while the node is running.
+	pub params_update_signer: Option<Address>,
 }
 
 const U16_MAX: usize = ::std::u16::MAX as usize;
@@ -214,6 +221,7 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 			randomness_contract_address,
 			block_gas_limit_contract_transitions,
 			posdao_transition: p.posdao_transition.map(Into::into),
+			params_update_signer: p.params_update_signer.map(Into::into),
 		}
 	}
 }
@@ -231,8 +239,8 @@ struct StepDurationInfo {
 struct Step {
 	calibrate: bool, // whether calibration is enabled.
 	inner: AtomicU64,
-	/// Planned durations of steps.
-	durations: Vec<StepDurationInfo>,
+	/// Planned durations of steps. Transitions after the current time may be added at runtime.
+	durations: RwLock<Vec<StepDurationInfo>>,
 }
 
 impl Step {
@@ -253,7 +261,7 @@ impl Step {
 	fn opt_duration_remaining(&self) -> Option<Duration> {
 		let next_step = self.load().checked_add(1)?;
 		let StepDurationInfo { transition_step, transition_timestamp, step_duration } =
-			self.durations.iter()
+			self.durations.read().iter()
 			.take_while(|info| info.transition_step < next_step)
 			.last()
 			.expect("durations cannot be empty")
@@ -263,6 +271,27 @@ impl Step {
 		Some(Duration::from_secs(next_time.saturating_sub(unix_now().as_secs())))
 	}
 
+	/// The duration of the given step, in seconds.
+	fn duration(&self, step: u64) -> u64 {
+		self.durations.read().iter().take_while(|info| info.transition_step <= step).last()
+			.expect("Duration map has at least a 0 entry.")
+			.step_duration
+	}
+
+	/// Time passed since the start of the given step. Returns `None` if the step hasn't started
+	/// yet or there was a counter overflow.
+	fn elapsed(&self, step: u64) -> Option<Duration> {
+		let StepDurationInfo { transition_step, transition_timestamp, step_duration } =
+			self.durations.read().iter()
+			.take_while(|info| info.transition_step <= step)
+			.last()
+			.expect("durations cannot be empty")
+			.clone();
+		let start = transition_timestamp
+			.checked_add(step.checked_sub(transition_step)?.checked_mul(step_duration)?)?;
+		unix_now().checked_sub(Duration::from_secs(start))
+	}
+
 	/// Increments the step number.
 	///
 	/// Panics if the new step number is `u64::MAX`.
@@ -290,7 +319,7 @@ impl Step {
 	fn opt_calibrate(&self) -> Option<()> {
 		let now = unix_now().as_secs();
 		let StepDurationInfo { transition_step, transition_timestamp, step_duration } =
-			self.durations.iter()
+			self.durations.read().iter()
 			.take_while(|info| info.transition_timestamp < now)
 			.last()
 			.expect("durations cannot be empty")
@@ -318,9 +347,7 @@ impl Step {
 			Err(None)
 		// wait a bit for blocks in near future
 		} else if given > current {
-			let d = self.durations.iter().take_while(|info| info.transition_step <= current).last()
-				.expect("Duration map has at least a 0 entry.")
-				.step_duration;
+			let d = self.duration(current);
 			Err(Some(OutOfBounds {
 				min: None,
 				max: Some(d * current),
@@ -589,17 +616,17 @@ pub struct AuthorityRound {
 	client: Arc<RwLock<Option<Weak<dyn EngineClient>>>>,
 	signer: RwLock<Option<Box<dyn EngineSigner>>>,
 	validators: Box<dyn ValidatorSet>,
-	validate_score_transition: u64,
-	validate_step_transition: u64,
+	validate_score_transition: AtomicU64,
+	validate_step_transition: AtomicU64,
 	empty_steps: Mutex<BTreeSet<EmptyStep>>,
 	epoch_manager: Mutex<EpochManager>,
 	immediate_transitions: bool,
 	block_reward: U256,
 	block_reward_contract_transitions: BTreeMap<u64, BlockRewardContract>,
-	maximum_uncle_count_transition: u64,
+	maximum_uncle_count_transition: AtomicU64,
 	maximum_uncle_count: usize,
 	empty_steps_transition: u64,
-	strict_empty_steps_transition: u64,
+	strict_empty_steps_transition: AtomicU64,
 	two_thirds_majority_transition: BlockNumber,
 	maximum_empty_steps: usize,
 	machine: Machine,
@@ -615,6 +642,12 @@ pub struct AuthorityRound {
 	/// modifications. For details about POSDAO, see the whitepaper:
 	/// https://www.xdaichain.com/for-validators/posdao-whitepaper
 	posdao_transition: Option<BlockNumber>,
+	/// The account whose signature authorizes parameter updates at runtime.
+	params_update_signer: Option<Address>,
+	/// Outcomes of the steps the local signer was expected to seal.
+	duties: DutyTracker,
+	/// Steps sealed by the local signer, if double-sign protection is enabled.
+	sealing_journal: RwLock<Option<Arc<SealingJournal>>>,
 }
 
 // header-chain validator.
@@ -680,6 +713,10 @@ impl engine::EpochVerifier for EpochVerifier {
 
 		if finalized.is_empty() { None } else { Some(finalized) }
 	}
+
+	fn validators(&self) -> Option<Vec<Address>> {
+		Some(self.subchain_validators.clone().into_inner())
+	}
 }
 
 fn header_seal_hash(header: &Header, empty_steps_rlp: Option<&[u8]>) -> H256 {
@@ -886,7 +923,7 @@ impl AuthorityRound {
 		let step = Step {
 			inner: AtomicU64::new(initial_step),
 			calibrate: our_params.start_step.is_none(),
-			durations,
+			durations: RwLock::new(durations),
 		};
 		step.calibrate();
 		let engine = Arc::new(
@@ -896,25 +933,28 @@ impl AuthorityRound {
 				client: Arc::new(RwLock::new(None)),
 				signer: RwLock::new(None),
 				validators: our_params.validators,
-				validate_score_transition: our_params.validate_score_transition,
-				validate_step_transition: our_params.validate_step_transition,
+				validate_score_transition: AtomicU64::new(our_params.validate_score_transition),
+				validate_step_transition: AtomicU64::new(our_params.validate_step_transition),
 				empty_steps: Default::default(),
 				epoch_manager: Mutex::new(EpochManager::blank(our_params.two_thirds_majority_transition)),
 				immediate_transitions: our_params.immediate_transitions,
 				block_reward: our_params.block_reward,
 				block_reward_contract_transitions: our_params.block_reward_contract_transitions,
-				maximum_uncle_count_transition: our_params.maximum_uncle_count_transition,
+				maximum_uncle_count_transition: AtomicU64::new(our_params.maximum_uncle_count_transition),
 				maximum_uncle_count: our_params.maximum_uncle_count,
 				empty_steps_transition: our_params.empty_steps_transition,
 				maximum_empty_steps: our_params.maximum_empty_steps,
 				two_thirds_majority_transition: our_params.two_thirds_majority_transition,
-				strict_empty_steps_transition: our_params.strict_empty_steps_transition,
+				strict_empty_steps_transition: AtomicU64::new(our_params.strict_empty_steps_transition),
 				machine,
 				received_step_hashes: RwLock::new(Default::default()),
 				randomness_contract_address: our_params.randomness_contract_address,
 				block_gas_limit_contract_transitions: our_params.block_gas_limit_contract_transitions,
 				gas_limit_override_cache: Mutex::new(LruCache::new(GAS_LIMIT_OVERRIDE_CACHE_CAPACITY)),
 				posdao_transition: our_params.posdao_transition,
+				params_update_signer: our_params.params_update_signer,
+				duties: Default::default(),
+				sealing_journal: RwLock::new(None),
 			});
 
 		// Do not initialize timeouts for tests.
@@ -922,6 +962,7 @@ impl AuthorityRound {
 			let handler = TransitionHandler {
 				step: engine.step.clone(),
 				client: engine.client.clone(),
+				engine: Arc::downgrade(&engine),
 			};
 			engine.transition_service.register_handler(Arc::new(handler))?;
 		}
@@ -1109,6 +1150,59 @@ impl AuthorityRound {
 		self.signer.read().as_ref().map(|s| s.address())
 	}
 
+	/// Finds the first of the `count` steps starting at `from` which the local signer `me` is
+	/// expected to seal on top of the best block.
+	fn local_slot(&self, me: &Address, from: u64, count: u64) -> Option<u64> {
+		let client = self.upgrade_client_or(None).ok()?;
+		let best = client.chain_info();
+		let mut header = Header::new();
+		header.set_parent_hash(best.best_block_hash);
+		header.set_number(best.best_block_number + 1);
+		let (validators, _) = self.epoch_set(&header).ok()?;
+		let count = cmp::min(count, validators.count(&best.best_block_hash) as u64);
+		(from..from.saturating_add(count))
+			.find(|step| is_step_proposer(&*validators, &best.best_block_hash, *step, me))
+	}
+
+	/// Marks the passed steps the local signer didn't seal as missed, and registers the current
+	/// step if the local signer is expected to seal it.
+	fn track_duties(&self) {
+		let me = match self.address() {
+			Some(me) => me,
+			None => return,
+		};
+		let step = self.step.inner.load();
+		for missed in self.duties.close_before(step) {
+			warn!(target: "engine", "Validator {} missed its sealing step {}.", me, missed);
+		}
+		if self.local_slot(&me, step, 1).is_some() {
+			self.duties.assign(step);
+		}
+	}
+
+	/// Journals the hash the local signer signs in the given step, failing if it signed another
+	/// block in the same step before.
+	fn journal_seal(&self, step: u64, seal_hash: H256) -> Result<(), EngineError> {
+		match (self.sealing_journal.read().as_ref(), self.address()) {
+			(Some(journal), Some(me)) => journal.record(me, step, seal_hash),
+			_ => Ok(()),
+		}
+	}
+
+	/// Records a block sealed by the local signer in the given step, warning if it comes late.
+	fn record_seal(&self, step: u64) {
+		let duration = Duration::from_secs(self.step.inner.duration(step));
+		let outcome = match self.step.inner.elapsed(step) {
+			// without calibration steps don't follow the clock.
+			Some(elapsed) if self.step.inner.calibrate && elapsed * 2 > duration => {
+				warn!(target: "engine", "Sealed step {} late, {} ms into the step.", step, AsMillis::as_millis(&elapsed));
+				SlotOutcome::Late(AsMillis::as_millis(&elapsed))
+			},
+			_ => SlotOutcome::Sealed,
+		};
+		self.duties.seal(step, outcome);
+	}
+
 	/// Make calls to the randomness contract.
 	fn run_randomness_phase(&self, block: &ExecutedBlock) -> Result<Vec<SignedTransaction>, Error> {
 		let contract_addr = match self.randomness_contract_address.range(..=block.header.number()).last() {
@@ -1208,6 +1302,7 @@ fn unix_now() -> Duration {
 struct TransitionHandler {
 	step: Arc<PermissionedStep>,
 	client: Arc<RwLock<Option<Weak<dyn EngineClient>>>>,
+	engine: Weak<AuthorityRound>,
 }
 
 const ENGINE_TIMEOUT_TOKEN: TimerToken = 23;
@@ -1227,6 +1322,9 @@ impl IoHandler<()> for TransitionHandler {
 			while AsMillis::as_millis(&self.step.inner.duration_remaining()) == 0 {
 				self.step.inner.increment();
 				self.step.can_propose.store(true, AtomicOrdering::SeqCst);
+				if let Some(engine) = self.engine.upgrade() {
+					engine.track_duties();
+				}
 				if let Some(ref weak) = *self.client.read() {
 					if let Some(c) = weak.upgrade() {
 						c.update_sealing(ForceUpdateSealing::No);
@@ -1257,6 +1355,7 @@ impl Engine for AuthorityRound {
 	fn step(&self) {
 		self.step.inner.increment();
 		self.step.can_propose.store(true, AtomicOrdering::SeqCst);
+		self.track_duties();
 		if let Ok(c) = self.upgrade_client_or(None) {
 			c.update_sealing(ForceUpdateSealing::No);
 		}
@@ -1297,7 +1396,7 @@ impl Engine for AuthorityRound {
 	}
 
 	fn maximum_uncle_count(&self, block: BlockNumber) -> usize {
-		if block >= self.maximum_uncle_count_transition {
+		if block >= self.maximum_uncle_count_transition.load(AtomicOrdering::SeqCst) {
 			self.maximum_uncle_count
 		} else {
 			// fallback to default value
@@ -1457,6 +1556,7 @@ impl Engine for AuthorityRound {
 				if self.step.can_propose.compare_and_swap(true, false, AtomicOrdering::SeqCst) {
 					trace!(target: "engine", "generate_seal: emitting empty step at step={}, block=#{}", step, header.number());
 					self.emit_empty_step(header.parent_hash());
+					self.duties.seal(step, SlotOutcome::EmptyStep);
 				}
 
 				return Seal::None;
@@ -1469,15 +1569,22 @@ impl Engine for AuthorityRound {
 				None
 			};
 
-			if let Ok(signature) = self.sign(header_seal_hash(header, empty_steps_rlp.as_ref().map(|e| &**e))) {
+			let seal_hash = header_seal_hash(header, empty_steps_rlp.as_ref().map(|e| &**e));
+			if let Ok(signature) = self.sign(seal_hash) {
 				trace!(target: "engine", "generate_seal: Issuing a block for step {}.", step);
 
 				// only issue the seal if we were the first to reach the compare_and_swap.
 				if self.step.can_propose.compare_and_swap(true, false, AtomicOrdering::SeqCst) {
+					if let Err(e) = self.journal_seal(step, seal_hash) {
+						warn!(target: "engine", "generate_seal: {}", e);
+						return Seal::None;
+					}
+
 					// we can drop all accumulated empty step messages that are
 					// older than the parent step since we're including them in
 					// the seal
 					self.clear_empty_steps(parent_step);
+					self.record_seal(step);
 
 					// report any skipped primaries between the parent block and
 					// the block we're sealing, unless we have empty steps enabled
@@ -1594,7 +1701,7 @@ impl Engine for AuthorityRound {
 
 	/// Check the number of seal fields.
 	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
-		if header.number() >= self.validate_score_transition && *header.difficulty() >= U256::from(U128::max_value()) {
+		if header.number() >= self.validate_score_transition.load(AtomicOrdering::SeqCst) && *header.difficulty() >= U256::from(U128::max_value()) {
 			return Err(Error::Block(BlockError::DifficultyOutOfBounds(OutOfBounds {
 				min: None,
 				max: Some(U256::from(U128::max_value())),
@@ -1636,7 +1743,7 @@ impl Engine for AuthorityRound {
 
 		// Ensure header is from the step after parent.
 		if step == parent_step
-			|| (header.number() >= self.validate_step_transition && step <= parent_step) {
+			|| (header.number() >= self.validate_step_transition.load(AtomicOrdering::SeqCst) && step <= parent_step) {
 			warn!(target: "engine", "Multiple blocks proposed for step {}.", parent_step);
 
 			self.validators.report_malicious(header.author(), set_number, header.number(), Default::default());
@@ -1667,7 +1774,7 @@ impl Engine for AuthorityRound {
 		// reported as there's no way to tell whether the empty step message was never sent or simply not included.
 		let empty_steps_len = if header.number() >= self.empty_steps_transition {
 			let validate_empty_steps = || -> Result<usize, Error> {
-				let strict_empty_steps = header.number() >= self.strict_empty_steps_transition;
+				let strict_empty_steps = header.number() >= self.strict_empty_steps_transition.load(AtomicOrdering::SeqCst);
 				let empty_steps = header_empty_steps(header)?;
 				let empty_steps_len = empty_steps.len();
 				let mut prev_empty_step = 0;
@@ -1723,7 +1830,7 @@ impl Engine for AuthorityRound {
 			0
 		};
 
-		if header.number() >= self.validate_score_transition {
+		if header.number() >= self.validate_score_transition.load(AtomicOrdering::SeqCst) {
 			let expected_difficulty = calculate_score(parent_step.into(), step.into(), empty_steps_len.into());
 			if header.difficulty() != &expected_difficulty {
 				return Err(Error::Block(BlockError::InvalidDifficulty(Mismatch {
@@ -1916,8 +2023,18 @@ impl Engine for AuthorityRound {
 		self.validators.register_client(client);
 	}
 
+	fn uses_sealing_journal(&self) -> bool { true }
+
+	fn set_sealing_journal(&self, journal: Arc<SealingJournal>) {
+		*self.sealing_journal.write() = Some(journal);
+	}
+
 	fn set_signer(&self, signer: Option<Box<dyn EngineSigner>>) {
+		let previous = self.address();
 		*self.signer.write() = signer;
+		if self.address() != previous {
+			self.duties.reset();
+		}
 	}
 
 	fn sign(&self, hash: H256) -> Result<Signature, Error> {
@@ -1953,6 +2070,18 @@ impl Engine for AuthorityRound {
 		self.machine.params()
 	}
 
+	fn params_reload(&self) -> Option<&dyn engine::ParamsReload> {
+		if self.params_update_signer.is_some() {
+			Some(self)
+		} else {
+			None
+		}
+	}
+
+	fn validator_duties(&self) -> Option<&dyn engine::ValidatorDuties> {
+		Some(self)
+	}
+
 	fn gas_limit_override(&self, header: &Header) -> Option<U256> {
 		let (_, &address) = self.block_gas_limit_contract_transitions.range(..=header.number()).last()?;
 		let client = self.upgrade_client_or("Unable to prepare block").ok()?;
@@ -1972,6 +2101,116 @@ impl Engine for AuthorityRound {
 	}
 }
 
+impl engine::ValidatorDuties for AuthorityRound {
+	fn validator_status(&self) -> ValidatorStatus {
+		let signer = self.address();
+		let current_step = self.step.inner.load();
+		let next_slot = signer.and_then(|me| self.local_slot(&me, current_step + 1, u64::MAX));
+		let (counts, recent_slots) = self.duties.snapshot();
+		ValidatorStatus {
+			signer,
+			current_step,
+			next_slot,
+			expected: counts.expected,
+			sealed: counts.sealed,
+			late: counts.late,
+			missed: counts.missed,
+			recent_slots,
+		}
+	}
+}
+
+impl engine::ParamsReload for AuthorityRound {
+	fn reload_params(&self, update: &[u8], signature: &Signature, best_block: BlockNumber) -> Result<(), Error> {
+		self.apply_params_update(update, signature, Some(best_block))
+	}
+
+	fn restore_params(&self, update: &[u8], signature: &Signature) -> Result<(), Error> {
+		self.apply_params_update(update, signature, None)
+	}
+}
+
+impl AuthorityRound {
+	/// Applies a signed parameters update. Unless `best_block` is `None`, which restores an update
+	/// accepted before a restart, the update may only change parameters of future blocks and steps.
+	fn apply_params_update(&self, update: &[u8], signature: &Signature, best_block: Option<BlockNumber>) -> Result<(), Error> {
+		let invalid = |msg: String| -> Error { EngineError::InvalidParamsUpdate(msg).into() };
+		let signer = self.params_update_signer.ok_or(EngineError::ParamsReloadUnsupported)?;
+		if !parity_crypto::publickey::verify_address(&signer, signature, &keccak(update))? {
+			return Err(invalid(format!("the update is not signed by {:?}", signer)));
+		}
+		let update = ethjson::spec::AuthorityRoundParamsUpdate::load(update).map_err(|e| invalid(e.to_string()))?;
+
+		// Check the whole update before applying any of it, so only blocks after the best block
+		// are affected.
+		let transitions: Vec<(&str, &AtomicU64, u64)> = vec![
+			("validateScoreTransition", &self.validate_score_transition, update.validate_score_transition),
+			("validateStepTransition", &self.validate_step_transition, update.validate_step_transition),
+			("maximumUncleCountTransition", &self.maximum_uncle_count_transition, update.maximum_uncle_count_transition),
+			("strictEmptyStepsTransition", &self.strict_empty_steps_transition, update.strict_empty_steps_transition),
+		]
+			.into_iter()
+			.filter_map(|(name, current, new)| new.map(|new| (name, current, new.into())))
+			.collect();
+		let validators: Vec<(BlockNumber, ethjson::spec::ValidatorSet)> = update.validators
+			.unwrap_or_default()
+			.into_iter()
+			.map(|(block, set)| (block.into(), set))
+			.collect();
+		if let Some(best_block) = best_block {
+			for &(name, current, new) in &transitions {
+				let current = current.load(AtomicOrdering::SeqCst);
+				if new != current && cmp::min(new, current) <= best_block {
+					return Err(invalid(format!("{} can only move between blocks after the best block #{}", name, best_block)));
+				}
+			}
+			if let Some(&(block, _)) = validators.iter().find(|&&(block, _)| block <= best_block) {
+				return Err(invalid(format!("validator set transition at block {} is not after the best block #{}", block, best_block)));
+			}
+		}
+
+		// restored step duration transitions may already have passed.
+		let now = best_block.map_or(0, |_| unix_now().as_secs());
+		let mut durations = self.step.inner.durations.write();
+		let mut new_durations = Vec::new();
+		let mut last = *durations.last().expect("durations cannot be empty");
+		for (timestamp, step_duration) in update.step_duration.unwrap_or_default() {
+			last = step_duration_transition(last, timestamp.into(), step_duration.into(), now)
+				.map_err(invalid)?;
+			new_durations.push(last);
+		}
+
+		for (block, set) in validators {
+			self.validators.schedule(block, new_validator_set_posdao(set, self.posdao_transition)).map_err(invalid)?;
+			info!(target: "engine", "Validator set transition scheduled at block {}", block);
+		}
+		for info in new_durations {
+			info!(target: "engine", "Step duration changes to {}s at step {}", info.step_duration, info.transition_step);
+			durations.push(info);
+		}
+		for (name, current, new) in transitions {
+			info!(target: "engine", "{} set to block {}", name, new);
+			current.store(new, AtomicOrdering::SeqCst);
+		}
+
+		Ok(())
+	}
+}
+
+/// Computes a step duration transition at `timestamp` following `last`. The transition has to be
+/// after `now` and after `last`.
+fn step_duration_transition(last: StepDurationInfo, timestamp: u64, step_duration: u64, now: u64) -> Result<StepDurationInfo, String> {
+	if step_duration == 0 || step_duration as usize > U16_MAX {
+		return Err(format!("step duration {} is out of bounds", step_duration));
+	}
+	if timestamp <= now || timestamp <= last.transition_timestamp {
+		return Err(format!("step duration transition at {} is not in the future", timestamp));
+	}
+	let (transition_step, transition_timestamp) = next_step_time_duration(last, timestamp)
+		.ok_or_else(|| format!("step duration transition at {} overflows", timestamp))?;
+	Ok(StepDurationInfo { transition_step, transition_timestamp, step_duration })
+}
+
 /// A helper accumulator function mapping a step duration and a step duration transition timestamp
 /// to the corresponding step number and the correct starting second of the step.
 fn next_step_time_duration(info: StepDurationInfo, time: u64) -> Option<(u64, u64)>
@@ -1998,15 +2237,17 @@ mod tests {
 	use accounts::AccountProvider;
 	use ethabi_contract::use_contract;
 	use ethereum_types::{Address, H520, H256, U256};
-	use parity_crypto::publickey::Signature;
+	use parity_crypto::publickey::{self, KeyPair, Secret, Signature};
 	use common_types::{
 		header::Header,
 		engines::{Seal, params::CommonParams},
 		ids::BlockId,
 		errors::{EthcoreError as Error, EngineError},
 		transaction::{Action, Transaction},
+		validator_status::{DutySlot, SlotOutcome},
 	};
 	use rlp::encode;
+	use parking_lot::RwLock;
 	use ethcore::{
 		block::*,
 		miner::{Author, MinerService},
@@ -2016,6 +2257,7 @@ mod tests {
 		},
 	};
 	use engine::Engine;
+	use engine::sealing_journal::SealingJournal;
 	use block_reward::BlockRewardContract;
 	use machine::Machine;
 	use spec::{self, Spec};
@@ -2049,6 +2291,7 @@ mod tests {
 			randomness_contract_address: BTreeMap::new(),
 			block_gas_limit_contract_transitions: BTreeMap::new(),
 			posdao_transition: Some(0),
+			params_update_signer: None,
 		};
 
 		// mutate aura params
@@ -2106,6 +2349,46 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn journaled_steps_are_not_sealed_twice() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr1 = tap.insert_account(keccak("1").into(), &"1".into()).unwrap();
+		let journal = Arc::new(SealingJournal::in_memory());
+		let spec = spec::new_test_round();
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let open_block = |engine: &dyn Engine, extra_data: Vec<u8>| {
+			let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+			OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes.clone(), addr1, (3141562.into(), 31415620.into()), extra_data, false)
+				.unwrap().close_and_lock().unwrap()
+		};
+
+		let engine = &*spec.engine;
+		engine.set_sealing_journal(journal.clone());
+		engine.set_signer(Some(Box::new((tap.clone(), addr1, "1".into()))));
+		let b1 = open_block(engine, vec![]);
+		match engine.generate_seal(&b1, &genesis_header) {
+			Seal::Regular(_) => {},
+			_ => panic!("block 1 not sealed"),
+		}
+		// spec starts with step 2.
+		assert!(journal.signed(&addr1, 2).is_some());
+
+		// after a restart the same block may be sealed again, but not a different one.
+		let restarted = spec::new_test_round().engine;
+		restarted.set_sealing_journal(journal.clone());
+		restarted.set_signer(Some(Box::new((tap.clone(), addr1, "1".into()))));
+		assert_eq!(restarted.generate_seal(&open_block(&*restarted, vec![1]), &genesis_header), Seal::None);
+
+		let restarted = spec::new_test_round().engine;
+		restarted.set_sealing_journal(journal);
+		restarted.set_signer(Some(Box::new((tap, addr1, "1".into()))));
+		match restarted.generate_seal(&b1, &genesis_header) {
+			Seal::Regular(_) => {},
+			_ => panic!("block 1 not sealed again"),
+		}
+	}
+
 	#[test]
 	fn generates_seal_iff_sealer_is_set() {
 		let tap = Arc::new(AccountProvider::transient_provider());
@@ -2365,11 +2648,11 @@ mod tests {
 		let step = Step {
 			calibrate: false,
 			inner: AtomicU64::new(::std::u64::MAX),
-			durations: [StepDurationInfo {
+			durations: RwLock::new(vec![StepDurationInfo {
 				transition_step: 0,
 				transition_timestamp: 0,
 				step_duration: 1,
-			}].to_vec().into_iter().collect(),
+			}]),
 		};
 		step.increment();
 	}
@@ -2381,11 +2664,11 @@ mod tests {
 		let step = Step {
 			calibrate: false,
 			inner: AtomicU64::new(::std::u64::MAX),
-			durations: [StepDurationInfo {
+			durations: RwLock::new(vec![StepDurationInfo {
 				transition_step: 0,
 				transition_timestamp: 0,
 				step_duration: 1,
-			}].to_vec().into_iter().collect(),
+			}]),
 		};
 		step.duration_remaining();
 	}
@@ -2415,11 +2698,11 @@ mod tests {
 		let step = Step {
 			calibrate: true,
 			inner: AtomicU64::new(::std::u64::MAX),
-			durations: [
+			durations: RwLock::new(vec![
 				StepDurationInfo { transition_step: 0, transition_timestamp: 0, step_duration: 1 },
 				StepDurationInfo { transition_step: now, transition_timestamp: now, step_duration: 2 },
 				StepDurationInfo { transition_step: now + 1, transition_timestamp: now + 2, step_duration: 4 },
-			].to_vec().into_iter().collect(),
+			]),
 		};
 		// calibrated step `now`
 		step.calibrate();
@@ -2840,6 +3123,41 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn tracks_missed_sealing_steps() {
+		let client = generate_dummy_client_with_spec(spec::new_test_round);
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr1 = tap.insert_account(keccak("1").into(), &"1".into()).unwrap();
+
+		let engine = client.engine();
+		engine.register_client(Arc::downgrade(&client) as _);
+		engine.set_signer(Some(Box::new((tap, addr1, "1".into()))));
+		let duties = engine.validator_duties().expect("AuRa tracks validator duties; qed");
+
+		// spec starts with step 2, and the signer seals the even steps.
+		let status = duties.validator_status();
+		assert_eq!(status.signer, Some(addr1));
+		assert_eq!(status.next_slot, Some(4));
+		assert_eq!(status.expected, 0);
+
+		// the miner has no author, so no blocks are sealed.
+		for _ in 0..4 {
+			engine.step();
+		}
+		let status = duties.validator_status();
+		assert_eq!(status.current_step, 6);
+		assert_eq!(status.next_slot, Some(8));
+		assert_eq!((status.expected, status.sealed, status.late, status.missed), (2, 0, 0, 1));
+		assert_eq!(status.recent_slots, vec![
+			DutySlot { step: 4, outcome: SlotOutcome::Missed },
+			DutySlot { step: 6, outcome: SlotOutcome::Pending },
+		]);
+
+		// changing the signer starts over.
+		engine.set_signer(None);
+		assert_eq!(duties.validator_status().expected, 0);
+	}
+
 	#[test]
 	fn extra_info_from_seal() {
 		let (spec, tap, accounts) = setup_empty_steps();
@@ -3052,4 +3370,71 @@ mod tests {
 		let deserialized: ethjson::spec::AuthorityRound = serde_json::from_str(config).unwrap();
 		AuthorityRoundParams::from(deserialized.params);
 	}
+
+	#[test]
+	fn reloads_params_signed_by_update_signer() {
+		let secret: Secret = keccak("params update").into();
+		let keypair = KeyPair::from_secret(secret).unwrap();
+		let engine = build_aura(|p| {
+			p.params_update_signer = Some(keypair.address());
+			p.validate_step_transition = 10;
+		});
+		let reload = engine.params_reload().expect("update signer is set; qed");
+
+		let update = br#"{ "validateStepTransition": 20 }"#;
+		let signature = publickey::sign(keypair.secret(), &keccak(&update[..])).unwrap();
+		let other: Secret = keccak("other").into();
+		let other_signature = publickey::sign(&other, &keccak(&update[..])).unwrap();
+
+		assert!(reload.reload_params(update, &other_signature, 5).is_err());
+		// the current transition is not after the best block
+		assert!(reload.reload_params(update, &signature, 10).is_err());
+		reload.reload_params(update, &signature, 5).unwrap();
+		assert_eq!(engine.validate_step_transition.load(AtomicOrdering::SeqCst), 20);
+
+		let timestamp = super::unix_now().as_secs() + 100;
+		let update = format!(r#"{{ "stepDuration": {{ "{}": 3 }} }}"#, timestamp);
+		let signature = publickey::sign(keypair.secret(), &keccak(update.as_bytes())).unwrap();
+		reload.reload_params(update.as_bytes(), &signature, 5).unwrap();
+		let durations = engine.step.inner.durations.read();
+		assert_eq!(durations.len(), 2);
+		assert_eq!(durations[1].transition_timestamp, timestamp);
+		assert_eq!(durations[1].step_duration, 3);
+	}
+
+	#[test]
+	fn restores_params_regardless_of_best_block() {
+		let secret: Secret = keccak("params update").into();
+		let keypair = KeyPair::from_secret(secret).unwrap();
+		let engine = build_aura(|p| {
+			p.params_update_signer = Some(keypair.address());
+			p.validate_step_transition = 10;
+		});
+		let reload = engine.params_reload().expect("update signer is set; qed");
+
+		// accepted before the restart, the transitions have passed since.
+		let timestamp = super::unix_now().as_secs() - 100;
+		let update = format!(r#"{{ "validateStepTransition": 20, "stepDuration": {{ "{}": 3 }} }}"#, timestamp);
+		let signature = publickey::sign(keypair.secret(), &keccak(update.as_bytes())).unwrap();
+		assert!(reload.reload_params(update.as_bytes(), &signature, 30).is_err());
+		let other: Secret = keccak("other").into();
+		let other_signature = publickey::sign(&other, &keccak(update.as_bytes())).unwrap();
+		assert!(reload.restore_params(update.as_bytes(), &other_signature).is_err());
+
+		reload.restore_params(update.as_bytes(), &signature).unwrap();
+		assert_eq!(engine.validate_step_transition.load(AtomicOrdering::SeqCst), 20);
+		assert_eq!(engine.step.inner.durations.read()[1].transition_timestamp, timestamp);
+	}
+
+	#[test]
+	fn rejects_validator_transition_without_multi_set() {
+		let secret: Secret = keccak("params update").into();
+		let keypair = KeyPair::from_secret(secret).unwrap();
+		let engine = build_aura(|p| p.params_update_signer = Some(keypair.address()));
+		let reload = engine.params_reload().expect("update signer is set; qed");
+
+		let update = br#"{ "validators": { "30": { "list": [] } } }"#;
+		let signature = publickey::sign(keypair.secret(), &keccak(&update[..])).unwrap();
+		assert!(reload.reload_params(update, &signature, 5).is_err());
+	}
 }
@@ -32,7 +32,7 @@ use common_types::{
 	receipt::Receipt,
 };
 use client_traits::EngineClient;
-use ethereum_types::{H256, H520};
+use ethereum_types::{Address, H256, H520};
 use parking_lot::RwLock;
 use engine::{Engine, ConstructedVerifier, signer::EngineSigner};
 use parity_crypto::publickey::Signature;
@@ -64,6 +64,10 @@ impl engine::EpochVerifier for EpochVerifier {
 	fn verify_light(&self, header: &Header) -> Result<(), Error> {
 		verify_external(header, &self.list)
 	}
+
+	fn validators(&self) -> Option<Vec<Address>> {
+		Some(self.list.clone().into_inner())
+	}
 }
 
 fn verify_external(header: &Header, validators: &dyn ValidatorSet) -> Result<(), Error> {
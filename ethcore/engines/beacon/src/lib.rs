@@ -0,0 +1,526 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An engine wrapping the consensus engine a chain started with and switching the chain to
+//! proof-of-stake once the terminal total difficulty is reached. Proof-of-stake blocks are
+//! built and chosen by an external consensus client through the Engine API, so this engine
+//! only checks their header fields and never seals or rewards.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Weak};
+
+use client_traits::EngineClient;
+use common_types::{
+	BlockNumber,
+	ancestry_action::AncestryAction,
+	header::{Header, ExtendedHeader},
+	engines::{
+		ForkChoice, Seal, SealingState, Headers, PendingTransitionStore,
+		params::CommonParams,
+		machine as machine_types,
+	},
+	errors::{BlockError, EthcoreError as Error, EngineError},
+	ids::BlockId,
+	receipt::Receipt,
+	snapshot::Snapshotting,
+	transaction::{self, SignedTransaction, UnverifiedTransaction},
+};
+use engine::{
+	Engine, ConstructedVerifier, DevMining, EpochChange, ParamsReload, SignerVoting,
+	signer::EngineSigner,
+	sealing_journal::SealingJournal,
+};
+use ethereum_types::{H64, H256, U256, U512, Address};
+use keccak_hash::KECCAK_EMPTY_LIST_RLP;
+use log::trace;
+use machine::{ExecutedBlock, Machine};
+use parity_crypto::publickey::Signature;
+use parking_lot::RwLock;
+use unexpected::{Mismatch, OutOfBounds};
+use vm::{EnvInfo, Schedule};
+
+/// Number of seal fields of a proof-of-stake header: the `prevRandao` value and a zero nonce.
+const POS_SEAL_FIELDS: usize = 2;
+/// Number of seal fields of a proof-of-stake header once EIP-1559 is active: the base fee follows the nonce,
+/// which keeps the header RLP in the order of the London header.
+const POS_SEAL_FIELDS_WITH_BASE_FEE: usize = 3;
+/// Maximum size of the extra data of a proof-of-stake header.
+const MAX_EXTRA_DATA_SIZE: usize = 32;
+/// Base fee of the first block with a base fee.
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+/// Ratio of the gas limit to the gas target of a block.
+const ELASTICITY_MULTIPLIER: u64 = 2;
+/// Bound of the change of the base fee from one block to the next.
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Engine delegating to the pre-merge engine until the terminal total difficulty is reached.
+pub struct Beacon {
+	inner: Arc<dyn Engine>,
+	terminal_total_difficulty: U256,
+	client: RwLock<Option<Weak<dyn EngineClient>>>,
+}
+
+impl Beacon {
+	/// Wrap `inner`, which keeps validating blocks up to the terminal total difficulty.
+	pub fn new(inner: Arc<dyn Engine>, terminal_total_difficulty: U256) -> Self {
+		Beacon {
+			inner,
+			terminal_total_difficulty,
+			client: RwLock::new(None),
+		}
+	}
+
+	/// The total difficulty at which the chain switches to proof-of-stake.
+	pub fn terminal_total_difficulty(&self) -> U256 {
+		self.terminal_total_difficulty
+	}
+
+	/// Proof-of-stake blocks have zero difficulty.
+	fn is_pos(header: &Header) -> bool {
+		header.difficulty().is_zero()
+	}
+
+	/// Number of seal fields of a proof-of-stake header.
+	fn pos_seal_fields(&self, header: &Header) -> usize {
+		if header.number() >= self.params().eip1559_transition {
+			POS_SEAL_FIELDS_WITH_BASE_FEE
+		} else {
+			POS_SEAL_FIELDS
+		}
+	}
+
+	/// The EIP-1559 base fee of the child of `parent`, if the child is past the transition.
+	fn expected_base_fee(&self, parent: &Header) -> Option<U256> {
+		if parent.number() + 1 < self.params().eip1559_transition {
+			return None;
+		}
+		let parent_base_fee = match self.base_fee(parent) {
+			Some(base_fee) => base_fee,
+			None => return Some(INITIAL_BASE_FEE.into()),
+		};
+
+		let gas_target = *parent.gas_limit() / ELASTICITY_MULTIPLIER;
+		let gas_used = *parent.gas_used();
+		if gas_target.is_zero() || gas_used == gas_target {
+			return Some(parent_base_fee);
+		}
+		let delta = |gas_delta: U256| {
+			parent_base_fee.full_mul(gas_delta) / U512::from(gas_target) / U512::from(BASE_FEE_MAX_CHANGE_DENOMINATOR)
+		};
+		let base_fee = if gas_used > gas_target {
+			let delta = U256::try_from(delta(gas_used - gas_target)).unwrap_or_else(|_| U256::max_value());
+			parent_base_fee.saturating_add(cmp::max(delta, U256::one()))
+		} else {
+			let delta = U256::try_from(delta(gas_target - gas_used)).expect("delta is lower than the parent base fee; qed");
+			parent_base_fee - delta
+		};
+		Some(base_fee)
+	}
+
+	fn client(&self) -> Option<Arc<dyn EngineClient>> {
+		self.client.read().as_ref().and_then(Weak::upgrade)
+	}
+
+	/// Whether the best block of the chain reached the terminal total difficulty.
+	fn terminal_reached(&self) -> bool {
+		self.client().map_or(false, |c| c.chain_info().total_difficulty >= self.terminal_total_difficulty)
+	}
+
+	/// Total difficulty of the given block, if the full client knows about it.
+	fn total_difficulty(&self, hash: H256) -> Option<U256> {
+		let client = self.client()?;
+		let full_client = client.as_full_client()?;
+		full_client.block_total_difficulty(BlockId::Hash(hash))
+	}
+}
+
+impl Engine for Beacon {
+	fn name(&self) -> &str { "Beacon" }
+
+	fn machine(&self) -> &Machine { self.inner.machine() }
+
+	fn seal_fields(&self, header: &Header) -> usize {
+		if Self::is_pos(header) { self.pos_seal_fields(header) } else { self.inner.seal_fields(header) }
+	}
+
+	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> { self.inner.extra_info(header) }
+
+	fn maximum_uncle_count(&self, block: BlockNumber) -> usize {
+		// proof-of-stake blocks have no uncles.
+		if self.terminal_reached() {
+			return 0;
+		}
+		self.inner.maximum_uncle_count(block)
+	}
+
+	fn maximum_gas_limit(&self) -> Option<U256> { self.inner.maximum_gas_limit() }
+
+	fn on_new_block(&self, block: &mut ExecutedBlock, epoch_begin: bool) -> Result<(), Error> {
+		if Self::is_pos(&block.header) {
+			return Ok(());
+		}
+		self.inner.on_new_block(block, epoch_begin)
+	}
+
+	fn on_close_block(&self, block: &mut ExecutedBlock, parent_header: &Header) -> Result<(), Error> {
+		// proof-of-stake rewards are paid on the beacon chain.
+		if Self::is_pos(&block.header) {
+			return Ok(());
+		}
+		self.inner.on_close_block(block, parent_header)
+	}
+
+	fn on_seal_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> { self.inner.on_seal_block(block) }
+
+	fn generate_engine_transactions(&self, block: &ExecutedBlock) -> Result<Vec<SignedTransaction>, Error> {
+		self.inner.generate_engine_transactions(block)
+	}
+
+	fn sealing_state(&self) -> SealingState {
+		if self.terminal_reached() {
+			SealingState::NotReady
+		} else {
+			self.inner.sealing_state()
+		}
+	}
+
+	fn should_reseal_on_update(&self) -> bool { self.inner.should_reseal_on_update() }
+
+	fn generate_seal(&self, block: &ExecutedBlock, parent: &Header) -> Seal {
+		if self.terminal_reached() {
+			return Seal::None;
+		}
+		self.inner.generate_seal(block, parent)
+	}
+
+	fn verify_local_seal(&self, header: &Header) -> Result<(), Error> {
+		if Self::is_pos(header) {
+			return Ok(());
+		}
+		self.inner.verify_local_seal(header)
+	}
+
+	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+		if !Self::is_pos(header) {
+			return self.inner.verify_block_basic(header);
+		}
+
+		let seal_fields = self.pos_seal_fields(header);
+		if header.seal().len() != seal_fields {
+			return Err(BlockError::InvalidSealArity(Mismatch { expected: seal_fields, found: header.seal().len() }).into());
+		}
+		let _prev_randao: H256 = rlp::decode(&header.seal()[0])?;
+		let nonce: H64 = rlp::decode(&header.seal()[1])?;
+		if !nonce.is_zero() {
+			return Err(BlockError::InvalidSeal.into());
+		}
+		if seal_fields == POS_SEAL_FIELDS_WITH_BASE_FEE {
+			let _base_fee: U256 = rlp::decode(&header.seal()[2])?;
+		}
+		if *header.uncles_hash() != KECCAK_EMPTY_LIST_RLP {
+			return Err(BlockError::InvalidUnclesHash(Mismatch { expected: KECCAK_EMPTY_LIST_RLP, found: *header.uncles_hash() }).into());
+		}
+		if header.extra_data().len() > MAX_EXTRA_DATA_SIZE {
+			return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(MAX_EXTRA_DATA_SIZE), found: header.extra_data().len() }).into());
+		}
+		Ok(())
+	}
+
+	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
+		if Self::is_pos(header) {
+			return Ok(());
+		}
+		self.inner.verify_block_unordered(header)
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header) -> Result<(), Error> {
+		let parent_terminal = if Self::is_pos(parent) {
+			Some(true)
+		} else {
+			self.total_difficulty(parent.hash()).map(|td| td >= self.terminal_total_difficulty)
+		};
+
+		match (Self::is_pos(header), parent_terminal) {
+			(true, Some(false)) => Err(EngineError::TerminalTotalDifficultyNotReached(parent.hash()).into()),
+			(false, Some(true)) => Err(EngineError::TerminalTotalDifficultyPassed(parent.hash()).into()),
+			(true, _) => {
+				match (self.expected_base_fee(parent), self.base_fee(header)) {
+					(Some(expected), Some(found)) if expected != found => Err(BlockError::InvalidBaseFee(Mismatch { expected, found }).into()),
+					(Some(_), None) | (None, Some(_)) => Err(BlockError::InvalidSeal.into()),
+					_ => Ok(()),
+				}
+			},
+			(false, _) => self.inner.verify_block_family(header, parent),
+		}
+	}
+
+	fn verify_block_external(&self, header: &Header) -> Result<(), Error> {
+		if Self::is_pos(header) {
+			return Ok(());
+		}
+		self.inner.verify_block_external(header)
+	}
+
+	fn genesis_epoch_data(&self, header: &Header, state: &machine_types::Call) -> Result<Vec<u8>, String> {
+		self.inner.genesis_epoch_data(header, state)
+	}
+
+	fn signals_epoch_end<'a>(&self, header: &Header, receipts: Option<&'a [Receipt]>) -> EpochChange {
+		self.inner.signals_epoch_end(header, receipts)
+	}
+
+	fn is_epoch_end(
+		&self,
+		chain_head: &Header,
+		finalized: &[H256],
+		chain: &Headers<Header>,
+		transition_store: &PendingTransitionStore,
+	) -> Option<Vec<u8>> {
+		self.inner.is_epoch_end(chain_head, finalized, chain, transition_store)
+	}
+
+	fn is_epoch_end_light(
+		&self,
+		chain_head: &Header,
+		chain: &Headers<Header>,
+		transition_store: &PendingTransitionStore,
+	) -> Option<Vec<u8>> {
+		self.inner.is_epoch_end_light(chain_head, chain, transition_store)
+	}
+
+	fn epoch_verifier<'a>(&self, header: &Header, proof: &'a [u8]) -> ConstructedVerifier<'a> {
+		self.inner.epoch_verifier(header, proof)
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
+		if Self::is_pos(parent) || self.terminal_reached() {
+			header.set_difficulty(U256::zero());
+			// the consensus client provides the `prevRandao` value of the block.
+			let mut seal = vec![rlp::encode(&H256::zero()), rlp::encode(&H64::zero())];
+			if let Some(base_fee) = self.expected_base_fee(parent) {
+				seal.push(rlp::encode(&base_fee));
+			}
+			header.set_seal(seal);
+		} else {
+			self.inner.populate_from_parent(header, parent)
+		}
+	}
+
+	fn handle_message(&self, message: &[u8]) -> Result<(), EngineError> { self.inner.handle_message(message) }
+
+	fn set_signer(&self, signer: Option<Box<dyn EngineSigner>>) { self.inner.set_signer(signer) }
+
+	fn uses_sealing_journal(&self) -> bool { self.inner.uses_sealing_journal() }
+
+	fn set_sealing_journal(&self, journal: Arc<SealingJournal>) { self.inner.set_sealing_journal(journal) }
+
+	fn sign(&self, hash: H256) -> Result<Signature, Error> { self.inner.sign(hash) }
+
+	fn register_client(&self, client: Weak<dyn EngineClient>) {
+		*self.client.write() = Some(client.clone());
+		self.inner.register_client(client);
+	}
+
+	fn step(&self) { self.inner.step() }
+
+	fn dev_mining(&self) -> Option<&dyn DevMining> { self.inner.dev_mining() }
+
+	fn signer_voting(&self) -> Option<&dyn SignerVoting> { self.inner.signer_voting() }
+
+	fn params_reload(&self) -> Option<&dyn ParamsReload> { self.inner.params_reload() }
+
+	fn snapshot_mode(&self) -> Snapshotting { self.inner.snapshot_mode() }
+
+	fn open_block_header_timestamp(&self, parent_timestamp: u64) -> u64 {
+		self.inner.open_block_header_timestamp(parent_timestamp)
+	}
+
+	fn is_timestamp_valid(&self, header_timestamp: u64, parent_timestamp: u64) -> bool {
+		self.inner.is_timestamp_valid(header_timestamp, parent_timestamp)
+	}
+
+	fn ancestry_actions(&self, header: &Header, ancestry: &mut dyn Iterator<Item = ExtendedHeader>) -> Vec<AncestryAction> {
+		// proof-of-stake blocks are finalized by the consensus client.
+		if Self::is_pos(header) {
+			return Vec::new();
+		}
+		self.inner.ancestry_actions(header, ancestry)
+	}
+
+	fn executive_author(&self, header: &Header) -> Result<Address, Error> {
+		if Self::is_pos(header) {
+			return Ok(*header.author());
+		}
+		self.inner.executive_author(header)
+	}
+
+	fn gas_limit_override(&self, header: &Header) -> Option<U256> { self.inner.gas_limit_override(header) }
+
+	fn fork_choice(&self, header: &Header) -> Option<ForkChoice> {
+		// the head only moves to a proof-of-stake block on a fork choice update.
+		if Self::is_pos(header) {
+			trace!(target: "engine", "Keeping best block on import of proof-of-stake block #{}", header.number());
+			return Some(ForkChoice::Old);
+		}
+		self.inner.fork_choice(header)
+	}
+
+	fn prev_randao(&self, header: &Header) -> Option<H256> {
+		if !Self::is_pos(header) {
+			return None;
+		}
+		header.seal().first().and_then(|field| rlp::decode(field).ok())
+	}
+
+	fn base_fee(&self, header: &Header) -> Option<U256> {
+		if !Self::is_pos(header) || header.seal().len() != POS_SEAL_FIELDS_WITH_BASE_FEE {
+			return None;
+		}
+		rlp::decode(&header.seal()[2]).ok()
+	}
+
+	fn params(&self) -> &CommonParams { self.inner.params() }
+
+	fn schedule(&self, block_number: BlockNumber) -> Schedule { self.inner.schedule(block_number) }
+
+	fn maximum_extra_data_size(&self) -> usize { self.inner.maximum_extra_data_size() }
+
+	fn account_start_nonce(&self, block: BlockNumber) -> U256 { self.inner.account_start_nonce(block) }
+
+	fn signing_chain_id(&self, env_info: &EnvInfo) -> Option<u64> { self.inner.signing_chain_id(env_info) }
+
+	fn verify_transaction_basic(&self, t: &UnverifiedTransaction, header: &Header) -> Result<(), transaction::Error> {
+		self.inner.verify_transaction_basic(t, header)
+	}
+
+	fn decode_transaction(&self, transaction: &[u8]) -> Result<UnverifiedTransaction, transaction::Error> {
+		self.inner.decode_transaction(transaction)
+	}
+
+	fn min_gas_limit(&self) -> U256 { self.inner.min_gas_limit() }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use common_types::{
+		engines::{ForkChoice, SealingState},
+		errors::{BlockError, EthcoreError as Error, EngineError},
+		header::Header,
+	};
+	use ethcore::test_helpers::generate_dummy_client_with_spec;
+	use ethereum_types::{H64, H256, U256};
+	use spec;
+
+	fn pos_header(parent: &Header) -> Header {
+		let mut header = Header::new();
+		header.set_parent_hash(parent.hash());
+		header.set_number(parent.number() + 1);
+		header.set_timestamp(parent.timestamp() + 12);
+		header.set_gas_limit(*parent.gas_limit());
+		header.set_seal(vec![rlp::encode(&H256::repeat_byte(7)), rlp::encode(&H64::zero()), rlp::encode(&U256::from(1_000_000_000))]);
+		header
+	}
+
+	#[test]
+	fn verifies_proof_of_stake_headers() {
+		let spec = spec::new_test_merge();
+		let engine = &*spec.engine;
+		let genesis = spec.genesis_header();
+		assert_eq!(engine.name(), "Beacon");
+
+		let mut header = pos_header(&genesis);
+		assert_eq!(engine.seal_fields(&header), 3);
+		assert!(engine.verify_block_basic(&header).is_ok());
+		assert_eq!(engine.fork_choice(&header), Some(ForkChoice::Old));
+		assert_eq!(engine.prev_randao(&header), Some(H256::repeat_byte(7)));
+		assert_eq!(engine.base_fee(&header), Some(U256::from(1_000_000_000)));
+
+		header.set_seal(vec![rlp::encode(&H256::zero()), rlp::encode(&H64::from_low_u64_be(1)), rlp::encode(&U256::one())]);
+		match engine.verify_block_basic(&header) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+
+		let mut header = pos_header(&genesis);
+		header.set_uncles_hash(H256::zero());
+		match engine.verify_block_basic(&header) {
+			Err(Error::Block(BlockError::InvalidUnclesHash(_))) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn enforces_terminal_total_difficulty() {
+		let spec = spec::new_test_merge();
+		let engine = spec.engine.clone();
+		let genesis = spec.genesis_header();
+		let client = generate_dummy_client_with_spec(spec::new_test_merge);
+		engine.register_client(Arc::downgrade(&client) as _);
+
+		// the genesis block has reached the terminal total difficulty.
+		assert_eq!(engine.sealing_state(), SealingState::NotReady);
+		let pos = pos_header(&genesis);
+		assert!(engine.verify_block_family(&pos, &genesis).is_ok());
+
+		let mut pow = pos_header(&genesis);
+		pow.set_difficulty(U256::from(0x20000));
+		match engine.verify_block_family(&pow, &genesis) {
+			Err(Error::Engine(EngineError::TerminalTotalDifficultyPassed(parent))) => assert_eq!(parent, genesis.hash()),
+			other => panic!("unexpected result: {:?}", other),
+		}
+
+		let mut child = Header::new();
+		engine.populate_from_parent(&mut child, &genesis);
+		assert!(child.difficulty().is_zero());
+		assert_eq!(engine.base_fee(&child), Some(U256::from(1_000_000_000)));
+	}
+
+	#[test]
+	fn verifies_base_fee() {
+		let spec = spec::new_test_merge();
+		let engine = spec.engine.clone();
+		let genesis = spec.genesis_header();
+		let client = generate_dummy_client_with_spec(spec::new_test_merge);
+		engine.register_client(Arc::downgrade(&client) as _);
+
+		// the first block with a base fee starts at the initial base fee.
+		let mut parent = pos_header(&genesis);
+		assert!(engine.verify_block_family(&parent, &genesis).is_ok());
+
+		// a full block raises the base fee by an eighth.
+		parent.set_gas_limit(U256::from(30_000_000));
+		parent.set_gas_used(U256::from(30_000_000));
+		let mut child = pos_header(&parent);
+		match engine.verify_block_family(&child, &parent) {
+			Err(Error::Block(BlockError::InvalidBaseFee(mismatch))) => {
+				assert_eq!(mismatch.expected, U256::from(1_125_000_000));
+				assert_eq!(mismatch.found, U256::from(1_000_000_000));
+			},
+			other => panic!("unexpected result: {:?}", other),
+		}
+		child.set_seal(vec![rlp::encode(&H256::zero()), rlp::encode(&H64::zero()), rlp::encode(&U256::from(1_125_000_000))]);
+		assert!(engine.verify_block_family(&child, &parent).is_ok());
+
+		// an empty block lowers it by an eighth.
+		parent.set_gas_used(U256::zero());
+		child.set_seal(vec![rlp::encode(&H256::zero()), rlp::encode(&H64::zero()), rlp::encode(&U256::from(875_000_000))]);
+		assert!(engine.verify_block_family(&child, &parent).is_ok());
+	}
+}
@@ -69,6 +69,8 @@ use std::{
 use client_traits::{EngineClient, ForceUpdateSealing};
 use engine::{
 	Engine,
+	SignerVoting,
+	sealing_journal::SealingJournal,
 	signer::EngineSigner,
 };
 use ethereum_types::{Address, H64, H160, H256, U256};
@@ -179,6 +181,7 @@ pub struct Clique {
 	block_state_by_hash: RwLock<LruCache<H256, CliqueBlockState>>,
 	proposals: RwLock<HashMap<Address, VoteType>>,
 	signer: RwLock<Option<Box<dyn EngineSigner>>>,
+	sealing_journal: RwLock<Option<Arc<SealingJournal>>>,
 }
 
 #[cfg(test)]
@@ -191,6 +194,7 @@ pub struct Clique {
 	pub block_state_by_hash: RwLock<LruCache<H256, CliqueBlockState>>,
 	pub proposals: RwLock<HashMap<Address, VoteType>>,
 	pub signer: RwLock<Option<Box<dyn EngineSigner>>>,
+	pub sealing_journal: RwLock<Option<Arc<SealingJournal>>>,
 }
 
 impl Clique {
@@ -206,6 +210,7 @@ impl Clique {
 			block_state_by_hash: RwLock::new(LruCache::new(STATE_CACHE_NUM)),
 			proposals: Default::default(),
 			signer: Default::default(),
+			sealing_journal: Default::default(),
 			machine,
 		};
 		let engine = Arc::new(engine);
@@ -244,6 +249,7 @@ impl Clique {
 			block_state_by_hash: RwLock::new(LruCache::new(STATE_CACHE_NUM)),
 			proposals: Default::default(),
 			signer: Default::default(),
+			sealing_journal: Default::default(),
 			machine: spec::new_test_machine(),
 		}
 	}
@@ -360,6 +366,28 @@ impl Clique {
 	}
 }
 
+impl SignerVoting for Clique {
+	fn signers(&self, header: &Header) -> Result<Vec<Address>, Error> {
+		Ok(self.state(header)?.signers().iter().cloned().collect())
+	}
+
+	fn proposals(&self) -> BTreeMap<Address, bool> {
+		self.proposals.read().iter()
+			.map(|(address, vote_type)| (*address, *vote_type == VoteType::Add))
+			.collect()
+	}
+
+	fn propose(&self, address: Address, authorize: bool) {
+		let vote_type = if authorize { VoteType::Add } else { VoteType::Remove };
+		trace!(target: "engine", "Proposing vote: beneficiary {}, type {:?}", address, vote_type);
+		self.proposals.write().insert(address, vote_type);
+	}
+
+	fn discard(&self, address: &Address) -> bool {
+		self.proposals.write().remove(address).is_some()
+	}
+}
+
 impl Engine for Clique {
 	fn name(&self) -> &str { "Clique" }
 
@@ -408,7 +436,6 @@ impl Engine for Clique {
 
 		// Cast a random Vote if not checkpoint
 		if !is_checkpoint {
-			// TODO(niklasad1): this will always be false because `proposals` is never written to
 			let votes = self.proposals.read().iter()
 				.filter(|(address, vote_type)| state.is_valid_vote(*address, **vote_type))
 				.map(|(address, vote_type)| (*address, *vote_type))
@@ -452,6 +479,11 @@ impl Engine for Clique {
 
 		header.set_extra_data(seal.clone());
 
+		// refuse to sign another block with the same number.
+		if let (Some(journal), Some(signer)) = (self.sealing_journal.read().as_ref(), self.signer.read().as_ref()) {
+			journal.record(signer.address(), header.number(), header.hash())?;
+		}
+
 		// append signature onto extra_data
 		let (sig, _msg) = self.sign_header(&header)?;
 		seal.extend_from_slice(&sig[..]);
@@ -756,6 +788,12 @@ impl Engine for Clique {
 		*current_signer = signer;
 	}
 
+	fn uses_sealing_journal(&self) -> bool { true }
+
+	fn set_sealing_journal(&self, journal: Arc<SealingJournal>) {
+		*self.sealing_journal.write() = Some(journal);
+	}
+
 	fn register_client(&self, client: Weak<dyn EngineClient>) {
 		*self.client.write() = Some(client.clone());
 	}
@@ -789,4 +827,6 @@ impl Engine for Clique {
 	fn params(&self) -> &CommonParams {
 		self.machine.params()
 	}
+
+	fn signer_voting(&self) -> Option<&dyn SignerVoting> { Some(self) }
 }
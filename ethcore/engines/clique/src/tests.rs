@@ -807,3 +807,66 @@ fn bonus_consensus_should_keep_track_of_votes_before_latest_per_signer() {
 	let tags = tester.into_tags(tester.clique_signers(&vote.hash()));
 	assert_eq!(&tags, &['A', 'B', 'C', 'D', 'E']);
 }
+
+#[test]
+fn signer_voting_reports_signers() {
+	let tester = CliqueTester::with(10, 1, vec!['A']);
+	let voting = tester.clique.signer_voting().unwrap();
+
+	let vote = tester.new_block_and_import(CliqueBlockType::Vote(VoteType::Add), &tester.genesis,
+										   Some(tester.signers[&'B'].address()), 'A').unwrap();
+
+	assert_eq!(tester.into_tags(voting.signers(&tester.genesis).unwrap().into_iter()), vec!['A']);
+	assert_eq!(tester.into_tags(voting.signers(&vote).unwrap().into_iter()), vec!['A', 'B']);
+}
+
+#[test]
+fn signer_voting_proposals() {
+	let tester = CliqueTester::with(10, 1, vec!['A']);
+	let voting = tester.clique.signer_voting().unwrap();
+	let (b, c) = (tester.signers[&'B'].address(), tester.signers[&'C'].address());
+
+	voting.propose(b, true);
+	voting.propose(c, true);
+	voting.propose(c, false);
+	assert_eq!(voting.proposals().into_iter().collect::<Vec<_>>(), {
+		let mut expected = vec![(b, true), (c, false)];
+		expected.sort();
+		expected
+	});
+
+	assert!(voting.discard(&b));
+	assert!(!voting.discard(&b));
+	assert_eq!(voting.proposals().into_iter().collect::<Vec<_>>(), vec![(c, false)]);
+}
+
+#[test]
+fn journaled_block_numbers_are_not_sealed_twice() {
+	let tester = CliqueTester::with(10, 1, vec!['A']);
+	let journal = Arc::new(SealingJournal::in_memory());
+	tester.clique.set_sealing_journal(journal.clone());
+	tester.clique.set_signer(Some(engine::signer::from_keypair(tester.signers[&'A'].clone())));
+
+	let open_block = |vanity: u8| OpenBlock::new(
+		&tester.clique,
+		Default::default(),
+		false,
+		tester.db.boxed_clone(),
+		&tester.genesis,
+		Arc::new(vec![tester.genesis.hash()]),
+		Address::zero(),
+		(3141562.into(), 31415620.into()),
+		vec![vanity; VANITY_LENGTH],
+		false,
+	).unwrap();
+
+	let mut block = open_block(0);
+	assert!(tester.clique.on_seal_block(block.block_mut()).is_ok());
+	assert!(journal.signed(&tester.signers[&'A'].address(), 1).is_some());
+
+	let mut conflicting = open_block(1);
+	match tester.clique.on_seal_block(conflicting.block_mut()) {
+		Err(Error::Engine(EngineError::SealRefused(_))) => {},
+		other => panic!("conflicting block sealed: {:?}", other),
+	}
+}
@@ -16,6 +16,8 @@
 
 use std::cmp;
 use std::collections::BTreeMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -42,11 +44,15 @@ use machine::{
 };
 use unexpected::{OutOfBounds, Mismatch};
 
+pub use ethash::EthashConfig;
+
 /// Number of blocks in an ethash snapshot.
 // make dependent on difficulty increment divisor?
 const SNAPSHOT_BLOCKS: u64 = 5000;
 /// Maximum number of blocks allowed in an ethash snapshot.
 const MAX_SNAPSHOT_BLOCKS: u64 = 30000;
+/// One in this many seals is fully verified below `EthashConfig::light_verification_below`.
+pub const LIGHT_VERIFICATION_SAMPLE: u64 = 32;
 
 /// Ethash params.
 #[derive(Debug, PartialEq)]
@@ -83,10 +89,8 @@ pub struct EthashParams {
 	pub expip2_transition: u64,
 	/// EXPIP-2 duration limit
 	pub expip2_duration_limit: u64,
-	/// Block reward contract transition block.
-	pub block_reward_contract_transition: u64,
-	/// Block reward contract.
-	pub block_reward_contract: Option<BlockRewardContract>,
+	/// Block reward contracts with the blocks from which they are used.
+	pub block_reward_contract_transitions: BTreeMap<BlockNumber, BlockRewardContract>,
 	/// Difficulty bomb delays.
 	pub difficulty_bomb_delays: BTreeMap<BlockNumber, BlockNumber>,
 	/// Block to transition to progpow
@@ -132,11 +136,23 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			expip2_transition: p.expip2_transition.map_or(u64::max_value(), Into::into),
 			expip2_duration_limit: p.expip2_duration_limit.map_or(30, Into::into),
 			progpow_transition: p.progpow_transition.map_or(u64::max_value(), Into::into),
-			block_reward_contract_transition: p.block_reward_contract_transition.map_or(0, Into::into),
-			block_reward_contract: match (p.block_reward_contract_code, p.block_reward_contract_address) {
-				(Some(code), _) => Some(BlockRewardContract::new_from_code(Arc::new(code.into()))),
-				(_, Some(address)) => Some(BlockRewardContract::new_from_address(address.into())),
-				(None, None) => None,
+			block_reward_contract_transitions: {
+				let transition = p.block_reward_contract_transition.map_or(0, Into::into);
+				let mut transitions: BTreeMap<_, _> = p.block_reward_contract_transitions.unwrap_or_default().into_iter()
+					.map(|(block, address)| (block.into(), BlockRewardContract::new_from_address(address.into())))
+					.collect();
+				if (p.block_reward_contract_code.is_some() || p.block_reward_contract_address.is_some()) &&
+					transitions.keys().next().map_or(false, |&block| block <= transition)
+				{
+					let s = "blockRewardContractTransition";
+					panic!("{} should be less than any of the keys in {}s", s, s);
+				}
+				if let Some(code) = p.block_reward_contract_code {
+					transitions.insert(transition, BlockRewardContract::new_from_code(Arc::new(code.into())));
+				} else if let Some(address) = p.block_reward_contract_address {
+					transitions.insert(transition, BlockRewardContract::new_from_address(address.into()));
+				}
+				transitions
 			},
 			difficulty_bomb_delays: p.difficulty_bomb_delays.unwrap_or_default().into_iter()
 				.map(|(block, delay)| (block.into(), delay.into()))
@@ -151,6 +167,8 @@ pub struct Ethash {
 	ethash_params: EthashParams,
 	pow: Arc<EthashManager>,
 	machine: Machine,
+	// randomly keyed, so that peers can't predict which seals get verified in light verification mode.
+	seal_sampler: RandomState,
 }
 
 impl Ethash {
@@ -160,18 +178,42 @@ impl Ethash {
 		ethash_params: EthashParams,
 		machine: Machine,
 		optimize_for: T,
+	) -> Self {
+		Self::with_config(cache_dir, ethash_params, machine, optimize_for, EthashConfig::default())
+	}
+
+	/// Create a new instance of Ethash engine with the given cache and verification settings
+	pub fn with_config<T: Into<Option<OptimizeFor>>>(
+		cache_dir: &Path,
+		ethash_params: EthashParams,
+		machine: Machine,
+		optimize_for: T,
+		config: EthashConfig,
 	) -> Self {
 		let progpow_transition = ethash_params.progpow_transition;
 
 		Ethash {
 			ethash_params,
 			machine,
-			pow: Arc::new(EthashManager::new(
+			pow: Arc::new(EthashManager::with_config(
 				cache_dir.as_ref(),
 				optimize_for.into(),
-				progpow_transition
+				progpow_transition,
+				config,
 			)),
+			seal_sampler: RandomState::new(),
+		}
+	}
+
+	// whether the full seal check of the block is skipped in light verification mode.
+	fn skip_seal_verification(&self, header: &Header) -> bool {
+		if header.number() >= self.pow.config().light_verification_below {
+			return false;
 		}
+
+		let mut hasher = self.seal_sampler.build_hasher();
+		hasher.write(header.bare_hash().as_bytes());
+		hasher.finish() % LIGHT_VERIFICATION_SAMPLE != 0
 	}
 }
 
@@ -247,6 +289,12 @@ impl Engine for Ethash {
 
 	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 2 }
 
+	// Switches to ProgPoW at the transition, like seal verification.
+	fn proof_of_work(&self, block_number: BlockNumber, pow_hash: &H256, nonce: u64) -> Option<(H256, U256)> {
+		let result = self.pow.compute_light(block_number, &pow_hash.0, nonce);
+		Some((H256(result.mix_hash), ethash::boundary_to_difficulty(&H256(result.value))))
+	}
+
 	fn maximum_gas_limit(&self) -> Option<U256> { Some(0x7fff_ffff_ffff_ffff_u64.into()) }
 
 	/// Apply the block reward on finalisation of the block.
@@ -257,8 +305,8 @@ impl Engine for Ethash {
 		let author = *block.header.author();
 		let number = block.header.number();
 
-		let rewards = match self.ethash_params.block_reward_contract {
-			Some(ref c) if number >= self.ethash_params.block_reward_contract_transition => {
+		let rewards = match self.ethash_params.block_reward_contract_transitions.range(..=number).next_back() {
+			Some((_, c)) => {
 				let mut beneficiaries = Vec::new();
 
 				beneficiaries.push((author, RewardKind::Author));
@@ -357,6 +405,12 @@ impl Engine for Ethash {
 	}
 
 	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
+		if self.skip_seal_verification(header) {
+			// the seal still has to be well-formed, `verify_block_basic` checked its difficulty.
+			EthashSeal::parse_seal(header.seal())?;
+			trace!(target: "miner", "Skipping full seal verification of #{} in light verification mode", header.number());
+			return Ok(());
+		}
 		verify_block_unordered(&self.pow, header)
 	}
 
@@ -527,7 +581,7 @@ mod tests {
 	use spec::{new_ropsten, new_mcip3_test, new_homestead_test_machine, Spec};
 	use tempfile::TempDir;
 
-	use super::{Ethash, EthashParams, ecip1017_eras_block_reward};
+	use super::{Ethash, EthashConfig, EthashParams, ecip1017_eras_block_reward};
 
 	fn test_spec() -> Spec {
 		let tempdir = TempDir::new().unwrap();
@@ -556,13 +610,43 @@ mod tests {
 			ecip1017_era_rounds: u64::max_value(),
 			expip2_transition: u64::max_value(),
 			expip2_duration_limit: 30,
-			block_reward_contract: None,
-			block_reward_contract_transition: 0,
+			block_reward_contract_transitions: BTreeMap::new(),
 			difficulty_bomb_delays: BTreeMap::new(),
 			progpow_transition: u64::max_value(),
 		}
 	}
 
+	#[test]
+	fn block_reward_contract_transitions() {
+		let params: ethjson::spec::EthashParams = serde_json::from_str(r#"{
+			"minimumDifficulty": "0x020000",
+			"difficultyBoundDivisor": "0x0800",
+			"blockRewardContractTransition": "0x10",
+			"blockRewardContractAddress": "0x0000000000000000000000000000000000000001",
+			"blockRewardContractTransitions": {
+				"0x20": "0x0000000000000000000000000000000000000002",
+				"0x30": "0x0000000000000000000000000000000000000003"
+			}
+		}"#).unwrap();
+		let params = EthashParams::from(params);
+		assert_eq!(params.block_reward_contract_transitions.keys().cloned().collect::<Vec<_>>(), vec![0x10, 0x20, 0x30]);
+	}
+
+	#[test]
+	#[should_panic(expected = "blockRewardContractTransition should be less than any of the keys")]
+	fn block_reward_contract_transition_after_transitions() {
+		let params: ethjson::spec::EthashParams = serde_json::from_str(r#"{
+			"minimumDifficulty": "0x020000",
+			"difficultyBoundDivisor": "0x0800",
+			"blockRewardContractTransition": "0x20",
+			"blockRewardContractAddress": "0x0000000000000000000000000000000000000001",
+			"blockRewardContractTransitions": {
+				"0x20": "0x0000000000000000000000000000000000000002"
+			}
+		}"#).unwrap();
+		EthashParams::from(params);
+	}
+
 	#[test]
 	fn on_close_block() {
 		let spec = test_spec();
@@ -747,6 +831,31 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn light_verification_checks_a_sample_of_seals() {
+		let machine = new_homestead_test_machine();
+		let ethparams = get_default_ethash_params();
+		let tempdir = TempDir::new().unwrap();
+		let config = EthashConfig {
+			pregenerate_epochs: 0,
+			light_verification_below: 1025,
+			..EthashConfig::default()
+		};
+		let ethash = Ethash::with_config(tempdir.path(), ethparams, machine, None, config);
+
+		let results: Vec<_> = (1..=1025).map(|number| {
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_seal(vec![rlp::encode(&H256::zero()), rlp::encode(&H64::zero())]);
+			ethash.verify_block_unordered(&header).is_ok()
+		}).collect();
+
+		// none of the seals are valid: only the sampled ones below the threshold are rejected.
+		assert!(results[..1024].iter().any(|ok| *ok));
+		assert!(results[..1024].iter().any(|ok| !*ok));
+		assert!(!results[1024]);
+	}
+
 	#[test]
 	fn can_do_proof_of_work_unordered_verification_fail() {
 		let engine = test_spec().engine;
@@ -14,9 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
 
+use client_traits::{EngineClient, ForceUpdateSealing};
 use common_types::{
+	BlockNumber,
 	header::Header,
 	engines::{
 		Seal,
@@ -25,48 +29,139 @@ use common_types::{
 	},
 	errors::EthcoreError as Error,
 };
-use engine::Engine;
+use engine::{Engine, DevMining};
 use ethjson;
+use io::{IoContext, IoHandler, IoService, TimerToken};
+use log::warn;
 use machine::{
 	ExecutedBlock,
 	Machine
 };
+use parking_lot::Mutex;
 
 
 /// `InstantSeal` params.
-#[derive(Default, Debug, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct InstantSealParams {
 	/// Whether to use millisecond timestamp
 	pub millisecond_timestamp: bool,
+	/// Whether to seal a block as soon as there are transactions to include
+	pub automine: bool,
+	/// Interval at which a block is sealed, even if it has no transactions
+	pub mining_interval: Option<Duration>,
+}
+
+impl Default for InstantSealParams {
+	fn default() -> Self {
+		InstantSealParams {
+			millisecond_timestamp: false,
+			automine: true,
+			mining_interval: None,
+		}
+	}
 }
 
 impl From<ethjson::spec::InstantSealParams> for InstantSealParams {
 	fn from(p: ethjson::spec::InstantSealParams) -> Self {
 		InstantSealParams {
 			millisecond_timestamp: p.millisecond_timestamp,
+			automine: p.automine.unwrap_or(true),
+			mining_interval: p.mining_interval
+				.map(|i| Duration::from_secs(i.into()))
+				.filter(|i| *i != Duration::from_secs(0)),
 		}
 	}
 }
 
 /// An engine which does not provide any consensus mechanism, just seals blocks internally.
-/// Only seals blocks which have transactions.
+/// Seals blocks which have transactions, unless automine is disabled, and optionally
+/// an empty block on a fixed interval or on demand.
 pub struct InstantSeal {
 	params: InstantSealParams,
 	machine: Machine,
 	last_sealed_block: AtomicU64,
+	automine: AtomicBool,
+	force_seal: Arc<AtomicBool>,
+	time_offset: AtomicU64,
+	interval_service: Mutex<Option<IoService<()>>>,
 }
 
 impl InstantSeal {
 	/// Returns new instance of InstantSeal over the given state machine.
 	pub fn new(params: InstantSealParams, machine: Machine) -> Self {
 		InstantSeal {
+			automine: AtomicBool::new(params.automine),
 			params,
 			machine,
 			last_sealed_block: AtomicU64::new(0),
+			force_seal: Arc::new(AtomicBool::new(false)),
+			time_offset: AtomicU64::new(0),
+			interval_service: Mutex::new(None),
+		}
+	}
+}
+
+const INTERVAL_TIMER_TOKEN: TimerToken = 0;
+
+/// Forces a block to be sealed on every tick of the mining interval.
+struct IntervalHandler {
+	interval: Duration,
+	force_seal: Arc<AtomicBool>,
+	client: Weak<dyn EngineClient>,
+}
+
+impl IoHandler<()> for IntervalHandler {
+	fn initialize(&self, io: &IoContext<()>) {
+		io.register_timer(INTERVAL_TIMER_TOKEN, self.interval)
+			.unwrap_or_else(|e| warn!(target: "engine", "Failed to start mining interval timer: {}.", e))
+	}
+
+	fn timeout(&self, _io: &IoContext<()>, timer: TimerToken) {
+		if timer == INTERVAL_TIMER_TOKEN {
+			if let Some(client) = self.client.upgrade() {
+				self.force_seal.store(true, Ordering::SeqCst);
+				client.update_sealing(ForceUpdateSealing::Yes);
+			}
 		}
 	}
 }
 
+impl DevMining for InstantSeal {
+	fn automine(&self) -> bool {
+		self.automine.load(Ordering::SeqCst)
+	}
+
+	fn set_automine(&self, automine: bool) {
+		self.automine.store(automine, Ordering::SeqCst);
+	}
+
+	fn force_seal(&self) {
+		self.force_seal.store(true, Ordering::SeqCst);
+	}
+
+	fn is_seal_forced(&self) -> bool {
+		self.force_seal.load(Ordering::SeqCst)
+	}
+
+	fn increase_time(&self, seconds: u64) -> Option<u64> {
+		self.time_offset.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |offset| offset.checked_add(seconds))
+			.ok()
+			.map(|offset| offset + seconds)
+	}
+
+	fn time_offset(&self) -> u64 {
+		self.time_offset.load(Ordering::SeqCst)
+	}
+
+	fn set_time_offset(&self, offset: u64) {
+		self.time_offset.store(offset, Ordering::SeqCst);
+	}
+
+	fn rewound(&self, best_block: BlockNumber) {
+		self.last_sealed_block.store(best_block, Ordering::SeqCst);
+	}
+}
+
 impl Engine for InstantSeal {
 	fn name(&self) -> &str { "InstantSeal" }
 
@@ -82,7 +177,8 @@ impl Engine for InstantSeal {
 	}
 
 	fn generate_seal(&self, block: &ExecutedBlock, _parent: &Header) -> Seal {
-		if !block.transactions.is_empty() {
+		let forced = self.is_seal_forced();
+		if forced || (self.automine() && !block.transactions.is_empty()) {
 			let block_number = block.header.number();
 			let last_sealed_block = self.last_sealed_block.load(Ordering::SeqCst);
 			// Return a regular seal if the given block is _higher_ than
@@ -90,6 +186,9 @@ impl Engine for InstantSeal {
 			if block_number > last_sealed_block {
 				let prev_last_sealed_block = self.last_sealed_block.compare_and_swap(last_sealed_block, block_number, Ordering::SeqCst);
 				if prev_last_sealed_block == last_sealed_block {
+					if forced {
+						self.force_seal.store(false, Ordering::SeqCst);
+					}
 					return Seal::Regular(Vec::new())
 				}
 			}
@@ -105,9 +204,10 @@ impl Engine for InstantSeal {
 		use std::{time, cmp};
 
 		let dur = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap_or_default();
-		let mut now = dur.as_secs();
+		let offset = self.time_offset.load(Ordering::SeqCst);
+		let mut now = dur.as_secs().saturating_add(offset);
 		if self.params.millisecond_timestamp {
-			now = now * 1000 + dur.subsec_millis() as u64;
+			now = now.saturating_mul(1000).saturating_add(dur.subsec_millis() as u64);
 		}
 		cmp::max(now, parent_timestamp)
 	}
@@ -120,6 +220,21 @@ impl Engine for InstantSeal {
 		self.machine.params()
 	}
 
+	fn register_client(&self, client: Weak<dyn EngineClient>) {
+		let interval = match self.params.mining_interval {
+			Some(interval) => interval,
+			None => return,
+		};
+		let handler = IntervalHandler { interval, force_seal: self.force_seal.clone(), client };
+		let service = IoService::<()>::start()
+			.and_then(|service| service.register_handler(Arc::new(handler)).map(|_| service));
+		match service {
+			Ok(service) => *self.interval_service.lock() = Some(service),
+			Err(e) => warn!(target: "engine", "Failed to start interval mining: {}.", e),
+		}
+	}
+
+	fn dev_mining(&self) -> Option<&dyn DevMining> { Some(self) }
 }
 
 
@@ -135,7 +250,9 @@ mod tests {
 		test_helpers::get_temp_state_db,
 		block::*,
 	};
+	use std::time::Duration;
 	use spec;
+	use super::InstantSealParams;
 
 	#[test]
 	fn instant_can_seal() {
@@ -162,4 +279,63 @@ mod tests {
 
 		assert!(engine.verify_block_unordered(&header).is_ok());
 	}
+
+	fn open_empty_block(spec: &spec::Spec) -> (LockedBlock, Header) {
+		let engine = &*spec.engine;
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![], false).unwrap();
+		(b.close_and_lock().unwrap(), genesis_header)
+	}
+
+	#[test]
+	fn instant_seals_empty_block_only_when_forced() {
+		let spec = spec::new_instant();
+		let engine = &*spec.engine;
+		let dev = engine.dev_mining().unwrap();
+		let (b, genesis_header) = open_empty_block(&spec);
+
+		assert_eq!(engine.generate_seal(&b, &genesis_header), Seal::None);
+		dev.force_seal();
+		assert!(dev.is_seal_forced());
+		assert_eq!(engine.generate_seal(&b, &genesis_header), Seal::Regular(Vec::new()));
+		assert!(!dev.is_seal_forced());
+	}
+
+	#[test]
+	fn instant_toggles_automine_and_increases_time() {
+		let spec = spec::new_instant();
+		let engine = &*spec.engine;
+		let dev = engine.dev_mining().unwrap();
+
+		assert!(dev.automine());
+		dev.set_automine(false);
+		assert!(!dev.automine());
+
+		let before = engine.open_block_header_timestamp(0);
+		assert_eq!(dev.increase_time(3600), Some(3600));
+		assert_eq!(dev.increase_time(60), Some(3660));
+		assert!(engine.open_block_header_timestamp(0) >= before + 3660);
+
+		// an overflowing increase is rejected and leaves the offset unchanged
+		assert_eq!(dev.increase_time(u64::max_value()), None);
+		assert_eq!(dev.time_offset(), 3660);
+		dev.set_time_offset(0);
+		assert_eq!(dev.time_offset(), 0);
+	}
+
+	#[test]
+	fn instant_params_from_json() {
+		let params: InstantSealParams = ethjson::spec::InstantSealParams {
+			millisecond_timestamp: false,
+			automine: Some(false),
+			mining_interval: Some(ethjson::uint::Uint(5.into())),
+		}.into();
+		assert_eq!(params, InstantSealParams {
+			millisecond_timestamp: false,
+			automine: false,
+			mining_interval: Some(Duration::from_secs(5)),
+		});
+	}
 }
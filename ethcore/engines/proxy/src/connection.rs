@@ -0,0 +1,141 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Connection to the external consensus process.
+//!
+//! Requests and responses are JSON-RPC 2.0 objects, one per line, exchanged over a Unix socket
+//! or a TCP connection.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Address of the external consensus process.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Endpoint {
+	/// `tcp://host:port`
+	Tcp(String),
+	/// `ipc:///path/to/socket`
+	#[cfg(unix)]
+	Ipc(PathBuf),
+}
+
+impl Endpoint {
+	/// Parse an endpoint URL.
+	pub fn parse(endpoint: &str) -> Result<Self, String> {
+		const TCP: &str = "tcp://";
+
+		if endpoint.starts_with(TCP) {
+			return Ok(Endpoint::Tcp(endpoint[TCP.len()..].into()));
+		}
+		#[cfg(unix)]
+		{
+			const IPC: &str = "ipc://";
+			if endpoint.starts_with(IPC) {
+				return Ok(Endpoint::Ipc(endpoint[IPC.len()..].into()));
+			}
+		}
+		Err(format!("Unsupported consensus endpoint: {}", endpoint))
+	}
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+	code: i64,
+	message: String,
+}
+
+#[derive(Deserialize)]
+struct Response {
+	id: u64,
+	#[serde(default)]
+	result: Value,
+	error: Option<RpcError>,
+}
+
+/// An open connection.
+pub struct Connection {
+	reader: BufReader<Box<dyn Read + Send>>,
+	writer: Box<dyn Write + Send>,
+	next_id: u64,
+}
+
+impl Connection {
+	/// Connect to the endpoint within `timeout`, failing requests that are not sent or answered
+	/// within `timeout` as well.
+	pub fn open(endpoint: &Endpoint, timeout: Duration) -> io::Result<Self> {
+		let (reader, writer): (Box<dyn Read + Send>, Box<dyn Write + Send>) = match *endpoint {
+			Endpoint::Tcp(ref address) => {
+				let address = address.to_socket_addrs()?.next()
+					.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the address resolved to nothing"))?;
+				let stream = TcpStream::connect_timeout(&address, timeout)?;
+				stream.set_read_timeout(Some(timeout))?;
+				stream.set_write_timeout(Some(timeout))?;
+				stream.set_nodelay(true)?;
+				(Box::new(stream.try_clone()?), Box::new(stream))
+			}
+			// connecting to a local socket doesn't wait for the listening process.
+			#[cfg(unix)]
+			Endpoint::Ipc(ref path) => {
+				let stream = UnixStream::connect(path)?;
+				stream.set_read_timeout(Some(timeout))?;
+				stream.set_write_timeout(Some(timeout))?;
+				(Box::new(stream.try_clone()?), Box::new(stream))
+			}
+		};
+		Ok(Connection { reader: BufReader::new(reader), writer, next_id: 0 })
+	}
+
+	/// Send a request and wait for its response.
+	pub fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+		self.next_id += 1;
+		let request = json!({
+			"jsonrpc": "2.0",
+			"id": self.next_id,
+			"method": method,
+			"params": [params],
+		});
+
+		let mut line = request.to_string();
+		line.push('\n');
+		self.writer.write_all(line.as_bytes())
+			.and_then(|_| self.writer.flush())
+			.map_err(|e| format!("failed to send {}: {}", method, e))?;
+
+		let mut line = String::new();
+		match self.reader.read_line(&mut line) {
+			Ok(0) => return Err("connection closed".into()),
+			Ok(_) => {},
+			Err(e) => return Err(format!("no response to {}: {}", method, e)),
+		}
+		let response: Response = serde_json::from_str(&line)
+			.map_err(|e| format!("invalid response to {}: {}", method, e))?;
+		if response.id != self.next_id {
+			return Err(format!("unexpected response id {} to request {}", response.id, self.next_id));
+		}
+		match response.error {
+			Some(error) => Err(format!("{} failed ({}): {}", method, error.code, error.message)),
+			None => Ok(response.result),
+		}
+	}
+}
@@ -0,0 +1,340 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An engine delegating seal verification, block sealing and fork choice to an external process,
+//! to experiment with consensus algorithms without recompiling ethcore.
+//!
+//! Version 1 of the protocol consists of the following JSON-RPC methods, each taking a single
+//! object parameter. Headers are hex encoded RLP.
+//!
+//! - `consensus_handshake` `{ "version" }` -> `{ "version" }`, sent on every (re)connection.
+//! - `consensus_sealingState` `{}` -> `"ready" | "notReady" | "external"`.
+//! - `consensus_generateSeal` `{ "header", "parent" }` -> `null` or the RLP encoded seal fields,
+//!   the header is sent without its seal.
+//! - `consensus_verifyHeader` `{ "stage", "header", "parent" }` -> `{ "valid", "reason" }`, where
+//!   `stage` is one of `local`, `basic`, `family` (with `parent`) and `external`.
+//! - `consensus_forkChoice` `{ "header" }` -> `"new" | "old"`, or `null` to compare total difficulties.
+//!
+//! Requests run concurrently over a pool of connections. Blocks are only rejected on an explicit
+//! `"valid": false`; when the process can't be reached or answers anything else the verification
+//! fails with `EngineError::ExternalConsensusUnavailable`, which doesn't mark the block bad.
+
+use std::time::Duration;
+
+use common_types::{
+	header::Header,
+	engines::{ForkChoice, Seal, SealingState, params::CommonParams},
+	errors::{EngineError, EthcoreError as Error},
+};
+use engine::Engine;
+use log::{debug, info, warn};
+use machine::{ExecutedBlock, Machine};
+use parking_lot::Mutex;
+use rustc_hex::{FromHex, ToHex};
+use serde_json::{json, Value};
+
+use crate::connection::{Connection, Endpoint};
+
+mod connection;
+
+/// Version of the protocol spoken with the external process.
+pub const PROTOCOL_VERSION: u64 = 1;
+/// Number of idle connections kept open, about the number of threads verifying blocks.
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+/// `ProxyEngine` params.
+pub struct ProxyEngineParams {
+	/// Endpoint of the external process.
+	pub endpoint: String,
+	/// Number of seal fields of the blocks.
+	pub seal_fields: usize,
+	/// Timeout of a request.
+	pub timeout: Duration,
+}
+
+impl From<ethjson::spec::ProxyEngineParams> for ProxyEngineParams {
+	fn from(p: ethjson::spec::ProxyEngineParams) -> Self {
+		ProxyEngineParams {
+			endpoint: p.endpoint,
+			seal_fields: p.seal_fields.map_or(1, |n| n as usize),
+			timeout: Duration::from_millis(p.timeout.unwrap_or(5_000)),
+		}
+	}
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	format!("0x{}", bytes.to_hex::<String>())
+}
+
+fn from_hex(value: &Value) -> Result<Vec<u8>, String> {
+	let hex = value.as_str().ok_or_else(|| format!("expected a hex string, got {}", value))?;
+	let hex = if hex.starts_with("0x") { &hex[2..] } else { hex };
+	hex.from_hex().map_err(|e| e.to_string())
+}
+
+/// Engine delegating consensus to an external process.
+pub struct ProxyEngine {
+	machine: Machine,
+	endpoint: Endpoint,
+	seal_fields: usize,
+	timeout: Duration,
+	idle_connections: Mutex<Vec<Connection>>,
+}
+
+impl ProxyEngine {
+	/// Create a new instance of the proxy engine, connections are opened on the first requests.
+	pub fn new(params: ProxyEngineParams, machine: Machine) -> Result<Self, Error> {
+		Ok(ProxyEngine {
+			machine,
+			endpoint: Endpoint::parse(&params.endpoint).map_err(EngineError::ExternalConsensus)?,
+			seal_fields: params.seal_fields,
+			timeout: params.timeout,
+			idle_connections: Mutex::new(Vec::new()),
+		})
+	}
+
+	fn connect(&self) -> Result<Connection, EngineError> {
+		let mut connection = Connection::open(&self.endpoint, self.timeout)
+			.map_err(|e| EngineError::ExternalConsensusUnavailable(format!("cannot connect to {:?}: {}", self.endpoint, e)))?;
+		let response = connection.call("consensus_handshake", json!({ "version": PROTOCOL_VERSION }))
+			.map_err(EngineError::ExternalConsensusUnavailable)?;
+		match response.get("version").and_then(Value::as_u64) {
+			Some(PROTOCOL_VERSION) => {
+				info!(target: "engine", "Connected to the external consensus process at {:?}", self.endpoint);
+				Ok(connection)
+			}
+			version => Err(EngineError::ExternalConsensusUnavailable(format!(
+				"unsupported protocol version {:?}, expected {}", version, PROTOCOL_VERSION
+			))),
+		}
+	}
+
+	/// Call the external process on an idle connection or a new one. The connection is closed if
+	/// the call fails.
+	fn call(&self, method: &str, params: Value) -> Result<Value, EngineError> {
+		let idle = self.idle_connections.lock().pop();
+		let mut connection = match idle {
+			Some(connection) => connection,
+			None => self.connect()?,
+		};
+		let result = connection.call(method, params).map_err(EngineError::ExternalConsensusUnavailable)?;
+		let mut idle = self.idle_connections.lock();
+		if idle.len() < MAX_IDLE_CONNECTIONS {
+			idle.push(connection);
+		}
+		Ok(result)
+	}
+
+	fn verify(&self, stage: &str, header: &Header, parent: Option<&Header>) -> Result<(), Error> {
+		let response = self.call("consensus_verifyHeader", json!({
+			"stage": stage,
+			"header": to_hex(&rlp::encode(header)),
+			"parent": parent.map(|p| to_hex(&rlp::encode(p))),
+		}))?;
+		match response.get("valid").and_then(Value::as_bool) {
+			Some(true) => Ok(()),
+			Some(false) => {
+				let reason = response.get("reason").and_then(Value::as_str).unwrap_or("no reason given");
+				Err(EngineError::ExternalConsensus(format!("block {} rejected: {}", header.hash(), reason)).into())
+			}
+			None => Err(EngineError::ExternalConsensusUnavailable(format!("invalid verification response: {}", response)).into()),
+		}
+	}
+}
+
+impl Engine for ProxyEngine {
+	fn name(&self) -> &str { "Proxy" }
+
+	fn machine(&self) -> &Machine { &self.machine }
+
+	fn seal_fields(&self, _header: &Header) -> usize { self.seal_fields }
+
+	fn sealing_state(&self) -> SealingState {
+		match self.call("consensus_sealingState", json!({})) {
+			Ok(ref state) if state == "ready" => SealingState::Ready,
+			Ok(ref state) if state == "external" => SealingState::External,
+			Ok(_) => SealingState::NotReady,
+			Err(e) => {
+				debug!(target: "engine", "Unable to get the sealing state: {}", e);
+				SealingState::NotReady
+			}
+		}
+	}
+
+	fn generate_seal(&self, block: &ExecutedBlock, parent: &Header) -> Seal {
+		let mut header = block.header.clone();
+		header.set_seal(Vec::new());
+		let response = self.call("consensus_generateSeal", json!({
+			"header": to_hex(&rlp::encode(&header)),
+			"parent": to_hex(&rlp::encode(parent)),
+		}));
+		let seal = match response {
+			Ok(Value::Null) => return Seal::None,
+			Ok(Value::Array(fields)) => fields.iter().map(from_hex).collect::<Result<Vec<_>, _>>(),
+			Ok(other) => Err(format!("expected a list of seal fields, got {}", other)),
+			Err(e) => Err(e.to_string()),
+		};
+		match seal {
+			Ok(seal) => Seal::Regular(seal),
+			Err(e) => {
+				warn!(target: "engine", "Unable to seal block {}: {}", block.header.number(), e);
+				Seal::None
+			}
+		}
+	}
+
+	fn verify_local_seal(&self, header: &Header) -> Result<(), Error> {
+		self.verify("local", header, None)
+	}
+
+	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+		match header.number() {
+			0 => Ok(()),
+			_ => self.verify("basic", header, None),
+		}
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header) -> Result<(), Error> {
+		self.verify("family", header, Some(parent))
+	}
+
+	fn verify_block_external(&self, header: &Header) -> Result<(), Error> {
+		self.verify("external", header, None)
+	}
+
+	fn fork_choice(&self, header: &Header) -> Option<ForkChoice> {
+		match self.call("consensus_forkChoice", json!({ "header": to_hex(&rlp::encode(header)) })) {
+			Ok(ref choice) if choice == "new" => Some(ForkChoice::New),
+			Ok(ref choice) if choice == "old" => Some(ForkChoice::Old),
+			Ok(_) => None,
+			Err(e) => {
+				warn!(target: "engine", "Falling back to the total difficulty fork choice for block {}: {}", header.hash(), e);
+				None
+			}
+		}
+	}
+
+	fn params(&self) -> &CommonParams {
+		self.machine.params()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{BufRead, BufReader, Write};
+	use std::net::{TcpListener, TcpStream};
+	use std::sync::Arc;
+	use std::thread;
+	use super::*;
+
+	/// Serve the protocol on a local port, rejecting every block in the `external` stage.
+	fn serve(version: u64) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let stream = stream.unwrap();
+				thread::spawn(move || answer(stream, version));
+			}
+		});
+		format!("tcp://{}", address)
+	}
+
+	fn answer(stream: TcpStream, version: u64) {
+		let mut writer = stream.try_clone().unwrap();
+		for line in BufReader::new(stream).lines() {
+			let request: Value = serde_json::from_str(&line.unwrap()).unwrap();
+			let params = &request["params"][0];
+			let result = match request["method"].as_str().unwrap() {
+				"consensus_handshake" => json!({ "version": version }),
+				"consensus_sealingState" => json!("ready"),
+				"consensus_forkChoice" => json!("old"),
+				"consensus_verifyHeader" => match params["stage"].as_str() {
+					Some("external") => json!({ "valid": false, "reason": "unknown signer" }),
+					_ => json!({ "valid": true }),
+				},
+				_ => Value::Null,
+			};
+			writeln!(writer, "{}", json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })).unwrap();
+		}
+	}
+
+	fn engine(endpoint: String) -> ProxyEngine {
+		let params = ProxyEngineParams { endpoint, seal_fields: 1, timeout: Duration::from_secs(5) };
+		ProxyEngine::new(params, spec::new_test_machine()).unwrap()
+	}
+
+	fn is_unavailable<T>(result: Result<T, Error>) -> bool {
+		match result {
+			Err(Error::Engine(EngineError::ExternalConsensusUnavailable(_))) => true,
+			_ => false,
+		}
+	}
+
+	#[test]
+	fn delegates_to_external_process() {
+		let engine = engine(serve(PROTOCOL_VERSION));
+		let mut header = Header::new();
+		header.set_number(1);
+
+		assert_eq!(engine.sealing_state(), SealingState::Ready);
+		assert_eq!(engine.fork_choice(&header), Some(ForkChoice::Old));
+		assert!(engine.verify_block_basic(&header).is_ok());
+		assert!(engine.verify_block_family(&header, &Header::new()).is_ok());
+		match engine.verify_block_external(&header) {
+			Err(Error::Engine(EngineError::ExternalConsensus(ref msg))) => assert!(msg.ends_with("unknown signer")),
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn verifies_concurrently() {
+		let engine = Arc::new(engine(serve(PROTOCOL_VERSION)));
+		let verifiers: Vec<_> = (0..4).map(|_| {
+			let engine = engine.clone();
+			thread::spawn(move || engine.verify_block_family(&Header::new(), &Header::new()).is_ok())
+		}).collect();
+		assert!(verifiers.into_iter().all(|v| v.join().unwrap()));
+		let idle = engine.idle_connections.lock().len();
+		assert!(idle >= 1 && idle <= 4);
+	}
+
+	#[test]
+	fn rejects_unsupported_protocol_version() {
+		let engine = engine(serve(PROTOCOL_VERSION + 1));
+		assert_eq!(engine.sealing_state(), SealingState::NotReady);
+		assert!(is_unavailable(engine.verify_block_external(&Header::new())));
+	}
+
+	#[test]
+	fn unresponsive_process_is_not_a_rejection() {
+		// accepts connections but never answers.
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+		let params = ProxyEngineParams { endpoint, seal_fields: 1, timeout: Duration::from_millis(100) };
+		let engine = ProxyEngine::new(params, spec::new_test_machine()).unwrap();
+		assert!(is_unavailable(engine.verify_block_external(&Header::new())));
+
+		drop(listener);
+		assert!(is_unavailable(engine.verify_block_external(&Header::new())));
+	}
+
+	#[test]
+	fn rejects_unknown_endpoint_scheme() {
+		let params = ProxyEngineParams { endpoint: "http://localhost".into(), seal_fields: 1, timeout: Duration::from_secs(1) };
+		assert!(ProxyEngine::new(params, spec::new_test_machine()).is_err());
+	}
+}
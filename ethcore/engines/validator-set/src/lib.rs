@@ -173,6 +173,11 @@ pub trait ValidatorSet: Send + Sync + 'static {
 	fn report_malicious(&self, _validator: &Address, _set_block: BlockNumber, _block: BlockNumber, _proof: Bytes) {}
 	/// Notifies about benign misbehaviour.
 	fn report_benign(&self, _validator: &Address, _set_block: BlockNumber, _block: BlockNumber) {}
+	/// Switches to the given validator set from block `block` onwards. Only validator sets
+	/// changing at fork blocks support this.
+	fn schedule(&self, _block: BlockNumber, _set: Box<dyn ValidatorSet>) -> Result<(), String> {
+		Err("Validator set transitions can only be scheduled on a multi validator set".into())
+	}
 	/// Allows blockchain state access.
 	fn register_client(&self, _client: Weak<dyn EngineClient>) {}
 }
@@ -17,7 +17,7 @@
 /// Validator set changing at fork blocks.
 
 use std::collections::BTreeMap;
-use std::sync::Weak;
+use std::sync::{Arc, Weak};
 
 use common_types::{
 	BlockNumber,
@@ -39,16 +39,18 @@ use super::{SystemCall, ValidatorSet};
 type BlockNumberLookup = Box<dyn Fn(BlockId) -> Result<BlockNumber, String> + Send + Sync + 'static>;
 
 pub struct Multi {
-	sets: BTreeMap<BlockNumber, Box<dyn ValidatorSet>>,
+	sets: RwLock<BTreeMap<BlockNumber, Arc<dyn ValidatorSet>>>,
 	block_number: RwLock<BlockNumberLookup>,
+	client: RwLock<Option<Weak<dyn EngineClient>>>,
 }
 
 impl Multi {
 	pub fn new(set_map: BTreeMap<BlockNumber, Box<dyn ValidatorSet>>) -> Self {
 		assert!(set_map.get(&0u64).is_some(), "ValidatorSet has to be specified from block 0.");
 		Multi {
-			sets: set_map,
+			sets: RwLock::new(set_map.into_iter().map(|(block, set)| (block, Arc::from(set))).collect()),
 			block_number: RwLock::new(Box::new(move |_| Err("No client!".into()))),
+			client: RwLock::new(None),
 		}
 	}
 
@@ -57,10 +59,10 @@ impl Multi {
 	{
 		let (set_block, set) = self.correct_set_by_number(header.number());
 		let first = set_block == header.number();
-		func(set, first)
+		func(&*set, first)
 	}
 
-	fn correct_set(&self, id: BlockId) -> Option<&dyn ValidatorSet> {
+	fn correct_set(&self, id: BlockId) -> Option<Arc<dyn ValidatorSet>> {
 		match self.block_number.read()(id).map(|parent_block| self.correct_set_by_number(parent_block)) {
 			Ok((_, set)) => Some(set),
 			Err(e) => {
@@ -72,8 +74,9 @@ impl Multi {
 
 	// get correct set by block number, along with block number at which
 	// this set was activated.
-	fn correct_set_by_number(&self, parent_block: BlockNumber) -> (BlockNumber, &dyn ValidatorSet) {
-		let (block, set) = self.sets.iter()
+	fn correct_set_by_number(&self, parent_block: BlockNumber) -> (BlockNumber, Arc<dyn ValidatorSet>) {
+		let sets = self.sets.read();
+		let (block, set) = sets.iter()
 			.rev()
 			.find(|&(block, _)| *block <= parent_block + 1)
 			.expect("constructor validation ensures that there is at least one validator set for block 0;
@@ -81,7 +84,7 @@ impl Multi {
 					 qed");
 
 		trace!(target: "engine", "Multi ValidatorSet retrieved for block {}.", block);
-		(*block, &**set)
+		(*block, set.clone())
 	}
 }
 
@@ -157,10 +160,26 @@ impl ValidatorSet for Multi {
 		self.correct_set_by_number(set_block).1.report_benign(validator, set_block, block);
 	}
 
+	fn schedule(&self, block: BlockNumber, set: Box<dyn ValidatorSet>) -> Result<(), String> {
+		let mut sets = self.sets.write();
+		let last = *sets.keys().next_back().expect("constructor validation ensures that there is at least one validator set; qed");
+		if block <= last {
+			return Err(format!("Validator set transitions must be scheduled after the last one at block {}", last));
+		}
+
+		if let Some(client) = self.client.read().clone() {
+			set.register_client(client);
+		}
+		debug!(target: "engine", "Scheduled validator set transition at block {}", block);
+		sets.insert(block, Arc::from(set));
+		Ok(())
+	}
+
 	fn register_client(&self, client: Weak<dyn EngineClient>) {
-		for set in self.sets.values() {
+		for set in self.sets.read().values() {
 			set.register_client(client.clone());
 		}
+		*self.client.write() = Some(client.clone());
 		*self.block_number.write() = Box::new(move |id| client
 			.upgrade()
 			.ok_or_else(|| "No client!".into())
@@ -275,4 +294,25 @@ mod tests {
 		}
 		assert!(multi.is_epoch_end(false, &header).is_some());
 	}
+
+	#[test]
+	fn schedules_transition_after_last_one() {
+		use super::super::SimpleList;
+
+		let mut map: BTreeMap<_, Box<dyn ValidatorSet>> = BTreeMap::new();
+		let list1: Vec<_> = (0..10).map(|_| Address::random()).collect();
+		let list2: Vec<_> = (0..10).map(|_| Address::random()).collect();
+		map.insert(0, Box::new(SimpleList::new(list1.clone())));
+		map.insert(500, Box::new(SimpleList::new(list1)));
+
+		let multi = Multi::new(map);
+		assert!(multi.schedule(500, Box::new(SimpleList::new(list2.clone()))).is_err());
+		multi.schedule(800, Box::new(SimpleList::new(list2))).unwrap();
+
+		let mut header = Header::new();
+		header.set_number(799);
+		assert!(multi.is_epoch_end(false, &header).is_none());
+		header.set_number(800);
+		assert!(multi.is_epoch_end(false, &header).is_some());
+	}
 }
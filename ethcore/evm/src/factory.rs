@@ -20,7 +20,7 @@ use std::sync::Arc;
 use vm::{Exec, Schedule};
 use ethereum_types::U256;
 use super::vm::ActionParams;
-use super::interpreter::SharedCache;
+use super::interpreter::{CacheStats, SharedCache};
 
 /// Evm factory. Creates appropriate Evm.
 #[derive(Clone)]
@@ -47,16 +47,26 @@ impl Factory {
 		}
 	}
 
+	/// Create new instance of a factory using the given code cache.
+	pub fn with_cache(evm_cache: Arc<SharedCache>) -> Self {
+		Factory { evm_cache }
+	}
+
+	/// Statistics of the code cache used by this factory.
+	pub fn cache_stats(&self) -> CacheStats {
+		self.evm_cache.stats()
+	}
+
 	fn can_fit_in_usize(gas: &U256) -> bool {
 		gas == &U256::from(gas.low_u64() as usize)
 	}
 }
 
 impl Default for Factory {
-	/// Returns native rust evm factory
+	/// Returns native rust evm factory using the process-wide code cache.
 	fn default() -> Factory {
 		Factory {
-			evm_cache: Arc::new(SharedCache::default()),
+			evm_cache: SharedCache::global(),
 		}
 	}
 }
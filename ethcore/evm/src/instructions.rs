@@ -339,6 +339,10 @@ enum_with_from_u8! {
 		DELEGATECALL = 0xf4,
 		#[doc = "create a new account and set creation address to sha3(sender + sha3(init code)) % 2**160"]
 		CREATE2 = 0xf5,
+		#[doc = "authorize the following AUTHCALLs to be made on behalf of the signer of a commit (EIP-3074)"]
+		AUTH = 0xf6,
+		#[doc = "like CALL but with the authorized account as the sender (EIP-3074)"]
+		AUTHCALL = 0xf7,
 		#[doc = "stop execution and revert state changes. Return output data."]
 		REVERT = 0xfd,
 		#[doc = "like CALL but it does not take value, nor modify the state"]
@@ -604,6 +608,8 @@ lazy_static! {
 		arr[SUICIDE as usize] = Some(InstructionInfo::new("SUICIDE", 1, 0, GasPriceTier::Special));
 		arr[CREATE2 as usize] = Some(InstructionInfo::new("CREATE2", 4, 1, GasPriceTier::Special));
 		arr[REVERT as usize] = Some(InstructionInfo::new("REVERT", 2, 0, GasPriceTier::Zero));
+		arr[AUTH as usize] = Some(InstructionInfo::new("AUTH", 4, 1, GasPriceTier::Special));
+		arr[AUTHCALL as usize] = Some(InstructionInfo::new("AUTHCALL", 8, 1, GasPriceTier::Special));
 		arr
 	};
 }
@@ -226,6 +226,30 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 
 				Request::GasMemProvide(gas, mem, Some(requested))
 			},
+			instructions::AUTHCALL => {
+				let mut gas = Gas::from(schedule.call_gas);
+				let mem = cmp::max(
+					mem_needed(stack.peek(6), stack.peek(7))?, // ret_off, ret_size
+					mem_needed(stack.peek(4), stack.peek(5))?  // args_off, args_size
+				);
+
+				let address = u256_to_address(stack.peek(1));
+				let is_value_transfer = !stack.peek(2).is_zero();
+
+				if is_value_transfer {
+					if !ext.exists_and_not_null(&address)? {
+						gas = overflowing!(gas.overflow_add(schedule.call_new_account_gas.into()));
+					}
+					gas = overflowing!(gas.overflow_add(schedule.call_value_transfer_gas.into()));
+				}
+
+				let requested = *stack.peek(0);
+
+				Request::GasMemProvide(gas, mem, Some(requested))
+			},
+			instructions::AUTH => {
+				Request::Gas(Gas::from(schedule.auth_gas))
+			},
 			instructions::DELEGATECALL => {
 				let gas = Gas::from(schedule.call_gas);
 				let mem = cmp::max(
@@ -28,6 +28,7 @@ use std::{cmp, mem};
 use std::sync::Arc;
 use std::convert::TryFrom;
 use hash::keccak;
+use crypto::publickey::{public_to_address, recover, Signature};
 use bytes::Bytes;
 use ethereum_types::{U256, U512, H256, Address, BigEndianHash};
 
@@ -43,7 +44,7 @@ use instructions::{self, Instruction, InstructionInfo};
 use self::gasometer::Gasometer;
 use self::stack::{Stack, VecStack};
 use self::memory::Memory;
-pub use self::shared_cache::SharedCache;
+pub use self::shared_cache::{AnalyzedCode, CacheStats, SharedCache};
 
 use bit_set::BitSet;
 
@@ -66,6 +67,9 @@ const TWO_POW_248: U256 = U256([0, 0, 0, 0x100000000000000]); //0x1 00000000 000
 /// https://eips.ethereum.org/EIPS/eip-2315.
 pub const MAX_SUB_STACK_SIZE : usize = 1023;
 
+/// Prefix of the message signed to authorize AUTHCALLs (EIP-3074).
+const AUTH_MAGIC: u8 = 0x03;
+
 /// Abstraction over raw vector of Bytes. Easier state management of PC.
 struct CodeReader {
 	position: ProgramCounter,
@@ -184,8 +188,8 @@ pub struct Interpreter<Cost: CostType> {
 	informant: informant::EvmInformant,
 	do_trace: bool,
 	done: bool,
-	valid_jump_destinations: Option<Arc<BitSet>>,
-	valid_subroutine_destinations: Option<Arc<BitSet>>,
+	analyzed_code: Option<Arc<AnalyzedCode>>,
+	authorized: Option<Address>,
 	gasometer: Option<Gasometer<Cost>>,
 	stack: VecStack<U256>,
 	return_stack: Vec<usize>,
@@ -278,14 +282,14 @@ impl<Cost: CostType> Interpreter<Cost> {
 		let reader = CodeReader::new(params.code.take().expect("VM always called with code; qed"));
 		let params = InterpreterParams::from(params);
 		let informant = informant::EvmInformant::new(depth);
-		let valid_jump_destinations = None;
-		let valid_subroutine_destinations = None;
+		let analyzed_code = None;
 		let gasometer = Cost::from_u256(params.gas).ok().map(|gas| Gasometer::<Cost>::new(gas));
 		let stack = VecStack::with_capacity(schedule.stack_limit, U256::zero());
 		let return_stack = Vec::with_capacity(MAX_SUB_STACK_SIZE);
 		Interpreter {
 			cache, params, reader, informant,
-			valid_jump_destinations, valid_subroutine_destinations,
+			analyzed_code,
+			authorized: None,
 			gasometer, stack, return_stack,
 			done: false,
 			// Overridden in `step_inner` based on
@@ -412,22 +416,22 @@ impl<Cost: CostType> Interpreter<Cost> {
 		// Advance
 		match result {
 			InstructionResult::JumpToPosition(position) => {
-				if self.valid_jump_destinations.is_none() {
-					self.valid_jump_destinations = Some(self.cache.jump_and_sub_destinations(&self.params.code_hash, &self.reader.code).0);
+				if self.analyzed_code.is_none() {
+					self.analyzed_code = Some(self.cache.analyzed_code(&self.params.code_hash, &self.reader.code));
 				}
-				let jump_destinations = self.valid_jump_destinations.as_ref().expect("jump_destinations are initialized on first jump; qed");
-				let pos = match self.verify_jump(position, jump_destinations) {
+				let analyzed_code = self.analyzed_code.as_ref().expect("analyzed_code is initialized on first jump; qed");
+				let pos = match self.verify_jump(position, analyzed_code.jump_destinations()) {
 					Ok(x) => x,
 					Err(e) => return InterpreterResult::Done(Err(e))
 				};
 				self.reader.position = pos;
 			},
 			InstructionResult::JumpToSubroutine(position) => {
-				if self.valid_subroutine_destinations.is_none() {
-					self.valid_subroutine_destinations = Some(self.cache.jump_and_sub_destinations(&self.params.code_hash, &self.reader.code).1);
+				if self.analyzed_code.is_none() {
+					self.analyzed_code = Some(self.cache.analyzed_code(&self.params.code_hash, &self.reader.code));
 				}
-				let subroutine_destinations = self.valid_subroutine_destinations.as_ref().expect("subroutine_destinations are initialized on first jump; qed");
-				let pos = match self.verify_jump(position, subroutine_destinations) {
+				let analyzed_code = self.analyzed_code.as_ref().expect("analyzed_code is initialized on first jump; qed");
+				let pos = match self.verify_jump(position, analyzed_code.sub_entrypoints()) {
 					Ok(x) => x,
 					Err(e) => return InterpreterResult::Done(Err(e))
 				};
@@ -471,7 +475,8 @@ impl<Cost: CostType> Interpreter<Cost> {
 			(instruction == EXTCODEHASH && !schedule.have_extcodehash) ||
 			(instruction == CHAINID && !schedule.have_chain_id) ||
 			(instruction == SELFBALANCE && !schedule.have_selfbalance) ||
-			((instruction == BEGINSUB || instruction == JUMPSUB || instruction == RETURNSUB) && !schedule.have_subs)
+			((instruction == BEGINSUB || instruction == JUMPSUB || instruction == RETURNSUB) && !schedule.have_subs) ||
+			((instruction == AUTH || instruction == AUTHCALL) && !schedule.have_auth)
 		{
 			return Err(vm::Error::BadInstruction {
 				instruction: instruction as u8
@@ -507,6 +512,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 			instructions::EXTCODECOPY => Some((read(1), read(3))),
 			instructions::CALL | instructions::CALLCODE => Some((read(5), read(6))),
 			instructions::DELEGATECALL | instructions::STATICCALL => Some((read(4), read(5))),
+			instructions::AUTHCALL => Some((read(6), read(7))),
 			_ => None,
 		};
 
@@ -634,7 +640,16 @@ impl<Cost: CostType> Interpreter<Cost> {
 					},
 				};
 			},
-			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL => {
+			instructions::AUTH => {
+				let commit = self.stack.pop_back();
+				let y_parity = self.stack.pop_back();
+				let r = self.stack.pop_back();
+				let s = self.stack.pop_back();
+
+				self.authorized = auth_signer(ext.chain_id(), &self.params.address, &commit, &y_parity, &r, &s);
+				self.stack.push(self.authorized.map_or_else(U256::zero, address_to_u256));
+			},
+			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL | instructions::AUTHCALL => {
 				assert!(ext.schedule().call_value_transfer_gas > ext.schedule().call_stipend, "overflow possible");
 
 				self.stack.pop_back();
@@ -650,6 +665,11 @@ impl<Cost: CostType> Interpreter<Cost> {
 					Some(self.stack.pop_back())
 				};
 
+				// `valueExt` is reserved for future extensions and must be zero.
+				if instruction == instructions::AUTHCALL && !self.stack.pop_back().is_zero() {
+					return Err(vm::Error::BadInstruction { instruction: instruction as u8 });
+				}
+
 				let in_off = self.stack.pop_back();
 				let in_size = self.stack.pop_back();
 				let out_off = self.stack.pop_back();
@@ -676,6 +696,15 @@ impl<Cost: CostType> Interpreter<Cost> {
 					},
 					instructions::DELEGATECALL => (&self.params.sender, &self.params.address, true, ActionType::DelegateCall),
 					instructions::STATICCALL => (&self.params.address, &code_address, true, ActionType::StaticCall),
+					instructions::AUTHCALL => {
+						// calling without a successful AUTH in the current frame is an exceptional halt.
+						let authorized = self.authorized.as_ref().ok_or(vm::Error::BadInstruction { instruction: instruction as u8 })?;
+						if ext.is_static() && value.map_or(false, |v| !v.is_zero()) {
+							return Err(vm::Error::MutableCallInStaticContext);
+						}
+						let has_balance = ext.balance(authorized)? >= value.expect("value set for all but delegate call; qed");
+						(authorized, &code_address, has_balance, ActionType::AuthCall)
+					},
 					_ => panic!(format!("Unexpected instruction {:?} in CALL branch.", instruction))
 				};
 
@@ -1271,6 +1300,28 @@ fn address_to_u256(value: Address) -> U256 {
 	H256::from(value).into_uint()
 }
 
+/// Recover the account authorizing `invoker` to send AUTHCALLs on its behalf. The signed message is
+/// `keccak256(AUTH_MAGIC || chain_id || invoker || commit)`, with the chain id and the invoker padded
+/// to 32 bytes. Returns `None` for invalid or malleable signatures.
+fn auth_signer(chain_id: u64, invoker: &Address, commit: &U256, y_parity: &U256, r: &U256, s: &U256) -> Option<Address> {
+	if *y_parity > U256::one() {
+		return None;
+	}
+
+	let mut message = [0u8; 97];
+	message[0] = AUTH_MAGIC;
+	U256::from(chain_id).to_big_endian(&mut message[1..33]);
+	message[45..65].copy_from_slice(invoker.as_bytes());
+	commit.to_big_endian(&mut message[65..97]);
+
+	let signature = Signature::from_rsv(&BigEndianHash::from_uint(r), &BigEndianHash::from_uint(s), y_parity.low_u64() as u8);
+	if !signature.is_valid() || !signature.is_low_s() {
+		return None;
+	}
+
+	recover(&signature, &keccak(&message[..])).ok().map(|public| public_to_address(&public))
+}
+
 #[cfg(test)]
 mod tests {
 	use std::sync::Arc;
@@ -15,6 +15,7 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use hash::KECCAK_EMPTY;
 use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
 use ethereum_types::H256;
@@ -24,61 +25,118 @@ use bit_set::BitSet;
 use super::super::instructions::{self, Instruction};
 
 const DEFAULT_CACHE_SIZE: usize = 4 * 1024 * 1024;
+/// Number of independently locked parts of the cache, so that executions running
+/// on different threads rarely wait for each other.
+const SHARDS: usize = 16;
 
-/// Stub for a sharing `BitSet` data in cache (reference counted)
+lazy_static! {
+	static ref GLOBAL_CACHE: Arc<SharedCache> = Arc::new(SharedCache::default());
+}
+
+/// Result of the analysis of contract code needed to execute it.
+pub struct AnalyzedCode {
+	jump_destinations: BitSet,
+	sub_entrypoints: BitSet,
+}
+
+impl AnalyzedCode {
+	/// Valid jump destinations of the code.
+	pub fn jump_destinations(&self) -> &BitSet {
+		&self.jump_destinations
+	}
+
+	/// Valid subroutine entrypoints of the code.
+	pub fn sub_entrypoints(&self) -> &BitSet {
+		&self.sub_entrypoints
+	}
+}
+
+/// Stub for sharing `AnalyzedCode` in cache (reference counted)
 /// and implementing MallocSizeOf on it.
 #[derive(Clone)]
-struct Bits(Arc<BitSet>);
+struct CacheItem(Arc<AnalyzedCode>);
 
-impl MallocSizeOf for Bits {
+impl MallocSizeOf for CacheItem {
 	fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
-		// dealing in bits here
-		self.0.capacity() * 8
+		// capacity is given in bits
+		(self.0.jump_destinations.capacity() + self.0.sub_entrypoints.capacity()) / 8
 	}
 }
 
-#[derive(MallocSizeOf, Clone)]
-struct CacheItem {
-	jump_destination: Bits,
-	sub_entrypoint: Bits,
+/// Statistics of the code cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CacheStats {
+	/// Number of lookups served from the cache.
+	pub hits: u64,
+	/// Number of lookups which required analyzing the code.
+	pub misses: u64,
+	/// Memory used by the cached entries, in bytes.
+	pub size: usize,
+	/// Maximal memory used by the cache, in bytes.
+	pub max_size: usize,
 }
-/// Global cache for EVM interpreter
+
+/// Cache of analyzed contract code, keyed by code hash and shared by all
+/// the interpreters created by the factories holding it.
 pub struct SharedCache {
-	jump_destinations: Mutex<MemoryLruCache<H256, CacheItem>>,
+	shards: Vec<Mutex<MemoryLruCache<H256, CacheItem>>>,
+	max_size: usize,
+	hits: AtomicU64,
+	misses: AtomicU64,
 }
 
 impl SharedCache {
-	/// Create a jump destinations cache with a maximum size in bytes
+	/// Create a code cache with a maximum size in bytes
 	/// to cache.
 	pub fn new(max_size: usize) -> Self {
 		SharedCache {
-			jump_destinations: Mutex::new(MemoryLruCache::new(max_size)),
+			shards: (0..SHARDS).map(|_| Mutex::new(MemoryLruCache::new(max_size / SHARDS))).collect(),
+			max_size,
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
 		}
 	}
 
-	/// Get jump destinations bitmap for a contract.
-	pub fn jump_and_sub_destinations(&self, code_hash: &Option<H256>, code: &[u8]) -> (Arc<BitSet>, Arc<BitSet>) {
-		if let Some(ref code_hash) = code_hash {
-			if code_hash == &KECCAK_EMPTY {
-				let cache_item = Self::find_jump_and_sub_destinations(code);
-				return (cache_item.jump_destination.0, cache_item.sub_entrypoint.0);
-			}
+	/// Process-wide cache used by the default factories.
+	pub fn global() -> Arc<SharedCache> {
+		GLOBAL_CACHE.clone()
+	}
 
-			if let Some(d) = self.jump_destinations.lock().get_mut(code_hash) {
-				return (d.jump_destination.0.clone(), d.sub_entrypoint.0.clone());
-			}
+	/// Get the analyzed code of a contract, analyzing it if it's not cached yet.
+	pub fn analyzed_code(&self, code_hash: &Option<H256>, code: &[u8]) -> Arc<AnalyzedCode> {
+		let code_hash = match code_hash {
+			Some(ref code_hash) if code_hash != &KECCAK_EMPTY => code_hash,
+			_ => return Arc::new(Self::find_jump_and_sub_destinations(code)),
+		};
+
+		if let Some(item) = self.shard(code_hash).lock().get_mut(code_hash) {
+			self.hits.fetch_add(1, Ordering::Relaxed);
+			return item.0.clone();
 		}
 
-		let d = Self::find_jump_and_sub_destinations(code);
+		self.misses.fetch_add(1, Ordering::Relaxed);
+		// analysis happens outside of the lock; concurrent misses on the same code
+		// only lead to the same entry being inserted twice.
+		let analyzed = Arc::new(Self::find_jump_and_sub_destinations(code));
+		self.shard(code_hash).lock().insert(*code_hash, CacheItem(analyzed.clone()));
+		analyzed
+	}
 
-		if let Some(ref code_hash) = code_hash {
-			self.jump_destinations.lock().insert(*code_hash, d.clone());
+	/// Hit, miss and memory statistics of the cache.
+	pub fn stats(&self) -> CacheStats {
+		CacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			size: self.shards.iter().map(|shard| shard.lock().current_size()).sum(),
+			max_size: self.max_size,
 		}
+	}
 
-		(d.jump_destination.0, d.sub_entrypoint.0)
+	fn shard(&self, code_hash: &H256) -> &Mutex<MemoryLruCache<H256, CacheItem>> {
+		&self.shards[code_hash[0] as usize % SHARDS]
 	}
 
-	fn find_jump_and_sub_destinations(code: &[u8]) -> CacheItem {
+	fn find_jump_and_sub_destinations(code: &[u8]) -> AnalyzedCode {
 		let mut jump_dests = BitSet::with_capacity(code.len());
 		let mut sub_entrypoints = BitSet::with_capacity(code.len());
 		let mut position = 0;
@@ -101,9 +159,10 @@ impl SharedCache {
 		}
 
 		jump_dests.shrink_to_fit();
-		CacheItem {
-			jump_destination: Bits(Arc::new(jump_dests)),
-			sub_entrypoint: Bits(Arc::new(sub_entrypoints)),
+		sub_entrypoints.shrink_to_fit();
+		AnalyzedCode {
+			jump_destinations: jump_dests,
+			sub_entrypoints,
 		}
 	}
 }
@@ -130,8 +189,8 @@ fn test_find_jump_destinations() {
 	let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
 
 	// then
-	assert!(cache_item.jump_destination.0.iter().eq(vec![66].into_iter()));
-	assert!(cache_item.sub_entrypoint.0.is_empty());
+	assert!(cache_item.jump_destinations.iter().eq(vec![66].into_iter()));
+	assert!(cache_item.sub_entrypoints.is_empty());
 }
 
 #[test]
@@ -151,8 +210,8 @@ fn test_find_jump_destinations_not_in_data_segments() {
 	let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
 
 	// then
-	assert!(cache_item.jump_destination.0.iter().eq(vec![6].into_iter()));
-	assert!(cache_item.sub_entrypoint.0.is_empty());
+	assert!(cache_item.jump_destinations.iter().eq(vec![6].into_iter()));
+	assert!(cache_item.sub_entrypoints.is_empty());
 }
 
 #[test]
@@ -166,8 +225,8 @@ fn test_find_sub_entrypoints() {
 	let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
 
 	// then
-	assert!(cache_item.jump_destination.0.is_empty());
-	assert!(cache_item.sub_entrypoint.0.iter().eq(vec![12, 17].into_iter()));
+	assert!(cache_item.jump_destinations.is_empty());
+	assert!(cache_item.sub_entrypoints.iter().eq(vec![12, 17].into_iter()));
 }
 
 #[test]
@@ -186,6 +245,26 @@ fn test_find_jump_and_sub_allowing_unknown_opcodes() {
 	let cache_item = SharedCache::find_jump_and_sub_destinations(&code);
 
 	// then
-	assert!(cache_item.jump_destination.0.iter().eq(vec![0].into_iter()));
-	assert!(cache_item.sub_entrypoint.0.iter().eq(vec![2].into_iter()));
+	assert!(cache_item.jump_destinations.iter().eq(vec![0].into_iter()));
+	assert!(cache_item.sub_entrypoints.iter().eq(vec![2].into_iter()));
+}
+
+#[test]
+fn test_cache_shares_analyzed_code() {
+	let cache = SharedCache::default();
+	let code = hex!("600656605B565B6004");
+	let code_hash = Some(H256::from_low_u64_be(1));
+
+	let first = cache.analyzed_code(&code_hash, &code);
+	let second = cache.analyzed_code(&code_hash, &code);
+	assert!(Arc::ptr_eq(&first, &second));
+
+	// code without a known hash is never cached.
+	cache.analyzed_code(&None, &code);
+
+	let stats = cache.stats();
+	assert_eq!(stats.hits, 1);
+	assert_eq!(stats.misses, 1);
+	assert!(stats.size > 0);
+	assert_eq!(stats.max_size, DEFAULT_CACHE_SIZE);
 }
@@ -24,6 +24,7 @@ extern crate vm;
 extern crate keccak_hash as hash;
 extern crate memory_cache;
 extern crate parity_bytes as bytes;
+extern crate parity_crypto as crypto;
 
 #[macro_use]
 extern crate lazy_static;
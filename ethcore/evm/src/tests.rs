@@ -24,6 +24,8 @@ use vm::{self, ActionParams, ActionValue, Ext};
 use vm::tests::{FakeExt, FakeCall, FakeCallType, test_finalize};
 use factory::Factory;
 use hex_literal::hex;
+use hash::keccak;
+use crypto::publickey::{public_to_address, sign, KeyPair, Secret};
 use super::interpreter::MAX_SUB_STACK_SIZE;
 
 evm_test!{test_add: test_add_int}
@@ -912,6 +914,75 @@ fn test_subs_substack_out(factory: super::Factory) {
 	assert_eq!(current, expected);
 }
 
+evm_test!{test_auth_call: test_auth_call_int}
+fn test_auth_call(factory: super::Factory) {
+	let invoker = Address::from_low_u64_be(0x1234);
+	let commit = H256::from_low_u64_be(0xc0ffee);
+	let key_pair = KeyPair::from_secret(Secret::from_str("4d5db4107d237df6a3d58ee5f70ae63d73d7658d4026f2eefd2f204c81682cb7").unwrap()).unwrap();
+
+	let mut message = vec![0x03];
+	message.extend_from_slice(H256::from_low_u64_be(7).as_bytes());
+	message.extend_from_slice(H256::from(invoker).as_bytes());
+	message.extend_from_slice(commit.as_bytes());
+	let signature = sign(key_pair.secret(), &keccak(&message)).unwrap();
+
+	// 7f <s> 7f <r> 60 <v> 7f <commit>          PUSH s, r, yParity, commit
+	// f6 60 00 55                               AUTH, PUSH1 0, SSTORE
+	// 60 00 (x6) 60 0f 61 10 00 f7 60 01 55     AUTHCALL to 0x0f, PUSH1 1, SSTORE
+	let mut code = vec![0x7f];
+	code.extend_from_slice(signature.s());
+	code.push(0x7f);
+	code.extend_from_slice(signature.r());
+	code.extend_from_slice(&[0x60, signature.v(), 0x7f]);
+	code.extend_from_slice(commit.as_bytes());
+	code.extend_from_slice(&hex!("f6600055 600060006000600060006000600f611000f7 600155"));
+
+	let mut params = ActionParams::default();
+	params.address = invoker;
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_berlin().with_chain_id(7);
+	ext.schedule.have_auth = true;
+
+	{
+		let vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap();
+	}
+
+	let signer = public_to_address(key_pair.public());
+	assert_store(&ext, 0, &format!("{:x}", H256::from(signer)));
+	assert_store(&ext, 1, "0000000000000000000000000000000000000000000000000000000000000001");
+	let call = ext.calls.iter().next().unwrap();
+	assert_eq!(call.sender_address, Some(signer));
+	assert_eq!(call.receive_address, Some(Address::from_low_u64_be(0x0f)));
+}
+
+evm_test!{test_authcall_requires_auth: test_authcall_requires_auth_int}
+fn test_authcall_requires_auth(factory: super::Factory) {
+	// AUTH with an invalid signature, then AUTHCALL.
+	let code = hex!("6000600060006000f6 600055 600060006000600060006000600f611000f7").to_vec();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code.clone()));
+	let mut ext = FakeExt::new_berlin();
+
+	// the opcodes are disabled unless enabled by the chain spec.
+	let current = {
+		let vm = factory.create(params.clone(), ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap())
+	};
+	assert_eq!(current, Result::Err(vm::Error::BadInstruction { instruction: 0xf6 }));
+
+	ext.schedule.have_auth = true;
+	let current = {
+		let vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap())
+	};
+	assert_eq!(current, Result::Err(vm::Error::BadInstruction { instruction: 0xf7 }));
+	assert!(ext.calls.is_empty());
+}
+
 evm_test!{test_subs_sub_at_end: test_subs_sub_at_end_int}
 fn test_subs_sub_at_end(factory: super::Factory) {
 	let code = hex!("6005565c5d5b60035e").to_vec();
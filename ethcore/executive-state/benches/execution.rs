@@ -72,6 +72,7 @@ fn build_env_info(header: &Header) -> vm::EnvInfo {
 		gas_limit: *header.gas_limit() * 10,
 		last_hashes: std::sync::Arc::new(vec![]),
 		gas_used: *header.gas_used(),
+		base_fee: None,
 	}
 }
 
@@ -1163,6 +1163,62 @@ mod tests {
 		assert_eq!(state.nonce(&a).unwrap(), U256::from(1u64));
 	}
 
+	#[test]
+	fn should_track_reads_and_committed_addresses() {
+		let a = Address::from_low_u64_be(1);
+		let b = Address::from_low_u64_be(2);
+		let c = Address::from_low_u64_be(3);
+		let mut state = get_temp_state();
+		state.track_reads();
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+		state.balance(&b).unwrap();
+		state.storage_at(&c, &H256::zero()).unwrap();
+		state.commit().unwrap();
+
+		assert_eq!(state.committed_addresses(), vec![a]);
+		let reads = state.reads();
+		assert_eq!(reads.accounts.into_iter().collect::<Vec<_>>(), vec![a, b]);
+		assert_eq!(reads.storage.into_iter().collect::<Vec<_>>(), vec![(c, H256::zero())]);
+	}
+
+	#[test]
+	fn should_track_written_storage_across_commits() {
+		let a = Address::from_low_u64_be(1);
+		let b = Address::from_low_u64_be(2);
+		let key = H256::from_low_u64_be(7);
+		let mut state = get_temp_state();
+		state.track_writes();
+		state.set_storage(&a, key, H256::from_low_u64_be(1)).unwrap();
+		state.commit().unwrap();
+		state.add_balance(&b, &U256::from(1u64), CleanupMode::NoEmpty).unwrap();
+		state.storage_at(&a, &H256::zero()).unwrap();
+		state.commit().unwrap();
+
+		let written = state.written_storage();
+		assert_eq!(written.keys().cloned().collect::<Vec<_>>(), vec![a, b]);
+		assert_eq!(written[&a].iter().cloned().collect::<Vec<_>>(), vec![key]);
+		assert!(written[&b].is_empty());
+	}
+
+	#[test]
+	fn should_not_report_storage_writes_as_changed_accounts() {
+		let a = Address::from_low_u64_be(1);
+		let key = H256::from_low_u64_be(7);
+		let mut state = get_temp_state();
+		state.add_balance(&a, &U256::from(1u64), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+
+		state.track_writes();
+		state.set_storage(&a, key, H256::from_low_u64_be(1)).unwrap();
+		state.commit().unwrap();
+		assert!(state.changed_accounts().is_empty());
+		assert_eq!(state.written_storage()[&a].iter().cloned().collect::<Vec<_>>(), vec![key]);
+
+		state.add_balance(&a, &U256::from(1u64), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+		assert_eq!(state.changed_accounts().into_iter().collect::<Vec<_>>(), vec![a]);
+	}
+
 	#[test]
 	fn remove() {
 		let a = Address::zero();
@@ -1209,6 +1265,60 @@ mod tests {
 		assert!(!state.exists_and_not_null(&a).unwrap());
 	}
 
+	struct MockFork;
+
+	impl account_state::fork::ForkSource for MockFork {
+		fn account(&self, address: &Address) -> Result<Option<account_state::fork::RemoteAccount>, String> {
+			Ok(Some(account_state::fork::RemoteAccount {
+				balance: U256::from(address.to_low_u64_be()),
+				nonce: U256::from(7),
+				code: vec![0x60, 0x00],
+			}))
+		}
+
+		fn storage_at(&self, _address: &Address, key: &H256) -> Result<H256, String> {
+			Ok(BigEndianHash::from_uint(&(key.into_uint() + 1)))
+		}
+	}
+
+	#[test]
+	fn forked_state_reads_untouched_values_from_the_fork() {
+		let a = Address::from_low_u64_be(10);
+		let b = Address::from_low_u64_be(20);
+		let c = Address::from_low_u64_be(30);
+		let one = H256::from_low_u64_be(1);
+		let two = H256::from_low_u64_be(2);
+		let mut db = get_temp_state_db();
+		db.set_fork(Arc::new(MockFork));
+
+		let (root, db) = {
+			let mut state = State::new(db, U256::from(0), Default::default());
+			assert_eq!(state.balance(&a).unwrap(), U256::from(10));
+			assert_eq!(state.nonce(&a).unwrap(), U256::from(7));
+			assert_eq!(state.code(&a).unwrap().map(|c| (*c).clone()), Some(vec![0x60, 0x00]));
+			assert_eq!(state.storage_at(&a, &one).unwrap(), two);
+
+			state.add_balance(&a, &U256::from(5), CleanupMode::NoEmpty).unwrap();
+			state.set_storage(&a, one, H256::zero()).unwrap();
+			state.set_storage(&a, two, H256::from_low_u64_be(9)).unwrap();
+			state.kill_account(&b);
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		assert_eq!(state.balance(&a).unwrap(), U256::from(15));
+		assert_eq!(state.nonce(&a).unwrap(), U256::from(7));
+		// cleared locally
+		assert_eq!(state.storage_at(&a, &one).unwrap(), H256::zero());
+		assert_eq!(state.storage_at(&a, &two).unwrap(), H256::from_low_u64_be(9));
+		assert_eq!(state.storage_at(&a, &H256::from_low_u64_be(3)).unwrap(), H256::from_low_u64_be(4));
+		// removed locally
+		assert!(!state.exists(&b).unwrap());
+		assert_eq!(state.storage_at(&b, &one).unwrap(), H256::zero());
+		assert_eq!(state.balance(&c).unwrap(), U256::from(30));
+	}
+
 	#[test]
 	fn remove_from_database() {
 		let a = Address::zero();
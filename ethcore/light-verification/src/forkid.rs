@@ -0,0 +1,196 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fork identifiers as defined by EIP-2124.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{BlockNumber, H256};
+
+/// CRC32 checksum of the genesis hash and the fork blocks passed so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForkHash(pub u32);
+
+/// Fork identifier announced to peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForkId {
+	/// Checksum of the passed forks.
+	pub hash: ForkHash,
+	/// Next scheduled fork, or 0 if none is known.
+	pub next: BlockNumber,
+}
+
+/// Reason for rejecting the fork id of a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+	/// The peer is on our chain but hasn't upgraded for a fork we passed.
+	RemoteStale,
+	/// The peer is on another chain, or we haven't upgraded for a fork it passed.
+	LocalIncompatibleOrStale,
+}
+
+impl fmt::Display for RejectReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			RejectReason::RemoteStale => write!(f, "Remote node is stale"),
+			RejectReason::LocalIncompatibleOrStale => write!(f, "Local node is incompatible or stale"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RejectReason {}
+
+fn crc32(crc: u32, data: &[u8]) -> u32 {
+	let mut crc = !crc;
+	for byte in data {
+		crc ^= u32::from(*byte);
+		for _ in 0..8 {
+			crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+		}
+	}
+	!crc
+}
+
+/// Fork blocks in the order they are hashed in: sorted, deduplicated and without forks at genesis.
+fn normalize(forks: &[BlockNumber]) -> Vec<BlockNumber> {
+	let mut forks = forks.iter().cloned().filter(|block| *block > 0).collect::<Vec<_>>();
+	forks.sort();
+	forks.dedup();
+	forks
+}
+
+/// Checksums after each prefix of `forks`, starting with the one of the genesis hash alone.
+fn checksums(genesis: &H256, forks: &[BlockNumber]) -> Vec<ForkHash> {
+	let mut hash = crc32(0, genesis.as_bytes());
+	let mut sums = Vec::with_capacity(forks.len() + 1);
+	sums.push(ForkHash(hash));
+	for fork in forks {
+		hash = crc32(hash, &fork.to_be_bytes());
+		sums.push(ForkHash(hash));
+	}
+	sums
+}
+
+/// Fork id at block `head` of a chain with the given genesis hash and fork blocks.
+pub fn fork_id(genesis: &H256, forks: &[BlockNumber], head: BlockNumber) -> ForkId {
+	let forks = normalize(forks);
+	let passed = forks.iter().take_while(|fork| **fork <= head).count();
+	ForkId {
+		hash: checksums(genesis, &forks)[passed],
+		next: forks.get(passed).cloned().unwrap_or(0),
+	}
+}
+
+/// Check the fork id of a peer against the local chain at block `head`.
+pub fn is_compatible(genesis: &H256, forks: &[BlockNumber], head: BlockNumber, remote: ForkId) -> Result<(), RejectReason> {
+	let forks = normalize(forks);
+	let sums = checksums(genesis, &forks);
+	let passed = forks.iter().take_while(|fork| **fork <= head).count();
+
+	// Same forks passed: the peer may only announce a fork we have already passed without
+	// having upgraded for it.
+	if sums[passed] == remote.hash {
+		if remote.next > 0 && head >= remote.next {
+			return Err(RejectReason::LocalIncompatibleOrStale);
+		}
+		return Ok(());
+	}
+	// The peer is behind us: it must be aware of the next fork it has to pass.
+	if let Some(index) = sums[..passed].iter().position(|sum| *sum == remote.hash) {
+		if forks[index] != remote.next {
+			return Err(RejectReason::RemoteStale);
+		}
+		return Ok(());
+	}
+	// The peer is ahead of us on forks we know of.
+	if sums[passed + 1..].contains(&remote.hash) {
+		return Ok(());
+	}
+	Err(RejectReason::LocalIncompatibleOrStale)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MAINNET_FORKS: [BlockNumber; 8] = [
+		1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000, 9_069_000, 9_200_000,
+	];
+
+	fn mainnet_genesis() -> H256 {
+		H256([
+			0xd4, 0xe5, 0x67, 0x40, 0xf8, 0x76, 0xae, 0xf8, 0xc0, 0x10, 0xb8, 0x6a, 0x40, 0xd5, 0xf5, 0x67,
+			0x45, 0xa1, 0x18, 0xd0, 0x90, 0x6a, 0x34, 0xe6, 0x9a, 0xec, 0x8c, 0x0d, 0xb1, 0xcb, 0x8f, 0xa3,
+		])
+	}
+
+	fn id(hash: u32, next: BlockNumber) -> ForkId {
+		ForkId { hash: ForkHash(hash), next }
+	}
+
+	#[test]
+	fn mainnet_fork_ids() {
+		let genesis = mainnet_genesis();
+		let cases = [
+			(0, id(0xfc64ec04, 1_150_000)),
+			(1_149_999, id(0xfc64ec04, 1_150_000)),
+			(1_150_000, id(0x97c2c34c, 1_920_000)),
+			(1_920_000, id(0x91d1f948, 2_463_000)),
+			(2_463_000, id(0x7a64da13, 2_675_000)),
+			(4_370_000, id(0xa00bc324, 7_280_000)),
+			(7_279_999, id(0xa00bc324, 7_280_000)),
+			(7_280_000, id(0x668db0af, 9_069_000)),
+			(9_069_000, id(0x879d6e30, 9_200_000)),
+			(9_200_000, id(0xe029e991, 0)),
+			(10_000_000, id(0xe029e991, 0)),
+		];
+		for (head, expected) in cases.iter() {
+			assert_eq!(fork_id(&genesis, &MAINNET_FORKS, *head), *expected, "head {}", head);
+		}
+	}
+
+	#[test]
+	fn ignores_order_duplicates_and_genesis_forks() {
+		let genesis = mainnet_genesis();
+		let mut forks = MAINNET_FORKS.to_vec();
+		forks.reverse();
+		forks.push(0);
+		forks.push(1_920_000);
+		assert_eq!(fork_id(&genesis, &forks, 7_280_000), id(0x668db0af, 9_069_000));
+	}
+
+	#[test]
+	fn eip2124_compatibility_rules() {
+		let genesis = mainnet_genesis();
+		let check = |head, remote| is_compatible(&genesis, &MAINNET_FORKS, head, remote);
+
+		// Same fork, the peer knows the next one or not.
+		assert_eq!(check(7_987_396, id(0x668db0af, 0)), Ok(()));
+		assert_eq!(check(7_987_396, id(0x668db0af, 9_069_000)), Ok(()));
+		// Same fork, the peer announces a fork we passed without upgrading.
+		assert_eq!(check(7_279_999, id(0xa00bc324, 7_279_999)), Err(RejectReason::LocalIncompatibleOrStale));
+		// The peer is behind but knows the fork it has to pass.
+		assert_eq!(check(7_987_396, id(0xa00bc324, 7_280_000)), Ok(()));
+		// The peer is behind and doesn't know the fork it has to pass.
+		assert_eq!(check(7_987_396, id(0xa00bc324, 0)), Err(RejectReason::RemoteStale));
+		// The peer is ahead on forks we know of.
+		assert_eq!(check(7_279_999, id(0x668db0af, 0)), Ok(()));
+		// Unknown chain.
+		assert_eq!(check(7_987_396, id(0xafec6b27, 0)), Err(RejectReason::LocalIncompatibleOrStale));
+	}
+}
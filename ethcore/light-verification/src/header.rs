@@ -0,0 +1,260 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Engine independent header checks.
+
+use core::fmt;
+
+use crate::{BlockNumber, U256};
+
+/// Fields of a header checked here, to be implemented by whatever header type the client decodes.
+pub trait HeaderFields {
+	/// Block number.
+	fn number(&self) -> BlockNumber;
+	/// Gas limit.
+	fn gas_limit(&self) -> U256;
+	/// Gas used.
+	fn gas_used(&self) -> U256;
+	/// Extra data.
+	fn extra_data(&self) -> &[u8];
+}
+
+/// Bounds of the gas limit of a header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasLimitBounds {
+	/// Minimum gas limit.
+	pub min: U256,
+	/// Maximum gas limit, if any.
+	pub max: Option<U256>,
+	/// Divisor of the parent gas limit bounding the change of gas limit between blocks.
+	pub bound_divisor: U256,
+}
+
+/// Chain rules a header is checked against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderRules {
+	/// Gas limit bounds, or `None` if the engine dictates the gas limit of the header.
+	pub gas_limit: Option<GasLimitBounds>,
+	/// Maximum size of the extra data, not enforced for the genesis.
+	pub maximum_extra_data_size: usize,
+	/// First block of the DAO hard fork, whose first ten blocks must have `dao-hard-fork` as extra data.
+	pub dao_hardfork_transition: Option<BlockNumber>,
+}
+
+/// Header verification error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderError {
+	/// Block number is out of bounds.
+	RidiculousNumber {
+		/// Minimum block number.
+		min: Option<BlockNumber>,
+		/// Maximum block number.
+		max: Option<BlockNumber>,
+		/// Block number found.
+		found: BlockNumber,
+	},
+	/// Block number is not the successor of the parent.
+	InvalidNumber {
+		/// Expected block number.
+		expected: BlockNumber,
+		/// Block number found.
+		found: BlockNumber,
+	},
+	/// More gas used than the gas limit.
+	TooMuchGasUsed {
+		/// Gas limit.
+		max: U256,
+		/// Gas used.
+		found: U256,
+	},
+	/// Gas limit is out of bounds.
+	InvalidGasLimit {
+		/// Minimum gas limit.
+		min: Option<U256>,
+		/// Maximum gas limit.
+		max: Option<U256>,
+		/// Gas limit found.
+		found: U256,
+	},
+	/// Extra data is too long, or is not the DAO hard fork marker.
+	ExtraDataOutOfBounds {
+		/// Maximum size of the extra data, `None` for the DAO hard fork marker.
+		max: Option<usize>,
+		/// Size of the extra data, 0 for the DAO hard fork marker.
+		found: usize,
+	},
+}
+
+impl fmt::Display for HeaderError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HeaderError::RidiculousNumber { found, .. } => write!(f, "Block number {} out of bounds", found),
+			HeaderError::InvalidNumber { expected, found } =>
+				write!(f, "Invalid block number: expected {}, found {}", expected, found),
+			HeaderError::TooMuchGasUsed { max, found } =>
+				write!(f, "Gas used {} exceeds the gas limit {}", found, max),
+			HeaderError::InvalidGasLimit { found, .. } => write!(f, "Gas limit {} out of bounds", found),
+			HeaderError::ExtraDataOutOfBounds { max: Some(max), found } =>
+				write!(f, "Extra data of {} bytes exceeds {} bytes", found, max),
+			HeaderError::ExtraDataOutOfBounds { max: None, .. } => write!(f, "Missing DAO hard fork extra data"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HeaderError {}
+
+/// Check the parameters of a header on their own.
+pub fn verify_header_params<H: HeaderFields + ?Sized>(header: &H, rules: &HeaderRules) -> Result<(), HeaderError> {
+	if header.number() >= BlockNumber::max_value() {
+		return Err(HeaderError::RidiculousNumber {
+			min: None,
+			max: Some(BlockNumber::max_value()),
+			found: header.number(),
+		});
+	}
+	if header.gas_used() > header.gas_limit() {
+		return Err(HeaderError::TooMuchGasUsed { max: header.gas_limit(), found: header.gas_used() });
+	}
+	if let Some(ref bounds) = rules.gas_limit {
+		if header.gas_limit() < bounds.min {
+			return Err(HeaderError::InvalidGasLimit { min: Some(bounds.min), max: None, found: header.gas_limit() });
+		}
+		if let Some(max) = bounds.max {
+			if header.gas_limit() > max {
+				return Err(HeaderError::InvalidGasLimit { min: None, max: Some(max), found: header.gas_limit() });
+			}
+		}
+	}
+	if header.number() != 0 && header.extra_data().len() > rules.maximum_extra_data_size {
+		return Err(HeaderError::ExtraDataOutOfBounds {
+			max: Some(rules.maximum_extra_data_size),
+			found: header.extra_data().len(),
+		});
+	}
+	if let Some(transition) = rules.dao_hardfork_transition {
+		if header.number() >= transition &&
+			header.number() <= transition + 9 &&
+			header.extra_data() != &b"dao-hard-fork"[..] {
+			return Err(HeaderError::ExtraDataOutOfBounds { max: None, found: 0 });
+		}
+	}
+	Ok(())
+}
+
+/// Check a header against its parent, looked up by `parent_hash`.
+pub fn verify_parent<H: HeaderFields + ?Sized>(header: &H, parent: &H, rules: &HeaderRules) -> Result<(), HeaderError> {
+	if header.number() != parent.number() + 1 {
+		return Err(HeaderError::InvalidNumber { expected: parent.number() + 1, found: header.number() });
+	}
+	if header.number() == 0 {
+		return Err(HeaderError::RidiculousNumber { min: Some(1), max: None, found: header.number() });
+	}
+	if let Some(ref bounds) = rules.gas_limit {
+		let parent_gas_limit = parent.gas_limit();
+		let min_gas = parent_gas_limit - parent_gas_limit / bounds.bound_divisor;
+		let max_gas = parent_gas_limit + parent_gas_limit / bounds.bound_divisor;
+		if header.gas_limit() <= min_gas || header.gas_limit() >= max_gas {
+			return Err(HeaderError::InvalidGasLimit { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit() });
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct TestHeader {
+		number: BlockNumber,
+		gas_limit: U256,
+		gas_used: U256,
+		extra_data: &'static [u8],
+	}
+
+	impl HeaderFields for TestHeader {
+		fn number(&self) -> BlockNumber { self.number }
+		fn gas_limit(&self) -> U256 { self.gas_limit }
+		fn gas_used(&self) -> U256 { self.gas_used }
+		fn extra_data(&self) -> &[u8] { self.extra_data }
+	}
+
+	fn header(number: BlockNumber, gas_limit: u64) -> TestHeader {
+		TestHeader { number, gas_limit: gas_limit.into(), gas_used: 0.into(), extra_data: b"" }
+	}
+
+	fn rules() -> HeaderRules {
+		HeaderRules {
+			gas_limit: Some(GasLimitBounds { min: 5000.into(), max: None, bound_divisor: 1024.into() }),
+			maximum_extra_data_size: 32,
+			dao_hardfork_transition: Some(100),
+		}
+	}
+
+	#[test]
+	fn checks_header_params() {
+		let rules = rules();
+		assert_eq!(verify_header_params(&header(1, 5000), &rules), Ok(()));
+		assert_eq!(
+			verify_header_params(&header(1, 4999), &rules),
+			Err(HeaderError::InvalidGasLimit { min: Some(5000.into()), max: None, found: 4999.into() })
+		);
+
+		let mut too_much_gas = header(1, 5000);
+		too_much_gas.gas_used = 5001.into();
+		assert_eq!(
+			verify_header_params(&too_much_gas, &rules),
+			Err(HeaderError::TooMuchGasUsed { max: 5000.into(), found: 5001.into() })
+		);
+
+		let mut long_extra = header(1, 5000);
+		long_extra.extra_data = &[0u8; 33];
+		assert_eq!(
+			verify_header_params(&long_extra, &rules),
+			Err(HeaderError::ExtraDataOutOfBounds { max: Some(32), found: 33 })
+		);
+		long_extra.number = 0;
+		assert_eq!(verify_header_params(&long_extra, &rules), Ok(()));
+	}
+
+	#[test]
+	fn checks_dao_extra_data() {
+		let rules = rules();
+		let mut dao = header(109, 5000);
+		assert_eq!(verify_header_params(&dao, &rules), Err(HeaderError::ExtraDataOutOfBounds { max: None, found: 0 }));
+		dao.extra_data = b"dao-hard-fork";
+		assert_eq!(verify_header_params(&dao, &rules), Ok(()));
+		assert_eq!(verify_header_params(&header(110, 5000), &rules), Ok(()));
+	}
+
+	#[test]
+	fn checks_against_parent() {
+		let rules = rules();
+		let parent = header(9, 1_024_000);
+		assert_eq!(verify_parent(&header(10, 1_024_999), &parent, &rules), Ok(()));
+		assert_eq!(
+			verify_parent(&header(11, 1_024_000), &parent, &rules),
+			Err(HeaderError::InvalidNumber { expected: 10, found: 11 })
+		);
+		assert_eq!(
+			verify_parent(&header(10, 1_025_000), &parent, &rules),
+			Err(HeaderError::InvalidGasLimit { min: Some(1_023_000.into()), max: Some(1_025_000.into()), found: 1_025_000.into() })
+		);
+
+		let engine_gas_limit = HeaderRules { gas_limit: None, ..rules };
+		assert_eq!(verify_parent(&header(10, 1_025_000), &parent, &engine_gas_limit), Ok(()));
+	}
+}
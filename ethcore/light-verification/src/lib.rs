@@ -0,0 +1,47 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of headers, fork ids and receipt proofs shared by the full node and light clients.
+//!
+//! Nothing here depends on the standard library, so with `default-features = false` the crate
+//! builds for `wasm32-unknown-unknown` and other embedded targets, and light clients running
+//! there validate data exactly the way the node does. Engine specific checks, such as seals and
+//! timestamps, are left to the caller.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+extern crate alloc;
+
+pub mod forkid;
+pub mod header;
+pub mod proof;
+
+pub use ethereum_types::{Address, H256, U256};
+
+use tiny_keccak::{Hasher, Keccak};
+
+/// Block number.
+pub type BlockNumber = u64;
+
+/// Keccak-256 hash of `data`.
+pub fn keccak(data: &[u8]) -> H256 {
+	let mut hasher = Keccak::v256();
+	let mut output = [0u8; 32];
+	hasher.update(data);
+	hasher.finalize(&mut output);
+	H256(output)
+}
@@ -0,0 +1,249 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Merkle-Patricia proofs of receipts and logs.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use rlp::{DecoderError, Rlp, RlpStream};
+
+use crate::{keccak, Address, H256};
+
+/// Proof verification error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofError {
+	/// A node on the path to the key is not part of the proof.
+	MissingNode(H256),
+	/// A node of the proof is not a valid trie node.
+	InvalidNode,
+	/// The key is proven absent from the trie.
+	NotIncluded,
+	/// The log index is past the logs of the receipt.
+	NoSuchLog(usize),
+	/// A node or the proven value couldn't be decoded.
+	Rlp(DecoderError),
+}
+
+impl From<DecoderError> for ProofError {
+	fn from(err: DecoderError) -> Self {
+		ProofError::Rlp(err)
+	}
+}
+
+impl fmt::Display for ProofError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ProofError::MissingNode(ref hash) => write!(f, "Proof is missing node {:?}", hash),
+			ProofError::InvalidNode => write!(f, "Proof contains an invalid node"),
+			ProofError::NotIncluded => write!(f, "Key is not included in the trie"),
+			ProofError::NoSuchLog(index) => write!(f, "Receipt has no log {}", index),
+			ProofError::Rlp(ref err) => write!(f, "Invalid RLP: {}", err),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofError {}
+
+/// A log entry proven to be part of a receipt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Log {
+	/// Address of the contract which emitted the log.
+	pub address: Address,
+	/// Topics.
+	pub topics: Vec<H256>,
+	/// Data.
+	pub data: Vec<u8>,
+}
+
+fn find_node<'a>(hash: &H256, proof: &'a [Vec<u8>]) -> Result<&'a [u8], ProofError> {
+	proof.iter()
+		.find(|node| keccak(node) == *hash)
+		.map(|node| &node[..])
+		.ok_or(ProofError::MissingNode(*hash))
+}
+
+/// Nibbles of a hex-prefix encoded path, and whether it belongs to a leaf.
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), ProofError> {
+	let (first, rest) = encoded.split_first().ok_or(ProofError::InvalidNode)?;
+	let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+	if first & 0x10 != 0 {
+		nibbles.push(first & 0x0f);
+	}
+	for byte in rest {
+		nibbles.push(byte >> 4);
+		nibbles.push(byte & 0x0f);
+	}
+	Ok((nibbles, first & 0x20 != 0))
+}
+
+/// Value stored under `key` in the trie with the given root, proven by the nodes on the path to it.
+///
+/// Returns `None` if the proof shows the key is absent.
+pub fn verify_proof(root: &H256, key: &[u8], proof: &[Vec<u8>]) -> Result<Option<Vec<u8>>, ProofError> {
+	if *root == keccak(&rlp::NULL_RLP) {
+		return Ok(None);
+	}
+
+	let mut nibbles = Vec::with_capacity(key.len() * 2);
+	for byte in key {
+		nibbles.push(byte >> 4);
+		nibbles.push(byte & 0x0f);
+	}
+	let mut remaining = &nibbles[..];
+	let mut node = find_node(root, proof)?.to_vec();
+
+	loop {
+		let rlp = Rlp::new(&node);
+		let child = match rlp.item_count()? {
+			2 => {
+				let (path, is_leaf) = decode_path(rlp.at(0)?.data()?)?;
+				if is_leaf {
+					return Ok(if remaining == &path[..] { Some(rlp.at(1)?.data()?.to_vec()) } else { None });
+				}
+				if !remaining.starts_with(&path) {
+					return Ok(None);
+				}
+				remaining = &remaining[path.len()..];
+				rlp.at(1)?
+			},
+			17 => match remaining.split_first() {
+				None => {
+					let value = rlp.at(16)?.data()?;
+					return Ok(if value.is_empty() { None } else { Some(value.to_vec()) });
+				},
+				Some((nibble, rest)) => {
+					remaining = rest;
+					rlp.at(*nibble as usize)?
+				},
+			},
+			_ => return Err(ProofError::InvalidNode),
+		};
+
+		// children shorter than a hash are embedded in their parent
+		let next = if child.is_list() {
+			child.as_raw().to_vec()
+		} else {
+			let hash = child.data()?;
+			match hash.len() {
+				0 => return Ok(None),
+				32 => find_node(&H256::from_slice(hash), proof)?.to_vec(),
+				_ => return Err(ProofError::InvalidNode),
+			}
+		};
+		node = next;
+	}
+}
+
+/// RLP encoded receipt of the transaction at `index`, proven against the receipts root of its block.
+pub fn verify_receipt(receipts_root: &H256, index: usize, proof: &[Vec<u8>]) -> Result<Vec<u8>, ProofError> {
+	let mut key = RlpStream::new();
+	key.append(&index);
+	verify_proof(receipts_root, &key.out(), proof)?.ok_or(ProofError::NotIncluded)
+}
+
+/// Log at `log_index` in the receipt of the transaction at `index`, proven against the receipts root of its block.
+pub fn verify_log(receipts_root: &H256, index: usize, log_index: usize, proof: &[Vec<u8>]) -> Result<Log, ProofError> {
+	let receipt = verify_receipt(receipts_root, index, proof)?;
+	let receipt = Rlp::new(&receipt);
+	// logs are the last field, whether or not the receipt starts with a state root or status
+	let logs = receipt.at(receipt.item_count()?.saturating_sub(1))?;
+	if log_index >= logs.item_count()? {
+		return Err(ProofError::NoSuchLog(log_index));
+	}
+	let log = logs.at(log_index)?;
+	Ok(Log {
+		address: log.val_at(0)?,
+		topics: log.list_at(1)?,
+		data: log.val_at(2)?,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn leaf(nibble: u8, value: &[u8]) -> Vec<u8> {
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&vec![0x30 | nibble]);
+		stream.append(&value.to_vec());
+		stream.out()
+	}
+
+	fn receipt(status: u8, logs: &[Log]) -> Vec<u8> {
+		let mut stream = RlpStream::new_list(4);
+		stream.append(&status);
+		stream.append(&21_000u64);
+		stream.append(&vec![0u8; 256]);
+		stream.begin_list(logs.len());
+		for log in logs {
+			stream.begin_list(3);
+			stream.append(&log.address);
+			stream.append_list(&log.topics);
+			stream.append(&log.data);
+		}
+		stream.out()
+	}
+
+	/// Receipts trie of two transactions: a branch holding the leaves of keys 0x80 and 0x01.
+	fn receipts_trie(receipts: &[Vec<u8>; 2]) -> (H256, Vec<u8>, Vec<u8>, Vec<u8>) {
+		let first = leaf(0, &receipts[0]);
+		let second = leaf(1, &receipts[1]);
+		let mut branch = RlpStream::new_list(17);
+		for nibble in 0..16 {
+			match nibble {
+				0 => { branch.append(&keccak(&second)); },
+				8 => { branch.append(&keccak(&first)); },
+				_ => { branch.append_empty_data(); },
+			}
+		}
+		branch.append_empty_data();
+		let branch = branch.out();
+		(keccak(&branch), branch, first, second)
+	}
+
+	#[test]
+	fn verifies_receipts_and_logs() {
+		let log = Log { address: Address::from_low_u64_be(5), topics: vec![H256::from_low_u64_be(7)], data: vec![1, 2, 3] };
+		let receipts = [receipt(1, &[]), receipt(0, &[log.clone()])];
+		let (root, branch, first, second) = receipts_trie(&receipts);
+		assert_eq!(root, triehash::ordered_trie_root(receipts.iter()));
+
+		assert_eq!(verify_receipt(&root, 0, &[branch.clone(), first.clone()]), Ok(receipts[0].clone()));
+		assert_eq!(verify_receipt(&root, 1, &[second.clone(), branch.clone()]), Ok(receipts[1].clone()));
+		assert_eq!(verify_log(&root, 1, 0, &[branch.clone(), second.clone()]), Ok(log));
+		assert_eq!(verify_log(&root, 1, 1, &[branch.clone(), second.clone()]), Err(ProofError::NoSuchLog(1)));
+		assert_eq!(verify_receipt(&root, 2, &[branch.clone()]), Err(ProofError::NotIncluded));
+		assert_eq!(verify_receipt(&root, 0, &[branch]), Err(ProofError::MissingNode(keccak(&first))));
+	}
+
+	#[test]
+	fn rejects_tampered_receipts() {
+		let receipts = [receipt(1, &[]), receipt(1, &[])];
+		let (root, branch, _, second) = receipts_trie(&receipts);
+		let tampered = leaf(0, &receipt(0, &[]));
+		assert_eq!(
+			verify_receipt(&root, 0, &[branch, tampered, second]),
+			Err(ProofError::MissingNode(keccak(&leaf(0, &receipts[0]))))
+		);
+	}
+
+	#[test]
+	fn empty_trie_proves_absence() {
+		assert_eq!(verify_proof(&keccak(&rlp::NULL_RLP), &[0x80], &[]), Ok(None));
+	}
+}
@@ -82,6 +82,17 @@ impl<DB: HashDB<KeccakHasher, DBValue>> CHT<DB> {
 	}
 }
 
+impl CHT<MemoryDB<KeccakHasher, memory_db::HashKey<KeccakHasher>, DBValue>> {
+	/// Consume the CHT, yielding all of its trie nodes keyed by their hash.
+	pub fn into_nodes(mut self) -> Vec<(H256, DBValue)> {
+		self.db.drain()
+			.into_iter()
+			.filter(|&(_, (_, rc))| rc > 0)
+			.map(|(hash, (node, _))| (hash, node))
+			.collect()
+	}
+}
+
 /// Block information necessary to build a CHT.
 pub struct BlockInfo {
 	/// The block's hash.
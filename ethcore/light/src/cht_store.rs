@@ -0,0 +1,232 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Canonical hash tries persisted by full nodes.
+//!
+//! A full node serving light clients generates the CHT for every `cht::SIZE` blocks
+//! once its last block is buried `GENERATION_DELAY` blocks deep, and stores both the
+//! root and the trie nodes. Header proofs are then a walk over the stored trie rather
+//! than a rebuild of the whole CHT from its headers on every request.
+
+use std::io;
+use std::sync::Arc;
+
+use common_types::ids::BlockId;
+use ethereum_types::H256;
+use ethtrie::TrieDB;
+use hash_db::{HashDBRef, Prefix};
+use keccak_hasher::KeccakHasher;
+use kvdb::{DBValue, KeyValueDB};
+use parking_lot::{Mutex, RwLock};
+use rlp::Rlp;
+use trie::{Recorder, Trie};
+
+use cht::{self, BlockInfo};
+use request::HeaderProofResponse;
+
+/// How many blocks the last block of a CHT has to be buried under before it is generated.
+pub const GENERATION_DELAY: u64 = cht::SIZE;
+
+const NODE_PREFIX: &[u8] = b"cht-node-";
+
+fn root_key(number: u64) -> String {
+	format!("cht-root-{:08x}", number)
+}
+
+fn node_key(hash: &H256) -> Vec<u8> {
+	let mut key = Vec::with_capacity(NODE_PREFIX.len() + 32);
+	key.extend_from_slice(NODE_PREFIX);
+	key.extend_from_slice(hash.as_bytes());
+	key
+}
+
+// read-only view over the stored CHT nodes.
+struct NodeReader<'a> {
+	db: &'a dyn KeyValueDB,
+	col: u32,
+}
+
+impl<'a> HashDBRef<KeccakHasher, DBValue> for NodeReader<'a> {
+	fn get(&self, key: &H256, _prefix: Prefix) -> Option<DBValue> {
+		match self.db.get(self.col, &node_key(key)) {
+			Ok(node) => node,
+			Err(e) => {
+				warn!(target: "cht", "Error reading CHT node {}: {}", key, e);
+				None
+			}
+		}
+	}
+
+	fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+		self.get(key, prefix).is_some()
+	}
+}
+
+/// Canonical hash tries stored in a column of a key-value database.
+pub struct ChtStore {
+	db: Arc<dyn KeyValueDB>,
+	col: u32,
+	roots: RwLock<Vec<H256>>,
+	generating: Mutex<()>,
+}
+
+impl ChtStore {
+	/// Open the store, loading the roots of all previously generated CHTs.
+	pub fn open(db: Arc<dyn KeyValueDB>, col: u32) -> io::Result<Self> {
+		let mut roots = Vec::new();
+		while let Some(raw) = db.get(col, root_key(roots.len() as u64).as_bytes())? {
+			if raw.len() != H256::len_bytes() {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, "Database corrupt: invalid CHT root"));
+			}
+			roots.push(H256::from_slice(&raw));
+		}
+
+		debug!(target: "cht", "Loaded {} stored CHT roots", roots.len());
+
+		Ok(ChtStore {
+			db,
+			col,
+			roots: RwLock::new(roots),
+			generating: Mutex::new(()),
+		})
+	}
+
+	/// Number of CHTs generated so far. CHTs are always generated in order.
+	pub fn len(&self) -> u64 {
+		self.roots.read().len() as u64
+	}
+
+	/// Get the root of the CHT with the given number, if it has been generated.
+	pub fn root(&self, cht_num: u64) -> Option<H256> {
+		self.roots.read().get(cht_num as usize).cloned()
+	}
+
+	/// Generate and store the next CHT if it is due given the best block number.
+	/// Returns the new root, or `None` if the CHT isn't due yet or the fetcher
+	/// failed to provide all of its blocks.
+	pub fn generate_next<F>(&self, best_block: u64, fetcher: F) -> Option<H256>
+		where F: FnMut(BlockId) -> Option<BlockInfo>
+	{
+		let _generating = self.generating.lock();
+
+		let cht_num = self.len();
+		let last_num = cht::start_number(cht_num + 1) - 1;
+		if best_block < last_num + GENERATION_DELAY {
+			return None;
+		}
+
+		let cht = cht::build(cht_num, fetcher)?;
+		let root = cht.root();
+
+		let mut batch = self.db.transaction();
+		for (hash, node) in cht.into_nodes() {
+			batch.put_vec(self.col, &node_key(&hash), node);
+		}
+		batch.put(self.col, root_key(cht_num).as_bytes(), root.as_bytes());
+
+		if let Err(e) = self.db.write(batch) {
+			warn!(target: "cht", "Error storing CHT #{}: {}", cht_num, e);
+			return None;
+		}
+
+		trace!(target: "cht", "Generated CHT #{} with root {}", cht_num, root);
+		self.roots.write().push(root);
+		Some(root)
+	}
+
+	/// Prove the canonical hash and total difficulty of a block against its stored CHT.
+	/// Returns `None` if the CHT covering the block hasn't been generated.
+	pub fn prove(&self, num: u64) -> Option<HeaderProofResponse> {
+		let root = cht::block_to_cht_number(num).and_then(|cht_num| self.root(cht_num))?;
+		let reader = NodeReader { db: &*self.db, col: self.col };
+		let mut recorder = Recorder::new();
+
+		let value = match TrieDB::new(&reader, &root).and_then(|t| t.get_with(&::rlp::encode(&num), &mut recorder)) {
+			Ok(Some(value)) => value,
+			Ok(None) => return None,
+			Err(e) => {
+				debug!(target: "pip_provider", "Error looking up number in stored CHT: {}", e);
+				return None;
+			}
+		};
+
+		let rlp = Rlp::new(&value);
+		match (rlp.val_at(0), rlp.val_at(1)) {
+			(Ok(hash), Ok(td)) => Some(HeaderProofResponse {
+				proof: recorder.drain().into_iter().map(|r| r.data).collect(),
+				hash,
+				td,
+			}),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use common_types::ids::BlockId;
+	use ethereum_types::{H256, U256};
+
+	use cht::{self, BlockInfo};
+	use super::{ChtStore, GENERATION_DELAY};
+
+	// a chain where block `n` has hash `n` and total difficulty `n`.
+	fn block_info(id: BlockId) -> Option<BlockInfo> {
+		let num = match id {
+			BlockId::Number(num) => num,
+			BlockId::Hash(hash) => hash.to_low_u64_be(),
+			_ => return None,
+		};
+
+		Some(BlockInfo {
+			hash: H256::from_low_u64_be(num),
+			parent_hash: H256::from_low_u64_be(num - 1),
+			total_difficulty: num.into(),
+		})
+	}
+
+	#[test]
+	fn generates_and_proves() {
+		let db = Arc::new(::kvdb_memorydb::create(1));
+		let store = ChtStore::open(db.clone(), 0).unwrap();
+
+		assert!(store.generate_next(cht::SIZE, block_info).is_none());
+		assert!(store.prove(1).is_none());
+
+		let best = 2 * cht::SIZE + GENERATION_DELAY;
+		while store.generate_next(best, block_info).is_some() {}
+		assert_eq!(store.len(), 2);
+
+		let expected = cht::compute_root(1, (cht::SIZE + 1..=2 * cht::SIZE)
+			.map(|n| (H256::from_low_u64_be(n), U256::from(n))));
+		assert_eq!(store.root(1), expected);
+
+		let num = cht::SIZE + 42;
+		let response = store.prove(num).unwrap();
+		assert_eq!(response.hash, H256::from_low_u64_be(num));
+		assert_eq!(response.td, num.into());
+		assert_eq!(
+			cht::check_proof(&response.proof, num, store.root(1).unwrap()),
+			Some((response.hash, response.td)),
+		);
+
+		let reopened = ChtStore::open(db, 0).unwrap();
+		assert_eq!(reopened.len(), 2);
+		assert_eq!(reopened.prove(num).map(|r| r.hash), Some(response.hash));
+	}
+}
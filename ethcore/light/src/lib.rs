@@ -34,6 +34,7 @@
 
 pub mod client;
 pub mod cht;
+pub mod cht_store;
 pub mod net;
 pub mod on_demand;
 pub mod transaction_queue;
@@ -43,6 +44,7 @@ pub mod provider;
 mod types;
 
 pub use self::cache::Cache;
+pub use self::cht_store::ChtStore;
 pub use self::provider::{Provider, MAX_HEADERS_PER_REQUEST};
 pub use self::transaction_queue::TransactionQueue;
 pub use types::request as request;
@@ -39,6 +39,7 @@ use self::context::{Ctx, TickCtx};
 use self::error::Punishment;
 use self::load_timer::{LoadDistribution, NullStore, MOVING_SAMPLE_SIZE};
 use self::request_set::RequestSet;
+use self::response_cache::ResponseCache;
 use self::id_guard::IdGuard;
 
 mod context;
@@ -46,6 +47,7 @@ mod error;
 mod load_timer;
 mod status;
 mod request_set;
+mod response_cache;
 
 #[cfg(test)]
 mod tests;
@@ -55,6 +57,7 @@ pub mod request_credits;
 pub use self::context::{BasicContext, EventContext, IoContext};
 pub use self::error::Error;
 pub use self::load_timer::{SampleStore, FileStore};
+pub use self::response_cache::CacheStats;
 pub use self::status::{Status, Capabilities, Announcement};
 
 const TIMEOUT: TimerToken = 0;
@@ -173,6 +176,8 @@ pub struct Peer {
 	skip_update: bool,
 	local_flow: Arc<FlowParams>,
 	awaiting_acknowledge: Option<(Instant, Arc<FlowParams>)>,
+	served_requests: u64,
+	credits_charged: U256,
 }
 
 /// Whether or not a peer was kept by a handler
@@ -267,16 +272,20 @@ pub struct Config {
 	pub max_stored_seconds: u64,
 	/// The network config median peers (used as default peer count)
 	pub median_peers: f64,
+	/// Maximum size, in bytes, of the cache of served responses.
+	pub response_cache_size: usize,
 }
 
 impl Default for Config {
 	fn default() -> Self {
 		const MEDIAN_PEERS: f64 = 25.0;
 		const MAX_ACCUMULATED: u64 = 60 * 5; // only charge for 5 minutes.
+		const RESPONSE_CACHE_SIZE: usize = 16 * 1024 * 1024;
 
 		Config {
 			max_stored_seconds: MAX_ACCUMULATED,
 			median_peers: MEDIAN_PEERS,
+			response_cache_size: RESPONSE_CACHE_SIZE,
 		}
 	}
 }
@@ -380,6 +389,28 @@ impl Statistics {
 	}
 }
 
+/// Request serving statistics of a peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerServingStats {
+	/// Id of the peer.
+	pub peer: PeerId,
+	/// Number of requests served to the peer.
+	pub served_requests: u64,
+	/// Credits charged for the served requests.
+	pub credits_charged: U256,
+	/// Credits the peer has left.
+	pub credits: U256,
+}
+
+/// Request serving statistics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServingStats {
+	/// Usage of the response cache.
+	pub cache: CacheStats,
+	/// Statistics of each connected peer.
+	pub peers: Vec<PeerServingStats>,
+}
+
 /// This is an implementation of the light ethereum network protocol, abstracted
 /// over a `Provider` of data and a p2p network.
 ///
@@ -405,6 +436,7 @@ pub struct LightProtocol {
 	sample_store: Box<dyn SampleStore>,
 	load_distribution: LoadDistribution,
 	statistics: RwLock<Statistics>,
+	response_cache: ResponseCache,
 }
 
 impl LightProtocol {
@@ -423,6 +455,8 @@ impl LightProtocol {
 			Duration::from_secs(params.config.max_stored_seconds),
 		);
 
+		let response_cache = ResponseCache::new(params.config.response_cache_size);
+
 		LightProtocol {
 			provider,
 			config: params.config,
@@ -438,6 +472,7 @@ impl LightProtocol {
 			sample_store,
 			load_distribution,
 			statistics: RwLock::new(Statistics::new()),
+			response_cache,
 		}
 	}
 
@@ -447,6 +482,27 @@ impl LightProtocol {
 			.map(|peer| peer.lock().status.clone())
 	}
 
+	/// Get statistics of the requests served to peers.
+	pub fn serving_stats(&self) -> ServingStats {
+		let peers = self.peers.read().iter().map(|(id, peer)| {
+			let mut peer = peer.lock();
+			let peer: &mut Peer = &mut *peer;
+			peer.local_flow.recharge(&mut peer.local_credits);
+
+			PeerServingStats {
+				peer: *id,
+				served_requests: peer.served_requests,
+				credits_charged: peer.credits_charged,
+				credits: peer.local_credits.current(),
+			}
+		}).collect();
+
+		ServingStats {
+			cache: self.response_cache.stats(),
+			peers,
+		}
+	}
+
 	/// Get number of (connected, active) peers.
 	pub fn peer_count(&self) -> (usize, usize) {
 		let num_pending = self.pending_peers.read().len();
@@ -915,6 +971,8 @@ impl LightProtocol {
 			skip_update: false,
 			local_flow,
 			awaiting_acknowledge: None,
+			served_requests: 0,
+			credits_charged: U256::zero(),
 		}));
 
 		let any_kept = self.handlers.iter().map(
@@ -1008,11 +1066,13 @@ impl LightProtocol {
 		// deserialize requests, check costs and request validity.
 		peer.local_flow.recharge(&mut peer.local_credits);
 
-		peer.local_credits.deduct_cost(peer.local_flow.base_cost())?;
+		let mut charged = peer.local_flow.base_cost();
+		peer.local_credits.deduct_cost(charged)?;
 		for request_rlp in raw.at(1)?.iter().take(MAX_REQUESTS) {
 			let request: Request = request_rlp.as_val()?;
 			let cost = peer.local_flow.compute_cost(&request).ok_or(Error::NotServer)?;
 			peer.local_credits.deduct_cost(cost)?;
+			charged = charged.saturating_add(cost);
 			request_builder.push(request).map_err(|_| Error::BadBackReference)?;
 		}
 
@@ -1021,7 +1081,8 @@ impl LightProtocol {
 		trace!(target: "pip", "Beginning to respond to requests (id: {}) from peer {}", req_id, peer_id);
 
 		// respond to all requests until one fails.
-		let responses = requests.respond_to_all(|complete_req| {
+		// cached responses are not timed, so that costs reflect the work of the provider.
+		let responses = requests.respond_to_all(|complete_req| self.response_cache.get_or_insert_with(complete_req, |complete_req| {
 			let _timer = self.load_distribution.begin_timer(&complete_req);
 			match complete_req {
 				CompleteRequest::Headers(req) => self.provider.block_headers(req).map(Response::Headers),
@@ -1035,7 +1096,10 @@ impl LightProtocol {
 				CompleteRequest::Execution(req) => self.provider.transaction_proof(req).map(Response::Execution),
 				CompleteRequest::Signal(req) => self.provider.epoch_signal(req).map(Response::Signal),
 			}
-		});
+		}));
+
+		peer.served_requests += responses.len() as u64;
+		peer.credits_charged = peer.credits_charged.saturating_add(charged);
 
 		trace!(target: "pip", "Responded to {}/{} requests in packet {}", responses.len(), num_requests, req_id);
 		trace!(target: "pip", "Peer {} has {} credits remaining.", peer_id, peer.local_credits.current());
@@ -0,0 +1,194 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Cache of recently served responses.
+//!
+//! Many light clients following the chain ask for the same proofs around the head.
+//! Only requests whose response can't change are cached: those addressed by block hash,
+//! ancestry walks starting at a hash and proofs from complete CHTs.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ethereum_types::H256;
+use memory_cache::MemoryLruCache;
+use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
+use parking_lot::Mutex;
+use request::{CompleteRequest, HashOrNumber, Response};
+use rlp;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CacheKey {
+	Headers { start: H256, skip: u64, max: u64 },
+	HeaderProof(u64),
+	Account { block_hash: H256, address_hash: H256 },
+	Storage { block_hash: H256, address_hash: H256, key_hash: H256 },
+	Code { block_hash: H256, code_hash: H256 },
+	Signal(H256),
+}
+
+impl CacheKey {
+	fn from_request(req: &CompleteRequest) -> Option<Self> {
+		match *req {
+			// forward walks follow the canonical chain, which may be reorganized.
+			CompleteRequest::Headers(ref req) => match req.start {
+				HashOrNumber::Hash(start) if req.reverse => Some(CacheKey::Headers { start, skip: req.skip, max: req.max }),
+				_ => None,
+			},
+			CompleteRequest::HeaderProof(ref req) => Some(CacheKey::HeaderProof(req.num)),
+			CompleteRequest::Account(ref req) => Some(CacheKey::Account {
+				block_hash: req.block_hash,
+				address_hash: req.address_hash,
+			}),
+			CompleteRequest::Storage(ref req) => Some(CacheKey::Storage {
+				block_hash: req.block_hash,
+				address_hash: req.address_hash,
+				key_hash: req.key_hash,
+			}),
+			CompleteRequest::Code(ref req) => Some(CacheKey::Code {
+				block_hash: req.block_hash,
+				code_hash: req.code_hash,
+			}),
+			CompleteRequest::Signal(ref req) => Some(CacheKey::Signal(req.block_hash)),
+			CompleteRequest::TransactionIndex(_) | CompleteRequest::Body(_)
+				| CompleteRequest::Receipts(_) | CompleteRequest::Execution(_) => None,
+		}
+	}
+}
+
+// a response along with its encoded size, which approximates its memory usage.
+struct CachedResponse {
+	response: Response,
+	size: usize,
+}
+
+impl MallocSizeOf for CachedResponse {
+	fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+		self.size
+	}
+}
+
+/// Hit and miss counts of the cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+	/// Requests answered from the cache.
+	pub hits: u64,
+	/// Cacheable requests which had to be answered by the provider.
+	pub misses: u64,
+	/// Approximate size of the cached responses, in bytes.
+	pub size: usize,
+}
+
+/// LRU cache of responses, bounded by their encoded size.
+pub struct ResponseCache {
+	responses: Mutex<MemoryLruCache<CacheKey, CachedResponse>>,
+	hits: AtomicU64,
+	misses: AtomicU64,
+}
+
+impl ResponseCache {
+	/// Create a cache holding up to `max_size` bytes of responses.
+	pub fn new(max_size: usize) -> Self {
+		ResponseCache {
+			responses: Mutex::new(MemoryLruCache::new(max_size)),
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
+		}
+	}
+
+	/// Get the cached response to the request, or compute it with `f`.
+	/// Only responses to cacheable requests are stored, and never missing ones.
+	pub fn get_or_insert_with<F>(&self, req: CompleteRequest, f: F) -> Option<Response>
+		where F: FnOnce(CompleteRequest) -> Option<Response>
+	{
+		let key = match CacheKey::from_request(&req) {
+			Some(key) => key,
+			None => return f(req),
+		};
+
+		if let Some(cached) = self.responses.lock().get_mut(&key) {
+			self.hits.fetch_add(1, Ordering::Relaxed);
+			return Some(cached.response.clone());
+		}
+
+		self.misses.fetch_add(1, Ordering::Relaxed);
+		let response = f(req)?;
+		let size = rlp::encode(&response).len();
+		self.responses.lock().insert(key, CachedResponse { response: response.clone(), size });
+		Some(response)
+	}
+
+	/// Current hit and miss counts.
+	pub fn stats(&self) -> CacheStats {
+		CacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			size: self.responses.lock().current_size(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use request::{self, CompleteRequest, HashOrNumber, Response};
+	use super::ResponseCache;
+
+	fn code_request(block_hash: H256) -> CompleteRequest {
+		CompleteRequest::Code(request::CompleteCodeRequest {
+			block_hash,
+			code_hash: H256::repeat_byte(2),
+		})
+	}
+
+	fn code_response(_: CompleteRequest) -> Option<Response> {
+		Some(Response::Code(request::CodeResponse { code: vec![1, 2, 3] }))
+	}
+
+	#[test]
+	fn should_serve_repeated_requests_from_cache() {
+		let cache = ResponseCache::new(1024 * 1024);
+		let req = code_request(H256::repeat_byte(1));
+
+		assert_eq!(cache.get_or_insert_with(req.clone(), code_response), code_response(req.clone()));
+		assert_eq!(cache.get_or_insert_with(req.clone(), |_| panic!("response should be cached")), code_response(req));
+
+		let stats = cache.stats();
+		assert_eq!((stats.hits, stats.misses), (1, 1));
+		assert!(stats.size > 0);
+	}
+
+	#[test]
+	fn should_not_cache_missing_or_mutable_responses() {
+		let cache = ResponseCache::new(1024 * 1024);
+		let req = code_request(H256::repeat_byte(1));
+		assert_eq!(cache.get_or_insert_with(req.clone(), |_| None), None);
+		assert_eq!(cache.get_or_insert_with(req.clone(), code_response), code_response(req));
+
+		let forward = CompleteRequest::Headers(request::CompleteHeadersRequest {
+			start: HashOrNumber::Hash(H256::repeat_byte(1)),
+			skip: 0,
+			max: 1,
+			reverse: false,
+		});
+		let headers = |_| Some(Response::Headers(request::HeadersResponse { headers: vec![] }));
+		assert!(cache.get_or_insert_with(forward.clone(), headers).is_some());
+		assert!(cache.get_or_insert_with(forward, headers).is_some());
+
+		let stats = cache.stats();
+		assert_eq!((stats.hits, stats.misses), (0, 2));
+	}
+}
@@ -513,6 +513,48 @@ fn get_state_proofs() {
 	proto.handle_packet(&expected, 1, packet::REQUEST, &request_body);
 }
 
+#[test]
+fn caches_state_proofs() {
+	let capabilities = capabilities();
+
+	let (provider, proto) = setup(capabilities);
+	let flow_params = proto.flow_params.read().clone();
+
+	let cur_status = status(provider.client.chain_info());
+
+	{
+		let packet_body = write_handshake(&cur_status, &capabilities, &proto);
+		proto.on_connect(1, &Expect::Send(1, packet::STATUS, packet_body.clone()));
+		proto.handle_packet(&Expect::Nothing, 1, packet::STATUS, &packet_body);
+	}
+
+	let key: H256 = BigEndianHash::from_uint(&U256::from(11223344));
+	let requests = encode_single(Request::Account(IncompleteAccountRequest {
+		block_hash: H256::zero().into(),
+		address_hash: key.into(),
+	}));
+	let response = Response::Account(TestProvider(provider.clone()).account_proof(CompleteAccountRequest {
+		block_hash: H256::zero(),
+		address_hash: key,
+	}).unwrap());
+	let cost = flow_params.compute_cost_multi(requests.requests()).unwrap();
+
+	for (req_id, spent) in vec![(1usize, cost), (2, cost * 2)] {
+		let request_body = make_packet(req_id, &requests);
+		let mut response_stream = RlpStream::new_list(3);
+		response_stream.append(&req_id).append(&(*flow_params.limit() - spent)).append_list(&[response.clone()]);
+
+		let expected = Expect::Respond(packet::RESPONSE, response_stream.out());
+		proto.handle_packet(&expected, 1, packet::REQUEST, &request_body);
+	}
+
+	let stats = proto.serving_stats();
+	assert_eq!((stats.cache.hits, stats.cache.misses), (1, 1));
+	assert_eq!(stats.peers.len(), 1);
+	assert_eq!(stats.peers[0].served_requests, 2);
+	assert_eq!(stats.peers[0].credits_charged, cost * 2);
+}
+
 #[test]
 fn get_contract_code() {
 	let capabilities = capabilities();
@@ -696,6 +738,8 @@ fn id_guard() {
 		skip_update: false,
 		local_flow: flow_params,
 		awaiting_acknowledge: None,
+		served_requests: 0,
+		credits_charged: U256::zero(),
 	}));
 
 	// first, malformed responses.
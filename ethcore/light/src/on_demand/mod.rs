@@ -63,6 +63,8 @@ pub const DEFAULT_RESPONSE_TIME_TO_LIVE: Duration = Duration::from_secs(10);
 pub const DEFAULT_MAX_REQUEST_BACKOFF_ROUNDS: usize = 10;
 /// The default number failed request to be regarded as failure
 pub const DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS: usize = 1;
+/// The default number of peers requests for historical (CHT-anchored) data are sent to at once
+pub const DEFAULT_HISTORICAL_FAN_OUT: usize = 2;
 
 /// OnDemand related errors
 pub mod error {
@@ -149,6 +151,8 @@ struct Pending {
 	sender: oneshot::Sender<PendingResponse>,
 	request_guard: RequestGuard,
 	response_guard: ResponseGuard,
+	// number of peers to send the requests to at once.
+	fan_out: usize,
 }
 
 impl Pending {
@@ -260,6 +264,7 @@ impl Pending {
 				CheckedRequest::Body(_, _) => "Body",
 				CheckedRequest::Account(_, _) => "Account",
 				CheckedRequest::Code(_, _) => "Code",
+				CheckedRequest::Storage(_, _) => "Storage",
 				CheckedRequest::Execution(_, _) => "Execution",
 				CheckedRequest::Signal(_, _) => "Signal",
 			}
@@ -325,6 +330,9 @@ fn guess_capabilities(requests: &[CheckedRequest]) -> Capabilities {
 			CheckedRequest::Code(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
 				update_since(&mut caps.serve_state_since, hdr.number());
 			},
+			CheckedRequest::Storage(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
+				update_since(&mut caps.serve_state_since, hdr.number());
+			},
 			CheckedRequest::Execution(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
 				update_since(&mut caps.serve_state_since, hdr.number());
 			},
@@ -363,13 +371,16 @@ pub struct OnDemand {
 	pending: RwLock<Vec<Pending>>,
 	peers: RwLock<HashMap<PeerId, Peer>>,
 	in_transit: RwLock<HashMap<ReqId, Pending>>,
+	// extra requests of a fanned out `Pending`, mapped to the id it is in transit under.
+	fanned_out: RwLock<HashMap<ReqId, ReqId>>,
 	cache: Arc<Mutex<Cache>>,
 	no_immediate_dispatch: bool,
 	response_time_window: Duration,
 	request_backoff_start: Duration,
 	request_backoff_max: Duration,
 	request_backoff_rounds_max: usize,
-	request_number_of_consecutive_errors: usize
+	request_number_of_consecutive_errors: usize,
+	historical_fan_out: usize,
 }
 
 impl OnDemandRequester for OnDemand {
@@ -410,6 +421,12 @@ impl OnDemandRequester for OnDemand {
 		let requests = builder.build();
 		let net_requests = requests.clone().map_requests(|req| req.into_net_request());
 		let capabilities = guess_capabilities(requests.requests());
+		// historical data is proved against CHTs: ask several peers, so that a bad or slow
+		// peer doesn't hold the request up.
+		let is_historical = requests.requests().iter().any(|req| match *req {
+			CheckedRequest::HeaderProof(_, _) => true,
+			_ => false,
+		});
 
 		self.submit_pending(ctx, Pending {
 			requests,
@@ -424,6 +441,7 @@ impl OnDemandRequester for OnDemand {
 				self.request_backoff_max,
 			),
 			response_guard: ResponseGuard::new(self.response_time_window),
+			fan_out: if is_historical { self.historical_fan_out } else { 1 },
 		});
 
 		Ok(receiver)
@@ -450,12 +468,14 @@ impl OnDemand {
 		request_backoff_max: Duration,
 		request_backoff_rounds_max: usize,
 		request_number_of_consecutive_errors: usize,
+		historical_fan_out: usize,
 	) -> Self {
 
 		Self {
 			pending: RwLock::new(Vec::new()),
 			peers: RwLock::new(HashMap::new()),
 			in_transit: RwLock::new(HashMap::new()),
+			fanned_out: RwLock::new(HashMap::new()),
 			cache,
 			no_immediate_dispatch: false,
 			response_time_window: Self::sanitize_circuit_breaker_input(response_time_window, "Response time window"),
@@ -463,6 +483,7 @@ impl OnDemand {
 			request_backoff_max: Self::sanitize_circuit_breaker_input(request_backoff_max, "Request maximum backoff time window"),
 			request_backoff_rounds_max,
 			request_number_of_consecutive_errors,
+			historical_fan_out: cmp::max(1, historical_fan_out),
 		}
 	}
 
@@ -486,6 +507,7 @@ impl OnDemand {
 		request_backoff_max: Duration,
 		request_backoff_rounds_max: usize,
 		request_number_of_consecutive_errors: usize,
+		historical_fan_out: usize,
 	) -> Self {
 		let mut me = OnDemand::new(
 			cache,
@@ -494,6 +516,7 @@ impl OnDemand {
 			request_backoff_max,
 			request_backoff_rounds_max,
 			request_number_of_consecutive_errors,
+			historical_fan_out,
 		);
 		me.no_immediate_dispatch = true;
 
@@ -530,6 +553,7 @@ impl OnDemand {
 				let num_peers = peers.len();
 				// The first peer to dispatch the request is chosen at random
 				let rand = rand::thread_rng().gen_range(0, cmp::max(1, num_peers));
+				let mut req_ids = Vec::with_capacity(pending.fan_out);
 
 				for (peer_id, peer) in peers
 					.iter()
@@ -537,20 +561,30 @@ impl OnDemand {
 					.skip(rand)
 					.take(num_peers)
 				{
+					if req_ids.len() == pending.fan_out {
+						break
+					}
 
 					if !peer.can_fulfill(&pending.required_capabilities) {
 						trace!(target: "on_demand", "Peer {} without required capabilities, skipping", peer_id);
 						continue
 					}
 
-					if pending.request_guard.is_call_permitted() {
-						if let Ok(req_id) = ctx.request_from(*peer_id, pending.net_requests.clone()) {
-							self.in_transit.write().insert(req_id, pending);
-							return None;
-						}
+					if req_ids.is_empty() && !pending.request_guard.is_call_permitted() {
+						continue
+					}
+
+					if let Ok(req_id) = ctx.request_from(*peer_id, pending.net_requests.clone()) {
+						req_ids.push(req_id);
 					}
 				}
 
+				if let Some((&req_id, fanned_out)) = req_ids.split_first() {
+					self.in_transit.write().insert(req_id, pending);
+					self.fanned_out.write().extend(fanned_out.iter().map(|&id| (id, req_id)));
+					return None;
+				}
+
 				// Register that the request round failed
 				if let RequestError::ReachedLimit = pending.request_guard.register_error() {
 					pending.request_limit_reached();
@@ -564,6 +598,26 @@ impl OnDemand {
 		trace!(target: "on_demand", "Was unable to dispatch {} requests.", pending.len());
 	}
 
+	// hand a request which got no usable response over to one of the other peers it was
+	// fanned out to, if any is still due to respond. Otherwise give it back.
+	fn hand_over(&self, req_id: ReqId, pending: Pending) -> Option<Pending> {
+		let mut in_transit = self.in_transit.write();
+		let mut fanned_out = self.fanned_out.write();
+
+		let next = match fanned_out.iter().find(|&(_, id)| *id == req_id) {
+			Some((&next, _)) => next,
+			None => return Some(pending),
+		};
+
+		trace!(target: "on_demand", "Handing request over from {} to {}", req_id, next);
+		fanned_out.remove(&next);
+		for id in fanned_out.values_mut().filter(|id| **id == req_id) {
+			*id = next;
+		}
+		in_transit.insert(next, pending);
+		None
+	}
+
 	// submit a pending request set. attempts to answer from cache before
 	// going to the network. if complete, sends response and consumes the struct.
 	fn submit_pending(&self, ctx: &dyn BasicContext, mut pending: Pending) {
@@ -603,8 +657,14 @@ impl Handler for OnDemand {
 
 		{
 			let mut pending = self.pending.write();
-			for unfulfilled in unfulfilled {
-				if let Some(unfulfilled) = self.in_transit.write().remove(unfulfilled) {
+			for req_id in unfulfilled {
+				// another peer is responsible for the request.
+				if self.fanned_out.write().remove(req_id).is_some() {
+					continue
+				}
+
+				let dropped = self.in_transit.write().remove(req_id);
+				if let Some(unfulfilled) = dropped.and_then(|dropped| self.hand_over(*req_id, dropped)) {
 					trace!(target: "on_demand", "Attempting to reassign dropped request");
 					pending.push(unfulfilled);
 				}
@@ -627,10 +687,13 @@ impl Handler for OnDemand {
 	}
 
 	fn on_responses(&self, ctx: &dyn EventContext, req_id: ReqId, responses: &[basic_request::Response]) {
+		// the first peer to respond to a fanned out request answers it, later responses are dropped.
+		let req_id = self.fanned_out.write().remove(&req_id).unwrap_or(req_id);
 		let mut pending = match self.in_transit.write().remove(&req_id) {
 			Some(req) => req,
 			None => return,
 		};
+		let num_answered = pending.requests.num_answered();
 
 		if responses.is_empty() {
 			// Max number of `bad` responses reached, drop the request
@@ -658,6 +721,14 @@ impl Handler for OnDemand {
 			}
 		}
 
+		// nothing usable: wait for the other peers the request was sent to, if any.
+		if pending.requests.num_answered() == num_answered {
+			pending = match self.hand_over(req_id, pending) {
+				Some(pending) => pending,
+				None => return,
+			};
+		}
+
 		pending.fill_unanswered();
 		self.submit_pending(ctx.as_basic(), pending);
 	}
@@ -26,7 +26,7 @@ use common_types::receipt::Receipt;
 use common_types::transaction::SignedTransaction;
 use engine::{Engine, StateDependentProof};
 use executive_state::{ProvedExecution, self};
-use ethereum_types::{H256, U256, Address};
+use ethereum_types::{H256, U256, Address, BigEndianHash};
 use ethtrie::{TrieError, TrieDB};
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY, KECCAK_EMPTY_LIST_RLP, keccak};
 use hash_db::HashDB;
@@ -58,6 +58,8 @@ pub enum Request {
 	Account(Account),
 	/// A request for a contract's code.
 	Code(Code),
+	/// A request for a storage value.
+	Storage(Storage),
 	/// A request for proof of execution.
 	Execution(TransactionProof),
 	/// A request for epoch change signal.
@@ -143,6 +145,7 @@ impl_single!(Receipts, BlockReceipts, Vec<Receipt>);
 impl_single!(Body, Body, encoded::Block);
 impl_single!(Account, Account, Option<BasicAccount>);
 impl_single!(Code, Code, Bytes);
+impl_single!(Storage, Storage, H256);
 impl_single!(Execution, TransactionProof, super::ExecutionResult);
 impl_single!(Signal, Signal, Vec<u8>);
 
@@ -254,6 +257,7 @@ pub enum CheckedRequest {
 	Body(Body, net_request::IncompleteBodyRequest),
 	Account(Account, net_request::IncompleteAccountRequest),
 	Code(Code, net_request::IncompleteCodeRequest),
+	Storage(Storage, net_request::IncompleteStorageRequest),
 	Execution(TransactionProof, net_request::IncompleteExecutionRequest),
 	Signal(Signal, net_request::IncompleteSignalRequest)
 }
@@ -325,6 +329,15 @@ impl From<Request> for CheckedRequest {
 				trace!(target: "on_demand", "Code Request, {:?}", net_req);
 				CheckedRequest::Code(req, net_req)
 			}
+			Request::Storage(req) => {
+				let net_req = net_request::IncompleteStorageRequest {
+					block_hash: req.header.field(),
+					address_hash: ::hash::keccak(&req.address).into(),
+					key_hash: ::hash::keccak(&req.key).into(),
+				};
+				trace!(target: "on_demand", "Storage Request, {:?}", net_req);
+				CheckedRequest::Storage(req, net_req)
+			}
 			Request::Execution(req) => {
 				let net_req = net_request::IncompleteExecutionRequest {
 					block_hash: req.header.field(),
@@ -363,6 +376,7 @@ impl CheckedRequest {
 			CheckedRequest::Body(_, req) => NetRequest::Body(req),
 			CheckedRequest::Account(_, req) => NetRequest::Account(req),
 			CheckedRequest::Code(_, req) => NetRequest::Code(req),
+			CheckedRequest::Storage(_, req) => NetRequest::Storage(req),
 			CheckedRequest::Execution(_, req) => NetRequest::Execution(req),
 			CheckedRequest::Signal(_, req) => NetRequest::Signal(req),
 		}
@@ -378,6 +392,7 @@ impl CheckedRequest {
 			CheckedRequest::Body(ref x, _) => x.0.needs_header(),
 			CheckedRequest::Account(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Code(ref x, _) => x.header.needs_header(),
+			CheckedRequest::Storage(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Execution(ref x, _) => x.header.needs_header(),
 			_ => None,
 		}
@@ -392,6 +407,7 @@ impl CheckedRequest {
 			CheckedRequest::Body(ref mut x, _) => x.0 = HeaderRef::Stored(header),
 			CheckedRequest::Account(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Code(ref mut x, _) => x.header = HeaderRef::Stored(header),
+			CheckedRequest::Storage(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Execution(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			_ => {},
 		}
@@ -492,6 +508,14 @@ impl CheckedRequest {
 					None
 				}
 			}
+			CheckedRequest::Storage(ref check, _) => {
+				// empty storage.
+				if check.storage_root.as_ref().map_or(false, |&root| root == KECCAK_NULL_RLP) {
+					Some(Response::Storage(H256::zero()))
+				} else {
+					None
+				}
+			}
 			_ => None,
 		}
 	}
@@ -508,6 +532,7 @@ macro_rules! match_me {
 			CheckedRequest::Body($check, $req) => $e,
 			CheckedRequest::Account($check, $req) => $e,
 			CheckedRequest::Code($check, $req) => $e,
+			CheckedRequest::Storage($check, $req) => $e,
 			CheckedRequest::Execution($check, $req) => $e,
 			CheckedRequest::Signal($check, $req) => $e,
 		}
@@ -546,6 +571,15 @@ impl IncompleteRequest for CheckedRequest {
 			CheckedRequest::Body(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Account(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Code(_, ref req) => req.check_outputs(f),
+			CheckedRequest::Storage(ref check, ref req) => {
+				req.check_outputs(&mut f)?;
+
+				// make sure the storage root given is definitively a hash.
+				match check.storage_root {
+					Field::BackReference(r, idx) => f(r, idx, OutputKind::Hash),
+					_ => Ok(()),
+				}
+			}
 			CheckedRequest::Execution(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Signal(_, ref req) => req.check_outputs(f),
 		}
@@ -556,6 +590,15 @@ impl IncompleteRequest for CheckedRequest {
 	}
 
 	fn fill<F>(&mut self, f: F) where F: Fn(usize, usize) -> Result<Output, net_request::NoSuchOutput> {
+		// the storage root isn't sent, but is needed to check the response.
+		if let CheckedRequest::Storage(ref mut check, _) = *self {
+			if let Field::BackReference(r, idx) = check.storage_root {
+				if let Ok(Output::Hash(root)) = f(r, idx) {
+					check.storage_root = Field::Scalar(root);
+				}
+			}
+		}
+
 		match_me!(*self, (_, ref mut req) => req.fill(f))
 	}
 
@@ -593,6 +636,10 @@ impl IncompleteRequest for CheckedRequest {
 				trace!(target: "on_demand", "Code request completed {:?}", req);
 				req.complete().map(CompleteRequest::Code)
 			}
+			CheckedRequest::Storage(_, req) => {
+				trace!(target: "on_demand", "Storage request completed {:?}", req);
+				req.complete().map(CompleteRequest::Storage)
+			}
 			CheckedRequest::Execution(_, req) => {
 				trace!(target: "on_demand", "Execution request completed {:?}", req);
 				req.complete().map(CompleteRequest::Execution)
@@ -654,6 +701,9 @@ impl net_request::CheckedRequest for CheckedRequest {
 			CheckedRequest::Code(ref prover, _) =>
 				expect!((&NetResponse::Code(ref res), &CompleteRequest::Code(ref req)) =>
 					prover.check_response(cache, &req.code_hash, &res.code).map(Response::Code)),
+			CheckedRequest::Storage(ref prover, _) =>
+				expect!((&NetResponse::Storage(ref res), _) =>
+					prover.check_response(cache, &res.proof).map(Response::Storage)),
 			CheckedRequest::Execution(ref prover, _) =>
 				expect!((&NetResponse::Execution(ref res), _) =>
 					prover.check_response(cache, &res.items).map(Response::Execution)),
@@ -685,6 +735,8 @@ pub enum Response {
 	Account(Option<BasicAccount>),
 	/// Response to a request for code.
 	Code(Vec<u8>),
+	/// Response to a request for a storage value.
+	Storage(H256),
 	/// Response to a request for proved execution.
 	Execution(super::ExecutionResult),
 	/// Response to a request for epoch change signal.
@@ -703,6 +755,7 @@ impl net_request::ResponseLike for Response {
 				f(0, Output::Hash(acc.code_hash));
 				f(1, Output::Hash(acc.storage_root));
 			}
+			Response::Storage(ref value) => f(0, Output::Hash(*value)),
 			_ => {}
 		}
 	}
@@ -1020,6 +1073,37 @@ impl Code {
 	}
 }
 
+/// Request for a storage value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+	/// Header reference.
+	pub header: HeaderRef,
+	/// Address of the account.
+	pub address: Address,
+	/// Storage key requested.
+	pub key: H256,
+	/// Account's storage root.
+	pub storage_root: Field<H256>,
+}
+
+impl Storage {
+	/// Check a response with a storage proof against the storage root.
+	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<H256, Error> {
+		let storage_root = match self.storage_root {
+			Field::Scalar(root) => root,
+			Field::BackReference(_, _) => return Err(Error::BadProof),
+		};
+
+		let mut db = journaldb::new_memory_db();
+		for node in proof { db.insert(hash_db::EMPTY_PREFIX, &node[..]); }
+
+		match TrieDB::new(&db, &storage_root).and_then(|t| t.get(keccak(&self.key).as_bytes()))? {
+			Some(val) => Ok(BigEndianHash::from_uint(&rlp::decode::<U256>(&val)?)),
+			None => Ok(H256::zero()),
+		}
+	}
+}
+
 /// Request for transaction execution, along with the parts necessary to verify the proof.
 #[derive(Clone)]
 pub struct TransactionProof {
@@ -1318,6 +1402,45 @@ mod tests {
 		assert!(req.check_response(&cache, &proof[..]).is_ok());
 	}
 
+	#[test]
+	fn check_storage_proof() {
+		let mut root = H256::zero();
+		let mut db = journaldb::new_memory_db();
+		let key = H256::random();
+		{
+			let mut trie = SecTrieDBMut::new(&mut db, &mut root);
+			for i in 1..100u64 {
+				trie.insert(H256::random().as_bytes(), &::rlp::encode(&U256::from(i))).unwrap();
+			}
+
+			trie.insert(key.as_bytes(), &::rlp::encode(&U256::from(1_000_000))).unwrap();
+		}
+
+		let proof = {
+			let trie = SecTrieDB::new(&db, &root).unwrap();
+			let mut recorder = Recorder::new();
+
+			trie.get_with(key.as_bytes(), &mut recorder).unwrap().unwrap();
+
+			recorder.drain().into_iter().map(|r| r.data).collect::<Vec<_>>()
+		};
+
+		let mut header = Header::new();
+		header.set_number(123_456);
+		let req = Storage {
+			header: encoded::Header::new(::rlp::encode(&header)).into(),
+			address: Address::random(),
+			key,
+			storage_root: root.into(),
+		};
+
+		let cache = Mutex::new(make_cache());
+		assert_eq!(req.check_response(&cache, &proof[..]), Ok(BigEndianHash::from_uint(&U256::from(1_000_000))));
+
+		let wrong_root = Storage { storage_root: H256::random().into(), ..req };
+		assert!(wrong_root.check_response(&cache, &proof[..]).is_err());
+	}
+
 	#[test]
 	fn check_code() {
 		let code = vec![1u8; 256];
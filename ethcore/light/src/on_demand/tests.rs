@@ -36,6 +36,7 @@ enum Context {
 	NoOp,
 	WithPeer(PeerId),
 	RequestFrom(PeerId, ReqId),
+	RequestFromEach(Vec<(PeerId, ReqId)>),
 	Punish(PeerId),
 	FaultyRequest,
 }
@@ -63,6 +64,10 @@ impl BasicContext for Context {
 	fn request_from(&self, peer_id: PeerId, _: ::request::NetworkRequests) -> Result<ReqId, Error> {
 		match *self {
 			Context::RequestFrom(id, req_id) => if peer_id == id { Ok(req_id) } else { Err(Error::NoCredits) },
+			Context::RequestFromEach(ref peers) => peers.iter()
+				.find(|&&(id, _)| id == peer_id)
+				.map(|&(_, req_id)| req_id)
+				.ok_or(Error::NoCredits),
 			Context::FaultyRequest => Err(Error::NoCredits),
 			_ => panic!("didn't expect to have requests dispatched."),
 		}
@@ -102,7 +107,8 @@ impl Harness {
 				// Request max backoff
 				Duration::from_secs(20),
 				super::DEFAULT_MAX_REQUEST_BACKOFF_ROUNDS,
-				super::DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS
+				super::DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS,
+				super::DEFAULT_HISTORICAL_FAN_OUT,
 			)
 		}
 	}
@@ -254,6 +260,66 @@ fn reassign() {
 	assert!(recv.wait().is_ok());
 }
 
+#[test]
+fn fans_out_historical_requests() {
+	let harness = Harness::create();
+
+	let peers = vec![(10101, ReqId(14426)), (12345, ReqId(555)), (20202, ReqId(999))];
+	for &(peer_id, _) in &peers {
+		harness.inject_peer(peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+
+	let _recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderProof::new(5, H256::zero()).unwrap().into()]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFromEach(peers.clone()));
+	assert_eq!(harness.service.pending.read().len(), 0);
+	assert_eq!(harness.service.in_transit.read().len(), 1);
+	assert_eq!(harness.service.fanned_out.read().len(), super::DEFAULT_HISTORICAL_FAN_OUT - 1);
+
+	// the request is handed over to the other peer when the first one leaves.
+	let first = *harness.service.in_transit.read().keys().next().unwrap();
+	let (&second, _) = harness.service.fanned_out.read().iter().next().unwrap();
+	let first_peer = peers.iter().find(|&&(_, req_id)| req_id == first).unwrap().0;
+	harness.service.on_disconnect(&Context::WithPeer(first_peer), &[first]);
+
+	assert_eq!(harness.service.pending.read().len(), 0);
+	assert!(harness.service.in_transit.read().contains_key(&second));
+	assert!(harness.service.fanned_out.read().is_empty());
+
+	// and back to the queue when no other peer is left.
+	let second_peer = peers.iter().find(|&&(_, req_id)| req_id == second).unwrap().0;
+	harness.service.on_disconnect(&Context::WithPeer(second_peer), &[second]);
+	assert_eq!(harness.service.pending.read().len(), 1);
+}
+
+#[test]
+fn does_not_fan_out_recent_requests() {
+	let harness = Harness::create();
+
+	let peers = vec![(10101, ReqId(14426)), (12345, ReqId(555))];
+	for &(peer_id, _) in &peers {
+		harness.inject_peer(peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+
+	let _recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderByHash(H256::zero().into()).into()]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFromEach(peers));
+	assert_eq!(harness.service.in_transit.read().len(), 1);
+	assert!(harness.service.fanned_out.read().is_empty());
+}
+
 #[test]
 fn partial_response() {
 	let harness = Harness::create();
@@ -35,6 +35,7 @@ use ethereum_types::H256;
 use parking_lot::RwLock;
 
 use cht::{self, BlockInfo};
+use cht_store::ChtStore;
 use client::{LightChainClient, AsLightClient};
 use transaction_queue::TransactionQueue;
 
@@ -306,6 +307,87 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 	}
 }
 
+/// A full node provider which serves header proofs from the CHTs persisted in
+/// a `ChtStore`, building them on the fly only when they haven't been stored yet.
+pub struct StoredChtProvider<P: ?Sized> {
+	inner: Arc<P>,
+	store: Arc<ChtStore>,
+}
+
+impl<P: ?Sized> StoredChtProvider<P> {
+	/// Create a new `StoredChtProvider` wrapping the given provider.
+	pub fn new(inner: Arc<P>, store: Arc<ChtStore>) -> Self {
+		StoredChtProvider {
+			inner,
+			store,
+		}
+	}
+}
+
+impl<P: Provider + ?Sized> Provider for StoredChtProvider<P> {
+	fn chain_info(&self) -> BlockChainInfo {
+		self.inner.chain_info()
+	}
+
+	fn reorg_depth(&self, a: &H256, b: &H256) -> Option<u64> {
+		self.inner.reorg_depth(a, b)
+	}
+
+	fn earliest_state(&self) -> Option<u64> {
+		self.inner.earliest_state()
+	}
+
+	fn block_headers(&self, req: request::CompleteHeadersRequest) -> Option<request::HeadersResponse> {
+		self.inner.block_headers(req)
+	}
+
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		self.inner.block_header(id)
+	}
+
+	fn transaction_index(&self, req: request::CompleteTransactionIndexRequest)
+		-> Option<request::TransactionIndexResponse>
+	{
+		self.inner.transaction_index(req)
+	}
+
+	fn block_body(&self, req: request::CompleteBodyRequest) -> Option<request::BodyResponse> {
+		self.inner.block_body(req)
+	}
+
+	fn block_receipts(&self, req: request::CompleteReceiptsRequest) -> Option<request::ReceiptsResponse> {
+		self.inner.block_receipts(req)
+	}
+
+	fn account_proof(&self, req: request::CompleteAccountRequest) -> Option<request::AccountResponse> {
+		self.inner.account_proof(req)
+	}
+
+	fn storage_proof(&self, req: request::CompleteStorageRequest) -> Option<request::StorageResponse> {
+		self.inner.storage_proof(req)
+	}
+
+	fn contract_code(&self, req: request::CompleteCodeRequest) -> Option<request::CodeResponse> {
+		self.inner.contract_code(req)
+	}
+
+	fn header_proof(&self, req: request::CompleteHeaderProofRequest) -> Option<request::HeaderProofResponse> {
+		self.store.prove(req.num).or_else(|| self.inner.header_proof(req))
+	}
+
+	fn transaction_proof(&self, req: request::CompleteExecutionRequest) -> Option<request::ExecutionResponse> {
+		self.inner.transaction_proof(req)
+	}
+
+	fn epoch_signal(&self, req: request::CompleteSignalRequest) -> Option<request::SignalResponse> {
+		self.inner.epoch_signal(req)
+	}
+
+	fn transactions_to_propagate(&self) -> Vec<PendingTransaction> {
+		self.inner.transactions_to_propagate()
+	}
+}
+
 /// The light client "provider" implementation. This wraps a `LightClient` and
 /// a light transaction queue.
 pub struct LightProvider<L> {
@@ -54,6 +54,10 @@ pub struct ExecutedBlock {
 	pub traces: Tracing,
 	/// Hashes of last 256 blocks.
 	pub last_hashes: Arc<LastHashes>,
+	/// The `prevRandao` value of a proof-of-stake block, exposed to the EVM as the difficulty.
+	pub prev_randao: Option<H256>,
+	/// The EIP-1559 base fee per gas of the block.
+	pub base_fee: Option<U256>,
 }
 
 impl ExecutedBlock {
@@ -72,6 +76,8 @@ impl ExecutedBlock {
 				Tracing::Disabled
 			},
 			last_hashes,
+			prev_randao: None,
+			base_fee: None,
 		}
 	}
 
@@ -82,10 +88,11 @@ impl ExecutedBlock {
 			number: self.header.number(),
 			author: self.header.author().clone(),
 			timestamp: self.header.timestamp(),
-			difficulty: self.header.difficulty().clone(),
+			difficulty: self.prev_randao.map_or_else(|| *self.header.difficulty(), |r| U256::from_big_endian(r.as_bytes())),
 			last_hashes: self.last_hashes.clone(),
 			gas_used: self.receipts.last().map_or(U256::zero(), |r| r.gas_used),
 			gas_limit: self.header.gas_limit().clone(),
+			base_fee: self.base_fee,
 		}
 	}
 
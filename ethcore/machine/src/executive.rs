@@ -29,7 +29,7 @@ use account_state::{Backend as StateBackend, State, CleanupMode};
 use evm::{ActionType, Finalize, FinalizationResult};
 use vm::{
 	self, EnvInfo, CreateContractAddress, ReturnData, CleanDustMode, ActionParams,
-	ActionValue, Schedule, TrapError, ResumeCall, ResumeCreate
+	ActionValue, Schedule, FeeRecipient, TrapError, ResumeCall, ResumeCreate
 };
 use trie_vm_factories::VmFactory;
 use trace::{self, Tracer, VMTracer};
@@ -306,7 +306,7 @@ impl<'a> CallCreateExecutive<'a> {
 			}
 		} else {
 			if (static_flag &&
-				(params.action_type == ActionType::StaticCall || params.action_type == ActionType::Call)) &&
+				(params.action_type == ActionType::StaticCall || params.action_type == ActionType::Call || params.action_type == ActionType::AuthCall)) &&
 				params.value.value() > U256::zero()
 			{
 				return Err(vm::Error::MutableCallInStaticContext);
@@ -887,6 +887,13 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			return Err(ExecutionError::InvalidNonce { expected: nonce, got: t.nonce });
 		}
 
+		// transactions pay at least the base fee of the block
+		if let Some(base_fee) = self.info.base_fee {
+			if t.gas_price < base_fee {
+				return Err(ExecutionError::GasPriceLowerThanBaseFee { gas_price: t.gas_price, base_fee });
+			}
+		}
+
 		// validate if transaction fits into given block
 		if self.info.gas_used + t.gas > self.info.gas_limit {
 			return Err(ExecutionError::BlockGasLimitReached {
@@ -1152,6 +1159,24 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		self.create_with_stack_depth(params, substate, 0, tracer, vm_tracer)
 	}
 
+	/// Pays `value` of the transaction fees to `recipient`.
+	fn pay_fees(&mut self, recipient: &FeeRecipient, value: &U256, substate: &mut Substate, schedule: &Schedule) -> Result<(), ExecutionError> {
+		match *recipient {
+			FeeRecipient::Author => {
+				trace!(target: "executive", "exec::finalize: Compensating author: fees_value={}, author={}\n", value, &self.info.author);
+				self.state.add_balance(&self.info.author, value, cleanup_mode(substate, schedule))?;
+			},
+			FeeRecipient::Address(ref recipient) => {
+				trace!(target: "executive", "exec::finalize: Paying fees: fees_value={}, recipient={}\n", value, recipient);
+				self.state.add_balance(recipient, value, cleanup_mode(substate, schedule))?;
+			},
+			FeeRecipient::Burn => {
+				trace!(target: "executive", "exec::finalize: Burning fees: fees_value={}\n", value);
+			},
+		}
+		Ok(())
+	}
+
 	/// Finalizes the transaction (does refunds and suicides).
 	fn finalize<T, V>(
 		&mut self,
@@ -1177,22 +1202,28 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let gas_left = gas_left_prerefund + refunded;
 
 		let gas_used = t.gas.saturating_sub(gas_left);
+		// the base fee part of the gas price goes to the base fee recipient, burnt by default, and
+		// only the rest to the fee recipient.
+		let base_fee = cmp::min(self.info.base_fee.unwrap_or_default(), t.gas_price);
 		let (refund_value, overflow_1) = gas_left.overflowing_mul(t.gas_price);
-		let (fees_value, overflow_2) = gas_used.overflowing_mul(t.gas_price);
-		if overflow_1 || overflow_2 {
+		let (fees_value, overflow_2) = gas_used.overflowing_mul(t.gas_price - base_fee);
+		let (base_fees_value, overflow_3) = gas_used.overflowing_mul(base_fee);
+		if overflow_1 || overflow_2 || overflow_3 {
 			return Err(ExecutionError::TransactionMalformed("U256 Overflow".to_string()));
 		}
 
 
-		trace!(target: "executive", "exec::finalize: t.gas={}, sstore_refunds={}, suicide_refunds={}, refunds_bound={}, gas_left_prerefund={}, refunded={}, gas_left={}, gas_used={}, refund_value={}, fees_value={}\n",
-			t.gas, sstore_refunds, suicide_refunds, refunds_bound, gas_left_prerefund, refunded, gas_left, gas_used, refund_value, fees_value);
+		trace!(target: "executive", "exec::finalize: t.gas={}, sstore_refunds={}, suicide_refunds={}, refunds_bound={}, gas_left_prerefund={}, refunded={}, gas_left={}, gas_used={}, refund_value={}, fees_value={}, base_fees_value={}\n",
+			t.gas, sstore_refunds, suicide_refunds, refunds_bound, gas_left_prerefund, refunded, gas_left, gas_used, refund_value, fees_value, base_fees_value);
 
 		let sender = t.sender();
 		trace!(target: "executive", "exec::finalize: Refunding refund_value={}, sender={}\n", refund_value, sender);
 		// Below: NoEmpty is safe since the sender must already be non-null to have sent this transaction
 		self.state.add_balance(&sender, &refund_value, CleanupMode::NoEmpty)?;
-		trace!(target: "executive", "exec::finalize: Compensating author: fees_value={}, author={}\n", fees_value, &self.info.author);
-		self.state.add_balance(&self.info.author, &fees_value, cleanup_mode(&mut substate, &schedule))?;
+		self.pay_fees(&schedule.fee_recipient, &fees_value, &mut substate, schedule)?;
+		if !base_fees_value.is_zero() {
+			self.pay_fees(&schedule.base_fee_recipient, &base_fees_value, &mut substate, schedule)?;
+		}
 
 		// perform suicides
 		for address in &substate.suicides {
@@ -1281,6 +1312,7 @@ mod tests {
 			new_frontier_test_machine,
 			new_byzantium_test_machine,
 			new_constantinople_test_machine,
+			new_fee_recipient_test_machine,
 			new_kovan_wasm_test_machine,
 		},
 	};
@@ -2030,6 +2062,89 @@ mod tests {
 		assert_eq!(state.storage_at(&contract, &H256::zero()).unwrap(), BigEndianHash::from_uint(&U256::from(1)));
 	}
 
+	evm_test!{test_transact_fee_recipient: test_transact_fee_recipient_int}
+	fn test_transact_fee_recipient(factory: Factory) {
+		let keypair = Random.generate();
+		let sender = keypair.address();
+		let author = Address::from_low_u64_be(0x10);
+		let recipient = Address::from_low_u64_be(0x42);
+		let base_fee_recipient = Address::from_low_u64_be(0x43);
+		let transfer = |nonce: u64, gas_price: u64| Transaction {
+			action: Action::Call(Address::from_low_u64_be(0x20)),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::from(gas_price),
+			nonce: U256::from(nonce),
+		}.sign(keypair.secret(), None);
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&sender, &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.author = author;
+		info.gas_limit = U256::from(100_000);
+		let machine = new_fee_recipient_test_machine();
+
+		// the author, then the recipient, then nobody is paid the fees, as set in the spec.
+		for number in 0..3 {
+			info.number = number;
+			let schedule = machine.schedule(info.number);
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&transfer(number, 1), TransactOptions::with_no_tracing()).unwrap();
+		}
+		assert_eq!(state.balance(&author).unwrap(), U256::from(21_000));
+		assert_eq!(state.balance(&recipient).unwrap(), U256::from(21_000));
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(1_000_000 - 3 * 21_000));
+
+		// the base fee is redirected, the rest of the fees is still burnt.
+		info.number = 3;
+		info.base_fee = Some(U256::from(2));
+		let schedule = machine.schedule(info.number);
+		assert_eq!(schedule.fee_recipient, FeeRecipient::Burn);
+		assert_eq!(schedule.base_fee_recipient, FeeRecipient::Address(base_fee_recipient));
+		let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+		ex.transact(&transfer(3, 5), TransactOptions::with_no_tracing()).unwrap();
+		assert_eq!(state.balance(&base_fee_recipient).unwrap(), U256::from(2 * 21_000));
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(1_000_000 - 3 * 21_000 - 5 * 21_000));
+	}
+
+	evm_test!{test_transact_burns_base_fee: test_transact_burns_base_fee_int}
+	fn test_transact_burns_base_fee(factory: Factory) {
+		let keypair = Random.generate();
+		let sender = keypair.address();
+		let author = Address::from_low_u64_be(0x10);
+		let transfer = |nonce: u64, gas_price: u64| Transaction {
+			action: Action::Call(Address::from_low_u64_be(0x20)),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::from(gas_price),
+			nonce: U256::from(nonce),
+		}.sign(keypair.secret(), None);
+
+		let mut state = get_temp_state_with_factory(factory);
+		state.add_balance(&sender, &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.author = author;
+		info.gas_limit = U256::from(100_000);
+		info.base_fee = Some(U256::from(7));
+		let machine = make_frontier_machine(0);
+		let schedule = machine.schedule(info.number);
+
+		let res = Executive::new(&mut state, &info, &machine, &schedule).transact(&transfer(0, 6), TransactOptions::with_no_tracing());
+		match res {
+			Err(ExecutionError::GasPriceLowerThanBaseFee { gas_price, base_fee }) => {
+				assert_eq!(gas_price, U256::from(6));
+				assert_eq!(base_fee, U256::from(7));
+			},
+			other => panic!("unexpected result: {:?}", other.map(|_| ())),
+		}
+
+		Executive::new(&mut state, &info, &machine, &schedule).transact(&transfer(0, 10), TransactOptions::with_no_tracing()).unwrap();
+		assert_eq!(state.balance(&author).unwrap(), U256::from(3 * 21_000));
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(1_000_000 - 10 * 21_000));
+	}
+
 	evm_test!{test_transact_invalid_nonce: test_transact_invalid_nonce_int}
 	fn test_transact_invalid_nonce(factory: Factory) {
 		let keypair = Random.generate();
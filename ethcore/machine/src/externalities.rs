@@ -490,6 +490,7 @@ mod tests {
 			last_hashes: Arc::new(vec![]),
 			gas_used: 0.into(),
 			gas_limit: 0.into(),
+			base_fee: None,
 		}
 	}
 
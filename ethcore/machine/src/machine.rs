@@ -22,14 +22,14 @@ use std::sync::Arc;
 
 use ethereum_types::{U256, H256, Address};
 use rlp::Rlp;
-use log::debug;
+use log::{debug, trace};
 
 use common_types::{
 	BlockNumber,
 	header::Header,
 	engines::{
 		EthashExtensions,
-		params::CommonParams,
+		params::{CommonParams, SystemCallPhase},
 	},
 	errors::{EngineError, EthcoreError as Error},
 	transaction::{self, SYSTEM_ADDRESS, UNSIGNED_SENDER, UnverifiedTransaction, SignedTransaction},
@@ -143,6 +143,22 @@ impl Machine {
 		data: Option<Vec<u8>>,
 		action_type: Option<ActionType>,
 	) -> Result<Vec<u8>, Error> {
+		self.system_call(block, contract_address, code, code_hash, value, gas, data, action_type)
+			.map(|(output, _)| output)
+	}
+
+	/// Run a system call, returning its output and whether its state changes were kept.
+	fn system_call(
+		&self,
+		block: &mut ExecutedBlock,
+		contract_address: Option<Address>,
+		code: Option<Arc<Vec<u8>>>,
+		code_hash: Option<H256>,
+		value: Option<ActionValue>,
+		gas: U256,
+		data: Option<Vec<u8>>,
+		action_type: Option<ActionType>,
+	) -> Result<(Vec<u8>, bool), Error> {
 		let env_info = {
 			let mut env_info = block.env_info();
 			env_info.gas_limit = env_info.gas_used.saturating_add(gas);
@@ -173,7 +189,7 @@ impl Machine {
 		let res = ex.call(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).map_err(|e| EngineError::FailedSystemCall(format!("{}", e)))?;
 		let output = res.return_data.to_vec();
 
-		Ok(output)
+		Ok((output, res.apply_state))
 	}
 
 	/// Push last known block hash to the state.
@@ -195,10 +211,47 @@ impl Machine {
 		Ok(())
 	}
 
+	/// Call the system contracts declared in the spec for the given phase of the block.
+	fn call_system_contracts(&self, block: &mut ExecutedBlock, phase: SystemCallPhase) -> Result<(), Error> {
+		let number = block.header.number();
+		for contract in self.params().system_contracts.iter().filter(|c| c.phase == phase && c.is_active(number)) {
+			if number == contract.activate_at {
+				if let Some(ref code) = contract.code {
+					block.state_mut().init_code(&contract.address, code.clone())?;
+				}
+			}
+			let (code, code_hash) = {
+				let state = &block.state;
+				(state.code(&contract.address)?, state.code_hash(&contract.address)?)
+			};
+			let (output, applied) = self.system_call(
+				block,
+				Some(contract.address),
+				code,
+				code_hash,
+				None,
+				contract.gas,
+				Some(contract.data.clone()),
+				None,
+			)?;
+			// a reverted or failed system contract leaves the block without the state transition
+			// the chain spec promises, so the block can't be valid.
+			if !applied {
+				return Err(EngineError::FailedSystemCall(format!(
+					"system contract {:?} reverted at block {}: 0x{}",
+					contract.address, number, output.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+				)).into());
+			}
+			trace!(target: "machine", "System contract {:?} returned {} bytes at block {}", contract.address, output.len(), number);
+		}
+		Ok(())
+	}
+
 	/// Logic to perform on a new block: updating last hashes and the DAO
 	/// fork, for ethash.
 	pub fn on_new_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
 		self.push_last_hash(block)?;
+		self.call_system_contracts(block, SystemCallPhase::BlockStart)?;
 
 		if let Some(ref ethash_params) = self.ethash_extensions {
 			if block.header.number() == ethash_params.dao_hardfork_transition {
@@ -214,6 +267,11 @@ impl Machine {
 		Ok(())
 	}
 
+	/// Logic to perform after the last transaction of a block, before the engine closes it.
+	pub fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
+		self.call_system_contracts(block, SystemCallPhase::BlockEnd)
+	}
+
 	/// Populate a header's fields based on its parent's header.
 	/// Usually implements the chain scoring rule based on weight.
 	/// The gas floor target must not be lower than the engine's minimum gas limit.
@@ -282,6 +340,9 @@ impl Machine {
 			}
 		};
 
+		schedule.fee_recipient = self.params.fee_recipient(block_number);
+		schedule.base_fee_recipient = self.params.base_fee_recipient(block_number);
+
 		if let Some(ref rules) = self.schedule_rules {
 			(rules)(&mut schedule, block_number)
 		}
@@ -408,7 +469,7 @@ fn round_block_gas_limit(gas_limit: U256, lower_limit: U256, upper_limit: U256)
 #[cfg(test)]
 mod tests {
 	use std::str::FromStr;
-	use common_types::header::Header;
+	use common_types::{engines::params::SystemContract, header::Header};
 	use hex_literal::hex;
 	use super::*;
 
@@ -482,4 +543,42 @@ mod tests {
 		machine.populate_from_parent(&mut header, &parent, U256::from(150_000), U256::from(150_002));
 		assert_eq!(*header.gas_limit(), U256::from(150_002));
 	}
+
+	fn machine_with_system_contract(code: Vec<u8>, data: Vec<u8>) -> Machine {
+		let mut params = spec::new_test().params().clone();
+		params.system_contracts = vec![SystemContract {
+			address: Address::from_low_u64_be(0xfeed),
+			phase: SystemCallPhase::BlockStart,
+			data,
+			gas: 100_000.into(),
+			activate_at: 0,
+			deactivate_at: BlockNumber::max_value(),
+			code: Some(code),
+		}];
+		Machine::regular(params, Default::default())
+	}
+
+	#[test]
+	fn system_contract_is_executed_at_block_start() {
+		// PUSH1 0 CALLDATALOAD PUSH1 0 SSTORE: stores the first word of the call data in slot 0.
+		let machine = machine_with_system_contract(hex!("600035600055").to_vec(), H256::from_low_u64_be(42).as_bytes().to_vec());
+		let mut block = ExecutedBlock::new(ethcore::test_helpers::get_temp_state(), Default::default(), false);
+
+		machine.on_new_block(&mut block).unwrap();
+
+		let stored = block.state.storage_at(&Address::from_low_u64_be(0xfeed), &H256::zero()).unwrap();
+		assert_eq!(stored, H256::from_low_u64_be(42));
+	}
+
+	#[test]
+	fn failing_system_contract_rejects_block() {
+		// INVALID
+		let machine = machine_with_system_contract(hex!("fe").to_vec(), Vec::new());
+		let mut block = ExecutedBlock::new(ethcore::test_helpers::get_temp_state(), Default::default(), false);
+
+		match machine.on_new_block(&mut block) {
+			Err(Error::Engine(EngineError::FailedSystemCall(_))) => {},
+			other => panic!("expected a failed system call, got {:?}", other),
+		}
+	}
 }
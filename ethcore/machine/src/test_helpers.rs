@@ -58,6 +58,10 @@ pub fn new_istanbul_test_machine() -> Machine { load_machine(include_bytes!("../
 /// Create a new Foundation Berlin era spec.
 pub fn new_berlin_test_machine() -> Machine { load_machine(include_bytes!("../../res/ethereum/test-specs/berlin_test.json")) }
 
+/// Create a new Frontier-era spec paying the transaction fees to an address, then burning them
+/// and paying the base fees to another address.
+pub fn new_fee_recipient_test_machine() -> Machine { load_machine(include_bytes!("../../res/ethereum/test-specs/fee_recipient_test.json")) }
+
 /// Create a new Musicoin-MCIP3-era spec.
 pub fn new_mcip3_test_machine() -> Machine { load_machine(include_bytes!("../../res/ethereum/test-specs/mcip3_test.json")) }
 
@@ -18,7 +18,7 @@
 
 use ethabi::FunctionOutputDecoder;
 use ethabi_contract::use_contract;
-use ethereum_types::{H256, U256, Address};
+use ethereum_types::{H256, U256, Address, BloomInput};
 use log::{trace, error};
 use lru_cache::LruCache;
 
@@ -36,6 +36,7 @@ use keccak_hash::KECCAK_EMPTY;
 use_contract!(transact_acl_deprecated, "res/tx_acl_deprecated.json");
 use_contract!(transact_acl, "res/tx_acl.json");
 use_contract!(transact_acl_gas_price, "res/tx_acl_gas_price.json");
+use_contract!(transact_acl_selector, "res/tx_acl_selector.json");
 
 const MAX_CACHE_SIZE: usize = 4096;
 
@@ -53,7 +54,54 @@ pub struct TransactionFilter {
 	contract_address: Address,
 	transition_block: BlockNumber,
 	permission_cache: Mutex<LruCache<(H256, Address), u32>>,
-	contract_version_cache: Mutex<LruCache<H256, Option<U256>>>
+	contract_version_cache: Mutex<LruCache<H256, Option<U256>>>,
+	selector_cache: Mutex<SelectorCache>,
+}
+
+/// Permissions returned by a version 4 contract, by sender, recipient and function selector.
+///
+/// The permissions are not tied to a block: they stay valid until the contract logs an event.
+struct SelectorCache {
+	/// Block the cached permissions were last known to be valid at.
+	block_hash: H256,
+	/// Allowed transaction types and highest allowed value.
+	permissions: LruCache<(Address, Address, [u8; 4]), (u32, U256)>,
+}
+
+impl SelectorCache {
+	fn new() -> Self {
+		SelectorCache {
+			block_hash: H256::zero(),
+			permissions: LruCache::new(MAX_CACHE_SIZE),
+		}
+	}
+
+	/// Moves the cache to the state after the given block. The cache is kept only if the block
+	/// is a child of the last one seen and its logs bloom excludes the permission contract.
+	fn revalidate<C: BlockInfo>(&mut self, block_hash: H256, contract_address: &Address, client: &C) {
+		if self.block_hash == block_hash {
+			return;
+		}
+
+		let unchanged = client.block_header(BlockId::Hash(block_hash)).map_or(false, |header| {
+			header.parent_hash() == self.block_hash &&
+				!header.log_bloom().contains_input(BloomInput::Raw(contract_address.as_bytes()))
+		});
+		if !unchanged {
+			trace!(target: "tx_filter", "Clearing selector permissions cache at {}", block_hash);
+			self.permissions.clear();
+		}
+		self.block_hash = block_hash;
+	}
+}
+
+/// The function selector a transaction calls, zero if there is none.
+fn selector(transaction: &SignedTransaction) -> [u8; 4] {
+	let mut selector = [0u8; 4];
+	if transaction.data.len() >= 4 {
+		selector.copy_from_slice(&transaction.data[..4]);
+	}
+	selector
 }
 
 impl TransactionFilter {
@@ -65,6 +113,7 @@ impl TransactionFilter {
 				transition_block: params.transaction_permission_contract_transition,
 				permission_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
 				contract_version_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
+				selector_cache: Mutex::new(SelectorCache::new()),
 			}
 		)
 	}
@@ -130,6 +179,10 @@ impl TransactionFilter {
 								(tx_permissions::NONE, true)
 							})
 					}
+					4 => {
+						trace!(target: "tx_filter", "Using filter with function selectors and value limits");
+						return self.selector_allowed(parent_hash, tx_type, transaction, to, client);
+					}
 					_ => {
 						error!(target: "tx_filter", "Unknown version of tx permissions contract is used");
 						(tx_permissions::NONE, true)
@@ -158,6 +211,45 @@ impl TransactionFilter {
 		);
 		permissions & tx_type != 0
 	}
+
+	/// Check a transaction against a version 4 contract, which grants transaction types per
+	/// sender, recipient and function selector, up to a maximum value.
+	fn selector_allowed<C: BlockInfo + CallContract>(&self, parent_hash: &H256, tx_type: u32, transaction: &SignedTransaction, to: Address, client: &C) -> bool {
+		let sender = transaction.sender();
+		let selector = selector(transaction);
+		let contract_address = self.contract_address;
+
+		let mut cache = self.selector_cache.lock();
+		cache.revalidate(*parent_hash, &contract_address, client);
+
+		let key = (sender, to, selector);
+		let (permissions, max_value) = match cache.permissions.get_mut(&key) {
+			Some(rule) => *rule,
+			None => {
+				let (data, decoder) = transact_acl_selector::functions::allowed_tx_types::call(sender, to, selector.to_vec());
+				let rule = client.call_contract(BlockId::Hash(*parent_hash), contract_address, data)
+					.and_then(|value| decoder.decode(&value).map_err(|e| e.to_string()))
+					.map(|(p, v)| (p.low_u32(), v));
+				match rule {
+					Ok(rule) => {
+						cache.permissions.insert(key, rule);
+						rule
+					}
+					// A failed call is retried for the next transaction rather than denying the key until the next block.
+					Err(e) => {
+						error!(target: "tx_filter", "Error calling tx permissions contract: {:?}", e);
+						(tx_permissions::NONE, U256::zero())
+					}
+				}
+			}
+		};
+
+		trace!(target: "tx_filter",
+			"Given transaction data: sender: {:?} to: {:?} selector: {:?} value: {}. Permissions required: {:X}, got: {:X} up to value {}",
+			sender, to, selector, transaction.value, tx_type, permissions, max_value
+		);
+		permissions & tx_type != 0 && transaction.value <= max_value
+	}
 }
 
 #[cfg(test)]
@@ -166,7 +258,7 @@ mod test {
 	use std::str::FromStr;
 
 	use tempfile::TempDir;
-	use ethereum_types::{U256, Address};
+	use ethereum_types::{U256, Address, Bloom, BloomInput};
 
 	use client_traits::BlockChainClient;
 	use common_types::{
@@ -176,13 +268,13 @@ mod test {
 	use ethcore::{
 		client::{Client, ClientConfig},
 		miner::Miner,
-		test_helpers,
+		test_helpers::{self, EachBlockWith, TestBlockChainClient},
 	};
 	use parity_crypto::publickey::{Secret, KeyPair};
 	use ethcore_io::IoChannel;
 	use spec::Spec;
 
-	use super::TransactionFilter;
+	use super::{TransactionFilter, SelectorCache, selector};
 
 	/// Contract code: https://gist.github.com/VladLupashevskyi/84f18eabb1e4afadf572cf92af3e7e7f
 	#[test]
@@ -305,6 +397,51 @@ mod test {
 		assert!(filter.transaction_allowed(&genesis, block_number, &tx.clone().sign(key1.secret(), None), &*client));
 	}
 
+	#[test]
+	fn selector_of_transaction() {
+		let mut tx = Transaction::default();
+		let key = KeyPair::from_secret(Secret::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap()).unwrap();
+		assert_eq!(selector(&tx.clone().sign(key.secret(), None)), [0u8; 4]);
+		tx.data = vec![0xa9, 0x05, 0x9c];
+		assert_eq!(selector(&tx.clone().sign(key.secret(), None)), [0u8; 4]);
+		tx.data = vec![0xa9, 0x05, 0x9c, 0xbb, 0x01, 0x02];
+		assert_eq!(selector(&tx.clone().sign(key.secret(), None)), [0xa9, 0x05, 0x9c, 0xbb]);
+	}
+
+	#[test]
+	fn selector_cache_is_cleared_on_contract_logs_and_reorgs() {
+		let contract = Address::from_low_u64_be(5);
+		let client = TestBlockChainClient::new();
+		client.add_blocks(1, EachBlockWith::Nothing);
+		client.add_block(EachBlockWith::Nothing, |mut header| {
+			let mut bloom = Bloom::default();
+			bloom.accrue(BloomInput::Raw(contract.as_bytes()));
+			header.set_log_bloom(bloom);
+			header
+		});
+		let genesis = client.block_hash(BlockId::Number(0)).unwrap();
+		let block1 = client.block_hash(BlockId::Number(1)).unwrap();
+		let block2 = client.block_hash(BlockId::Number(2)).unwrap();
+		let key = (Address::from_low_u64_be(1), Address::from_low_u64_be(2), [0u8; 4]);
+
+		let mut cache = SelectorCache::new();
+		cache.revalidate(genesis, &contract, &client);
+		cache.permissions.insert(key, (1, U256::zero()));
+
+		// the next block doesn't touch the contract
+		cache.revalidate(block1, &contract, &client);
+		assert!(cache.permissions.contains_key(&key));
+
+		// the contract logged an event
+		cache.revalidate(block2, &contract, &client);
+		assert!(!cache.permissions.contains_key(&key));
+
+		// not a child of the last block seen
+		cache.permissions.insert(key, (1, U256::zero()));
+		cache.revalidate(block1, &contract, &client);
+		assert!(!cache.permissions.contains_key(&key));
+	}
+
 	/// Contract code: https://gist.github.com/arkpar/38a87cb50165b7e683585eec71acb05a
 	#[test]
 	fn transaction_filter_deprecated() {
@@ -39,8 +39,12 @@ extern crate spec;
 #[macro_use]
 extern crate log;
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Weak;
+use std::time::SystemTime;
 
 use common_types::{
 	ids::BlockId,
@@ -56,10 +60,31 @@ use parking_lot::RwLock;
 
 use_contract!(peer_set, "res/peer_set.json");
 
-/// Connection filter that uses a contract to manage permissions.
+/// Node permissioning settings.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NodePermissions {
+	/// Contract deciding which nodes connections are allowed with.
+	pub contract: Option<Address>,
+	/// File listing the only nodes connections are allowed with, one enode or node id per line.
+	/// Nodes must be permitted by both the file and the contract if both are set.
+	pub file: Option<PathBuf>,
+	/// Nodes connections are always allowed with.
+	pub overrides: Vec<NodeId>,
+}
+
+impl NodePermissions {
+	/// Whether connections are restricted at all.
+	pub fn is_restricted(&self) -> bool {
+		self.contract.is_some() || self.file.is_some()
+	}
+}
+
+/// Connection filter that uses a contract and/or a local file to manage permissions.
 pub struct NodeFilter {
 	client: Weak<dyn BlockChainClient>,
-	contract_address: Address,
+	contract_address: Option<Address>,
+	file: Option<PermissionsFile>,
+	overrides: HashSet<NodeId>,
 	cache: RwLock<Cache>
 }
 
@@ -68,25 +93,71 @@ struct Cache {
 	order: VecDeque<NodeId>
 }
 
+/// Allowed nodes read from a file, reloaded when the file changes.
+struct PermissionsFile {
+	path: PathBuf,
+	state: RwLock<(Option<SystemTime>, HashSet<NodeId>)>,
+}
+
+impl PermissionsFile {
+	fn open(path: PathBuf) -> Result<Self, String> {
+		let file = PermissionsFile {
+			path,
+			state: RwLock::new((None, HashSet::new())),
+		};
+		file.reload()?;
+		Ok(file)
+	}
+
+	/// Re-reads the file if it was modified since it was last read.
+	fn reload(&self) -> Result<(), String> {
+		let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+		if modified.is_some() && modified == self.state.read().0 {
+			return Ok(());
+		}
+
+		let content = fs::read_to_string(&self.path)
+			.map_err(|e| format!("Cannot read node permissions file {}: {}", self.path.display(), e))?;
+		let nodes = content.lines()
+			.map(str::trim)
+			.filter(|line| !line.is_empty() && !line.starts_with('#'))
+			.map(parse_node_id)
+			.collect::<Result<HashSet<_>, _>>()?;
+		*self.state.write() = (modified, nodes);
+		Ok(())
+	}
+
+	fn contains(&self, id: &NodeId) -> bool {
+		self.state.read().1.contains(id)
+	}
+}
+
+/// Parses a node id, either bare or as part of an enode URL.
+pub fn parse_node_id(node: &str) -> Result<NodeId, String> {
+	let id = node.trim_start_matches("enode://");
+	let id = id.split('@').next().unwrap_or(id);
+	NodeId::from_str(id).map_err(|_| format!("Invalid node id: {}", node))
+}
+
 // Increase cache size due to possible reserved peers, which do not count in the node table size
 pub const CACHE_SIZE: usize = MAX_NODES_IN_TABLE + 1024;
 
 impl NodeFilter {
-	/// Create a new instance. Accepts a contract address.
-	pub fn new(client: Weak<dyn BlockChainClient>, contract_address: Address) -> NodeFilter {
-		NodeFilter {
+	/// Create a new instance. Fails if the permissions file can't be read.
+	pub fn new(client: Weak<dyn BlockChainClient>, permissions: NodePermissions) -> Result<NodeFilter, String> {
+		Ok(NodeFilter {
 			client,
-			contract_address,
+			contract_address: permissions.contract,
+			file: permissions.file.map(PermissionsFile::open).transpose()?,
+			overrides: permissions.overrides.into_iter().collect(),
 			cache: RwLock::new(Cache{
 				cache: HashMap::with_capacity(CACHE_SIZE),
 				order: VecDeque::with_capacity(CACHE_SIZE)
 			})
-		}
+		})
 	}
-}
 
-impl ConnectionFilter for NodeFilter {
-	fn connection_allowed(&self, own_id: &NodeId, connecting_id: &NodeId, _direction: ConnectionDirection) -> bool {
+	fn contract_allows(&self, address: Address, own_id: &NodeId, connecting_id: &NodeId) -> bool {
 		let client = match self.client.upgrade() {
 			Some(client) => client,
 			None => return false,
@@ -96,7 +167,6 @@ impl ConnectionFilter for NodeFilter {
 			return *allowed;
 		}
 
-		let address = self.contract_address;
 		let own_low = H256::from_slice(&own_id[0..32]);
 		let own_high = H256::from_slice(&own_id[32..64]);
 		let id_low = H256::from_slice(&connecting_id[0..32]);
@@ -121,11 +191,34 @@ impl ConnectionFilter for NodeFilter {
 	}
 }
 
+impl ConnectionFilter for NodeFilter {
+	fn connection_allowed(&self, own_id: &NodeId, connecting_id: &NodeId, _direction: ConnectionDirection) -> bool {
+		if self.overrides.contains(connecting_id) {
+			return true;
+		}
+		if let Some(ref file) = self.file {
+			if !file.contains(connecting_id) {
+				return false;
+			}
+		}
+		match self.contract_address {
+			Some(address) => self.contract_allows(address, own_id, connecting_id),
+			None => true,
+		}
+	}
+}
+
 impl ChainNotify for NodeFilter {
 	fn new_blocks(&self, _new_blocks: NewBlocks)	{
 		let mut cache = self.cache.write();
 		cache.cache.clear();
 		cache.order.clear();
+
+		if let Some(ref file) = self.file {
+			if let Err(e) = file.reload() {
+				warn!("{}, keeping the previously read node permissions", e);
+			}
+		}
 	}
 }
 
@@ -140,7 +233,7 @@ mod test {
 	use ethcore::test_helpers;
 	use network::{ConnectionDirection, ConnectionFilter, NodeId};
 	use io::IoChannel;
-	use super::NodeFilter;
+	use super::{NodeFilter, NodePermissions, parse_node_id};
 	use tempfile::TempDir;
 	use ethereum_types::Address;
 	use std::str::FromStr;
@@ -161,7 +254,8 @@ mod test {
 			Arc::new(Miner::new_for_tests(&spec, None)),
 			IoChannel::disconnected(),
 		).unwrap();
-		let filter = NodeFilter::new(Arc::downgrade(&client) as Weak<dyn BlockChainClient>, contract_addr);
+		let permissions = NodePermissions { contract: Some(contract_addr), ..Default::default() };
+		let filter = NodeFilter::new(Arc::downgrade(&client) as Weak<dyn BlockChainClient>, permissions).unwrap();
 		let self1 = NodeId::from_str("00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002").unwrap();
 		let self2 = NodeId::from_str("00000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000003").unwrap();
 		let node1 = NodeId::from_str("00000000000000000000000000000000000000000000000000000000000000110000000000000000000000000000000000000000000000000000000000000012").unwrap();
@@ -173,4 +267,38 @@ mod test {
 		assert!(filter.connection_allowed(&self2, &node1, ConnectionDirection::Inbound));
 		assert!(filter.connection_allowed(&self2, &node2, ConnectionDirection::Inbound));
 	}
+
+	#[test]
+	fn node_filter_with_file_and_overrides() {
+		let tempdir = TempDir::new().unwrap();
+		let path = tempdir.path().join("nodes");
+		let node1 = "00000000000000000000000000000000000000000000000000000000000000110000000000000000000000000000000000000000000000000000000000000012";
+		let node2 = "00000000000000000000000000000000000000000000000000000000000000210000000000000000000000000000000000000000000000000000000000000022";
+		let node3 = "00000000000000000000000000000000000000000000000000000000000000310000000000000000000000000000000000000000000000000000000000000032";
+		::std::fs::write(&path, format!("# allowed nodes\n\nenode://{}@127.0.0.1:30303\n", node1)).unwrap();
+
+		let client = Weak::<Client>::new() as Weak<dyn BlockChainClient>;
+		let filter = NodeFilter::new(client, NodePermissions {
+			contract: None,
+			file: Some(path),
+			overrides: vec![NodeId::from_str(node2).unwrap()],
+		}).unwrap();
+		let own = NodeId::from_str(node3).unwrap();
+
+		assert!(filter.connection_allowed(&own, &NodeId::from_str(node1).unwrap(), ConnectionDirection::Outbound));
+		assert!(filter.connection_allowed(&own, &NodeId::from_str(node2).unwrap(), ConnectionDirection::Inbound));
+		assert!(!filter.connection_allowed(&own, &NodeId::from_str(node3).unwrap(), ConnectionDirection::Inbound));
+	}
+
+	#[test]
+	fn should_reject_invalid_permissions_file() {
+		let tempdir = TempDir::new().unwrap();
+		let path = tempdir.path().join("nodes");
+		::std::fs::write(&path, "enode://1234@127.0.0.1:30303\n").unwrap();
+
+		let client = Weak::<Client>::new() as Weak<dyn BlockChainClient>;
+		assert!(NodeFilter::new(client.clone(), NodePermissions { file: Some(path), ..Default::default() }).is_err());
+		assert!(NodeFilter::new(client, NodePermissions { file: Some(tempdir.path().join("missing")), ..Default::default() }).is_err());
+		assert!(parse_node_id("enode://1234").is_err());
+	}
 }
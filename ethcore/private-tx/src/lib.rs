@@ -108,7 +108,7 @@ use types::{
 	engines::machine::Executed,
 };
 use ethcore::client::{Client, Call};
-use client_traits::{BlockInfo, ChainNotify};
+use client_traits::{BlockInfo, ChainInfo, ChainNotify};
 use ethcore::miner::{self, Miner, MinerService, pool_client::NonceCache};
 use state_db::StateDB;
 use account_state::State;
@@ -142,6 +142,15 @@ const STATE_RETRIEVAL_TIMER: TimerToken = 0;
 /// Timer for private state retrieval, 5 secs duration
 const STATE_RETRIEVAL_TICK: Duration = Duration::from_secs(5);
 
+/// Timer for the check of new contract participations
+const PARTICIPATION_SYNC_TIMER: TimerToken = 1;
+
+/// Timer for the check of new contract participations, 30 secs duration
+const PARTICIPATION_SYNC_TICK: Duration = Duration::from_secs(30);
+
+/// Number of blocks for which the validators of a private contract are cached
+const VALIDATORS_EPOCH_LENGTH: u64 = 1024;
+
 /// Configurtion for private transaction provider
 #[derive(Default, PartialEq, Debug, Clone)]
 pub struct ProviderConfig {
@@ -216,6 +225,10 @@ pub struct Provider {
 	logging: Option<Logging>,
 	use_offchain_storage: bool,
 	state_storage: PrivateStateStorage,
+	/// Contracts whose state history was requested since start
+	history_requested: RwLock<HashSet<Address>>,
+	/// Validators of the private contracts with the epoch they were read in
+	validators_cache: RwLock<HashMap<Address, (u64, Vec<Address>)>>,
 }
 
 #[derive(Debug)]
@@ -254,6 +267,8 @@ impl Provider {
 			logging: config.logs_path.map(|path| Logging::new(Arc::new(FileLogsSerializer::with_path(path)))),
 			use_offchain_storage: config.use_offchain_storage,
 			state_storage: PrivateStateStorage::new(db),
+			history_requested: RwLock::default(),
+			validators_cache: RwLock::default(),
 		}
 	}
 
@@ -570,6 +585,107 @@ impl Provider {
 		Ok(())
 	}
 
+	/// Requests from peers the private states the contract had between the given blocks and
+	/// which are missing in the local db. The states are addressed by the hashes stored in the
+	/// public contract, so the received data is verified against the chain. Returns the hashes
+	/// of the requested states.
+	pub fn sync_private_state_history(&self, address: &Address, from: BlockId, to: BlockId) -> Result<Vec<H256>, Error> {
+		if !self.use_offchain_storage {
+			return Err(Error::Msg("Private state history is only stored off-chain with --private-state-offchain".into()));
+		}
+		let from = self.client.block_header(from).ok_or(Error::StatePruned)?.number();
+		let to = self.client.block_header(to).ok_or(Error::StatePruned)?.number();
+
+		let mut missing_hashes = HashSet::new();
+		for block in self.state_change_blocks(address, from, to) {
+			let state_hash = match self.get_decrypted_state_from_contract(address, BlockId::Number(block)) {
+				Ok(state_hash) => state_hash,
+				// Not deployed yet or pruned
+				Err(_) => continue,
+			};
+			if state_hash.len() != H256::len_bytes() {
+				return Err(Error::StateIncorrect);
+			}
+			let state_hash = H256::from_slice(&state_hash);
+			if self.state_storage.private_state_db().state(&state_hash).is_err() {
+				missing_hashes.insert(state_hash);
+			}
+		}
+
+		if missing_hashes.is_empty() {
+			return Ok(Vec::new());
+		}
+		let hashes_to_sync = self.state_storage.add_request(RequestType::History(*address), missing_hashes.clone());
+		trace!(target: "privatetx", "Requesting history of {:?} with the following hashes: {:?}", address, hashes_to_sync);
+		for hash in hashes_to_sync {
+			self.notify(|notify| notify.broadcast(ChainMessageType::PrivateStateRequest(hash)));
+		}
+		Ok(missing_hashes.into_iter().collect())
+	}
+
+	/// Blocks between `from` and `to` at which the contract had a different private state than in
+	/// the previous block, including `from`. Every state change increments the contract nonce, so
+	/// the changes are found by bisecting on the nonce.
+	fn state_change_blocks(&self, address: &Address, from: u64, to: u64) -> Vec<u64> {
+		let nonce_at = |block| self.get_contract_nonce(address, BlockId::Number(block)).ok();
+		let mut blocks = vec![from];
+		let mut ranges = vec![(from, nonce_at(from), to, nonce_at(to))];
+		while let Some((low, low_nonce, high, high_nonce)) = ranges.pop() {
+			if low_nonce == high_nonce || low >= high {
+				continue;
+			}
+			if high == low + 1 {
+				blocks.push(high);
+				continue;
+			}
+			let mid = low + (high - low) / 2;
+			let mid_nonce = nonce_at(mid);
+			ranges.push((mid, mid_nonce, high, high_nonce));
+			ranges.push((low, low_nonce, mid, mid_nonce));
+		}
+		blocks
+	}
+
+	/// Requests the state history of the private contracts this node became a validator of.
+	/// Runs on the IO service, off the import path, since bisecting the history executes contract calls.
+	fn sync_new_participations(&self) {
+		let key_server_account = match self.keys_provider.key_server_account() {
+			Some(account) => account,
+			None => return,
+		};
+		let epoch = self.client.chain_info().best_block_number / VALIDATORS_EPOCH_LENGTH;
+		let contracts = self.keys_provider.available_keys(BlockId::Latest, &key_server_account).unwrap_or_default();
+		for contract in contracts {
+			if self.history_requested.read().contains(&contract) {
+				continue;
+			}
+			let is_validator = self.epoch_validators(epoch, &contract)
+				.map(|validators| validators.iter().any(|v| self.validator_accounts.contains(v)))
+				.unwrap_or(false);
+			if !is_validator {
+				continue;
+			}
+			self.history_requested.write().insert(contract);
+			match self.sync_private_state_history(&contract, BlockId::Earliest, BlockId::Latest) {
+				Ok(ref hashes) if !hashes.is_empty() => info!(target: "privatetx", "Retrieving {} past private states of {:?}", hashes.len(), contract),
+				Ok(_) => {},
+				Err(e) => warn!(target: "privatetx", "Cannot retrieve private state history of {:?}: {}", contract, e),
+			}
+		}
+	}
+
+	/// Validators of the contract, read once per epoch.
+	fn epoch_validators(&self, epoch: u64, contract: &Address) -> Result<Vec<Address>, Error> {
+		if let Some((cached_epoch, ref validators)) = self.validators_cache.read().get(contract) {
+			if *cached_epoch == epoch {
+				return Ok(validators.clone());
+			}
+		}
+		let validators = self.get_validators(BlockId::Latest, contract)?;
+		self.validators_cache.write().insert(*contract, (epoch, validators.clone()));
+		Ok(validators)
+	}
+
 	fn private_state_sync_completed(&self, hash: &H256) -> Result<(), Error> {
 		self.state_storage.state_sync_completed(hash);
 		if self.state_storage.requests_ready() {
@@ -583,6 +699,9 @@ impl Provider {
 							Err(e) => error!(target: "privatetx", "Cannot process creation request with error: {:?}", e),
 						}
 					}
+					RequestType::History(contract) => {
+						info!(target: "privatetx", "Private state history of {:?} synced", contract);
+					}
 					RequestType::Verification(transaction) => {
 						if let Err(err) = self.process_verification_transaction(&transaction) {
 							warn!(target: "privatetx", "Error while processing pending verification request: {:?}", err);
@@ -878,12 +997,14 @@ impl IoHandler<ClientIoMessage<Client>> for Provider {
 	fn initialize(&self, io: &IoContext<ClientIoMessage<Client>>) {
 		if self.use_offchain_storage {
 			io.register_timer(STATE_RETRIEVAL_TIMER, STATE_RETRIEVAL_TICK).expect("Error registering state retrieval timer");
+			io.register_timer(PARTICIPATION_SYNC_TIMER, PARTICIPATION_SYNC_TICK).expect("Error registering participation sync timer");
 		}
 	}
 
 	fn timeout(&self, _io: &IoContext<ClientIoMessage<Client>>, timer: TimerToken) {
 		match timer {
 			STATE_RETRIEVAL_TIMER => self.state_storage.tick(&self.logging),
+			PARTICIPATION_SYNC_TIMER => self.sync_new_participations(),
 			_ => warn!("IO service triggered unregistered timer '{}'", timer),
 		}
 	}
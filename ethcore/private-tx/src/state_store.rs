@@ -18,7 +18,7 @@ use std::collections::{HashSet, HashMap};
 use std::sync::Arc;
 use std::time::{Instant, Duration};
 use parking_lot::RwLock;
-use ethereum_types::H256;
+use ethereum_types::{Address, H256};
 use kvdb::KeyValueDB;
 use types::transaction::SignedTransaction;
 use private_transactions::VerifiedPrivateTransaction;
@@ -34,6 +34,8 @@ pub enum RequestType {
 	Verification(Arc<VerifiedPrivateTransaction>),
 	/// Creation of the private transaction
 	Creation(SignedTransaction),
+	/// Retrieval of the past states of the private contract
+	History(Address),
 }
 
 #[derive(Clone, PartialEq)]
@@ -155,6 +157,10 @@ impl PrivateStateStorage {
 							logging.private_state_sync_failed(&tx_hash);
 						}
 					}
+					RequestType::History(contract) => {
+						warn!(target: "privatetx", "Private state history request for {:?} staled due to timeout", contract);
+						return false;
+					}
 				}
 				trace!(target: "privatetx", "Private state request for {:?} staled due to timeout", &tx_hash);
 				delete_request = true;
@@ -163,3 +169,37 @@ impl PrivateStateStorage {
 		});
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use ethcore::test_helpers::new_db;
+	use ethereum_types::{Address, H256};
+	use super::{PrivateStateStorage, RequestType};
+
+	#[test]
+	fn history_request_becomes_ready_when_all_states_synced() {
+		let db = new_db();
+		let storage = PrivateStateStorage::new(db.key_value().clone());
+		let contract = Address::from_low_u64_be(1);
+		let hashes: HashSet<H256> = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)].into_iter().collect();
+
+		let mut requested = storage.add_request(RequestType::History(contract), hashes.clone());
+		requested.sort();
+		assert_eq!(requested, vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)]);
+		// already syncing hashes are not requested again
+		assert!(storage.add_request(RequestType::History(contract), hashes).is_empty());
+
+		storage.state_sync_completed(&H256::from_low_u64_be(1));
+		assert!(!storage.requests_ready());
+		storage.state_sync_completed(&H256::from_low_u64_be(2));
+		assert!(storage.requests_ready());
+
+		let drained = storage.drain_ready_requests();
+		assert_eq!(drained.len(), 2);
+		assert!(drained.iter().all(|request| match request {
+			RequestType::History(address) => *address == contract,
+			_ => false,
+		}));
+	}
+}
@@ -0,0 +1,57 @@
+pragma solidity ^0.4.20;
+
+// Example of a version 4 transaction permission contract, with rules per function selector.
+
+contract TxPermission {
+    /// Allowed transaction types mask
+    uint32 constant None = 0;
+    uint32 constant All = 0xffffffff;
+    uint32 constant Basic = 0x01;
+    uint32 constant Call = 0x02;
+    uint32 constant Create = 0x04;
+    uint32 constant Private = 0x08;
+
+    struct Rule {
+        uint32 typesMask;
+        uint256 maxValue;
+    }
+
+    address public owner;
+    mapping(address => mapping(address => mapping(bytes4 => Rule))) rules;
+
+    /// Must be emitted whenever the result of `allowedTxTypes` may change,
+    /// the node drops its cached permissions when the contract logs any event.
+    event PermissionsChanged(address indexed sender);
+
+    function TxPermission() public {
+        owner = msg.sender;
+    }
+
+    /// Contract version
+    function contractVersion() public constant returns (uint256) {
+        return 4;
+    }
+
+    function setRule(address _sender, address _to, bytes4 _selector, uint32 _typesMask, uint256 _maxValue) public {
+        require(msg.sender == owner);
+        rules[_sender][_to][_selector] = Rule(_typesMask, _maxValue);
+        emit PermissionsChanged(_sender);
+    }
+
+    /// @dev Defines the allowed transaction types which may be initiated by the specified sender
+    /// to the specified recipient and function. The result is cached by the node until the contract
+    /// emits an event, so it must not depend on anything else.
+    /// @param _sender Transaction sender address.
+    /// @param _to Transaction recipient address. If creating a contract, the `_to` address is zero.
+    /// @param _selector First four bytes of the transaction data, zero if the data is shorter.
+    /// @return `uint32 typesMask` - Set of allowed transactions, see version 3.
+    /// `uint256 maxValue` - The highest transaction amount in wei allowed.
+    function allowedTxTypes(address _sender, address _to, bytes4 _selector)
+        public
+        view
+        returns(uint32 typesMask, uint256 maxValue)
+    {
+        Rule storage rule = rules[_sender][_to][_selector];
+        return (rule.typesMask, rule.maxValue);
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Genesis accounts streamed from a file, for allocations too large to be loaded at once like the
+//! state of a forked main net.
+
+use std::{
+	cell::Cell,
+	cmp, fmt,
+	fs::File,
+	io::{self, BufReader, Read},
+	path::{Path, PathBuf},
+	rc::Rc,
+	time::{Duration, Instant},
+};
+
+use common_types::errors::EthcoreError as Error;
+use ethereum_types::Address;
+use ethjson;
+use log::info;
+use pod::PodAccount;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+
+/// Interval between two progress reports.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// File of genesis accounts, mapping addresses to accounts like the `accounts` of a spec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountsFile {
+	path: PathBuf,
+}
+
+impl AccountsFile {
+	/// Accounts of the file at `path`, which is only opened when read.
+	pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+		AccountsFile { path: path.into() }
+	}
+
+	/// Path of the file.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Pass the accounts of the file to `f` in file order, holding a single one in memory at a
+	/// time, and log the progress. Returns the number of accounts read.
+	pub fn for_each<F>(&self, mut f: F) -> Result<usize, Error>
+		where F: FnMut(Address, PodAccount) -> Result<(), Error>
+	{
+		let file = File::open(&self.path).map_err(|e| self.error(e))?;
+		let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+		let read = Rc::new(Cell::new(0));
+		let mut deserializer = serde_json::Deserializer::from_reader(Counter {
+			inner: BufReader::new(file),
+			read: read.clone(),
+		});
+
+		let mut count = 0;
+		let mut last_report = Instant::now();
+		let mut visit = |address: Address, account: ethjson::spec::Account| {
+			if account.builtin.is_some() || account.constructor.is_some() {
+				return Err(Error::Msg(format!("Genesis account {:?} of {} has a builtin or a constructor, which only the spec can declare", address, self.path.display())));
+			}
+			f(address, account.into())?;
+			count += 1;
+			if last_report.elapsed() >= PROGRESS_INTERVAL {
+				info!(target: "spec", "Genesis state: {} accounts, {}% of {} read", count, read.get() * 100 / cmp::max(size, 1), self.path.display());
+				last_report = Instant::now();
+			}
+			Ok(())
+		};
+
+		let mut failure = None;
+		let result = deserializer.deserialize_map(AccountsVisitor { visit: &mut visit, failure: &mut failure })
+			.and_then(|_| deserializer.end());
+		match (result, failure) {
+			(_, Some(e)) => Err(e),
+			(Err(e), None) => Err(self.error(e)),
+			(Ok(()), None) => Ok(count),
+		}
+	}
+
+	fn error<E: fmt::Display>(&self, e: E) -> Error {
+		Error::Msg(format!("Could not read genesis accounts from {}: {}", self.path.display(), e))
+	}
+}
+
+/// Reader counting the bytes read, for progress reports.
+struct Counter<R> {
+	inner: R,
+	read: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for Counter<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let n = self.inner.read(buf)?;
+		self.read.set(self.read.get() + n as u64);
+		Ok(n)
+	}
+}
+
+/// Visits the entries of the accounts map one by one instead of collecting them.
+struct AccountsVisitor<'a, F> {
+	visit: &'a mut F,
+	/// Error returned by `visit`, which aborts the deserialization.
+	failure: &'a mut Option<Error>,
+}
+
+impl<'de, 'a, F> Visitor<'de> for AccountsVisitor<'a, F>
+	where F: FnMut(Address, ethjson::spec::Account) -> Result<(), Error>
+{
+	type Value = ();
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a map of addresses to accounts")
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+		while let Some((address, account)) = map.next_entry::<ethjson::hash::Address, ethjson::spec::Account>()? {
+			if let Err(e) = (self.visit)(address.into(), account) {
+				*self.failure = Some(e);
+				return Err(de::Error::custom("aborted"));
+			}
+		}
+		Ok(())
+	}
+}
@@ -125,6 +125,7 @@ bundle_custom_spec! {
 	"instant_seal" => new_instant,
 	"null" => new_null,
 	"null_morden" => new_test,
+	"null_morden_merge" => new_test_merge,
 	"null_morden_with_finality" => new_test_with_finality,
 	"null_morden_with_reward" => new_test_with_reward,
 	"validator_contract" => new_validator_contract,
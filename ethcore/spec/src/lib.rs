@@ -16,11 +16,16 @@
 
 //! Blockchain params.
 
+mod accounts_file;
 mod chain;
 mod genesis;
 mod seal;
 mod spec;
+mod spec_type;
 
+pub use self::accounts_file::AccountsFile;
 pub use self::chain::*;
 pub use self::genesis::Genesis;
 pub use self::spec::{Spec, SpecHardcodedSync, SpecParams};
+pub use self::spec_type::SpecType;
+pub use ethash_engine::EthashConfig;
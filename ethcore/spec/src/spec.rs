@@ -20,8 +20,9 @@ use std::{
 	collections::{BTreeMap, BTreeSet},
 	convert::TryFrom,
 	fmt,
+	fs,
 	io::Read,
-	path::Path,
+	path::{Path, PathBuf},
 	sync::Arc,
 };
 
@@ -36,20 +37,22 @@ use common_types::{
 use account_state::{Backend, State, backend::Basic as BasicBackend};
 use authority_round::AuthorityRound;
 use basic_authority::BasicAuthority;
+use beacon::Beacon;
 use bytes::Bytes;
 use builtin::Builtin;
 use clique::Clique;
 use engine::Engine;
-use ethash_engine::Ethash;
+use ethash_engine::{Ethash, EthashConfig};
 use ethereum_types::{H256, Bloom, U256, Address};
 use ethjson;
 use instant_seal::{InstantSeal, InstantSealParams};
 use keccak_hash::{KECCAK_NULL_RLP, keccak};
-use log::{trace, warn};
+use log::{info, trace, warn};
 use machine::{executive::Executive, Machine, substate::Substate};
 use maplit::btreeset;
 use null_engine::NullEngine;
-use pod::PodState;
+use pod::{PodAccount, PodState};
+use proxy_engine::ProxyEngine;
 use rlp::{Rlp, RlpStream};
 use serde::{Serialize, Serializer};
 use trace::{NoopTracer, NoopVMTracer};
@@ -57,6 +60,7 @@ use trie_vm_factories::Factories;
 use vm::{EnvInfo, ActionType, ActionValue, ActionParams, ParamsType};
 
 use crate::{
+	AccountsFile,
 	Genesis,
 	seal::Generic as GenericSeal,
 };
@@ -71,6 +75,11 @@ pub struct SpecParams<'a> {
 	/// memory. This may get more fine-grained in the future but for now is simply a binary
 	/// option.
 	pub optimization_setting: Option<OptimizeFor>,
+	/// Ethash cache and verification settings, used by chains sealed with Ethash.
+	pub ethash_config: EthashConfig,
+	/// Directory the relative paths of the spec are resolved against, the working directory if
+	/// not set.
+	pub spec_dir: Option<&'a Path>,
 }
 
 impl<'a> SpecParams<'a> {
@@ -79,6 +88,8 @@ impl<'a> SpecParams<'a> {
 		SpecParams {
 			cache_dir: path,
 			optimization_setting: None,
+			ethash_config: EthashConfig::default(),
+			spec_dir: None,
 		}
 	}
 
@@ -87,8 +98,17 @@ impl<'a> SpecParams<'a> {
 		SpecParams {
 			cache_dir: path,
 			optimization_setting: Some(optimization),
+			ethash_config: EthashConfig::default(),
+			spec_dir: None,
 		}
 	}
+
+	/// Resolve the relative paths of the spec against the given directory, usually the one of the
+	/// spec file.
+	pub fn with_spec_dir(mut self, dir: &'a Path) -> Self {
+		self.spec_dir = Some(dir);
+		self
+	}
 }
 
 impl<'a, T: AsRef<Path>> From<&'a T> for SpecParams<'a> {
@@ -97,37 +117,76 @@ impl<'a, T: AsRef<Path>> From<&'a T> for SpecParams<'a> {
 	}
 }
 
+/// Number of accounts of a genesis accounts file inserted into the state trie at once.
+const ACCOUNTS_FILE_BATCH: usize = 1000;
+
+/// Insert accounts into the state trie under `root`, and their code and storage into the database.
+/// Fails if an account is already in the trie.
+fn insert_accounts<'a, T, I>(accounts: I, root: &mut H256, factories: &Factories, db: &mut T) -> Result<(), Error>
+	where T: Backend, I: IntoIterator<Item = (&'a Address, &'a PodAccount)> + Clone,
+{
+	{
+		let mut t = if *root == KECCAK_NULL_RLP {
+			factories.trie.create(db.as_hash_db_mut(), root)
+		} else {
+			factories.trie.from_existing(db.as_hash_db_mut(), root)?
+		};
+
+		for (address, account) in accounts.clone() {
+			if t.insert(address.as_bytes(), &account.rlp())?.is_some() {
+				return Err(Error::Msg(format!("Genesis account {:?} is declared twice", address)));
+			}
+		}
+	}
+
+	for (address, account) in accounts {
+		account.insert_additional(
+			&mut *factories.accountdb.create(
+				db.as_hash_db_mut(),
+				keccak(address),
+			),
+			&factories.trie,
+		);
+	}
+	Ok(())
+}
+
 /// given a pre-constructor state, run all the given constructors and produce a new state and
-/// state root.
+/// state root. `flush` is called after each batch of accounts of the accounts file is inserted.
 fn run_constructors<T: Backend>(
 	genesis_state: &PodState,
+	accounts_file: Option<&AccountsFile>,
 	constructors: &[(Address, Bytes)],
 	engine: &dyn Engine,
 	author: Address,
 	timestamp: u64,
 	difficulty: U256,
 	factories: &Factories,
-	mut db: T
+	mut db: T,
+	flush: &mut dyn FnMut(&mut T) -> Result<(), Error>,
 ) -> Result<(H256, T), Error> {
 	let mut root = KECCAK_NULL_RLP;
 
 	// basic accounts in spec.
-	{
-		let mut t = factories.trie.create(db.as_hash_db_mut(), &mut root);
-
-		for (address, account) in genesis_state.get().iter() {
-			t.insert(address.as_bytes(), &account.rlp())?;
-		}
-	}
+	insert_accounts(genesis_state.get().iter(), &mut root, factories, &mut db)?;
 
-	for (address, account) in genesis_state.get().iter() {
-		account.insert_additional(
-			&mut *factories.accountdb.create(
-				db.as_hash_db_mut(),
-				keccak(address),
-			),
-			&factories.trie,
-		);
+	if let Some(accounts_file) = accounts_file {
+		let mut batch = Vec::with_capacity(ACCOUNTS_FILE_BATCH);
+		let count = accounts_file.for_each(|address, account| {
+			if genesis_state.get().contains_key(&address) {
+				return Err(Error::Msg(format!("Genesis account {:?} is both in the spec and in {}", address, accounts_file.path().display())));
+			}
+			batch.push((address, account));
+			if batch.len() == ACCOUNTS_FILE_BATCH {
+				insert_accounts(batch.iter().map(|(a, acc)| (a, acc)), &mut root, factories, &mut db)?;
+				flush(&mut db)?;
+				batch.clear();
+			}
+			Ok(())
+		})?;
+		insert_accounts(batch.iter().map(|(a, acc)| (a, acc)), &mut root, factories, &mut db)?;
+		flush(&mut db)?;
+		info!(target: "spec", "Genesis state: {} accounts from {} committed", count, accounts_file.path().display());
 	}
 
 	let start_nonce = engine.account_start_nonce(0);
@@ -146,6 +205,7 @@ fn run_constructors<T: Backend>(
 			last_hashes: Default::default(),
 			gas_used: U256::zero(),
 			gas_limit: U256::max_value(),
+			base_fee: None,
 		};
 
 		let from = Address::zero();
@@ -241,6 +301,8 @@ pub struct Spec {
 	pub state_root: H256,
 	/// Genesis state as plain old data.
 	pub genesis_state: PodState,
+	/// Genesis accounts streamed from a file, on top of `genesis_state`.
+	pub genesis_accounts_file: Option<AccountsFile>,
 }
 
 /// Part of `Spec`. Describes the hardcoded synchronization parameters.
@@ -292,8 +354,19 @@ impl fmt::Display for SpecHardcodedSync {
 }
 
 fn convert_json_to_spec(
-	(address, builtin): (ethjson::hash::Address, ethjson::spec::builtin::Builtin),
+	(address, mut builtin): (ethjson::hash::Address, ethjson::spec::builtin::Builtin),
+	spec_dir: Option<&Path>,
 ) -> Result<(Address, Builtin), Error> {
+	if let Some(ref mut module) = builtin.wasm {
+		if let Some(file) = module.file.take() {
+			if module.code.is_some() {
+				return Err(Error::Msg(format!("WASM builtin {:?} has both a code and a file", address)));
+			}
+			let path = spec_dir.map_or_else(|| PathBuf::from(&file), |dir| dir.join(&file));
+			let code = fs::read(&path).map_err(|e| Error::Msg(format!("Failed to read WASM builtin {}: {}", path.display(), e)))?;
+			module.code = Some(code.into());
+		}
+	}
 	let builtin = Builtin::try_from(builtin)?;
 	Ok((address.into(), builtin))
 }
@@ -304,7 +377,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 		.accounts
 		.builtins()
 		.into_iter()
-		.map(convert_json_to_spec)
+		.map(|builtin| convert_json_to_spec(builtin, spec_params.spec_dir))
 		.collect();
 	let builtins = builtins?;
 	let g = Genesis::from(s.genesis);
@@ -312,6 +385,10 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 	let params = CommonParams::from(s.params);
 
 	let hardcoded_sync = s.hardcoded_sync.map(Into::into);
+	let genesis_accounts_file = s.accounts_file.map(|file| AccountsFile::new(match spec_params.spec_dir {
+		Some(dir) => dir.join(file),
+		None => PathBuf::from(file),
+	}));
 
 	let (engine, hard_forks) = Spec::engine(spec_params, s.engine, params, builtins);
 	let author = g.author;
@@ -324,16 +401,23 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 		.collect();
 	let genesis_state: PodState = s.accounts.into();
 
-	let (state_root, _) = run_constructors(
-		&genesis_state,
-		&constructors,
-		&*engine,
-		author,
-		timestamp,
-		difficulty,
-		&Default::default(),
-		BasicBackend(journaldb::new_memory_db()),
-	)?;
+	let state_root = match (&genesis_accounts_file, g.state_root) {
+		// computing the root of a large allocation is as costly as committing it
+		(Some(_), Some(state_root)) => state_root,
+		(Some(file), None) => return Err(Error::Msg(format!("The genesis of a spec with the accounts file {} must have a stateRoot", file.path().display()))),
+		(None, _) => run_constructors(
+			&genesis_state,
+			None,
+			&constructors,
+			&*engine,
+			author,
+			timestamp,
+			difficulty,
+			&Default::default(),
+			BasicBackend(journaldb::new_memory_db()),
+			&mut |_| Ok(()),
+		)?.0,
+	};
 
 	let s = Spec {
 		engine,
@@ -354,6 +438,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 		hardcoded_sync,
 		constructors,
 		genesis_state,
+		genesis_accounts_file,
 		state_root,
 	};
 
@@ -409,6 +494,8 @@ impl Spec {
 			params.eip2046_transition,
 			params.eip2200_advance_transition,
 			params.eip2315_transition,
+			params.eip3074_transition,
+			params.eip1559_transition,
 			params.dust_protection_transition,
 			params.wasm_activation_transition,
 			params.kip4_transition,
@@ -421,6 +508,7 @@ impl Spec {
 			hard_forks.insert(1);
 		}
 
+		let terminal_total_difficulty = params.terminal_total_difficulty;
 		let machine = Self::machine(&engine_spec, params, builtins);
 
 		let engine: Arc<dyn Engine> = match engine_spec {
@@ -449,7 +537,7 @@ impl Spec {
 						hard_forks.insert(delay.into());
 					}
 				}
-				Arc::new(Ethash::new(spec_params.cache_dir, ethash.params.into(), machine, spec_params.optimization_setting))
+				Arc::new(Ethash::with_config(spec_params.cache_dir, ethash.params.into(), machine, spec_params.optimization_setting, spec_params.ethash_config))
 			},
 			ethjson::spec::Engine::InstantSeal(Some(instant_seal)) => Arc::new(InstantSeal::new(instant_seal.params.into(), machine)),
 			ethjson::spec::Engine::InstantSeal(None) => Arc::new(InstantSeal::new(InstantSealParams::default(), machine)),
@@ -458,6 +546,14 @@ impl Spec {
 								.expect("Failed to start Clique consensus engine."),
 			ethjson::spec::Engine::AuthorityRound(authority_round) => AuthorityRound::new(authority_round.params.into(), machine)
 				.expect("Failed to start AuthorityRound consensus engine."),
+			ethjson::spec::Engine::Proxy(proxy) => Arc::new(ProxyEngine::new(proxy.params.into(), machine)
+				.expect("Failed to start the proxy consensus engine.")),
+		};
+
+		// Once the terminal total difficulty is reached the chain is extended by a consensus client.
+		let engine: Arc<dyn Engine> = match terminal_total_difficulty {
+			Some(ttd) => Arc::new(Beacon::new(engine, ttd)),
+			None => engine,
 		};
 
 		// Dummy value is a filler for non-existent transitions
@@ -541,11 +637,16 @@ impl Spec {
 		self.seal_rlp = seal_rlp;
 	}
 
-	/// Alter the value of the genesis state.
+	/// Alter the value of the genesis state. Fails if the genesis has an accounts file, whose state
+	/// root can't be recomputed in memory.
 	pub fn set_genesis_state(&mut self, s: PodState) -> Result<(), Error> {
+		if let Some(ref file) = self.genesis_accounts_file {
+			return Err(Error::Msg(format!("Can't alter the genesis state of a spec with the accounts file {}", file.path().display())));
+		}
 		self.genesis_state = s;
 		let (root, _) = run_constructors(
 			&self.genesis_state,
+			None,
 			&self.constructors,
 			&*self.engine,
 			self.author,
@@ -553,6 +654,7 @@ impl Spec {
 			self.difficulty,
 			&Default::default(),
 			BasicBackend(journaldb::new_memory_db()),
+			&mut |_| Ok(()),
 		)?;
 
 		self.state_root = root;
@@ -561,6 +663,16 @@ impl Spec {
 
 	/// Ensure that the given state DB has the trie nodes in for the genesis state.
 	pub fn ensure_db_good<T: Backend>(&self, db: T, factories: &Factories) -> Result<T, Error> {
+		self.ensure_db_good_with_flush(db, factories, |_| Ok(()))
+	}
+
+	/// Ensure that the given state DB has the trie nodes in for the genesis state, calling `flush`
+	/// after each batch of accounts of the genesis accounts file is inserted, so they can be
+	/// written out instead of piling up in memory.
+	pub fn ensure_db_good_with_flush<T, F>(&self, db: T, factories: &Factories, mut flush: F) -> Result<T, Error> where
+		T: Backend,
+		F: FnMut(&mut T) -> Result<(), Error>,
+	{
 		if db.as_hash_db().contains(&self.state_root, hash_db::EMPTY_PREFIX) {
 			return Ok(db);
 		}
@@ -569,16 +681,20 @@ impl Spec {
 		// called anyway.
 		let (root, db) = run_constructors(
 			&self.genesis_state,
+			self.genesis_accounts_file.as_ref(),
 			&self.constructors,
 			&*self.engine,
 			self.author,
 			self.timestamp,
 			self.difficulty,
 			factories,
-			db
+			db,
+			&mut flush,
 		)?;
 
-		assert_eq!(root, self.state_root, "Spec's state root has not been precomputed correctly.");
+		if root != self.state_root {
+			return Err(Error::Msg(format!("Genesis state root {:?} differs from the state root {:?} of the spec", root, self.state_root)));
+		}
 		Ok(db)
 	}
 
@@ -591,7 +707,7 @@ impl Spec {
 					.accounts
 					.builtins()
 					.into_iter()
-					.map(convert_json_to_spec)
+					.map(|builtin| convert_json_to_spec(builtin, None))
 					.collect();
 				let builtins = builtins?;
 				let params = CommonParams::from(s.params);
@@ -632,6 +748,7 @@ impl Spec {
 				gas_limit: U256::max_value(),
 				last_hashes: Arc::new(Vec::new()),
 				gas_used: 0.into(),
+				base_fee: None,
 			};
 
 			let from = Address::zero();
@@ -663,12 +780,14 @@ mod tests {
 	use std::str::FromStr;
 
 	use account_state::State;
+	use bytes::BytesRef;
 	use common_types::{view, views::BlockView};
 	use ethereum_types::{Address, H256};
 	use ethcore::test_helpers::get_temp_state_db;
+	use pod::PodState;
 	use tempfile::TempDir;
 
-	use super::Spec;
+	use super::{Spec, SpecParams};
 
 	#[test]
 	fn test_load_empty() {
@@ -709,4 +828,94 @@ mod tests {
 		assert_eq!(state.storage_at(&address, &H256::zero()).unwrap(), expected);
 		assert_eq!(state.balance(&address).unwrap(), 1.into());
 	}
+
+	#[test]
+	fn genesis_accounts_file() {
+		let tempdir = TempDir::new().unwrap();
+		let accounts = r#"{
+			"0x00000000000000000000000000000000000000aa": { "balance": "0x10", "code": "0x6000", "storage": { "0x01": "0x02" } },
+			"0x00000000000000000000000000000000000000bb": { "balance": "1", "nonce": "3" }
+		}"#;
+		std::fs::write(tempdir.path().join("accounts.json"), accounts).unwrap();
+		let load = |spec: &serde_json::Value| {
+			let params = SpecParams::from_path(tempdir.path()).with_spec_dir(tempdir.path());
+			Spec::load(params, serde_json::to_vec(spec).unwrap().as_slice())
+		};
+
+		let mut inline: serde_json::Value = serde_json::from_slice(include_bytes!("../../res/null_morden.json")).unwrap();
+		let mut streamed = inline.clone();
+		let accounts: serde_json::Map<_, _> = serde_json::from_str(accounts).unwrap();
+		inline["accounts"].as_object_mut().unwrap().extend(accounts);
+		let inline = load(&inline).unwrap();
+
+		// the state root of the accounts file isn't computed on load
+		streamed["accountsFile"] = "accounts.json".into();
+		assert!(load(&streamed).is_err());
+		streamed["genesis"]["stateRoot"] = format!("{:?}", inline.state_root).into();
+		let mut spec = load(&streamed).unwrap();
+		assert_eq!(spec.state_root, inline.state_root);
+		assert!(spec.set_genesis_state(PodState::default()).is_err());
+
+		let mut flushes = 0;
+		let db = spec.ensure_db_good_with_flush(get_temp_state_db(), &Default::default(), |_| {
+			flushes += 1;
+			Ok(())
+		}).unwrap();
+		assert_eq!(flushes, 1);
+		let state = State::from_existing(db.boxed_clone(), spec.state_root, 0.into(), Default::default()).unwrap();
+		let address = Address::from_low_u64_be(0xaa);
+		assert_eq!(state.balance(&address).unwrap(), 0x10.into());
+		assert_eq!(state.storage_at(&address, &H256::from_low_u64_be(1)).unwrap(), H256::from_low_u64_be(2));
+		assert_eq!(state.nonce(&Address::from_low_u64_be(0xbb)).unwrap(), 3.into());
+
+		// the state root of the genesis is checked when committing
+		streamed["genesis"]["stateRoot"] = format!("{:?}", H256::zero()).into();
+		let spec = load(&streamed).unwrap();
+		assert_eq!(spec.state_root, H256::zero());
+		assert!(spec.ensure_db_good(get_temp_state_db(), &Default::default()).is_err());
+
+		// accounts declared twice, in the spec and the file or twice in the file, are rejected
+		std::fs::write(tempdir.path().join("accounts.json"), r#"{ "0x0000000000000000000000000000000000000001": { "balance": "1" } }"#).unwrap();
+		let error = load(&streamed).unwrap().ensure_db_good(get_temp_state_db(), &Default::default()).unwrap_err();
+		assert!(error.to_string().contains("both in the spec"), "{}", error);
+		std::fs::write(tempdir.path().join("accounts.json"), r#"{
+			"0x00000000000000000000000000000000000000aa": { "balance": "1" },
+			"0x00000000000000000000000000000000000000aa": { "balance": "2" }
+		}"#).unwrap();
+		let error = load(&streamed).unwrap().ensure_db_good(get_temp_state_db(), &Default::default()).unwrap_err();
+		assert!(error.to_string().contains("declared twice"), "{}", error);
+	}
+
+	#[test]
+	fn wasm_builtin_file() {
+		// module returning its input reversed, see the builtin crate
+		let module = "\
+			0061736d0100000001110460000060017f0060027f7f006000017f023f0403656e76066d656d6f72790201010103656e\
+			760c696e7075745f6c656e677468000303656e760b66657463685f696e707574000103656e7603726574000203020100\
+			0708010463616c6c00030a3d013b01027f100021004100100102400340200120004f0d01200020016a200020016b4101\
+			6b2d00003a0000200141016a21010c000b0b2000200010020b";
+		let module: Vec<u8> = (0..module.len()).step_by(2).map(|i| u8::from_str_radix(&module[i..i + 2], 16).unwrap()).collect();
+		let tempdir = TempDir::new().unwrap();
+		let load = |spec: &serde_json::Value| {
+			let params = SpecParams::from_path(tempdir.path()).with_spec_dir(tempdir.path());
+			Spec::load(params, serde_json::to_vec(spec).unwrap().as_slice())
+		};
+		let mut spec: serde_json::Value = serde_json::from_slice(include_bytes!("../../res/null_morden.json")).unwrap();
+		spec["accounts"]["0x0000000000000000000000000000000000000100"] = serde_json::json!({
+			"builtin": {
+				"name": "wasm",
+				"pricing": { "linear": { "base": 100, "word": 10 } },
+				"wasm": { "file": "reverse.wasm", "budget": 1000000 }
+			}
+		});
+		assert!(load(&spec).is_err());
+
+		std::fs::write(tempdir.path().join("reverse.wasm"), module).unwrap();
+		let spec = load(&spec).unwrap();
+		let builtin = spec.engine.machine().builtin(&Address::from_low_u64_be(0x100), 0).unwrap();
+		let mut output = [0u8; 3];
+		builtin.execute(&[1, 2, 3], &mut BytesRef::Fixed(&mut output[..])).unwrap();
+		assert_eq!(output, [3, 2, 1]);
+		assert_eq!(builtin.cost(&[1, 2, 3], 0), 110.into());
+	}
 }
@@ -0,0 +1,242 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parsing chain names into their specifications.
+
+use std::{fmt, fs, str};
+use std::path::Path;
+
+use crate::spec::{Spec, SpecParams};
+
+/// A chain supported by OpenEthereum, or the path of a chain specification file.
+#[derive(Debug, PartialEq)]
+pub enum SpecType {
+	Foundation,
+	Classic,
+	ClassicNoPhoenix,
+	Poanet,
+	Xdai,
+	Volta,
+	Ewc,
+	Musicoin,
+	Ellaism,
+	Mix,
+	Callisto,
+	EtherCore,
+	Mordor,
+	Ropsten,
+	Kovan,
+	Rinkeby,
+	Goerli,
+	YOLOv1,
+	Kotti,
+	Sokol,
+	Evantestcore,
+	Evancore,
+	Dev,
+	Custom(String),
+}
+
+impl Default for SpecType {
+	fn default() -> Self {
+		SpecType::Foundation
+	}
+}
+
+impl str::FromStr for SpecType {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let spec = match s {
+			"eth" | "ethereum"  | "foundation" | "mainnet" => SpecType::Foundation,
+			"etc" | "classic" => SpecType::Classic,
+			"classic-no-phoenix" | "classic-oppose-phoenix" | "classic-oppose-phoenix-fork" =>
+				SpecType::ClassicNoPhoenix,
+			"poanet" | "poacore" => SpecType::Poanet,
+			"xdai" => SpecType::Xdai,
+			"volta" => SpecType::Volta,
+			"ewc" | "energyweb" => SpecType::Ewc,
+			"musicoin" => SpecType::Musicoin,
+			"ellaism" => SpecType::Ellaism,
+			"mix" => SpecType::Mix,
+			"callisto" => SpecType::Callisto,
+			"ethercore" => SpecType::EtherCore,
+			"mordor" | "classic-testnet" => SpecType::Mordor,
+			"ropsten" => SpecType::Ropsten,
+			"kovan" => SpecType::Kovan,
+			"rinkeby" => SpecType::Rinkeby,
+			"goerli" | "görli" | "testnet" => SpecType::Goerli,
+			"yolo" | "yolov1" => SpecType::YOLOv1,
+			"kotti" => SpecType::Kotti,
+			"sokol" | "poasokol" => SpecType::Sokol,
+			"evantestcore" => SpecType::Evantestcore,
+			"evancore" => SpecType::Evancore,
+			"dev" => SpecType::Dev,
+			other => SpecType::Custom(other.into()),
+		};
+		Ok(spec)
+	}
+}
+
+impl fmt::Display for SpecType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match *self {
+			SpecType::Foundation => "foundation",
+			SpecType::Classic => "classic",
+			SpecType::ClassicNoPhoenix => "classic-no-phoenix",
+			SpecType::Poanet => "poanet",
+			SpecType::Xdai => "xdai",
+			SpecType::Volta => "volta",
+			SpecType::Ewc => "energyweb",
+			SpecType::Musicoin => "musicoin",
+			SpecType::Ellaism => "ellaism",
+			SpecType::Mix => "mix",
+			SpecType::Callisto => "callisto",
+			SpecType::EtherCore => "ethercore",
+			SpecType::Mordor => "mordor",
+			SpecType::Ropsten => "ropsten",
+			SpecType::Kovan => "kovan",
+			SpecType::Rinkeby => "rinkeby",
+			SpecType::Goerli => "goerli",
+			SpecType::YOLOv1 => "yolov1",
+			SpecType::Kotti => "kotti",
+			SpecType::Sokol => "sokol",
+			SpecType::Evantestcore => "evantestcore",
+			SpecType::Evancore => "evancore",
+			SpecType::Dev => "dev",
+			SpecType::Custom(ref custom) => custom,
+		})
+	}
+}
+
+impl SpecType {
+	/// Load the specification of the chain.
+	pub fn spec<'a, T: Into<SpecParams<'a>>>(&self, params: T) -> Result<Spec, String> {
+		let params = params.into();
+		match *self {
+			SpecType::Foundation => Ok(crate::new_foundation(params)),
+			SpecType::Classic => Ok(crate::new_classic(params)),
+			SpecType::ClassicNoPhoenix => Ok(crate::new_classic_no_phoenix(params)),
+			SpecType::Poanet => Ok(crate::new_poanet(params)),
+			SpecType::Xdai => Ok(crate::new_xdai(params)),
+			SpecType::Volta => Ok(crate::new_volta(params)),
+			SpecType::Ewc => Ok(crate::new_ewc(params)),
+			SpecType::Musicoin => Ok(crate::new_musicoin(params)),
+			SpecType::Ellaism => Ok(crate::new_ellaism(params)),
+			SpecType::Mix => Ok(crate::new_mix(params)),
+			SpecType::Callisto => Ok(crate::new_callisto(params)),
+			SpecType::EtherCore => Ok(crate::new_ethercore(params)),
+			SpecType::Mordor => Ok(crate::new_mordor(params)),
+			SpecType::Ropsten => Ok(crate::new_ropsten(params)),
+			SpecType::Kovan => Ok(crate::new_kovan(params)),
+			SpecType::Rinkeby => Ok(crate::new_rinkeby(params)),
+			SpecType::Goerli => Ok(crate::new_goerli(params)),
+			SpecType::YOLOv1 => Ok(crate::new_yolov1(params)),
+			SpecType::Kotti => Ok(crate::new_kotti(params)),
+			SpecType::Sokol => Ok(crate::new_sokol(params)),
+			SpecType::Evantestcore => Ok(crate::new_evantestcore(params)),
+			SpecType::Evancore => Ok(crate::new_evancore(params)),
+			SpecType::Dev => Ok(crate::new_instant()),
+			SpecType::Custom(ref filename) => {
+				let file = fs::File::open(filename).map_err(|e| format!("Could not load specification file at {}: {}", filename, e))?;
+				let dir = Path::new(filename).parent().unwrap_or_else(|| Path::new(""));
+				Spec::load(params.with_spec_dir(dir), file).map_err(|e| e.to_string())
+			}
+		}
+	}
+
+	/// Name of the chain in the data directory layout of old releases, if it differs.
+	pub fn legacy_fork_name(&self) -> Option<String> {
+		match *self {
+			SpecType::Classic => Some("classic".to_owned()),
+			SpecType::Musicoin => Some("musicoin".to_owned()),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::SpecType;
+
+	#[test]
+	fn test_spec_type_parsing() {
+		assert_eq!(SpecType::Foundation, "eth".parse().unwrap());
+		assert_eq!(SpecType::Foundation, "ethereum".parse().unwrap());
+		assert_eq!(SpecType::Foundation, "foundation".parse().unwrap());
+		assert_eq!(SpecType::Foundation, "mainnet".parse().unwrap());
+		assert_eq!(SpecType::Classic, "etc".parse().unwrap());
+		assert_eq!(SpecType::Classic, "classic".parse().unwrap());
+		assert_eq!(SpecType::Poanet, "poanet".parse().unwrap());
+		assert_eq!(SpecType::Poanet, "poacore".parse().unwrap());
+		assert_eq!(SpecType::Xdai, "xdai".parse().unwrap());
+		assert_eq!(SpecType::Volta, "volta".parse().unwrap());
+		assert_eq!(SpecType::Ewc, "ewc".parse().unwrap());
+		assert_eq!(SpecType::Ewc, "energyweb".parse().unwrap());
+		assert_eq!(SpecType::Musicoin, "musicoin".parse().unwrap());
+		assert_eq!(SpecType::Ellaism, "ellaism".parse().unwrap());
+		assert_eq!(SpecType::Mix, "mix".parse().unwrap());
+		assert_eq!(SpecType::Callisto, "callisto".parse().unwrap());
+		assert_eq!(SpecType::EtherCore, "ethercore".parse().unwrap());
+		assert_eq!(SpecType::Mordor, "mordor".parse().unwrap());
+		assert_eq!(SpecType::Mordor, "classic-testnet".parse().unwrap());
+		assert_eq!(SpecType::Ropsten, "ropsten".parse().unwrap());
+		assert_eq!(SpecType::Kovan, "kovan".parse().unwrap());
+		assert_eq!(SpecType::Rinkeby, "rinkeby".parse().unwrap());
+		assert_eq!(SpecType::Goerli, "goerli".parse().unwrap());
+		assert_eq!(SpecType::Goerli, "görli".parse().unwrap());
+		assert_eq!(SpecType::Goerli, "testnet".parse().unwrap());
+		assert_eq!(SpecType::YOLOv1, "yolo".parse().unwrap());
+		assert_eq!(SpecType::YOLOv1, "yolov1".parse().unwrap());
+		assert_eq!(SpecType::Kotti, "kotti".parse().unwrap());
+		assert_eq!(SpecType::Sokol, "sokol".parse().unwrap());
+		assert_eq!(SpecType::Sokol, "poasokol".parse().unwrap());
+		assert_eq!(SpecType::Evantestcore, "evantestcore".parse().unwrap());
+		assert_eq!(SpecType::Evancore, "evancore".parse().unwrap());
+	}
+
+	#[test]
+	fn test_spec_type_default() {
+		assert_eq!(SpecType::Foundation, SpecType::default());
+	}
+
+	#[test]
+	fn test_spec_type_display() {
+		assert_eq!(format!("{}", SpecType::Foundation), "foundation");
+		assert_eq!(format!("{}", SpecType::Classic), "classic");
+		assert_eq!(format!("{}", SpecType::Poanet), "poanet");
+		assert_eq!(format!("{}", SpecType::Xdai), "xdai");
+		assert_eq!(format!("{}", SpecType::Volta), "volta");
+		assert_eq!(format!("{}", SpecType::Ewc), "energyweb");
+		assert_eq!(format!("{}", SpecType::Musicoin), "musicoin");
+		assert_eq!(format!("{}", SpecType::Ellaism), "ellaism");
+		assert_eq!(format!("{}", SpecType::Mix), "mix");
+		assert_eq!(format!("{}", SpecType::Callisto), "callisto");
+		assert_eq!(format!("{}", SpecType::EtherCore), "ethercore");
+		assert_eq!(format!("{}", SpecType::Mordor), "mordor");
+		assert_eq!(format!("{}", SpecType::Ropsten), "ropsten");
+		assert_eq!(format!("{}", SpecType::Kovan), "kovan");
+		assert_eq!(format!("{}", SpecType::Rinkeby), "rinkeby");
+		assert_eq!(format!("{}", SpecType::Goerli), "goerli");
+		assert_eq!(format!("{}", SpecType::YOLOv1), "yolov1");
+		assert_eq!(format!("{}", SpecType::Kotti), "kotti");
+		assert_eq!(format!("{}", SpecType::Sokol), "sokol");
+		assert_eq!(format!("{}", SpecType::Evantestcore), "evantestcore");
+		assert_eq!(format!("{}", SpecType::Evancore), "evancore");
+		assert_eq!(format!("{}", SpecType::Dev), "dev");
+		assert_eq!(format!("{}", SpecType::Custom("foo/bar".into())), "foo/bar");
+	}
+}
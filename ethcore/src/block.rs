@@ -61,6 +61,7 @@ use machine::ExecutedBlock;
 ///
 /// It's a bit like a Vec<Transaction>, except that whenever a transaction is pushed, we execute it and
 /// maintain the system `state()`. We also archive execution receipts in preparation for later block creation.
+#[derive(Clone)]
 pub struct OpenBlock<'x> {
 	block: ExecutedBlock,
 	engine: &'x dyn Engine,
@@ -128,6 +129,7 @@ impl<'x> OpenBlock<'x> {
 
 		engine.machine().populate_from_parent(&mut r.block.header, parent, gas_floor_target, gas_ceil_target);
 		engine.populate_from_parent(&mut r.block.header, parent);
+		r.update_fee_fields();
 
 		engine.machine().on_new_block(&mut r.block)?;
 		engine.on_new_block(&mut r.block, is_epoch_begin)?;
@@ -140,6 +142,19 @@ impl<'x> OpenBlock<'x> {
 		self.block.header.set_timestamp(timestamp);
 	}
 
+	/// Alter the seal fields of the block before pushing transactions, e.g. to set the `prevRandao`
+	/// value of a proof-of-stake block.
+	pub fn set_seal_fields(&mut self, seal: Vec<Bytes>) {
+		self.block.header.set_seal(seal);
+		self.update_fee_fields();
+	}
+
+	/// Read the `prevRandao` value and the base fee the transactions are executed with from the header.
+	fn update_fee_fields(&mut self) {
+		self.block.prev_randao = self.engine.prev_randao(&self.block.header);
+		self.block.base_fee = self.engine.base_fee(&self.block.header);
+	}
+
 	/// Removes block gas limit.
 	pub fn remove_gas_limit(&mut self) {
 		self.block.header.set_gas_limit(U256::max_value());
@@ -168,6 +183,11 @@ impl<'x> OpenBlock<'x> {
 	///
 	/// If valid, it will be executed, and archived together with the receipt.
 	pub fn push_transaction(&mut self, t: SignedTransaction) -> Result<&Receipt, Error> {
+		self.push_transaction_with_output(t).map(|(receipt, _)| receipt)
+	}
+
+	/// Push a transaction into the block, returning its receipt together with the output of the call.
+	pub fn push_transaction_with_output(&mut self, t: SignedTransaction) -> Result<(&Receipt, Bytes), Error> {
 		if self.block.transactions_set.contains(&t.hash()) {
 			return Err(TransactionError::AlreadyImported.into());
 		}
@@ -181,7 +201,7 @@ impl<'x> OpenBlock<'x> {
 			traces.push(outcome.trace.into());
 		}
 		self.block.receipts.push(outcome.receipt);
-		Ok(self.block.receipts.last().expect("receipt just pushed; qed"))
+		Ok((self.block.receipts.last().expect("receipt just pushed; qed"), outcome.output))
 	}
 
 	/// Push transactions onto the block.
@@ -224,6 +244,7 @@ impl<'x> OpenBlock<'x> {
 		// such rewards affect the state and the state root (see
 		// https://github.com/openethereum/openethereum/pull/11475).
 		self.block.header.set_seal(header.seal().to_vec());
+		self.update_fee_fields();
 		// TODO: that's horrible. set only for backwards compatibility
 		if header.extra_data().len() > self.engine.maximum_extra_data_size() {
 			warn!("Couldn't set extradata. Ignoring.");
@@ -248,6 +269,7 @@ impl<'x> OpenBlock<'x> {
 	/// Turn this into a `LockedBlock`.
 	pub fn close_and_lock(self) -> Result<LockedBlock, Error> {
 		let mut s = self;
+		s.engine.machine().on_close_block(&mut s.block)?;
 		s.engine.on_close_block(&mut s.block, &s.parent)?;
 		s.block.state.commit()?;
 
@@ -418,6 +440,40 @@ pub(crate) fn enact(
 	last_hashes: Arc<LastHashes>,
 	factories: Factories,
 	is_epoch_begin: bool,
+) -> Result<LockedBlock, Error> {
+	enact_block(header, transactions, uncles, engine, tracing, db, parent, last_hashes, factories, is_epoch_begin, false)
+}
+
+/// Enact the block like `enact`, recording the accounts and storage keys it writes, including
+/// those written by the engine while closing the block, e.g. rewards and system calls.
+/// They can be read with `State::written_storage` and `State::changed_accounts`.
+pub(crate) fn enact_tracking_writes(
+	header: &Header,
+	transactions: Vec<SignedTransaction>,
+	uncles: Vec<Header>,
+	engine: &dyn Engine,
+	tracing: bool,
+	db: StateDB,
+	parent: &Header,
+	last_hashes: Arc<LastHashes>,
+	factories: Factories,
+	is_epoch_begin: bool,
+) -> Result<LockedBlock, Error> {
+	enact_block(header, transactions, uncles, engine, tracing, db, parent, last_hashes, factories, is_epoch_begin, true)
+}
+
+fn enact_block(
+	header: &Header,
+	transactions: Vec<SignedTransaction>,
+	uncles: Vec<Header>,
+	engine: &dyn Engine,
+	tracing: bool,
+	db: StateDB,
+	parent: &Header,
+	last_hashes: Arc<LastHashes>,
+	factories: Factories,
+	is_epoch_begin: bool,
+	track_writes: bool,
 ) -> Result<LockedBlock, Error> {
 	// For trace log
 	let trace_state = if log_enabled!(target: "enact", ::log::Level::Trace) {
@@ -440,6 +496,9 @@ pub(crate) fn enact(
 		vec![],
 		is_epoch_begin,
 	)?;
+	if track_writes {
+		b.block.state.track_writes();
+	}
 
 	if let Some(ref s) = trace_state {
 		let env = b.env_info();
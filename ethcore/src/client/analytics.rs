@@ -0,0 +1,356 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Flattening of chain data into rows of a stable schema, used by analytics exports.
+
+use bytes::ToPretty;
+use client_traits::{BlockChainClient, BlockInfo};
+use ethereum_types::{Address, H256};
+use trace::{LocalizedTrace, RewardType, trace::{Action, CallType, Res}};
+use types::{
+	BlockNumber,
+	ids::BlockId,
+	receipt::{LocalizedReceipt, TransactionOutcome},
+	transaction::Action as TransactionAction,
+};
+
+/// Table of an analytics export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsTable {
+	/// One row per block.
+	Blocks,
+	/// One row per transaction.
+	Transactions,
+	/// One row per transaction receipt.
+	Receipts,
+	/// One row per log.
+	Logs,
+	/// One row per trace, empty unless tracing is enabled.
+	Traces,
+}
+
+impl AnalyticsTable {
+	/// All the tables, in export order.
+	pub const ALL: [AnalyticsTable; 5] = [
+		AnalyticsTable::Blocks,
+		AnalyticsTable::Transactions,
+		AnalyticsTable::Receipts,
+		AnalyticsTable::Logs,
+		AnalyticsTable::Traces,
+	];
+
+	/// Name of the table.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			AnalyticsTable::Blocks => "blocks",
+			AnalyticsTable::Transactions => "transactions",
+			AnalyticsTable::Receipts => "receipts",
+			AnalyticsTable::Logs => "logs",
+			AnalyticsTable::Traces => "traces",
+		}
+	}
+
+	/// Column names of the table. Columns are only ever appended, so that existing
+	/// ingestion pipelines keep working.
+	pub fn columns(&self) -> &'static [&'static str] {
+		match *self {
+			AnalyticsTable::Blocks => &[
+				"number", "hash", "parent_hash", "timestamp", "author", "difficulty",
+				"gas_limit", "gas_used", "transaction_count", "uncle_count", "size",
+			],
+			AnalyticsTable::Transactions => &[
+				"block_number", "block_hash", "transaction_index", "hash", "from", "to",
+				"nonce", "value", "gas", "gas_price", "input",
+			],
+			AnalyticsTable::Receipts => &[
+				"block_number", "block_hash", "transaction_index", "transaction_hash", "status",
+				"state_root", "cumulative_gas_used", "gas_used", "contract_address", "log_count",
+			],
+			AnalyticsTable::Logs => &[
+				"block_number", "block_hash", "transaction_index", "transaction_hash", "log_index",
+				"transaction_log_index", "address", "topic0", "topic1", "topic2", "topic3", "data",
+			],
+			AnalyticsTable::Traces => &[
+				"block_number", "block_hash", "transaction_index", "transaction_hash", "trace_address",
+				"subtraces", "type", "call_type", "from", "to", "value", "gas", "input",
+				"gas_used", "output", "error",
+			],
+		}
+	}
+
+	/// Types of the columns of the table, in the order of `columns`.
+	pub fn column_types(&self) -> &'static [ColumnType] {
+		use self::ColumnType::{Bytes as B, Integer as I, Quantity as Q, Text as T};
+		match *self {
+			AnalyticsTable::Blocks => &[I, B, B, I, B, Q, Q, Q, I, I, I],
+			AnalyticsTable::Transactions => &[I, B, I, B, B, B, Q, Q, Q, Q, B],
+			AnalyticsTable::Receipts => &[I, B, I, B, I, B, Q, Q, B, I],
+			AnalyticsTable::Logs => &[I, B, I, B, I, I, B, B, B, B, B, B],
+			AnalyticsTable::Traces => &[I, B, I, B, T, I, T, T, B, B, Q, Q, B, Q, B, T],
+		}
+	}
+}
+
+/// Type of the values of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+	/// Unsigned integer of at most 64 bits, in decimal.
+	Integer,
+	/// 256-bit unsigned integer, in decimal.
+	Quantity,
+	/// Hash, address or bytes, in 0x-prefixed hex.
+	Bytes,
+	/// Text.
+	Text,
+}
+
+/// Rows of every table for a single block. Values are decimal for quantities,
+/// 0x-prefixed hex for hashes, addresses and bytes, and empty when absent.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AnalyticsRows {
+	/// Rows of the blocks table.
+	pub blocks: Vec<Vec<String>>,
+	/// Rows of the transactions table.
+	pub transactions: Vec<Vec<String>>,
+	/// Rows of the receipts table.
+	pub receipts: Vec<Vec<String>>,
+	/// Rows of the logs table.
+	pub logs: Vec<Vec<String>>,
+	/// Rows of the traces table.
+	pub traces: Vec<Vec<String>>,
+}
+
+impl AnalyticsRows {
+	/// Rows of the given table.
+	pub fn table(&self, table: AnalyticsTable) -> &[Vec<String>] {
+		match table {
+			AnalyticsTable::Blocks => &self.blocks,
+			AnalyticsTable::Transactions => &self.transactions,
+			AnalyticsTable::Receipts => &self.receipts,
+			AnalyticsTable::Logs => &self.logs,
+			AnalyticsTable::Traces => &self.traces,
+		}
+	}
+}
+
+fn hash(h: &H256) -> String {
+	format!("{:#x}", h)
+}
+
+fn address(a: &Address) -> String {
+	format!("{:#x}", a)
+}
+
+fn data(d: &[u8]) -> String {
+	format!("0x{}", d.to_hex())
+}
+
+/// Flatten the canonical block with the given number. Returns `None` if the block is unknown.
+pub fn analytics_rows<C: BlockChainClient + ?Sized>(client: &C, number: BlockNumber) -> Option<AnalyticsRows> {
+	let id = BlockId::Number(number);
+	let block = client.block(id)?;
+	let block_hash = hash(&block.hash());
+	let receipts = client.localized_block_receipts(id).unwrap_or_default();
+	let transactions = block.transactions();
+
+	let mut rows = AnalyticsRows::default();
+	rows.blocks.push(vec![
+		number.to_string(),
+		block_hash.clone(),
+		hash(&block.parent_hash()),
+		block.timestamp().to_string(),
+		address(&block.author()),
+		block.difficulty().to_string(),
+		block.gas_limit().to_string(),
+		block.gas_used().to_string(),
+		transactions.len().to_string(),
+		block.uncles_count().to_string(),
+		block.raw().len().to_string(),
+	]);
+
+	for (index, transaction) in transactions.iter().enumerate() {
+		let receipt = receipts.get(index).filter(|r| r.transaction_hash == transaction.hash());
+		rows.transactions.push(vec![
+			number.to_string(),
+			block_hash.clone(),
+			index.to_string(),
+			hash(&transaction.hash()),
+			receipt.map_or_else(String::new, |r| address(&r.from)),
+			match transaction.action {
+				TransactionAction::Call(ref to) => address(to),
+				TransactionAction::Create => String::new(),
+			},
+			transaction.nonce.to_string(),
+			transaction.value.to_string(),
+			transaction.gas.to_string(),
+			transaction.gas_price.to_string(),
+			data(&transaction.data),
+		]);
+	}
+
+	for receipt in &receipts {
+		push_receipt(&mut rows, receipt);
+	}
+
+	for trace in client.block_traces(id).unwrap_or_default() {
+		push_trace(&mut rows, &trace);
+	}
+
+	Some(rows)
+}
+
+fn push_receipt(rows: &mut AnalyticsRows, receipt: &LocalizedReceipt) {
+	let (status, state_root) = match receipt.outcome {
+		TransactionOutcome::Unknown => (String::new(), String::new()),
+		TransactionOutcome::StateRoot(ref root) => (String::new(), hash(root)),
+		TransactionOutcome::StatusCode(code) => (code.to_string(), String::new()),
+	};
+	rows.receipts.push(vec![
+		receipt.block_number.to_string(),
+		hash(&receipt.block_hash),
+		receipt.transaction_index.to_string(),
+		hash(&receipt.transaction_hash),
+		status,
+		state_root,
+		receipt.cumulative_gas_used.to_string(),
+		receipt.gas_used.to_string(),
+		receipt.contract_address.as_ref().map_or_else(String::new, address),
+		receipt.logs.len().to_string(),
+	]);
+
+	for log in &receipt.logs {
+		let mut row = vec![
+			log.block_number.to_string(),
+			hash(&log.block_hash),
+			log.transaction_index.to_string(),
+			hash(&log.transaction_hash),
+			log.log_index.to_string(),
+			log.transaction_log_index.to_string(),
+			address(&log.entry.address),
+		];
+		row.extend((0..4).map(|i| log.entry.topics.get(i).map_or_else(String::new, hash)));
+		row.push(data(&log.entry.data));
+		rows.logs.push(row);
+	}
+}
+
+fn push_trace(rows: &mut AnalyticsRows, trace: &LocalizedTrace) {
+	let (kind, call_type, from, to, value, gas, input) = match trace.action {
+		Action::Call(ref call) => (
+			"call",
+			match call.call_type.0 {
+				Some(CallType::Call) => "call",
+				Some(CallType::CallCode) => "callcode",
+				Some(CallType::DelegateCall) => "delegatecall",
+				Some(CallType::StaticCall) => "staticcall",
+				Some(CallType::AuthCall) => "authcall",
+				None => "",
+			},
+			address(&call.from),
+			address(&call.to),
+			call.value.to_string(),
+			call.gas.to_string(),
+			data(&call.input),
+		),
+		Action::Create(ref create) => (
+			"create",
+			"",
+			address(&create.from),
+			String::new(),
+			create.value.to_string(),
+			create.gas.to_string(),
+			data(&create.init),
+		),
+		Action::Suicide(ref suicide) => (
+			"suicide",
+			"",
+			address(&suicide.address),
+			address(&suicide.refund_address),
+			suicide.balance.to_string(),
+			String::new(),
+			String::new(),
+		),
+		Action::Reward(ref reward) => (
+			"reward",
+			match reward.reward_type {
+				RewardType::Block => "block",
+				RewardType::Uncle => "uncle",
+				RewardType::EmptyStep => "emptyStep",
+				RewardType::External => "external",
+			},
+			String::new(),
+			address(&reward.author),
+			reward.value.to_string(),
+			String::new(),
+			String::new(),
+		),
+	};
+	let (gas_used, output, error) = match trace.result {
+		Res::Call(ref result) => (result.gas_used.to_string(), data(&result.output), String::new()),
+		Res::Create(ref result) => (result.gas_used.to_string(), address(&result.address), String::new()),
+		Res::FailedCall(ref e) | Res::FailedCreate(ref e) => (String::new(), String::new(), e.to_string()),
+		Res::None => (String::new(), String::new(), String::new()),
+	};
+
+	rows.traces.push(vec![
+		trace.block_number.to_string(),
+		hash(&trace.block_hash),
+		trace.transaction_number.map_or_else(String::new, |n| n.to_string()),
+		trace.transaction_hash.as_ref().map_or_else(String::new, hash),
+		trace.trace_address.iter().map(ToString::to_string).collect::<Vec<_>>().join("."),
+		trace.subtraces.to_string(),
+		kind.to_owned(),
+		call_type.to_owned(),
+		from,
+		to,
+		value,
+		gas,
+		input,
+		gas_used,
+		output,
+		error,
+	]);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use test_helpers::{TestBlockChainClient, EachBlockWith};
+
+	#[test]
+	fn rows_match_the_schema() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(2, EachBlockWith::Transaction);
+
+		let rows = analytics_rows(&client, 1).unwrap();
+		assert_eq!(rows.blocks.len(), 1);
+		assert_eq!(rows.transactions.len(), 1);
+		for table in AnalyticsTable::ALL.iter() {
+			assert_eq!(table.column_types().len(), table.columns().len(), "{}", table.name());
+			for row in rows.table(*table) {
+				assert_eq!(row.len(), table.columns().len(), "{}", table.name());
+			}
+		}
+		assert_eq!(rows.blocks[0][0], "1");
+		assert_eq!(rows.blocks[0][8], "1");
+	}
+
+	#[test]
+	fn unknown_block_has_no_rows() {
+		let client = TestBlockChainClient::new();
+		assert!(analytics_rows(&client, 10).is_none());
+	}
+}
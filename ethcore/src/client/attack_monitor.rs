@@ -0,0 +1,327 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Heuristics detecting majority attacks from the shape of the imported chain.
+//!
+//! Honest forks are short-lived: competing branches rarely grow past a block or two
+//! and the difficulty only moves gradually. An attacker mining a private chain shows
+//! up as a long competing branch growing faster than the canon chain, a sudden
+//! difficulty swing and deep or repeated reorganizations.
+
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use ethereum_types::{H256, U256};
+use parking_lot::Mutex;
+use types::threat_status::{ThreatLevel, ThreatStatus};
+
+/// Number of the most recently imported blocks the status is computed over.
+const WINDOW: usize = 256;
+/// Reorganizations retracting at least this many blocks are considered deep.
+const DEEP_REORG_DEPTH: u64 = 3;
+/// Deep reorganizations in the window making an attack likely.
+const REPEATED_DEEP_REORGS: u64 = 2;
+/// Competing branch lengths raising the level to elevated and high.
+const ELEVATED_SIDE_BRANCH: u64 = 3;
+const HIGH_SIDE_BRANCH: u64 = 6;
+/// Difficulty swings between a block and its parent, in per mille, raising the level to elevated and high.
+const ELEVATED_DIFFICULTY_SWING: u64 = 100;
+const HIGH_DIFFICULTY_SWING: u64 = 250;
+/// Blocks a competing branch needs before its growth rate is compared to the canon chain's.
+const MIN_RATE_BLOCKS: usize = 3;
+/// How many times faster than the canon chain a competing branch grows to raise the level to high.
+const HIGH_RATE_FACTOR: u64 = 2;
+
+/// What the import of a single block looked like.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ImportedBlock {
+	/// Whether the block became canon.
+	pub canon: bool,
+	/// Length of the block's branch from its common ancestor with the canon chain, 0 for canon blocks.
+	pub side_branch: u64,
+	/// Common ancestor of the block's branch with the canon chain, `None` for canon blocks.
+	pub fork_point: Option<H256>,
+	/// Difficulty change from the parent, in per mille. Only set on proof-of-work chains, where
+	/// the difficulty follows the hash rate.
+	pub difficulty_swing: u64,
+	/// Number of canon blocks retracted, or held back from being retracted, by the import.
+	pub reorg_depth: u64,
+	/// Whether the block was kept out of the canon chain for reorganizing it deeper than allowed.
+	pub held: bool,
+}
+
+impl ImportedBlock {
+	/// Difficulty change between a block and its parent, in per mille.
+	pub fn difficulty_swing(difficulty: &U256, parent_difficulty: &U256) -> u64 {
+		if parent_difficulty.is_zero() {
+			return 0;
+		}
+		let change = if difficulty > parent_difficulty {
+			*difficulty - *parent_difficulty
+		} else {
+			*parent_difficulty - *difficulty
+		};
+		let swing = change.saturating_mul(1000.into()) / *parent_difficulty;
+		cmp::min(swing, u64::max_value().into()).low_u64()
+	}
+}
+
+/// Tracks the recently imported blocks for signs of an attack.
+#[derive(Default)]
+pub struct AttackMonitor {
+	imported: Mutex<VecDeque<(Instant, ImportedBlock)>>,
+	level: Mutex<ThreatLevel>,
+}
+
+impl AttackMonitor {
+	/// Records a block imported now. Returns the new status if the threat level changed.
+	pub fn note_import(&self, block: ImportedBlock) -> Option<ThreatStatus> {
+		self.note_import_at(Instant::now(), block)
+	}
+
+	fn note_import_at(&self, at: Instant, block: ImportedBlock) -> Option<ThreatStatus> {
+		let status = {
+			let mut imported = self.imported.lock();
+			if imported.len() == WINDOW {
+				imported.pop_front();
+			}
+			imported.push_back((at, block));
+			status(&imported)
+		};
+
+		let mut level = self.level.lock();
+		if *level == status.level {
+			return None;
+		}
+		*level = status.level;
+		Some(status)
+	}
+
+	/// Signs of an attack in the recently imported blocks.
+	pub fn status(&self) -> ThreatStatus {
+		status(&self.imported.lock())
+	}
+}
+
+/// Blocks per hour between the first and the last of blocks imported at the given instants, in
+/// import order. Blocks imported within a second count as imported over one.
+fn rate(instants: &[Instant]) -> u64 {
+	match (instants.first(), instants.last()) {
+		(Some(first), Some(last)) if instants.len() > 1 => {
+			let millis = cmp::max(last.duration_since(*first).as_millis(), 1_000) as u64;
+			(instants.len() as u64 - 1) * 3_600_000 / millis
+		},
+		_ => 0,
+	}
+}
+
+fn status(imported: &VecDeque<(Instant, ImportedBlock)>) -> ThreatStatus {
+	let mut status = ThreatStatus {
+		window: imported.len() as u64,
+		..Default::default()
+	};
+
+	let mut canon = Vec::new();
+	let mut branches: HashMap<H256, Vec<Instant>> = HashMap::new();
+	for (at, block) in imported {
+		match block.fork_point {
+			Some(fork_point) if !block.canon => branches.entry(fork_point).or_default().push(*at),
+			_ if block.canon => canon.push(*at),
+			_ => {},
+		}
+	}
+	status.canon_rate = rate(&canon);
+	status.fastest_side_branch_rate = branches.values()
+		.filter(|branch| branch.len() >= MIN_RATE_BLOCKS)
+		.map(|branch| rate(branch))
+		.max()
+		.unwrap_or(0);
+
+	for (_, block) in imported {
+		if !block.canon {
+			status.side_blocks += 1;
+		}
+		if block.reorg_depth >= DEEP_REORG_DEPTH && !block.held {
+			status.deep_reorgs += 1;
+		}
+		if block.held {
+			status.held_blocks += 1;
+		}
+		status.longest_side_branch = cmp::max(status.longest_side_branch, block.side_branch);
+		status.max_difficulty_swing = cmp::max(status.max_difficulty_swing, block.difficulty_swing);
+		status.max_reorg_depth = cmp::max(status.max_reorg_depth, block.reorg_depth);
+	}
+
+	let (branch, swing, deep_reorgs, held_blocks, max_reorg_depth, canon_rate, side_rate) = (
+		status.longest_side_branch,
+		status.max_difficulty_swing,
+		status.deep_reorgs,
+		status.held_blocks,
+		status.max_reorg_depth,
+		status.canon_rate,
+		status.fastest_side_branch_rate,
+	);
+	let mut raise = |level: ThreatLevel, reason: String| {
+		status.level = cmp::max(status.level, level);
+		status.reasons.push(reason);
+	};
+
+	if branch >= ELEVATED_SIDE_BRANCH {
+		let level = if branch >= HIGH_SIDE_BRANCH { ThreatLevel::High } else { ThreatLevel::Elevated };
+		raise(level, format!("competing branch of {} blocks", branch));
+	}
+
+	// a competing branch outpacing the canon chain is mined with more hash rate, or was mined in
+	// private and is released at once.
+	if side_rate > canon_rate {
+		let level = if side_rate > canon_rate.saturating_mul(HIGH_RATE_FACTOR) { ThreatLevel::High } else { ThreatLevel::Elevated };
+		raise(level, format!("competing branch growing by {} blocks per hour, against {} for the canon chain", side_rate, canon_rate));
+	}
+
+	if swing >= ELEVATED_DIFFICULTY_SWING {
+		let level = if swing >= HIGH_DIFFICULTY_SWING { ThreatLevel::High } else { ThreatLevel::Elevated };
+		raise(level, format!("difficulty changed by {}.{}% in one block", swing / 10, swing % 10));
+	}
+
+	if deep_reorgs > 0 {
+		let level = if deep_reorgs >= REPEATED_DEEP_REORGS { ThreatLevel::High } else { ThreatLevel::Elevated };
+		raise(level, format!("{} reorganizations of at least {} blocks", deep_reorgs, DEEP_REORG_DEPTH));
+	}
+
+	if held_blocks > 0 {
+		raise(ThreatLevel::High, format!("reorganization of {} blocks held back", max_reorg_depth));
+	}
+
+	status
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::*;
+
+	fn canon() -> ImportedBlock {
+		ImportedBlock { canon: true, ..Default::default() }
+	}
+
+	fn side(branch: u64) -> ImportedBlock {
+		ImportedBlock { side_branch: branch, ..Default::default() }
+	}
+
+	#[test]
+	fn ignores_short_forks() {
+		let monitor = AttackMonitor::default();
+		for _ in 0..10 {
+			assert_eq!(monitor.note_import(canon()), None);
+		}
+		assert_eq!(monitor.note_import(side(1)), None);
+		assert_eq!(monitor.note_import(side(2)), None);
+
+		let status = monitor.status();
+		assert_eq!(status.level, ThreatLevel::Normal);
+		assert_eq!(status.window, 12);
+		assert_eq!(status.side_blocks, 2);
+		assert_eq!(status.longest_side_branch, 2);
+	}
+
+	#[test]
+	fn raises_level_as_competing_branch_grows() {
+		let monitor = AttackMonitor::default();
+		monitor.note_import(canon());
+		for branch in 1..3 {
+			assert_eq!(monitor.note_import(side(branch)), None);
+		}
+		assert_eq!(monitor.note_import(side(3)).unwrap().level, ThreatLevel::Elevated);
+		assert_eq!(monitor.note_import(side(4)), None);
+		assert_eq!(monitor.note_import(side(5)), None);
+
+		let status = monitor.note_import(side(6)).unwrap();
+		assert_eq!(status.level, ThreatLevel::High);
+		assert_eq!(status.reasons, vec!["competing branch of 6 blocks".to_owned()]);
+	}
+
+	#[test]
+	fn repeated_deep_reorgs_are_high() {
+		let monitor = AttackMonitor::default();
+		let reorg = ImportedBlock { canon: true, reorg_depth: 4, ..Default::default() };
+		assert_eq!(monitor.note_import(reorg).unwrap().level, ThreatLevel::Elevated);
+		assert_eq!(monitor.note_import(canon()), None);
+
+		let status = monitor.note_import(reorg).unwrap();
+		assert_eq!(status.level, ThreatLevel::High);
+		assert_eq!(status.deep_reorgs, 2);
+		assert_eq!(status.max_reorg_depth, 4);
+	}
+
+	#[test]
+	fn forgets_blocks_out_of_window() {
+		let monitor = AttackMonitor::default();
+		let held = ImportedBlock { side_branch: 1, reorg_depth: 10, held: true, ..Default::default() };
+		assert_eq!(monitor.note_import(held).unwrap().level, ThreatLevel::High);
+
+		for _ in 1..WINDOW {
+			monitor.note_import(canon());
+		}
+		assert_eq!(monitor.status().held_blocks, 1);
+		assert_eq!(monitor.note_import(canon()).unwrap().level, ThreatLevel::Normal);
+	}
+
+	#[test]
+	fn raises_level_when_competing_branch_outpaces_canon_chain() {
+		let monitor = AttackMonitor::default();
+		let start = Instant::now();
+		// the canon chain grows by a block every 15 seconds.
+		for i in 0..20 {
+			assert_eq!(monitor.note_import_at(start + Duration::from_secs(i * 15), canon()), None);
+		}
+
+		// a competing branch growing as fast is only flagged for its length.
+		let fork_point = Some(H256::from_low_u64_be(1));
+		let slow = |branch| ImportedBlock { side_branch: branch, fork_point, ..Default::default() };
+		let now = start + Duration::from_secs(300);
+		for branch in 1..4 {
+			monitor.note_import_at(now + Duration::from_secs((branch - 1) * 15), slow(branch));
+		}
+		let status = monitor.status();
+		assert_eq!(status.reasons, vec!["competing branch of 3 blocks".to_owned()]);
+		assert_eq!(status.canon_rate, 240);
+		assert_eq!(status.fastest_side_branch_rate, 240);
+
+		// another one released at once is.
+		let fork_point = Some(H256::from_low_u64_be(2));
+		let fast = |branch| ImportedBlock { side_branch: branch, fork_point, ..Default::default() };
+		let now = now + Duration::from_secs(60);
+		monitor.note_import_at(now, fast(1));
+		monitor.note_import_at(now, fast(2));
+		let status = monitor.note_import_at(now, fast(3)).unwrap();
+		assert_eq!(status.level, ThreatLevel::High);
+		assert_eq!(status.fastest_side_branch_rate, 2 * 3_600);
+	}
+
+	#[test]
+	fn measures_difficulty_swing() {
+		assert_eq!(ImportedBlock::difficulty_swing(&1100.into(), &1000.into()), 100);
+		assert_eq!(ImportedBlock::difficulty_swing(&700.into(), &1000.into()), 300);
+		assert_eq!(ImportedBlock::difficulty_swing(&1000.into(), &0.into()), 0);
+
+		let monitor = AttackMonitor::default();
+		let status = monitor.note_import(ImportedBlock { canon: true, difficulty_swing: 300, ..Default::default() }).unwrap();
+		assert_eq!(status.level, ThreatLevel::High);
+		assert_eq!(status.reasons, vec!["difficulty changed by 30.0% in one block".to_owned()]);
+	}
+}
@@ -16,27 +16,77 @@
 
 //! Stores recently seen bad blocks.
 
+use std::sync::Arc;
+
 use bytes::{Bytes, ToPretty};
+use db::COL_BAD_BLOCKS;
 use ethereum_types::H256;
 use itertools::Itertools;
+use kvdb::{DBTransaction, KeyValueDB};
 use memory_cache::MemoryLruCache;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rlp::{Rlp, RlpStream};
 use types::verification::Unverified;
 
+/// Number of the most recent bad blocks kept in the database.
+const PERSISTED_BAD_BLOCKS: u64 = 64;
+
 /// Recently seen bad blocks.
 pub struct BadBlocks {
-	last_blocks: RwLock<MemoryLruCache<H256, (Unverified, String)>>,
+	last_blocks: RwLock<MemoryLruCache<H256, (Unverified, String, Option<String>)>>,
+	/// Diagnostics gathered on a block before it is reported.
+	noted: Mutex<Option<(H256, String)>>,
+	/// Database the most recent bad blocks are persisted to, if any.
+	db: Option<Arc<dyn KeyValueDB>>,
+	next_index: Mutex<u64>,
 }
 
 impl Default for BadBlocks {
 	fn default() -> Self {
 		BadBlocks {
 			last_blocks: RwLock::new(MemoryLruCache::new(8 * 1024 * 1024)),
+			noted: Mutex::new(None),
+			db: None,
+			next_index: Mutex::new(0),
 		}
 	}
 }
 
 impl BadBlocks {
+	/// Creates a store persisting the most recent bad blocks to `db`, loading those
+	/// persisted by previous runs.
+	pub fn new(db: Arc<dyn KeyValueDB>) -> Self {
+		let mut persisted: Vec<(u64, Bytes, String, Option<String>)> = db.iter(COL_BAD_BLOCKS)
+			.filter_map(|(key, value)| {
+				let index = index_from_key(&key)?;
+				let rlp = Rlp::new(&value);
+				let diagnostics: String = rlp.val_at(2).ok()?;
+				Some((index, rlp.val_at(0).ok()?, rlp.val_at(1).ok()?, Some(diagnostics).filter(|d| !d.is_empty())))
+			})
+			.collect();
+		persisted.sort_by_key(|&(index, ..)| index);
+
+		let store = BadBlocks {
+			next_index: Mutex::new(persisted.last().map_or(0, |&(index, ..)| index + 1)),
+			db: Some(db),
+			..Default::default()
+		};
+		{
+			let mut last_blocks = store.last_blocks.write();
+			for (_, raw, message, diagnostics) in persisted {
+				if let Ok(unverified) = Unverified::from_rlp(raw) {
+					last_blocks.insert(unverified.header.hash(), (unverified, message, diagnostics));
+				}
+			}
+		}
+		store
+	}
+
+	/// Attaches diagnostics to the block with given hash, to be kept along with it once reported.
+	pub fn note_diagnostics(&self, hash: H256, diagnostics: String) {
+		*self.noted.lock() = Some((hash, diagnostics));
+	}
+
 	/// Reports given RLP as invalid block.
 	pub fn report(&self, raw: Bytes, message: String) {
 		match Unverified::from_rlp(raw) {
@@ -58,7 +108,13 @@ impl BadBlocks {
 						.map(|(index, tx)| format!("[Tx {}] {:?}", index, tx))
 						.join("\n"),
 				);
-				self.last_blocks.write().insert(unverified.header.hash(), (unverified, message));
+				let hash = unverified.header.hash();
+				let diagnostics = match self.noted.lock().take() {
+					Some((noted, diagnostics)) if noted == hash => Some(diagnostics),
+					_ => None,
+				};
+				self.persist(&unverified.bytes, &message, diagnostics.as_ref());
+				self.last_blocks.write().insert(hash, (unverified, message, diagnostics));
 			},
 			Err(err) => {
 				error!(target: "client", "Bad undecodable block detected: {}\n{:?}", message, err);
@@ -71,11 +127,94 @@ impl BadBlocks {
 		self.last_blocks.read()
 			.backstore()
 			.iter()
-			.map(|(_k, (unverified, message))| (
+			.map(|(_k, (unverified, message, _))| (
 				Unverified::from_rlp(unverified.bytes.clone())
 					.expect("Bytes coming from UnverifiedBlock so decodable; qed"),
 				message.clone(),
 			))
 			.collect()
 	}
+
+	/// Returns the diagnostics gathered on a recently detected bad block, if any.
+	pub fn diagnostics(&self, hash: &H256) -> Option<String> {
+		self.last_blocks.read()
+			.backstore()
+			.iter()
+			.find(|(k, _)| *k == hash)
+			.and_then(|(_, (_, _, diagnostics))| diagnostics.clone())
+	}
+
+	/// Writes a bad block to the database, dropping the oldest one beyond the cap.
+	fn persist(&self, raw: &[u8], message: &str, diagnostics: Option<&String>) {
+		let db = match self.db {
+			Some(ref db) => db,
+			None => return,
+		};
+
+		let mut next_index = self.next_index.lock();
+		let index = *next_index;
+		*next_index += 1;
+
+		let mut record = RlpStream::new_list(3);
+		record.append(&raw);
+		record.append(&message);
+		record.append(&diagnostics.map_or("", |d| d.as_str()));
+
+		let mut batch = DBTransaction::new();
+		batch.put(COL_BAD_BLOCKS, &index.to_be_bytes(), &record.out());
+		if index >= PERSISTED_BAD_BLOCKS {
+			batch.delete(COL_BAD_BLOCKS, &(index - PERSISTED_BAD_BLOCKS).to_be_bytes());
+		}
+		if let Err(e) = db.write(batch) {
+			warn!(target: "client", "Failed to persist a bad block: {}", e);
+		}
+	}
+}
+
+fn index_from_key(key: &[u8]) -> Option<u64> {
+	let mut index = [0u8; 8];
+	if key.len() != index.len() {
+		return None;
+	}
+	index.copy_from_slice(key);
+	Some(u64::from_be_bytes(index))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use kvdb_memorydb;
+	use test_helpers::{get_bad_state_dummy_block, get_good_dummy_block};
+	use types::verification::Unverified;
+	use super::{BadBlocks, PERSISTED_BAD_BLOCKS};
+
+	#[test]
+	fn keeps_bad_blocks_across_restarts() {
+		let db = Arc::new(kvdb_memorydb::create(::db::NUM_COLUMNS));
+		let bad = Unverified::from_rlp(get_bad_state_dummy_block()).unwrap().header.hash();
+
+		let store = BadBlocks::new(db.clone());
+		store.note_diagnostics(bad, "{}".into());
+		store.report(get_bad_state_dummy_block(), "Bad state root".into());
+		store.report(get_good_dummy_block(), "Unknown parent".into());
+		assert_eq!(store.bad_blocks().len(), 2);
+
+		let store = BadBlocks::new(db);
+		let mut reasons: Vec<_> = store.bad_blocks().into_iter().map(|(_, reason)| reason).collect();
+		reasons.sort();
+		assert_eq!(reasons, vec!["Bad state root".to_owned(), "Unknown parent".to_owned()]);
+		assert_eq!(store.diagnostics(&bad), Some("{}".into()));
+	}
+
+	#[test]
+	fn caps_persisted_bad_blocks() {
+		let db = Arc::new(kvdb_memorydb::create(::db::NUM_COLUMNS));
+		let store = BadBlocks::new(db.clone());
+		for _ in 0..PERSISTED_BAD_BLOCKS + 3 {
+			store.report(get_bad_state_dummy_block(), "Bad state root".into());
+		}
+		assert_eq!(db.iter(::db::COL_BAD_BLOCKS).count() as u64, PERSISTED_BAD_BLOCKS);
+		assert_eq!(*BadBlocks::new(db).next_index.lock(), PERSISTED_BAD_BLOCKS + 3);
+	}
 }
@@ -0,0 +1,350 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Results of `eth_call`s on the best block, kept across blocks which don't touch their state.
+//!
+//! Every entry remembers the accounts and storage keys the call looked up. When a block is
+//! imported on top of the block an entry was computed on, the entry survives unless the block
+//! changed the balance, nonce or code of one of those accounts or wrote one of those storage
+//! keys. Storage is only read by the code of its account, so a call reading the storage of an
+//! account which was created or removed read the account too. Calls reading the block
+//! environment (number, timestamp, block hashes, ...) are only answered from the cache on the
+//! block they were executed on.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use account_state::StateReads;
+use ethereum_types::{Address, H256, U256};
+use machine::executed::Executed;
+use parking_lot::Mutex;
+use trace::VMTracer;
+
+/// Instructions whose result depends on the block a call is executed on:
+/// BLOCKHASH, COINBASE, TIMESTAMP, NUMBER, DIFFICULTY and GASLIMIT.
+const ENV_INSTRUCTIONS: ::std::ops::RangeInclusive<u8> = 0x40..=0x45;
+
+/// Calls are identified by the transaction hash and the sender, which unsigned transactions
+/// don't commit to.
+type Key = (H256, Address);
+
+struct Entry {
+	executed: Executed,
+	/// Accounts and storage keys looked up by the call.
+	reads: StateReads,
+	/// Whether the result depends on the block environment.
+	pinned: bool,
+}
+
+impl Entry {
+	fn is_affected_by(&self, changed: &BTreeSet<Address>, written: &BTreeMap<Address, BTreeSet<H256>>) -> bool {
+		self.pinned
+			|| self.reads.accounts.iter().any(|address| changed.contains(address))
+			|| self.reads.storage.iter().any(|(address, key)| written.get(address).map_or(false, |keys| keys.contains(key)))
+	}
+}
+
+#[derive(Default)]
+struct Inner {
+	/// Block the entries are valid for.
+	head: H256,
+	entries: HashMap<Key, Entry>,
+	/// Insertion order of the entries, oldest first.
+	order: VecDeque<Key>,
+}
+
+/// Counters of the cache's effectiveness.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CallCacheStats {
+	/// Calls answered from the cache.
+	pub hits: u64,
+	/// Calls executed.
+	pub misses: u64,
+	/// Entries carried over to a new best block.
+	pub promoted: u64,
+	/// Entries dropped because a new best block modified the state they read.
+	pub invalidated: u64,
+}
+
+/// Bounded cache of call results on the best block.
+pub struct CallCache {
+	capacity: usize,
+	inner: Mutex<Inner>,
+	hits: AtomicU64,
+	misses: AtomicU64,
+	promoted: AtomicU64,
+	invalidated: AtomicU64,
+}
+
+impl CallCache {
+	/// Create a cache holding at most `capacity` results, with `head` being the best block.
+	pub fn new(capacity: usize, head: H256) -> Self {
+		CallCache {
+			capacity,
+			inner: Mutex::new(Inner { head, ..Default::default() }),
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
+			promoted: AtomicU64::new(0),
+			invalidated: AtomicU64::new(0),
+		}
+	}
+
+	/// Result of the call made by `sender` with transaction `hash` on block `head`, if known.
+	pub fn get(&self, head: &H256, hash: &H256, sender: &Address) -> Option<Executed> {
+		let inner = self.inner.lock();
+		let executed = if inner.head == *head {
+			inner.entries.get(&(*hash, *sender)).map(|entry| entry.executed.clone())
+		} else {
+			None
+		};
+		match executed {
+			Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+			None => self.misses.fetch_add(1, Ordering::Relaxed),
+		};
+		executed
+	}
+
+	/// Remember the result of a call executed on block `head` which looked up `reads`, and read
+	/// the block environment if `pinned`, see `EnvironmentObserver`.
+	pub fn insert(&self, head: H256, hash: H256, sender: Address, executed: Executed, reads: StateReads, pinned: bool) {
+		let mut inner = self.inner.lock();
+		if inner.head != head {
+			// Computed on a block which is no longer the best one.
+			return;
+		}
+		let key = (hash, sender);
+		if inner.entries.insert(key, Entry { executed, reads, pinned }).is_none() {
+			inner.order.push_back(key);
+		}
+		while inner.entries.len() > self.capacity {
+			let oldest = inner.order.pop_front().expect("order holds every key of entries; entries are not empty; qed");
+			inner.entries.remove(&oldest);
+		}
+	}
+
+	/// Note that `hash` became the best block, which `changed` the balance, nonce or code of some
+	/// accounts and `written` some of their storage keys. If its parent is the block the entries
+	/// were valid for, entries not reading any of them are kept for the new block, otherwise the
+	/// cache is cleared.
+	pub fn advance(&self, parent: &H256, hash: H256, changed: &BTreeSet<Address>, written: &BTreeMap<Address, BTreeSet<H256>>) {
+		let mut guard = self.inner.lock();
+		let inner = &mut *guard;
+		if inner.head == hash {
+			return;
+		}
+		let before = inner.entries.len();
+		if inner.head == *parent {
+			inner.entries.retain(|_, entry| !entry.is_affected_by(changed, written));
+		} else {
+			inner.entries.clear();
+		}
+		let entries = &inner.entries;
+		inner.order.retain(|key| entries.contains_key(key));
+		self.promoted.fetch_add(entries.len() as u64, Ordering::Relaxed);
+		self.invalidated.fetch_add((before - entries.len()) as u64, Ordering::Relaxed);
+		inner.head = hash;
+	}
+
+	/// Counters of the cache's effectiveness.
+	pub fn stats(&self) -> CallCacheStats {
+		CallCacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			promoted: self.promoted.load(Ordering::Relaxed),
+			invalidated: self.invalidated.load(Ordering::Relaxed),
+		}
+	}
+
+	/// Number of results in the cache.
+	pub fn len(&self) -> usize {
+		self.inner.lock().entries.len()
+	}
+}
+
+/// VM tracer noting whether an execution read the block environment, without recording anything.
+#[derive(Default)]
+pub struct EnvironmentObserver {
+	/// Whether an instruction of each running frame was seen, innermost last.
+	frames: Vec<bool>,
+	reads_environment: bool,
+}
+
+impl VMTracer for EnvironmentObserver {
+	type Output = bool;
+
+	fn trace_next_instruction(&mut self, _pc: usize, instruction: u8, _current_gas: U256) -> bool {
+		if let Some(seen) = self.frames.last_mut() {
+			*seen = true;
+		}
+		self.reads_environment |= ENV_INSTRUCTIONS.contains(&instruction);
+		// the other instructions of the frame no longer matter.
+		!self.reads_environment
+	}
+
+	fn prepare_subtrace(&mut self, code: &[u8]) {
+		self.frames.push(code.is_empty());
+	}
+
+	fn done_subtrace(&mut self) {
+		// code run without reporting any instruction, e.g. by a VM which doesn't support
+		// tracing, is assumed to read the environment.
+		if let Some(false) = self.frames.pop() {
+			self.reads_environment = true;
+		}
+	}
+
+	fn drain(self) -> Option<bool> {
+		Some(self.reads_environment)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn executed(output: u8) -> Executed {
+		Executed {
+			exception: None,
+			gas: Default::default(),
+			gas_used: Default::default(),
+			refunded: Default::default(),
+			cumulative_gas_used: Default::default(),
+			logs: Vec::new(),
+			contracts_created: Vec::new(),
+			output: vec![output],
+			trace: Vec::new(),
+			vm_trace: None,
+			state_diff: None,
+		}
+	}
+
+	fn reads(accounts: &[u64], storage: &[(u64, u64)]) -> StateReads {
+		StateReads {
+			accounts: accounts.iter().map(|&a| Address::from_low_u64_be(a)).collect(),
+			storage: storage.iter().map(|&(a, key)| (Address::from_low_u64_be(a), H256::from_low_u64_be(key))).collect(),
+		}
+	}
+
+	fn changed(accounts: &[u64]) -> BTreeSet<Address> {
+		accounts.iter().map(|&a| Address::from_low_u64_be(a)).collect()
+	}
+
+	fn written(storage: &[(u64, u64)]) -> BTreeMap<Address, BTreeSet<H256>> {
+		let mut written = BTreeMap::new();
+		for &(a, key) in storage {
+			written.entry(Address::from_low_u64_be(a)).or_insert_with(BTreeSet::new).insert(H256::from_low_u64_be(key));
+		}
+		written
+	}
+
+	#[test]
+	fn keeps_results_across_blocks_not_touching_them() {
+		let cache = CallCache::new(16, H256::zero());
+		let (b0, b1, b2) = (H256::from_low_u64_be(0), H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+		let (call, sender) = (H256::from_low_u64_be(10), Address::from_low_u64_be(100));
+		cache.insert(b0, call, sender, executed(1), reads(&[1], &[]), false);
+
+		assert_eq!(cache.get(&b0, &call, &sender).unwrap().output, vec![1]);
+
+		cache.advance(&b0, b1, &changed(&[2, 3]), &written(&[]));
+		assert_eq!(cache.get(&b1, &call, &sender).unwrap().output, vec![1]);
+		assert!(cache.get(&b0, &call, &sender).is_none());
+
+		cache.advance(&b1, b2, &changed(&[1]), &written(&[]));
+		assert!(cache.get(&b2, &call, &sender).is_none());
+		assert_eq!(cache.stats(), CallCacheStats { hits: 2, misses: 2, promoted: 1, invalidated: 1 });
+	}
+
+	#[test]
+	fn invalidates_results_per_storage_key() {
+		let cache = CallCache::new(16, H256::zero());
+		let (b0, b1, b2) = (H256::from_low_u64_be(0), H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+		let (call, sender) = (H256::from_low_u64_be(10), Address::from_low_u64_be(100));
+		cache.insert(b0, call, sender, executed(1), reads(&[1], &[(1, 5)]), false);
+
+		// other keys of the account the call read were written.
+		cache.advance(&b0, b1, &changed(&[]), &written(&[(1, 6), (2, 5)]));
+		assert!(cache.get(&b1, &call, &sender).is_some());
+
+		cache.advance(&b1, b2, &changed(&[]), &written(&[(1, 5)]));
+		assert!(cache.get(&b2, &call, &sender).is_none());
+	}
+
+	#[test]
+	fn drops_results_reading_the_block_environment() {
+		let cache = CallCache::new(16, H256::zero());
+		let (b0, b1) = (H256::from_low_u64_be(0), H256::from_low_u64_be(1));
+		let sender = Address::from_low_u64_be(100);
+		cache.insert(b0, H256::from_low_u64_be(10), sender, executed(1), reads(&[], &[]), true);
+		cache.insert(b0, H256::from_low_u64_be(11), sender, executed(2), reads(&[], &[]), false);
+		assert_eq!(cache.len(), 2);
+
+		cache.advance(&b0, b1, &changed(&[]), &written(&[]));
+		assert!(cache.get(&b1, &H256::from_low_u64_be(10), &sender).is_none());
+		assert!(cache.get(&b1, &H256::from_low_u64_be(11), &sender).is_some());
+	}
+
+	#[test]
+	fn clears_on_reorganization() {
+		let cache = CallCache::new(16, H256::zero());
+		let (b0, b1, other) = (H256::from_low_u64_be(0), H256::from_low_u64_be(1), H256::from_low_u64_be(5));
+		let sender = Address::from_low_u64_be(100);
+		cache.insert(b0, H256::from_low_u64_be(10), sender, executed(1), reads(&[], &[]), false);
+
+		cache.advance(&other, b1, &changed(&[]), &written(&[]));
+		assert_eq!(cache.len(), 0);
+
+		// results of calls on a block which is no longer the best one are not kept
+		cache.insert(b0, H256::from_low_u64_be(10), sender, executed(1), reads(&[], &[]), false);
+		assert_eq!(cache.len(), 0);
+	}
+
+	#[test]
+	fn evicts_oldest_results() {
+		let cache = CallCache::new(2, H256::zero());
+		let head = H256::zero();
+		let sender = Address::from_low_u64_be(100);
+		for call in 0..3 {
+			cache.insert(head, H256::from_low_u64_be(call), sender, executed(call as u8), reads(&[], &[]), false);
+		}
+		assert_eq!(cache.len(), 2);
+		assert!(cache.get(&head, &H256::from_low_u64_be(0), &sender).is_none());
+		assert!(cache.get(&head, &H256::from_low_u64_be(2), &sender).is_some());
+	}
+
+	#[test]
+	fn observes_environment_reads() {
+		let run = |frames: &[&[u8]]| {
+			let mut observer = EnvironmentObserver::default();
+			for instructions in frames {
+				observer.prepare_subtrace(&[0; 2]);
+				for (pc, &instruction) in instructions.iter().enumerate() {
+					observer.trace_next_instruction(pc, instruction, U256::zero());
+				}
+			}
+			for _ in frames {
+				observer.done_subtrace();
+			}
+			observer.drain().unwrap()
+		};
+
+		assert!(!run(&[&[0x54, 0x60]]));
+		// NUMBER in a nested call.
+		assert!(run(&[&[0x54], &[0x43]]));
+		// code run without reporting its instructions.
+		assert!(run(&[&[0x54], &[]]));
+	}
+}
@@ -0,0 +1,532 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Copies of the state at regular block intervals, kept when the state of the blocks in between
+//! is pruned, so historical states can be rebuilt by re-executing blocks from the nearest copy.
+
+use std::io;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+use std::time::Duration;
+
+use ethereum_types::H256;
+use ethtrie::TrieDB;
+use hash::KECCAK_EMPTY;
+use hash_db::{HashDB, Hasher, Prefix, EMPTY_PREFIX};
+use journaldb::{self, Algorithm};
+use kvdb::{DBTransaction, DBValue, KeyValueDB};
+use parking_lot::Mutex;
+use rayon::prelude::*;
+use state_db::StateDB;
+use trie::Trie;
+use trie_vm_factories::Factories;
+use types::basic_account::BasicAccount;
+
+use db::COL_STATE_CHECKPOINTS;
+
+/// Number of copied values written to the database at once.
+const WRITE_BATCH_SIZE: usize = 16_384;
+/// Number of accounts whose storage is copied in parallel at once.
+const ACCOUNTS_PER_BATCH: usize = 4_096;
+/// Prefix of the keys marking complete copies, distinct from the 32-byte keys of the state.
+const CHECKPOINT_KEY_PREFIX: &[u8] = b"checkpoint";
+/// Prefix of the keys holding the number of the last copy which wrote a value.
+const GENERATION_KEY_PREFIX: &[u8] = b"generation";
+/// Maximal time a caller waits for a pruned state to be rebuilt.
+const REBUILD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// State of a canonical block to copy.
+struct Request {
+	db: Arc<dyn KeyValueDB>,
+	state_db: StateDB,
+	number: u64,
+	hash: H256,
+	state_root: H256,
+}
+
+/// Rebuild of a pruned state, sending the result to its caller.
+type RebuildJob = Box<dyn FnOnce() + Send>;
+
+/// Copies the state of every `interval`th block into a separate column, on a separate thread.
+///
+/// Values shared by several copies are stored once, so each copy only adds the state changed
+/// since the previous one. A copy is marked complete once all its values are written, and the
+/// state being copied is not pruned until then. Each value records the last copy which wrote it,
+/// so once there are more than `kept` copies, the values only written by older ones are deleted.
+pub struct StateCheckpoints {
+	interval: u64,
+	requests: Mutex<SyncSender<Request>>,
+	rebuilds: Mutex<SyncSender<RebuildJob>>,
+	/// Number of the block whose state is being copied, `0` if none.
+	copying_at: Arc<AtomicU64>,
+}
+
+impl StateCheckpoints {
+	/// Create the checkpoints and start their threads, keeping the `kept` most recent copies, or
+	/// all copies if `kept` is `0`. The threads stop when the checkpoints are dropped.
+	pub fn new(interval: u64, kept: usize, factories: Factories) -> Self {
+		assert!(interval > 0, "checkpoint interval must be at least one block");
+		// a request is only accepted while the thread waits for it, so at most one copy runs.
+		let (requests, receiver) = mpsc::sync_channel(0);
+		let copying_at = Arc::new(AtomicU64::new(0));
+		let thread_copying_at = copying_at.clone();
+		thread::Builder::new()
+			.name("State checkpoints".into())
+			.spawn(move || Self::run(factories, kept, receiver, thread_copying_at))
+			.expect("Failed to create state checkpoint thread.");
+
+		let (rebuilds, receiver) = mpsc::sync_channel::<RebuildJob>(0);
+		thread::Builder::new()
+			.name("State rebuilds".into())
+			.spawn(move || receiver.into_iter().for_each(|job| job()))
+			.expect("Failed to create state rebuild thread.");
+
+		StateCheckpoints {
+			interval,
+			requests: Mutex::new(requests),
+			rebuilds: Mutex::new(rebuilds),
+			copying_at,
+		}
+	}
+
+	/// Number of blocks between two checkpoints.
+	pub fn interval(&self) -> u64 {
+		self.interval
+	}
+
+	/// Whether the state of the given block should be copied.
+	pub fn is_checkpoint(&self, number: u64) -> bool {
+		number % self.interval == 0
+	}
+
+	/// Number of the block whose state is being copied, which must not be pruned yet. `0` if none.
+	pub fn copying_at(&self) -> u64 {
+		self.copying_at.load(Ordering::SeqCst)
+	}
+
+	/// Whether the copy of the state of the given block is complete.
+	pub fn has_checkpoint(db: &dyn KeyValueDB, hash: &H256) -> bool {
+		match db.get(COL_STATE_CHECKPOINTS, &checkpoint_key(hash)) {
+			Ok(marker) => marker.is_some(),
+			Err(e) => {
+				warn!(target: "client", "Failed to read state checkpoint of {}: {}", hash, e);
+				false
+			}
+		}
+	}
+
+	/// Read-only state database over the copies, which holds the changes of re-executed blocks in memory.
+	pub fn state_db(db: Arc<dyn KeyValueDB>, cache_size: usize) -> StateDB {
+		StateDB::new(journaldb::new(db, Algorithm::Archive, COL_STATE_CHECKPOINTS), cache_size)
+	}
+
+	/// Copy the state of the given canonical block in the background.
+	/// Never blocks; the copy is skipped if the previous one is still running.
+	pub fn copy(&self, db: Arc<dyn KeyValueDB>, state_db: StateDB, number: u64, hash: H256, state_root: H256) {
+		// pin the state before the request is accepted, so it's not pruned by the caller meanwhile.
+		let busy = || warn!(target: "client", "Skipping the state checkpoint at block #{}: the previous copy is still running", number);
+		if self.copying_at.compare_exchange(0, number, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+			return busy();
+		}
+		let accepted = match self.requests.lock().try_send(Request { db, state_db, number, hash, state_root }) {
+			Ok(()) => {
+				debug!(target: "client", "Copying the state of block #{} ({})", number, hash);
+				true
+			},
+			Err(TrySendError::Full(_)) => {
+				busy();
+				false
+			},
+			Err(TrySendError::Disconnected(_)) => {
+				warn!(target: "client", "State checkpoint thread is not running");
+				false
+			},
+		};
+		if !accepted {
+			self.copying_at.store(0, Ordering::SeqCst);
+		}
+	}
+
+	/// Rebuild a pruned state on the rebuild thread and wait at most `REBUILD_TIMEOUT` for it.
+	/// Fails at once if another rebuild is running. `rebuild` must give up once its flag is set.
+	pub fn rebuild<T, F>(&self, rebuild: F) -> Option<T> where
+		T: Send + 'static,
+		F: FnOnce(&AtomicBool) -> Option<T> + Send + 'static,
+	{
+		let (sender, receiver) = mpsc::channel();
+		let cancelled = Arc::new(AtomicBool::new(false));
+		let job_cancelled = cancelled.clone();
+		let job: RebuildJob = Box::new(move || {
+			let _ = sender.send(rebuild(&job_cancelled));
+		});
+		match self.rebuilds.lock().try_send(job) {
+			Ok(()) => {},
+			Err(TrySendError::Full(_)) => {
+				debug!(target: "client", "Not rebuilding a pruned state: another rebuild is running");
+				return None;
+			},
+			Err(TrySendError::Disconnected(_)) => {
+				warn!(target: "client", "State rebuild thread is not running");
+				return None;
+			},
+		}
+
+		match receiver.recv_timeout(REBUILD_TIMEOUT) {
+			Ok(state) => state,
+			Err(_) => {
+				cancelled.store(true, Ordering::SeqCst);
+				warn!(target: "client", "Gave up rebuilding a pruned state after {:?}", REBUILD_TIMEOUT);
+				None
+			}
+		}
+	}
+
+	fn run(factories: Factories, kept: usize, requests: Receiver<Request>, copying_at: Arc<AtomicU64>) {
+		for request in requests {
+			let copied = Self::copy_state(&factories, &request);
+			copying_at.store(0, Ordering::SeqCst);
+			match copied {
+				Ok(values) => info!(target: "client", "Copied the state of block #{} ({} values)", request.number, values),
+				Err(e) => {
+					warn!(target: "client", "Failed to copy the state of block #{}: {}", request.number, e);
+					continue;
+				},
+			}
+
+			match Self::collect_garbage(&*request.db, kept) {
+				Ok(0) => {},
+				Ok(removed) => info!(target: "client", "Removed {} values of old state checkpoints", removed),
+				Err(e) => warn!(target: "client", "Failed to remove old state checkpoints: {}", e),
+			}
+		}
+	}
+
+	fn copy_state(factories: &Factories, request: &Request) -> Result<usize, String> {
+		let copier = Copier::new(&*request.db, request.number);
+		let copying = CopyingDB { inner: request.state_db.as_hash_db(), copier: &copier };
+		let db: &dyn HashDB<_, DBValue> = &copying;
+
+		// reading every node of the account and storage tries copies them
+		let accounts = TrieDB::new(&db, &request.state_root).map_err(|e| e.to_string())?;
+		let mut batch = Vec::with_capacity(ACCOUNTS_PER_BATCH);
+		for item in accounts.iter().map_err(|e| e.to_string())? {
+			let (key, value) = item.map_err(|e| e.to_string())?;
+			let account: BasicAccount = rlp::decode(&value).map_err(|e| e.to_string())?;
+			batch.push((H256::from_slice(&key), account));
+			if batch.len() == ACCOUNTS_PER_BATCH {
+				Self::copy_storage(factories, request, &copier, mem::replace(&mut batch, Vec::with_capacity(ACCOUNTS_PER_BATCH)))?;
+			}
+		}
+		Self::copy_storage(factories, request, &copier, batch)?;
+
+		copier.finish(&request.hash, &request.state_root)
+	}
+
+	/// Copy the storage and code of the given accounts in parallel.
+	fn copy_storage(factories: &Factories, request: &Request, copier: &Copier, accounts: Vec<(H256, BasicAccount)>) -> Result<(), String> {
+		let template = Mutex::new(request.state_db.boxed_clone());
+		accounts.into_par_iter()
+			.map_init(|| template.lock().boxed_clone(), |state_db, (address_hash, account)| {
+				let copying = CopyingDB { inner: state_db.as_hash_db(), copier };
+				let db: &dyn HashDB<_, DBValue> = &copying;
+				let account_db = factories.accountdb.readonly(db, address_hash);
+				let account_db = account_db.as_hash_db();
+
+				let storage = TrieDB::new(&account_db, &account.storage_root).map_err(|e| e.to_string())?;
+				for item in storage.iter().map_err(|e| e.to_string())? {
+					item.map_err(|e| e.to_string())?;
+				}
+				if account.code_hash != KECCAK_EMPTY && account_db.get(&account.code_hash, EMPTY_PREFIX).is_none() {
+					return Err(format!("missing code {}", account.code_hash));
+				}
+				Ok(())
+			})
+			.try_reduce(|| (), |(), ()| Ok(()))?;
+		copier.check()
+	}
+
+	/// Delete the complete copies older than the `kept` most recent ones, and the values no
+	/// newer copy wrote. Returns the number of deleted values.
+	fn collect_garbage(db: &dyn KeyValueDB, kept: usize) -> Result<usize, String> {
+		let mut checkpoints = db.iter_from_prefix(COL_STATE_CHECKPOINTS, CHECKPOINT_KEY_PREFIX)
+			.take_while(|(key, _)| key.starts_with(CHECKPOINT_KEY_PREFIX))
+			.map(|(key, marker)| (checkpoint_number(&marker), key))
+			.collect::<Vec<_>>();
+		if kept == 0 || checkpoints.len() <= kept {
+			return Ok(0);
+		}
+		checkpoints.sort_by(|a, b| b.0.cmp(&a.0));
+		let oldest_kept = checkpoints[kept - 1].0;
+
+		// the old copies are unusable as soon as one of their values is deleted.
+		let mut batch = DBTransaction::new();
+		for (_, key) in &checkpoints[kept..] {
+			batch.delete(COL_STATE_CHECKPOINTS, key);
+		}
+		db.write(batch).map_err(|e| e.to_string())?;
+
+		let mut removed = 0;
+		let mut batch = DBTransaction::new();
+		let generations = db.iter_from_prefix(COL_STATE_CHECKPOINTS, GENERATION_KEY_PREFIX)
+			.take_while(|(key, _)| key.starts_with(GENERATION_KEY_PREFIX));
+		for (key, generation) in generations {
+			if checkpoint_number(&generation) >= oldest_kept {
+				continue;
+			}
+			batch.delete(COL_STATE_CHECKPOINTS, &key[GENERATION_KEY_PREFIX.len()..]);
+			batch.delete(COL_STATE_CHECKPOINTS, &key);
+			removed += 1;
+			if batch.ops.len() >= WRITE_BATCH_SIZE {
+				db.write(mem::replace(&mut batch, DBTransaction::new())).map_err(|e| e.to_string())?;
+			}
+		}
+		db.write(batch).map_err(|e| e.to_string())?;
+		Ok(removed)
+	}
+}
+
+fn checkpoint_key(hash: &H256) -> Vec<u8> {
+	let mut key = CHECKPOINT_KEY_PREFIX.to_vec();
+	key.extend_from_slice(hash.as_bytes());
+	key
+}
+
+fn generation_key(key: &[u8]) -> Vec<u8> {
+	let mut generation = GENERATION_KEY_PREFIX.to_vec();
+	generation.extend_from_slice(key);
+	generation
+}
+
+/// Block number at the end of a checkpoint marker or generation value.
+fn checkpoint_number(value: &[u8]) -> u64 {
+	let mut number = [0u8; 8];
+	if value.len() >= 8 {
+		number.copy_from_slice(&value[value.len() - 8..]);
+	}
+	u64::from_be_bytes(number)
+}
+
+/// Writes the values read by the copying views of a state in batches.
+struct Copier<'a> {
+	db: &'a dyn KeyValueDB,
+	generation: [u8; 8],
+	pending: Mutex<(DBTransaction, usize)>,
+	written: Mutex<usize>,
+	error: Mutex<Option<io::Error>>,
+}
+
+impl<'a> Copier<'a> {
+	fn new(db: &'a dyn KeyValueDB, number: u64) -> Self {
+		Copier {
+			db,
+			generation: number.to_be_bytes(),
+			pending: Mutex::new((DBTransaction::new(), 0)),
+			written: Mutex::new(0),
+			error: Mutex::new(None),
+		}
+	}
+
+	fn copy(&self, key: &[u8], value: &[u8]) {
+		let full = {
+			let mut pending = self.pending.lock();
+			pending.0.put(COL_STATE_CHECKPOINTS, key, value);
+			pending.0.put(COL_STATE_CHECKPOINTS, &generation_key(key), &self.generation);
+			pending.1 += 1;
+			match pending.1 >= WRITE_BATCH_SIZE {
+				true => Some(mem::replace(&mut *pending, (DBTransaction::new(), 0))),
+				false => None,
+			}
+		};
+		if let Some((batch, values)) = full {
+			self.write(batch, values);
+		}
+	}
+
+	fn write(&self, batch: DBTransaction, values: usize) {
+		match self.db.write(batch) {
+			Ok(()) => *self.written.lock() += values,
+			Err(e) => { self.error.lock().get_or_insert(e); },
+		}
+	}
+
+	/// Fails if writing the copied values failed.
+	fn check(&self) -> Result<(), String> {
+		match *self.error.lock() {
+			Some(ref e) => Err(e.to_string()),
+			None => Ok(()),
+		}
+	}
+
+	/// Write the remaining values and mark the copy complete.
+	fn finish(&self, hash: &H256, state_root: &H256) -> Result<usize, String> {
+		let (mut batch, values) = mem::replace(&mut *self.pending.lock(), (DBTransaction::new(), 0));
+		let mut marker = state_root.as_bytes().to_vec();
+		marker.extend_from_slice(&self.generation);
+		batch.put(COL_STATE_CHECKPOINTS, &checkpoint_key(hash), &marker);
+		self.write(batch, values);
+		self.check()?;
+		Ok(*self.written.lock())
+	}
+}
+
+/// Read-only view of a state database copying every value read into the checkpoint column.
+struct CopyingDB<'a, 'b, H: Hasher> {
+	inner: &'a dyn HashDB<H, DBValue>,
+	copier: &'a Copier<'b>,
+}
+
+impl<'a, 'b, H: Hasher> HashDB<H, DBValue> for CopyingDB<'a, 'b, H> {
+	fn get(&self, key: &H::Out, prefix: Prefix) -> Option<DBValue> {
+		let value = self.inner.get(key, prefix)?;
+		self.copier.copy(key.as_ref(), &value);
+		Some(value)
+	}
+
+	fn contains(&self, key: &H::Out, prefix: Prefix) -> bool {
+		self.get(key, prefix).is_some()
+	}
+
+	fn insert(&mut self, _prefix: Prefix, _value: &[u8]) -> H::Out {
+		unimplemented!()
+	}
+
+	fn emplace(&mut self, _key: H::Out, _prefix: Prefix, _value: DBValue) {
+		unimplemented!()
+	}
+
+	fn remove(&mut self, _key: &H::Out, _prefix: Prefix) {
+		unimplemented!()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::mpsc;
+	use std::thread;
+
+	use ethereum_types::{Address, H256, U256};
+	use ethtrie::{TrieDB, TrieDBMut};
+	use hash_db::{HashDB, EMPTY_PREFIX};
+	use journaldb::{self, Algorithm, JournalDB};
+	use kvdb::KeyValueDB;
+	use state_db::StateDB;
+	use trie::{Trie, TrieMut};
+	use trie_vm_factories::Factories;
+	use types::basic_account::BasicAccount;
+
+	use db::{COL_STATE, COL_STATE_CHECKPOINTS, NUM_COLUMNS};
+	use hash::{keccak, KECCAK_EMPTY, KECCAK_NULL_RLP};
+	use super::{Request, StateCheckpoints};
+
+	/// Insert accounts with the given nonces into a new state and return its root.
+	fn insert_accounts(jdb: &mut Box<dyn JournalDB>, nonces: &[u64]) -> H256 {
+		let mut root = H256::zero();
+		let mut trie = TrieDBMut::new(jdb.as_hash_db_mut(), &mut root);
+		for (i, nonce) in nonces.iter().enumerate() {
+			let account = BasicAccount {
+				nonce: (*nonce).into(),
+				balance: U256::from(1000),
+				storage_root: KECCAK_NULL_RLP,
+				code_hash: KECCAK_EMPTY,
+				code_version: U256::zero(),
+			};
+			trie.insert(keccak(Address::from_low_u64_be(i as u64)).as_bytes(), &rlp::encode(&account)).unwrap();
+		}
+		drop(trie);
+		root
+	}
+
+	fn copy(db: &Arc<dyn KeyValueDB>, jdb: &dyn JournalDB, number: u64, state_root: H256) -> H256 {
+		let hash = keccak(number.to_be_bytes());
+		let request = Request {
+			db: db.clone(),
+			state_db: StateDB::new(jdb.boxed_clone(), 0),
+			number,
+			hash,
+			state_root,
+		};
+		assert!(StateCheckpoints::copy_state(&Factories::default(), &request).unwrap() > 0);
+		hash
+	}
+
+	fn accounts(db: &Arc<dyn KeyValueDB>, root: &H256) -> usize {
+		let copy = StateCheckpoints::state_db(db.clone(), 0);
+		let hash_db = copy.as_hash_db();
+		let trie = TrieDB::new(&hash_db, root).unwrap();
+		trie.iter().unwrap().map(|item| item.unwrap()).count()
+	}
+
+	#[test]
+	fn should_copy_state_and_mark_it_complete() {
+		let db: Arc<dyn KeyValueDB> = Arc::new(kvdb_memorydb::create(NUM_COLUMNS));
+		let mut jdb = journaldb::new(db.clone(), Algorithm::Archive, COL_STATE);
+		let root = insert_accounts(&mut jdb, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+		assert!(!StateCheckpoints::has_checkpoint(&*db, &keccak(10u64.to_be_bytes())));
+
+		let hash = copy(&db, &*jdb, 10, root);
+		assert!(StateCheckpoints::has_checkpoint(&*db, &hash));
+
+		let copy = StateCheckpoints::state_db(db.clone(), 0);
+		assert!(copy.as_hash_db().contains(&root, EMPTY_PREFIX));
+		assert!(db.get(COL_STATE_CHECKPOINTS, root.as_bytes()).unwrap().is_some());
+		assert_eq!(accounts(&db, &root), 10);
+	}
+
+	#[test]
+	fn should_remove_values_of_old_copies_only() {
+		let db: Arc<dyn KeyValueDB> = Arc::new(kvdb_memorydb::create(NUM_COLUMNS));
+		let mut jdb = journaldb::new(db.clone(), Algorithm::Archive, COL_STATE);
+		let old_root = insert_accounts(&mut jdb, &[0, 1, 2, 3]);
+		let root = insert_accounts(&mut jdb, &[0, 1, 2, 7]);
+		let old_hash = copy(&db, &*jdb, 10, old_root);
+		let hash = copy(&db, &*jdb, 20, root);
+
+		assert_eq!(StateCheckpoints::collect_garbage(&*db, 2).unwrap(), 0);
+		assert!(StateCheckpoints::collect_garbage(&*db, 1).unwrap() > 0);
+
+		assert!(!StateCheckpoints::has_checkpoint(&*db, &old_hash));
+		assert!(db.get(COL_STATE_CHECKPOINTS, old_root.as_bytes()).unwrap().is_none());
+		assert!(StateCheckpoints::has_checkpoint(&*db, &hash));
+		assert_eq!(accounts(&db, &root), 4);
+		assert_eq!(StateCheckpoints::collect_garbage(&*db, 1).unwrap(), 0);
+	}
+
+	#[test]
+	fn should_rebuild_one_state_at_once() {
+		let checkpoints = Arc::new(StateCheckpoints::new(10, 0, Factories::default()));
+		assert_eq!(checkpoints.rebuild(|_| Some(1)), Some(1));
+
+		let (started, wait_started) = mpsc::channel();
+		let (release, wait_release) = mpsc::channel::<()>();
+		let running = {
+			let checkpoints = checkpoints.clone();
+			thread::spawn(move || checkpoints.rebuild(move |_| {
+				started.send(()).unwrap();
+				wait_release.recv().unwrap();
+				Some(2)
+			}))
+		};
+		wait_started.recv().unwrap();
+		assert_eq!(checkpoints.rebuild(|_| Some(3)), None);
+
+		release.send(()).unwrap();
+		assert_eq!(running.join().unwrap(), Some(2));
+	}
+}
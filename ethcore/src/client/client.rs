@@ -15,30 +15,31 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::convert::TryFrom;
 use std::io::{BufRead, BufReader};
+use std::ops::Bound;
 use std::str::from_utf8;
 use std::sync::{Arc, Weak};
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering as AtomicOrdering, Ordering, AtomicU64};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use ansi_term::Colour;
 use bytes::Bytes;
 use bytes::ToPretty;
-use ethereum_types::{Address, H256, H264, U256};
-use hash::keccak;
-use hash_db::EMPTY_PREFIX;
+use ethereum_types::{Address, H256, H264, H520, U256};
+use hash::{keccak, KECCAK_NULL_RLP};
+use hash_db::{HashDB, EMPTY_PREFIX};
 use kvdb::{DBTransaction, DBValue, KeyValueDB};
 use parking_lot::{Mutex, RwLock};
 use rand::rngs::OsRng;
-use rlp::PayloadInfo;
+use rlp::{DecoderError, PayloadInfo, Rlp, RlpStream};
 use rustc_hex::FromHex;
-use trie::{Trie, TrieFactory, TrieSpec};
+use trie::{Trie, TrieFactory, TrieLayout, TrieSpec};
 
 use account_state::State;
 use account_state::state::StateInfo;
-use block::{ClosedBlock, Drain, enact, LockedBlock, OpenBlock, SealedBlock};
+use block::{ClosedBlock, Drain, enact, enact_tracking_writes, LockedBlock, OpenBlock, SealedBlock};
 use blockchain::{
 	BlockChain,
 	BlockChainDB,
@@ -52,11 +53,18 @@ use blockchain::{
 };
 use call_contract::CallContract;
 use client::{
-	bad_blocks, BlockProducer, BroadcastProposalBlock, Call,
+	bad_blocks, mismatch_dump, BlockProducer, BroadcastProposalBlock, Call,
 	ClientConfig, EngineInfo, ImportSealedBlock, PrepareOpenBlock,
 	ReopenBlock, SealedBlockImporter,
 };
 use client::ancient_import::AncientVerifier;
+use client::attack_monitor::{AttackMonitor, ImportedBlock};
+use client::call_cache::{CallCache, EnvironmentObserver};
+use client::checkpoints::StateCheckpoints;
+use client::commit_pipeline::CommitPipeline;
+use client::header_accumulator::HeaderAccumulator;
+use client::prefetch::{StatePrefetcher, TouchedAccounts};
+use client::receipt_proof;
 use client_traits::{
 	AccountData,
 	BadBlocks,
@@ -68,6 +76,7 @@ use client_traits::{
 	ChainInfo,
 	ChainNotify,
 	DatabaseRestore,
+	ForkChoiceClient,
 	ImportBlock,
 	ImportExportBlocks,
 	IoClient,
@@ -79,13 +88,16 @@ use client_traits::{
 	Tick,
 	TransactionInfo,
 	TransactionRequest,
-	ForceUpdateSealing
+	ForceUpdateSealing,
+	UncleCandidate,
+	UncleCandidates,
 };
 use db::{keys::BlockDetails, Readable, Writable};
-use engine::Engine;
+use engine::{Engine, epoch_validators};
 use ethcore_miner::pool::VerifiedTransaction;
 use ethtrie::Layout;
 use evm::Schedule;
+use evm::interpreter::CacheStats as CodeCacheStats;
 use executive_state;
 use io::IoChannel;
 use journaldb;
@@ -94,40 +106,49 @@ use machine::{
 	executive::{contract_address, Executive, TransactOptions},
 	transaction_ext::Transaction,
 };
+use memory_cache::MemoryLruCache;
 use miner::{Miner, MinerService, PendingOrdering};
 use registrar::RegistrarClient;
 use snapshot::{self, SnapshotClient, SnapshotWriter};
 use spec::Spec;
-use state_db::StateDB;
+use state_db::{CacheBudget, StateDB};
+use stats::{DurationHistogram, PrometheusMetrics, PrometheusRegistry, Span, Tracer};
 use trace::{self, Database as TraceDatabase, ImportRequest as TraceImportRequest, LocalizedTrace, TraceDB};
 use trie_vm_factories::{Factories, VmFactory};
 use types::{
+	account_diff::{AccountDiff, Diff},
 	ancestry_action::AncestryAction,
+	ancestry_proof::AncestryProof,
+	basic_account::BasicAccount,
 	block::PreverifiedBlock,
 	block_status::BlockStatus,
 	blockchain_info::BlockChainInfo,
 	BlockNumber,
 	call_analytics::CallAnalytics,
 	chain_notify::{ChainMessageType, ChainRoute, NewBlocks},
-	client_types::{ClientReport, IoStats, Mode, StateResult},
+	client_types::{ClientReport, IoStats, Mode, StateResult, StateWrites},
+	confirmation_status::{CompetingBranch, ConfirmationStatus},
 	encoded,
 	engines::{
-		epoch::{PendingTransition, Transition as EpochTransition},
+		epoch::{EpochValidators, PendingTransition, Transition as EpochTransition},
 		ForkChoice,
 		machine::Call as MachineCall,
 		MAX_UNCLE_AGE,
 		SealingState,
 	},
 	errors::{BlockError, EngineError, EthcoreError, EthcoreResult, ExecutionError, ImportError, SnapshotError},
-	filter::Filter,
+	filter::{Filter, ReconstructLogsError},
+	gas_stats::BlockGasStats,
 	header::Header,
 	ids::{BlockId, TraceId, TransactionId, UncleId},
 	import_route::ImportRoute,
 	io_message::ClientIoMessage,
 	log_entry::LocalizedLogEntry,
 	pruning_info::PruningInfo,
-	receipt::{LocalizedReceipt, Receipt},
+	receipt::{LocalizedReceipt, Receipt, ReceiptProof},
 	snapshot::{Progress, Snapshotting},
+	state_diff::StateDiff,
+	threat_status::{ThreatLevel, ThreatStatus},
 	trace_filter::Filter as TraceFilter,
 	transaction::{self, Action, CallError, LocalizedTransaction, SignedTransaction, UnverifiedTransaction},
 	verification::{Unverified, VerificationQueueInfo as BlockQueueInfo},
@@ -142,6 +163,29 @@ const MAX_ANCIENT_BLOCKS_QUEUE_SIZE: usize = 4096;
 const MAX_ANCIENT_BLOCKS_TO_IMPORT: usize = 4;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Number of blocks whose bodies and receipts are recompressed on every tick.
+const RECOMPRESS_BLOCKS_PER_TICK: u64 = 256;
+// Number of old blocks whose traces are added to the trace address index on every tick.
+const TRACE_INDEX_BLOCKS_PER_TICK: u64 = 1024;
+// Number of chunks of 256 blocks added to the header accumulator at once, on import and on
+// every tick, so that a node upgraded with a long chain catches up without holding locks long.
+const HEADER_ACCUMULATOR_CHUNKS_PER_UPDATE: usize = 64;
+// Number of canonical blocks below a transaction's block searched for competing branches.
+const FORK_SEARCH_DEPTH: u64 = 64;
+// Number of rounds without any queued block being verified or imported after which the queue is
+// no longer drained, about five seconds when waiting for the verification.
+const DRAIN_STALLED_ROUNDS: usize = 500;
+// Longest time the changes of imported blocks are buffered before being written to the database.
+const COMMIT_BATCH_MAX_AGE: Duration = Duration::from_secs(10);
+// Memory budget of the per-block gas statistics cache, enough for the longest range of
+// `parity_gasStats` over blocks of a few hundred transactions each (about 30 KiB of statistics).
+const GAS_STATS_CACHE_SIZE: usize = 64 * 1024 * 1024;
+// Memory budget of the changed accounts and storage keys of recent `state_diff` ranges.
+const STATE_DIFF_CACHE_SIZE: usize = 32 * 1024 * 1024;
+// Number of recently imported blocks whose state writes are kept for `state_writes`.
+const STATE_WRITES_BLOCKS: usize = 64;
+// Key of the engine parameters updates applied at runtime, restored when the client starts.
+const ENGINE_PARAMS_UPDATES_KEY: &[u8] = b"engine_params_updates";
 
 struct SleepState {
 	last_activity: Option<Instant>,
@@ -157,6 +201,22 @@ impl SleepState {
 	}
 }
 
+/// Imported blocks whose changes are buffered and not yet flushed to the database.
+#[derive(Default)]
+struct UnflushedBlocks {
+	/// Number of buffered blocks.
+	count: usize,
+	/// When the first of them was imported.
+	since: Option<Instant>,
+}
+
+impl UnflushedBlocks {
+	/// Whether the oldest buffered changes are due to be written.
+	fn is_expired(&self) -> bool {
+		self.since.map_or(false, |since| since.elapsed() >= COMMIT_BATCH_MAX_AGE)
+	}
+}
+
 struct Importer {
 	/// Lock used during block import
 	pub import_lock: Mutex<()>, // FIXME Maybe wrap the whole `Importer` instead?
@@ -212,6 +272,15 @@ pub struct Client {
 	/// Report on the status of client
 	report: RwLock<ClientReport>,
 
+	/// Time taken to execute and commit a single block.
+	block_import_time: DurationHistogram,
+
+	/// Time taken to flush imported blocks to the database.
+	db_write_time: Arc<DurationHistogram>,
+
+	/// Records spans of the block import pipeline.
+	tracer: RwLock<Tracer>,
+
 	sleep_state: Mutex<SleepState>,
 
 	/// Flag changed by `sleep` and `wake_up` methods. Not to be confused with `enabled`.
@@ -249,6 +318,62 @@ pub struct Client {
 	exit_handler: Mutex<Option<Box<dyn Fn(String) + 'static + Send>>>,
 
 	importer: Importer,
+
+	/// Loads the state touched by verified blocks ahead of their import.
+	state_prefetcher: Option<StatePrefetcher>,
+
+	/// Flushes imported blocks to the database while the next ones are executed.
+	commit_pipeline: Option<CommitPipeline>,
+
+	/// Imported blocks whose changes are buffered and not yet flushed to the database.
+	unflushed_blocks: Mutex<UnflushedBlocks>,
+
+	/// Copies of the state kept beyond the pruning history.
+	checkpoints: Option<StateCheckpoints>,
+
+	/// Branch kept out of the canon chain because switching to it would retract more than
+	/// `max_reorg_depth` blocks.
+	held_reorg: Mutex<Option<HeldReorg>>,
+
+	/// Watches the imported blocks for signs of a majority attack.
+	attack_monitor: AttackMonitor,
+
+	/// Set by `rewrite_database` until the background rewrites of the database catch up.
+	rewrite_requested: AtomicBool,
+
+	/// Results of calls on the best block, kept while imported blocks don't touch their state.
+	call_cache: Option<CallCache>,
+
+	/// Gas statistics of recently queried blocks, so that rolling ranges only compute the new blocks.
+	gas_stats_cache: Mutex<MemoryLruCache<H256, BlockGasStats>>,
+	/// Changed accounts and storage keys between two blocks, so that the pages of a `state_diff`
+	/// don't compute them again.
+	state_diff_cache: Mutex<MemoryLruCache<(H256, H256), BTreeMap<Address, BTreeSet<H256>>>>,
+
+	/// Accumulator over the canonical block hashes, extended as blocks are imported.
+	header_accumulator: Mutex<HeaderAccumulator>,
+
+	/// Whether the state written by imported blocks is kept in `state_writes`.
+	record_state_writes: AtomicBool,
+	/// State written by the most recently imported blocks, oldest first.
+	state_writes: Mutex<VecDeque<(H256, StateWrites)>>,
+
+	/// When the block import paused by the operator resumes by itself, if paused.
+	import_paused_until: Mutex<Option<Instant>>,
+
+	/// Signed engine parameters updates applied at runtime, in the order they were applied.
+	engine_params_updates: Mutex<Vec<(Bytes, H520)>>,
+}
+
+/// A reorganization deeper than the configured limit, waiting to be accepted by the operator.
+#[derive(Debug, Clone, Copy)]
+struct HeldReorg {
+	/// Most recent block of the held branch.
+	head: H256,
+	/// Number of `head`.
+	number: BlockNumber,
+	/// Number of canon blocks switching to the branch would retract.
+	depth: u64,
 }
 
 impl Importer {
@@ -257,6 +382,7 @@ impl Importer {
 		engine: Arc<dyn Engine>,
 		message_channel: IoChannel<ClientIoMessage<Client>>,
 		miner: Arc<Miner>,
+		db: Arc<dyn KeyValueDB>,
 	) -> Result<Importer, EthcoreError> {
 		let block_queue = BlockQueue::new(
 			config.queue.clone(),
@@ -271,7 +397,7 @@ impl Importer {
 			miner,
 			ancient_verifier: AncientVerifier::new(engine.clone()),
 			engine,
-			bad_blocks: Default::default(),
+			bad_blocks: bad_blocks::BadBlocks::new(db),
 		})
 	}
 
@@ -281,18 +407,40 @@ impl Importer {
 		if !client.enabled.load(AtomicOrdering::Relaxed) {
 			return 0;
 		}
+		if client.is_import_paused() {
+			return 0;
+		}
 
 		let max_blocks_to_import = client.config.max_round_blocks_to_import;
+		let tracer = client.tracer.read().clone();
 		let (imported_blocks, import_results, invalid_blocks, imported, duration, has_more_blocks_to_import) = {
 			let mut imported_blocks = Vec::with_capacity(max_blocks_to_import);
 			let mut invalid_blocks = HashSet::new();
+			let mut dropped_blocks = HashSet::new();
 			let mut import_results = Vec::with_capacity(max_blocks_to_import);
 
 			let _import_lock = self.import_lock.lock();
+			// the import may have been paused while waiting for the lock.
+			if client.is_import_paused() {
+				return 0;
+			}
+			if let Some(ref pipeline) = client.commit_pipeline {
+				if pipeline.failed() {
+					client.recover_commit_pipeline(pipeline);
+				}
+			}
 			let blocks = self.block_queue.drain(max_blocks_to_import);
 			if blocks.is_empty() {
 				return 0;
 			}
+			if let Some(ref prefetcher) = client.state_prefetcher {
+				// warm the cache for the drained blocks and for the ones which will be imported next.
+				let mut touched = TouchedAccounts::default();
+				blocks.iter().for_each(|(block, _)| touched.note_block(block));
+				self.block_queue.peek_verified(max_blocks_to_import, |(block, _)| touched.note_block(block));
+				let best = client.chain.read().best_block_header();
+				prefetcher.prefetch(client.state_db.read().boxed_clone(), best.hash(), *best.state_root(), touched);
+			}
 			trace_time!("import_verified_blocks");
 			let start = Instant::now();
 
@@ -304,14 +452,25 @@ impl Importer {
 				// LockedBlock. See https://github.com/openethereum/openethereum/issues/11603
 				let preverified_header = block.header.clone();
 				let hash = block.header.hash();
+				let mut span = self.block_span(&tracer, &hash);
+				span.set_attribute("block.number", block.header.number());
+				span.set_attribute("block.hash", format!("{:#x}", hash));
 
 				let is_invalid = invalid_blocks.contains(block.header.parent_hash());
 				if is_invalid {
 					invalid_blocks.insert(hash);
 					continue;
 				}
+				if dropped_blocks.contains(block.header.parent_hash()) {
+					dropped_blocks.insert(hash);
+					continue;
+				}
 
-				match self.check_and_lock_block(block, client) {
+				let block_start = Instant::now();
+				let execute_span = span.child("block.execute");
+				let locked = self.check_and_lock_block(block, client);
+				execute_span.end();
+				match locked {
 					Ok((locked_block, pending)) => {
 						if let Some(sync_until_block_nr) = client.config.sync_until {
 							if locked_block.header.number() > sync_until_block_nr {
@@ -324,6 +483,7 @@ impl Importer {
 						imported_blocks.push(hash);
 						let transactions_len = locked_block.transactions.len();
 						let gas_used = *locked_block.header.gas_used();
+						let commit_span = span.child("block.commit");
 						let route = self.commit_block(
 							locked_block,
 							&preverified_header,
@@ -331,10 +491,18 @@ impl Importer {
 							pending,
 							client
 						);
+						commit_span.end();
 						import_results.push(route);
 						client.report.write().accrue_block(gas_used, transactions_len);
+						client.block_import_time.observe(block_start.elapsed());
 					}
+					Err(ref err) if verification::queue::is_transient(err) => {
+						span.set_attribute("error", err);
+						debug!(target: "client", "Dropping block #{} ({}) from the queue: {}", preverified_header.number(), hash, err);
+						dropped_blocks.insert(hash);
+					},
 					Err(err) => {
+						span.set_attribute("error", &err);
 						self.bad_blocks.report(block_bytes, err.to_string());
 						invalid_blocks.insert(hash);
 					},
@@ -347,15 +515,18 @@ impl Importer {
 			if !invalid_blocks.is_empty() {
 				self.block_queue.mark_as_bad(&invalid_blocks);
 			}
+			// the sync downloads the dropped blocks again.
+			self.block_queue.drop_items(&dropped_blocks.into_iter().collect::<Vec<_>>());
 			let has_more_blocks_to_import = !self.block_queue.mark_as_good(&imported_blocks);
 			(imported_blocks, import_results, invalid_blocks, imported, start.elapsed(), has_more_blocks_to_import)
 		};
 
 		{
-			if !imported_blocks.is_empty() {
+			// rounds which only rejected blocks are reported too, e.g. to answer Engine API calls waiting for them.
+			if !imported_blocks.is_empty() || !invalid_blocks.is_empty() {
 				let route = ChainRoute::from(import_results.as_ref());
 
-				if !has_more_blocks_to_import {
+				if !has_more_blocks_to_import && !imported_blocks.is_empty() {
 					self.miner.chain_new_blocks(client, &imported_blocks, &invalid_blocks, route.enacted(), route.retracted(), false);
 				}
 
@@ -375,11 +546,53 @@ impl Importer {
 			}
 		}
 
-		let db = client.db.read();
-		db.key_value().flush().expect("DB flush failed.");
+		if !imported_blocks.is_empty() {
+			client.update_header_accumulator();
+		}
+
+		// While syncing, let the changes of several rounds accumulate in the database overlay
+		// and write them at once. This needs no journal of its own: the overlay is written in a
+		// single atomic batch holding the blocks, their state journal and the best block, so a
+		// crash leaves the database at the last flushed block and the lost rounds are simply
+		// imported again. The overlay is written once the queue runs dry, or when it grows older
+		// than `COMMIT_BATCH_MAX_AGE`, which `tick` also checks in case the queue stalls.
+		if client.defer_flush(imported, has_more_blocks_to_import) {
+			return imported;
+		}
+
+		match client.commit_pipeline {
+			Some(ref pipeline) => if let Err(e) = pipeline.flush() {
+				warn!(target: "client", "Failed to flush imported blocks: {}", e);
+				let _import_lock = self.import_lock.lock();
+				client.recover_commit_pipeline(pipeline);
+			},
+			None => {
+				let flush_start = Instant::now();
+				client.db.read().key_value().flush().expect("DB flush failed.");
+				client.db_write_time.observe(flush_start.elapsed());
+			},
+		}
 		imported
 	}
 
+	/// Start the root span of a block's import, covering its time in the queue.
+	fn block_span(&self, tracer: &Tracer, hash: &H256) -> Span {
+		let timings = if tracer.is_enabled() { self.block_queue.take_timings(hash) } else { None };
+		let timings = match timings {
+			Some(timings) => timings,
+			None => return tracer.span("block.import"),
+		};
+
+		let span = tracer.span_at("block.import", timings.enqueued);
+		if let Some(started) = timings.verification_started {
+			span.child_at("block.queue", timings.enqueued).end_at(started);
+			if let Some(verified) = timings.verified {
+				span.child_at("block.verify", started).end_at(verified);
+			}
+		}
+		span
+	}
+
 	fn check_and_lock_block(&self, block: PreverifiedBlock, client: &Client) -> EthcoreResult<(LockedBlock, Option<PendingTransition>)> {
 		let engine = &*self.engine;
 		let header = &block.header;
@@ -429,6 +642,10 @@ impl Importer {
 
 		let is_epoch_begin = chain.epoch_transition(parent.number(), *header.parent_hash()).is_some();
 
+		// the call cache keeps the results of calls not reading what the block writes, state
+		// subscribers are notified of the changes of what it writes.
+		let track_writes = client.call_cache.is_some() || client.record_state_writes.load(AtomicOrdering::Relaxed);
+		let enact = if track_writes { enact_tracking_writes } else { enact };
 		let enact_result = enact(
 			header,
 			block.transactions,
@@ -462,6 +679,7 @@ impl Importer {
 		// Final Verification
 		if let Err(e) = verification::verify_block_final(&header, &locked_block.header) {
 			warn!(target: "client", "Stage 5 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+			self.note_root_mismatch(&header, &parent, &locked_block, &e, client);
 			return Err(e);
 		}
 
@@ -475,6 +693,32 @@ impl Importer {
 		Ok((locked_block, pending))
 	}
 
+	/// Build a diagnostic report on a block whose execution disagrees with its header, keep it
+	/// with the bad block and write it to the dumps directory, if any.
+	fn note_root_mismatch(&self, header: &Header, parent: &Header, block: &LockedBlock, error: &EthcoreError, client: &Client) {
+		let pre_state = match State::from_existing(
+			client.state_db.read().boxed_clone_canon(header.parent_hash()),
+			*parent.state_root(),
+			self.engine.account_start_nonce(header.number()),
+			client.factories.clone(),
+		) {
+			Ok(state) => state,
+			Err(e) => {
+				warn!(target: "client", "Failed to open the parent state of bad block #{}: {}", header.number(), e);
+				return;
+			}
+		};
+		let report = mismatch_dump::report(header, block, &pre_state, &error.to_string());
+
+		if let Some(ref dir) = client.config.root_mismatch_dumps {
+			match mismatch_dump::write(dir, header, &report) {
+				Ok(path) => warn!(target: "client", "Wrote a report on block #{} to {}", header.number(), path.display()),
+				Err(e) => warn!(target: "client", "Failed to write a report on block #{}: {}", header.number(), e),
+			}
+		}
+		self.bad_blocks.note_diagnostics(header.hash(), report.to_string());
+	}
+
 	/// Import a block with transaction receipts.
 	///
 	/// The block is guaranteed to be the next best blocks in the
@@ -547,15 +791,37 @@ impl Importer {
 		let route = chain.tree_route(best_hash, *parent).expect("forks are only kept when it has common ancestors; tree route from best to prospective's parent always exists; qed");
 		let fork_choice = if route.is_from_route_finalized {
 			ForkChoice::Old
+		} else if let Some(fork_choice) = self.engine.fork_choice(&header) {
+			fork_choice
 		} else if new_total_difficulty > best_total_difficulty {
 			ForkChoice::New
 		} else {
 			ForkChoice::Old
 		};
 
+		let mut held = false;
+		let fork_choice = match (fork_choice, client.config.max_reorg_depth) {
+			(ForkChoice::New, Some(max_depth)) if route.index as u64 > max_depth => {
+				held = true;
+				client.hold_reorg(*hash, number, route.index as u64);
+				ForkChoice::Old
+			}
+			(fork_choice, _) => fork_choice,
+		};
+		let side_branch = (route.blocks.len() - route.index) as u64 + 1;
+		let fork_point = route.ancestor;
+		let held_depth = route.index as u64;
+		// the difficulty of the other engines doesn't follow the hash rate.
+		let difficulty_swing = match self.engine.snapshot_mode() {
+			Snapshotting::PoW { .. } => chain.block_header_data(parent)
+				.map_or(0, |parent| ImportedBlock::difficulty_swing(header.difficulty(), &parent.difficulty())),
+			_ => 0,
+		};
+
 		// CHECK! I *think* this is fine, even if the state_root is equal to another
 		// already-imported block of the same number.
 		// TODO: Prove it with a test.
+		let (changed, written) = (block.state.changed_accounts(), block.state.written_storage());
 		let mut state = block.state.drop().1;
 
 		// check epoch end signal, potentially generating a proof on the current
@@ -594,6 +860,26 @@ impl Importer {
 
 		let is_canon = route.enacted.last().map_or(false, |h| h == hash);
 		state.sync_cache(&route.enacted, &route.retracted, is_canon);
+		if let Some(ref call_cache) = client.call_cache {
+			if is_canon {
+				call_cache.advance(parent, *hash, &changed, &written);
+			}
+		}
+		if client.record_state_writes.load(AtomicOrdering::Relaxed) {
+			let mut state_writes = client.state_writes.lock();
+			if state_writes.len() == STATE_WRITES_BLOCKS {
+				state_writes.pop_front();
+			}
+			state_writes.push_back((*hash, StateWrites { accounts: changed, storage: written }));
+		}
+		client.note_imported_block(ImportedBlock {
+			canon: is_canon,
+			side_branch: if is_canon { 0 } else { side_branch },
+			fork_point: if is_canon { None } else { Some(fork_point) },
+			difficulty_swing,
+			reorg_depth: if held { held_depth } else { route.retracted.len() as u64 },
+			held,
+		});
 		// Final commit to the DB
 		client.db.read().key_value().write_buffered(batch);
 		chain.commit();
@@ -602,6 +888,13 @@ impl Importer {
 
 		client.update_last_hashes(&parent, hash);
 
+		if let Some(ref checkpoints) = client.checkpoints {
+			if is_canon && checkpoints.is_checkpoint(number) {
+				let db = client.db.read().key_value().clone();
+				checkpoints.copy(db, state.boxed_clone(), number, *hash, *header.state_root());
+			}
+		}
+
 		if let Err(e) = client.prune_ancient(state, &chain) {
 			warn!("Failed to prune ancient state data: {}", e);
 		}
@@ -636,6 +929,7 @@ impl Importer {
 							last_hashes: client.build_last_hashes(*header.parent_hash()),
 							gas_used: U256::default(),
 							gas_limit: u64::max_value().into(),
+							base_fee: None,
 						};
 
 						let call = move |addr, data| {
@@ -740,11 +1034,31 @@ impl Client {
 			accountdb: Default::default(),
 		};
 
+		let db_write_time = Arc::new(DurationHistogram::default());
+		let header_accumulator = HeaderAccumulator::load(&**db.key_value());
+		let (commit_pipeline, db) = match config.commit_pipeline {
+			true => {
+				let (pipeline, db) = CommitPipeline::new(db, db_write_time.clone());
+				(Some(pipeline), db)
+			},
+			false => (None, db),
+		};
+
 		let journal_db = journaldb::new(db.key_value().clone(), config.pruning, ::db::COL_STATE);
-		let mut state_db = StateDB::new(journal_db, config.state_cache_size);
+		let cache_budget = CacheBudget::new(config.state_cache_size, config.state_cache_ratios);
+		let mut state_db = StateDB::with_budget(journal_db, cache_budget);
+		if let Some(ref fork) = config.state_fork {
+			state_db.set_fork(fork.0.clone());
+		}
 		if state_db.journal_db().is_empty() {
-			// Sets the correct state root.
-			state_db = spec.ensure_db_good(state_db, &factories)?;
+			// Sets the correct state root. The accounts of a genesis accounts file are written as
+			// they are inserted, the rest of the genesis state is journalled below.
+			let key_value = db.key_value().clone();
+			state_db = spec.ensure_db_good_with_flush(state_db, &factories, |state_db: &mut StateDB| {
+				let batch = state_db.journal_db_mut().drain_transaction_overlay()?;
+				key_value.write(batch)?;
+				Ok(())
+			})?;
 			let mut batch = DBTransaction::new();
 			state_db.journal_under(&mut batch, 0, &spec.genesis_header().hash())?;
 			db.key_value().write(batch)?;
@@ -772,7 +1086,24 @@ impl Client {
 
 		let awake = match config.mode { Mode::Dark(..) | Mode::Off => false, _ => true };
 
-		let importer = Importer::new(&config, engine.clone(), message_channel.clone(), miner)?;
+		let importer = Importer::new(&config, engine.clone(), message_channel.clone(), miner, db.key_value().clone())?;
+		let state_prefetcher = match config.state_prefetch {
+			true => Some(StatePrefetcher::new(factories.clone())),
+			false => None,
+		};
+		let call_cache = match config.call_cache_size {
+			0 => None,
+			size => Some(CallCache::new(size, chain.best_block_hash())),
+		};
+
+		let checkpoints = match config.state_checkpoint_interval {
+			0 => None,
+			_ if !state_db.is_prunable() => {
+				info!(target: "client", "State is not pruned, ignoring the state checkpoint interval");
+				None
+			},
+			interval => Some(StateCheckpoints::new(interval, config.state_checkpoints_kept, factories.clone())),
+		};
 
 		let registrar_address = engine.machine().params().registrar;
 		if let Some(ref addr) = registrar_address {
@@ -792,6 +1123,9 @@ impl Client {
 			db: RwLock::new(db.clone()),
 			state_db: RwLock::new(state_db),
 			report: RwLock::new(Default::default()),
+			block_import_time: Default::default(),
+			db_write_time,
+			tracer: Default::default(),
 			io_channel: RwLock::new(message_channel),
 			notify: RwLock::new(Vec::new()),
 			queue_transactions: IoChannelQueue::new(config.transaction_verification_queue_size),
@@ -806,9 +1140,26 @@ impl Client {
 			registrar_address,
 			exit_handler: Mutex::new(None),
 			importer,
+			state_prefetcher,
+			commit_pipeline,
+			unflushed_blocks: Mutex::new(UnflushedBlocks::default()),
+			checkpoints,
+			held_reorg: Mutex::new(None),
+			attack_monitor: AttackMonitor::default(),
+			rewrite_requested: AtomicBool::new(false),
+			call_cache,
+			gas_stats_cache: Mutex::new(MemoryLruCache::new(GAS_STATS_CACHE_SIZE)),
+			state_diff_cache: Mutex::new(MemoryLruCache::new(STATE_DIFF_CACHE_SIZE)),
+			header_accumulator: Mutex::new(header_accumulator),
+			record_state_writes: AtomicBool::new(false),
+			state_writes: Mutex::new(VecDeque::new()),
+			import_paused_until: Mutex::new(None),
+			engine_params_updates: Mutex::new(Vec::new()),
 			config,
 		});
 
+		client.restore_engine_params()?;
+
 		// ensure genesis epoch proof in the DB.
 		{
 			let chain = client.chain.read();
@@ -846,6 +1197,39 @@ impl Client {
 		Ok(client)
 	}
 
+	// extends the header accumulator with the blocks imported since the last update, or drops
+	// the reorganised ones, and persists its changes.
+	fn update_header_accumulator(&self) {
+		let chain = self.chain.read();
+		let mut accumulator = self.header_accumulator.lock();
+		let previous = accumulator.chunks();
+		let kept = accumulator.sync(chain.best_block_number(), HEADER_ACCUMULATOR_CHUNKS_PER_UPDATE, |n| chain.block_hash(n));
+		if kept == previous && accumulator.chunks() == previous {
+			return;
+		}
+		let mut batch = DBTransaction::new();
+		accumulator.write(&mut batch, kept, previous);
+		self.db.read().key_value().write_buffered(batch);
+	}
+
+	// re-apply the engine parameters updates accepted before the restart.
+	fn restore_engine_params(&self) -> Result<(), EthcoreError> {
+		let raw = match self.db.read().key_value().get(::db::COL_EXTRA, ENGINE_PARAMS_UPDATES_KEY)? {
+			Some(raw) => raw,
+			None => return Ok(()),
+		};
+		let updates = Rlp::new(&raw).iter()
+			.map(|update| Ok((update.val_at(0)?, update.val_at(1)?)))
+			.collect::<Result<Vec<(Bytes, H520)>, DecoderError>>()?;
+		let reload = self.engine.params_reload().ok_or(EngineError::ParamsReloadUnsupported)?;
+		for &(ref update, signature) in &updates {
+			reload.restore_params(update, &signature.into())?;
+		}
+		info!(target: "client", "Restored {} engine parameters updates", updates.len());
+		*self.engine_params_updates.lock() = updates;
+		Ok(())
+	}
+
 	/// Wakes up client if it's a sleep.
 	pub fn keep_alive(&self) {
 		let should_wake = match *self.mode.lock() {
@@ -871,6 +1255,12 @@ impl Client {
 		*self.exit_handler.lock() = Some(Box::new(f));
 	}
 
+	/// Set the tracer recording spans of the block import pipeline.
+	pub fn set_tracer(&self, tracer: Tracer) {
+		self.importer.block_queue.set_record_timings(tracer.is_enabled());
+		*self.tracer.write() = tracer;
+	}
+
 	/// Returns engine reference.
 	pub fn engine(&self) -> &dyn Engine {
 		&*self.engine
@@ -884,6 +1274,28 @@ impl Client {
 		}
 	}
 
+	// keep the head on the current chain, pause the import and alert the operator about a branch
+	// which would reorganize it deeper than allowed. The blocks of the import round in progress
+	// are still imported.
+	fn hold_reorg(&self, head: H256, number: BlockNumber, depth: u64) {
+		*self.held_reorg.lock() = Some(HeldReorg { head, number, depth });
+		error!(
+			target: "client",
+			"Held back a reorganization retracting {} blocks to #{} ({}) and paused the block import; call parity_acceptReorg to switch to it or parity_resumeSync to keep the current head",
+			depth, number, head,
+		);
+		self.notify(|notify| notify.reorg_held(&head, number, depth));
+	}
+
+	fn note_imported_block(&self, block: ImportedBlock) {
+		if let Some(status) = self.attack_monitor.note_import(block) {
+			match status.level {
+				ThreatLevel::Normal => info!(target: "client", "Threat level back to normal"),
+				level => warn!(target: "client", "Threat level raised to {:?}: {}", level, status.reasons.join(", ")),
+			}
+		}
+	}
+
 	/// Register an action to be done if a mode/spec_name change happens.
 	pub fn on_user_defaults_change<F>(&self, f: F) where F: 'static + FnMut(Option<Mode>) + Send {
 		*self.on_user_defaults_change.lock() = Some(Box::new(f));
@@ -897,6 +1309,122 @@ impl Client {
 		}
 	}
 
+	/// Import everything left in the block queue, waiting for blocks still being verified.
+	/// Gives up at `deadline`, or earlier once the queued blocks can't be imported. Returns
+	/// true if the queue has been emptied.
+	pub fn drain_queue(&self, deadline: Instant) -> bool {
+		let mut queued = self.importer.block_queue.queue_info().total_queue_size();
+		let mut stalled_rounds = 0;
+		while queued != 0 {
+			if !self.enabled.load(AtomicOrdering::Relaxed) {
+				warn!(target: "client", "Leaving {} queued blocks: the block import is disabled.", queued);
+				return false;
+			}
+			if self.is_import_paused() {
+				warn!(target: "client", "Leaving {} queued blocks: the block import is paused.", queued);
+				return false;
+			}
+			if Instant::now() >= deadline {
+				warn!(target: "client", "Leaving {} queued blocks: the deadline was reached.", queued);
+				return false;
+			}
+			if self.import_verified_blocks() == 0 {
+				std::thread::sleep(Duration::from_millis(10));
+			}
+
+			let left = self.importer.block_queue.queue_info().total_queue_size();
+			stalled_rounds = if left < queued { 0 } else { stalled_rounds + 1 };
+			if stalled_rounds >= DRAIN_STALLED_ROUNDS {
+				warn!(target: "client", "Leaving {} queued blocks: none was verified or imported for {} rounds.", left, stalled_rounds);
+				return false;
+			}
+			queued = left;
+		}
+		true
+	}
+
+	/// Wait for the block import in progress, if any, to finish.
+	/// Returns false if it's still running at `deadline`.
+	pub fn wait_for_import(&self, deadline: Instant) -> bool {
+		self.importer.import_lock.try_lock_until(deadline).is_some()
+	}
+
+	/// Write all buffered changes to the database.
+	pub fn flush_database(&self) -> std::io::Result<()> {
+		*self.unflushed_blocks.lock() = UnflushedBlocks::default();
+		let db = self.db.read();
+		match self.commit_pipeline {
+			Some(ref pipeline) => pipeline.sync()
+				.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)),
+			None => db.key_value().flush(),
+		}
+	}
+
+	/// Retry the failed write of the commit pipeline, or drop all blocks which are not in the
+	/// database yet and reload the chain from it, so they are imported again.
+	/// Must be called with the import lock held.
+	fn recover_commit_pipeline(&self, pipeline: &CommitPipeline) {
+		match pipeline.sync() {
+			Ok(()) => info!(target: "client", "Wrote imported blocks after a failed flush"),
+			Err(e) => {
+				warn!(target: "client", "Dropping imported blocks which could not be written to the database: {}", e);
+				pipeline.discard();
+				*self.unflushed_blocks.lock() = UnflushedBlocks::default();
+				self.reload_chain();
+				info!(target: "client", "Rolled back to best block #{}", self.chain.read().best_block_number());
+			},
+		}
+	}
+
+	/// Reopen the state, chain and traces from the database, dropping everything cached.
+	fn reload_chain(&self) {
+		let mut state_db = self.state_db.write();
+		let mut chain = self.chain.write();
+		let mut tracedb = self.tracedb.write();
+		self.importer.miner.clear();
+		let db = self.db.read();
+
+		let cache_budget = state_db.cache_budget();
+		*state_db = StateDB::with_budget(journaldb::new(db.key_value().clone(), self.pruning, ::db::COL_STATE), cache_budget);
+		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
+		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
+		self.last_hashes.write().clear();
+	}
+
+	/// Notes the blocks imported by a round. Returns true if the buffered changes may wait
+	/// for the next rounds instead of being written now.
+	fn defer_flush(&self, imported: usize, has_more_blocks_to_import: bool) -> bool {
+		let mut unflushed = self.unflushed_blocks.lock();
+		unflushed.count += imported;
+		if unflushed.count > 0 && unflushed.since.is_none() {
+			unflushed.since = Some(Instant::now());
+		}
+		let defer = has_more_blocks_to_import
+			&& unflushed.count < self.config.commit_batch_blocks
+			&& !unflushed.is_expired();
+		if !defer {
+			*unflushed = UnflushedBlocks::default();
+		}
+		defer
+	}
+
+	/// Whether the operator paused the block import. A timed out pause is lifted.
+	fn is_import_paused(&self) -> bool {
+		// a held back reorganization pauses the import until accepted or rejected by resuming it.
+		if self.held_reorg.lock().is_some() {
+			return true;
+		}
+		let timed_out = match *self.import_paused_until.lock() {
+			Some(until) => Instant::now() >= until,
+			None => return false,
+		};
+		if timed_out {
+			warn!(target: "client", "Block import pause timed out");
+			self.resume_import();
+		}
+		!timed_out
+	}
+
 	/// The env info as of the best block.
 	pub fn latest_env_info(&self) -> EnvInfo {
 		self.env_info(BlockId::Latest).expect("Best block header always stored; qed")
@@ -905,19 +1433,39 @@ impl Client {
 	/// The env info as of a given block.
 	/// returns `None` if the block unknown.
 	pub fn env_info(&self, id: BlockId) -> Option<EnvInfo> {
-		self.block_header(id).map(|header| {
-			EnvInfo {
-				number: header.number(),
-				author: header.author(),
-				timestamp: header.timestamp(),
-				difficulty: header.difficulty(),
-				last_hashes: self.build_last_hashes(header.parent_hash()),
-				gas_used: U256::default(),
-				gas_limit: header.gas_limit(),
-			}
+		let header = self.block_header(id)?.decode().ok()?;
+		Some(EnvInfo {
+			base_fee: self.engine.base_fee(&header),
+			..self.call_env_info(&header, *header.gas_limit())
 		})
 	}
 
+	/// Environment of calls executed on top of the state of `header`. Unlike transactions included in a
+	/// block, calls are not charged the base fee.
+	fn call_env_info(&self, header: &Header, gas_limit: U256) -> EnvInfo {
+		EnvInfo {
+			number: header.number(),
+			author: *header.author(),
+			timestamp: header.timestamp(),
+			difficulty: self.engine.prev_randao(header).map_or_else(|| *header.difficulty(), |r| U256::from_big_endian(r.as_bytes())),
+			last_hashes: self.build_last_hashes(*header.parent_hash()),
+			gas_used: U256::default(),
+			gas_limit,
+			base_fee: None,
+		}
+	}
+
+	/// Possible uncles of block `number` authored by `author` on top of `parent`, ordered by the uncle policy.
+	fn uncle_headers(&self, chain: &BlockChain, parent: &H256, number: BlockNumber, author: &Address) -> Vec<Header> {
+		let candidates = chain
+			.find_uncle_headers(parent, MAX_UNCLE_AGE)
+			.unwrap_or_else(Vec::new)
+			.into_iter()
+			.map(|h| h.decode().expect("decoding failure"))
+			.collect();
+		self.config.uncles.order(candidates, number, author)
+	}
+
 	fn build_last_hashes(&self, parent_hash: H256) -> Arc<LastHashes> {
 		{
 			let hashes = self.last_hashes.read();
@@ -927,18 +1475,7 @@ impl Client {
 				return Arc::new(res);
 			}
 		}
-		let mut last_hashes = LastHashes::new();
-		last_hashes.resize(256, H256::zero());
-		last_hashes[0] = parent_hash;
-		let chain = self.chain.read();
-		for i in 0..255 {
-			match chain.block_details(&last_hashes[i]) {
-				Some(details) => {
-					last_hashes[i + 1] = details.parent;
-				},
-				None => break,
-			}
-		}
+		let last_hashes = ancestor_hashes(&self.chain.read(), parent_hash);
 		let mut cached_hashes = self.last_hashes.write();
 		*cached_hashes = VecDeque::from(last_hashes.clone());
 		Arc::new(last_hashes)
@@ -992,6 +1529,11 @@ impl Client {
 						       freeze_at, earliest_era, latest_era, state_db.journal_db().journal_size());
 						break;
 					}
+					let copying_at = self.checkpoints.as_ref().map_or(0, |checkpoints| checkpoints.copying_at());
+					if copying_at > 0 && copying_at == earliest_era {
+						trace!(target: "pruning", "Pruning is paused at era {} (state checkpoint under way)", copying_at);
+						break;
+					}
 					trace!(target: "pruning", "Pruning state for ancient era #{}; latest era={}, journal_size={}",
 					       earliest_era, latest_era, state_db.journal_db().journal_size());
 					match chain.block_hash(earliest_era) {
@@ -1080,7 +1622,8 @@ impl Client {
 			// early exit for pruned blocks
 			if state_db.is_prunable() && self.pruning_info().earliest_state > block_number {
 				trace!(target: "client", "State for block #{} is pruned. Earliest state: {:?}", block_number, self.pruning_info().earliest_state);
-				return None;
+				drop(state_db);
+				return self.reconstruct_state(&header);
 			}
 
 			let db = state_db.boxed_clone();
@@ -1089,6 +1632,77 @@ impl Client {
 		})
 	}
 
+	/// Rebuild the pruned state of a block by re-executing the blocks since the nearest state
+	/// checkpoint among its ancestors, at most one checkpoint interval back. The blocks are
+	/// re-executed on the rebuild thread of the checkpoints, one state at a time.
+	fn reconstruct_state(&self, header: &encoded::Header) -> Option<State<StateDB>> {
+		let checkpoints = self.checkpoints.as_ref()?;
+		let interval = checkpoints.interval();
+		let db = self.db.read().key_value().clone();
+		let chain = self.chain.read().clone();
+		let engine = self.engine.clone();
+		let factories = self.factories.clone();
+		let cache_size = self.config.state_cache_size;
+		let target = header.clone();
+
+		let state_db = checkpoints.rebuild(move |cancelled| {
+			// blocks to re-execute, newest first
+			let mut route = Vec::new();
+			let mut checkpoint = target.clone();
+			while !(checkpoint.number() % interval == 0 && StateCheckpoints::has_checkpoint(&*db, &checkpoint.hash())) {
+				if checkpoint.number() == 0 || route.len() as u64 >= interval {
+					trace!(target: "client", "No state checkpoint found for block #{}", target.number());
+					return None;
+				}
+				let parent = chain.block_header_data(&checkpoint.parent_hash())?;
+				route.push(checkpoint.hash());
+				checkpoint = parent;
+			}
+
+			debug!(target: "client", "Rebuilding the state of block #{} from the checkpoint at #{}", target.number(), checkpoint.number());
+			let mut state_db = StateCheckpoints::state_db(db, cache_size);
+			let mut parent = checkpoint.decode().ok()?;
+			for hash in route.into_iter().rev() {
+				if cancelled.load(AtomicOrdering::SeqCst) {
+					return None;
+				}
+				let block = chain.block(&hash)?;
+				let block_header = block.decode_header();
+				let transactions = block.transactions()
+					.into_iter()
+					.map(SignedTransaction::new)
+					.collect::<Result<Vec<_>, _>>()
+					.ok()?;
+				let is_epoch_begin = chain.epoch_transition(parent.number(), *block_header.parent_hash()).is_some();
+
+				let locked = enact(
+					&block_header,
+					transactions,
+					block.uncles(),
+					&*engine,
+					false,
+					state_db,
+					&parent,
+					Arc::new(ancestor_hashes(&chain, *block_header.parent_hash())),
+					factories.clone(),
+					is_epoch_begin,
+				).map_err(|e| {
+					warn!(target: "client", "Failed to re-execute block #{} ({}) to rebuild its state: {}", block_header.number(), hash, e);
+				}).ok()?;
+				if locked.header.state_root() != block_header.state_root() {
+					warn!(target: "client", "Rebuilt state of block #{} ({}) has an unexpected root", block_header.number(), hash);
+					return None;
+				}
+
+				state_db = locked.drain().state.drop().1;
+				parent = block_header;
+			}
+			Some(state_db)
+		})?;
+
+		State::from_existing(state_db, header.state_root(), self.engine.account_start_nonce(header.number()), self.factories.clone()).ok()
+	}
+
 	/// Attempt to get a copy of a specific block's beginning state.
 	///
 	/// This will not fail if given BlockId::Latest.
@@ -1101,6 +1715,211 @@ impl Client {
 		}
 	}
 
+	/// Collects the accounts and storage keys which differ between the states with given roots
+	/// by walking their tries side by side. Requires fat DB to map trie keys back to addresses.
+	fn state_diff_keys_from_tries(&self, db: &StateDB, pre: &H256, post: &H256) -> Result<BTreeMap<Address, BTreeSet<H256>>, CallError> {
+		let db = db.as_hash_db();
+		let storage_root = |account: Option<DBValue>| match account {
+			Some(account) => rlp::decode::<BasicAccount>(&account)
+				.map(|account| account.storage_root)
+				.map_err(|_| CallError::StateCorrupt),
+			None => Ok(KECCAK_NULL_RLP),
+		};
+
+		let mut keys = BTreeMap::new();
+		for (address, pre_account, post_account) in self.diff_fat_tries(db, pre, post)? {
+			let address = Address::from_slice(&address);
+			let (pre_root, post_root) = (storage_root(pre_account)?, storage_root(post_account)?);
+			let account_db = &self.factories.accountdb.readonly(db, keccak(&address));
+			let storage = self.diff_fat_tries(account_db.as_hash_db(), &pre_root, &post_root)?
+				.into_iter()
+				.map(|(key, _, _)| H256::from_slice(&key))
+				.collect();
+			keys.insert(address, storage);
+		}
+		Ok(keys)
+	}
+
+	/// Walks two fat tries in key hash order and returns the keys whose values differ between them.
+	fn diff_fat_tries(
+		&self,
+		db: &dyn HashDB<<Layout as TrieLayout>::Hash, DBValue>,
+		pre: &H256,
+		post: &H256,
+	) -> Result<Vec<(Vec<u8>, Option<DBValue>, Option<DBValue>)>, CallError> {
+		let mut changes = Vec::new();
+		if pre == post {
+			return Ok(changes);
+		}
+
+		let pre_trie = self.factories.trie.readonly(db, pre).map_err(|_| CallError::StateCorrupt)?;
+		let post_trie = self.factories.trie.readonly(db, post).map_err(|_| CallError::StateCorrupt)?;
+		let mut pre_items = pre_trie.iter().map_err(|_| CallError::StateCorrupt)?;
+		let mut post_items = post_trie.iter().map_err(|_| CallError::StateCorrupt)?;
+		let mut next_pre = pre_items.next().transpose().map_err(|_| CallError::StateCorrupt)?;
+		let mut next_post = post_items.next().transpose().map_err(|_| CallError::StateCorrupt)?;
+
+		const PRE_PROOF: &str = "ordering is only `Less` or `Equal` when there is a `pre` item; qed";
+		const POST_PROOF: &str = "ordering is only `Greater` or `Equal` when there is a `post` item; qed";
+
+		loop {
+			let ordering = match (&next_pre, &next_post) {
+				(None, None) => break,
+				(Some(_), None) => cmp::Ordering::Less,
+				(None, Some(_)) => cmp::Ordering::Greater,
+				(Some((pre_key, _)), Some((post_key, _))) => keccak(pre_key).cmp(&keccak(post_key)),
+			};
+
+			match ordering {
+				cmp::Ordering::Less => {
+					let (key, value) = next_pre.take().expect(PRE_PROOF);
+					changes.push((key, Some(value), None));
+					next_pre = pre_items.next().transpose().map_err(|_| CallError::StateCorrupt)?;
+				}
+				cmp::Ordering::Greater => {
+					let (key, value) = next_post.take().expect(POST_PROOF);
+					changes.push((key, None, Some(value)));
+					next_post = post_items.next().transpose().map_err(|_| CallError::StateCorrupt)?;
+				}
+				cmp::Ordering::Equal => {
+					let (key, pre_value) = next_pre.take().expect(PRE_PROOF);
+					let (_, post_value) = next_post.take().expect(POST_PROOF);
+					if pre_value != post_value {
+						changes.push((key, Some(pre_value), Some(post_value)));
+					}
+					next_pre = pre_items.next().transpose().map_err(|_| CallError::StateCorrupt)?;
+					next_post = post_items.next().transpose().map_err(|_| CallError::StateCorrupt)?;
+				}
+			}
+		}
+
+		Ok(changes)
+	}
+
+	/// Collects the accounts and storage keys written by the canonical blocks between `from` (exclusive)
+	/// and `to` (inclusive) by re-executing them, including the changes made by the engine while
+	/// closing the blocks, e.g. rewards and system calls.
+	fn state_diff_keys_from_replay(&self, from: BlockNumber, to: BlockNumber) -> Result<BTreeMap<Address, BTreeSet<H256>>, CallError> {
+		let mut keys: BTreeMap<Address, BTreeSet<H256>> = BTreeMap::new();
+		for number in from + 1..=to {
+			let block = self.block(BlockId::Number(number)).ok_or(CallError::StatePruned)?;
+			let header = block.decode_header();
+			let parent = self.block_header_decoded(BlockId::Hash(*header.parent_hash())).ok_or(CallError::StatePruned)?;
+			let db = {
+				let state_db = self.state_db.read();
+				if state_db.is_prunable() && self.pruning_info().earliest_state > parent.number() {
+					return Err(CallError::StatePruned);
+				}
+				state_db.boxed_clone()
+			};
+
+			let transactions = block.transactions()
+				.into_iter()
+				.map(SignedTransaction::new)
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(|_| CallError::StateCorrupt)?;
+			let is_epoch_begin = self.chain.read().epoch_transition(parent.number(), *header.parent_hash()).is_some();
+
+			let locked = enact_tracking_writes(
+				&header,
+				transactions,
+				block.uncles(),
+				&*self.engine,
+				false,
+				db,
+				&parent,
+				self.build_last_hashes(*header.parent_hash()),
+				self.factories.clone(),
+				is_epoch_begin,
+			).map_err(|e| {
+				warn!(target: "client", "Failed to re-execute block #{} ({}) for a state diff: {}", header.number(), header.hash(), e);
+				CallError::StateCorrupt
+			})?;
+			for (address, written) in locked.state.written_storage() {
+				keys.entry(address).or_default().extend(written);
+			}
+		}
+		Ok(keys)
+	}
+
+	/// Up to `count` changed accounts following `after`, and the last of them if more accounts follow.
+	fn state_diff_page(
+		keys: &BTreeMap<Address, BTreeSet<H256>>,
+		after: Option<&Address>,
+		count: u64,
+	) -> (Vec<(Address, BTreeSet<H256>)>, Option<Address>) {
+		let mut accounts = match after {
+			Some(after) => keys.range((Bound::Excluded(*after), Bound::Unbounded)),
+			None => keys.range(..),
+		};
+		let page: Vec<_> = accounts.by_ref()
+			.take(count as usize)
+			.map(|(address, keys)| (*address, keys.clone()))
+			.collect();
+		let next = match accounts.next() {
+			Some(_) => page.last().map(|&(address, _)| address),
+			None => None,
+		};
+		(page, next)
+	}
+
+	/// Computes the diff of an account between two states, restricted to the given storage keys.
+	fn account_diff(pre: &State<StateDB>, post: &State<StateDB>, address: &Address, keys: &BTreeSet<H256>) -> Result<Option<AccountDiff>, CallError> {
+		let read = |state: &State<StateDB>| -> Result<_, CallError> {
+			if !state.exists(address).map_err(|_| CallError::StateCorrupt)? {
+				return Ok(None);
+			}
+			let code = state.code(address).map_err(|_| CallError::StateCorrupt)?
+				.map_or_else(Vec::new, |code| (*code).clone());
+			let mut storage = BTreeMap::new();
+			for key in keys {
+				let value = state.storage_at(address, key).map_err(|_| CallError::StateCorrupt)?;
+				if !value.is_zero() {
+					storage.insert(*key, value);
+				}
+			}
+			let balance = state.balance(address).map_err(|_| CallError::StateCorrupt)?;
+			let nonce = state.nonce(address).map_err(|_| CallError::StateCorrupt)?;
+			Ok(Some((balance, nonce, code, storage)))
+		};
+
+		let diff = match (read(pre)?, read(post)?) {
+			(None, None) => return Ok(None),
+			(None, Some((balance, nonce, code, storage))) => AccountDiff {
+				balance: Diff::Born(balance),
+				nonce: Diff::Born(nonce),
+				code: Diff::Born(code),
+				storage: storage.into_iter().map(|(key, value)| (key, Diff::Born(value))).collect(),
+			},
+			(Some((balance, nonce, code, storage)), None) => AccountDiff {
+				balance: Diff::Died(balance),
+				nonce: Diff::Died(nonce),
+				code: Diff::Died(code),
+				storage: storage.into_iter().map(|(key, value)| (key, Diff::Died(value))).collect(),
+			},
+			(Some((pre_balance, pre_nonce, pre_code, pre_storage)), Some((post_balance, post_nonce, post_code, post_storage))) => AccountDiff {
+				balance: Diff::new(pre_balance, post_balance),
+				nonce: Diff::new(pre_nonce, post_nonce),
+				code: Diff::new(pre_code, post_code),
+				storage: keys.iter().filter_map(|key| {
+					let pre_value = pre_storage.get(key).cloned().unwrap_or_default();
+					let post_value = post_storage.get(key).cloned().unwrap_or_default();
+					if pre_value == post_value {
+						None
+					} else {
+						Some((*key, Diff::new(pre_value, post_value)))
+					}
+				}).collect(),
+			},
+		};
+
+		if diff.balance.is_same() && diff.nonce.is_same() && diff.code.is_same() && diff.storage.is_empty() {
+			Ok(None)
+		} else {
+			Ok(Some(diff))
+		}
+	}
+
 	/// Get a copy of the best block's state.
 	pub fn state(&self) -> impl StateInfo {
 		let (state, _) = self.latest_state_and_header();
@@ -1112,11 +1931,17 @@ impl Client {
 		self.chain.read().cache_size()
 	}
 
+	/// Get statistics of the contract code cache shared by all executions.
+	pub fn code_cache_stats(&self) -> CodeCacheStats {
+		self.factories.vm.code_cache_stats()
+	}
+
 	/// Get the report.
 	pub fn report(&self) -> ClientReport {
 		let mut report = self.report.read().clone();
 		let state_db = self.state_db.read();
 		report.state_db_mem = state_db.mem_used();
+		report.state_cache = state_db.cache_stats();
 		let io_stats = state_db.journal_db().io_stats();
 		report.io_stats = IoStats {
 			transactions: io_stats.transactions,
@@ -1137,6 +1962,21 @@ impl Client {
 		self.tracedb.read().collect_garbage();
 	}
 
+	/// Whether the background rewrites of the database may run now.
+	fn may_rewrite_database(&self) -> bool {
+		let window = match self.config.db_rewrite_window {
+			Some(window) => window,
+			None => return true,
+		};
+		if self.rewrite_requested.load(AtomicOrdering::SeqCst) {
+			return true;
+		}
+		let minute = SystemTime::now().duration_since(UNIX_EPOCH)
+			.map(|now| (now.as_secs() / 60 % (24 * 60)) as u32)
+			.unwrap_or(0);
+		window.contains(minute)
+	}
+
 	fn check_snooze(&self) {
 		let mode = self.mode.lock().clone();
 		match mode {
@@ -1224,6 +2064,29 @@ impl Client {
 			data,
 		}.fake_sign(from)
 	}
+	// replays the transactions preceding the given one in its block, then the transaction itself with the given options.
+	fn replay_with<T, V>(&self, id: TransactionId, options: TransactOptions<T, V>) -> Result<types::engines::machine::Executed<T::Output, V::Output>, CallError> where
+		T: trace::Tracer,
+		V: trace::VMTracer,
+	{
+		let address = self.transaction_address(id).ok_or_else(|| CallError::TransactionNotFound)?;
+		let block = BlockId::Hash(address.block_hash);
+		let mut env_info = self.env_info(block).ok_or_else(|| CallError::StatePruned)?;
+		let body = self.block_body(block).ok_or_else(|| CallError::StatePruned)?;
+		let mut state = self.state_at_beginning(block).ok_or_else(|| CallError::StatePruned)?;
+		let machine = self.engine.machine();
+
+		const PROOF: &str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
+		let mut txs = body.transactions().into_iter().map(|t| SignedTransaction::new(t).expect(PROOF));
+		for t in txs.by_ref().take(address.index) {
+			let executed = Self::do_virtual_call(machine, &env_info, &mut state, &t, CallAnalytics::default())?;
+			env_info.gas_used = env_info.gas_used + executed.gas_used;
+		}
+
+		let t = txs.next().expect("The transaction address contains a valid index within block; qed");
+		let schedule = machine.schedule(env_info.number);
+		Ok(Executive::new(&mut state, &env_info, machine, &schedule).transact_virtual(&t, options.dont_check_nonce())?)
+	}
 
 	fn do_virtual_call(
 		machine: &::machine::Machine,
@@ -1300,6 +2163,12 @@ impl DatabaseRestore for Client {
 		trace!(target: "snapshot", "Replacing client database with {:?}", new_db);
 
 		let _import_lock = self.importer.import_lock.lock();
+		if let Some(ref pipeline) = self.commit_pipeline {
+			// the old database must not be written to once it's replaced.
+			if let Err(e) = pipeline.sync() {
+				warn!(target: "snapshot", "Failed to flush the database before restoring: {}", e);
+			}
+		}
 		let mut state_db = self.state_db.write();
 		let mut chain = self.chain.write();
 		let mut tracedb = self.tracedb.write();
@@ -1307,10 +2176,11 @@ impl DatabaseRestore for Client {
 		let db = self.db.write();
 		db.restore(new_db)?;
 
-		let cache_size = state_db.cache_size();
-		*state_db = StateDB::new(journaldb::new(db.key_value().clone(), self.pruning, ::db::COL_STATE), cache_size);
+		let cache_budget = state_db.cache_budget();
+		*state_db = StateDB::with_budget(journaldb::new(db.key_value().clone(), self.pruning, ::db::COL_STATE), cache_budget);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
+		*self.header_accumulator.lock() = HeaderAccumulator::load(&**db.key_value());
 		Ok(())
 	}
 }
@@ -1383,12 +2253,83 @@ impl BlockChainReset for Client {
 		Ok(())
 	}
 
+	fn rewind(&self, num: u32) -> Result<(), String> {
+		let _import_lock = self.importer.import_lock.lock();
+		if let Some(ref pipeline) = self.commit_pipeline {
+			// blocks still buffered by the pipeline would be written after the reset.
+			pipeline.sync()
+				.map_err(|e| format!("Failed to flush the database before rewinding: {}", e))?;
+		}
+		self.reset(num)?;
+		self.reload_chain();
+
+		if let Some(dev_mining) = self.engine.dev_mining() {
+			dev_mining.rewound(self.chain.read().best_block_number());
+		}
+		Ok(())
+	}
+
 	/// Ask the client what the history parameter is.
 	fn pruning_history(&self) -> u64 {
 		self.history
 	}
 }
 
+impl ForkChoiceClient for Client {
+	fn set_head(&self, head: H256, finalized: Option<H256>) -> Result<(), String> {
+		let start = Instant::now();
+		let route = {
+			// scope for self.import_lock
+			let _import_lock = self.importer.import_lock.lock();
+			let chain = self.chain.read();
+			let number = chain.block_number(&head).ok_or_else(|| format!("Unknown block {:?}", head))?;
+
+			let mut batch = DBTransaction::new();
+			let route = if chain.best_block_hash() == head {
+				ImportRoute::none()
+			} else if chain.is_canon(&head) {
+				// the Engine API lets the consensus client point at a canonical ancestor, which keeps the best block.
+				debug!(target: "client", "Keeping best block on fork choice update to its ancestor {:?}", head);
+				ImportRoute::none()
+			} else {
+				chain.set_best_block(&mut batch, head)
+					.ok_or_else(|| format!("Block {:?} retracts a finalized block", head))?
+			};
+			if let Some(finalized) = finalized {
+				chain.mark_finalized(&mut batch, finalized).ok_or_else(|| format!("Unknown finalized block {:?}", finalized))?;
+			}
+
+			self.tracedb.read().enact(number + 1 - route.enacted.len() as BlockNumber, &route.enacted);
+			self.db.read().key_value().write_buffered(batch);
+			chain.commit();
+			self.state_db.write().sync_cache(&route.enacted, &route.retracted, true);
+			route
+		};
+
+		if route.enacted.is_empty() {
+			return Ok(());
+		}
+
+		info!(target: "client", "Head set to #{} ({})", self.chain.read().best_block_number(), head);
+		let route = ChainRoute::from([route].as_ref());
+		self.importer.miner.chain_new_blocks(self, &[], &[], route.enacted(), route.retracted(), false);
+		self.notify(|notify| {
+			notify.new_blocks(
+				NewBlocks::new(
+					vec![],
+					vec![],
+					route.clone(),
+					vec![],
+					vec![],
+					start.elapsed(),
+					false
+				)
+			);
+		});
+		self.db.read().key_value().flush().map_err(|e| format!("DB flush failed: {}", e))
+	}
+}
+
 impl Nonce for Client {
 	fn nonce(&self, address: &Address, id: BlockId) -> Option<U256> {
 		self.state_at(id).and_then(|s| s.nonce(address).ok())
@@ -1402,10 +2343,84 @@ impl Balance for Client {
 			StateOrBlock::Block(id) => self.state_at(id).and_then(|s| s.balance(address).ok())
 		}
 	}
+
+	fn balances(&self, addresses: &[Address], state: StateOrBlock) -> Option<Result<Vec<U256>, String>> {
+		let state = match state {
+			StateOrBlock::State(s) => s,
+			StateOrBlock::Block(id) => Box::new(self.state_at(id)?) as Box<dyn StateInfo>,
+		};
+		Some(addresses.iter().map(|address| state.balance(address).map_err(|e| e.to_string())).collect())
+	}
 }
 
 impl AccountData for Client {}
 
+impl PrometheusMetrics for Client {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let report = self.report();
+		let queue = self.queue_info();
+		let pool = self.importer.miner.queue_status().status;
+
+		r.register_gauge("chain_best_block", "Best block number", self.chain.read().best_block_number() as i64);
+		if self.config.max_reorg_depth.is_some() {
+			let depth = self.held_reorg.lock().map_or(0, |held| held.depth);
+			r.register_gauge("chain_held_reorg_depth", "Blocks retracted by the reorganization waiting to be accepted", depth as i64);
+		}
+		let threat = self.attack_monitor.status();
+		r.register_gauge("chain_threat_level", "Likelihood of a majority attack: 0 normal, 1 elevated, 2 high", threat.level as i64);
+		r.register_gauge("chain_side_blocks", "Recently imported blocks which did not become canon", threat.side_blocks as i64);
+		r.register_gauge("chain_longest_side_branch", "Longest recent competing branch", threat.longest_side_branch as i64);
+		r.register_gauge("chain_canon_blocks_per_hour", "Recent growth rate of the canon chain", threat.canon_rate as i64);
+		r.register_gauge("chain_side_branch_blocks_per_hour", "Recent growth rate of the fastest growing competing branch", threat.fastest_side_branch_rate as i64);
+		r.register_gauge("chain_max_difficulty_swing_permille", "Largest recent difficulty change between a block and its parent", threat.max_difficulty_swing as i64);
+		r.register_gauge("chain_deep_reorgs", "Recent reorganizations deeper than honest forks usually are", threat.deep_reorgs as i64);
+		r.register_counter("import_blocks_total", "Blocks imported since start", report.blocks_imported as u64);
+		r.register_counter("import_transactions_total", "Transactions applied since start", report.transactions_applied as u64);
+		r.register_histogram("import_block_seconds", "Time to execute and commit a block", &self.block_import_time.snapshot());
+
+		r.register_gauge("queue_unverified", "Blocks waiting for verification", queue.unverified_queue_size as i64);
+		r.register_gauge("queue_verifying", "Blocks being verified", queue.verifying_queue_size as i64);
+		r.register_gauge("queue_verified", "Verified blocks waiting for import", queue.verified_queue_size as i64);
+		r.register_gauge("queue_mem_bytes", "Memory used by the verification queue", queue.mem_used as i64);
+
+		r.register_gauge("txpool_transactions", "Transactions in the pool", pool.transaction_count as i64);
+		r.register_gauge("txpool_senders", "Distinct senders of pooled transactions", pool.senders as i64);
+		r.register_gauge("txpool_mem_bytes", "Memory used by the transaction pool", pool.mem_usage as i64);
+
+		r.register_histogram("db_write_seconds", "Time to flush imported blocks to the database", &self.db_write_time.snapshot());
+		r.register_gauge("state_db_mem_bytes", "Memory used by the state database journal", report.state_db_mem as i64);
+		for (name, stats) in &[("accounts", report.state_cache.accounts), ("storage", report.state_cache.storage), ("code", report.state_cache.code)] {
+			r.register_counter(&format!("state_cache_{}_hits_total", name), "Lookups answered from the state cache", stats.hits);
+			r.register_counter(&format!("state_cache_{}_misses_total", name), "Lookups read from the database", stats.misses);
+		}
+
+		if let Some(ref call_cache) = self.call_cache {
+			let stats = call_cache.stats();
+			r.register_gauge("call_cache_entries", "Call results kept for the best block", call_cache.len() as i64);
+			r.register_counter("call_cache_hits_total", "Calls answered from the call cache", stats.hits);
+			r.register_counter("call_cache_misses_total", "Calls on the best block which were executed", stats.misses);
+			r.register_counter("call_cache_promoted_total", "Call results kept for a new best block", stats.promoted);
+			r.register_counter("call_cache_invalidated_total", "Call results dropped because a new best block modified the state they read", stats.invalidated);
+		}
+
+		if let Some(index) = self.tracedb.read().address_index_status() {
+			r.register_gauge("trace_address_index_first_block", "First block whose traces are in the address index", index.first_block as i64);
+			r.register_gauge("trace_address_index_bytes", "Approximate size of the trace address index", index.size as i64);
+		}
+
+		if let Some(duties) = self.engine.validator_duties() {
+			let status = duties.validator_status();
+			if status.signer.is_some() {
+				r.register_gauge("validator_step", "Current consensus step", status.current_step as i64);
+				r.register_counter("validator_slots_expected_total", "Steps the local validator was expected to seal", status.expected);
+				r.register_counter("validator_slots_sealed_total", "Expected steps sealed in time", status.sealed);
+				r.register_counter("validator_slots_late_total", "Expected steps sealed in the second half of the step", status.late);
+				r.register_counter("validator_slots_missed_total", "Expected steps which passed without a seal", status.missed);
+			}
+		}
+	}
+}
+
 impl ChainInfo for Client {
 	fn chain_info(&self) -> BlockChainInfo {
 		let mut chain_info = self.chain.read().chain_info();
@@ -1524,30 +2539,51 @@ impl Call for Client {
 	type State = State<::state_db::StateDB>;
 
 	fn call(&self, transaction: &SignedTransaction, analytics: CallAnalytics, state: &mut Self::State, header: &Header) -> Result<Executed, CallError> {
-		let env_info = EnvInfo {
-			number: header.number(),
-			author: *header.author(),
-			timestamp: header.timestamp(),
-			difficulty: *header.difficulty(),
-			last_hashes: self.build_last_hashes(*header.parent_hash()),
-			gas_used: U256::default(),
-			gas_limit: U256::max_value(),
-		};
+		let env_info = self.call_env_info(header, U256::max_value());
 		let machine = self.engine.machine();
 
 		Self::do_virtual_call(&machine, &env_info, state, transaction, analytics)
 	}
 
-	fn call_many(&self, transactions: &[(SignedTransaction, CallAnalytics)], state: &mut Self::State, header: &Header) -> Result<Vec<Executed>, CallError> {
-		let mut env_info = EnvInfo {
-			number: header.number(),
-			author: *header.author(),
-			timestamp: header.timestamp(),
-			difficulty: *header.difficulty(),
-			last_hashes: self.build_last_hashes(*header.parent_hash()),
-			gas_used: U256::default(),
-			gas_limit: U256::max_value(),
+	fn call_cached(&self, transaction: &SignedTransaction, state: &mut Self::State, header: &Header) -> Result<Executed, CallError> {
+		let head = header.hash();
+		let call_cache = match self.call_cache {
+			Some(ref call_cache) if head == self.chain.read().best_block_hash() => call_cache,
+			_ => return self.call(transaction, Default::default(), state, header),
 		};
+		let (hash, sender) = (transaction.hash(), transaction.sender());
+		if let Some(executed) = call_cache.get(&head, &hash, &sender) {
+			return Ok(executed);
+		}
+
+		let env_info = self.call_env_info(header, U256::max_value());
+		let machine = self.engine.machine();
+		let schedule = machine.schedule(env_info.number);
+		let options = TransactOptions::new(trace::NoopTracer, EnvironmentObserver::default())
+			.dont_check_nonce()
+			.save_output_from_contract();
+		state.track_reads();
+		let observed = Executive::new(state, &env_info, machine, &schedule).transact_virtual(transaction, options)?;
+		let pinned = observed.vm_trace.unwrap_or(true);
+		let executed = Executed {
+			exception: observed.exception,
+			gas: observed.gas,
+			gas_used: observed.gas_used,
+			refunded: observed.refunded,
+			cumulative_gas_used: observed.cumulative_gas_used,
+			logs: observed.logs,
+			contracts_created: observed.contracts_created,
+			output: observed.output,
+			trace: observed.trace,
+			vm_trace: None,
+			state_diff: None,
+		};
+		call_cache.insert(head, hash, sender, executed.clone(), state.reads(), pinned);
+		Ok(executed)
+	}
+
+	fn call_many(&self, transactions: &[(SignedTransaction, CallAnalytics)], state: &mut Self::State, header: &Header) -> Result<Vec<Executed>, CallError> {
+		let mut env_info = self.call_env_info(header, U256::max_value());
 
 		let mut results = Vec::with_capacity(transactions.len());
 		let machine = self.engine.machine();
@@ -1561,20 +2597,22 @@ impl Call for Client {
 		Ok(results)
 	}
 
+	fn call_gas_profile(&self, transaction: &SignedTransaction, state: &mut Self::State, header: &Header) -> Result<Vec<trace::GasProfile>, CallError> {
+		let env_info = self.call_env_info(header, U256::max_value());
+		let machine = self.engine.machine();
+		let schedule = machine.schedule(env_info.number);
+		let profiler = trace::GasProfiler::new();
+		let options = TransactOptions::new(profiler.clone(), profiler).dont_check_nonce();
+
+		Ok(Executive::new(state, &env_info, machine, &schedule).transact_virtual(transaction, options)?.trace)
+	}
+
 	fn estimate_gas(&self, t: &SignedTransaction, state: &Self::State, header: &Header) -> Result<U256, CallError> {
 		let (mut upper, max_upper, env_info) = {
 			let init = *header.gas_limit();
 			let max = init * U256::from(10);
 
-			let env_info = EnvInfo {
-				number: header.number(),
-				author: *header.author(),
-				timestamp: header.timestamp(),
-				difficulty: *header.difficulty(),
-				last_hashes: self.build_last_hashes(*header.parent_hash()),
-				gas_used: U256::default(),
-				gas_limit: max,
-			};
+			let env_info = self.call_env_info(header, max);
 
 			(init, max, env_info)
 		};
@@ -1656,6 +2694,62 @@ impl BadBlocks for Client {
 	fn bad_blocks(&self) -> Vec<(Unverified, String)> {
 		self.importer.bad_blocks.bad_blocks()
 	}
+
+	fn bad_block_diagnostics(&self, hash: &H256) -> Option<String> {
+		self.importer.bad_blocks.diagnostics(hash)
+	}
+}
+
+impl UncleCandidates for Client {
+	fn uncle_candidates(&self, author: &Address) -> Vec<UncleCandidate> {
+		let engine = &*self.engine;
+		let chain = self.chain.read();
+		let parent = chain.best_block_header();
+		let parent_hash = parent.hash();
+		let number = parent.number() + 1;
+
+		let candidates = self.uncle_headers(&chain, &parent_hash, number, author);
+		let limit = self.config.uncles.limit(engine.maximum_uncle_count(number));
+		let is_epoch_begin = chain.epoch_transition(parent.number(), parent_hash).is_some();
+		let last_hashes = self.build_last_hashes(parent_hash);
+
+		// the rewards are whatever the engine bestows when closing an empty block
+		let author_balance = |uncle: Option<&Header>| -> Option<U256> {
+			let mut block = OpenBlock::new(
+				engine,
+				self.factories.clone(),
+				false,
+				self.state_db.read().boxed_clone_canon(&parent_hash),
+				&parent,
+				last_hashes.clone(),
+				*author,
+				(*parent.gas_limit(), *parent.gas_limit()),
+				Vec::new(),
+				is_epoch_begin,
+			).ok()?;
+			if let Some(uncle) = uncle {
+				block.push_uncle(uncle.clone()).ok()?;
+			}
+			block.close().ok()?.state.balance(author).ok()
+		};
+		let base_balance = author_balance(None);
+
+		candidates.into_iter().enumerate().map(|(index, uncle)| {
+			let expected_reward = match (base_balance, author_balance(Some(&uncle))) {
+				(Some(base), Some(with_uncle)) => with_uncle.saturating_sub(base),
+				_ => U256::zero(),
+			};
+			UncleCandidate {
+				hash: uncle.hash(),
+				number: uncle.number(),
+				author: *uncle.author(),
+				age: number - uncle.number(),
+				own: uncle.author() == author,
+				included: index < limit,
+				expected_reward,
+			}
+		}).collect()
+	}
 }
 
 impl BlockChainClient for Client {
@@ -1667,6 +2761,17 @@ impl BlockChainClient for Client {
 		Ok(self.replay_block_transactions(block, analytics)?.nth(address.index).expect(PROOF).1)
 	}
 
+	fn replay_struct_logs(&self, id: TransactionId, logger: trace::StructLogger) -> Result<usize, CallError> {
+		let options = TransactOptions::new(trace::NoopTracer, logger);
+		Ok(self.replay_with(id, options)?.vm_trace.unwrap_or_default())
+	}
+
+	fn replay_gas_profile(&self, id: TransactionId) -> Result<Vec<trace::GasProfile>, CallError> {
+		let profiler = trace::GasProfiler::new();
+		let options = TransactOptions::new(profiler.clone(), profiler);
+		Ok(self.replay_with(id, options)?.trace)
+	}
+
 	fn replay_block_transactions(&self, block: BlockId, analytics: CallAnalytics) -> Result<Box<dyn Iterator<Item = (H256, Executed)>>, CallError> {
 		let mut env_info = self.env_info(block).ok_or_else(|| CallError::StatePruned)?;
 		let body = self.block_body(block).ok_or_else(|| CallError::StatePruned)?;
@@ -1688,6 +2793,84 @@ impl BlockChainClient for Client {
 			})))
 	}
 
+	fn execution_witness(&self, id: BlockId) -> Result<Vec<Bytes>, CallError> {
+		let block = self.block(id).ok_or(CallError::StatePruned)?;
+		let header = block.decode_header();
+		if header.number() == 0 {
+			return Ok(Vec::new());
+		}
+		let parent = self.block_header_decoded(BlockId::Hash(*header.parent_hash())).ok_or(CallError::StatePruned)?;
+
+		let (db, recorder) = {
+			let state_db = self.state_db.read();
+			if state_db.is_prunable() && self.pruning_info().earliest_state > parent.number() {
+				return Err(CallError::StatePruned);
+			}
+			state_db.recording_clone()
+		};
+
+		let transactions = block.transactions()
+			.into_iter()
+			.map(SignedTransaction::new)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|_| CallError::StateCorrupt)?;
+		let is_epoch_begin = self.chain.read().epoch_transition(parent.number(), *header.parent_hash()).is_some();
+
+		enact(
+			&header,
+			transactions,
+			block.uncles(),
+			&*self.engine,
+			false,
+			db,
+			&parent,
+			self.build_last_hashes(*header.parent_hash()),
+			self.factories.clone(),
+			is_epoch_begin,
+		).map_err(|e| {
+			warn!(target: "client", "Failed to re-execute block #{} ({}) for its witness: {}", header.number(), header.hash(), e);
+			CallError::StateCorrupt
+		})?;
+
+		Ok(recorder.values())
+	}
+
+	fn state_diff(&self, from: BlockId, to: BlockId, after: Option<&Address>, count: u64) -> Result<(StateDiff, Option<Address>), CallError> {
+		let from_number = self.block_number(from).ok_or(CallError::StatePruned)?;
+		let to_number = self.block_number(to).ok_or(CallError::StatePruned)?;
+		let from_hash = self.block_hash(from).ok_or(CallError::StatePruned)?;
+		let to_hash = self.block_hash(to).ok_or(CallError::StatePruned)?;
+		let pre = self.state_at(from).ok_or(CallError::StatePruned)?;
+		let post = self.state_at(to).ok_or(CallError::StatePruned)?;
+
+		// the changed keys are computed once for all pages of a range.
+		let range = (from_hash, to_hash);
+		let cached = self.state_diff_cache.lock().get_mut(&range).map(|keys| Self::state_diff_page(keys, after, count));
+		let (page, next) = match cached {
+			Some(page) => page,
+			None => {
+				let keys = if self.factories.trie.is_fat() {
+					let db = self.state_db.read().boxed_clone();
+					self.state_diff_keys_from_tries(&db, pre.root(), post.root())?
+				} else {
+					self.state_diff_keys_from_replay(cmp::min(from_number, to_number), cmp::max(from_number, to_number))?
+				};
+				let page = Self::state_diff_page(&keys, after, count);
+				self.state_diff_cache.lock().insert(range, keys);
+				page
+			}
+		};
+
+		let mut raw = BTreeMap::new();
+		for (address, keys) in page {
+			if let Some(diff) = Self::account_diff(&pre, &post, &address, &keys)? {
+				raw.insert(address, diff);
+			}
+		}
+
+		Ok((StateDiff { raw }, next))
+	}
+
 	fn mode(&self) -> Mode {
 		self.mode.lock().clone()
 	}
@@ -1745,6 +2928,122 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn reload_engine_params(&self, update: &[u8], signature: H520) -> Result<(), EthcoreError> {
+		let reload = self.engine.params_reload().ok_or(EngineError::ParamsReloadUnsupported)?;
+		// updates are applied and stored in the same order.
+		let mut updates = self.engine_params_updates.lock();
+		let best_block = self.chain.read().best_block_number();
+		reload.reload_params(update, &signature.into(), best_block)?;
+		updates.push((update.to_vec(), signature));
+
+		let mut stream = RlpStream::new_list(updates.len());
+		for &(ref update, ref signature) in &*updates {
+			stream.begin_list(2).append(update).append(signature);
+		}
+		let mut batch = DBTransaction::new();
+		batch.put(::db::COL_EXTRA, ENGINE_PARAMS_UPDATES_KEY, &stream.out());
+		self.db.read().key_value().write(batch)?;
+		info!(target: "client", "Engine parameters updated; re-verifying queued blocks");
+		self.importer.block_queue.reverify();
+		Ok(())
+	}
+
+	fn threat_status(&self) -> ThreatStatus {
+		self.attack_monitor.status()
+	}
+
+	fn block_gas_stats(&self, hash: &H256) -> Option<BlockGasStats> {
+		if let Some(stats) = self.gas_stats_cache.lock().get_mut(hash) {
+			return Some(stats.clone());
+		}
+		let block = self.block(BlockId::Hash(*hash))?;
+		let receipts = self.block_receipts(hash)?;
+		let stats = BlockGasStats::new(&block, &receipts.receipts);
+		self.gas_stats_cache.lock().insert(*hash, stats.clone());
+		Some(stats)
+	}
+
+	fn state_writes(&self, hash: &H256) -> Option<StateWrites> {
+		self.state_writes.lock().iter().rev()
+			.find(|(block, _)| block == hash)
+			.map(|(_, writes)| writes.clone())
+	}
+
+	fn record_state_writes(&self, record: bool) {
+		let was_recording = self.record_state_writes.swap(record, AtomicOrdering::Relaxed);
+		if was_recording && !record {
+			self.state_writes.lock().clear();
+		}
+	}
+
+	fn rewrite_database(&self) -> Result<(), String> {
+		self.db.read().key_value().flush().map_err(|e| e.to_string())?;
+		if !self.rewrite_requested.swap(true, AtomicOrdering::SeqCst) {
+			info!(target: "client", "Rewriting the database in the background until caught up");
+		}
+		Ok(())
+	}
+
+	fn pause_import(&self, timeout: Duration) -> Result<(), String> {
+		let was_paused = self.import_paused_until.lock().replace(Instant::now() + timeout).is_some();
+		let _import_lock = self.importer.import_lock.lock();
+		self.flush_database().map_err(|e| e.to_string())?;
+		if !was_paused {
+			warn!(target: "client", "Block import paused at #{} for at most {} seconds", self.chain.read().best_block_number(), timeout.as_secs());
+		}
+		Ok(())
+	}
+
+	fn resume_import(&self) -> bool {
+		let rejected = self.held_reorg.lock().take();
+		if let Some(held) = rejected {
+			warn!(target: "client", "Rejected the reorganization retracting {} blocks to #{} ({})", held.depth, held.number, held.head);
+		}
+		if self.import_paused_until.lock().take().is_none() && rejected.is_none() {
+			return false;
+		}
+		info!(target: "client", "Block import resumed");
+		// the queue signals verified blocks only once until drained, which the paused import didn't do.
+		if let Err(e) = self.io_channel.read().send(ClientIoMessage::BlockVerified) {
+			debug!(target: "client", "Failed to signal the resumed block import: {:?}", e);
+		}
+		true
+	}
+
+	fn accept_reorg(&self, hash: H256) -> Result<(), String> {
+		let held = self.held_reorg.lock().ok_or_else(|| "No reorganization is held back".to_owned())?;
+		{
+			let chain = self.chain.read();
+			// the branch may be accepted by any of its blocks past the fork point, it is switched to
+			// up to its most recent one.
+			let route = chain.tree_route(hash, held.head).ok_or_else(|| format!("Unknown block {:?}", hash))?;
+			if route.index != 0 || chain.is_canon(&hash) {
+				return Err(format!("Block {:?} is not part of the held back branch", hash));
+			}
+
+			// the states of a branch are pruned with the blocks of the same numbers on the canon chain.
+			let fork = chain.tree_route(chain.best_block_hash(), held.head)
+				.and_then(|route| chain.block_number(&route.ancestor))
+				.ok_or_else(|| format!("Unknown block {:?}", held.head))?;
+			let earliest_era = self.state_db.read().journal_db().earliest_era();
+			if earliest_era.map_or(false, |earliest| earliest > fork + 1) {
+				return Err(format!("The state of the held back branch was pruned from block #{}", fork + 1));
+			}
+		}
+
+		self.set_head(held.head, None)?;
+		let mut current = self.held_reorg.lock();
+		if current.map_or(false, |current| current.head == held.head) {
+			*current = None;
+		}
+		drop(current);
+		warn!(target: "client", "Accepted a reorganization retracting {} blocks to #{} ({})", held.depth, held.number, held.head);
+		if let Err(e) = self.io_channel.read().send(ClientIoMessage::BlockVerified) {
+			debug!(target: "client", "Failed to signal the resumed block import: {:?}", e);
+		}
+		Ok(())
+	}
+
 	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
 		self.block_number_ref(&id)
 	}
@@ -1770,6 +3069,12 @@ impl BlockChainClient for Client {
 		Self::block_hash(&chain, id).and_then(|hash| chain.block_details(&hash)).map(|d| d.total_difficulty)
 	}
 
+	fn block_finalized(&self, id: BlockId) -> Option<bool> {
+		let chain = self.chain.read();
+
+		Self::block_hash(&chain, id).and_then(|hash| chain.block_details(&hash)).map(|d| d.is_finalized)
+	}
+
 	fn storage_root(&self, address: &Address, id: BlockId) -> Option<H256> {
 		self.state_at(id).and_then(|s| s.storage_root(address).ok()).and_then(|x| x)
 	}
@@ -1796,6 +3101,14 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn storage_at_batch(&self, positions: &[(Address, H256)], state: StateOrBlock) -> Option<Result<Vec<H256>, String>> {
+		let state = match state {
+			StateOrBlock::State(s) => s,
+			StateOrBlock::Block(id) => Box::new(self.state_at(id)?) as Box<dyn StateInfo>,
+		};
+		Some(positions.iter().map(|(address, position)| state.storage_at(address, position).map_err(|e| e.to_string())).collect())
+	}
+
 	fn list_accounts(&self, id: BlockId, after: Option<&Address>, count: u64) -> Option<Vec<Address>> {
 		if !self.factories.trie.is_fat() {
 			trace!(target: "fatdb", "list_accounts: Not a fat DB");
@@ -1922,6 +3235,51 @@ impl BlockChainClient for Client {
 		Some(receipt)
 	}
 
+	fn confirmation_status(&self, id: TransactionId) -> Option<ConfirmationStatus> {
+		let block_hash = self.transaction_address(id)?.block_hash;
+		let chain = self.chain.read();
+		let details = chain.block_details(&block_hash)?;
+		if chain.block_hash(details.number) != Some(block_hash) {
+			return None;
+		}
+
+		// Any known branch forking off below the including block would revert the transaction.
+		let mut competing_branch: Option<CompetingBranch> = None;
+		for fork_number in details.number.saturating_sub(FORK_SEARCH_DEPTH)..details.number {
+			let fork_hash = chain.block_hash(fork_number)?;
+			let canonical_child = chain.block_hash(fork_number + 1);
+			let mut pending = chain.block_details(&fork_hash)?.children
+				.into_iter()
+				.filter(|child| Some(*child) != canonical_child)
+				.collect::<Vec<_>>();
+
+			while let Some(hash) = pending.pop() {
+				let block = match chain.block_details(&hash) {
+					Some(block) => block,
+					None => continue,
+				};
+				if competing_branch.as_ref().map_or(true, |branch| block.total_difficulty > branch.total_difficulty) {
+					competing_branch = Some(CompetingBranch {
+						hash,
+						fork_number,
+						length: block.number - fork_number,
+						total_difficulty: block.total_difficulty,
+					});
+				}
+				pending.extend(block.children);
+			}
+		}
+
+		Some(ConfirmationStatus {
+			block_hash,
+			block_number: details.number,
+			depth: chain.best_block_number() + 1 - details.number,
+			total_difficulty: chain.best_block_total_difficulty(),
+			competing_branch,
+			finalized: details.is_finalized,
+		})
+	}
+
 	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>> {
 		let hash = self.block_hash(id)?;
 
@@ -2072,6 +3430,53 @@ impl BlockChainClient for Client {
 		Ok(chain.logs(blocks, |entry| filter.matches(entry), filter.limit))
 	}
 
+	fn reconstructed_logs(&self, filter: Filter) -> Result<Vec<LocalizedLogEntry>, ReconstructLogsError> {
+		let from = self.block_number(filter.from_block).ok_or(ReconstructLogsError::UnknownBlock(filter.from_block))?;
+		let to = self.block_number(filter.to_block).ok_or(ReconstructLogsError::UnknownBlock(filter.to_block))?;
+
+		// blocks which may contain matching logs but lost their receipts
+		let blocks = {
+			let chain = self.chain.read();
+			chain.blocks_with_bloom(&filter.bloom_possibilities(), from, to)
+				.into_iter()
+				.filter_map(|number| chain.block_hash(number).map(|hash| (number, hash)))
+				.filter(|(_, hash)| chain.block_receipts(hash).is_none())
+				.collect::<Vec<_>>()
+		};
+
+		let last = blocks.last().map_or(to, |&(number, _)| number);
+		let mut logs = Vec::new();
+		for (number, hash) in blocks {
+			let executed = match self.replay_block_transactions(BlockId::Hash(hash), CallAnalytics::default()) {
+				Ok(executed) => executed,
+				Err(e) => {
+					debug!(target: "client", "Can't reconstruct logs of block #{} ({}): {}", number, hash, e);
+					return Err(ReconstructLogsError::StatePruned(number, last));
+				}
+			};
+
+			let mut log_index = 0;
+			for (transaction_index, (transaction_hash, executed)) in executed.enumerate() {
+				for (transaction_log_index, entry) in executed.logs.into_iter().enumerate() {
+					if filter.matches(&entry) {
+						logs.push(LocalizedLogEntry {
+							entry,
+							block_hash: hash,
+							block_number: number,
+							transaction_hash,
+							transaction_index,
+							transaction_log_index,
+							log_index,
+						});
+					}
+					log_index += 1;
+				}
+			}
+		}
+
+		Ok(logs)
+	}
+
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		if !self.tracedb.read().tracing_enabled() {
 			return None;
@@ -2180,6 +3585,17 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn validator_set_at(&self, id: BlockId) -> Option<EpochValidators> {
+		let header = self.block_header_decoded(id)?;
+		// the genesis block opens the first epoch; any other block belongs to the epoch of its parent.
+		let epoch_of = if header.number() == 0 { header.hash() } else { *header.parent_hash() };
+
+		let chain = self.chain.read();
+		let transition = chain.epoch_transition_for(epoch_of)?;
+		let transition_header = chain.block_header_data(&transition.block_hash)?.decode().ok()?;
+		epoch_validators(&*self.engine, &transition_header, transition.proof)
+	}
+
 	fn create_transaction(&self, TransactionRequest { action, data, gas, gas_price, nonce }: TransactionRequest)
 		-> Result<SignedTransaction, transaction::Error>
 	{
@@ -2315,6 +3731,32 @@ impl Tick for Client {
 	// TODO: manage by real events.
 	fn tick(&self, prevent_sleep: bool) {
 		self.check_garbage();
+		self.importer.miner.remove_expired_transactions();
+		self.importer.miner.refresh_transaction_filter();
+		// writes the blocks left buffered by an import which stalled before completing a batch.
+		if self.unflushed_blocks.lock().is_expired() {
+			if let Some(_import_lock) = self.importer.import_lock.try_lock() {
+				if let Err(e) = self.flush_database() {
+					warn!(target: "client", "Failed to write buffered blocks: {}", e);
+				}
+			}
+		}
+		// lifts the pause of the block import once timed out, even with no new blocks to import.
+		// While paused, the database is left untouched, e.g. for the operator to copy it.
+		let paused = self.is_import_paused();
+		if !paused {
+			self.update_header_accumulator();
+		}
+		if !paused && self.may_rewrite_database() {
+			let mut rewritten = 0;
+			if self.config.blockchain.compression {
+				rewritten += self.chain.read().recompress(RECOMPRESS_BLOCKS_PER_TICK) as u64;
+			}
+			rewritten += self.tracedb.read().backfill_address_index(TRACE_INDEX_BLOCKS_PER_TICK);
+			if rewritten == 0 && self.rewrite_requested.swap(false, AtomicOrdering::SeqCst) {
+				info!(target: "client", "Background database rewrites caught up");
+			}
+		}
 		if !prevent_sleep {
 			self.check_snooze();
 		}
@@ -2325,19 +3767,16 @@ impl ReopenBlock for Client {
 	fn reopen_block(&self, block: ClosedBlock) -> OpenBlock {
 		let engine = &*self.engine;
 		let mut block = block.reopen(engine);
-		let max_uncles = engine.maximum_uncle_count(block.header.number());
+		let max_uncles = self.config.uncles.limit(engine.maximum_uncle_count(block.header.number()));
 		if block.uncles.len() < max_uncles {
 			let chain = self.chain.read();
 			let h = chain.best_block_hash();
+			let author = *block.header.author();
 			// Add new uncles
-			let uncles = chain
-				.find_uncle_hashes(&h, MAX_UNCLE_AGE)
-				.unwrap_or_else(Vec::new);
-
-			for h in uncles {
-				if !block.uncles.iter().any(|header| header.hash() == h) {
-					let uncle = chain.block_header_data(&h).expect("find_uncle_hashes only returns hashes for existing headers; qed");
-					let uncle = uncle.decode().expect("decoding failure");
+			let uncles = self.uncle_headers(&chain, &h, block.header.number(), &author);
+
+			for uncle in uncles {
+				if !block.uncles.iter().any(|header| header.hash() == uncle.hash()) {
 					block.push_uncle(uncle).expect("pushing up to maximum_uncle_count;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2373,13 +3812,12 @@ impl PrepareOpenBlock for Client {
 		)?;
 
 		// Add uncles
-		chain
-			.find_uncle_headers(&h, MAX_UNCLE_AGE)
-			.unwrap_or_else(Vec::new)
-			.iter()
-			.take(engine.maximum_uncle_count(open_block.header.number()))
-			.for_each(|h| {
-				open_block.push_uncle(h.decode().expect("decoding failure")).expect("pushing maximum_uncle_count;
+		let number = open_block.header.number();
+		self.uncle_headers(&chain, &h, number, &author)
+			.into_iter()
+			.take(self.config.uncles.limit(engine.maximum_uncle_count(number)))
+			.for_each(|uncle| {
+				open_block.push_uncle(uncle).expect("pushing maximum_uncle_count;
 												open_block was just created;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2420,6 +3858,9 @@ impl ImportSealedBlock for Client {
 
 			// scope for self.import_lock
 			let _import_lock = self.importer.import_lock.lock();
+			if self.is_import_paused() {
+				return Err(EthcoreError::Msg("Block import is paused".into()));
+			}
 			trace_time!("import_sealed_block");
 
 			let block_bytes = block.rlp_bytes();
@@ -2556,6 +3997,48 @@ impl ProvingBlockChainClient for Client {
 		)
 	}
 
+	fn prove_receipt(&self, hash: H256) -> Option<ReceiptProof> {
+		let address = self.transaction_address(TransactionId::Hash(hash))?;
+		let id = BlockId::Hash(address.block_hash);
+		let header = self.block_header(id)?;
+		let body = self.block_body(id)?;
+		let receipts = self.block_receipts(&address.block_hash)?.receipts;
+
+		let receipts: Vec<Bytes> = receipts.iter().map(rlp::encode).collect();
+		let transactions: Vec<Bytes> = body.transactions_rlp().iter().map(|t| t.as_raw().to_vec()).collect();
+		let (receipts_root, receipt_proof) = receipt_proof::prove_ordered(&receipts, address.index)?;
+		let (transactions_root, transaction_proof) = receipt_proof::prove_ordered(&transactions, address.index)?;
+
+		// the tries are rebuilt from the stored data, so a mismatch means a corrupted database.
+		if receipts_root != header.receipts_root() || transactions_root != header.transactions_root() {
+			warn!(target: "client", "Stored receipts or transactions of block {} do not match its header", address.block_hash);
+			return None;
+		}
+
+		Some(ReceiptProof {
+			block_hash: address.block_hash,
+			block_number: header.number(),
+			transaction_index: address.index,
+			receipts_root,
+			receipt: receipts[address.index].clone(),
+			receipt_proof,
+			transactions_root,
+			transaction: transactions[address.index].clone(),
+			transaction_proof,
+		})
+	}
+
+	fn prove_ancestry(&self, number: BlockNumber, head: BlockNumber) -> Option<AncestryProof> {
+		let chain = self.chain.read();
+		let best = chain.best_block_number();
+		if number > head || head > best {
+			return None;
+		}
+
+		// heads past the accumulator are proven once it caught up with them.
+		self.header_accumulator.lock().prove(number, head, |n| chain.block_hash(n))
+	}
+
 	fn epoch_signal(&self, hash: H256) -> Option<Vec<u8>> {
 		// pending transitions are never deleted, and do not contain
 		// finality proofs by definition.
@@ -2768,6 +4251,22 @@ impl ImportExportBlocks for Client {
 	}
 }
 
+/// Hashes of the 256 most recent ancestors of a block, starting with its parent given here.
+fn ancestor_hashes(chain: &BlockChain, parent_hash: H256) -> LastHashes {
+	let mut last_hashes = LastHashes::new();
+	last_hashes.resize(256, H256::zero());
+	last_hashes[0] = parent_hash;
+	for i in 0..255 {
+		match chain.block_details(&last_hashes[i]) {
+			Some(details) => {
+				last_hashes[i + 1] = details.parent;
+			},
+			None => break,
+		}
+	}
+	last_hashes
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
 fn transaction_receipt(
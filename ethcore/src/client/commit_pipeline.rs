@@ -0,0 +1,390 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background flushing of imported blocks to disk.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Instant;
+
+use blockchain::BlockChainDB;
+use kvdb::{DBOp, DBTransaction, DBValue, IoStats, IoStatsKind, KeyValueDB};
+use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
+use parking_lot::{Condvar, Mutex, RwLock};
+use stats::DurationHistogram;
+
+/// Changes of a column which are not in the database yet. `None` marks a deletion.
+type ColumnChanges = HashMap<Vec<u8>, Option<DBValue>>;
+
+#[derive(Default)]
+struct Changes {
+	/// Changes written since the last flush started.
+	buffered: HashMap<u32, ColumnChanges>,
+	/// Changes of the flush in progress, or of the last failed one.
+	writing: HashMap<u32, ColumnChanges>,
+}
+
+impl Changes {
+	fn get(&self, col: u32, key: &[u8]) -> Option<Option<DBValue>> {
+		self.buffered.get(&col).and_then(|changes| changes.get(key))
+			.or_else(|| self.writing.get(&col).and_then(|changes| changes.get(key)))
+			.cloned()
+	}
+}
+
+/// Key-value database keeping the changes of imported blocks in memory until the pipeline writes
+/// them in one batch. Unlike the buffer of the database itself, the changes of a failed write are
+/// kept apart from the ones written afterwards, so they can be retried or discarded together.
+pub struct PipelinedKeyValueDB {
+	inner: Arc<dyn KeyValueDB>,
+	changes: RwLock<Changes>,
+	/// Held while changes are written to `inner`.
+	write_lock: Mutex<()>,
+}
+
+impl PipelinedKeyValueDB {
+	fn new(inner: Arc<dyn KeyValueDB>) -> Self {
+		PipelinedKeyValueDB {
+			inner,
+			changes: RwLock::new(Changes::default()),
+			write_lock: Mutex::new(()),
+		}
+	}
+
+	/// Drop all changes which are not in the database yet.
+	fn discard(&self) {
+		let _write_lock = self.write_lock.lock();
+		*self.changes.write() = Changes::default();
+	}
+}
+
+impl KeyValueDB for PipelinedKeyValueDB {
+	fn get(&self, col: u32, key: &[u8]) -> io::Result<Option<DBValue>> {
+		match self.changes.read().get(col, key) {
+			Some(value) => Ok(value),
+			None => self.inner.get(col, key),
+		}
+	}
+
+	fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.inner.get_by_prefix(col, prefix)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		let mut changes = self.changes.write();
+		for op in transaction.ops {
+			match op {
+				DBOp::Insert { col, key, value } => {
+					changes.buffered.entry(col).or_default().insert(key.to_vec(), Some(value));
+				},
+				DBOp::Delete { col, key } => {
+					changes.buffered.entry(col).or_default().insert(key.to_vec(), None);
+				},
+			}
+		}
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		let _write_lock = self.write_lock.lock();
+		let transaction = {
+			let mut changes = self.changes.write();
+			let Changes { ref mut buffered, ref mut writing } = *changes;
+			for (col, column) in buffered.drain() {
+				writing.entry(col).or_default().extend(column);
+			}
+
+			let mut transaction = DBTransaction::new();
+			for (col, column) in writing.iter() {
+				for (key, value) in column {
+					match *value {
+						Some(ref value) => transaction.put(*col, key, value),
+						None => transaction.delete(*col, key),
+					}
+				}
+			}
+			transaction
+		};
+		if transaction.ops.is_empty() {
+			return Ok(());
+		}
+
+		self.inner.write(transaction)?;
+		self.changes.write().writing.clear();
+		Ok(())
+	}
+
+	fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.inner.iter(col)
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: u32, prefix: &'a [u8]) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.inner.iter_from_prefix(col, prefix)
+	}
+
+	fn restore(&self, new_db: &str) -> io::Result<()> {
+		self.discard();
+		self.inner.restore(new_db)
+	}
+
+	fn io_stats(&self, kind: IoStatsKind) -> IoStats {
+		self.inner.io_stats(kind)
+	}
+}
+
+impl MallocSizeOf for PipelinedKeyValueDB {
+	fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+		let changes = self.changes.read();
+		self.inner.size_of(ops) + changes.buffered.size_of(ops) + changes.writing.size_of(ops)
+	}
+}
+
+struct PipelinedBlockChainDB {
+	inner: Arc<dyn BlockChainDB>,
+	key_value: Arc<dyn KeyValueDB>,
+}
+
+impl BlockChainDB for PipelinedBlockChainDB {
+	fn key_value(&self) -> &Arc<dyn KeyValueDB> {
+		&self.key_value
+	}
+
+	fn blooms(&self) -> &blooms_db::Database {
+		self.inner.blooms()
+	}
+
+	fn trace_blooms(&self) -> &blooms_db::Database {
+		self.inner.trace_blooms()
+	}
+}
+
+#[derive(Default)]
+struct Status {
+	/// Whether a flush is queued or running.
+	pending: bool,
+	/// Error of the failed flush.
+	error: Option<String>,
+}
+
+/// Flushes the changes buffered by each import round on a separate thread, so the
+/// execution of the next round overlaps with the disk writes and fsyncs of the previous one.
+///
+/// At most one flush is in flight; the next round waits for it. A failed flush stops the
+/// pipeline: its changes stay in memory until the caller retries them with `sync` or drops
+/// them, together with everything imported after them, with `discard`.
+pub struct CommitPipeline {
+	db: Arc<PipelinedKeyValueDB>,
+	requests: Mutex<Sender<()>>,
+	status: Arc<(Mutex<Status>, Condvar)>,
+}
+
+impl CommitPipeline {
+	/// Create a new pipeline over `db` and start its thread. Returns the database the client
+	/// must use, whose key-value store keeps the changes in memory until they are flushed.
+	/// The thread stops when the pipeline is dropped.
+	pub fn new(db: Arc<dyn BlockChainDB>, flush_time: Arc<DurationHistogram>) -> (Self, Arc<dyn BlockChainDB>) {
+		let key_value = Arc::new(PipelinedKeyValueDB::new(db.key_value().clone()));
+		let (requests, receiver) = mpsc::channel::<()>();
+		let status = Arc::new((Mutex::new(Status::default()), Condvar::new()));
+
+		let thread_db = key_value.clone();
+		let thread_status = status.clone();
+		thread::Builder::new()
+			.name("Commit pipeline".into())
+			.spawn(move || {
+				for () in receiver {
+					let start = Instant::now();
+					let result = thread_db.flush();
+					flush_time.observe(start.elapsed());
+
+					let (ref status, ref flushed) = *thread_status;
+					let mut status = status.lock();
+					status.pending = false;
+					if let Err(e) = result {
+						warn!(target: "client", "Failed to flush imported blocks: {}", e);
+						status.error = Some(e.to_string());
+					}
+					flushed.notify_all();
+				}
+			})
+			.expect("Failed to create commit pipeline thread.");
+
+		let pipelined_db = Arc::new(PipelinedBlockChainDB {
+			inner: db,
+			key_value: key_value.clone(),
+		});
+		let pipeline = CommitPipeline {
+			db: key_value,
+			requests: Mutex::new(requests),
+			status,
+		};
+		(pipeline, pipelined_db)
+	}
+
+	/// Queue a flush of the changes buffered so far, waiting for the one in flight.
+	/// Returns the error of an earlier failed flush instead, in which case nothing is queued.
+	pub fn flush(&self) -> Result<(), String> {
+		let (ref status, ref flushed) = *self.status;
+		let mut status = status.lock();
+		while status.error.is_none() && status.pending {
+			flushed.wait(&mut status);
+		}
+		if let Some(ref error) = status.error {
+			return Err(error.clone());
+		}
+
+		status.pending = true;
+		if self.requests.lock().send(()).is_err() {
+			// the thread is gone, flush in place.
+			status.pending = false;
+			return self.db.flush().map_err(|e| e.to_string());
+		}
+		Ok(())
+	}
+
+	/// Whether a flush failed and the pipeline waits for `sync` or `discard`.
+	/// Doesn't wait for the flush in flight.
+	pub fn failed(&self) -> bool {
+		self.status.0.lock().error.is_some()
+	}
+
+	/// Wait until the queued flush is done and return its error, if any.
+	pub fn wait(&self) -> Result<(), String> {
+		let (ref status, ref flushed) = *self.status;
+		let mut status = status.lock();
+		while status.pending {
+			flushed.wait(&mut status);
+		}
+		status.error.clone().map_or(Ok(()), Err)
+	}
+
+	/// Wait for the queued flush, then write all changes not in the database yet in place,
+	/// including the ones of a failed flush. Resumes the pipeline if this succeeds.
+	pub fn sync(&self) -> Result<(), String> {
+		let _ = self.wait();
+		self.db.flush().map_err(|e| e.to_string())?;
+		self.status.0.lock().error = None;
+		Ok(())
+	}
+
+	/// Wait for the queued flush, then drop all changes not in the database yet and resume
+	/// the pipeline. The caller must reload everything read from these changes.
+	pub fn discard(&self) {
+		let _ = self.wait();
+		self.db.discard();
+		self.status.0.lock().error = None;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{AtomicBool, Ordering};
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+	use tempfile::TempDir;
+	use test_helpers::new_db_with_key_value;
+
+	/// Database whose writes fail while `fail` is set.
+	struct FailingDB {
+		inner: Database,
+		fail: AtomicBool,
+	}
+
+	impl KeyValueDB for FailingDB {
+		fn get(&self, col: u32, key: &[u8]) -> io::Result<Option<DBValue>> { self.inner.get(col, key) }
+		fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> { self.inner.get_by_prefix(col, prefix) }
+		fn write_buffered(&self, transaction: DBTransaction) { self.inner.write_buffered(transaction) }
+		fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+			match self.fail.load(Ordering::SeqCst) {
+				true => Err(io::Error::new(io::ErrorKind::Other, "write failed")),
+				false => self.inner.write(transaction),
+			}
+		}
+		fn flush(&self) -> io::Result<()> { self.inner.flush() }
+		fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> { self.inner.iter(col) }
+		fn iter_from_prefix<'a>(&'a self, col: u32, prefix: &'a [u8]) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+			self.inner.iter_from_prefix(col, prefix)
+		}
+		fn restore(&self, new_db: &str) -> io::Result<()> { self.inner.restore(new_db) }
+	}
+
+	impl MallocSizeOf for FailingDB {
+		fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize { self.inner.size_of(ops) }
+	}
+
+	fn put(db: &dyn KeyValueDB, key: u8) {
+		let mut batch = DBTransaction::new();
+		batch.put(0, &[key], &[key]);
+		db.write_buffered(batch);
+	}
+
+	#[test]
+	fn flushes_in_background() {
+		let tempdir = TempDir::new().unwrap();
+		let path = tempdir.path().to_str().unwrap();
+		let histogram = Arc::new(DurationHistogram::default());
+		{
+			let rocksdb = Database::open(&DatabaseConfig::with_columns(1), path).unwrap();
+			let (pipeline, db) = CommitPipeline::new(new_db_with_key_value(Arc::new(rocksdb)), histogram.clone());
+			for i in 0..3u8 {
+				put(&**db.key_value(), i);
+				pipeline.flush().unwrap();
+			}
+			pipeline.wait().unwrap();
+		}
+
+		let rocksdb = Database::open(&DatabaseConfig::with_columns(1), path).unwrap();
+		assert_eq!(rocksdb.get(0, &[2]).unwrap(), Some(vec![2]));
+		assert_eq!(histogram.snapshot().count, 3);
+	}
+
+	#[test]
+	fn discards_changes_of_failed_flush() {
+		let tempdir = TempDir::new().unwrap();
+		let rocksdb = Database::open(&DatabaseConfig::with_columns(1), tempdir.path().to_str().unwrap()).unwrap();
+		let failing = Arc::new(FailingDB { inner: rocksdb, fail: AtomicBool::new(false) });
+		let (pipeline, db) = CommitPipeline::new(new_db_with_key_value(failing.clone()), Default::default());
+
+		put(&**db.key_value(), 1);
+		pipeline.flush().unwrap();
+		pipeline.wait().unwrap();
+
+		failing.fail.store(true, Ordering::SeqCst);
+		put(&**db.key_value(), 2);
+		pipeline.flush().unwrap();
+		assert!(pipeline.wait().is_err());
+		assert!(pipeline.failed());
+		put(&**db.key_value(), 3);
+		assert!(pipeline.flush().is_err());
+		assert!(pipeline.sync().is_err());
+		// the changes stay readable until they are discarded.
+		assert_eq!(db.key_value().get(0, &[2]).unwrap(), Some(vec![2]));
+
+		pipeline.discard();
+		assert!(!pipeline.failed());
+		assert_eq!(db.key_value().get(0, &[1]).unwrap(), Some(vec![1]));
+		assert_eq!(db.key_value().get(0, &[2]).unwrap(), None);
+		assert_eq!(db.key_value().get(0, &[3]).unwrap(), None);
+
+		failing.fail.store(false, Ordering::SeqCst);
+		put(&**db.key_value(), 4);
+		pipeline.flush().unwrap();
+		pipeline.wait().unwrap();
+		assert_eq!(failing.inner.get(0, &[4]).unwrap(), Some(vec![4]));
+	}
+}
@@ -14,24 +14,37 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
+use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 
+use account_state::fork::ForkSource;
 use blockchain::Config as BlockChainConfig;
+use ethereum_types::Address;
 use journaldb;
 use snapshot::SnapshotConfiguration;
+use state_db::CacheRatios;
 use trace::Config as TraceConfig;
 use types::client_types::Mode;
+use types::header::Header;
+use types::BlockNumber;
 use verification::{VerifierType, QueueConfig};
 
-/// Client state db compaction profile
+/// Client state db compaction and tuning profile
 #[derive(Debug, PartialEq, Clone)]
 pub enum DatabaseCompactionProfile {
 	/// Try to determine compaction profile automatically
 	Auto,
 	/// SSD compaction profile
 	SSD,
+	/// NVMe profile: small blocks and more open files for fast random reads
+	NVMe,
 	/// HDD or other slow storage io compaction profile
 	HDD,
+	/// Small memtables, block cache and number of open files, for machines short of memory
+	LowMemory,
 }
 
 impl Default for DatabaseCompactionProfile {
@@ -47,12 +60,113 @@ impl FromStr for DatabaseCompactionProfile {
 		match s {
 			"auto" => Ok(DatabaseCompactionProfile::Auto),
 			"ssd" => Ok(DatabaseCompactionProfile::SSD),
+			"nvme" => Ok(DatabaseCompactionProfile::NVMe),
 			"hdd" => Ok(DatabaseCompactionProfile::HDD),
-			_ => Err("Invalid compaction profile given. Expected default/hdd/ssd.".into()),
+			"low-memory" => Ok(DatabaseCompactionProfile::LowMemory),
+			_ => Err("Invalid compaction profile given. Expected auto/ssd/nvme/hdd/low-memory.".into()),
 		}
 	}
 }
 
+/// Daily time window, in UTC, to which the background rewrites of the database are restricted.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RewriteWindow {
+	/// Start of the window, in minutes since midnight.
+	pub start: u32,
+	/// End of the window, in minutes since midnight. Before `start` for windows spanning midnight.
+	pub end: u32,
+}
+
+impl RewriteWindow {
+	/// Whether the window contains the given minute of the day.
+	pub fn contains(&self, minute: u32) -> bool {
+		if self.start <= self.end {
+			self.start <= minute && minute < self.end
+		} else {
+			minute >= self.start || minute < self.end
+		}
+	}
+}
+
+impl FromStr for RewriteWindow {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let minutes = |time: &str| -> Option<u32> {
+			let mut parts = time.trim().splitn(2, ':');
+			let hours: u32 = parts.next()?.parse().ok()?;
+			let minutes: u32 = parts.next()?.parse().ok()?;
+			if hours < 24 && minutes < 60 { Some(hours * 60 + minutes) } else { None }
+		};
+		let invalid = || format!("Invalid rewrite window {}. Expected HH:MM-HH:MM.", s);
+
+		let mut times = s.splitn(2, '-');
+		let start = times.next().and_then(minutes).ok_or_else(invalid)?;
+		let end = times.next().and_then(minutes).ok_or_else(invalid)?;
+		if start == end {
+			return Err(format!("Invalid rewrite window {}. The window is empty.", s));
+		}
+		Ok(RewriteWindow { start, end })
+	}
+}
+
+/// Policy selecting the uncles included in authored blocks.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnclePolicy {
+	/// Maximal number of uncles to include, never more than the engine allows. `None` for the engine maximum.
+	pub max_uncles: Option<usize>,
+	/// Minimal number of generations between an included uncle and the authored block.
+	pub min_age: u64,
+	/// Include uncles authored by the block author before any other.
+	pub prefer_own: bool,
+}
+
+impl Default for UnclePolicy {
+	fn default() -> Self {
+		UnclePolicy {
+			max_uncles: None,
+			min_age: 1,
+			prefer_own: false,
+		}
+	}
+}
+
+impl UnclePolicy {
+	/// Orders `candidates` for block `number` authored by `author` and drops the ones the policy
+	/// never includes.
+	pub fn order(&self, candidates: Vec<Header>, number: BlockNumber, author: &Address) -> Vec<Header> {
+		let mut candidates: Vec<_> = candidates.into_iter()
+			.filter(|uncle| number.saturating_sub(uncle.number()) >= self.min_age)
+			.collect();
+		if self.prefer_own {
+			// stable, keeps the chain order otherwise
+			candidates.sort_by_key(|uncle| uncle.author() != author);
+		}
+		candidates
+	}
+
+	/// Number of uncles to include given the engine maximum.
+	pub fn limit(&self, engine_max: usize) -> usize {
+		self.max_uncles.map_or(engine_max, |max| cmp::min(max, engine_max))
+	}
+}
+
+/// Remote state read by a chain forked off another network.
+#[derive(Clone)]
+pub struct StateFork(pub Arc<dyn ForkSource>);
+
+impl fmt::Debug for StateFork {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("StateFork")
+	}
+}
+
+impl PartialEq for StateFork {
+	fn eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.0, &other.0)
+	}
+}
+
 /// Client configuration. Includes configs for all sub-systems.
 #[derive(Debug, PartialEq, Clone)]
 pub struct ClientConfig {
@@ -72,6 +186,11 @@ pub struct ClientConfig {
 	pub db_cache_size: Option<usize>,
 	/// State db compaction profile
 	pub db_compaction: DatabaseCompactionProfile,
+	/// Collect RocksDB statistics, at some cost in performance.
+	pub db_statistics: bool,
+	/// Daily window outside of which the database isn't rewritten in the background, unless
+	/// requested with `rewrite_database`. `None` for no restriction.
+	pub db_rewrite_window: Option<RewriteWindow>,
 	/// Operating mode
 	pub mode: Mode,
 	/// The chain spec name
@@ -80,22 +199,52 @@ pub struct ClientConfig {
 	pub verifier_type: VerifierType,
 	/// State db cache-size. Default: 25Mb.
 	pub state_cache_size: usize,
+	/// Split of the state db cache between accounts, storage and code.
+	pub state_cache_ratios: CacheRatios,
+	/// Load the state touched by verified blocks into the state cache ahead of their import.
+	pub state_prefetch: bool,
 	/// EVM jump-tables cache size.
 	pub jump_table_size: usize,
 	/// Minimum state pruning history size.
 	pub history: u64,
 	/// Ideal memory usage for state pruning history.
 	pub history_mem: usize,
+	/// Keep a copy of the state every this many blocks, to rebuild pruned states from.
+	/// `0` keeps no copies.
+	pub state_checkpoint_interval: u64,
+	/// Number of most recent state copies kept. Older copies are deleted. `0` keeps all copies.
+	pub state_checkpoints_kept: usize,
 	/// Check seal validity on block import
 	pub check_seal: bool,
 	/// Maximal number of transactions queued for verification in a separate thread.
 	pub transaction_verification_queue_size: usize,
 	/// Maximal number of blocks to import at each round.
 	pub max_round_blocks_to_import: usize,
+	/// Write each import round to the database while the next round is executed. The rounds
+	/// imported after a failed write are dropped and imported again.
+	pub commit_pipeline: bool,
+	/// Minimal number of blocks whose changes are written to the database at once while
+	/// more verified blocks are waiting. `0` writes after each import round. Buffered
+	/// changes are written after ten seconds at the latest.
+	pub commit_batch_blocks: usize,
 	/// Snapshot configuration
 	pub snapshot: SnapshotConfiguration,
 	/// Stop importing at this block and enter sleep mode.
 	pub sync_until: Option<u64>,
+	/// Uncles included in authored blocks.
+	pub uncles: UnclePolicy,
+	/// Write a diagnostic report to this directory for every block whose state root, receipts
+	/// root, gas used or log bloom disagree with the result of executing it.
+	pub root_mismatch_dumps: Option<PathBuf>,
+	/// Maximal number of canon blocks a reorganization may retract. Deeper ones keep the current
+	/// head until accepted with `accept_reorg`.
+	pub max_reorg_depth: Option<u64>,
+	/// Maximal number of `call` results on the best block kept for the following blocks which
+	/// don't modify the accounts they read. `0` disables the cache.
+	pub call_cache_size: usize,
+	/// Remote state read for the accounts and storage missing from the local state. Must be set
+	/// from the first start of a chain forked off the remote one.
+	pub state_fork: Option<StateFork>,
 }
 
 impl Default for ClientConfig {
@@ -113,24 +262,39 @@ impl Default for ClientConfig {
 			name: "default".into(),
 			db_cache_size: None,
 			db_compaction: Default::default(),
+			db_statistics: false,
+			db_rewrite_window: None,
 			mode: Mode::Active,
 			spec_name: "".into(),
 			verifier_type: VerifierType::Canon,
 			state_cache_size: 1 * mb,
+			state_cache_ratios: Default::default(),
+			state_prefetch: true,
 			jump_table_size: 1 * mb,
 			history: 128,
 			history_mem: 64 * mb,
+			state_checkpoint_interval: 0,
+			state_checkpoints_kept: 16,
 			check_seal: true,
 			transaction_verification_queue_size: 8192,
 			max_round_blocks_to_import: 12,
+			commit_pipeline: false,
+			commit_batch_blocks: 0,
 			snapshot: Default::default(),
 			sync_until: None,
+			uncles: Default::default(),
+			root_mismatch_dumps: None,
+			max_reorg_depth: None,
+			call_cache_size: 0,
+			state_fork: None,
 		}
 	}
 }
 #[cfg(test)]
 mod test {
-	use super::DatabaseCompactionProfile;
+	use super::{DatabaseCompactionProfile, RewriteWindow, UnclePolicy};
+	use ethereum_types::Address;
+	use types::header::Header;
 
 	#[test]
 	fn test_default_compaction_profile() {
@@ -142,5 +306,51 @@ mod test {
 		assert_eq!(DatabaseCompactionProfile::Auto, "auto".parse().unwrap());
 		assert_eq!(DatabaseCompactionProfile::SSD, "ssd".parse().unwrap());
 		assert_eq!(DatabaseCompactionProfile::HDD, "hdd".parse().unwrap());
+		assert_eq!(DatabaseCompactionProfile::NVMe, "nvme".parse().unwrap());
+		assert_eq!(DatabaseCompactionProfile::LowMemory, "low-memory".parse().unwrap());
+		assert!("fast".parse::<DatabaseCompactionProfile>().is_err());
+	}
+
+	#[test]
+	fn test_parsing_rewrite_window() {
+		let window: RewriteWindow = "02:00-05:30".parse().unwrap();
+		assert_eq!(window, RewriteWindow { start: 120, end: 330 });
+		assert!(!window.contains(119));
+		assert!(window.contains(120));
+		assert!(!window.contains(330));
+
+		let window: RewriteWindow = "23:00-01:00".parse().unwrap();
+		assert!(window.contains(23 * 60 + 30));
+		assert!(window.contains(30));
+		assert!(!window.contains(60));
+		assert!(!window.contains(12 * 60));
+
+		assert!("02:00".parse::<RewriteWindow>().is_err());
+		assert!("24:00-01:00".parse::<RewriteWindow>().is_err());
+		assert!("02:00-02:00".parse::<RewriteWindow>().is_err());
+	}
+
+	fn uncle(number: u64, author: Address) -> Header {
+		let mut header = Header::new();
+		header.set_number(number);
+		header.set_author(author);
+		header
+	}
+
+	#[test]
+	fn test_uncle_policy() {
+		let own = Address::from_low_u64_be(1);
+		let other = Address::from_low_u64_be(2);
+		let candidates = vec![uncle(9, other), uncle(8, own), uncle(7, other), uncle(6, own)];
+
+		let default = UnclePolicy::default();
+		assert_eq!(default.order(candidates.clone(), 10, &own), candidates);
+		assert_eq!(default.limit(2), 2);
+
+		let policy = UnclePolicy { max_uncles: Some(1), min_age: 2, prefer_own: true };
+		let ordered: Vec<_> = policy.order(candidates, 10, &own).iter().map(|u| u.number()).collect();
+		assert_eq!(ordered, vec![8, 6, 7]);
+		assert_eq!(policy.limit(2), 1);
+		assert_eq!(UnclePolicy { max_uncles: Some(5), ..policy }.limit(2), 2);
 	}
 }
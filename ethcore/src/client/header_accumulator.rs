@@ -0,0 +1,292 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Header accumulator of the canonical chain.
+//!
+//! Only the subtrees of at least `STORED_HEIGHT` are kept, which is a few megabytes for
+//! mainnet; the lower levels are recomputed from the canonical hashes when proving. The
+//! roots of the lowest stored subtrees are persisted in the extras column, one key each,
+//! so that the accumulator only ever catches up with the blocks imported since.
+
+use ethereum_types::H256;
+use kvdb::{DBTransaction, KeyValueDB};
+use rlp::{Rlp, RlpStream};
+use types::{
+	ancestry_proof::{bag_peaks, merge, peak_heights, AncestryProof},
+	BlockNumber,
+};
+
+/// Height of the lowest stored subtrees.
+const STORED_HEIGHT: u32 = 8;
+
+/// Number of leaves of a stored subtree.
+const CHUNK: u64 = 1 << STORED_HEIGHT;
+
+/// Prefix of the keys of the persisted chunks, followed by their index (big-endian).
+const CHUNK_KEY_PREFIX: &[u8] = b"header_accumulator";
+
+fn chunk_key(index: usize) -> Vec<u8> {
+	let mut key = CHUNK_KEY_PREFIX.to_vec();
+	key.extend_from_slice(&(index as u64).to_be_bytes());
+	key
+}
+
+/// Accumulator over the canonical block hashes, synchronised with the chain as it grows.
+#[derive(Default)]
+pub struct HeaderAccumulator {
+	/// Roots of the complete subtrees of height `STORED_HEIGHT + i`, in order.
+	levels: Vec<Vec<H256>>,
+	/// Hash of the last block of every stored chunk, to detect reorganisations.
+	chunk_ends: Vec<H256>,
+}
+
+impl HeaderAccumulator {
+	/// Load the chunks persisted by `write`. They may be ahead of the chain after a crash,
+	/// `sync` drops the ones which aren't canonical.
+	pub fn load(db: &dyn KeyValueDB) -> Self {
+		let mut accumulator = HeaderAccumulator::default();
+		loop {
+			let raw = match db.get(::db::COL_EXTRA, &chunk_key(accumulator.chunks())) {
+				Ok(Some(raw)) => raw,
+				Ok(None) => break,
+				Err(e) => {
+					warn!(target: "client", "Failed to load the header accumulator: {}", e);
+					break;
+				},
+			};
+			let rlp = Rlp::new(&raw);
+			match (rlp.val_at(0), rlp.val_at(1)) {
+				(Ok(root), Ok(end)) => {
+					accumulator.chunk_ends.push(end);
+					accumulator.push(root);
+				},
+				_ => break,
+			}
+		}
+		accumulator
+	}
+
+	/// Persist the chunks from `from` on, deleting the ones up to `previous` which were dropped.
+	/// `from` is the number of chunks `sync` kept and `previous` their number before it.
+	pub fn write(&self, batch: &mut DBTransaction, from: usize, previous: usize) {
+		for index in self.chunks()..previous {
+			batch.delete(::db::COL_EXTRA, &chunk_key(index));
+		}
+		for index in from..self.chunks() {
+			let mut stream = RlpStream::new_list(2);
+			stream.append(&self.levels[0][index]).append(&self.chunk_ends[index]);
+			batch.put(::db::COL_EXTRA, &chunk_key(index), &stream.out());
+		}
+	}
+
+	/// Number of stored chunks of `CHUNK` blocks.
+	pub fn chunks(&self) -> usize {
+		self.chunk_ends.len()
+	}
+
+	/// Number of blocks covered by the stored subtrees.
+	fn leaves(&self) -> u64 {
+		(self.chunk_ends.len() as u64) << STORED_HEIGHT
+	}
+
+	/// Catch up with the canonical chain ending at `best` by at most `max_chunks` chunks,
+	/// dropping the chunks which were reorganised away first. `hash_of` gives the canonical
+	/// hash of a block number. Returns the number of chunks kept from before.
+	pub fn sync<F>(&mut self, best: BlockNumber, max_chunks: usize, hash_of: F) -> usize
+		where F: Fn(BlockNumber) -> Option<H256>
+	{
+		while !self.chunk_ends.is_empty() && (self.leaves() > best + 1 || !self.is_canonical(&hash_of)) {
+			self.truncate(self.chunk_ends.len() - 1);
+		}
+		let kept = self.chunks();
+
+		while self.chunks() - kept < max_chunks && self.leaves() + CHUNK <= best + 1 {
+			let start = self.leaves();
+			let (root, end) = match (leaves_root(STORED_HEIGHT, start, &hash_of), hash_of(start + CHUNK - 1)) {
+				(Some(root), Some(end)) => (root, end),
+				_ => break,
+			};
+			self.chunk_ends.push(end);
+			self.push(root);
+		}
+		kept
+	}
+
+	/// Whether the last stored chunk is canonical, and so all the chunks before it.
+	fn is_canonical<F>(&self, hash_of: F) -> bool
+		where F: Fn(BlockNumber) -> Option<H256>
+	{
+		match self.chunk_ends.last() {
+			Some(end) => hash_of(self.leaves() - 1) == Some(*end),
+			None => true,
+		}
+	}
+
+	/// Prove that `number` is an ancestor of `head`. Both must be canonical and
+	/// the accumulator synchronised up to at least `head`.
+	pub fn prove<F>(&self, number: BlockNumber, head: BlockNumber, hash_of: F) -> Option<AncestryProof>
+		where F: Fn(BlockNumber) -> Option<H256>
+	{
+		if number > head || head >= self.leaves() + CHUNK || !self.is_canonical(&hash_of) {
+			return None;
+		}
+
+		let size = head + 1;
+		let (mut peaks, mut siblings, mut offset) = (Vec::new(), Vec::new(), 0);
+		for height in peak_heights(size) {
+			peaks.push(self.subtree(height, offset >> height, &hash_of)?);
+			if number >= offset && number < offset + (1 << height) {
+				siblings = (0..height)
+					.map(|h| self.subtree(h, (number >> h) ^ 1, &hash_of))
+					.collect::<Option<_>>()?;
+			}
+			offset += 1 << height;
+		}
+
+		let head_hash = hash_of(head)?;
+		Some(AncestryProof {
+			block_number: number,
+			block_hash: hash_of(number)?,
+			head_number: head,
+			head_hash,
+			siblings,
+			root: bag_peaks(size, &head_hash, &peaks),
+			peaks,
+		})
+	}
+
+	fn subtree<F>(&self, height: u32, index: u64, hash_of: F) -> Option<H256>
+		where F: Fn(BlockNumber) -> Option<H256>
+	{
+		if height < STORED_HEIGHT {
+			return leaves_root(height, index << height, hash_of);
+		}
+		self.levels.get((height - STORED_HEIGHT) as usize)?.get(index as usize).cloned()
+	}
+
+	fn push(&mut self, root: H256) {
+		let mut node = root;
+		let mut height = 0;
+		loop {
+			if self.levels.len() == height {
+				self.levels.push(Vec::new());
+			}
+			let level = &mut self.levels[height];
+			level.push(node);
+			if level.len() % 2 == 1 {
+				break;
+			}
+			node = merge(&level[level.len() - 2], &level[level.len() - 1]);
+			height += 1;
+		}
+	}
+
+	fn truncate(&mut self, chunks: usize) {
+		self.chunk_ends.truncate(chunks);
+		for (height, level) in self.levels.iter_mut().enumerate() {
+			level.truncate(chunks >> height);
+		}
+	}
+}
+
+/// Root of the subtree of `height` over the blocks starting at `start`.
+fn leaves_root<F>(height: u32, start: BlockNumber, hash_of: F) -> Option<H256>
+	where F: Fn(BlockNumber) -> Option<H256>
+{
+	let mut nodes = (start..start + (1 << height)).map(&hash_of).collect::<Option<Vec<_>>>()?;
+	while nodes.len() > 1 {
+		nodes = nodes.chunks(2).map(|pair| merge(&pair[0], &pair[1])).collect();
+	}
+	Some(nodes[0])
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hash::keccak;
+	use kvdb::{DBTransaction, KeyValueDB};
+	use super::{HeaderAccumulator, CHUNK};
+
+	fn canonical(n: u64) -> Option<H256> {
+		Some(keccak(n.to_be_bytes()))
+	}
+
+	#[test]
+	fn proves_ancestors_of_any_head() {
+		let best = 3 * CHUNK + 17;
+		let mut accumulator = HeaderAccumulator::default();
+		accumulator.sync(best, usize::max_value(), canonical);
+		assert_eq!(accumulator.leaves(), 3 * CHUNK);
+
+		for &(number, head) in &[(0, 0), (0, best), (5, 300), (CHUNK, 2 * CHUNK), (best, best), (700, 3 * CHUNK + 1)] {
+			let proof = accumulator.prove(number, head, canonical).unwrap();
+			assert_eq!(proof.block_hash, canonical(number).unwrap());
+			assert_eq!(proof.head_hash, canonical(head).unwrap());
+			assert!(proof.verify(), "block {} of head {}", number, head);
+		}
+
+		assert!(accumulator.prove(10, 9, canonical).is_none());
+		assert!(accumulator.prove(0, 4 * CHUNK, canonical).is_none());
+	}
+
+	#[test]
+	fn follows_reorganisations() {
+		let fork = 2 * CHUNK - 10;
+		let reorged = |n: u64| if n < fork { canonical(n) } else { Some(keccak(n.to_le_bytes())) };
+
+		let mut accumulator = HeaderAccumulator::default();
+		accumulator.sync(4 * CHUNK, usize::max_value(), canonical);
+		let before = accumulator.prove(1, 3 * CHUNK, canonical).unwrap();
+
+		accumulator.sync(4 * CHUNK, usize::max_value(), reorged);
+		let after = accumulator.prove(1, 3 * CHUNK, reorged).unwrap();
+		assert!(after.verify());
+		assert_ne!(before.root, after.root);
+
+		let mut fresh = HeaderAccumulator::default();
+		fresh.sync(4 * CHUNK, usize::max_value(), reorged);
+		assert_eq!(fresh.prove(1, 3 * CHUNK, reorged).unwrap(), after);
+
+		// a shorter chain drops the chunks past its head.
+		accumulator.sync(CHUNK + 1, usize::max_value(), reorged);
+		assert_eq!(accumulator.leaves(), CHUNK);
+	}
+
+	#[test]
+	fn catches_up_in_steps_and_persists_chunks() {
+		let db = kvdb_memorydb::create(::db::NUM_COLUMNS);
+		let mut accumulator = HeaderAccumulator::default();
+		assert_eq!(accumulator.sync(4 * CHUNK, 3, canonical), 0);
+		assert_eq!(accumulator.chunks(), 3);
+		assert_eq!(accumulator.sync(4 * CHUNK, 3, canonical), 3);
+		assert_eq!(accumulator.chunks(), 4);
+
+		let mut batch = DBTransaction::new();
+		accumulator.write(&mut batch, 0, 0);
+		db.write(batch).unwrap();
+		let loaded = HeaderAccumulator::load(&db);
+		assert_eq!(loaded.prove(1, 4 * CHUNK, canonical), accumulator.prove(1, 4 * CHUNK, canonical));
+
+		// chunks dropped by a reorganisation are deleted.
+		let reorged = |n: u64| if n < CHUNK + 5 { canonical(n) } else { Some(keccak(n.to_le_bytes())) };
+		let kept = accumulator.sync(2 * CHUNK, 0, reorged);
+		assert_eq!(kept, 1);
+		let mut batch = DBTransaction::new();
+		accumulator.write(&mut batch, kept, 4);
+		db.write(batch).unwrap();
+		assert_eq!(HeaderAccumulator::load(&db).chunks(), 1);
+	}
+}
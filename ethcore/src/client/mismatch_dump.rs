@@ -0,0 +1,186 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Diagnostic reports on blocks whose execution disagrees with their header.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use account_state::State;
+use ethereum_types::{Address, H256, U256};
+use machine::executed_block::ExecutedBlock;
+use serde_json::{self, Value};
+use state_db::StateDB;
+use types::{
+	header::Header,
+	receipt::TransactionOutcome,
+};
+
+/// Maximal number of reports kept in the reports directory. The oldest ones are deleted.
+const MAX_REPORTS: usize = 64;
+
+/// Build a report on `block`, whose execution produced a header disagreeing with `expected`.
+/// `pre_state` is the state of the parent block.
+///
+/// The report lists the roots and gas used on both sides, the outcome of every transaction,
+/// and the accounts whose balance, nonce, code or storage root the block changed.
+pub fn report(expected: &Header, block: &ExecutedBlock, pre_state: &State<StateDB>, error: &str) -> Value {
+	let got = &block.header;
+
+	let mut previous_gas_used = U256::zero();
+	let transactions: Vec<Value> = block.transactions.iter().zip(&block.receipts).enumerate().map(|(index, (tx, receipt))| {
+		let gas_used = receipt.gas_used.saturating_sub(previous_gas_used);
+		previous_gas_used = receipt.gas_used;
+		let outcome = match receipt.outcome {
+			TransactionOutcome::Unknown => Value::Null,
+			TransactionOutcome::StateRoot(root) => json!({ "stateRoot": root }),
+			TransactionOutcome::StatusCode(status) => json!({ "status": status }),
+		};
+		json!({
+			"index": index,
+			"hash": tx.hash(),
+			"sender": tx.sender(),
+			"gasUsed": gas_used,
+			"cumulativeGasUsed": receipt.gas_used,
+			"outcome": outcome,
+			"logs": receipt.logs.len(),
+		})
+	}).collect();
+
+	// the first transaction pushing the gas used past the expected total is executed differently,
+	// or follows one which is.
+	let suspect = block.receipts.iter().position(|receipt| receipt.gas_used > *expected.gas_used());
+
+	let accounts: Vec<Value> = block.state.cached_addresses().into_iter().filter_map(|address| {
+		let pre = account_summary(pre_state, &address)?;
+		let post = account_summary(&block.state, &address)?;
+		if pre == post {
+			return None;
+		}
+		Some(json!({
+			"address": address,
+			"balance": { "pre": pre.balance, "post": post.balance },
+			"nonce": { "pre": pre.nonce, "post": post.nonce },
+			"codeHash": { "pre": pre.code_hash, "post": post.code_hash },
+			"storageRoot": { "pre": pre.storage_root, "post": post.storage_root },
+		}))
+	}).collect();
+
+	json!({
+		"number": expected.number(),
+		"hash": expected.hash(),
+		"parentHash": expected.parent_hash(),
+		"error": error,
+		"stateRoot": { "expected": expected.state_root(), "found": got.state_root() },
+		"receiptsRoot": { "expected": expected.receipts_root(), "found": got.receipts_root() },
+		"gasUsed": {
+			"expected": expected.gas_used(),
+			"found": got.gas_used(),
+			"difference": signed_difference(got.gas_used(), expected.gas_used()),
+		},
+		"logBloomMatches": expected.log_bloom() == got.log_bloom(),
+		"suspectTransaction": suspect,
+		"transactions": transactions,
+		"accounts": accounts,
+	})
+}
+
+/// Write a report on the block with given header to a file named after the block in `dir`,
+/// deleting the oldest reports beyond `MAX_REPORTS`.
+pub fn write(dir: &Path, header: &Header, report: &Value) -> io::Result<PathBuf> {
+	fs::create_dir_all(dir)?;
+	let path = dir.join(format!("{}-{:x}.json", header.number(), header.hash()));
+	fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+	prune(dir, MAX_REPORTS)?;
+	Ok(path)
+}
+
+/// Delete the oldest reports of `dir` beyond the `max` most recent ones.
+fn prune(dir: &Path, max: usize) -> io::Result<()> {
+	let mut reports = Vec::new();
+	for entry in fs::read_dir(dir)? {
+		let entry = entry?;
+		if entry.path().extension().map_or(false, |extension| extension == "json") {
+			reports.push((entry.metadata()?.modified()?, entry.path()));
+		}
+	}
+	if reports.len() <= max {
+		return Ok(());
+	}
+	reports.sort();
+	for (_, path) in &reports[..reports.len() - max] {
+		fs::remove_file(path)?;
+	}
+	Ok(())
+}
+
+/// `a - b` as a decimal string, negative if `b` is greater.
+fn signed_difference(a: &U256, b: &U256) -> String {
+	if a >= b {
+		(a - b).to_string()
+	} else {
+		format!("-{}", b - a)
+	}
+}
+
+#[derive(PartialEq)]
+struct AccountSummary {
+	balance: U256,
+	nonce: U256,
+	code_hash: Option<H256>,
+	storage_root: Option<H256>,
+}
+
+fn account_summary(state: &State<StateDB>, address: &Address) -> Option<AccountSummary> {
+	Some(AccountSummary {
+		balance: state.balance(address).ok()?,
+		nonce: state.nonce(address).ok()?,
+		code_hash: state.code_hash(address).ok()?,
+		storage_root: state.storage_root(address).ok()?,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use ethereum_types::U256;
+	use tempfile::TempDir;
+	use super::{prune, signed_difference};
+
+	#[test]
+	fn computes_signed_difference() {
+		assert_eq!(signed_difference(&U256::from(5), &U256::from(3)), "2");
+		assert_eq!(signed_difference(&U256::from(3), &U256::from(5)), "-2");
+		assert_eq!(signed_difference(&U256::zero(), &U256::max_value()), format!("-{}", U256::max_value()));
+	}
+
+	#[test]
+	fn keeps_most_recent_reports() {
+		let dir = TempDir::new().unwrap();
+		for i in 0..5 {
+			fs::write(dir.path().join(format!("{}.json", i)), b"{}").unwrap();
+			// modification times are not precise enough to order files written at once.
+			::std::thread::sleep(::std::time::Duration::from_millis(20));
+		}
+		prune(dir.path(), 3).unwrap();
+		let mut left = fs::read_dir(dir.path()).unwrap()
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect::<Vec<_>>();
+		left.sort();
+		assert_eq!(left, vec!["2.json", "3.json", "4.json"]);
+	}
+}
@@ -16,14 +16,26 @@
 
 //! Blockchain database client.
 
+mod analytics;
 mod ancient_import;
+mod attack_monitor;
 mod bad_blocks;
+mod call_cache;
+mod checkpoints;
 mod client;
+mod commit_pipeline;
 mod config;
+mod header_accumulator;
+mod mismatch_dump;
+mod prefetch;
+mod receipt_proof;
 mod traits;
 
+pub use self::analytics::{AnalyticsRows, AnalyticsTable, ColumnType, analytics_rows};
 pub use self::client::Client;
-pub use self::config::{ClientConfig, DatabaseCompactionProfile};
+pub use self::config::{ClientConfig, DatabaseCompactionProfile, RewriteWindow, StateFork, UnclePolicy};
+pub use state_db::CacheRatios as StateCacheRatios;
+pub use account_state::fork::{ForkSource as StateForkSource, RemoteAccount};
 pub use self::traits::{
     ReopenBlock, PrepareOpenBlock, ImportSealedBlock, BroadcastProposalBlock,
     Call, EngineInfo, BlockProducer, SealedBlockImporter,
@@ -0,0 +1,156 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background loading of the state touched by verified blocks.
+
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+use account_state::{Account, Backend};
+use ethereum_types::{Address, H256};
+use ethtrie::Result as TrieResult;
+use hash::KECCAK_EMPTY;
+use parking_lot::Mutex;
+use state_db::StateDB;
+use trie::Trie;
+use trie_vm_factories::Factories;
+use types::block::PreverifiedBlock;
+use types::transaction::Action;
+
+/// Maximal number of requests waiting for the prefetch thread. Further requests are dropped.
+const MAX_PENDING_REQUESTS: usize = 4;
+
+/// Accounts to load from the state of a canonical block.
+struct Request {
+	/// State database to load the accounts from and whose shared cache is warmed.
+	state_db: StateDB,
+	/// Hash of the block.
+	parent_hash: H256,
+	/// State root of the block.
+	state_root: H256,
+	/// Accounts in order of first use.
+	addresses: Vec<Address>,
+}
+
+/// Accounts touched by the transactions of a sequence of blocks, in order of first use.
+#[derive(Default)]
+pub struct TouchedAccounts {
+	seen: HashSet<Address>,
+	addresses: Vec<Address>,
+}
+
+impl TouchedAccounts {
+	/// Note the senders and recipients of the block transactions.
+	pub fn note_block(&mut self, block: &PreverifiedBlock) {
+		for transaction in &block.transactions {
+			let recipient = match transaction.action {
+				Action::Call(ref address) => Some(*address),
+				Action::Create => None,
+			};
+			for address in Some(transaction.sender()).into_iter().chain(recipient) {
+				if self.seen.insert(address) {
+					self.addresses.push(address);
+				}
+			}
+		}
+	}
+}
+
+/// Loads the senders and recipients of the transactions in verified blocks, together with
+/// the code and the recently touched storage of the recipients, into the shared state cache.
+/// Runs on a separate thread, so the disk reads overlap with verification and with the
+/// execution of the blocks imported before.
+pub struct StatePrefetcher {
+	requests: Mutex<SyncSender<Request>>,
+}
+
+impl StatePrefetcher {
+	/// Create a new prefetcher and start its thread. The thread stops when the prefetcher is dropped.
+	pub fn new(factories: Factories) -> Self {
+		let (requests, receiver) = mpsc::sync_channel(MAX_PENDING_REQUESTS);
+		thread::Builder::new()
+			.name("State prefetch".into())
+			.spawn(move || Self::run(factories, receiver))
+			.expect("Failed to create state prefetch thread.");
+
+		StatePrefetcher {
+			requests: Mutex::new(requests),
+		}
+	}
+
+	/// Request loading of the given accounts from the state of the canonical block `parent_hash`.
+	/// Never blocks; the request is dropped if the prefetcher is busy.
+	pub fn prefetch(&self, state_db: StateDB, parent_hash: H256, state_root: H256, accounts: TouchedAccounts) {
+		let addresses = accounts.addresses;
+		if addresses.is_empty() {
+			return;
+		}
+
+		let count = addresses.len();
+		match self.requests.lock().try_send(Request { state_db, parent_hash, state_root, addresses }) {
+			Ok(()) => trace!(target: "client", "Prefetching {} accounts on top of {}", count, parent_hash),
+			Err(TrySendError::Full(_)) => trace!(target: "client", "State prefetcher busy, skipping {} accounts", count),
+			Err(TrySendError::Disconnected(_)) => warn!(target: "client", "State prefetch thread is not running"),
+		}
+	}
+
+	fn run(factories: Factories, requests: Receiver<Request>) {
+		for request in requests {
+			let mut loaded = 0;
+			for address in &request.addresses {
+				match Self::load(&factories, &request, address) {
+					Ok(true) => loaded += 1,
+					Ok(false) => {},
+					Err(e) => {
+						// the state may have been pruned meanwhile; the block import will report real errors.
+						debug!(target: "client", "Failed to prefetch {} on top of {}: {}", address, request.parent_hash, e);
+						break;
+					}
+				}
+			}
+			trace!(target: "client", "Prefetched {} of {} accounts on top of {}", loaded, request.addresses.len(), request.parent_hash);
+		}
+	}
+
+	fn load(factories: &Factories, request: &Request, address: &Address) -> TrieResult<bool> {
+		let state_db = &request.state_db;
+		if state_db.is_account_cached(address) {
+			return Ok(false);
+		}
+
+		let db = &state_db.as_hash_db();
+		let db = factories.trie.readonly(db, &request.state_root)?;
+		let from_rlp = |b: &[u8]| Account::from_rlp(b).expect("decoding db value failed");
+		let mut maybe_acc = db.get_with(address.as_bytes(), from_rlp)?;
+		if let Some(ref mut account) = maybe_acc {
+			let accountdb = factories.accountdb.readonly(state_db.as_hash_db(), account.address_hash(address));
+			if account.code_hash() != KECCAK_EMPTY {
+				match state_db.get_cached_code(&account.code_hash()) {
+					Some(code) => account.cache_given_code(code),
+					None => if let Some(code) = account.cache_code(accountdb.as_hash_db()) {
+						state_db.cache_code(account.code_hash(), code);
+					},
+				}
+			}
+			for key in state_db.hot_storage_keys(address) {
+				account.storage_at(accountdb.as_hash_db(), &key)?;
+			}
+		}
+
+		Ok(state_db.prefetch_account(&request.parent_hash, *address, maybe_acc))
+	}
+}
@@ -0,0 +1,85 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Inclusion proofs against the ordered tries of a block, i.e. the transactions
+//! and receipts tries, which are keyed by the RLP-encoded item index.
+
+use bytes::Bytes;
+use ethereum_types::H256;
+use ethtrie::{TrieDB, TrieDBMut};
+use hash_db::HashDB;
+use journaldb::new_memory_db;
+use trie::{Recorder, Trie, TrieMut};
+
+/// Build the ordered trie of `items` and prove the item at `index`.
+/// Returns the trie root and the trie nodes (in order from the root) proving the item,
+/// or `None` if `index` is out of bounds.
+pub fn prove_ordered(items: &[Bytes], index: usize) -> Option<(H256, Vec<Bytes>)> {
+	if index >= items.len() {
+		return None;
+	}
+
+	let mut db = new_memory_db();
+	let mut root = H256::zero();
+	{
+		let mut t = TrieDBMut::new(&mut db, &mut root);
+		for (i, item) in items.iter().enumerate() {
+			t.insert(&rlp::encode(&i), item).expect("fresh in-memory database is infallible; qed");
+		}
+	}
+
+	let mut recorder = Recorder::new();
+	let db: &dyn HashDB<_, _> = &db;
+	let t = TrieDB::new(&db, &root).expect("root was just committed to the database; qed");
+	t.get_with(&rlp::encode(&index), &mut recorder).expect("fresh in-memory database is infallible; qed");
+
+	Some((root, recorder.drain().into_iter().map(|x| x.data).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::prove_ordered;
+	use ethtrie::TrieDB;
+	use hash_db::{HashDB, EMPTY_PREFIX};
+	use journaldb::new_memory_db;
+	use trie::Trie;
+	use triehash::ordered_trie_root;
+
+	#[test]
+	fn proof_verifies_against_ordered_root() {
+		let items: Vec<_> = (0..300u32).map(|i| rlp::encode(&(i * 7919))).collect();
+		let index = 129;
+
+		let (root, proof) = prove_ordered(&items, index).unwrap();
+		assert_eq!(root, ordered_trie_root(items.iter()));
+
+		let mut db = new_memory_db();
+		for node in &proof {
+			db.insert(EMPTY_PREFIX, node);
+		}
+		let db: &dyn HashDB<_, _> = &db;
+		let t = TrieDB::new(&db, &root).unwrap();
+		let value = t.get(&rlp::encode(&index)).unwrap().unwrap();
+		assert_eq!(&value[..], &items[index][..]);
+	}
+
+	#[test]
+	fn out_of_bounds_index_has_no_proof() {
+		let items = vec![rlp::encode(&1u8)];
+		assert!(prove_ordered(&items, 1).is_none());
+		assert!(prove_ordered(&[], 0).is_none());
+	}
+}
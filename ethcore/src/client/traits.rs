@@ -28,6 +28,7 @@ use block::{OpenBlock, SealedBlock, ClosedBlock};
 use engine::Engine;
 use machine::executed::Executed;
 use account_state::state::StateInfo;
+use trace::GasProfile;
 
 /// Provides `call` and `call_many` methods
 pub trait Call {
@@ -37,10 +38,19 @@ pub trait Call {
 	/// Makes a non-persistent transaction call.
 	fn call(&self, tx: &SignedTransaction, analytics: CallAnalytics, state: &mut Self::State, header: &Header) -> Result<Executed, CallError>;
 
+	/// Makes a non-persistent transaction call without analytics, possibly answered from the
+	/// results of earlier identical calls on the same state.
+	fn call_cached(&self, tx: &SignedTransaction, state: &mut Self::State, header: &Header) -> Result<Executed, CallError> {
+		self.call(tx, Default::default(), state, header)
+	}
+
 	/// Makes multiple non-persistent but dependent transaction calls.
 	/// Returns a vector of successes or a failure if any of the transaction fails.
 	fn call_many(&self, txs: &[(SignedTransaction, CallAnalytics)], state: &mut Self::State, header: &Header) -> Result<Vec<Executed>, CallError>;
 
+	/// Makes a non-persistent transaction call, returning the gas spent by every call site.
+	fn call_gas_profile(&self, tx: &SignedTransaction, state: &mut Self::State, header: &Header) -> Result<Vec<GasProfile>, CallError>;
+
 	/// Estimates how much gas will be necessary for a call.
 	fn estimate_gas(&self, t: &SignedTransaction, state: &Self::State, header: &Header) -> Result<U256, CallError>;
 }
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fmt;
 use std::path::Path;
 use std::sync::Arc;
 use client::{Client, ClientConfig};
@@ -121,6 +122,87 @@ fn check_poststate(client: &Arc<Client>, test_name: &str, post_state: State) ->
 	success
 }
 
+/// Reason a blockchain test didn't pass.
+#[derive(Debug, PartialEq)]
+pub enum BlockchainTestError {
+	/// The test targets a fork without a chain spec, and wasn't run.
+	Unsupported(String),
+	/// The test ran and failed.
+	Failed(String),
+}
+
+impl fmt::Display for BlockchainTestError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BlockchainTestError::Unsupported(ref e) => write!(f, "skipped: {}", e),
+			BlockchainTestError::Failed(ref e) => write!(f, "{}", e),
+		}
+	}
+}
+
+/// Run a single blockchain test: import its blocks on top of its genesis and check the best
+/// block and the post state.
+pub fn run_blockchain_test(name: &str, blockchain: blockchain::BlockChain) -> Result<(), BlockchainTestError> {
+	let spec = {
+		let mut spec = EvmTestClient::fork_spec_from_json(&blockchain.network)
+			.ok_or_else(|| BlockchainTestError::Unsupported(format!("Unimplemented chainspec '{:?}'", blockchain.network)))?;
+
+		let genesis = Genesis::from(blockchain.genesis());
+		let state = From::from(blockchain.pre_state.clone());
+		spec.set_genesis_state(state)
+			.map_err(|e| BlockchainTestError::Failed(format!("Failed to overwrite genesis state: {}", e)))?;
+		spec.overwrite_genesis_params(genesis);
+		spec
+	};
+
+	let db = test_helpers::new_db();
+	let mut config = ClientConfig::default();
+	if ethjson::test_helpers::blockchain::Engine::NoProof == blockchain.engine {
+		config.verifier_type = VerifierType::CanonNoSeal;
+		config.check_seal = false;
+	}
+	config.history = 8;
+	config.queue.verifier_settings.num_verifiers = 1;
+	let client = Client::new(
+		config,
+		&spec,
+		db,
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).map_err(|e| BlockchainTestError::Failed(format!("Failed to instantiate a new Client: {}", e)))?;
+
+	for b in blockchain.blocks_rlp() {
+		let bytes_len = b.len();
+		let block = Unverified::from_rlp(b);
+		match block {
+			Ok(block) => {
+				let num = block.header.number();
+				let hash = block.hash();
+				trace!(target: "json-tests", "{} – Importing {} bytes. Block #{}/{}", name, bytes_len, num, hash);
+				let res = client.import_block(block);
+				if let Err(e) = res {
+					warn!(target: "json-tests", "{} – Error importing block #{}/{}: {:?}", name, num, hash, e);
+				}
+				client.flush_queue();
+			},
+			Err(decoder_err) => {
+				warn!(target: "json-tests", "Error decoding test block: {:?} ({} bytes)", decoder_err, bytes_len);
+			}
+		}
+	}
+
+	let best_block_hash = client.chain_info().best_block_hash;
+	let expected_best_block_hash: H256 = blockchain.best_block.into();
+	if best_block_hash != expected_best_block_hash {
+		return Err(BlockchainTestError::Failed(format!("Best block mismatch (got: {:#x}, expected: {:#x})", best_block_hash, expected_best_block_hash)));
+	}
+
+	match blockchain.post_state {
+		Some(post_state) if !check_poststate(&client, name, post_state) => Err(BlockchainTestError::Failed("Post state mismatch".into())),
+		_ => Ok(()),
+	}
+}
+
 #[allow(dead_code)]
 pub fn json_chain_test<H: FnMut(&str, HookType)>(path: &Path, json_data: &[u8], start_stop_hook: &mut H, is_legacy: bool) -> Vec<String> {
 	let _ = ::env_logger::try_init();
@@ -136,90 +218,14 @@ pub fn json_chain_test<H: FnMut(&str, HookType)>(path: &Path, json_data: &[u8],
 
 		start_stop_hook(&name, HookType::OnStart);
 
-		let mut fail = false;
-		{
-			let mut fail_unless = |cond: bool| {
-				if !cond && !fail {
-					failed.push(name.clone());
-					flushed_writeln!("FAIL");
-					fail = true;
-					true
-				} else {
-					false
-				}
-			};
-
-			flushed_write!("   - {}...", name);
-
-			let spec = {
-				let mut spec = match EvmTestClient::fork_spec_from_json(&blockchain.network) {
-					Some(spec) => spec,
-					None => {
-						panic!("Unimplemented chainspec '{:?}' in test '{}'", blockchain.network, name);
-					}
-				};
-
-				let genesis = Genesis::from(blockchain.genesis());
-				let state = From::from(blockchain.pre_state.clone());
-				spec.set_genesis_state(state).expect("Failed to overwrite genesis state");
-				spec.overwrite_genesis_params(genesis);
-				spec
-			};
-
-			{
-				let db = test_helpers::new_db();
-				let mut config = ClientConfig::default();
-				if ethjson::test_helpers::blockchain::Engine::NoProof == blockchain.engine {
-					config.verifier_type = VerifierType::CanonNoSeal;
-					config.check_seal = false;
-				}
-				config.history = 8;
-				config.queue.verifier_settings.num_verifiers = 1;
-				let client = Client::new(
-					config,
-					&spec,
-					db,
-					Arc::new(Miner::new_for_tests(&spec, None)),
-					IoChannel::disconnected(),
-				).expect("Failed to instantiate a new Client");
-
-				for b in blockchain.blocks_rlp() {
-					let bytes_len = b.len();
-					let block = Unverified::from_rlp(b);
-					match block {
-						Ok(block) => {
-							let num = block.header.number();
-							let hash = block.hash();
-							trace!(target: "json-tests", "{} – Importing {} bytes. Block #{}/{}", name, bytes_len, num, hash);
-							let res = client.import_block(block);
-							if let Err(e) = res {
-								warn!(target: "json-tests", "{} – Error importing block #{}/{}: {:?}", name, num, hash, e);
-							}
-							client.flush_queue();
-						},
-						Err(decoder_err) => {
-							warn!(target: "json-tests", "Error decoding test block: {:?} ({} bytes)", decoder_err, bytes_len);
-						}
-					}
-				}
-
-				let post_state_success = if let Some(post_state) = blockchain.post_state.clone() {
-					check_poststate(&client, &name, post_state)
-				} else {
-					true
-				};
-
-				fail_unless(
-					client.chain_info().best_block_hash == blockchain.best_block.into()
-					&& post_state_success
-				);
-			}
-		}
+		flushed_write!("   - {}...", name);
 
-		if !fail {
-			flushed_writeln!("OK");
-		} else {
-			flushed_writeln!("FAILED");
+		match run_blockchain_test(&name, blockchain) {
+			Ok(()) => flushed_writeln!("OK"),
+			Err(e) => {
+				failed.push(name.clone());
+				flushed_writeln!("FAILED: {}", e);
+			},
 		}
 
 		start_stop_hook(&name, HookType::OnStop);
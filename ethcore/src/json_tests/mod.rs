@@ -30,6 +30,7 @@ mod trie;
 #[cfg(test)]
 mod difficulty;
 
+pub use self::chain::{run_blockchain_test, BlockchainTestError};
 pub use self::executive::run_test_path as run_executive_test_path;
 pub use self::executive::run_test_file as run_executive_test_file;
 pub use self::test_common::HookType;
@@ -20,6 +20,7 @@
 
 extern crate account_state;
 extern crate ansi_term;
+extern crate blooms_db;
 extern crate client_traits;
 extern crate common_types as types;
 extern crate engine;
@@ -39,6 +40,7 @@ extern crate kvdb;
 extern crate machine;
 extern crate memory_cache;
 extern crate parity_bytes as bytes;
+extern crate parity_util_mem;
 extern crate parking_lot;
 extern crate trie_db as trie;
 extern crate patricia_trie_ethereum as ethtrie;
@@ -48,10 +50,13 @@ extern crate registrar;
 extern crate rlp;
 extern crate rustc_hex;
 extern crate serde;
+#[macro_use]
+extern crate serde_json;
 extern crate snapshot;
 extern crate spec;
 extern crate state_db;
 extern crate trace;
+extern crate stats;
 extern crate trie_vm_factories;
 extern crate triehash_ethereum as triehash;
 extern crate unexpected;
@@ -83,12 +88,8 @@ extern crate kvdb_rocksdb;
 extern crate lazy_static;
 #[cfg(any(test, feature = "test-helpers"))]
 extern crate pod;
-#[cfg(any(test, feature = "blooms-db"))]
-extern crate blooms_db;
 #[cfg(feature = "env_logger")]
 extern crate env_logger;
-#[cfg(test)]
-extern crate serde_json;
 #[cfg(any(test, feature = "tempdir"))]
 extern crate tempfile;
 
@@ -0,0 +1,381 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction bundles.
+//!
+//! A bundle is an ordered list of transactions that is placed at the top of a block as a whole or
+//! not at all. Bundles are submitted for a specific block number and only considered when authoring
+//! that block.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use bytes::Bytes;
+use ethereum_types::{H256, U256, Address};
+use hash::keccak;
+use types::{
+	BlockNumber,
+	errors::EthcoreError as Error,
+	receipt::TransactionOutcome,
+	transaction::{Action, SignedTransaction},
+};
+
+use block::OpenBlock;
+
+/// Maximal number of bundles kept in the pool.
+pub const MAX_BUNDLES: usize = 256;
+/// Maximal number of transactions in a single bundle.
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 64;
+/// How many blocks past the best block a bundle may target.
+pub const MAX_BUNDLE_HORIZON: BlockNumber = 256;
+
+/// Transactions to be included atomically and in order at the top of a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+	/// Transactions of the bundle in execution order.
+	pub transactions: Vec<SignedTransaction>,
+	/// Number of the block the bundle is valid for.
+	pub block_number: BlockNumber,
+	/// Earliest block timestamp the bundle is valid for.
+	pub min_timestamp: Option<u64>,
+	/// Latest block timestamp the bundle is valid for.
+	pub max_timestamp: Option<u64>,
+	/// Hashes of transactions that are allowed to revert without invalidating the bundle.
+	pub reverting_hashes: HashSet<H256>,
+}
+
+impl Bundle {
+	/// Hash identifying the bundle: keccak of the concatenated transaction hashes.
+	pub fn hash(&self) -> H256 {
+		let hashes: Vec<u8> = self.transactions.iter()
+			.flat_map(|tx| tx.hash().as_bytes().to_vec())
+			.collect();
+		keccak(hashes)
+	}
+
+	/// Returns true if the bundle may be included in a block with given number and timestamp.
+	pub fn is_eligible(&self, block_number: BlockNumber, timestamp: u64) -> bool {
+		self.block_number == block_number
+			&& self.min_timestamp.map_or(true, |min| timestamp >= min)
+			&& self.max_timestamp.map_or(true, |max| timestamp <= max)
+	}
+}
+
+/// Reasons for refusing a bundle.
+#[derive(Debug, PartialEq)]
+pub enum BundleError {
+	/// The bundle has no transactions.
+	Empty,
+	/// The bundle has more than `MAX_BUNDLE_TRANSACTIONS` transactions.
+	TooManyTransactions(usize),
+	/// The bundle targets a block that is already part of the chain.
+	Stale {
+		/// Current best block.
+		best: BlockNumber,
+		/// Block targeted by the bundle.
+		target: BlockNumber,
+	},
+	/// The bundle targets a block too far in the future.
+	TooFarAhead {
+		/// Current best block.
+		best: BlockNumber,
+		/// Block targeted by the bundle.
+		target: BlockNumber,
+	},
+	/// The bundle pool is full.
+	Full,
+}
+
+impl fmt::Display for BundleError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BundleError::Empty => write!(f, "Bundle has no transactions"),
+			BundleError::TooManyTransactions(count) =>
+				write!(f, "Bundle has {} transactions, at most {} are allowed", count, MAX_BUNDLE_TRANSACTIONS),
+			BundleError::Stale { best, target } =>
+				write!(f, "Bundle targets block {} but the best block is already {}", target, best),
+			BundleError::TooFarAhead { best, target } =>
+				write!(f, "Bundle targets block {} which is more than {} blocks past the best block {}", target, MAX_BUNDLE_HORIZON, best),
+			BundleError::Full => write!(f, "Bundle pool is full"),
+		}
+	}
+}
+
+/// Bundles waiting for the block they target.
+#[derive(Default)]
+pub struct BundlePool {
+	bundles: BTreeMap<BlockNumber, Vec<Bundle>>,
+	count: usize,
+	updated: BTreeSet<BlockNumber>,
+}
+
+impl BundlePool {
+	/// Adds a bundle to the pool, replacing an identical one. Returns the bundle hash.
+	pub fn import(&mut self, best_block: BlockNumber, bundle: Bundle) -> Result<H256, BundleError> {
+		if bundle.transactions.is_empty() {
+			return Err(BundleError::Empty);
+		}
+		if bundle.transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+			return Err(BundleError::TooManyTransactions(bundle.transactions.len()));
+		}
+		if bundle.block_number <= best_block {
+			return Err(BundleError::Stale { best: best_block, target: bundle.block_number });
+		}
+		if bundle.block_number > best_block + MAX_BUNDLE_HORIZON {
+			return Err(BundleError::TooFarAhead { best: best_block, target: bundle.block_number });
+		}
+
+		let hash = bundle.hash();
+		let target = bundle.block_number;
+		let bundles = self.bundles.entry(target).or_insert_with(Vec::new);
+		match bundles.iter().position(|b| b.hash() == hash) {
+			Some(index) => bundles[index] = bundle,
+			None if self.count >= MAX_BUNDLES => return Err(BundleError::Full),
+			None => {
+				bundles.push(bundle);
+				self.count += 1;
+			}
+		}
+		self.updated.insert(target);
+		Ok(hash)
+	}
+
+	/// Returns the bundles eligible for a block with given number and timestamp, in submission order.
+	pub fn pending(&self, block_number: BlockNumber, timestamp: u64) -> Vec<Bundle> {
+		self.bundles.get(&block_number)
+			.map(|bundles| bundles.iter().filter(|b| b.is_eligible(block_number, timestamp)).cloned().collect())
+			.unwrap_or_default()
+	}
+
+	/// Returns true if bundles for given block were added since the last call.
+	pub fn take_updated(&mut self, block_number: BlockNumber) -> bool {
+		self.updated.remove(&block_number)
+	}
+
+	/// Drops bundles for blocks that are already part of the chain.
+	pub fn cull(&mut self, best_block: BlockNumber) {
+		let remaining = self.bundles.split_off(&(best_block + 1));
+		let dropped: usize = self.bundles.values().map(Vec::len).sum();
+		self.count -= dropped;
+		self.bundles = remaining;
+		self.updated = self.updated.split_off(&(best_block + 1));
+	}
+
+	/// Number of bundles in the pool.
+	pub fn len(&self) -> usize {
+		self.count
+	}
+
+	/// Returns true if the pool holds no bundles.
+	pub fn is_empty(&self) -> bool {
+		self.count == 0
+	}
+}
+
+/// Outcome of a single transaction of an executed bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleTransactionResult {
+	/// Transaction hash.
+	pub hash: H256,
+	/// Transaction sender.
+	pub sender: Address,
+	/// Transaction recipient, `None` for contract creation.
+	pub to: Option<Address>,
+	/// Gas price of the transaction.
+	pub gas_price: U256,
+	/// Gas used by the transaction.
+	pub gas_used: U256,
+	/// Change of the block author's balance caused by the transaction.
+	pub coinbase_diff: U256,
+	/// Whether the transaction succeeded. Always true before Byzantium, where receipts carry no status.
+	pub success: bool,
+	/// Output of the call, or the revert data if it failed.
+	pub output: Bytes,
+}
+
+/// Outcome of an executed bundle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleSimulation {
+	/// Hash of the bundle.
+	pub bundle_hash: H256,
+	/// Number of the block the bundle was executed in.
+	pub block_number: BlockNumber,
+	/// Total gas used by the bundle.
+	pub gas_used: U256,
+	/// Fees paid for the gas used by the bundle.
+	pub gas_fees: U256,
+	/// Change of the block author's balance caused by the bundle, including direct payments.
+	pub coinbase_diff: U256,
+	/// Per-transaction outcomes.
+	pub transactions: Vec<BundleTransactionResult>,
+}
+
+impl BundleSimulation {
+	/// Author payment per unit of gas used by the bundle.
+	pub fn effective_gas_price(&self) -> U256 {
+		if self.gas_used.is_zero() {
+			U256::zero()
+		} else {
+			self.coinbase_diff / self.gas_used
+		}
+	}
+
+	/// Returns the hash of the first transaction that failed without being allowed to revert.
+	pub fn first_disallowed_revert(&self, bundle: &Bundle) -> Option<H256> {
+		self.transactions.iter()
+			.find(|tx| !tx.success && !bundle.reverting_hashes.contains(&tx.hash))
+			.map(|tx| tx.hash)
+	}
+}
+
+/// Executes the bundle on top of a copy of `block`.
+///
+/// Returns the block with all bundle transactions applied along with the outcome of the execution.
+/// `block` itself is left untouched so the caller can decide whether to keep the result.
+/// Fails if any of the transactions cannot be applied at all, e.g. because of an invalid nonce.
+pub fn execute<'x>(block: &OpenBlock<'x>, bundle: &Bundle) -> Result<(OpenBlock<'x>, BundleSimulation), Error> {
+	let mut block = block.clone();
+	let author = *block.header.author();
+	let start_balance = block.state.balance(&author)?;
+	let start_gas = block.receipts.last().map_or_else(U256::zero, |r| r.gas_used);
+
+	let mut transactions = Vec::with_capacity(bundle.transactions.len());
+	for tx in &bundle.transactions {
+		let hash = tx.hash();
+		let sender = tx.sender();
+		let to = match tx.action {
+			Action::Call(address) => Some(address),
+			Action::Create => None,
+		};
+		let gas_price = tx.gas_price;
+		let balance_before = block.state.balance(&author)?;
+		let gas_before = block.receipts.last().map_or_else(U256::zero, |r| r.gas_used);
+
+		let (success, gas_after, output) = {
+			let (receipt, output) = block.push_transaction_with_output(tx.clone())?;
+			let success = match receipt.outcome {
+				TransactionOutcome::StatusCode(status) => status != 0,
+				_ => true,
+			};
+			(success, receipt.gas_used, output)
+		};
+
+		let balance_after = block.state.balance(&author)?;
+		transactions.push(BundleTransactionResult {
+			hash,
+			sender,
+			to,
+			gas_price,
+			gas_used: gas_after - gas_before,
+			coinbase_diff: balance_after.saturating_sub(balance_before),
+			success,
+			output,
+		});
+	}
+
+	let end_balance = block.state.balance(&author)?;
+	let end_gas = block.receipts.last().map_or_else(U256::zero, |r| r.gas_used);
+	let gas_fees = transactions.iter().fold(U256::zero(), |acc, tx| acc + tx.gas_price * tx.gas_used);
+	let simulation = BundleSimulation {
+		bundle_hash: bundle.hash(),
+		block_number: block.header.number(),
+		gas_used: end_gas - start_gas,
+		gas_fees,
+		coinbase_diff: end_balance.saturating_sub(start_balance),
+		transactions,
+	};
+	Ok((block, simulation))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use parity_crypto::publickey::{Generator, Random};
+	use rustc_hex::FromHex;
+	use types::transaction::Transaction;
+
+	fn transaction(nonce: u64) -> SignedTransaction {
+		let keypair = Random.generate();
+		Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::from(nonce),
+		}.sign(keypair.secret(), None)
+	}
+
+	fn bundle(block_number: BlockNumber) -> Bundle {
+		Bundle {
+			transactions: vec![transaction(0)],
+			block_number,
+			min_timestamp: None,
+			max_timestamp: None,
+			reverting_hashes: HashSet::new(),
+		}
+	}
+
+	#[test]
+	fn should_check_eligibility() {
+		let mut bundle = bundle(5);
+		bundle.min_timestamp = Some(10);
+		bundle.max_timestamp = Some(20);
+
+		assert!(bundle.is_eligible(5, 15));
+		assert!(!bundle.is_eligible(4, 15));
+		assert!(!bundle.is_eligible(5, 9));
+		assert!(!bundle.is_eligible(5, 21));
+	}
+
+	#[test]
+	fn should_refuse_invalid_bundles() {
+		let mut pool = BundlePool::default();
+		let mut empty = bundle(5);
+		empty.transactions.clear();
+		let mut large = bundle(5);
+		large.transactions = (0..MAX_BUNDLE_TRANSACTIONS as u64 + 1).map(transaction).collect();
+
+		assert_eq!(pool.import(4, empty), Err(BundleError::Empty));
+		assert_eq!(pool.import(4, large), Err(BundleError::TooManyTransactions(MAX_BUNDLE_TRANSACTIONS + 1)));
+		assert_eq!(pool.import(5, bundle(5)), Err(BundleError::Stale { best: 5, target: 5 }));
+		assert_eq!(pool.import(4, bundle(5 + MAX_BUNDLE_HORIZON)), Err(BundleError::TooFarAhead { best: 4, target: 260 }));
+		assert!(pool.is_empty());
+	}
+
+	#[test]
+	fn should_replace_identical_bundles_and_cull() {
+		let mut pool = BundlePool::default();
+		let first = bundle(5);
+		let hash = pool.import(4, first.clone()).unwrap();
+		assert_eq!(hash, first.hash());
+		assert_eq!(pool.import(4, first), Ok(hash));
+		pool.import(4, bundle(5)).unwrap();
+		pool.import(4, bundle(6)).unwrap();
+
+		assert_eq!(pool.len(), 3);
+		assert_eq!(pool.pending(5, 0).len(), 2);
+		assert!(pool.take_updated(5));
+		assert!(!pool.take_updated(5));
+
+		pool.cull(5);
+		assert_eq!(pool.len(), 1);
+		assert!(pool.pending(5, 0).is_empty());
+		assert_eq!(pool.pending(6, 0).len(), 1);
+		assert!(!pool.take_updated(5));
+		assert!(pool.take_updated(6));
+	}
+}
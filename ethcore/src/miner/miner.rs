@@ -24,13 +24,16 @@ use bytes::Bytes;
 use call_contract::CallContract;
 use ethcore_miner::gas_pricer::GasPricer;
 use ethcore_miner::local_accounts::LocalAccounts;
-use ethcore_miner::pool::{self, TransactionQueue, VerifiedTransaction, QueueStatus, PrioritizationStrategy, TxStatus};
+use ethcore_miner::pool::{
+	self, TransactionQueue, VerifiedTransaction, QueueStatus, PrioritizationStrategy, ReplacementPolicy, TxStatus,
+};
 use ethcore_miner::service_transaction_checker::ServiceTransactionChecker;
 #[cfg(feature = "work-notify")]
 use ethcore_miner::work_notify::NotifyWork;
 use ethereum_types::{H256, U256, Address};
 use futures::sync::mpsc;
 use io::IoChannel;
+use miner::bundle::{self, Bundle, BundleError, BundlePool, BundleSimulation};
 use miner::filter_options::FilterOptions;
 use miner::pool_client::{PoolClient, CachedNonceClient, NonceCache};
 use miner::{self, MinerService};
@@ -56,7 +59,7 @@ use types::{
 };
 use using_queue::{UsingQueue, GetAction};
 
-use block::{ClosedBlock, SealedBlock};
+use block::{ClosedBlock, OpenBlock, SealedBlock};
 use client::{BlockProducer, SealedBlockImporter, Client};
 use client_traits::{BlockChain, ChainInfo, Nonce, TransactionInfo, EngineClient, ForceUpdateSealing};
 use engine::{Engine, signer::EngineSigner};
@@ -148,6 +151,14 @@ pub struct MinerOptions {
 
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
+	/// Rules for replacing queued transactions with the same sender and nonce.
+	pub tx_queue_replacement: ReplacementPolicy,
+	/// Per-sender and global limits on pending and future transactions.
+	pub tx_queue_quotas: pool::Quotas,
+	/// Time-to-live of local and remote transactions in the queue.
+	pub tx_queue_expiry: pool::Expiry,
+	/// Apply the transaction filter also when authoring blocks, not only when importing to the queue.
+	pub tx_filter_authoring: bool,
 	/// Simple senders penalization.
 	pub tx_queue_penalization: Penalization,
 	/// Do we want to mark transactions received locally (e.g. RPC) as local if we don't have the sending account?
@@ -174,6 +185,10 @@ impl Default for MinerOptions {
 			enable_resubmission: true,
 			infinite_pending_block: false,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_replacement: ReplacementPolicy::default(),
+			tx_queue_quotas: pool::Quotas::default(),
+			tx_queue_expiry: pool::Expiry::default(),
+			tx_filter_authoring: false,
 			tx_queue_penalization: Penalization::Disabled,
 			tx_queue_no_unfamiliar_locals: false,
 			refuse_service_transactions: false,
@@ -221,6 +236,54 @@ impl Author {
 	}
 }
 
+/// Timing statistics of pending block construction.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PendingBlockStats {
+	/// Number of pending blocks built from scratch on top of a new parent.
+	pub full_builds: u64,
+	/// Number of times new transactions were appended to the existing pending block.
+	pub incremental_builds: u64,
+	/// Number of times the existing pending block was kept as there were no new transactions.
+	pub reused: u64,
+	/// Time taken by the last build.
+	pub last_build_time: Duration,
+	/// Total time spent building pending blocks.
+	pub total_build_time: Duration,
+	/// Number of transactions pushed by the last build.
+	pub last_transactions_pushed: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockBuild {
+	Full,
+	Incremental,
+	Reused,
+}
+
+/// Starting point of a pending block.
+enum PendingBase<'a> {
+	/// Previous work on top of the same parent.
+	Existing(ClosedBlock),
+	/// A freshly opened block with the transactions the engine wants to include.
+	Fresh(OpenBlock<'a>, Vec<SignedTransaction>),
+}
+
+impl<'a> PendingBase<'a> {
+	fn header(&self) -> &Header {
+		match *self {
+			PendingBase::Existing(ref block) => &block.header,
+			PendingBase::Fresh(ref block, _) => &block.header,
+		}
+	}
+
+	fn engine_transactions(&self) -> usize {
+		match *self {
+			PendingBase::Existing(_) => 0,
+			PendingBase::Fresh(_, ref transactions) => transactions.len(),
+		}
+	}
+}
+
 struct SealingWork {
 	queue: UsingQueue<ClosedBlock>,
 	enabled: bool,
@@ -258,6 +321,9 @@ pub struct Miner {
 	accounts: Arc<dyn LocalAccounts>,
 	io_channel: RwLock<Option<IoChannel<ClientIoMessage<Client>>>>,
 	service_transaction_checker: Option<ServiceTransactionChecker>,
+	// NOTE Lock after `sealing` if both are needed.
+	bundles: RwLock<BundlePool>,
+	build_stats: Mutex<PendingBlockStats>,
 }
 
 impl Miner {
@@ -282,6 +348,33 @@ impl Miner {
 		receiver
 	}
 
+	/// Set the filter applied to transactions entering the queue
+	/// and, if enabled by `tx_filter_authoring`, to transactions included in authored blocks.
+	pub fn set_transaction_filter(&self, filter: Option<Arc<dyn pool::TransactionFilter>>) {
+		self.transaction_queue.set_filter(filter);
+	}
+
+	/// Lets the transaction filter refresh its rules.
+	pub fn refresh_transaction_filter(&self) {
+		if let Some(filter) = self.transaction_queue.filter() {
+			filter.refresh();
+		}
+	}
+
+	/// Removes transactions which stayed in the queue for longer than their time-to-live.
+	///
+	/// Called periodically by the client, expired transactions are also removed when culling the queue.
+	pub fn remove_expired_transactions(&self) {
+		self.transaction_queue.remove_expired();
+	}
+
+	/// Set a callback to be notified about lifecycle events of transactions in the pool.
+	pub fn transaction_events_receiver(&self) -> mpsc::UnboundedReceiver<Arc<Vec<pool::PoolEvent>>> {
+		let (sender, receiver) = mpsc::unbounded();
+		self.transaction_queue.add_event_listener(Box::new(sender));
+		receiver
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new<A: LocalAccounts + 'static>(
 		options: MinerOptions,
@@ -292,10 +385,20 @@ impl Miner {
 		let limits = options.pool_limits.clone();
 		let verifier_options = options.pool_verification_options.clone();
 		let tx_queue_strategy = options.tx_queue_strategy;
+		let tx_queue_replacement = options.tx_queue_replacement;
 		let nonce_cache_size = cmp::max(4096, limits.max_count / 4);
 		let refuse_service_transactions = options.refuse_service_transactions;
 		let engine = spec.engine.clone();
 
+		let transaction_queue = TransactionQueue::with_replacement_policy(
+			limits,
+			verifier_options,
+			tx_queue_strategy,
+			tx_queue_replacement,
+		);
+		transaction_queue.set_quotas(options.tx_queue_quotas.clone());
+		transaction_queue.set_expiry(options.tx_queue_expiry);
+
 		Miner {
 			sealing: Mutex::new(SealingWork {
 				queue: UsingQueue::new(options.work_queue_size),
@@ -311,7 +414,7 @@ impl Miner {
 			gas_pricer: Mutex::new(gas_pricer),
 			nonce_cache: NonceCache::new(nonce_cache_size),
 			options,
-			transaction_queue: Arc::new(TransactionQueue::new(limits, verifier_options, tx_queue_strategy)),
+			transaction_queue: Arc::new(transaction_queue),
 			accounts: Arc::new(accounts),
 			engine,
 			io_channel: RwLock::new(None),
@@ -320,6 +423,8 @@ impl Miner {
 			} else {
 				Some(ServiceTransactionChecker::default())
 			},
+			bundles: RwLock::new(BundlePool::default()),
+			build_stats: Mutex::new(PendingBlockStats::default()),
 		}
 	}
 
@@ -366,9 +471,21 @@ impl Miner {
 		});
 	}
 
-	/// Clear all pending block states
+	/// Import a previously submitted local transaction, keeping its original submission metadata.
+	pub fn import_resubmitted_transaction<C: miner::BlockChainClient>(
+		&self,
+		chain: &C,
+		pending: PendingTransaction,
+		submission: pool::local_transactions::Submission,
+	) -> Result<(), transaction::Error> {
+		self.transaction_queue.note_local_resubmission(pending.hash(), submission);
+		self.import_own_transaction(chain, pending)
+	}
+
+	/// Clear all pending block states and cached nonces
 	pub fn clear(&self) {
 		self.sealing.lock().queue.reset();
+		self.nonce_cache.clear();
 	}
 
 	/// Updates transaction queue verification limits.
@@ -386,6 +503,28 @@ impl Miner {
 		});
 	}
 
+	/// Replaces the gas pricer and immediately recalibrates the minimal gas price
+	/// of the transaction queue, keeping the currently known block gas limit.
+	pub fn set_gas_pricer(&self, gas_pricer: GasPricer) {
+		let txq = self.transaction_queue.clone();
+		let mut options = txq.status().options;
+		let mut gp = self.gas_pricer.lock();
+		*gp = gas_pricer;
+		gp.recalibrate(move |gas_price| {
+			debug!(target: "miner", "minimal_gas_price: Got gas price! {}", gas_price);
+			options.minimal_gas_price = gas_price;
+			txq.set_verifier_options(options);
+		});
+	}
+
+	/// Updates per-sender quotas and expiry of the transaction queue.
+	///
+	/// NOTE: Pool size limits are fixed at construction and can't be changed here.
+	pub fn set_transaction_queue_limits(&self, quotas: pool::Quotas, expiry: pool::Expiry) {
+		self.transaction_queue.set_quotas(quotas);
+		self.transaction_queue.set_expiry(expiry);
+	}
+
 	/// Returns ServiceTransactionChecker
 	pub fn service_transaction_checker(&self) -> Option<ServiceTransactionChecker> {
 		self.service_transaction_checker.clone()
@@ -427,26 +566,28 @@ impl Miner {
 		C: BlockChain + CallContract + BlockProducer + Nonce + Sync,
 	{
 		trace_time!("prepare_block");
+		let build_start = Instant::now();
 		let chain_info = chain.chain_info();
 
 		// Open block
 		// Some engines add transactions to the block for their own purposes, e.g. AuthorityRound RANDAO.
-		let (mut open_block, original_work_hash, engine_txs) = {
+		let (base, original_work_hash) = {
 			let mut sealing = self.sealing.lock();
 			let last_work_hash = sealing.queue.peek_last_ref().map(|pb| pb.header.hash());
 			let best_hash = chain_info.best_block_hash;
+			// bundles are only placed in fresh blocks, so new ones force the block to be rebuilt.
+			let has_new_bundles = self.bundles.write().take_updated(chain_info.best_block_number + 1);
 
 			// check to see if last ClosedBlock in would_seals is actually same parent block.
 			// if so
-			//   duplicate, re-open and push any new transactions.
+			//   duplicate and push any new transactions, re-opening it only if there are some.
 			//   if at least one was pushed successfully, close and enqueue new ClosedBlock;
 			//   otherwise, leave everything alone.
 			// otherwise, author a fresh block.
-			match sealing.queue.get_pending_if(|b| b.header.parent_hash() == &best_hash) {
+			let base = match sealing.queue.get_pending_if(|b| b.header.parent_hash() == &best_hash && !has_new_bundles) {
 				Some(old_block) => {
 					trace!(target: "miner", "prepare_block: Already have previous work; updating and returning");
-					// add transactions to old_block
-					(chain.reopen_block(old_block), last_work_hash, Vec::new())
+					PendingBase::Existing(old_block)
 				}
 				None => {
 					// block not found - create it.
@@ -468,7 +609,7 @@ impl Miner {
 
 					// Before adding from the queue to the new block, give the engine a chance to add transactions.
 					match self.engine.generate_engine_transactions(&block) {
-						Ok(transactions) => (block, last_work_hash, transactions),
+						Ok(transactions) => PendingBase::Fresh(block, transactions),
 						Err(err) => {
 							error!(target: "miner", "Failed to prepare engine transactions for new block: {:?}. \
 								   This is likely an error in chain specification or on-chain consensus smart \
@@ -477,21 +618,10 @@ impl Miner {
 						}
 					}
 				}
-			}
+			};
+			(base, last_work_hash)
 		};
 
-		if self.options.infinite_pending_block {
-			open_block.remove_gas_limit();
-		}
-
-		let mut invalid_transactions = HashSet::new();
-		let mut not_allowed_transactions = HashSet::new();
-		let mut senders_to_penalize = HashSet::new();
-		let block_number = open_block.header.number();
-
-		let mut tx_count = 0usize;
-		let mut skipped_transactions = 0usize;
-
 		let client = self.pool_client(chain);
 		let engine_params = self.engine.params();
 		let min_tx_gas: U256 = self.engine.schedule(chain_info.best_block_number).tx_gas.into();
@@ -500,11 +630,16 @@ impl Miner {
 		} else {
 			None
 		};
+		let gas_limit = if self.options.infinite_pending_block {
+			U256::max_value()
+		} else {
+			*base.header().gas_limit()
+		};
 		// we will never need more transactions than limit divided by min gas
 		let max_transactions = if min_tx_gas.is_zero() {
 			usize::max_value()
 		} else {
-			MAX_SKIPPED_TRANSACTIONS.saturating_add(cmp::min(*open_block.header.gas_limit() / min_tx_gas, u64::max_value().into()).as_u64() as usize)
+			MAX_SKIPPED_TRANSACTIONS.saturating_add(cmp::min(gas_limit / min_tx_gas, u64::max_value().into()).as_u64() as usize)
 		};
 
 		let queue_txs: Vec<Arc<_>> = self.transaction_queue.pending(
@@ -513,11 +648,60 @@ impl Miner {
 				block_number: chain_info.best_block_number,
 				current_timestamp: chain_info.best_block_timestamp,
 				nonce_cap,
-				max_len: max_transactions.saturating_sub(engine_txs.len()),
+				max_len: max_transactions.saturating_sub(base.engine_transactions()),
 				ordering: miner::PendingOrdering::Priority,
 			}
 		);
 
+		let (mut open_block, engine_txs, queue_txs, build_kind) = match base {
+			PendingBase::Existing(old_block) => {
+				// Transactions already in the block were verified and executed when it was built.
+				let queue_txs: Vec<_> = queue_txs.into_iter()
+					.filter(|tx| !old_block.transactions_set.contains(&tx.hash()))
+					.collect();
+				// New uncles are only picked up when the block is re-opened.
+				if queue_txs.is_empty() && !self.options.reseal_on_uncle {
+					trace!(target: "miner", "prepare_block: No new transactions; keeping previous work");
+					self.note_block_build(BlockBuild::Reused, build_start.elapsed(), 0);
+					return Some((old_block, original_work_hash));
+				}
+				(chain.reopen_block(old_block), Vec::new(), queue_txs, BlockBuild::Incremental)
+			}
+			PendingBase::Fresh(block, engine_txs) => (block, engine_txs, queue_txs, BlockBuild::Full),
+		};
+
+		if self.options.infinite_pending_block {
+			open_block.remove_gas_limit();
+		}
+
+		if build_kind == BlockBuild::Full {
+			self.push_bundles(&mut open_block);
+		}
+
+		let mut invalid_transactions = HashSet::new();
+		let mut not_allowed_transactions = HashSet::new();
+		let mut senders_to_penalize = HashSet::new();
+		let block_number = open_block.header.number();
+
+		// Filter rules might have changed since the transactions entered the pool.
+		let queue_txs = match self.transaction_queue.filter() {
+			Some(ref filter) if self.options.tx_filter_authoring => queue_txs.into_iter()
+				.filter(|tx| match filter.check(tx.signed()) {
+					Ok(()) => true,
+					Err(e) => {
+						let hash = tx.signed().hash();
+						debug!(target: "miner", "Skipping filtered transaction {:?}: {:?}", hash, e);
+						not_allowed_transactions.insert(hash);
+						false
+					},
+				})
+				.collect(),
+			_ => queue_txs,
+		};
+
+		let mut tx_count = 0usize;
+		let mut skipped_transactions = 0usize;
+
 		let took_ms = |elapsed: &Duration| {
 			elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000
 		};
@@ -579,6 +763,10 @@ impl Miner {
 				Err(Error::Execution(ExecutionError::InvalidNonce { expected, got })) => {
 					debug!(target: "miner", "Skipping adding transaction to block because of invalid nonce: {:?} (expected: {:?}, got: {:?})", hash, expected, got);
 				},
+				// the transaction may be included once the base fee drops.
+				Err(Error::Execution(ExecutionError::GasPriceLowerThanBaseFee { gas_price, base_fee })) => {
+					debug!(target: "miner", "Skipping adding transaction to block because its gas price {} is below the base fee {}: {:?}", gas_price, base_fee, hash);
+				},
 				// already have transaction - ignore
 				Err(Error::Transaction(transaction::Error::AlreadyImported)) => {},
 				Err(Error::Transaction(transaction::Error::NotAllowed)) => {
@@ -613,9 +801,56 @@ impl Miner {
 			self.transaction_queue.penalize(senders_to_penalize.iter());
 		}
 
+		self.note_block_build(build_kind, build_start.elapsed(), tx_count);
 		Some((block, original_work_hash))
 	}
 
+	fn note_block_build(&self, kind: BlockBuild, took: Duration, transactions: usize) {
+		let mut stats = self.build_stats.lock();
+		match kind {
+			BlockBuild::Full => stats.full_builds += 1,
+			BlockBuild::Incremental => stats.incremental_builds += 1,
+			BlockBuild::Reused => stats.reused += 1,
+		}
+		stats.last_build_time = took;
+		stats.total_build_time += took;
+		stats.last_transactions_pushed = transactions;
+	}
+
+	/// Places the bundles targeting the block at its top, highest bidders first.
+	///
+	/// Bundles are ordered by the lowest gas price of their transactions, then by submission order, and
+	/// each of them is executed exactly once on top of the bundles already included. Bundles that fail to
+	/// apply, revert without being allowed to, or pay the author less than the minimal gas price per unit
+	/// of gas are left out.
+	fn push_bundles(&self, open_block: &mut OpenBlock) {
+		let mut bundles = self.bundles.read().pending(open_block.header.number(), open_block.header.timestamp());
+		if bundles.is_empty() {
+			return;
+		}
+
+		let minimal_gas_price = self.transaction_queue.status().options.minimal_gas_price;
+		let bid = |bundle: &Bundle| bundle.transactions.iter().map(|tx| tx.gas_price).min().unwrap_or_default();
+		// stable sort, so equal bids keep the submission order
+		bundles.sort_by(|a, b| bid(b).cmp(&bid(a)));
+
+		for bundle in bundles {
+			match bundle::execute(open_block, &bundle) {
+				Ok((_, ref simulation)) if simulation.first_disallowed_revert(&bundle).is_some() => {
+					debug!(target: "miner", "Skipping reverting bundle {:?}", simulation.bundle_hash);
+				}
+				Ok((_, ref simulation)) if simulation.effective_gas_price() < minimal_gas_price => {
+					debug!(target: "miner", "Skipping unprofitable bundle {:?}", simulation.bundle_hash);
+				}
+				Ok((block, simulation)) => {
+					debug!(target: "miner", "Included bundle {:?} with {} transactions", simulation.bundle_hash, bundle.transactions.len());
+					*open_block = block;
+				}
+				Err(e) => debug!(target: "miner", "Skipping bundle {:?} that failed to apply: {:?}", bundle.hash(), e),
+			}
+		}
+	}
+
 	/// Returns `true` if we should create pending block even if some other conditions are not met.
 	///
 	/// In general we always seal iff:
@@ -692,6 +927,7 @@ impl Miner {
 			let sealing = self.sealing.lock();
 			if block.transactions.is_empty()
 				&& !self.forced_sealing()
+				&& !self.engine.dev_mining().map_or(false, |d| d.is_seal_forced())
 				&& Instant::now() <= sealing.next_mandatory_reseal
 			{
 				return false
@@ -946,6 +1182,26 @@ impl miner::MinerService for Miner {
 		}
 	}
 
+	fn submit_bundle<C>(&self, chain: &C, bundle: Bundle) -> Result<H256, BundleError> where
+		C: ChainInfo,
+	{
+		let best_block = chain.chain_info().best_block_number;
+		let hash = self.bundles.write().import(best_block, bundle)?;
+		debug!(target: "miner", "Imported bundle {:?}", hash);
+		Ok(hash)
+	}
+
+	fn call_bundle<C>(&self, chain: &C, bundle: &Bundle, timestamp: Option<u64>) -> Result<BundleSimulation, Error> where
+		C: BlockChain + CallContract + BlockProducer + Nonce + Sync,
+	{
+		let params = self.params.read().clone();
+		let mut open_block = chain.prepare_open_block(params.author, params.gas_range_target, params.extra_data)?;
+		if let Some(timestamp) = timestamp {
+			open_block.set_timestamp(timestamp);
+		}
+		bundle::execute(&open_block, bundle).map(|(_, simulation)| simulation)
+	}
+
 	fn sensible_gas_price(&self) -> U256 {
 		// 10% above our minimum.
 		self.transaction_queue.current_worst_gas_price() * 110u32 / 100
@@ -1057,6 +1313,10 @@ impl miner::MinerService for Miner {
 		self.transaction_queue.local_transactions()
 	}
 
+	fn local_transaction_submissions(&self) -> BTreeMap<H256, pool::local_transactions::Submission> {
+		self.transaction_queue.local_submissions()
+	}
+
 	fn queued_transactions(&self) -> Vec<Arc<VerifiedTransaction>> {
 		self.transaction_queue.all_transactions()
 	}
@@ -1189,10 +1449,29 @@ impl miner::MinerService for Miner {
 			.expect("remove() returns one result per hash; one hash passed; qed")
 	}
 
+	fn transaction_age(&self, hash: &H256) -> Option<pool::TransactionAge> {
+		self.transaction_queue.transaction_age(hash)
+	}
+
+	fn inspect_pool<C>(&self, chain: &C) -> pool::Inspection where
+		C: ChainInfo + Nonce + Sync,
+	{
+		let chain_info = chain.chain_info();
+		self.transaction_queue.inspect(
+			CachedNonceClient::new(chain, &self.nonce_cache),
+			chain_info.best_block_number,
+			chain_info.best_block_timestamp,
+		)
+	}
+
 	fn queue_status(&self) -> QueueStatus {
 		self.transaction_queue.status()
 	}
 
+	fn pending_block_stats(&self) -> PendingBlockStats {
+		self.build_stats.lock().clone()
+	}
+
 	fn pending_receipts(&self, best_block: BlockNumber) -> Option<Vec<RichReceipt>> {
 		self.map_existing_pending_block(|pending| {
 			let receipts = &pending.receipts;
@@ -1357,6 +1636,8 @@ impl miner::MinerService for Miner {
 		if has_new_best_block {
 			// Clear nonce cache
 			self.nonce_cache.clear();
+			// Drop bundles for blocks already in the chain
+			self.bundles.write().cull(chain.chain_info().best_block_number);
 		}
 
 		// First update gas limit in transaction queue and minimal gas price.
@@ -1506,6 +1787,93 @@ mod tests {
 		assert!(sealing_work.is_some(), "Expected closed block");
 	}
 
+	#[test]
+	fn should_place_bundles_at_the_top_of_new_blocks() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let bundle = Bundle {
+			transactions: vec![transaction(), transaction()],
+			block_number: 1,
+			min_timestamp: None,
+			max_timestamp: None,
+			reverting_hashes: HashSet::new(),
+		};
+		let pool_transaction = transaction();
+
+		// when
+		assert_eq!(miner.submit_bundle(&client, bundle.clone()), Ok(bundle.hash()));
+		miner.import_own_transaction(&client, PendingTransaction::new(pool_transaction.clone(), None)).unwrap();
+
+		// then
+		let pending = miner.pending_transactions(0).unwrap();
+		assert_eq!(pending, vec![bundle.transactions[0].clone(), bundle.transactions[1].clone(), pool_transaction]);
+	}
+
+	#[test]
+	fn should_append_new_transactions_to_existing_pending_block() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let first = transaction();
+		miner.import_own_transaction(&client, PendingTransaction::new(first.clone(), None)).unwrap();
+		assert_eq!(miner.pending_block_stats().full_builds, 1);
+
+		// when nothing changed, the previous work is kept
+		let (block, _) = miner.prepare_block(&client).unwrap();
+		assert_eq!(block.transactions, vec![first.clone()]);
+		assert_eq!(miner.pending_block_stats().reused, 1);
+
+		// when a transaction arrives, it's appended to the previous work
+		let second = transaction();
+		miner.import_external_transactions(&client, vec![second.clone().into()]).pop().unwrap().unwrap();
+		let (block, _) = miner.prepare_block(&client).unwrap();
+
+		// then
+		assert_eq!(block.transactions, vec![first, second]);
+		let stats = miner.pending_block_stats();
+		assert_eq!(stats.full_builds, 1);
+		assert_eq!(stats.incremental_builds, 1);
+		assert_eq!(stats.last_transactions_pushed, 1);
+	}
+
+	#[test]
+	fn should_refuse_stale_bundles() {
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let bundle = Bundle {
+			transactions: vec![transaction()],
+			block_number: 0,
+			min_timestamp: None,
+			max_timestamp: None,
+			reverting_hashes: HashSet::new(),
+		};
+
+		assert_eq!(miner.submit_bundle(&client, bundle), Err(BundleError::Stale { best: 0, target: 0 }));
+	}
+
+	#[test]
+	fn should_simulate_bundles() {
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let bundle = Bundle {
+			transactions: vec![transaction(), transaction()],
+			block_number: 1,
+			min_timestamp: None,
+			max_timestamp: None,
+			reverting_hashes: HashSet::new(),
+		};
+
+		let simulation = miner.call_bundle(&client, &bundle, None).unwrap();
+		assert_eq!(simulation.bundle_hash, bundle.hash());
+		assert_eq!(simulation.block_number, 1);
+		assert_eq!(simulation.transactions.len(), 2);
+		assert_eq!(simulation.gas_used, simulation.transactions[0].gas_used + simulation.transactions[1].gas_used);
+		assert!(simulation.transactions.iter().all(|tx| tx.success && tx.to.is_none()));
+		// nothing is left behind in the pool or the pending block
+		assert!(miner.pending_transactions(0).is_none());
+	}
+
 	#[test]
 	fn should_still_work_after_a_couple_of_blocks() {
 		// given
@@ -1543,6 +1911,10 @@ mod tests {
 				infinite_pending_block: false,
 				tx_queue_penalization: Penalization::Disabled,
 				tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+				tx_queue_replacement: ReplacementPolicy::default(),
+				tx_queue_quotas: pool::Quotas::default(),
+				tx_queue_expiry: pool::Expiry::default(),
+				tx_filter_authoring: false,
 				tx_queue_no_unfamiliar_locals: false,
 				refuse_service_transactions: false,
 				pool_limits: Default::default(),
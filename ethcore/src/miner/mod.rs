@@ -21,12 +21,14 @@
 
 mod miner;
 mod filter_options;
+pub mod bundle;
 pub mod pool_client;
 #[cfg(feature = "stratum")]
 pub mod stratum;
 
-pub use self::miner::{Miner, MinerOptions, Penalization, PendingSet, AuthoringParams, Author};
+pub use self::miner::{Miner, MinerOptions, Penalization, PendingSet, AuthoringParams, Author, PendingBlockStats};
 pub use self::filter_options::FilterOptions;
+pub use self::bundle::{Bundle, BundleError, BundleSimulation, BundleTransactionResult};
 pub use ethcore_miner::local_accounts::LocalAccounts;
 pub use ethcore_miner::pool::PendingOrdering;
 
@@ -34,7 +36,7 @@ use std::sync::Arc;
 use std::collections::{BTreeSet, BTreeMap};
 
 use bytes::Bytes;
-use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, local_transactions};
+use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, TransactionAge, Inspection, local_transactions};
 use ethereum_types::{H256, U256, Address};
 use types::transaction::{self, UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use types::{
@@ -137,6 +139,19 @@ pub trait MinerService : Send + Sync {
 	/// On chains where sealing is done externally (e.g. PoW) we provide only reward beneficiary.
 	fn set_author<T: Into<Option<Author>>>(&self, author: T);
 
+	// Bundles
+
+	/// Submits a bundle to be placed atomically at the top of the block it targets.
+	/// Returns the bundle hash.
+	fn submit_bundle<C>(&self, chain: &C, bundle: Bundle) -> Result<H256, BundleError>
+		where C: ChainInfo;
+
+	/// Executes a bundle on top of the latest block, as if it was placed at the top of the next one.
+	///
+	/// All transactions are executed regardless of `reverting_hashes`; failures are reported in the outcome.
+	fn call_bundle<C>(&self, chain: &C, bundle: &Bundle, timestamp: Option<u64>) -> Result<BundleSimulation, Error>
+		where C: BlockChain + CallContract + BlockProducer + Nonce + Sync;
+
 	// Transaction Pool
 
 	/// Imports transactions to transaction queue.
@@ -205,11 +220,25 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of local transactions with statuses.
 	fn local_transactions(&self) -> BTreeMap<H256, local_transactions::Status>;
 
+	/// Get submission metadata of local transactions.
+	fn local_transaction_submissions(&self) -> BTreeMap<H256, local_transactions::Submission>;
+
+	/// Get the age of a transaction in the pool and when it is going to expire.
+	fn transaction_age(&self, hash: &H256) -> Option<TransactionAge>;
+
+	/// Get score components and status of all transactions in the pool,
+	/// together with transactions recently removed without being mined.
+	fn inspect_pool<C>(&self, chain: &C) -> Inspection
+		where C: ChainInfo + Nonce + Sync;
+
 	/// Get current queue status.
 	///
 	/// Status includes verification thresholds and current pool utilization and limits.
 	fn queue_status(&self) -> QueueStatus;
 
+	/// Get timing statistics of pending block construction.
+	fn pending_block_stats(&self) -> PendingBlockStats;
+
 	// Misc
 
 	/// Suggested gas price.
@@ -16,6 +16,8 @@
 
 //! Client-side stratum job dispatcher and mining notifier handler
 
+use std::cmp;
+use std::collections::{HashSet, VecDeque};
 use std::sync::{Arc, Weak};
 use std::net::{SocketAddr, AddrParseError};
 use std::fmt;
@@ -28,12 +30,17 @@ use ethcore_miner::work_notify::NotifyWork;
 #[cfg(feature = "work-notify")]
 use ethcore_stratum::PushWorkHandler;
 use ethcore_stratum::{
-	JobDispatcher, Stratum as StratumService, Error as StratumServiceError,
+	JobDispatcher, Stratum as StratumService, Error as StratumServiceError, ShareResult, TlsConfig,
 };
 use miner::{Miner, MinerService};
 use parking_lot::Mutex;
 use rlp::encode;
 
+pub use ethcore_stratum::{VarDiffConfig, WorkerStats, WorkerStatsProvider};
+
+/// Number of recent work packages shares are accepted for.
+const RECENT_WORK: usize = 8;
+
 /// Configures stratum server options.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Options {
@@ -45,6 +52,21 @@ pub struct Options {
 	pub port: u16,
 	/// Secret for peers
 	pub secret: Option<H256>,
+	/// Variable share difficulty, `None` if every share has to meet the block difficulty
+	pub vardiff: Option<VarDiffConfig>,
+	/// TLS listener, if enabled
+	pub tls: Option<TlsOptions>,
+}
+
+/// Configures the TLS listener of the stratum server.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TlsOptions {
+	/// Port, the listener uses the same network address as the plain server
+	pub port: u16,
+	/// Path to the PEM encoded certificate chain
+	pub certificate: String,
+	/// Path to the PEM encoded private key
+	pub private_key: String,
 }
 
 fn clean_0x(s: &str) -> &str {
@@ -113,9 +135,21 @@ impl fmt::Display for PayloadError {
 	}
 }
 
+/// Work package sent to the workers
+#[derive(Debug)]
+struct Work {
+	pow_hash: H256,
+	difficulty: U256,
+	number: u64,
+	/// Nonces of the shares accepted for this work
+	shares: HashSet<H64>,
+}
+
 /// Job dispatcher for stratum service
 pub struct StratumJobDispatcher {
 	seed_compute: Mutex<SeedHashCompute>,
+	/// Recently sent work packages, newest last
+	recent_work: Mutex<VecDeque<Work>>,
 	client: Weak<Client>,
 	miner: Weak<Miner>,
 }
@@ -128,11 +162,67 @@ impl JobDispatcher for StratumJobDispatcher {
 
 	fn job(&self) -> Option<String> {
 		self.with_core(|client, miner| miner.work_package(&*client).map(|(pow_hash, number, _timestamp, difficulty)| {
+			self.note_work(pow_hash, difficulty, number);
 			self.payload(pow_hash, difficulty, number)
 		}))
 	}
 
+	fn job_with_difficulty(&self, share_difficulty: &U256) -> Option<String> {
+		// the latest work is known whenever it was pushed, don't call back into the miner then
+		let latest = self.recent_work.lock().back().map(|work| (work.pow_hash, work.difficulty, work.number));
+		match latest {
+			Some((pow_hash, difficulty, number)) => Some(self.payload(pow_hash, cmp::min(*share_difficulty, difficulty), number)),
+			None => self.job(),
+		}
+	}
+
+	fn block_difficulty(&self) -> Option<U256> {
+		self.recent_work.lock().back().map(|work| work.difficulty)
+	}
+
 	fn submit(&self, payload: Vec<String>) -> Result<(), StratumServiceError> {
+		self.submit_seal(Self::decode(payload)?)
+	}
+
+	fn submit_share(&self, payload: Vec<String>, share_difficulty: &U256) -> Result<ShareResult, StratumServiceError> {
+		let payload = Self::decode(payload)?;
+		let (number, block_difficulty) = self.with_work(&payload, |work| (work.number, work.difficulty))?;
+
+		let client = self.client.upgrade().ok_or_else(|| StratumServiceError::Dispatch("Client is shutting down".into()))?;
+		let (mix_hash, difficulty) = client.engine().proof_of_work(number, &payload.pow_hash, payload.nonce.to_low_u64_be())
+			.ok_or_else(|| StratumServiceError::InvalidShare("Shares are only supported by proof-of-work engines".into()))?;
+		if mix_hash != payload.mix_hash {
+			return Err(StratumServiceError::InvalidShare(format!("Invalid mix hash {}", payload.mix_hash)));
+		}
+		if difficulty < cmp::min(*share_difficulty, block_difficulty) {
+			return Err(StratumServiceError::InvalidShare(format!("Share difficulty {} below target {}", difficulty, share_difficulty)));
+		}
+
+		// only valid shares are remembered, so invalid submissions can't grow the set
+		if !self.with_work(&payload, |work| work.shares.insert(payload.nonce))? {
+			return Err(StratumServiceError::InvalidShare(format!("Duplicate share {}", payload.nonce)));
+		}
+
+		if difficulty >= block_difficulty {
+			self.submit_seal(payload).map(|_| ShareResult::Block)
+		} else {
+			Ok(ShareResult::Share)
+		}
+	}
+}
+
+impl StratumJobDispatcher {
+	/// New stratum job dispatcher given the miner and client
+	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
+		StratumJobDispatcher {
+			seed_compute: Mutex::new(SeedHashCompute::default()),
+			recent_work: Mutex::new(VecDeque::with_capacity(RECENT_WORK)),
+			client: client,
+			miner: miner,
+		}
+	}
+
+	fn decode(payload: Vec<String>) -> Result<SubmitPayload, StratumServiceError> {
 		let payload = SubmitPayload::from_args(payload).map_err(|e|
 			StratumServiceError::Dispatch(e.to_string())
 		)?;
@@ -145,6 +235,29 @@ impl JobDispatcher for StratumJobDispatcher {
 			payload.mix_hash,
 		);
 
+		Ok(payload)
+	}
+
+	/// Remembers the work sent to the workers, so that shares for it can be verified
+	fn note_work(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		let mut recent_work = self.recent_work.lock();
+		if recent_work.back().map_or(false, |work| work.pow_hash == pow_hash) {
+			return;
+		}
+		if recent_work.len() == RECENT_WORK {
+			recent_work.pop_front();
+		}
+		recent_work.push_back(Work { pow_hash, difficulty, number, shares: HashSet::new() });
+	}
+
+	/// Runs `f` on the recent work the share was mined for
+	fn with_work<F, R>(&self, payload: &SubmitPayload, f: F) -> Result<R, StratumServiceError> where F: FnOnce(&mut Work) -> R {
+		self.recent_work.lock().iter_mut().rev().find(|work| work.pow_hash == payload.pow_hash)
+			.map(f)
+			.ok_or_else(|| StratumServiceError::InvalidShare(format!("Unknown or stale work {}", payload.pow_hash)))
+	}
+
+	fn submit_seal(&self, payload: SubmitPayload) -> Result<(), StratumServiceError> {
 		self.with_core_result(|client, miner| {
 			let seal = vec![encode(&payload.mix_hash), encode(&payload.nonce)];
 
@@ -159,17 +272,6 @@ impl JobDispatcher for StratumJobDispatcher {
 			}
 		})
 	}
-}
-
-impl StratumJobDispatcher {
-	/// New stratum job dispatcher given the miner and client
-	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
-		StratumJobDispatcher {
-			seed_compute: Mutex::new(SeedHashCompute::default()),
-			client: client,
-			miner: miner,
-		}
-	}
 
 	/// Serializes payload for stratum service
 	fn payload(&self, pow_hash: H256, difficulty: U256, number: u64) -> String {
@@ -223,6 +325,7 @@ impl NotifyWork for Stratum {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
 		trace!(target: "stratum", "Notify work");
 
+		self.dispatcher.note_work(pow_hash, difficulty, number);
 		self.service.push_work_all(
 			self.dispatcher.payload(pow_hash, difficulty, number)
 		);
@@ -236,21 +339,37 @@ impl Stratum {
 		use std::net::IpAddr;
 
 		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client));
-
-		let service = StratumService::start(
-			&SocketAddr::new(options.listen_addr.parse::<IpAddr>()?, options.port),
+		let listen_addr = options.listen_addr.parse::<IpAddr>()?;
+		let tls = options.tls.as_ref().map(|tls| TlsConfig {
+			addr: SocketAddr::new(listen_addr, tls.port),
+			certificate: tls.certificate.clone().into(),
+			private_key: tls.private_key.clone().into(),
+		});
+
+		let service = StratumService::start_with(
+			&SocketAddr::new(listen_addr, options.port),
 			dispatcher.clone(),
 			options.secret.clone(),
+			options.vardiff.clone(),
+			tls,
 		)?;
 
 		Ok(Stratum { dispatcher, service })
 	}
 
+	/// Statistics of the connected workers
+	pub fn workers(&self) -> Arc<dyn WorkerStatsProvider> {
+		self.service.clone()
+	}
+
 	/// Start STRATUM job dispatcher and register it in the miner
+	///
+	/// Returns the statistics of the connected workers.
 	#[cfg(feature = "work-notify")]
-	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
+	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<Arc<dyn WorkerStatsProvider>, Error> {
 		let stratum = Stratum::start(cfg, Arc::downgrade(&miner.clone()), client)?;
+		let workers = stratum.workers();
 		miner.add_work_listener(Box::new(stratum) as Box<dyn NotifyWork>);
-		Ok(())
+		Ok(workers)
 	}
 }
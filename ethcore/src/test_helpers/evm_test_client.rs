@@ -226,6 +226,7 @@ impl<'a> EvmTestClient<'a> {
 			last_hashes: Arc::new([H256::zero(); 256].to_vec()),
 			gas_used: 0.into(),
 			gas_limit: *genesis.gas_limit(),
+			base_fee: None,
 		};
 		self.call_envinfo(params, tracer, vm_tracer, info)
 	}
@@ -252,6 +253,13 @@ impl<'a> EvmTestClient<'a> {
 		).map_err(EvmTestError::Evm)
 	}
 
+	/// Commits the changes made by the executed calls.
+	/// Returns the state root and the state dump, if enabled.
+	pub fn commit(&mut self) -> Result<(H256, Option<PodState>), EvmTestError> {
+		self.state.commit()?;
+		Ok((*self.state.root(), (self.dump_state)(&self.state)))
+	}
+
 	/// Executes a SignedTransaction within context of the provided state and `EnvInfo`.
 	/// Returns the state root, gas left and the output.
 	pub fn transact<T: trace::Tracer, V: trace::VMTracer>(
@@ -307,6 +307,11 @@ impl BlockChainDB for TestBlockChainDB {
 
 /// Creates new test instance of `BlockChainDB`
 pub fn new_db() -> Arc<dyn BlockChainDB> {
+	new_db_with_key_value(Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS)))
+}
+
+/// Creates new test instance of `BlockChainDB` over the given key-value store
+pub fn new_db_with_key_value(key_value: Arc<dyn KeyValueDB>) -> Arc<dyn BlockChainDB> {
 	let blooms_dir = TempDir::new().unwrap();
 	let trace_blooms_dir = TempDir::new().unwrap();
 
@@ -315,7 +320,7 @@ pub fn new_db() -> Arc<dyn BlockChainDB> {
 		trace_blooms: blooms_db::Database::open(trace_blooms_dir.path()).unwrap(),
 		_blooms_dir: blooms_dir,
 		_trace_blooms_dir: trace_blooms_dir,
-		key_value: Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS))
+		key_value,
 	};
 
 	Arc::new(db)
@@ -20,13 +20,14 @@ use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder};
 use std::sync::Arc;
 use std::collections::{HashMap, BTreeMap};
+use std::time::Duration;
 use blockchain::BlockProvider;
 
 use blockchain::{TreeRoute, BlockReceipts};
 use bytes::Bytes;
 use db::{NUM_COLUMNS, COL_STATE};
 use ethcore_miner::pool::VerifiedTransaction;
-use ethereum_types::{H256, U256, Address};
+use ethereum_types::{H256, H520, U256, Address};
 use parity_crypto::publickey::{Generator, Random};
 use ethtrie;
 use hash::keccak;
@@ -38,23 +39,27 @@ use rlp::{Rlp, RlpStream};
 use rustc_hex::FromHex;
 use types::{
 	BlockNumber,
+	ancestry_proof::AncestryProof,
 	encoded,
-	engines::epoch::Transition as EpochTransition,
+	engines::epoch::{EpochValidators, Transition as EpochTransition},
 	ids::{BlockId, TransactionId, UncleId, TraceId},
 	basic_account::BasicAccount,
-	errors::{EthcoreError as Error, EthcoreResult},
+	errors::{EngineError, EthcoreError as Error, EthcoreResult},
 	transaction::{self, Transaction, LocalizedTransaction, SignedTransaction, Action, CallError},
-	filter::Filter,
+	filter::{Filter, ReconstructLogsError},
 	trace_filter::Filter as TraceFilter,
 	call_analytics::CallAnalytics,
 	header::Header,
 	log_entry::LocalizedLogEntry,
 	pruning_info::PruningInfo,
-	receipt::{Receipt, LocalizedReceipt, TransactionOutcome},
+	receipt::{Receipt, LocalizedReceipt, ReceiptProof, TransactionOutcome},
+	state_diff::StateDiff,
+	threat_status::ThreatStatus,
 	view,
 	views::BlockView,
 	verification::Unverified,
 	client_types::{Mode, StateResult},
+	confirmation_status::ConfirmationStatus,
 	blockchain_info::BlockChainInfo,
 	block_status::BlockStatus,
 	verification::VerificationQueueInfo as BlockQueueInfo,
@@ -70,8 +75,8 @@ use client::{
 };
 use client_traits::{
 	BlockInfo, Nonce, Balance, ChainInfo, TransactionInfo, BlockChainClient, ImportBlock,
-	AccountData, BlockChain, IoClient, BadBlocks, ScheduleInfo, StateClient, ProvingBlockChainClient,
-	StateOrBlock, ForceUpdateSealing, TransactionRequest
+	AccountData, BlockChain, BlockChainReset, IoClient, BadBlocks, ScheduleInfo, StateClient, ProvingBlockChainClient,
+	StateOrBlock, ForceUpdateSealing, ForkChoiceClient, TransactionRequest, UncleCandidate, UncleCandidates,
 };
 use engine::Engine;
 use machine::executed::Executed;
@@ -80,7 +85,7 @@ use miner::{self, Miner, MinerService};
 use spec::{Spec, self};
 use account_state::state::StateInfo;
 use state_db::StateDB;
-use trace::LocalizedTrace;
+use trace::{GasProfile, LocalizedTrace, StructLogger};
 
 /// Test client.
 pub struct TestBlockChainClient {
@@ -104,12 +109,20 @@ pub struct TestBlockChainClient {
 	pub storage: RwLock<HashMap<(Address, H256), H256>>,
 	/// Code.
 	pub code: RwLock<HashMap<Address, Bytes>>,
+	/// Balances at past blocks, whose state is readable for accounts without code or storage.
+	pub block_balances: RwLock<HashMap<H256, HashMap<Address, U256>>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Result<Executed, CallError>>>,
+	/// Number of steps executed by replayed transactions, `None` if transactions aren't found.
+	pub struct_log_steps: RwLock<Option<usize>>,
+	/// Number of transaction replays started.
+	pub struct_log_replays: AtomicUsize,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionId, LocalizedReceipt>>,
 	/// Logs
 	pub logs: RwLock<Vec<LocalizedLogEntry>>,
+	/// Logs reconstructed by re-executing blocks.
+	pub reconstructed_logs: RwLock<Vec<LocalizedLogEntry>>,
 	/// Should return errors on logs.
 	pub error_on_logs: RwLock<Option<BlockId>>,
 	/// Block queue size.
@@ -128,8 +141,12 @@ pub struct TestBlockChainClient {
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
+	/// Uncle candidates to report.
+	pub uncle_candidates: RwLock<Vec<UncleCandidate>>,
 	/// Is disabled
 	pub disabled: AtomicBool,
+	/// Is the block import paused
+	pub import_paused: AtomicBool,
 }
 
 /// Used for generating test client blocks.
@@ -186,9 +203,13 @@ impl TestBlockChainClient {
 			nonces: RwLock::new(HashMap::new()),
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
+			block_balances: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			struct_log_steps: RwLock::new(None),
+			struct_log_replays: AtomicUsize::new(0),
 			receipts: RwLock::new(HashMap::new()),
 			logs: RwLock::new(Vec::new()),
+			reconstructed_logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
 			miner: Arc::new(Miner::new_for_tests(&spec, None)),
 			spec: spec,
@@ -197,7 +218,9 @@ impl TestBlockChainClient {
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
+			uncle_candidates: RwLock::new(Vec::new()),
 			disabled: AtomicBool::new(false),
+			import_paused: AtomicBool::new(false),
 			error_on_logs: RwLock::new(None),
 		};
 
@@ -229,6 +252,12 @@ impl TestBlockChainClient {
 		self.nonces.write().insert(address, nonce);
 	}
 
+	/// Set balance of account `address` at block `hash` to `balance`, making the state of the
+	/// block readable.
+	pub fn set_block_balance(&self, hash: H256, address: Address, balance: U256) {
+		self.block_balances.write().entry(hash).or_default().insert(address, balance);
+	}
+
 	/// Set `code` at `address`.
 	pub fn set_code(&self, address: Address, code: Bytes) {
 		self.code.write().insert(address, code);
@@ -254,6 +283,11 @@ impl TestBlockChainClient {
 		*self.logs.write() = logs;
 	}
 
+	/// Set logs to return for each reconstructed logs call.
+	pub fn set_reconstructed_logs(&self, logs: Vec<LocalizedLogEntry>) {
+		*self.reconstructed_logs.write() = logs;
+	}
+
 	/// Set return errors on logs.
 	pub fn set_error_on_logs(&self, val: Option<BlockId>) {
 		*self.error_on_logs.write() = val;
@@ -461,6 +495,7 @@ impl Nonce for TestBlockChainClient {
 	fn nonce(&self, address: &Address, id: BlockId) -> Option<U256> {
 		match id {
 			BlockId::Latest => Some(self.nonces.read().get(address).cloned().unwrap_or(self.spec.params().account_start_nonce)),
+			BlockId::Hash(hash) if self.block_balances.read().contains_key(&hash) => Some(self.spec.params().account_start_nonce),
 			_ => None,
 		}
 	}
@@ -474,6 +509,8 @@ impl Balance for TestBlockChainClient {
 	fn balance(&self, address: &Address, state: StateOrBlock) -> Option<U256> {
 		match state {
 			StateOrBlock::Block(BlockId::Latest) | StateOrBlock::State(_) => Some(self.balances.read().get(address).cloned().unwrap_or_else(U256::zero)),
+			StateOrBlock::Block(BlockId::Hash(hash)) => self.block_balances.read().get(&hash)
+				.map(|balances| balances.get(address).cloned().unwrap_or_else(U256::zero)),
 			_ => None,
 		}
 	}
@@ -623,6 +660,10 @@ impl Call for TestBlockChainClient {
 		Ok(res)
 	}
 
+	fn call_gas_profile(&self, _t: &SignedTransaction, _state: &mut Self::State, _header: &Header) -> Result<Vec<GasProfile>, CallError> {
+		self.execution_result.read().clone().unwrap().map(|_| Vec::new())
+	}
+
 	fn estimate_gas(&self, _t: &SignedTransaction, _state: &Self::State, _header: &Header) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
@@ -630,6 +671,7 @@ impl Call for TestBlockChainClient {
 
 /// NewType wrapper around `()` to impersonate `State` in trait impls. State will not be used by
 /// test client, since all methods that accept state are mocked.
+#[derive(Clone)]
 pub struct TestState;
 
 impl StateInfo for TestState {
@@ -654,7 +696,62 @@ impl StateClient for TestBlockChainClient {
 
 impl EngineInfo for TestBlockChainClient {
 	fn engine(&self) -> &dyn Engine {
-		unimplemented!()
+		&*self.spec.engine
+	}
+}
+
+impl BlockChainReset for TestBlockChainClient {
+	fn reset(&self, num: u32) -> Result<(), String> {
+		let mut numbers = self.numbers.write();
+		let best = numbers.len() - 1;
+		if num == 0 || num as usize > best {
+			return Err(format!("Cannot reset the chain {} blocks back", num));
+		}
+
+		let mut blocks = self.blocks.write();
+		for n in (best + 1 - num as usize)..=best {
+			if let Some(hash) = numbers.remove(&n) {
+				blocks.remove(&hash);
+			}
+		}
+		*self.last_hash.write() = numbers[&(best - num as usize)];
+		Ok(())
+	}
+
+	fn rewind(&self, num: u32) -> Result<(), String> {
+		self.reset(num)?;
+		self.miner.clear();
+		if let Some(dev_mining) = self.spec.engine.dev_mining() {
+			dev_mining.rewound(self.chain_info().best_block_number);
+		}
+		Ok(())
+	}
+
+	fn pruning_history(&self) -> u64 {
+		self.history.read().unwrap_or(0)
+	}
+}
+
+impl ForkChoiceClient for TestBlockChainClient {
+	fn set_head(&self, head: H256, _finalized: Option<H256>) -> Result<(), String> {
+		let blocks = self.blocks.read();
+		let header = blocks.get(&head).map(|b| view!(BlockView, b).header()).ok_or_else(|| format!("Unknown block {:?}", head))?;
+		let mut numbers = self.numbers.write();
+		let number = header.number() as usize;
+		numbers.retain(|n, _| *n <= number);
+
+		let mut hash = head;
+		let mut n = number;
+		while numbers.get(&n) != Some(&hash) {
+			numbers.insert(n, hash);
+			if n == 0 {
+				break;
+			}
+			hash = *view!(BlockView, &blocks[&hash]).header().parent_hash();
+			n -= 1;
+		}
+		*self.last_hash.write() = head;
+		Ok(())
 	}
 }
 
@@ -671,6 +768,12 @@ impl BadBlocks for TestBlockChainClient {
 	}
 }
 
+impl UncleCandidates for TestBlockChainClient {
+	fn uncle_candidates(&self, _author: &Address) -> Vec<UncleCandidate> {
+		self.uncle_candidates.read().clone()
+	}
+}
+
 impl BlockChainClient for TestBlockChainClient {
 	fn replay(&self, _id: TransactionId, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
@@ -687,6 +790,26 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn replay_struct_logs(&self, _id: TransactionId, mut logger: StructLogger) -> Result<usize, CallError> {
+		use trace::VMTracer;
+
+		let steps = self.struct_log_steps.read().ok_or(CallError::TransactionNotFound)?;
+		self.struct_log_replays.fetch_add(1, AtomicOrder::SeqCst);
+		// one `PUSH1` per step, with the step number as program counter
+		for pc in 0..steps {
+			if !logger.trace_next_instruction(pc, 0x60, U256::from(1_000_000)) {
+				break;
+			}
+			logger.trace_prepare_execute(pc, 0x60, U256::from(3), None, None);
+			logger.trace_executed(U256::from(3), &[U256::from(pc)], &[]);
+		}
+		Ok(logger.drain().unwrap_or(0))
+	}
+
+	fn replay_gas_profile(&self, _id: TransactionId) -> Result<Vec<GasProfile>, CallError> {
+		Err(CallError::TransactionNotFound)
+	}
+
 	fn replay_block_transactions(&self, _block: BlockId, _analytics: CallAnalytics) -> Result<Box<dyn Iterator<Item = (H256, Executed)>>, CallError> {
 		Ok(Box::new(
 			self.traces
@@ -699,10 +822,22 @@ impl BlockChainClient for TestBlockChainClient {
 		))
 	}
 
+	fn execution_witness(&self, _block: BlockId) -> Result<Vec<Bytes>, CallError> {
+		Err(CallError::StatePruned)
+	}
+
+	fn state_diff(&self, _from: BlockId, _to: BlockId, _after: Option<&Address>, _count: u64) -> Result<(StateDiff, Option<Address>), CallError> {
+		Err(CallError::StatePruned)
+	}
+
 	fn block_total_difficulty(&self, _id: BlockId) -> Option<U256> {
 		Some(U256::zero())
 	}
 
+	fn block_finalized(&self, id: BlockId) -> Option<bool> {
+		self.block_hash(id).map(|_| false)
+	}
+
 	fn block_hash(&self, id: BlockId) -> Option<H256> {
 		Self::block_hash(self, id)
 	}
@@ -753,6 +888,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.receipts.read().get(&id).cloned()
 	}
 
+	fn confirmation_status(&self, _id: TransactionId) -> Option<ConfirmationStatus> {
+		None
+	}
+
 	fn localized_block_receipts(&self, _id: BlockId) -> Option<Vec<LocalizedReceipt>> {
 		Some(self.receipts.read().values().cloned().collect())
 	}
@@ -771,6 +910,10 @@ impl BlockChainClient for TestBlockChainClient {
 		})
 	}
 
+	fn reconstructed_logs(&self, _filter: Filter) -> Result<Vec<LocalizedLogEntry>, ReconstructLogsError> {
+		Ok(self.reconstructed_logs.read().clone())
+	}
+
 	fn last_hashes(&self) -> LastHashes {
 		unimplemented!();
 	}
@@ -901,6 +1044,32 @@ impl BlockChainClient for TestBlockChainClient {
 
 	fn set_spec_name(&self, _: String) -> Result<(), ()> { unimplemented!(); }
 
+	fn reload_engine_params(&self, update: &[u8], signature: H520) -> Result<(), Error> {
+		let reload = self.spec.engine.params_reload().ok_or(EngineError::ParamsReloadUnsupported)?;
+		reload.reload_params(update, &signature.into(), self.chain_info().best_block_number)
+	}
+
+	fn threat_status(&self) -> ThreatStatus {
+		ThreatStatus::default()
+	}
+
+	fn accept_reorg(&self, _hash: H256) -> Result<(), String> {
+		Err("No reorganization is held back".into())
+	}
+
+	fn rewrite_database(&self) -> Result<(), String> {
+		Ok(())
+	}
+
+	fn pause_import(&self, _timeout: Duration) -> Result<(), String> {
+		self.import_paused.store(true, AtomicOrder::Relaxed);
+		Ok(())
+	}
+
+	fn resume_import(&self) -> bool {
+		self.import_paused.swap(false, AtomicOrder::Relaxed)
+	}
+
 	fn disable(&self) { self.disabled.store(true, AtomicOrder::Relaxed); }
 
 	fn pruning_info(&self) -> PruningInfo {
@@ -911,6 +1080,10 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn validator_set_at(&self, _id: BlockId) -> Option<EpochValidators> {
+		None
+	}
+
 	fn create_transaction(&self, TransactionRequest { action, data, gas, gas_price, nonce }: TransactionRequest)
 		-> Result<SignedTransaction, transaction::Error>
 	{
@@ -962,6 +1135,14 @@ impl ProvingBlockChainClient for TestBlockChainClient {
 		None
 	}
 
+	fn prove_receipt(&self, _: H256) -> Option<ReceiptProof> {
+		None
+	}
+
+	fn prove_ancestry(&self, _: BlockNumber, _: BlockNumber) -> Option<AncestryProof> {
+		None
+	}
+
 	fn epoch_signal(&self, _: H256) -> Option<Vec<u8>> {
 		None
 	}
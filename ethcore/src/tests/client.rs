@@ -14,20 +14,36 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::str::{FromStr, from_utf8};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::{mem, thread};
 
 use account_state::state::StateInfo;
-use ethereum_types::{U256, Address};
+use bytes::Bytes;
+use db::Writable;
+use db::keys::BlockReceipts;
+use ethereum_types::{H64, H256, H264, U256, Address};
 use parity_crypto::publickey::KeyPair;
+use ethtrie::TrieDB;
 use hash::keccak;
+use hash_db::{HashDB, EMPTY_PREFIX};
 use io::IoChannel;
+use journaldb::new_memory_db;
+use kvdb::{DBOp, DBTransaction, DBValue, KeyValueDB};
+use kvdb_rocksdb::{Database, DatabaseConfig};
+use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
+use parking_lot::RwLock;
 use tempfile::TempDir;
 use types::{
 	data_format::DataFormat,
-	ids::BlockId,
+	ids::{BlockId, TransactionId},
 	transaction::{PendingTransaction, Transaction, Action, Condition},
-	filter::Filter,
+	filter::{Filter, ReconstructLogsError},
+	header::Header,
+	threat_status::ThreatLevel,
 	verification::Unverified,
 	view,
 	views::BlockView,
@@ -36,7 +52,7 @@ use types::{
 use client::{Client, ClientConfig, PrepareOpenBlock, ImportSealedBlock};
 use client_traits::{
 	BlockInfo, BlockChainClient, BlockChainReset, ChainInfo,
-	ImportExportBlocks, Tick, ImportBlock
+	ForkChoiceClient, ImportExportBlocks, Tick, ImportBlock
 };
 use machine::executive::{Executive, TransactOptions};
 use miner::{Miner, PendingOrdering, MinerService};
@@ -44,10 +60,12 @@ use account_state::{State, CleanupMode, backend};
 use test_helpers::{
 	self,
 	generate_dummy_client, push_blocks_to_client, get_test_client_with_blocks, get_good_dummy_block_seq,
-	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block
+	generate_dummy_client_with_data, generate_dummy_client_with_spec, get_good_dummy_block, get_bad_state_dummy_block,
+	create_test_block, push_block_with_transactions,
 };
 use rustc_hex::ToHex;
 use registrar::RegistrarClient;
+use trie::Trie;
 
 fn into_u256_vec<'a, T, I>(iter: I) -> Vec<U256>
 where
@@ -144,6 +162,30 @@ fn query_bad_block() {
 	assert!(bad_block.is_none());
 }
 
+#[test]
+fn dumps_root_mismatch_report() {
+	let dumps = TempDir::new().unwrap();
+	let spec = spec::new_test();
+	let client = Client::new(
+		ClientConfig { root_mismatch_dumps: Some(dumps.path().to_owned()), ..Default::default() },
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let bad_block = Unverified::from_rlp(get_bad_state_dummy_block()).unwrap();
+	let hash = bad_block.header.hash();
+	client.import_block(bad_block).unwrap();
+	client.flush_queue();
+	assert!(client.block_header(BlockId::Hash(hash)).is_none());
+
+	let report = ::std::fs::read(dumps.path().join(format!("1-{:x}.json", hash))).unwrap();
+	let report: ::serde_json::Value = ::serde_json::from_slice(&report).unwrap();
+	assert_eq!(report["stateRoot"]["expected"], format!("{:#x}", H256::from_low_u64_be(0xbad)));
+	assert_eq!(report["suspectTransaction"], ::serde_json::Value::Null);
+}
+
 #[test]
 fn returns_chain_info() {
 	let dummy_block = get_good_dummy_block();
@@ -181,6 +223,112 @@ fn returns_logs_with_limit() {
 	assert_eq!(logs.len(), 0);
 }
 
+/// Client whose best block created a contract logging once, with the receipts of that block
+/// removed from the database. Returns the hash of that transaction.
+fn client_without_receipts(config: ClientConfig, empty_blocks: usize) -> (Arc<Client>, H256) {
+	let spec = spec::new_test();
+	let db = test_helpers::new_db();
+	let new_client = |db| Client::new(
+		config.clone(),
+		&spec,
+		db,
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let client = new_client(db.clone());
+	let key = KeyPair::from_secret(keccak("test").into()).unwrap();
+	let transaction = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100_000.into(),
+		action: Action::Create,
+		value: 0.into(),
+		// PUSH1 0 PUSH1 0 LOG0
+		data: vec![0x60, 0x00, 0x60, 0x00, 0xa0],
+	}.sign(key.secret(), None);
+	push_block_with_transactions(&client, &[transaction.clone()]);
+	let hash = client.chain_info().best_block_hash;
+	for _ in 0..empty_blocks {
+		push_block_with_transactions(&client, &[]);
+	}
+	assert_eq!(client.logs(all_logs()).unwrap().len(), 1);
+	drop(client);
+
+	// a new client doesn't have the receipts cached.
+	let mut batch = DBTransaction::new();
+	Writable::delete::<BlockReceipts, H264>(&mut batch, ::db::COL_EXTRA, &hash);
+	db.key_value().write(batch).unwrap();
+	(new_client(db), transaction.hash())
+}
+
+fn all_logs() -> Filter {
+	Filter {
+		from_block: BlockId::Earliest,
+		to_block: BlockId::Latest,
+		address: None,
+		topics: vec![],
+		limit: None,
+	}
+}
+
+#[test]
+fn reconstructs_logs_of_blocks_without_receipts() {
+	let (client, transaction_hash) = client_without_receipts(ClientConfig::default(), 0);
+	assert!(client.logs(all_logs()).unwrap().is_empty());
+
+	let logs = client.reconstructed_logs(all_logs()).unwrap();
+	assert_eq!(logs.len(), 1);
+	assert_eq!(logs[0].block_number, 1);
+	assert_eq!(logs[0].transaction_hash, transaction_hash);
+	assert_eq!(logs[0].log_index, 0);
+}
+
+#[test]
+fn fails_to_reconstruct_logs_of_blocks_with_pruned_state() {
+	let config = ClientConfig { history: 8, history_mem: 0, ..Default::default() };
+	let (client, _) = client_without_receipts(config, 12);
+	assert!(client.pruning_info().earliest_state > 0);
+
+	assert_eq!(client.reconstructed_logs(all_logs()), Err(ReconstructLogsError::StatePruned(1, 1)));
+}
+
+#[test]
+fn rebuilds_pruned_states_from_checkpoints() {
+	let spec = spec::new_test();
+	let config = ClientConfig { history: 8, history_mem: 0, state_checkpoint_interval: 4, ..Default::default() };
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+	let key = KeyPair::from_secret(keccak("test").into()).unwrap();
+	for nonce in 0..20u64 {
+		let transaction = Transaction {
+			nonce: nonce.into(),
+			gas_price: 0.into(),
+			gas: 21000.into(),
+			action: Action::Call(Address::zero()),
+			value: 0.into(),
+			data: Vec::new(),
+		}.sign(key.secret(), None);
+		push_block_with_transactions(&client, &[transaction]);
+		if nonce == 3 {
+			// let the copy of the state of block 4 finish, it keeps the state from being pruned meanwhile.
+			thread::sleep(Duration::from_millis(500));
+		}
+	}
+	assert!(client.pruning_info().earliest_state > 5);
+
+	// block 5 is re-executed on top of the copy of block 4.
+	let state = client.state_at(BlockId::Number(5)).unwrap();
+	assert_eq!(state.nonce(&key.address()).unwrap(), 5.into());
+	// there's no copy before block 2.
+	assert!(client.state_at(BlockId::Number(2)).is_none());
+}
+
 #[test]
 fn returns_block_body() {
 	let dummy_block = get_good_dummy_block();
@@ -201,6 +349,386 @@ fn imports_block_sequence() {
 	assert!(!block.into_inner().is_empty());
 }
 
+/// Key-value store keeping the written changes in memory until they are flushed, like the
+/// RocksDB backend does. Once crashed, nothing reaches the flushed store anymore.
+struct BufferedKeyValueDB {
+	flushed: Arc<dyn KeyValueDB>,
+	buffered: RwLock<Vec<DBOp>>,
+	flushes: AtomicUsize,
+	crashed: AtomicBool,
+	failing: AtomicBool,
+}
+
+impl BufferedKeyValueDB {
+	fn new(flushed: Arc<dyn KeyValueDB>) -> Arc<Self> {
+		Arc::new(BufferedKeyValueDB {
+			flushed,
+			buffered: RwLock::new(Vec::new()),
+			flushes: AtomicUsize::new(0),
+			crashed: AtomicBool::new(false),
+			failing: AtomicBool::new(false),
+		})
+	}
+
+	fn flushes(&self) -> usize {
+		self.flushes.load(Ordering::SeqCst)
+	}
+
+	fn crash(&self) {
+		self.crashed.store(true, Ordering::SeqCst);
+		self.buffered.write().clear();
+	}
+
+	fn fail_writes(&self, fail: bool) {
+		self.failing.store(fail, Ordering::SeqCst);
+	}
+}
+
+impl MallocSizeOf for BufferedKeyValueDB {
+	fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
+		0
+	}
+}
+
+impl KeyValueDB for BufferedKeyValueDB {
+	fn get(&self, col: u32, key: &[u8]) -> ::std::io::Result<Option<DBValue>> {
+		let buffered = self.buffered.read().iter().rev().find(|op| op.col() == col && op.key() == key).cloned();
+		match buffered {
+			Some(DBOp::Insert { value, .. }) => Ok(Some(value)),
+			Some(DBOp::Delete { .. }) => Ok(None),
+			None => self.flushed.get(col, key),
+		}
+	}
+
+	fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.flushed.get_by_prefix(col, prefix)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		if !self.crashed.load(Ordering::SeqCst) {
+			self.buffered.write().extend(transaction.ops);
+		}
+	}
+
+	fn flush(&self) -> ::std::io::Result<()> {
+		let ops = mem::replace(&mut *self.buffered.write(), Vec::new());
+		self.flushes.fetch_add(1, Ordering::SeqCst);
+		if self.failing.load(Ordering::SeqCst) {
+			return Err(::std::io::Error::new(::std::io::ErrorKind::Other, "write failed"));
+		}
+		self.flushed.write(DBTransaction { ops })
+	}
+
+	fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.flushed.iter(col)
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: u32, prefix: &'a [u8]) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.flushed.iter_from_prefix(col, prefix)
+	}
+
+	fn restore(&self, new_db: &str) -> ::std::io::Result<()> {
+		self.flushed.restore(new_db)
+	}
+}
+
+/// Client importing one block per round and writing the changes of 4 blocks at once.
+fn new_batching_client(key_value: Arc<BufferedKeyValueDB>) -> Arc<Client> {
+	let spec = spec::new_test();
+	let config = ClientConfig {
+		max_round_blocks_to_import: 1,
+		commit_batch_blocks: 4,
+		..Default::default()
+	};
+	Client::new(
+		config,
+		&spec,
+		test_helpers::new_db_with_key_value(key_value),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap()
+}
+
+/// Queues the blocks and waits until they are all verified.
+fn queue_verified_blocks(client: &Client, blocks: &[Vec<u8>]) {
+	for block in blocks {
+		client.import_block(Unverified::from_rlp(block.clone()).unwrap()).unwrap();
+	}
+	while client.queue_info().verified_queue_size < blocks.len() {
+		thread::sleep(Duration::from_millis(10));
+	}
+}
+
+#[test]
+fn batches_database_writes_while_syncing() {
+	let db = BufferedKeyValueDB::new(Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS)));
+	let client = new_batching_client(db.clone());
+	let blocks = get_good_dummy_block_seq(5);
+	queue_verified_blocks(&client, &blocks);
+
+	let flushes = db.flushes();
+	for _ in 0..3 {
+		assert_eq!(client.import_verified_blocks(), 1);
+	}
+	assert_eq!(client.chain_info().best_block_number, 3);
+	assert_eq!(db.flushes(), flushes);
+
+	// the fourth block completes the batch.
+	assert_eq!(client.import_verified_blocks(), 1);
+	assert_eq!(db.flushes(), flushes + 1);
+
+	// the last block is written as soon as the queue runs dry.
+	assert_eq!(client.import_verified_blocks(), 1);
+	assert_eq!(db.flushes(), flushes + 1);
+	assert_eq!(client.import_verified_blocks(), 1);
+	assert_eq!(db.flushes(), flushes + 2);
+	assert_eq!(client.chain_info().best_block_number, 6);
+}
+
+#[test]
+fn resumes_import_after_crash_with_buffered_blocks() {
+	let flushed: Arc<dyn KeyValueDB> = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS));
+	let db = BufferedKeyValueDB::new(flushed.clone());
+	let client = new_batching_client(db.clone());
+	let blocks = get_good_dummy_block_seq(5);
+	queue_verified_blocks(&client, &blocks);
+
+	for _ in 0..5 {
+		assert_eq!(client.import_verified_blocks(), 1);
+	}
+	assert_eq!(client.chain_info().best_block_number, 5);
+	db.crash();
+	drop(client);
+
+	// the database holds the first batch, whose state is complete.
+	let client = new_batching_client(BufferedKeyValueDB::new(flushed));
+	assert_eq!(client.chain_info().best_block_number, 4);
+	assert!(client.block_header(BlockId::Number(5)).is_none());
+	assert!(client.state_at(BlockId::Latest).is_some());
+
+	queue_verified_blocks(&client, &blocks[4..]);
+	client.flush_queue();
+	assert_eq!(client.chain_info().best_block_number, 6);
+}
+
+#[test]
+fn rolls_back_blocks_whose_write_failed() {
+	let tempdir = TempDir::new().unwrap();
+	let config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let rocksdb = Database::open(&config, tempdir.path().to_str().unwrap()).unwrap();
+	let db = BufferedKeyValueDB::new(Arc::new(rocksdb));
+	let spec = spec::new_test();
+	let config = ClientConfig { max_round_blocks_to_import: 1, commit_pipeline: true, ..Default::default() };
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db_with_key_value(db.clone()),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+	let blocks = get_good_dummy_block_seq(1);
+	queue_verified_blocks(&client, &blocks);
+
+	assert_eq!(client.import_verified_blocks(), 1);
+	client.flush_database().unwrap();
+
+	db.fail_writes(true);
+	assert_eq!(client.import_verified_blocks(), 1);
+	assert_eq!(client.chain_info().best_block_number, 2);
+	assert!(client.flush_database().is_err());
+
+	// the next round drops the block which is not in the database.
+	assert_eq!(client.import_verified_blocks(), 0);
+	assert_eq!(client.chain_info().best_block_number, 1);
+	assert!(client.block_header(BlockId::Number(2)).is_none());
+	assert!(client.state_at(BlockId::Latest).is_some());
+
+	db.fail_writes(false);
+	queue_verified_blocks(&client, &blocks[1..]);
+	client.flush_queue();
+	client.flush_database().unwrap();
+	assert_eq!(client.chain_info().best_block_number, 2);
+}
+
+#[test]
+fn pausing_stops_concurrent_import() {
+	let spec = spec::new_test();
+	let config = ClientConfig { max_round_blocks_to_import: 1, ..Default::default() };
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+	let blocks = get_good_dummy_block_seq(19);
+	queue_verified_blocks(&client, &blocks);
+
+	let importer = {
+		let client = client.clone();
+		thread::spawn(move || while client.import_verified_blocks() > 0 {})
+	};
+	while client.chain_info().best_block_number == 0 {
+		thread::yield_now();
+	}
+	client.pause_import(Duration::from_secs(60)).unwrap();
+
+	// no round waiting for the import lock may go on once the pause is in place.
+	let paused_at = client.chain_info().best_block_number;
+	importer.join().unwrap();
+	assert_eq!(client.chain_info().best_block_number, paused_at);
+	assert_eq!(client.import_verified_blocks(), 0);
+
+	assert!(client.resume_import());
+	client.flush_queue();
+	assert_eq!(client.chain_info().best_block_number, 20);
+}
+
+#[test]
+fn draining_paused_import_returns_early() {
+	let client = get_test_client_with_blocks(Vec::new());
+	let blocks = get_good_dummy_block_seq(2);
+	queue_verified_blocks(&client, &blocks);
+	client.pause_import(Duration::from_secs(60)).unwrap();
+
+	let started = Instant::now();
+	assert!(!client.drain_queue(started + Duration::from_secs(60)));
+	assert!(started.elapsed() < Duration::from_secs(1));
+	assert_eq!(client.chain_info().best_block_number, 0);
+
+	assert!(client.resume_import());
+	assert!(client.drain_queue(Instant::now() + Duration::from_secs(60)));
+	assert_eq!(client.chain_info().best_block_number, 3);
+}
+
+#[test]
+fn returns_execution_witness() {
+	let client = generate_dummy_client_with_data(3, 2, &into_u256_vec(&[1, 2, 3]));
+	let parent = client.block_header(BlockId::Number(1)).unwrap();
+
+	let witness = client.execution_witness(BlockId::Number(2)).unwrap();
+	assert!(witness.iter().any(|node| keccak(node) == parent.state_root()));
+	assert!(client.execution_witness(BlockId::Number(0)).unwrap().is_empty());
+	assert!(client.execution_witness(BlockId::Number(10)).is_err());
+}
+
+#[test]
+fn returns_confirmation_status() {
+	let client = generate_dummy_client_with_data(3, 2, &into_u256_vec(&[1, 2, 3]));
+	let block = client.block(BlockId::Number(1)).unwrap();
+	let transaction = block.transaction_hashes()[0];
+
+	let status = client.confirmation_status(TransactionId::Hash(transaction)).unwrap();
+	assert_eq!(status.block_hash, block.hash());
+	assert_eq!(status.block_number, 1);
+	assert_eq!(status.depth, 3);
+	assert_eq!(status.total_difficulty, client.chain_info().total_difficulty);
+	assert_eq!(status.competing_branch, None);
+	assert!(!status.finalized);
+
+	assert!(client.confirmation_status(TransactionId::Hash(H256::from_low_u64_be(1))).is_none());
+}
+
+#[test]
+fn proves_receipts_and_transactions_against_header_roots() {
+	let client = generate_dummy_client_with_data(3, 2, &into_u256_vec(&[1, 2, 3]));
+	let block = client.block(BlockId::Number(2)).unwrap();
+	let header = block.header_view();
+	let transaction = block.transaction_hashes()[1];
+
+	let proof = client.prove_receipt(transaction).unwrap();
+	assert_eq!(proof.block_hash, block.hash());
+	assert_eq!(proof.transaction_index, 1);
+	assert_eq!(proof.receipts_root, header.receipts_root());
+	assert_eq!(proof.transactions_root, header.transactions_root());
+
+	// only the nodes of the proof are needed to find the items under the header roots.
+	let prove = |root: &H256, nodes: &[Bytes]| {
+		let mut db = new_memory_db();
+		for node in nodes {
+			db.insert(EMPTY_PREFIX, node);
+		}
+		let db: &dyn HashDB<_, _> = &db;
+		let trie = TrieDB::new(&db, root).unwrap();
+		trie.get(&rlp::encode(&proof.transaction_index)).unwrap().map(|value| value.to_vec())
+	};
+	let stored = &client.block_receipts(&block.hash()).unwrap().receipts[1];
+	assert_eq!(prove(&header.receipts_root(), &proof.receipt_proof), Some(rlp::encode(stored)));
+	assert_eq!(prove(&header.transactions_root(), &proof.transaction_proof), Some(block.transactions_rlp().at(1).unwrap().as_raw().to_vec()));
+	assert_eq!(proof.receipt, rlp::encode(stored));
+
+	assert!(client.prove_receipt(H256::from_low_u64_be(1)).is_none());
+}
+
+#[test]
+fn confirmation_status_reports_competing_branch() {
+	let client = generate_dummy_client(0);
+	push_blocks_to_client(&client, 53, 1, 3);
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(3, client.chain_info().best_block_number);
+
+	// A lighter sibling of the second block.
+	let parent = client.block_header(BlockId::Number(1)).unwrap();
+	let mut header = Header::new();
+	header.set_gas_limit(parent.gas_limit());
+	header.set_difficulty(U256::from(0x20000));
+	header.set_timestamp(parent.timestamp() + 7);
+	header.set_number(2);
+	header.set_parent_hash(parent.hash());
+	header.set_state_root(parent.state_root());
+	client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(3, client.chain_info().best_block_number);
+
+	let status = client.confirmation_status(TransactionId::Location(BlockId::Number(2), 0)).unwrap();
+	let branch = status.competing_branch.unwrap();
+	assert_eq!(branch.hash, header.hash());
+	assert_eq!(branch.fork_number, 1);
+	assert_eq!(branch.length, 1);
+	assert!(branch.total_difficulty < status.total_difficulty);
+
+	let status = client.confirmation_status(TransactionId::Location(BlockId::Number(1), 0)).unwrap();
+	assert_eq!(status.competing_branch, None);
+}
+
+#[test]
+fn returns_paginated_state_diff() {
+	let client = generate_dummy_client_with_data(3, 2, &into_u256_vec(&[1, 2, 3]));
+
+	let (diff, next) = client.state_diff(BlockId::Number(0), BlockId::Number(3), None, 100).unwrap();
+	assert!(next.is_none());
+	assert!(!diff.raw.is_empty());
+
+	let mut paged = BTreeMap::new();
+	let mut after = None;
+	loop {
+		let (page, next) = client.state_diff(BlockId::Number(0), BlockId::Number(3), after.as_ref(), 1).unwrap();
+		assert!(page.raw.len() <= 1);
+		paged.extend(page.raw);
+		match next {
+			Some(next) => after = Some(next),
+			None => break,
+		}
+	}
+	assert_eq!(paged, diff.raw);
+
+	let (unchanged, _) = client.state_diff(BlockId::Number(2), BlockId::Number(2), None, 100).unwrap();
+	assert!(unchanged.raw.is_empty());
+	assert!(client.state_diff(BlockId::Number(0), BlockId::Number(10), None, 100).is_err());
+}
+
+#[test]
+fn state_diff_includes_changes_made_when_closing_blocks() {
+	let client = test_helpers::generate_dummy_client_with_spec_and_data(spec::new_test_with_reward, 2, 0, &[], false);
+	let author = client.block_header(BlockId::Number(2)).unwrap().author();
+
+	let (diff, next) = client.state_diff(BlockId::Number(0), BlockId::Number(2), None, 100).unwrap();
+	assert!(next.is_none());
+	assert!(!diff.raw[&author].balance.is_same());
+}
+
 #[test]
 fn can_collect_garbage() {
 	let client = generate_dummy_client(100);
@@ -258,6 +786,59 @@ fn can_handle_long_fork() {
 	assert_eq!(2000, client.chain_info().best_block_number);
 }
 
+#[test]
+fn holds_reorgs_deeper_than_allowed() {
+	let spec = spec::new_test();
+	let mut config = ClientConfig::default();
+	config.max_reorg_depth = Some(2);
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let genesis = spec.genesis_header();
+	let import_branch = |timestamp_salt: u64, count: u64| {
+		let mut parent = genesis.hash();
+		let mut hashes = Vec::new();
+		for number in 1..=count {
+			let mut header = Header::new();
+			header.set_gas_limit(*genesis.gas_limit());
+			header.set_difficulty(U256::from(0x20000));
+			header.set_timestamp(timestamp_salt + number * 10);
+			header.set_number(number);
+			header.set_parent_hash(parent);
+			header.set_state_root(*genesis.state_root());
+			parent = header.hash();
+			hashes.push(parent);
+			client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()).unwrap();
+		}
+		client.flush_queue();
+		hashes
+	};
+
+	let canon = import_branch(0, 3);
+	assert_eq!(client.chain_info().best_block_hash, canon[2]);
+
+	// the heavier branch retracts three blocks, it is imported but the head stays and the import
+	// pauses.
+	let branch = import_branch(5, 5);
+	assert_eq!(client.chain_info().best_block_hash, canon[2]);
+	assert!(client.block_header(BlockId::Hash(branch[3])).is_some());
+	assert!(client.accept_reorg(canon[1]).is_err());
+	assert!(client.accept_reorg(genesis.hash()).is_err());
+	assert_eq!(client.threat_status().level, ThreatLevel::High);
+	assert!(client.threat_status().held_blocks >= 1);
+
+	client.accept_reorg(branch[0]).unwrap();
+	client.flush_queue();
+	assert_eq!(client.chain_info().best_block_hash, branch[4]);
+	assert_eq!(client.block_hash(BlockId::Number(3)), Some(branch[2]));
+	assert!(client.accept_reorg(branch[4]).is_err());
+}
+
 #[test]
 fn can_mine() {
 	let dummy_blocks = get_good_dummy_block_seq(2);
@@ -399,6 +980,49 @@ fn reset_blockchain() {
 	assert!(client.block_header(BlockId::Number(15)).is_some());
 }
 
+#[test]
+fn rewind_blockchain() {
+	let client = get_test_client_with_blocks(get_good_dummy_block_seq(19));
+	assert_eq!(client.chain_info().best_block_number, 20);
+
+	assert!(client.rewind(5).is_ok());
+
+	assert_eq!(client.chain_info().best_block_number, 15);
+	assert!(client.block_header(BlockId::Number(16)).is_none());
+	assert_eq!(client.block_header(BlockId::Number(15)).unwrap().hash(), client.best_block_header().hash());
+	assert!(client.rewind(0).is_err());
+}
+
+#[test]
+fn set_head_after_terminal_total_difficulty() {
+	let client = generate_dummy_client_with_spec(spec::new_test_merge);
+	client.engine().register_client(Arc::downgrade(&client) as _);
+	let genesis_hash = client.chain_info().best_block_hash;
+
+	for number in 1..3 {
+		let b = client.prepare_open_block(Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock().unwrap();
+		// the engine fills in the base fee, the consensus client would provide `prevRandao`.
+		let seal = b.header.seal().to_vec();
+		assert_eq!(seal.len(), 3);
+		let b = b.seal(client.engine(), seal).unwrap();
+		assert!(b.header.difficulty().is_zero());
+		let hash = client.import_sealed_block(b).unwrap();
+
+		// proof-of-stake blocks only become the best block on a fork choice update.
+		assert_eq!(client.chain_info().best_block_number, number - 1);
+		client.set_head(hash, Some(genesis_hash)).unwrap();
+		assert_eq!(client.chain_info().best_block_hash, hash);
+		assert_eq!(client.block_hash(BlockId::Number(number)), Some(hash));
+	}
+
+	// moving the head back to a canonical ancestor is a no-op.
+	let best_hash = client.chain_info().best_block_hash;
+	client.set_head(genesis_hash, None).unwrap();
+	assert_eq!(client.chain_info().best_block_hash, best_hash);
+	assert!(client.set_head(H256::repeat_byte(1), None).is_err());
+}
+
 #[test]
 fn import_export_hex() {
 	let client = get_test_client_with_blocks(get_good_dummy_block_seq(19));
@@ -474,3 +1098,20 @@ fn import_export_binary() {
 	assert!(client.block_header(BlockId::Number(17)).is_some());
 	assert!(client.block_header(BlockId::Number(16)).is_some());
 }
+
+#[test]
+fn returns_validator_set_of_genesis_epoch() {
+	let client = generate_dummy_client_with_spec(spec::new_test_round);
+	let genesis_hash = client.block_hash(BlockId::Number(0)).unwrap();
+
+	let epoch = client.validator_set_at(BlockId::Latest).unwrap();
+	assert_eq!(epoch.transition_hash, genesis_hash);
+	assert_eq!(epoch.transition_number, 0);
+	assert_eq!(epoch.validators, vec![
+		Address::from_str("7d577a597b2742b498cb5cf0c26cdcd726d39e6e").unwrap(),
+		Address::from_str("82a978b3f5962a5b0957d9ee9eef472ee55b42f1").unwrap(),
+	]);
+
+	assert!(generate_dummy_client(1).validator_set_at(BlockId::Latest).is_none());
+	assert!(client.validator_set_at(BlockId::Number(100)).is_none());
+}
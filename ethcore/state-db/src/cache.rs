@@ -0,0 +1,327 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Memory bounded caches backing the shared state cache.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use account_state::Account;
+use common_types::client_types::CacheCategoryStats;
+use ethereum_types::{Address, H256};
+use lru_cache::LruCache;
+
+/// Share of the state cache memory given to every category, in percent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheRatios {
+	/// Share of the accounts cache.
+	pub accounts: usize,
+	/// Share of the account storage cache.
+	pub storage: usize,
+	/// Share of the contract code cache.
+	pub code: usize,
+}
+
+impl Default for CacheRatios {
+	fn default() -> Self {
+		CacheRatios {
+			accounts: 25,
+			storage: 60,
+			code: 15,
+		}
+	}
+}
+
+/// Memory budget of every state cache category, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheBudget {
+	/// Budget of the accounts cache.
+	pub accounts: usize,
+	/// Budget of the account storage cache.
+	pub storage: usize,
+	/// Budget of the contract code cache.
+	pub code: usize,
+}
+
+impl CacheBudget {
+	/// Split `total` bytes between the categories according to the given ratios.
+	pub fn new(total: usize, ratios: CacheRatios) -> Self {
+		let ratios = match ratios.accounts + ratios.storage + ratios.code {
+			0 => CacheRatios::default(),
+			_ => ratios,
+		};
+		let sum = ratios.accounts + ratios.storage + ratios.code;
+		let accounts = total / sum * ratios.accounts;
+		let storage = total / sum * ratios.storage;
+
+		CacheBudget {
+			accounts,
+			storage,
+			code: total - accounts - storage,
+		}
+	}
+
+	/// Total memory budget of the cache.
+	pub fn total(&self) -> usize {
+		self.accounts + self.storage + self.code
+	}
+}
+
+/// Hit and miss counters of a cache category.
+#[derive(Default)]
+pub struct HitCounter {
+	hits: AtomicU64,
+	misses: AtomicU64,
+}
+
+impl HitCounter {
+	/// Note the outcome of a cache lookup.
+	pub fn note(&self, hit: bool) {
+		match hit {
+			true => self.hits.fetch_add(1, Ordering::Relaxed),
+			false => self.misses.fetch_add(1, Ordering::Relaxed),
+		};
+	}
+
+	/// Statistics of the category, including the given memory size and budget.
+	pub fn stats(&self, size: usize, budget: usize) -> CacheCategoryStats {
+		CacheCategoryStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			size,
+			budget,
+		}
+	}
+}
+
+/// Cached account. `None` indicates that the account is known to be missing.
+struct Entry {
+	account: Option<Account>,
+	storage_size: usize,
+}
+
+impl Entry {
+	fn new(account: Option<Account>) -> Self {
+		let storage_size = account.as_ref().map_or(0, Account::storage_cache_size);
+		Entry { account, storage_size }
+	}
+}
+
+/// LRU cache of accounts bounded by the memory used by the accounts themselves
+/// and by their cached storage. Least recently used accounts are evicted until
+/// both the accounts and the storage budgets are met.
+pub struct AccountLru {
+	inner: LruCache<Address, Entry>,
+	accounts_size: usize,
+	storage_size: usize,
+	accounts_budget: usize,
+	storage_budget: usize,
+}
+
+// memory used by a single account entry, excluding its storage.
+const ACCOUNT_ENTRY_SIZE: usize = ::std::mem::size_of::<Address>() + ::std::mem::size_of::<Entry>();
+
+impl AccountLru {
+	/// Create a new cache with the accounts and storage budgets given in bytes.
+	pub fn new(accounts_budget: usize, storage_budget: usize) -> Self {
+		AccountLru {
+			// eviction is driven by the memory used, not the number of items.
+			inner: LruCache::new(usize::max_value()),
+			accounts_size: 0,
+			storage_size: 0,
+			accounts_budget,
+			storage_budget,
+		}
+	}
+
+	/// Insert an account, replacing any previously cached value.
+	pub fn insert(&mut self, address: Address, account: Option<Account>) {
+		let entry = Entry::new(account);
+		self.accounts_size += ACCOUNT_ENTRY_SIZE;
+		self.storage_size += entry.storage_size;
+		if let Some(old) = self.inner.insert(address, entry) {
+			self.accounts_size -= ACCOUNT_ENTRY_SIZE;
+			self.storage_size -= old.storage_size;
+		}
+		self.trim();
+	}
+
+	/// Call `f` with the cached account, marking it as recently used. The memory used by the
+	/// account storage is recalculated afterwards, since `f` may cache more storage entries.
+	/// Returns `None` if the account is not in the cache.
+	pub fn with_account<F, U>(&mut self, address: &Address, f: F) -> Option<U>
+		where F: FnOnce(&mut Option<Account>) -> U
+	{
+		let (result, old_size, new_size) = {
+			let entry = self.inner.get_mut(address)?;
+			let result = f(&mut entry.account);
+			let old_size = entry.storage_size;
+			entry.storage_size = entry.account.as_ref().map_or(0, Account::storage_cache_size);
+			(result, old_size, entry.storage_size)
+		};
+
+		self.storage_size = self.storage_size - old_size + new_size;
+		self.trim();
+		Some(result)
+	}
+
+	/// Returns true if the account is cached, marking it as recently used.
+	pub fn contains(&mut self, address: &Address) -> bool {
+		self.inner.get_mut(address).is_some()
+	}
+
+	/// Returns true if the account is cached and known to exist, marking it as recently used.
+	pub fn contains_existing(&mut self, address: &Address) -> bool {
+		self.inner.get_mut(address).map_or(false, |entry| entry.account.is_some())
+	}
+
+	/// Remove an account from the cache.
+	pub fn remove(&mut self, address: &Address) {
+		if let Some(entry) = self.inner.remove(address) {
+			self.accounts_size -= ACCOUNT_ENTRY_SIZE;
+			self.storage_size -= entry.storage_size;
+		}
+	}
+
+	/// Remove all the accounts.
+	pub fn clear(&mut self) {
+		self.inner.clear();
+		self.accounts_size = 0;
+		self.storage_size = 0;
+	}
+
+	/// Number of cached accounts.
+	pub fn len(&self) -> usize {
+		self.inner.len()
+	}
+
+	/// Memory used by the cached accounts, excluding their storage, in bytes.
+	pub fn accounts_size(&self) -> usize {
+		self.accounts_size
+	}
+
+	/// Memory used by the cached account storage, in bytes.
+	pub fn storage_size(&self) -> usize {
+		self.storage_size
+	}
+
+	fn trim(&mut self) {
+		while self.accounts_size > self.accounts_budget || self.storage_size > self.storage_budget {
+			match self.inner.remove_lru() {
+				Some((_, entry)) => {
+					self.accounts_size -= ACCOUNT_ENTRY_SIZE;
+					self.storage_size -= entry.storage_size;
+				},
+				None => break,
+			}
+		}
+	}
+}
+
+/// Maximal number of contracts whose storage keys are remembered.
+const MAX_HOT_CONTRACTS: usize = 4096;
+/// Maximal number of storage keys remembered for a single contract.
+const MAX_HOT_KEYS: usize = 32;
+
+/// Storage keys recently touched in contracts. Remembered independently of the cached
+/// accounts, so that the storage can be loaded again after the account has been evicted.
+pub struct HotStorage {
+	inner: LruCache<Address, Vec<H256>>,
+}
+
+impl Default for HotStorage {
+	fn default() -> Self {
+		HotStorage {
+			inner: LruCache::new(MAX_HOT_CONTRACTS),
+		}
+	}
+}
+
+impl HotStorage {
+	/// Note storage keys touched in a contract, replacing the previously noted ones.
+	pub fn note(&mut self, address: Address, mut keys: Vec<H256>) {
+		if keys.is_empty() {
+			return;
+		}
+		keys.truncate(MAX_HOT_KEYS);
+		self.inner.insert(address, keys);
+	}
+
+	/// Storage keys recently touched in a contract.
+	pub fn keys(&mut self, address: &Address) -> Vec<H256> {
+		self.inner.get_mut(address).cloned().unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn splits_budget_by_ratios() {
+		let budget = CacheBudget::new(1000, CacheRatios { accounts: 1, storage: 2, code: 1 });
+		assert_eq!(budget, CacheBudget { accounts: 250, storage: 500, code: 250 });
+		assert_eq!(budget.total(), 1000);
+
+		let fallback = CacheBudget::new(100, CacheRatios { accounts: 0, storage: 0, code: 0 });
+		assert_eq!(fallback, CacheBudget::new(100, CacheRatios::default()));
+	}
+
+	#[test]
+	fn evicts_by_account_budget() {
+		let mut cache = AccountLru::new(ACCOUNT_ENTRY_SIZE * 2, 0);
+		let (a, b, c) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2), Address::from_low_u64_be(3));
+		cache.insert(a, None);
+		cache.insert(b, Some(Account::new_basic(1.into(), 0.into())));
+		assert!(cache.with_account(&a, |_| ()).is_some());
+		cache.insert(c, None);
+
+		assert_eq!(cache.len(), 2);
+		assert!(cache.with_account(&b, |_| ()).is_none());
+		assert_eq!(cache.accounts_size(), ACCOUNT_ENTRY_SIZE * 2);
+	}
+
+	#[test]
+	fn evicts_by_storage_budget() {
+		let entry_size = 2 * ::std::mem::size_of::<H256>();
+		let mut cache = AccountLru::new(usize::max_value(), entry_size);
+		let (a, b) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+		cache.insert(a, Some(Account::new_basic(1.into(), 0.into())));
+		cache.insert(b, Some(Account::new_basic(2.into(), 0.into())));
+		assert_eq!(cache.storage_size(), 0);
+
+		cache.with_account(&a, |acc| acc.as_mut().unwrap().set_storage(H256::zero(), H256::zero()));
+		assert_eq!(cache.storage_size(), entry_size);
+		assert_eq!(cache.len(), 2);
+
+		cache.with_account(&b, |acc| acc.as_mut().unwrap().set_storage(H256::zero(), H256::zero()));
+		assert_eq!(cache.storage_size(), entry_size);
+		assert!(cache.with_account(&a, |_| ()).is_none());
+		assert!(cache.with_account(&b, |_| ()).is_some());
+	}
+
+	#[test]
+	fn remembers_bounded_hot_storage() {
+		let mut hot = HotStorage::default();
+		let address = Address::from_low_u64_be(1);
+		hot.note(address, (0..MAX_HOT_KEYS as u64 + 8).map(H256::from_low_u64_be).collect());
+		assert_eq!(hot.keys(&address).len(), MAX_HOT_KEYS);
+
+		// an empty set of keys doesn't wipe the noted ones.
+		hot.note(address, Vec::new());
+		assert_eq!(hot.keys(&address)[0], H256::from_low_u64_be(0));
+		assert!(hot.keys(&Address::from_low_u64_be(2)).is_empty());
+	}
+}
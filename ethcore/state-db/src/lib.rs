@@ -24,29 +24,31 @@ use ethereum_types::{Address, H256};
 use hash_db::HashDB;
 use kvdb::{DBTransaction, DBValue};
 use log::trace;
-use lru_cache::LruCache;
 use parking_lot::Mutex;
 
-use account_state::{self, Account};
+use account_state::{self, Account, ForkSource};
 use common_types::BlockNumber;
-use journaldb::JournalDB;
+use common_types::client_types::{CacheCategoryStats, StateCacheStats};
+use journaldb::{JournalDB, Recorder, RecordingDB};
 use keccak_hasher::KeccakHasher;
 use memory_cache::MemoryLruCache;
 
-const STATE_CACHE_BLOCKS: usize = 12;
+mod cache;
+
+pub use crate::cache::{CacheBudget, CacheRatios};
+use crate::cache::{AccountLru, HitCounter, HotStorage};
 
-// The percentage of supplied cache size to go to accounts.
-const ACCOUNT_CACHE_RATIO: usize = 90;
+const STATE_CACHE_BLOCKS: usize = 12;
 
 /// Shared canonical state cache.
 struct AccountCache {
-	/// DB Account cache. `None` indicates that account is known to be missing.
-	// When changing the type of the values here, be sure to update `mem_used` and
-	// `new`.
-	accounts: LruCache<Address, Option<Account>>,
+	/// DB Account cache bounded by the memory used by accounts and their storage.
+	accounts: AccountLru,
 	/// Information on the modifications in recently committed blocks; specifically which addresses
 	/// changed in which block. Ordered by block number.
 	modifications: VecDeque<BlockChanges>,
+	/// Storage keys recently touched in contracts.
+	hot_storage: HotStorage,
 }
 
 /// Buffered account cache item.
@@ -60,6 +62,13 @@ struct CacheQueueItem {
 	modified: bool,
 }
 
+/// Hit and miss counters of the shared state cache.
+#[derive(Default)]
+struct CacheMetrics {
+	accounts: HitCounter,
+	code: HitCounter,
+}
+
 #[derive(Debug)]
 /// Accumulates a list of accounts changed in a block.
 struct BlockChanges {
@@ -96,9 +105,11 @@ pub struct StateDB {
 	account_cache: Arc<Mutex<AccountCache>>,
 	/// DB Code cache. Maps code hashes to shared bytes.
 	code_cache: Arc<Mutex<MemoryLruCache<H256, Arc<Vec<u8>>>>>,
+	/// Hit and miss counters of the shared caches.
+	metrics: Arc<CacheMetrics>,
 	/// Local dirty cache.
 	local_cache: Vec<CacheQueueItem>,
-	cache_size: usize,
+	budget: CacheBudget,
 	/// Hash of the block on top of which this instance was created or
 	/// `None` if cache is disabled
 	parent_hash: Option<H256>,
@@ -106,6 +117,10 @@ pub struct StateDB {
 	commit_hash: Option<H256>,
 	/// Number of the committing block or `None` if not committed yet.
 	commit_number: Option<BlockNumber>,
+	/// Whether reads are recorded, in which case the shared caches are bypassed.
+	recording: bool,
+	/// Remote state the chain is forked off, if any.
+	fork: Option<Arc<dyn ForkSource>>,
 }
 
 impl Clone for StateDB {
@@ -116,26 +131,31 @@ impl Clone for StateDB {
 
 impl StateDB {
 	/// Create a new instance wrapping `JournalDB` and the maximum allowed size
-	/// of the LRU cache in bytes. Actual used memory may (read: will) be higher due to bookkeeping.
-	// TODO: make the cache size actually accurate by moving the account storage cache
-	// into the `AccountCache` structure as its own `LruCache<(Address, H256), H256>`.
+	/// of the LRU caches in bytes, split between the categories in default ratios.
+	/// Actual used memory may (read: will) be higher due to bookkeeping.
 	pub fn new(db: Box<dyn JournalDB>, cache_size: usize) -> StateDB {
-		let acc_cache_size = cache_size * ACCOUNT_CACHE_RATIO / 100;
-		let code_cache_size = cache_size - acc_cache_size;
-		let cache_items = acc_cache_size / ::std::mem::size_of::<Option<Account>>();
+		Self::with_budget(db, CacheBudget::new(cache_size, CacheRatios::default()))
+	}
 
+	/// Create a new instance wrapping `JournalDB` with the given memory budget
+	/// of every cache category.
+	pub fn with_budget(db: Box<dyn JournalDB>, budget: CacheBudget) -> StateDB {
 		StateDB {
 			db,
 			account_cache: Arc::new(Mutex::new(AccountCache {
-				accounts: LruCache::new(cache_items),
+				accounts: AccountLru::new(budget.accounts, budget.storage),
 				modifications: VecDeque::new(),
+				hot_storage: HotStorage::default(),
 			})),
-			code_cache: Arc::new(Mutex::new(MemoryLruCache::new(code_cache_size))),
+			code_cache: Arc::new(Mutex::new(MemoryLruCache::new(budget.code))),
+			metrics: Arc::new(CacheMetrics::default()),
 			local_cache: Vec::new(),
-			cache_size,
+			budget,
 			parent_hash: None,
 			commit_hash: None,
 			commit_number: None,
+			recording: false,
+			fork: None,
 		}
 	}
 
@@ -220,13 +240,16 @@ impl StateDB {
 				}
 				if is_best {
 					let acc = account.account.0;
-					if let Some(&mut Some(ref mut existing)) = cache.accounts.get_mut(&account.address) {
-						if let Some(new) = acc {
-							if account.modified {
-								existing.overwrite_with(new);
-							}
-							continue;
+					if let Some(ref acc) = acc {
+						cache.hot_storage.note(account.address, acc.cached_storage_keys());
+					}
+					if acc.is_some() && cache.accounts.contains_existing(&account.address) {
+						if let (true, Some(new)) = (account.modified, acc) {
+							cache.accounts.with_account(&account.address, |existing| {
+								existing.as_mut().expect("account is known to exist; qed").overwrite_with(new)
+							});
 						}
+						continue;
 					}
 					cache.accounts.insert(account.address, acc);
 				}
@@ -266,28 +289,59 @@ impl StateDB {
 			db: self.db.boxed_clone(),
 			account_cache: self.account_cache.clone(),
 			code_cache: self.code_cache.clone(),
+			metrics: self.metrics.clone(),
 			local_cache: Vec::new(),
-			cache_size: self.cache_size,
+			budget: self.budget,
 			parent_hash: None,
 			commit_hash: None,
 			commit_number: None,
+			recording: self.recording,
+			fork: self.fork.clone(),
 		}
 	}
 
+	/// Clone the database, recording the trie nodes and code read through the clone.
+	/// The shared caches are bypassed so that every read reaches the database.
+	pub fn recording_clone(&self) -> (StateDB, Recorder) {
+		let (db, recorder) = RecordingDB::new(self.db.boxed_clone());
+		let state_db = StateDB {
+			db: Box::new(db),
+			account_cache: self.account_cache.clone(),
+			code_cache: self.code_cache.clone(),
+			metrics: self.metrics.clone(),
+			local_cache: Vec::new(),
+			budget: self.budget,
+			parent_hash: None,
+			commit_hash: None,
+			commit_number: None,
+			recording: true,
+			fork: self.fork.clone(),
+		};
+		(state_db, recorder)
+	}
+
 	/// Clone the database for a canonical state.
 	pub fn boxed_clone_canon(&self, parent: &H256) -> StateDB {
 		StateDB {
 			db: self.db.boxed_clone(),
 			account_cache: self.account_cache.clone(),
 			code_cache: self.code_cache.clone(),
+			metrics: self.metrics.clone(),
 			local_cache: Vec::new(),
-			cache_size: self.cache_size,
+			budget: self.budget,
 			parent_hash: Some(parent.clone()),
 			commit_hash: None,
 			commit_number: None,
+			recording: self.recording,
+			fork: self.fork.clone(),
 		}
 	}
 
+	/// Read the accounts and storage missing from the database from `fork`. Clones share it.
+	pub fn set_fork(&mut self, fork: Arc<dyn ForkSource>) {
+		self.fork = Some(fork);
+	}
+
 	/// Check if pruning is enabled on the database.
 	pub fn is_prunable(&self) -> bool {
 		self.db.is_prunable()
@@ -297,9 +351,32 @@ impl StateDB {
 	pub fn mem_used(&self) -> usize {
 		// TODO: account for LRU-cache overhead; this is a close approximation.
 		self.db.mem_used() + {
-			let accounts = self.account_cache.lock().accounts.len();
+			let (accounts_size, storage_size) = {
+				let cache = self.account_cache.lock();
+				(cache.accounts.accounts_size(), cache.accounts.storage_size())
+			};
 			let code_size = self.code_cache.lock().current_size();
-			code_size + accounts * ::std::mem::size_of::<Option<Account>>()
+			accounts_size + storage_size + code_size
+		}
+	}
+
+	/// Hit and miss statistics and memory usage of the shared state cache.
+	pub fn cache_stats(&self) -> StateCacheStats {
+		let (accounts_size, storage_size) = {
+			let cache = self.account_cache.lock();
+			(cache.accounts.accounts_size(), cache.accounts.storage_size())
+		};
+		let code_size = self.code_cache.lock().current_size();
+
+		StateCacheStats {
+			accounts: self.metrics.accounts.stats(accounts_size, self.budget.accounts),
+			// storage is cached together with its account, so lookups are counted as account hits.
+			storage: CacheCategoryStats {
+				size: storage_size,
+				budget: self.budget.storage,
+				..Default::default()
+			},
+			code: self.metrics.code.stats(code_size, self.budget.code),
 		}
 	}
 
@@ -308,9 +385,59 @@ impl StateDB {
 		&*self.db
 	}
 
-	/// Query how much memory is set aside for the accounts cache (in bytes).
+	/// Returns underlying `JournalDB` mutably.
+	pub fn journal_db_mut(&mut self) -> &mut dyn JournalDB {
+		&mut *self.db
+	}
+
+	/// Query how much memory is set aside for the state caches (in bytes).
 	pub fn cache_size(&self) -> usize {
-		self.cache_size
+		self.budget.total()
+	}
+
+	/// Memory budget of every state cache category.
+	pub fn cache_budget(&self) -> CacheBudget {
+		self.budget
+	}
+
+	/// Returns true if the account is in the shared cache, regardless of the block it is valid for.
+	pub fn is_account_cached(&self, address: &Address) -> bool {
+		self.account_cache.lock().accounts.contains(address)
+	}
+
+	/// Storage keys recently touched in the given contract.
+	pub fn hot_storage_keys(&self, address: &Address) -> Vec<H256> {
+		self.account_cache.lock().hot_storage.keys(address)
+	}
+
+	/// Insert an account loaded from the state of the canonical block `at` into the shared cache,
+	/// ahead of the execution of the blocks building on top of it. The account is dropped if it's
+	/// already cached or if it has been modified by a canonical block committed after `at`.
+	/// Returns true if the account was inserted.
+	pub fn prefetch_account(&self, at: &H256, address: Address, account: Option<Account>) -> bool {
+		let mut cache = self.account_cache.lock();
+		if cache.accounts.contains(&address) || !Self::is_current(&address, at, &cache.modifications) {
+			return false;
+		}
+		cache.accounts.insert(address, account);
+		true
+	}
+
+	/// Check if the account loaded from the state of block `at` still matches the latest canonical state.
+	fn is_current(addr: &Address, at: &H256, modifications: &VecDeque<BlockChanges>) -> bool {
+		if modifications.is_empty() {
+			return true;
+		}
+		for m in modifications.iter().filter(|m| m.is_canon) {
+			if &m.hash == at {
+				return true;
+			}
+			if m.accounts.contains(addr) {
+				trace!("Prefetched {:?} skipped: modified after {:?}", addr, at);
+				return false;
+			}
+		}
+		false
 	}
 
 	/// Check if the account can be returned from cache by matching current block parent hash against canonical
@@ -364,31 +491,45 @@ impl account_state::Backend for StateDB {
 	}
 
 	fn get_cached_account(&self, addr: &Address) -> Option<Option<Account>> {
-		self.parent_hash.as_ref().and_then(|parent_hash| {
+		self.parent_hash.as_ref().filter(|_| !self.recording).and_then(|parent_hash| {
 			let mut cache = self.account_cache.lock();
 			if !Self::is_allowed(addr, parent_hash, &cache.modifications) {
 				return None;
 			}
-			cache.accounts.get_mut(addr).map(|a| a.as_ref().map(|a| a.clone_basic()))
+			let result = cache.accounts.with_account(addr, |a| a.as_ref().map(|a| a.clone_basic()));
+			self.metrics.accounts.note(result.is_some());
+			result
 		})
 	}
 
 	fn get_cached<F, U>(&self, a: &Address, f: F) -> Option<U>
 		where F: FnOnce(Option<&mut Account>) -> U
 	{
-		self.parent_hash.as_ref().and_then(|parent_hash| {
+		self.parent_hash.as_ref().filter(|_| !self.recording).and_then(|parent_hash| {
 			let mut cache = self.account_cache.lock();
 			if !Self::is_allowed(a, parent_hash, &cache.modifications) {
 				return None;
 			}
-			cache.accounts.get_mut(a).map(|c| f(c.as_mut()))
+			let result = cache.accounts.with_account(a, |c| f(c.as_mut()));
+			self.metrics.accounts.note(result.is_some());
+			result
 		})
 	}
 
 	fn get_cached_code(&self, hash: &H256) -> Option<Arc<Vec<u8>>> {
+		if self.recording {
+			return None;
+		}
+
 		let mut cache = self.code_cache.lock();
 
-		cache.get_mut(hash).map(|code| code.clone())
+		let result = cache.get_mut(hash).map(|code| code.clone());
+		self.metrics.code.note(result.is_some());
+		result
+	}
+
+	fn fork(&self) -> Option<&dyn ForkSource> {
+		self.fork.as_ref().map(|fork| &**fork)
 	}
 }
 
@@ -473,4 +614,37 @@ mod tests {
 		let s = state_db.boxed_clone_canon(&h3a);
 		assert!(s.get_cached_account(&address).is_none());
 	}
+
+	#[test]
+	fn prefetch_skips_accounts_modified_later() {
+		let state_db = get_temp_state_db();
+		let (modified, untouched) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+		let (h0, h1, h2) = (H256::random(), H256::random(), H256::random());
+		let mut batch = DBTransaction::new();
+
+		let mut s = state_db.boxed_clone_canon(&H256::random());
+		s.journal_under(&mut batch, 0, &h0).unwrap();
+		s.sync_cache(&[], &[], true);
+
+		// h1 is not canonical yet, so the state of h0 is still the latest one.
+		let mut s = state_db.boxed_clone_canon(&h0);
+		s.add_to_account_cache(modified, Some(Account::new_basic(3.into(), 0.into())), true);
+		s.journal_under(&mut batch, 1, &h1).unwrap();
+		s.sync_cache(&[], &[], false);
+		assert!(state_db.prefetch_account(&h0, untouched, Some(Account::new_basic(1.into(), 0.into()))));
+		assert!(!state_db.prefetch_account(&h0, untouched, None));
+
+		let mut s = state_db.boxed_clone_canon(&h1);
+		s.journal_under(&mut batch, 2, &h2).unwrap();
+		s.sync_cache(&[h1, h2], &[], true);
+
+		// the account was modified in h1, so its value at h0 is stale.
+		assert!(!state_db.prefetch_account(&h0, modified, Some(Account::new_basic(2.into(), 0.into()))));
+		assert!(!state_db.prefetch_account(&H256::random(), modified, None));
+		assert!(state_db.prefetch_account(&h1, modified, Some(Account::new_basic(3.into(), 0.into()))));
+
+		let s = state_db.boxed_clone_canon(&h2);
+		assert_eq!(s.get_cached_account(&untouched).unwrap().unwrap().balance(), &U256::from(1));
+		assert_eq!(s.get_cached_account(&modified).unwrap().unwrap().balance(), &U256::from(3));
+	}
 }
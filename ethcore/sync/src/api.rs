@@ -27,7 +27,7 @@ use crate::sync_io::NetSyncIo;
 use crate::light_sync::{self, SyncInfo};
 use crate::private_tx::PrivateTxHandler;
 use crate::chain::{
-	fork_filter::ForkFilterApi,
+	fork_filter::{ForkFilterApi, ForkStatus},
 	sync_packet::SyncPacket::{PrivateTransactionPacket, SignedPrivateTransactionPacket},
 	ChainSyncApi, SyncState, SyncStatus as EthSyncStatus,
 	ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64,
@@ -47,14 +47,14 @@ use light::client::AsLightClient;
 use light::Provider;
 use light::net::{
 	self as light_net, LightProtocol, Params as LightParams,
-	Capabilities, Handler as LightHandler, EventContext, SampleStore,
+	Capabilities, Handler as LightHandler, EventContext, SampleStore, ServingStats,
 };
 use log::{trace, warn};
 use network::{
 	client_version::ClientVersion,
 	NetworkProtocolHandler, NetworkContext, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, Error,
-	ConnectionFilter, IpFilter, NatType
+	ConnectionFilter, IpFilter, NatStatus, NatType, PeerDiversity
 };
 use snapshot::SnapshotService;
 use parking_lot::{RwLock, Mutex};
@@ -129,6 +129,8 @@ pub struct SyncConfig {
 	pub warp_sync: WarpSync,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Period after which local transactions are sent again to peers which already received them.
+	pub local_transactions_rebroadcast: Option<Duration>,
 }
 
 impl Default for SyncConfig {
@@ -142,6 +144,7 @@ impl Default for SyncConfig {
 			fork_block: None,
 			warp_sync: WarpSync::Disabled,
 			serve_light: false,
+			local_transactions_rebroadcast: None,
 		}
 	}
 }
@@ -168,6 +171,16 @@ pub trait SyncProvider: Send + Sync {
 
 	/// are we in the middle of a major sync?
 	fn is_major_syncing(&self) -> bool;
+
+	/// Statistics of the light protocol requests served to peers, if serving light clients.
+	fn light_serving_stats(&self) -> Option<ServingStats> {
+		None
+	}
+
+	/// Fork identification (EIP-2124) of the local chain, if syncing over the eth protocol.
+	fn fork_status(&self) -> Option<ForkStatus> {
+		None
+	}
 }
 
 /// Transaction stats
@@ -456,9 +469,17 @@ impl SyncProvider for EthSync {
 		self.eth_handler.sync.write().sync_notifications()
 	}
 
+	fn fork_status(&self) -> Option<ForkStatus> {
+		Some(self.eth_handler.sync.fork_status(&*self.eth_handler.chain))
+	}
+
 	fn is_major_syncing(&self) -> bool {
 		self.is_major_syncing.load(Ordering::SeqCst)
 	}
+
+	fn light_serving_stats(&self) -> Option<ServingStats> {
+		self.light_proto.as_ref().map(|lp| lp.serving_stats())
+	}
 }
 
 const PEERS_TIMER: TimerToken = 0;
@@ -681,8 +702,12 @@ pub trait ManageNetwork: Send + Sync {
 	fn stop_network(&self);
 	/// Returns the minimum and maximum peers.
 	fn num_peers_range(&self) -> RangeInclusive<u32>;
+	/// Change the minimum and maximum peers without restarting the network.
+	fn set_peer_limits(&self, min_peers: u32, max_peers: u32);
 	/// Get network context for protocol.
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut dyn FnMut(&dyn NetworkContext));
+	/// Returns the state of the NAT port mapping, if the network is running.
+	fn nat_status(&self) -> Option<NatStatus>;
 }
 
 impl ManageNetwork for EthSync {
@@ -727,9 +752,17 @@ impl ManageNetwork for EthSync {
 		self.network.num_peers_range()
 	}
 
+	fn set_peer_limits(&self, min_peers: u32, max_peers: u32) {
+		self.network.set_peer_limits(min_peers, max_peers);
+	}
+
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut dyn FnMut(&dyn NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
+
+	fn nat_status(&self) -> Option<NatStatus> {
+		self.network.nat_status()
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -769,6 +802,8 @@ pub struct NetworkConfiguration {
 	pub allow_non_reserved: bool,
 	/// IP Filtering
 	pub ip_filter: IpFilter,
+	/// Limits on peers sharing a network or client implementation
+	pub peer_diversity: PeerDiversity,
 	/// Client version string
 	pub client_version: String,
 }
@@ -807,6 +842,7 @@ impl NetworkConfiguration {
 			},
 			reserved_nodes: self.reserved_nodes,
 			ip_filter: self.ip_filter,
+			peer_diversity: self.peer_diversity,
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
 			client_version: self.client_version,
 		})
@@ -832,6 +868,7 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			snapshot_peers: *other.reserved_protocols.get(&WARP_SYNC_PROTOCOL_ID).unwrap_or(&0),
 			reserved_nodes: other.reserved_nodes,
 			ip_filter: other.ip_filter,
+			peer_diversity: other.peer_diversity,
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
 			client_version: other.client_version,
 		}
@@ -1027,9 +1064,17 @@ impl ManageNetwork for LightSync {
 		self.network.num_peers_range()
 	}
 
+	fn set_peer_limits(&self, min_peers: u32, max_peers: u32) {
+		self.network.set_peer_limits(min_peers, max_peers);
+	}
+
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut dyn FnMut(&dyn NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
+
+	fn nat_status(&self) -> Option<NatStatus> {
+		self.network.nat_status()
+	}
 }
 
 impl LightSyncProvider for LightSync {
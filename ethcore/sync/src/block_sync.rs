@@ -36,7 +36,7 @@ use common_types::{
 	BlockNumber,
 	block_status::BlockStatus,
 	ids::BlockId,
-	errors::{EthcoreError, BlockError, ImportError},
+	errors::{EthcoreError, BlockError, EngineError, ImportError},
 };
 
 const MAX_HEADERS_TO_REQUEST: usize = 128;
@@ -591,6 +591,10 @@ impl BlockDownloader {
 					debug_sync!(self, "Block temporarily invalid: {:?}, restarting sync", h);
 					break;
 				},
+				Err(EthcoreError::Engine(EngineError::ExternalConsensusUnavailable(ref e))) => {
+					debug_sync!(self, "Block {:?} can't be verified for now: {}, restarting sync", h, e);
+					break;
+				},
 				Err(EthcoreError::FullQueue(limit)) => {
 					debug_sync!(self, "Block import queue full ({}), restarting sync", limit);
 					download_action = DownloadAction::Reset;
@@ -18,34 +18,50 @@
 //! to support Ethereum network protocol, version 64 and above.
 
 // Re-export ethereum-forkid crate contents here.
-pub use ethereum_forkid::{BlockNumber, ForkId, RejectReason};
+pub use ethereum_forkid::{BlockNumber, ForkHash, ForkId, RejectReason};
+
+use std::collections::BTreeSet;
 
 use client_traits::ChainInfo;
 use ethereum_forkid::ForkFilter;
 use parity_util_mem::MallocSizeOf;
 
+/// Fork identification of the local chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForkStatus {
+	/// Current fork id, as announced to peers.
+	pub fork_id: ForkId,
+	/// Block numbers of all forks of the chain, including scheduled ones.
+	pub forks: Vec<BlockNumber>,
+	/// Number of peers rejected because of an incompatible fork id.
+	pub rejected_peers: usize,
+}
+
 /// Wrapper around fork filter that provides integration with `ForkFilter`.
 #[derive(MallocSizeOf)]
 pub struct ForkFilterApi {
 	inner: ForkFilter,
+	forks: Vec<BlockNumber>,
+	rejected_peers: usize,
 }
 
 impl ForkFilterApi {
 	/// Create `ForkFilterApi` from `ChainInfo` and an `Iterator` over the hard forks.
 	pub fn new<C: ?Sized + ChainInfo, I: IntoIterator<Item = BlockNumber>>(client: &C, forks: I) -> Self {
 		let chain_info = client.chain_info();
+		// same as the fork filter, forks at genesis don't change the fork id
+		let forks = forks.into_iter().filter(|block| *block > 0).collect::<BTreeSet<_>>();
 		Self {
-			inner: ForkFilter::new(chain_info.best_block_number, chain_info.genesis_hash, forks),
+			inner: ForkFilter::new(chain_info.best_block_number, chain_info.genesis_hash, forks.iter().cloned()),
+			forks: forks.into_iter().collect(),
+			rejected_peers: 0,
 		}
 	}
 
 	#[cfg(test)]
 	/// Dummy version of ForkFilterApi with no forks.
 	pub fn new_dummy<C: ?Sized + ChainInfo>(client: &C) -> Self {
-		let chain_info = client.chain_info();
-		Self {
-			inner: ForkFilter::new(chain_info.best_block_number, chain_info.genesis_hash, vec![]),
-		}
+		Self::new(client, vec![])
 	}
 
 	fn update_head<C: ?Sized + ChainInfo>(&mut self, client: &C) {
@@ -58,10 +74,23 @@ impl ForkFilterApi {
 		self.inner.current()
 	}
 
-	/// Wrapper for `ForkFilter::is_compatible`
+	/// Wrapper for `ForkFilter::is_compatible`, counting rejections.
 	pub fn is_compatible<C: ?Sized + ChainInfo>(&mut self, client: &C, fork_id: ForkId) -> Result<(), RejectReason> {
 		self.update_head(client);
-		self.inner.is_compatible(fork_id)
+		let result = self.inner.is_compatible(fork_id);
+		if result.is_err() {
+			self.rejected_peers += 1;
+		}
+		result
+	}
+
+	/// Current fork id along with the forks it is derived from.
+	pub fn status<C: ?Sized + ChainInfo>(&mut self, client: &C) -> ForkStatus {
+		ForkStatus {
+			fork_id: self.current(client),
+			forks: self.forks.clone(),
+			rejected_peers: self.rejected_peers,
+		}
 	}
 }
 
@@ -79,8 +108,15 @@ mod tests {
 
 		assert_eq!(
 			ForkFilterApi::new(&client, spec_forks).inner,
-			ForkFilter::new(0, genesis_hash, forks)
+			ForkFilter::new(0, genesis_hash, forks.clone())
 		);
+
+		// light clients must announce and accept the same fork ids
+		for head in forks.iter().flat_map(|fork| vec![fork - 1, *fork]) {
+			let expected = ForkFilter::new(head, genesis_hash, forks.clone()).current();
+			let light = light_verification::forkid::fork_id(&genesis_hash, &forks, head);
+			assert_eq!((light.hash.0, light.next), (expected.hash.0, expected.next), "head {}", head);
+		}
 	}
 
 	#[test]
@@ -131,6 +167,24 @@ mod tests {
 		)
 	}
 
+	#[test]
+	fn status_includes_scheduled_forks_and_rejections() {
+		let spec = spec::new_goerli(&String::new());
+		let client = TestBlockChainClient::new_with_spec(spec);
+		let mut filter = ForkFilterApi::new(&client, vec![0, 1_561_651, 1_561_651, 9_000_000]);
+
+		let status = filter.status(&client);
+		assert_eq!(status.forks, vec![1_561_651, 9_000_000]);
+		assert_eq!(status.fork_id.next, 1_561_651);
+		assert_eq!(status.rejected_peers, 0);
+
+		let mut foreign = status.fork_id;
+		foreign.hash.0 ^= 1;
+		assert!(filter.is_compatible(&client, foreign).is_err());
+		assert!(filter.is_compatible(&client, status.fork_id).is_ok());
+		assert_eq!(filter.status(&client).rejected_peers, 1);
+	}
+
 	#[test]
 	fn goerli_spec() {
 		test_spec(
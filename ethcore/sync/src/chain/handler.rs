@@ -41,7 +41,7 @@ use bytes::Bytes;
 use num_traits::FromPrimitive;
 use ethereum_types::{H256, U256};
 use keccak_hash::keccak;
-use network::PeerId;
+use network::{PeerId, DisconnectReason};
 use network::client_version::ClientVersion;
 use log::{debug, trace, error, warn};
 use rlp::Rlp;
@@ -645,8 +645,9 @@ impl SyncHandler {
 			return Err(DownloaderImportError::Invalid);
 		}
 		if let Some((fork_id, reason)) = forkid_validation_error {
-			trace!(target: "sync", "Peer {} incompatible fork id (fork id: {:#x}/{}, error: {:?})", peer_id, fork_id.hash.0, fork_id.next, reason);
-			return Err(DownloaderImportError::Invalid);
+			debug!(target: "sync", "Peer {} incompatible fork id (fork id: {:#x}/{}, error: {:?})", peer_id, fork_id.hash.0, fork_id.next, reason);
+			io.disable_peer_with_reason(peer_id, DisconnectReason::SubprotocolError);
+			return Err(DownloaderImportError::Useless);
 		}
 
 		if false
@@ -104,7 +104,7 @@ use crate::{
 	ETH_PROTOCOL, EthProtocolInfo as PeerInfoDigest, PriorityTask, SyncConfig, WarpSync, WARP_SYNC_PROTOCOL_ID,
 	api::{Notification, PRIORITY_TIMER_INTERVAL},
 	block_sync::{BlockDownloader, DownloadAction},
-	chain::fork_filter::ForkFilterApi,
+	chain::fork_filter::{ForkFilterApi, ForkStatus},
 	sync_io::SyncIo,
 	snapshot_sync::Snapshot,
 	transactions_stats::{TransactionsStats, Stats as TransactionStats},
@@ -455,6 +455,11 @@ impl ChainSyncApi {
 		self.sync.read().status()
 	}
 
+	/// Returns the fork identification of the local chain
+	pub fn fork_status(&self, chain: &dyn BlockChainClient) -> ForkStatus {
+		self.sync.write().fork_filter.status(chain)
+	}
+
 	/// Returns transactions propagation statistics
 	pub fn transactions_stats(&self) -> BTreeMap<H256, crate::api::TransactionStats> {
 		self.sync.read().transactions_stats()
@@ -683,6 +688,10 @@ pub struct ChainSync {
 	private_tx_handler: Option<Arc<dyn PrivateTxHandler>>,
 	/// Enable warp sync.
 	warp_sync: WarpSync,
+	/// Period after which local transactions are rebroadcast to all peers.
+	local_transactions_rebroadcast: Option<Duration>,
+	/// Last time local transactions were rebroadcast.
+	last_local_transactions_rebroadcast: Instant,
 
 	#[ignore_malloc_size_of = "mpsc unmettered, ignoring"]
 	status_sinks: Vec<futures_mpsc::UnboundedSender<SyncState>>
@@ -719,6 +728,8 @@ impl ChainSync {
 			transactions_stats: TransactionsStats::default(),
 			private_tx_handler,
 			warp_sync: config.warp_sync,
+			local_transactions_rebroadcast: config.local_transactions_rebroadcast,
+			last_local_transactions_rebroadcast: Instant::now(),
 			status_sinks: Vec::new()
 		};
 		sync.update_targets(chain);
@@ -1461,6 +1472,17 @@ impl ChainSync {
 		SyncHandler::on_peer_connected(self, io, peer);
 	}
 
+	/// Returns true if local transactions are due to be rebroadcast to all peers.
+	fn local_transactions_rebroadcast_due(&mut self) -> bool {
+		match self.local_transactions_rebroadcast {
+			Some(period) if self.last_local_transactions_rebroadcast.elapsed() >= period => {
+				self.last_local_transactions_rebroadcast = Instant::now();
+				true
+			},
+			_ => false,
+		}
+	}
+
 	/// Propagates new transactions to all peers.
 	/// Called every `TX_TIMER` (1.3sec).
 	pub fn propagate_new_transactions(&mut self, io: &mut dyn SyncIo) {
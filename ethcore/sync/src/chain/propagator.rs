@@ -112,6 +112,19 @@ impl SyncPropagator {
 			return 0;
 		}
 
+		if sync.local_transactions_rebroadcast_due() {
+			let local = transactions.iter()
+				.filter(|tx| tx.is_local())
+				.map(|tx| tx.signed().hash())
+				.collect::<Vec<_>>();
+			trace!(target: "sync", "Re-broadcasting {} local transactions to all peers.", local.len());
+			for peer_info in sync.peers.values_mut() {
+				for hash in &local {
+					peer_info.last_sent_transactions.remove(hash);
+				}
+			}
+		}
+
 		let (transactions, service_transactions): (Vec<_>, Vec<_>) = transactions.iter()
 			.map(|tx| tx.signed())
 			.partition(|tx| !tx.gas_price.is_zero());
@@ -40,6 +40,7 @@ mod tests;
 
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
+pub use chain::fork_filter::{ForkHash, ForkId, ForkStatus};
 pub use devp2p::validate_node_url;
-pub use network::{NonReservedPeerMode, Error, ConnectionFilter, ConnectionDirection};
+pub use network::{NonReservedPeerMode, Error, ConnectionFilter, ConnectionDirection, NatStatus, NatType};
 pub use private_tx::{PrivateTxHandler, NoopPrivateTxHandler, SimplePrivateTxHandler};
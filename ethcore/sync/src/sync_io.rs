@@ -23,7 +23,7 @@ use bytes::Bytes;
 use client_traits::BlockChainClient;
 use ethcore_private_tx::PrivateStateDB;
 use network::client_version::ClientVersion;
-use network::{NetworkContext, PeerId, PacketId, Error, SessionInfo, ProtocolId};
+use network::{NetworkContext, PeerId, PacketId, Error, SessionInfo, ProtocolId, DisconnectReason};
 use parking_lot::RwLock;
 use snapshot::SnapshotService;
 use common_types::BlockNumber;
@@ -34,6 +34,8 @@ use common_types::BlockNumber;
 pub trait SyncIo {
 	/// Disable a peer
 	fn disable_peer(&mut self, peer_id: PeerId);
+	/// Disable a peer, telling it why
+	fn disable_peer_with_reason(&mut self, peer_id: PeerId, reason: DisconnectReason);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
@@ -99,6 +101,10 @@ impl<'s> SyncIo for NetSyncIo<'s> {
 		self.network.disable_peer(peer_id);
 	}
 
+	fn disable_peer_with_reason(&mut self, peer_id: PeerId, reason: DisconnectReason) {
+		self.network.disable_peer_with_reason(peer_id, reason);
+	}
+
 	fn disconnect_peer(&mut self, peer_id: PeerId) {
 		self.network.disconnect_peer(peer_id);
 	}
@@ -111,6 +111,10 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		self.disconnect_peer(peer_id);
 	}
 
+	fn disable_peer_with_reason(&mut self, peer_id: PeerId, _reason: network::DisconnectReason) {
+		self.disconnect_peer(peer_id);
+	}
+
 	fn disconnect_peer(&mut self, peer_id: PeerId) {
 		self.to_disconnect.insert(peer_id);
 	}
@@ -15,7 +15,8 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Trace database.
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
 use ethcore_blockchain::{BlockProvider, BlockChainDB, TransactionAddress};
@@ -24,23 +25,81 @@ use ethcore_db::{
 	cache_manager::CacheManager,
 	Key, Writable, Readable, CacheUpdatePolicy,
 };
-use ethereum_types::{H256, H264};
+use ethereum_types::{Address, H256, H264};
 use kvdb::DBTransaction;
+use log::info;
 use parity_util_mem::MallocSizeOfExt;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rlp_derive::{RlpEncodable, RlpDecodable};
 
 use crate::{
 	BlockNumber,
 	LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportRequest,
 	flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces},
+	trace::{Action, Res},
 };
 
 const TRACE_DB_VER: &'static [u8] = b"1.0";
+/// Key of the address index status.
+const ADDRESS_INDEX_KEY: &'static [u8] = b"address-index";
+/// Index entries of an address are looked up in buckets of `2^ADDRESS_INDEX_BUCKET_BITS` blocks.
+const ADDRESS_INDEX_BUCKET_BITS: u32 = 16;
+/// Length of the keys of address index entries: index, address and block number.
+const ADDRESS_INDEX_KEY_LEN: usize = 1 + 20 + 8;
 
 #[derive(Debug, Copy, Clone)]
 enum TraceDBIndex {
 	/// Block traces index.
 	BlockTraces = 0,
+	/// Address to trace locations index.
+	AddressTraces = 1,
+}
+
+/// Key of the locations of the traces of a block involving an address.
+fn address_index_key(address: &Address, block_number: BlockNumber) -> [u8; ADDRESS_INDEX_KEY_LEN] {
+	let mut key = [0u8; ADDRESS_INDEX_KEY_LEN];
+	key[0] = TraceDBIndex::AddressTraces as u8;
+	key[1..21].copy_from_slice(address.as_bytes());
+	key[21..].copy_from_slice(&block_number.to_be_bytes());
+	key
+}
+
+/// Addresses a trace can be filtered by.
+fn trace_addresses(trace: &FlatTrace) -> Vec<Address> {
+	match trace.action {
+		Action::Call(ref call) => vec![call.from, call.to],
+		Action::Create(ref create) => match trace.result {
+			Res::Create(ref result) => vec![create.from, result.address],
+			_ => vec![create.from],
+		},
+		Action::Suicide(ref suicide) => vec![suicide.address, suicide.refund_address],
+		Action::Reward(ref reward) => vec![reward.author],
+	}
+}
+
+/// Position of a trace among the traces of its block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RlpEncodable, RlpDecodable)]
+struct TraceLocation {
+	transaction: u64,
+	trace: u64,
+}
+
+/// Traces of a block involving an address.
+#[derive(Debug, RlpEncodable, RlpDecodable)]
+struct AddressTraces {
+	/// Hash of the indexed block, entries of retracted blocks are ignored.
+	block_hash: H256,
+	locations: Vec<TraceLocation>,
+}
+
+/// Progress and size of the address index.
+#[derive(Debug, Clone, Copy, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct AddressIndexStatus {
+	/// Traces of canonical blocks from this one on are indexed.
+	/// Lowered by the backfill until it reaches the genesis.
+	pub first_block: u64,
+	/// Approximate size of the index entries, in bytes.
+	pub size: u64,
 }
 
 impl Key<FlatBlockTraces> for H256 {
@@ -102,6 +161,8 @@ pub struct TraceDB<T> where T: DatabaseExtras {
 	enabled: bool,
 	/// extras
 	extras: Arc<T>,
+	/// address index status, `None` until the first block is indexed
+	address_index: Mutex<Option<AddressIndexStatus>>,
 }
 
 impl<T> TraceDB<T> where T: DatabaseExtras {
@@ -114,15 +175,109 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		batch.put(db::COL_TRACE, b"version", TRACE_DB_VER);
 		db.key_value().write(batch).expect("failed to update version");
 
+		let address_index = db.key_value().get(db::COL_TRACE, ADDRESS_INDEX_KEY)
+			.expect("Low level database error when fetching the trace address index status. Some issue with disk?")
+			.map(|status| rlp::decode(&status).expect("address index status is stored as rlp; qed"));
+
 		TraceDB {
 			traces: RwLock::new(HashMap::new()),
 			cache_manager: RwLock::new(CacheManager::new(config.pref_cache_size, config.max_cache_size, 10 * 1024)),
 			db,
 			enabled: config.enabled,
 			extras,
+			address_index: Mutex::new(address_index),
 		}
 	}
 
+	/// Progress and size of the address index, `None` if no block is indexed yet.
+	pub fn address_index_status(&self) -> Option<AddressIndexStatus> {
+		*self.address_index.lock()
+	}
+
+	/// Index the traces of up to `max_blocks` canonical blocks imported before the address index
+	/// existed, continuing below the first indexed block. Returns the number of indexed blocks.
+	pub fn backfill_address_index(&self, max_blocks: u64) -> u64 {
+		if !self.tracing_enabled() {
+			return 0;
+		}
+
+		let to = match *self.address_index.lock() {
+			Some(status) if status.first_block > 0 => status.first_block,
+			_ => return 0,
+		};
+
+		// the traces are read without the lock, so the import of new blocks doesn't wait for them.
+		let from = to.saturating_sub(max_blocks);
+		let mut batch = DBTransaction::new();
+		let mut size = 0;
+		for number in from..to {
+			let hash = match self.extras.block_hash(number) {
+				Some(hash) => hash,
+				// blocks in the gap of a warp-synced database.
+				None => continue,
+			};
+			// read past the cache, old traces are unlikely to be queried soon.
+			let traces: Option<FlatBlockTraces> = self.db.key_value().read(db::COL_TRACE, &hash);
+			if let Some(traces) = traces {
+				size += Self::index_addresses(&mut batch, number, hash, &traces);
+			}
+		}
+
+		let mut address_index = self.address_index.lock();
+		let status = match *address_index {
+			Some(ref mut status) if status.first_block == to => status,
+			// another backfill indexed these blocks meanwhile.
+			_ => return 0,
+		};
+		status.size += size;
+		status.first_block = from;
+		batch.put(db::COL_TRACE, ADDRESS_INDEX_KEY, &rlp::encode(&*status));
+		self.db.key_value().write(batch).expect("Low level database error when writing the trace address index. Some issue with disk?");
+
+		if from == 0 {
+			info!(target: "trace", "Trace address index is complete");
+		}
+		to - from
+	}
+
+	/// Write the address index entries of a canonical block, returns their size.
+	fn index_addresses(batch: &mut DBTransaction, block_number: BlockNumber, block_hash: H256, traces: &FlatBlockTraces) -> u64 {
+		let mut locations: BTreeMap<Address, Vec<TraceLocation>> = BTreeMap::new();
+		for (transaction, tx_traces) in traces.0.iter().enumerate() {
+			for (trace, flat_trace) in tx_traces.0.iter().enumerate() {
+				let location = TraceLocation { transaction: transaction as u64, trace: trace as u64 };
+				for address in trace_addresses(flat_trace) {
+					let address_locations = locations.entry(address).or_insert_with(Vec::new);
+					if address_locations.last() != Some(&location) {
+						address_locations.push(location);
+					}
+				}
+			}
+		}
+
+		locations.into_iter()
+			.map(|(address, locations)| {
+				let value = rlp::encode(&AddressTraces { block_hash, locations });
+				batch.put(db::COL_TRACE, &address_index_key(&address, block_number), &value);
+				(ADDRESS_INDEX_KEY_LEN + value.len()) as u64
+			})
+			.sum()
+	}
+
+	/// Index the traces of newly canonical blocks, `enacted` starts at `first_number`.
+	fn index_enacted(&self, batch: &mut DBTransaction, first_number: BlockNumber, enacted: &[(H256, FlatBlockTraces)]) {
+		let size: u64 = enacted.iter()
+			.enumerate()
+			.map(|(i, &(hash, ref traces))| Self::index_addresses(batch, first_number + i as u64, hash, traces))
+			.sum();
+
+		let mut address_index = self.address_index.lock();
+		// blocks imported before the index existed are indexed by the backfill.
+		let status = address_index.get_or_insert(AddressIndexStatus { first_block: first_number, size: 0 });
+		status.size += size;
+		batch.put(db::COL_TRACE, ADDRESS_INDEX_KEY, &rlp::encode(&*status));
+	}
+
 	fn cache_size(&self) -> usize {
 		self.traces.read().malloc_size_of()
 	}
@@ -233,26 +388,28 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			return;
 		}
 
-		// now let's rebuild the blooms
+		// now let's rebuild the blooms and the address index
 		if !request.enacted.is_empty() {
 			let range_start = request.block_number + 1 - request.enacted.len() as u64;
-			let enacted_blooms: Vec<_> = request.enacted
+			let enacted: Vec<_> = request.enacted
 				.iter()
 				// all traces are expected to be found here. That's why `expect` has been used
 				// instead of `filter_map`. If some traces haven't been found, it means that
 				// traces database is corrupted or incomplete.
 				.map(|block_hash|
 					if block_hash == &request.block_hash {
-						request.traces.bloom()
+						(*block_hash, request.traces.clone())
 					} else {
-						self.traces(block_hash).expect("Traces database is incomplete.").bloom()
+						(*block_hash, self.traces(block_hash).expect("Traces database is incomplete."))
 					}
 				)
 				.collect();
+			let enacted_blooms: Vec<_> = enacted.iter().map(|&(_, ref traces)| traces.bloom()).collect();
 
 			self.db.trace_blooms()
 				.insert_blooms(range_start, enacted_blooms.iter())
 				.expect("Low level database error. Some issue with disk?");
+			self.index_enacted(batch, range_start, &enacted);
 		}
 
 		// insert new block traces into the cache and the database
@@ -266,6 +423,26 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 		}
 	}
 
+	fn enact(&self, first_number: BlockNumber, enacted: &[H256]) {
+		if !self.tracing_enabled() || enacted.is_empty() {
+			return;
+		}
+
+		let enacted: Vec<_> = enacted
+			.iter()
+			.map(|block_hash| (*block_hash, self.traces(block_hash).expect("Traces database is incomplete.")))
+			.collect();
+		let enacted_blooms: Vec<_> = enacted.iter().map(|&(_, ref traces)| traces.bloom()).collect();
+
+		self.db.trace_blooms()
+			.insert_blooms(first_number, enacted_blooms.iter())
+			.expect("Low level database error. Some issue with disk?");
+
+		let mut batch = DBTransaction::new();
+		self.index_enacted(&mut batch, first_number, &enacted);
+		self.db.key_value().write(batch).expect("Low level database error when writing the trace address index. Some issue with disk?");
+	}
+
 	fn trace(&self, block_number: BlockNumber, tx_position: usize, trace_position: Vec<usize>) -> Option<LocalizedTrace> {
 		self.extras.block_hash(block_number)
 			.and_then(|block_hash| self.transactions_traces(&block_hash)
@@ -350,9 +527,34 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 	}
 
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
+		let (start, end) = (filter.range.start as BlockNumber, filter.range.end as BlockNumber);
+		// the index finds the traces of any of the addresses of one side of the filter
+		let addresses = match filter.from_address.matches_all() {
+			true => filter.to_address.addresses(),
+			false => filter.from_address.addresses(),
+		};
+		let first_indexed = self.address_index_status().map(|status| status.first_block);
+
+		match first_indexed {
+			Some(first_indexed) if !addresses.is_empty() && first_indexed <= end => {
+				let mut traces = match start < first_indexed {
+					true => self.filter_with_blooms(filter, start, first_indexed - 1),
+					false => Vec::new(),
+				};
+				traces.extend(self.filter_with_address_index(filter, addresses, cmp::max(start, first_indexed), end));
+				traces
+			},
+			_ => self.filter_with_blooms(filter, start, end),
+		}
+	}
+}
+
+impl<T> TraceDB<T> where T: DatabaseExtras {
+	/// Traces of the inclusive block range matching the filter, from the blocks whose trace blooms match it.
+	fn filter_with_blooms(&self, filter: &Filter, start: BlockNumber, end: BlockNumber) -> Vec<LocalizedTrace> {
 		let possibilities = filter.bloom_possibilities();
 		let numbers = self.db.trace_blooms()
-			.filter(filter.range.start as u64, filter.range.end as u64, &possibilities)
+			.filter(start, end, &possibilities)
 			.expect("Low level database error. Some issue with disk?");
 
 		numbers.into_iter()
@@ -366,6 +568,59 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			})
 			.collect()
 	}
+
+	/// Traces of the inclusive block range matching the filter, from the indexed traces involving
+	/// any of the given addresses.
+	fn filter_with_address_index(&self, filter: &Filter, addresses: &[Address], start: BlockNumber, end: BlockNumber) -> Vec<LocalizedTrace> {
+		let bucket_len = ADDRESS_INDEX_KEY_LEN - (ADDRESS_INDEX_BUCKET_BITS / 8) as usize;
+		let mut candidates: BTreeMap<BlockNumber, (H256, BTreeSet<TraceLocation>)> = BTreeMap::new();
+		for address in addresses {
+			for bucket in (start >> ADDRESS_INDEX_BUCKET_BITS)..=(end >> ADDRESS_INDEX_BUCKET_BITS) {
+				let prefix = address_index_key(address, bucket << ADDRESS_INDEX_BUCKET_BITS);
+				for (key, value) in self.db.key_value().iter_from_prefix(db::COL_TRACE, &prefix[..bucket_len]) {
+					let mut number = [0u8; 8];
+					number.copy_from_slice(&key[21..ADDRESS_INDEX_KEY_LEN]);
+					let number = BlockNumber::from_be_bytes(number);
+					if number < start || number > end {
+						continue;
+					}
+
+					let entry: AddressTraces = rlp::decode(&value).expect("address index entries are stored as rlp; qed");
+					if self.extras.block_hash(number) != Some(entry.block_hash) {
+						// written for a block which has been retracted since
+						continue;
+					}
+					candidates.entry(number)
+						.or_insert_with(|| (entry.block_hash, BTreeSet::new()))
+						.1
+						.extend(entry.locations);
+				}
+			}
+		}
+
+		candidates.into_iter()
+			.flat_map(|(number, (hash, locations))| {
+				let traces = self.traces(&hash).expect("Expected to find a trace. Db is probably corrupted.");
+				let tx_traces: Vec<Vec<FlatTrace>> = traces.0.into_iter().map(Into::into).collect();
+
+				let mut selected: BTreeMap<usize, Vec<FlatTrace>> = BTreeMap::new();
+				for location in locations {
+					let (transaction, trace) = (location.transaction as usize, location.trace as usize);
+					let trace = tx_traces.get(transaction)
+						.and_then(|traces| traces.get(trace))
+						.cloned()
+						.expect("Address index refers to a missing trace. Db is probably corrupted.");
+					selected.entry(transaction).or_insert_with(Vec::new).push(trace);
+				}
+
+				selected.into_iter()
+					.flat_map(|(transaction, traces)| {
+						self.matching_transaction_traces(filter, traces.into(), hash, number, transaction)
+					})
+					.collect::<Vec<_>>()
+			})
+			.collect()
+	}
 }
 
 #[cfg(test)]
@@ -379,9 +634,11 @@ mod tests {
 	use ethereum_types::{H256, U256, Address};
 	use kvdb::DBTransaction;
 
+	use ethcore_db as db;
+
 	use crate::{
 		BlockNumber, Config, TraceDB, Database as TraceDatabase, ImportRequest, DatabaseExtras,
-		Filter, LocalizedTrace, AddressesFilter, TraceError,
+		Filter, LocalizedTrace, AddressesFilter, TraceError, AddressIndexStatus,
 		trace::{Call, CallType, Action, Res},
 		flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces}
 	};
@@ -626,6 +883,80 @@ mod tests {
 		assert_eq!(tracedb.trace(2, 0, vec![]).unwrap(), create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
 	}
 
+	#[test]
+	fn filter_with_address_index_and_backfill() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from_low_u64_be(0xa1);
+		let block_2 = H256::from_low_u64_be(0xa2);
+		let retracted_3 = H256::from_low_u64_be(0xa3);
+		let tx_1 = H256::from_low_u64_be(0xff);
+		let tx_2 = H256::from_low_u64_be(0xaf);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::zero());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2.clone());
+		extras.block_hashes.insert(3, H256::from_low_u64_be(0xb3));
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+		extras.transaction_hashes.insert(2, vec![tx_2.clone()]);
+
+		{
+			let tracedb = TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone()));
+			for (number, hash) in vec![(1, block_1), (2, block_2), (3, retracted_3)] {
+				let mut batch = DBTransaction::new();
+				tracedb.import(&mut batch, create_simple_import_request(number, hash));
+				db.key_value().write(batch).unwrap();
+			}
+
+			let status = tracedb.address_index_status().unwrap();
+			assert_eq!(status.first_block, 1);
+			assert!(status.size > 0);
+
+			let filter = Filter {
+				range: (1..3),
+				from_address: AddressesFilter::from(vec![]),
+				to_address: AddressesFilter::from(vec![Address::from_low_u64_be(2)]),
+			};
+			assert_eq!(tracedb.filter(&filter), vec![
+				create_simple_localized_trace(1, block_1.clone(), tx_1.clone()),
+				create_simple_localized_trace(2, block_2.clone(), tx_2.clone()),
+			]);
+
+			let filter = Filter {
+				range: (1..3),
+				from_address: AddressesFilter::from(vec![Address::from_low_u64_be(2)]),
+				to_address: AddressesFilter::from(vec![]),
+			};
+			assert!(tracedb.filter(&filter).is_empty());
+		}
+
+		// pretend block 1 was imported before the index existed
+		let mut batch = DBTransaction::new();
+		batch.delete(db::COL_TRACE, &super::address_index_key(&Address::from_low_u64_be(1), 1));
+		batch.delete(db::COL_TRACE, &super::address_index_key(&Address::from_low_u64_be(2), 1));
+		batch.put(db::COL_TRACE, super::ADDRESS_INDEX_KEY, &rlp::encode(&AddressIndexStatus { first_block: 2, size: 0 }));
+		db.key_value().write(batch).unwrap();
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+		let filter = Filter {
+			range: (1..3),
+			from_address: AddressesFilter::from(vec![Address::from_low_u64_be(1)]),
+			to_address: AddressesFilter::from(vec![]),
+		};
+		let expected = vec![
+			create_simple_localized_trace(1, block_1.clone(), tx_1.clone()),
+			create_simple_localized_trace(2, block_2.clone(), tx_2.clone()),
+		];
+		assert_eq!(tracedb.filter(&filter), expected);
+
+		assert_eq!(tracedb.backfill_address_index(10), 2);
+		assert_eq!(tracedb.address_index_status().unwrap().first_block, 0);
+		assert_eq!(tracedb.backfill_address_index(10), 0);
+		assert_eq!(tracedb.filter(&filter), expected);
+	}
+
 	#[test]
 	fn query_trace_after_reopen() {
 		let db = new_db();
@@ -0,0 +1,335 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracer aggregating the gas spent by every call site.
+
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::Arc;
+
+use ethereum_types::{U256, Address};
+use evm::Instruction;
+use parking_lot::Mutex;
+use vm::{Error as VmError, ActionParams};
+
+use crate::{Tracer, VMTracer, trace::RewardType};
+
+/// Group of instructions the gas of a call site is aggregated by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OpcodeClass {
+	/// Arithmetic, stack, control flow and environment instructions.
+	Compute,
+	/// Memory and call data accesses.
+	Memory,
+	/// Storage reads and writes.
+	Storage,
+	/// Accesses to the balance and code of other accounts.
+	Account,
+	/// `SHA3`.
+	Hashing,
+	/// `LOG0` to `LOG4`.
+	Log,
+	/// Calls, contract creations and `SELFDESTRUCT`, excluding the gas spent by the callee.
+	Call,
+}
+
+impl OpcodeClass {
+	/// Class of the given instruction.
+	pub fn of(instruction: u8) -> Self {
+		let instruction = match Instruction::from_u8(instruction) {
+			Some(instruction) => instruction,
+			None => return OpcodeClass::Compute,
+		};
+
+		match instruction {
+			Instruction::SLOAD | Instruction::SSTORE => OpcodeClass::Storage,
+			Instruction::MLOAD | Instruction::MSTORE | Instruction::MSTORE8 | Instruction::CALLDATACOPY |
+				Instruction::CODECOPY | Instruction::RETURNDATACOPY => OpcodeClass::Memory,
+			Instruction::BALANCE | Instruction::EXTCODESIZE | Instruction::EXTCODECOPY |
+				Instruction::EXTCODEHASH => OpcodeClass::Account,
+			Instruction::SHA3 => OpcodeClass::Hashing,
+			Instruction::CALL | Instruction::CALLCODE | Instruction::DELEGATECALL | Instruction::STATICCALL |
+				Instruction::AUTHCALL | Instruction::CREATE | Instruction::CREATE2 |
+				Instruction::SUICIDE => OpcodeClass::Call,
+			i if i.log_topics().is_some() => OpcodeClass::Log,
+			_ => OpcodeClass::Compute,
+		}
+	}
+
+	/// Lowercase name of the class.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			OpcodeClass::Compute => "compute",
+			OpcodeClass::Memory => "memory",
+			OpcodeClass::Storage => "storage",
+			OpcodeClass::Account => "account",
+			OpcodeClass::Hashing => "hashing",
+			OpcodeClass::Log => "log",
+			OpcodeClass::Call => "call",
+		}
+	}
+}
+
+/// Gas spent by a single call or contract creation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasProfile {
+	/// Address of the executed code.
+	pub address: Address,
+	/// First four bytes of the call data, if the call has any.
+	pub selector: Option<[u8; 4]>,
+	/// True if the code was executed to create a contract.
+	pub is_create: bool,
+	/// Gas spent by the call, including the gas spent by its subcalls.
+	pub gas: U256,
+	/// Gas spent by the instructions of the call itself, per opcode class.
+	pub opcodes: BTreeMap<OpcodeClass, U256>,
+	/// Profiles of the subcalls, in the order of execution.
+	pub calls: Vec<GasProfile>,
+}
+
+impl GasProfile {
+	fn new(address: Address, selector: Option<[u8; 4]>, is_create: bool) -> Self {
+		GasProfile {
+			address,
+			selector,
+			is_create,
+			gas: U256::zero(),
+			opcodes: BTreeMap::new(),
+			calls: Vec::new(),
+		}
+	}
+
+	/// Gas spent by the instructions of the call itself.
+	pub fn self_gas(&self) -> U256 {
+		self.opcodes.values().fold(U256::zero(), |acc, gas| acc.saturating_add(*gas))
+	}
+}
+
+struct PendingInstruction {
+	class: OpcodeClass,
+	gas_before: U256,
+	gas_cost: U256,
+	subcalls_gas: U256,
+}
+
+struct Frame {
+	profile: GasProfile,
+	subcalls_gas: U256,
+}
+
+#[derive(Default)]
+struct Profiles {
+	frames: Vec<Frame>,
+	pending: Vec<PendingInstruction>,
+	gas: U256,
+	roots: Vec<GasProfile>,
+}
+
+impl Profiles {
+	fn done(&mut self, gas_used: Option<U256>) {
+		let Frame { mut profile, subcalls_gas } = match self.frames.pop() {
+			Some(frame) => frame,
+			None => return,
+		};
+		profile.gas = gas_used.unwrap_or_else(|| profile.self_gas().saturating_add(subcalls_gas));
+
+		match self.frames.last_mut() {
+			Some(parent) => {
+				parent.subcalls_gas = parent.subcalls_gas.saturating_add(profile.gas);
+				parent.profile.calls.push(profile);
+			},
+			None => self.roots.push(profile),
+		}
+	}
+
+	fn charge(&mut self, class: OpcodeClass, gas: U256) {
+		if let Some(frame) = self.frames.last_mut() {
+			let entry = frame.profile.opcodes.entry(class).or_insert_with(U256::zero);
+			*entry = entry.saturating_add(gas);
+		}
+	}
+}
+
+/// Tracer building a tree of the gas spent by every call of a transaction, aggregated
+/// by the contract address, the function selector and the opcode class.
+///
+/// The same profiler has to be used as both the tracer and the VM tracer of the execution,
+/// e.g. `TransactOptions::new(profiler.clone(), profiler)`. The tree is returned by `Tracer::drain`.
+#[derive(Clone, Default)]
+pub struct GasProfiler {
+	profiles: Arc<Mutex<Profiles>>,
+}
+
+impl GasProfiler {
+	/// Create a new profiler.
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl Tracer for GasProfiler {
+	type Output = GasProfile;
+
+	fn prepare_trace_call(&mut self, params: &ActionParams, _depth: usize, _is_builtin: bool) {
+		let selector = params.data.as_ref()
+			.filter(|data| data.len() >= 4)
+			.map(|data| [data[0], data[1], data[2], data[3]]);
+		self.profiles.lock().frames.push(Frame {
+			profile: GasProfile::new(params.code_address, selector, false),
+			subcalls_gas: U256::zero(),
+		});
+	}
+
+	fn prepare_trace_create(&mut self, params: &ActionParams) {
+		self.profiles.lock().frames.push(Frame {
+			profile: GasProfile::new(params.address, None, true),
+			subcalls_gas: U256::zero(),
+		});
+	}
+
+	fn done_trace_call(&mut self, gas_used: U256, _output: &[u8]) {
+		self.profiles.lock().done(Some(gas_used));
+	}
+
+	fn done_trace_create(&mut self, gas_used: U256, _code: &[u8], _address: Address) {
+		self.profiles.lock().done(Some(gas_used));
+	}
+
+	fn done_trace_failed(&mut self, _error: &VmError) {
+		self.profiles.lock().done(None);
+	}
+
+	fn trace_suicide(&mut self, _address: Address, _balance: U256, _refund_address: Address) {}
+
+	fn trace_reward(&mut self, _author: Address, _value: U256, _reward_type: RewardType) {}
+
+	fn drain(self) -> Vec<GasProfile> {
+		mem::replace(&mut self.profiles.lock().roots, Vec::new())
+	}
+}
+
+impl VMTracer for GasProfiler {
+	type Output = ();
+
+	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, current_gas: U256) -> bool {
+		self.profiles.lock().gas = current_gas;
+		true
+	}
+
+	fn trace_prepare_execute(&mut self, _pc: usize, instruction: u8, gas_cost: U256, _mem_written: Option<(usize, usize)>, _store_written: Option<(U256, U256)>) {
+		let mut profiles = self.profiles.lock();
+		let subcalls_gas = profiles.frames.last().map_or_else(U256::zero, |frame| frame.subcalls_gas);
+		let gas_before = profiles.gas;
+		profiles.pending.push(PendingInstruction {
+			class: OpcodeClass::of(instruction),
+			gas_before,
+			gas_cost,
+			subcalls_gas,
+		});
+	}
+
+	fn trace_failed(&mut self) {
+		let mut profiles = self.profiles.lock();
+		let instruction = profiles.pending.pop().expect("pushed in trace_prepare_execute; qed");
+		profiles.charge(instruction.class, min(instruction.gas_cost, instruction.gas_before));
+	}
+
+	fn trace_executed(&mut self, gas_remaining: U256, _stack_push: &[U256], _mem: &[u8]) {
+		let mut profiles = self.profiles.lock();
+		let instruction = profiles.pending.pop().expect("pushed in trace_prepare_execute; qed");
+		let subcalls_gas = profiles.frames.last()
+			.map_or_else(U256::zero, |frame| frame.subcalls_gas.saturating_sub(instruction.subcalls_gas));
+		let gas = instruction.gas_before.saturating_sub(gas_remaining).saturating_sub(subcalls_gas);
+		profiles.charge(instruction.class, gas);
+	}
+
+	fn drain(self) -> Option<()> { None }
+}
+
+#[cfg(test)]
+mod tests {
+	use vm::ActionValue;
+	use super::*;
+
+	fn call(address: Address, data: Vec<u8>) -> ActionParams {
+		ActionParams {
+			code_address: address,
+			address,
+			data: Some(data),
+			value: ActionValue::Transfer(0.into()),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn aggregates_gas_per_call_site() {
+		let mut profiler = GasProfiler::new();
+		let mut vm_profiler = profiler.clone();
+		let contract = Address::from_low_u64_be(1);
+		let token = Address::from_low_u64_be(2);
+
+		profiler.prepare_trace_call(&call(contract, vec![0xa9, 0x05, 0x9c, 0xbb, 0x00]), 0, false);
+		// PUSH1, SLOAD, then a CALL spending 5 gas itself and 703 in the callee.
+		vm_profiler.trace_next_instruction(0, 0x60, 10_000.into());
+		vm_profiler.trace_prepare_execute(0, 0x60, 3.into(), None, None);
+		vm_profiler.trace_executed(9_997.into(), &[], &[]);
+		vm_profiler.trace_next_instruction(2, 0x54, 9_997.into());
+		vm_profiler.trace_prepare_execute(2, 0x54, 800.into(), None, None);
+		vm_profiler.trace_executed(9_197.into(), &[], &[]);
+		vm_profiler.trace_next_instruction(3, 0xf1, 9_197.into());
+		vm_profiler.trace_prepare_execute(3, 0xf1, 1_005.into(), None, None);
+		profiler.prepare_trace_call(&call(token, vec![]), 1, false);
+		vm_profiler.prepare_subtrace(&[]);
+		vm_profiler.trace_next_instruction(0, 0x54, 1_000.into());
+		vm_profiler.trace_prepare_execute(0, 0x54, 800.into(), None, None);
+		vm_profiler.trace_failed();
+		profiler.done_trace_failed(&VmError::OutOfGas);
+		vm_profiler.done_subtrace();
+		vm_profiler.trace_executed(8_389.into(), &[0.into()], &[]);
+		profiler.done_trace_call(811.into(), &[]);
+
+		let profiles = profiler.drain();
+		assert_eq!(profiles.len(), 1);
+		let root = &profiles[0];
+		assert_eq!(root.address, contract);
+		assert_eq!(root.selector, Some([0xa9, 0x05, 0x9c, 0xbb]));
+		assert_eq!(root.gas, 811.into());
+		assert_eq!(root.opcodes.get(&OpcodeClass::Compute), Some(&3.into()));
+		assert_eq!(root.opcodes.get(&OpcodeClass::Storage), Some(&800.into()));
+		assert_eq!(root.opcodes.get(&OpcodeClass::Call), Some(&8.into()));
+
+		assert_eq!(root.calls.len(), 1);
+		let callee = &root.calls[0];
+		assert_eq!(callee.address, token);
+		assert_eq!(callee.selector, None);
+		assert_eq!(callee.gas, 800.into());
+		assert_eq!(callee.opcodes.get(&OpcodeClass::Storage), Some(&800.into()));
+		assert!(callee.calls.is_empty());
+	}
+
+	#[test]
+	fn classifies_instructions() {
+		assert_eq!(OpcodeClass::of(0x01), OpcodeClass::Compute);
+		assert_eq!(OpcodeClass::of(0x37), OpcodeClass::Memory);
+		assert_eq!(OpcodeClass::of(0x55), OpcodeClass::Storage);
+		assert_eq!(OpcodeClass::of(0x3b), OpcodeClass::Account);
+		assert_eq!(OpcodeClass::of(0x20), OpcodeClass::Hashing);
+		assert_eq!(OpcodeClass::of(0xa2), OpcodeClass::Log);
+		assert_eq!(OpcodeClass::of(0xf5), OpcodeClass::Call);
+		assert_eq!(OpcodeClass::of(0xfe), OpcodeClass::Compute);
+	}
+}
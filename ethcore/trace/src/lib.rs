@@ -16,24 +16,28 @@
 
 //! Tracing
 
-use ethereum_types::{U256, Address};
+use ethereum_types::{H256, U256, Address};
 use kvdb::DBTransaction;
 use vm::{Error as VmError, ActionParams};
 
 mod config;
 mod db;
 mod executive_tracer;
+mod gas_profiler;
 mod import;
 mod noop_tracer;
+mod struct_logger;
 mod types;
 
 pub use crate::{
 	config::Config,
-	db::{TraceDB, DatabaseExtras},
+	db::{TraceDB, DatabaseExtras, AddressIndexStatus},
 	localized::LocalizedTrace,
 	executive_tracer::{ExecutiveTracer, ExecutiveVMTracer},
+	gas_profiler::{GasProfile, GasProfiler, OpcodeClass},
 	import::ImportRequest,
 	noop_tracer::{NoopTracer, NoopVMTracer},
+	struct_logger::{StructLog, StructLogger},
 	types::{
 		Tracing,
 		error::Error as TraceError,
@@ -116,6 +120,10 @@ pub trait Database {
 	/// Imports new block traces.
 	fn import(&self, batch: &mut DBTransaction, request: ImportRequest);
 
+	/// Rebuilds the trace blooms of already imported blocks which became canonical without being
+	/// imported again. `enacted` is ordered from oldest to newest and starts at `first_number`.
+	fn enact(&self, first_number: BlockNumber, enacted: &[H256]);
+
 	/// Returns localized trace at given position.
 	fn trace(&self, block_number: BlockNumber, tx_position: usize, trace_position: Vec<usize>) -> Option<LocalizedTrace>;
 
@@ -0,0 +1,207 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opcode level tracer streaming the executed steps.
+
+use std::cmp::min;
+use std::sync::mpsc::SyncSender;
+
+use ethereum_types::U256;
+use evm::Instruction;
+use log::{trace, warn};
+
+use crate::{
+	VMTracer,
+	trace::{VMExecutedOperation, MemoryDiff, StorageDiff},
+};
+
+/// A single executed VM step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLog {
+	/// Depth of the call executing the step, starting at 0 for the transaction itself.
+	pub depth: usize,
+	/// The program counter.
+	pub pc: usize,
+	/// The executed instruction.
+	pub instruction: u8,
+	/// Gas available before the step.
+	pub gas: U256,
+	/// The gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Stack items pushed and memory and storage written by the step,
+	/// or `None` if the instruction failed.
+	pub executed: Option<VMExecutedOperation>,
+}
+
+impl StructLog {
+	/// Mnemonic of the executed instruction.
+	pub fn instruction_name(&self) -> &'static str {
+		Instruction::from_u8(self.instruction).map_or("INVALID", |i| i.info().name)
+	}
+}
+
+struct PendingStep {
+	log: StructLog,
+	mem_written: Option<(usize, usize)>,
+	store_written: Option<(U256, U256)>,
+}
+
+/// VM tracer sending every executed step to a channel as soon as it completes, so the trace
+/// of a transaction never has to be held in memory at once. The channel is bounded: execution
+/// waits for the receiver to catch up, and tracing stops once the receiver is dropped.
+///
+/// A step is sent when it completes, so `CALL` and `CREATE` steps follow the steps of the code they executed.
+pub struct StructLogger {
+	sender: SyncSender<StructLog>,
+	depth: usize,
+	gas: U256,
+	pending: Vec<PendingStep>,
+	sent: usize,
+	stopped: bool,
+}
+
+impl StructLogger {
+	/// Create a new tracer sending the steps to the given channel.
+	pub fn new(sender: SyncSender<StructLog>) -> Self {
+		StructLogger {
+			sender,
+			depth: 0,
+			gas: U256::zero(),
+			pending: Vec::new(),
+			sent: 0,
+			stopped: false,
+		}
+	}
+
+	fn send(&mut self, log: StructLog) {
+		if self.stopped {
+			return;
+		}
+
+		match self.sender.send(log) {
+			Ok(()) => self.sent += 1,
+			Err(_) => {
+				trace!(target: "trace", "Struct log receiver dropped after {} steps", self.sent);
+				self.stopped = true;
+			},
+		}
+	}
+}
+
+impl VMTracer for StructLogger {
+	/// Number of steps sent.
+	type Output = usize;
+
+	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, current_gas: U256) -> bool {
+		self.gas = current_gas;
+		!self.stopped
+	}
+
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: U256, mem_written: Option<(usize, usize)>, store_written: Option<(U256, U256)>) {
+		self.pending.push(PendingStep {
+			log: StructLog {
+				depth: self.depth,
+				pc,
+				instruction,
+				gas: self.gas,
+				gas_cost,
+				executed: None,
+			},
+			mem_written,
+			store_written,
+		});
+	}
+
+	fn trace_failed(&mut self) {
+		let step = self.pending.pop().expect("pushed in trace_prepare_execute; qed");
+		self.send(step.log);
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem: &[u8]) {
+		let PendingStep { mut log, mem_written, store_written } = self.pending.pop().expect("pushed in trace_prepare_execute; qed");
+		let mem_diff = mem_written.map(|(o, s)| {
+			if o + s > mem.len() {
+				warn!(target: "trace", "mem_written is out of bounds");
+			}
+			MemoryDiff { offset: o, data: mem[min(mem.len(), o)..min(o + s, mem.len())].to_vec() }
+		});
+		log.executed = Some(VMExecutedOperation {
+			gas_used,
+			stack_push: stack_push.to_vec(),
+			mem_diff,
+			store_diff: store_written.map(|(l, v)| StorageDiff { location: l, value: v }),
+		});
+		self.send(log);
+	}
+
+	fn prepare_subtrace(&mut self, _code: &[u8]) {
+		self.depth += 1;
+	}
+
+	fn done_subtrace(&mut self) {
+		self.depth -= 1;
+	}
+
+	fn drain(self) -> Option<usize> { Some(self.sent) }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::mpsc;
+	use super::*;
+
+	#[test]
+	fn streams_completed_steps() {
+		let (sender, receiver) = mpsc::sync_channel(8);
+		let mut logger = StructLogger::new(sender);
+
+		// PUSH1 0x20 at the top level, then a CALL executing a single MSTORE.
+		assert!(logger.trace_next_instruction(0, 0x60, 100.into()));
+		logger.trace_prepare_execute(0, 0x60, 3.into(), None, None);
+		logger.trace_executed(3.into(), &[0x20.into()], &[]);
+		assert!(logger.trace_next_instruction(2, 0xf1, 97.into()));
+		logger.trace_prepare_execute(2, 0xf1, 40.into(), Some((0, 2)), None);
+		logger.prepare_subtrace(&[]);
+		assert!(logger.trace_next_instruction(0, 0x52, 50.into()));
+		logger.trace_prepare_execute(0, 0x52, 6.into(), Some((0, 32)), None);
+		logger.trace_failed();
+		logger.done_subtrace();
+		logger.trace_executed(40.into(), &[1.into()], &[0xab, 0xcd, 0xef]);
+		assert_eq!(logger.drain(), Some(3));
+
+		let logs = receiver.iter().collect::<Vec<_>>();
+		assert_eq!(logs.iter().map(|l| (l.depth, l.pc, l.instruction_name())).collect::<Vec<_>>(),
+			vec![(0, 0, "PUSH1"), (1, 0, "MSTORE"), (0, 2, "CALL")]);
+		assert_eq!(logs[0].gas, 100.into());
+		assert_eq!(logs[1].executed, None);
+		let call = logs[2].executed.as_ref().unwrap();
+		assert_eq!(call.stack_push, vec![1.into()]);
+		assert_eq!(call.mem_diff, Some(MemoryDiff { offset: 0, data: vec![0xab, 0xcd] }));
+	}
+
+	#[test]
+	fn stops_when_receiver_is_dropped() {
+		let (sender, receiver) = mpsc::sync_channel(8);
+		let mut logger = StructLogger::new(sender);
+		drop(receiver);
+
+		assert!(logger.trace_next_instruction(0, 0x00, 10.into()));
+		logger.trace_prepare_execute(0, 0x00, 0.into(), None, None);
+		logger.trace_executed(0.into(), &[], &[]);
+		assert!(!logger.trace_next_instruction(1, 0x00, 10.into()));
+		assert_eq!(logger.drain(), Some(0));
+	}
+}
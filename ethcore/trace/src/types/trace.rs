@@ -51,6 +51,8 @@ pub enum CallType {
 	DelegateCall,
 	/// Static call
 	StaticCall,
+	/// Call on behalf of an authorized account (EIP-3074)
+	AuthCall,
 }
 
 impl TryFrom<ActionType> for CallType {
@@ -61,6 +63,7 @@ impl TryFrom<ActionType> for CallType {
 			ActionType::CallCode => Ok(CallType::CallCode),
 			ActionType::DelegateCall => Ok(CallType::DelegateCall),
 			ActionType::StaticCall => Ok(CallType::StaticCall),
+			ActionType::AuthCall => Ok(CallType::AuthCall),
 			ActionType::Create => Err("Create cannot be converted to CallType"),
 			ActionType::Create2 => Err("Create2 cannot be converted to CallType"),
 		}
@@ -102,6 +105,7 @@ impl TryFrom<ActionType> for CreationMethod {
 			ActionType::CallCode => Err("CallCode cannot be converted to CreationMethod"),
 			ActionType::DelegateCall => Err("DelegateCall cannot be converted to CreationMethod"),
 			ActionType::StaticCall => Err("StaticCall cannot be converted to CreationMethod"),
+			ActionType::AuthCall => Err("AuthCall cannot be converted to CreationMethod"),
 			ActionType::Create => Ok(CreationMethod::Create),
 			ActionType::Create2 => Ok(CreationMethod::Create2),
 		}
@@ -166,6 +170,7 @@ impl Encodable for BackwardsCompatibleCallType {
 			Some(CallType::CallCode) => 2,
 			Some(CallType::DelegateCall) => 3,
 			Some(CallType::StaticCall) => 4,
+			Some(CallType::AuthCall) => 5,
 		};
 		Encodable::rlp_append(&v, s);
 	}
@@ -181,6 +186,7 @@ impl Decodable for BackwardsCompatibleCallType {
 				2 => Some(CallType::CallCode),
 				3 => Some(CallType::DelegateCall),
 				4 => Some(CallType::StaticCall),
+				5 => Some(CallType::AuthCall),
 				_ => return Err(DecoderError::Custom("Invalid value of CallType item")),
 			}.into()))
 		} else {
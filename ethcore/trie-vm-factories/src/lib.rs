@@ -18,6 +18,7 @@ use trie_db::TrieFactory;
 use ethtrie::Layout;
 use account_db::Factory as AccountFactory;
 use evm::{Factory as EvmFactory};
+use evm::interpreter::CacheStats as CodeCacheStats;
 use vm::{Exec, ActionParams, VersionedSchedule, Schedule};
 use wasm::WasmInterpreter;
 
@@ -52,6 +53,11 @@ impl VmFactory {
 	pub fn new(cache_size: usize) -> Self {
 		VmFactory { evm: EvmFactory::new(cache_size) }
 	}
+
+	/// Statistics of the contract code cache shared by the created interpreters.
+	pub fn code_cache_stats(&self) -> CodeCacheStats {
+		self.evm.cache_stats()
+	}
 }
 
 impl From<EvmFactory> for VmFactory {
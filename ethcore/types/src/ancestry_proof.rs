@@ -0,0 +1,184 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ancestry proofs against the header accumulator.
+//!
+//! The accumulator is a Merkle mountain range whose leaves are the canonical block hashes
+//! in order of block number. The accumulator of a head commits to every ancestor of that
+//! head, so an inclusion proof of a leaf shows the block to be one of them.
+
+use ethereum_types::H256;
+use hash::keccak;
+
+use crate::BlockNumber;
+
+/// Hash of an inner node of the accumulator.
+pub fn merge(left: &H256, right: &H256) -> H256 {
+	let mut data = [0u8; 64];
+	data[..32].copy_from_slice(left.as_bytes());
+	data[32..].copy_from_slice(right.as_bytes());
+	keccak(&data[..])
+}
+
+/// Heights of the peaks of an accumulator of `size` leaves, from the leftmost peak.
+pub fn peak_heights(size: u64) -> Vec<u32> {
+	(0..64).rev().filter(|height| (size >> height) & 1 == 1).collect()
+}
+
+/// Root of an accumulator of `size` leaves: the hash of its size (big-endian) and of its
+/// last leaf, the head, followed by its peaks, from the leftmost one.
+pub fn bag_peaks(size: u64, head: &H256, peaks: &[H256]) -> H256 {
+	let mut data = Vec::with_capacity(40 + 32 * peaks.len());
+	data.extend_from_slice(&size.to_be_bytes());
+	data.extend_from_slice(head.as_bytes());
+	for peak in peaks {
+		data.extend_from_slice(peak.as_bytes());
+	}
+	keccak(data)
+}
+
+/// Proof that a block is an ancestor of (or is) the given head.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AncestryProof {
+	/// Number of the proven block.
+	pub block_number: BlockNumber,
+	/// Hash of the proven block.
+	pub block_hash: H256,
+	/// Number of the head, i.e. the accumulator covers `head_number + 1` blocks.
+	pub head_number: BlockNumber,
+	/// Hash of the head.
+	pub head_hash: H256,
+	/// Roots of the sibling subtrees on the path from the block to its peak, from the bottom.
+	pub siblings: Vec<H256>,
+	/// Peaks of the accumulator, from the leftmost one.
+	pub peaks: Vec<H256>,
+	/// Root of the accumulator.
+	pub root: H256,
+}
+
+impl AncestryProof {
+	/// Check the proof against its own root, which commits to the head too.
+	pub fn verify(&self) -> bool {
+		if self.block_number > self.head_number {
+			return false;
+		}
+
+		let size = self.head_number + 1;
+		let heights = peak_heights(size);
+		if heights.len() != self.peaks.len() || bag_peaks(size, &self.head_hash, &self.peaks) != self.root {
+			return false;
+		}
+
+		let mut offset = 0;
+		for (peak, height) in self.peaks.iter().zip(heights) {
+			let width = 1u64 << height;
+			if self.block_number < offset + width {
+				if self.siblings.len() != height as usize {
+					return false;
+				}
+				let mut index = self.block_number - offset;
+				let mut node = self.block_hash;
+				for sibling in &self.siblings {
+					node = if index & 1 == 0 { merge(&node, sibling) } else { merge(sibling, &node) };
+					index >>= 1;
+				}
+				return node == *peak;
+			}
+			offset += width;
+		}
+		false
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use super::{bag_peaks, merge, peak_heights, AncestryProof};
+
+	fn leaf(n: u64) -> H256 {
+		H256::from_low_u64_be(n + 1)
+	}
+
+	fn subtree(height: u32, index: u64) -> H256 {
+		if height == 0 {
+			leaf(index)
+		} else {
+			merge(&subtree(height - 1, index * 2), &subtree(height - 1, index * 2 + 1))
+		}
+	}
+
+	fn prove(number: u64, head: u64) -> AncestryProof {
+		let size = head + 1;
+		let (mut peaks, mut siblings, mut offset) = (Vec::new(), Vec::new(), 0);
+		for height in peak_heights(size) {
+			peaks.push(subtree(height, offset >> height));
+			if number >= offset && number < offset + (1 << height) {
+				siblings = (0..height).map(|h| subtree(h, (number >> h) ^ 1)).collect();
+			}
+			offset += 1 << height;
+		}
+		AncestryProof {
+			block_number: number,
+			block_hash: leaf(number),
+			head_number: head,
+			head_hash: leaf(head),
+			siblings,
+			root: bag_peaks(size, &leaf(head), &peaks),
+			peaks,
+		}
+	}
+
+	#[test]
+	fn peaks_follow_binary_representation() {
+		assert_eq!(peak_heights(1), vec![0]);
+		assert_eq!(peak_heights(11), vec![3, 1, 0]);
+		assert_eq!(peak_heights(16), vec![4]);
+	}
+
+	#[test]
+	fn every_ancestor_is_proven() {
+		for head in 0..40 {
+			for number in 0..=head {
+				assert!(prove(number, head).verify(), "block {} of head {}", number, head);
+			}
+		}
+	}
+
+	#[test]
+	fn tampered_proofs_are_rejected() {
+		let proof = prove(5, 20);
+
+		let mut other_block = proof.clone();
+		other_block.block_hash = leaf(6);
+		assert!(!other_block.verify());
+
+		let mut other_number = proof.clone();
+		other_number.block_number = 4;
+		assert!(!other_number.verify());
+
+		let mut other_head = proof.clone();
+		other_head.head_hash = leaf(19);
+		assert!(!other_head.verify());
+
+		let mut descendant = proof.clone();
+		descendant.block_number = 21;
+		assert!(!descendant.verify());
+
+		let mut other_root = proof;
+		other_root.root = H256::zero();
+		assert!(!other_root.verify());
+	}
+}
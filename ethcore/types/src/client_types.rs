@@ -18,12 +18,13 @@
 
 use std::{
 	cmp,
+	collections::{BTreeMap, BTreeSet},
 	fmt::{Display, Formatter, Error as FmtError},
 	ops,
 	time::Duration,
 };
 
-use ethereum_types::U256;
+use ethereum_types::{Address, H256, U256};
 
 /// Operating mode for the client.
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -64,6 +65,42 @@ pub struct ClientReport {
 	pub state_db_mem: usize,
 	/// I/O statistics for the state DB.
 	pub io_stats: IoStats,
+	/// Statistics of the shared state cache.
+	pub state_cache: StateCacheStats,
+}
+
+/// Statistics of a single category of the state cache.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CacheCategoryStats {
+	/// Number of lookups answered from the cache.
+	pub hits: u64,
+	/// Number of lookups which missed the cache.
+	pub misses: u64,
+	/// Memory currently used, in bytes.
+	pub size: usize,
+	/// Memory budget, in bytes.
+	pub budget: usize,
+}
+
+impl CacheCategoryStats {
+	/// Ratio of lookups answered from the cache; `None` if there were no lookups.
+	pub fn hit_rate(&self) -> Option<f64> {
+		match self.hits + self.misses {
+			0 => None,
+			total => Some(self.hits as f64 / total as f64),
+		}
+	}
+}
+
+/// Statistics of the shared state cache.
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct StateCacheStats {
+	/// Cached accounts.
+	pub accounts: CacheCategoryStats,
+	/// Cached account storage.
+	pub storage: CacheCategoryStats,
+	/// Cached contract code.
+	pub code: CacheCategoryStats,
 }
 
 /// I/O statistics.
@@ -105,11 +142,28 @@ impl<'a> ops::Sub<&'a ClientReport> for ClientReport {
 		self.transactions_applied -= other.transactions_applied;
 		self.gas_processed = self.gas_processed - other.gas_processed;
 		self.state_db_mem = higher_mem - lower_mem;
+		// counters are reset when the state database is replaced.
+		let sub = |this: &mut CacheCategoryStats, other: &CacheCategoryStats| {
+			this.hits = this.hits.saturating_sub(other.hits);
+			this.misses = this.misses.saturating_sub(other.misses);
+		};
+		sub(&mut self.state_cache.accounts, &other.state_cache.accounts);
+		sub(&mut self.state_cache.storage, &other.state_cache.storage);
+		sub(&mut self.state_cache.code, &other.state_cache.code);
 
 		self
 	}
 }
 
+/// State written by the import of a block.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateWrites {
+	/// Accounts whose balance, nonce or code was changed, including created and removed ones.
+	pub accounts: BTreeSet<Address>,
+	/// Storage keys written, by account.
+	pub storage: BTreeMap<Address, BTreeSet<H256>>,
+}
+
 /// Result to be used during get address code at given block's state
 pub enum StateResult<T> {
 	/// State is missing
@@ -0,0 +1,50 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Confirmation status of a transaction.
+
+use ethereum_types::{H256, U256};
+use crate::BlockNumber;
+
+/// How safely a transaction is included in the canonical chain.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConfirmationStatus {
+	/// Hash of the block including the transaction.
+	pub block_hash: H256,
+	/// Number of the block including the transaction.
+	pub block_number: BlockNumber,
+	/// Number of canonical blocks from the including block up to the best one, inclusive.
+	pub depth: u64,
+	/// Total difficulty of the best block.
+	pub total_difficulty: U256,
+	/// Heaviest known branch which doesn't include the transaction, if any.
+	pub competing_branch: Option<CompetingBranch>,
+	/// Whether the engine has finalized the including block.
+	pub finalized: bool,
+}
+
+/// A branch forking off the canonical chain below a given block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompetingBranch {
+	/// Hash of the branch's best block.
+	pub hash: H256,
+	/// Number of the canonical block the branch forks off.
+	pub fork_number: BlockNumber,
+	/// Number of blocks in the branch.
+	pub length: u64,
+	/// Total difficulty of the branch's best block.
+	pub total_difficulty: U256,
+}
@@ -16,7 +16,7 @@
 
 //! Epoch verifiers and transitions.
 
-use ethereum_types::H256;
+use ethereum_types::{Address, H256};
 use rlp::{Encodable, Decodable, DecoderError, RlpStream, Rlp};
 
 /// A full epoch transition.
@@ -69,3 +69,16 @@ impl Decodable for PendingTransition {
 		})
 	}
 }
+
+/// The validator set of an epoch together with the stored transition proving it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochValidators {
+	/// Hash of the block at which the epoch started.
+	pub transition_hash: H256,
+	/// Number of the block at which the epoch started.
+	pub transition_number: u64,
+	/// "transition/epoch" proof from the engine combined with a finality proof.
+	pub proof: Vec<u8>,
+	/// Validators allowed to seal blocks of the epoch.
+	pub validators: Vec<Address>,
+}
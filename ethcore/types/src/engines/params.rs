@@ -16,6 +16,8 @@
 
 //! Engine-specific parameter types.
 
+use std::collections::BTreeMap;
+
 use ethereum_types::{Address, U256, H256};
 use bytes::Bytes;
 use crate::{
@@ -24,6 +26,8 @@ use crate::{
 };
 
 const MAX_TRANSACTION_SIZE: usize = 300 * 1024;
+/// Gas available to system contract calls unless specified in the spec.
+const DEFAULT_SYSTEM_CALL_GAS: u64 = 1_000_000;
 
 /// Parameters common to ethereum-like blockchains.
 /// NOTE: when adding bugfix hard-fork parameters,
@@ -106,6 +110,10 @@ pub struct CommonParams {
 	pub eip2200_advance_transition: BlockNumber,
 	/// Number of first block where EIP-2028 rules begin.
 	pub eip2315_transition: BlockNumber,
+	/// Number of first block where the experimental EIP-3074 AUTH and AUTHCALL opcodes are enabled.
+	pub eip3074_transition: BlockNumber,
+	/// Number of first block where EIP-1559 base fees are charged. Only proof-of-stake blocks carry a base fee.
+	pub eip1559_transition: BlockNumber,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
 	pub dust_protection_transition: BlockNumber,
 	/// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -137,6 +145,66 @@ pub struct CommonParams {
 	pub transaction_permission_contract_transition: BlockNumber,
 	/// Maximum size of transaction's RLP payload
 	pub max_transaction_size: usize,
+	/// Contracts called by the system address at the start or the end of every block.
+	pub system_contracts: Vec<SystemContract>,
+	/// Total difficulty after which blocks are produced by a consensus client, if the chain merges.
+	pub terminal_total_difficulty: Option<U256>,
+	/// Recipients of the transaction fees from the given blocks on.
+	pub fee_recipient_transitions: BTreeMap<BlockNumber, vm::FeeRecipient>,
+	/// Recipients of the base fee part of the transaction fees from the given blocks on.
+	pub base_fee_recipient_transitions: BTreeMap<BlockNumber, vm::FeeRecipient>,
+}
+
+/// Point of the block processing at which a system contract is called.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SystemCallPhase {
+	/// Before the first transaction of the block.
+	BlockStart,
+	/// After the last transaction of the block, before the block rewards.
+	BlockEnd,
+}
+
+/// Contract called by the system address in every block.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SystemContract {
+	/// Contract address.
+	pub address: Address,
+	/// When the contract is called.
+	pub phase: SystemCallPhase,
+	/// Call data.
+	pub data: Bytes,
+	/// Gas available to the call.
+	pub gas: U256,
+	/// First block in which the contract is called.
+	pub activate_at: BlockNumber,
+	/// First block in which the contract is not called anymore.
+	pub deactivate_at: BlockNumber,
+	/// Code deployed at the contract address in the activation block.
+	pub code: Option<Bytes>,
+}
+
+impl SystemContract {
+	/// Returns true if the contract is called in the given block.
+	pub fn is_active(&self, block_number: BlockNumber) -> bool {
+		block_number >= self.activate_at && block_number < self.deactivate_at
+	}
+}
+
+impl From<ethjson::spec::SystemContract> for SystemContract {
+	fn from(c: ethjson::spec::SystemContract) -> Self {
+		SystemContract {
+			address: c.address.into(),
+			phase: match c.phase {
+				ethjson::spec::SystemCallPhase::BlockStart => SystemCallPhase::BlockStart,
+				ethjson::spec::SystemCallPhase::BlockEnd => SystemCallPhase::BlockEnd,
+			},
+			data: c.data.map_or_else(Vec::new, Into::into),
+			gas: c.gas.map_or(DEFAULT_SYSTEM_CALL_GAS.into(), Into::into),
+			activate_at: c.activate_at.map_or(0, Into::into),
+			deactivate_at: c.deactivate_at.map_or_else(BlockNumber::max_value, Into::into),
+			code: c.code.map(Into::into),
+		}
+	}
 }
 
 impl CommonParams {
@@ -158,6 +226,19 @@ impl CommonParams {
 		}
 	}
 
+	/// Recipient of the transaction fees of the given block, the author unless the spec says otherwise.
+	pub fn fee_recipient(&self, block_number: u64) -> vm::FeeRecipient {
+		self.fee_recipient_transitions.range(..=block_number).next_back()
+			.map_or(vm::FeeRecipient::Author, |(_, recipient)| *recipient)
+	}
+
+	/// Recipient of the base fee part of the transaction fees of the given block, burnt as in
+	/// EIP-1559 unless the spec says otherwise.
+	pub fn base_fee_recipient(&self, block_number: u64) -> vm::FeeRecipient {
+		self.base_fee_recipient_transitions.range(..=block_number).next_back()
+			.map_or(vm::FeeRecipient::Burn, |(_, recipient)| *recipient)
+	}
+
 	/// Returns max code size at given block.
 	pub fn max_code_size(&self, block_number: u64) -> u64 {
 		if block_number >= self.max_code_size_transition {
@@ -177,6 +258,7 @@ impl CommonParams {
 		schedule.have_extcodehash = block_number >= self.eip1052_transition;
 		schedule.have_chain_id = block_number >= self.eip1344_transition;
 		schedule.have_subs = block_number >= self.eip2315_transition;
+		schedule.have_auth = block_number >= self.eip3074_transition;
 		schedule.eip1283 =
 			(block_number >= self.eip1283_transition &&
 			 !(block_number >= self.eip1283_disable_transition)) ||
@@ -241,6 +323,19 @@ impl CommonParams {
 	}
 }
 
+fn fee_recipient_transitions(transitions: Option<BTreeMap<ethjson::uint::Uint, ethjson::spec::FeeRecipient>>) -> BTreeMap<BlockNumber, vm::FeeRecipient> {
+	transitions.map_or_else(BTreeMap::new, |transitions| {
+		transitions.into_iter().map(|(block, recipient)| {
+			let recipient = match recipient {
+				ethjson::spec::FeeRecipient::Address(address) => vm::FeeRecipient::Address(address.into()),
+				ethjson::spec::FeeRecipient::Keyword(ethjson::spec::FeeRecipientKeyword::Author) => vm::FeeRecipient::Author,
+				ethjson::spec::FeeRecipient::Keyword(ethjson::spec::FeeRecipientKeyword::Burn) => vm::FeeRecipient::Burn,
+			};
+			(block.into(), recipient)
+		}).collect()
+	})
+}
+
 impl From<ethjson::spec::Params> for CommonParams {
 	fn from(p: ethjson::spec::Params) -> Self {
 		CommonParams {
@@ -348,6 +443,14 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into,
 			),
+			eip3074_transition: p.eip3074_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_transition: p.eip1559_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
 			dust_protection_transition: p.dust_protection_transition.map_or_else(
 				BlockNumber::max_value,
 				Into::into,
@@ -376,6 +479,12 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into
 			),
+			system_contracts: p.system_contracts.map_or_else(Vec::new, |contracts| {
+				contracts.into_iter().map(Into::into).collect()
+			}),
+			terminal_total_difficulty: p.terminal_total_difficulty.map(Into::into),
+			fee_recipient_transitions: fee_recipient_transitions(p.fee_recipient_transitions),
+			base_fee_recipient_transitions: fee_recipient_transitions(p.base_fee_recipient_transitions),
 		}
 	}
 }
@@ -68,6 +68,21 @@ pub enum EngineError {
 	CliqueInvalidNonce(H64),
 	/// The signer signed a block to recently
 	CliqueTooRecentlySigned(Address),
+	/// The external consensus process rejected the request
+	ExternalConsensus(String),
+	/// The external consensus process could not be reached or gave no usable answer, the request
+	/// may succeed when retried
+	ExternalConsensusUnavailable(String),
+	/// Proof-of-stake block whose parent is below the terminal total difficulty
+	TerminalTotalDifficultyNotReached(H256),
+	/// Proof-of-work block whose parent already reached the terminal total difficulty
+	TerminalTotalDifficultyPassed(H256),
+	/// The engine parameters cannot be updated at runtime
+	ParamsReloadUnsupported,
+	/// Rejected update of the engine parameters
+	InvalidParamsUpdate(String),
+	/// The sealing journal refused to sign a block
+	SealRefused(String),
 	/// Custom
 	Custom(String),
 }
@@ -86,6 +101,13 @@ impl fmt::Display for EngineError {
 			CliqueWrongAuthorCheckpoint(ref oob) => format!("Unexpected checkpoint author: {}", oob),
 			CliqueFaultyRecoveredSigners(ref mis) => format!("Faulty recovered signers {:?}", mis),
 			CliqueTooRecentlySigned(ref address) => format!("The signer: {} has signed a block too recently", address),
+			ExternalConsensus(ref msg) => format!("External consensus engine: {}", msg),
+			ExternalConsensusUnavailable(ref msg) => format!("External consensus engine unavailable: {}", msg),
+			TerminalTotalDifficultyNotReached(ref parent) => format!("Proof-of-stake block on top of {} which is below the terminal total difficulty", parent),
+			TerminalTotalDifficultyPassed(ref parent) => format!("Proof-of-work block on top of {} which reached the terminal total difficulty", parent),
+			ParamsReloadUnsupported => "The engine parameters cannot be updated while the node is running".into(),
+			InvalidParamsUpdate(ref msg) => format!("Invalid engine parameters update: {}", msg),
+			SealRefused(ref msg) => format!("Refused to seal, {}", msg),
 			Custom(ref s) => s.clone(),
 			DoubleVote(ref address) => format!("Author {} issued too many blocks.", address),
 			NotProposer(ref mis) => format!("Author is not a current proposer: {}", mis),
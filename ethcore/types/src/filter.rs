@@ -17,6 +17,7 @@
 //! Blockchain filter
 
 use crate::{
+	BlockNumber,
 	ids::BlockId,
 	log_entry::LogEntry,
 };
@@ -109,6 +110,16 @@ impl Filter {
 	}
 }
 
+/// Error of a search for the logs of blocks whose receipts are no longer stored.
+#[derive(Debug, PartialEq)]
+pub enum ReconstructLogsError {
+	/// One of the blocks of the filter can't be found.
+	UnknownBlock(BlockId),
+	/// Blocks in this range, inclusive, lost their receipts and the state to re-execute them is
+	/// pruned and can't be rebuilt from a state checkpoint either.
+	StatePruned(BlockNumber, BlockNumber),
+}
+
 #[cfg(test)]
 mod tests {
 	use ethereum_types::{Bloom, Address, H256};
@@ -0,0 +1,239 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Gas usage and price statistics over a range of blocks.
+
+use std::collections::HashMap;
+
+use ethereum_types::{Address, U256};
+use parity_util_mem::MallocSizeOf;
+
+use crate::{
+	BlockNumber,
+	encoded,
+	receipt::Receipt,
+	transaction::Action,
+};
+
+/// Percentiles of the transaction gas price reported by `GasStats`.
+pub const GAS_PRICE_PERCENTILES: [u8; 5] = [10, 25, 50, 75, 90];
+
+/// Gas consumed by calls to a contract.
+#[derive(Debug, PartialEq, Clone, MallocSizeOf)]
+pub struct ContractGas {
+	/// Address of the contract.
+	pub address: Address,
+	/// Gas used by transactions calling it.
+	pub gas_used: U256,
+	/// Number of transactions calling it.
+	pub transactions: u64,
+}
+
+/// Gas statistics of a single block, computed from its transactions and receipts.
+#[derive(Debug, PartialEq, Clone, MallocSizeOf)]
+pub struct BlockGasStats {
+	/// Block number.
+	pub number: BlockNumber,
+	/// Gas used by the block.
+	pub gas_used: U256,
+	/// Block gas limit.
+	pub gas_limit: U256,
+	/// Gas price and gas used of every transaction, in block order.
+	pub transactions: Vec<(U256, U256)>,
+	/// Gas used by calls to contracts, sorted by address.
+	pub contracts: Vec<ContractGas>,
+}
+
+impl BlockGasStats {
+	/// Compute the statistics of a block. The receipts must be those of the block's transactions.
+	pub fn new(block: &encoded::Block, receipts: &[Receipt]) -> Self {
+		let mut transactions = Vec::with_capacity(receipts.len());
+		let mut contracts = HashMap::<Address, ContractGas>::new();
+		let mut cumulative = U256::zero();
+		for (tx, receipt) in block.transactions().iter().zip(receipts) {
+			let gas_used = receipt.gas_used.saturating_sub(cumulative);
+			cumulative = receipt.gas_used;
+			transactions.push((tx.gas_price, gas_used));
+
+			// plain value transfers carry no data, anything else is treated as a contract call
+			if let Action::Call(address) = tx.action {
+				if !tx.data.is_empty() {
+					let entry = contracts.entry(address).or_insert_with(|| ContractGas {
+						address,
+						gas_used: U256::zero(),
+						transactions: 0,
+					});
+					entry.gas_used = entry.gas_used.saturating_add(gas_used);
+					entry.transactions += 1;
+				}
+			}
+		}
+
+		let mut contracts: Vec<_> = contracts.into_iter().map(|(_, c)| c).collect();
+		contracts.sort_by_key(|c| c.address);
+
+		BlockGasStats {
+			number: block.number(),
+			gas_used: block.gas_used(),
+			gas_limit: block.gas_limit(),
+			transactions,
+			contracts,
+		}
+	}
+}
+
+/// Gas statistics aggregated over a range of blocks.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct GasStats {
+	/// First block of the range.
+	pub from: BlockNumber,
+	/// Last block of the range.
+	pub to: BlockNumber,
+	/// Number of transactions in the range.
+	pub transactions: u64,
+	/// Total gas used.
+	pub gas_used: U256,
+	/// Total gas limit.
+	pub gas_limit: U256,
+	/// Gas used and gas limit of every block, in block order.
+	pub blocks: Vec<(BlockNumber, U256, U256)>,
+	/// Average gas price, weighted by the gas each transaction used.
+	pub average_gas_price: U256,
+	/// Transaction gas prices at `GAS_PRICE_PERCENTILES`.
+	pub gas_price_percentiles: Vec<(u8, U256)>,
+	/// Contracts which consumed the most gas, most expensive first.
+	pub top_contracts: Vec<ContractGas>,
+}
+
+impl GasStats {
+	/// Aggregate the statistics of consecutive blocks, keeping at most `top` contracts.
+	/// Returns `None` if `blocks` is empty.
+	pub fn aggregate(blocks: &[BlockGasStats], top: usize) -> Option<Self> {
+		let first = blocks.first()?;
+		let last = blocks.last()?;
+
+		let mut stats = GasStats {
+			from: first.number,
+			to: last.number,
+			..Default::default()
+		};
+		let mut prices = Vec::new();
+		let mut weighted = U256::zero();
+		let mut tx_gas = U256::zero();
+		let mut contracts = HashMap::<Address, ContractGas>::new();
+
+		for block in blocks {
+			stats.gas_used = stats.gas_used.saturating_add(block.gas_used);
+			stats.gas_limit = stats.gas_limit.saturating_add(block.gas_limit);
+			stats.blocks.push((block.number, block.gas_used, block.gas_limit));
+			for &(price, gas) in &block.transactions {
+				prices.push(price);
+				weighted = weighted.saturating_add(price.saturating_mul(gas));
+				tx_gas = tx_gas.saturating_add(gas);
+			}
+			for contract in &block.contracts {
+				let entry = contracts.entry(contract.address).or_insert_with(|| ContractGas {
+					address: contract.address,
+					gas_used: U256::zero(),
+					transactions: 0,
+				});
+				entry.gas_used = entry.gas_used.saturating_add(contract.gas_used);
+				entry.transactions += contract.transactions;
+			}
+		}
+
+		stats.transactions = prices.len() as u64;
+		if !tx_gas.is_zero() {
+			stats.average_gas_price = weighted / tx_gas;
+		}
+		if !prices.is_empty() {
+			prices.sort();
+			stats.gas_price_percentiles = GAS_PRICE_PERCENTILES.iter()
+				.map(|&p| (p, prices[(prices.len() - 1) * p as usize / 100]))
+				.collect();
+		}
+
+		let mut contracts: Vec<_> = contracts.into_iter().map(|(_, c)| c).collect();
+		contracts.sort_by(|a, b| b.gas_used.cmp(&a.gas_used).then(a.address.cmp(&b.address)));
+		contracts.truncate(top);
+		stats.top_contracts = contracts;
+
+		Some(stats)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn block(number: BlockNumber, transactions: Vec<(u64, u64)>, contracts: Vec<(u64, u64)>) -> BlockGasStats {
+		BlockGasStats {
+			number,
+			gas_used: transactions.iter().map(|&(_, g)| U256::from(g)).fold(U256::zero(), |a, b| a + b),
+			gas_limit: 1_000_000.into(),
+			transactions: transactions.into_iter().map(|(p, g)| (p.into(), g.into())).collect(),
+			contracts: contracts.into_iter().map(|(a, g)| ContractGas {
+				address: Address::from_low_u64_be(a),
+				gas_used: g.into(),
+				transactions: 1,
+			}).collect(),
+		}
+	}
+
+	#[test]
+	fn should_return_none_for_no_blocks() {
+		assert_eq!(GasStats::aggregate(&[], 10), None);
+	}
+
+	#[test]
+	fn should_aggregate_blocks() {
+		let blocks = vec![
+			block(5, vec![(10, 21_000), (30, 100_000)], vec![(1, 100_000)]),
+			block(6, vec![], vec![]),
+			block(7, vec![(20, 50_000)], vec![(2, 50_000)]),
+		];
+		let stats = GasStats::aggregate(&blocks, 1).unwrap();
+
+		assert_eq!(stats.from, 5);
+		assert_eq!(stats.to, 7);
+		assert_eq!(stats.transactions, 3);
+		assert_eq!(stats.gas_used, 171_000.into());
+		assert_eq!(stats.gas_limit, 3_000_000.into());
+		assert_eq!(stats.blocks.len(), 3);
+		// (10 * 21000 + 30 * 100000 + 20 * 50000) / 171000
+		assert_eq!(stats.average_gas_price, 24.into());
+		assert_eq!(stats.gas_price_percentiles, vec![
+			(10, 10.into()), (25, 10.into()), (50, 20.into()), (75, 20.into()), (90, 20.into()),
+		]);
+		assert_eq!(stats.top_contracts.len(), 1);
+		assert_eq!(stats.top_contracts[0].address, Address::from_low_u64_be(1));
+	}
+
+	#[test]
+	fn should_sum_contract_gas_across_blocks() {
+		let blocks = vec![
+			block(1, vec![(1, 60_000)], vec![(1, 60_000)]),
+			block(2, vec![(1, 50_000), (1, 40_000)], vec![(2, 50_000), (1, 40_000)]),
+		];
+		let stats = GasStats::aggregate(&blocks, 10).unwrap();
+
+		assert_eq!(stats.top_contracts[0].address, Address::from_low_u64_be(1));
+		assert_eq!(stats.top_contracts[0].gas_used, 100_000.into());
+		assert_eq!(stats.top_contracts[0].transactions, 2);
+		assert_eq!(stats.top_contracts[1].gas_used, 50_000.into());
+	}
+}
@@ -44,6 +44,7 @@ pub mod views;
 
 pub mod account_diff;
 pub mod ancestry_action;
+pub mod ancestry_proof;
 pub mod basic_account;
 pub mod block;
 pub mod block_status;
@@ -51,10 +52,12 @@ pub mod blockchain_info;
 pub mod call_analytics;
 pub mod chain_notify;
 pub mod client_types;
+pub mod confirmation_status;
 pub mod encoded;
 pub mod engines;
 pub mod errors;
 pub mod filter;
+pub mod gas_stats;
 pub mod header;
 pub mod ids;
 pub mod io_message;
@@ -65,9 +68,11 @@ pub mod receipt;
 pub mod security_level;
 pub mod snapshot;
 pub mod state_diff;
+pub mod threat_status;
 pub mod trace_filter;
 pub mod transaction;
 pub mod tree_route;
+pub mod validator_status;
 pub mod verification;
 pub mod data_format;
 
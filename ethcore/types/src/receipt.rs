@@ -20,6 +20,7 @@ use crate::{
 	log_entry::{LogEntry, LocalizedLogEntry},
 	BlockNumber,
 };
+use bytes::Bytes;
 use ethereum_types::{H160, H256, U256, Address, Bloom};
 use parity_util_mem::MallocSizeOf;
 use rlp::{Rlp, RlpStream, Encodable, Decodable, DecoderError};
@@ -169,6 +170,29 @@ pub struct LocalizedReceipt {
 	pub from: H160
 }
 
+/// Merkle-Patricia inclusion proof of a transaction and its receipt within a canonical block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptProof {
+	/// Block hash.
+	pub block_hash: H256,
+	/// Block number.
+	pub block_number: BlockNumber,
+	/// Transaction index, whose RLP encoding is the key in both tries.
+	pub transaction_index: usize,
+	/// Receipts root of the block header.
+	pub receipts_root: H256,
+	/// RLP-encoded receipt.
+	pub receipt: Bytes,
+	/// Trie nodes proving the receipt, in order from the root.
+	pub receipt_proof: Vec<Bytes>,
+	/// Transactions root of the block header.
+	pub transactions_root: H256,
+	/// RLP-encoded transaction.
+	pub transaction: Bytes,
+	/// Trie nodes proving the transaction, in order from the root.
+	pub transaction_proof: Vec<Bytes>,
+}
+
 #[cfg(test)]
 mod tests {
 	use std::str::FromStr;
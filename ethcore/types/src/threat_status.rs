@@ -0,0 +1,62 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Signs of a majority attack on the chain.
+
+/// How likely it is that the chain is under a majority attack.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ThreatLevel {
+	/// Nothing unusual was observed.
+	Normal,
+	/// Competing branches or reorganizations beyond what honest forks produce were observed.
+	Elevated,
+	/// An attack is likely, deposits should wait for more confirmations.
+	High,
+}
+
+impl Default for ThreatLevel {
+	fn default() -> Self {
+		ThreatLevel::Normal
+	}
+}
+
+/// Signs of an attack observed in the most recently imported blocks.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ThreatStatus {
+	/// Overall threat level.
+	pub level: ThreatLevel,
+	/// What raised the level above normal.
+	pub reasons: Vec<String>,
+	/// Number of recently imported blocks the status is computed over.
+	pub window: u64,
+	/// Imported blocks which did not become canon.
+	pub side_blocks: u64,
+	/// Length of the longest competing branch, from its common ancestor with the canon chain.
+	pub longest_side_branch: u64,
+	/// Canon blocks imported per hour.
+	pub canon_rate: u64,
+	/// Blocks imported per hour on the fastest growing competing branch.
+	pub fastest_side_branch_rate: u64,
+	/// Largest difficulty change between a block and its parent, in per mille.
+	pub max_difficulty_swing: u64,
+	/// Reorganizations retracting more blocks than honest forks usually do.
+	pub deep_reorgs: u64,
+	/// Largest number of canon blocks retracted, or held back from being retracted, by a single import.
+	pub max_reorg_depth: u64,
+	/// Blocks kept out of the canon chain for reorganizing it deeper than allowed.
+	pub held_blocks: u64,
+}
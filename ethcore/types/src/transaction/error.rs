@@ -75,6 +75,8 @@ pub enum Error {
 	RecipientBanned,
 	/// Contract creation code is banned.
 	CodeBanned,
+	/// Called function selector is banned.
+	SelectorBanned,
 	/// Invalid chain ID given.
 	InvalidChainId,
 	/// Not enough permissions given by permission contract.
@@ -123,6 +125,7 @@ impl fmt::Display for Error {
 			SenderBanned => "Sender is temporarily banned.".into(),
 			RecipientBanned => "Recipient is temporarily banned.".into(),
 			CodeBanned => "Contract code is temporarily banned.".into(),
+			SelectorBanned => "Called function is banned.".into(),
 			InvalidChainId => "Transaction of this chain ID is not allowed on this chain.".into(),
 			InvalidSignature(ref err) => format!("Transaction has invalid signature: {}.", err),
 			NotAllowed => "Sender does not have permissions to execute this type of transaction".into(),
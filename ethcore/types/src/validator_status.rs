@@ -0,0 +1,65 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Sealing duties of the local validator.
+
+use ethereum_types::Address;
+
+/// What happened in a step the local validator was expected to seal.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SlotOutcome {
+	/// The step is still running.
+	Pending,
+	/// A block was sealed in time.
+	Sealed,
+	/// A block was sealed, but only the given number of milliseconds into the step.
+	Late(u64),
+	/// An empty step message was broadcast instead of a block.
+	EmptyStep,
+	/// The step passed without a seal.
+	Missed,
+}
+
+/// A step the local validator was expected to seal.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DutySlot {
+	/// The step number.
+	pub step: u64,
+	/// What happened in the step.
+	pub outcome: SlotOutcome,
+}
+
+/// Sealing duties of the local validator since the node started.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ValidatorStatus {
+	/// Address of the local signer, if any.
+	pub signer: Option<Address>,
+	/// The current step.
+	pub current_step: u64,
+	/// The next step after the current one the local signer is expected to seal, if any.
+	pub next_slot: Option<u64>,
+	/// Number of steps the local signer was expected to seal.
+	pub expected: u64,
+	/// Number of expected steps sealed in time, including empty steps.
+	pub sealed: u64,
+	/// Number of expected steps sealed late.
+	pub late: u64,
+	/// Number of expected steps which passed without a seal.
+	pub missed: u64,
+	/// The most recent expected steps, oldest first.
+	pub recent_slots: Vec<DutySlot>,
+}
@@ -57,8 +57,7 @@ fn ethash_params() -> EthashParams {
 		},
 		expip2_transition: u64::max_value(),
 		expip2_duration_limit: 30,
-		block_reward_contract_transition: 0,
-		block_reward_contract: None,
+		block_reward_contract_transitions: BTreeMap::new(),
 		difficulty_bomb_delays: {
 			let mut m = BTreeMap::new();
 			m.insert(4370000, 3000000);
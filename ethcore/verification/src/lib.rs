@@ -25,7 +25,7 @@ pub mod queue;
 pub mod test_helpers;
 
 pub use self::verification::{FullFamilyParams, verify_block_family, verify_block_final};
-pub use self::queue::{BlockQueue, Config as QueueConfig};
+pub use self::queue::{BlockQueue, Config as QueueConfig, QueueTimings};
 
 /// Verifier type.
 #[derive(Debug, PartialEq, Clone)]
@@ -73,6 +73,9 @@ pub trait Kind: 'static + Sized + Send + Sync {
 
 	/// Attempt to verify the `Unverified` item using the given engine.
 	fn verify(unverified: Self::Unverified, engine: &dyn Engine, check_seal: bool) -> Result<Self::Verified, Error>;
+
+	/// Turn a verified item back into an input, so that it can be verified again.
+	fn unverify(verified: Self::Verified) -> Self::Input;
 }
 
 /// The blocks verification module.
@@ -126,6 +129,10 @@ pub mod blocks {
 				}
 			}
 		}
+
+		fn unverify((_, bytes): Self::Verified) -> Self::Input {
+			Unverified::from_rlp(bytes).expect("the block was decoded from these bytes when it was queued; qed")
+		}
 	}
 
 	impl BlockLike for Unverified {
@@ -213,5 +220,9 @@ pub mod headers {
 				false => Ok(unverified),
 			}
 		}
+
+		fn unverify(verified: Self::Verified) -> Self::Input {
+			verified
+		}
 	}
 }
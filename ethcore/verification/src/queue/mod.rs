@@ -21,11 +21,12 @@ use std::thread::{self, JoinHandle};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::cmp;
+use std::time::SystemTime;
 use std::collections::{VecDeque, HashSet, HashMap};
 use common_types::{
 	block_status::BlockStatus,
 	io_message::ClientIoMessage,
-	errors::{BlockError, EthcoreError as Error, ImportError},
+	errors::{BlockError, EngineError, EthcoreError as Error, ImportError},
 	verification::VerificationQueueInfo as QueueInfo,
 };
 use ethcore_io::*;
@@ -43,12 +44,34 @@ pub mod kind;
 const MIN_MEM_LIMIT: usize = 16384;
 const MIN_QUEUE_LIMIT: usize = 512;
 
+/// Whether the verification of an item failed for a reason which may go away, e.g. a block from
+/// the future or an unreachable external consensus engine. Such items aren't marked bad.
+pub fn is_transient(error: &Error) -> bool {
+	match *error {
+		Error::Block(BlockError::TemporarilyInvalid(_)) |
+		Error::Engine(EngineError::ExternalConsensusUnavailable(_)) => true,
+		_ => false,
+	}
+}
+
 /// Type alias for block queue convenience.
 pub type BlockQueue<C> = VerificationQueue<self::kind::Blocks, C>;
 
 /// Type alias for header queue convenience.
 pub type HeaderQueue<C> = VerificationQueue<self::kind::Headers, C>;
 
+/// Times at which an item moved through the queue. Recorded only when enabled
+/// with `VerificationQueue::set_record_timings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueTimings {
+	/// When the item was added to the queue.
+	pub enqueued: SystemTime,
+	/// When a verifier thread picked the item up.
+	pub verification_started: Option<SystemTime>,
+	/// When verification finished.
+	pub verified: Option<SystemTime>,
+}
+
 /// Verification queue configuration
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
@@ -197,8 +220,23 @@ struct Verification<K: Kind> {
 	verifying: LenCachingMutex<VecDeque<Verifying<K>>>,
 	verified: LenCachingMutex<VecDeque<K::Verified>>,
 	bad: Mutex<HashSet<H256>>,
+	// Items whose verification failed for a transient reason and their descendants, which may be
+	// queued again.
+	dropped: Mutex<HashSet<H256>>,
+	timings: Mutex<HashMap<H256, QueueTimings>>,
 	sizes: Sizes,
 	check_seal: bool,
+	record_timings: AtomicBool,
+}
+
+impl<K: Kind> Verification<K> {
+	fn note_timing<F: FnOnce(&mut QueueTimings)>(&self, hash: &H256, f: F) {
+		if self.record_timings.load(AtomicOrdering::Relaxed) {
+			if let Some(timings) = self.timings.lock().get_mut(hash) {
+				f(timings);
+			}
+		}
+	}
 }
 
 impl<K: Kind, C> VerificationQueue<K, C> {
@@ -209,12 +247,15 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 			verifying: LenCachingMutex::new(VecDeque::new()),
 			verified: LenCachingMutex::new(VecDeque::new()),
 			bad: Mutex::new(HashSet::new()),
+			dropped: Mutex::new(HashSet::new()),
+			timings: Mutex::new(HashMap::new()),
 			sizes: Sizes {
 				unverified: AtomicUsize::new(0),
 				verifying: AtomicUsize::new(0),
 				verified: AtomicUsize::new(0),
 			},
 			check_seal,
+			record_timings: AtomicBool::new(false),
 		});
 		let more_to_verify = Arc::new(Condvar::new());
 		let deleting = Arc::new(AtomicBool::new(false));
@@ -356,8 +397,10 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 			};
 
 			let hash = item.hash();
+			verification.note_timing(&hash, |t| t.verification_started = Some(SystemTime::now()));
 			let is_ready = match K::verify(item, &*engine, verification.check_seal) {
 				Ok(verified) => {
+					verification.note_timing(&hash, |t| t.verified = Some(SystemTime::now()));
 					let mut verifying = verification.verifying.lock();
 					let mut idx = None;
 					for (i, e) in verifying.iter_mut().enumerate() {
@@ -374,22 +417,30 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 						// we're next!
 						let mut verified = verification.verified.lock();
 						let mut bad = verification.bad.lock();
-						VerificationQueue::<_, C>::drain_verifying(&mut verifying, &mut verified, &mut bad, &verification.sizes);
+						let mut dropped = verification.dropped.lock();
+						VerificationQueue::<_, C>::drain_verifying(&mut verifying, &mut verified, &mut bad, &mut dropped, &verification.sizes);
 						true
 					} else {
 						false
 					}
 				},
-				Err(_) => {
+				Err(e) => {
 					let mut verifying = verification.verifying.lock();
 					let mut verified = verification.verified.lock();
 					let mut bad = verification.bad.lock();
+					let mut dropped = verification.dropped.lock();
 
-					bad.insert(hash.clone());
+					if is_transient(&e) {
+						debug!(target: "verification", "Dropping {} from the queue: {}", hash, e);
+						dropped.insert(hash.clone());
+					} else {
+						bad.insert(hash.clone());
+					}
 					verifying.retain(|e| e.hash != hash);
+					verification.timings.lock().remove(&hash);
 
 					if verifying.front().map_or(false, |x| x.output.is_some()) {
-						VerificationQueue::<_, C>::drain_verifying(&mut verifying, &mut verified, &mut bad, &verification.sizes);
+						VerificationQueue::<_, C>::drain_verifying(&mut verifying, &mut verified, &mut bad, &mut dropped, &verification.sizes);
 						true
 					} else {
 						false
@@ -407,6 +458,7 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 		verifying: &mut VecDeque<Verifying<K>>,
 		verified: &mut VecDeque<K::Verified>,
 		bad: &mut HashSet<H256>,
+		dropped: &mut HashSet<H256>,
 		sizes: &Sizes,
 	) {
 		let mut removed_size = 0;
@@ -419,6 +471,8 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 
 			if bad.contains(&output.parent_hash()) {
 				bad.insert(output.hash());
+			} else if dropped.contains(&output.parent_hash()) {
+				dropped.insert(output.hash());
 			} else {
 				inserted_size += size;
 				verified.push_back(output);
@@ -444,7 +498,22 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 		sizes.verified.store(0, AtomicOrdering::Release);
 		*self.total_difficulty.write() = 0.into();
 
+		self.verification.dropped.lock().clear();
 		self.processing.write().clear();
+		self.verification.timings.lock().clear();
+	}
+
+	/// Start or stop recording `QueueTimings` of imported items.
+	pub fn set_record_timings(&self, record: bool) {
+		self.verification.record_timings.store(record, AtomicOrdering::Relaxed);
+		if !record {
+			self.verification.timings.lock().clear();
+		}
+	}
+
+	/// Remove and return recorded timings of the given item.
+	pub fn take_timings(&self, hash: &H256) -> Option<QueueTimings> {
+		self.verification.timings.lock().remove(hash)
 	}
 
 	/// Wait for unverified queue to be empty
@@ -455,8 +524,34 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 		}
 	}
 
+	/// Verify all queued items again, e.g. after the engine parameters changed. Waits for the items
+	/// being verified; the verified ones go back through both verification stages.
+	pub fn reverify(&self) {
+		self.flush();
+
+		let items = {
+			let mut verified = self.verification.verified.lock();
+			let drained_size = verified.iter().map(MallocSizeOfExt::malloc_size_of).sum();
+			self.verification.sizes.verified.fetch_sub(drained_size, AtomicOrdering::SeqCst);
+			verified.drain(..).collect::<Vec<_>>()
+		};
+		if items.is_empty() {
+			return;
+		}
+
+		debug!(target: "verification", "Re-verifying {} queued items", items.len());
+		let hashes: Vec<_> = items.iter().map(BlockLike::hash).collect();
+		self.mark_as_good(&hashes);
+		for item in items {
+			if let Err((e, _)) = self.import(K::unverify(item)) {
+				debug!(target: "verification", "Queued item failed re-verification: {}", e);
+			}
+		}
+	}
+
 	/// Check if the item is currently in the queue
 	pub fn status(&self, hash: &H256) -> Status {
+		self.purge_dropped();
 		if self.processing.read().contains_key(hash) {
 			return Status::Queued;
 		}
@@ -472,7 +567,9 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 	pub fn import(&self, input: K::Input) -> Result<H256, (Error, Option<K::Input>)> {
 		let hash = input.hash();
 		let raw_hash = input.raw_hash();
+		self.purge_dropped();
 		{
+			self.verification.dropped.lock().remove(&hash);
 			if self.processing.read().contains_key(&hash) {
 				return Err((Error::Import(ImportError::AlreadyQueued), Some(input)));
 			}
@@ -498,14 +595,21 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 					let mut td = self.total_difficulty.write();
 					*td = *td + item.difficulty();
 				}
+				if self.verification.record_timings.load(AtomicOrdering::Relaxed) {
+					self.verification.timings.lock().insert(hash, QueueTimings {
+						enqueued: SystemTime::now(),
+						verification_started: None,
+						verified: None,
+					});
+				}
 				self.verification.unverified.lock().push_back(item);
 				self.more_to_verify.notify_all();
 				Ok(hash)
 			},
 			Err((err, input)) => {
 				match err {
-					// Don't mark future blocks as bad.
-					Error::Block(BlockError::TemporarilyInvalid(_)) => {},
+					// Don't mark future blocks or blocks which may verify later as bad.
+					ref err if is_transient(err) => {},
 					// If the transaction root or uncles hash is invalid, it doesn't necessarily mean
 					// that the header is invalid. We might have just received a malformed block body,
 					// so we shouldn't put the header hash to `bad`.
@@ -528,16 +632,33 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 	/// Mark given item and all its children as bad. pauses verification
 	/// until complete.
 	pub fn mark_as_bad(&self, hashes: &[H256]) {
+		self.remove_with_children(hashes, false)
+	}
+
+	/// Remove given items and all their children from the queue without marking them bad, after
+	/// their import failed for a transient reason. They may be queued again.
+	pub fn drop_items(&self, hashes: &[H256]) {
+		self.remove_with_children(hashes, true)
+	}
+
+	fn remove_with_children(&self, hashes: &[H256], transient: bool) {
 		if hashes.is_empty() {
 			return;
 		}
 		let mut verified_lock = self.verification.verified.lock();
 		let verified = &mut *verified_lock;
-		let mut bad = self.verification.bad.lock();
+		let mut bad_lock = self.verification.bad.lock();
+		let mut dropped_lock = self.verification.dropped.lock();
+		let removed = match transient {
+			true => &mut *dropped_lock,
+			false => &mut *bad_lock,
+		};
 		let mut processing = self.processing.write();
-		bad.reserve(hashes.len());
+		let mut timings = self.verification.timings.lock();
+		removed.reserve(hashes.len());
 		for hash in hashes {
-			bad.insert(hash.clone());
+			timings.remove(hash);
+			removed.insert(hash.clone());
 			if let Some(difficulty) = processing.remove(hash) {
 				let mut td = self.total_difficulty.write();
 				*td = *td - difficulty;
@@ -547,9 +668,10 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 		let mut new_verified = VecDeque::new();
 		let mut removed_size = 0;
 		for output in verified.drain(..) {
-			if bad.contains(&output.parent_hash()) {
+			if removed.contains(&output.parent_hash()) {
 				removed_size += output.malloc_size_of();
-				bad.insert(output.hash());
+				timings.remove(&output.hash());
+				removed.insert(output.hash());
 				if let Some(difficulty) = processing.remove(&output.hash()) {
 					let mut td = self.total_difficulty.write();
 					*td = *td - difficulty;
@@ -563,6 +685,25 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 		*verified = new_verified;
 	}
 
+	// forget the items dropped by the verifiers, so they can be queued again. Their hashes are kept
+	// while other items are queued, to drop the descendants as well.
+	fn purge_dropped(&self) {
+		let mut dropped = self.verification.dropped.lock();
+		if dropped.is_empty() {
+			return;
+		}
+		let mut processing = self.processing.write();
+		for hash in dropped.iter() {
+			if let Some(difficulty) = processing.remove(hash) {
+				let mut td = self.total_difficulty.write();
+				*td = *td - difficulty;
+			}
+		}
+		if processing.is_empty() {
+			dropped.clear();
+		}
+	}
+
 	/// Mark given item as processed.
 	/// Returns true if the queue becomes empty.
 	pub fn mark_as_good(&self, hashes: &[H256]) -> bool {
@@ -579,6 +720,12 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 		processing.is_empty()
 	}
 
+	/// Calls `f` on up to `max` verified items from the front of the queue, without removing them.
+	/// The queue is locked meanwhile, so `f` should be cheap.
+	pub fn peek_verified<F: FnMut(&K::Verified)>(&self, max: usize, f: F) {
+		self.verification.verified.lock().iter().take(max).for_each(f);
+	}
+
 	/// Removes up to `max` verified items from the queue
 	pub fn drain(&self, max: usize) -> Vec<K::Verified> {
 		let mut verified = self.verification.verified.lock();
@@ -678,6 +825,7 @@ impl<K: Kind, C> VerificationQueue<K, C> {
 			(u_len as isize, v_len as isize)
 		};
 
+		self.purge_dropped();
 		self.processing.write().shrink_to_fit();
 
 		if !self.scale_verifiers { return }
@@ -750,7 +898,7 @@ impl<K: Kind, C> Drop for VerificationQueue<K, C> {
 #[cfg(test)]
 mod tests {
 	use ethcore_io::*;
-	use super::{BlockQueue, Config, State};
+	use super::{BlockQueue, Config, State, Status};
 	use ethcore::test_helpers::{get_good_dummy_block_seq, get_good_dummy_block};
 	use ethcore::client::Client;
 	use parity_bytes::Bytes;
@@ -835,6 +983,22 @@ mod tests {
 		assert_eq!(queue.total_difficulty(), 0.into());
 	}
 
+	#[test]
+	fn records_timings_when_enabled() {
+		let queue = get_test_queue(false);
+		let block = get_good_dummy_block();
+		let hash = view!(BlockView, &block).header().hash().clone();
+		queue.set_record_timings(true);
+		queue.import(new_unverified(block)).expect("error importing block that is valid by definition");
+		queue.flush();
+
+		let timings = queue.take_timings(&hash).expect("timings are recorded");
+		let started = timings.verification_started.expect("verification has started");
+		let verified = timings.verified.expect("verification has finished");
+		assert!(timings.enqueued <= started && started <= verified);
+		assert!(queue.take_timings(&hash).is_none());
+	}
+
 	#[test]
 	fn returns_ok_for_drained_duplicates() {
 		let queue = get_test_queue(false);
@@ -852,6 +1016,50 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn reverifies_verified_blocks() {
+		let queue = get_test_queue(false);
+		let block = get_good_dummy_block();
+		let hash = view!(BlockView, &block).header().hash().clone();
+		queue.import(new_unverified(block)).expect("error importing block that is valid by definition");
+		queue.flush();
+
+		queue.reverify();
+		queue.flush();
+		match queue.status(&hash) {
+			Status::Queued => {},
+			_ => panic!("re-verified block should still be queued"),
+		}
+		assert_eq!(queue.total_difficulty(), 131072.into());
+		assert_eq!(queue.drain(10).len(), 1);
+	}
+
+	#[test]
+	fn dropped_blocks_can_be_queued_again() {
+		let queue = get_test_queue(false);
+		let blocks = get_good_dummy_block_seq(2);
+		let hashes: Vec<_> = blocks.iter().map(|b| view!(BlockView, b).header().hash()).collect();
+		for block in &blocks {
+			queue.import(new_unverified(block.clone())).expect("error importing block that is valid by definition");
+		}
+		queue.flush();
+
+		// the child goes along with its parent, neither is bad.
+		queue.drop_items(&hashes[..1]);
+		for hash in &hashes {
+			match queue.status(hash) {
+				Status::Unknown => {},
+				_ => panic!("dropped block should be unknown"),
+			}
+		}
+		assert!(queue.drain(10).is_empty());
+		assert_eq!(queue.total_difficulty(), 0.into());
+
+		queue.import(new_unverified(blocks[0].clone())).expect("dropped block can be queued again");
+		queue.flush();
+		assert_eq!(queue.drain(10).len(), 1);
+	}
+
 	#[test]
 	fn returns_empty_once_finished() {
 		let queue = get_test_queue(false);
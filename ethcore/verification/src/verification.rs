@@ -24,7 +24,9 @@
 use std::collections::HashSet;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use ethereum_types::U256;
 use keccak_hash::keccak;
+use light_verification::header::{self as light, GasLimitBounds, HeaderError, HeaderFields, HeaderRules};
 use rlp::Rlp;
 use triehash::ordered_trie_root;
 use unexpected::{Mismatch, OutOfBounds};
@@ -272,6 +274,43 @@ pub fn verify_block_final(expected: &Header, got: &Header) -> Result<(), Error>
 	Ok(())
 }
 
+/// Fields of a header checked by the verification shared with light clients.
+struct Fields<'a>(&'a Header);
+
+impl<'a> HeaderFields for Fields<'a> {
+	fn number(&self) -> BlockNumber { self.0.number() }
+	fn gas_limit(&self) -> U256 { *self.0.gas_limit() }
+	fn gas_used(&self) -> U256 { *self.0.gas_used() }
+	fn extra_data(&self) -> &[u8] { self.0.extra_data() }
+}
+
+/// Rules of the engine that apply to `header`.
+fn header_rules(header: &Header, engine: &dyn Engine) -> HeaderRules {
+	let gas_limit = match engine.gas_limit_override(header) {
+		Some(_) => None,
+		None => Some(GasLimitBounds {
+			min: engine.min_gas_limit(),
+			max: engine.maximum_gas_limit(),
+			bound_divisor: engine.params().gas_limit_bound_divisor,
+		}),
+	};
+	HeaderRules {
+		gas_limit,
+		maximum_extra_data_size: engine.maximum_extra_data_size(),
+		dao_hardfork_transition: engine.machine().ethash_extensions().map(|ext| ext.dao_hardfork_transition),
+	}
+}
+
+fn to_block_error(err: HeaderError) -> BlockError {
+	match err {
+		HeaderError::RidiculousNumber { min, max, found } => BlockError::RidiculousNumber(OutOfBounds { min, max, found }),
+		HeaderError::InvalidNumber { expected, found } => BlockError::InvalidNumber(Mismatch { expected, found }),
+		HeaderError::TooMuchGasUsed { max, found } => BlockError::TooMuchGasUsed(OutOfBounds { min: None, max: Some(max), found }),
+		HeaderError::InvalidGasLimit { min, max, found } => BlockError::InvalidGasLimit(OutOfBounds { min, max, found }),
+		HeaderError::ExtraDataOutOfBounds { max, found } => BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max, found }),
+	}
+}
+
 /// Check basic header parameters.
 pub(crate) fn verify_header_params(header: &Header, engine: &dyn Engine, check_seal: bool) -> Result<(), Error> {
 	if check_seal {
@@ -283,59 +322,7 @@ pub(crate) fn verify_header_params(header: &Header, engine: &dyn Engine, check_s
 		}
 	}
 
-	if header.number() >= From::from(BlockNumber::max_value()) {
-		return Err(From::from(BlockError::RidiculousNumber(OutOfBounds {
-			max: Some(From::from(BlockNumber::max_value())),
-			min: None,
-			found: header.number()
-		})))
-	}
-	if header.gas_used() > header.gas_limit() {
-		return Err(From::from(BlockError::TooMuchGasUsed(OutOfBounds {
-			max: Some(*header.gas_limit()),
-			min: None,
-			found: *header.gas_used()
-		})));
-	}
-	if engine.gas_limit_override(header).is_none() {
-		let min_gas_limit = engine.min_gas_limit();
-		if header.gas_limit() < &min_gas_limit {
-			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds {
-				min: Some(min_gas_limit),
-				max: None,
-				found: *header.gas_limit()
-			})));
-		}
-		if let Some(limit) = engine.maximum_gas_limit() {
-			if header.gas_limit() > &limit {
-				return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds {
-					min: None,
-					max: Some(limit),
-					found: *header.gas_limit()
-				})));
-			}
-		}
-	}
-	let maximum_extra_data_size = engine.maximum_extra_data_size();
-	if header.number() != 0 && header.extra_data().len() > maximum_extra_data_size {
-		return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds {
-			min: None,
-			max: Some(maximum_extra_data_size),
-			found: header.extra_data().len()
-		})));
-	}
-
-	if let Some(ref ext) = engine.machine().ethash_extensions() {
-		if header.number() >= ext.dao_hardfork_transition &&
-			header.number() <= ext.dao_hardfork_transition + 9 &&
-			header.extra_data()[..] != b"dao-hard-fork"[..] {
-			return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds {
-				min: None,
-				max: None,
-				found: 0
-			})));
-		}
-	}
+	light::verify_header_params(&Fields(header), &header_rules(header, engine)).map_err(to_block_error)?;
 
 	Ok(())
 }
@@ -381,33 +368,7 @@ fn verify_parent(header: &Header, parent: &Header, engine: &dyn Engine) -> Resul
 			.ok_or(BlockError::TimestampOverflow)?;
 		return Err(From::from(BlockError::InvalidTimestamp(OutOfBounds { max: None, min: Some(min), found }.into())))
 	}
-	if header.number() != parent.number() + 1 {
-		return Err(From::from(BlockError::InvalidNumber(Mismatch {
-			expected: parent.number() + 1,
-			found: header.number()
-		})));
-	}
-
-	if header.number() == 0 {
-		return Err(BlockError::RidiculousNumber(OutOfBounds {
-			min: Some(1),
-			max: None,
-			found: header.number()
-		}).into());
-	}
-	if engine.gas_limit_override(header).is_none() {
-		let gas_limit_divisor = engine.params().gas_limit_bound_divisor;
-		let parent_gas_limit = *parent.gas_limit();
-		let min_gas = parent_gas_limit - parent_gas_limit / gas_limit_divisor;
-		let max_gas = parent_gas_limit + parent_gas_limit / gas_limit_divisor;
-		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
-			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds {
-				min: Some(min_gas),
-				max: Some(max_gas),
-				found: *header.gas_limit()
-			})));
-		}
-	}
+	light::verify_parent(&Fields(header), &Fields(parent), &header_rules(header, engine)).map_err(to_block_error)?;
 
 	Ok(())
 }
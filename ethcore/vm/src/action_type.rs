@@ -32,7 +32,9 @@ pub enum ActionType {
 	/// STATICCALL.
 	StaticCall,
 	/// CREATE2.
-	Create2
+	Create2,
+	/// AUTHCALL.
+	AuthCall,
 }
 
 impl Encodable for ActionType {
@@ -44,6 +46,7 @@ impl Encodable for ActionType {
 			ActionType::DelegateCall => 3,
 			ActionType::StaticCall => 4,
 			ActionType::Create2 => 5,
+			ActionType::AuthCall => 6,
 		};
 		Encodable::rlp_append(&v, s);
 	}
@@ -58,6 +61,7 @@ impl Decodable for ActionType {
 			3 => ActionType::DelegateCall,
 			4 => ActionType::StaticCall,
 			5 => ActionType::Create2,
+			6 => ActionType::AuthCall,
 			_ => return Err(DecoderError::Custom("Invalid value of ActionType item")),
 		}))
 	}
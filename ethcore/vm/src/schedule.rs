@@ -16,7 +16,7 @@
 
 //! Cost schedule and other parameterisations for the EVM.
 use std::collections::HashMap;
-use ethereum_types::U256;
+use ethereum_types::{Address, U256};
 
 /// Definition of schedules that can be applied to a version.
 #[derive(Debug)]
@@ -75,6 +75,8 @@ pub struct Schedule {
 	pub call_gas: usize,
 	/// Gas price for staticcall to precompiles
 	pub staticcall_precompile_gas: usize,
+	/// Gas price for `AUTH` opcode
+	pub auth_gas: usize,
 	/// Stipend for transfer for `CALL|CALLCODE` opcode when `value>0`
 	pub call_stipend: usize,
 	/// Additional gas required for value transfer (`CALL|CALLCODE`)
@@ -134,6 +136,8 @@ pub struct Schedule {
 	pub have_selfbalance: bool,
 	/// BEGINSUB, JUMPSUB and RETURNSUB opcodes enabled.
 	pub have_subs: bool,
+	/// Experimental AUTH and AUTHCALL opcodes enabled (EIP-3074).
+	pub have_auth: bool,
 	/// Kill basic accounts below this balance if touched.
 	pub kill_dust: CleanDustMode,
 	/// Enable EIP-1283 rules
@@ -146,6 +150,10 @@ pub struct Schedule {
 	pub versions: HashMap<U256, VersionedSchedule>,
 	/// Wasm extra schedule settings, if wasm activated
 	pub wasm: Option<WasmCosts>,
+	/// Where the transaction fees go.
+	pub fee_recipient: FeeRecipient,
+	/// Where the base fee part of the transaction fees goes once blocks have a base fee.
+	pub base_fee_recipient: FeeRecipient,
 }
 
 /// Wasm cost table
@@ -202,6 +210,23 @@ impl Default for WasmCosts {
 	}
 }
 
+/// Recipient of the transaction fees.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FeeRecipient {
+	/// The block author.
+	Author,
+	/// A fixed account, e.g. a fee-distribution contract.
+	Address(Address),
+	/// Nobody, the fees are burnt.
+	Burn,
+}
+
+impl Default for FeeRecipient {
+	fn default() -> Self {
+		FeeRecipient::Author
+	}
+}
+
 /// Dust accounts cleanup mode.
 #[derive(Debug, PartialEq, Eq)]
 pub enum CleanDustMode {
@@ -237,6 +262,7 @@ impl Schedule {
 			have_selfbalance: false,
 			have_extcodehash: false,
 			have_subs: false,
+			have_auth: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -256,6 +282,7 @@ impl Schedule {
 			create_gas: 32000,
 			call_gas: 700,
 			staticcall_precompile_gas: 700,
+			auth_gas: 3100,
 			call_stipend: 2300,
 			call_value_transfer_gas: 9000,
 			call_new_account_gas: 25000,
@@ -286,6 +313,8 @@ impl Schedule {
 			latest_version: U256::zero(),
 			versions: HashMap::new(),
 			wasm: None,
+			fee_recipient: FeeRecipient::Author,
+			base_fee_recipient: FeeRecipient::Burn,
 		}
 	}
 
@@ -338,6 +367,7 @@ impl Schedule {
 			have_selfbalance: false,
 			have_extcodehash: false,
 			have_subs: false,
+			have_auth: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -357,6 +387,7 @@ impl Schedule {
 			create_gas: 32000,
 			call_gas: 40,
 			staticcall_precompile_gas: 40,
+			auth_gas: 3100,
 			call_stipend: 2300,
 			call_value_transfer_gas: 9000,
 			call_new_account_gas: 25000,
@@ -387,6 +418,8 @@ impl Schedule {
 			latest_version: U256::zero(),
 			versions: HashMap::new(),
 			wasm: None,
+			fee_recipient: FeeRecipient::Author,
+			base_fee_recipient: FeeRecipient::Burn,
 		}
 	}
 
@@ -0,0 +1,127 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Report the outcome of every test as a line of JSON, without execution traces.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use trace;
+
+use crate::{
+	display,
+	info as vm,
+};
+
+/// Outcome of a single test.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TestOutcome<'a> {
+	name: &'a str,
+	pass: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error: Option<&'a str>,
+	#[serde(skip_serializing_if = "is_false")]
+	skipped: bool,
+	time: u64,
+}
+
+fn is_false(b: &bool) -> bool { !*b }
+
+impl<'a> TestOutcome<'a> {
+	/// Outcome of the named test, failed with the given error if any.
+	pub fn new(name: &'a str, error: Option<&'a str>, time: &Duration) -> Self {
+		TestOutcome {
+			name,
+			pass: error.is_none(),
+			error,
+			skipped: false,
+			time: display::as_micros(time),
+		}
+	}
+
+	/// Outcome of the named test, which wasn't run for the given reason.
+	pub fn skipped(name: &'a str, reason: &'a str) -> Self {
+		TestOutcome {
+			name,
+			pass: false,
+			error: Some(reason),
+			skipped: true,
+			time: 0,
+		}
+	}
+
+	/// Print the outcome to the standard output.
+	pub fn print(&self) {
+		println!("{}", serde_json::to_string(self).expect("Serialization cannot fail; qed"));
+	}
+}
+
+/// Informant reporting the outcome of every transaction of a state test.
+#[derive(Default)]
+pub struct Informant {
+	test: String,
+}
+
+impl vm::Informant for Informant {
+	/// Name of the running test.
+	type Sink = String;
+
+	fn before_test(&mut self, name: &str, action: &str) {
+		self.test = name.to_owned();
+		// the test is not run if it can't be started.
+		if action != "starting" {
+			TestOutcome::skipped(name, action).print();
+		}
+	}
+
+	fn clone_sink(&self) -> Self::Sink { self.test.clone() }
+
+	fn finish(result: vm::RunResult<Self::Output>, sink: &mut Self::Sink) {
+		match result {
+			Ok(success) => TestOutcome::new(sink, None, &success.time).print(),
+			Err(failure) => TestOutcome::new(sink, Some(failure.error.to_string().as_str()), &failure.time).print(),
+		}
+	}
+}
+
+impl trace::VMTracer for Informant {
+	type Output = ();
+
+	fn drain(self) -> Option<()> { None }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_serialize_outcomes() {
+		let time = Duration::from_micros(1500);
+		assert_eq!(
+			serde_json::to_string(&TestOutcome::new("add11:istanbul:0", None, &time)).unwrap(),
+			r#"{"name":"add11:istanbul:0","pass":true,"time":1500}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&TestOutcome::new("add11:istanbul:0", Some("State root mismatch"), &time)).unwrap(),
+			r#"{"name":"add11:istanbul:0","pass":false,"error":"State root mismatch","time":1500}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&TestOutcome::skipped("add11:frontier:0", "missing fork specification")).unwrap(),
+			r#"{"name":"add11:frontier:0","pass":false,"error":"missing fork specification","skipped":true,"time":0}"#
+		);
+	}
+}
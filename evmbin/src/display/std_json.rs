@@ -16,7 +16,7 @@
 
 //! Log EVM instruction output data traces from a standardized JSON formatting informant.
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::io;
 
 use ethereum_types::{H256, U256, BigEndianHash};
@@ -61,7 +61,7 @@ pub struct Informant<Trace, Out> {
 	instruction: u8,
 	depth: usize,
 	stack: Vec<U256>,
-	storage: HashMap<H256, H256>,
+	storage: BTreeMap<H256, H256>,
 	subinfos: Vec<Informant<Trace, Out>>,
 	subdepth: usize,
 	trace_sink: Trace,
@@ -76,7 +76,7 @@ pub struct TraceData<'a> {
 	op_name: &'a str,
 	gas: &'a str,
 	stack: &'a [U256],
-	storage: &'a HashMap<H256, H256>,
+	storage: &'a BTreeMap<H256, H256>,
 	depth: usize,
 }
 
@@ -0,0 +1,165 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Input of the differential fuzzing mode.
+
+use std::cmp;
+use std::sync::Arc;
+
+use ethereum_types::{Address, U256};
+use ethjson::{
+	bytes::Bytes,
+	hash,
+	spec::{ForkSpec, State},
+	uint::Uint,
+	vm::Env,
+};
+use pod::PodState;
+use serde::Deserialize;
+use vm::{ActionParams, ActionType, ActionValue, EnvInfo};
+
+/// Code, input and pre-state of a single execution, read from the standard input.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzInput {
+	/// Fork the code is executed with (i.e. Istanbul).
+	pub fork: ForkSpec,
+	/// Block environment of the execution.
+	pub env: Env,
+	/// Accounts before the execution.
+	pub pre: State,
+	/// Executed code. Defaults to the code of the `to` account of the pre-state.
+	pub code: Option<Bytes>,
+	/// Address the code is executed at.
+	#[serde(default)]
+	pub to: hash::Address,
+	/// Sender of the call.
+	#[serde(default)]
+	pub from: hash::Address,
+	/// Call data.
+	#[serde(default)]
+	pub input: Bytes,
+	/// Supplied gas.
+	pub gas: Uint,
+	/// Value transferred from the sender to the `to` account. Capped at the balance of the sender
+	/// in the pre-state.
+	#[serde(default)]
+	pub value: Uint,
+}
+
+impl FuzzInput {
+	/// Split the input into the fork, the environment, the pre-state and the parameters of the call.
+	pub fn into_parts(self) -> (ForkSpec, EnvInfo, PodState, ActionParams) {
+		let pre_state: PodState = self.pre.into();
+		let to: Address = self.to.into();
+		let from: Address = self.from.into();
+		let code = match self.code {
+			Some(code) => Some(code.into()),
+			None => pre_state.get().get(&to).and_then(|account| account.code.clone()),
+		};
+
+		let mut params = ActionParams::default();
+		params.action_type = ActionType::Call;
+		params.code = code.map(Arc::new);
+		params.code_hash = None;
+		params.code_address = to;
+		params.address = to;
+		params.sender = from;
+		params.origin = from;
+		params.data = Some(self.input.into());
+		params.gas = self.gas.into();
+		// the transfer of a call can't exceed the balance of the sender, transactions are rejected before.
+		let balance = pre_state.get().get(&from).map_or_else(U256::zero, |account| account.balance);
+		params.value = ActionValue::Transfer(cmp::min(self.value.into(), balance));
+
+		(self.fork, self.env.into(), pre_state, params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_take_code_from_pre_state() {
+		let input: FuzzInput = serde_json::from_str(r#"{
+			"fork": "Istanbul",
+			"env": {
+				"currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+				"currentDifficulty": "0x020000",
+				"currentGasLimit": "0x0f4240",
+				"currentNumber": "0x01",
+				"currentTimestamp": "0x03e8"
+			},
+			"pre": {
+				"0x0000000000000000000000000000000000000005": {
+					"balance": "0x00",
+					"code": "0x600160005500",
+					"nonce": "0x00",
+					"storage": {}
+				}
+			},
+			"to": "0x0000000000000000000000000000000000000005",
+			"input": "0x01",
+			"gas": "0xffff"
+		}"#).unwrap();
+
+		let (fork, env_info, pre_state, params) = input.into_parts();
+		assert_eq!(fork, ForkSpec::Istanbul);
+		assert_eq!(env_info.number, 1);
+		assert_eq!(pre_state.get().len(), 1);
+		assert_eq!(params.address, Address::from_low_u64_be(5));
+		assert_eq!(params.code.as_ref().map(|c| c.to_vec()), Some(vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x00]));
+		assert_eq!(params.data, Some(vec![0x01]));
+		assert_eq!(params.gas, U256::from(0xffff));
+	}
+
+	#[test]
+	fn should_cap_value_at_sender_balance() {
+		let input: FuzzInput = serde_json::from_str(r#"{
+			"fork": "Istanbul",
+			"env": {
+				"currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+				"currentDifficulty": "0x020000",
+				"currentGasLimit": "0x0f4240",
+				"currentNumber": "0x01",
+				"currentTimestamp": "0x03e8"
+			},
+			"pre": {
+				"0x0000000000000000000000000000000000000001": {
+					"balance": "0x64",
+					"code": "0x",
+					"nonce": "0x00",
+					"storage": {}
+				}
+			},
+			"from": "0x0000000000000000000000000000000000000001",
+			"to": "0x0000000000000000000000000000000000000005",
+			"code": "0x00",
+			"gas": "0xffff",
+			"value": "0xffffffff"
+		}"#).unwrap();
+
+		let (_, _, _, params) = input.into_parts();
+		assert_eq!(params.value.value(), U256::from(100));
+	}
+
+	#[test]
+	fn should_reject_unknown_fields() {
+		assert!(serde_json::from_str::<FuzzInput>(r#"{"fork":"Istanbul","gasPrice":"0x1"}"#).is_err());
+	}
+}
@@ -103,6 +103,31 @@ pub fn run_action<T: Informant>(
 	})
 }
 
+/// Execute given `ActionParams` on top of the given state and environment and return the result,
+/// including the resulting state root and, with a fat trie, the resulting state.
+pub fn run_action_on_state<T: Informant>(
+	spec: &spec::Spec,
+	pre_state: &PodState,
+	env_info: vm::EnvInfo,
+	params: ActionParams,
+	mut informant: T,
+	trie_spec: TrieSpec,
+) -> RunResult<T::Output> {
+	informant.set_gas(params.gas);
+
+	run(spec, trie_spec, params.gas, pre_state, |mut client| {
+		let result = client.call_envinfo(params, &mut trace::NoopTracer, &mut informant, env_info);
+		let (state_root, end_state) = match client.commit() {
+			Ok(committed) => committed,
+			Err(err) => return (Err(err), H256::zero(), None, None, informant.drain()),
+		};
+		match result {
+			Ok(r) => (Ok(r.return_data.to_vec()), state_root, end_state, Some(r.gas_left), informant.drain()),
+			Err(err) => (Err(err), state_root, end_state, None, informant.drain()),
+		}
+	})
+}
+
 /// Input data to run transaction.
 #[derive(Debug)]
 pub struct TxInput<'a, T> {
@@ -132,11 +157,21 @@ pub struct TxInput<'a, T> {
 	pub trie_spec: TrieSpec,
 }
 
+/// Outcome of a state test transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+	/// The transaction executed and the state root matched.
+	Passed,
+	/// The transaction failed or the state root didn't match.
+	Failed,
+	/// The fork of the test has no chain spec, the transaction wasn't run.
+	Skipped,
+}
+
 /// Execute given transaction and verify resulting state root.
-/// Returns true if the transaction executes successfully.
 pub fn run_transaction<T: Informant>(
 	tx_input: TxInput<T>
-) -> bool {
+) -> Outcome {
 	let TxInput {
 		state_test_name, tx_index, fork_spec_name, pre_state, post_root, env_info, transaction, mut informant, trie_spec, ..
 	} = tx_input;
@@ -150,7 +185,7 @@ pub fn run_transaction<T: Informant>(
 		None => {
 			informant.before_test(&format!("{}:{}:{}",
 				&state_test_name, fork_spec_name_formatted, &tx_index), "skipping because of missing fork specification");
-			return false;
+			return Outcome::Skipped;
 		},
 	};
 
@@ -179,9 +214,9 @@ pub fn run_transaction<T: Informant>(
 		}
 	});
 
-	let ok = result.is_ok();
+	let outcome = if result.is_ok() { Outcome::Passed } else { Outcome::Failed };
 	T::finish(result, &mut sink);
-	ok
+	outcome
 }
 
 /// Execute EVM with given `ActionParams`.
@@ -34,21 +34,24 @@
 
 #![warn(missing_docs)]
 
-use std::sync::Arc;
-use std::{fmt, fs};
-use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::{cmp, fmt, fs, io, thread};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use parity_bytes::Bytes;
 use docopt::Docopt;
 use rustc_hex::FromHex;
 use ethereum_types::{U256, Address};
-use ethcore::{json_tests, test_helpers::TrieSpec};
+use ethcore::{json_tests, test_helpers::{EvmTestClient, TrieSpec}};
 use spec;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use vm::{ActionParams, ActionType};
 
 mod info;
 mod display;
+mod fuzz;
 
 use crate::info::{Informant, TxInput};
 
@@ -57,14 +60,23 @@ EVM implementation for OpenEthereum.
   Copyright 2015-2020 Parity Technologies (UK) Ltd.
 
 Usage:
-    openethereum-evm state-test <file> [--chain CHAIN --only NAME --json --std-json --std-dump-json --std-out-only --std-err-only]
+    openethereum-evm state-test <file> [--chain CHAIN --only NAME --jobs N --report --json --std-json --std-dump-json --std-out-only --std-err-only]
+    openethereum-evm blockchain-test <file> [--chain CHAIN --only NAME --jobs N --report]
+    openethereum-evm fuzz [--std-out-only --std-err-only]
     openethereum-evm stats [options]
     openethereum-evm stats-jsontests-vm <file>
     openethereum-evm [options]
     openethereum-evm [-h | --help]
 
 Commands:
-    state-test         Run a state test on a provided state test JSON file.
+    state-test         Run the state tests of a JSON file, or of every JSON file
+                       in a directory (i.e. GeneralStateTests).
+    blockchain-test    Run the blockchain tests of a JSON file, or of every JSON file
+                       in a directory (i.e. BlockchainTests).
+    fuzz               Execute the code, input and pre-state of every JSON object
+                       read from the standard input and print the standardized JSON
+                       traces, output, gas used and resulting state, for differential
+                       fuzzing against other EVM implementations.
     stats              Execute EVM runtime code and return the statistics.
     stats-jsontests-vm Execute standard json-tests on a provided state test JSON
                        file path, format VMTests, and return timing statistics
@@ -85,6 +97,11 @@ State test options:
                        HomesteadToDaoAt5, HomesteadToEIP150At5, ByzantiumToConstantinopleFixAt5 ,
                        ConstantinopleFixToIstanbulAt5).
     --only NAME        Runs only a single test matching the name.
+    --jobs N           Number of tests run in parallel. [default: 1]
+    --report           Print the outcome of every test as a line of JSON
+                       instead of the execution details. Exits with a non-zero
+                       code if any test fails. Tests of forks without a chain
+                       spec are reported as skipped, not failed.
 
 General options:
     --chain PATH       Path to chain spec file.
@@ -105,6 +122,10 @@ fn main() {
 
 	if args.cmd_state_test {
 		run_state_test(args)
+	} else if args.cmd_blockchain_test {
+		run_blockchain_test(args)
+	} else if args.cmd_fuzz {
+		run_fuzz(args)
 	} else if args.cmd_stats_jsontests_vm {
 		run_stats_jsontests_vm(args)
 	} else if args.flag_json {
@@ -123,149 +144,331 @@ fn main() {
 }
 
 fn run_state_test(args: Args) {
-	use ethjson::test_helpers::state::Test;
+	use ethjson::test_helpers::state::State;
 
-	// Parse the specified state test JSON file provided to the command `state-test <file>`.
-	let file = args.arg_file.expect("PATH to a state test JSON file is required");
-	let mut file = match fs::File::open(&file) {
-		Err(err) => die(format!("Unable to open path: {:?}: {}", file, err)),
-		Ok(file) => file,
-	};
-	let state_test = match Test::load(&mut file) {
-		Err(err) => die(format!("Unable to load the test file: {}", err)),
-		Ok(test) => test,
-	};
-	// Parse the name CLI option `--only NAME`.
-	let only_test = args.flag_only.map(|s| s.to_lowercase());
+	// Parse the state tests of the file, or of every JSON file of the directory provided to the command `state-test <file>`.
+	let file = args.arg_file.clone().expect("PATH to a state test JSON file is required");
+	let state_tests = load_tests::<State>(&file, args.flag_only.as_ref());
+	let jobs = args.flag_jobs;
+	let report = args.flag_report;
+	let args = Arc::new(args);
+
+	let tally = run_tests(jobs, state_tests, move |state_test_name, test| run_state_test_case(&args, state_test_name, test));
+	tally.finish(report);
+}
+
+fn run_state_test_case(args: &Args, state_test_name: &str, test: ethjson::test_helpers::state::State) -> Tally {
 	// Parse the chain `--chain CHAIN`
-	let only_chain = args.flag_chain.map(|s| s.to_lowercase());
-
-	// Iterate over 1st level (outer) key-value pair of the state test JSON file.
-	// Skip to next iteration if CLI option `--only NAME` was parsed into `only_test` and does not match
-	// the current key `state_test_name` (i.e. add11, create2callPrecompiles).
-	for (state_test_name, test) in state_test {
-		if let Some(false) = only_test.as_ref().map(|only_test| {
-			&state_test_name.to_lowercase() == only_test
+	let only_chain = args.flag_chain.as_ref().map(|s| s.to_lowercase());
+	let mut tally = Tally::default();
+
+	// Assign from 2nd level key-value pairs of the state test JSON file (i.e. env, post, pre, transaction).
+	let multitransaction = test.transaction;
+	let env_info = test.env.into();
+	let pre = test.pre_state.into();
+
+	// Iterate over remaining "post" key of the 2nd level key-value pairs in the state test JSON file.
+	// Skip to next iteration if CLI option `--chain CHAIN` was parsed into `only_chain` and does not match
+	// the current key `fork_spec_name` (i.e. Constantinople, EIP150, EIP158).
+	for (fork_spec_name, states) in test.post_states {
+		if let Some(false) = only_chain.as_ref().map(|only_chain| {
+			&format!("{:?}", fork_spec_name).to_lowercase() == only_chain
 		}) {
 			continue;
 		}
 
-		// Assign from 2nd level key-value pairs of the state test JSON file (i.e. env, post, pre, transaction).
-		let multitransaction = test.transaction;
-		let env_info = test.env.into();
-		let pre = test.pre_state.into();
-
-		// Iterate over remaining "post" key of the 2nd level key-value pairs in the state test JSON file.
-		// Skip to next iteration if CLI option `--chain CHAIN` was parsed into `only_chain` and does not match
-		// the current key `fork_spec_name` (i.e. Constantinople, EIP150, EIP158).
-		for (fork_spec_name, states) in test.post_states {
-			if let Some(false) = only_chain.as_ref().map(|only_chain| {
-				&format!("{:?}", fork_spec_name).to_lowercase() == only_chain
-			}) {
-				continue;
-			}
-
-			// Iterate over the 3rd level key-value pairs of the state test JSON file
-			// (i.e. list of transactions and associated state roots hashes corresponding each chain).
-			for (tx_index, state) in states.into_iter().enumerate() {
-				let post_root = state.hash.into();
-				let transaction = multitransaction.select(&state.indexes).into();
-
-				// Determine the type of trie with state root to create in the database.
-				// The database is a key-value datastore implemented as a database-backend
-				// modified Merkle tree.
-				// Use a secure trie database specification when CLI option `--std-dump-json`
-				// is specified, otherwise use secure trie with fat trie database.
-				let trie_spec = if args.flag_std_dump_json {
-					TrieSpec::Fat
-				} else {
-					TrieSpec::Secure
+		// Iterate over the 3rd level key-value pairs of the state test JSON file
+		// (i.e. list of transactions and associated state roots hashes corresponding each chain).
+		for (tx_index, state) in states.into_iter().enumerate() {
+			let post_root = state.hash.into();
+			let transaction = multitransaction.select(&state.indexes).into();
+
+			// Determine the type of trie with state root to create in the database.
+			// The database is a key-value datastore implemented as a database-backend
+			// modified Merkle tree.
+			// Use a secure trie database specification when CLI option `--std-dump-json`
+			// is specified, otherwise use secure trie with fat trie database.
+			let trie_spec = if args.flag_std_dump_json {
+				TrieSpec::Fat
+			} else {
+				TrieSpec::Secure
+			};
+
+			// Execute the given transaction and verify resulting state root
+			// for CLI option `--report`.
+			tally.add(if args.flag_report {
+				let tx_input = TxInput {
+					state_test_name,
+					tx_index,
+					fork_spec_name: &fork_spec_name,
+					pre_state: &pre,
+					post_root,
+					env_info: &env_info,
+					transaction,
+					informant: display::report::Informant::default(),
+					trie_spec,
 				};
-
+				// Use Report informant
+				info::run_transaction(tx_input)
+			// Execute the given transaction and verify resulting state root
+			// for CLI option `--std-dump-json` or `--std-json`.
+			} else if args.flag_std_dump_json || args.flag_std_json {
+				if args.flag_std_err_only {
+					let tx_input = TxInput {
+						state_test_name,
+						tx_index,
+						fork_spec_name: &fork_spec_name,
+						pre_state: &pre,
+						post_root,
+						env_info: &env_info,
+						transaction,
+						informant: display::std_json::Informant::err_only(),
+						trie_spec,
+					};
+					// Use Standard JSON informant with err only
+					info::run_transaction(tx_input)
+				} else if args.flag_std_out_only {
+					let tx_input = TxInput {
+						state_test_name,
+						tx_index,
+						fork_spec_name: &fork_spec_name,
+						pre_state: &pre,
+						post_root,
+						env_info: &env_info,
+						transaction,
+						informant: display::std_json::Informant::out_only(),
+						trie_spec,
+					};
+					// Use Standard JSON informant with out only
+					info::run_transaction(tx_input)
+				} else {
+					let tx_input = TxInput {
+						state_test_name,
+						tx_index,
+						fork_spec_name: &fork_spec_name,
+						pre_state: &pre,
+						post_root,
+						env_info: &env_info,
+						transaction,
+						informant: display::std_json::Informant::default(),
+						trie_spec,
+					};
+					// Use Standard JSON informant default
+					info::run_transaction(tx_input)
+				}
+			} else {
 				// Execute the given transaction and verify resulting state root
-				// for CLI option `--std-dump-json` or `--std-json`.
-				if args.flag_std_dump_json || args.flag_std_json {
-					if args.flag_std_err_only {
-						let tx_input = TxInput {
-							state_test_name: &state_test_name,
-							tx_index,
-							fork_spec_name: &fork_spec_name,
-							pre_state: &pre,
-							post_root,
-							env_info: &env_info,
-							transaction,
-							informant: display::std_json::Informant::err_only(),
-							trie_spec,
-						};
-						// Use Standard JSON informant with err only
-						info::run_transaction(tx_input);
-					} else if args.flag_std_out_only {
-						let tx_input = TxInput {
-							state_test_name: &state_test_name,
-							tx_index,
-							fork_spec_name: &fork_spec_name,
-							pre_state: &pre,
-							post_root,
-							env_info: &env_info,
-							transaction,
-							informant: display::std_json::Informant::out_only(),
-							trie_spec,
-						};
-						// Use Standard JSON informant with out only
-						info::run_transaction(tx_input);
-					} else {
-						let tx_input = TxInput {
-							state_test_name: &state_test_name,
-							tx_index,
-							fork_spec_name: &fork_spec_name,
-							pre_state: &pre,
-							post_root,
-							env_info: &env_info,
-							transaction,
-							informant: display::std_json::Informant::default(),
-							trie_spec,
-						};
-						// Use Standard JSON informant default
-						info::run_transaction(tx_input);
-					}
+				// for CLI option `--json`.
+				if args.flag_json {
+					let tx_input = TxInput {
+						state_test_name,
+						tx_index,
+						fork_spec_name: &fork_spec_name,
+						pre_state: &pre,
+						post_root,
+						env_info: &env_info,
+						transaction,
+						informant: display::json::Informant::default(),
+						trie_spec,
+					};
+					// Use JSON informant
+					info::run_transaction(tx_input)
 				} else {
-					// Execute the given transaction and verify resulting state root
-					// for CLI option `--json`.
-					if args.flag_json {
-						let tx_input = TxInput {
-							state_test_name: &state_test_name,
-							tx_index,
-							fork_spec_name: &fork_spec_name,
-							pre_state: &pre,
-							post_root,
-							env_info: &env_info,
-							transaction,
-							informant: display::json::Informant::default(),
-							trie_spec,
-						};
-						// Use JSON informant
-						info::run_transaction(tx_input);
-					} else {
-						let tx_input = TxInput {
-							state_test_name: &state_test_name,
-							tx_index,
-							fork_spec_name: &fork_spec_name,
-							pre_state: &pre,
-							post_root,
-							env_info: &env_info,
-							transaction,
-							informant: display::simple::Informant::default(),
-							trie_spec,
-						};
-						// Use Simple informant
-						info::run_transaction(tx_input);
-					}
+					let tx_input = TxInput {
+						state_test_name,
+						tx_index,
+						fork_spec_name: &fork_spec_name,
+						pre_state: &pre,
+						post_root,
+						env_info: &env_info,
+						transaction,
+						informant: display::simple::Informant::default(),
+						trie_spec,
+					};
+					// Use Simple informant
+					info::run_transaction(tx_input)
+				}
+			});
+		}
+	}
+
+	tally
+}
+
+fn run_blockchain_test(args: Args) {
+	use ethjson::test_helpers::blockchain::BlockChain;
+
+	// Parse the blockchain tests of the file, or of every JSON file of the directory provided to the command `blockchain-test <file>`.
+	let file = args.arg_file.clone().expect("PATH to a blockchain test JSON file is required");
+	let mut blockchain_tests = load_tests::<BlockChain>(&file, args.flag_only.as_ref());
+	// Parse the chain `--chain CHAIN`
+	if let Some(only_chain) = args.flag_chain.as_ref().map(|s| s.to_lowercase()) {
+		blockchain_tests.retain(|(_, test)| format!("{:?}", test.network).to_lowercase() == only_chain);
+	}
+	let report = args.flag_report;
+
+	let tally = run_tests(args.flag_jobs, blockchain_tests, move |name, test| {
+		use crate::json_tests::BlockchainTestError::{Failed, Unsupported};
+		use crate::display::report::TestOutcome;
+
+		let start = Instant::now();
+		let result = json_tests::run_blockchain_test(name, test);
+		let time = start.elapsed();
+		match result {
+			Ok(()) if report => TestOutcome::new(name, None, &time).print(),
+			Err(Unsupported(ref e)) if report => TestOutcome::skipped(name, e).print(),
+			Err(Failed(ref e)) if report => TestOutcome::new(name, Some(e.as_str()), &time).print(),
+			Ok(()) => println!("Test: {} OK ({})", name, display::format_time(&time)),
+			Err(Unsupported(ref e)) => println!("Test: {} SKIPPED: {}", name, e),
+			Err(Failed(ref e)) => println!("Test: {} FAILED: {} ({})", name, e, display::format_time(&time)),
+		}
+		let mut tally = Tally::default();
+		tally.add(match result {
+			Ok(()) => info::Outcome::Passed,
+			Err(Unsupported(_)) => info::Outcome::Skipped,
+			Err(Failed(_)) => info::Outcome::Failed,
+		});
+		tally
+	});
+	tally.finish(report);
+}
+
+// Number of tests, or state test transactions, by outcome.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+struct Tally {
+	passed: usize,
+	failed: usize,
+	skipped: usize,
+}
+
+impl Tally {
+	fn add(&mut self, outcome: info::Outcome) {
+		match outcome {
+			info::Outcome::Passed => self.passed += 1,
+			info::Outcome::Failed => self.failed += 1,
+			info::Outcome::Skipped => self.skipped += 1,
+		}
+	}
+
+	fn merge(&mut self, other: Tally) {
+		self.passed += other.passed;
+		self.failed += other.failed;
+		self.skipped += other.skipped;
+	}
+
+	// Print the totals, on the standard error so they don't mix with the reported outcomes, and exit
+	// with a non-zero code if `--report` is set and a test failed. Skipped tests are not failures.
+	fn finish(&self, report: bool) {
+		eprintln!("{} passed, {} failed, {} skipped", self.passed, self.failed, self.skipped);
+		if report && self.failed > 0 {
+			::std::process::exit(1);
+		}
+	}
+}
+
+// Load the tests of the given JSON file, or of every JSON file of the given directory and its subdirectories.
+// Only the test matching the name of the CLI option `--only NAME` is kept, if it is set.
+fn load_tests<T: DeserializeOwned>(path: &Path, only_test: Option<&String>) -> Vec<(String, T)> {
+	use ethjson::test_helpers::tester::GenericTester;
+
+	let only_test = only_test.map(|s| s.to_lowercase());
+	let mut tests = Vec::new();
+	for file in json_files(path) {
+		let reader = match fs::File::open(&file) {
+			Err(err) => die(format!("Unable to open path: {:?}: {}", file, err)),
+			Ok(file) => file,
+		};
+		let file_tests = match GenericTester::<String, T>::load(reader) {
+			Err(err) => die(format!("Unable to load the test file {:?}: {}", file, err)),
+			Ok(tests) => tests,
+		};
+		tests.extend(file_tests.into_iter().filter(|(name, _)| {
+			only_test.as_ref().map_or(true, |only_test| &name.to_lowercase() == only_test)
+		}));
+	}
+	tests
+}
+
+fn json_files(path: &Path) -> Vec<PathBuf> {
+	if !path.is_dir() {
+		return vec![path.to_path_buf()];
+	}
+
+	let mut entries = match fs::read_dir(path) {
+		Err(err) => die(format!("Unable to read directory: {:?}: {}", path, err)),
+		Ok(entries) => entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect::<Vec<_>>(),
+	};
+	entries.sort();
+
+	entries.into_iter().flat_map(|entry| {
+		if entry.is_dir() {
+			json_files(&entry)
+		} else if entry.extension().map_or(false, |ext| ext == "json") {
+			vec![entry]
+		} else {
+			Vec::new()
+		}
+	}).collect()
+}
+
+// Run the tests on the given number of threads and return the tally of their outcomes.
+fn run_tests<T, F>(jobs: usize, tests: Vec<(String, T)>, run: F) -> Tally where
+	T: Send + 'static,
+	F: Fn(&str, T) -> Tally + Send + Sync + 'static,
+{
+	let tests = Arc::new(Mutex::new(tests.into_iter()));
+	let run = Arc::new(run);
+
+	let workers = (0..cmp::max(jobs, 1)).map(|_| {
+		let tests = tests.clone();
+		let run = run.clone();
+		thread::spawn(move || {
+			let mut tally = Tally::default();
+			loop {
+				let next = tests.lock().expect("The lock is only held to take the next test; qed").next();
+				match next {
+					Some((name, test)) => tally.merge(run(&name, test)),
+					None => return tally,
 				}
 			}
+		})
+	}).collect::<Vec<_>>();
+
+	workers.into_iter().fold(Tally::default(), |mut tally, worker| {
+		tally.merge(worker.join().expect("Tests abort on panic; qed"));
+		tally
+	})
+}
+
+fn run_fuzz(args: Args) {
+	use crate::fuzz::FuzzInput;
+
+	// Read the inputs from the standard input, one JSON object after another.
+	let stdin = io::stdin();
+	let inputs = serde_json::Deserializer::from_reader(stdin.lock()).into_iter::<FuzzInput>();
+	for input in inputs {
+		let input = input.unwrap_or_else(|e| die(format!("Invalid input: {}", e)));
+		let (fork, env_info, pre_state, params) = input.into_parts();
+		let spec = EvmTestClient::fork_spec_from_json(&fork)
+			.unwrap_or_else(|| die(format!("Unsupported fork: {:?}", fork)));
+
+		if args.flag_std_err_only {
+			run_fuzz_input(&spec, &pre_state, env_info, params, display::std_json::Informant::err_only())
+		} else if args.flag_std_out_only {
+			run_fuzz_input(&spec, &pre_state, env_info, params, display::std_json::Informant::out_only())
+		} else {
+			run_fuzz_input(&spec, &pre_state, env_info, params, display::std_json::Informant::default())
 		}
 	}
 }
 
+// Execute a single input, always dumping the resulting state.
+fn run_fuzz_input<T: Informant>(spec: &spec::Spec, pre_state: &pod::PodState, env_info: vm::EnvInfo, params: ActionParams, informant: T) {
+	let mut sink = informant.clone_sink();
+	let result = info::run_action_on_state(spec, pre_state, env_info, params, informant, TrieSpec::Fat);
+	T::finish(result, &mut sink);
+}
+
 fn run_stats_jsontests_vm(args: Args) {
 	use crate::json_tests::HookType;
 	use std::collections::HashMap;
@@ -337,7 +540,9 @@ fn run_call<T: Informant>(args: Args, informant: T) {
 #[derive(Debug, Deserialize)]
 struct Args {
 	cmd_stats: bool,
+	cmd_fuzz: bool,
 	cmd_state_test: bool,
+	cmd_blockchain_test: bool,
 	cmd_stats_jsontests_vm: bool,
 	arg_file: Option<PathBuf>,
 	flag_code: Option<String>,
@@ -348,6 +553,8 @@ struct Args {
 	flag_gas_price: Option<String>,
 	flag_only: Option<String>,
 	flag_chain: Option<String>,
+	flag_jobs: usize,
+	flag_report: bool,
 	flag_json: bool,
 	flag_std_json: bool,
 	flag_std_dump_json: bool,
@@ -448,10 +655,11 @@ mod tests {
 	use ethjson::test_helpers::state::State;
 	use serde::Deserialize;
 
-	use super::{Args, USAGE, Address, run_call};
+	use std::path::Path;
+	use super::{Args, USAGE, Address, run_call, json_files, load_tests, run_tests};
 	use crate::{
-		display::std_json::tests::informant,
-		info::{self, TxInput}
+		display::{self, std_json::tests::informant},
+		info::{self, Informant, TxInput}
 	};
 
 	#[derive(Debug, PartialEq, Deserialize)]
@@ -528,6 +736,87 @@ mod tests {
 		assert_eq!(args.flag_std_err_only, true);
 	}
 
+	#[test]
+	fn should_parse_blockchain_test_command() {
+		let args = run(&[
+			"openethereum-evm",
+			"blockchain-test",
+			"./BlockchainTests",
+			"--chain", "istanbul",
+			"--only=add11",
+			"--jobs", "4",
+			"--report",
+		]);
+
+		assert_eq!(args.cmd_blockchain_test, true);
+		assert_eq!(args.arg_file, Some("./BlockchainTests".into()));
+		assert_eq!(args.flag_chain, Some("istanbul".to_owned()));
+		assert_eq!(args.flag_only, Some("add11".to_owned()));
+		assert_eq!(args.flag_jobs, 4);
+		assert_eq!(args.flag_report, true);
+	}
+
+	#[test]
+	fn should_load_and_run_filtered_tests() {
+		use ethjson::test_helpers::state::State;
+
+		let res = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/res"));
+		assert_eq!(json_files(res), vec![
+			res.join("create2callPrecompiles.json"),
+			res.join("testchain.json"),
+			res.join("teststate.json"),
+		]);
+
+		let tests = load_tests::<State>(&res.join("teststate.json"), Some(&"ADD11".to_owned()));
+		assert_eq!(tests.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["add11"]);
+		assert_eq!(run_tests(2, tests, |_, _| false), 1);
+	}
+
+	#[test]
+	fn should_parse_fuzz_command() {
+		let args = run(&[
+			"openethereum-evm",
+			"fuzz",
+			"--std-out-only",
+		]);
+
+		assert_eq!(args.cmd_fuzz, true);
+		assert_eq!(args.flag_std_out_only, true);
+	}
+
+	#[test]
+	fn should_dump_state_of_fuzz_input() {
+		use crate::fuzz::FuzzInput;
+		use ethcore::test_helpers::EvmTestClient;
+
+		let input: FuzzInput = serde_json::from_str(r#"{
+			"fork": "Istanbul",
+			"env": {
+				"currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+				"currentDifficulty": "0x020000",
+				"currentGasLimit": "0x0f4240",
+				"currentNumber": "0x01",
+				"currentTimestamp": "0x03e8"
+			},
+			"pre": {},
+			"code": "0x600160005500",
+			"to": "0x0000000000000000000000000000000000000005",
+			"gas": "0xffff"
+		}"#).unwrap();
+		let (fork, env_info, pre_state, params) = input.into_parts();
+		let spec = EvmTestClient::fork_spec_from_json(&fork).unwrap();
+
+		let (inf, trace, out) = informant();
+		let mut sink = inf.clone_sink();
+		let result = info::run_action_on_state(&spec, &pre_state, env_info, params, inf, TrieSpec::Fat);
+		assert_eq!(result.as_ref().ok().map(|success| success.gas_used), Some(0x4e26.into()));
+		<display::std_json::Informant<_, _> as info::Informant>::finish(result, &mut sink);
+
+		let trace = String::from_utf8_lossy(&**trace.0.lock().unwrap()).into_owned();
+		assert!(trace.contains(r#""storage":{"0x0000000000000000000000000000000000000000000000000000000000000000":"0x0000000000000000000000000000000000000000000000000000000000000001"}"#));
+		assert!(String::from_utf8_lossy(&**out.0.lock().unwrap()).starts_with(r#"{"output":"0x","gasUsed":"0x4e26","#));
+	}
+
 	#[test]
 	#[should_panic]
 	fn should_not_parse_only_flag_without_state_test() {
@@ -582,7 +871,7 @@ mod tests {
 					informant,
 					trie_spec,
 				};
-				assert!(!info::run_transaction(tx_input));
+				assert_eq!(info::run_transaction(tx_input), info::Outcome::Failed);
 				assert!(
 					&String::from_utf8_lossy(&**res.0.lock().unwrap()).contains("State root mismatch")
 				);
@@ -618,7 +907,7 @@ mod tests {
 					informant,
 					trie_spec,
 				};
-				assert!(info::run_transaction(tx_input));
+				assert_eq!(info::run_transaction(tx_input), info::Outcome::Passed);
 			}
 		}
 	}
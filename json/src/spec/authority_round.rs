@@ -39,8 +39,10 @@
 //! ```
 
 use std::collections::BTreeMap;
+use std::io::Read;
 use crate::{bytes::Bytes, hash::Address, uint::Uint};
 use serde::Deserialize;
+use serde_json::Error;
 use super::{StepDuration, ValidatorSet};
 
 /// Authority params deserialization.
@@ -103,6 +105,35 @@ pub struct AuthorityRoundParams {
 	/// The block number at which the consensus engine switches from AuRa to AuRa with POSDAO
 	/// modifications.
 	pub posdao_transition: Option<Uint>,
+	/// The account whose signature authorizes updates of these parameters while the node is running.
+	pub params_update_signer: Option<Address>,
+}
+
+/// Update of the Authority Round parameters applied while the node is running. Every entry must
+/// take effect after the current best block.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorityRoundParamsUpdate {
+	/// Validator sets to switch to, keyed by the first block they apply to.
+	pub validators: Option<BTreeMap<Uint, ValidatorSet>>,
+	/// Step durations to switch to, keyed by the timestamp they apply from.
+	pub step_duration: Option<BTreeMap<Uint, Uint>>,
+	/// Block at which score validation should start.
+	pub validate_score_transition: Option<Uint>,
+	/// Block from which monotonic steps start.
+	pub validate_step_transition: Option<Uint>,
+	/// Block at which maximum uncle count should be considered.
+	pub maximum_uncle_count_transition: Option<Uint>,
+	/// Strict validation of empty steps transition block.
+	pub strict_empty_steps_transition: Option<Uint>,
+}
+
+impl AuthorityRoundParamsUpdate {
+	/// Loads the update from json.
+	pub fn load<R>(reader: R) -> Result<Self, Error> where R: Read {
+		serde_json::from_reader(reader)
+	}
 }
 
 /// Authority engine deserialization.
@@ -121,7 +152,7 @@ mod tests {
 	use serde_json;
 
 	use super::{Address, Uint, StepDuration};
-	use crate::{spec::{validator_set::ValidatorSet, authority_round::AuthorityRound}};
+	use crate::{spec::{validator_set::ValidatorSet, authority_round::{AuthorityRound, AuthorityRoundParamsUpdate}}};
 
 	#[test]
 	fn authority_round_deserialization() {
@@ -168,4 +199,25 @@ mod tests {
 		assert_eq!(deserialized.params.block_gas_limit_contract_transitions,
 				   Some(expected_bglc.to_vec().into_iter().collect()));
 	}
+
+	#[test]
+	fn authority_round_params_update_deserialization() {
+		let s = r#"{
+			"validators": {
+				"100": { "contract": "0x1000000000000000000000000000000000000001" }
+			},
+			"stepDuration": {
+				"1600000000": 3
+			},
+			"validateStepTransition": 120
+		}"#;
+
+		let deserialized = AuthorityRoundParamsUpdate::load(s.as_bytes()).unwrap();
+		assert_eq!(deserialized.validators.unwrap(), vec![
+			(Uint(100.into()), ValidatorSet::Contract(Address(H160::from_str("1000000000000000000000000000000000000001").unwrap()))),
+		].into_iter().collect());
+		assert_eq!(deserialized.step_duration.unwrap(), vec![(Uint(1_600_000_000u64.into()), Uint(3.into()))].into_iter().collect());
+		assert_eq!(deserialized.validate_step_transition, Some(Uint(120.into())));
+		assert_eq!(deserialized.validate_score_transition, None);
+	}
 }
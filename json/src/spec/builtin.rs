@@ -18,7 +18,7 @@
 
 use std::collections::BTreeMap;
 
-use crate::uint::Uint;
+use crate::{bytes::Bytes, uint::Uint};
 use serde::Deserialize;
 
 /// Linear pricing.
@@ -31,6 +31,19 @@ pub struct Linear {
 	pub word: u64,
 }
 
+/// Quadratic pricing: linear in the number of input words plus a quadratic term, in the style of
+/// EVM memory expansion.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Quadratic {
+	/// Base price.
+	pub base: u64,
+	/// Price for word.
+	pub word: u64,
+	/// Divisor of the squared word count.
+	pub quad_divisor: u64,
+}
+
 /// Pricing for modular exponentiation.
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
@@ -104,6 +117,8 @@ pub enum Pricing {
 	},
 	/// Linear pricing.
 	Linear(Linear),
+	/// Linear pricing with a quadratic term in the number of words.
+	Quadratic(Quadratic),
 	/// Pricing for modular exponentiation.
 	Modexp(Modexp),
 	/// Pricing for alt_bn128_pairing exponentiation.
@@ -120,6 +135,18 @@ pub enum Pricing {
 	Bls12G2Multiexp(Bls12G2Multiexp),
 }
 
+/// WASM module implementing a builtin, given either inline or as a file.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WasmModule {
+	/// Module code.
+	pub code: Option<Bytes>,
+	/// File holding the module, relative to the chain spec.
+	pub file: Option<String>,
+	/// Number of instructions a call may execute.
+	pub budget: u64,
+}
+
 /// Builtin compability layer
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
@@ -130,6 +157,8 @@ pub struct BuiltinCompat {
 	pricing: PricingCompat,
 	/// Activation block.
 	activate_at: Option<Uint>,
+	/// Module implementing the builtin, if named `wasm`.
+	wasm: Option<WasmModule>,
 }
 
 /// Spec builtin.
@@ -139,6 +168,8 @@ pub struct Builtin {
 	pub name: String,
 	/// Builtin pricing.
 	pub pricing: BTreeMap<u64, PricingAt>,
+	/// Module implementing the builtin, if named `wasm`.
+	pub wasm: Option<WasmModule>,
 }
 
 impl From<BuiltinCompat> for Builtin {
@@ -154,7 +185,7 @@ impl From<BuiltinCompat> for Builtin {
 				pricings.into_iter().map(|(a, p)| (a.into(), p)).collect()
 			}
 		};
-		Self { name: legacy.name, pricing }
+		Self { name: legacy.name, pricing, wasm: legacy.wasm }
 	}
 }
 
@@ -182,7 +213,7 @@ pub struct PricingAt {
 
 #[cfg(test)]
 mod tests {
-	use super::{Builtin, BuiltinCompat, Pricing, PricingAt, Linear, Modexp, AltBn128ConstOperations, Bls12G1Multiexp, Bls12G2Multiexp};
+	use super::{Builtin, BuiltinCompat, Pricing, PricingAt, Linear, Quadratic, Modexp, AltBn128ConstOperations, Bls12G1Multiexp, Bls12G2Multiexp, WasmModule};
 	use maplit::btreemap;
 
 	#[test]
@@ -201,6 +232,37 @@ mod tests {
 		]);
 	}
 
+	#[test]
+	fn deserialization_quadratic_pricing() {
+		let s = r#"{
+			"name": "identity",
+			"pricing": { "quadratic": { "base": 15, "word": 3, "quad_divisor": 512 } }
+		}"#;
+		let builtin: Builtin = serde_json::from_str::<BuiltinCompat>(s).unwrap().into();
+		assert_eq!(builtin.pricing, btreemap![
+			0 => PricingAt {
+				info: None,
+				price: Pricing::Quadratic(Quadratic { base: 15, word: 3, quad_divisor: 512 })
+			}
+		]);
+	}
+
+	#[test]
+	fn deserialization_wasm_builtin() {
+		let s = r#"{
+			"name": "wasm",
+			"pricing": { "linear": { "base": 100, "word": 10 } },
+			"wasm": { "file": "precompiles/reverse.wasm", "budget": 1000000 }
+		}"#;
+		let builtin: Builtin = serde_json::from_str::<BuiltinCompat>(s).unwrap().into();
+		assert_eq!(builtin.name, "wasm");
+		assert_eq!(builtin.wasm, Some(WasmModule {
+			code: None,
+			file: Some("precompiles/reverse.wasm".into()),
+			budget: 1_000_000,
+		}));
+	}
+
 	#[test]
 	fn deserialize_multiple_pricings() {
 		let s = r#"{
@@ -16,7 +16,7 @@
 
 //! Engine deserialization.
 
-use super::{Ethash, BasicAuthority, AuthorityRound, NullEngine, InstantSeal, Clique};
+use super::{Ethash, BasicAuthority, AuthorityRound, NullEngine, InstantSeal, Clique, ProxyEngine};
 use serde::Deserialize;
 
 /// Engine deserialization.
@@ -36,7 +36,9 @@ pub enum Engine {
 	/// AuthorityRound engine.
 	AuthorityRound(AuthorityRound),
 	/// Clique engine.
-	Clique(Clique)
+	Clique(Clique),
+	/// Engine delegating consensus to an external process.
+	Proxy(ProxyEngine),
 }
 
 #[cfg(test)]
@@ -146,5 +148,18 @@ mod tests {
 			Engine::Clique(_) => {}, // Clique is unit tested in its own file.
 			_ => panic!(),
 		};
+
+		let s = r#"{
+			"proxy": {
+				"params": {
+					"endpoint": "tcp://127.0.0.1:8600"
+				}
+			}
+		}"#;
+		let deserialized: Engine = serde_json::from_str(s).unwrap();
+		match deserialized {
+			Engine::Proxy(_) => {}, // Proxy is unit tested in its own file.
+			_ => panic!(),
+		};
 	}
 }
@@ -66,6 +66,10 @@ pub struct EthashParams {
 	pub block_reward_contract_address: Option<Address>,
 	/// Block reward code. This overrides the block reward contract address.
 	pub block_reward_contract_code: Option<Bytes>,
+	/// Block reward contract addresses with the blocks from which they are used. The single
+	/// block reward contract, if any, is added at `block_reward_contract_transition`, which must
+	/// be less than these blocks.
+	pub block_reward_contract_transitions: Option<BTreeMap<Uint, Address>>,
 
 	/// See main EthashParams docs.
 	pub dao_hardfork_transition: Option<Uint>,
@@ -173,6 +177,7 @@ mod tests {
 				block_reward_contract_address: None,
 				block_reward_contract_code: None,
 				block_reward_contract_transition: None,
+				block_reward_contract_transitions: None,
 				dao_hardfork_transition: Some(Uint(U256::from(0x08))),
 				dao_hardfork_beneficiary: Some(Address(H160::from_str("abcabcabcabcabcabcabcabcabcabcabcabcabca").unwrap())),
 				dao_hardfork_accounts: Some(vec![
@@ -234,6 +239,7 @@ mod tests {
 				block_reward_contract_address: None,
 				block_reward_contract_code: None,
 				block_reward_contract_transition: None,
+				block_reward_contract_transitions: None,
 				dao_hardfork_transition: None,
 				dao_hardfork_beneficiary: None,
 				dao_hardfork_accounts: None,
@@ -16,6 +16,7 @@
 
 //! Instant seal engine params deserialization.
 
+use crate::uint::Uint;
 use serde::Deserialize;
 
 /// Instant seal engine params deserialization.
@@ -26,6 +27,10 @@ pub struct InstantSealParams {
 	/// Whether to enable millisecond timestamp.
 	#[serde(default)]
 	pub millisecond_timestamp: bool,
+	/// Whether to seal a block as soon as there are transactions to include. Defaults to true.
+	pub automine: Option<bool>,
+	/// Interval in seconds at which a block is sealed, even if it has no transactions.
+	pub mining_interval: Option<Uint>,
 }
 
 /// Instant seal engine descriptor.
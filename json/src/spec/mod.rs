@@ -32,12 +32,14 @@ pub mod null_engine;
 pub mod instant_seal;
 pub mod hardcoded_sync;
 pub mod clique;
+pub mod proxy;
 pub mod step_duration;
+pub mod system_contract;
 
 pub use self::account::Account;
 pub use self::builtin::{Builtin, Pricing, Linear};
 pub use self::genesis::Genesis;
-pub use self::params::Params;
+pub use self::params::{Params, FeeRecipient, FeeRecipientKeyword};
 pub use self::spec::{Spec, ForkSpec};
 pub use self::seal::{Seal, Ethereum, AuthorityRoundSeal, TendermintSeal};
 pub use self::engine::Engine;
@@ -45,9 +47,11 @@ pub use self::state::{State, HashOrMap};
 pub use self::ethash::{Ethash, EthashParams, BlockReward};
 pub use self::validator_set::ValidatorSet;
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
-pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
+pub use self::authority_round::{AuthorityRound, AuthorityRoundParams, AuthorityRoundParamsUpdate};
 pub use self::clique::{Clique, CliqueParams};
+pub use self::proxy::{ProxyEngine, ProxyEngineParams};
 pub use self::null_engine::{NullEngine, NullEngineParams};
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::hardcoded_sync::HardcodedSync;
 pub use self::step_duration::StepDuration;
+pub use self::system_contract::{SystemCallPhase, SystemContract};
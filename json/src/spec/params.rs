@@ -16,13 +16,36 @@
 
 //! Spec params deserialization.
 
+use std::collections::BTreeMap;
+
 use crate::{
 	bytes::Bytes,
 	hash::{H256, Address},
+	spec::SystemContract,
 	uint::{self, Uint}
 };
 use serde::Deserialize;
 
+/// Recipient of the transaction fees: an address, `"author"` or `"burn"`.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum FeeRecipient {
+	/// Fees are paid to the given address.
+	Address(Address),
+	/// Fees are paid to the block author or burnt.
+	Keyword(FeeRecipientKeyword),
+}
+
+/// Recipients of the transaction fees other than an address.
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeRecipientKeyword {
+	/// Fees are paid to the block author.
+	Author,
+	/// Fees are burnt.
+	Burn,
+}
+
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -113,6 +136,10 @@ pub struct Params {
 	/// See `CommonParams` docs.
 	pub eip2315_transition: Option<Uint>,
 	/// See `CommonParams` docs.
+	pub eip3074_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_transition: Option<Uint>,
+	/// See `CommonParams` docs.
 	pub dust_protection_transition: Option<Uint>,
 	/// See `CommonParams` docs.
 	pub nonce_cap_increment: Option<Uint>,
@@ -145,12 +172,24 @@ pub struct Params {
 	pub kip4_transition: Option<Uint>,
 	/// KIP6 activiation block height.
 	pub kip6_transition: Option<Uint>,
+	/// Contracts called by the system address at the start or the end of every block.
+	pub system_contracts: Option<Vec<SystemContract>>,
+	/// Total difficulty at which the chain switches to proof-of-stake and its head is chosen by a consensus client.
+	pub terminal_total_difficulty: Option<Uint>,
+	/// Recipients of the transaction fees from the given blocks on. Fees go to the block author
+	/// before the first transition.
+	pub fee_recipient_transitions: Option<BTreeMap<Uint, FeeRecipient>>,
+	/// Recipients of the base fee part of the transaction fees from the given blocks on. The base
+	/// fee is burnt before the first transition.
+	pub base_fee_recipient_transitions: Option<BTreeMap<Uint, FeeRecipient>>,
 }
 
 #[cfg(test)]
 mod tests {
-	use super::{Params, Uint};
-	use ethereum_types::U256;
+	use std::str::FromStr;
+	use super::{FeeRecipient, FeeRecipientKeyword, Params, Uint};
+	use crate::hash::Address;
+	use ethereum_types::{H160, U256};
 
 	#[test]
 	fn params_deserialization() {
@@ -178,6 +217,36 @@ mod tests {
 		assert_eq!(deserialized.wasm_activation_transition, Some(Uint(U256::from(0x1010))));
 	}
 
+	#[test]
+	fn fee_recipient_transitions_deserialization() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID": "0x1",
+			"minGasLimit": "0x1388",
+			"gasLimitBoundDivisor": "0x20",
+			"feeRecipientTransitions": {
+				"0": "0x0000000000000000000000000000000000000042",
+				"100": "burn",
+				"200": "author"
+			},
+			"baseFeeRecipientTransitions": {
+				"300": "0x0000000000000000000000000000000000000043"
+			}
+		}"#;
+
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		let transitions = deserialized.fee_recipient_transitions.unwrap();
+		assert_eq!(transitions.into_iter().collect::<Vec<_>>(), vec![
+			(Uint(U256::from(0)), FeeRecipient::Address(Address(H160::from_str("0000000000000000000000000000000000000042").unwrap()))),
+			(Uint(U256::from(100)), FeeRecipient::Keyword(FeeRecipientKeyword::Burn)),
+			(Uint(U256::from(200)), FeeRecipient::Keyword(FeeRecipientKeyword::Author)),
+		]);
+		let transitions = deserialized.base_fee_recipient_transitions.unwrap();
+		assert_eq!(transitions.into_iter().collect::<Vec<_>>(), vec![
+			(Uint(U256::from(300)), FeeRecipient::Address(Address(H160::from_str("0000000000000000000000000000000000000043").unwrap()))),
+		]);
+	}
+
 	#[test]
 	#[should_panic(expected = "a non-zero value")]
 	fn test_zero_value_divisor() {
@@ -0,0 +1,61 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Proxy engine params deserialization.
+
+use serde::Deserialize;
+
+/// Proxy engine params deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyEngineParams {
+	/// Endpoint of the external consensus process, `ipc:///path/to/socket` or `tcp://host:port`.
+	pub endpoint: String,
+	/// Number of seal fields of the blocks.
+	pub seal_fields: Option<u64>,
+	/// Timeout of a request to the external process in milliseconds.
+	pub timeout: Option<u64>,
+}
+
+/// Proxy engine descriptor.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyEngine {
+	/// Proxy engine parameters.
+	pub params: ProxyEngineParams,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ProxyEngine;
+
+	#[test]
+	fn proxy_engine_deserialization() {
+		let s = r#"{
+			"params": {
+				"endpoint": "ipc:///tmp/consensus.ipc",
+				"sealFields": 1,
+				"timeout": 2000
+			}
+		}"#;
+
+		let deserialized: ProxyEngine = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.endpoint, "ipc:///tmp/consensus.ipc");
+		assert_eq!(deserialized.params.seal_fields, Some(1));
+		assert_eq!(deserialized.params.timeout, Some(2000));
+	}
+}
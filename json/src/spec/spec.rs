@@ -73,6 +73,9 @@ pub struct Spec {
 	pub genesis: Genesis,
 	/// Genesis state.
 	pub accounts: State,
+	/// File of further genesis accounts, mapping addresses to accounts like `accounts` but read as
+	/// a stream. Relative to the spec file. Requires the `stateRoot` of the genesis.
+	pub accounts_file: Option<String>,
 	/// Boot nodes.
 	pub nodes: Option<Vec<String>>,
 	/// Hardcoded synchronization for the light client.
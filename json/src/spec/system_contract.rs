@@ -0,0 +1,86 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Spec system contract deserialization.
+
+use crate::{bytes::Bytes, hash::Address, uint::Uint};
+use serde::Deserialize;
+
+/// Point of the block processing at which a system contract is called.
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SystemCallPhase {
+	/// Before the first transaction of the block.
+	BlockStart,
+	/// After the last transaction of the block, before the block rewards.
+	BlockEnd,
+}
+
+/// Contract called by the system address in every block.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemContract {
+	/// Contract address.
+	pub address: Address,
+	/// When the contract is called.
+	pub phase: SystemCallPhase,
+	/// Call data, empty by default.
+	pub data: Option<Bytes>,
+	/// Gas available to the call.
+	pub gas: Option<Uint>,
+	/// First block in which the contract is called.
+	pub activate_at: Option<Uint>,
+	/// First block in which the contract is not called anymore.
+	pub deactivate_at: Option<Uint>,
+	/// Code deployed at the contract address in the activation block.
+	pub code: Option<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{SystemCallPhase, SystemContract};
+	use crate::{bytes::Bytes, hash::Address, uint::Uint};
+	use ethereum_types::{H160, U256};
+	use std::str::FromStr;
+
+	#[test]
+	fn system_contract_deserialization() {
+		let s = r#"{
+			"address": "0x0000000000000000000000000000000000000100",
+			"phase": "blockEnd",
+			"data": "0x1234",
+			"gas": "0x10000",
+			"activateAt": "0x10",
+			"code": "0x6000"
+		}"#;
+
+		let deserialized: SystemContract = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.address, Address(H160::from_low_u64_be(0x100)));
+		assert_eq!(deserialized.phase, SystemCallPhase::BlockEnd);
+		assert_eq!(deserialized.data, Some(Bytes::from_str("1234").unwrap()));
+		assert_eq!(deserialized.gas, Some(Uint(U256::from(0x10000))));
+		assert_eq!(deserialized.activate_at, Some(Uint(U256::from(0x10))));
+		assert_eq!(deserialized.deactivate_at, None);
+		assert_eq!(deserialized.code, Some(Bytes::from_str("6000").unwrap()));
+	}
+
+	#[test]
+	fn system_contract_requires_phase() {
+		let s = r#"{ "address": "0x0000000000000000000000000000000000000100" }"#;
+		assert!(serde_json::from_str::<SystemContract>(s).is_err());
+	}
+}
@@ -0,0 +1,237 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Stable facade over the database of an OpenEthereum node.
+//!
+//! Opens the data directory of a stopped node read-only and answers block, receipt and
+//! state queries, and executes calls, in-process without going through JSON-RPC.
+//!
+//! ```no_run
+//! use openethereum_lib::{BlockId, Node, Options};
+//!
+//! let node = Node::open(&Options { chain: "goerli".into(), ..Default::default() }).unwrap();
+//! let best = node.best_block_number();
+//! let block = node.block(BlockId::Number(best)).unwrap();
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use client_traits::{Balance, BlockChainClient, BlockInfo, ChainInfo, Nonce, StateClient};
+use common_types::call_analytics::CallAnalytics;
+use common_types::transaction::{Action, Transaction};
+use ethcore::client::{Call, Client};
+
+mod open;
+
+pub use common_types::block::Block;
+pub use common_types::header::Header;
+pub use common_types::ids::{BlockId, TransactionId};
+pub use common_types::log_entry::LogEntry;
+pub use common_types::receipt::LocalizedReceipt;
+pub use common_types::transaction::LocalizedTransaction;
+pub use ethereum_types::{Address, H256, U256};
+pub use parity_bytes::Bytes;
+
+pub use crate::open::Options;
+
+/// Gas given to calls which don't set a limit, and the most any call can use.
+const MAX_CALL_GAS: u64 = 500_000_000;
+
+/// Errors returned by the facade.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+	/// The database couldn't be opened.
+	Open(String),
+	/// The requested block is not in the database.
+	UnknownBlock,
+	/// The state of the requested block has been pruned.
+	StatePruned,
+	/// Data read from the database couldn't be decoded.
+	Corrupted(String),
+	/// The call couldn't be executed.
+	Call(String),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Open(ref e) => write!(f, "Failed to open the database: {}", e),
+			Error::UnknownBlock => write!(f, "Unknown block"),
+			Error::StatePruned => write!(f, "State of the block is not available"),
+			Error::Corrupted(ref e) => write!(f, "Corrupted database: {}", e),
+			Error::Call(ref e) => write!(f, "Call failed: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// A message call or contract creation, executed without being signed or persisted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallRequest {
+	/// Sender.
+	pub from: Address,
+	/// Recipient, or `None` to create a contract.
+	pub to: Option<Address>,
+	/// Gas limit; `None` for the maximum.
+	pub gas: Option<U256>,
+	/// Gas price.
+	pub gas_price: U256,
+	/// Transferred value.
+	pub value: U256,
+	/// Input data.
+	pub data: Bytes,
+	/// Nonce; the sender's nonce at the block if `None`.
+	pub nonce: Option<U256>,
+}
+
+/// Outcome of a call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallResult {
+	/// Returned data, or the code of the created contract.
+	pub output: Bytes,
+	/// Gas used.
+	pub gas_used: U256,
+	/// Logs emitted.
+	pub logs: Vec<LogEntry>,
+	/// VM error the call ended with, if any.
+	pub exception: Option<String>,
+}
+
+/// A node database opened read-only.
+///
+/// The node must not be running while the database is open.
+pub struct Node {
+	client: Arc<Client>,
+}
+
+impl Node {
+	/// Open the database described by `options`.
+	pub fn open(options: &Options) -> Result<Self, Error> {
+		let client = open::open_client(options).map_err(Error::Open)?;
+		Ok(Node { client })
+	}
+
+	/// Number of the best block.
+	pub fn best_block_number(&self) -> u64 {
+		self.client.chain_info().best_block_number
+	}
+
+	/// Hash of the best block.
+	pub fn best_block_hash(&self) -> H256 {
+		self.client.chain_info().best_block_hash
+	}
+
+	/// Block header.
+	pub fn block_header(&self, id: BlockId) -> Result<Header, Error> {
+		self.client.block_header(id)
+			.ok_or(Error::UnknownBlock)?
+			.decode()
+			.map_err(|e| Error::Corrupted(e.to_string()))
+	}
+
+	/// Block with its transactions and uncles.
+	pub fn block(&self, id: BlockId) -> Result<Block, Error> {
+		self.client.block(id)
+			.ok_or(Error::UnknownBlock)?
+			.decode()
+			.map_err(|e| Error::Corrupted(e.to_string()))
+	}
+
+	/// Transaction with its location in the chain.
+	pub fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
+		self.client.transaction(id)
+	}
+
+	/// Receipt of a transaction.
+	pub fn receipt(&self, id: TransactionId) -> Option<LocalizedReceipt> {
+		self.client.transaction_receipt(id)
+	}
+
+	/// Receipts of all transactions in a block.
+	pub fn block_receipts(&self, id: BlockId) -> Result<Vec<LocalizedReceipt>, Error> {
+		self.client.localized_block_receipts(id).ok_or(Error::UnknownBlock)
+	}
+
+	/// Balance of an account at a block.
+	pub fn balance(&self, address: &Address, id: BlockId) -> Result<U256, Error> {
+		self.client.balance(address, id.into()).ok_or(Error::StatePruned)
+	}
+
+	/// Nonce of an account at a block.
+	pub fn nonce(&self, address: &Address, id: BlockId) -> Result<U256, Error> {
+		self.client.nonce(address, id).ok_or(Error::StatePruned)
+	}
+
+	/// Code of an account at a block; `None` if the account has none.
+	pub fn code(&self, address: &Address, id: BlockId) -> Result<Option<Bytes>, Error> {
+		self.client.code(address, id.into()).map_err(|_| Error::StatePruned)
+	}
+
+	/// Value of a storage slot at a block.
+	pub fn storage_at(&self, address: &Address, position: &H256, id: BlockId) -> Result<H256, Error> {
+		self.client.storage_at(address, position, id.into()).ok_or(Error::StatePruned)
+	}
+
+	/// Execute a call on top of the state after the given block.
+	pub fn call(&self, request: &CallRequest, id: BlockId) -> Result<CallResult, Error> {
+		let header = self.block_header(id)?;
+		let mut state = self.client.state_at(id).ok_or(Error::StatePruned)?;
+		let nonce = match request.nonce {
+			Some(nonce) => nonce,
+			None => self.nonce(&request.from, id)?,
+		};
+		let max_gas = U256::from(MAX_CALL_GAS);
+		let transaction = Transaction {
+			nonce,
+			action: request.to.map_or(Action::Create, Action::Call),
+			gas: request.gas.map_or(max_gas, |gas| gas.min(max_gas)),
+			gas_price: request.gas_price,
+			value: request.value,
+			data: request.data.clone(),
+		}.fake_sign(request.from);
+
+		let executed = self.client.call(&transaction, CallAnalytics::default(), &mut state, &header)
+			.map_err(|e| Error::Call(e.to_string()))?;
+		Ok(CallResult {
+			output: executed.output,
+			gas_used: executed.gas_used,
+			logs: executed.logs,
+			exception: executed.exception.map(|e| e.to_string()),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_fail_to_open_missing_datadir() {
+		let tempdir = tempfile::tempdir().unwrap();
+		let options = Options {
+			base_path: Some(tempdir.path().to_str().unwrap().into()),
+			..Default::default()
+		};
+
+		match Node::open(&options) {
+			Err(Error::Open(_)) => {},
+			Err(e) => panic!("unexpected error: {}", e),
+			Ok(_) => panic!("opened a missing database"),
+		}
+	}
+}
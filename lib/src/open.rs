@@ -0,0 +1,180 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Locating and opening the database of a stopped node, without networking, RPC or migrations.
+
+use std::cmp;
+use std::collections::HashSet;
+use std::fs::File;
+use std::sync::Arc;
+
+use common_types::client_types::Mode;
+use dir::Directories;
+use dir::helpers::replace_home;
+use ethcore::client::{Client, ClientConfig};
+use ethcore::miner::{Miner, MinerOptions};
+use ethcore_io::IoChannel;
+use ethcore_miner::gas_pricer::GasPricer;
+use ethereum_types::{Address, U256};
+use journaldb::Algorithm;
+use serde::{Deserialize, Deserializer};
+use spec::SpecType;
+
+/// Location and cache sizes of the database of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+	/// Chain name or path of a chain spec file, as given to `--chain`.
+	pub chain: String,
+	/// Data directory, as given to `--base-path`. The default one if not set.
+	pub base_path: Option<String>,
+	/// Databases directory, as given to `--db-path`. Under the data directory if not set.
+	pub db_path: Option<String>,
+	/// Total memory used for caches, in megabytes.
+	pub cache_size: u32,
+}
+
+impl Default for Options {
+	fn default() -> Self {
+		Options {
+			chain: "foundation".into(),
+			base_path: None,
+			db_path: None,
+			cache_size: 128,
+		}
+	}
+}
+
+impl Options {
+	fn directories(&self) -> Directories {
+		let mut dirs = match self.base_path {
+			Some(ref base) => {
+				let base = replace_home("", base);
+				Directories {
+					db: format!("{}/chains", base),
+					cache: format!("{}/cache", base),
+					keys: format!("{}/keys", base),
+					signer: format!("{}/signer", base),
+					secretstore: format!("{}/secretstore", base),
+					base,
+				}
+			},
+			None => Directories::default(),
+		};
+		if let Some(ref db_path) = self.db_path {
+			dirs.db = replace_home(&dirs.base, db_path);
+		}
+		dirs
+	}
+
+	/// Client configuration with the caches split like the node does with `--cache-size`.
+	fn client_config(&self, settings: &Settings, spec_name: String) -> ClientConfig {
+		let mb = 1024 * 1024;
+		let total = self.cache_size as usize;
+		let blockchain = cmp::max(total / 10, 4);
+		let state = total * 2 / 10;
+
+		let mut config = ClientConfig::default();
+		config.blockchain.max_cache_size = blockchain * mb;
+		config.blockchain.pref_cache_size = blockchain * 3 / 4 * mb;
+		config.db_cache_size = Some(cmp::max(total * 7 / 10, 8));
+		config.state_cache_size = state * 3 / 4 * mb;
+		config.jump_table_size = state / 4 * mb;
+		config.mode = Mode::Off;
+		config.tracing.enabled = settings.tracing;
+		config.fat_db = settings.fat_db;
+		config.pruning = settings.pruning;
+		config.spec_name = spec_name;
+		config
+	}
+}
+
+/// The settings a database was created with, saved by the node next to it.
+#[derive(Deserialize)]
+struct Settings {
+	#[serde(deserialize_with = "deserialize_algorithm")]
+	pruning: Algorithm,
+	tracing: bool,
+	fat_db: bool,
+}
+
+fn deserialize_algorithm<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Algorithm, D::Error> {
+	let pruning = String::deserialize(deserializer)?;
+	pruning.parse().map_err(|_| serde::de::Error::custom("invalid pruning method"))
+}
+
+/// Open the client of an existing database with the pruning, tracing and fat DB settings it
+/// was created with. The database files are never written. They are locked while the client
+/// is alive, so the node using them must be stopped. Fails if the database needs a migration.
+pub fn open_client(options: &Options) -> Result<Arc<Client>, String> {
+	let dirs = options.directories();
+	let spec = options.chain.parse::<SpecType>()?.spec(&dirs.cache)?;
+	let db_dirs = dirs.database(spec.genesis_header().hash(), None, spec.data_dir.clone());
+
+	let settings_path = db_dirs.user_defaults_path();
+	let settings: Settings = match File::open(&settings_path) {
+		Ok(file) => serde_json::from_reader(file)
+			.map_err(|e| format!("Invalid settings file {}: {}", settings_path.display(), e))?,
+		Err(_) => return Err(format!("No {} database found in {}", spec.name, dirs.db)),
+	};
+
+	let client_config = options.client_config(&settings, spec.name.to_lowercase());
+	let client_db = node_db::open_client_db_read_only(&db_dirs.db_path(settings.pruning), &client_config)?;
+	// nothing is imported or sealed, the miner only answers the client's queries about pending
+	// transactions, of which there are none.
+	let miner = Arc::new(Miner::new(
+		MinerOptions::default(),
+		GasPricer::new_fixed(U256::zero()),
+		&spec,
+		HashSet::<Address>::new(),
+	));
+
+	Client::new(client_config, &spec, client_db, miner, IoChannel::disconnected())
+		.map_err(|e| format!("Failed to open the client: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Options, open_client};
+
+	#[test]
+	fn should_place_databases_under_base_path() {
+		let options = Options {
+			base_path: Some("/tmp/oe".into()),
+			..Default::default()
+		};
+		let dirs = options.directories();
+		assert_eq!(dirs.db, "/tmp/oe/chains");
+		assert_eq!(dirs.cache, "/tmp/oe/cache");
+
+		let options = Options {
+			base_path: Some("/tmp/oe".into()),
+			db_path: Some("$BASE/databases".into()),
+			..Default::default()
+		};
+		assert_eq!(options.directories().db, "/tmp/oe/databases");
+	}
+
+	#[test]
+	fn should_not_open_missing_database() {
+		let tempdir = tempfile::tempdir().unwrap();
+		let options = Options {
+			chain: "dev".into(),
+			base_path: Some(tempdir.path().to_string_lossy().into_owned()),
+			..Default::default()
+		};
+		assert!(open_client(&options).is_err());
+	}
+}
@@ -0,0 +1,350 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Append-only journal of local transactions.
+//!
+//! Every submitted local transaction is appended to the journal as soon as it enters the pool,
+//! and a finalization record is appended once it leaves the pool for good (mined, culled,
+//! replaced or rejected). Replaying the journal on startup yields the transactions that should
+//! be resubmitted. The file is rewritten with only the live entries once it grows too large.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use common_types::transaction::PendingTransaction;
+use ethereum_types::H256;
+use log::{debug, warn};
+use parking_lot::Mutex;
+use serde_derive::{Serialize, Deserialize};
+use serde_json;
+
+use crate::TransactionEntry;
+
+/// Minimal number of appended records before the journal is compacted.
+const MIN_RECORDS_BEFORE_COMPACTION: usize = 256;
+
+/// A local transaction recorded in the journal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+	/// The transaction with its condition.
+	pub transaction: PendingTransaction,
+	/// UNIX timestamp of the first submission.
+	pub submitted_at: u64,
+	/// Number of times the transaction was already resubmitted.
+	pub resubmissions: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+enum Record {
+	#[serde(rename_all = "camelCase")]
+	Submitted {
+		transaction: TransactionEntry,
+		submitted_at: u64,
+		resubmissions: u32,
+	},
+	Finalized {
+		hash: H256,
+	},
+}
+
+struct State {
+	file: File,
+	entries: HashMap<H256, JournalEntry>,
+	order: Vec<H256>,
+	records: usize,
+}
+
+impl State {
+	fn insert(&mut self, entry: JournalEntry) {
+		let hash = entry.transaction.hash();
+		if self.entries.insert(hash, entry).is_none() {
+			self.order.push(hash);
+		}
+	}
+
+	fn remove(&mut self, hash: &H256) -> bool {
+		if self.entries.remove(hash).is_none() {
+			return false;
+		}
+		self.order.retain(|h| h != hash);
+		true
+	}
+
+	fn append(&mut self, record: &Record) -> io::Result<()> {
+		let mut line = serde_json::to_vec(record)?;
+		line.push(b'\n');
+		self.file.write_all(&line)?;
+		self.file.sync_data()?;
+		self.records += 1;
+		Ok(())
+	}
+}
+
+/// Journal of local transactions persisted in a file.
+pub struct Journal {
+	path: PathBuf,
+	state: Mutex<State>,
+}
+
+impl Journal {
+	/// Open the journal at given path, replaying and compacting existing records.
+	pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref().to_owned();
+		let mut entries = HashMap::new();
+		let mut order = Vec::new();
+
+		if path.exists() {
+			let reader = BufReader::new(File::open(&path)?);
+			for line in reader.lines() {
+				let line = line?;
+				if line.trim().is_empty() {
+					continue;
+				}
+
+				match serde_json::from_str(&line) {
+					Ok(Record::Submitted { transaction, submitted_at, resubmissions }) => {
+						if let Some(transaction) = transaction.into_pending() {
+							let hash = transaction.hash();
+							let entry = JournalEntry { transaction, submitted_at, resubmissions };
+							if entries.insert(hash, entry).is_none() {
+								order.push(hash);
+							}
+						}
+					},
+					Ok(Record::Finalized { hash }) => {
+						if entries.remove(&hash).is_some() {
+							order.retain(|h| h != &hash);
+						}
+					},
+					// the last record might have been only partially written before a crash.
+					Err(e) => warn!(target: "local_store", "Skipping invalid journal record: {}", e),
+				}
+			}
+		}
+
+		let journal = Journal {
+			state: Mutex::new(State {
+				file: OpenOptions::new().create(true).append(true).open(&path)?,
+				entries,
+				order,
+				records: 0,
+			}),
+			path,
+		};
+		journal.compact()?;
+
+		Ok(journal)
+	}
+
+	/// Record a submitted transaction.
+	///
+	/// Any other journaled transaction with the same sender and nonce is considered replaced.
+	pub fn submitted(&self, entry: JournalEntry) -> io::Result<()> {
+		let mut state = self.state.lock();
+		let hash = entry.transaction.hash();
+		let (sender, nonce) = (entry.transaction.sender(), entry.transaction.nonce);
+		let replaced: Vec<H256> = state.order.iter()
+			.filter(|h| **h != hash)
+			.filter(|h| {
+				let tx = &state.entries[*h].transaction;
+				tx.sender() == sender && tx.nonce == nonce
+			})
+			.cloned()
+			.collect();
+
+		for hash in replaced {
+			debug!(target: "local_store", "Journaled transaction {:?} replaced.", hash);
+			state.remove(&hash);
+			state.append(&Record::Finalized { hash })?;
+		}
+
+		state.append(&Record::Submitted {
+			transaction: entry.transaction.clone().into(),
+			submitted_at: entry.submitted_at,
+			resubmissions: entry.resubmissions,
+		})?;
+		state.insert(entry);
+		drop(state);
+
+		self.maybe_compact()
+	}
+
+	/// Record a transaction which left the pool for good.
+	pub fn finalized(&self, hash: &H256) -> io::Result<()> {
+		{
+			let mut state = self.state.lock();
+			if !state.remove(hash) {
+				return Ok(());
+			}
+			state.append(&Record::Finalized { hash: *hash })?;
+		}
+
+		self.maybe_compact()
+	}
+
+	/// Returns true if the transaction is journaled.
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.state.lock().entries.contains_key(hash)
+	}
+
+	/// All live entries in submission order.
+	pub fn entries(&self) -> Vec<JournalEntry> {
+		let state = self.state.lock();
+		state.order.iter().map(|hash| state.entries[hash].clone()).collect()
+	}
+
+	/// Remove all entries.
+	pub fn clear(&self) -> io::Result<()> {
+		{
+			let mut state = self.state.lock();
+			state.entries.clear();
+			state.order.clear();
+		}
+
+		self.compact()
+	}
+
+	/// Rewrite the journal file with only the live entries.
+	pub fn compact(&self) -> io::Result<()> {
+		let mut state = self.state.lock();
+		let tmp_path = self.path.with_extension("tmp");
+
+		{
+			let mut tmp = File::create(&tmp_path)?;
+			for hash in &state.order {
+				let entry = &state.entries[hash];
+				let mut line = serde_json::to_vec(&Record::Submitted {
+					transaction: entry.transaction.clone().into(),
+					submitted_at: entry.submitted_at,
+					resubmissions: entry.resubmissions,
+				})?;
+				line.push(b'\n');
+				tmp.write_all(&line)?;
+			}
+			tmp.sync_all()?;
+		}
+
+		fs::rename(&tmp_path, &self.path)?;
+		state.file = OpenOptions::new().append(true).open(&self.path)?;
+		state.records = state.order.len();
+
+		Ok(())
+	}
+
+	fn maybe_compact(&self) -> io::Result<()> {
+		let should_compact = {
+			let state = self.state.lock();
+			state.records >= MIN_RECORDS_BEFORE_COMPACTION && state.records > 2 * state.order.len()
+		};
+
+		if should_compact {
+			self.compact()
+		} else {
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Journal, JournalEntry};
+
+	use common_types::transaction::{Transaction, Condition, PendingTransaction};
+	use ethkey::Brain;
+	use parity_crypto::publickey::Generator;
+	use tempfile::TempDir;
+
+	fn entry(nonce: u64, gas_price: u64, submitted_at: u64) -> JournalEntry {
+		let keypair = Brain::new("abcd".into()).generate();
+		let mut tx = Transaction::default();
+		tx.nonce = nonce.into();
+		tx.gas_price = gas_price.into();
+		let condition = match nonce {
+			5 => Some(Condition::Number(100_000)),
+			_ => None,
+		};
+
+		JournalEntry {
+			transaction: PendingTransaction::new(tx.sign(keypair.secret(), None), condition),
+			submitted_at,
+			resubmissions: 0,
+		}
+	}
+
+	#[test]
+	fn replays_live_entries() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("journal");
+		let (tx1, tx2, tx3) = (entry(4, 1, 10), entry(5, 1, 20), entry(6, 1, 30));
+
+		{
+			let journal = Journal::open(&path).unwrap();
+			journal.submitted(tx1.clone()).unwrap();
+			journal.submitted(tx2.clone()).unwrap();
+			journal.submitted(tx3.clone()).unwrap();
+			journal.finalized(&tx1.transaction.hash()).unwrap();
+		}
+
+		let journal = Journal::open(&path).unwrap();
+		assert_eq!(journal.entries(), vec![tx2, tx3]);
+		assert!(!journal.contains(&tx1.transaction.hash()));
+	}
+
+	#[test]
+	fn updates_resubmitted_and_drops_replaced_entries() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("journal");
+		let (tx1, tx2) = (entry(4, 1, 10), entry(5, 1, 20));
+		let replacement = entry(4, 2, 30);
+
+		{
+			let journal = Journal::open(&path).unwrap();
+			journal.submitted(tx1.clone()).unwrap();
+			journal.submitted(tx2.clone()).unwrap();
+			journal.submitted(JournalEntry { resubmissions: 1, ..tx2.clone() }).unwrap();
+			journal.submitted(replacement.clone()).unwrap();
+		}
+
+		let journal = Journal::open(&path).unwrap();
+		assert_eq!(journal.entries(), vec![JournalEntry { resubmissions: 1, ..tx2 }, replacement]);
+	}
+
+	#[test]
+	fn skips_truncated_records() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("journal");
+		let tx1 = entry(4, 1, 10);
+
+		{
+			let journal = Journal::open(&path).unwrap();
+			journal.submitted(tx1.clone()).unwrap();
+		}
+		{
+			use std::io::Write;
+			let mut file = ::std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+			file.write_all(br#"{"event":"submitted","transac"#).unwrap();
+		}
+
+		let journal = Journal::open(&path).unwrap();
+		assert_eq!(journal.entries(), vec![tx1.clone()]);
+
+		journal.clear().unwrap();
+		assert_eq!(Journal::open(&path).unwrap().entries(), vec![]);
+	}
+}
@@ -16,9 +16,10 @@
 
 //! Manages local node data: pending local transactions, sync security level
 
+use std::collections::HashSet;
 use std::io;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use common_types::{
 	BlockNumber,
@@ -34,11 +35,20 @@ use rlp::Rlp;
 use serde_derive::{Serialize, Deserialize};
 use serde_json;
 
+mod journal;
+
+pub use self::journal::{Journal, JournalEntry};
+
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
 
 const UPDATE_TIMER: TimerToken = 0;
 const UPDATE_TIMEOUT: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
 
+/// Journaled transactions resubmitted this many times are given up on.
+pub const MAX_RESUBMISSIONS: u32 = 3;
+/// Journaled transactions submitted longer ago than this are given up on.
+pub const MAX_RESUBMISSION_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 #[derive(Serialize, Deserialize)]
 enum Condition {
 	Number(BlockNumber),
@@ -112,6 +122,7 @@ pub fn create<T: NodeInfo>(db: Arc<dyn KeyValueDB>, col: u32, node: T) -> LocalD
 		db,
 		col,
 		node,
+		journal: None,
 	}
 }
 
@@ -123,9 +134,58 @@ pub struct LocalDataStore<T: NodeInfo> {
 	db: Arc<dyn KeyValueDB>,
 	col: u32,
 	node: T,
+	journal: Option<Journal>,
 }
 
 impl<T: NodeInfo> LocalDataStore<T> {
+	/// Keep a journal of submitted local transactions in addition to the periodic snapshots.
+	pub fn with_journal(mut self, journal: Journal) -> Self {
+		self.journal = Some(journal);
+		self
+	}
+
+	/// The local transactions journal, if any.
+	pub fn journal(&self) -> Option<&Journal> {
+		self.journal.as_ref()
+	}
+
+	/// Local transactions which should be resubmitted to the queue after a restart.
+	///
+	/// Contains all journaled transactions followed by the ones found only in the last snapshot.
+	/// Journaled transactions which were resubmitted `MAX_RESUBMISSIONS` times or are older than
+	/// `MAX_RESUBMISSION_AGE` are finalized instead, so that transactions dropped from the pool
+	/// are not resubmitted forever.
+	pub fn transactions_to_resubmit(&self) -> io::Result<Vec<JournalEntry>> {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+		let journaled = self.journal.as_ref().map(Journal::entries).unwrap_or_default();
+		let journaled_hashes: HashSet<_> = journaled.iter().map(|entry| entry.transaction.hash()).collect();
+
+		let mut entries = Vec::with_capacity(journaled.len());
+		for entry in journaled {
+			let expired = entry.resubmissions >= MAX_RESUBMISSIONS
+				|| now.saturating_sub(entry.submitted_at) > MAX_RESUBMISSION_AGE.as_secs();
+			match self.journal {
+				Some(ref journal) if expired => {
+					debug!(target: "local_store", "Giving up on journaled transaction {:?}.", entry.transaction.hash());
+					journal.finalized(&entry.transaction.hash())?;
+				},
+				_ => entries.push(entry),
+			}
+		}
+
+		entries.extend(self.pending_transactions()?
+			.into_iter()
+			.filter(|tx| !journaled_hashes.contains(&tx.hash()))
+			.map(|transaction| JournalEntry {
+				transaction,
+				submitted_at: now,
+				resubmissions: 0,
+			})
+		);
+
+		Ok(entries)
+	}
+
 	/// Attempt to read pending transactions out of the local store.
 	pub fn pending_transactions(&self) -> io::Result<Vec<PendingTransaction>> {
 		if let Some(val) = self.db.get(self.col, LOCAL_TRANSACTIONS_KEY)? {
@@ -149,14 +209,24 @@ impl<T: NodeInfo> LocalDataStore<T> {
 			.map(Into::into)
 			.collect();
 
-		self.write_txs(&local_entries)
+		self.write_txs(&local_entries)?;
+
+		match self.journal {
+			Some(ref journal) => journal.compact(),
+			None => Ok(()),
+		}
 	}
 
-	/// Clear data in this column.
+	/// Clear data in this column and the journal.
 	pub fn clear(&self) -> io::Result<()> {
 		trace!(target: "local_store", "Clearing local store entries.");
 
-		self.write_txs(&[])
+		self.write_txs(&[])?;
+
+		match self.journal {
+			Some(ref journal) => journal.clear(),
+			None => Ok(()),
+		}
 	}
 
 	// helper for writing a vector of transaction entries to disk.
@@ -200,6 +270,7 @@ mod tests {
 	use super::NodeInfo;
 
 	use std::sync::Arc;
+	use std::time::{SystemTime, UNIX_EPOCH};
 	use common_types::transaction::{Transaction, Condition, PendingTransaction};
 	use ethkey::Brain;
 	use parity_crypto::publickey::Generator;
@@ -262,6 +333,74 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn resubmits_journaled_and_stored_transactions() {
+		let keypair = Brain::new("abcd".into()).generate();
+		let transactions: Vec<_> = (0..3u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let dir = tempfile::TempDir::new().unwrap();
+		let db = Arc::new(::kvdb_memorydb::create(1));
+		{
+			// first two transactions snapshotted.
+			let _store = super::create(db.clone(), 0, Dummy(transactions[..2].to_vec()));
+		}
+
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+		let journal = super::Journal::open(dir.path().join("journal")).unwrap();
+		for (tx, submitted_at) in transactions[1..].iter().zip(vec![now - 20, now - 10]) {
+			journal.submitted(super::JournalEntry {
+				transaction: tx.clone(),
+				submitted_at,
+				resubmissions: 1,
+			}).unwrap();
+		}
+
+		let store = super::create(db.clone(), 0, Dummy(vec![])).with_journal(journal);
+		let entries = store.transactions_to_resubmit().unwrap();
+		let resubmitted: Vec<_> = entries.iter().map(|entry| entry.transaction.clone()).collect();
+		assert_eq!(resubmitted, vec![transactions[1].clone(), transactions[2].clone(), transactions[0].clone()]);
+		assert_eq!(entries[0].submitted_at, now - 20);
+		assert_eq!(entries[2].resubmissions, 0);
+
+		store.clear().unwrap();
+		assert_eq!(store.transactions_to_resubmit().unwrap(), vec![]);
+	}
+
+	#[test]
+	fn gives_up_on_old_and_often_resubmitted_transactions() {
+		let keypair = Brain::new("abcd".into()).generate();
+		let transactions: Vec<_> = (0..3u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let dir = tempfile::TempDir::new().unwrap();
+		let db = Arc::new(::kvdb_memorydb::create(1));
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+		let journal = super::Journal::open(dir.path().join("journal")).unwrap();
+		let entries = vec![
+			(now, super::MAX_RESUBMISSIONS - 1),
+			(now, super::MAX_RESUBMISSIONS),
+			(now - super::MAX_RESUBMISSION_AGE.as_secs() - 1, 0),
+		];
+		for (tx, (submitted_at, resubmissions)) in transactions.iter().zip(entries) {
+			journal.submitted(super::JournalEntry { transaction: tx.clone(), submitted_at, resubmissions }).unwrap();
+		}
+
+		let store = super::create(db, 0, Dummy(vec![])).with_journal(journal);
+		let resubmitted: Vec<_> = store.transactions_to_resubmit().unwrap().into_iter().map(|entry| entry.transaction).collect();
+		assert_eq!(resubmitted, vec![transactions[0].clone()]);
+		assert!(!store.journal().unwrap().contains(&transactions[1].hash()));
+		assert!(!store.journal().unwrap().contains(&transactions[2].hash()));
+	}
+
 	#[test]
 	fn skips_bad_transactions() {
 		let keypair = Brain::new("abcd".into()).generate();
@@ -37,6 +37,7 @@ extern crate registrar;
 extern crate rlp;
 extern crate transaction_pool as txpool;
 extern crate serde;
+extern crate serde_json;
 
 #[macro_use]
 extern crate ethabi_contract;
@@ -53,6 +54,8 @@ extern crate rustc_hex;
 extern crate parity_crypto;
 #[cfg(test)]
 extern crate env_logger;
+#[cfg(test)]
+extern crate tempfile;
 
 pub mod external;
 #[cfg(feature = "price-info")]
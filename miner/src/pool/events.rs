@@ -0,0 +1,104 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool lifecycle events.
+//!
+//! Unlike `TxStatus` notifications, events carry enough details to follow the
+//! whole lifecycle of a transaction: when it enters the pool, becomes ready for
+//! inclusion, gets replaced, leaves the pool and why, or gets mined.
+
+use ethereum_types::{H256, U256, Address};
+use futures::sync::mpsc;
+use std::sync::Arc;
+
+/// Reason for a transaction leaving the pool without being mined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason {
+	/// Pushed out because the pool reached its limits.
+	Limit,
+	/// Marked as invalid, e.g. when it failed during block production.
+	Invalid,
+	/// Canceled by the user.
+	Canceled,
+	/// Became stale or its nonce was used by another transaction.
+	Culled,
+	/// Stayed in the pool for longer than its time-to-live.
+	Expired,
+	/// Replaced by a transaction with the same sender and nonce.
+	Replaced,
+}
+
+/// Lifecycle event of a transaction in the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum PoolEvent {
+	/// Transaction entered the pool.
+	#[serde(rename_all = "camelCase")]
+	Added {
+		/// Transaction hash.
+		hash: H256,
+		/// Transaction sender.
+		sender: Address,
+		/// Transaction nonce.
+		nonce: U256,
+		/// Transaction gas price.
+		gas_price: U256,
+		/// Whether the transaction is local.
+		local: bool,
+	},
+	/// Transaction became ready for inclusion in a block.
+	Promoted {
+		/// Transaction hash.
+		hash: H256,
+	},
+	/// Transaction was replaced by another one with the same sender and nonce.
+	Replaced {
+		/// Hash of the replaced transaction.
+		hash: H256,
+		/// Hash of the replacement.
+		by: H256,
+	},
+	/// Transaction left the pool without being mined.
+	Dropped {
+		/// Transaction hash.
+		hash: H256,
+		/// Why the transaction was dropped.
+		reason: DropReason,
+		/// Transaction which pushed this one out, if any.
+		#[serde(skip_serializing_if = "Option::is_none")]
+		by: Option<H256>,
+	},
+	/// Transaction was included in a block.
+	Mined {
+		/// Transaction hash.
+		hash: H256,
+	},
+}
+
+/// Receives lifecycle events of transactions in the pool.
+pub trait PoolEventListener: Send + Sync {
+	/// Handle a batch of events, in the order they happened.
+	///
+	/// Returns `false` when the listener is no longer interested in events and should be removed.
+	fn on_events(&self, events: &[PoolEvent]) -> bool;
+}
+
+impl PoolEventListener for mpsc::UnboundedSender<Arc<Vec<PoolEvent>>> {
+	fn on_events(&self, events: &[PoolEvent]) -> bool {
+		self.unbounded_send(Arc::new(events.to_vec())).is_ok()
+	}
+}
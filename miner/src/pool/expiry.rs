@@ -0,0 +1,120 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Time-to-live of transactions in the pool.
+
+use std::time::{Duration, SystemTime};
+
+use pool::VerifiedTransaction;
+
+/// How long transactions are allowed to stay in the pool.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Expiry {
+	/// Time-to-live of local transactions, `None` means they never expire.
+	pub local: Option<Duration>,
+	/// Time-to-live of remote transactions, `None` means they never expire.
+	pub remote: Option<Duration>,
+}
+
+impl Expiry {
+	/// Returns true if transactions never expire.
+	pub fn is_disabled(&self) -> bool {
+		self.local.is_none() && self.remote.is_none()
+	}
+
+	/// Returns time-to-live of given transaction.
+	pub fn ttl(&self, tx: &VerifiedTransaction) -> Option<Duration> {
+		if tx.is_local() {
+			self.local
+		} else {
+			self.remote
+		}
+	}
+
+	/// Returns the age of given transaction and when it is going to be evicted.
+	pub fn age(&self, tx: &VerifiedTransaction, now: SystemTime) -> TransactionAge {
+		let received_at = tx.received_at();
+		TransactionAge {
+			received_at,
+			age: now.duration_since(received_at).unwrap_or_default(),
+			expires_at: self.ttl(tx).and_then(|ttl| received_at.checked_add(ttl)),
+			is_local: tx.is_local(),
+		}
+	}
+
+	/// Returns true if given transaction outlived its time-to-live.
+	pub fn is_expired(&self, tx: &VerifiedTransaction, now: SystemTime) -> bool {
+		self.age(tx, now).is_expired(now)
+	}
+}
+
+/// Age of a transaction in the pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransactionAge {
+	/// When the transaction was received.
+	pub received_at: SystemTime,
+	/// How long the transaction has been in the pool.
+	pub age: Duration,
+	/// When the transaction is going to be evicted, if ever.
+	pub expires_at: Option<SystemTime>,
+	/// Whether the transaction is local.
+	pub is_local: bool,
+}
+
+impl TransactionAge {
+	/// Returns true if the transaction should already be evicted.
+	pub fn is_expired(&self, now: SystemTime) -> bool {
+		self.expires_at.map_or(false, |expires_at| expires_at <= now)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pool::tests::tx::{Tx, TxExt};
+
+	#[test]
+	fn should_expire_local_and_remote_transactions_separately() {
+		let expiry = Expiry {
+			local: None,
+			remote: Some(Duration::from_secs(60)),
+		};
+		let remote = Tx::default().signed().verified();
+		let mut local = Tx::default().signed().verified();
+		local.priority = ::pool::Priority::Local;
+
+		let now = remote.received_at() + Duration::from_secs(30);
+		assert_eq!(expiry.age(&remote, now).age, Duration::from_secs(30));
+		assert!(!expiry.is_expired(&remote, now));
+
+		let now = now + Duration::from_secs(30);
+		assert!(expiry.is_expired(&remote, now));
+		assert!(!expiry.is_expired(&local, now));
+		assert_eq!(expiry.age(&local, now).expires_at, None);
+	}
+
+	#[test]
+	fn should_never_expire_when_ttl_overflows() {
+		let expiry = Expiry {
+			local: None,
+			remote: Some(Duration::from_secs(u64::max_value())),
+		};
+		let remote = Tx::default().signed().verified();
+
+		assert_eq!(expiry.age(&remote, remote.received_at()).expires_at, None);
+		assert!(!expiry.is_expired(&remote, remote.received_at()));
+	}
+}
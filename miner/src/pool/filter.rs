@@ -0,0 +1,222 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pluggable filters applied to transactions entering the pool.
+//!
+//! Filters allow operators to restrict which senders, recipients or contract
+//! functions can be used on their node, independently of any on-chain permissioning.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ethereum_types::{Address, H32};
+use parking_lot::RwLock;
+use types::transaction::{self, Action, SignedTransaction};
+
+/// Filter deciding which transactions are allowed in the pool.
+pub trait TransactionFilter: fmt::Debug + Send + Sync {
+	/// Checks if given transaction is allowed.
+	///
+	/// Returns the reason of the rejection otherwise.
+	fn check(&self, tx: &SignedTransaction) -> Result<(), transaction::Error>;
+
+	/// Called periodically, allows the filter to refresh its rules.
+	fn refresh(&self) {}
+}
+
+/// Lists of banned and allowed senders, recipients and 4-byte function selectors.
+///
+/// Allow lists are applied only when present, ban lists always take precedence.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct FilterLists {
+	/// Senders which are not allowed to send transactions.
+	pub banned_senders: HashSet<Address>,
+	/// If present, only these senders are allowed to send transactions.
+	pub allowed_senders: Option<HashSet<Address>>,
+	/// Recipients which are not allowed to receive transactions.
+	pub banned_recipients: HashSet<Address>,
+	/// If present, only these recipients are allowed to receive transactions.
+	pub allowed_recipients: Option<HashSet<Address>>,
+	/// Function selectors which are not allowed to be called.
+	pub banned_selectors: HashSet<H32>,
+	/// If present, only these function selectors are allowed to be called.
+	pub allowed_selectors: Option<HashSet<H32>>,
+}
+
+impl FilterLists {
+	/// Reads the lists from a JSON file.
+	pub fn load(path: &Path) -> Result<Self, String> {
+		let file = File::open(path)
+			.map_err(|e| format!("Unable to open transaction filter file {}: {}", path.display(), e))?;
+		serde_json::from_reader(file)
+			.map_err(|e| format!("Invalid transaction filter file {}: {}", path.display(), e))
+	}
+}
+
+fn is_allowed<T: ::std::hash::Hash + Eq>(item: &T, banned: &HashSet<T>, allowed: &Option<HashSet<T>>) -> bool {
+	!banned.contains(item) && allowed.as_ref().map_or(true, |allowed| allowed.contains(item))
+}
+
+impl TransactionFilter for FilterLists {
+	fn check(&self, tx: &SignedTransaction) -> Result<(), transaction::Error> {
+		if !is_allowed(&tx.sender(), &self.banned_senders, &self.allowed_senders) {
+			return Err(transaction::Error::SenderBanned);
+		}
+
+		if let Action::Call(ref recipient) = tx.action {
+			if !is_allowed(recipient, &self.banned_recipients, &self.allowed_recipients) {
+				return Err(transaction::Error::RecipientBanned);
+			}
+
+			let has_selector_rules = !self.banned_selectors.is_empty() || self.allowed_selectors.is_some();
+			if has_selector_rules && tx.data.len() >= 4 {
+				let selector = H32::from_slice(&tx.data[..4]);
+				if !is_allowed(&selector, &self.banned_selectors, &self.allowed_selectors) {
+					return Err(transaction::Error::SelectorBanned);
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Filter driven by a JSON file with `FilterLists`, reloaded whenever the file changes.
+pub struct FileFilter {
+	path: PathBuf,
+	lists: RwLock<FilterLists>,
+	modified: RwLock<Option<SystemTime>>,
+}
+
+impl FileFilter {
+	/// Creates a new filter reading the lists from given file.
+	pub fn new<P: Into<PathBuf>>(path: P) -> Result<Self, String> {
+		let path = path.into();
+		let modified = Self::modified(&path);
+		let lists = FilterLists::load(&path)?;
+		Ok(FileFilter {
+			path,
+			lists: RwLock::new(lists),
+			modified: RwLock::new(modified),
+		})
+	}
+
+	fn modified(path: &Path) -> Option<SystemTime> {
+		fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+	}
+
+	/// Reloads the lists if the file has changed since it was last read.
+	///
+	/// Returns true if the lists were reloaded. If the new file is invalid, previous lists are kept.
+	pub fn reload_if_changed(&self) -> bool {
+		let modified = Self::modified(&self.path);
+		if modified == *self.modified.read() {
+			return false;
+		}
+
+		*self.modified.write() = modified;
+		match FilterLists::load(&self.path) {
+			Ok(lists) => {
+				info!(target: "txqueue", "Reloaded transaction filter from {}", self.path.display());
+				*self.lists.write() = lists;
+				true
+			},
+			Err(err) => {
+				warn!(target: "txqueue", "{}. Keeping previous rules.", err);
+				false
+			},
+		}
+	}
+}
+
+impl fmt::Debug for FileFilter {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("FileFilter")
+			.field("path", &self.path)
+			.finish()
+	}
+}
+
+impl TransactionFilter for FileFilter {
+	fn check(&self, tx: &SignedTransaction) -> Result<(), transaction::Error> {
+		self.lists.read().check(tx)
+	}
+
+	fn refresh(&self) {
+		self.reload_if_changed();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+	use pool::tests::tx::Tx;
+
+	fn call(to: Address, data: Vec<u8>) -> SignedTransaction {
+		let mut tx = Tx::default().unsigned();
+		tx.action = Action::Call(to);
+		tx.data = data;
+		tx.fake_sign(Address::from_low_u64_be(1))
+	}
+
+	#[test]
+	fn should_apply_ban_and_allow_lists() {
+		let lists: FilterLists = serde_json::from_str(r#"{
+			"bannedSenders": ["0x0000000000000000000000000000000000000002"],
+			"allowedRecipients": ["0x0000000000000000000000000000000000000010"],
+			"bannedSelectors": ["0xa9059cbb"]
+		}"#).unwrap();
+		let allowed = Address::from_low_u64_be(0x10);
+
+		assert_eq!(lists.check(&call(allowed, vec![0x12, 0x34, 0x56, 0x78])), Ok(()));
+		assert_eq!(lists.check(&call(allowed, vec![0xa9, 0x05, 0x9c, 0xbb, 0])), Err(transaction::Error::SelectorBanned));
+		assert_eq!(lists.check(&call(Address::from_low_u64_be(0x11), vec![])), Err(transaction::Error::RecipientBanned));
+
+		let mut tx = Tx::default().unsigned();
+		tx.action = Action::Call(allowed);
+		let banned_sender = tx.fake_sign(Address::from_low_u64_be(2));
+		assert_eq!(lists.check(&banned_sender), Err(transaction::Error::SenderBanned));
+	}
+
+	#[test]
+	fn should_reload_changed_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("filter.json");
+		File::create(&path).unwrap().write_all(b"{}").unwrap();
+		let filter = FileFilter::new(&path).unwrap();
+		let tx = call(Address::from_low_u64_be(0x10), vec![]);
+		assert_eq!(filter.check(&tx), Ok(()));
+
+		File::create(&path).unwrap()
+			.write_all(br#"{"bannedRecipients": ["0x0000000000000000000000000000000000000010"]}"#)
+			.unwrap();
+		// make sure the change is noticed even on file systems with coarse timestamps
+		*filter.modified.write() = None;
+		assert!(filter.reload_if_changed());
+		assert_eq!(filter.check(&tx), Err(transaction::Error::RecipientBanned));
+
+		// invalid files don't affect the rules
+		File::create(&path).unwrap().write_all(b"{").unwrap();
+		*filter.modified.write() = None;
+		assert!(!filter.reload_if_changed());
+		assert_eq!(filter.check(&tx), Err(transaction::Error::RecipientBanned));
+	}
+}
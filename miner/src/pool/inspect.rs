@@ -0,0 +1,167 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool introspection.
+//!
+//! Exposes the components the pool scores transactions by, so it's possible to
+//! tell why a transaction is (or isn't) going to be included in the next block,
+//! and keeps a short history of transactions removed without being mined.
+
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+use ethereum_types::{H256, U256, Address};
+
+use super::{DropReason, Priority};
+
+/// Number of recently dropped transactions remembered by the pool.
+pub const RECENTLY_DROPPED: usize = 256;
+
+/// Status of a transaction in the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStatus {
+	/// Transaction can be included in the next block.
+	Pending,
+	/// Transaction is waiting for a nonce gap to be filled or for its conditions to be met.
+	Future,
+}
+
+/// Pooled transaction with the components of its score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PooledTransaction {
+	/// Transaction hash.
+	pub hash: H256,
+	/// Transaction sender.
+	pub sender: Address,
+	/// Transaction nonce.
+	pub nonce: U256,
+	/// Transaction gas price (fee cap for fee-market transactions).
+	pub gas_price: U256,
+	/// Fee paid to the block author on top of the base fee.
+	pub priority_fee: U256,
+	/// Time the transaction was received.
+	pub received_at: SystemTime,
+	/// Insertion order within the pool.
+	pub insertion_id: usize,
+	/// Transaction priority (locality).
+	pub priority: Priority,
+	/// Number of bits the gas price is shifted by because of the priority.
+	pub boost: usize,
+	/// Score before penalization: the gas price shifted by the priority boost.
+	pub score: U256,
+	/// Current status.
+	pub status: TransactionStatus,
+}
+
+/// Transaction removed from the pool without being mined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DroppedTransaction {
+	/// Transaction hash.
+	pub hash: H256,
+	/// Why the transaction was removed.
+	pub reason: DropReason,
+	/// Transaction which pushed this one out, if any.
+	pub by: Option<H256>,
+	/// Time the transaction was removed.
+	pub dropped_at: SystemTime,
+}
+
+/// Bounded history of dropped transactions, oldest first.
+#[derive(Debug, Clone)]
+pub struct RecentlyDropped {
+	capacity: usize,
+	entries: VecDeque<DroppedTransaction>,
+}
+
+impl Default for RecentlyDropped {
+	fn default() -> Self {
+		RecentlyDropped::new(RECENTLY_DROPPED)
+	}
+}
+
+impl RecentlyDropped {
+	/// Create new history keeping at most `capacity` entries.
+	pub fn new(capacity: usize) -> Self {
+		RecentlyDropped {
+			capacity,
+			entries: VecDeque::with_capacity(capacity),
+		}
+	}
+
+	/// Record a dropped transaction, forgetting the oldest one if full.
+	pub fn push(&mut self, hash: H256, reason: DropReason, by: Option<H256>) {
+		if self.capacity == 0 {
+			return;
+		}
+		if self.entries.len() == self.capacity {
+			self.entries.pop_front();
+		}
+		self.entries.push_back(DroppedTransaction {
+			hash,
+			reason,
+			by,
+			dropped_at: SystemTime::now(),
+		});
+	}
+
+	/// Number of recorded transactions.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Returns true if no transactions were recorded.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Recorded transactions, oldest first.
+	pub fn entries(&self) -> Vec<DroppedTransaction> {
+		self.entries.iter().cloned().collect()
+	}
+}
+
+/// Snapshot of the pool.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inspection {
+	/// Transactions currently in the pool.
+	pub transactions: Vec<PooledTransaction>,
+	/// Transactions recently removed without being mined, oldest first.
+	pub dropped: Vec<DroppedTransaction>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_forget_oldest_entries() {
+		// given
+		let mut dropped = RecentlyDropped::new(2);
+
+		// when
+		dropped.push(H256::from_low_u64_be(1), DropReason::Limit, None);
+		dropped.push(H256::from_low_u64_be(2), DropReason::Invalid, None);
+		dropped.push(H256::from_low_u64_be(3), DropReason::Replaced, Some(H256::from_low_u64_be(4)));
+
+		// then
+		let entries = dropped.entries();
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].hash, H256::from_low_u64_be(2));
+		assert_eq!(entries[0].reason, DropReason::Invalid);
+		assert_eq!(entries[1].reason, DropReason::Replaced);
+		assert_eq!(entries[1].by, Some(H256::from_low_u64_be(4)));
+	}
+}
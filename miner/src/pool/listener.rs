@@ -16,15 +16,18 @@
 
 //! Notifier for new transaction hashes.
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 
-use ethereum_types::H256;
+use ethereum_types::{H256, U256, Address};
 use futures::sync::mpsc;
 use txpool::{self, VerifiedTransaction};
 
 use pool::VerifiedTransaction as Transaction;
-use pool::TxStatus;
+use pool::{TxStatus, ScoredTransaction};
+use pool::events::{PoolEvent, PoolEventListener, DropReason};
+use pool::inspect::RecentlyDropped;
 
 /// Transaction pool logger.
 #[derive(Default, Debug)]
@@ -79,10 +82,96 @@ impl txpool::Listener<Transaction> for Logger {
 pub struct TransactionsPoolNotifier {
 	full_listeners: Vec<mpsc::UnboundedSender<Arc<Vec<(H256, TxStatus)>>>>,
 	pending_listeners: Vec<mpsc::UnboundedSender<Arc<Vec<H256>>>>,
+	event_listeners: Vec<Box<dyn PoolEventListener>>,
 	tx_statuses: Vec<(H256, TxStatus)>,
+	events: Vec<PoolEvent>,
+	/// Transactions in the pool which are not known to be ready yet, by sender and nonce.
+	unpromoted: HashMap<Address, BTreeMap<U256, H256>>,
+	/// Senders with transactions added since their readiness was last checked.
+	touched: HashSet<Address>,
+	in_chain: Option<Arc<dyn Fn(&H256) -> bool + Send + Sync>>,
+	/// Reason reported for canceled transactions, if they are not canceled by the user.
+	cancel_reason: Option<DropReason>,
+	recently_dropped: RecentlyDropped,
 }
 
 impl TransactionsPoolNotifier {
+	/// Add new listener to receive lifecycle events.
+	pub fn add_event_listener(&mut self, f: Box<dyn PoolEventListener>) {
+		self.event_listeners.push(f);
+	}
+
+	/// Returns true if there are any lifecycle events listeners.
+	pub fn has_event_listeners(&self) -> bool {
+		!self.event_listeners.is_empty()
+	}
+
+	/// Set blockchain checker used to tell mined transactions from culled ones.
+	pub fn set_in_chain_checker(&mut self, checker: Arc<dyn Fn(&H256) -> bool + Send + Sync>) {
+		self.in_chain = Some(checker);
+	}
+
+	/// Senders whose transactions might have become ready for inclusion.
+	///
+	/// Only senders with transactions added since the last call are returned, unless `all` is set
+	/// because the state changed, e.g. after a new block.
+	pub fn promotion_candidates(&mut self, all: bool) -> Vec<Address> {
+		let touched = std::mem::replace(&mut self.touched, HashSet::new());
+		match all {
+			true => self.unpromoted.keys().cloned().collect(),
+			false => touched.into_iter().filter(|sender| self.unpromoted.contains_key(sender)).collect(),
+		}
+	}
+
+	/// Note that transactions of `sender` with nonces below `next_nonce` are ready for inclusion.
+	pub fn promoted(&mut self, sender: &Address, next_nonce: U256) {
+		let ready = match self.unpromoted.get_mut(sender) {
+			Some(transactions) => {
+				let pending = transactions.split_off(&next_nonce);
+				std::mem::replace(transactions, pending)
+			},
+			None => return,
+		};
+		if self.unpromoted.get(sender).map_or(false, BTreeMap::is_empty) {
+			self.unpromoted.remove(sender);
+		}
+		self.events.extend(ready.into_iter().map(|(_, hash)| PoolEvent::Promoted { hash }));
+	}
+
+	/// Set the reason reported for transactions canceled from now on.
+	///
+	/// Used when transactions are removed by the pool itself rather than by the user.
+	pub fn set_cancel_reason(&mut self, reason: Option<DropReason>) {
+		self.cancel_reason = reason;
+	}
+
+	/// Transactions recently removed from the pool without being mined.
+	pub fn recently_dropped(&self) -> &RecentlyDropped {
+		&self.recently_dropped
+	}
+
+	fn removed(&mut self, tx: &Transaction, event: PoolEvent) {
+		let hash = tx.hash();
+		match event {
+			PoolEvent::Dropped { reason, by, .. } => self.recently_dropped.push(*hash, reason, by),
+			PoolEvent::Replaced { by, .. } => self.recently_dropped.push(*hash, DropReason::Replaced, Some(by)),
+			_ => {},
+		}
+
+		if self.has_event_listeners() {
+			let nonce = tx.signed().nonce;
+			if let Some(transactions) = self.unpromoted.get_mut(tx.sender()) {
+				if transactions.get(&nonce) == Some(hash) {
+					transactions.remove(&nonce);
+				}
+				if transactions.is_empty() {
+					self.unpromoted.remove(tx.sender());
+				}
+			}
+			self.events.push(event);
+		}
+	}
+
 	/// Add new full listener to receive notifications.
 	pub fn add_full_listener(&mut self, f: mpsc::UnboundedSender<Arc<Vec<(H256, TxStatus)>>>) {
 		self.full_listeners.push(f);
@@ -95,6 +184,15 @@ impl TransactionsPoolNotifier {
 
 	/// Notify listeners about all currently transactions.
 	pub fn notify(&mut self) {
+		if !self.events.is_empty() {
+			let events = std::mem::replace(&mut self.events, Vec::new());
+			self.event_listeners.retain(|listener| listener.on_events(&events));
+			if self.event_listeners.is_empty() {
+				self.unpromoted.clear();
+				self.touched.clear();
+			}
+		}
+
 		if self.tx_statuses.is_empty() {
 			return;
 		}
@@ -124,33 +222,67 @@ impl fmt::Debug for TransactionsPoolNotifier {
 		fmt.debug_struct("TransactionsPoolNotifier")
 			.field("full_listeners", &self.full_listeners.len())
 			.field("pending_listeners", &self.pending_listeners.len())
+			.field("event_listeners", &self.event_listeners.len())
+			.field("unpromoted", &self.unpromoted.len())
+			.field("recently_dropped", &self.recently_dropped.len())
 			.finish()
 	}
 }
 
 impl txpool::Listener<Transaction> for TransactionsPoolNotifier {
-	fn added(&mut self, tx: &Arc<Transaction>, _old: Option<&Arc<Transaction>>) {
+	fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Added));
+
+		if let Some(old) = old {
+			self.removed(old, PoolEvent::Replaced { hash: *old.hash(), by: *tx.hash() });
+		}
+
+		if self.has_event_listeners() {
+			self.events.push(PoolEvent::Added {
+				hash: *tx.hash(),
+				sender: *tx.sender(),
+				nonce: tx.signed().nonce,
+				gas_price: tx.signed().gas_price,
+				local: tx.priority().is_local(),
+			});
+			self.unpromoted.entry(*tx.sender()).or_insert_with(BTreeMap::new).insert(tx.signed().nonce, *tx.hash());
+			self.touched.insert(*tx.sender());
+		}
 	}
 
 	fn rejected<H: fmt::Debug + fmt::LowerHex>(&mut self, tx: &Arc<Transaction>, _reason: &txpool::Error<H>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Rejected));
 	}
 
-	fn dropped(&mut self, tx: &Arc<Transaction>, _new: Option<&Transaction>) {
+	fn dropped(&mut self, tx: &Arc<Transaction>, new: Option<&Transaction>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Dropped));
+		self.removed(tx, PoolEvent::Dropped {
+			hash: *tx.hash(),
+			reason: DropReason::Limit,
+			by: new.map(|new| *new.hash()),
+		});
 	}
 
 	fn invalid(&mut self, tx: &Arc<Transaction>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Invalid));
+		self.removed(tx, PoolEvent::Dropped { hash: *tx.hash(), reason: DropReason::Invalid, by: None });
 	}
 
 	fn canceled(&mut self, tx: &Arc<Transaction>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Canceled));
+		let reason = self.cancel_reason.unwrap_or(DropReason::Canceled);
+		self.removed(tx, PoolEvent::Dropped { hash: *tx.hash(), reason, by: None });
 	}
 
 	fn culled(&mut self, tx: &Arc<Transaction>) {
 		self.tx_statuses.push((tx.hash.clone(), TxStatus::Culled));
+
+		let is_in_chain = self.in_chain.as_ref().map(|checker| checker(tx.hash())).unwrap_or(false);
+		let event = match is_in_chain {
+			true => PoolEvent::Mined { hash: *tx.hash() },
+			false => PoolEvent::Dropped { hash: *tx.hash(), reason: DropReason::Culled, by: None },
+		};
+		self.removed(tx, event);
 	}
 }
 
@@ -16,7 +16,8 @@
 
 //! Local Transactions List.
 
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, time::SystemTime};
+use std::collections::HashMap;
 
 use ethereum_types::H256;
 use linked_hash_map::LinkedHashMap;
@@ -61,10 +62,30 @@ impl Status {
 	}
 }
 
+/// Submission metadata of a local transaction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Submission {
+	/// Time the transaction was first submitted to this node.
+	pub submitted_at: SystemTime,
+	/// Number of times the transaction was resubmitted to the pool, e.g. after a restart.
+	pub resubmissions: u32,
+}
+
+impl Submission {
+	/// Metadata of a transaction submitted just now.
+	pub fn now() -> Self {
+		Submission {
+			submitted_at: SystemTime::now(),
+			resubmissions: 0,
+		}
+	}
+}
+
 /// Keeps track of local transactions that are in the queue or were mined/dropped recently.
 pub struct LocalTransactionsList {
 	max_old: usize,
 	transactions: LinkedHashMap<H256, Status>,
+	submissions: HashMap<H256, Submission>,
 	pending: usize,
 	in_chain: Option<Box<dyn Fn(&H256) -> bool + Send + Sync>>,
 }
@@ -92,6 +113,7 @@ impl LocalTransactionsList {
 		LocalTransactionsList {
 			max_old,
 			transactions: Default::default(),
+			submissions: Default::default(),
 			pending: 0,
 			in_chain: None,
 		}
@@ -117,6 +139,18 @@ impl LocalTransactionsList {
 		&self.transactions
 	}
 
+	/// Return submission metadata of all currently stored transactions.
+	pub fn submissions(&self) -> &HashMap<H256, Submission> {
+		&self.submissions
+	}
+
+	/// Record submission metadata of a transaction which is about to be resubmitted.
+	///
+	/// The metadata is kept instead of a fresh one when the transaction gets imported.
+	pub fn note_resubmission(&mut self, hash: H256, submission: Submission) {
+		self.submissions.insert(hash, submission);
+	}
+
 	/// Returns true if there are pending local transactions.
 	pub fn has_pending(&self) -> bool {
 		self.pending > 0
@@ -138,6 +172,9 @@ impl LocalTransactionsList {
 		for hash in to_remove {
 			self.transactions.remove(&hash);
 		}
+
+		let transactions = &self.transactions;
+		self.submissions.retain(|hash, _| transactions.contains_key(hash));
 	}
 
 	fn insert(&mut self, hash: H256, status: Status) {
@@ -157,8 +194,10 @@ impl txpool::Listener<Transaction> for LocalTransactionsList {
 		}
 
 		debug!(target: "own_tx", "Imported to the pool (hash {:?})", tx.hash());
+		let submission = self.submissions.remove(tx.hash()).unwrap_or_else(Submission::now);
 		self.clear_old();
 		self.insert(*tx.hash(), Status::Pending(tx.clone()));
+		self.submissions.insert(*tx.hash(), submission);
 		self.pending += 1;
 
 		if let Some(old) = old {
@@ -178,6 +217,7 @@ impl txpool::Listener<Transaction> for LocalTransactionsList {
 
 		debug!(target: "own_tx", "Transaction rejected (hash {:?}). {}", tx.hash(), reason);
 		self.insert(*tx.hash(), Status::Rejected(tx.clone(), format!("{}", reason)));
+		self.submissions.entry(*tx.hash()).or_insert_with(Submission::now);
 		self.clear_old();
 	}
 
@@ -303,6 +343,35 @@ mod tests {
 		assert!(list.contains(tx3.hash()));
 	}
 
+	#[test]
+	fn should_keep_submission_of_resubmitted_transactions() {
+		// given
+		let mut list = LocalTransactionsList::new(1);
+		let tx1 = new_tx(10);
+		let tx2 = new_tx(20);
+		let submission = Submission {
+			submitted_at: SystemTime::UNIX_EPOCH,
+			resubmissions: 2,
+		};
+
+		// when
+		list.note_resubmission(*tx1.hash(), submission);
+		list.added(&tx1, None);
+		list.added(&tx2, None);
+
+		// then
+		assert_eq!(list.submissions()[tx1.hash()], submission);
+		assert_eq!(list.submissions()[tx2.hash()].resubmissions, 0);
+
+		// when
+		list.invalid(&tx1);
+		list.invalid(&tx2);
+
+		// then
+		assert!(!list.submissions().contains_key(tx1.hash()));
+		assert!(list.submissions().contains_key(tx2.hash()));
+	}
+
 	fn new_tx<T: Into<U256>>(nonce: T) -> Arc<Transaction> {
 		let keypair = Random.generate();
 		let signed = transaction::Transaction {
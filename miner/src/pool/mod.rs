@@ -16,6 +16,8 @@
 
 //! Transaction Pool
 
+use std::time::SystemTime;
+
 use ethereum_types::{U256, H256, Address};
 use parity_util_mem::MallocSizeOfExt;
 use types::transaction;
@@ -26,7 +28,12 @@ mod queue;
 mod ready;
 
 pub mod client;
+pub mod events;
+pub mod expiry;
+pub mod filter;
+pub mod inspect;
 pub mod local_transactions;
+pub mod quotas;
 pub mod replace;
 pub mod scoring;
 pub mod verifier;
@@ -35,6 +42,12 @@ pub mod verifier;
 mod tests;
 
 pub use self::queue::{TransactionQueue, Status as QueueStatus};
+pub use self::scoring::ReplacementPolicy;
+pub use self::quotas::{Quotas, QuotaStats};
+pub use self::events::{PoolEvent, PoolEventListener, DropReason};
+pub use self::expiry::{Expiry, TransactionAge};
+pub use self::filter::{TransactionFilter, FilterLists, FileFilter};
+pub use self::inspect::{Inspection, PooledTransaction, DroppedTransaction, TransactionStatus};
 pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options};
 
 /// How to prioritize transactions in the pool
@@ -117,6 +130,20 @@ pub trait ScoredTransaction {
 	/// Gets transaction gas price.
 	fn gas_price(&self) -> &U256;
 
+	/// Gets maximal fee per gas the transaction is willing to pay.
+	///
+	/// `None` for legacy transactions, which pay their gas price.
+	fn max_fee_per_gas(&self) -> Option<&U256> {
+		None
+	}
+
+	/// Gets maximal fee per gas paid to the block author on top of the base fee.
+	///
+	/// `None` for legacy transactions, which pay their gas price.
+	fn max_priority_fee_per_gas(&self) -> Option<&U256> {
+		None
+	}
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256;
 }
@@ -130,6 +157,7 @@ pub struct VerifiedTransaction {
 	sender: Address,
 	priority: Priority,
 	insertion_id: usize,
+	received_at: SystemTime,
 }
 
 impl VerifiedTransaction {
@@ -147,6 +175,7 @@ impl VerifiedTransaction {
 			sender,
 			priority: Priority::Retracted,
 			insertion_id: 0,
+			received_at: SystemTime::now(),
 		}
 	}
 
@@ -155,6 +184,16 @@ impl VerifiedTransaction {
 		self.insertion_id
 	}
 
+	/// Gets the time when transaction was verified for inclusion in the pool.
+	pub fn received_at(&self) -> SystemTime {
+		self.received_at
+	}
+
+	/// Returns true if the transaction is local.
+	pub fn is_local(&self) -> bool {
+		self.priority.is_local()
+	}
+
 	/// Gets wrapped `SignedTransaction`
 	pub fn signed(&self) -> &transaction::SignedTransaction {
 		&self.transaction
@@ -19,7 +19,8 @@
 use std::{cmp, fmt};
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::SystemTime;
 
 use ethereum_types::{H256, U256, Address};
 use futures::sync::mpsc;
@@ -29,8 +30,11 @@ use types::transaction;
 
 use pool::{
 	self, replace, scoring, verifier, client, ready, listener,
-	PrioritizationStrategy, PendingOrdering, PendingSettings, TxStatus
+	PrioritizationStrategy, PendingOrdering, PendingSettings, ReplacementPolicy, TxStatus,
+	DropReason, Expiry, TransactionAge, TransactionFilter, ScoredTransaction,
+	Inspection, PooledTransaction, TransactionStatus,
 };
+use pool::quotas::{Quotas, QuotaMetrics, QuotaStats, Occupancy, SenderOccupancy};
 use pool::local_transactions::LocalTransactionsList;
 
 type Listener = (LocalTransactionsList, (listener::TransactionsPoolNotifier, listener::Logger));
@@ -61,6 +65,8 @@ pub struct Status {
 	pub status: txpool::LightStatus,
 	/// Current limits of the transaction pool.
 	pub limits: txpool::Options,
+	/// Transactions rejected or evicted because of per-sender and future quotas.
+	pub quotas: QuotaStats,
 }
 
 impl fmt::Display for Status {
@@ -75,7 +81,18 @@ impl fmt::Display for Status {
 			mem_max = self.limits.max_mem_usage / 1024,
 			gp = self.options.minimal_gas_price / 1_000_000,
 			max_gas = cmp::min(self.options.block_gas_limit, self.options.tx_gas_limit),
-		)
+		)?;
+		if self.quotas.total() > 0 {
+			writeln!(
+				fmt,
+				"Quotas: rejected {pending} pending/sender, {future_sender} future/sender, {future} future; evicted {evicted} future",
+				pending = self.quotas.pending_per_sender_rejected,
+				future_sender = self.quotas.future_per_sender_rejected,
+				future = self.quotas.future_rejected,
+				evicted = self.quotas.future_evicted,
+			)?;
+		}
+		Ok(())
 	}
 }
 
@@ -204,6 +221,12 @@ pub struct TransactionQueue {
 	options: RwLock<verifier::Options>,
 	cached_pending: RwLock<CachedPending>,
 	recently_rejected: RecentlyRejected,
+	quotas: RwLock<Quotas>,
+	quota_metrics: QuotaMetrics,
+	/// State nonces of senders, used to compute their quota occupancy. Cleared whenever the pool is culled.
+	sender_nonces: RwLock<HashMap<Address, U256>>,
+	expiry: RwLock<Expiry>,
+	filter: RwLock<Option<Arc<dyn TransactionFilter>>>,
 }
 
 impl TransactionQueue {
@@ -212,17 +235,59 @@ impl TransactionQueue {
 		limits: txpool::Options,
 		verification_options: verifier::Options,
 		strategy: PrioritizationStrategy,
+	) -> Self {
+		Self::with_replacement_policy(limits, verification_options, strategy, Default::default())
+	}
+
+	/// Create new queue with given pool limits, initial verification options
+	/// and rules for replacing transactions with the same sender and nonce.
+	pub fn with_replacement_policy(
+		limits: txpool::Options,
+		verification_options: verifier::Options,
+		strategy: PrioritizationStrategy,
+		replacement: ReplacementPolicy,
 	) -> Self {
 		let max_count = limits.max_count;
+		let scoring = scoring::NonceAndGasPrice::new(strategy).with_replacement_policy(replacement);
 		TransactionQueue {
 			insertion_id: Default::default(),
-			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice(strategy), limits)),
+			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring, limits)),
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
+			quotas: Default::default(),
+			quota_metrics: Default::default(),
+			sender_nonces: Default::default(),
+			expiry: Default::default(),
+			filter: RwLock::new(None),
 		}
 	}
 
+	/// Update per-sender and future transactions quotas.
+	pub fn set_quotas(&self, quotas: Quotas) {
+		*self.quotas.write() = quotas;
+	}
+
+	/// Update time-to-live of transactions in the pool.
+	pub fn set_expiry(&self, expiry: Expiry) {
+		*self.expiry.write() = expiry;
+	}
+
+	/// Returns time-to-live of transactions in the pool.
+	pub fn expiry(&self) -> Expiry {
+		*self.expiry.read()
+	}
+
+	/// Set the filter applied to all transactions entering the pool.
+	pub fn set_filter(&self, filter: Option<Arc<dyn TransactionFilter>>) {
+		*self.filter.write() = filter;
+	}
+
+	/// Returns the filter applied to transactions entering the pool.
+	pub fn filter(&self) -> Option<Arc<dyn TransactionFilter>> {
+		self.filter.read().clone()
+	}
+
 	/// Update verification options
 	///
 	/// Some parameters of verification may vary in time (like block gas limit or minimal gas price).
@@ -234,7 +299,11 @@ impl TransactionQueue {
 	pub fn set_in_chain_checker<F>(&self, f: F) where
 		F: Fn(&H256) -> bool + Send + Sync + 'static
 	{
-		self.pool.write().listener_mut().0.set_in_chain_checker(f)
+		let checker = Arc::new(f);
+		let local_checker = checker.clone();
+		let mut pool = self.pool.write();
+		pool.listener_mut().0.set_in_chain_checker(move |hash: &H256| local_checker(hash));
+		(pool.listener_mut().1).0.set_in_chain_checker(checker);
 	}
 
 	/// Import a set of transactions to the pool.
@@ -270,7 +339,16 @@ impl TransactionQueue {
 			transaction_to_replace,
 		);
 
-		let mut replace = replace::ReplaceByScoreAndReadiness::new(self.pool.read().scoring().clone(), client);
+		let mut replace = replace::ReplaceByScoreAndReadiness::new(self.pool.read().scoring().clone(), client.clone());
+
+		let filter = self.filter();
+		let quotas = self.quotas.read().clone();
+		let mut occupancy = if quotas.is_unlimited() {
+			None
+		} else {
+			// The global budget requires knowing about future transactions of all senders.
+			Some(self.occupancy(&client, quotas.max_future.is_some()))
+		};
 
 		let results = transactions
 			.into_iter()
@@ -288,8 +366,13 @@ impl TransactionQueue {
 
 				let imported = verifier
 					.verify_transaction(transaction)
-					.and_then(|verified| {
-						self.pool.write().import(verified, &mut replace).map_err(convert_error)
+					.and_then(|verified| match filter {
+						Some(ref filter) => filter.check(verified.signed()).map(|_| verified),
+						None => Ok(verified),
+					})
+					.and_then(|verified| match occupancy {
+						Some(ref mut occupancy) => self.import_with_quotas(&client, &quotas, occupancy, verified, &mut replace),
+						None => self.pool.write().import(verified, &mut replace).map(|_| ()).map_err(convert_error),
 					});
 
 				match imported {
@@ -303,6 +386,7 @@ impl TransactionQueue {
 			.collect::<Vec<_>>();
 
 		// Notify about imported transactions.
+		self.promote(client, false);
 		(self.pool.write().listener_mut().1).0.notify();
 
 		if results.iter().any(|r| r.is_ok()) {
@@ -312,6 +396,86 @@ impl TransactionQueue {
 		results
 	}
 
+	/// Imports a verified transaction, making sure it doesn't exceed the quotas.
+	fn import_with_quotas<C: client::NonceClient + Clone>(
+		&self,
+		client: &C,
+		quotas: &Quotas,
+		occupancy: &mut Occupancy,
+		verified: pool::VerifiedTransaction,
+		replace: &mut replace::ReplaceByScoreAndReadiness<scoring::NonceAndGasPrice, C>,
+	) -> Result<(), transaction::Error> {
+		let sender = verified.sender;
+		let nonce = verified.signed().nonce;
+		let hash = verified.hash;
+		let is_local = verified.priority.is_local();
+
+		if !occupancy.contains(&sender) {
+			occupancy.add(sender, self.sender_occupancy(client, &sender));
+		}
+
+		let to_evict = if is_local {
+			None
+		} else {
+			match occupancy.check(quotas, &sender, nonce) {
+				Ok(to_evict) => to_evict,
+				Err(exceeded) => {
+					debug!(target: "txqueue", "[{:?}] Rejected: {:?} quota of {:?} exceeded", hash, exceeded, sender);
+					self.quota_metrics.rejected(exceeded);
+					return Err(transaction::Error::LimitReached);
+				},
+			}
+		};
+
+		self.pool.write().import(verified, replace).map_err(convert_error)?;
+
+		// Only make room once the new transaction is actually in the pool.
+		if let Some((evicted_sender, evicted)) = to_evict {
+			debug!(target: "txqueue", "[{:?}] Evicting future transaction of {:?} to make room for {:?}", evicted, evicted_sender, hash);
+			{
+				let mut pool = self.pool.write();
+				(pool.listener_mut().1).0.set_cancel_reason(Some(DropReason::Limit));
+				pool.remove(&evicted, false);
+				(pool.listener_mut().1).0.set_cancel_reason(None);
+			}
+			occupancy.evict(&evicted_sender, &evicted);
+			self.quota_metrics.evicted();
+		}
+		occupancy.insert(sender, nonce, hash, is_local);
+		Ok(())
+	}
+
+	/// Returns current occupancy of the pool, either for all senders or to be filled lazily.
+	fn occupancy<C: client::NonceClient>(&self, client: &C, all_senders: bool) -> Occupancy {
+		let mut occupancy = Occupancy::default();
+		if all_senders {
+			let senders: Vec<_> = self.pool.read().senders().cloned().collect();
+			for sender in senders {
+				let sender_occupancy = self.sender_occupancy(client, &sender);
+				occupancy.add(sender, sender_occupancy);
+			}
+		}
+		occupancy
+	}
+
+	/// Returns pending and future transactions of given sender.
+	fn sender_occupancy<C: client::NonceClient>(&self, client: &C, sender: &Address) -> SenderOccupancy {
+		let all = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
+		let transactions = self.pool.read()
+			.pending_from_sender(all, sender)
+			.map(|tx| (tx.signed().nonce, tx.hash, tx.priority.is_local()))
+			.collect::<Vec<_>>();
+
+		let state_nonce = self.sender_nonces.read().get(sender).cloned();
+		let state_nonce = state_nonce.unwrap_or_else(|| {
+			let nonce = client.account_nonce(sender);
+			self.sender_nonces.write().insert(*sender, nonce);
+			nonce
+		});
+
+		SenderOccupancy::new(state_nonce, transactions)
+	}
+
 	/// Returns all transactions in the queue without explicit ordering.
 	pub fn all_transactions(&self) -> Vec<Arc<pool::VerifiedTransaction>> {
 		let ready = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
@@ -446,8 +610,10 @@ impl TransactionQueue {
 		};
 
 		self.recently_rejected.clear();
+		// the state nonces may have changed since the last block
+		self.sender_nonces.write().clear();
 
-		let mut removed = 0;
+		let mut removed = self.remove_expired();
 		let senders: Vec<_> = {
 			let pool = self.pool.read();
 			let senders = pool.senders().cloned().collect();
@@ -459,6 +625,113 @@ impl TransactionQueue {
 			removed += self.pool.write().cull(Some(chunk), state_readiness);
 		}
 		debug!(target: "txqueue", "Removed {} stalled transactions. {}", removed, self.status());
+
+		// the state nonces changed, so any sender's transactions may have become ready
+		self.promote(client, true);
+		(self.pool.write().listener_mut().1).0.notify();
+	}
+
+	/// Removes transactions which outlived their time-to-live.
+	///
+	/// Returns the number of removed transactions.
+	pub fn remove_expired(&self) -> usize {
+		let expiry = self.expiry();
+		if expiry.is_disabled() {
+			return 0;
+		}
+
+		let now = SystemTime::now();
+		let expired = self.all_transactions()
+			.into_iter()
+			.filter(|tx| expiry.is_expired(tx, now))
+			.map(|tx| tx.hash)
+			.collect::<Vec<_>>();
+		if expired.is_empty() {
+			return 0;
+		}
+
+		let removed = {
+			let mut pool = self.pool.write();
+			(pool.listener_mut().1).0.set_cancel_reason(Some(DropReason::Expired));
+			let removed = expired.iter().filter_map(|hash| pool.remove(hash, false)).count();
+			(pool.listener_mut().1).0.set_cancel_reason(None);
+			(pool.listener_mut().1).0.notify();
+			removed
+		};
+
+		debug!(target: "txqueue", "Removed {} expired transactions.", removed);
+		self.cached_pending.write().clear();
+		removed
+	}
+
+	/// Returns the age of a transaction in the pool and when it is going to expire.
+	pub fn transaction_age(&self, hash: &H256) -> Option<TransactionAge> {
+		self.find(hash).map(|tx| self.expiry().age(&tx, SystemTime::now()))
+	}
+
+	/// Returns a snapshot of the pool with score components of every transaction
+	/// and the transactions recently removed without being mined.
+	pub fn inspect<C>(
+		&self,
+		client: C,
+		block_number: u64,
+		current_timestamp: u64,
+	) -> Inspection where
+		C: client::NonceClient,
+	{
+		let pool = self.pool.read();
+		let ready = Self::ready(client, block_number, current_timestamp, None);
+		let pending = pool.unordered_pending(ready).map(|tx| tx.hash).collect::<HashSet<_>>();
+		let all = |_tx: &pool::VerifiedTransaction| txpool::Readiness::Ready;
+		let transactions = pool.unordered_pending(all).map(|tx| {
+			let boost = scoring::priority_boost(tx.priority);
+			PooledTransaction {
+				hash: tx.hash,
+				sender: tx.sender,
+				nonce: tx.signed().nonce,
+				gas_price: tx.signed().gas_price,
+				priority_fee: *tx.max_priority_fee_per_gas().unwrap_or(&tx.signed().gas_price),
+				received_at: tx.received_at,
+				insertion_id: tx.insertion_id,
+				priority: tx.priority,
+				boost,
+				score: tx.signed().gas_price << boost,
+				status: if pending.contains(&tx.hash) { TransactionStatus::Pending } else { TransactionStatus::Future },
+			}
+		}).collect();
+
+		Inspection {
+			transactions,
+			dropped: (pool.listener().1).0.recently_dropped().entries(),
+		}
+	}
+
+	/// Emit promotion events for transactions which became ready since they were added.
+	///
+	/// Only senders with newly added transactions are checked, unless `state_changed` is set.
+	fn promote<C: client::NonceClient + Clone>(&self, client: C, state_changed: bool) {
+		let senders = {
+			let mut pool = self.pool.write();
+			let notifier = &mut (pool.listener_mut().1).0;
+			if !notifier.has_event_listeners() {
+				return;
+			}
+			notifier.promotion_candidates(state_changed)
+		};
+
+		let next_nonces: Vec<_> = senders
+			.into_iter()
+			.filter_map(|sender| self.next_nonce(client.clone(), &sender).map(|nonce| (sender, nonce)))
+			.collect();
+		if next_nonces.is_empty() {
+			return;
+		}
+
+		let mut pool = self.pool.write();
+		let notifier = &mut (pool.listener_mut().1).0;
+		for (sender, next_nonce) in next_nonces {
+			notifier.promoted(&sender, next_nonce);
+		}
 	}
 
 	/// Returns next valid nonce for given sender
@@ -505,10 +778,12 @@ impl TransactionQueue {
 		let results = {
 			let mut pool = self.pool.write();
 
-			hashes
+			let results = hashes
 				.into_iter()
 				.map(|hash| pool.remove(hash, is_invalid))
-				.collect::<Vec<_>>()
+				.collect::<Vec<_>>();
+			(pool.listener_mut().1).0.notify();
+			results
 		};
 
 		if results.iter().any(Option::is_some) {
@@ -550,6 +825,7 @@ impl TransactionQueue {
 			options,
 			status,
 			limits,
+			quotas: self.quota_metrics.stats(),
 		}
 	}
 
@@ -569,6 +845,22 @@ impl TransactionQueue {
 		self.pool.read().listener().0.all_transactions().iter().map(|(a, b)| (*a, b.clone())).collect()
 	}
 
+	/// Returns submission metadata of recently seen local transactions.
+	pub fn local_submissions(&self) -> BTreeMap<H256, pool::local_transactions::Submission> {
+		self.pool.read().listener().0.submissions().iter().map(|(a, b)| (*a, *b)).collect()
+	}
+
+	/// Record submission metadata of a local transaction which is about to be resubmitted.
+	pub fn note_local_resubmission(&self, hash: H256, submission: pool::local_transactions::Submission) {
+		self.pool.write().listener_mut().0.note_resubmission(hash, submission)
+	}
+
+	/// Add a listener to be notified about lifecycle events of transactions in the pool.
+	pub fn add_event_listener(&self, f: Box<dyn pool::PoolEventListener>) {
+		let mut pool = self.pool.write();
+		(pool.listener_mut().1).0.add_event_listener(f);
+	}
+
 	/// Add a listener to be notified about all transactions the pool
 	pub fn add_pending_listener(&self, f: mpsc::UnboundedSender<Arc<Vec<H256>>>) {
 		let mut pool = self.pool.write();
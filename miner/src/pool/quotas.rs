@@ -0,0 +1,341 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-sender and global quotas for pending and future transactions.
+//!
+//! A transaction is considered pending if its nonce belongs to the contiguous
+//! sequence of nonces starting at the sender's state nonce, and future
+//! (nonce-gapped) otherwise.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ethereum_types::{H256, U256, Address};
+
+/// Limits on the number of pending and future transactions in the pool.
+///
+/// Local transactions are exempt from quotas.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Quotas {
+	/// Maximal number of pending transactions from a single sender.
+	pub max_pending_per_sender: Option<usize>,
+	/// Maximal number of future transactions from a single sender.
+	pub max_future_per_sender: Option<usize>,
+	/// Maximal number of future transactions in the whole pool.
+	pub max_future: Option<usize>,
+}
+
+impl Quotas {
+	/// Returns true if none of the quotas is set.
+	pub fn is_unlimited(&self) -> bool {
+		self.max_pending_per_sender.is_none()
+			&& self.max_future_per_sender.is_none()
+			&& self.max_future.is_none()
+	}
+}
+
+/// Number of transactions rejected or evicted because of quotas.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct QuotaStats {
+	/// Transactions rejected because the sender had too many pending transactions.
+	pub pending_per_sender_rejected: usize,
+	/// Transactions rejected because the sender had too many future transactions.
+	pub future_per_sender_rejected: usize,
+	/// Future transactions rejected because the global future budget was exhausted.
+	pub future_rejected: usize,
+	/// Future transactions evicted to make room for future transactions of other senders.
+	pub future_evicted: usize,
+}
+
+impl QuotaStats {
+	/// Total number of transactions rejected or evicted because of quotas.
+	pub fn total(&self) -> usize {
+		self.pending_per_sender_rejected
+			+ self.future_per_sender_rejected
+			+ self.future_rejected
+			+ self.future_evicted
+	}
+}
+
+/// Quota that would be exceeded by importing a transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exceeded {
+	/// Too many pending transactions from the sender.
+	PendingPerSender,
+	/// Too many future transactions from the sender.
+	FuturePerSender,
+	/// Too many future transactions in the pool.
+	Future,
+}
+
+/// Counters backing `QuotaStats`.
+#[derive(Debug, Default)]
+pub struct QuotaMetrics {
+	pending_per_sender_rejected: AtomicUsize,
+	future_per_sender_rejected: AtomicUsize,
+	future_rejected: AtomicUsize,
+	future_evicted: AtomicUsize,
+}
+
+impl QuotaMetrics {
+	/// Records a transaction rejected because of given quota.
+	pub fn rejected(&self, exceeded: Exceeded) {
+		let counter = match exceeded {
+			Exceeded::PendingPerSender => &self.pending_per_sender_rejected,
+			Exceeded::FuturePerSender => &self.future_per_sender_rejected,
+			Exceeded::Future => &self.future_rejected,
+		};
+		counter.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Records a future transaction evicted from the pool.
+	pub fn evicted(&self) {
+		self.future_evicted.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Returns a snapshot of the counters.
+	pub fn stats(&self) -> QuotaStats {
+		QuotaStats {
+			pending_per_sender_rejected: self.pending_per_sender_rejected.load(Ordering::Relaxed),
+			future_per_sender_rejected: self.future_per_sender_rejected.load(Ordering::Relaxed),
+			future_rejected: self.future_rejected.load(Ordering::Relaxed),
+			future_evicted: self.future_evicted.load(Ordering::Relaxed),
+		}
+	}
+}
+
+/// Transactions of a single sender currently in the pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SenderOccupancy {
+	/// Nonce following the last pending transaction.
+	next_nonce: U256,
+	/// Number of pending transactions.
+	pending: usize,
+	/// Future transactions by nonce: hash and whether the transaction is local.
+	future: BTreeMap<U256, (H256, bool)>,
+}
+
+impl SenderOccupancy {
+	/// Creates occupancy given the sender's state nonce and its transactions in the pool
+	/// as `(nonce, hash, is_local)` tuples.
+	pub fn new<I: IntoIterator<Item = (U256, H256, bool)>>(state_nonce: U256, transactions: I) -> Self {
+		let mut occupancy = SenderOccupancy {
+			next_nonce: state_nonce,
+			pending: 0,
+			future: transactions
+				.into_iter()
+				.filter(|&(nonce, _, _)| nonce >= state_nonce)
+				.map(|(nonce, hash, is_local)| (nonce, (hash, is_local)))
+				.collect(),
+		};
+		occupancy.promote();
+		occupancy
+	}
+
+	/// Moves future transactions which are no longer gapped to pending.
+	fn promote(&mut self) {
+		while self.future.remove(&self.next_nonce).is_some() {
+			self.pending += 1;
+			self.next_nonce = self.next_nonce.saturating_add(U256::from(1));
+		}
+	}
+
+	/// Records a transaction imported to the pool.
+	///
+	/// Returns the change of the number of future transactions.
+	fn insert(&mut self, nonce: U256, hash: H256, is_local: bool) -> isize {
+		let future = self.future.len() as isize;
+		if nonce < self.next_nonce {
+			// replacement of a pending transaction
+		} else if nonce == self.next_nonce {
+			self.pending += 1;
+			self.next_nonce = self.next_nonce.saturating_add(U256::from(1));
+			self.promote();
+		} else {
+			self.future.insert(nonce, (hash, is_local));
+		}
+		self.future.len() as isize - future
+	}
+
+	/// Returns the future transaction which should be evicted first.
+	fn worst_future(&self) -> Option<(U256, H256, bool)> {
+		self.future.iter().next_back().map(|(nonce, &(hash, is_local))| (*nonce, hash, is_local))
+	}
+}
+
+/// Pool occupancy used to enforce quotas while importing a batch of transactions.
+#[derive(Debug, Default)]
+pub struct Occupancy {
+	senders: HashMap<Address, SenderOccupancy>,
+	future: usize,
+}
+
+impl Occupancy {
+	/// Returns true if the occupancy of given sender is already known.
+	pub fn contains(&self, sender: &Address) -> bool {
+		self.senders.contains_key(sender)
+	}
+
+	/// Adds the occupancy of given sender.
+	pub fn add(&mut self, sender: Address, occupancy: SenderOccupancy) {
+		self.future += occupancy.future.len();
+		if let Some(previous) = self.senders.insert(sender, occupancy) {
+			self.future -= previous.future.len();
+		}
+	}
+
+	/// Number of future transactions in the pool.
+	pub fn future(&self) -> usize {
+		self.future
+	}
+
+	/// Checks if a transaction from given sender with given nonce can be imported.
+	///
+	/// The occupancy of the sender has to be added first.
+	/// Returns a future transaction which has to be evicted from the pool
+	/// to make room for the new one, if any.
+	pub fn check(&self, quotas: &Quotas, sender: &Address, nonce: U256) -> Result<Option<(Address, H256)>, Exceeded> {
+		let occupancy = match self.senders.get(sender) {
+			Some(occupancy) => occupancy,
+			None => return Ok(None),
+		};
+
+		if nonce < occupancy.next_nonce {
+			return Ok(None);
+		}
+
+		if nonce == occupancy.next_nonce {
+			return match quotas.max_pending_per_sender {
+				Some(max) if occupancy.pending >= max => Err(Exceeded::PendingPerSender),
+				_ => Ok(None),
+			};
+		}
+
+		if occupancy.future.contains_key(&nonce) {
+			return Ok(None);
+		}
+
+		if let Some(max) = quotas.max_future_per_sender {
+			if occupancy.future.len() >= max {
+				return Err(Exceeded::FuturePerSender);
+			}
+		}
+
+		match quotas.max_future {
+			Some(max) if self.future >= max => {
+				// Make room by evicting from the sender which holds the most future transactions.
+				let worst = self.senders
+					.iter()
+					.filter(|&(address, other)| address != sender && other.future.len() > occupancy.future.len())
+					.max_by_key(|&(_, other)| other.future.len())
+					.and_then(|(address, other)| other.worst_future().map(|worst| (*address, worst)));
+
+				match worst {
+					Some((address, (_, hash, false))) => Ok(Some((address, hash))),
+					_ => Err(Exceeded::Future),
+				}
+			},
+			_ => Ok(None),
+		}
+	}
+
+	/// Records a transaction imported to the pool.
+	pub fn insert(&mut self, sender: Address, nonce: U256, hash: H256, is_local: bool) {
+		if let Some(occupancy) = self.senders.get_mut(&sender) {
+			let change = occupancy.insert(nonce, hash, is_local);
+			self.future = (self.future as isize + change) as usize;
+		}
+	}
+
+	/// Records a future transaction evicted from the pool.
+	pub fn evict(&mut self, sender: &Address, hash: &H256) {
+		if let Some(occupancy) = self.senders.get_mut(sender) {
+			let nonce = occupancy.future.iter().find(|&(_, &(h, _))| h == *hash).map(|(nonce, _)| *nonce);
+			if let Some(nonce) = nonce {
+				occupancy.future.remove(&nonce);
+				self.future -= 1;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sender(nonces: &[u64]) -> SenderOccupancy {
+		SenderOccupancy::new(
+			U256::from(10),
+			nonces.iter().map(|n| (U256::from(*n), H256::from_low_u64_be(*n), false)),
+		)
+	}
+
+	#[test]
+	fn should_split_pending_and_future() {
+		let occupancy = sender(&[9, 10, 11, 13, 14]);
+		assert_eq!(occupancy.pending, 2);
+		assert_eq!(occupancy.next_nonce, 12.into());
+		assert_eq!(occupancy.future.len(), 2);
+	}
+
+	#[test]
+	fn should_enforce_per_sender_quotas() {
+		let quotas = Quotas {
+			max_pending_per_sender: Some(2),
+			max_future_per_sender: Some(2),
+			max_future: None,
+		};
+		let a = Address::from_low_u64_be(1);
+		let mut occupancy = Occupancy::default();
+		occupancy.add(a, sender(&[10, 11, 13, 14]));
+
+		// replacements are always fine
+		assert_eq!(occupancy.check(&quotas, &a, 10.into()), Ok(None));
+		assert_eq!(occupancy.check(&quotas, &a, 14.into()), Ok(None));
+		assert_eq!(occupancy.check(&quotas, &a, 12.into()), Err(Exceeded::PendingPerSender));
+		assert_eq!(occupancy.check(&quotas, &a, 15.into()), Err(Exceeded::FuturePerSender));
+	}
+
+	#[test]
+	fn should_evict_from_the_biggest_future_holder() {
+		let quotas = Quotas {
+			max_pending_per_sender: None,
+			max_future_per_sender: None,
+			max_future: Some(3),
+		};
+		let a = Address::from_low_u64_be(1);
+		let b = Address::from_low_u64_be(2);
+		let mut occupancy = Occupancy::default();
+		occupancy.add(a, sender(&[12, 13, 14]));
+		occupancy.add(b, sender(&[10]));
+		assert_eq!(occupancy.future(), 3);
+
+		// pending transactions do not count towards the budget
+		assert_eq!(occupancy.check(&quotas, &b, 11.into()), Ok(None));
+		// the spammy sender can't push out anyone
+		assert_eq!(occupancy.check(&quotas, &a, 15.into()), Err(Exceeded::Future));
+		// but others evict its worst transaction
+		assert_eq!(occupancy.check(&quotas, &b, 12.into()), Ok(Some((a, H256::from_low_u64_be(14)))));
+
+		occupancy.evict(&a, &H256::from_low_u64_be(14));
+		occupancy.insert(b, 12.into(), H256::zero(), false);
+		assert_eq!(occupancy.future(), 3);
+
+		// filling the gap moves future transactions to pending
+		occupancy.insert(b, 11.into(), H256::zero(), false);
+		assert_eq!(occupancy.future(), 2);
+	}
+}
@@ -143,7 +143,7 @@ mod tests {
 
 	#[test]
 	fn should_always_accept_local_transactions_unless_same_sender_and_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -196,7 +196,7 @@ mod tests {
 
 	#[test]
 	fn should_replace_same_sender_by_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -239,7 +239,7 @@ mod tests {
 	#[test]
 	fn should_replace_different_sender_by_priority_and_gas_price() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(0);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -292,7 +292,7 @@ mod tests {
 
 	#[test]
 	fn should_not_replace_ready_transaction_with_future_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -318,7 +318,7 @@ mod tests {
 
 	#[test]
 	fn should_compute_readiness_with_pooled_transactions_from_the_same_sender_as_the_existing_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -373,7 +373,7 @@ mod tests {
 
 	#[test]
 	fn should_compute_readiness_with_pooled_transactions_from_the_same_sender_as_the_new_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -429,7 +429,7 @@ mod tests {
 
 	#[test]
 	fn should_accept_local_tx_with_same_sender_and_nonce_with_better_gas_price() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
@@ -471,7 +471,7 @@ mod tests {
 
 	#[test]
 	fn should_reject_local_tx_with_same_sender_and_nonce_with_worse_gas_price() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
 		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
 
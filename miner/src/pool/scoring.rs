@@ -21,26 +21,67 @@
 //! and with the same `nonce` only one of them can be included.
 //! We choose the one with higher gas price, but also require that gas price increment
 //! is high enough to prevent attacking miners by requiring them to reshuffle/reexecute
-//! the queue too often.
+//! the queue too often. The required increment is set by the `ReplacementPolicy`.
 //!
 //! Transactions between senders are prioritized using `gas price`. Higher `gas price`
 //! yields more profits for miners. Additionally we prioritize transactions that originate
 //! from our local node (own transactions).
 
 use std::cmp;
+use std::convert::TryFrom;
 
-use ethereum_types::U256;
+use ethereum_types::{U256, U512};
 use txpool::{self, scoring};
-use super::{verifier, PrioritizationStrategy, VerifiedTransaction, ScoredTransaction};
+use super::{verifier, PrioritizationStrategy, Priority, VerifiedTransaction, ScoredTransaction};
 
-/// Transaction with the same (sender, nonce) can be replaced only if
-/// `new_gas_price >= old_gas_price + old_gas_price >> SHIFT`
-const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
+/// Default price bump required to replace a transaction, in basis points (12.5%).
+pub const DEFAULT_PRICE_BUMP: u32 = 1_250;
 
-/// Calculate minimal gas price requirement.
-#[inline]
-fn bump_gas_price(old_gp: U256) -> U256 {
-	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
+/// Number of bits the gas price of a transaction is shifted by when computing its score.
+pub fn priority_boost(priority: Priority) -> usize {
+	match priority {
+		Priority::Local => 15,
+		Priority::Retracted => 10,
+		Priority::Regular => 0,
+	}
+}
+
+/// Rules a transaction has to satisfy to replace one with the same (sender, nonce).
+///
+/// A legacy transaction has to raise the gas price of a legacy transaction by at least `price_bump`.
+/// As soon as a fee-market transaction is involved, both the fee cap and the priority fee have to be
+/// raised by that much, a legacy transaction paying its gas price for both.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ReplacementPolicy {
+	/// Minimal price increment in basis points of the replaced transaction's price.
+	pub price_bump: u32,
+}
+
+impl Default for ReplacementPolicy {
+	fn default() -> Self {
+		ReplacementPolicy {
+			price_bump: DEFAULT_PRICE_BUMP,
+		}
+	}
+}
+
+impl ReplacementPolicy {
+	/// Calculate minimal price required to replace a transaction paying `old`.
+	pub fn bump(&self, old: &U256) -> U256 {
+		let increment = old.full_mul(self.price_bump.into()) / U512::from(10_000);
+		old.saturating_add(U256::try_from(increment).unwrap_or_else(|_| U256::max_value()))
+	}
+
+	/// Returns true if `new` pays enough to replace `old`.
+	pub fn should_replace<P: ScoredTransaction>(&self, old: &P, new: &P) -> bool {
+		if old.max_fee_per_gas().is_none() && new.max_fee_per_gas().is_none() {
+			return new.gas_price() >= &self.bump(old.gas_price());
+		}
+
+		let fee_cap = |tx: &P| *tx.max_fee_per_gas().unwrap_or_else(|| tx.gas_price());
+		let priority_fee = |tx: &P| *tx.max_priority_fee_per_gas().unwrap_or_else(|| tx.gas_price());
+		fee_cap(new) >= self.bump(&fee_cap(old)) && priority_fee(new) >= self.bump(&priority_fee(old))
+	}
 }
 
 /// Simple, gas-price based scoring for transactions.
@@ -48,9 +89,36 @@ fn bump_gas_price(old_gp: U256) -> U256 {
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
 /// We might want to store penalization status in some persistent state.
 #[derive(Debug, Clone)]
-pub struct NonceAndGasPrice(pub PrioritizationStrategy);
+pub struct NonceAndGasPrice {
+	strategy: PrioritizationStrategy,
+	replacement: ReplacementPolicy,
+}
 
 impl NonceAndGasPrice {
+	/// Create new scoring with given prioritization strategy and default replacement rules.
+	pub fn new(strategy: PrioritizationStrategy) -> Self {
+		NonceAndGasPrice {
+			strategy,
+			replacement: Default::default(),
+		}
+	}
+
+	/// Use given rules to decide on replacing transactions with the same (sender, nonce).
+	pub fn with_replacement_policy(mut self, replacement: ReplacementPolicy) -> Self {
+		self.replacement = replacement;
+		self
+	}
+
+	/// Prioritization strategy of this scoring.
+	pub fn strategy(&self) -> PrioritizationStrategy {
+		self.strategy
+	}
+
+	/// Replacement rules of this scoring.
+	pub fn replacement_policy(&self) -> ReplacementPolicy {
+		self.replacement
+	}
+
 	/// Decide if the transaction should even be considered into the pool (if the pool is full).
 	///
 	/// Used by Verifier to quickly reject transactions that don't have any chance to get into the pool later on,
@@ -80,14 +148,10 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 			return scoring::Choice::InsertNew
 		}
 
-		let old_gp = old.gas_price();
-		let new_gp = new.gas_price();
-
-		let min_required_gp = bump_gas_price(*old_gp);
-
-		match min_required_gp.cmp(&new_gp) {
-			cmp::Ordering::Greater => scoring::Choice::RejectNew,
-			_ => scoring::Choice::ReplaceOld,
+		if self.replacement.should_replace(old, new) {
+			scoring::Choice::ReplaceOld
+		} else {
+			scoring::Choice::RejectNew
 		}
 	}
 
@@ -101,13 +165,7 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 				assert!(i < txs.len());
 				assert!(i < scores.len());
 
-				scores[i] = *txs[i].transaction.gas_price();
-				let boost = match txs[i].priority() {
-					super::Priority::Local => 15,
-					super::Priority::Retracted => 10,
-					super::Priority::Regular => 0,
-				};
-				scores[i] = scores[i] << boost;
+				scores[i] = *txs[i].transaction.gas_price() << priority_boost(txs[i].priority());
 			},
 			// We are only sending an event in case of penalization.
 			// So just lower the priority of all non-local transactions.
@@ -135,10 +193,57 @@ mod tests {
 	use pool::tests::tx::{Tx, TxExt};
 	use txpool::Scoring;
 
+	#[test]
+	fn should_require_configured_price_bump_to_replace() {
+		// given
+		let default = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
+		let strict = default.clone().with_replacement_policy(ReplacementPolicy { price_bump: 5_000 });
+		let (old, _) = Tx::gas_price(100).signed_replacement();
+		let (_, new1) = Tx::gas_price(111).signed_replacement();
+		let (_, new2) = Tx::gas_price(149).signed_replacement();
+		let (old, new1, new2) = (old.verified(), new1.verified(), new2.verified());
+
+		// then
+		assert_eq!(ReplacementPolicy::default().bump(&100.into()), 112.into());
+		assert_eq!(ReplacementPolicy { price_bump: 1_000 }.bump(&U256::max_value()), U256::max_value());
+		assert_eq!(default.choose(&old, &new1), scoring::Choice::ReplaceOld);
+		assert_eq!(strict.choose(&old, &new1), scoring::Choice::RejectNew);
+		assert_eq!(strict.choose(&old, &new2), scoring::Choice::ReplaceOld);
+	}
+
+	struct FeeTx {
+		gas_price: U256,
+		fees: Option<(U256, U256)>,
+	}
+
+	impl ScoredTransaction for FeeTx {
+		fn priority(&self) -> Priority { Priority::Regular }
+		fn gas_price(&self) -> &U256 { &self.gas_price }
+		fn max_fee_per_gas(&self) -> Option<&U256> { self.fees.as_ref().map(|fees| &fees.0) }
+		fn max_priority_fee_per_gas(&self) -> Option<&U256> { self.fees.as_ref().map(|fees| &fees.1) }
+		fn nonce(&self) -> U256 { 0.into() }
+	}
+
+	#[test]
+	fn should_require_fee_cap_and_priority_fee_bump_for_fee_market_transactions() {
+		let policy = ReplacementPolicy::default();
+		let legacy = |gas_price: u64| FeeTx { gas_price: gas_price.into(), fees: None };
+		let fee_market = |fee_cap: u64, priority_fee: u64| FeeTx { gas_price: fee_cap.into(), fees: Some((fee_cap.into(), priority_fee.into())) };
+
+		// both the fee cap and the priority fee have to be bumped
+		assert!(policy.should_replace(&fee_market(100, 10), &fee_market(113, 12)));
+		assert!(!policy.should_replace(&fee_market(100, 10), &fee_market(200, 10)));
+		assert!(!policy.should_replace(&fee_market(100, 10), &fee_market(111, 20)));
+		// a legacy transaction pays its gas price as fee cap and priority fee
+		assert!(!policy.should_replace(&legacy(100), &fee_market(200, 10)));
+		assert!(policy.should_replace(&legacy(100), &fee_market(113, 113)));
+		assert!(policy.should_replace(&fee_market(100, 10), &legacy(113)));
+	}
+
 	#[test]
 	fn should_calculate_score_correctly() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let (tx1, tx2, tx3) = Tx::default().signed_triple();
 		let transactions = vec![tx1, tx2, tx3].into_iter().enumerate().map(|(i, tx)| {
 			let mut verified = tx.verified();
@@ -15,6 +15,7 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{atomic, Arc};
+use std::time::SystemTime;
 
 use ethereum_types::{U256, H256, Address};
 use rlp::Rlp;
@@ -90,6 +91,7 @@ impl TestClient {
 			priority: pool::Priority::Regular,
 			transaction: tx,
 			insertion_id: 1,
+			received_at: SystemTime::now(),
 		}
 	}
 
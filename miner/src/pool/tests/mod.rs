@@ -1074,3 +1074,184 @@ fn should_not_reject_early_in_case_gas_price_is_less_than_min_effective() {
 	assert_eq!(txq.status().status.transaction_count, 2);
 	assert!(client.was_verification_triggered());
 }
+
+#[test]
+fn should_emit_lifecycle_events() {
+	use std::sync::Arc;
+	use parking_lot::Mutex;
+	use pool::{PoolEvent, PoolEventListener, DropReason};
+
+	struct Events(Arc<Mutex<Vec<PoolEvent>>>);
+	impl PoolEventListener for Events {
+		fn on_events(&self, events: &[PoolEvent]) -> bool {
+			self.0.lock().extend_from_slice(events);
+			true
+		}
+	}
+
+	// given
+	let txq = new_queue();
+	let events = Arc::new(Mutex::new(Vec::new()));
+	txq.add_event_listener(Box::new(Events(events.clone())));
+	let (tx1, tx2) = Tx::default().signed_pair();
+	let (hash1, hash2) = (tx1.hash(), tx2.hash());
+	let added = |tx: &transaction::SignedTransaction| PoolEvent::Added {
+		hash: tx.hash(),
+		sender: tx.sender(),
+		nonce: tx.nonce,
+		gas_price: tx.gas_price,
+		local: false,
+	};
+	let (added1, added2) = (added(&tx1), added(&tx2));
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx2].unverified());
+	assert_eq!(res, vec![Ok(())]);
+
+	// then
+	assert_eq!(*events.lock(), vec![added2.clone()]);
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1].unverified());
+	assert_eq!(res, vec![Ok(())]);
+
+	// then
+	let emitted = events.lock().clone();
+	assert_eq!(emitted.len(), 4);
+	assert_eq!(emitted[1], added1);
+	assert!(emitted[2..].contains(&PoolEvent::Promoted { hash: hash1 }));
+	assert!(emitted[2..].contains(&PoolEvent::Promoted { hash: hash2 }));
+
+	// when
+	txq.remove(vec![&hash2], true);
+
+	// then
+	assert_eq!(events.lock()[4], PoolEvent::Dropped { hash: hash2, reason: DropReason::Invalid, by: None });
+}
+
+#[test]
+fn should_enforce_sender_and_future_quotas() {
+	// given
+	let txq = TransactionQueue::new(
+		txpool::Options {
+			max_count: 10,
+			max_per_sender: 10,
+			max_mem_usage: 5_000_000,
+		},
+		verifier::Options {
+			minimal_gas_price: 1.into(),
+			block_gas_limit: 1_000_000.into(),
+			tx_gas_limit: 1_000_000.into(),
+			no_early_reject: false,
+		},
+		PrioritizationStrategy::GasPriceOnly,
+	);
+	txq.set_quotas(pool::Quotas {
+		max_pending_per_sender: Some(1),
+		max_future_per_sender: Some(2),
+		max_future: Some(2),
+	});
+	let (tx1, tx2, tx3) = Tx::default().signed_triple();
+	let (tx4, tx5, tx6) = Tx::default().signed_triple();
+	let (tx7, _, _) = Tx::default().signed_triple();
+	let hash3 = tx3.hash();
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx2, tx3, tx5].unverified());
+
+	// then
+	// the future budget got exhausted, so the worst future transaction of the biggest holder was evicted
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+	assert!(txq.find(&hash3).is_none());
+	assert_eq!(txq.status().quotas.future_evicted, 1);
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx6, tx4, tx1, tx7].unverified());
+
+	// then
+	// the sender holding the most future transactions can't push out anyone,
+	// while filling the nonce gaps is always possible.
+	assert_eq!(res, vec![
+		Err(transaction::Error::LimitReached),
+		Ok(()),
+		Ok(()),
+		Ok(()),
+	]);
+	assert_eq!(txq.status().quotas.future_rejected, 1);
+	assert_eq!(txq.status().status.transaction_count, 5);
+}
+
+#[test]
+fn should_remove_expired_transactions() {
+	// given
+	let txq = new_queue();
+	txq.set_expiry(pool::Expiry {
+		local: None,
+		remote: Some(::std::time::Duration::from_secs(0)),
+	});
+	let (tx1, tx2) = Tx::default().signed_pair();
+	let (hash1, hash2) = (tx1.hash(), tx2.hash());
+	let res = txq.import(TestClient::new(), vec![tx1.local(), tx2.unverified()]);
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+	let age = txq.transaction_age(&hash2).unwrap();
+	assert!(age.is_expired(age.received_at));
+
+	// when
+	let removed = txq.remove_expired();
+
+	// then
+	assert_eq!(removed, 1);
+	assert_eq!(txq.status().status.transaction_count, 1);
+	assert!(txq.find(&hash1).is_some());
+}
+
+#[test]
+fn should_reject_filtered_transactions() {
+	// given
+	let txq = new_queue();
+	let (tx1, tx2) = (Tx::default().signed(), Tx::default().signed());
+	let mut lists = pool::FilterLists::default();
+	lists.banned_senders.insert(tx1.sender());
+	txq.set_filter(Some(::std::sync::Arc::new(lists)));
+
+	// when
+	let res = txq.import(TestClient::new(), vec![tx1, tx2].unverified());
+
+	// then
+	assert_eq!(res, vec![Err(transaction::Error::SenderBanned), Ok(())]);
+	assert_eq!(txq.status().status.transaction_count, 1);
+}
+
+#[test]
+fn should_inspect_pool_and_remember_dropped_transactions() {
+	// given
+	let txq = new_queue();
+	let (tx1, tx2) = Tx::default().signed_replacement();
+	let tx3 = Tx { nonce: 125, ..Default::default() }.signed();
+	let (hash1, hash2, hash3) = (tx1.hash(), tx2.hash(), tx3.hash());
+	let res = txq.import(TestClient::new(), vec![tx1.unverified(), tx3.local()]);
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+	let res = txq.import(TestClient::new(), vec![tx2.unverified()]);
+	assert_eq!(res, vec![Ok(())]);
+
+	// when
+	let inspection = txq.inspect(TestClient::new(), 0, 0);
+
+	// then
+	assert_eq!(inspection.transactions.len(), 2);
+	let replacement = inspection.transactions.iter().find(|tx| tx.hash == hash2).unwrap();
+	assert_eq!(replacement.status, pool::TransactionStatus::Pending);
+	assert_eq!(replacement.priority, pool::Priority::Regular);
+	assert_eq!(replacement.boost, 0);
+	assert_eq!(replacement.score, 2.into());
+	let future = inspection.transactions.iter().find(|tx| tx.hash == hash3).unwrap();
+	assert_eq!(future.status, pool::TransactionStatus::Future);
+	assert_eq!(future.priority, pool::Priority::Local);
+	assert_eq!(future.boost, 15);
+	assert_eq!(future.score, U256::from(1) << 15);
+
+	assert_eq!(inspection.dropped.len(), 1);
+	assert_eq!(inspection.dropped[0].hash, hash1);
+	assert_eq!(inspection.dropped[0].reason, pool::DropReason::Replaced);
+	assert_eq!(inspection.dropped[0].by, Some(hash2));
+}
@@ -25,6 +25,7 @@
 use std::cmp;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
+use std::time::SystemTime;
 
 use ethereum_types::{U256, H256};
 use rlp::Encodable;
@@ -338,6 +339,7 @@ impl<C: Client> txpool::Verifier<Transaction> for Verifier<C, ::pool::scoring::N
 			hash,
 			sender,
 			insertion_id: self.id.fetch_add(1, atomic::Ordering::AcqRel),
+			received_at: SystemTime::now(),
 		})
 	}
 }
@@ -21,18 +21,25 @@ extern crate jsonrpc_core;
 extern crate ethereum_types;
 extern crate keccak_hash as hash;
 extern crate parking_lot;
+extern crate tokio;
+extern crate tokio_rustls;
 
 #[macro_use] extern crate log;
 
-#[cfg(test)] extern crate tokio;
 #[cfg(test)] extern crate tokio_io;
 #[cfg(test)] extern crate env_logger;
 
+mod tls;
 mod traits;
+mod vardiff;
+mod worker;
 
+pub use tls::TlsConfig;
 pub use traits::{
-	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration,
+	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration, ShareResult, WorkerStatsProvider,
 };
+pub use vardiff::VarDiffConfig;
+pub use worker::WorkerStats;
 
 use jsonrpc_tcp_server::{
 	Server as JsonRpcServer, ServerBuilder as JsonRpcServerBuilder,
@@ -43,9 +50,12 @@ use std::sync::Arc;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
+use std::time::Instant;
 use hash::keccak;
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use parking_lot::RwLock;
+use tls::{TlsListener, TlsPeers};
+use worker::Worker;
 
 type RpcResult = Result<jsonrpc_core::Value, jsonrpc_core::Error>;
 
@@ -65,6 +75,8 @@ pub struct Stratum {
 	///
 	/// Used to push messages to peers
 	tcp_dispatcher: Dispatcher,
+	/// TLS listener proxying to the rpc server, if enabled
+	tls_listener: Option<TlsListener>,
 }
 
 impl Stratum {
@@ -73,7 +85,19 @@ impl Stratum {
 		dispatcher: Arc<dyn JobDispatcher>,
 		secret: Option<H256>,
 	) -> Result<Arc<Stratum>, Error> {
+		Stratum::start_with(addr, dispatcher, secret, None, None)
+	}
+
+	/// Starts the server with variable share difficulty and a TLS listener, if configured
+	pub fn start_with(
+		addr: &SocketAddr,
+		dispatcher: Arc<dyn JobDispatcher>,
+		secret: Option<H256>,
+		vardiff: Option<VarDiffConfig>,
+		tls: Option<TlsConfig>,
+	) -> Result<Arc<Stratum>, Error> {
 
+		let tls_peers = TlsPeers::default();
 		let implementation = Arc::new(StratumImpl {
 			subscribers: RwLock::default(),
 			job_queue: RwLock::default(),
@@ -81,6 +105,8 @@ impl Stratum {
 			workers: Arc::new(RwLock::default()),
 			secret,
 			notify_counter: RwLock::new(NOTIFY_COUNTER_INITIAL),
+			vardiff,
+			tls_peers: tls_peers.clone(),
 		});
 
 		let mut delegate = IoDelegate::<StratumImpl, SocketMetadata>::new(implementation.clone());
@@ -94,11 +120,16 @@ impl Stratum {
 		let tcp_dispatcher = server_builder.dispatcher();
 		let server_builder = server_builder.session_meta_extractor(PeerMetaExtractor::new(tcp_dispatcher.clone()));
 		let server = server_builder.start(addr)?;
+		let tls_listener = match tls {
+			Some(ref tls) => Some(TlsListener::start(tls, *addr, tls_peers)?),
+			None => None,
+		};
 
 		let stratum = Arc::new(Stratum {
 			rpc_server: Some(server),
 			implementation,
 			tcp_dispatcher,
+			tls_listener,
 		});
 
 		Ok(stratum)
@@ -111,8 +142,16 @@ impl PushWorkHandler for Stratum {
 	}
 }
 
+impl WorkerStatsProvider for Stratum {
+	fn workers(&self) -> Vec<WorkerStats> {
+		self.implementation.workers()
+	}
+}
+
 impl Drop for Stratum {
 	fn drop(&mut self) {
+		// stop proxying TLS connections before the rpc server goes away
+		self.tls_listener.take();
 		// shut down rpc server
 		self.rpc_server.take().map(|server| server.close());
 	}
@@ -125,12 +164,16 @@ struct StratumImpl {
 	job_queue: RwLock<HashSet<SocketAddr>>,
 	/// Payload manager
 	dispatcher: Arc<dyn JobDispatcher>,
-	/// Authorized workers (socket - worker_id)
-	workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+	/// Authorized workers (socket - worker)
+	workers: Arc<RwLock<HashMap<SocketAddr, Worker>>>,
 	/// Secret if any
 	secret: Option<H256>,
 	/// Dispatch notify counter
 	notify_counter: RwLock<u32>,
+	/// Variable share difficulty, `None` if every share has to meet the block difficulty
+	vardiff: Option<VarDiffConfig>,
+	/// Connections proxied by the TLS listener
+	tls_peers: TlsPeers,
 }
 
 impl StratumImpl {
@@ -164,39 +207,92 @@ impl StratumImpl {
 				}
 			}
 			trace!(target: "stratum", "New worker #{} registered", worker_id);
-			self.workers.write().insert(meta.addr().clone(), worker_id);
+			let difficulty = match self.vardiff {
+				Some(ref vardiff) => vardiff.clamp(vardiff.initial_difficulty, self.dispatcher.block_difficulty()),
+				None => U256::zero(),
+			};
+			self.workers.write().insert(meta.addr().clone(), Worker::new(worker_id, difficulty, Instant::now()));
+			if self.vardiff.is_some() {
+				if let Some(ref tcp_dispatcher) = meta.tcp_dispatcher {
+					self.push_work(meta.addr(), difficulty, tcp_dispatcher);
+				}
+			}
 			to_value(true)
 		}).map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))
 	}
 
 	/// rpc method `mining.submit`
 	fn submit(&self, params: Params, meta: SocketMetadata) -> RpcResult {
-		Ok(match params {
-			Params::Array(vals) => {
-				// first two elements are service messages (worker_id & job_id)
-				match self.dispatcher.submit(vals.iter().skip(2)
-					.filter_map(|val| match *val {
-						Value::String(ref s) => Some(s.to_owned()),
-						_ => None
-					})
-					.collect::<Vec<String>>()) {
-					Ok(()) => {
-						self.update_peers(&meta.tcp_dispatcher.expect("tcp_dispatcher is always initialized; qed"));
-						to_value(true)
-					},
-					Err(submit_err) => {
-						warn!("Error while submitting share: {:?}", submit_err);
-						to_value(false)
+		let payload = match params {
+			// first two elements are service messages (worker_id & job_id)
+			Params::Array(vals) => vals.iter().skip(2)
+				.filter_map(|val| match *val {
+					Value::String(ref s) => Some(s.to_owned()),
+					_ => None
+				})
+				.collect::<Vec<String>>(),
+			_ => {
+				trace!(target: "stratum", "Invalid submit work format {:?}", params);
+				return Ok(to_value(false).expect("Only true/false is returned and it's always serializable; qed"));
+			}
+		};
+		let tcp_dispatcher = meta.tcp_dispatcher.expect("tcp_dispatcher is always initialized; qed");
+
+		// workers which did not authorize always mine at the block difficulty
+		let share_difficulty = match self.vardiff {
+			Some(_) => self.workers.read().get(&meta.addr).map(Worker::share_difficulty),
+			None => None,
+		};
+		let submitted = match share_difficulty {
+			Some(difficulty) => self.dispatcher.submit_share(payload, &difficulty).map(|result| (result, difficulty)),
+			None => self.dispatcher.submit(payload)
+				.map(|_| (ShareResult::Block, self.dispatcher.block_difficulty().unwrap_or_default())),
+		};
+
+		Ok(match submitted {
+			Ok((result, difficulty)) => {
+				let retargeted = self.workers.write().get_mut(&meta.addr).and_then(|worker| {
+					let now = Instant::now();
+					worker.accept_share(result, difficulty, now);
+					match self.vardiff {
+						Some(ref vardiff) if worker.retarget(vardiff, self.dispatcher.block_difficulty(), now) => Some(worker.difficulty()),
+						_ => None,
 					}
+				});
+				match (result, retargeted) {
+					(ShareResult::Block, _) => self.update_peers(&tcp_dispatcher),
+					(ShareResult::Share, Some(difficulty)) => self.push_work(&meta.addr, difficulty, &tcp_dispatcher),
+					(ShareResult::Share, None) => {},
 				}
+				to_value(true)
 			},
-			_ => {
-				trace!(target: "stratum", "Invalid submit work format {:?}", params);
+			Err(submit_err) => {
+				if let Some(worker) = self.workers.write().get_mut(&meta.addr) {
+					worker.reject_share();
+				}
+				warn!(target: "stratum", "Error while submitting share: {:?}", submit_err);
 				to_value(false)
 			}
 		}.expect("Only true/false is returned and it's always serializable; qed"))
 	}
 
+	/// Statistics of all authorized workers
+	fn workers(&self) -> Vec<WorkerStats> {
+		let now = Instant::now();
+		let block_difficulty = self.dispatcher.block_difficulty().unwrap_or_default();
+		let tls_peers = self.tls_peers.read();
+		self.workers.write().iter_mut().map(|(addr, worker)| {
+			let mut stats = match tls_peers.get(addr) {
+				Some(remote) => worker.stats(*remote, true, now),
+				None => worker.stats(*addr, false, now),
+			};
+			if self.vardiff.is_none() {
+				stats.difficulty = block_difficulty;
+			}
+			stats
+		}).collect()
+	}
+
 	/// Helper method
 	fn update_peers(&self, tcp_dispatcher: &Dispatcher) {
 		if let Some(job) = self.dispatcher.job() {
@@ -204,25 +300,50 @@ impl StratumImpl {
 		}
 	}
 
+	fn next_request_id(&self) -> u32 {
+		let mut counter = self.notify_counter.write();
+		if *counter == ::std::u32::MAX {
+			*counter = NOTIFY_COUNTER_INITIAL;
+		} else {
+			*counter = *counter + 1
+		}
+		*counter
+	}
+
+	/// Pushes the job at `difficulty` to a single worker
+	fn push_work(&self, addr: &SocketAddr, difficulty: U256, tcp_dispatcher: &Dispatcher) {
+		if let Some(payload) = self.dispatcher.job_with_difficulty(&difficulty) {
+			let msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", self.next_request_id(), payload);
+			trace!(target: "stratum", "pushing work to {} (payload: '{}')", addr, &msg);
+			if let Err(e) = tcp_dispatcher.push_message(addr, msg) {
+				trace!(target: "stratum", "Unable to push work to {}: {:?}", addr, e);
+			}
+		}
+	}
+
 	fn push_work_all(&self, payload: String, tcp_dispatcher: &Dispatcher) {
 		let hup_peers = {
-			let workers = self.workers.read();
-			let next_request_id = {
-				let mut counter = self.notify_counter.write();
-				if *counter == ::std::u32::MAX {
-					*counter = NOTIFY_COUNTER_INITIAL;
-				} else {
-					*counter = *counter + 1
-				}
-				*counter
-			};
+			let mut workers = self.workers.write();
+			let next_request_id = self.next_request_id();
+			let block_difficulty = self.dispatcher.block_difficulty();
+			let now = Instant::now();
 
 			let mut hup_peers = HashSet::new();
 			let workers_msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload);
 			trace!(target: "stratum", "pushing work for {} workers (payload: '{}')", workers.len(), &workers_msg);
-			for (addr, _) in workers.iter() {
+			for (addr, worker) in workers.iter_mut() {
 				trace!(target: "stratum", "pushing work to {}", addr);
-				match tcp_dispatcher.push_message(addr, workers_msg.clone()) {
+				// with variable difficulty every worker gets a job with its own share target
+				let msg = match self.vardiff {
+					Some(ref vardiff) => {
+						worker.retarget(vardiff, block_difficulty, now);
+						self.dispatcher.job_with_difficulty(&worker.difficulty())
+							.map(|job| format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, job))
+							.unwrap_or_else(|| workers_msg.clone())
+					},
+					None => workers_msg.clone(),
+				};
+				match tcp_dispatcher.push_message(addr, msg) {
 					Err(PushMessageError::NoSuchPeer) => {
 						trace!(target: "stratum", "Worker no longer connected: {}", addr);
 						hup_peers.insert(addr.clone());
@@ -471,6 +592,58 @@ mod tests {
 			response);
 	}
 
+	struct ShareManager {
+		shares: ::parking_lot::Mutex<Vec<U256>>,
+	}
+
+	impl JobDispatcher for ShareManager {
+		fn job_with_difficulty(&self, share_difficulty: &U256) -> Option<String> {
+			Some(format!(r#"["0x{:x}"]"#, share_difficulty))
+		}
+
+		fn block_difficulty(&self) -> Option<U256> {
+			Some(U256::from(1_000_000))
+		}
+
+		fn submit(&self, _payload: Vec<String>) -> Result<(), Error> {
+			Ok(())
+		}
+
+		fn submit_share(&self, _payload: Vec<String>, share_difficulty: &U256) -> Result<ShareResult, Error> {
+			self.shares.lock().push(*share_difficulty);
+			Ok(ShareResult::Share)
+		}
+	}
+
+	#[test]
+	fn checks_shares_at_worker_difficulty() {
+		let addr = "127.0.0.1:19990".parse().unwrap();
+		let manager = Arc::new(ShareManager { shares: Default::default() });
+		let vardiff = VarDiffConfig {
+			initial_difficulty: U256::from(5000),
+			min_difficulty: U256::from(1000),
+			..VarDiffConfig::default()
+		};
+		let stratum = Stratum::start_with(&addr, manager.clone(), None, Some(vardiff), None)
+			.expect("There should be no error starting stratum");
+
+		let request = concat!(
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1.rig", ""], "id": 1}"#, "\n",
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1.rig", "", "0x01", "0x02", "0x03"], "id": 2}"#,
+		);
+		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
+
+		assert!(response.contains(r#""params": ["0x1388"]"#), "worker should get a job at its share difficulty: {}", response);
+		assert_eq!(*manager.shares.lock(), vec![U256::from(5000)]);
+
+		let workers = stratum.workers();
+		assert_eq!(workers.len(), 1);
+		assert_eq!(workers[0].name, "miner1.rig");
+		assert_eq!(workers[0].difficulty, U256::from(5000));
+		assert_eq!(workers[0].accepted_shares, 1);
+		assert!(!workers[0].tls);
+	}
+
 	#[test]
 	fn jsonprc_server_is_send_and_sync() {
 		fn is_send_and_sync<T: Send + Sync>() {}
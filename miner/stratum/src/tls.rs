@@ -0,0 +1,184 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! TLS listener for the stratum server
+//!
+//! `jsonrpc-tcp-server` only speaks plain TCP, so TLS connections are terminated here
+//! and proxied to the plain listener over a local connection. The TLS stack is the same
+//! `rustls` the node already uses for HTTPS fetches.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tokio::io::{copy, AsyncRead};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::prelude::{future::{self, Either}, Future, Stream};
+use tokio::runtime::{self, Runtime};
+use tokio::timer::Timeout;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::{NoClientAuth, ServerConfig};
+use tokio_rustls::rustls::internal::pemfile;
+
+use traits::Error;
+
+/// How long a worker may take to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Proxied TLS connections (local address of the proxy connection - remote address of the worker).
+pub type TlsPeers = Arc<RwLock<HashMap<SocketAddr, SocketAddr>>>;
+
+/// TLS listener configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+	/// Address of the TLS listener.
+	pub addr: SocketAddr,
+	/// Path to the PEM encoded certificate chain.
+	pub certificate: PathBuf,
+	/// Path to the PEM encoded private key (PKCS#8 or RSA).
+	pub private_key: PathBuf,
+}
+
+/// Running TLS listener. Stops accepting and proxying connections when dropped.
+pub struct TlsListener {
+	runtime: Option<Runtime>,
+}
+
+impl TlsListener {
+	/// Starts accepting TLS connections and proxying them to the plain server at `backend`.
+	pub fn start(config: &TlsConfig, backend: SocketAddr, peers: TlsPeers) -> Result<Self, Error> {
+		let acceptor = TlsAcceptor::from(load_server_config(&config.certificate, &config.private_key)?);
+		let listener = TcpListener::bind(&config.addr)?;
+		let backend = local_backend(backend);
+
+		// all connections are driven by a single event loop thread
+		let mut runtime = runtime::Builder::new()
+			.core_threads(1)
+			.name_prefix("stratum-tls-")
+			.build()?;
+		let server = listener.incoming()
+			.then(|result| {
+				if let Err(ref e) = result {
+					warn!(target: "stratum", "Error accepting TLS connection: {}", e);
+				}
+				Ok::<_, ()>(result.ok())
+			})
+			.filter_map(|stream| stream)
+			.for_each(move |stream| {
+				tokio::spawn(proxy(acceptor.clone(), stream, backend, peers.clone()));
+				Ok(())
+			});
+		runtime.spawn(server);
+
+		Ok(TlsListener {
+			runtime: Some(runtime),
+		})
+	}
+}
+
+impl Drop for TlsListener {
+	fn drop(&mut self) {
+		if let Some(runtime) = self.runtime.take() {
+			let _ = runtime.shutdown_now().wait();
+		}
+	}
+}
+
+fn load_server_config(certificate: &Path, private_key: &Path) -> Result<Arc<ServerConfig>, Error> {
+	let certs = pemfile::certs(&mut BufReader::new(File::open(certificate)?))
+		.map_err(|_| Error::Tls(format!("Invalid certificate file: {}", certificate.display())))?;
+	if certs.is_empty() {
+		return Err(Error::Tls(format!("No certificates found in {}", certificate.display())));
+	}
+
+	let invalid_key = |_| Error::Tls(format!("Invalid private key file: {}", private_key.display()));
+	let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(File::open(private_key)?)).map_err(invalid_key)?;
+	if keys.is_empty() {
+		keys = pemfile::rsa_private_keys(&mut BufReader::new(File::open(private_key)?)).map_err(invalid_key)?;
+	}
+	let key = keys.into_iter().next()
+		.ok_or_else(|| Error::Tls(format!("No private key found in {}", private_key.display())))?;
+
+	let mut config = ServerConfig::new(NoClientAuth::new());
+	config.set_single_cert(certs, key).map_err(|e| Error::Tls(e.to_string()))?;
+	Ok(Arc::new(config))
+}
+
+/// Plain server address reachable from this host.
+fn local_backend(addr: SocketAddr) -> SocketAddr {
+	match addr.ip() {
+		IpAddr::V4(ip) if ip.is_unspecified() => SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), addr.port()),
+		IpAddr::V6(ip) if ip.is_unspecified() => SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), addr.port()),
+		_ => addr,
+	}
+}
+
+fn proxy(acceptor: TlsAcceptor, stream: TcpStream, backend: SocketAddr, peers: TlsPeers) -> impl Future<Item = (), Error = ()> {
+	future::result(stream.peer_addr())
+		.and_then(move |remote| {
+			let handshake = Timeout::new(acceptor.accept(stream), HANDSHAKE_TIMEOUT)
+				.map_err(|e| e.into_inner().unwrap_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "TLS handshake timed out")));
+			handshake.join(TcpStream::connect(&backend)).and_then(move |(tls, plain)| {
+				let local = plain.local_addr();
+				local.map(move |local| (tls, plain, local, remote))
+			})
+		})
+		.and_then(move |(tls, plain, local, remote)| {
+			trace!(target: "stratum", "TLS connection from {}", remote);
+			peers.write().insert(local, remote);
+
+			let (tls_reader, tls_writer) = tls.split();
+			let (plain_reader, plain_writer) = plain.split();
+			// the connection is closed as soon as either side is done
+			copy(tls_reader, plain_writer).select2(copy(plain_reader, tls_writer)).then(move |result| {
+				peers.write().remove(&local);
+				match result {
+					Ok(_) => Ok(()),
+					Err(Either::A((e, _))) | Err(Either::B((e, _))) => Err(e),
+				}
+			})
+		})
+		.map_err(|e| debug!(target: "stratum", "TLS connection closed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn resolves_unspecified_backend_to_localhost() {
+		assert_eq!(local_backend("0.0.0.0:8008".parse().unwrap()), "127.0.0.1:8008".parse().unwrap());
+		assert_eq!(local_backend("[::]:8008".parse().unwrap()), "[::1]:8008".parse().unwrap());
+		assert_eq!(local_backend("10.0.0.1:8008".parse().unwrap()), "10.0.0.1:8008".parse().unwrap());
+	}
+
+	#[test]
+	fn rejects_missing_certificate() {
+		let config = TlsConfig {
+			addr: "127.0.0.1:0".parse().unwrap(),
+			certificate: "/nonexistent/stratum.crt".into(),
+			private_key: "/nonexistent/stratum.key".into(),
+		};
+		let peers = TlsPeers::default();
+		assert!(TlsListener::start(&config, "127.0.0.1:8008".parse().unwrap(), peers).is_err());
+	}
+}
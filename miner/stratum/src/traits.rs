@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use jsonrpc_tcp_server::PushMessageError;
 
+use worker::WorkerStats;
+
 #[derive(Debug, Clone)]
 pub enum Error {
 	NoWork,
@@ -24,6 +26,8 @@ pub enum Error {
 	Io(String),
 	Tcp(String),
 	Dispatch(String),
+	InvalidShare(String),
+	Tls(String),
 }
 
 impl From<std::io::Error> for Error {
@@ -46,8 +50,25 @@ pub trait JobDispatcher: Send + Sync {
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
 	fn job(&self) -> Option<String> { None }
+	// json for job update with the share target lowered to `share_difficulty`
+	fn job_with_difficulty(&self, _share_difficulty: &U256) -> Option<String> { self.job() }
+	// difficulty of the block currently being mined
+	fn block_difficulty(&self) -> Option<U256> { None }
 	// miner job result
 	fn submit(&self, payload: Vec<String>) -> Result<(), Error>;
+	// miner share checked against `share_difficulty`, submitted as a block only if it meets the block difficulty
+	fn submit_share(&self, payload: Vec<String>, _share_difficulty: &U256) -> Result<ShareResult, Error> {
+		self.submit(payload).map(|_| ShareResult::Block)
+	}
+}
+
+/// Outcome of an accepted share
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareResult {
+	/// Share meets the worker difficulty only
+	Share,
+	/// Share sealed a block
+	Block,
 }
 
 /// Interface that can handle requests to push job for workers
@@ -56,6 +77,12 @@ pub trait PushWorkHandler: Send + Sync {
 	fn push_work_all(&self, payload: String);
 }
 
+/// Interface that exposes statistics of the connected workers
+pub trait WorkerStatsProvider: Send + Sync {
+	/// statistics of all authorized workers
+	fn workers(&self) -> Vec<WorkerStats>;
+}
+
 pub struct ServiceConfiguration {
 	pub io_path: String,
 	pub listen_addr: String,
@@ -0,0 +1,162 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Variable share difficulty
+//!
+//! Every worker gets its own share difficulty, retargeted so that it submits roughly one
+//! share per `target_share_time`. Shares below the block difficulty are only accounted for,
+//! which lets small pools track the contribution of each worker without flooding the node.
+
+use std::cmp;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use ethereum_types::{U256, U512};
+
+/// Maximal factor by which the difficulty may change in a single retarget.
+const MAX_ADJUSTMENT: u64 = 4;
+
+/// Variable share difficulty settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarDiffConfig {
+	/// Share difficulty assigned to newly authorized workers.
+	pub initial_difficulty: U256,
+	/// Share difficulty is never retargeted below this value.
+	pub min_difficulty: U256,
+	/// Share difficulty is never retargeted above this value (nor above the block difficulty).
+	pub max_difficulty: U256,
+	/// Desired time between two shares of a single worker.
+	pub target_share_time: Duration,
+	/// Minimal time between two retargets of a single worker.
+	pub retarget_time: Duration,
+}
+
+impl Default for VarDiffConfig {
+	fn default() -> Self {
+		VarDiffConfig {
+			initial_difficulty: U256::from(4_000_000_000u64),
+			min_difficulty: U256::from(100_000_000u64),
+			max_difficulty: U256::max_value(),
+			target_share_time: Duration::from_secs(15),
+			retarget_time: Duration::from_secs(90),
+		}
+	}
+}
+
+impl VarDiffConfig {
+	/// Clamps `difficulty` into the configured bounds and the block difficulty (if known).
+	pub fn clamp(&self, difficulty: U256, block_difficulty: Option<U256>) -> U256 {
+		let max = match block_difficulty {
+			Some(block) => cmp::min(self.max_difficulty, block),
+			None => self.max_difficulty,
+		};
+		cmp::max(cmp::min(difficulty, max), cmp::min(self.min_difficulty, max))
+	}
+
+	/// Computes the new share difficulty of a worker that submitted `shares` shares
+	/// during `elapsed` at `difficulty`.
+	///
+	/// Returns `None` if it is too early to retarget or the difficulty is close enough to the target.
+	pub fn retarget(&self, difficulty: U256, shares: u64, elapsed: Duration, block_difficulty: Option<U256>) -> Option<U256> {
+		if elapsed < self.retarget_time {
+			return None;
+		}
+
+		let target_ms = cmp::max(as_millis(self.target_share_time), 1);
+		let new_difficulty = if shares == 0 {
+			// no shares at all, the worker is way slower than assumed
+			difficulty / MAX_ADJUSTMENT
+		} else {
+			let actual_ms = cmp::max(as_millis(elapsed) / shares, 1);
+			// tolerate 20% deviation from the target share time
+			if actual_ms * 5 >= target_ms * 4 && actual_ms * 5 <= target_ms * 6 {
+				return None;
+			}
+			let adjusted = difficulty.full_mul(U256::from(target_ms)) / U512::from(actual_ms);
+			let adjusted = U256::try_from(adjusted).unwrap_or_else(|_| U256::max_value());
+			cmp::max(
+				cmp::min(adjusted, difficulty.saturating_mul(U256::from(MAX_ADJUSTMENT))),
+				difficulty / MAX_ADJUSTMENT,
+			)
+		};
+
+		let new_difficulty = self.clamp(new_difficulty, block_difficulty);
+		if new_difficulty == difficulty {
+			None
+		} else {
+			Some(new_difficulty)
+		}
+	}
+}
+
+fn as_millis(duration: Duration) -> u64 {
+	duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+	use ethereum_types::U256;
+
+	fn config() -> VarDiffConfig {
+		VarDiffConfig {
+			initial_difficulty: U256::from(1000),
+			min_difficulty: U256::from(100),
+			max_difficulty: U256::from(100_000),
+			target_share_time: Duration::from_secs(10),
+			retarget_time: Duration::from_secs(60),
+		}
+	}
+
+	#[test]
+	fn does_not_retarget_too_early() {
+		assert_eq!(config().retarget(U256::from(1000), 60, Duration::from_secs(30), None), None);
+	}
+
+	#[test]
+	fn keeps_difficulty_close_to_target() {
+		// 7 shares in 60 seconds is within 20% of one share per 10 seconds
+		assert_eq!(config().retarget(U256::from(1000), 7, Duration::from_secs(60), None), None);
+	}
+
+	#[test]
+	fn raises_difficulty_of_fast_workers() {
+		// 12 shares per 60 seconds, twice the target rate
+		assert_eq!(config().retarget(U256::from(1000), 12, Duration::from_secs(60), None), Some(U256::from(2000)));
+		// adjustment is limited to a factor of 4
+		assert_eq!(config().retarget(U256::from(1000), 600, Duration::from_secs(60), None), Some(U256::from(4000)));
+	}
+
+	#[test]
+	fn lowers_difficulty_of_slow_workers() {
+		assert_eq!(config().retarget(U256::from(1000), 3, Duration::from_secs(60), None), Some(U256::from(500)));
+		assert_eq!(config().retarget(U256::from(1000), 0, Duration::from_secs(60), None), Some(U256::from(250)));
+		// never below the minimum
+		assert_eq!(config().retarget(U256::from(200), 0, Duration::from_secs(60), None), Some(U256::from(100)));
+		assert_eq!(config().retarget(U256::from(100), 0, Duration::from_secs(60), None), None);
+	}
+
+	#[test]
+	fn never_exceeds_block_difficulty() {
+		assert_eq!(
+			config().retarget(U256::from(1000), 600, Duration::from_secs(60), Some(U256::from(1500))),
+			Some(U256::from(1500))
+		);
+		assert_eq!(config().clamp(U256::from(1000), Some(U256::from(50))), U256::from(50));
+	}
+}
@@ -0,0 +1,240 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Per-connection worker state and statistics
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use ethereum_types::U256;
+
+use traits::ShareResult;
+use vardiff::VarDiffConfig;
+
+/// Accepted shares older than this are not taken into account for the hashrate estimate.
+const HASHRATE_WINDOW: Duration = Duration::from_secs(600);
+
+/// Snapshot of statistics of a single connected worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+	/// Name the worker authorized with.
+	pub name: String,
+	/// Remote address of the worker.
+	pub address: SocketAddr,
+	/// Whether the worker is connected over TLS.
+	pub tls: bool,
+	/// Current share difficulty.
+	pub difficulty: U256,
+	/// Number of accepted shares.
+	pub accepted_shares: u64,
+	/// Number of rejected shares.
+	pub rejected_shares: u64,
+	/// Number of shares which sealed a block.
+	pub blocks: u64,
+	/// Estimated hashrate (hashes per second) based on accepted shares.
+	pub hashrate: U256,
+	/// Time since the worker authorized.
+	pub connected_for: Duration,
+	/// Time since the last accepted share.
+	pub last_share: Option<Duration>,
+}
+
+/// State of an authorized worker.
+pub struct Worker {
+	name: String,
+	difficulty: U256,
+	previous_difficulty: Option<U256>,
+	accepted_shares: u64,
+	rejected_shares: u64,
+	blocks: u64,
+	connected_at: Instant,
+	last_share_at: Option<Instant>,
+	retargeted_at: Instant,
+	shares_since_retarget: u64,
+	recent_shares: VecDeque<(Instant, U256)>,
+}
+
+impl Worker {
+	/// New worker with the given name and share difficulty.
+	pub fn new(name: String, difficulty: U256, now: Instant) -> Self {
+		Worker {
+			name,
+			difficulty,
+			previous_difficulty: None,
+			accepted_shares: 0,
+			rejected_shares: 0,
+			blocks: 0,
+			connected_at: now,
+			last_share_at: None,
+			retargeted_at: now,
+			shares_since_retarget: 0,
+			recent_shares: VecDeque::new(),
+		}
+	}
+
+	/// Name the worker authorized with.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Current share difficulty.
+	pub fn difficulty(&self) -> U256 {
+		self.difficulty
+	}
+
+	/// Difficulty submitted shares are checked against.
+	///
+	/// Shares found for the work sent before the last retarget are still accepted
+	/// at the previous difficulty if that was lower.
+	pub fn share_difficulty(&self) -> U256 {
+		match self.previous_difficulty {
+			Some(previous) => cmp::min(previous, self.difficulty),
+			None => self.difficulty,
+		}
+	}
+
+	/// Accounts an accepted share checked against `difficulty`.
+	pub fn accept_share(&mut self, result: ShareResult, difficulty: U256, now: Instant) {
+		self.accepted_shares += 1;
+		self.shares_since_retarget += 1;
+		if result == ShareResult::Block {
+			self.blocks += 1;
+		}
+		self.last_share_at = Some(now);
+		self.recent_shares.push_back((now, difficulty));
+		self.prune(now);
+	}
+
+	/// Accounts a rejected share.
+	pub fn reject_share(&mut self) {
+		self.rejected_shares += 1;
+	}
+
+	/// Retargets the share difficulty if necessary. Returns `true` if the difficulty has changed.
+	pub fn retarget(&mut self, config: &VarDiffConfig, block_difficulty: Option<U256>, now: Instant) -> bool {
+		let elapsed = now.duration_since(self.retargeted_at);
+		let new_difficulty = config.retarget(self.difficulty, self.shares_since_retarget, elapsed, block_difficulty);
+		if elapsed >= config.retarget_time {
+			self.retargeted_at = now;
+			self.shares_since_retarget = 0;
+			self.previous_difficulty = None;
+		}
+
+		match new_difficulty {
+			Some(difficulty) => {
+				trace!(target: "stratum", "Retargeting worker {} from {} to {}", self.name, self.difficulty, difficulty);
+				self.previous_difficulty = Some(self.difficulty);
+				self.difficulty = difficulty;
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Estimated hashrate based on the shares accepted within the hashrate window.
+	pub fn hashrate(&mut self, now: Instant) -> U256 {
+		self.prune(now);
+		let window = cmp::min(HASHRATE_WINDOW, now.duration_since(self.connected_at)).as_secs();
+		if window == 0 {
+			return U256::zero();
+		}
+		let work = self.recent_shares.iter().fold(U256::zero(), |sum, &(_, difficulty)| sum.saturating_add(difficulty));
+		work / U256::from(window)
+	}
+
+	/// Statistics snapshot.
+	pub fn stats(&mut self, address: SocketAddr, tls: bool, now: Instant) -> WorkerStats {
+		WorkerStats {
+			name: self.name.clone(),
+			address,
+			tls,
+			difficulty: self.difficulty,
+			accepted_shares: self.accepted_shares,
+			rejected_shares: self.rejected_shares,
+			blocks: self.blocks,
+			hashrate: self.hashrate(now),
+			connected_for: now.duration_since(self.connected_at),
+			last_share: self.last_share_at.map(|at| now.duration_since(at)),
+		}
+	}
+
+	fn prune(&mut self, now: Instant) {
+		while self.recent_shares.front().map_or(false, |&(at, _)| now.duration_since(at) > HASHRATE_WINDOW) {
+			self.recent_shares.pop_front();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::{Duration, Instant};
+	use ethereum_types::U256;
+
+	fn config() -> VarDiffConfig {
+		VarDiffConfig {
+			initial_difficulty: U256::from(1000),
+			min_difficulty: U256::from(100),
+			max_difficulty: U256::from(100_000),
+			target_share_time: Duration::from_secs(10),
+			retarget_time: Duration::from_secs(60),
+		}
+	}
+
+	#[test]
+	fn accounts_shares_and_hashrate() {
+		let start = Instant::now();
+		let mut worker = Worker::new("rig1".into(), U256::from(1000), start);
+
+		for i in 0..6 {
+			worker.accept_share(ShareResult::Share, U256::from(1000), start + Duration::from_secs(10 * i));
+		}
+		worker.accept_share(ShareResult::Block, U256::from(1000), start + Duration::from_secs(60));
+		worker.reject_share();
+
+		let stats = worker.stats("127.0.0.1:1234".parse().unwrap(), false, start + Duration::from_secs(70));
+		assert_eq!(stats.name, "rig1");
+		assert_eq!(stats.accepted_shares, 7);
+		assert_eq!(stats.rejected_shares, 1);
+		assert_eq!(stats.blocks, 1);
+		assert_eq!(stats.hashrate, U256::from(100));
+		assert_eq!(stats.last_share, Some(Duration::from_secs(10)));
+	}
+
+	#[test]
+	fn retargets_and_accepts_previous_difficulty() {
+		let start = Instant::now();
+		let mut worker = Worker::new("rig1".into(), U256::from(1000), start);
+		for i in 0..12 {
+			worker.accept_share(ShareResult::Share, U256::from(1000), start + Duration::from_secs(5 * i));
+		}
+
+		assert!(!worker.retarget(&config(), None, start + Duration::from_secs(30)));
+		assert!(worker.retarget(&config(), None, start + Duration::from_secs(60)));
+		assert_eq!(worker.difficulty(), U256::from(2000));
+		assert_eq!(worker.share_difficulty(), U256::from(1000));
+
+		// another window passes at the target rate
+		for i in 0..6 {
+			worker.accept_share(ShareResult::Share, U256::from(2000), start + Duration::from_secs(60 + 10 * i));
+		}
+		assert!(!worker.retarget(&config(), None, start + Duration::from_secs(120)));
+		assert_eq!(worker.share_difficulty(), U256::from(2000));
+	}
+}
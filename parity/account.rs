@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use ethstore::KdfParams;
 use params::SpecType;
 
 #[derive(Debug, PartialEq)]
@@ -21,7 +22,10 @@ pub enum AccountCmd {
 	New(NewAccount),
 	List(ListAccounts),
 	Import(ImportAccounts),
-	ImportFromGeth(ImportFromGethAccounts)
+	ImportFromGeth(ImportFromGethAccounts),
+	Export(ExportKeystore),
+	Restore(RestoreKeystore),
+	Reencrypt(ReencryptKeystore),
 }
 
 #[derive(Debug, PartialEq)]
@@ -32,7 +36,7 @@ pub struct ListAccounts {
 
 #[derive(Debug, PartialEq)]
 pub struct NewAccount {
-	pub iterations: u32,
+	pub kdf: KdfParams,
 	pub path: String,
 	pub spec: SpecType,
 	pub password_file: Option<String>,
@@ -55,6 +59,40 @@ pub struct ImportFromGethAccounts {
 	pub spec: SpecType,
 }
 
+/// Parameters for keystore archive export
+#[derive(Debug, PartialEq)]
+pub struct ExportKeystore {
+	pub path: String,
+	pub spec: SpecType,
+	/// vault to export, root keystore if `None`
+	pub vault: Option<String>,
+	/// archive file, standard output if `None`
+	pub file: Option<String>,
+	pub password_files: Vec<String>,
+}
+
+/// Parameters for keystore archive import
+#[derive(Debug, PartialEq)]
+pub struct RestoreKeystore {
+	pub path: String,
+	pub spec: SpecType,
+	/// vault to import to, root keystore if `None`
+	pub vault: Option<String>,
+	pub file: String,
+	pub password_files: Vec<String>,
+}
+
+/// Parameters for keystore re-encryption
+#[derive(Debug, PartialEq)]
+pub struct ReencryptKeystore {
+	pub path: String,
+	pub spec: SpecType,
+	/// vault to re-encrypt, root keystore if `None`
+	pub vault: Option<String>,
+	pub kdf: KdfParams,
+	pub password_files: Vec<String>,
+}
+
 
 #[cfg(not(feature = "accounts"))]
 pub fn execute(_cmd: AccountCmd) -> Result<String, String> {
@@ -64,18 +102,24 @@ pub fn execute(_cmd: AccountCmd) -> Result<String, String> {
 #[cfg(feature = "accounts")]
 mod command {
 	use super::*;
+	use std::fs;
+	use std::io::{self, Write};
 	use std::path::PathBuf;
 	use accounts::{AccountProvider, AccountProviderSettings};
+	use ethkey::Password;
 	use ethstore::{EthStore, SecretStore, SecretVaultRef, import_account, import_accounts, read_geth_accounts};
 	use ethstore::accounts_dir::RootDiskDirectory;
-	use helpers::{password_prompt, password_from_file};
+	use helpers::{password_prompt, password_from_file, passwords_from_files};
 
 	pub fn execute(cmd: AccountCmd) -> Result<String, String> {
 		match cmd {
 			AccountCmd::New(new_cmd) => new(new_cmd),
 			AccountCmd::List(list_cmd) => list(list_cmd),
 			AccountCmd::Import(import_cmd) => import(import_cmd),
-			AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd)
+			AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd),
+			AccountCmd::Export(export_cmd) => export_keystore(export_cmd),
+			AccountCmd::Restore(restore_cmd) => restore_keystore(restore_cmd),
+			AccountCmd::Reencrypt(reencrypt_cmd) => reencrypt_keystore(reencrypt_cmd),
 		}
 	}
 
@@ -86,9 +130,9 @@ mod command {
 		RootDiskDirectory::create(path).map_err(|e| format!("Could not open keys directory: {}", e))
 	}
 
-	fn secret_store(dir: Box<RootDiskDirectory>, iterations: Option<u32>) -> Result<EthStore, String> {
-		match iterations {
-			Some(i) => EthStore::open_with_iterations(dir, i),
+	fn secret_store(dir: Box<RootDiskDirectory>, kdf: Option<KdfParams>) -> Result<EthStore, String> {
+		match kdf {
+			Some(kdf) => EthStore::open_with_kdf(dir, kdf),
 			_ => EthStore::open(dir)
 		}.map_err(|e| format!("Could not open keys store: {}", e))
 	}
@@ -100,7 +144,7 @@ mod command {
 		};
 
 		let dir = Box::new(keys_dir(n.path, n.spec)?);
-		let secret_store = Box::new(secret_store(dir, Some(n.iterations))?);
+		let secret_store = Box::new(secret_store(dir, Some(n.kdf))?);
 		let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
 		let new_account = acc_provider.new_account(&password).map_err(|e| format!("Could not create new account: {}", e))?;
 		Ok(format!("0x{:x}", new_account))
@@ -150,6 +194,71 @@ mod command {
 			Err(err) => Err(format!("Import geth accounts failed. {}", err))
 		}
 	}
+
+	fn passwords(password_files: &[String]) -> Result<Vec<Password>, String> {
+		match passwords_from_files(password_files)? {
+			ref passwords if passwords.is_empty() => Ok(vec![password_prompt()?]),
+			passwords => Ok(passwords),
+		}
+	}
+
+	/// Opens account provider over given keys directory, opening `vault` with the first matching password.
+	fn account_provider(path: String, spec: SpecType, vault: &Option<String>, passwords: &[Password]) -> Result<AccountProvider, String> {
+		let dir = Box::new(keys_dir(path, spec)?);
+		let secret_store = Box::new(secret_store(dir, None)?);
+		let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
+		if let Some(ref vault) = *vault {
+			if !passwords.iter().any(|password| acc_provider.open_vault(vault, password).is_ok()) {
+				return Err(format!("Could not open vault {}: none of the passwords match.", vault));
+			}
+		}
+		Ok(acc_provider)
+	}
+
+	fn export_keystore(e: ExportKeystore) -> Result<String, String> {
+		let passwords = passwords(&e.password_files)?;
+		let acc_provider = account_provider(e.path, e.spec, &e.vault, &passwords)?;
+		let vault = e.vault.unwrap_or_default();
+		let archive = acc_provider.export_vault(&vault, &passwords[0]).map_err(|e| format!("Could not export keystore: {}", e))?;
+
+		match e.file {
+			Some(file) => {
+				fs::write(&file, &archive).map_err(|e| format!("Could not write archive to {}: {}", file, e))?;
+				Ok(format!("Keystore exported to {}", file))
+			},
+			None => {
+				io::stdout().write_all(&archive).map_err(|e| format!("Could not write archive: {}", e))?;
+				Ok(String::new())
+			},
+		}
+	}
+
+	fn restore_keystore(r: RestoreKeystore) -> Result<String, String> {
+		let passwords = passwords(&r.password_files)?;
+		let archive = fs::read(&r.file).map_err(|e| format!("Could not read archive {}: {}", r.file, e))?;
+		let acc_provider = account_provider(r.path, r.spec, &r.vault, &passwords)?;
+		let vault = r.vault.unwrap_or_default();
+		let imported = acc_provider.import_vault(&vault, &archive, &passwords[0]).map_err(|e| format!("Could not import keystore: {}", e))?;
+
+		Ok(format!("{} account(s) imported", imported.len()))
+	}
+
+	fn reencrypt_keystore(r: ReencryptKeystore) -> Result<String, String> {
+		let passwords = passwords(&r.password_files)?;
+		let acc_provider = account_provider(r.path, r.spec, &r.vault, &passwords)?;
+		let vault = r.vault.unwrap_or_default();
+		let result = acc_provider.reencrypt_accounts(&vault, &passwords, r.kdf, |done, total| {
+			print!("\rRe-encrypted {}/{} keys", done, total);
+			let _ = io::stdout().flush();
+		}).map_err(|e| format!("Could not re-encrypt keystore: {}", e))?;
+		println!();
+
+		let mut report = format!("{} account(s) re-encrypted, {} already up to date", result.reencrypted.len(), result.unchanged.len());
+		for account in result.locked {
+			report.push_str(&format!("\nCould not unlock 0x{:x}: none of the passwords match", account.address));
+		}
+		Ok(report)
+	}
 }
 
 #[cfg(feature = "accounts")]
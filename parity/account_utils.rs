@@ -14,13 +14,19 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fs;
 use std::sync::Arc;
 
 use dir::Directories;
+use ethcore::client::Client;
 use ethereum_types::Address;
 use ethkey::Password;
+use hash_fetch::fetch::Client as FetchClient;
+use io::IoHandler;
+use parity_rpc::signer::RemoteSigner;
+use types::io_message::ClientIoMessage;
 
-use params::{SpecType, AccountsConfig};
+use params::{SpecType, AccountsConfig, RemoteSignerConfig};
 
 #[cfg(not(feature = "accounts"))]
 mod accounts {
@@ -55,6 +61,10 @@ mod accounts {
 	pub fn accounts_list(_account_provider: Arc<AccountProvider>) -> Arc<dyn Fn() -> Vec<Address> + Send + Sync> {
 		Arc::new(|| vec![])
 	}
+
+	pub fn session_relocker(_account_provider: Arc<AccountProvider>) -> Option<Arc<dyn IoHandler<ClientIoMessage<Client>> + Send>> {
+		None
+	}
 }
 
 #[cfg(feature = "accounts")]
@@ -62,7 +72,9 @@ mod accounts {
 	use super::*;
 	use upgrade::upgrade_key_location;
 	use ethereum_types::{H160, H256};
+	use io::{IoContext, TimerToken};
 	use std::str::FromStr;
+	use std::time::Duration;
 
 	pub use accounts::AccountProvider;
 
@@ -86,9 +98,12 @@ mod accounts {
 					H160::from_str("00a329c0648769a73afac7f9381e08fb43dbea72").expect("the string is valid hex; qed"),
 				],
 			},
+			unlock_session_duration: Some(cfg.unlock_session),
+			unlock_session_durations: cfg.unlock_sessions.clone(),
+			unlock_idle_timeout: cfg.unlock_idle_timeout,
 		};
 
-		let ethstore = EthStore::open_with_iterations(dir, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e))?;
+		let ethstore = EthStore::open_with_kdf(dir, cfg.kdf).map_err(|e| format!("Could not open keys directory: {}", e))?;
 		if cfg.refresh_time > 0 {
 			ethstore.set_refresh_time(::std::time::Duration::from_secs(cfg.refresh_time));
 		}
@@ -102,9 +117,9 @@ mod accounts {
 			insert_dev_account(&account_provider);
 		}
 
-		for a in cfg.unlocked_accounts {
+		for a in &cfg.unlocked_accounts {
 			// Check if the account exists
-			if !account_provider.has_account(a) {
+			if !account_provider.has_account(*a) {
 				return Err(format!("Account {} not found for the current chain. {}", a, build_create_account_hint(spec, &dirs.keys)));
 			}
 
@@ -112,10 +127,12 @@ mod accounts {
 			if passwords.is_empty() {
 				return Err(format!("No password found to unlock account {}. {}", a, VERIFY_PASSWORD_HINT));
 			}
+		}
 
-			if !passwords.iter().any(|p| account_provider.unlock_account_permanently(a, (*p).clone()).is_ok()) {
-				return Err(format!("No valid password to unlock account {}. {}", a, VERIFY_PASSWORD_HINT));
-			}
+		// decrypting key files is slow, so spread it across the cores (the provider bounds the memory used)
+		let failed = account_provider.unlock_accounts_permanently(&cfg.unlocked_accounts, passwords, ::num_cpus::get());
+		if let Some(a) = failed.first() {
+			return Err(format!("No valid password to unlock account {}. {}", a, VERIFY_PASSWORD_HINT));
 		}
 
 		Ok(account_provider)
@@ -220,6 +237,29 @@ mod accounts {
 		Arc::new(move || account_provider.accounts().unwrap_or_default())
 	}
 
+	const RELOCK_TIMER: TimerToken = 0;
+
+	/// Periodically locks accounts whose unlock session has expired.
+	struct SessionRelocker(Arc<AccountProvider>);
+
+	impl IoHandler<ClientIoMessage<Client>> for SessionRelocker {
+		fn initialize(&self, io: &IoContext<ClientIoMessage<Client>>) {
+			io.register_timer(RELOCK_TIMER, Duration::from_secs(1)).expect("Error registering timer");
+		}
+
+		fn timeout(&self, _io: &IoContext<ClientIoMessage<Client>>, timer: TimerToken) {
+			if timer == RELOCK_TIMER {
+				for address in self.0.relock_expired() {
+					info!("Unlock session of account {:?} has expired, account locked.", address);
+				}
+			}
+		}
+	}
+
+	pub fn session_relocker(account_provider: Arc<AccountProvider>) -> Option<Arc<dyn IoHandler<ClientIoMessage<Client>> + Send>> {
+		Some(Arc::new(SessionRelocker(account_provider)))
+	}
+
 	fn insert_dev_account(account_provider: &AccountProvider) {
 		let secret = parity_crypto::publickey::Secret::from_str("4d5db4107d237df6a3d58ee5f70ae63d73d7658d4026f2eefd2f204c81682cb7".into()).expect("Valid account;qed");
 		let dev_account = parity_crypto::publickey::KeyPair::from_secret(secret.clone()).expect("Valid secret produces valid key;qed");
@@ -243,6 +283,37 @@ mod accounts {
 	}
 }
 
+/// Connect to the external signer, if configured.
+pub fn remote_signer(cfg: Option<&RemoteSignerConfig>, fetch: FetchClient) -> Result<Option<Arc<RemoteSigner>>, String> {
+	let cfg = match cfg {
+		Some(cfg) => cfg,
+		None => return Ok(None),
+	};
+
+	let token = match cfg.token_file {
+		Some(ref path) => Some(fs::read_to_string(path)
+			.map_err(|e| format!("Unable to read remote signer token from {}: {}", path, e))?
+			.trim()
+			.to_owned()),
+		None => None,
+	};
+
+	let signer = RemoteSigner::connect(&cfg.endpoint, token, fetch)?;
+	match signer.accounts() {
+		Ok(accounts) => info!("Using remote signer at {} with {} account(s)", cfg.endpoint, accounts.len()),
+		Err(e) => warn!("Remote signer at {} is not available yet: {}", cfg.endpoint, e),
+	}
+
+	Ok(Some(Arc::new(signer)))
+}
+
+pub fn remote_accounts_list(signer: Arc<RemoteSigner>) -> Arc<dyn Fn() -> Vec<Address> + Send + Sync> {
+	Arc::new(move || signer.accounts().unwrap_or_else(|e| {
+		warn!("Unable to list remote signer accounts: {}", e);
+		signer.known_accounts()
+	}))
+}
+
 pub use self::accounts::{
 	AccountProvider,
 	prepare_account_provider,
@@ -250,4 +321,5 @@ pub use self::accounts::{
 	miner_author,
 	private_tx_signer,
 	accounts_list,
+	session_relocker,
 };
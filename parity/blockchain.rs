@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::str::from_utf8;
-use std::{io, fs};
-use std::io::{BufReader, BufRead};
+use std::cmp;
+use std::str::{from_utf8, FromStr};
+use std::{io, fs, thread};
+use std::io::{BufReader, BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
@@ -26,20 +28,30 @@ use hash::{keccak, KECCAK_NULL_RLP};
 use ethereum_types::{U256, H256, Address};
 use bytes::ToPretty;
 use rlp::PayloadInfo;
-use client_traits::{BlockChainReset, Nonce, Balance, BlockChainClient, ImportExportBlocks};
+use client_traits::{BlockChainReset, Nonce, Balance, BlockChainClient, ChainInfo, ImportExportBlocks};
 use ethcore::{
-	client::{DatabaseCompactionProfile},
+	client::{AnalyticsTable, Client, ColumnType, DatabaseCompactionProfile, analytics_rows},
 	miner::Miner,
 };
 use ethcore_service::ClientService;
+use parquet::basic::{Compression, ConvertedType, Repetition, Type as PhysicalType};
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::{FileWriter, SerializedFileWriter};
+use parquet::schema::types::{ColumnPath, Type as ParquetType};
 use cache::CacheConfig;
 use informant::{Informant, FullNodeInformantData};
+#[cfg(unix)]
+use local_sync::LocalSyncClient;
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
 use helpers::{to_client_config, execute_upgrades};
 use dir::Directories;
 use user_defaults::UserDefaults;
 use ethcore_private_tx;
 use db;
+#[cfg(unix)]
+use snapshot_cmd::restore_using;
 use ansi_term::Colour;
 use types::{
 	ids::BlockId,
@@ -55,7 +67,10 @@ pub enum BlockchainCmd {
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
-	Reset(ResetBlockchain)
+	ExportAnalytics(ExportAnalytics),
+	Reset(ResetBlockchain),
+	Tier(TierBlockchain),
+	Stats(StatsBlockchain),
 }
 
 #[derive(Debug, PartialEq)]
@@ -72,6 +87,25 @@ pub struct ResetBlockchain {
 	pub num: u32,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct TierBlockchain {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub cold_path: Option<String>,
+	pub parts: Vec<db::ColdPart>,
+	pub hot_blocks: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StatsBlockchain {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct KillBlockchain {
 	pub spec: SpecType,
@@ -85,6 +119,8 @@ pub struct ImportBlockchain {
 	pub cache_config: CacheConfig,
 	pub dirs: Directories,
 	pub file_path: Option<String>,
+	/// Local sync socket of another instance to import from instead of a file.
+	pub from_ipc: Option<String>,
 	pub format: Option<DataFormat>,
 	pub pruning: Pruning,
 	pub pruning_history: u64,
@@ -139,11 +175,62 @@ pub struct ExportState {
 	pub max_round_blocks_to_import: usize,
 }
 
+/// Format of analytics export files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnalyticsFormat {
+	/// Comma-separated values with a header row.
+	Csv,
+	/// Snappy-compressed Parquet with typed columns, absent values being null.
+	Parquet,
+}
+
+impl AnalyticsFormat {
+	fn extension(&self) -> &'static str {
+		match *self {
+			AnalyticsFormat::Csv => "csv",
+			AnalyticsFormat::Parquet => "parquet",
+		}
+	}
+}
+
+impl FromStr for AnalyticsFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"csv" => Ok(AnalyticsFormat::Csv),
+			"parquet" => Ok(AnalyticsFormat::Parquet),
+			x => Err(format!("Invalid analytics export format: {}. Must be either 'csv' or 'parquet'.", x)),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportAnalytics {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub out_dir: String,
+	pub format: AnalyticsFormat,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub fat_db: Switch,
+	pub tracing: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+	pub threads: usize,
+	pub max_round_blocks_to_import: usize,
+}
+
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
 		BlockchainCmd::Import(import_cmd) => {
-			if import_cmd.light {
+			if import_cmd.light && import_cmd.from_ipc.is_some() {
+				Err("Importing from another instance is not supported for light clients".into())
+			} else if import_cmd.light {
 				execute_import_light(import_cmd)
 			} else {
 				execute_import(import_cmd)
@@ -151,7 +238,10 @@ pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 		}
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
 		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
+		BlockchainCmd::ExportAnalytics(export_cmd) => execute_export_analytics(export_cmd),
 		BlockchainCmd::Reset(reset_cmd) => execute_reset(reset_cmd),
+		BlockchainCmd::Tier(tier_cmd) => execute_tier(tier_cmd),
+		BlockchainCmd::Stats(stats_cmd) => execute_stats(stats_cmd),
 	}
 }
 
@@ -207,7 +297,7 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 	// initialize database.
 	let db = db::open_db_light(
 		&client_path.to_str().expect("DB path could not be converted to string."),
-		&cmd.cache_config,
+		cmd.cache_config.blockchain() as usize,
 		&cmd.compaction,
 	).map_err(|e| format!("Failed to open database: {:?}", e))?;
 
@@ -363,7 +453,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 
-	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, None);
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -389,9 +479,10 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
-	let instream: Box<dyn io::Read> = match cmd.file_path {
-		Some(f) => Box::new(fs::File::open(&f).map_err(|_| format!("Cannot open given file: {}", f))?),
-		None => Box::new(io::stdin()),
+	let instream: Option<Box<dyn io::Read>> = match (cmd.from_ipc.is_some(), cmd.file_path) {
+		(true, _) => None,
+		(false, Some(f)) => Some(Box::new(fs::File::open(&f).map_err(|_| format!("Cannot open given file: {}", f))?)),
+		(false, None) => Some(Box::new(io::stdin())),
 	};
 
 	let informant = Arc::new(Informant::new(
@@ -407,7 +498,11 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 
 	service.register_io_handler(informant).map_err(|_| "Unable to register informant handler".to_owned())?;
 
-	client.import_blocks(instream, cmd.format)?;
+	match (instream, cmd.from_ipc) {
+		(Some(instream), _) => client.import_blocks(instream, cmd.format)?,
+		(None, Some(path)) => import_from_local_sync(&service, &path)?,
+		(None, None) => unreachable!("instream is only None when importing from a local sync socket; qed"),
+	}
 
 	// save user defaults
 	user_defaults.pruning = algorithm;
@@ -430,6 +525,29 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+/// Restore the latest snapshot of another instance if our database is empty, then import its blocks.
+#[cfg(unix)]
+fn import_from_local_sync(service: &ClientService, path: &str) -> Result<(), String> {
+	let client = service.client();
+	let source = LocalSyncClient::connect(path)?;
+
+	if let Some(manifest) = source.snapshot() {
+		if client.chain_info().best_block_number == 0 {
+			info!("Restoring snapshot at #{} from {}", manifest.block_number, path);
+			restore_using(service.snapshot_service(), &source, true)?;
+		}
+	}
+
+	let from = client.chain_info().best_block_number + 1;
+	info!("Importing blocks from #{} from {}", from, path);
+	client.import_blocks(Box::new(source.blocks(from)?), Some(DataFormat::Binary))
+}
+
+#[cfg(not(unix))]
+fn import_from_local_sync(_service: &ClientService, _path: &str) -> Result<(), String> {
+	Err("Importing from another instance is only supported on unix platforms".into())
+}
+
 fn start_client(
 	dirs: Directories,
 	spec: SpecType,
@@ -498,7 +616,7 @@ fn start_client(
 		None
 	);
 
-	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, None);
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -639,6 +757,239 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	Ok(())
 }
 
+fn execute_export_analytics(cmd: ExportAnalytics) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.cache_config,
+		false,
+		cmd.max_round_blocks_to_import,
+	)?;
+	let client = service.client();
+
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+	if from > to {
+		return Err("From block is after to block".into());
+	}
+	fs::create_dir_all(&cmd.out_dir).map_err(|e| format!("Cannot create directory {}: {}", cmd.out_dir, e))?;
+
+	// Every thread exports a contiguous part of the range into its own files.
+	let threads = cmp::max(cmd.threads as u64, 1);
+	let part_len = (to - from) / threads + 1;
+	let parts = (0..threads)
+		.map(|i| from + i * part_len)
+		.take_while(|start| *start <= to)
+		.map(|start| {
+			let client = client.clone();
+			let out_dir = PathBuf::from(&cmd.out_dir);
+			let format = cmd.format;
+			let end = cmp::min(start + part_len - 1, to);
+			thread::Builder::new()
+				.name(format!("Analytics export {}-{}", start, end))
+				.spawn(move || export_analytics_range(&client, &out_dir, format, start, end))
+				.map_err(|e| format!("Error starting export thread: {}", e))
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	for part in parts {
+		part.join().map_err(|_| "Export thread panicked".to_owned())??;
+	}
+
+	info!("Export completed.");
+	Ok(())
+}
+
+fn export_analytics_range(client: &Client, out_dir: &Path, format: AnalyticsFormat, from: u64, to: u64) -> Result<(), String> {
+	let mut files = AnalyticsTable::ALL.iter()
+		.map(|table| {
+			let path = out_dir.join(format!("{}-{}-{}.{}", table.name(), from, to, format.extension()));
+			Ok((*table, AnalyticsWriter::create(&path, format, *table)?))
+		})
+		.collect::<Result<Vec<_>, String>>()?;
+
+	for number in from..=to {
+		let rows = analytics_rows(client, number).ok_or_else(|| format!("Block #{} could not be found", number))?;
+		for &mut (table, ref mut out) in &mut files {
+			for row in rows.table(table) {
+				out.write_row(row)?;
+			}
+		}
+		if (number - from + 1) % 10000 == 0 {
+			info!("#{}", number);
+		}
+	}
+
+	for (_, out) in files {
+		out.finish()?;
+	}
+	Ok(())
+}
+
+/// Writes the rows of one table into a file of the export format.
+enum AnalyticsWriter {
+	Csv(io::BufWriter<fs::File>),
+	Parquet(ParquetTableWriter),
+}
+
+impl AnalyticsWriter {
+	fn create(path: &Path, format: AnalyticsFormat, table: AnalyticsTable) -> Result<Self, String> {
+		let file = fs::File::create(path).map_err(|e| format!("Cannot write to file {}: {}", path.display(), e))?;
+		match format {
+			AnalyticsFormat::Csv => {
+				let mut out = io::BufWriter::new(file);
+				write_csv_row(&mut out, table.columns())?;
+				Ok(AnalyticsWriter::Csv(out))
+			},
+			AnalyticsFormat::Parquet => ParquetTableWriter::new(file, table.name(), table.columns(), table.column_types())
+				.map(AnalyticsWriter::Parquet),
+		}
+	}
+
+	fn write_row(&mut self, row: &[String]) -> Result<(), String> {
+		match *self {
+			AnalyticsWriter::Csv(ref mut out) => write_csv_row(out, row),
+			AnalyticsWriter::Parquet(ref mut out) => out.write_row(row),
+		}
+	}
+
+	fn finish(self) -> Result<(), String> {
+		match self {
+			AnalyticsWriter::Csv(mut out) => out.flush().map_err(|e| format!("Write error: {}", e)),
+			AnalyticsWriter::Parquet(out) => out.finish(),
+		}
+	}
+}
+
+fn write_csv_row<W: Write, S: AsRef<str>>(out: &mut W, row: &[S]) -> Result<(), String> {
+	let line = row.iter()
+		.map(|field| {
+			let field = field.as_ref();
+			if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+				format!("\"{}\"", field.replace('"', "\"\""))
+			} else {
+				field.to_owned()
+			}
+		})
+		.collect::<Vec<_>>()
+		.join(",");
+	writeln!(out, "{}", line).map_err(|e| format!("Write error: {}", e))
+}
+
+/// Number of rows buffered for each row group of a Parquet file.
+const PARQUET_ROW_GROUP_ROWS: usize = 32 * 1024;
+
+/// Writes the rows of a table into a Parquet file, one row group at a time.
+///
+/// Integers are stored as unsigned 64-bit integers and hashes, addresses and bytes as binary.
+/// 256-bit quantities exceed the integer and decimal types of Parquet, they are stored as
+/// decimal strings like text. Absent values are nulls.
+struct ParquetTableWriter {
+	file: SerializedFileWriter<fs::File>,
+	types: &'static [ColumnType],
+	rows: Vec<Vec<String>>,
+	row_group_rows: usize,
+}
+
+impl ParquetTableWriter {
+	fn new(file: fs::File, name: &str, columns: &[&str], types: &'static [ColumnType]) -> Result<Self, String> {
+		let mut fields = columns.iter()
+			.zip(types)
+			.map(|(column, column_type)| {
+				let builder = match *column_type {
+					ColumnType::Integer => ParquetType::primitive_type_builder(column, PhysicalType::INT64)
+						.with_converted_type(ConvertedType::UINT_64),
+					ColumnType::Bytes => ParquetType::primitive_type_builder(column, PhysicalType::BYTE_ARRAY),
+					ColumnType::Quantity | ColumnType::Text => ParquetType::primitive_type_builder(column, PhysicalType::BYTE_ARRAY)
+						.with_converted_type(ConvertedType::UTF8),
+				};
+				builder.with_repetition(Repetition::OPTIONAL).build().map(Arc::new)
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Invalid schema of table {}: {}", name, e))?;
+		let schema = ParquetType::group_type_builder(name)
+			.with_fields(&mut fields)
+			.build()
+			.map_err(|e| format!("Invalid schema of table {}: {}", name, e))?;
+		// dictionary encoding of binary columns can't hash empty values, e.g. the input of plain transfers.
+		let properties = columns.iter()
+			.zip(types)
+			.filter(|&(_, column_type)| *column_type == ColumnType::Bytes)
+			.fold(WriterProperties::builder().set_compression(Compression::SNAPPY), |properties, (column, _)| {
+				properties.set_column_dictionary_enabled(ColumnPath::from(*column), false)
+			})
+			.build();
+		let file = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+			.map_err(|e| format!("Write error: {}", e))?;
+
+		Ok(ParquetTableWriter {
+			file,
+			types,
+			rows: Vec::new(),
+			row_group_rows: PARQUET_ROW_GROUP_ROWS,
+		})
+	}
+
+	fn write_row(&mut self, row: &[String]) -> Result<(), String> {
+		self.rows.push(row.to_vec());
+		if self.rows.len() >= self.row_group_rows {
+			self.write_row_group()?;
+		}
+		Ok(())
+	}
+
+	fn write_row_group(&mut self) -> Result<(), String> {
+		let mut row_group = self.file.next_row_group().map_err(|e| format!("Write error: {}", e))?;
+		for (index, column_type) in self.types.iter().enumerate() {
+			let mut column = row_group.next_column()
+				.map_err(|e| format!("Write error: {}", e))?
+				.ok_or_else(|| "Write error: missing column".to_owned())?;
+			// empty fields are nulls, only the others have a value.
+			let fields = self.rows.iter().map(|row| &row[index]);
+			let levels = fields.clone().map(|field| if field.is_empty() { 0 } else { 1 }).collect::<Vec<_>>();
+			let values = fields.filter(|field| !field.is_empty());
+			match (&mut column, *column_type) {
+				(ColumnWriter::Int64ColumnWriter(ref mut writer), ColumnType::Integer) => {
+					let values = values
+						.map(|field| field.parse::<u64>().map(|value| value as i64))
+						.collect::<Result<Vec<_>, _>>()
+						.map_err(|e| format!("Invalid integer in column {}: {}", index, e))?;
+					writer.write_batch(&values, Some(&levels), None).map_err(|e| format!("Write error: {}", e))?;
+				},
+				(ColumnWriter::ByteArrayColumnWriter(ref mut writer), ColumnType::Bytes) => {
+					let values = values
+						.map(|field| field.trim_start_matches("0x").from_hex::<Vec<u8>>().map(ByteArray::from))
+						.collect::<Result<Vec<_>, _>>()
+						.map_err(|e| format!("Invalid hex in column {}: {}", index, e))?;
+					writer.write_batch(&values, Some(&levels), None).map_err(|e| format!("Write error: {}", e))?;
+				},
+				(ColumnWriter::ByteArrayColumnWriter(ref mut writer), _) => {
+					let values = values.map(|field| ByteArray::from(field.as_bytes().to_vec())).collect::<Vec<_>>();
+					writer.write_batch(&values, Some(&levels), None).map_err(|e| format!("Write error: {}", e))?;
+				},
+				_ => return Err(format!("Write error: unexpected writer of column {}", index)),
+			}
+			row_group.close_column(column).map_err(|e| format!("Write error: {}", e))?;
+		}
+		self.file.close_row_group(row_group).map_err(|e| format!("Write error: {}", e))?;
+		self.rows.clear();
+		Ok(())
+	}
+
+	fn finish(mut self) -> Result<(), String> {
+		if !self.rows.is_empty() {
+			self.write_row_group()?;
+		}
+		self.file.close().map(|_| ()).map_err(|e| format!("Write error: {}", e))
+	}
+}
+
 fn execute_reset(cmd: ResetBlockchain) -> Result<(), String> {
 	let service = start_client(
 		cmd.dirs,
@@ -661,6 +1012,45 @@ fn execute_reset(cmd: ResetBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+fn execute_tier(cmd: TierBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir);
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, &cmd.compaction)?;
+	cmd.dirs.create_dirs(false, false)?;
+
+	// the layout is recorded in the database, it must not depend on the working directory.
+	let cold_path = match cmd.cold_path {
+		Some(path) => Some(::std::env::current_dir().map_err(|e| e.to_string())?.join(path)),
+		None => None,
+	};
+	db::tier_database(&db_dirs.client_path(algorithm), &cmd.compaction, cold_path, cmd.parts, cmd.hot_blocks)?;
+	info!("Database parts moved.");
+	Ok(())
+}
+
+fn execute_stats(cmd: StatsBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir);
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, &cmd.compaction)?;
+
+	for (path, stats) in db::database_stats(&db_dirs.client_path(algorithm), &cmd.compaction)? {
+		println!("{}", path.display());
+		for (column, keys) in stats.keys {
+			println!("  {:<22}{:>16} keys", column, keys);
+		}
+		println!("  {:<22}{:>16} bytes", "memory", stats.memory);
+	}
+	Ok(())
+}
+
 pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
 	let genesis_hash = spec.genesis_header().hash();
@@ -678,7 +1068,11 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 
 #[cfg(test)]
 mod test {
-	use super::DataFormat;
+	use std::fs;
+	use parquet::file::reader::{FileReader, SerializedFileReader};
+	use tempfile::TempDir;
+	use ethcore::client::ColumnType;
+	use super::{AnalyticsFormat, DataFormat, ParquetTableWriter, write_csv_row};
 
 	#[test]
 	fn test_data_format_parsing() {
@@ -686,4 +1080,45 @@ mod test {
 		assert_eq!(DataFormat::Binary, "bin".parse().unwrap());
 		assert_eq!(DataFormat::Hex, "hex".parse().unwrap());
 	}
+
+	#[test]
+	fn test_analytics_format_parsing() {
+		assert_eq!(AnalyticsFormat::Csv, "csv".parse().unwrap());
+		assert_eq!(AnalyticsFormat::Parquet, "parquet".parse().unwrap());
+		assert!("json".parse::<AnalyticsFormat>().is_err());
+	}
+
+	#[test]
+	fn test_csv_row_quoting() {
+		let mut out = Vec::new();
+		write_csv_row(&mut out, &["1", "0xab", "a,b", "say \"hi\""]).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), "1,0xab,\"a,b\",\"say \"\"hi\"\"\"\n");
+	}
+
+	#[test]
+	fn test_parquet_table_writing() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("logs.parquet");
+		let types = &[ColumnType::Integer, ColumnType::Bytes, ColumnType::Quantity, ColumnType::Text];
+		let mut writer = ParquetTableWriter::new(fs::File::create(&path).unwrap(), "logs", &["log_index", "topic0", "value", "error"], types).unwrap();
+		writer.row_group_rows = 2;
+		for row in &[["0", "0xab", "1", "Out of gas"], ["1", "", "2", ""], ["2", "0x", "", ""]] {
+			writer.write_row(&row.iter().map(|field| field.to_string()).collect::<Vec<_>>()).unwrap();
+		}
+		writer.finish().unwrap();
+
+		let reader = SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+		assert_eq!(reader.metadata().num_row_groups(), 2);
+		assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+		let rows = reader.get_row_iter(None).unwrap().map(|row| row.to_string()).collect::<Vec<_>>();
+		assert_eq!(rows, vec![
+			r#"{log_index: 0, topic0: [171], value: "1", error: "Out of gas"}"#,
+			r#"{log_index: 1, topic0: null, value: "2", error: null}"#,
+			r#"{log_index: 2, topic0: [], value: null, error: null}"#,
+		]);
+
+		let mut writer = ParquetTableWriter::new(fs::File::create(&path).unwrap(), "logs", &["log_index"], &[ColumnType::Integer]).unwrap();
+		writer.write_row(&["0x1".to_owned()]).unwrap();
+		assert!(writer.finish().is_err());
+	}
 }
@@ -0,0 +1,85 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background generation of canonical hash tries on full nodes serving light clients.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use client_traits::{BlockChainClient, BlockInfo, ChainInfo};
+use ethcore::client::Client;
+use light::cht::BlockInfo as ChtBlockInfo;
+use light::ChtStore;
+
+/// How often the generator checks whether new CHTs are due.
+const GENERATION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Generates CHTs as the chain progresses, stops when dropped.
+pub struct ChtService {
+	stop: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for ChtService {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::SeqCst);
+		if let Some(handle) = self.handle.take() {
+			handle.thread().unpark();
+			let _ = handle.join();
+		}
+	}
+}
+
+/// Start the thread generating CHTs for the given client into the store.
+pub fn start_cht_service(client: Arc<Client>, store: Arc<ChtStore>) -> Result<ChtService, String> {
+	let stop = Arc::new(AtomicBool::new(false));
+	let handle = {
+		let stop = stop.clone();
+		thread::Builder::new()
+			.name("CHT generation".into())
+			.spawn(move || {
+				let block_info = |id| {
+					let header = client.block_header(id)?;
+					let total_difficulty = client.block_total_difficulty(id)?;
+					Some(ChtBlockInfo {
+						hash: header.hash(),
+						parent_hash: header.parent_hash(),
+						total_difficulty,
+					})
+				};
+
+				while !stop.load(Ordering::SeqCst) {
+					let best_block = client.chain_info().best_block_number;
+					let mut generated = 0;
+					while !stop.load(Ordering::SeqCst) && store.generate_next(best_block, &block_info).is_some() {
+						generated += 1;
+					}
+					if generated > 0 {
+						info!(target: "cht", "Generated {} CHTs, {} stored", generated, store.len());
+					}
+					thread::park_timeout(GENERATION_INTERVAL);
+				}
+			})
+			.map_err(|e| format!("Error starting CHT generation thread: {}", e))?
+	};
+
+	Ok(ChtService {
+		stop,
+		handle: Some(handle),
+	})
+}
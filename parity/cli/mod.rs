@@ -58,6 +58,45 @@ usage! {
 				"<PATH>...",
 				"Path to the accounts",
 			}
+
+			CMD cmd_account_export
+			{
+				"Export accounts of the given --chain (default: mainnet) to an archive encrypted with the first --password",
+
+				ARG arg_account_export_vault: (Option<String>) = None,
+				"--vault=[NAME]",
+				"Export accounts of vault NAME instead of the root keystore. The vault is opened with the first matching --password.",
+
+				ARG arg_account_export_file: (Option<String>) = None,
+				"[FILE]",
+				"Path to the archive file (default: standard output)",
+			}
+
+			CMD cmd_account_restore
+			{
+				"Import accounts from an archive created by `parity account export` to the given --chain (default: mainnet)",
+
+				ARG arg_account_restore_vault: (Option<String>) = None,
+				"--vault=[NAME]",
+				"Import accounts to vault NAME instead of the root keystore. The vault is opened with the first matching --password.",
+
+				ARG arg_account_restore_file: (Option<String>) = None,
+				"<FILE>",
+				"Path to the archive file, decrypted with the first --password",
+			}
+
+			CMD cmd_account_reencrypt
+			{
+				"Re-encrypt accounts of the given --chain (default: mainnet) with new key derivation parameters, unlocking each with the first matching --password",
+
+				ARG arg_account_reencrypt_vault: (Option<String>) = None,
+				"--vault=[NAME]",
+				"Re-encrypt accounts of vault NAME instead of the root keystore. The vault is opened with the first matching --password.",
+
+				ARG arg_account_reencrypt_kdf: (String) = "scrypt:262144:8:1",
+				"--kdf=[KDF]",
+				"Key derivation function to use, either pbkdf2:ITERATIONS or scrypt:N:R:P.",
+			}
 		}
 
 		CMD cmd_wallet
@@ -85,6 +124,10 @@ usage! {
 			ARG arg_import_file: (Option<String>) = None,
 			"[FILE]",
 			"Path to the file to import from",
+
+			ARG arg_import_from_ipc: (Option<String>) = None,
+			"--from-ipc=[SOCKET]",
+			"Import from the local sync socket of another instance on this host instead of a file. An empty database is bootstrapped from the latest snapshot of the other instance first.",
 		}
 
 		CMD cmd_export
@@ -144,6 +187,31 @@ usage! {
 				"[FILE]",
 				"Path to the exported file",
 			}
+
+			CMD cmd_export_analytics
+			{
+				"Export blocks, transactions, receipts, logs and traces from the given --chain database (default: mainnet) into one file per table and range, for ingestion into analytics warehouses. Traces are only exported if the chain was synced with --tracing on.",
+
+				ARG arg_export_analytics_format: (String) = "csv",
+				"--format=[FORMAT]",
+				"Export in a given format. FORMAT must be either 'csv' or 'parquet'. Parquet columns are typed: integers, binary hashes and bytes, and decimal strings for 256-bit quantities, with absent values stored as nulls.",
+
+				ARG arg_export_analytics_from: (String) = "0",
+				"--from=[BLOCK]",
+				"Export from block BLOCK, which may be an index or hash.",
+
+				ARG arg_export_analytics_to: (String) = "latest",
+				"--to=[BLOCK]",
+				"Export to (including) block BLOCK, which may be an index, hash or latest.",
+
+				ARG arg_export_analytics_threads: (Option<usize>) = None,
+				"--threads=[NUM]",
+				"Number of threads the block range is split across. (default: number of CPUs)",
+
+				ARG arg_export_analytics_dir: (Option<String>) = None,
+				"[DIR]",
+				"Path to the directory the files are written to. (default: current directory)",
+			}
 		}
 
 		CMD cmd_signer
@@ -197,6 +265,23 @@ usage! {
 			ARG arg_restore_file: (Option<String>) = None,
 			"[FILE]",
 			"Path to the file to restore from",
+
+			ARG arg_restore_from_url: (Option<String>) = None,
+			"--from-url=[URL]",
+			"Download the snapshot from S3-compatible object storage at URL instead of a file. Chunks downloaded by an interrupted restoration are kept. Requests are signed with AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN and AWS_REGION if set.",
+		}
+
+		CMD cmd_snapshot_upload
+		{
+			"Upload the latest local snapshot of the given --chain (default: mainnet) to S3-compatible object storage",
+
+			ARG arg_snapshot_upload_url: (Option<String>) = None,
+			"<URL>",
+			"Bucket URL and key prefix to upload to, e.g. https://bucket.s3.amazonaws.com/mainnet. Chunks already uploaded are skipped. Requests are signed with AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN and AWS_REGION if set.",
+
+			ARG arg_snapshot_upload_file: (Option<String>) = None,
+			"--file=[FILE]",
+			"Upload this snapshot file instead of the latest local snapshot",
 		}
 
 		CMD cmd_tools
@@ -229,6 +314,26 @@ usage! {
 				"Number of blocks to revert",
 			}
 
+			CMD cmd_db_tier {
+				"Move parts of the old blocks of the database of the given --chain (default: mainnet) to a cold database, e.g. on a larger and slower disk, or back to the main one. The node keeps moving blocks as they get old",
+
+				ARG arg_db_tier_cold_path: (Option<String>) = None,
+				"--cold-path=[PATH]",
+				"Directory of the cold database. Required the first time, defaults to the current one afterwards. Parts kept in a previous cold database are moved to the new one.",
+
+				ARG arg_db_tier_parts: (String) = "bodies,receipts,traces",
+				"--parts=[PARTS]",
+				"Parts kept in the cold database, the others are moved back to the main one. PARTS is a comma-separated list of headers, bodies, receipts, traces and checkpoints, or none. Checkpoints are moved whatever their age.",
+
+				ARG arg_db_tier_hot_blocks: (u64) = 90000u64,
+				"--hot-blocks=[NUM]",
+				"Number of the most recent blocks whose parts stay in the main database.",
+			}
+
+			CMD cmd_db_stats {
+				"Print the estimated number of keys of every column and the memory usage of the database of the given --chain (default: mainnet)",
+			}
+
 		}
 
 		CMD cmd_export_hardcoded_sync
@@ -236,6 +341,104 @@ usage! {
 			"Print the hashed light clients headers of the given --chain (default: mainnet) in a JSON format. To be used as hardcoded headers in a genesis file.",
 		}
 
+		CMD cmd_config
+		{
+			"Validate or print the configuration",
+
+			CMD cmd_config_check {
+				"Validate the command line and config file against the given --chain (default: mainnet), reporting conflicting and deprecated options",
+			}
+
+			CMD cmd_config_dump {
+				"Print the effective configuration, merged from the command line, the config file and the defaults, as TOML",
+			}
+		}
+
+		CMD cmd_spec
+		{
+			"Generate, validate and convert chain specs",
+
+			CMD cmd_spec_new
+			{
+				"Generate a new chain spec from the given options, or interactively",
+
+				ARG arg_spec_new_name: (Option<String>) = None,
+				"--name=[NAME]",
+				"Name of the chain (default: Private chain)",
+
+				ARG arg_spec_new_engine: (Option<String>) = None,
+				"--engine=[ENGINE]",
+				"Consensus engine of the chain: ethash, clique, authority-round or instant-seal (default: instant-seal)",
+
+				ARG arg_spec_new_chain_id: (Option<u64>) = None,
+				"--chain-id=[ID]",
+				"Chain and network id (default: 17)",
+
+				ARG arg_spec_new_premine: (Option<String>) = None,
+				"--premine=[ALLOCATIONS]",
+				"Comma separated ADDRESS=WEI balances of the genesis block, WEI is decimal or 0x prefixed hex",
+
+				ARG arg_spec_new_validators: (Option<String>) = None,
+				"--validators=[ADDRESSES]",
+				"Comma separated addresses of the initial validators of clique and authority-round chains",
+
+				ARG arg_spec_new_block_time: (Option<u64>) = None,
+				"--block-time=[SECONDS]",
+				"Block time of clique and authority-round chains (default: 5)",
+
+				ARG arg_spec_new_gas_limit: (Option<u64>) = None,
+				"--gas-limit=[GAS]",
+				"Gas limit of the genesis block (default: 8000000)",
+
+				ARG arg_spec_new_forks: (Option<String>) = None,
+				"--forks=[SCHEDULE]",
+				"Comma separated FORK=BLOCK activations, forks are homestead, tangerine-whistle, spurious-dragon, byzantium, constantinople, petersburg, istanbul and muir-glacier. Forks before a listed one activate along with it, forks after the last listed one never do (default: istanbul=0)",
+
+				FLAG flag_spec_new_interactive: (bool) = false,
+				"--interactive",
+				"Prompt for the options not given",
+
+				ARG arg_spec_new_file: (Option<String>) = None,
+				"[FILE]",
+				"File to write the spec to, printed if not given",
+			}
+
+			CMD cmd_spec_check
+			{
+				"Validate a chain spec, reporting errors along with how to fix them and suspicious settings",
+
+				ARG arg_spec_check_file: (Option<String>) = None,
+				"<FILE>",
+				"Chain spec to validate",
+			}
+
+			CMD cmd_spec_from_geth
+			{
+				"Convert a Geth genesis file into a chain spec",
+
+				ARG arg_spec_from_geth_genesis: (Option<String>) = None,
+				"<GENESIS>",
+				"Geth genesis file to convert",
+
+				ARG arg_spec_from_geth_spec: (Option<String>) = None,
+				"[SPEC]",
+				"File to write the spec to, printed if not given",
+			}
+
+			CMD cmd_spec_to_geth
+			{
+				"Convert an Ethash or Clique chain spec into a Geth genesis file",
+
+				ARG arg_spec_to_geth_spec: (Option<String>) = None,
+				"<SPEC>",
+				"Chain spec to convert",
+
+				ARG arg_spec_to_geth_genesis: (Option<String>) = None,
+				"[GENESIS]",
+				"File to write the genesis to, printed if not given",
+			}
+		}
+
 		// CMD removed in 2.0
 
 		CMD cmd_dapp
@@ -318,10 +521,26 @@ usage! {
 			"--db-path=[PATH]",
 			"Specify the database directory path",
 
+			ARG arg_profile: (Option<String>) = None, or |c: &Config| c.parity.as_ref()?.profile.clone(),
+			"--profile=[NAME]",
+			"Run as the named profile, to run several instances on one host. Each profile uses its own directory under the base path and shifts all ports by 100 per profile, on top of --ports-shift.",
+
 			ARG arg_sync_until: (Option<u64>) = None, or |c: &Config| c.parity.as_ref()?.sync_until.clone(),
 			"--sync-until=[NUM]",
 			"Sync until the given block has been imported, then enter offline mode. Intended for debug/benchmarking only.",
 
+			ARG arg_max_reorg_depth: (Option<u64>) = None, or |c: &Config| c.parity.as_ref()?.max_reorg_depth.clone(),
+			"--max-reorg-depth=[NUM]",
+			"Keep the current head instead of switching to a branch which would retract more than NUM blocks. The branch is still imported, the block import is paused, an error is logged, a reorgHeld webhook event is posted and the chain_held_reorg_depth metric is set until it is accepted with parity_acceptReorg, or rejected with parity_resumeSync.",
+
+			ARG arg_fork_url: (Option<String>) = None, or |c: &Config| c.parity.as_ref()?.fork_url.clone(),
+			"--fork-url=[URL]",
+			"Fork the state of a live network into a local InstantSeal chain such as --chain=dev. Accounts and storage the local chain never touched are read from the JSON-RPC endpoint at URL. The fork is pinned in the database on first start.",
+
+			ARG arg_fork_block: (Option<u64>) = None, or |c: &Config| c.parity.as_ref()?.fork_block.clone(),
+			"--fork-block=[NUM]",
+			"Block of the remote network the state is forked at, the latest one if not given. Requires --fork-url.",
+
 		["Convenience Options"]
 			FLAG flag_unsafe_expose: (bool) = false, or |c: &Config| c.misc.as_ref()?.unsafe_expose,
 			"--unsafe-expose",
@@ -344,6 +563,10 @@ usage! {
 			"--keys-iterations=[NUM]",
 			"Specify the number of iterations to use when deriving key from the password (bigger is more secure)",
 
+			ARG arg_keys_kdf: (Option<String>) = None, or |c: &Config| c.account.as_ref()?.keys_kdf.clone(),
+			"--keys-kdf=[KDF]",
+			"Specify the key derivation function for new key files, either pbkdf2:ITERATIONS or scrypt:N:R:P. Overrides --keys-iterations.",
+
 			ARG arg_accounts_refresh: (u64) = 5u64, or |c: &Config| c.account.as_ref()?.refresh_time.clone(),
 			"--accounts-refresh=[TIME]",
 			"Specify the cache time of accounts read from disk. If you manage thousands of accounts set this to 0 to disable refresh.",
@@ -352,6 +575,18 @@ usage! {
 			"--unlock=[ACCOUNTS]",
 			"Unlock ACCOUNTS for the duration of the execution. ACCOUNTS is a comma-delimited list of addresses.",
 
+			ARG arg_unlock_session: (u64) = 300u64, or |c: &Config| c.account.as_ref()?.unlock_session.clone(),
+			"--unlock-session=[SECS]",
+			"Specify how long an account unlocked via personal_unlockAccount without an explicit duration stays unlocked.",
+
+			ARG arg_unlock_sessions: (Option<String>) = None, or |c: &Config| c.account.as_ref()?.unlock_sessions.as_ref().map(|vec| vec.join(",")),
+			"--unlock-sessions=[ACCOUNTS]",
+			"Override --unlock-session for particular accounts. ACCOUNTS is a comma-delimited list of ADDRESS:SECS pairs.",
+
+			ARG arg_unlock_idle_timeout: (u64) = 0u64, or |c: &Config| c.account.as_ref()?.unlock_idle_timeout.clone(),
+			"--unlock-idle-timeout=[SECS]",
+			"Lock unlocked accounts, including the ones unlocked with --unlock, after SECS seconds without signing. 0 disables the idle timeout.",
+
 			ARG arg_enable_signing_queue: (bool) = false, or |c: &Config| c.account.as_ref()?.enable_signing_queue,
 			"--enable-signing-queue=[BOOLEAN]",
 			"Enables the signing queue for external transaction signing either via CLI or personal_unlockAccount, turned off by default.",
@@ -360,6 +595,14 @@ usage! {
 			"--password=[FILE]...",
 			"Provide a file containing a password for unlocking an account. Leading and trailing whitespace is trimmed.",
 
+			ARG arg_remote_signer: (Option<String>) = None, or |c: &Config| c.account.as_ref()?.remote_signer.clone(),
+			"--remote-signer=[URL]",
+			"Forward account listing and signing to an external signer implementing the Clef API. URL is an http(s):// endpoint or a path to an IPC socket.",
+
+			ARG arg_remote_signer_token_file: (Option<String>) = None, or |c: &Config| c.account.as_ref()?.remote_signer_token_file.clone(),
+			"--remote-signer-token-file=[FILE]",
+			"Provide a file containing a bearer token used to authenticate with an HTTP remote signer.",
+
 		["Private Transactions Options"]
 			FLAG flag_private_enabled: (bool) = false, or |c: &Config| c.private_tx.as_ref()?.enabled,
 			"--private-tx-enabled",
@@ -445,12 +688,28 @@ usage! {
 
 			ARG arg_nat: (String) = "any", or |c: &Config| c.network.as_ref()?.nat.clone(),
 			"--nat=[METHOD]",
-			"Specify method to use for determining public address. Must be one of: any, none, upnp, extip:<IP>.",
+			"Specify method to use for determining public address. Must be one of: any, none, upnp, natpmp, pcp, extip:<IP>.",
 
 			ARG arg_allow_ips: (String) = "all", or |c: &Config| c.network.as_ref()?.allow_ips.clone(),
 			"--allow-ips=[FILTER]",
 			"Filter outbound connections. Must be one of: private - connect to private network IP addresses only; public - connect to public network IP addresses only; all - connect to any IP address.",
 
+			ARG arg_max_peers_per_subnet: (Option<u16>) = None, or |c: &Config| c.network.as_ref()?.max_peers_per_subnet.clone(),
+			"--max-peers-per-subnet=[NUM]",
+			"Allow up to NUM non-reserved peers from the same /24 IPv4 or /48 IPv6 subnet.",
+
+			ARG arg_max_peers_per_asn: (Option<u16>) = None, or |c: &Config| c.network.as_ref()?.max_peers_per_asn.clone(),
+			"--max-peers-per-asn=[NUM]",
+			"Allow up to NUM non-reserved peers from the same autonomous system. Requires --peer-asn-map.",
+
+			ARG arg_peer_asn_map: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.peer_asn_map.clone(),
+			"--peer-asn-map=[FILE]",
+			"Provide a file mapping networks to autonomous systems, one '<network> <asn>' pair per line, e.g. '1.1.1.0/24 AS13335'.",
+
+			ARG arg_max_peers_per_client: (Option<u16>) = None, or |c: &Config| c.network.as_ref()?.max_peers_per_client.clone(),
+			"--max-peers-per-client=[NUM]",
+			"Allow up to NUM non-reserved peers running the same client implementation.",
+
 			ARG arg_max_pending_peers: (u16) = 64u16, or |c: &Config| c.network.as_ref()?.max_pending_peers.clone(),
 			"--max-pending-peers=[NUM]",
 			"Allow up to NUM pending connections.",
@@ -471,6 +730,22 @@ usage! {
 			"--reserved-peers=[FILE]",
 			"Provide a file containing enodes, one per line. These nodes will always have a reserved slot on top of the normal maximum peers.",
 
+			ARG arg_node_permissions_contract: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.node_permissions_contract.clone(),
+			"--node-permissions-contract=[ADDRESS]",
+			"Override the node permissioning contract from our chain. Connections are only allowed with nodes permitted by the contract.",
+
+			ARG arg_node_permissions_file: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.node_permissions_file.clone(),
+			"--node-permissions-file=[FILE]",
+			"Provide a file containing enodes or node ids, one per line. Connections are only allowed with the listed nodes. The file is re-read on new blocks when it changes.",
+
+			ARG arg_node_permissions_overrides: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.node_permissions_overrides.as_ref().map(|vec| vec.join(",")),
+			"--node-permissions-overrides=[NODES]",
+			"Always allow connections with these nodes, regardless of the node permissioning contract or file. NODES should be comma-delimited enodes or node ids.",
+
+			ARG arg_local_sync_socket: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.local_sync_socket.clone(),
+			"--local-sync-socket=[PATH]",
+			"Serve the latest snapshot and the blocks of this node over a unix socket at PATH, so other instances on this host can bootstrap from it with 'parity import --from-ipc'.",
+
 			CHECK |args: &Args| {
 				if let (Some(max_peers), Some(min_peers)) = (args.arg_max_peers, args.arg_min_peers) {
 					if min_peers > max_peers {
@@ -486,6 +761,10 @@ usage! {
 			"--jsonrpc-allow-missing-blocks",
 			"RPC calls will return 'null' instead of an error if ancient block sync is still in progress and the block information requested could not be found",
 
+			FLAG flag_jsonrpc_reconstruct_logs: (bool) = false, or |c: &Config| c.rpc.as_ref()?.reconstruct_logs.clone(),
+			"--jsonrpc-reconstruct-logs",
+			"eth_getLogs re-executes blocks whose receipts were pruned, rebuilding their parent state from a state checkpoint if it's pruned too. Such logs are returned with logType 'reconstructed'. Requests fail if neither the state nor a checkpoint is available. This is slow.",
+
 			FLAG flag_no_jsonrpc: (bool) = false, or |c: &Config| c.rpc.as_ref()?.disable.clone(),
 			"--no-jsonrpc",
 			"Disable the HTTP JSON-RPC API server.",
@@ -508,7 +787,7 @@ usage! {
 
 			ARG arg_jsonrpc_apis: (String) = "web3,eth,pubsub,net,parity,private,parity_pubsub,traces,rpc,parity_transactions_pool", or |c: &Config| c.rpc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-apis=[APIS]",
-			"Specify the APIs available through the HTTP JSON-RPC interface using a comma-delimited list of API names. Possible names are: all, safe, debug, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
+			"Specify the APIs available through the HTTP JSON-RPC interface using a comma-delimited list of API names. Possible names are: all, safe, debug, evm, clique, engine, bundle, miner, mining, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
 
 			ARG arg_jsonrpc_hosts: (String) = "none", or |c: &Config| c.rpc.as_ref()?.hosts.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-hosts=[HOSTS]",
@@ -522,6 +801,26 @@ usage! {
 			"--jsonrpc-server-threads=[NUM]",
 			"Enables multiple threads handling incoming connections for HTTP JSON-RPC server.",
 
+			ARG arg_jsonrpc_heavy_threads: (usize) = 4usize, or |c: &Config| c.rpc.as_ref()?.heavy_threads.clone(),
+			"--jsonrpc-heavy-threads=[NUM]",
+			"Number of threads running long RPC requests (trace_*, eth_getLogs, debug_trace*) for all JSON-RPC servers. Requests are cancelled when their client disconnects.",
+
+			ARG arg_jsonrpc_heavy_queue: (usize) = 256usize, or |c: &Config| c.rpc.as_ref()?.heavy_queue.clone(),
+			"--jsonrpc-heavy-queue=[NUM]",
+			"Number of long RPC requests queued or running above which new ones are rejected with a `too many requests` error.",
+
+			ARG arg_jsonrpc_pools: (Option<String>) = None, or |c: &Config| c.rpc.as_ref()?.pools.as_ref().map(|vec| vec.join(",")),
+			"--jsonrpc-pools=[POOLS]",
+			"Give kinds of long RPC requests a pool of their own, as a comma-separated list of KIND:THREADS:QUEUE. Kinds are traces, logs and debug, e.g. \"traces:2:32,logs:4:512\". Other kinds share the pool configured with --jsonrpc-heavy-threads and --jsonrpc-heavy-queue.",
+
+			ARG arg_jsonrpc_dedup_ttl: (Option<u64>) = None, or |c: &Config| c.rpc.as_ref()?.dedup_ttl,
+			"--jsonrpc-dedup-ttl=[MS]",
+			"Execute identical calls of eth_call, eth_estimateGas, eth_getBalance, eth_getCode, eth_getStorageAt and eth_getTransactionCount at the same chain head only once: concurrent calls share the execution and repeats within MS milliseconds get its response. 0 only shares concurrent executions. Calls on the pending block are always executed.",
+
+			ARG arg_jsonrpc_call_cache: (usize) = 0usize, or |c: &Config| c.rpc.as_ref()?.call_cache.clone(),
+			"--jsonrpc-call-cache=[NUM]",
+			"Keep the results of up to NUM eth_calls on the latest block and answer identical calls from them until an imported block modifies an account the call read. Calls reading the block number, timestamp, coinbase, difficulty, gas limit or block hashes are only kept for the block they were made on. 0 disables the cache.",
+
 			ARG arg_jsonrpc_cors: (String) = "none", or |c: &Config| c.rpc.as_ref()?.cors.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-cors=[URL]",
 			"Specify CORS header for HTTP JSON-RPC API responses. Special options: \"all\", \"none\".",
@@ -532,7 +831,19 @@ usage! {
 
 			ARG arg_poll_lifetime: (u32) = 60u32, or |c: &Config| c.rpc.as_ref()?.poll_lifetime.clone(),
 			"--poll-lifetime=[S]",
-			"Set the RPC filter lifetime to S seconds. The filter has to be polled at least every S seconds , otherwise it is removed.",
+			"Set the RPC filter lifetime to S seconds. The filter has to be polled at least every S seconds , otherwise it is removed. 0 keeps filters until they are uninstalled.",
+
+			ARG arg_poll_max_per_origin: (Option<usize>) = None, or |c: &Config| c.rpc.as_ref()?.poll_max_per_origin.clone(),
+			"--poll-max-per-origin=[NUM]",
+			"Reject new RPC filters from a client which already has NUM filters installed. WebSocket and IPC clients are told apart by their session. HTTP clients are told apart by their Origin and User-Agent headers, which they can change at will, so only --poll-max-total bounds their filters.",
+
+			ARG arg_poll_max_total: (usize) = 10000usize, or |c: &Config| c.rpc.as_ref()?.poll_max_total.clone(),
+			"--poll-max-total=[NUM]",
+			"Reject new RPC filters once NUM filters are installed by all clients together.",
+
+			FLAG flag_poll_persist: (bool) = false, or |c: &Config| c.rpc.as_ref()?.poll_persist.clone(),
+			"--poll-persist",
+			"Keep the installed RPC filters across restarts. Filters are saved in the chain's data directory when installed or removed, periodically while they are polled, and at shutdown. After a crash, changes reported since the last save may be reported again.",
 
 		["API and Console Options – WebSockets"]
 			FLAG flag_no_ws: (bool) = false, or |c: &Config| c.websockets.as_ref()?.disable.clone(),
@@ -549,7 +860,7 @@ usage! {
 
 			ARG arg_ws_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,private,traces,rpc,parity_transactions_pool", or |c: &Config| c.websockets.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--ws-apis=[APIS]",
-			"Specify the JSON-RPC APIs available through the WebSockets interface using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
+			"Specify the JSON-RPC APIs available through the WebSockets interface using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
 
 			ARG arg_ws_origins: (String) = "parity://*,chrome-extension://*,moz-extension://*", or |c: &Config| c.websockets.as_ref()?.origins.as_ref().map(|vec| vec.join(",")),
 			"--ws-origins=[URL]",
@@ -578,7 +889,83 @@ usage! {
 
 			ARG arg_ipc_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,parity_accounts,private,traces,rpc,parity_transactions_pool", or |c: &Config| c.ipc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--ipc-apis=[APIS]",
-			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
+			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
+
+		["API and Console Options – Engine API"]
+			FLAG flag_engine_api: (bool) = false, or |c: &Config| c.engine_api.as_ref()?.enable.clone(),
+			"--engine-api",
+			"Start the Engine API server even if the chain specification has no terminal total difficulty.",
+
+			ARG arg_engine_api_port: (u16) = 8551u16, or |c: &Config| c.engine_api.as_ref()?.port.clone(),
+			"--engine-api-port=[PORT]",
+			"Specify the port portion of the JWT-authenticated Engine API server used by the consensus client.",
+
+			ARG arg_engine_api_interface: (String) = "local", or |c: &Config| c.engine_api.as_ref()?.interface.clone(),
+			"--engine-api-interface=[IP]",
+			"Specify the hostname portion of the Engine API server, IP should be an interface's IP address, or all (all interfaces) or local.",
+
+			ARG arg_jwt_secret: (Option<String>) = None, or |c: &Config| c.engine_api.as_ref()?.jwt_secret.clone(),
+			"--jwt-secret=[PATH]",
+			"Specify the file holding the hex-encoded 32-byte secret shared with the consensus client. A new secret is generated at $BASE/jwtsecret if none is given.",
+
+		["Metrics"]
+			FLAG flag_metrics: (bool) = false, or |c: &Config| c.metrics.as_ref()?.enable.clone(),
+			"--metrics",
+			"Enable the Prometheus metrics endpoint at /metrics.",
+
+			ARG arg_metrics_prefix: (String) = "oe_", or |c: &Config| c.metrics.as_ref()?.prefix.clone(),
+			"--metrics-prefix=[PREFIX]",
+			"Prefix of every exported metric name.",
+
+			ARG arg_metrics_port: (u16) = 3000u16, or |c: &Config| c.metrics.as_ref()?.port.clone(),
+			"--metrics-port=[PORT]",
+			"Specify the port portion of the metrics server.",
+
+			ARG arg_metrics_interface: (String) = "local", or |c: &Config| c.metrics.as_ref()?.interface.clone(),
+			"--metrics-interface=[IP]",
+			"Specify the hostname portion of the metrics server, IP should be an interface's IP address, or all (all interfaces) or local.",
+
+			ARG arg_otlp_endpoint: (Option<String>) = None, or |c: &Config| c.metrics.as_ref()?.otlp_endpoint.clone(),
+			"--otlp-endpoint=[URL]",
+			"Send tracing spans of block import and RPC handling to the OpenTelemetry collector at URL using OTLP/HTTP, e.g. http://localhost:4318.",
+
+			ARG arg_otlp_service_name: (String) = "openethereum", or |c: &Config| c.metrics.as_ref()?.otlp_service_name.clone(),
+			"--otlp-service-name=[NAME]",
+			"Service name reported with exported tracing spans.",
+
+		["IPFS Options"]
+			FLAG flag_ipfs_api: (bool) = false, or |c: &Config| c.ipfs.as_ref()?.enable.clone(),
+			"--ipfs-api",
+			"Enable the IPFS gateway serving block headers, transactions, receipts and trie nodes by their CIDs at /ipfs and /api/v0/block/get.",
+
+			ARG arg_ipfs_api_port: (u16) = 5001u16, or |c: &Config| c.ipfs.as_ref()?.port.clone(),
+			"--ipfs-api-port=[PORT]",
+			"Specify the port portion of the IPFS gateway.",
+
+			ARG arg_ipfs_api_interface: (String) = "local", or |c: &Config| c.ipfs.as_ref()?.interface.clone(),
+			"--ipfs-api-interface=[IP]",
+			"Specify the hostname portion of the IPFS gateway, IP should be an interface's IP address, or all (all interfaces) or local.",
+
+		["Webhook Options"]
+			ARG arg_webhook_urls: (Option<String>) = None, or |c: &Config| c.webhooks.as_ref()?.urls.as_ref().map(|vec| vec.join(",")),
+			"--webhook-urls=[URLS]",
+			"Post new head, reorg, finalized block and log events as JSON to the given comma-separated URLs.",
+
+			ARG arg_webhook_secret_file: (Option<String>) = None, or |c: &Config| c.webhooks.as_ref()?.secret_file.clone(),
+			"--webhook-secret-file=[FILE]",
+			"Sign webhook notifications with the secret read from FILE, the hex-encoded HMAC-SHA256 of the body is sent in the X-Signature header.",
+
+			ARG arg_webhook_log_addresses: (Option<String>) = None, or |c: &Config| c.webhooks.as_ref()?.log_addresses.as_ref().map(|vec| vec.join(",")),
+			"--webhook-log-addresses=[ADDRESSES]",
+			"Post logs emitted by the given comma-separated contract addresses.",
+
+			ARG arg_webhook_log_topics: (Option<String>) = None, or |c: &Config| c.webhooks.as_ref()?.log_topics.as_ref().map(|vec| vec.join(",")),
+			"--webhook-log-topics=[TOPICS]",
+			"Only post logs whose first topic is one of the given comma-separated topics.",
+
+			ARG arg_webhook_max_retries: (u32) = 5u32, or |c: &Config| c.webhooks.as_ref()?.max_retries.clone(),
+			"--webhook-max-retries=[NUM]",
+			"Number of times a failed webhook notification is retried with exponential backoff.",
 
 		["Light Client Options"]
 			ARG arg_on_demand_response_time_window: (Option<u64>) = None, or |c: &Config| c.light.as_ref()?.on_demand_response_time_window,
@@ -601,6 +988,10 @@ usage! {
 			"--on-demand-consecutive-failures=[TIMES]",
 			"Specify light client the number of failures for a request until it gets exponentially backed off",
 
+			ARG arg_on_demand_historical_fan_out: (Option<usize>) = None, or |c: &Config| c.light.as_ref()?.on_demand_historical_fan_out,
+			"--on-demand-historical-fan-out=[PEERS]",
+			"Specify the number of peers light client requests for historical state are sent to at once. The first valid response is used",
+
 		["Secret Store Options"]
 			FLAG flag_no_secretstore: (bool) = false, or |c: &Config| c.secretstore.as_ref()?.disable.clone(),
 			"--no-secretstore",
@@ -687,6 +1078,10 @@ usage! {
 			"--reseal-on-uncle",
 			"Force the node to author new blocks when a new uncle block is imported.",
 
+			FLAG flag_prefer_own_uncles: (bool) = false, or |c: &Config| c.mining.as_ref()?.prefer_own_uncles.clone(),
+			"--prefer-own-uncles",
+			"Prefer uncles authored by this node over other candidates when filling the uncle slots of a new block.",
+
 			FLAG flag_remove_solved: (bool) = false, or |c: &Config| c.mining.as_ref()?.remove_solved.clone(),
 			"--remove-solved",
 			"Move solved blocks from the work package queue instead of cloning them. This gives a slightly faster import speed, but means that extra solutions submitted for the same work package will go unused.",
@@ -699,6 +1094,10 @@ usage! {
 			"--tx-queue-no-early-reject",
 			"Disables transaction queue optimization to early reject transactions below minimal effective gas price. This allows local transactions to always enter the pool, despite it being full, but requires additional ecrecover on every transaction.",
 
+			FLAG flag_tx_filter_authoring: (bool) = false, or |c: &Config| c.mining.as_ref()?.tx_filter_authoring.clone(),
+			"--tx-filter-authoring",
+			"Apply the rules of --tx-filter-file also to transactions included in authored blocks, not only to transactions entering the queue.",
+
 			FLAG flag_refuse_service_transactions: (bool) = false, or |c: &Config| c.mining.as_ref()?.refuse_service_transactions.clone(),
 			"--refuse-service-transactions",
 			"Always refuse service transactions.",
@@ -713,7 +1112,7 @@ usage! {
 
 			// For backward compatibility; Stratum should be enabled if the config file
 			// contains a `[stratum]` section and it is not explicitly disabled (disable = true)
-			FLAG flag_stratum: (bool) = false, or |c: &Config| Some(c.stratum.as_ref().map(|s| s.disable != Some(true)).unwrap_or(false)),
+			FLAG flag_stratum: (bool) = false, or |c: &Config| c.stratum.as_ref()?.disable.or(Some(false)).map(|d| !d),
 			"--stratum",
 			"Run Stratum server for miner push notification.",
 
@@ -729,6 +1128,14 @@ usage! {
 			"--reseal-max-period=[MS]",
 			"Specify the maximum time since last block to enable force-sealing. MS is time measured in milliseconds.",
 
+			ARG arg_max_uncles: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.max_uncles.clone(),
+			"--max-uncles=[NUM]",
+			"Include at most NUM uncles in authored blocks. The engine limit is used if this is unset or higher.",
+
+			ARG arg_uncle_min_age: (u64) = 1u64, or |c: &Config| c.mining.as_ref()?.uncle_min_age.clone(),
+			"--uncle-min-age=[BLOCKS]",
+			"Only include uncles which are at least BLOCKS blocks older than the block being authored.",
+
 			ARG arg_work_queue_size: (usize) = 20usize, or |c: &Config| c.mining.as_ref()?.work_queue_size.clone(),
 			"--work-queue-size=[ITEMS]",
 			"Specify the number of historical work packages which are kept cached lest a solution is found for them later. High values take more memory but result in fewer unusable solutions.",
@@ -757,6 +1164,10 @@ usage! {
 			"--gas-cap=[GAS]",
 			"A cap on how large we will raise the gas limit per block due to transaction volume.",
 
+			ARG arg_gas_limit_target: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.gas_limit_target.clone(),
+			"--gas-limit-target=[GAS]",
+			"Block gas limit to converge to when sealing new blocks, moving by at most the parent gas limit divided by the gas limit bound divisor (1024 on most chains) per block. Overrides --gas-floor-target and --gas-cap, and can be changed at runtime with miner_setGasLimit.",
+
 			ARG arg_tx_queue_mem_limit: (u32) = 4u32, or |c: &Config| c.mining.as_ref()?.tx_queue_mem_limit.clone(),
 			"--tx-queue-mem-limit=[MB]",
 			"Maximum amount of memory that can be used by the transaction queue. Setting this parameter to 0 disables limiting.",
@@ -769,7 +1180,35 @@ usage! {
 			"--tx-queue-per-sender=[LIMIT]",
 			"Maximum number of transactions per sender in the queue. By default it's 1% of the entire queue, but not less than 16.",
 
-			ARG arg_tx_queue_locals: (Option<String>) = None, or |c: &Config| helpers::join_set(c.mining.as_ref()?.tx_queue_locals.as_ref()),
+			ARG arg_tx_queue_pending_per_sender: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_pending_per_sender.clone(),
+			"--tx-queue-pending-per-sender=[LIMIT]",
+			"Maximum number of pending (ready to be mined) transactions per sender in the queue. Local transactions are exempt.",
+
+			ARG arg_tx_queue_future_per_sender: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_future_per_sender.clone(),
+			"--tx-queue-future-per-sender=[LIMIT]",
+			"Maximum number of future (nonce-gapped) transactions per sender in the queue. Local transactions are exempt.",
+
+			ARG arg_tx_queue_future: (Option<usize>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_future.clone(),
+			"--tx-queue-future=[LIMIT]",
+			"Maximum number of future (nonce-gapped) transactions in the entire queue. When exceeded, future transactions of the sender holding the most of them are evicted first.",
+
+			ARG arg_tx_queue_local_ttl: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_local_ttl.clone(),
+			"--tx-queue-local-ttl=[SECS]",
+			"Maximum time in seconds a local transaction can stay in the queue. By default local transactions never expire.",
+
+			ARG arg_tx_queue_remote_ttl: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_remote_ttl.clone(),
+			"--tx-queue-remote-ttl=[SECS]",
+			"Maximum time in seconds a transaction received from the network can stay in the queue. By default transactions never expire.",
+
+			ARG arg_tx_queue_local_rebroadcast: (Option<u64>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_local_rebroadcast.clone(),
+			"--tx-queue-local-rebroadcast=[SECS]",
+			"Re-send pending local transactions to all peers every SECS seconds, even to the ones they were already sent to.",
+
+			ARG arg_tx_filter_file: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_filter_file.clone(),
+			"--tx-filter-file=[PATH]",
+			"JSON file with banned and allowed senders, recipients and function selectors (bannedSenders, allowedSenders, bannedRecipients, allowedRecipients, bannedSelectors, allowedSelectors). Transactions violating the rules are rejected by the queue. The file is reloaded when it changes.",
+
+			ARG arg_tx_queue_locals: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_queue_locals.as_ref().and_then(|set| helpers::join_set(Some(set))),
 			"--tx-queue-locals=[ACCOUNTS]",
 			"Specify local accounts for which transactions are prioritized in the queue. ACCOUNTS is a comma-delimited list of addresses.",
 
@@ -777,6 +1216,10 @@ usage! {
 			"--tx-queue-strategy=[S]",
 			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price",
 
+			ARG arg_tx_queue_price_bump: (String) = "12.5", or |c: &Config| c.mining.as_ref()?.tx_queue_price_bump.clone(),
+			"--tx-queue-price-bump=[PERCENT]",
+			"Minimal percentage by which a transaction has to raise the gas price of a queued transaction with the same sender and nonce to replace it. Transactions with a separate fee cap have to raise both the fee cap and the priority fee.",
+
 			ARG arg_stratum_interface: (String) = "local", or |c: &Config| c.stratum.as_ref()?.interface.clone(),
 			"--stratum-interface=[IP]",
 			"Interface address for Stratum server.",
@@ -801,6 +1244,10 @@ usage! {
 			"--engine-signer=[ADDRESS]",
 			"Specify the address which should be used to sign consensus messages and issue blocks. Relevant only to non-PoW chains.",
 
+			ARG arg_sealing_journal: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.sealing_journal.clone(),
+			"--sealing-journal=[PATH]",
+			"Specify the file recording the blocks signed by the engine signer. A block conflicting with a recorded one in the same step or block number is never signed. Keep it on another disk than the data directory to protect against double signing after the data directory is restored. Defaults to a file in the data directory, outside of the databases.",
+
 			ARG arg_tx_gas_limit: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_gas_limit.clone(),
 			"--tx-gas-limit=[GAS]",
 			"Apply a limit of GAS as the maximum amount of gas a single transaction may have for it to be mined.",
@@ -821,6 +1268,30 @@ usage! {
 			"--stratum-secret=[STRING]",
 			"Secret for authorizing Stratum server for peers.",
 
+			ARG arg_stratum_share_difficulty: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.share_difficulty.clone(),
+			"--stratum-share-difficulty=[DIFF]",
+			"Enable variable share difficulty for Stratum workers, starting at DIFF. Every worker gets its own share target, retargeted towards one share per --stratum-share-time. Only shares meeting the block difficulty are submitted as blocks.",
+
+			ARG arg_stratum_min_share_difficulty: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.min_share_difficulty.clone(),
+			"--stratum-min-share-difficulty=[DIFF]",
+			"Minimal share difficulty of Stratum workers when variable share difficulty is enabled.",
+
+			ARG arg_stratum_share_time: (u64) = 15u64, or |c: &Config| c.stratum.as_ref()?.share_time.clone(),
+			"--stratum-share-time=[SECS]",
+			"Desired time between two shares of a single Stratum worker when variable share difficulty is enabled.",
+
+			ARG arg_stratum_tls_port: (Option<u16>) = None, or |c: &Config| c.stratum.as_ref()?.tls_port.clone(),
+			"--stratum-tls-port=[PORT]",
+			"Port for Stratum server to accept TLS connections on, in addition to --stratum-port. Requires --stratum-tls-cert and --stratum-tls-key.",
+
+			ARG arg_stratum_tls_cert: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.tls_cert.clone(),
+			"--stratum-tls-cert=[FILE]",
+			"PEM encoded certificate chain for Stratum TLS connections.",
+
+			ARG arg_stratum_tls_key: (Option<String>) = None, or |c: &Config| c.stratum.as_ref()?.tls_key.clone(),
+			"--stratum-tls-key=[FILE]",
+			"PEM encoded private key (PKCS#8 or RSA) for Stratum TLS connections.",
+
 			ARG arg_max_round_blocks_to_import: (usize) = 12usize, or |c: &Config| c.mining.as_ref()?.max_round_blocks_to_import.clone(),
 			"--max-round-blocks-to-import=[S]",
 			"Maximal number of blocks to import for each import round.",
@@ -851,6 +1322,30 @@ usage! {
 			"--log-file=[FILENAME]",
 			"Specify a filename into which logging should be appended.",
 
+			ARG arg_log_format: (String) = "text", or |c: &Config| c.misc.as_ref()?.log_format.clone(),
+			"--log-format=[FORMAT]",
+			"Specify the format of log output. FORMAT can be one of: text - Human readable lines; json - One JSON object per line with timestamp, level, target, thread and message fields.",
+
+			ARG arg_log_file_max_size: (u64) = 0u64, or |c: &Config| c.misc.as_ref()?.log_file_max_size.clone(),
+			"--log-file-max-size=[MB]",
+			"Rotate the log file once it grows beyond MB megabytes. 0 disables rotation.",
+
+			ARG arg_log_file_count: (usize) = 5usize, or |c: &Config| c.misc.as_ref()?.log_file_count.clone(),
+			"--log-file-count=[NUM]",
+			"Number of rotated log files to keep when --log-file-max-size is set.",
+
+			ARG arg_shutdown_timeout: (u64) = 300u64, or |c: &Config| c.misc.as_ref()?.shutdown_timeout.clone(),
+			"--shutdown-timeout=[SECS]",
+			"Maximum time to spend shutting down gracefully. Shutdown phases still pending when it elapses are skipped.",
+
+			ARG arg_shutdown_phases: (String) = "queue,journal,import,fsync", or |c: &Config| c.misc.as_ref()?.shutdown_phases.clone(),
+			"--shutdown-phases=[PHASES]",
+			"Comma-separated phases run on shutdown once RPC and networking are stopped. PHASES can contain: queue - Import blocks left in the verification queue; journal - Persist local transactions; import - Finish the block import in progress; fsync - Flush the databases to disk. Use none to skip all of them.",
+
+			ARG arg_dump_root_mismatches: (Option<String>) = None, or |c: &Config| c.misc.as_ref()?.dump_root_mismatches.clone(),
+			"--dump-root-mismatches=[DIR]",
+			"Write a JSON report to DIR for every block rejected because its state root, receipts root, gas used or log bloom disagree with the result of executing it. The report lists the outcome of every transaction and the accounts the block changed.",
+
 		["Footprint Options"]
 			FLAG flag_scale_verifiers: (bool) = false, or |c: &Config| c.footprint.as_ref()?.scale_verifiers.clone(),
 			"--scale-verifiers",
@@ -872,6 +1367,14 @@ usage! {
 			"--pruning-memory=[MB]",
 			"The ideal amount of memory in megabytes to use to store recent states. As many states as possible will be kept within this limit, and at least --pruning-history states will always be kept.",
 
+			ARG arg_pruning_checkpoints: (u64) = 0u64, or |c: &Config| c.footprint.as_ref()?.pruning_checkpoints.clone(),
+			"--pruning-checkpoints=[BLOCKS]",
+			"When pruning is active, keep a copy of the state every BLOCKS blocks. Pruned states are rebuilt on demand by re-executing the blocks since the nearest earlier copy. 0 keeps no copies.",
+
+			ARG arg_pruning_checkpoints_kept: (usize) = 16usize, or |c: &Config| c.footprint.as_ref()?.pruning_checkpoints_kept.clone(),
+			"--pruning-checkpoints-kept=[NUM]",
+			"Keep the NUM most recent copies of the state made with --pruning-checkpoints and delete older ones. 0 keeps all copies.",
+
 			ARG arg_cache_size_db: (u32) = 128u32, or |c: &Config| c.footprint.as_ref()?.cache_size_db.clone(),
 			"--cache-size-db=[MB]",
 			"Override database cache size.",
@@ -888,9 +1391,37 @@ usage! {
 			"--cache-size-state=[MB]",
 			"Specify the maximum size of memory to use for the state cache.",
 
+			ARG arg_state_cache_ratios: (String) = "25,60,15", or |c: &Config| c.footprint.as_ref()?.state_cache_ratios.clone(),
+			"--state-cache-ratios=[PCT]",
+			"Split of the state cache memory between the accounts, storage and code caches, as three comma-separated percentages.",
+
+			FLAG flag_no_state_prefetch: (bool) = false, or |c: &Config| c.footprint.as_ref()?.state_prefetch.map(|p| !p),
+			"--no-state-prefetch",
+			"Disable loading the accounts touched by verified blocks into the state cache ahead of their import.",
+
+			FLAG flag_commit_pipeline: (bool) = false, or |c: &Config| c.footprint.as_ref()?.commit_pipeline.clone(),
+			"--commit-pipeline",
+			"Write each round of imported blocks to the database while the next round is executed. Blocks whose write failed are dropped and imported again.",
+
+			ARG arg_commit_batch_blocks: (usize) = 0usize, or |c: &Config| c.footprint.as_ref()?.commit_batch_blocks.clone(),
+			"--commit-batch-blocks=[NUM]",
+			"While syncing, write the changes of at least NUM imported blocks to the database at once, trading memory for fewer writes. 0 writes after each round of --max-round-blocks-to-import blocks.",
+
 			ARG arg_db_compaction: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.db_compaction.clone(),
 			"--db-compaction=[TYPE]",
-			"Database compaction type. TYPE may be one of: ssd - suitable for SSDs and fast HDDs; hdd - suitable for slow HDDs; auto - determine automatically.",
+			"Database compaction and tuning type. TYPE may be one of: ssd - suitable for SSDs and fast HDDs; nvme - suitable for NVMe drives, more open files and smaller blocks; hdd - suitable for slow HDDs; low-memory - small memtables and block cache, whatever --cache-size-db; auto - determine automatically.",
+
+			ARG arg_db_rewrite_window: (Option<String>) = None, or |c: &Config| c.footprint.as_ref()?.db_rewrite_window.clone(),
+			"--db-rewrite-window=[WINDOW]",
+			"Rewrite the database in the background (recompression with --db-compression, trace address indexing) only within the daily WINDOW, given as HH:MM-HH:MM in UTC. parity_rewriteDatabase runs the rewrites outside of the window.",
+
+			FLAG flag_db_compression: (bool) = false, or |c: &Config| c.footprint.as_ref()?.db_compression.clone(),
+			"--db-compression",
+			"Store block bodies and receipts compressed with zstd. Existing data is recompressed in the background.",
+
+			FLAG flag_db_statistics: (bool) = false, or |c: &Config| c.footprint.as_ref()?.db_statistics.clone(),
+			"--db-statistics",
+			"Collect the internal statistics of RocksDB, exported by the metrics server. Slows down the database.",
 
 			ARG arg_fat_db: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.fat_db.clone(),
 			"--fat-db=[BOOL]",
@@ -904,6 +1435,18 @@ usage! {
 			"--num-verifiers=[INT]",
 			"Amount of verifier threads to use or to begin with, if verifier auto-scaling is enabled.",
 
+			ARG arg_ethash_pregenerate_epochs: (u64) = 1u64, or |c: &Config| c.footprint.as_ref()?.ethash_pregenerate_epochs.clone(),
+			"--ethash-pregenerate-epochs=[NUM]",
+			"Number of upcoming epochs whose Ethash caches are generated in the background ahead of the epoch change.",
+
+			ARG arg_ethash_cache_memory: (usize) = 512usize, or |c: &Config| c.footprint.as_ref()?.ethash_cache_memory.clone(),
+			"--ethash-cache-memory=[MB]",
+			"Maximum amount of memory used by the Ethash caches kept in memory. The cache of the current epoch is always kept.",
+
+			ARG arg_ethash_light_verification_below: (Option<u64>) = None, or |c: &Config| c.footprint.as_ref()?.ethash_light_verification_below.clone(),
+			"--ethash-light-verification-below=[BLOCK]",
+			"Only fully verify the Ethash seal of a random sample of the blocks below BLOCK, which speeds up syncing old history. The difficulty claimed by every seal is still checked.",
+
 		["Import/export Options"]
 			FLAG flag_no_seal_check: (bool) = false, or |_| None,
 			"--no-seal-check",
@@ -927,6 +1470,23 @@ usage! {
 			"--whisper-pool-size=[MB]",
 			"Does nothing. Whisper has been moved to https://github.com/paritytech/whisper",
 
+		["Gossip Options"]
+			FLAG flag_gossip: (bool) = false, or |c: &Config| c.gossip.as_ref()?.enable.clone(),
+			"--gossip",
+			"Enable the gossip messaging service, a libp2p gossipsub swarm relaying the messages of the shh API. Messages are not encrypted.",
+
+			ARG arg_gossip_interface: (String) = "all", or |c: &Config| c.gossip.as_ref()?.interface.clone(),
+			"--gossip-interface=[IP]",
+			"Network interfaces the gossip swarm listens on. Valid values are 'all', 'local' or [IP].",
+
+			ARG arg_gossip_port: (u16) = 30305u16, or |c: &Config| c.gossip.as_ref()?.port.clone(),
+			"--gossip-port=[PORT]",
+			"Port the gossip swarm listens on.",
+
+			ARG arg_gossip_bootnodes: (Option<String>) = None, or |c: &Config| c.gossip.as_ref()?.bootnodes.as_ref().map(|vec| vec.join(",")),
+			"--gossip-bootnodes=[NODES]",
+			"Comma-separated list of gossip peers to dial at startup, as multiaddresses such as /ip4/10.0.0.1/tcp/30305.",
+
 		["Legacy Options"]
 			// Options that are hidden from config, but are still unique for its functionality.
 
@@ -1143,6 +1703,10 @@ struct Config {
 	rpc: Option<Rpc>,
 	websockets: Option<Ws>,
 	ipc: Option<Ipc>,
+	engine_api: Option<EngineApi>,
+	metrics: Option<Metrics>,
+	ipfs: Option<Ipfs>,
+	webhooks: Option<Webhooks>,
 	dapps: Option<Dapps>,
 	secretstore: Option<SecretStore>,
 	private_tx: Option<PrivateTransactions>,
@@ -1152,6 +1716,7 @@ struct Config {
 	misc: Option<Misc>,
 	stratum: Option<Stratum>,
 	whisper: Option<Whisper>,
+	gossip: Option<Gossip>,
 	light: Option<Light>,
 }
 
@@ -1172,10 +1737,14 @@ struct Operating {
 	db_path: Option<String>,
 	keys_path: Option<String>,
 	identity: Option<String>,
+	profile: Option<String>,
 	light: Option<bool>,
 	no_persistent_txqueue: Option<bool>,
 	no_hardcoded_sync: Option<bool>,
 	sync_until: Option<u64>,
+	max_reorg_depth: Option<u64>,
+	fork_url: Option<String>,
+	fork_block: Option<u64>,
 
 	#[serde(rename = "public_node")]
 	_legacy_public_node: Option<bool>,
@@ -1188,8 +1757,14 @@ struct Account {
 	enable_signing_queue: Option<bool>,
 	password: Option<Vec<String>>,
 	keys_iterations: Option<u32>,
+	keys_kdf: Option<String>,
 	refresh_time: Option<u64>,
+	unlock_session: Option<u64>,
+	unlock_sessions: Option<Vec<String>>,
+	unlock_idle_timeout: Option<u64>,
 	fast_unlock: Option<bool>,
+	remote_signer: Option<String>,
+	remote_signer_token_file: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1235,12 +1810,20 @@ struct Network {
 	max_pending_peers: Option<u16>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
+	max_peers_per_subnet: Option<u16>,
+	max_peers_per_asn: Option<u16>,
+	peer_asn_map: Option<String>,
+	max_peers_per_client: Option<u16>,
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
+	node_permissions_contract: Option<String>,
+	node_permissions_file: Option<String>,
+	node_permissions_overrides: Option<Vec<String>>,
+	local_sync_socket: Option<String>,
 	no_serve_light: Option<bool>,
 }
 
@@ -1254,11 +1837,20 @@ struct Rpc {
 	apis: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
 	server_threads: Option<usize>,
+	heavy_threads: Option<usize>,
+	heavy_queue: Option<usize>,
+	pools: Option<Vec<String>>,
+	dedup_ttl: Option<u64>,
+	call_cache: Option<usize>,
 	max_payload: Option<usize>,
 	keep_alive: Option<bool>,
 	experimental_rpcs: Option<bool>,
 	poll_lifetime: Option<u32>,
+	poll_max_per_origin: Option<usize>,
+	poll_max_total: Option<usize>,
+	poll_persist: Option<bool>,
 	allow_missing_blocks: Option<bool>,
+	reconstruct_logs: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1282,6 +1874,44 @@ struct Ipc {
 	apis: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EngineApi {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+	jwt_secret: Option<String>,
+}
+
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Metrics {
+	enable: Option<bool>,
+	prefix: Option<String>,
+	port: Option<u16>,
+	interface: Option<String>,
+	otlp_endpoint: Option<String>,
+	otlp_service_name: Option<String>,
+}
+
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Ipfs {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+}
+
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Webhooks {
+	urls: Option<Vec<String>>,
+	secret_file: Option<String>,
+	log_addresses: Option<Vec<String>>,
+	log_topics: Option<Vec<String>>,
+	max_retries: Option<u32>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Dapps {
@@ -1332,8 +1962,12 @@ struct SecretStore {
 struct Mining {
 	author: Option<String>,
 	engine_signer: Option<String>,
+	sealing_journal: Option<String>,
 	force_sealing: Option<bool>,
 	reseal_on_uncle: Option<bool>,
+	prefer_own_uncles: Option<bool>,
+	max_uncles: Option<usize>,
+	uncle_min_age: Option<u64>,
 	reseal_on_txs: Option<String>,
 	reseal_min_period: Option<u64>,
 	reseal_max_period: Option<u64>,
@@ -1348,16 +1982,26 @@ struct Mining {
 	price_update_period: Option<String>,
 	gas_floor_target: Option<String>,
 	gas_cap: Option<String>,
+	gas_limit_target: Option<String>,
 	extra_data: Option<String>,
 	tx_queue_size: Option<usize>,
 	tx_queue_per_sender: Option<usize>,
+	tx_queue_pending_per_sender: Option<usize>,
+	tx_queue_future_per_sender: Option<usize>,
+	tx_queue_future: Option<usize>,
+	tx_queue_local_ttl: Option<u64>,
+	tx_queue_remote_ttl: Option<u64>,
+	tx_queue_local_rebroadcast: Option<u64>,
 	tx_queue_mem_limit: Option<u32>,
 	tx_queue_locals: Option<HashSet<String>>,
 	tx_queue_strategy: Option<String>,
+	tx_queue_price_bump: Option<String>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
 	tx_queue_no_unfamiliar_locals: Option<bool>,
 	tx_queue_no_early_reject: Option<bool>,
+	tx_filter_file: Option<String>,
+	tx_filter_authoring: Option<bool>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
 	refuse_service_transactions: Option<bool>,
@@ -1372,6 +2016,12 @@ struct Stratum {
 	interface: Option<String>,
 	port: Option<u16>,
 	secret: Option<String>,
+	share_difficulty: Option<String>,
+	min_share_difficulty: Option<String>,
+	share_time: Option<u64>,
+	tls_port: Option<u16>,
+	tls_cert: Option<String>,
+	tls_key: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1381,16 +2031,28 @@ struct Footprint {
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	pruning_checkpoints: Option<u64>,
+	pruning_checkpoints_kept: Option<usize>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
 	cache_size_blocks: Option<u32>,
 	cache_size_queue: Option<u32>,
 	cache_size_state: Option<u32>,
+	state_cache_ratios: Option<String>,
+	state_prefetch: Option<bool>,
+	commit_pipeline: Option<bool>,
+	commit_batch_blocks: Option<usize>,
 	db_compaction: Option<String>,
+	db_rewrite_window: Option<String>,
+	db_compression: Option<bool>,
+	db_statistics: Option<bool>,
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	ethash_pregenerate_epochs: Option<u64>,
+	ethash_cache_memory: Option<usize>,
+	ethash_light_verification_below: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1405,6 +2067,12 @@ struct Snapshots {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	log_format: Option<String>,
+	log_file_max_size: Option<u64>,
+	log_file_count: Option<usize>,
+	shutdown_timeout: Option<u64>,
+	shutdown_phases: Option<String>,
+	dump_root_mismatches: Option<String>,
 	color: Option<bool>,
 	ports_shift: Option<u16>,
 	unsafe_expose: Option<bool>,
@@ -1417,6 +2085,15 @@ struct Whisper {
 	pool_size: Option<usize>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Gossip {
+	enable: Option<bool>,
+	interface: Option<String>,
+	port: Option<u16>,
+	bootnodes: Option<Vec<String>>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Light {
@@ -1425,6 +2102,7 @@ struct Light {
 	on_demand_request_backoff_max: Option<u64>,
 	on_demand_request_backoff_rounds_max: Option<usize>,
 	on_demand_request_consecutive_failures: Option<usize>,
+	on_demand_historical_fan_out: Option<usize>,
 }
 
 #[cfg(test)]
@@ -1662,6 +2340,40 @@ mod tests {
 		assert_eq!(args.arg_stratum_secret, None);
 	}
 
+	#[test]
+	fn should_parse_config_subcommands() {
+		let args = Args::parse(&["parity", "config", "check"]).unwrap();
+		assert!(args.cmd_config && args.cmd_config_check && !args.cmd_config_dump);
+
+		let args = Args::parse(&["parity", "config", "dump", "--chain=dev"]).unwrap();
+		assert!(args.cmd_config && args.cmd_config_dump);
+		assert_eq!(args.arg_chain, "dev");
+	}
+
+	#[test]
+	fn should_dump_effective_config() {
+		let args = Args::parse_without_config(&["parity", "--min-peers=10", "--no-warp", "--jsonrpc-apis=eth,net", "--no-persistent-txqueue"]).unwrap();
+		let config = args.effective_config();
+
+		let network = config["network"].as_table().unwrap();
+		assert_eq!(network["min_peers"], toml::Value::Integer(10));
+		assert!(!network.contains_key("max_peers"));
+		assert_eq!(config["rpc"]["apis"], toml::Value::Array(vec!["eth".into(), "net".into()]));
+		assert_eq!(config["network"]["warp"], toml::Value::Boolean(false));
+		assert_eq!(config["parity"]["no_persistent_txqueue"], toml::Value::Boolean(true));
+	}
+
+	#[test]
+	fn should_load_dumped_config() {
+		let config = toml::from_str(include_str!("./tests/config.full.toml")).unwrap();
+		let args = Args::parse_with_config(&["parity", "--chain", "xyz"], config).unwrap();
+		let dumped = args.effective_config();
+
+		let config: Config = toml::Value::Table(dumped.clone()).try_into().unwrap();
+		let reloaded = Args::parse_with_config(&["parity"], config).unwrap();
+		assert_eq!(reloaded.effective_config(), dumped);
+	}
+
 	#[test]
 	fn should_parse_full_config() {
 		// given
@@ -1679,12 +2391,16 @@ mod tests {
 			cmd_account_new: false,
 			cmd_account_list: false,
 			cmd_account_import: false,
+			cmd_account_export: false,
+			cmd_account_restore: false,
+			cmd_account_reencrypt: false,
 			cmd_wallet: false,
 			cmd_wallet_import: false,
 			cmd_import: false,
 			cmd_export: false,
 			cmd_export_blocks: false,
 			cmd_export_state: false,
+			cmd_export_analytics: false,
 			cmd_signer: false,
 			cmd_signer_list: false,
 			cmd_signer_sign: false,
@@ -1692,32 +2408,72 @@ mod tests {
 			cmd_signer_new_token: false,
 			cmd_snapshot: false,
 			cmd_restore: false,
+			cmd_snapshot_upload: false,
 			cmd_tools: false,
 			cmd_tools_hash: false,
 			cmd_db: false,
 			cmd_db_kill: false,
 			cmd_db_reset: false,
+			cmd_db_tier: false,
+			cmd_db_stats: false,
 			cmd_export_hardcoded_sync: false,
+			cmd_config: false,
+			cmd_config_check: false,
+			cmd_config_dump: false,
+			cmd_spec: false,
+			cmd_spec_new: false,
+			cmd_spec_check: false,
+			cmd_spec_from_geth: false,
+			cmd_spec_to_geth: false,
 
 			// Arguments
 			arg_daemon_pid_file: None,
 			arg_import_file: None,
 			arg_import_format: None,
+			arg_import_from_ipc: None,
 			arg_export_blocks_file: None,
 			arg_export_blocks_format: None,
 			arg_export_state_file: None,
 			arg_export_state_format: None,
+			arg_export_analytics_dir: None,
 			arg_snapshot_file: None,
 			arg_restore_file: None,
+			arg_restore_from_url: None,
+			arg_snapshot_upload_url: None,
+			arg_snapshot_upload_file: None,
 			arg_tools_hash_file: None,
+			arg_spec_new_name: None,
+			arg_spec_new_engine: None,
+			arg_spec_new_chain_id: None,
+			arg_spec_new_premine: None,
+			arg_spec_new_validators: None,
+			arg_spec_new_block_time: None,
+			arg_spec_new_gas_limit: None,
+			arg_spec_new_forks: None,
+			flag_spec_new_interactive: false,
+			arg_spec_new_file: None,
+			arg_spec_check_file: None,
+			arg_spec_from_geth_genesis: None,
+			arg_spec_from_geth_spec: None,
+			arg_spec_to_geth_spec: None,
+			arg_spec_to_geth_genesis: None,
 
 			arg_enable_signing_queue: false,
 			arg_signer_sign_id: None,
 			arg_signer_reject_id: None,
 			arg_dapp_path: None,
 			arg_account_import_path: None,
+			arg_account_export_vault: None,
+			arg_account_export_file: None,
+			arg_account_restore_vault: None,
+			arg_account_restore_file: None,
+			arg_account_reencrypt_vault: None,
+			arg_account_reencrypt_kdf: "scrypt:262144:8:1".into(),
 			arg_wallet_import_path: None,
 			arg_db_reset_num: 10,
+			arg_db_tier_cold_path: None,
+			arg_db_tier_parts: "bodies,receipts,traces".into(),
+			arg_db_tier_hot_blocks: 90000u64,
 
 			// -- Operating Options
 			arg_mode: "last".into(),
@@ -1735,11 +2491,15 @@ mod tests {
 			arg_db_path: Some("$HOME/.parity/chains".into()),
 			arg_keys_path: "$HOME/.parity/keys".into(),
 			arg_identity: "".into(),
+			arg_profile: Some("mainnet-archive".into()),
 			flag_light: false,
 			flag_no_hardcoded_sync: false,
 			flag_no_persistent_txqueue: false,
 			flag_force_direct: false,
 			arg_sync_until: None,
+			arg_max_reorg_depth: None,
+			arg_fork_url: None,
+			arg_fork_block: None,
 
 			// -- Convenience Options
 			arg_config: "$BASE/config.toml".into(),
@@ -1748,10 +2508,16 @@ mod tests {
 
 			// -- Account Options
 			arg_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
+			arg_unlock_session: 300u64,
+			arg_unlock_sessions: Some("0xdeadbeefcafe0000000000000000000000000000:60".into()),
+			arg_unlock_idle_timeout: 0u64,
 			arg_password: vec!["~/.safe/password.file".into()],
 			arg_keys_iterations: 10240u32,
+			arg_keys_kdf: Some("scrypt:262144:8:1".into()),
 			arg_accounts_refresh: 5u64,
 			flag_fast_unlock: false,
+			arg_remote_signer: None,
+			arg_remote_signer_token_file: None,
 
 			// -- Private Transactions Options
 			flag_private_enabled: true,
@@ -1780,6 +2546,10 @@ mod tests {
 			arg_max_pending_peers: 64u16,
 			arg_snapshot_peers: 0u16,
 			arg_allow_ips: "all".into(),
+			arg_max_peers_per_subnet: Some(5u16),
+			arg_max_peers_per_asn: None,
+			arg_peer_asn_map: None,
+			arg_max_peers_per_client: None,
 			arg_nat: "any".into(),
 			arg_network_id: Some(1),
 			arg_bootnodes: Some("".into()),
@@ -1787,6 +2557,10 @@ mod tests {
 			arg_node_key: None,
 			arg_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
+			arg_node_permissions_contract: None,
+			arg_node_permissions_file: Some("./path_to_permissions".into()),
+			arg_node_permissions_overrides: None,
+			arg_local_sync_socket: None,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
 
@@ -1801,10 +2575,19 @@ mod tests {
 			arg_jsonrpc_apis: "web3,eth,net,parity,traces,rpc,secretstore".into(),
 			arg_jsonrpc_hosts: "none".into(),
 			arg_jsonrpc_server_threads: Some(4),
+			arg_jsonrpc_heavy_threads: 2usize,
+			arg_jsonrpc_heavy_queue: 128usize,
+			arg_jsonrpc_pools: Some("traces:1:16".into()),
+			arg_jsonrpc_dedup_ttl: Some(500),
+			arg_jsonrpc_call_cache: 1024usize,
 			arg_jsonrpc_threads: None, // DEPRECATED, does nothing
 			arg_jsonrpc_max_payload: None,
 			arg_poll_lifetime: 60u32,
+			arg_poll_max_per_origin: Some(32),
+			arg_poll_max_total: 10000usize,
+			flag_poll_persist: true,
 			flag_jsonrpc_allow_missing_blocks: false,
+			flag_jsonrpc_reconstruct_logs: false,
 
 			// WS
 			flag_no_ws: false,
@@ -1820,6 +2603,33 @@ mod tests {
 			arg_ipc_path: "$HOME/.parity/jsonrpc.ipc".into(),
 			arg_ipc_apis: "web3,eth,net,parity,parity_accounts,personal,traces,rpc,secretstore".into(),
 			arg_ipc_chmod: "660".into(),
+
+			// ENGINE API
+			flag_engine_api: false,
+			arg_engine_api_port: 8551u16,
+			arg_engine_api_interface: "local".into(),
+			arg_jwt_secret: Some("$HOME/.parity/jwtsecret".into()),
+
+			// METRICS
+			flag_metrics: false,
+			arg_metrics_prefix: "oe_".into(),
+			arg_metrics_port: 3000u16,
+			arg_metrics_interface: "local".into(),
+			arg_otlp_endpoint: Some("http://localhost:4318".into()),
+			arg_otlp_service_name: "openethereum".into(),
+
+			// IPFS
+			flag_ipfs_api: false,
+			arg_ipfs_api_port: 5001u16,
+			arg_ipfs_api_interface: "local".into(),
+
+			// WEBHOOKS
+			arg_webhook_urls: Some("http://localhost:8000/events".into()),
+			arg_webhook_secret_file: Some("webhook.secret".into()),
+			arg_webhook_log_addresses: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+			arg_webhook_log_topics: None,
+			arg_webhook_max_retries: 5u32,
+
 			// DAPPS
 			arg_dapps_path: Some("$HOME/.parity/dapps".into()),
 			flag_no_dapps: false,
@@ -1848,11 +2658,15 @@ mod tests {
 			// -- Sealing/Mining Options
 			arg_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			arg_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+			arg_sealing_journal: None,
 			flag_force_sealing: true,
 			arg_reseal_on_txs: "all".into(),
 			arg_reseal_min_period: 4000u64,
 			arg_reseal_max_period: 60000u64,
 			flag_reseal_on_uncle: false,
+			flag_prefer_own_uncles: false,
+			arg_max_uncles: None,
+			arg_uncle_min_age: 1u64,
 			arg_work_queue_size: 20usize,
 			arg_tx_gas_limit: Some("10000000".into()),
 			arg_tx_time_limit: Some(100u64),
@@ -1864,14 +2678,24 @@ mod tests {
 			arg_price_update_period: "hourly".into(),
 			arg_gas_floor_target: "8000000".into(),
 			arg_gas_cap: "10000000".into(),
+			arg_gas_limit_target: Some("12000000".into()),
 			arg_extra_data: Some("Parity".into()),
 			flag_tx_queue_no_unfamiliar_locals: false,
 			flag_tx_queue_no_early_reject: false,
+			flag_tx_filter_authoring: false,
 			arg_tx_queue_size: 8192usize,
 			arg_tx_queue_per_sender: None,
+			arg_tx_queue_pending_per_sender: None,
+			arg_tx_queue_future_per_sender: None,
+			arg_tx_queue_future: None,
+			arg_tx_queue_local_ttl: None,
+			arg_tx_queue_remote_ttl: None,
+			arg_tx_queue_local_rebroadcast: None,
+			arg_tx_filter_file: None,
 			arg_tx_queue_mem_limit: 4u32,
 			arg_tx_queue_locals: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			arg_tx_queue_strategy: "gas_factor".into(),
+			arg_tx_queue_price_bump: "12.5".into(),
 			arg_tx_queue_ban_count: Some(1u16),
 			arg_tx_queue_ban_time: Some(180u16),
 			flag_remove_solved: false,
@@ -1884,22 +2708,40 @@ mod tests {
 			arg_stratum_interface: "local".to_owned(),
 			arg_stratum_port: 8008u16,
 			arg_stratum_secret: None,
+			arg_stratum_share_difficulty: None,
+			arg_stratum_min_share_difficulty: None,
+			arg_stratum_share_time: 15u64,
+			arg_stratum_tls_port: None,
+			arg_stratum_tls_cert: None,
+			arg_stratum_tls_key: None,
 
 			// -- Footprint Options
 			arg_tracing: "auto".into(),
 			arg_pruning: "auto".into(),
 			arg_pruning_history: 64u64,
 			arg_pruning_memory: 500usize,
+			arg_pruning_checkpoints: 4096u64,
+			arg_pruning_checkpoints_kept: 8usize,
 			arg_cache_size_db: 64u32,
 			arg_cache_size_blocks: 8u32,
 			arg_cache_size_queue: 50u32,
 			arg_cache_size_state: 25u32,
+			arg_state_cache_ratios: "25,60,15".into(),
+			flag_no_state_prefetch: false,
+			flag_commit_pipeline: false,
+			arg_commit_batch_blocks: 0usize,
 			arg_cache_size: Some(128),
 			flag_fast_and_loose: false,
 			arg_db_compaction: "ssd".into(),
+			arg_db_rewrite_window: None,
+			flag_db_compression: false,
+			flag_db_statistics: false,
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
+			arg_ethash_pregenerate_epochs: 2u64,
+			arg_ethash_cache_memory: 256usize,
+			arg_ethash_light_verification_below: Some(1000000),
 
 			// -- Import/Export Options
 			arg_export_blocks_from: "1".into(),
@@ -1909,6 +2751,10 @@ mod tests {
 			flag_export_state_no_storage: false,
 			arg_export_state_min_balance: None,
 			arg_export_state_max_balance: None,
+			arg_export_analytics_format: "csv".into(),
+			arg_export_analytics_from: "0".into(),
+			arg_export_analytics_to: "latest".into(),
+			arg_export_analytics_threads: None,
 
 			// -- Snapshot Optons
 			arg_export_state_at: "latest".into(),
@@ -1922,11 +2768,18 @@ mod tests {
 			arg_on_demand_request_backoff_max: Some(15),
 			arg_on_demand_request_backoff_rounds_max: Some(100),
 			arg_on_demand_request_consecutive_failures: Some(1),
+			arg_on_demand_historical_fan_out: Some(3),
 
 			// -- Whisper options.
 			flag_whisper: false,
 			arg_whisper_pool_size: Some(20),
 
+			// -- Gossip options.
+			flag_gossip: false,
+			arg_gossip_interface: "all".into(),
+			arg_gossip_port: 30305u16,
+			arg_gossip_bootnodes: Some("".into()),
+
 			// -- Legacy Options
 			flag_warp: false,
 			flag_geth: false,
@@ -1972,6 +2825,12 @@ mod tests {
 			flag_version: false,
 			arg_logging: Some("own_tx=trace".into()),
 			arg_log_file: Some("/var/log/parity.log".into()),
+			arg_log_format: "json".into(),
+			arg_log_file_max_size: 100u64,
+			arg_log_file_count: 10usize,
+			arg_shutdown_timeout: 60u64,
+			arg_shutdown_phases: "queue,fsync".into(),
+			arg_dump_root_mismatches: None,
 			flag_no_color: false,
 			flag_no_config: false,
 		});
@@ -2017,10 +2876,14 @@ mod tests {
 				db_path: None,
 				keys_path: None,
 				identity: None,
+				profile: None,
 				light: None,
 				no_hardcoded_sync: None,
 				no_persistent_txqueue: None,
 				sync_until: Some(123),
+				max_reorg_depth: None,
+				fork_url: None,
+				fork_block: None,
 				_legacy_public_node: None,
 			}),
 			account: Some(Account {
@@ -2028,8 +2891,14 @@ mod tests {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
 				keys_iterations: None,
+				keys_kdf: None,
 				refresh_time: None,
+				unlock_session: None,
+				unlock_sessions: None,
+				unlock_idle_timeout: None,
 				fast_unlock: None,
+				remote_signer: None,
+				remote_signer_token_file: None,
 			}),
 			ui: Some(Ui {
 				path: None,
@@ -2049,6 +2918,10 @@ mod tests {
 				max_pending_peers: Some(30),
 				snapshot_peers: Some(40),
 				allow_ips: Some("public".into()),
+				max_peers_per_subnet: None,
+				max_peers_per_asn: None,
+				peer_asn_map: None,
+				max_peers_per_client: None,
 				nat: Some("any".into()),
 				id: None,
 				bootnodes: None,
@@ -2056,6 +2929,10 @@ mod tests {
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
+				node_permissions_contract: None,
+				node_permissions_file: None,
+				node_permissions_overrides: None,
+				local_sync_socket: None,
 				no_serve_light: None,
 			}),
 			websockets: Some(Ws {
@@ -2075,11 +2952,20 @@ mod tests {
 				apis: None,
 				hosts: None,
 				server_threads: Some(13),
+				heavy_threads: None,
+				heavy_queue: None,
+				pools: None,
+				dedup_ttl: None,
+				call_cache: None,
 				max_payload: None,
 				keep_alive: None,
 				experimental_rpcs: None,
 				poll_lifetime: None,
-				allow_missing_blocks: None
+				poll_max_per_origin: None,
+				poll_max_total: None,
+				poll_persist: None,
+				allow_missing_blocks: None,
+				reconstruct_logs: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -2087,6 +2973,10 @@ mod tests {
 				chmod: None,
 				apis: Some(vec!["rpc".into(), "eth".into()]),
 			}),
+			engine_api: None,
+			metrics: None,
+			ipfs: None,
+			webhooks: None,
 			dapps: Some(Dapps {
 				_legacy_disable: None,
 				_legacy_port: Some(8080),
@@ -2122,9 +3012,13 @@ mod tests {
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+				sealing_journal: None,
 				force_sealing: Some(true),
 				reseal_on_txs: Some("all".into()),
 				reseal_on_uncle: None,
+				prefer_own_uncles: None,
+				max_uncles: None,
+				uncle_min_age: None,
 				reseal_min_period: Some(4000),
 				reseal_max_period: Some(60000),
 				work_queue_size: None,
@@ -2136,15 +3030,25 @@ mod tests {
 				price_update_period: Some("hourly".into()),
 				gas_floor_target: None,
 				gas_cap: None,
+				gas_limit_target: None,
 				tx_queue_size: Some(8192),
 				tx_queue_per_sender: None,
+				tx_queue_pending_per_sender: None,
+				tx_queue_future_per_sender: None,
+				tx_queue_future: None,
+				tx_queue_local_ttl: None,
+				tx_queue_remote_ttl: None,
+				tx_queue_local_rebroadcast: None,
 				tx_queue_mem_limit: None,
 				tx_queue_locals: None,
 				tx_queue_strategy: None,
+				tx_queue_price_bump: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
 				tx_queue_no_unfamiliar_locals: None,
 				tx_queue_no_early_reject: None,
+				tx_filter_file: None,
+				tx_filter_authoring: None,
 				tx_gas_limit: None,
 				tx_time_limit: None,
 				extra_data: None,
@@ -2159,16 +3063,28 @@ mod tests {
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				pruning_checkpoints: None,
+				pruning_checkpoints_kept: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(256),
 				cache_size_blocks: Some(16),
 				cache_size_queue: Some(100),
 				cache_size_state: Some(25),
+				state_cache_ratios: None,
+				state_prefetch: None,
+				commit_pipeline: None,
+				commit_batch_blocks: Some(1024),
 				db_compaction: Some("ssd".into()),
+				db_rewrite_window: None,
+				db_compression: None,
+				db_statistics: None,
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				ethash_pregenerate_epochs: None,
+				ethash_cache_memory: None,
+				ethash_light_verification_below: None,
 			}),
 			light: Some(Light {
 				on_demand_response_time_window: Some(2),
@@ -2176,6 +3092,7 @@ mod tests {
 				on_demand_request_backoff_max: Some(15),
 				on_demand_request_backoff_rounds_max: Some(10),
 				on_demand_request_consecutive_failures: Some(1),
+				on_demand_historical_fan_out: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
@@ -2184,6 +3101,12 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				log_format: None,
+				log_file_max_size: None,
+				log_file_count: None,
+				shutdown_timeout: None,
+				shutdown_phases: None,
+				dump_root_mismatches: None,
 				color: Some(true),
 				ports_shift: Some(0),
 				unsafe_expose: Some(false),
@@ -2192,6 +3115,7 @@ mod tests {
 				enabled: Some(true),
 				pool_size: Some(50),
 			}),
+			gossip: None,
 			stratum: None,
 		});
 	}
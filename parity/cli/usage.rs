@@ -123,10 +123,10 @@ macro_rules! usage {
 			$(
 			[$group_name:expr]
 				$(
-					FLAG $flag:ident : (bool) = false, or $flag_from_config:expr, $flag_usage:expr, $flag_help:expr,
+					FLAG $flag:ident : (bool) = false, or |$flag_c:ident: &Config| $flag_config:ident.$flag_section:ident.as_ref()?.$flag_key:ident $(.$flag_conversion:ident($($flag_conversion_args:tt)*))*, $flag_usage:expr, $flag_help:expr,
 				)*
 				$(
-					ARG $arg:ident : ($($arg_type_tt:tt)+) = $arg_default:expr, or $arg_from_config:expr, $arg_usage:expr, $arg_help:expr,
+					ARG $arg:ident : ($($arg_type_tt:tt)+) = $arg_default:expr, or |$arg_c:ident: &Config| $arg_config:ident.$arg_section:ident.as_ref()?.$arg_key:ident $(.$arg_conversion:ident($($arg_conversion_args:tt)*))*, $arg_usage:expr, $arg_help:expr,
 				)*
 				$(
 					CHECK $check:expr,
@@ -135,7 +135,7 @@ macro_rules! usage {
 		}
 	) => {
 		use toml;
-		use std::{fs, io, process, cmp};
+		use std::{fmt, fs, io, process, cmp};
 		use std::io::Read;
 		use parity_version::version;
 		use clap::{Arg, App, SubCommand, AppSettings, ArgSettings, Error as ClapError, ErrorKind as ClapErrorKind};
@@ -182,6 +182,41 @@ macro_rules! usage {
 			}
 		}
 
+		/// Insert the value of an option into the config file `section` under `key`.
+		/// `conversion` are the methods applied to the value read from a config file.
+		/// Legacy keys and unset options are skipped.
+		fn insert_config_value<T: ::serde::Serialize>(config: &mut toml::value::Table, section: &str, key: &str, conversion: &str, value: &T) {
+			if key.starts_with("_legacy") {
+				return;
+			}
+			let value = match toml::Value::try_from(value) {
+				Ok(value) => value,
+				Err(_) => return,
+			};
+			// Undo the conversions done when reading the config file.
+			let value = match value {
+				toml::Value::String(ref s) if conversion.contains("join") => toml::Value::Array(
+					s.split(',').filter(|s| !s.is_empty()).map(|s| toml::Value::String(s.to_owned())).collect()
+				),
+				toml::Value::Boolean(b) if conversion.contains('!') => toml::Value::Boolean(!b),
+				value => value,
+			};
+			if let Some(section) = config.entry(section.to_owned()).or_insert_with(|| toml::Value::Table(Default::default())).as_table_mut() {
+				section.insert(key.to_owned(), value);
+			}
+		}
+
+		impl fmt::Display for ArgsError {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				match *self {
+					ArgsError::Clap(ref e) => write!(f, "{}", e),
+					ArgsError::Decode(ref e) => write!(f, "Invalid parameters in config file: {}", e),
+					ArgsError::Config(ref path, ref e) => write!(f, "Error reading config file at {}: {}", path, e),
+					ArgsError::PeerConfiguration => write!(f, "You have supplied `min_peers` > `max_peers`"),
+				}
+			}
+		}
+
 		impl From<ClapError> for ArgsError {
 			fn from(e: ClapError) -> Self {
 				ArgsError::Clap(e)
@@ -364,6 +399,34 @@ macro_rules! usage {
 				Ok(toml::from_str(config)?)
 			}
 
+			/// Resolved value of every option that can be set from a config file, laid out as one.
+			pub fn effective_config(&self) -> toml::value::Table {
+				let mut config = toml::value::Table::new();
+				$(
+					if $group_name != "Legacy Options" {
+						$(
+							insert_config_value(
+								&mut config,
+								stringify!($flag_section),
+								stringify!($flag_key),
+								stringify!($($flag_conversion($($flag_conversion_args)*))*),
+								&self.$flag,
+							);
+						)*
+						$(
+							insert_config_value(
+								&mut config,
+								stringify!($arg_section),
+								stringify!($arg_key),
+								stringify!($($arg_conversion($($arg_conversion_args)*))*),
+								&self.$arg,
+							);
+						)*
+					}
+				)*
+				config
+			}
+
 			pub fn print_version() -> String {
 				format!(include_str!("./version.txt"), version())
 			}
@@ -544,13 +607,15 @@ macro_rules! usage {
 
 				$(
 					$(
-						args.$flag = self.$flag || $flag_from_config(&config).unwrap_or(false);
+						let from_config = |$flag_c: &Config| $flag_config.$flag_section.as_ref()?.$flag_key $(.$flag_conversion($($flag_conversion_args)*))*;
+						args.$flag = self.$flag || from_config(&config).unwrap_or(false);
 					)*
 					$(
+						let from_config = |$arg_c: &Config| $arg_config.$arg_section.as_ref()?.$arg_key $(.$arg_conversion($($arg_conversion_args)*))*;
 						args.$arg = if_option!(
 							$($arg_type_tt)+,
-							THEN { self.$arg.or_else(|| $arg_from_config(&config)).or_else(|| $arg_default.into()) }
-							ELSE { self.$arg.or_else(|| $arg_from_config(&config)).unwrap_or_else(|| $arg_default.into()) }
+							THEN { self.$arg.or_else(|| from_config(&config)).or_else(|| $arg_default.into()) }
+							ELSE { self.$arg.or_else(|| from_config(&config)).unwrap_or_else(|| $arg_default.into()) }
 						);
 					)*
 				)*
@@ -0,0 +1,175 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Validation of the command line and config file without starting the node.
+
+use std::collections::BTreeMap;
+
+use cli::Args;
+use profile;
+use run::RunCmd;
+use types::engines::SealingState;
+
+/// Command to validate the configuration of a node, see `openethereum config check`.
+#[derive(Debug, PartialEq)]
+pub struct ConfigCheckCmd {
+	/// The command the same configuration would start.
+	pub run_cmd: RunCmd,
+	/// Deprecated options in use, with their replacement if any.
+	pub deprecated: Vec<String>,
+	/// Options contradicting each other.
+	pub conflicts: Vec<String>,
+}
+
+/// Options which are valid on their own but contradict each other.
+pub fn find_conflicts(args: &Args) -> Vec<String> {
+	let mut result = vec![];
+
+	if args.flag_no_warp && args.arg_warp_barrier.is_some() {
+		result.push("--warp-barrier has no effect with --no-warp.".to_owned());
+	}
+
+	if args.flag_reserved_only && args.arg_reserved_peers.is_none() {
+		result.push("--reserved-only without --reserved-peers, the node will not connect to any peer.".to_owned());
+	}
+
+	if args.flag_light {
+		let sealing = [
+			("--force-sealing", args.flag_force_sealing),
+			("--author", args.arg_author.is_some()),
+			("--engine-signer", args.arg_engine_signer.is_some()),
+		];
+		for &(option, _) in sealing.iter().filter(|&&(_, set)| set) {
+			result.push(format!("{} has no effect with --light, light clients don't seal blocks.", option));
+		}
+	}
+
+	if let Some(per_sender) = args.arg_tx_queue_per_sender {
+		if per_sender > args.arg_tx_queue_size {
+			result.push(format!("--tx-queue-per-sender ({}) is larger than --tx-queue-size ({}).", per_sender, args.arg_tx_queue_size));
+		}
+	}
+
+	result
+}
+
+/// Enabled services listening on the same port.
+fn port_conflicts(cmd: &RunCmd) -> Vec<String> {
+	let mut by_port: BTreeMap<u16, Vec<&str>> = BTreeMap::new();
+	for (option, port) in cmd.listening_ports() {
+		by_port.entry(port).or_default().push(option);
+	}
+
+	by_port.into_iter()
+		.filter(|&(_, ref options)| options.len() > 1)
+		.map(|(port, options)| format!("Port {} is used by more than one service: {}.", port, options.join(", ")))
+		.collect()
+}
+
+/// Validate the configuration against the chain spec. Returns the report, as an error if
+/// there are conflicts.
+pub fn execute(cmd: ConfigCheckCmd) -> Result<String, String> {
+	let run = cmd.run_cmd;
+	let spec = run.spec.spec(&run.dirs.cache)?;
+	let mut conflicts = cmd.conflicts;
+	conflicts.extend(port_conflicts(&run));
+
+	let mut notes = vec![];
+	if let Some(ref profile) = run.profile {
+		conflicts.extend(profile::find_collisions(profile, &run.listening_ports(), run.ipc_path())?);
+		notes.push(format!("Profile {} uses slot {}, ports are shifted by {}.", profile.name, profile.slot, profile.ports_shift()));
+	}
+	if run.miner_extras.engine_signer != Default::default() && spec.engine.sealing_state() == SealingState::External {
+		conflicts.push(format!("--engine-signer has no effect, blocks of {} are sealed by external miners.", spec.engine.name()));
+	}
+	if let Some(network_id) = run.network_id {
+		if network_id != spec.network_id() {
+			notes.push(format!("--network-id {} overrides the network id {} of the chain spec.", network_id, spec.network_id()));
+		}
+	}
+
+	let mut report = format!("Chain: {} (engine {})\n", spec.name, spec.engine.name());
+	for line in cmd.deprecated.iter() {
+		report.push_str(&format!("deprecated: {}\n", line));
+	}
+	for line in notes.iter() {
+		report.push_str(&format!("note: {}\n", line));
+	}
+	for line in conflicts.iter() {
+		report.push_str(&format!("conflict: {}\n", line));
+	}
+
+	if conflicts.is_empty() {
+		report.push_str("Configuration is valid.");
+		Ok(report)
+	} else {
+		report.push_str(&format!("Configuration has {} conflict(s).", conflicts.len()));
+		Err(report)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use cli::Args;
+	use configuration::{Cmd, Configuration};
+	use super::{execute, find_conflicts, port_conflicts};
+
+	fn args(args: &[&str]) -> Args {
+		Args::parse_without_config(args).unwrap()
+	}
+
+	fn check(args: &[&str]) -> Result<String, String> {
+		match (Configuration { args: self::args(args) }).into_command().unwrap().cmd {
+			Cmd::ConfigCheck(check_cmd) => execute(check_cmd),
+			_ => panic!("Should be a config check command"),
+		}
+	}
+
+	#[test]
+	fn should_find_conflicting_options() {
+		assert!(find_conflicts(&args(&["parity"])).is_empty());
+
+		let conflicts = find_conflicts(&args(&["parity", "--no-warp", "--warp-barrier=100", "--light", "--force-sealing"]));
+		assert_eq!(conflicts, vec![
+			"--warp-barrier has no effect with --no-warp.".to_owned(),
+			"--force-sealing has no effect with --light, light clients don't seal blocks.".to_owned(),
+		]);
+	}
+
+	#[test]
+	fn should_find_port_conflicts() {
+		let conf = Configuration { args: args(&["parity", "--ws-port=8545", "--metrics", "--metrics-port=30303"]) };
+		let run_cmd = match conf.into_command().unwrap().cmd {
+			Cmd::Run(run_cmd) => run_cmd,
+			_ => panic!("Should be a run command"),
+		};
+
+		assert_eq!(port_conflicts(&run_cmd), vec![
+			"Port 8545 is used by more than one service: --jsonrpc-port, --ws-port.".to_owned(),
+			"Port 30303 is used by more than one service: --port, --metrics-port.".to_owned(),
+		]);
+	}
+
+	#[test]
+	fn should_check_against_chain_spec() {
+		let report = check(&["parity", "config", "check", "--chain=dev"]).unwrap();
+		assert!(report.ends_with("Configuration is valid."));
+
+		let report = check(&["parity", "config", "check", "--engine-signer=0x0000000000000000000000000000000000000001"]).unwrap_err();
+		assert!(report.contains("conflict: --engine-signer has no effect, blocks of Ethash are sealed by external miners."));
+	}
+}
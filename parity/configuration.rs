@@ -16,11 +16,12 @@
 
 use std::time::Duration;
 use std::io::Read;
-use std::net::{SocketAddr, ToSocketAddrs};
-use std::path::PathBuf;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
 use std::collections::{HashSet, BTreeMap};
 use std::iter::FromIterator;
 use std::cmp;
+use toml;
 use cli::{Args, ArgsError};
 use hash::keccak;
 use ethereum_types::{U256, H256, Address};
@@ -29,30 +30,47 @@ use bytes::Bytes;
 use ansi_term::Colour;
 use sync::{NetworkConfiguration, validate_node_url, self};
 use parity_crypto::publickey::{Secret, Public};
+use ethcore::client::UnclePolicy;
 use ethcore::miner::{stratum, MinerOptions};
 use snapshot::SnapshotConfiguration;
 use miner::pool;
 use verification::queue::VerifierSettings;
-
-use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
+use spec::EthashConfig;
+
+use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration, EngineApiConfiguration, RequestPoolConfiguration, RequestPoolsConfiguration};
+use ipfs::IpfsConfiguration;
+use metrics::MetricsConfiguration;
+use otlp::OtlpConfiguration;
+use webhooks::WebhookConfiguration;
+use shutdown::{ShutdownConfiguration, to_shutdown_phases};
+use reload::ReloadableConfig;
+use profile::{self, ProfileConfiguration};
 use parity_rpc::NetworkSettings;
 use cache::CacheConfig;
-use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization};
+use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, to_state_cache_ratios, to_kdf_params, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_hashes, to_queue_strategy, to_queue_penalization, to_queue_ttl, to_unlock_sessions, to_replacement_policy};
 use dir::helpers::{replace_home, replace_home_and_local};
-use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecType};
-use ethcore_logger::Config as LogConfig;
+use params::{ResealPolicy, AccountsConfig, RemoteSignerConfig, GasPricerConfig, MinerExtras, SpecType};
+use ethcore_logger::{Config as LogConfig, validate_levels};
+use ethstore::KdfParams;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
 use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
+use state_fork::StateForkConfig;
 use types::data_format::DataFormat;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ResetBlockchain};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ExportAnalytics, ResetBlockchain, TierBlockchain, StatsBlockchain};
 use export_hardcoded_sync::ExportHsyncCmd;
+use db;
+use config_check::{ConfigCheckCmd, find_conflicts};
+use deprecated::find_deprecated;
 use presale::ImportWallet;
-use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
+use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts, ExportKeystore, RestoreKeystore, ReencryptKeystore};
 use snapshot_cmd::{self, SnapshotCommand};
-use network::{IpFilter, NatType};
+use spec_cmd::{NewSpec, SpecCmd};
+use network::{AsnMap, IpFilter, NatType, PeerDiversity};
+use gossip::{GossipConfig, Multiaddr};
+use node_filter::{NodePermissions, parse_node_id};
 
 const DEFAULT_MAX_PEERS: u16 = 50;
 const DEFAULT_MIN_PEERS: u16 = 25;
@@ -84,6 +102,9 @@ pub enum Cmd {
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
 	ExportHardcodedSync(ExportHsyncCmd),
+	ConfigCheck(ConfigCheckCmd),
+	ConfigDump(String),
+	Spec(SpecCmd),
 }
 
 pub struct Execute {
@@ -114,7 +135,9 @@ impl Configuration {
 		Ok(config)
 	}
 
-	pub(crate) fn into_command(self) -> Result<Execute, String> {
+	pub(crate) fn into_command(mut self) -> Result<Execute, String> {
+		let profile = self.apply_profile()?;
+		self.validate_ports_shift()?;
 		let dirs = self.directories();
 		let pruning = self.args.arg_pruning.parse()?;
 		let pruning_history = self.args.arg_pruning_history;
@@ -124,12 +147,21 @@ impl Configuration {
 			mode => Some(to_mode(&mode, self.args.arg_mode_timeout, self.args.arg_mode_alarm)?),
 		};
 		let update_policy = self.update_policy()?;
+		self.validate_log_format()?;
 		let logger_config = self.logger_config();
 		let ws_conf = self.ws_config()?;
 		let snapshot_conf = self.snapshot_config()?;
 		let http_conf = self.http_config()?;
 		let ipc_conf = self.ipc_config()?;
+		let engine_api_conf = self.engine_api_config();
+		let metrics_conf = self.metrics_config();
+		let ipfs_conf = self.ipfs_config();
+		let otlp_conf = self.otlp_config();
+		let webhook_conf = self.webhook_config()?;
+		let shutdown_conf = self.shutdown_config()?;
+		let reload_conf = self.reloadable_config()?;
 		let net_conf = self.net_config()?;
+		let node_permissions = self.node_permissions()?;
 		let network_id = self.network_id();
 		let cache_config = self.cache_config();
 		let tracing = self.args.arg_tracing.parse()?;
@@ -141,10 +173,7 @@ impl Configuration {
 		let secretstore_conf = self.secretstore_config()?;
 		let format = self.format()?;
 
-		let key_iterations = self.args.arg_keys_iterations;
-		if key_iterations == 0 {
-			return Err("--key-iterations must be non-zero".into());
-		}
+		let keys_kdf = self.keys_kdf()?;
 
 		let cmd = if self.args.flag_version {
 			Cmd::Version
@@ -192,6 +221,23 @@ impl Configuration {
 				cache_config,
 				num: self.args.arg_db_reset_num,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_db_tier {
+			Cmd::Blockchain(BlockchainCmd::Tier(TierBlockchain {
+				cold_path: self.args.arg_db_tier_cold_path.as_ref().map(|path| replace_home(&dirs.base, path)),
+				spec,
+				dirs,
+				pruning,
+				compaction,
+				parts: db::parse_cold_parts(&self.args.arg_db_tier_parts)?,
+				hot_blocks: self.args.arg_db_tier_hot_blocks,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_stats {
+			Cmd::Blockchain(BlockchainCmd::Stats(StatsBlockchain {
+				spec,
+				dirs,
+				pruning,
+				compaction,
+			}))
 		} else if self.args.cmd_db && self.args.cmd_db_kill {
 			Cmd::Blockchain(BlockchainCmd::Kill(KillBlockchain {
 				spec: spec,
@@ -201,7 +247,7 @@ impl Configuration {
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
-					iterations: key_iterations,
+					kdf: keys_kdf,
 					path: dirs.keys,
 					spec: spec,
 					password_file: self.accounts_config()?.password_files.first().map(|x| x.to_owned()),
@@ -220,6 +266,30 @@ impl Configuration {
 					spec: spec,
 				};
 				AccountCmd::Import(import_acc)
+			} else if self.args.cmd_account_export {
+				AccountCmd::Export(ExportKeystore {
+					path: dirs.keys,
+					spec: spec,
+					vault: self.args.arg_account_export_vault.clone(),
+					file: self.args.arg_account_export_file.clone(),
+					password_files: self.accounts_config()?.password_files,
+				})
+			} else if self.args.cmd_account_restore {
+				AccountCmd::Restore(RestoreKeystore {
+					path: dirs.keys,
+					spec: spec,
+					vault: self.args.arg_account_restore_vault.clone(),
+					file: self.args.arg_account_restore_file.clone().expect("CLI argument is required; qed"),
+					password_files: self.accounts_config()?.password_files,
+				})
+			} else if self.args.cmd_account_reencrypt {
+				AccountCmd::Reencrypt(ReencryptKeystore {
+					path: dirs.keys,
+					spec: spec,
+					vault: self.args.arg_account_reencrypt_vault.clone(),
+					kdf: to_kdf_params(&self.args.arg_account_reencrypt_kdf)?,
+					password_files: self.accounts_config()?.password_files,
+				})
 			} else {
 				unreachable!();
 			};
@@ -235,7 +305,7 @@ impl Configuration {
 			Cmd::Account(account_cmd)
 		} else if self.args.cmd_wallet {
 			let presale_cmd = ImportWallet {
-				iterations: key_iterations,
+				kdf: keys_kdf,
 				path: dirs.keys,
 				spec: spec,
 				wallet_path: self.args.arg_wallet_import_path.clone().unwrap(),
@@ -248,6 +318,7 @@ impl Configuration {
 				cache_config: cache_config,
 				dirs: dirs,
 				file_path: self.args.arg_import_file.clone(),
+				from_ipc: self.args.arg_import_from_ipc.clone(),
 				format: format,
 				pruning: pruning,
 				pruning_history: pruning_history,
@@ -303,6 +374,25 @@ impl Configuration {
 					max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
 				};
 				Cmd::Blockchain(BlockchainCmd::ExportState(export_cmd))
+			} else if self.args.cmd_export_analytics {
+				let export_cmd = ExportAnalytics {
+					spec: spec,
+					cache_config: cache_config,
+					dirs: dirs,
+					out_dir: self.args.arg_export_analytics_dir.clone().unwrap_or_else(|| ".".into()),
+					format: self.args.arg_export_analytics_format.parse()?,
+					pruning: pruning,
+					pruning_history: pruning_history,
+					pruning_memory: self.args.arg_pruning_memory,
+					compaction: compaction,
+					tracing: tracing,
+					fat_db: fat_db,
+					from_block: to_block_id(&self.args.arg_export_analytics_from)?,
+					to_block: to_block_id(&self.args.arg_export_analytics_to)?,
+					threads: self.args.arg_export_analytics_threads.unwrap_or_else(::num_cpus::get),
+					max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+				};
+				Cmd::Blockchain(BlockchainCmd::ExportAnalytics(export_cmd))
 			} else {
 				unreachable!();
 			}
@@ -318,6 +408,7 @@ impl Configuration {
 				fat_db: fat_db,
 				compaction: compaction,
 				file_path: self.args.arg_snapshot_file.clone(),
+				url: None,
 				kind: snapshot_cmd::Kind::Take,
 				block_at: to_block_id(&self.args.arg_snapshot_at)?,
 				max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
@@ -336,12 +427,32 @@ impl Configuration {
 				fat_db: fat_db,
 				compaction: compaction,
 				file_path: self.args.arg_restore_file.clone(),
+				url: self.args.arg_restore_from_url.clone(),
 				kind: snapshot_cmd::Kind::Restore,
 				block_at: to_block_id("latest")?, // unimportant.
 				max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
 				snapshot_conf: snapshot_conf,
 			};
 			Cmd::Snapshot(restore_cmd)
+		} else if self.args.cmd_snapshot_upload {
+			let upload_cmd = SnapshotCommand {
+				cache_config: cache_config,
+				dirs: dirs,
+				spec: spec,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				tracing: tracing,
+				fat_db: fat_db,
+				compaction: compaction,
+				file_path: self.args.arg_snapshot_upload_file.clone(),
+				url: self.args.arg_snapshot_upload_url.clone(),
+				kind: snapshot_cmd::Kind::Upload,
+				block_at: to_block_id("latest")?, // unimportant.
+				max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+				snapshot_conf: snapshot_conf,
+			};
+			Cmd::Snapshot(upload_cmd)
 		} else if self.args.cmd_export_hardcoded_sync {
 			let export_hs_cmd = ExportHsyncCmd {
 				cache_config: cache_config,
@@ -351,6 +462,36 @@ impl Configuration {
 				compaction: compaction,
 			};
 			Cmd::ExportHardcodedSync(export_hs_cmd)
+		} else if self.args.cmd_config && self.args.cmd_config_dump {
+			let config = toml::Value::Table(self.args.effective_config());
+			Cmd::ConfigDump(toml::to_string(&config).map_err(|e| format!("Failed to print configuration: {}", e))?)
+		} else if self.args.cmd_spec {
+			Cmd::Spec(if self.args.cmd_spec_new {
+				SpecCmd::New(NewSpec {
+					name: self.args.arg_spec_new_name.clone(),
+					engine: self.args.arg_spec_new_engine.clone(),
+					chain_id: self.args.arg_spec_new_chain_id,
+					premine: self.args.arg_spec_new_premine.clone(),
+					validators: self.args.arg_spec_new_validators.clone(),
+					block_time: self.args.arg_spec_new_block_time,
+					gas_limit: self.args.arg_spec_new_gas_limit,
+					forks: self.args.arg_spec_new_forks.clone(),
+					interactive: self.args.flag_spec_new_interactive,
+					file: self.args.arg_spec_new_file.clone(),
+				})
+			} else if self.args.cmd_spec_check {
+				SpecCmd::Check(self.args.arg_spec_check_file.clone().expect("CLI argument is required; qed"))
+			} else if self.args.cmd_spec_from_geth {
+				SpecCmd::FromGeth {
+					genesis: self.args.arg_spec_from_geth_genesis.clone().expect("CLI argument is required; qed"),
+					file: self.args.arg_spec_from_geth_spec.clone(),
+				}
+			} else {
+				SpecCmd::ToGeth {
+					spec: self.args.arg_spec_to_geth_spec.clone().expect("CLI argument is required; qed"),
+					file: self.args.arg_spec_to_geth_genesis.clone(),
+				}
+			})
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_daemon_pid_file.clone().expect("CLI argument is required; qed"))
@@ -368,27 +509,56 @@ impl Configuration {
 				pruning,
 				pruning_history,
 				pruning_memory: self.args.arg_pruning_memory,
+				pruning_checkpoints: self.args.arg_pruning_checkpoints,
+				pruning_checkpoints_kept: self.args.arg_pruning_checkpoints_kept,
 				daemon,
 				logger_config: logger_config.clone(),
 				miner_options: self.miner_options()?,
 				gas_price_percentile: self.args.arg_gas_price_percentile,
 				poll_lifetime: self.args.arg_poll_lifetime,
+				poll_max_per_origin: self.args.arg_poll_max_per_origin,
+				poll_max_total: self.args.arg_poll_max_total,
+				poll_persist: self.args.flag_poll_persist,
+				request_pools: self.request_pools_config()?,
+				rpc_dedup_ttl: self.args.arg_jsonrpc_dedup_ttl.map(Duration::from_millis),
+				rpc_call_cache: self.args.arg_jsonrpc_call_cache,
 				ws_conf,
 				snapshot_conf,
 				http_conf,
 				ipc_conf,
+				engine_api_conf,
+				metrics_conf,
+				ipfs_conf,
+				otlp_conf,
+				webhook_conf,
+				shutdown_conf,
+				reload_conf,
+				profile,
 				net_conf,
+				node_permissions,
+				local_sync_socket: self.args.arg_local_sync_socket.as_ref().map(|path| replace_home(&self.directories().base, path)),
 				network_id,
 				acc_conf: self.accounts_config()?,
 				gas_pricer_conf: self.gas_pricer_config()?,
 				miner_extras: self.miner_extras()?,
 				stratum: self.stratum_options()?,
+				gossip: self.gossip_config()?,
 				update_policy,
 				allow_missing_blocks: self.args.flag_jsonrpc_allow_missing_blocks,
+				reconstruct_logs: self.args.flag_jsonrpc_reconstruct_logs,
 				mode,
 				tracing,
 				fat_db,
 				compaction,
+				db_compression: self.args.flag_db_compression,
+				db_statistics: self.args.flag_db_statistics,
+				db_rewrite_window: self.args.arg_db_rewrite_window.as_ref().map(|window| window.parse()).transpose()?,
+				state_cache_ratios: to_state_cache_ratios(&self.args.arg_state_cache_ratios)?,
+				state_prefetch: !self.args.flag_no_state_prefetch,
+				commit_pipeline: self.args.flag_commit_pipeline,
+				commit_batch_blocks: self.args.arg_commit_batch_blocks,
+				uncle_policy: self.uncle_policy()?,
+				root_mismatch_dumps: self.args.arg_dump_root_mismatches.as_ref().map(|dir| replace_home(&self.directories().base, dir)),
 				warp_sync,
 				warp_barrier: self.args.arg_warp_barrier,
 				geth_compatibility,
@@ -403,9 +573,12 @@ impl Configuration {
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings,
+				ethash_conf: self.ethash_config(),
 				serve_light: !self.args.flag_no_serve_light,
 				light: self.args.flag_light,
 				no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
+				local_transactions_rebroadcast: self.args.arg_tx_queue_local_rebroadcast.map(Duration::from_secs),
+				tx_filter_file: self.args.arg_tx_filter_file.clone(),
 				no_hardcoded_sync: self.args.flag_no_hardcoded_sync,
 				max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
 				on_demand_response_time_window: self.args.arg_on_demand_response_time_window,
@@ -413,9 +586,21 @@ impl Configuration {
 				on_demand_request_backoff_max: self.args.arg_on_demand_request_backoff_max,
 				on_demand_request_backoff_rounds_max: self.args.arg_on_demand_request_backoff_rounds_max,
 				on_demand_request_consecutive_failures: self.args.arg_on_demand_request_consecutive_failures,
+				on_demand_historical_fan_out: self.args.arg_on_demand_historical_fan_out,
 				sync_until: self.args.arg_sync_until,
+				max_reorg_depth: self.args.arg_max_reorg_depth,
+				state_fork: self.state_fork()?,
 			};
-			Cmd::Run(run_cmd)
+
+			if self.args.cmd_config && self.args.cmd_config_check {
+				Cmd::ConfigCheck(ConfigCheckCmd {
+					run_cmd,
+					deprecated: find_deprecated(&self.args).iter().map(ToString::to_string).collect(),
+					conflicts: find_conflicts(&self.args),
+				})
+			} else {
+				Cmd::Run(run_cmd)
+			}
 		};
 
 		Ok(Execute {
@@ -425,13 +610,19 @@ impl Configuration {
 	}
 
 	fn miner_extras(&self) -> Result<MinerExtras, String> {
-		let floor = to_u256(&self.args.arg_gas_floor_target)?;
-		let ceil = to_u256(&self.args.arg_gas_cap)?;
+		let (floor, ceil) = match self.args.arg_gas_limit_target {
+			Some(ref target) => {
+				let target = to_u256(target)?;
+				(target, target)
+			}
+			None => (to_u256(&self.args.arg_gas_floor_target)?, to_u256(&self.args.arg_gas_cap)?),
+		};
 		let extras = MinerExtras {
 			author: self.author()?,
 			extra_data: self.extra_data()?,
 			gas_range_target: (floor, ceil),
 			engine_signer: self.engine_signer()?,
+			sealing_journal: self.args.arg_sealing_journal.as_ref().map(|path| replace_home(&self.directories().base, path)),
 			work_notify: self.work_notify(),
 			local_accounts: HashSet::from_iter(to_addresses(&self.args.arg_tx_queue_locals)?.into_iter()),
 		};
@@ -474,6 +665,19 @@ impl Configuration {
 			mode: self.args.arg_logging.clone(),
 			color: !self.args.flag_no_color && !cfg!(windows),
 			file: self.args.arg_log_file.as_ref().map(|log_file| replace_home(&self.directories().base, log_file)),
+			json: self.args.arg_log_format == "json",
+			file_max_size: match self.args.arg_log_file_max_size {
+				0 => None,
+				mb => Some(mb * 1024 * 1024),
+			},
+			file_count: self.args.arg_log_file_count,
+		}
+	}
+
+	fn validate_log_format(&self) -> Result<(), String> {
+		match self.args.arg_log_format.as_str() {
+			"text" | "json" => Ok(()),
+			other => Err(format!("Invalid log format: {}. Expected 'text' or 'json'.", other)),
 		}
 	}
 
@@ -487,6 +691,17 @@ impl Configuration {
 		Ok(name.parse()?)
 	}
 
+	fn state_fork(&self) -> Result<Option<StateForkConfig>, String> {
+		match self.args.arg_fork_url {
+			Some(ref url) => Ok(Some(StateForkConfig {
+				url: url.clone(),
+				block: self.args.arg_fork_block,
+			})),
+			None if self.args.arg_fork_block.is_some() => Err("--fork-block requires --fork-url".into()),
+			None => Ok(None),
+		}
+	}
+
 	fn is_dev_chain(&self) -> Result<bool, String> {
 		Ok(self.chain()? == SpecType::Dev)
 	}
@@ -504,6 +719,23 @@ impl Configuration {
 		}
 	}
 
+	fn peer_diversity(&self) -> Result<PeerDiversity, String> {
+		let asn_map = match self.args.arg_peer_asn_map {
+			Some(ref path) => {
+				let content = ::std::fs::read_to_string(path).map_err(|e| format!("Error reading AS map file {}: {}", path, e))?;
+				PeerDiversity::parse_asn_map(&content)?
+			},
+			None if self.args.arg_max_peers_per_asn.is_some() => return Err("--max-peers-per-asn requires --peer-asn-map".into()),
+			None => AsnMap::default(),
+		};
+		Ok(PeerDiversity {
+			max_per_subnet: self.args.arg_max_peers_per_subnet.map(|n| n as usize),
+			max_per_asn: self.args.arg_max_peers_per_asn.map(|n| n as usize),
+			asn_map,
+			max_per_client: self.args.arg_max_peers_per_client.map(|n| n as usize),
+		})
+	}
+
 	fn min_peers(&self) -> u32 {
 		self.args.arg_min_peers
 			.or(cmp::min(self.args.arg_max_peers, Some(DEFAULT_MIN_PEERS)))
@@ -522,14 +754,32 @@ impl Configuration {
 		self.args.arg_notify_work.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.to_owned()).collect())
 	}
 
+	fn keys_kdf(&self) -> Result<KdfParams, String> {
+		match self.args.arg_keys_kdf {
+			Some(ref kdf) => to_kdf_params(kdf),
+			None if self.args.arg_keys_iterations == 0 => Err("--key-iterations must be non-zero".into()),
+			None => Ok(KdfParams::Pbkdf2 { iterations: self.args.arg_keys_iterations }),
+		}
+	}
+
 	fn accounts_config(&self) -> Result<AccountsConfig, String> {
 		let cfg = AccountsConfig {
-			iterations: self.args.arg_keys_iterations,
+			kdf: self.keys_kdf()?,
 			refresh_time: self.args.arg_accounts_refresh,
 			testnet: self.args.flag_testnet,
 			password_files: self.args.arg_password.iter().map(|s| replace_home(&self.directories().base, s)).collect(),
 			unlocked_accounts: to_addresses(&self.args.arg_unlock)?,
 			enable_fast_unlock: self.args.flag_fast_unlock,
+			unlock_session: Duration::from_secs(self.args.arg_unlock_session),
+			unlock_sessions: to_unlock_sessions(&self.args.arg_unlock_sessions)?,
+			unlock_idle_timeout: match self.args.arg_unlock_idle_timeout {
+				0 => None,
+				secs => Some(Duration::from_secs(secs)),
+			},
+			remote_signer: self.args.arg_remote_signer.as_ref().map(|endpoint| RemoteSignerConfig {
+				endpoint: endpoint.clone(),
+				token_file: self.args.arg_remote_signer_token_file.as_ref().map(|s| replace_home(&self.directories().base, s)),
+			}),
 		};
 
 		Ok(cfg)
@@ -542,10 +792,92 @@ impl Configuration {
 				listen_addr: self.stratum_interface(),
 				port: self.args.arg_ports_shift + self.args.arg_stratum_port,
 				secret: self.args.arg_stratum_secret.as_ref().map(|s| s.parse::<H256>().unwrap_or_else(|_| keccak(s))),
+				vardiff: self.stratum_vardiff()?,
+				tls: self.stratum_tls()?,
 			}))
 		} else { Ok(None) }
 	}
 
+	fn uncle_policy(&self) -> Result<UnclePolicy, String> {
+		if self.args.arg_uncle_min_age == 0 {
+			return Err("Uncles must be at least one block older than the authored block, --uncle-min-age can't be 0".into());
+		}
+
+		Ok(UnclePolicy {
+			max_uncles: self.args.arg_max_uncles,
+			min_age: self.args.arg_uncle_min_age,
+			prefer_own: self.args.flag_prefer_own_uncles,
+		})
+	}
+
+	fn gossip_config(&self) -> Result<Option<GossipConfig>, String> {
+		if !self.args.flag_gossip {
+			return Ok(None);
+		}
+
+		let interface = self.interface(&self.args.arg_gossip_interface);
+		let ip = interface.parse::<IpAddr>().map_err(|_| format!("Invalid gossip interface given: {}", interface))?;
+		let protocol = if ip.is_ipv4() { "ip4" } else { "ip6" };
+		let port = self.args.arg_ports_shift + self.args.arg_gossip_port;
+		let listen_address = format!("/{}/{}/tcp/{}", protocol, ip, port).parse::<Multiaddr>()
+			.map_err(|e| format!("Invalid gossip listen address: {}", e))?;
+
+		let boot_nodes = match self.args.arg_gossip_bootnodes {
+			Some(ref nodes) => nodes.split(',')
+				.filter(|node| !node.is_empty())
+				.map(|node| node.parse::<Multiaddr>().map_err(|_| format!("Invalid gossip bootnode given: {}", node)))
+				.collect::<Result<_, _>>()?,
+			None => Vec::new(),
+		};
+
+		Ok(Some(GossipConfig {
+			listen_address,
+			boot_nodes,
+			..Default::default()
+		}))
+	}
+
+	fn stratum_vardiff(&self) -> Result<Option<stratum::VarDiffConfig>, String> {
+		let initial_difficulty = match self.args.arg_stratum_share_difficulty {
+			Some(ref difficulty) => to_u256(difficulty)?,
+			None => return Ok(None),
+		};
+		if initial_difficulty.is_zero() {
+			return Err("--stratum-share-difficulty must be greater than zero".into());
+		}
+
+		let defaults = stratum::VarDiffConfig::default();
+		let min_difficulty = match self.args.arg_stratum_min_share_difficulty {
+			Some(ref difficulty) => to_u256(difficulty)?,
+			None => cmp::min(defaults.min_difficulty, initial_difficulty),
+		};
+		if min_difficulty.is_zero() || min_difficulty > initial_difficulty {
+			return Err("--stratum-min-share-difficulty must be greater than zero and not above --stratum-share-difficulty".into());
+		}
+		if self.args.arg_stratum_share_time == 0 {
+			return Err("--stratum-share-time must be greater than zero".into());
+		}
+
+		Ok(Some(stratum::VarDiffConfig {
+			initial_difficulty,
+			min_difficulty,
+			target_share_time: Duration::from_secs(self.args.arg_stratum_share_time),
+			..defaults
+		}))
+	}
+
+	fn stratum_tls(&self) -> Result<Option<stratum::TlsOptions>, String> {
+		match (self.args.arg_stratum_tls_port, &self.args.arg_stratum_tls_cert, &self.args.arg_stratum_tls_key) {
+			(None, _, _) => Ok(None),
+			(Some(port), Some(certificate), Some(private_key)) => Ok(Some(stratum::TlsOptions {
+				port: self.args.arg_ports_shift + port,
+				certificate: certificate.clone(),
+				private_key: private_key.clone(),
+			})),
+			(Some(_), _, _) => Err("--stratum-tls-port requires --stratum-tls-cert and --stratum-tls-key".into()),
+		}
+	}
+
 	fn miner_options(&self) -> Result<MinerOptions, String> {
 		let is_dev_chain = self.is_dev_chain()?;
 		if is_dev_chain && self.args.flag_force_sealing && self.args.arg_reseal_min_period == 0 {
@@ -569,6 +901,17 @@ impl Configuration {
 
 			tx_queue_penalization: to_queue_penalization(self.args.arg_tx_time_limit)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
+			tx_queue_replacement: to_replacement_policy(&self.args.arg_tx_queue_price_bump)?,
+			tx_queue_quotas: pool::Quotas {
+				max_pending_per_sender: self.args.arg_tx_queue_pending_per_sender,
+				max_future_per_sender: self.args.arg_tx_queue_future_per_sender,
+				max_future: self.args.arg_tx_queue_future,
+			},
+			tx_queue_expiry: pool::Expiry {
+				local: to_queue_ttl(self.args.arg_tx_queue_local_ttl)?,
+				remote: to_queue_ttl(self.args.arg_tx_queue_remote_ttl)?,
+			},
+			tx_filter_authoring: self.args.flag_tx_filter_authoring,
 			tx_queue_no_unfamiliar_locals: self.args.flag_tx_queue_no_unfamiliar_locals,
 			refuse_service_transactions: self.args.flag_refuse_service_transactions,
 
@@ -707,6 +1050,18 @@ impl Configuration {
 		}
 	}
 
+	fn node_permissions(&self) -> Result<NodePermissions, String> {
+		let overrides = match self.args.arg_node_permissions_overrides {
+			Some(ref nodes) if !nodes.is_empty() => nodes.split(',').map(parse_node_id).collect::<Result<_, _>>()?,
+			_ => Vec::new(),
+		};
+		Ok(NodePermissions {
+			contract: self.args.arg_node_permissions_contract.clone().map(|a| to_address(Some(a))).transpose()?,
+			file: self.args.arg_node_permissions_file.as_ref().map(|path| replace_home(&self.directories().base, path).into()),
+			overrides,
+		})
+	}
+
 	fn net_addresses(&self) -> Result<(SocketAddr, Option<SocketAddr>), String> {
 		let port = self.args.arg_ports_shift + self.args.arg_port;
 		let listen_address = SocketAddr::new(self.interface(&self.args.arg_interface).parse().unwrap(), port);
@@ -731,11 +1086,12 @@ impl Configuration {
 
 	fn net_config(&self) -> Result<NetworkConfiguration, String> {
 		let mut ret = NetworkConfiguration::new();
-		ret.nat_enabled = self.args.arg_nat == "any" || self.args.arg_nat == "upnp" || self.args.arg_nat == "natpmp";
+		ret.nat_enabled = ["any", "upnp", "natpmp", "pcp"].contains(&&self.args.arg_nat[..]);
 		ret.nat_type = match &self.args.arg_nat[..] {
 			"any" => NatType::Any,
 			"upnp" => NatType::UPnP,
 			"natpmp" => NatType::NatPMP,
+			"pcp" => NatType::PCP,
 			_ => NatType::Nothing,
 		};
 		ret.boot_nodes = to_bootnodes(&self.args.arg_bootnodes)?;
@@ -754,6 +1110,7 @@ impl Configuration {
 		ret.min_peers = self.min_peers();
 		ret.snapshot_peers = self.snapshot_peers();
 		ret.ip_filter = self.ip_filter()?;
+		ret.peer_diversity = self.peer_diversity()?;
 		ret.max_pending_peers = self.max_pending_peers();
 		let mut net_path = PathBuf::from(self.directories().base);
 		net_path.push("network");
@@ -882,6 +1239,37 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn request_pools_config(&self) -> Result<RequestPoolsConfiguration, String> {
+		let mut conf = RequestPoolsConfiguration::default();
+		conf.shared = RequestPoolConfiguration {
+			threads: cmp::max(1, self.args.arg_jsonrpc_heavy_threads),
+			max_queued: cmp::max(1, self.args.arg_jsonrpc_heavy_queue),
+		};
+
+		let pools = self.args.arg_jsonrpc_pools.as_ref().map_or("", String::as_str);
+		for pool in pools.split(',').map(str::trim).filter(|pool| !pool.is_empty()) {
+			let invalid = || format!("Invalid request pool: {}. Expected KIND:THREADS:QUEUE.", pool);
+			let parts = pool.split(':').collect::<Vec<_>>();
+			if parts.len() != 3 {
+				return Err(invalid());
+			}
+			let threads = parts[1].parse::<usize>().map_err(|_| invalid())?;
+			let max_queued = parts[2].parse::<usize>().map_err(|_| invalid())?;
+			let pool_conf = Some(RequestPoolConfiguration {
+				threads: cmp::max(1, threads),
+				max_queued: cmp::max(1, max_queued),
+			});
+			match parts[0] {
+				"traces" => conf.traces = pool_conf,
+				"logs" => conf.logs = pool_conf,
+				"debug" => conf.debug = pool_conf,
+				other => return Err(format!("Invalid request pool kind: {}. Expected traces, logs or debug.", other)),
+			}
+		}
+
+		Ok(conf)
+	}
+
 	fn ws_config(&self) -> Result<WsConfiguration, String> {
 		let support_token_api =
 			// enabled when not unlocking
@@ -902,6 +1290,131 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn engine_api_config(&self) -> EngineApiConfiguration {
+		let base = self.directories().base;
+		let jwt_secret = self.args.arg_jwt_secret.as_ref().map_or("$BASE/jwtsecret", |s| s.as_str());
+
+		EngineApiConfiguration {
+			enabled: self.args.flag_engine_api,
+			interface: self.interface(&self.args.arg_engine_api_interface),
+			port: self.args.arg_ports_shift + self.args.arg_engine_api_port,
+			jwt_secret: replace_home(&base, jwt_secret).into(),
+		}
+	}
+
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			prefix: self.args.arg_metrics_prefix.clone(),
+			interface: self.interface(&self.args.arg_metrics_interface),
+			port: self.args.arg_ports_shift + self.args.arg_metrics_port,
+		}
+	}
+
+	fn ipfs_config(&self) -> IpfsConfiguration {
+		IpfsConfiguration {
+			enabled: self.args.flag_ipfs_api,
+			interface: self.interface(&self.args.arg_ipfs_api_interface),
+			port: self.args.arg_ports_shift + self.args.arg_ipfs_api_port,
+		}
+	}
+
+	fn otlp_config(&self) -> OtlpConfiguration {
+		OtlpConfiguration {
+			endpoint: self.args.arg_otlp_endpoint.clone(),
+			service_name: self.args.arg_otlp_service_name.clone(),
+		}
+	}
+
+	fn webhook_config(&self) -> Result<WebhookConfiguration, String> {
+		let urls = match self.args.arg_webhook_urls {
+			Some(ref urls) if !urls.is_empty() => urls.split(',').map(Into::into).collect(),
+			_ => Vec::new(),
+		};
+		let secret = match self.args.arg_webhook_secret_file {
+			Some(ref path) => Some(::std::fs::read_to_string(path)
+				.map_err(|e| format!("Error reading webhook secret file {}: {}", path, e))?
+				.trim()
+				.to_owned()),
+			None => None,
+		};
+		Ok(WebhookConfiguration {
+			urls,
+			secret,
+			log_addresses: to_addresses(&self.args.arg_webhook_log_addresses)?,
+			log_topics: to_hashes(&self.args.arg_webhook_log_topics)?,
+			max_retries: self.args.arg_webhook_max_retries,
+		})
+	}
+
+	fn shutdown_config(&self) -> Result<ShutdownConfiguration, String> {
+		Ok(ShutdownConfiguration {
+			timeout: Duration::from_secs(self.args.arg_shutdown_timeout),
+			phases: to_shutdown_phases(&self.args.arg_shutdown_phases)?,
+		})
+	}
+
+	/// Move the base path and shift the ports to those of the named profile, see `profile`.
+	///
+	/// The profile option is cleared, so that it is only applied once.
+	pub(crate) fn apply_profile(&mut self) -> Result<Option<ProfileConfiguration>, String> {
+		let name = match self.args.arg_profile.take() {
+			Some(name) => name,
+			None => return Ok(None),
+		};
+
+		let profiles_dir = Path::new(&self.directories().base).join("profiles");
+		let profile = profile::resolve(&profiles_dir, &name)?;
+		self.args.arg_base_path = Some(profile.base_path().to_string_lossy().into_owned());
+		self.args.arg_ports_shift = self.args.arg_ports_shift.checked_add(profile.ports_shift())
+			.ok_or_else(|| format!("--ports-shift is too large for profile {}", name))?;
+
+		Ok(Some(profile))
+	}
+
+	/// Check that every port stays in range once shifted by `--ports-shift` and the profile.
+	fn validate_ports_shift(&self) -> Result<(), String> {
+		let ports = [
+			("--port", Some(self.args.arg_port)),
+			("--jsonrpc-port", Some(self.args.arg_rpcport.unwrap_or(self.args.arg_jsonrpc_port))),
+			("--ws-port", Some(self.args.arg_ws_port)),
+			("--engine-api-port", Some(self.args.arg_engine_api_port)),
+			("--metrics-port", Some(self.args.arg_metrics_port)),
+			("--ipfs-api-port", Some(self.args.arg_ipfs_api_port)),
+			("--stratum-port", Some(self.args.arg_stratum_port)),
+			("--stratum-tls-port", self.args.arg_stratum_tls_port),
+			("--gossip-port", Some(self.args.arg_gossip_port)),
+			("--secretstore-port", Some(self.args.arg_secretstore_port)),
+			("--secretstore-http-port", Some(self.args.arg_secretstore_http_port)),
+		];
+		for &(option, port) in ports.iter() {
+			if let Some(port) = port {
+				if port.checked_add(self.args.arg_ports_shift).is_none() {
+					return Err(format!("{} {} is out of range when shifted by {}", option, port, self.args.arg_ports_shift));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Settings that can be applied to a running node, see `reload`.
+	pub(crate) fn reloadable_config(&self) -> Result<ReloadableConfig, String> {
+		let logging = self.args.arg_logging.clone().unwrap_or_default();
+		validate_levels(&logging)?;
+		let miner_options = self.miner_options()?;
+
+		Ok(ReloadableConfig {
+			logging,
+			min_peers: self.min_peers(),
+			max_peers: self.max_peers(),
+			reserved_peers: self.init_reserved_nodes()?,
+			reserved_only: self.args.flag_reserved_only,
+			tx_queue_quotas: miner_options.tx_queue_quotas,
+			tx_queue_expiry: miner_options.tx_queue_expiry,
+			gas_pricer: self.gas_pricer_config()?,
+		})
+	}
+
 	fn private_provider_config(&self) -> Result<(ProviderConfig, EncryptorConfig, bool), String> {
 		let dirs = self.directories();
 		let provider_conf = ProviderConfig {
@@ -1156,6 +1669,14 @@ impl Configuration {
 
 		settings
 	}
+
+	fn ethash_config(&self) -> EthashConfig {
+		EthashConfig {
+			pregenerate_epochs: self.args.arg_ethash_pregenerate_epochs,
+			max_cache_memory: self.args.arg_ethash_cache_memory * 1024 * 1024,
+			light_verification_below: self.args.arg_ethash_light_verification_below.unwrap_or(0),
+		}
+	}
 }
 
 fn into_secretstore_service_contract_address(s: Option<&String>) -> Result<Option<SecretStoreContractAddress>, String> {
@@ -1174,13 +1695,15 @@ mod tests {
 
 	use tempfile::TempDir;
 	use ethcore::miner::MinerOptions;
-	use miner::pool::PrioritizationStrategy;
+	use ethcore::client::{DatabaseCompactionProfile, RewriteWindow};
+	use miner::pool::{PrioritizationStrategy, Quotas, Expiry};
 	use parity_rpc::NetworkSettings;
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 	use types::ids::BlockId;
 	use types::data_format::DataFormat;
-	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, ExportState};
+	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts, ReencryptKeystore};
+	use ethstore::KdfParams;
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, ExportState, ExportAnalytics, AnalyticsFormat};
 	use cli::Args;
 	use dir::{Directories, default_hypervisor_path};
 	use helpers::{default_network_config};
@@ -1189,6 +1712,7 @@ mod tests {
 	use rpc::WsConfiguration;
 	use rpc_apis::ApiSet;
 	use run::RunCmd;
+	use shutdown::ShutdownPhase;
 
 	use network::{AllowIP, IpFilter};
 
@@ -1218,7 +1742,7 @@ mod tests {
 		let args = vec!["parity", "account", "new"];
 		let conf = parse(&args);
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
-			iterations: 10240,
+			kdf: KdfParams::Pbkdf2 { iterations: 10240 },
 			path: Directories::default().keys,
 			password_file: None,
 			spec: SpecType::default(),
@@ -1248,12 +1772,28 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_account_reencrypt() {
+		let args = vec!["parity", "account", "reencrypt", "--vault", "work", "--kdf", "pbkdf2:262144", "--password", "pwd"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::Reencrypt(ReencryptKeystore {
+			path: Directories::default().keys,
+			spec: SpecType::default(),
+			vault: Some("work".into()),
+			kdf: KdfParams::Pbkdf2 { iterations: 262144 },
+			password_files: vec!["pwd".into()],
+		})));
+
+		let args = vec!["parity", "account", "reencrypt", "--kdf", "scrypt:1000:8:1"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
 	#[test]
 	fn test_command_wallet_import() {
 		let args = vec!["parity", "wallet", "import", "my_wallet.json", "--password", "pwd"];
 		let conf = parse(&args);
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::ImportPresaleWallet(ImportWallet {
-			iterations: 10240,
+			kdf: KdfParams::Pbkdf2 { iterations: 10240 },
 			path: Directories::default().keys,
 			wallet_path: "my_wallet.json".into(),
 			password_file: Some("pwd".into()),
@@ -1270,6 +1810,7 @@ mod tests {
 			cache_config: Default::default(),
 			dirs: Default::default(),
 			file_path: Some("blockchain.json".into()),
+			from_ipc: None,
 			format: Default::default(),
 			pruning: Default::default(),
 			pruning_history: 128,
@@ -1285,6 +1826,46 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_blockchain_import_from_ipc() {
+		let args = vec!["parity", "import", "--from-ipc=/tmp/parity-local-sync.ipc"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Blockchain(BlockchainCmd::Import(import_cmd)) => {
+				assert_eq!(import_cmd.file_path, None);
+				assert_eq!(import_cmd.from_ipc, Some("/tmp/parity-local-sync.ipc".into()));
+			},
+			_ => panic!("Should be import command"),
+		}
+	}
+
+	#[test]
+	fn test_command_snapshot_upload() {
+		let args = vec!["parity", "snapshot-upload", "https://bucket.s3.amazonaws.com/mainnet"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Snapshot(upload_cmd) => {
+				assert_eq!(upload_cmd.kind, snapshot_cmd::Kind::Upload);
+				assert_eq!(upload_cmd.url, Some("https://bucket.s3.amazonaws.com/mainnet".into()));
+				assert_eq!(upload_cmd.file_path, None);
+			},
+			_ => panic!("Should be snapshot command"),
+		}
+	}
+
+	#[test]
+	fn test_command_restore_from_url() {
+		let args = vec!["parity", "restore", "--from-url=https://bucket.s3.amazonaws.com/mainnet"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Snapshot(restore_cmd) => {
+				assert_eq!(restore_cmd.kind, snapshot_cmd::Kind::Restore);
+				assert_eq!(restore_cmd.url, Some("https://bucket.s3.amazonaws.com/mainnet".into()));
+			},
+			_ => panic!("Should be snapshot command"),
+		}
+	}
+
 	#[test]
 	fn test_command_blockchain_export() {
 		let args = vec!["parity", "export", "blocks", "blockchain.json"];
@@ -1308,6 +1889,45 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_db_tier() {
+		let args = vec!["parity", "db", "tier", "--cold-path=/mnt/cold", "--parts=receipts,traces", "--hot-blocks=1000"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Tier(TierBlockchain {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			cold_path: Some("/mnt/cold".into()),
+			parts: vec![db::ColdPart::Receipts, db::ColdPart::Traces],
+			hot_blocks: 1000,
+		})));
+
+		let args = vec!["parity", "db", "tier", "--parts=none"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Tier(TierBlockchain {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			cold_path: None,
+			parts: vec![],
+			hot_blocks: 90000,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_stats() {
+		let args = vec!["parity", "db", "stats", "--db-compaction=low-memory"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Stats(StatsBlockchain {
+			spec: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			compaction: DatabaseCompactionProfile::LowMemory,
+		})));
+	}
+
 	#[test]
 	fn test_command_state_export() {
 		let args = vec!["parity", "export", "state", "state.json"];
@@ -1333,6 +1953,35 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_analytics_export() {
+		let args = vec!["parity", "export", "analytics", "--from", "10", "--to", "20", "--threads", "2", "out"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::ExportAnalytics(ExportAnalytics {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			out_dir: "out".into(),
+			format: AnalyticsFormat::Csv,
+			pruning: Default::default(),
+			pruning_history: 128,
+			pruning_memory: 64,
+			compaction: Default::default(),
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			from_block: BlockId::Number(10),
+			to_block: BlockId::Number(20),
+			threads: 2,
+			max_round_blocks_to_import: 12,
+		})));
+
+		assert!(parse(&["parity", "export", "analytics", "--format", "json"]).into_command().is_err());
+		match parse(&["parity", "export", "analytics", "--format", "parquet"]).into_command().unwrap().cmd {
+			Cmd::Blockchain(BlockchainCmd::ExportAnalytics(cmd)) => assert_eq!(cmd.format, AnalyticsFormat::Parquet),
+			_ => panic!("Expected an analytics export"),
+		}
+	}
+
 	#[test]
 	fn test_command_blockchain_export_with_custom_format() {
 		let args = vec!["parity", "export", "blocks", "--format", "hex", "blockchain.json"];
@@ -1375,6 +2024,9 @@ mod tests {
 			color: !cfg!(windows),
 			mode: None,
 			file: None,
+			json: false,
+			file_max_size: None,
+			file_count: 5,
 		} ));
 	}
 
@@ -1395,21 +2047,49 @@ mod tests {
 		let conf = parse(&args);
 		let mut expected = RunCmd {
 			allow_missing_blocks: false,
+			reconstruct_logs: false,
 			cache_config: Default::default(),
 			dirs: Default::default(),
 			spec: Default::default(),
 			pruning: Default::default(),
 			pruning_history: 128,
 			pruning_memory: 64,
+			pruning_checkpoints: 0,
+			pruning_checkpoints_kept: 16,
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
 			gas_price_percentile: 50,
 			poll_lifetime: 60,
+			poll_max_per_origin: None,
+			poll_max_total: 10000,
+			poll_persist: false,
+			request_pools: Default::default(),
+			rpc_dedup_ttl: None,
+			rpc_call_cache: 0,
 			ws_conf: Default::default(),
 			http_conf: Default::default(),
 			ipc_conf: Default::default(),
+			engine_api_conf: Default::default(),
+			metrics_conf: Default::default(),
+			ipfs_conf: Default::default(),
+			otlp_conf: Default::default(),
+			webhook_conf: Default::default(),
+			shutdown_conf: Default::default(),
+			reload_conf: ReloadableConfig {
+				logging: "".into(),
+				min_peers: 25,
+				max_peers: 50,
+				reserved_peers: vec![],
+				reserved_only: false,
+				tx_queue_quotas: Default::default(),
+				tx_queue_expiry: Default::default(),
+				gas_pricer: Default::default(),
+			},
+			profile: None,
 			net_conf: default_network_config(),
+			node_permissions: Default::default(),
+			local_sync_socket: None,
 			network_id: None,
 			warp_sync: true,
 			warp_barrier: None,
@@ -1429,6 +2109,15 @@ mod tests {
 			mode: Default::default(),
 			tracing: Default::default(),
 			compaction: Default::default(),
+			db_compression: false,
+			db_statistics: false,
+			db_rewrite_window: None,
+			state_cache_ratios: Default::default(),
+			state_prefetch: true,
+			commit_pipeline: false,
+			commit_batch_blocks: 0,
+			uncle_policy: Default::default(),
+			root_mismatch_dumps: None,
 			geth_compatibility: false,
 			experimental_rpcs: false,
 			net_settings: Default::default(),
@@ -1441,20 +2130,27 @@ mod tests {
 			fat_db: Default::default(),
 			snapshot_conf: Default::default(),
 			stratum: None,
+			gossip: None,
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
+			ethash_conf: Default::default(),
 			serve_light: true,
 			light: false,
 			no_hardcoded_sync: false,
 			no_persistent_txqueue: false,
+			local_transactions_rebroadcast: None,
+			tx_filter_file: None,
 			max_round_blocks_to_import: 12,
 			on_demand_response_time_window: None,
 			on_demand_request_backoff_start: None,
 			on_demand_request_backoff_max: None,
 			on_demand_request_backoff_rounds_max: None,
 			on_demand_request_consecutive_failures: None,
+			on_demand_historical_fan_out: None,
 			sync_until: None,
+			max_reorg_depth: None,
+			state_fork: None,
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		expected.secretstore_conf.http_enabled = cfg!(feature = "secretstore");
@@ -1592,6 +2288,370 @@ mod tests {
 		assert_eq!(http_conf.max_payload, 5);
 	}
 
+	#[test]
+	fn should_parse_heavy_request_threads() {
+		let shared = |conf: Configuration| conf.request_pools_config().unwrap().shared.threads;
+
+		assert_eq!(shared(parse(&["parity"])), 4);
+		assert_eq!(shared(parse(&["parity", "--jsonrpc-heavy-threads=8"])), 8);
+		assert_eq!(shared(parse(&["parity", "--jsonrpc-heavy-threads=0"])), 1);
+	}
+
+	#[test]
+	fn should_parse_request_pools() {
+		let conf = parse(&["parity", "--jsonrpc-heavy-queue=64", "--jsonrpc-pools=traces:2:32, logs:8:512"]);
+		let pools = conf.request_pools_config().unwrap();
+
+		assert_eq!(pools.shared, RequestPoolConfiguration { threads: 4, max_queued: 64 });
+		assert_eq!(pools.traces, Some(RequestPoolConfiguration { threads: 2, max_queued: 32 }));
+		assert_eq!(pools.logs, Some(RequestPoolConfiguration { threads: 8, max_queued: 512 }));
+		assert_eq!(pools.debug, None);
+
+		assert!(parse(&["parity", "--jsonrpc-pools=traces:2"]).request_pools_config().is_err());
+		assert!(parse(&["parity", "--jsonrpc-pools=blocks:2:32"]).request_pools_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_pruning_checkpoints() {
+		let checkpoints = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => c.pruning_checkpoints,
+			_ => panic!("Should be Cmd::Run"),
+		};
+
+		assert_eq!(checkpoints(&["parity"]), 0);
+		assert_eq!(checkpoints(&["parity", "--pruning-checkpoints=4096"]), 4096);
+	}
+
+	#[test]
+	fn should_parse_engine_api_settings() {
+		let conf = parse(&["parity",
+			"--engine-api",
+			"--engine-api-port=9551",
+			"--engine-api-interface=all",
+			"--jwt-secret=/tmp/jwt.hex",
+		]);
+
+		let engine_api_conf = conf.engine_api_config();
+		assert!(engine_api_conf.enabled);
+		assert_eq!(engine_api_conf.port, 9551);
+		assert_eq!(&engine_api_conf.interface, "0.0.0.0");
+		assert_eq!(engine_api_conf.jwt_secret, ::std::path::PathBuf::from("/tmp/jwt.hex"));
+	}
+
+	#[test]
+	fn should_parse_metrics_settings() {
+		assert_eq!(parse(&["parity"]).metrics_config(), MetricsConfiguration::default());
+
+		let conf = parse(&["parity", "--metrics", "--metrics-port=9100", "--metrics-interface=all", "--metrics-prefix=node_"]);
+		assert_eq!(conf.metrics_config(), MetricsConfiguration {
+			enabled: true,
+			prefix: "node_".into(),
+			interface: "0.0.0.0".into(),
+			port: 9100,
+		});
+	}
+
+	#[test]
+	fn should_parse_ipfs_settings() {
+		assert_eq!(parse(&["parity"]).ipfs_config(), IpfsConfiguration::default());
+
+		let conf = parse(&["parity", "--ipfs-api", "--ipfs-api-port=5002", "--ipfs-api-interface=all"]);
+		assert_eq!(conf.ipfs_config(), IpfsConfiguration {
+			enabled: true,
+			interface: "0.0.0.0".into(),
+			port: 5002,
+		});
+	}
+
+	#[test]
+	fn should_parse_otlp_settings() {
+		assert_eq!(parse(&["parity"]).otlp_config(), OtlpConfiguration::default());
+
+		let conf = parse(&["parity", "--otlp-endpoint=http://collector:4318", "--otlp-service-name=validator-1"]);
+		assert_eq!(conf.otlp_config(), OtlpConfiguration {
+			endpoint: Some("http://collector:4318".into()),
+			service_name: "validator-1".into(),
+		});
+	}
+
+	#[test]
+	fn should_parse_webhook_settings() {
+		assert_eq!(parse(&["parity"]).webhook_config().unwrap(), WebhookConfiguration::default());
+
+		let tempdir = TempDir::new().unwrap();
+		let secret_file = tempdir.path().join("webhook.secret");
+		File::create(&secret_file).unwrap().write_all(b"secret\n").unwrap();
+		let secret_arg = format!("--webhook-secret-file={}", secret_file.display());
+		let conf = parse(&[
+			"parity",
+			"--webhook-urls=http://a/events,http://b/events",
+			&secret_arg,
+			"--webhook-log-addresses=0x0000000000000000000000000000000000000001",
+			"--webhook-log-topics=0x0000000000000000000000000000000000000000000000000000000000000002",
+			"--webhook-max-retries=2",
+		]);
+		assert_eq!(conf.webhook_config().unwrap(), WebhookConfiguration {
+			urls: vec!["http://a/events".into(), "http://b/events".into()],
+			secret: Some("secret".into()),
+			log_addresses: vec![Address::from_low_u64_be(1)],
+			log_topics: vec![H256::from_low_u64_be(2)],
+			max_retries: 2,
+		});
+
+		assert!(parse(&["parity", "--webhook-log-topics=0x12"]).webhook_config().is_err());
+		assert!(parse(&["parity", "--webhook-secret-file=/nonexistent/webhook.secret"]).webhook_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_node_permissions() {
+		assert_eq!(parse(&["parity"]).node_permissions().unwrap(), NodePermissions::default());
+
+		let node = "a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c";
+		let conf = parse(&[
+			"parity",
+			"--node-permissions-contract=0x0000000000000000000000000000000000000005",
+			"--node-permissions-file=/tmp/permitted_nodes",
+			&format!("--node-permissions-overrides=enode://{}@22.99.55.44:7770,{}", node, node),
+		]);
+		assert_eq!(conf.node_permissions().unwrap(), NodePermissions {
+			contract: Some(Address::from_low_u64_be(5)),
+			file: Some("/tmp/permitted_nodes".into()),
+			overrides: vec![node.parse().unwrap(), node.parse().unwrap()],
+		});
+
+		assert!(parse(&["parity", "--node-permissions-overrides=enode://1234@22.99.55.44:7770"]).node_permissions().is_err());
+	}
+
+	#[test]
+	fn should_parse_peer_diversity() {
+		assert_eq!(parse(&["parity"]).peer_diversity().unwrap(), PeerDiversity::default());
+
+		let conf = parse(&["parity", "--max-peers-per-subnet=4", "--max-peers-per-client=20"]);
+		assert_eq!(conf.peer_diversity().unwrap(), PeerDiversity {
+			max_per_subnet: Some(4),
+			max_per_client: Some(20),
+			..Default::default()
+		});
+
+		assert!(parse(&["parity", "--max-peers-per-asn=3"]).peer_diversity().is_err());
+	}
+
+	#[test]
+	fn should_parse_shutdown_settings() {
+		assert_eq!(parse(&["parity"]).shutdown_config().unwrap(), ShutdownConfiguration::default());
+
+		let conf = parse(&["parity", "--shutdown-timeout=10", "--shutdown-phases=journal,fsync"]);
+		assert_eq!(conf.shutdown_config().unwrap(), ShutdownConfiguration {
+			timeout: Duration::from_secs(10),
+			phases: vec![ShutdownPhase::Journal, ShutdownPhase::Fsync],
+		});
+
+		assert!(parse(&["parity", "--shutdown-phases=all"]).shutdown_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_config_subcommands() {
+		let conf = parse(&["parity", "config", "check", "--no-warp", "--warp-barrier=10", "--warp"]);
+		match conf.into_command().unwrap().cmd {
+			Cmd::ConfigCheck(check) => {
+				assert_eq!(check.deprecated, vec!["Option '--warp' does nothing. It's on by default.".to_owned()]);
+				assert_eq!(check.conflicts, vec!["--warp-barrier has no effect with --no-warp.".to_owned()]);
+			},
+			_ => panic!("Should be a config check command"),
+		}
+
+		let conf = parse(&["parity", "config", "dump", "--min-peers=10"]);
+		match conf.into_command().unwrap().cmd {
+			Cmd::ConfigDump(config) => assert!(config.contains("min_peers = 10")),
+			_ => panic!("Should be a config dump command"),
+		}
+	}
+
+	#[test]
+	fn should_parse_spec_subcommands() {
+		let conf = parse(&["parity", "spec", "new", "--engine=clique", "--chain-id=99", "--validators=0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e", "--forks=byzantium=0", "spec.json"]);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Spec(SpecCmd::New(NewSpec {
+			engine: Some("clique".into()),
+			chain_id: Some(99),
+			validators: Some("0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e".into()),
+			forks: Some("byzantium=0".into()),
+			file: Some("spec.json".into()),
+			..Default::default()
+		})));
+
+		let conf = parse(&["parity", "spec", "check", "spec.json"]);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Spec(SpecCmd::Check("spec.json".into())));
+
+		let conf = parse(&["parity", "spec", "from-geth", "genesis.json"]);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Spec(SpecCmd::FromGeth { genesis: "genesis.json".into(), file: None }));
+
+		let conf = parse(&["parity", "spec", "to-geth", "spec.json", "genesis.json"]);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Spec(SpecCmd::ToGeth { spec: "spec.json".into(), file: Some("genesis.json".into()) }));
+	}
+
+	#[test]
+	fn should_parse_state_fork() {
+		let run_cmd = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(run_cmd) => run_cmd,
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(run_cmd(&["parity", "--chain=dev"]).state_fork, None);
+		assert_eq!(run_cmd(&["parity", "--chain=dev", "--fork-url=http://127.0.0.1:8545"]).state_fork, Some(StateForkConfig {
+			url: "http://127.0.0.1:8545".into(),
+			block: None,
+		}));
+		assert_eq!(run_cmd(&["parity", "--chain=dev", "--fork-url=http://127.0.0.1:8545", "--fork-block=100"]).state_fork, Some(StateForkConfig {
+			url: "http://127.0.0.1:8545".into(),
+			block: Some(100),
+		}));
+		assert!(parse(&["parity", "--chain=dev", "--fork-block=100"]).into_command().is_err());
+	}
+
+	#[test]
+	fn should_apply_profile() {
+		let tempdir = TempDir::new().unwrap();
+		let base_path = tempdir.path().to_str().unwrap();
+		let conf = parse(&["parity", "--base-path", base_path, "--profile=goerli", "--ports-shift=1"]);
+		let run_cmd = match conf.into_command().unwrap().cmd {
+			Cmd::Run(run_cmd) => run_cmd,
+			_ => panic!("Should be a run command"),
+		};
+
+		let profile_path = tempdir.path().join("profiles").join("goerli");
+		assert_eq!(run_cmd.profile.as_ref().map(|p| p.slot), Some(1));
+		assert_eq!(Path::new(&run_cmd.dirs.base), profile_path.as_path());
+		assert_eq!(Path::new(&run_cmd.dirs.keys), profile_path.join("keys").as_path());
+		assert_eq!(run_cmd.http_conf.port, 8545 + 101);
+		assert_eq!(run_cmd.ws_conf.port, 8546 + 101);
+		assert_eq!(run_cmd.net_conf.listen_address, Some("0.0.0.0:30404".into()));
+
+		assert!(parse(&["parity", "--base-path", base_path, "--profile=../goerli"]).into_command().is_err());
+	}
+
+	#[test]
+	fn should_parse_ethash_config() {
+		let conf = parse(&["parity", "--ethash-pregenerate-epochs=0", "--ethash-cache-memory=64", "--ethash-light-verification-below=4370000"]);
+		assert_eq!(conf.ethash_config(), EthashConfig {
+			pregenerate_epochs: 0,
+			max_cache_memory: 64 * 1024 * 1024,
+			light_verification_below: 4370000,
+		});
+		assert_eq!(parse(&["parity"]).ethash_config(), EthashConfig::default());
+	}
+
+	#[test]
+	fn should_parse_reloadable_config() {
+		let conf = parse(&["parity", "--chain=dev", "--logging=sync=debug", "--min-peers=10", "--max-peers=20", "--reserved-only", "--tx-queue-remote-ttl=60"]);
+		let reloadable = conf.reloadable_config().unwrap();
+		assert_eq!(reloadable.logging, "sync=debug");
+		assert_eq!((reloadable.min_peers, reloadable.max_peers), (10, 20));
+		assert!(reloadable.reserved_only);
+		assert_eq!(reloadable.tx_queue_expiry.remote, Some(Duration::from_secs(60)));
+		assert_eq!(reloadable.gas_pricer, GasPricerConfig::Fixed(U256::zero()));
+
+		assert!(parse(&["parity", "--logging=sync=loud"]).reloadable_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_log_output_settings() {
+		let conf = parse(&["parity"]);
+		let log = conf.logger_config();
+		assert!(!log.json);
+		assert_eq!(log.file_max_size, None);
+		assert_eq!(log.file_count, 5);
+
+		let conf = parse(&["parity", "--log-format=json", "--log-file-max-size=10", "--log-file-count=3"]);
+		let log = conf.logger_config();
+		assert!(log.json);
+		assert_eq!(log.file_max_size, Some(10 * 1024 * 1024));
+		assert_eq!(log.file_count, 3);
+
+		assert!(parse(&["parity", "--log-format=xml"]).into_command().is_err());
+	}
+
+	#[test]
+	fn should_parse_gas_limit_target() {
+		let conf = parse(&["parity", "--gas-floor-target=1000", "--gas-limit-target=12000000"]);
+		assert_eq!(conf.miner_extras().unwrap().gas_range_target, (12_000_000.into(), 12_000_000.into()));
+
+		let conf = parse(&["parity", "--gas-floor-target=1000", "--gas-cap=2000"]);
+		assert_eq!(conf.miner_extras().unwrap().gas_range_target, (1_000.into(), 2_000.into()));
+	}
+
+	#[test]
+	fn should_parse_sealing_journal() {
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.miner_extras().unwrap().sealing_journal, None);
+
+		let conf = parse(&["parity", "--base-path=/tmp/base", "--sealing-journal=$BASE/sealing.journal"]);
+		assert_eq!(conf.miner_extras().unwrap().sealing_journal, Some("/tmp/base/sealing.journal".into()));
+	}
+
+	#[test]
+	fn should_parse_root_mismatch_dumps() {
+		let root_mismatch_dumps = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => c.root_mismatch_dumps,
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(root_mismatch_dumps(&["parity"]), None);
+		assert_eq!(root_mismatch_dumps(&["parity", "--base-path=/tmp/base", "--dump-root-mismatches=$BASE/mismatches"]), Some("/tmp/base/mismatches".into()));
+	}
+
+	#[test]
+	fn should_parse_db_rewrite_window() {
+		let window = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => c.db_rewrite_window,
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(window(&["parity"]), None);
+		assert_eq!(window(&["parity", "--db-rewrite-window=22:30-04:00"]), Some(RewriteWindow { start: 22 * 60 + 30, end: 4 * 60 }));
+		assert!(parse(&["parity", "--db-rewrite-window=22:30"]).into_command().is_err());
+	}
+
+	#[test]
+	fn should_parse_rpc_dedup_ttl() {
+		let ttl = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => c.rpc_dedup_ttl,
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(ttl(&["parity"]), None);
+		assert_eq!(ttl(&["parity", "--jsonrpc-dedup-ttl=0"]), Some(Duration::from_millis(0)));
+		assert_eq!(ttl(&["parity", "--jsonrpc-dedup-ttl=250"]), Some(Duration::from_millis(250)));
+	}
+
+	#[test]
+	fn should_parse_poll_limits() {
+		let limits = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => (c.poll_lifetime, c.poll_max_per_origin, c.poll_max_total, c.poll_persist),
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(limits(&["parity"]), (60, None, 10000, false));
+		assert_eq!(
+			limits(&["parity", "--poll-lifetime=0", "--poll-max-per-origin=16", "--poll-max-total=64", "--poll-persist"]),
+			(0, Some(16), 64, true)
+		);
+	}
+
+	#[test]
+	fn should_parse_rpc_call_cache() {
+		let size = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => c.rpc_call_cache,
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(size(&["parity"]), 0);
+		assert_eq!(size(&["parity", "--jsonrpc-call-cache=4096"]), 4096);
+	}
+
+	#[test]
+	fn should_parse_max_reorg_depth() {
+		let max_reorg_depth = |args: &[&str]| match parse(args).into_command().unwrap().cmd {
+			Cmd::Run(c) => c.max_reorg_depth,
+			_ => panic!("Should be Cmd::Run"),
+		};
+		assert_eq!(max_reorg_depth(&["parity"]), None);
+		assert_eq!(max_reorg_depth(&["parity", "--max-reorg-depth=6"]), Some(6));
+	}
+
 	#[test]
 	fn should_parse_ui_configuration() {
 		// given
@@ -1756,6 +2816,126 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn should_parse_stratum_share_difficulty_and_tls() {
+		let conf = parse(&[
+			"parity", "--stratum", "--stratum-share-difficulty", "2000000000", "--stratum-share-time", "10",
+			"--stratum-tls-port", "8009", "--stratum-tls-cert", "stratum.crt", "--stratum-tls-key", "stratum.key",
+		]);
+		let options = conf.stratum_options().unwrap().unwrap();
+
+		let vardiff = options.vardiff.unwrap();
+		assert_eq!(vardiff.initial_difficulty, 2_000_000_000u64.into());
+		assert_eq!(vardiff.min_difficulty, 100_000_000u64.into());
+		assert_eq!(vardiff.target_share_time, Duration::from_secs(10));
+		assert_eq!(options.tls, Some(stratum::TlsOptions {
+			port: 8009,
+			certificate: "stratum.crt".into(),
+			private_key: "stratum.key".into(),
+		}));
+
+		assert_eq!(parse(&["parity", "--stratum"]).stratum_options().unwrap().unwrap().vardiff, None);
+		assert!(parse(&["parity", "--stratum", "--stratum-tls-port", "8009"]).stratum_options().is_err());
+		assert!(parse(&["parity", "--stratum", "--stratum-share-difficulty", "1000", "--stratum-min-share-difficulty", "2000"])
+			.stratum_options().is_err());
+	}
+
+	#[test]
+	fn should_parse_gossip_options() {
+		assert_eq!(parse(&["parity"]).gossip_config().unwrap(), None);
+
+		let conf = parse(&[
+			"parity", "--gossip", "--gossip-interface", "local", "--gossip-port", "4000",
+			"--gossip-bootnodes", "/ip4/10.0.0.1/tcp/30305,/ip6/::1/tcp/30305",
+		]);
+		assert_eq!(conf.gossip_config().unwrap(), Some(GossipConfig {
+			listen_address: "/ip4/127.0.0.1/tcp/4000".parse().unwrap(),
+			boot_nodes: vec!["/ip4/10.0.0.1/tcp/30305".parse().unwrap(), "/ip6/::1/tcp/30305".parse().unwrap()],
+			..Default::default()
+		}));
+
+		let conf = parse(&["parity", "--gossip", "--gossip-interface", "::1"]);
+		assert_eq!(conf.gossip_config().unwrap().unwrap().listen_address, "/ip6/::1/tcp/30305".parse::<Multiaddr>().unwrap());
+
+		assert!(parse(&["parity", "--gossip", "--gossip-interface", "eth0"]).gossip_config().is_err());
+		assert!(parse(&["parity", "--gossip", "--gossip-bootnodes", "enode://1234@10.0.0.1:30303"]).gossip_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_uncle_policy() {
+		let conf = parse(&["parity", "--max-uncles", "1", "--uncle-min-age", "3", "--prefer-own-uncles"]);
+		assert_eq!(conf.uncle_policy().unwrap(), UnclePolicy {
+			max_uncles: Some(1),
+			min_age: 3,
+			prefer_own: true,
+		});
+
+		assert_eq!(parse(&["parity"]).uncle_policy().unwrap(), UnclePolicy::default());
+		assert!(parse(&["parity", "--uncle-min-age", "0"]).uncle_policy().is_err());
+	}
+
+	#[test]
+	fn should_parse_tx_queue_quotas() {
+		let conf = parse(&["parity", "--tx-queue-pending-per-sender", "8", "--tx-queue-future", "512"]);
+		assert_eq!(conf.miner_options().unwrap().tx_queue_quotas, Quotas {
+			max_pending_per_sender: Some(8),
+			max_future_per_sender: None,
+			max_future: Some(512),
+		});
+		assert!(parse(&["parity"]).miner_options().unwrap().tx_queue_quotas.is_unlimited());
+	}
+
+	#[test]
+	fn should_parse_tx_queue_expiry() {
+		let conf = parse(&["parity", "--tx-queue-remote-ttl", "3600"]);
+		assert_eq!(conf.miner_options().unwrap().tx_queue_expiry, Expiry {
+			local: None,
+			remote: Some(Duration::from_secs(3600)),
+		});
+		assert!(parse(&["parity"]).miner_options().unwrap().tx_queue_expiry.is_disabled());
+		assert!(parse(&["parity", "--tx-queue-local-ttl", "18446744073709551615"]).miner_options().is_err());
+	}
+
+	#[test]
+	fn should_parse_remote_signer() {
+		let conf = parse(&["parity", "--remote-signer", "https://localhost:8550", "--remote-signer-token-file", "/tmp/token"]);
+		assert_eq!(conf.accounts_config().unwrap().remote_signer, Some(RemoteSignerConfig {
+			endpoint: "https://localhost:8550".into(),
+			token_file: Some("/tmp/token".into()),
+		}));
+		assert_eq!(parse(&["parity"]).accounts_config().unwrap().remote_signer, None);
+	}
+
+	#[test]
+	fn should_parse_keys_kdf() {
+		assert_eq!(parse(&["parity"]).accounts_config().unwrap().kdf, KdfParams::Pbkdf2 { iterations: 10240 });
+		assert_eq!(parse(&["parity", "--keys-iterations", "262144"]).accounts_config().unwrap().kdf, KdfParams::Pbkdf2 { iterations: 262144 });
+		assert_eq!(
+			parse(&["parity", "--keys-iterations", "262144", "--keys-kdf", "scrypt:16384:8:1"]).accounts_config().unwrap().kdf,
+			KdfParams::Scrypt { n: 16384, r: 8, p: 1 }
+		);
+		assert!(parse(&["parity", "--keys-kdf", "scrypt:16383:8:1"]).accounts_config().is_err());
+		assert!(parse(&["parity", "--keys-iterations", "0"]).accounts_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_unlock_session() {
+		let conf = parse(&["parity"]).accounts_config().unwrap();
+		assert_eq!(conf.unlock_session, Duration::from_secs(300));
+		assert_eq!(conf.unlock_idle_timeout, None);
+
+		let conf = parse(&["parity", "--unlock-session", "60", "--unlock-idle-timeout", "30", "--unlock-sessions", "0x0000000000000000000000000000000000000001:10"]).accounts_config().unwrap();
+		assert_eq!(conf.unlock_session, Duration::from_secs(60));
+		assert_eq!(conf.unlock_sessions.get(&Address::from_low_u64_be(1)), Some(&Duration::from_secs(10)));
+		assert_eq!(conf.unlock_idle_timeout, Some(Duration::from_secs(30)));
+	}
+
+	#[test]
+	fn should_reject_ports_shifted_out_of_range() {
+		let conf = parse(&["parity", "--ports-shift", "40000"]);
+		assert_eq!(conf.into_command().err(), Some("--port 30303 is out of range when shifted by 40000".to_owned()));
+	}
+
 	#[test]
 	fn should_apply_ports_shift() {
 		// given
@@ -20,14 +20,14 @@ use std::path::Path;
 use ethereum_types::Bloom;
 use types::errors::EthcoreError as Error;
 use rlp;
-use super::kvdb_rocksdb::DatabaseConfig;
+use kvdb_rocksdb::DatabaseConfig;
 use super::open_database;
 
 const LOG_BLOOMS_ELEMENTS_PER_INDEX: u64 = 16;
 
 pub fn migrate_blooms<P: AsRef<Path>>(path: P, config: &DatabaseConfig) -> Result<(), Error> {
 	// init
-	let db = open_database(&path.as_ref().to_string_lossy(), config)?;
+	let db = open_database(&path.as_ref().to_string_lossy(), config, None)?;
 
 	// possible optimization:
 	// pre-allocate space on disk for faster migration
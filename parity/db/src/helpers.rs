@@ -17,13 +17,49 @@
 use std::collections::HashMap;
 use std::path::Path;
 use ethcore::client::{ClientConfig, DatabaseCompactionProfile};
-use super::kvdb_rocksdb::{CompactionProfile, DatabaseConfig};
+use kvdb_rocksdb::{CompactionProfile, DatabaseConfig};
 
+/// Memory budget (in MiB) of the state column with the low-memory profile.
+const LOW_MEMORY_STATE_BUDGET: usize = 64;
+/// Memory budget (in MiB) of every other column with the low-memory profile.
+const LOW_MEMORY_COLUMN_BUDGET: usize = 4;
+
+/// Compaction of `profile`. RocksDB always compacts by level, the profiles set the size of the
+/// files of the first level, which the size of the other levels follows, and of their blocks.
 pub fn compaction_profile(profile: &DatabaseCompactionProfile, db_path: &Path) -> CompactionProfile {
 	match profile {
 		&DatabaseCompactionProfile::Auto => CompactionProfile::auto(db_path),
 		&DatabaseCompactionProfile::SSD => CompactionProfile::ssd(),
+		// smaller blocks and files, NVMe drives serve small random reads as fast as large ones.
+		&DatabaseCompactionProfile::NVMe => CompactionProfile {
+			initial_file_size: 32 * 1024 * 1024,
+			block_size: 4 * 1024,
+		},
 		&DatabaseCompactionProfile::HDD => CompactionProfile::hdd(),
+		// smaller files keep the compactions, and the memory they need, short.
+		&DatabaseCompactionProfile::LowMemory => CompactionProfile {
+			initial_file_size: 16 * 1024 * 1024,
+			..CompactionProfile::ssd()
+		},
+	}
+}
+
+/// Applies the tuning of `profile` beyond compaction: the memtable and block cache budgets and
+/// the number of table files kept open.
+pub fn tune(profile: &DatabaseCompactionProfile, config: &mut DatabaseConfig) {
+	match profile {
+		&DatabaseCompactionProfile::NVMe => {
+			config.max_open_files = 1024;
+		},
+		&DatabaseCompactionProfile::LowMemory => {
+			for col in 0..config.columns {
+				let cap = if col == ethcore_db::COL_STATE { LOW_MEMORY_STATE_BUDGET } else { LOW_MEMORY_COLUMN_BUDGET };
+				let budget = config.memory_budget.get(&col).map_or(cap, |budget| std::cmp::min(*budget, cap));
+				config.memory_budget.insert(col, budget);
+			}
+			config.max_open_files = 64;
+		},
+		_ => {},
 	}
 }
 
@@ -68,6 +104,46 @@ pub fn client_db_config(client_path: &Path, client_config: &ClientConfig) -> Dat
 
 	client_db_config.memory_budget = memory_per_column(client_config.db_cache_size);
 	client_db_config.compaction = compaction_profile(&client_config.db_compaction, &client_path);
+	client_db_config.enable_statistics = client_config.db_statistics;
+	tune(&client_config.db_compaction, &mut client_db_config);
 
 	client_db_config
 }
+
+#[cfg(test)]
+mod tests {
+	use ethcore::client::{ClientConfig, DatabaseCompactionProfile};
+	use std::path::Path;
+	use super::client_db_config;
+
+	#[test]
+	fn low_memory_profile_caps_memory_budget() {
+		let client_config = ClientConfig {
+			db_cache_size: Some(4096),
+			db_compaction: DatabaseCompactionProfile::LowMemory,
+			..Default::default()
+		};
+		let config = client_db_config(Path::new("/tmp"), &client_config);
+
+		assert_eq!(config.memory_budget[&ethcore_db::COL_STATE], 64);
+		assert_eq!(config.memory_budget[&ethcore_db::COL_HEADERS], 4);
+		assert_eq!(config.memory_budget.len(), ethcore_db::NUM_COLUMNS as usize);
+		assert_eq!(config.max_open_files, 64);
+		assert_eq!(config.compaction.initial_file_size, 16 * 1024 * 1024);
+	}
+
+	#[test]
+	fn nvme_profile_uses_small_blocks() {
+		let client_config = ClientConfig {
+			db_compaction: DatabaseCompactionProfile::NVMe,
+			db_statistics: true,
+			..Default::default()
+		};
+		let config = client_db_config(Path::new("/tmp"), &client_config);
+
+		assert_eq!(config.compaction.block_size, 4 * 1024);
+		assert_eq!(config.compaction.initial_file_size, 32 * 1024 * 1024);
+		assert_eq!(config.max_open_files, 1024);
+		assert!(config.enable_statistics);
+	}
+}
@@ -0,0 +1,283 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opening, migrating and tiering the RocksDB databases of a node.
+
+extern crate blooms_db;
+extern crate common_types as types;
+extern crate ethcore;
+extern crate ethcore_blockchain;
+extern crate ethcore_db;
+extern crate ethereum_types;
+extern crate kvdb;
+extern crate kvdb_rocksdb;
+extern crate migration_rocksdb;
+extern crate parity_util_mem;
+extern crate parking_lot;
+extern crate rlp;
+extern crate stats;
+
+#[macro_use]
+extern crate log;
+
+#[cfg(test)]
+extern crate kvdb_memorydb;
+#[cfg(test)]
+extern crate tempfile;
+
+use std::{io, fs};
+use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use ethcore_db::NUM_COLUMNS;
+use ethcore::client::{ClientConfig, DatabaseCompactionProfile};
+use kvdb::KeyValueDB;
+use ethcore_blockchain::{BlockChainDBHandler, BlockChainDB};
+use kvdb_rocksdb::{CompactionProfile, Database, DatabaseConfig};
+use self::tiered::{ColdLayout, TieredDatabase, move_parts, path_str};
+
+mod blooms;
+mod migration;
+mod helpers;
+mod metrics;
+mod read_only;
+mod tiered;
+
+pub use self::migration::{check_version, migrate};
+pub use self::metrics::{DatabaseMetrics, DatabaseStats};
+pub use self::read_only::ReadOnlyDatabase;
+pub use self::tiered::{ColdPart, parse_parts as parse_cold_parts};
+
+struct AppDB {
+	key_value: Arc<dyn KeyValueDB>,
+	// Header bloom
+	blooms: blooms_db::Database,
+	// Trace bloom
+	trace_blooms: blooms_db::Database,
+}
+
+impl BlockChainDB for AppDB {
+	fn key_value(&self) -> &Arc<dyn KeyValueDB> {
+		&self.key_value
+	}
+
+	fn blooms(&self) -> &blooms_db::Database {
+		&self.blooms
+	}
+
+	fn trace_blooms(&self) -> &blooms_db::Database {
+		&self.trace_blooms
+	}
+}
+
+/// Create a restoration db handler using the config generated by `client_path` and `client_config`.
+/// The statistics of the databases it opens are exported by `metrics`, if given.
+pub fn restoration_db_handler(
+	client_path: &Path,
+	client_config: &ClientConfig,
+	metrics: Option<Arc<DatabaseMetrics>>,
+) -> Box<dyn BlockChainDBHandler> {
+	let client_db_config = helpers::client_db_config(client_path, client_config);
+
+	struct RestorationDBHandler {
+		config: DatabaseConfig,
+		metrics: Option<Arc<DatabaseMetrics>>,
+	}
+
+	impl BlockChainDBHandler for RestorationDBHandler {
+		fn open(&self, db_path: &Path) -> io::Result<Arc<dyn BlockChainDB>> {
+			open_database(&db_path.to_string_lossy(), &self.config, self.metrics.as_ref().map(|metrics| &**metrics))
+		}
+	}
+
+	Box::new(RestorationDBHandler {
+		config: client_db_config,
+		metrics,
+	})
+}
+
+/// Open the client DB of an existing data directory without ever writing to it, failing if it
+/// needs a migration. `db_path` is the directory of the pruning algorithm.
+pub fn open_client_db_read_only(db_path: &Path, client_config: &ClientConfig) -> Result<Arc<dyn BlockChainDB>, String> {
+	check_version(db_path).map_err(|e| e.to_string())?;
+
+	let client_path = db_path.join("db");
+	let open = || -> io::Result<Arc<dyn BlockChainDB>> {
+		let client_db_config = helpers::client_db_config(&client_path, client_config);
+		let key_value = open_key_value(&client_path.to_string_lossy(), &client_db_config, None)?;
+		Ok(Arc::new(AppDB {
+			key_value: Arc::new(ReadOnlyDatabase::new(key_value, NUM_COLUMNS)),
+			blooms: blooms_db::Database::open(client_path.join("blooms"))?,
+			trace_blooms: blooms_db::Database::open(client_path.join("trace_blooms"))?,
+		}))
+	};
+	open().map_err(|e| format!("Failed to open database {}: {}", client_path.display(), e))
+}
+
+/// Open a new light client DB with a cache of `cache_size` megabytes.
+pub fn open_db_light(
+	client_path: &str,
+	cache_size: usize,
+	compaction: &DatabaseCompactionProfile
+) -> io::Result<Arc<dyn BlockChainDB>> {
+	let path = Path::new(client_path);
+
+	let mut db_config = DatabaseConfig {
+		memory_budget: helpers::memory_per_column_light(cache_size),
+		compaction: helpers::compaction_profile(&compaction, path),
+		.. DatabaseConfig::with_columns(NUM_COLUMNS)
+	};
+	helpers::tune(compaction, &mut db_config);
+
+	open_database(client_path, &db_config, None)
+}
+
+pub fn open_database(
+	client_path: &str,
+	config: &DatabaseConfig,
+	metrics: Option<&DatabaseMetrics>,
+) -> io::Result<Arc<dyn BlockChainDB>> {
+	let path = Path::new(client_path);
+
+	let blooms_path = path.join("blooms");
+	let trace_blooms_path = path.join("trace_blooms");
+	fs::create_dir_all(&blooms_path)?;
+	fs::create_dir_all(&trace_blooms_path)?;
+
+	let db = AppDB {
+		key_value: open_key_value(client_path, config, metrics)?,
+		blooms: blooms_db::Database::open(blooms_path)?,
+		trace_blooms: blooms_db::Database::open(trace_blooms_path)?,
+	};
+
+	Ok(Arc::new(db))
+}
+
+/// Opens the key-value database at `client_path` together with its cold half, if parts of it
+/// were moved to one.
+fn open_key_value(client_path: &str, config: &DatabaseConfig, metrics: Option<&DatabaseMetrics>) -> io::Result<Arc<dyn KeyValueDB>> {
+	let key_value = open_tiers(client_path, config, metrics)?;
+	Ok(match metrics {
+		Some(metrics) => metrics.timed(key_value),
+		None => key_value,
+	})
+}
+
+fn open_tiers(client_path: &str, config: &DatabaseConfig, metrics: Option<&DatabaseMetrics>) -> io::Result<Arc<dyn KeyValueDB>> {
+	let hot = Arc::new(Database::open(&config, client_path)?);
+	if let Some(metrics) = metrics {
+		metrics.track(&hot);
+	}
+	let layout = match ColdLayout::read(&*hot)? {
+		Some(layout) => layout,
+		None => return Ok(hot),
+	};
+
+	// an unmounted disk must not be mistaken for an empty cold database.
+	if !layout.path.exists() {
+		return Err(io::Error::new(io::ErrorKind::NotFound, format!("Cold database {} is missing", layout.path.display())));
+	}
+	let cold_config = DatabaseConfig {
+		compaction: CompactionProfile::auto(&layout.path),
+		.. config.clone()
+	};
+	let cold = Arc::new(Database::open(&cold_config, path_str(&layout.path)?)?);
+	if let Some(metrics) = metrics {
+		metrics.track(&cold);
+	}
+	Ok(Arc::new(TieredDatabase::new(hot, cold, layout)))
+}
+
+/// Moves the `parts` of the blocks of the client database at `client_path` older than the
+/// `hot_blocks` most recent ones to the cold database at `cold_path`, and the other parts back
+/// to the hot one. `cold_path` defaults to the current cold database.
+pub fn tier_database(
+	client_path: &Path,
+	compaction: &DatabaseCompactionProfile,
+	cold_path: Option<PathBuf>,
+	parts: Vec<ColdPart>,
+	hot_blocks: u64,
+) -> Result<(), String> {
+	let open = |path: &Path, compaction: CompactionProfile| {
+		let config = DatabaseConfig {
+			compaction,
+			.. DatabaseConfig::with_columns(NUM_COLUMNS)
+		};
+		path_str(path)
+			.and_then(|path| Database::open(&config, path))
+			.map_err(|e| format!("Failed to open database {}: {}", path.display(), e))
+	};
+
+	let hot = open(client_path, helpers::compaction_profile(compaction, client_path))?;
+	let current = ColdLayout::read(&hot).map_err(|e| e.to_string())?;
+	let cold_path = match (cold_path, &current) {
+		(Some(path), _) => path,
+		(None, Some(current)) => current.path.clone(),
+		(None, None) => return Err("The database has no cold part yet, --cold-path is required".into()),
+	};
+
+	// parts leaving a previous cold database go through the hot one.
+	let mut moved_up_to = 0;
+	if let Some(current) = current {
+		if current.path != cold_path {
+			if !current.path.exists() {
+				return Err(format!("Cold database {} is missing", current.path.display()));
+			}
+			let old = open(&current.path, helpers::compaction_profile(compaction, &current.path))?;
+			let layout = ColdLayout { parts: Vec::new(), .. current };
+			move_parts(&hot, &old, &layout).map_err(|e| format!("Failed to move data: {}", e))?;
+		} else if current.parts == parts {
+			// blocks already moved stay cold, even if more of them are kept hot now.
+			moved_up_to = current.moved_up_to;
+		}
+	}
+
+	fs::create_dir_all(&cold_path).map_err(|e| format!("Failed to create {}: {}", cold_path.display(), e))?;
+	let cold = open(&cold_path, helpers::compaction_profile(compaction, &cold_path))?;
+	let layout = ColdLayout { path: cold_path, parts, hot_blocks, moved_up_to };
+	move_parts(&hot, &cold, &layout).map_err(|e| format!("Failed to move data: {}", e))
+}
+
+/// Collects the size of the client database at `client_path` and of its cold half, if any.
+pub fn database_stats(
+	client_path: &Path,
+	compaction: &DatabaseCompactionProfile,
+) -> Result<Vec<(PathBuf, DatabaseStats)>, String> {
+	let open = |path: &Path| {
+		let mut config = DatabaseConfig {
+			compaction: helpers::compaction_profile(compaction, path),
+			.. DatabaseConfig::with_columns(NUM_COLUMNS)
+		};
+		helpers::tune(compaction, &mut config);
+		path_str(path)
+			.and_then(|path| Database::open(&config, path))
+			.map_err(|e| format!("Failed to open database {}: {}", path.display(), e))
+	};
+	let collect = |path: &Path, db: &Database| {
+		DatabaseStats::collect(db)
+			.map(|stats| (path.to_path_buf(), stats))
+			.map_err(|e| format!("Failed to read statistics of {}: {}", path.display(), e))
+	};
+
+	let hot = open(client_path)?;
+	let mut stats = vec![collect(client_path, &hot)?];
+	if let Some(layout) = ColdLayout::read(&hot).map_err(|e| e.to_string())? {
+		if !layout.path.exists() {
+			return Err(format!("Cold database {} is missing", layout.path.display()));
+		}
+		stats.push(collect(&layout.path, &open(&layout.path)?)?);
+	}
+	Ok(stats)
+}
@@ -0,0 +1,261 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! RocksDB statistics of the client databases.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Weak};
+use std::time::Instant;
+
+use ethcore_db::NUM_COLUMNS;
+use kvdb::{DBTransaction, DBValue, IoStats, IoStatsKind, KeyValueDB};
+use parity_util_mem::{MallocSizeOf, MallocSizeOfExt, MallocSizeOfOps};
+use parking_lot::RwLock;
+use stats::{DurationHistogram, PrometheusMetrics, PrometheusRegistry};
+use kvdb_rocksdb::Database;
+
+/// Buckets of the read latency histogram, in seconds. Most reads are served from the block cache.
+const READ_BUCKETS: [f64; 10] = [0.00001, 0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.1];
+
+/// Names of the client database columns, by index.
+const COLUMN_NAMES: [&str; NUM_COLUMNS as usize] = [
+	"state",
+	"headers",
+	"bodies",
+	"extra",
+	"traces",
+	"account_bloom",
+	"node_info",
+	"light_chain",
+	"private_transactions",
+	"state_checkpoints",
+	"bad_blocks",
+];
+
+/// Size of a single client database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseStats {
+	/// Estimated number of keys of every column, with its name.
+	pub keys: Vec<(&'static str, u64)>,
+	/// Memory used by the memtables, block cache and table readers, in bytes.
+	pub memory: usize,
+}
+
+impl DatabaseStats {
+	/// Collects the size of `db`.
+	pub fn collect(db: &Database) -> io::Result<Self> {
+		let keys = COLUMN_NAMES.iter().enumerate()
+			.map(|(col, name)| db.num_keys(col as u32).map(|keys| (*name, keys)))
+			.collect::<io::Result<_>>()?;
+
+		Ok(DatabaseStats {
+			keys,
+			memory: db.malloc_size_of(),
+		})
+	}
+}
+
+/// Exports the RocksDB statistics of the open client databases as metrics. The statistics of
+/// the hot and cold halves of a tiered database are summed.
+pub struct DatabaseMetrics {
+	databases: RwLock<Vec<Weak<Database>>>,
+	read_time: Arc<DurationHistogram>,
+}
+
+impl Default for DatabaseMetrics {
+	fn default() -> Self {
+		DatabaseMetrics {
+			databases: Default::default(),
+			read_time: Arc::new(DurationHistogram::new(&READ_BUCKETS)),
+		}
+	}
+}
+
+impl DatabaseMetrics {
+	/// Exports the statistics of `db` for as long as it is open.
+	pub fn track(&self, db: &Arc<Database>) {
+		let mut databases = self.databases.write();
+		databases.retain(|db| db.upgrade().is_some());
+		databases.push(Arc::downgrade(db));
+	}
+
+	/// Wraps `db` to measure the latency of its reads.
+	pub fn timed(&self, db: Arc<dyn KeyValueDB>) -> Arc<dyn KeyValueDB> {
+		Arc::new(TimedDatabase {
+			db,
+			read_time: self.read_time.clone(),
+		})
+	}
+
+	fn open_databases(&self) -> Vec<Arc<Database>> {
+		self.databases.read().iter().filter_map(Weak::upgrade).collect()
+	}
+}
+
+/// Key-value database recording how long its point reads take.
+struct TimedDatabase {
+	db: Arc<dyn KeyValueDB>,
+	read_time: Arc<DurationHistogram>,
+}
+
+impl MallocSizeOf for TimedDatabase {
+	fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+		(*self.db).size_of(ops)
+	}
+}
+
+impl KeyValueDB for TimedDatabase {
+	fn get(&self, col: u32, key: &[u8]) -> io::Result<Option<DBValue>> {
+		let start = Instant::now();
+		let value = self.db.get(col, key);
+		self.read_time.observe(start.elapsed());
+		value
+	}
+
+	fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> {
+		let start = Instant::now();
+		let value = self.db.get_by_prefix(col, prefix);
+		self.read_time.observe(start.elapsed());
+		value
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		self.db.write_buffered(transaction)
+	}
+
+	fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+		self.db.write(transaction)
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		self.db.flush()
+	}
+
+	fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.db.iter(col)
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: u32, prefix: &'a [u8]) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.db.iter_from_prefix(col, prefix)
+	}
+
+	fn restore(&self, new_db: &str) -> io::Result<()> {
+		self.db.restore(new_db)
+	}
+
+	fn io_stats(&self, kind: IoStatsKind) -> IoStats {
+		self.db.io_stats(kind)
+	}
+}
+
+impl PrometheusMetrics for DatabaseMetrics {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let databases = self.open_databases();
+
+		let mut keys = BTreeMap::new();
+		let mut memory = 0;
+		let mut counts = BTreeMap::new();
+		let mut p99s = BTreeMap::new();
+		for db in &databases {
+			if let Ok(stats) = DatabaseStats::collect(db) {
+				for (name, count) in stats.keys {
+					*keys.entry(name).or_insert(0) += count;
+				}
+				memory += stats.memory;
+			}
+
+			// empty unless the database was opened with statistics enabled.
+			for (name, value) in db.get_statistics() {
+				*counts.entry(name.clone()).or_insert(0) += value.count;
+				if let Some(times) = value.times {
+					let p99 = p99s.entry(name).or_insert(0f64);
+					*p99 = p99.max(times.p99);
+				}
+			}
+		}
+
+		r.register_histogram("db_read_seconds", "Time to read a single value from the database", &self.read_time.snapshot());
+		r.register_gauge("db_memory_usage", "Memory used by the memtables, block cache and table readers of the database, in bytes", memory as i64);
+		r.register_labelled_gauges("db_column_keys", "Estimated number of keys in the database column", "column",
+			keys.iter().map(|(name, keys)| (*name, *keys as i64)));
+		r.register_labelled_counters("db_rocksdb_ticker", "RocksDB ticker statistics, named as in the RocksDB documentation", "name",
+			counts.iter().map(|(name, count)| (name.as_str(), *count)));
+		r.register_labelled_gauges("db_rocksdb_histogram_p99", "99th percentile of RocksDB histogram statistics, named as in the RocksDB documentation", "name",
+			p99s.iter().map(|(name, p99)| (name.as_str(), p99.round() as i64)));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethcore_db::NUM_COLUMNS;
+	use kvdb::{DBTransaction, KeyValueDB};
+	use stats::{PrometheusMetrics, PrometheusRegistry};
+	use super::{DatabaseMetrics, DatabaseStats};
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+	use tempfile::TempDir;
+
+	fn open(dir: &TempDir) -> Database {
+		let config = DatabaseConfig {
+			enable_statistics: true,
+			.. DatabaseConfig::with_columns(NUM_COLUMNS)
+		};
+		Database::open(&config, &dir.path().to_string_lossy()).unwrap()
+	}
+
+	#[test]
+	fn collects_column_keys() {
+		let dir = TempDir::new().unwrap();
+		let db = open(&dir);
+		let mut batch = DBTransaction::new();
+		batch.put(ethcore_db::COL_HEADERS, b"header", b"1");
+		db.write(batch).unwrap();
+		db.flush().unwrap();
+
+		let stats = DatabaseStats::collect(&db).unwrap();
+		assert_eq!(stats.keys.len(), NUM_COLUMNS as usize);
+		assert_eq!(stats.keys[ethcore_db::COL_HEADERS as usize], ("headers", 1));
+	}
+
+	#[test]
+	fn exports_metrics_of_open_databases() {
+		let dir = TempDir::new().unwrap();
+		let metrics = DatabaseMetrics::default();
+		let db = Arc::new(open(&dir));
+		metrics.track(&db);
+		db.get(ethcore_db::COL_STATE, b"missing").unwrap();
+
+		let mut registry = PrometheusRegistry::new("oe_");
+		metrics.prometheus_metrics(&mut registry);
+		let output = registry.output();
+		assert!(output.contains("oe_db_column_keys{column=\"state\"}"));
+		assert!(output.contains("oe_db_rocksdb_ticker{name=\""));
+
+		let timed = metrics.timed(db.clone());
+		timed.get(ethcore_db::COL_STATE, b"missing").unwrap();
+		let mut registry = PrometheusRegistry::new("oe_");
+		metrics.prometheus_metrics(&mut registry);
+		assert!(registry.output().contains("oe_db_read_seconds_count 1\n"));
+		drop(timed);
+
+		drop(db);
+		let mut registry = PrometheusRegistry::new("oe_");
+		metrics.prometheus_metrics(&mut registry);
+		assert!(registry.output().contains("oe_db_memory_usage 0\n"));
+	}
+}
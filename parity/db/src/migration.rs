@@ -18,8 +18,8 @@ use std::fs;
 use std::io::{Read, Write, Error as IoError, ErrorKind};
 use std::path::{Path, PathBuf};
 use std::fmt::{Display, Formatter, Error as FmtError};
-use super::migration_rocksdb::{Manager as MigrationManager, Config as MigrationConfig, ChangeColumns, VacuumAccountsBloom};
-use super::kvdb_rocksdb::{CompactionProfile, DatabaseConfig};
+use migration_rocksdb::{Manager as MigrationManager, Config as MigrationConfig, ChangeColumns, VacuumAccountsBloom};
+use kvdb_rocksdb::{CompactionProfile, DatabaseConfig};
 use ethcore::client::DatabaseCompactionProfile;
 use ethcore_db::NUM_COLUMNS;
 use types::errors::EthcoreError;
@@ -56,14 +56,30 @@ pub const TO_V14: ChangeColumns = ChangeColumns {
 /// NOTE: column 5 is still there, but has no data.
 pub const TO_V15: VacuumAccountsBloom = VacuumAccountsBloom {
 	column_to_vacuum: 5,
-	columns: NUM_COLUMNS,
+	columns: 9,
 	version: 15,
 };
 
+/// The migration from v15 to v16.
+/// Adds a column for the copies of the state at checkpoint blocks.
+pub const TO_V16: ChangeColumns = ChangeColumns {
+	pre_columns: 9,
+	post_columns: 10,
+	version: 16,
+};
+
+/// The migration from v16 to v17.
+/// Adds a column for the most recent bad blocks.
+pub const TO_V17: ChangeColumns = ChangeColumns {
+	pre_columns: 10,
+	post_columns: NUM_COLUMNS,
+	version: 17,
+};
+
 /// Database is assumed to be at default version, when no version file is found.
 const DEFAULT_VERSION: u32 = 5;
 /// Current version of database models.
-const CURRENT_VERSION: u32 = 15;
+const CURRENT_VERSION: u32 = 17;
 /// A version of database at which blooms-db was introduced for header and trace blooms.
 const BLOOMS_DB_VERSION: u32 = 13;
 /// Defines how many items are migrated to the new version of database at once.
@@ -78,6 +94,8 @@ pub enum Error {
 	UnknownDatabaseVersion,
 	/// Existing DB is newer than the known one.
 	FutureDBVersion,
+	/// Existing DB needs a migration.
+	OutdatedDBVersion,
 	/// Migration is not possible.
 	MigrationImpossible,
 	/// Blooms-db migration error.
@@ -92,6 +110,7 @@ impl Display for Error {
 		let out = match *self {
 			Error::UnknownDatabaseVersion => "Current database version cannot be read".into(),
 			Error::FutureDBVersion => "Database was created with newer client version. Upgrade your client or delete DB and resync.".into(),
+			Error::OutdatedDBVersion => format!("Database needs a migration to version {}. Start the client once to migrate it.", CURRENT_VERSION),
 			Error::MigrationImpossible => format!("Database migration to version {} is not possible.", CURRENT_VERSION),
 			Error::BloomsDB(ref err) => format!("blooms-db migration error: {}", err),
 			Error::Io(ref err) => format!("Unexpected io error on DB migration: {}.", err),
@@ -167,6 +186,8 @@ fn consolidated_database_migrations(compaction_profile: &CompactionProfile) -> R
 	manager.add_migration(TO_V12).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(TO_V14).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(TO_V15).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(TO_V16).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(TO_V17).map_err(|_| Error::MigrationImpossible)?;
 	Ok(manager)
 }
 
@@ -209,6 +230,18 @@ fn exists(path: &Path) -> bool {
 	fs::metadata(path).is_ok()
 }
 
+/// Fails unless the database is of the current version, without migrating it.
+pub fn check_version(path: &Path) -> Result<(), Error> {
+	let version = current_version(path)?;
+	if version > CURRENT_VERSION {
+		return Err(Error::FutureDBVersion);
+	}
+	if version < CURRENT_VERSION {
+		return Err(Error::OutdatedDBVersion);
+	}
+	Ok(())
+}
+
 /// Migrates the database.
 pub fn migrate(path: &Path, compaction_profile: &DatabaseCompactionProfile) -> Result<(), Error> {
 	let compaction_profile = helpers::compaction_profile(&compaction_profile, path);
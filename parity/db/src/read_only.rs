@@ -0,0 +1,154 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database which is never written, for tools opening the database of a stopped node.
+//!
+//! The client writes to its database even when it imports nothing, e.g. to update its caches
+//! or journals. Those writes are kept in memory, so the client sees them but the files of the
+//! node stay as they were.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+
+use kvdb::{DBOp, DBTransaction, DBValue, IoStats, IoStatsKind, KeyValueDB};
+use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
+use parking_lot::RwLock;
+
+/// Values written to a column since the database was opened, `None` for deleted ones.
+type Overlay = BTreeMap<Vec<u8>, Option<DBValue>>;
+
+type KeyValue = (Box<[u8]>, Box<[u8]>);
+
+/// Key-value database keeping the changes written to it in memory.
+pub struct ReadOnlyDatabase {
+	inner: Arc<dyn KeyValueDB>,
+	overlay: RwLock<Vec<Overlay>>,
+}
+
+impl ReadOnlyDatabase {
+	/// Wrap a database with `columns` columns.
+	pub fn new(inner: Arc<dyn KeyValueDB>, columns: u32) -> Self {
+		ReadOnlyDatabase {
+			inner,
+			overlay: RwLock::new(vec![Overlay::new(); columns as usize]),
+		}
+	}
+
+	/// Entries of `inner` not changed in the overlay of the column, followed by the values
+	/// written to it which start with `prefix`.
+	fn merge<'a>(&'a self, col: u32, prefix: &[u8], inner: Box<dyn Iterator<Item = KeyValue> + 'a>) -> Box<dyn Iterator<Item = KeyValue> + 'a> {
+		let overlay: Overlay = self.overlay.read()[col as usize].iter()
+			.filter(|(key, _)| key.starts_with(prefix))
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.collect();
+		if overlay.is_empty() {
+			return inner;
+		}
+
+		let written = overlay.iter()
+			.filter_map(|(key, value)| value.as_ref().map(|value| (key.clone().into_boxed_slice(), value.clone().into_boxed_slice())))
+			.collect::<Vec<_>>();
+		Box::new(inner
+			.filter(move |(key, _)| !overlay.contains_key(&**key))
+			.chain(written))
+	}
+}
+
+impl MallocSizeOf for ReadOnlyDatabase {
+	fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+		(*self.inner).size_of(ops) + self.overlay.read().size_of(ops)
+	}
+}
+
+impl KeyValueDB for ReadOnlyDatabase {
+	fn get(&self, col: u32, key: &[u8]) -> io::Result<Option<DBValue>> {
+		match self.overlay.read()[col as usize].get(key) {
+			Some(value) => Ok(value.clone()),
+			None => self.inner.get(col, key),
+		}
+	}
+
+	fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.iter_from_prefix(col, prefix).next().map(|(_, value)| value)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		let mut overlay = self.overlay.write();
+		for op in transaction.ops {
+			match op {
+				DBOp::Insert { col, key, value } => overlay[col as usize].insert(key.into_vec(), Some(value)),
+				DBOp::Delete { col, key } => overlay[col as usize].insert(key.into_vec(), None),
+			};
+		}
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		Ok(())
+	}
+
+	fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item = KeyValue> + 'a> {
+		self.merge(col, &[], self.inner.iter(col))
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: u32, prefix: &'a [u8]) -> Box<dyn Iterator<Item = KeyValue> + 'a> {
+		self.merge(col, prefix, self.inner.iter_from_prefix(col, prefix))
+	}
+
+	fn restore(&self, _new_db: &str) -> io::Result<()> {
+		Err(io::Error::new(io::ErrorKind::Other, "the database is opened read-only"))
+	}
+
+	fn io_stats(&self, kind: IoStatsKind) -> IoStats {
+		self.inner.io_stats(kind)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use kvdb::{DBTransaction, KeyValueDB};
+	use kvdb_memorydb;
+
+	use super::ReadOnlyDatabase;
+
+	#[test]
+	fn keeps_writes_in_memory() {
+		let inner = Arc::new(kvdb_memorydb::create(1));
+		let mut batch = DBTransaction::new();
+		batch.put(0, b"a", b"1");
+		batch.put(0, b"b", b"2");
+		inner.write(batch).unwrap();
+
+		let db = ReadOnlyDatabase::new(inner.clone(), 1);
+		let mut batch = DBTransaction::new();
+		batch.put(0, b"a", b"3");
+		batch.delete(0, b"b");
+		batch.put(0, b"c", b"4");
+		db.write(batch).unwrap();
+
+		assert_eq!(db.get(0, b"a").unwrap().unwrap(), b"3".to_vec());
+		assert_eq!(db.get(0, b"b").unwrap(), None);
+		let mut entries = db.iter(0).map(|(key, value)| (key.into_vec(), value.into_vec())).collect::<Vec<_>>();
+		entries.sort();
+		assert_eq!(entries, vec![(b"a".to_vec(), b"3".to_vec()), (b"c".to_vec(), b"4".to_vec())]);
+
+		assert_eq!(inner.get(0, b"a").unwrap().unwrap(), b"1".to_vec());
+		assert_eq!(inner.get(0, b"b").unwrap().unwrap(), b"2".to_vec());
+		assert_eq!(inner.get(0, b"c").unwrap(), None);
+	}
+}
@@ -0,0 +1,628 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Chain database keeping the old blocks in a separate, cold database.
+//!
+//! Archive nodes spend most of their disk on block headers, bodies, receipts and traces which
+//! are rarely read once old. Those of the blocks older than the most recent ones can live on a
+//! larger and slower disk while the state, the indexes and the recent blocks stay on the fast
+//! one. New blocks are written to the hot database and moved to the cold one in the background
+//! as the chain grows. The layout is recorded in the hot database, so every command opening it
+//! finds the cold one.
+
+use std::io;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+
+use ethcore_db::{COL_BODIES, COL_EXTRA, COL_HEADERS, COL_NODE_INFO, COL_STATE_CHECKPOINTS, COL_TRACE, Key};
+use ethcore_db::keys::ExtrasIndex;
+use ethereum_types::H256;
+use kvdb::{DBOp, DBTransaction, DBValue, IoStats, IoStatsKind, KeyValueDB};
+use parity_util_mem::{MallocSizeOf, MallocSizeOfOps};
+use parking_lot::Mutex;
+use rlp::{Rlp, RlpStream};
+use types::BlockNumber;
+
+use kvdb_rocksdb::Database;
+
+/// Key of the layout in the node info column of the hot database.
+const LAYOUT_KEY: &[u8] = b"cold_db";
+/// Number of entries moved between the databases in one write.
+const MOVE_BATCH: usize = 10_000;
+/// Number of blocks moved to the cold database in one write.
+const MOVE_BLOCKS_BATCH: BlockNumber = 1_000;
+/// Number of blocks the chain grows past the hot ones before they are moved in the background.
+const MOVE_INTERVAL: BlockNumber = 1_000;
+
+type KeyValue = (Box<[u8]>, Box<[u8]>);
+
+/// Part of the chain database which may be kept in the cold database.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColdPart {
+	/// Block headers.
+	Headers,
+	/// Block bodies.
+	Bodies,
+	/// Block receipts.
+	Receipts,
+	/// Transaction traces.
+	Traces,
+	/// Copies of the state kept at checkpoint blocks, moved as a whole rather than by age.
+	Checkpoints,
+}
+
+const ALL_PARTS: [ColdPart; 5] = [ColdPart::Headers, ColdPart::Bodies, ColdPart::Receipts, ColdPart::Traces, ColdPart::Checkpoints];
+
+impl ColdPart {
+	fn name(&self) -> &'static str {
+		match *self {
+			ColdPart::Headers => "headers",
+			ColdPart::Bodies => "bodies",
+			ColdPart::Receipts => "receipts",
+			ColdPart::Traces => "traces",
+			ColdPart::Checkpoints => "checkpoints",
+		}
+	}
+
+	fn column(&self) -> u32 {
+		match *self {
+			ColdPart::Headers => COL_HEADERS,
+			ColdPart::Bodies => COL_BODIES,
+			ColdPart::Receipts => COL_EXTRA,
+			ColdPart::Traces => COL_TRACE,
+			ColdPart::Checkpoints => COL_STATE_CHECKPOINTS,
+		}
+	}
+
+	/// Keys of the part within its column, empty if it takes the whole column.
+	fn key_prefix(&self) -> &'static [u8] {
+		match *self {
+			ColdPart::Receipts => &[ExtrasIndex::BlockReceipts as u8],
+			_ => &[],
+		}
+	}
+
+	/// Whether the part is kept per block, and moved once the blocks get old.
+	fn by_block(&self) -> bool {
+		*self != ColdPart::Checkpoints
+	}
+
+	/// Key of the entry of the block with `hash`.
+	fn block_key(&self, hash: &H256) -> Vec<u8> {
+		let mut key = self.key_prefix().to_vec();
+		key.extend_from_slice(hash.as_bytes());
+		key
+	}
+
+	fn contains(&self, col: u32, key: &[u8]) -> bool {
+		let prefix = self.key_prefix();
+		// the address index and version entries of the traces are not per block.
+		col == self.column() && key.starts_with(prefix) && (!self.by_block() || key.len() == prefix.len() + 32)
+	}
+}
+
+impl FromStr for ColdPart {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		ALL_PARTS.iter()
+			.find(|part| part.name() == s)
+			.cloned()
+			.ok_or_else(|| format!("Invalid database part {}. Expected headers, bodies, receipts, traces or checkpoints.", s))
+	}
+}
+
+/// Parses a comma-separated list of parts, `none` for an empty one.
+pub fn parse_parts(s: &str) -> Result<Vec<ColdPart>, String> {
+	if s == "none" {
+		return Ok(Vec::new());
+	}
+	s.split(',').map(str::parse).collect()
+}
+
+/// Path of a database as given to RocksDB, which only takes unicode ones.
+pub fn path_str(path: &Path) -> io::Result<&str> {
+	path.to_str().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Database path {} is not valid unicode", path.display())))
+}
+
+/// Where the parts of a tiered database are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColdLayout {
+	/// Directory of the cold database.
+	pub path: PathBuf,
+	/// Parts kept in the cold database.
+	pub parts: Vec<ColdPart>,
+	/// Number of the most recent blocks whose parts stay in the hot database.
+	pub hot_blocks: BlockNumber,
+	/// Last block whose parts were moved to the cold database.
+	pub moved_up_to: BlockNumber,
+}
+
+impl ColdLayout {
+	/// Reads the layout recorded in the hot database, `None` if it keeps every part.
+	pub fn read(hot: &dyn KeyValueDB) -> io::Result<Option<ColdLayout>> {
+		let raw = match hot.get(COL_NODE_INFO, LAYOUT_KEY)? {
+			Some(raw) => raw,
+			None => return Ok(None),
+		};
+
+		let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid cold database layout: {}", e));
+		let rlp = Rlp::new(&raw);
+		let path: String = rlp.val_at(0).map_err(|e| invalid(e.to_string()))?;
+		let parts: Vec<String> = rlp.list_at(1).map_err(|e| invalid(e.to_string()))?;
+		Ok(Some(ColdLayout {
+			path: path.into(),
+			parts: parts.iter().map(|part| part.parse()).collect::<Result<_, _>>().map_err(invalid)?,
+			hot_blocks: rlp.val_at(2).map_err(|e| invalid(e.to_string()))?,
+			moved_up_to: rlp.val_at(3).map_err(|e| invalid(e.to_string()))?,
+		}))
+	}
+
+	fn write(&self, batch: &mut DBTransaction) -> io::Result<()> {
+		if self.parts.is_empty() {
+			batch.delete(COL_NODE_INFO, LAYOUT_KEY);
+			return Ok(());
+		}
+
+		let mut stream = RlpStream::new_list(4);
+		stream.append(&path_str(&self.path)?);
+		stream.begin_list(self.parts.len());
+		for part in &self.parts {
+			stream.append(&part.name());
+		}
+		stream.append(&self.hot_blocks);
+		stream.append(&self.moved_up_to);
+		batch.put(COL_NODE_INFO, LAYOUT_KEY, &stream.out());
+		Ok(())
+	}
+
+	fn cold_part(&self, col: u32, key: &[u8]) -> Option<ColdPart> {
+		self.parts.iter().find(|part| part.contains(col, key)).cloned()
+	}
+}
+
+/// Number of the canon block whose hash index entry `op` writes, if any.
+fn indexed_block(op: &DBOp) -> Option<BlockNumber> {
+	match *op {
+		DBOp::Insert { col, ref key, .. } if col == COL_EXTRA && key.len() == 5 && key[0] == ExtrasIndex::BlockHash as u8 =>
+			Some(key[1..].iter().fold(0, |n, byte| (n << 8) | *byte as BlockNumber)),
+		_ => None,
+	}
+}
+
+/// The halves of a tiered database, shared with the thread moving the old blocks.
+struct Tiers {
+	hot: Arc<Database>,
+	cold: Arc<Database>,
+	layout: ColdLayout,
+	moved_up_to: AtomicU64,
+	moving: AtomicBool,
+	stop: AtomicBool,
+}
+
+impl Tiers {
+	fn move_old_blocks(&self) {
+		match move_blocks(&*self.hot, &*self.cold, &self.layout, &self.moved_up_to, &self.stop) {
+			Ok(moved) if moved > 0 => debug!(target: "db", "Moved {} blocks to the cold database", moved),
+			Ok(_) => {},
+			Err(e) => warn!("Failed to move old blocks to the cold database: {}", e),
+		}
+		self.moving.store(false, Ordering::SeqCst);
+	}
+}
+
+/// Database routing the parts of the old blocks listed in its layout to the cold database and
+/// everything else to the hot one.
+pub struct TieredDatabase {
+	tiers: Arc<Tiers>,
+	mover: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+impl TieredDatabase {
+	/// Combines the hot and cold halves of a database moved to `layout`.
+	pub fn new(hot: Arc<Database>, cold: Arc<Database>, layout: ColdLayout) -> Self {
+		TieredDatabase {
+			tiers: Arc::new(Tiers {
+				hot,
+				cold,
+				moved_up_to: AtomicU64::new(layout.moved_up_to),
+				layout,
+				moving: AtomicBool::new(false),
+				stop: AtomicBool::new(false),
+			}),
+			mover: Mutex::new(None),
+		}
+	}
+
+	/// Starts moving the blocks which got old in the background, unless they are being moved already.
+	fn start_moving(&self) {
+		if self.tiers.moving.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+			return;
+		}
+		let tiers = self.tiers.clone();
+		let spawned = thread::Builder::new()
+			.name("db-tiering".into())
+			.spawn(move || tiers.move_old_blocks());
+		match spawned {
+			Ok(handle) => if let Some(previous) = self.mover.lock().replace(handle) {
+				// it is done, `moving` was reset.
+				let _ = previous.join();
+			},
+			Err(e) => {
+				warn!("Failed to start moving old blocks to the cold database: {}", e);
+				self.tiers.moving.store(false, Ordering::SeqCst);
+			},
+		}
+	}
+
+	fn stop_moving(&self) {
+		self.tiers.stop.store(true, Ordering::SeqCst);
+		if let Some(handle) = self.mover.lock().take() {
+			let _ = handle.join();
+		}
+		self.tiers.stop.store(false, Ordering::SeqCst);
+	}
+}
+
+impl Drop for TieredDatabase {
+	fn drop(&mut self) {
+		self.stop_moving();
+	}
+}
+
+impl MallocSizeOf for TieredDatabase {
+	fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+		(*self.tiers.hot).size_of(ops) + (*self.tiers.cold).size_of(ops)
+	}
+}
+
+impl KeyValueDB for TieredDatabase {
+	fn get(&self, col: u32, key: &[u8]) -> io::Result<Option<DBValue>> {
+		let tiers = &self.tiers;
+		match tiers.layout.cold_part(col, key) {
+			Some(part) if !part.by_block() => tiers.cold.get(col, key),
+			// blocks are moved by copying them before deleting them from the hot database.
+			Some(_) => match tiers.hot.get(col, key)? {
+				Some(value) => Ok(Some(value)),
+				None => tiers.cold.get(col, key),
+			},
+			None => tiers.hot.get(col, key),
+		}
+	}
+
+	fn get_by_prefix(&self, col: u32, prefix: &[u8]) -> Option<Box<[u8]>> {
+		self.iter_from_prefix(col, prefix).next().map(|(_, value)| value)
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		let tiers = &self.tiers;
+		let moved_up_to = tiers.moved_up_to.load(Ordering::SeqCst);
+		let indexed = transaction.ops.iter().filter_map(indexed_block).max();
+		// blocks imported below the moved ones, e.g. ancient blocks after a snapshot restoration,
+		// are never moved, they go straight to the cold database.
+		let old = indexed.map_or(false, |number| number <= moved_up_to);
+
+		let (mut hot, mut cold) = (DBTransaction::new(), DBTransaction::new());
+		for op in transaction.ops {
+			let delete = match op {
+				DBOp::Delete { .. } => true,
+				DBOp::Insert { .. } => false,
+			};
+			match tiers.layout.cold_part(op.col(), op.key()) {
+				Some(part) if !part.by_block() => cold.ops.push(op),
+				// a block may be in either database while it is moved.
+				Some(_) if delete => {
+					cold.ops.push(op.clone());
+					hot.ops.push(op);
+				},
+				Some(_) if old => cold.ops.push(op),
+				_ => hot.ops.push(op),
+			}
+		}
+		// the hot database references the cold data, not the other way around.
+		tiers.cold.write_buffered(cold);
+		tiers.hot.write_buffered(hot);
+
+		if indexed.map_or(false, |number| number > moved_up_to + tiers.layout.hot_blocks + MOVE_INTERVAL) {
+			self.start_moving();
+		}
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		self.tiers.cold.flush()?;
+		self.tiers.hot.flush()
+	}
+
+	fn iter<'a>(&'a self, col: u32) -> Box<dyn Iterator<Item = KeyValue> + 'a> {
+		merge(self.tiers.hot.iter(col), self.tiers.cold.iter(col))
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: u32, prefix: &'a [u8]) -> Box<dyn Iterator<Item = KeyValue> + 'a> {
+		merge(self.tiers.hot.iter_from_prefix(col, prefix), self.tiers.cold.iter_from_prefix(col, prefix))
+	}
+
+	fn restore(&self, new_db: &str) -> io::Result<()> {
+		self.stop_moving();
+		let tiers = &self.tiers;
+		tiers.hot.restore(new_db)?;
+		// the restored database keeps every part, the cold data it replaces is stale.
+		for part in &tiers.layout.parts {
+			move_part(&*tiers.cold, None, *part)?;
+		}
+		for part in tiers.layout.parts.iter().filter(|part| !part.by_block()) {
+			move_part(&*tiers.hot, Some(&*tiers.cold), *part)?;
+		}
+		tiers.moved_up_to.store(0, Ordering::SeqCst);
+		let layout = ColdLayout { moved_up_to: 0, .. tiers.layout.clone() };
+		let mut batch = DBTransaction::new();
+		layout.write(&mut batch)?;
+		tiers.hot.write(batch)
+	}
+
+	fn io_stats(&self, kind: IoStatsKind) -> IoStats {
+		// the state, whose reads the stats are collected for, is always hot.
+		self.tiers.hot.io_stats(kind)
+	}
+}
+
+/// Merges two iterators sorted by key into one, taking the entries of `a` over the ones of `b`
+/// with the same key.
+fn merge<'a>(
+	a: Box<dyn Iterator<Item = KeyValue> + 'a>,
+	b: Box<dyn Iterator<Item = KeyValue> + 'a>,
+) -> Box<dyn Iterator<Item = KeyValue> + 'a> {
+	let (mut a, mut b) = (a.peekable(), b.peekable());
+	Box::new(iter::from_fn(move || {
+		let a_first = match (a.peek(), b.peek()) {
+			(Some(x), Some(y)) if x.0 == y.0 => {
+				b.next();
+				true
+			},
+			(Some(x), Some(y)) => x.0 < y.0,
+			(Some(_), None) => true,
+			(None, _) => false,
+		};
+		if a_first { a.next() } else { b.next() }
+	}))
+}
+
+/// Moves the entries of `part` out of `from`, into `to` if given. Returns the number of entries moved.
+fn move_part(from: &dyn KeyValueDB, to: Option<&dyn KeyValueDB>, part: ColdPart) -> io::Result<usize> {
+	let col = part.column();
+	let prefix = part.key_prefix();
+	let mut moved = 0;
+	let mut entries = from.iter_from_prefix(col, prefix).take_while(|(key, _)| key.starts_with(prefix)).peekable();
+	while entries.peek().is_some() {
+		let (mut insert, mut delete) = (DBTransaction::new(), DBTransaction::new());
+		for (key, value) in entries.by_ref().take(MOVE_BATCH) {
+			insert.put(col, &key, &value);
+			delete.delete(col, &key);
+			moved += 1;
+		}
+		// a move interrupted between the writes leaves copies behind, never loses data.
+		if let Some(to) = to {
+			to.write(insert)?;
+		}
+		from.write(delete)?;
+	}
+	Ok(moved)
+}
+
+/// Number of the best block of the hot database, if it has any.
+fn best_block(hot: &dyn KeyValueDB) -> io::Result<Option<BlockNumber>> {
+	let best = match hot.get(COL_EXTRA, b"best")? {
+		Some(best) if best.len() == 32 => H256::from_slice(&best),
+		_ => return Ok(None),
+	};
+	let details = match hot.get(COL_EXTRA, Key::<ethcore_db::keys::BlockDetails>::key(&best).as_ref())? {
+		Some(details) => details,
+		None => return Ok(None),
+	};
+	Rlp::new(&details).val_at(0)
+		.map(Some)
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid block details: {}", e)))
+}
+
+/// Moves the parts of the canon blocks after `moved_up_to` which are older than the hot ones to
+/// the cold database, recording the progress in `moved_up_to` and in the layout. Stops between
+/// writes once `stop` is set. Returns the number of blocks moved.
+fn move_blocks(
+	hot: &dyn KeyValueDB,
+	cold: &dyn KeyValueDB,
+	layout: &ColdLayout,
+	moved_up_to: &AtomicU64,
+	stop: &AtomicBool,
+) -> io::Result<BlockNumber> {
+	let parts: Vec<_> = layout.parts.iter().filter(|part| part.by_block()).collect();
+	let last = match best_block(hot)? {
+		Some(best) if !parts.is_empty() => best.saturating_sub(layout.hot_blocks),
+		_ => return Ok(0),
+	};
+
+	let first = moved_up_to.load(Ordering::SeqCst) + 1;
+	let mut from = first;
+	while from <= last && !stop.load(Ordering::SeqCst) {
+		let to = ::std::cmp::min(from + MOVE_BLOCKS_BATCH - 1, last);
+		let (mut insert, mut delete) = (DBTransaction::new(), DBTransaction::new());
+		for number in from..=to {
+			let hash = match hot.get(COL_EXTRA, Key::<H256>::key(&number).as_ref())? {
+				Some(hash) if hash.len() == 32 => H256::from_slice(&hash),
+				_ => continue,
+			};
+			for part in &parts {
+				let key = part.block_key(&hash);
+				if let Some(value) = hot.get(part.column(), &key)? {
+					insert.put(part.column(), &key, &value);
+					delete.delete(part.column(), &key);
+				}
+			}
+		}
+		// the progress is recorded with the deletion, a move interrupted before it starts over.
+		ColdLayout { moved_up_to: to, .. layout.clone() }.write(&mut delete)?;
+		cold.write(insert)?;
+		hot.write(delete)?;
+		moved_up_to.store(to, Ordering::SeqCst);
+		from = to + 1;
+	}
+	Ok(from - first)
+}
+
+/// Moves the parts of the database between `hot` and `cold` to match `layout` and records it.
+/// Parts leaving the layout are moved back to the hot database, the whole-column ones joining
+/// it to the cold one and the blocks after `layout.moved_up_to` older than the hot ones too.
+pub fn move_parts(hot: &dyn KeyValueDB, cold: &dyn KeyValueDB, layout: &ColdLayout) -> io::Result<()> {
+	for part in ALL_PARTS.iter() {
+		let (from, to, tier) = match (layout.parts.contains(part), part.by_block()) {
+			(true, true) => continue,
+			(true, false) => (hot, cold, "cold"),
+			(false, _) => (cold, hot, "hot"),
+		};
+		let moved = move_part(from, Some(to), *part)?;
+		if moved > 0 {
+			info!("Moved {} {} entries to the {} database", moved, part.name(), tier);
+		}
+	}
+
+	let mut batch = DBTransaction::new();
+	layout.write(&mut batch)?;
+	hot.write(batch)?;
+
+	let moved = move_blocks(hot, cold, layout, &AtomicU64::new(layout.moved_up_to), &AtomicBool::new(false))?;
+	if moved > 0 {
+		info!("Moved {} old blocks to the cold database", moved);
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethcore_db::keys::BlockDetails;
+	use ethcore_db::{COL_STATE, NUM_COLUMNS};
+	use ethereum_types::U256;
+	use kvdb_rocksdb::DatabaseConfig;
+	use tempfile::TempDir;
+
+	fn open(dir: &TempDir) -> Database {
+		Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), &dir.path().to_string_lossy()).unwrap()
+	}
+
+	fn hash(number: BlockNumber) -> H256 {
+		H256::from_low_u64_be(number + 1)
+	}
+
+	fn receipts_key(number: BlockNumber) -> Vec<u8> {
+		ColdPart::Receipts.block_key(&hash(number))
+	}
+
+	/// Writes the index, body and receipts of a canon block, and makes it the best one.
+	fn insert_block(batch: &mut DBTransaction, number: BlockNumber) {
+		let details = BlockDetails { number, total_difficulty: U256::zero(), parent: H256::zero(), children: vec![], is_finalized: false };
+		batch.put(COL_EXTRA, Key::<H256>::key(&number).as_ref(), hash(number).as_bytes());
+		batch.put(COL_EXTRA, Key::<BlockDetails>::key(&hash(number)).as_ref(), &rlp::encode(&details));
+		batch.put(COL_EXTRA, b"best", hash(number).as_bytes());
+		batch.put(COL_BODIES, hash(number).as_bytes(), b"body");
+		batch.put(COL_EXTRA, &receipts_key(number), b"receipts");
+	}
+
+	fn layout(cold_dir: &TempDir, parts: Vec<ColdPart>, moved_up_to: BlockNumber) -> ColdLayout {
+		ColdLayout { path: cold_dir.path().into(), parts, hot_blocks: 2, moved_up_to }
+	}
+
+	#[test]
+	fn parses_parts() {
+		assert_eq!(parse_parts("bodies,receipts").unwrap(), vec![ColdPart::Bodies, ColdPart::Receipts]);
+		assert_eq!(parse_parts("none").unwrap(), vec![]);
+		assert!(parse_parts("state").is_err());
+	}
+
+	#[test]
+	fn writes_new_blocks_to_the_hot_database() {
+		let (hot_dir, cold_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+		let layout = layout(&cold_dir, vec![ColdPart::Bodies, ColdPart::Receipts, ColdPart::Checkpoints], 10);
+		let db = TieredDatabase::new(Arc::new(open(&hot_dir)), Arc::new(open(&cold_dir)), layout);
+
+		let mut batch = DBTransaction::new();
+		batch.put(COL_STATE, b"state", b"1");
+		batch.put(COL_STATE_CHECKPOINTS, b"node", b"2");
+		insert_block(&mut batch, 11);
+		db.write(batch).unwrap();
+
+		let (hot, cold) = (&db.tiers.hot, &db.tiers.cold);
+		assert_eq!(hot.get(COL_STATE, b"state").unwrap().unwrap(), b"1".to_vec());
+		assert_eq!(cold.get(COL_STATE_CHECKPOINTS, b"node").unwrap().unwrap(), b"2".to_vec());
+		assert_eq!(hot.get(COL_BODIES, hash(11).as_bytes()).unwrap().unwrap(), b"body".to_vec());
+		assert_eq!(hot.get(COL_EXTRA, &receipts_key(11)).unwrap().unwrap(), b"receipts".to_vec());
+		assert!(cold.get(COL_BODIES, hash(11).as_bytes()).unwrap().is_none());
+
+		// blocks below the moved ones are written to the cold database.
+		let mut batch = DBTransaction::new();
+		insert_block(&mut batch, 3);
+		db.write(batch).unwrap();
+		assert_eq!(cold.get(COL_BODIES, hash(3).as_bytes()).unwrap().unwrap(), b"body".to_vec());
+		assert!(hot.get(COL_BODIES, hash(3).as_bytes()).unwrap().is_none());
+		assert_eq!(db.get(COL_EXTRA, &receipts_key(3)).unwrap().unwrap(), b"receipts".to_vec());
+		assert!(hot.get(COL_EXTRA, Key::<H256>::key(&3).as_ref()).unwrap().is_some());
+
+		let keys: Vec<_> = db.iter(COL_BODIES).map(|(key, _)| key.into_vec()).collect();
+		assert_eq!(keys, vec![hash(3).as_bytes().to_vec(), hash(11).as_bytes().to_vec()]);
+	}
+
+	#[test]
+	fn moves_old_blocks_between_databases() {
+		let (hot_dir, cold_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+		let (hot, cold) = (open(&hot_dir), open(&cold_dir));
+
+		let mut batch = DBTransaction::new();
+		for number in 0..6 {
+			insert_block(&mut batch, number);
+		}
+		hot.write(batch).unwrap();
+
+		move_parts(&hot, &cold, &layout(&cold_dir, vec![ColdPart::Receipts], 0)).unwrap();
+		assert_eq!(ColdLayout::read(&hot).unwrap(), Some(layout(&cold_dir, vec![ColdPart::Receipts], 3)));
+		for number in 1..4 {
+			assert!(hot.get(COL_EXTRA, &receipts_key(number)).unwrap().is_none());
+			assert_eq!(cold.get(COL_EXTRA, &receipts_key(number)).unwrap().unwrap(), b"receipts".to_vec());
+		}
+		for &number in &[0, 4, 5] {
+			assert!(hot.get(COL_EXTRA, &receipts_key(number)).unwrap().is_some());
+		}
+		assert!(hot.get(COL_BODIES, hash(1).as_bytes()).unwrap().is_some());
+
+		move_parts(&hot, &cold, &layout(&cold_dir, vec![], 0)).unwrap();
+		assert_eq!(ColdLayout::read(&hot).unwrap(), None);
+		assert_eq!(hot.get(COL_EXTRA, &receipts_key(1)).unwrap().unwrap(), b"receipts".to_vec());
+		assert_eq!(cold.iter(COL_EXTRA).count(), 0);
+	}
+
+	#[test]
+	fn rejects_non_unicode_paths() {
+		#[cfg(unix)]
+		{
+			use std::ffi::OsStr;
+			use std::os::unix::ffi::OsStrExt;
+
+			let layout = ColdLayout { path: OsStr::from_bytes(b"/mnt/\xff").into(), parts: vec![ColdPart::Bodies], hot_blocks: 0, moved_up_to: 0 };
+			assert!(layout.write(&mut DBTransaction::new()).is_err());
+		}
+	}
+}
@@ -88,7 +88,7 @@ pub fn execute(cmd: ExportHsyncCmd) -> Result<String, String> {
 	// initialize database.
 	let db = db::open_db_light(
 		&db_dirs.client_path(algorithm).to_str().expect("DB path could not be converted to string."),
-		&cmd.cache_config,
+		cmd.cache_config.blockchain() as usize,
 		&cmd.compaction,
 	).map_err(|e| format!("Failed to open database {:?}", e))?;
 
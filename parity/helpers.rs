@@ -18,13 +18,13 @@ use std::io;
 use std::io::{Write, BufReader, BufRead};
 use std::time::Duration;
 use std::fs::File;
-use std::collections::HashSet;
-use ethereum_types::{U256, Address};
+use std::collections::{HashMap, HashSet};
+use ethereum_types::{U256, H256, Address};
 use journaldb::Algorithm;
-use ethcore::client::{DatabaseCompactionProfile, ClientConfig};
+use ethcore::client::{DatabaseCompactionProfile, ClientConfig, StateCacheRatios};
 use ethcore::miner::{PendingSet, Penalization};
 use verification::VerifierType;
-use miner::pool::PrioritizationStrategy;
+use miner::pool::{PrioritizationStrategy, ReplacementPolicy};
 use cache::CacheConfig;
 use dir::DatabaseDirectories;
 use dir::helpers::replace_home;
@@ -33,6 +33,7 @@ use sync::{validate_node_url, self};
 use db::migrate;
 use path;
 use ethkey::Password;
+use ethstore::KdfParams;
 use types::{
 	ids::BlockId,
 	client_types::Mode,
@@ -119,6 +120,19 @@ pub fn to_queue_strategy(s: &str) -> Result<PrioritizationStrategy, String> {
 	}
 }
 
+/// Parses the percentage a transaction has to raise the price by to replace another one.
+pub fn to_replacement_policy(s: &str) -> Result<ReplacementPolicy, String> {
+	let percent = s.parse::<f64>()
+		.map_err(|_| format!("Invalid price bump {:?} given. Must be a decimal number.", s))?;
+	if !(percent >= 0.0 && percent <= 1_000.0) {
+		return Err(format!("Invalid price bump {:?} given. Must be between 0 and 1000 percent.", s));
+	}
+
+	Ok(ReplacementPolicy {
+		price_bump: (percent * 100.0).round() as u32,
+	})
+}
+
 pub fn to_queue_penalization(time: Option<u64>) -> Result<Penalization, String> {
 	Ok(match time {
 		Some(threshold_ms) => Penalization::Enabled {
@@ -128,6 +142,32 @@ pub fn to_queue_penalization(time: Option<u64>) -> Result<Penalization, String>
 	})
 }
 
+/// Longest time-to-live of queued transactions accepted on the command line (one year).
+const MAX_TX_QUEUE_TTL: u64 = 365 * 24 * 60 * 60;
+
+pub fn to_queue_ttl(secs: Option<u64>) -> Result<Option<Duration>, String> {
+	match secs {
+		Some(secs) if secs > MAX_TX_QUEUE_TTL => Err(format!("Transaction time-to-live of {} seconds is too large, at most {} are allowed", secs, MAX_TX_QUEUE_TTL)),
+		secs => Ok(secs.map(Duration::from_secs)),
+	}
+}
+
+/// Parses a comma-delimited list of `ADDRESS:SECS` unlock session durations.
+pub fn to_unlock_sessions(s: &Option<String>) -> Result<HashMap<Address, Duration>, String> {
+	match *s {
+		Some(ref sessions) if !sessions.is_empty() => sessions.split(',')
+			.map(|session| {
+				let invalid = || format!("Invalid unlock session {:?}. Must be ADDRESS:SECS.", session);
+				let mut parts = session.splitn(2, ':');
+				let address = parts.next().and_then(|a| clean_0x(a).parse().ok()).ok_or_else(invalid)?;
+				let secs = parts.next().and_then(|s| s.trim().parse().ok()).ok_or_else(invalid)?;
+				Ok((address, Duration::from_secs(secs)))
+			})
+			.collect(),
+		_ => Ok(HashMap::new()),
+	}
+}
+
 pub fn to_address(s: Option<String>) -> Result<Address, String> {
 	match s {
 		Some(ref a) => clean_0x(a).parse().map_err(|_| format!("Invalid address: {:?}", a)),
@@ -144,11 +184,49 @@ pub fn to_addresses(s: &Option<String>) -> Result<Vec<Address>, String> {
 	}
 }
 
+pub fn to_hashes(s: &Option<String>) -> Result<Vec<H256>, String> {
+	match *s {
+		Some(ref hashes) if !hashes.is_empty() => hashes.split(',')
+			.map(|h| clean_0x(h).parse().map_err(|_| format!("Invalid hash: {:?}", h)))
+			.collect(),
+		_ => Ok(Vec::new()),
+	}
+}
+
 /// Tries to parse string as a price.
 pub fn to_price(s: &str) -> Result<f32, String> {
 	s.parse::<f32>().map_err(|_| format!("Invalid transaction price {:?} given. Must be a decimal number.", s))
 }
 
+/// Tries to parse the split of the state cache between accounts, storage and code.
+pub fn to_state_cache_ratios(s: &str) -> Result<StateCacheRatios, String> {
+	let ratios = s.split(',')
+		.map(|r| r.trim().parse::<usize>())
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|_| format!("Invalid state cache ratios {:?} given. Must be three comma-separated percentages.", s))?;
+
+	match ratios[..] {
+		[accounts, storage, code] if accounts + storage + code == 100 => Ok(StateCacheRatios { accounts, storage, code }),
+		_ => Err(format!("Invalid state cache ratios {:?} given. Expected three percentages adding up to 100.", s)),
+	}
+}
+
+/// Tries to parse key derivation parameters given as `pbkdf2:ITERATIONS` or `scrypt:N:R:P`.
+pub fn to_kdf_params(s: &str) -> Result<KdfParams, String> {
+	let invalid = || format!("Invalid key derivation function {:?} given. Must be pbkdf2:ITERATIONS or scrypt:N:R:P.", s);
+	let mut parts = s.split(':');
+	let function = parts.next().unwrap_or("");
+	let params = parts.map(|p| p.trim().parse::<u32>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+
+	let kdf = match (function, &params[..]) {
+		("pbkdf2", &[iterations]) => KdfParams::Pbkdf2 { iterations },
+		("scrypt", &[n, r, p]) => KdfParams::Scrypt { n, r, p },
+		_ => return Err(invalid()),
+	};
+	kdf.validate().map_err(|e| format!("{} {}.", invalid(), e))?;
+	Ok(kdf)
+}
+
 pub fn join_set(set: Option<&HashSet<String>>) -> Option<String> {
 	match set {
 		Some(s) => Some(s.iter().map(|s| s.as_str()).collect::<Vec<&str>>().join(",")),
@@ -221,6 +299,7 @@ pub fn default_network_config() -> ::sync::NetworkConfiguration {
 		snapshot_peers: 0,
 		max_pending_peers: 64,
 		ip_filter: IpFilter::default(),
+		peer_diversity: Default::default(),
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
 		client_version: ::parity_version::version(),
@@ -353,14 +432,16 @@ mod tests {
 	use std::io::Write;
 	use std::collections::HashSet;
 	use tempfile::TempDir;
-	use ethereum_types::U256;
+	use ethereum_types::{U256, Address};
+	use ethcore::client::StateCacheRatios;
 	use ethcore::miner::PendingSet;
 	use ethkey::Password;
+	use ethstore::KdfParams;
 	use types::{
 		ids::BlockId,
 		client_types::Mode,
 	};
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, join_set, password_from_file};
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, to_replacement_policy, to_state_cache_ratios, to_kdf_params, to_unlock_sessions, geth_ipc_path, to_bootnodes, join_set, password_from_file};
 
 	#[test]
 	fn test_to_duration() {
@@ -475,6 +556,49 @@ but the first password is trimmed
 		assert_eq!(to_price("2.33").unwrap(), 2.33);
 	}
 
+	#[test]
+	fn test_to_replacement_policy() {
+		assert_eq!(to_replacement_policy("12.5").unwrap().price_bump, 1_250);
+		assert_eq!(to_replacement_policy("10").unwrap().price_bump, 1_000);
+		assert_eq!(to_replacement_policy("0").unwrap().price_bump, 0);
+		assert!(to_replacement_policy("-1").is_err());
+		assert!(to_replacement_policy("NaN").is_err());
+		assert!(to_replacement_policy("ten").is_err());
+	}
+
+	#[test]
+	fn test_to_state_cache_ratios() {
+		assert_eq!(to_state_cache_ratios("25,60,15").unwrap(), StateCacheRatios::default());
+		assert_eq!(to_state_cache_ratios("50, 40, 10").unwrap(), StateCacheRatios { accounts: 50, storage: 40, code: 10 });
+		assert!(to_state_cache_ratios("50,40").is_err());
+		assert!(to_state_cache_ratios("50,40,20").is_err());
+		assert!(to_state_cache_ratios("a,b,c").is_err());
+	}
+
+	#[test]
+	fn test_to_unlock_sessions() {
+		let sessions = to_unlock_sessions(&Some("0x0000000000000000000000000000000000000001:10,0000000000000000000000000000000000000002:20".into())).unwrap();
+		assert_eq!(sessions.len(), 2);
+		assert_eq!(sessions[&Address::from_low_u64_be(1)], Duration::from_secs(10));
+		assert_eq!(sessions[&Address::from_low_u64_be(2)], Duration::from_secs(20));
+		assert!(to_unlock_sessions(&None).unwrap().is_empty());
+		assert!(to_unlock_sessions(&Some("0x0000000000000000000000000000000000000001".into())).is_err());
+		assert!(to_unlock_sessions(&Some("0x01:10".into())).is_err());
+	}
+
+	#[test]
+	fn test_to_kdf_params() {
+		assert_eq!(to_kdf_params("pbkdf2:10240").unwrap(), KdfParams::Pbkdf2 { iterations: 10240 });
+		assert_eq!(to_kdf_params("scrypt:262144:8:1").unwrap(), KdfParams::Scrypt { n: 262144, r: 8, p: 1 });
+		assert!(to_kdf_params("scrypt:1000:8:1").is_err());
+		assert!(to_kdf_params("pbkdf2:0").is_err());
+		assert!(to_kdf_params("pbkdf2:4294967295").is_err());
+		assert!(to_kdf_params("scrypt:2147483648:8:1").is_err());
+		assert!(to_kdf_params("scrypt:262144:8:0").is_err());
+		assert!(to_kdf_params("pbkdf2").is_err());
+		assert!(to_kdf_params("argon2:1").is_err());
+	}
+
 	#[test]
 	#[cfg(windows)]
 	fn test_geth_ipc_path() {
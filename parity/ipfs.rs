@@ -0,0 +1,336 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! IPFS gateway serving chain data as IPLD blocks addressed by CIDs.
+
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use client_traits::{BlockChainClient, BlockInfo};
+use ethereum_types::H256;
+use futures::future;
+use jsonrpc_core::MetaIoHandler;
+use parity_rpc::{self as rpc, hyper, DomainsValidation, HttpServer, Metadata, RequestMiddleware, RequestMiddlewareAction};
+use rustc_hex::FromHex;
+use types::ids::{BlockId, TransactionId};
+
+/// Multihash code of Keccak-256.
+const KECCAK_256: u64 = 0x1b;
+/// Version of the CIDs.
+const CID_VERSION: u64 = 1;
+/// Alphabet of the lowercase RFC 4648 base32 multibase encoding.
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpfsConfiguration {
+	/// Is the gateway enabled (default is false)?
+	pub enabled: bool,
+	/// The IP of the network interface used (default is 127.0.0.1).
+	pub interface: String,
+	/// The network port (default is 5001).
+	pub port: u16,
+}
+
+impl Default for IpfsConfiguration {
+	fn default() -> Self {
+		IpfsConfiguration {
+			enabled: false,
+			interface: "127.0.0.1".into(),
+			port: 5001,
+		}
+	}
+}
+
+/// IPLD codecs of the served chain data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Codec {
+	/// RLP encoded block header.
+	EthBlock,
+	/// RLP encoded signed transaction.
+	EthTx,
+	/// RLP encoded node of the state trie.
+	EthStateTrie,
+	/// RLP encoded node of a storage trie.
+	EthStorageTrie,
+}
+
+impl Codec {
+	fn code(&self) -> u64 {
+		match *self {
+			Codec::EthBlock => 0x90,
+			Codec::EthTx => 0x93,
+			Codec::EthStateTrie => 0x96,
+			Codec::EthStorageTrie => 0x98,
+		}
+	}
+
+	fn from_code(code: u64) -> Option<Self> {
+		match code {
+			0x90 => Some(Codec::EthBlock),
+			0x93 => Some(Codec::EthTx),
+			0x96 => Some(Codec::EthStateTrie),
+			0x98 => Some(Codec::EthStorageTrie),
+			_ => None,
+		}
+	}
+}
+
+/// Version 1 CID of a Keccak-256 addressed IPLD block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cid {
+	/// Codec of the block.
+	pub codec: Codec,
+	/// Keccak-256 hash of the block.
+	pub hash: H256,
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+	while value >= 0x80 {
+		out.push(value as u8 | 0x80);
+		value >>= 7;
+	}
+	out.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Option<u64> {
+	let mut value = 0u64;
+	for shift in (0..64).step_by(7) {
+		let (byte, rest) = input.split_first()?;
+		*input = rest;
+		value |= u64::from(byte & 0x7f) << shift;
+		if byte & 0x80 == 0 {
+			return Some(value);
+		}
+	}
+	None
+}
+
+fn base32_encode(input: &[u8]) -> String {
+	let mut out = String::with_capacity((input.len() * 8 + 4) / 5);
+	let (mut buffer, mut bits) = (0u16, 0);
+	for byte in input {
+		buffer = (buffer << 8) | u16::from(*byte);
+		bits += 8;
+		while bits >= 5 {
+			bits -= 5;
+			out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+		}
+	}
+	if bits > 0 {
+		out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+	}
+	out
+}
+
+fn base32_decode(input: &str) -> Option<Vec<u8>> {
+	let mut out = Vec::with_capacity(input.len() * 5 / 8);
+	let (mut buffer, mut bits) = (0u16, 0);
+	for c in input.bytes() {
+		let value = BASE32_ALPHABET.iter().position(|a| *a == c.to_ascii_lowercase())? as u16;
+		buffer = (buffer << 5) | value;
+		bits += 5;
+		if bits >= 8 {
+			bits -= 8;
+			out.push((buffer >> bits) as u8);
+		}
+	}
+	Some(out)
+}
+
+impl FromStr for Cid {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("Invalid CID: {}", s);
+		let bytes = match s.chars().next() {
+			Some('b') | Some('B') => base32_decode(&s[1..]),
+			Some('f') | Some('F') => s[1..].from_hex().ok(),
+			_ => None,
+		}.ok_or_else(invalid)?;
+
+		let mut input = &bytes[..];
+		if read_varint(&mut input) != Some(CID_VERSION) {
+			return Err(format!("Unsupported CID version: {}", s));
+		}
+		let codec = read_varint(&mut input).ok_or_else(invalid)?;
+		let codec = Codec::from_code(codec).ok_or_else(|| format!("Unsupported CID codec 0x{:x}: {}", codec, s))?;
+		if read_varint(&mut input) != Some(KECCAK_256) {
+			return Err(format!("Unsupported CID hash function, must be keccak-256: {}", s));
+		}
+		if read_varint(&mut input) != Some(32) || input.len() != 32 {
+			return Err(invalid());
+		}
+
+		Ok(Cid {
+			codec,
+			hash: H256::from_slice(input),
+		})
+	}
+}
+
+impl fmt::Display for Cid {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut bytes = Vec::with_capacity(38);
+		write_varint(&mut bytes, CID_VERSION);
+		write_varint(&mut bytes, self.codec.code());
+		write_varint(&mut bytes, KECCAK_256);
+		write_varint(&mut bytes, 32);
+		bytes.extend_from_slice(self.hash.as_bytes());
+		write!(f, "b{}", base32_encode(&bytes))
+	}
+}
+
+/// Why a request could not be served.
+#[derive(Debug, PartialEq)]
+enum Error {
+	BadRequest(String),
+	NotFound,
+}
+
+/// Serves IPLD blocks at `/ipfs/<cid>` and `/api/v0/block/get?arg=<cid>`. Transactions, uncles
+/// and receipts of a block are served as RLP lists at `/ipfs/<block cid>/<field>`.
+struct IpfsMiddleware {
+	client: Arc<dyn BlockChainClient>,
+}
+
+impl IpfsMiddleware {
+	fn resolve(&self, path: &str, query: Option<&str>) -> Result<Bytes, Error> {
+		let (cid, field) = if path == "/api/v0/block/get" {
+			let arg = query.unwrap_or_default()
+				.split('&')
+				.find(|pair| pair.starts_with("arg="))
+				.map(|pair| &pair[4..])
+				.ok_or_else(|| Error::BadRequest("Missing argument arg".into()))?;
+			(arg, None)
+		} else if path.starts_with("/ipfs/") {
+			let mut parts = path[6..].splitn(2, '/');
+			(parts.next().unwrap_or_default(), parts.next().filter(|field| !field.is_empty()))
+		} else {
+			return Err(Error::NotFound);
+		};
+		let cid: Cid = cid.trim_start_matches("/ipfs/").parse().map_err(Error::BadRequest)?;
+
+		let data = match (cid.codec, field) {
+			(Codec::EthBlock, None) => self.client.block_header(BlockId::Hash(cid.hash)).map(|header| header.into_inner()),
+			(Codec::EthBlock, Some("transactions")) => self.client.block_body(BlockId::Hash(cid.hash))
+				.map(|body| body.transactions_rlp().as_raw().to_vec()),
+			(Codec::EthBlock, Some("uncles")) => self.client.block_body(BlockId::Hash(cid.hash))
+				.map(|body| body.uncles_rlp().as_raw().to_vec()),
+			(Codec::EthBlock, Some("receipts")) => self.client.block_receipts(&cid.hash)
+				.map(|receipts| rlp::encode(&receipts)),
+			(Codec::EthTx, None) => self.client.transaction(TransactionId::Hash(cid.hash))
+				.map(|tx| rlp::encode(&tx.signed)),
+			(Codec::EthStateTrie, None) | (Codec::EthStorageTrie, None) => self.client.state_data(&cid.hash),
+			(_, Some(field)) => return Err(Error::BadRequest(format!("Unknown path {} of {}", field, cid))),
+		};
+		data.ok_or(Error::NotFound)
+	}
+}
+
+impl RequestMiddleware for IpfsMiddleware {
+	fn on_request(&self, request: hyper::Request<hyper::Body>) -> RequestMiddlewareAction {
+		let response = if request.method() != hyper::Method::GET && request.method() != hyper::Method::POST {
+			hyper::Response::builder()
+				.status(hyper::StatusCode::METHOD_NOT_ALLOWED)
+				.body(hyper::Body::empty())
+		} else {
+			match self.resolve(request.uri().path(), request.uri().query()) {
+				Ok(data) => hyper::Response::builder()
+					.header(hyper::header::CONTENT_TYPE, "application/vnd.ipld.raw")
+					.body(hyper::Body::from(data)),
+				Err(Error::BadRequest(e)) => hyper::Response::builder()
+					.status(hyper::StatusCode::BAD_REQUEST)
+					.body(hyper::Body::from(e)),
+				Err(Error::NotFound) => hyper::Response::builder()
+					.status(hyper::StatusCode::NOT_FOUND)
+					.body(hyper::Body::empty()),
+			}
+		};
+
+		RequestMiddlewareAction::Respond {
+			should_validate_hosts: false,
+			response: Box::new(future::ok(response.expect("status, header and body are valid; qed"))),
+		}
+	}
+}
+
+/// Start the IPFS gateway if it's enabled.
+pub fn start_server(conf: IpfsConfiguration, client: Arc<dyn BlockChainClient>) -> Result<Option<HttpServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid IPFS listen host/port given: {}", url))?;
+
+	let start_result = rpc::start_http_with_middleware(
+		&addr,
+		DomainsValidation::Disabled,
+		DomainsValidation::Disabled,
+		MetaIoHandler::<Metadata>::default(),
+		rpc::RpcExtractor,
+		IpfsMiddleware { client },
+		1,
+		1,
+		true,
+	);
+
+	match start_result {
+		Ok(server) => {
+			info!("IPFS gateway available at http://{}/ipfs", url);
+			Ok(Some(server))
+		},
+		Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => Err(
+			format!("IPFS address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --ipfs-api-port and --ipfs-api-interface options.", url)
+		),
+		Err(e) => Err(format!("IPFS server error: {:?}", e)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_hex::ToHex;
+	use super::*;
+
+	#[test]
+	fn should_encode_and_parse_cids() {
+		let cid = Cid { codec: Codec::EthBlock, hash: H256::from_low_u64_be(0xdead) };
+		let encoded = cid.to_string();
+		assert!(encoded.starts_with("bagiacgza"));
+		assert_eq!(encoded.parse::<Cid>().unwrap(), cid);
+
+		let tx = Cid { codec: Codec::EthTx, hash: H256::from_low_u64_be(1) };
+		assert!(tx.to_string().starts_with("bagjqcgza"));
+
+		let base16 = format!("f0190011b20{}", cid.hash.as_bytes().to_hex::<String>());
+		assert_eq!(base16.parse::<Cid>().unwrap(), cid);
+	}
+
+	#[test]
+	fn should_reject_unsupported_cids() {
+		// sha2-256 multihash
+		assert!("f0190011220".parse::<Cid>().is_err());
+		// dag-pb codec
+		assert!(format!("f01701b20{}", H256::zero().as_bytes().to_hex::<String>()).parse::<Cid>().is_err());
+		// CIDv0
+		assert!("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".parse::<Cid>().is_err());
+		// truncated hash
+		assert!("f0190011b2000".parse::<Cid>().is_err());
+	}
+}
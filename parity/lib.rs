@@ -28,6 +28,7 @@ extern crate jsonrpc_core;
 extern crate num_cpus;
 extern crate number_prefix;
 extern crate parking_lot;
+extern crate parquet;
 extern crate regex;
 extern crate rlp;
 extern crate rpassword;
@@ -39,7 +40,6 @@ extern crate serde_json;
 extern crate serde_derive;
 extern crate toml;
 
-extern crate blooms_db;
 extern crate cli_signer;
 
 extern crate client_traits;
@@ -52,6 +52,7 @@ extern crate ethcore_light as light;
 extern crate ethcore_logger;
 extern crate ethcore_miner as miner;
 extern crate ethcore_network as network;
+extern crate ethcore_network_gossip as gossip;
 extern crate ethcore_private_tx;
 extern crate ethcore_service;
 extern crate ethcore_sync as sync;
@@ -61,6 +62,8 @@ extern crate ethstore;
 extern crate journaldb;
 extern crate keccak_hash as hash;
 extern crate kvdb;
+extern crate lru_cache;
+extern crate node_db as db;
 extern crate node_filter;
 extern crate parity_bytes as bytes;
 extern crate parity_crypto;
@@ -70,10 +73,12 @@ extern crate parity_path as path;
 extern crate parity_rpc;
 extern crate parity_runtime;
 extern crate parity_updater as updater;
+extern crate parity_util_mem;
 extern crate parity_version;
 extern crate registrar;
 extern crate snapshot;
 extern crate spec;
+extern crate stats;
 extern crate verification;
 
 #[macro_use]
@@ -91,10 +96,15 @@ extern crate ethabi;
 #[cfg(feature = "secretstore")]
 extern crate ethcore_call_contract as call_contract;
 
+#[cfg(unix)]
+extern crate nix;
+
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
 
+#[cfg(test)]
+extern crate kvdb_memorydb;
 #[cfg(test)]
 extern crate tempfile;
 
@@ -102,25 +112,38 @@ mod account;
 mod account_utils;
 mod blockchain;
 mod cache;
+mod cht;
 mod cli;
+mod config_check;
 mod configuration;
 mod export_hardcoded_sync;
 mod deprecated;
 mod helpers;
 mod informant;
+mod ipfs;
 mod light_helpers;
+#[cfg(unix)]
+mod local_sync;
+mod metrics;
 mod modules;
+mod otlp;
 mod params;
 mod presale;
+mod profile;
+mod reload;
 mod rpc;
 mod rpc_apis;
 mod run;
 mod secretstore;
+mod shutdown;
 mod signer;
 mod snapshot_cmd;
+mod spec_cmd;
+mod snapshot_storage;
+mod state_fork;
 mod upgrade;
 mod user_defaults;
-mod db;
+mod webhooks;
 
 use std::fs::File;
 use std::io::BufReader;
@@ -135,6 +158,7 @@ pub use self::configuration::Configuration;
 pub use self::run::RunningClient;
 pub use parity_rpc::PubSubSession;
 pub use ethcore_logger::{Config as LoggerConfig, setup_log, RotatingLogger};
+pub use reload::reload_on_sighup;
 
 fn print_hash_of(maybe_file: Option<String>) -> Result<String, String> {
 	if let Some(file) = maybe_file {
@@ -187,22 +211,24 @@ pub enum ExecutionAction {
 	Running(RunningClient),
 }
 
-fn execute<Cr, Rr>(
+fn execute<Cr, Rr, Sr>(
 	command: Execute,
 	logger: Arc<RotatingLogger>,
 	on_client_rq: Cr,
-	on_updater_rq: Rr
+	on_updater_rq: Rr,
+	on_shutdown_rq: Sr,
 ) -> Result<ExecutionAction, String>
 	where
 		Cr: Fn(String) + 'static + Send,
-		Rr: Fn() + 'static + Send
+		Rr: Fn() + 'static + Send,
+		Sr: Fn() + 'static + Send + Sync
 {
 	#[cfg(feature = "deadlock_detection")]
 	run_deadlock_detection_thread();
 
 	match command.cmd {
 		Cmd::Run(run_cmd) => {
-			let outcome = run::execute(run_cmd, logger, on_client_rq, on_updater_rq)?;
+			let outcome = run::execute(run_cmd, logger, on_client_rq, on_updater_rq, on_shutdown_rq)?;
 			Ok(ExecutionAction::Running(outcome))
 		},
 		Cmd::Version => Ok(ExecutionAction::Instant(Some(Args::print_version()))),
@@ -216,6 +242,9 @@ fn execute<Cr, Rr>(
 		Cmd::SignerReject { id, port, authfile } => cli_signer::signer_reject(id, port, authfile).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Snapshot(snapshot_cmd) => snapshot_cmd::execute(snapshot_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::ExportHardcodedSync(export_hs_cmd) => export_hardcoded_sync::execute(export_hs_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::ConfigCheck(check_cmd) => config_check::execute(check_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::ConfigDump(config) => Ok(ExecutionAction::Instant(Some(config))),
+		Cmd::Spec(spec_cmd) => spec_cmd::execute(spec_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 	}
 }
 
@@ -226,25 +255,31 @@ fn execute<Cr, Rr>(
 ///
 /// `on_updater_rq` is the action to perform when the updater has a new binary to execute.
 ///
+/// `on_shutdown_rq` is the action to perform when the client receives an RPC request to shut down.
+///
 /// The first parameter is the command line arguments that you would pass when running the parity
 /// binary.
 ///
 /// On error, returns what to print on stderr.
 // FIXME: totally independent logging capability, see https://github.com/openethereum/openethereum/issues/10252
-pub fn start<Cr, Rr>(
+pub fn start<Cr, Rr, Sr>(
 	conf: Configuration,
 	logger: Arc<RotatingLogger>,
 	on_client_rq: Cr,
-	on_updater_rq: Rr
+	on_updater_rq: Rr,
+	on_shutdown_rq: Sr,
 ) -> Result<ExecutionAction, String>
 	where
 		Cr: Fn(String) + 'static + Send,
-		Rr: Fn() + 'static + Send
+		Rr: Fn() + 'static + Send,
+		Sr: Fn() + 'static + Send + Sync
 {
-	let deprecated = find_deprecated(&conf.args);
-	for d in deprecated {
-		println!("{}", d);
+	// `config check` reports deprecated options itself
+	if !conf.args.cmd_config {
+		for d in find_deprecated(&conf.args) {
+			println!("{}", d);
+		}
 	}
 
-	execute(conf.into_command()?, logger, on_client_rq, on_updater_rq)
+	execute(conf.into_command()?, logger, on_client_rq, on_updater_rq, on_shutdown_rq)
 }
@@ -0,0 +1,389 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Fast-path sync between instances on the same host.
+//!
+//! A running node can serve its latest snapshot and its blocks over a unix socket, which a new node
+//! bootstraps its database from with `parity import --from-ipc`, bypassing devp2p.
+//!
+//! Each connection carries requests, one per line:
+//!   - `manifest`: the manifest of the latest snapshot, length-prefixed (empty if there is none).
+//!   - `chunk <hash>`: a snapshot chunk, length-prefixed (empty if unknown).
+//!   - `blocks <number>`: RLP blocks from `number` up to the best block, after which the connection is closed.
+//!
+//! The socket is only accessible to the user running the node.
+
+use std::fs::{self, DirBuilder, Permissions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use bytes::Bytes;
+use client_traits::{BlockChainClient, BlockInfo, ChainInfo};
+use ethereum_types::H256;
+use parking_lot::Mutex;
+use snapshot::SnapshotService;
+use snapshot::io::SnapshotReader;
+use types::{
+	BlockNumber,
+	ids::BlockId,
+	snapshot::ManifestData,
+};
+
+/// Largest frame read from the server, well above the size of snapshot chunks and manifests.
+const MAX_FRAME_SIZE: u64 = 64 * 1024 * 1024;
+/// Longest request line read from a client.
+const MAX_REQUEST_SIZE: u64 = 128;
+/// Maximum number of connections served at the same time.
+const MAX_CONNECTIONS: usize = 8;
+
+/// Requests understood by the local sync server.
+#[derive(Debug, PartialEq)]
+enum Request {
+	Manifest,
+	Chunk(H256),
+	Blocks(BlockNumber),
+}
+
+impl Request {
+	fn parse(line: &str) -> Result<Self, String> {
+		let mut parts = line.trim().split_whitespace();
+		match (parts.next(), parts.next(), parts.next()) {
+			(Some("manifest"), None, _) => Ok(Request::Manifest),
+			(Some("chunk"), Some(hash), None) => hash.trim_start_matches("0x").parse()
+				.map(Request::Chunk)
+				.map_err(|_| format!("Invalid chunk hash: {}", hash)),
+			(Some("blocks"), Some(number), None) => number.parse()
+				.map(Request::Blocks)
+				.map_err(|_| format!("Invalid block number: {}", number)),
+			_ => Err(format!("Invalid request: {}", line.trim())),
+		}
+	}
+
+	fn to_line(&self) -> String {
+		match *self {
+			Request::Manifest => "manifest\n".into(),
+			Request::Chunk(ref hash) => format!("chunk {:x}\n", hash),
+			Request::Blocks(number) => format!("blocks {}\n", number),
+		}
+	}
+}
+
+fn write_frame<W: Write>(out: &mut W, data: &[u8]) -> io::Result<()> {
+	out.write_all(&(data.len() as u64).to_be_bytes())?;
+	out.write_all(data)?;
+	out.flush()
+}
+
+fn read_frame<R: Read>(input: &mut R) -> io::Result<Bytes> {
+	let mut len = [0u8; 8];
+	input.read_exact(&mut len)?;
+	let len = u64::from_be_bytes(len);
+	if len > MAX_FRAME_SIZE {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Frame of {} bytes exceeds the limit of {} bytes", len, MAX_FRAME_SIZE)));
+	}
+	let mut data = vec![0; len as usize];
+	input.read_exact(&mut data)?;
+	Ok(data)
+}
+
+/// Serves the requests of one connection.
+fn serve<S: Read + Write>(
+	stream: S,
+	client: &dyn BlockChainClient,
+	snapshot: &dyn SnapshotService,
+) -> Result<(), String> {
+	let mut reader = BufReader::new(stream);
+	let mut line = String::new();
+	loop {
+		line.clear();
+		match (&mut reader).take(MAX_REQUEST_SIZE).read_line(&mut line).map_err(|e| e.to_string())? {
+			0 => return Ok(()),
+			_ if !line.ends_with('\n') => return Err(format!("Request exceeds {} bytes or is incomplete", MAX_REQUEST_SIZE)),
+			_ => {},
+		}
+
+		match Request::parse(&line)? {
+			Request::Manifest => {
+				let manifest = snapshot.manifest().map_or_else(Vec::new, ManifestData::into_rlp);
+				write_frame(reader.get_mut(), &manifest).map_err(|e| e.to_string())?;
+			},
+			Request::Chunk(hash) => {
+				let chunk = snapshot.chunk(hash).unwrap_or_default();
+				write_frame(reader.get_mut(), &chunk).map_err(|e| e.to_string())?;
+			},
+			Request::Blocks(from) => {
+				let out = reader.get_mut();
+				let best = client.chain_info().best_block_number;
+				for number in from..=best {
+					let block = client.block(BlockId::Number(number))
+						.ok_or_else(|| format!("Block #{} is missing", number))?;
+					out.write_all(&block.into_inner()).map_err(|e| e.to_string())?;
+				}
+				return out.flush().map_err(|e| e.to_string());
+			},
+		}
+	}
+}
+
+/// Serves the snapshot and blocks of a running node to other instances on the same host.
+pub struct LocalSyncServer {
+	path: String,
+	stop: Arc<AtomicBool>,
+}
+
+impl Drop for LocalSyncServer {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::SeqCst);
+		// wake up the listener so it notices it should stop
+		let _ = UnixStream::connect(&self.path);
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+/// Start the local sync server if a socket path is given.
+pub fn start_server(
+	path: Option<String>,
+	client: Arc<dyn BlockChainClient>,
+	snapshot: Arc<dyn SnapshotService>,
+) -> Result<Option<LocalSyncServer>, String> {
+	let path = match path {
+		Some(path) => path,
+		None => return Ok(None),
+	};
+
+	// remove a socket left behind by an instance that was not shut down cleanly
+	if UnixStream::connect(&path).is_err() {
+		let _ = fs::remove_file(&path);
+	}
+	let listener = bind_private(&path)
+		.map_err(|e| format!("Failed to bind local sync socket {}: {}", path, e))?;
+	let stop = Arc::new(AtomicBool::new(false));
+
+	let server_stop = stop.clone();
+	let connections = Arc::new(AtomicUsize::new(0));
+	thread::Builder::new()
+		.name("local-sync".into())
+		.spawn(move || {
+			for stream in listener.incoming() {
+				if server_stop.load(Ordering::SeqCst) {
+					break;
+				}
+				let stream = match stream {
+					Ok(stream) => stream,
+					Err(e) => {
+						warn!(target: "local_sync", "Failed to accept connection: {}", e);
+						continue;
+					},
+				};
+				if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+					connections.fetch_sub(1, Ordering::SeqCst);
+					debug!(target: "local_sync", "Refusing connection, {} connections are served already", MAX_CONNECTIONS);
+					continue;
+				}
+				let (client, snapshot, served) = (client.clone(), snapshot.clone(), connections.clone());
+				let spawned = thread::Builder::new()
+					.name("local-sync-conn".into())
+					.spawn(move || {
+						if let Err(e) = serve(stream, &*client, &*snapshot) {
+							debug!(target: "local_sync", "Connection closed: {}", e);
+						}
+						served.fetch_sub(1, Ordering::SeqCst);
+					});
+				if let Err(e) = spawned {
+					connections.fetch_sub(1, Ordering::SeqCst);
+					warn!(target: "local_sync", "Failed to serve connection: {}", e);
+				}
+			}
+		})
+		.map_err(|e| format!("Failed to start local sync server: {}", e))?;
+
+	info!("Serving local sync at {}", path);
+	Ok(Some(LocalSyncServer { path, stop }))
+}
+
+/// Binds a socket at `path` which only the current user can connect to. The socket is created in a
+/// private directory and moved into place, so it is never reachable with looser permissions.
+fn bind_private(path: &str) -> io::Result<UnixListener> {
+	let private_dir = format!("{}.{}.tmp", path, process::id());
+	DirBuilder::new().mode(0o700).create(&private_dir)?;
+	let private_path = Path::new(&private_dir).join("socket");
+	let listener = UnixListener::bind(&private_path).and_then(|listener| {
+		fs::set_permissions(&private_path, Permissions::from_mode(0o600))?;
+		fs::rename(&private_path, path)?;
+		Ok(listener)
+	});
+	let _ = fs::remove_file(&private_path);
+	let _ = fs::remove_dir(&private_dir);
+	listener
+}
+
+/// Connection to a local sync server.
+pub struct LocalSyncClient {
+	path: String,
+	stream: Mutex<BufReader<UnixStream>>,
+	manifest: Option<ManifestData>,
+}
+
+impl LocalSyncClient {
+	/// Connect to the server at `path` and fetch the manifest of its latest snapshot.
+	pub fn connect(path: &str) -> Result<Self, String> {
+		let stream = UnixStream::connect(path)
+			.map_err(|e| format!("Failed to connect to local sync socket {}: {}", path, e))?;
+		let mut client = LocalSyncClient {
+			path: path.into(),
+			stream: Mutex::new(BufReader::new(stream)),
+			manifest: None,
+		};
+		let manifest = client.request(Request::Manifest).map_err(|e| format!("Failed to fetch snapshot manifest: {}", e))?;
+		if !manifest.is_empty() {
+			client.manifest = Some(ManifestData::from_rlp(&manifest).map_err(|e| format!("Invalid snapshot manifest: {}", e))?);
+		}
+		Ok(client)
+	}
+
+	/// Manifest of the latest snapshot of the server, if it has one.
+	pub fn snapshot(&self) -> Option<&ManifestData> {
+		self.manifest.as_ref()
+	}
+
+	/// Stream of RLP blocks from `from` up to the best block of the server.
+	pub fn blocks(&self, from: BlockNumber) -> Result<UnixStream, String> {
+		let mut stream = UnixStream::connect(&self.path)
+			.map_err(|e| format!("Failed to connect to local sync socket {}: {}", self.path, e))?;
+		stream.write_all(Request::Blocks(from).to_line().as_bytes()).map_err(|e| e.to_string())?;
+		Ok(stream)
+	}
+
+	fn request(&self, request: Request) -> io::Result<Bytes> {
+		let mut stream = self.stream.lock();
+		stream.get_mut().write_all(request.to_line().as_bytes())?;
+		read_frame(&mut *stream)
+	}
+}
+
+impl SnapshotReader for LocalSyncClient {
+	fn manifest(&self) -> &ManifestData {
+		self.manifest.as_ref().expect("snapshot restoration only starts if there is a manifest; qed")
+	}
+
+	fn chunk(&self, hash: H256) -> io::Result<Bytes> {
+		match self.request(Request::Chunk(hash))? {
+			ref chunk if chunk.is_empty() => Err(io::Error::new(io::ErrorKind::NotFound, "chunk not available")),
+			chunk => Ok(chunk),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+	use ethcore::test_helpers::{EachBlockWith, TestBlockChainClient};
+	use types::snapshot::RestorationStatus;
+	use tempfile::TempDir;
+
+	struct TestSnapshot {
+		manifest: ManifestData,
+		chunks: HashMap<H256, Bytes>,
+	}
+
+	impl SnapshotService for TestSnapshot {
+		fn manifest(&self) -> Option<ManifestData> { Some(self.manifest.clone()) }
+		fn supported_versions(&self) -> Option<(u64, u64)> { None }
+		fn completed_chunks(&self) -> Option<Vec<H256>> { None }
+		fn chunk(&self, hash: H256) -> Option<Bytes> { self.chunks.get(&hash).cloned() }
+		fn status(&self) -> RestorationStatus { RestorationStatus::Inactive }
+		fn begin_restore(&self, _manifest: ManifestData) {}
+		fn abort_restore(&self) {}
+		fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) {}
+		fn restore_block_chunk(&self, _hash: H256, _chunk: Bytes) {}
+		fn abort_snapshot(&self) {}
+		fn shutdown(&self) {}
+	}
+
+	#[test]
+	fn should_parse_requests() {
+		let hash = H256::from_low_u64_be(0xdead);
+		for request in vec![Request::Manifest, Request::Chunk(hash), Request::Blocks(1024)] {
+			assert_eq!(Request::parse(&request.to_line()).unwrap(), request);
+		}
+		assert_eq!(Request::parse(&format!("chunk 0x{:x}", hash)).unwrap(), Request::Chunk(hash));
+
+		assert!(Request::parse("blocks").is_err());
+		assert!(Request::parse("blocks latest").is_err());
+		assert!(Request::parse("chunk 1234").is_err());
+		assert!(Request::parse("state").is_err());
+	}
+
+	#[test]
+	fn should_read_written_frames() {
+		let mut buffer = Vec::new();
+		write_frame(&mut buffer, b"chunk").unwrap();
+		write_frame(&mut buffer, b"").unwrap();
+
+		let mut input = &buffer[..];
+		assert_eq!(read_frame(&mut input).unwrap(), b"chunk".to_vec());
+		assert_eq!(read_frame(&mut input).unwrap(), Vec::<u8>::new());
+		assert!(read_frame(&mut input).is_err());
+
+		let oversized = (MAX_FRAME_SIZE + 1).to_be_bytes();
+		assert_eq!(read_frame(&mut &oversized[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn should_serve_snapshot_and_blocks_over_private_socket() {
+		let tempdir = TempDir::new().unwrap();
+		let path = tempdir.path().join("sync.ipc").to_str().unwrap().to_owned();
+
+		let client = TestBlockChainClient::new();
+		client.add_blocks(5, EachBlockWith::Transaction);
+		let chunk_hash = H256::from_low_u64_be(1);
+		let manifest = ManifestData {
+			version: 2,
+			state_hashes: vec![chunk_hash],
+			block_hashes: Vec::new(),
+			state_root: H256::from_low_u64_be(2),
+			block_number: 5,
+			block_hash: client.block_header(BlockId::Number(5)).unwrap().hash(),
+		};
+		let snapshot = TestSnapshot {
+			manifest: manifest.clone(),
+			chunks: vec![(chunk_hash, b"state chunk".to_vec())].into_iter().collect(),
+		};
+		let expected_blocks: Vec<u8> = (2..=5)
+			.flat_map(|n| client.block(BlockId::Number(n)).unwrap().into_inner())
+			.collect();
+
+		let _server = start_server(Some(path.clone()), Arc::new(client), Arc::new(snapshot)).unwrap().unwrap();
+		assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+		let source = LocalSyncClient::connect(&path).unwrap();
+		assert_eq!(source.snapshot(), Some(&manifest));
+		assert_eq!(SnapshotReader::chunk(&source, chunk_hash).unwrap(), b"state chunk".to_vec());
+		assert_eq!(SnapshotReader::chunk(&source, H256::from_low_u64_be(3)).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+		let mut blocks = Vec::new();
+		source.blocks(2).unwrap().read_to_end(&mut blocks).unwrap();
+		assert_eq!(blocks, expected_blocks);
+	}
+}
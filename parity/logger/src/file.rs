@@ -0,0 +1,118 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Log file with size-based rotation.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+
+/// Log file which is rotated once it grows beyond `max_size` bytes.
+///
+/// Rotated files are renamed to `<path>.1`, `<path>.2`, ... with `<path>.1` being the most recent.
+/// At most `max_files` rotated files are kept.
+pub struct LogFile {
+	path: PathBuf,
+	max_size: Option<u64>,
+	max_files: usize,
+	file: Mutex<(File, u64)>,
+}
+
+impl LogFile {
+	/// Open the log file for appending.
+	pub fn open<P: AsRef<Path>>(path: P, max_size: Option<u64>, max_files: usize) -> io::Result<Self> {
+		let path = path.as_ref().to_owned();
+		let file = Self::open_file(&path)?;
+		let size = file.metadata()?.len();
+		Ok(LogFile {
+			path,
+			max_size,
+			max_files,
+			file: Mutex::new((file, size)),
+		})
+	}
+
+	fn open_file(path: &Path) -> io::Result<File> {
+		OpenOptions::new().append(true).create(true).open(path)
+	}
+
+	fn rotated_path(&self, index: usize) -> PathBuf {
+		let mut path = self.path.clone().into_os_string();
+		path.push(format!(".{}", index));
+		path.into()
+	}
+
+	fn rotate(&self, file: &mut (File, u64)) -> io::Result<()> {
+		if self.max_files == 0 {
+			fs::remove_file(&self.path)?;
+		} else {
+			let oldest = self.rotated_path(self.max_files);
+			if oldest.exists() {
+				fs::remove_file(oldest)?;
+			}
+			for index in (1..self.max_files).rev() {
+				let from = self.rotated_path(index);
+				if from.exists() {
+					fs::rename(from, self.rotated_path(index + 1))?;
+				}
+			}
+			fs::rename(&self.path, self.rotated_path(1))?;
+		}
+		*file = (Self::open_file(&self.path)?, 0);
+		Ok(())
+	}
+
+	/// Append a line to the file, rotating it first if the line would exceed the size limit.
+	pub fn write_line(&self, line: &str) -> io::Result<()> {
+		let mut file = self.file.lock();
+		let len = line.len() as u64 + 1;
+		if let Some(max_size) = self.max_size {
+			if file.1 > 0 && file.1 + len > max_size {
+				self.rotate(&mut file)?;
+			}
+		}
+		file.0.write_all(line.as_bytes())?;
+		file.0.write_all(b"\n")?;
+		file.1 += len;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use tempfile::TempDir;
+	use super::LogFile;
+
+	#[test]
+	fn should_rotate_when_size_is_exceeded() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("parity.log");
+		let file = LogFile::open(&path, Some(10), 2).unwrap();
+
+		for line in &["first", "second", "third", "fourth"] {
+			file.write_line(line).unwrap();
+		}
+
+		assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+		assert_eq!(fs::read_to_string(dir.path().join("parity.log.1")).unwrap(), "third\n");
+		assert_eq!(fs::read_to_string(dir.path().join("parity.log.2")).unwrap(), "second\n");
+		assert!(!dir.path().join("parity.log.3").exists());
+	}
+}
@@ -28,23 +28,35 @@ extern crate time;
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(test)]
+extern crate tempfile;
+
+mod file;
 mod rotating;
 
-use std::{env, thread, fs};
+use std::{env, thread};
 use std::sync::{Weak, Arc};
 use std::io::Write;
 use env_logger::{Builder as LogBuilder, Formatter};
 use regex::Regex;
+use rlog::Record;
 use ansi_term::Colour;
 use parking_lot::Mutex;
 
-pub use rotating::{RotatingLogger, init_log};
+pub use file::LogFile;
+pub use rotating::{RotatingLogger, init_log, validate_levels};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
 	pub mode: Option<String>,
 	pub color: bool,
 	pub file: Option<String>,
+	/// Output one JSON object per line instead of plain text.
+	pub json: bool,
+	/// Rotate the log file once it exceeds this many bytes.
+	pub file_max_size: Option<u64>,
+	/// Number of rotated log files to keep.
+	pub file_count: usize,
 }
 
 impl Default for Config {
@@ -53,6 +65,9 @@ impl Default for Config {
 			mode: None,
 			color: !cfg!(windows),
 			file: None,
+			json: false,
+			file_max_size: None,
+			file_count: 5,
 		}
 	}
 }
@@ -67,43 +82,42 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 
 	let mut levels = String::new();
 	let mut builder = LogBuilder::new();
-	// Disable info logging by default for some modules:
-	builder.filter(Some("ws"), LevelFilter::Warn);
-	builder.filter(Some("hyper"), LevelFilter::Warn);
-	builder.filter(Some("rustls"), LevelFilter::Error);
-	// Enable info for others.
-	builder.filter(None, LevelFilter::Info);
+	// Records are filtered by the rotating logger, so that levels can be changed at runtime.
+	builder.filter(None, LevelFilter::Trace);
 
 	if let Ok(lvl) = env::var("RUST_LOG") {
 		levels.push_str(&lvl);
 		levels.push_str(",");
-		builder.parse(&lvl);
 	}
 
 	if let Some(ref s) = config.mode {
 		levels.push_str(s);
-		builder.parse(s);
 	}
 
 	let isatty = atty::is(atty::Stream::Stderr);
-	let enable_color = config.color && isatty;
+	let enable_color = config.color && isatty && !config.json;
+	let json = config.json;
 	let logs = Arc::new(RotatingLogger::new(levels));
 	let logger = logs.clone();
-	let mut open_options = fs::OpenOptions::new();
 
 	let maybe_file = match config.file.as_ref() {
-		Some(f) => Some(open_options
-			.append(true).create(true).open(f)
+		Some(f) => Some(LogFile::open(f, config.file_max_size, config.file_count)
 			.map_err(|e| format!("Cannot write to log file given: {}, {}", f, e))?),
 		None => None,
 	};
 
 	let format = move |buf: &mut Formatter, record: &Record| {
-		let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
+		if !logger.matches(record) {
+			return Ok(());
+		}
 
-		let with_color = if max_level() <= LevelFilter::Info {
+		let with_color = if json {
+			json_line(record)
+		} else if max_level() <= LevelFilter::Info {
+			let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
 			format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
 		} else {
+			let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
 			let name = thread::current().name().map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
 			format!("{} {} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), record.args())
 		};
@@ -115,10 +129,9 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 			false => removed_color.clone(),
 		};
 
-		if let Some(mut file) = maybe_file.as_ref() {
+		if let Some(file) = maybe_file.as_ref() {
 			// ignore errors - there's nothing we can do
-			let _ = file.write_all(removed_color.as_bytes());
-			let _ = file.write_all(b"\n");
+			let _ = file.write_line(&removed_color);
 		}
 		logger.append(removed_color);
 		if !isatty && record.level() <= Level::Info && atty::is(atty::Stream::Stdout) {
@@ -132,6 +145,8 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 	builder.format(format);
 	builder.try_init()
 		.and_then(|_| {
+			// env_logger enables every level, restrict it to the configured ones.
+			set_max_level(logs.max_level());
 			*ROTATING_LOGGER.lock() = Arc::downgrade(&logs);
 			Ok(logs)
 		})
@@ -143,6 +158,39 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		})
 }
 
+/// Format the record as a single line JSON object.
+fn json_line(record: &Record) -> String {
+	let now = time::now_utc();
+	let timestamp = format!("{}.{:03}Z", time::strftime("%Y-%m-%dT%H:%M:%S", &now).unwrap(), now.tm_nsec / 1_000_000);
+	format!(
+		"{{\"timestamp\":\"{}\",\"level\":\"{}\",\"target\":{},\"thread\":{},\"message\":{}}}",
+		timestamp,
+		record.level(),
+		json_string(record.target()),
+		thread::current().name().map_or_else(|| "null".into(), json_string),
+		json_string(&record.args().to_string()),
+	)
+}
+
+/// Quote and escape a JSON string.
+fn json_string(s: &str) -> String {
+	let mut out = String::with_capacity(s.len() + 2);
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+	out
+}
+
 fn kill_color(s: &str) -> String {
 	lazy_static! {
 		static ref RE: Regex = Regex::new("\x1b\\[[^m]+m").unwrap();
@@ -157,6 +205,25 @@ fn should_remove_colour() {
 	assert_eq!(after, "test");
 }
 
+#[test]
+fn should_escape_json_strings() {
+	assert_eq!(json_string("plain"), r#""plain""#);
+	assert_eq!(json_string("a \"quoted\"\\path\n\x1b"), r#""a \"quoted\"\\path\n\u001b""#);
+}
+
+#[test]
+fn should_format_json_line() {
+	let line = json_line(&Record::builder()
+		.args(format_args!("Imported #{}", 1))
+		.level(rlog::Level::Info)
+		.target("import")
+		.build());
+
+	assert!(line.starts_with(r#"{"timestamp":""#));
+	assert!(line.contains(r#"Z","level":"INFO","target":"import","thread":"#));
+	assert!(line.ends_with(r#","message":"Imported #1"}"#));
+}
+
 #[test]
 fn should_remove_multiple_colour() {
 	let t = format!("{} {}", Colour::Red.bold().paint("test"), Colour::White.normal().paint("again"));
@@ -17,8 +17,10 @@
 //! Common log helper functions
 
 use std::env;
-use rlog::LevelFilter;
+use std::str::FromStr;
+use rlog::{self, LevelFilter, Record};
 use env_logger::Builder as LogBuilder;
+use env_logger::filter::{Builder as FilterBuilder, Filter};
 use arrayvec::ArrayVec;
 
 use parking_lot::{RwLock, RwLockReadGuard};
@@ -45,10 +47,39 @@ pub fn init_log() {
 
 const LOG_SIZE : usize = 128;
 
+/// Filter with the default levels, overridden by `levels`.
+fn build_filter(levels: &str) -> Filter {
+	let mut builder = FilterBuilder::new();
+	// Disable info logging by default for some modules:
+	builder.filter(Some("ws"), LevelFilter::Warn);
+	builder.filter(Some("hyper"), LevelFilter::Warn);
+	builder.filter(Some("rustls"), LevelFilter::Error);
+	// Enable info for others.
+	builder.filter(None, LevelFilter::Info);
+	builder.parse(levels);
+	builder.build()
+}
+
+/// Checks that every comma-separated directive is `level`, `target` or `target=level`.
+pub fn validate_levels(levels: &str) -> Result<(), String> {
+	let directives = levels.split('/').next().unwrap_or_default();
+	for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+		let mut parts = directive.split('=');
+		match (parts.next(), parts.next(), parts.next()) {
+			(Some(target), None, None) if !target.is_empty() => {},
+			(Some(target), Some(level), None) if !target.is_empty() && LevelFilter::from_str(level).is_ok() => {},
+			_ => return Err(format!("Invalid logging directive: {}", directive)),
+		}
+	}
+	Ok(())
+}
+
 /// Logger implementation that keeps up to `LOG_SIZE` log elements.
 pub struct RotatingLogger {
 	/// Defined logger levels
-	levels: String,
+	levels: RwLock<String>,
+	/// Filter built from the levels
+	filter: RwLock<Filter>,
 	/// Logs array. Latest log is always at index 0
 	logs: RwLock<ArrayVec<[String; LOG_SIZE]>>,
 }
@@ -56,14 +87,34 @@ pub struct RotatingLogger {
 impl RotatingLogger {
 
 	/// Creates new `RotatingLogger` with given levels.
-	/// It does not enforce levels - it's just read only.
 	pub fn new(levels: String) -> Self {
 		RotatingLogger {
-			levels: levels,
+			filter: RwLock::new(build_filter(&levels)),
+			levels: RwLock::new(levels),
 			logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
 		}
 	}
 
+	/// Replace logger levels, e.g. `sync=debug,rpc=trace`. Takes effect immediately.
+	pub fn set_levels(&self, levels: String) -> Result<(), String> {
+		validate_levels(&levels)?;
+		let filter = build_filter(&levels);
+		rlog::set_max_level(filter.filter());
+		*self.filter.write() = filter;
+		*self.levels.write() = levels;
+		Ok(())
+	}
+
+	/// Whether the record passes the current levels.
+	pub fn matches(&self, record: &Record) -> bool {
+		self.filter.read().matches(record)
+	}
+
+	/// Most verbose level enabled by the current levels.
+	pub fn max_level(&self) -> LevelFilter {
+		self.filter.read().filter()
+	}
+
 	/// Append new log entry
 	pub fn append(&self, log: String) {
 		let mut logs = self.logs.write();
@@ -74,8 +125,8 @@ impl RotatingLogger {
 	}
 
 	/// Return levels
-	pub fn levels(&self) -> &str {
-		&self.levels
+	pub fn levels(&self) -> String {
+		self.levels.read().clone()
 	}
 
 	/// Return logs
@@ -87,6 +138,7 @@ impl RotatingLogger {
 
 #[cfg(test)]
 mod test {
+	use rlog::LevelFilter;
 	use super::RotatingLogger;
 
 	fn logger() -> RotatingLogger {
@@ -105,6 +157,22 @@ mod test {
 		assert_eq!(levels, "test");
 	}
 
+	#[test]
+	fn should_update_log_levels() {
+		// given
+		let logger = logger();
+
+		// when
+		let invalid = logger.set_levels("sync=loud".into());
+		let valid = logger.set_levels("sync=debug,rpc".into());
+
+		// then
+		assert!(invalid.is_err());
+		assert!(valid.is_ok());
+		assert_eq!(logger.levels(), "sync=debug,rpc");
+		assert_eq!(logger.max_level(), LevelFilter::Trace);
+	}
+
 	#[test]
 	fn should_return_latest_logs() {
 		// given
@@ -235,6 +235,23 @@ fn main_direct(force_can_restart: bool) -> i32 {
 	// again.
 	let exiting = Arc::new(AtomicBool::new(false));
 
+	// Requested by Ctrl-C and the `parity_shutdown` RPC.
+	let request_exit = {
+		let e = exit.clone();
+		let exiting = exiting.clone();
+		move || {
+			if !exiting.swap(true, Ordering::SeqCst) {
+				*e.0.lock() = ExitStatus {
+					panicking: false,
+					should_exit: true,
+					should_restart: false,
+					spec_name_override: None,
+				};
+				e.1.notify_all();
+			}
+		}
+	};
+
 	let exec = if can_restart {
 		start(
 			conf,
@@ -268,11 +285,12 @@ fn main_direct(force_can_restart: bool) -> i32 {
 						e.1.notify_all();
 					}
 				}
-			}
+			},
+			request_exit.clone(),
 		)
 	} else {
 		trace!(target: "mode", "Not hypervised: not setting exit handlers.");
-		start(conf, logger, move |_| {}, move || {})
+		start(conf, logger, move |_| {}, move || {}, request_exit.clone())
 	};
 
 	let res = match exec {
@@ -298,21 +316,9 @@ fn main_direct(force_can_restart: bool) -> i32 {
 					}
 				});
 
-				ctrlc::set_handler({
-					let e = exit.clone();
-					let exiting = exiting.clone();
-					move || {
-						if !exiting.swap(true, Ordering::SeqCst) {
-							*e.0.lock() = ExitStatus {
-								panicking: false,
-								should_exit: true,
-								should_restart: false,
-								spec_name_override: None,
-							};
-							e.1.notify_all();
-						}
-					}
-				}).expect("Error setting Ctrl-C handler");
+				ctrlc::set_handler(request_exit).expect("Error setting Ctrl-C handler");
+				// SIGHUP is also trapped by the handler above, use it to reload the configuration instead.
+				openethereum::reload_on_sighup().expect("Error setting SIGHUP handler");
 
 				// so the client has started successfully
 				// if this is a daemon, detach from the parent process
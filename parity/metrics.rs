@@ -0,0 +1,185 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Prometheus metrics endpoint.
+
+use std::io;
+use std::sync::Arc;
+
+use ethereum_types::U256;
+use futures::future;
+use jsonrpc_core::MetaIoHandler;
+use parity_rpc::{self as rpc, hyper, DomainsValidation, HttpServer, Metadata, RequestMiddleware, RequestMiddlewareAction};
+use stats::{PrometheusMetrics, PrometheusRegistry};
+use sync::SyncProvider;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsConfiguration {
+	/// Is the metrics server enabled (default is false)?
+	pub enabled: bool,
+	/// Prefix of every metric name (default is `oe_`).
+	pub prefix: String,
+	/// The IP of the network interface used (default is 127.0.0.1).
+	pub interface: String,
+	/// The network port (default is 3000).
+	pub port: u16,
+}
+
+impl Default for MetricsConfiguration {
+	fn default() -> Self {
+		MetricsConfiguration {
+			enabled: false,
+			prefix: "oe_".into(),
+			interface: "127.0.0.1".into(),
+			port: 3000,
+		}
+	}
+}
+
+/// Peer counts of the sync module.
+pub struct SyncMetrics(pub Arc<dyn SyncProvider>);
+
+impl PrometheusMetrics for SyncMetrics {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let status = self.0.status();
+		r.register_gauge("sync_peers", "Connected peers", status.num_peers as i64);
+		r.register_gauge("sync_active_peers", "Peers we are downloading from", status.num_active_peers as i64);
+		r.register_gauge("sync_highest_block", "Highest block known to peers", status.highest_block_number.unwrap_or(0) as i64);
+
+		if let Some(stats) = self.0.light_serving_stats() {
+			r.register_counter("pip_cache_hits", "Light client requests answered from the response cache", stats.cache.hits);
+			r.register_counter("pip_cache_misses", "Cacheable light client requests answered by the provider", stats.cache.misses);
+			r.register_gauge("pip_cache_size", "Size of the light client response cache, in bytes", stats.cache.size as i64);
+
+			let peers: Vec<_> = stats.peers.iter().map(|peer| (peer.peer.to_string(), peer)).collect();
+			r.register_labelled_gauges("pip_peer_served_requests", "Light client requests served to the peer", "peer",
+				peers.iter().map(|(id, peer)| (id.as_str(), peer.served_requests as i64)));
+			r.register_labelled_gauges("pip_peer_credits_charged", "Credits charged to the peer for served requests", "peer",
+				peers.iter().map(|(id, peer)| (id.as_str(), saturating_i64(peer.credits_charged))));
+			r.register_labelled_gauges("pip_peer_credits", "Credits the peer has left", "peer",
+				peers.iter().map(|(id, peer)| (id.as_str(), saturating_i64(peer.credits))));
+		}
+	}
+}
+
+fn saturating_i64(value: U256) -> i64 {
+	value.min(U256::from(i64::max_value())).low_u64() as i64
+}
+
+/// Renders metrics of all sources at `/metrics` and rejects any other request.
+struct MetricsMiddleware {
+	prefix: String,
+	sources: Vec<Arc<dyn PrometheusMetrics + Send + Sync>>,
+}
+
+impl MetricsMiddleware {
+	fn render(&self) -> String {
+		let mut registry = PrometheusRegistry::new(&self.prefix);
+		for source in &self.sources {
+			source.prometheus_metrics(&mut registry);
+		}
+		registry.output()
+	}
+}
+
+impl RequestMiddleware for MetricsMiddleware {
+	fn on_request(&self, request: hyper::Request<hyper::Body>) -> RequestMiddlewareAction {
+		let response = if request.method() == hyper::Method::GET && request.uri().path() == "/metrics" {
+			hyper::Response::builder()
+				.header(hyper::header::CONTENT_TYPE, "text/plain; version=0.0.4")
+				.body(hyper::Body::from(self.render()))
+		} else {
+			hyper::Response::builder()
+				.status(hyper::StatusCode::NOT_FOUND)
+				.body(hyper::Body::empty())
+		};
+
+		RequestMiddlewareAction::Respond {
+			should_validate_hosts: false,
+			response: Box::new(future::ok(response.expect("status, header and body are valid; qed"))),
+		}
+	}
+}
+
+/// Start the metrics server if it's enabled.
+pub fn start_metrics_server(
+	conf: MetricsConfiguration,
+	sources: Vec<Arc<dyn PrometheusMetrics + Send + Sync>>,
+) -> Result<Option<HttpServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid metrics listen host/port given: {}", url))?;
+	let middleware = MetricsMiddleware {
+		prefix: conf.prefix,
+		sources,
+	};
+
+	let start_result = rpc::start_http_with_middleware(
+		&addr,
+		DomainsValidation::Disabled,
+		DomainsValidation::Disabled,
+		MetaIoHandler::<Metadata>::default(),
+		rpc::RpcExtractor,
+		middleware,
+		1,
+		1,
+		true,
+	);
+
+	match start_result {
+		Ok(server) => {
+			info!("Metrics available at http://{}/metrics", url);
+			Ok(Some(server))
+		},
+		Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => Err(
+			format!("Metrics address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --metrics-port and --metrics-interface options.", url)
+		),
+		Err(e) => Err(format!("Metrics server error: {:?}", e)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use parity_rpc::informant::RpcStats;
+	use super::*;
+
+	#[test]
+	fn should_serve_metrics_only_at_metrics_path() {
+		let stats = Arc::new(RpcStats::default());
+		stats.open_session();
+		let middleware = MetricsMiddleware {
+			prefix: "oe_".into(),
+			sources: vec![stats],
+		};
+
+		assert!(middleware.render().contains("\noe_rpc_sessions 1\n"));
+
+		let request = |method, path| hyper::Request::builder().method(method).uri(path).body(hyper::Body::empty()).unwrap();
+		match middleware.on_request(request("GET", "/metrics")) {
+			RequestMiddlewareAction::Respond { .. } => {},
+			RequestMiddlewareAction::Proceed { .. } => panic!("metrics should be served by the middleware"),
+		}
+		match middleware.on_request(request("POST", "/")) {
+			RequestMiddlewareAction::Respond { .. } => {},
+			RequestMiddlewareAction::Proceed { .. } => panic!("JSON-RPC requests should be rejected"),
+		}
+	}
+}
@@ -0,0 +1,176 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Export of tracing spans to an OpenTelemetry collector over OTLP/HTTP.
+
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use futures::Future;
+use hash_fetch::fetch::{self, Fetch};
+use parity_rpc::hyper::header::{CONTENT_TYPE, HeaderValue};
+use parking_lot::Mutex;
+use stats::{FinishedSpan, SpanExporter, Tracer, otlp_json};
+
+/// How often pending spans are sent to the collector.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(2);
+/// Spans finished above this limit before the next flush are dropped.
+const MAX_PENDING_SPANS: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpConfiguration {
+	/// Base URL of the OTLP/HTTP collector, tracing is disabled if not set.
+	pub endpoint: Option<String>,
+	/// Value of the `service.name` resource attribute (default is `openethereum`).
+	pub service_name: String,
+}
+
+impl Default for OtlpConfiguration {
+	fn default() -> Self {
+		OtlpConfiguration {
+			endpoint: None,
+			service_name: "openethereum".into(),
+		}
+	}
+}
+
+/// Buffers finished spans until they are sent to the collector.
+struct OtlpExporter {
+	pending: Mutex<Vec<FinishedSpan>>,
+	dropped: AtomicUsize,
+}
+
+impl SpanExporter for OtlpExporter {
+	fn export(&self, span: FinishedSpan) {
+		let mut pending = self.pending.lock();
+		if pending.len() < MAX_PENDING_SPANS {
+			pending.push(span);
+		} else {
+			self.dropped.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+}
+
+/// Background thread sending spans to the collector. Stops when dropped.
+pub struct OtlpService {
+	tracer: Tracer,
+	stop: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl OtlpService {
+	/// Tracer whose spans are sent by this service.
+	pub fn tracer(&self) -> Tracer {
+		self.tracer.clone()
+	}
+}
+
+impl Drop for OtlpService {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::SeqCst);
+		if let Some(handle) = self.handle.take() {
+			handle.thread().unpark();
+			let _ = handle.join();
+		}
+	}
+}
+
+/// Start sending spans to the collector if an endpoint is configured.
+pub fn start_otlp_exporter(conf: OtlpConfiguration, fetch: fetch::Client) -> Result<Option<OtlpService>, String> {
+	let endpoint = match conf.endpoint {
+		Some(endpoint) => endpoint,
+		None => return Ok(None),
+	};
+	let url = fetch::Url::parse(&format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+		.map_err(|e| format!("Invalid OTLP endpoint {}: {}", endpoint, e))?;
+
+	let exporter = Arc::new(OtlpExporter {
+		pending: Mutex::new(Vec::new()),
+		dropped: AtomicUsize::new(0),
+	});
+	let stop = Arc::new(AtomicBool::new(false));
+
+	let handle = {
+		let exporter = exporter.clone();
+		let stop = stop.clone();
+		thread::Builder::new()
+			.name("OTLP exporter".into())
+			.spawn(move || {
+				loop {
+					thread::park_timeout(FLUSH_INTERVAL);
+					let stopping = stop.load(Ordering::SeqCst);
+					let spans = mem::replace(&mut *exporter.pending.lock(), Vec::new());
+					let dropped = exporter.dropped.swap(0, Ordering::Relaxed);
+					if dropped > 0 {
+						warn!(target: "otlp", "Dropped {} spans, the collector at {} is not keeping up", dropped, url);
+					}
+
+					if !spans.is_empty() {
+							let request = fetch::Request::post(url.clone())
+							.with_header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+							.with_body(otlp_json(&conf.service_name, &spans));
+						match fetch.fetch(request, Default::default()).wait() {
+							Ok(ref response) if response.is_success() => {},
+							Ok(response) => debug!(target: "otlp", "Collector at {} rejected {} spans: {}", url, spans.len(), response.status()),
+							Err(e) => debug!(target: "otlp", "Failed to send {} spans to {}: {:?}", spans.len(), url, e),
+						}
+					}
+
+					if stopping {
+						break;
+					}
+				}
+			})
+			.map_err(|e| format!("Error starting OTLP exporter: {}", e))?
+	};
+
+	info!("Sending tracing spans to {}", url);
+	Ok(Some(OtlpService {
+		tracer: Tracer::new(exporter),
+		stop,
+		handle: Some(handle),
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_drop_spans_above_limit() {
+		let exporter = Arc::new(OtlpExporter {
+			pending: Mutex::new(Vec::new()),
+			dropped: AtomicUsize::new(0),
+		});
+		let tracer = Tracer::new(exporter.clone());
+		for _ in 0..MAX_PENDING_SPANS + 2 {
+			tracer.span("rpc.request").end();
+		}
+
+		assert_eq!(exporter.pending.lock().len(), MAX_PENDING_SPANS);
+		assert_eq!(exporter.dropped.load(Ordering::Relaxed), 2);
+	}
+
+	#[test]
+	fn should_not_start_without_endpoint() {
+		let fetch = fetch::Client::new(1).unwrap();
+		assert!(start_otlp_exporter(OtlpConfiguration::default(), fetch).unwrap().is_none());
+	}
+}
@@ -14,165 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
-use std::{str, fs, fmt};
+use std::str;
 
-use spec::{Spec, SpecParams, self};
 use ethereum_types::{U256, Address};
 use parity_runtime::Executor;
 use hash_fetch::fetch::Client as FetchClient;
 use journaldb::Algorithm;
+use ethstore::KdfParams;
 use miner::gas_pricer::GasPricer;
 use miner::gas_price_calibrator::{GasPriceCalibratorOptions, GasPriceCalibrator};
 use parity_version::version_data;
 use user_defaults::UserDefaults;
 use types::client_types::Mode;
 
-use crate::configuration;
-
-#[derive(Debug, PartialEq)]
-pub enum SpecType {
-	Foundation,
-	Classic,
-	ClassicNoPhoenix,
-	Poanet,
-	Xdai,
-	Volta,
-	Ewc,
-	Musicoin,
-	Ellaism,
-	Mix,
-	Callisto,
-	EtherCore,
-	Mordor,
-	Ropsten,
-	Kovan,
-	Rinkeby,
-	Goerli,
-	YOLOv1,
-	Kotti,
-	Sokol,
-	Evantestcore,
-	Evancore,
-	Dev,
-	Custom(String),
-}
-
-impl Default for SpecType {
-	fn default() -> Self {
-		SpecType::Foundation
-	}
-}
-
-impl str::FromStr for SpecType {
-	type Err = String;
-
-	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let spec = match s {
-			"eth" | "ethereum"  | "foundation" | "mainnet" => SpecType::Foundation,
-			"etc" | "classic" => SpecType::Classic,
-			"classic-no-phoenix" | "classic-oppose-phoenix" | "classic-oppose-phoenix-fork" =>
-				SpecType::ClassicNoPhoenix,
-			"poanet" | "poacore" => SpecType::Poanet,
-			"xdai" => SpecType::Xdai,
-			"volta" => SpecType::Volta,
-			"ewc" | "energyweb" => SpecType::Ewc,
-			"musicoin" => SpecType::Musicoin,
-			"ellaism" => SpecType::Ellaism,
-			"mix" => SpecType::Mix,
-			"callisto" => SpecType::Callisto,
-			"ethercore" => SpecType::EtherCore,
-			"mordor" | "classic-testnet" => SpecType::Mordor,
-			"ropsten" => SpecType::Ropsten,
-			"kovan" => SpecType::Kovan,
-			"rinkeby" => SpecType::Rinkeby,
-			"goerli" | "görli" | "testnet" => SpecType::Goerli,
-			"yolo" | "yolov1" => SpecType::YOLOv1,
-			"kotti" => SpecType::Kotti,
-			"sokol" | "poasokol" => SpecType::Sokol,
-			"evantestcore" => SpecType::Evantestcore,
-			"evancore" => SpecType::Evancore,
-			"dev" => SpecType::Dev,
-			other => SpecType::Custom(other.into()),
-		};
-		Ok(spec)
-	}
-}
-
-impl fmt::Display for SpecType {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		f.write_str(match *self {
-			SpecType::Foundation => "foundation",
-			SpecType::Classic => "classic",
-			SpecType::ClassicNoPhoenix => "classic-no-phoenix",
-			SpecType::Poanet => "poanet",
-			SpecType::Xdai => "xdai",
-			SpecType::Volta => "volta",
-			SpecType::Ewc => "energyweb",
-			SpecType::Musicoin => "musicoin",
-			SpecType::Ellaism => "ellaism",
-			SpecType::Mix => "mix",
-			SpecType::Callisto => "callisto",
-			SpecType::EtherCore => "ethercore",
-			SpecType::Mordor => "mordor",
-			SpecType::Ropsten => "ropsten",
-			SpecType::Kovan => "kovan",
-			SpecType::Rinkeby => "rinkeby",
-			SpecType::Goerli => "goerli",
-			SpecType::YOLOv1 => "yolov1",
-			SpecType::Kotti => "kotti",
-			SpecType::Sokol => "sokol",
-			SpecType::Evantestcore => "evantestcore",
-			SpecType::Evancore => "evancore",
-			SpecType::Dev => "dev",
-			SpecType::Custom(ref custom) => custom,
-		})
-	}
-}
-
-impl SpecType {
-	pub fn spec<'a, T: Into<SpecParams<'a>>>(&self, params: T) -> Result<Spec, String> {
-		let params = params.into();
-		match *self {
-			SpecType::Foundation => Ok(spec::new_foundation(params)),
-			SpecType::Classic => Ok(spec::new_classic(params)),
-			SpecType::ClassicNoPhoenix => Ok(spec::new_classic_no_phoenix(params)),
-			SpecType::Poanet => Ok(spec::new_poanet(params)),
-			SpecType::Xdai => Ok(spec::new_xdai(params)),
-			SpecType::Volta => Ok(spec::new_volta(params)),
-			SpecType::Ewc => Ok(spec::new_ewc(params)),
-			SpecType::Musicoin => Ok(spec::new_musicoin(params)),
-			SpecType::Ellaism => Ok(spec::new_ellaism(params)),
-			SpecType::Mix => Ok(spec::new_mix(params)),
-			SpecType::Callisto => Ok(spec::new_callisto(params)),
-			SpecType::EtherCore => Ok(spec::new_ethercore(params)),
-			SpecType::Mordor => Ok(spec::new_mordor(params)),
-			SpecType::Ropsten => Ok(spec::new_ropsten(params)),
-			SpecType::Kovan => Ok(spec::new_kovan(params)),
-			SpecType::Rinkeby => Ok(spec::new_rinkeby(params)),
-			SpecType::Goerli => Ok(spec::new_goerli(params)),
-			SpecType::YOLOv1 => Ok(spec::new_yolov1(params)),
-			SpecType::Kotti => Ok(spec::new_kotti(params)),
-			SpecType::Sokol => Ok(spec::new_sokol(params)),
-			SpecType::Evantestcore => Ok(spec::new_evantestcore(params)),
-			SpecType::Evancore => Ok(spec::new_evancore(params)),
-			SpecType::Dev => Ok(spec::new_instant()),
-			SpecType::Custom(ref filename) => {
-				let file = fs::File::open(filename).map_err(|e| format!("Could not load specification file at {}: {}", filename, e))?;
-				Spec::load(params, file).map_err(|e| e.to_string())
-			}
-		}
-	}
+pub use spec::SpecType;
 
-	pub fn legacy_fork_name(&self) -> Option<String> {
-		match *self {
-			SpecType::Classic => Some("classic".to_owned()),
-			SpecType::Musicoin => Some("musicoin".to_owned()),
-			_ => None,
-		}
-	}
-}
+use crate::configuration;
 
 #[derive(Debug, PartialEq)]
 pub enum Pruning {
@@ -242,30 +101,46 @@ impl str::FromStr for ResealPolicy {
 	}
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct RemoteSignerConfig {
+	/// `http(s)://` URL or path to an IPC socket.
+	pub endpoint: String,
+	/// File containing the bearer token for HTTP endpoints.
+	pub token_file: Option<String>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct AccountsConfig {
-	pub iterations: u32,
+	pub kdf: KdfParams,
 	pub refresh_time: u64,
 	pub testnet: bool,
 	pub password_files: Vec<String>,
 	pub unlocked_accounts: Vec<Address>,
 	pub enable_fast_unlock: bool,
+	pub unlock_session: Duration,
+	pub unlock_sessions: HashMap<Address, Duration>,
+	pub unlock_idle_timeout: Option<Duration>,
+	pub remote_signer: Option<RemoteSignerConfig>,
 }
 
 impl Default for AccountsConfig {
 	fn default() -> Self {
 		AccountsConfig {
-			iterations: 10240,
+			kdf: KdfParams::Pbkdf2 { iterations: 10240 },
 			refresh_time: 5,
 			testnet: false,
 			password_files: Vec::new(),
 			unlocked_accounts: Vec::new(),
 			enable_fast_unlock: false,
+			unlock_session: Duration::from_secs(300),
+			unlock_sessions: HashMap::new(),
+			unlock_idle_timeout: None,
+			remote_signer: None,
 		}
 	}
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum GasPricerConfig {
 	Fixed(U256),
 	Calibrated {
@@ -310,6 +185,7 @@ impl GasPricerConfig {
 pub struct MinerExtras {
 	pub author: Address,
 	pub engine_signer: Address,
+	pub sealing_journal: Option<String>,
 	pub extra_data: Vec<u8>,
 	pub gas_range_target: (U256, U256),
 	pub work_notify: Vec<String>,
@@ -321,6 +197,7 @@ impl Default for MinerExtras {
 		MinerExtras {
 			author: Default::default(),
 			engine_signer: Default::default(),
+			sealing_journal: None,
 			extra_data: version_data(),
 			gas_range_target: (8_000_000.into(), 10_000_000.into()),
 			work_notify: Default::default(),
@@ -386,75 +263,7 @@ pub fn mode_switch_to_bool(switch: Option<Mode>, user_defaults: &UserDefaults) -
 mod tests {
 	use journaldb::Algorithm;
 	use user_defaults::UserDefaults;
-	use super::{SpecType, Pruning, ResealPolicy, Switch, tracing_switch_to_bool};
-
-	#[test]
-	fn test_spec_type_parsing() {
-		assert_eq!(SpecType::Foundation, "eth".parse().unwrap());
-		assert_eq!(SpecType::Foundation, "ethereum".parse().unwrap());
-		assert_eq!(SpecType::Foundation, "foundation".parse().unwrap());
-		assert_eq!(SpecType::Foundation, "mainnet".parse().unwrap());
-		assert_eq!(SpecType::Classic, "etc".parse().unwrap());
-		assert_eq!(SpecType::Classic, "classic".parse().unwrap());
-		assert_eq!(SpecType::Poanet, "poanet".parse().unwrap());
-		assert_eq!(SpecType::Poanet, "poacore".parse().unwrap());
-		assert_eq!(SpecType::Xdai, "xdai".parse().unwrap());
-		assert_eq!(SpecType::Volta, "volta".parse().unwrap());
-		assert_eq!(SpecType::Ewc, "ewc".parse().unwrap());
-		assert_eq!(SpecType::Ewc, "energyweb".parse().unwrap());
-		assert_eq!(SpecType::Musicoin, "musicoin".parse().unwrap());
-		assert_eq!(SpecType::Ellaism, "ellaism".parse().unwrap());
-		assert_eq!(SpecType::Mix, "mix".parse().unwrap());
-		assert_eq!(SpecType::Callisto, "callisto".parse().unwrap());
-		assert_eq!(SpecType::EtherCore, "ethercore".parse().unwrap());
-		assert_eq!(SpecType::Mordor, "mordor".parse().unwrap());
-		assert_eq!(SpecType::Mordor, "classic-testnet".parse().unwrap());
-		assert_eq!(SpecType::Ropsten, "ropsten".parse().unwrap());
-		assert_eq!(SpecType::Kovan, "kovan".parse().unwrap());
-		assert_eq!(SpecType::Rinkeby, "rinkeby".parse().unwrap());
-		assert_eq!(SpecType::Goerli, "goerli".parse().unwrap());
-		assert_eq!(SpecType::Goerli, "görli".parse().unwrap());
-		assert_eq!(SpecType::Goerli, "testnet".parse().unwrap());
-		assert_eq!(SpecType::YOLOv1, "yolo".parse().unwrap());
-		assert_eq!(SpecType::YOLOv1, "yolov1".parse().unwrap());
-		assert_eq!(SpecType::Kotti, "kotti".parse().unwrap());
-		assert_eq!(SpecType::Sokol, "sokol".parse().unwrap());
-		assert_eq!(SpecType::Sokol, "poasokol".parse().unwrap());
-		assert_eq!(SpecType::Evantestcore, "evantestcore".parse().unwrap());
-		assert_eq!(SpecType::Evancore, "evancore".parse().unwrap());
-	}
-
-	#[test]
-	fn test_spec_type_default() {
-		assert_eq!(SpecType::Foundation, SpecType::default());
-	}
-
-	#[test]
-	fn test_spec_type_display() {
-		assert_eq!(format!("{}", SpecType::Foundation), "foundation");
-		assert_eq!(format!("{}", SpecType::Classic), "classic");
-		assert_eq!(format!("{}", SpecType::Poanet), "poanet");
-		assert_eq!(format!("{}", SpecType::Xdai), "xdai");
-		assert_eq!(format!("{}", SpecType::Volta), "volta");
-		assert_eq!(format!("{}", SpecType::Ewc), "energyweb");
-		assert_eq!(format!("{}", SpecType::Musicoin), "musicoin");
-		assert_eq!(format!("{}", SpecType::Ellaism), "ellaism");
-		assert_eq!(format!("{}", SpecType::Mix), "mix");
-		assert_eq!(format!("{}", SpecType::Callisto), "callisto");
-		assert_eq!(format!("{}", SpecType::EtherCore), "ethercore");
-		assert_eq!(format!("{}", SpecType::Mordor), "mordor");
-		assert_eq!(format!("{}", SpecType::Ropsten), "ropsten");
-		assert_eq!(format!("{}", SpecType::Kovan), "kovan");
-		assert_eq!(format!("{}", SpecType::Rinkeby), "rinkeby");
-		assert_eq!(format!("{}", SpecType::Goerli), "goerli");
-		assert_eq!(format!("{}", SpecType::YOLOv1), "yolov1");
-		assert_eq!(format!("{}", SpecType::Kotti), "kotti");
-		assert_eq!(format!("{}", SpecType::Sokol), "sokol");
-		assert_eq!(format!("{}", SpecType::Evantestcore), "evantestcore");
-		assert_eq!(format!("{}", SpecType::Evancore), "evancore");
-		assert_eq!(format!("{}", SpecType::Dev), "dev");
-		assert_eq!(format!("{}", SpecType::Custom("foo/bar".into())), "foo/bar");
-	}
+	use super::{Pruning, ResealPolicy, Switch, tracing_switch_to_bool};
 
 	#[test]
 	fn test_pruning_parsing() {
@@ -16,13 +16,13 @@
 
 
 use ethkey::Password;
-use ethstore::PresaleWallet;
+use ethstore::{KdfParams, PresaleWallet};
 use helpers::{password_prompt, password_from_file};
 use params::SpecType;
 
 #[derive(Debug, PartialEq)]
 pub struct ImportWallet {
-	pub iterations: u32,
+	pub kdf: KdfParams,
 	pub path: String,
 	pub spec: SpecType,
 	pub wallet_path: String,
@@ -49,7 +49,7 @@ pub fn import_account(cmd: &ImportWallet, kp: parity_crypto::publickey::KeyPair,
 	use ethstore::accounts_dir::RootDiskDirectory;
 
 	let dir = Box::new(RootDiskDirectory::create(cmd.path.clone()).unwrap());
-	let secret_store = Box::new(EthStore::open_with_iterations(dir, cmd.iterations).unwrap());
+	let secret_store = Box::new(EthStore::open_with_kdf(dir, cmd.kdf).unwrap());
 	let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
 	acc_provider.insert_account(kp.secret().clone(), &password).unwrap();
 }
@@ -0,0 +1,256 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Named profiles, to run several instances on the same host.
+//!
+//! A profile gets its own base path, `<base>/profiles/<name>`, which separates the database,
+//! keys and IPC socket, and a slot shifting every port by `PORTS_PER_PROFILE` times the slot.
+//! Slots and the ports in use are recorded in `<base>/profiles/profiles.json`, so that
+//! profiles overriding their ports can't silently collide with each other.
+//! Instances starting at the same time update the registry one at a time, under a lock file.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json;
+
+/// Ports of consecutive profile slots are this far apart.
+pub const PORTS_PER_PROFILE: u16 = 100;
+
+/// Name of the registry file in the profiles directory.
+const REGISTRY_FILE: &str = "profiles.json";
+
+/// Name of the file held while the registry is updated.
+const LOCK_FILE: &str = "profiles.json.lock";
+
+/// A lock file older than this was left behind by an instance which didn't finish its update.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ProfileEntry {
+	slot: u16,
+	#[serde(default)]
+	ports: BTreeMap<String, u16>,
+	#[serde(default)]
+	ipc_path: Option<String>,
+}
+
+type Registry = BTreeMap<String, ProfileEntry>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileConfiguration {
+	pub name: String,
+	/// Slot of the profile, starting at 1 so that an instance without profile keeps the default ports.
+	pub slot: u16,
+	/// Directory holding the registry and the base path of every profile.
+	pub profiles_dir: PathBuf,
+}
+
+impl ProfileConfiguration {
+	/// Base path of the profile.
+	pub fn base_path(&self) -> PathBuf {
+		self.profiles_dir.join(&self.name)
+	}
+
+	/// Added to every port of the profile.
+	pub fn ports_shift(&self) -> u16 {
+		self.slot * PORTS_PER_PROFILE
+	}
+
+	fn registry_path(&self) -> PathBuf {
+		self.profiles_dir.join(REGISTRY_FILE)
+	}
+}
+
+fn read_registry(path: &Path) -> Result<Registry, String> {
+	match File::open(path) {
+		Ok(file) => serde_json::from_reader(file).map_err(|e| format!("Invalid profile registry {}: {}", path.display(), e)),
+		Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Registry::new()),
+		Err(e) => Err(format!("Cannot read profile registry {}: {}", path.display(), e)),
+	}
+}
+
+/// Exclusive access to the registry, released on drop.
+struct RegistryLock(PathBuf);
+
+impl RegistryLock {
+	fn acquire(profiles_dir: &Path) -> Result<Self, String> {
+		let path = profiles_dir.join(LOCK_FILE);
+		let deadline = Instant::now() + 2 * STALE_LOCK_AGE;
+		loop {
+			match OpenOptions::new().write(true).create_new(true).open(&path) {
+				Ok(_) => return Ok(RegistryLock(path)),
+				Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists && Instant::now() < deadline => {
+					let stale = fs::metadata(&path)
+						.and_then(|metadata| metadata.modified())
+						.ok()
+						.and_then(|modified| modified.elapsed().ok())
+						.map_or(false, |age| age > STALE_LOCK_AGE);
+					if stale {
+						let _ = fs::remove_file(&path);
+					} else {
+						thread::sleep(Duration::from_millis(50));
+					}
+				},
+				Err(e) => return Err(format!("Cannot lock profile registry {}: {}", path.display(), e)),
+			}
+		}
+	}
+}
+
+impl Drop for RegistryLock {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.0);
+	}
+}
+
+/// Replace the registry file, so that readers never see it partially written.
+fn write_registry(path: &Path, registry: &Registry) -> Result<(), String> {
+	let error = |e: &dyn fmt::Display| format!("Cannot write profile registry {}: {}", path.display(), e);
+	let tmp_path = path.with_extension("json.tmp");
+	let file = File::create(&tmp_path).map_err(|e| error(&e))?;
+	serde_json::to_writer_pretty(&file, registry).map_err(|e| error(&e))?;
+	file.sync_all().map_err(|e| error(&e))?;
+	fs::rename(&tmp_path, path).map_err(|e| error(&e))
+}
+
+/// Look up the slot of a profile, or pick the first free one for a new profile.
+pub fn resolve(profiles_dir: &Path, name: &str) -> Result<ProfileConfiguration, String> {
+	if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+		return Err(format!("Invalid profile name {:?}. Use letters, digits, '-' and '_' only.", name));
+	}
+
+	let registry = read_registry(&profiles_dir.join(REGISTRY_FILE))?;
+	let slot = match registry.get(name) {
+		Some(entry) => entry.slot,
+		None => (1..).find(|slot| registry.values().all(|entry| entry.slot != *slot))
+			.expect("there are fewer profiles than slots; qed"),
+	};
+	if u32::from(slot) * u32::from(PORTS_PER_PROFILE) > u32::from(u16::max_value()) {
+		return Err(format!("Too many profiles, no ports left for profile {}", name));
+	}
+
+	Ok(ProfileConfiguration {
+		name: name.to_owned(),
+		slot,
+		profiles_dir: profiles_dir.to_owned(),
+	})
+}
+
+/// Describe every port and the IPC path the profile shares with other registered profiles.
+pub fn find_collisions(profile: &ProfileConfiguration, ports: &[(&str, u16)], ipc_path: Option<&str>) -> Result<Vec<String>, String> {
+	let registry = read_registry(&profile.registry_path())?;
+	let mut collisions = vec![];
+
+	for (name, entry) in registry.iter().filter(|&(name, _)| *name != profile.name) {
+		for &(option, port) in ports {
+			if let Some((other, _)) = entry.ports.iter().find(|&(_, other_port)| *other_port == port) {
+				collisions.push(format!("{} {} of profile {} is already used as {} by profile {}.", option, port, profile.name, other, name));
+			}
+		}
+		if ipc_path.is_some() && entry.ipc_path.as_ref().map(String::as_str) == ipc_path {
+			collisions.push(format!("IPC path of profile {} is already used by profile {}.", profile.name, name));
+		}
+	}
+
+	Ok(collisions)
+}
+
+/// Record the slot, ports and IPC path of the profile. Fails if they collide with another profile,
+/// or if another profile took the same slot since it was resolved.
+pub fn register(profile: &ProfileConfiguration, ports: &[(&str, u16)], ipc_path: Option<&str>) -> Result<(), String> {
+	fs::create_dir_all(&profile.profiles_dir)
+		.map_err(|e| format!("Cannot create profiles directory {}: {}", profile.profiles_dir.display(), e))?;
+	let _lock = RegistryLock::acquire(&profile.profiles_dir)?;
+
+	let collisions = find_collisions(profile, ports, ipc_path)?;
+	if !collisions.is_empty() {
+		return Err(collisions.join("\n"));
+	}
+
+	let registry_path = profile.registry_path();
+	let mut registry = read_registry(&registry_path)?;
+	if let Some((name, _)) = registry.iter().find(|&(name, entry)| *name != profile.name && entry.slot == profile.slot) {
+		return Err(format!("Slot {} of profile {} was taken by profile {}, restart to get a new slot.", profile.slot, profile.name, name));
+	}
+	registry.insert(profile.name.clone(), ProfileEntry {
+		slot: profile.slot,
+		ports: ports.iter().map(|&(option, port)| (option.to_owned(), port)).collect(),
+		ipc_path: ipc_path.map(ToOwned::to_owned),
+	});
+
+	write_registry(&registry_path, &registry)
+}
+
+#[cfg(test)]
+mod tests {
+	use tempfile::TempDir;
+	use super::{find_collisions, register, resolve};
+
+	#[test]
+	fn should_allocate_slots() {
+		let dir = TempDir::new().unwrap();
+		let archive = resolve(dir.path(), "mainnet-archive").unwrap();
+		assert_eq!(archive.slot, 1);
+		assert_eq!(archive.ports_shift(), 100);
+		assert_eq!(archive.base_path(), dir.path().join("mainnet-archive"));
+		register(&archive, &[("--port", 30403)], None).unwrap();
+
+		// registered profiles keep their slot, new ones get the next free one
+		assert_eq!(resolve(dir.path(), "mainnet-archive").unwrap().slot, 1);
+		assert_eq!(resolve(dir.path(), "goerli").unwrap().slot, 2);
+
+		assert!(resolve(dir.path(), "../goerli").is_err());
+		assert!(resolve(dir.path(), "").is_err());
+	}
+
+	#[test]
+	fn should_detect_collisions() {
+		let dir = TempDir::new().unwrap();
+		let archive = resolve(dir.path(), "archive").unwrap();
+		register(&archive, &[("--port", 30403), ("--jsonrpc-port", 8645)], Some("/tmp/archive.ipc")).unwrap();
+
+		let light = resolve(dir.path(), "light").unwrap();
+		let ports = [("--port", 30503), ("--ws-port", 8645)];
+		assert_eq!(find_collisions(&light, &ports, Some("/tmp/archive.ipc")).unwrap(), vec![
+			"--ws-port 8645 of profile light is already used as --jsonrpc-port by profile archive.".to_owned(),
+			"IPC path of profile light is already used by profile archive.".to_owned(),
+		]);
+		assert!(register(&light, &ports, None).is_err());
+		assert!(register(&light, &[("--port", 30503)], None).is_ok());
+
+		// a profile doesn't collide with its own previous registration
+		assert!(register(&archive, &[("--port", 30403)], None).is_ok());
+	}
+
+	#[test]
+	fn should_reject_slot_taken_since_resolved() {
+		let dir = TempDir::new().unwrap();
+		let archive = resolve(dir.path(), "archive").unwrap();
+		let light = resolve(dir.path(), "light").unwrap();
+		assert_eq!(archive.slot, light.slot);
+
+		register(&archive, &[("--port", 30403)], None).unwrap();
+		assert!(register(&light, &[("--port", 30503)], None).is_err());
+		assert!(!dir.path().join(super::LOCK_FILE).exists());
+	}
+}
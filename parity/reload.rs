@@ -0,0 +1,303 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Reloading a subset of the configuration without restarting the node.
+//!
+//! Only the settings in `ReloadableConfig` are applied, any other change to the command
+//! line or the config file requires a restart. A reload is triggered by `SIGHUP` or by the
+//! `parity_reloadConfig` RPC.
+
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use ethcore::miner::Miner;
+use ethcore_logger::RotatingLogger;
+use hash_fetch::fetch;
+use miner::pool;
+use parity_runtime::Executor;
+use parking_lot::Mutex;
+use sync::ManageNetwork;
+
+use configuration::Configuration;
+use params::GasPricerConfig;
+
+/// How often the watcher checks whether `SIGHUP` was received.
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Set by the `SIGHUP` handler, cleared by the watcher thread.
+static SIGHUP_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// Settings that can be changed while the node is running.
+#[derive(Debug, PartialEq)]
+pub struct ReloadableConfig {
+	/// Logging targets, as in `--logging`.
+	pub logging: String,
+	pub min_peers: u32,
+	pub max_peers: u32,
+	/// Enode URLs read from the `--reserved-peers` file.
+	pub reserved_peers: Vec<String>,
+	pub reserved_only: bool,
+	pub tx_queue_quotas: pool::Quotas,
+	pub tx_queue_expiry: pool::Expiry,
+	pub gas_pricer: GasPricerConfig,
+}
+
+impl ReloadableConfig {
+	/// Human readable description of every setting that differs in `new`.
+	pub fn changes(&self, new: &ReloadableConfig) -> Vec<String> {
+		let mut changes = Vec::new();
+		if self.logging != new.logging {
+			changes.push(format!("logging: {:?} -> {:?}", self.logging, new.logging));
+		}
+		if self.min_peers != new.min_peers {
+			changes.push(format!("min_peers: {} -> {}", self.min_peers, new.min_peers));
+		}
+		if self.max_peers != new.max_peers {
+			changes.push(format!("max_peers: {} -> {}", self.max_peers, new.max_peers));
+		}
+		for peer in new.reserved_peers.iter().filter(|p| !self.reserved_peers.contains(p)) {
+			changes.push(format!("reserved_peers: +{}", peer));
+		}
+		for peer in self.reserved_peers.iter().filter(|p| !new.reserved_peers.contains(p)) {
+			changes.push(format!("reserved_peers: -{}", peer));
+		}
+		if self.reserved_only != new.reserved_only {
+			changes.push(format!("reserved_only: {} -> {}", self.reserved_only, new.reserved_only));
+		}
+		if self.tx_queue_quotas != new.tx_queue_quotas {
+			changes.push(format!("tx_queue_quotas: {:?} -> {:?}", self.tx_queue_quotas, new.tx_queue_quotas));
+		}
+		if self.tx_queue_expiry != new.tx_queue_expiry {
+			changes.push(format!("tx_queue_expiry: {:?} -> {:?}", self.tx_queue_expiry, new.tx_queue_expiry));
+		}
+		if self.gas_pricer != new.gas_pricer {
+			changes.push(format!("gas_pricer: {:?} -> {:?}", self.gas_pricer, new.gas_pricer));
+		}
+		changes
+	}
+}
+
+/// Re-reads the configuration and applies the reloadable settings to the running services.
+pub struct Reloader {
+	args: Vec<String>,
+	current: Mutex<ReloadableConfig>,
+	logger: Arc<RotatingLogger>,
+	network: Arc<dyn ManageNetwork>,
+	/// Not available for the light client, transaction queue and gas price settings are ignored.
+	miner: Option<Arc<Miner>>,
+	fetch: fetch::Client,
+	executor: Executor,
+}
+
+impl Reloader {
+	/// Creates a reloader for a node started with the given command line and configuration.
+	pub fn new(
+		args: Vec<String>,
+		current: ReloadableConfig,
+		logger: Arc<RotatingLogger>,
+		network: Arc<dyn ManageNetwork>,
+		miner: Option<Arc<Miner>>,
+		fetch: fetch::Client,
+		executor: Executor,
+	) -> Self {
+		Reloader {
+			args,
+			current: Mutex::new(current),
+			logger,
+			network,
+			miner,
+			fetch,
+			executor,
+		}
+	}
+
+	fn read_config(args: &[String]) -> Result<ReloadableConfig, String> {
+		let mut conf = Configuration::parse_cli(args).map_err(|e| e.to_string())?;
+		conf.apply_profile()?;
+		conf.reloadable_config()
+	}
+
+	/// Re-read the configuration and apply what changed. Returns the changes, which are
+	/// only validated and not applied if `dry_run` is set.
+	pub fn reload(&self, dry_run: bool) -> Result<Vec<String>, String> {
+		let mut new = Self::read_config(&self.args)?;
+		let mut current = self.current.lock();
+		if self.miner.is_none() {
+			new.tx_queue_quotas = current.tx_queue_quotas.clone();
+			new.tx_queue_expiry = current.tx_queue_expiry;
+			new.gas_pricer = current.gas_pricer.clone();
+		}
+
+		let changes = current.changes(&new);
+		if dry_run || changes.is_empty() {
+			return Ok(changes);
+		}
+
+		if current.logging != new.logging {
+			// `RUST_LOG` is kept in front of the configured targets, as on startup.
+			let levels = match env::var("RUST_LOG") {
+				Ok(lvl) => format!("{},{}", lvl, new.logging),
+				Err(_) => new.logging.clone(),
+			};
+			self.logger.set_levels(levels)?;
+		}
+		if current.min_peers != new.min_peers || current.max_peers != new.max_peers {
+			self.network.set_peer_limits(new.min_peers, new.max_peers);
+		}
+		for peer in new.reserved_peers.iter().filter(|p| !current.reserved_peers.contains(p)) {
+			self.network.add_reserved_peer(peer.clone())?;
+		}
+		for peer in current.reserved_peers.iter().filter(|p| !new.reserved_peers.contains(p)) {
+			self.network.remove_reserved_peer(peer.clone())?;
+		}
+		if current.reserved_only != new.reserved_only {
+			if new.reserved_only {
+				self.network.deny_unreserved_peers();
+			} else {
+				self.network.accept_unreserved_peers();
+			}
+		}
+		if let Some(ref miner) = self.miner {
+			if current.tx_queue_quotas != new.tx_queue_quotas || current.tx_queue_expiry != new.tx_queue_expiry {
+				miner.set_transaction_queue_limits(new.tx_queue_quotas.clone(), new.tx_queue_expiry);
+			}
+			if current.gas_pricer != new.gas_pricer {
+				miner.set_gas_pricer(new.gas_pricer.to_gas_pricer(self.fetch.clone(), self.executor.clone()));
+			}
+		}
+
+		*current = new;
+		Ok(changes)
+	}
+}
+
+/// Reload the configuration on `SIGHUP` instead of exiting.
+///
+/// Must be called after the Ctrl-C handler is installed, since it also traps `SIGHUP`.
+#[cfg(unix)]
+pub fn reload_on_sighup() -> Result<(), String> {
+	use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+	extern "C" fn on_sighup(_: nix::libc::c_int) {
+		SIGHUP_RECEIVED.store(true, Ordering::SeqCst);
+	}
+
+	let action = SigAction::new(SigHandler::Handler(on_sighup), SaFlags::SA_RESTART, SigSet::empty());
+	unsafe { signal::sigaction(Signal::SIGHUP, &action) }
+		.map(|_| ())
+		.map_err(|e| format!("Error setting SIGHUP handler: {}", e))
+}
+
+/// Reload the configuration on `SIGHUP` instead of exiting.
+#[cfg(not(unix))]
+pub fn reload_on_sighup() -> Result<(), String> {
+	Ok(())
+}
+
+/// Applies the configuration each time `SIGHUP` is received, stops when dropped.
+pub struct ReloadService {
+	stop: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for ReloadService {
+	fn drop(&mut self) {
+		self.stop.store(true, Ordering::SeqCst);
+		if let Some(handle) = self.handle.take() {
+			handle.thread().unpark();
+			let _ = handle.join();
+		}
+	}
+}
+
+/// Start the thread reloading the configuration on `SIGHUP`.
+pub fn start_reload_service(reloader: Arc<Reloader>) -> Result<ReloadService, String> {
+	let stop = Arc::new(AtomicBool::new(false));
+	let handle = {
+		let stop = stop.clone();
+		thread::Builder::new()
+			.name("Config reload".into())
+			.spawn(move || {
+				while !stop.load(Ordering::SeqCst) {
+					thread::park_timeout(SIGNAL_POLL_INTERVAL);
+					if !SIGHUP_RECEIVED.swap(false, Ordering::SeqCst) {
+						continue;
+					}
+					match reloader.reload(false) {
+						Ok(ref changes) if changes.is_empty() => info!("Configuration reloaded, nothing changed"),
+						Ok(changes) => info!("Configuration reloaded: {}", changes.join(", ")),
+						Err(e) => warn!("Configuration not reloaded: {}", e),
+					}
+				}
+			})
+			.map_err(|e| format!("Error starting config reload thread: {}", e))?
+	};
+
+	Ok(ReloadService {
+		stop,
+		handle: Some(handle),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use ethereum_types::U256;
+	use miner::pool;
+	use params::GasPricerConfig;
+	use super::ReloadableConfig;
+
+	fn config() -> ReloadableConfig {
+		ReloadableConfig {
+			logging: "".into(),
+			min_peers: 25,
+			max_peers: 50,
+			reserved_peers: vec!["enode://a@127.0.0.1:30303".into()],
+			reserved_only: false,
+			tx_queue_quotas: pool::Quotas::default(),
+			tx_queue_expiry: pool::Expiry::default(),
+			gas_pricer: GasPricerConfig::Fixed(U256::zero()),
+		}
+	}
+
+	#[test]
+	fn should_report_no_changes() {
+		assert!(config().changes(&config()).is_empty());
+	}
+
+	#[test]
+	fn should_report_changes() {
+		let new = ReloadableConfig {
+			logging: "sync=debug".into(),
+			max_peers: 100,
+			reserved_peers: vec!["enode://b@127.0.0.1:30303".into()],
+			tx_queue_expiry: pool::Expiry { local: None, remote: Some(Duration::from_secs(60)) },
+			..config()
+		};
+
+		assert_eq!(config().changes(&new), vec![
+			"logging: \"\" -> \"sync=debug\"".to_owned(),
+			"max_peers: 50 -> 100".to_owned(),
+			"reserved_peers: +enode://b@127.0.0.1:30303".to_owned(),
+			"reserved_peers: -enode://a@127.0.0.1:30303".to_owned(),
+			"tx_queue_expiry: Expiry { local: None, remote: None } -> Expiry { local: None, remote: Some(60s) }".to_owned(),
+		]);
+	}
+}
@@ -23,10 +23,11 @@ use dir::default_data_path;
 use dir::helpers::replace_home;
 use helpers::parity_ipc_path;
 use jsonrpc_core::MetaIoHandler;
-use parity_runtime::Executor;
+use parity_runtime::{Executor, Runtime};
 use parity_rpc::informant::{RpcStats, Middleware};
-use parity_rpc::{self as rpc, Metadata, DomainsValidation};
-use rpc_apis::{self, ApiSet};
+use parity_rpc::{self as rpc, Metadata, DomainsValidation, JwtAuth, JwtSecret, HeavyRequests, RequestPools, ResponseCache};
+use rpc_apis::{self, Api, ApiSet};
+use stats::Tracer;
 
 pub use parity_rpc::{IpcServer, HttpServer, RequestMiddleware};
 pub use parity_rpc::ws::{Server as WsServer, ws};
@@ -126,6 +127,96 @@ impl Default for WsConfiguration {
 	}
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineApiConfiguration {
+	/// Is the Engine API server enabled regardless of the chain specification (default is false)?
+	pub enabled: bool,
+	/// The IP of the network interface used (default is 127.0.0.1).
+	pub interface: String,
+	/// The network port (default is 8551).
+	pub port: u16,
+	/// Path of the hex-encoded secret shared with the consensus client.
+	pub jwt_secret: PathBuf,
+}
+
+impl Default for EngineApiConfiguration {
+	fn default() -> Self {
+		let data_dir = default_data_path();
+		EngineApiConfiguration {
+			enabled: false,
+			interface: "127.0.0.1".into(),
+			port: 8551,
+			jwt_secret: replace_home(&data_dir, "$BASE/jwtsecret").into(),
+		}
+	}
+}
+
+/// Threads and queue of a pool running expensive requests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RequestPoolConfiguration {
+	/// Number of threads running requests (default is 4).
+	pub threads: usize,
+	/// Number of requests queued or running above which new ones are rejected (default is 256).
+	pub max_queued: usize,
+}
+
+impl Default for RequestPoolConfiguration {
+	fn default() -> Self {
+		RequestPoolConfiguration {
+			threads: 4,
+			max_queued: 256,
+		}
+	}
+}
+
+/// Threads of the pool running Engine API requests.
+const ENGINE_POOL_THREADS: usize = 2;
+/// Engine API requests queued before new ones are rejected.
+const ENGINE_POOL_MAX_QUEUED: usize = 64;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestPoolsConfiguration {
+	/// Pool of the request kinds without a pool of their own.
+	pub shared: RequestPoolConfiguration,
+	/// Dedicated pool of `trace_*` requests.
+	pub traces: Option<RequestPoolConfiguration>,
+	/// Dedicated pool of `eth_getLogs` requests.
+	pub logs: Option<RequestPoolConfiguration>,
+	/// Dedicated pool of `debug_trace*` requests.
+	pub debug: Option<RequestPoolConfiguration>,
+}
+
+impl RequestPoolsConfiguration {
+	/// Start the pools. The returned runtimes have to be kept alive as long as the pools are used.
+	pub fn start(&self) -> (RequestPools, Vec<Runtime>) {
+		let mut runtimes = Vec::new();
+		let mut start = |name, conf: &RequestPoolConfiguration| {
+			let runtime = Runtime::with_thread_count(conf.threads);
+			let pool = HeavyRequests::with_queue_limit(name, runtime.executor(), conf.max_queued);
+			runtimes.push(runtime);
+			pool
+		};
+
+		let shared = match (self.traces, self.logs, self.debug) {
+			(Some(_), Some(_), Some(_)) => None,
+			_ => Some(start("shared", &self.shared)),
+		};
+		let mut pool = |name, conf: &Option<RequestPoolConfiguration>| match *conf {
+			Some(ref conf) => start(name, conf),
+			None => shared.clone().expect("shared pool is started unless every kind has a pool; qed"),
+		};
+
+		let pools = RequestPools {
+			traces: pool("traces", &self.traces),
+			logs: pool("logs", &self.logs),
+			debug: pool("debug", &self.debug),
+			// the consensus client waits for its requests, so they never queue behind traces.
+			engine: start("engine", &RequestPoolConfiguration { threads: ENGINE_POOL_THREADS, max_queued: ENGINE_POOL_MAX_QUEUED }),
+		};
+		(pools, runtimes)
+	}
+}
+
 impl WsConfiguration {
 	pub fn address(&self) -> Option<rpc::Host> {
 		address(self.enabled, &self.interface, self.port, &self.hosts)
@@ -147,6 +238,8 @@ pub struct Dependencies<D: rpc_apis::Dependencies> {
 	pub apis: Arc<D>,
 	pub executor: Executor,
 	pub stats: Arc<RpcStats>,
+	pub tracer: Tracer,
+	pub response_cache: Option<Arc<ResponseCache>>,
 }
 
 pub fn new_ws<D: rpc_apis::Dependencies>(
@@ -165,10 +258,11 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 	let handler = {
 		let mut handler = MetaIoHandler::with_middleware((
 			rpc::WsDispatcher::new(full_handler),
-			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier())
+			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.tracer.clone(), deps.response_cache.clone())
 		));
 		let apis = conf.apis.list_apis();
 		deps.apis.extend_with_set(&mut handler, &apis);
+		deps.stats.register_methods(handler.iter().map(|(method, _)| method.clone()));
 
 		handler
 	};
@@ -244,6 +338,43 @@ pub fn new_http<D: rpc_apis::Dependencies>(
 	}
 }
 
+pub fn new_engine_api<D: rpc_apis::Dependencies>(
+	conf: EngineApiConfiguration,
+	deps: &Dependencies<D>,
+) -> Result<Option<HttpServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid Engine API listen host/port given: {}", url))?;
+	// Every request is authenticated with the shared secret, so hosts and origins are not restricted.
+	let apis = [Api::EngineApi, Api::Eth, Api::Net, Api::Web3].iter().cloned().collect();
+	let handler = setup_apis(ApiSet::List(apis), deps);
+	let secret = JwtSecret::load_or_generate(&conf.jwt_secret)?;
+
+	let start_result = rpc::start_http_with_middleware(
+		&addr,
+		DomainsValidation::Disabled,
+		DomainsValidation::Disabled,
+		handler,
+		rpc::RpcExtractor,
+		JwtAuth::new(secret),
+		4,
+		// Execution payloads carry whole blocks.
+		16,
+		true,
+	);
+
+	match start_result {
+		Ok(server) => Ok(Some(server)),
+		Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => Err(
+			format!("Engine API address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --engine-api-port and --engine-api-interface options.", url)
+		),
+		Err(e) => Err(format!("Engine API error: {:?}", e)),
+	}
+}
+
 pub fn new_ipc<D: rpc_apis::Dependencies>(
 	conf: IpcConfiguration,
 	dependencies: &Dependencies<D>
@@ -311,10 +442,11 @@ pub fn setup_apis<D>(apis: ApiSet, deps: &Dependencies<D>) -> MetaIoHandler<Meta
 	where D: rpc_apis::Dependencies
 {
 	let mut handler = MetaIoHandler::with_middleware(
-		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier())
+		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.tracer.clone(), deps.response_cache.clone())
 	);
 	let apis = apis.list_apis();
 	deps.apis.extend_with_set(&mut handler, &apis);
+	deps.stats.register_methods(handler.iter().map(|(method, _)| method.clone()));
 
 	handler
 }
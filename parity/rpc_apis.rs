@@ -24,20 +24,23 @@ pub use parity_rpc::signer::SignerService;
 use account_utils::{self, AccountProvider};
 use ethcore::client::Client;
 use ethcore::miner::Miner;
+use ethcore::miner::stratum::WorkerStatsProvider;
 use snapshot::SnapshotService;
 use client_traits::BlockChainClient;
 use sync::SyncState;
 use ethcore_logger::RotatingLogger;
 use ethcore_private_tx::Provider as PrivateTransactionManager;
 use ethcore_service::PrivateTxService;
+use gossip::GossipProvider;
 use hash_fetch::fetch::Client as FetchClient;
 use jsonrpc_core::{self as core, MetaIoHandler};
 use light::client::LightChainClient;
-use light::{Cache as LightDataCache, TransactionQueue as LightTransactionQueue};
+use light::{ChtStore, Cache as LightDataCache, TransactionQueue as LightTransactionQueue};
 use miner::external::ExternalMiner;
 use parity_rpc::dispatch::{FullDispatcher, LightDispatcher};
 use parity_rpc::informant::{ActivityNotifier, ClientNotifier};
-use parity_rpc::{Host, Metadata, NetworkSettings};
+use parity_rpc::{Host, InstalledFilters, Metadata, NetworkSettings, PollLimits, RequestPools};
+use parity_rpc::signer::RemoteSigner;
 use parity_rpc::v1::traits::TransactionsPool;
 use parity_runtime::Executor;
 use parking_lot::{Mutex, RwLock};
@@ -77,8 +80,24 @@ pub enum Api {
 	/// Geth-compatible (best-effort) debug API (Potentially UNSAFE)
 	/// NOTE We don't aim to support all methods, only the ones that are useful.
 	Debug,
+	/// Clique signer votes (UNSAFE: Side Effects affecting the signer set)
+	Clique,
+	/// Development chain controls of the instant seal engine (UNSAFE: Side Effects rewinding the chain)
+	Evm,
+	/// Miner settings (UNSAFE: Side Effects affecting block production)
+	Miner,
+	/// Statistics of the stratum server workers (Safe)
+	Mining,
+	/// Transaction bundles placed at the top of authored blocks (UNSAFE: Side Effects affecting block production)
+	/// NOTE Not part of any default set, it has to be listed explicitly.
+	Bundle,
+	/// Engine API for proof-of-stake consensus clients (UNSAFE: Side Effects selecting the canonical chain)
+	/// NOTE Not part of any default set; the Engine API server always serves it behind JWT authentication.
+	EngineApi,
 	/// Parity Transactions pool PubSub
 	ParityTransactionsPool,
+	/// Gossip messaging under Whisper's method names (Safe: messages are public)
+	Shh,
 	/// Deprecated api
 	Deprecated,
 }
@@ -90,8 +109,14 @@ impl FromStr for Api {
 		use self::Api::*;
 
 		match s {
+			"bundle" => Ok(Bundle),
+			"clique" => Ok(Clique),
 			"debug" => Ok(Debug),
+			"engine" => Ok(EngineApi),
 			"eth" => Ok(Eth),
+			"evm" => Ok(Evm),
+			"miner" => Ok(Miner),
+			"mining" => Ok(Mining),
 			"net" => Ok(Net),
 			"parity" => Ok(Parity),
 			"parity_accounts" => Ok(ParityAccounts),
@@ -106,7 +131,8 @@ impl FromStr for Api {
 			"traces" => Ok(Traces),
 			"web3" => Ok(Web3),
 			"parity_transactions_pool" => Ok(ParityTransactionsPool),
-			"shh" | "shh_pubsub" => Ok(Deprecated),
+			"shh" => Ok(Shh),
+			"shh_pubsub" => Ok(Deprecated),
 			api => Err(format!("Unknown api: {}", api)),
 		}
 	}
@@ -173,9 +199,15 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 	let mut modules = BTreeMap::new();
 	for api in apis {
 		let (name, version) = match *api {
+			Api::Bundle => ("bundle", "1.0"),
+			Api::Clique => ("clique", "1.0"),
 			Api::Debug => ("debug", "1.0"),
+			Api::EngineApi => ("engine", "1.0"),
 			Api::Eth => ("eth", "1.0"),
 			Api::EthPubSub => ("pubsub", "1.0"),
+			Api::Evm => ("evm", "1.0"),
+			Api::Miner => ("miner", "1.0"),
+			Api::Mining => ("mining", "1.0"),
 			Api::Net => ("net", "1.0"),
 			Api::Parity => ("parity", "1.0"),
 			Api::ParityAccounts => ("parity_accounts", "1.0"),
@@ -185,6 +217,7 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 			Api::Private => ("private", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
 			Api::SecretStore => ("secretstore", "1.0"),
+			Api::Shh => ("shh", "1.0"),
 			Api::Signer => ("signer", "1.0"),
 			Api::Traces => ("traces", "1.0"),
 			Api::Web3 => ("web3", "1.0"),
@@ -239,9 +272,12 @@ pub struct FullDependencies {
 	pub sync: Arc<dyn SyncProvider>,
 	pub net: Arc<dyn ManageNetwork>,
 	pub accounts: Arc<AccountProvider>,
+	pub remote_signer: Option<Arc<RemoteSigner>>,
 	pub private_tx_service: Option<Arc<PrivateTxService>>,
 	pub miner: Arc<Miner>,
 	pub external_miner: Arc<ExternalMiner>,
+	pub stratum_workers: Option<Arc<dyn WorkerStatsProvider>>,
+	pub gossip: Option<Arc<dyn GossipProvider>>,
 	pub logger: Arc<RotatingLogger>,
 	pub settings: Arc<NetworkSettings>,
 	pub net_service: Arc<dyn ManageNetwork>,
@@ -251,10 +287,15 @@ pub struct FullDependencies {
 	pub ws_address: Option<Host>,
 	pub fetch: FetchClient,
 	pub executor: Executor,
+	pub request_pools: RequestPools,
 	pub gas_price_percentile: usize,
-	pub poll_lifetime: u32,
+	pub filters: Arc<InstalledFilters>,
 	pub allow_missing_blocks: bool,
+	pub reconstruct_logs: bool,
 	pub no_ancient_blocks: bool,
+	pub shutdown: Arc<dyn Fn() + Send + Sync>,
+	pub reload: Arc<dyn Fn(bool) -> Result<Vec<String>, String> + Send + Sync>,
+	pub cht_store: Option<Arc<ChtStore>>,
 }
 
 impl FullDependencies {
@@ -277,13 +318,38 @@ impl FullDependencies {
 			nonces.clone(),
 			self.gas_price_percentile,
 		);
-		let account_signer = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
-		let accounts = account_utils::accounts_list(self.accounts.clone());
+		let (account_signer, accounts) = match self.remote_signer {
+			Some(ref signer) => (signer.clone() as Arc<dyn dispatch::Accounts>, account_utils::remote_accounts_list(signer.clone())),
+			None => (Arc::new(dispatch::Signer::new(self.accounts.clone())) as _, account_utils::accounts_list(self.accounts.clone())),
+		};
 
 		for api in apis {
 			match *api {
 				Api::Debug => {
-					handler.extend_with(DebugClient::new(self.client.clone()).to_delegate());
+					handler.extend_with(DebugClient::new(self.client.clone(), self.request_pools.debug.clone()).to_delegate());
+					handler.extend_with(DebugPubSubClient::new(self.client.clone(), self.request_pools.debug.clone(), self.executor.clone()).to_delegate());
+				}
+				Api::Clique => {
+					handler.extend_with(CliqueClient::new(self.client.clone()).to_delegate());
+				}
+				Api::Bundle => {
+					handler.extend_with(EthBundleClient::new(&self.client, &self.miner).to_delegate());
+				}
+				Api::EngineApi => {
+					let client = EngineApiClient::new(self.client.clone(), self.miner.clone(), self.request_pools.engine.clone());
+					if let Some(h) = client.handler().upgrade() {
+						self.client.add_notify(h);
+					}
+					handler.extend_with(client.to_delegate());
+				}
+				Api::Evm => {
+					handler.extend_with(EvmClient::new(&self.client, &self.miner).to_delegate());
+				}
+				Api::Miner => {
+					handler.extend_with(MinerClient::new(&self.miner).to_delegate());
+				}
+				Api::Mining => {
+					handler.extend_with(MiningClient::new(self.stratum_workers.clone()).to_delegate());
 				}
 				Api::Web3 => {
 					handler.extend_with(Web3Client::default().to_delegate());
@@ -305,9 +371,11 @@ impl FullDependencies {
 							send_block_number_in_get_work: !self.geth_compatibility,
 							gas_price_percentile: self.gas_price_percentile,
 							allow_missing_blocks: self.allow_missing_blocks,
+							reconstruct_logs: self.reconstruct_logs,
 							allow_experimental_rpcs: self.experimental_rpcs,
 							no_ancient_blocks: self.no_ancient_blocks
-						}
+						},
+						self.request_pools.logs.clone(),
 					);
 					handler.extend_with(client.to_delegate());
 
@@ -315,7 +383,8 @@ impl FullDependencies {
 						let filter_client = EthFilterClient::new(
 							self.client.clone(),
 							self.miner.clone(),
-							self.poll_lifetime,
+							self.filters.clone(),
+							self.request_pools.logs.clone(),
 						);
 						handler.extend_with(filter_client.to_delegate());
 
@@ -350,7 +419,9 @@ impl FullDependencies {
 				Api::ParityTransactionsPool => {
 					if !for_generic_pubsub {
 						let receiver = self.miner.full_transactions_receiver();
-						let client = TransactionsPoolClient::new(self.executor.clone(), receiver);
+						let miner = self.miner.clone();
+						let client = TransactionsPoolClient::new(self.executor.clone(), receiver)
+							.with_events(move || miner.transaction_events_receiver());
 						handler.extend_with(TransactionsPoolClient::to_delegate(client));
 					}
 				}
@@ -362,7 +433,7 @@ impl FullDependencies {
 							dispatcher.clone(),
 							self.geth_compatibility,
 							self.experimental_rpcs,
-						).to_delegate(),
+						).with_remote_signer(self.remote_signer.clone().map(|signer| signer as _)).to_delegate(),
 					);
 				}
 				Api::Signer => {
@@ -392,6 +463,8 @@ impl FullDependencies {
 							signer,
 							self.ws_address.clone(),
 							self.snapshot.clone().into(),
+							self.cht_store.clone(),
+							self.request_pools.clone(),
 						).to_delegate(),
 					);
 					#[cfg(feature = "accounts")]
@@ -424,6 +497,10 @@ impl FullDependencies {
 							&self.miner,
 							&self.updater,
 							&self.net_service,
+							&self.logger,
+							&self.shutdown,
+							&self.reload,
+							&self.filters,
 							self.fetch.clone(),
 						).to_delegate(),
 					);
@@ -435,7 +512,7 @@ impl FullDependencies {
 						).to_delegate(),
 					);
 				}
-				Api::Traces => handler.extend_with(TracesClient::new(&self.client).to_delegate()),
+				Api::Traces => handler.extend_with(TracesClient::new(&self.client, self.request_pools.traces.clone()).to_delegate()),
 				Api::Rpc => {
 					let modules = to_modules(&apis);
 					handler.extend_with(RpcClient::new(modules).to_delegate());
@@ -450,6 +527,9 @@ impl FullDependencies {
 							.to_delegate(),
 					);
 				}
+				Api::Shh => {
+					handler.extend_with(ShhClient::new(self.gossip.clone()).to_delegate());
+				}
 				Api::Deprecated => {},
 			}
 		}
@@ -487,6 +567,7 @@ pub struct LightDependencies<T> {
 	pub sync: Arc<LightSync>,
 	pub net: Arc<dyn ManageNetwork>,
 	pub accounts: Arc<AccountProvider>,
+	pub remote_signer: Option<Arc<RemoteSigner>>,
 	pub logger: Arc<RotatingLogger>,
 	pub settings: Arc<NetworkSettings>,
 	pub on_demand: Arc<::light::on_demand::OnDemand>,
@@ -499,7 +580,9 @@ pub struct LightDependencies<T> {
 	pub executor: Executor,
 	pub private_tx_service: Option<Arc<PrivateTransactionManager>>,
 	pub gas_price_percentile: usize,
-	pub poll_lifetime: u32,
+	pub poll_limits: PollLimits,
+	pub shutdown: Arc<dyn Fn() + Send + Sync>,
+	pub reload: Arc<dyn Fn(bool) -> Result<Vec<String>, String> + Send + Sync>,
 }
 
 impl<C: LightChainClient + 'static> LightDependencies<C> {
@@ -522,14 +605,34 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 			))),
 			self.gas_price_percentile,
 		);
-		let account_signer = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
-		let accounts = account_utils::accounts_list(self.accounts.clone());
+		let (account_signer, accounts) = match self.remote_signer {
+			Some(ref signer) => (signer.clone() as Arc<dyn dispatch::Accounts>, account_utils::remote_accounts_list(signer.clone())),
+			None => (Arc::new(dispatch::Signer::new(self.accounts.clone())) as _, account_utils::accounts_list(self.accounts.clone())),
+		};
 
 		for api in apis {
 			match *api {
 				Api::Debug => {
 					warn!(target: "rpc", "Debug API is not available in light client mode.")
 				}
+				Api::Clique => {
+					warn!(target: "rpc", "Clique API is not available in light client mode.")
+				}
+				Api::Bundle => {
+					warn!(target: "rpc", "Bundle API is not available in light client mode.")
+				}
+				Api::EngineApi => {
+					warn!(target: "rpc", "Engine API is not available in light client mode.")
+				}
+				Api::Evm => {
+					warn!(target: "rpc", "Evm API is not available in light client mode.")
+				}
+				Api::Miner => {
+					warn!(target: "rpc", "Miner API is not available in light client mode.")
+				}
+				Api::Mining => {
+					warn!(target: "rpc", "Mining API is not available in light client mode.")
+				}
 				Api::Web3 => {
 					handler.extend_with(Web3Client::default().to_delegate());
 				}
@@ -545,7 +648,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						accounts.clone(),
 						self.cache.clone(),
 						self.gas_price_percentile,
-						self.poll_lifetime,
+						self.poll_limits,
 					);
 					handler.extend_with(Eth::to_delegate(client.clone()));
 
@@ -599,7 +702,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 							dispatcher.clone(),
 							self.geth_compatibility,
 							self.experimental_rpcs,
-						).to_delegate(),
+						).with_remote_signer(self.remote_signer.clone().map(|signer| signer as _)).to_delegate(),
 					);
 				}
 				Api::Signer => {
@@ -653,8 +756,14 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 					handler.extend_with(ParityAccounts::to_delegate(ParityAccountsClient::new(&self.accounts)));
 				}
 				Api::ParitySet => handler.extend_with(
-					light::ParitySetClient::new(self.client.clone(), self.sync.clone(), self.fetch.clone())
-						.to_delegate(),
+					light::ParitySetClient::new(
+						self.client.clone(),
+						self.sync.clone(),
+						self.logger.clone(),
+						self.shutdown.clone(),
+						self.reload.clone(),
+						self.fetch.clone(),
+					).to_delegate(),
 				),
 				Api::Traces => handler.extend_with(light::TracesClient.to_delegate()),
 				Api::Rpc => {
@@ -671,6 +780,9 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 						handler.extend_with(PrivateClient::new(private_tx_service).to_delegate());
 					}
 				}
+				Api::Shh => {
+					warn!(target: "rpc", "Shh API is not available in light client mode.")
+				}
 				Api::Deprecated => {},
 			}
 		}
@@ -732,6 +844,10 @@ impl ApiSet {
 			}
 			ApiSet::All => {
 				public_list.insert(Api::Debug);
+				public_list.insert(Api::Evm);
+				public_list.insert(Api::Miner);
+				public_list.insert(Api::Mining);
+				public_list.insert(Api::Clique);
 				public_list.insert(Api::Traces);
 				public_list.insert(Api::ParityPubSub);
 				public_list.insert(Api::ParityAccounts);
@@ -740,6 +856,7 @@ impl ApiSet {
 				public_list.insert(Api::Personal);
 				public_list.insert(Api::SecretStore);
 				public_list.insert(Api::ParityTransactionsPool);
+				public_list.insert(Api::Shh);
 				public_list
 			}
 			ApiSet::PubSub => [
@@ -764,6 +881,12 @@ mod test {
 	#[test]
 	fn test_api_parsing() {
 		assert_eq!(Api::Debug, "debug".parse().unwrap());
+		assert_eq!(Api::Evm, "evm".parse().unwrap());
+		assert_eq!(Api::Miner, "miner".parse().unwrap());
+		assert_eq!(Api::Mining, "mining".parse().unwrap());
+		assert_eq!(Api::Clique, "clique".parse().unwrap());
+		assert_eq!(Api::Bundle, "bundle".parse().unwrap());
+		assert_eq!(Api::EngineApi, "engine".parse().unwrap());
 		assert_eq!(Api::Web3, "web3".parse().unwrap());
 		assert_eq!(Api::Net, "net".parse().unwrap());
 		assert_eq!(Api::Eth, "eth".parse().unwrap());
@@ -778,6 +901,8 @@ mod test {
 		assert_eq!(Api::SecretStore, "secretstore".parse().unwrap());
 		assert_eq!(Api::Private, "private".parse().unwrap());
 		assert_eq!(Api::ParityTransactionsPool, "parity_transactions_pool".parse().unwrap());
+		assert_eq!(Api::Shh, "shh".parse().unwrap());
+		assert_eq!(Api::Deprecated, "shh_pubsub".parse().unwrap());
 		assert!("rp".parse::<Api>().is_err());
 	}
 
@@ -855,7 +980,12 @@ mod test {
 					Api::Personal,
 					Api::Private,
 					Api::Debug,
+					Api::Evm,
+					Api::Miner,
+					Api::Mining,
+					Api::Clique,
 					Api::ParityTransactionsPool,
+					Api::Shh,
 				].into_iter()
 				.collect()
 			)
@@ -882,7 +1012,12 @@ mod test {
 					Api::Signer,
 					Api::Private,
 					Api::Debug,
+					Api::Evm,
+					Api::Miner,
+					Api::Mining,
+					Api::Clique,
 					Api::ParityTransactionsPool,
+					Api::Shh,
 				].into_iter()
 				.collect()
 			)
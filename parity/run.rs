@@ -15,36 +15,47 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::any::Any;
+use std::env;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::{Arc, Weak, atomic};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 use std::thread;
 
 use ansi_term::Colour;
-use client_traits::{BlockInfo, BlockChainClient};
-use ethcore::client::{Client, DatabaseCompactionProfile};
+use client_traits::{BlockInfo, BlockChainClient, ChainInfo};
+use engine::sealing_journal::SealingJournal;
+use ethcore::client::{Client, DatabaseCompactionProfile, RewriteWindow, StateCacheRatios, StateFork, UnclePolicy};
 use ethcore::miner::{self, stratum, Miner, MinerService, MinerOptions};
 use snapshot::{self, SnapshotConfiguration};
-use spec::SpecParams;
+use spec::{EthashConfig, SpecParams};
 use verification::queue::VerifierSettings;
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
 use ethcore_service::ClientService;
+use ethereum_types::H256;
 use futures::Stream;
 use hash_fetch::{self, fetch};
 use informant::{Informant, LightNodeInformantData, FullNodeInformantData};
 use journaldb::Algorithm;
-use light::Cache as LightDataCache;
+use light::{ChtStore, Cache as LightDataCache};
+use light::provider::{Provider, StoredChtProvider};
 use miner::external::ExternalMiner;
+use miner::pool::TxStatus;
 use miner::work_notify::WorkPoster;
-use node_filter::NodeFilter;
+use node_filter::{NodeFilter, NodePermissions};
 use parity_runtime::Runtime;
-use sync::{self, SyncConfig, PrivateTxHandler};
+use stats::PrometheusMetrics;
+use sync::{self, SyncConfig, PrivateTxHandler, ManageNetwork};
+use gossip::{GossipConfig, GossipProvider, GossipService};
 use types::{
 	client_types::Mode,
 	engines::OptimizeFor,
 	snapshot::Snapshotting,
 };
 use parity_rpc::{
-	Origin, Metadata, NetworkSettings, informant, PubSubSession, FutureResult, FutureResponse, FutureOutput
+	Origin, Metadata, NetworkSettings, informant, response_cache, ResponseCache, InstalledFilters, PollLimits,
+	PubSubSession, FutureResult, FutureResponse, FutureOutput
 };
 use updater::{UpdateFilter, UpdatePolicy, Updater};
 use parity_version::version;
@@ -57,8 +68,18 @@ use account_utils;
 use helpers::{to_client_config, execute_upgrades, passwords_from_files};
 use dir::{Directories, DatabaseDirectories};
 use cache::CacheConfig;
+use cht;
 use user_defaults::UserDefaults;
 use jsonrpc_core;
+use ipfs::{self, IpfsConfiguration};
+#[cfg(unix)]
+use local_sync;
+use metrics::{self, MetricsConfiguration, SyncMetrics};
+use otlp::{self, OtlpConfiguration};
+use webhooks::{self, WebhookConfiguration};
+use reload::{self, ReloadableConfig, Reloader};
+use profile::{self, ProfileConfiguration};
+use shutdown::{ShutdownConfiguration, ShutdownPhase};
 use modules;
 use rpc;
 use rpc_apis;
@@ -66,6 +87,7 @@ use secretstore;
 use signer;
 use db;
 use registrar::RegistrarClient;
+use state_fork::{self, StateForkConfig};
 
 // How often we attempt to take a snapshot: only snapshot on blocknumbers that are multiples of this.
 const SNAPSHOT_PERIOD: u64 = 5000;
@@ -91,16 +113,34 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub pruning_checkpoints: u64,
+	pub pruning_checkpoints_kept: usize,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
 	pub miner_options: MinerOptions,
 	pub gas_price_percentile: usize,
 	pub poll_lifetime: u32,
+	pub poll_max_per_origin: Option<usize>,
+	pub poll_max_total: usize,
+	pub poll_persist: bool,
+	pub request_pools: rpc::RequestPoolsConfiguration,
+	pub rpc_dedup_ttl: Option<Duration>,
+	pub rpc_call_cache: usize,
 	pub ws_conf: rpc::WsConfiguration,
 	pub http_conf: rpc::HttpConfiguration,
 	pub ipc_conf: rpc::IpcConfiguration,
+	pub engine_api_conf: rpc::EngineApiConfiguration,
+	pub metrics_conf: MetricsConfiguration,
+	pub ipfs_conf: IpfsConfiguration,
+	pub otlp_conf: OtlpConfiguration,
+	pub webhook_conf: WebhookConfiguration,
+	pub shutdown_conf: ShutdownConfiguration,
+	pub reload_conf: ReloadableConfig,
+	pub profile: Option<ProfileConfiguration>,
 	pub net_conf: sync::NetworkConfiguration,
+	pub node_permissions: NodePermissions,
+	pub local_sync_socket: Option<String>,
 	pub network_id: Option<u64>,
 	pub warp_sync: bool,
 	pub warp_barrier: Option<u64>,
@@ -112,6 +152,15 @@ pub struct RunCmd {
 	pub tracing: Switch,
 	pub fat_db: Switch,
 	pub compaction: DatabaseCompactionProfile,
+	pub db_compression: bool,
+	pub db_statistics: bool,
+	pub db_rewrite_window: Option<RewriteWindow>,
+	pub state_cache_ratios: StateCacheRatios,
+	pub state_prefetch: bool,
+	pub commit_pipeline: bool,
+	pub commit_batch_blocks: usize,
+	pub uncle_policy: UnclePolicy,
+	pub root_mismatch_dumps: Option<String>,
 	pub geth_compatibility: bool,
 	pub experimental_rpcs: bool,
 	pub net_settings: NetworkSettings,
@@ -122,14 +171,19 @@ pub struct RunCmd {
 	pub name: String,
 	pub custom_bootnodes: bool,
 	pub stratum: Option<stratum::Options>,
+	pub gossip: Option<GossipConfig>,
 	pub snapshot_conf: SnapshotConfiguration,
 	pub check_seal: bool,
 	pub allow_missing_blocks: bool,
+	pub reconstruct_logs: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
+	pub ethash_conf: EthashConfig,
 	pub serve_light: bool,
 	pub light: bool,
 	pub no_persistent_txqueue: bool,
+	pub local_transactions_rebroadcast: Option<Duration>,
+	pub tx_filter_file: Option<String>,
 	pub no_hardcoded_sync: bool,
 	pub max_round_blocks_to_import: usize,
 	pub on_demand_response_time_window: Option<u64>,
@@ -137,7 +191,10 @@ pub struct RunCmd {
 	pub on_demand_request_backoff_max: Option<u64>,
 	pub on_demand_request_backoff_rounds_max: Option<usize>,
 	pub on_demand_request_consecutive_failures: Option<usize>,
+	pub on_demand_historical_fan_out: Option<usize>,
 	pub sync_until: Option<u64>,
+	pub max_reorg_depth: Option<u64>,
+	pub state_fork: Option<StateForkConfig>,
 }
 
 // node info fetcher for the local store.
@@ -145,6 +202,36 @@ struct FullNodeInfo {
 	miner: Option<Arc<Miner>>, // TODO: only TXQ needed, just use that after decoupling.
 }
 
+impl RunCmd {
+	/// Ports the node listens on, with the option setting each of them.
+	pub fn listening_ports(&self) -> Vec<(&'static str, u16)> {
+		let p2p_port = self.net_conf.listen_address.as_ref()
+			.and_then(|addr| addr.parse::<SocketAddr>().ok())
+			.map(|addr| addr.port());
+		let services = vec![
+			("--port", p2p_port),
+			("--jsonrpc-port", Some(self.http_conf.port).filter(|_| self.http_conf.enabled)),
+			("--ws-port", Some(self.ws_conf.port).filter(|_| self.ws_conf.enabled)),
+			("--engine-api-port", Some(self.engine_api_conf.port).filter(|_| self.engine_api_conf.enabled)),
+			("--metrics-port", Some(self.metrics_conf.port).filter(|_| self.metrics_conf.enabled)),
+			("--ipfs-api-port", Some(self.ipfs_conf.port).filter(|_| self.ipfs_conf.enabled)),
+			("--stratum-port", self.stratum.as_ref().map(|s| s.port)),
+			("--gossip-port", self.gossip.as_ref().and_then(GossipConfig::listen_port)),
+			("--secretstore-port", Some(self.secretstore_conf.port).filter(|_| self.secretstore_conf.enabled)),
+			("--secretstore-http-port", Some(self.secretstore_conf.http_port).filter(|_| self.secretstore_conf.enabled && self.secretstore_conf.http_enabled)),
+		];
+
+		services.into_iter()
+			.filter_map(|(option, port)| port.map(|port| (option, port)))
+			.collect()
+	}
+
+	/// IPC socket of the node, if enabled.
+	pub fn ipc_path(&self) -> Option<&str> {
+		Some(self.ipc_conf.socket_addr.as_str()).filter(|_| self.ipc_conf.enabled)
+	}
+}
+
 impl ::local_store::NodeInfo for FullNodeInfo {
 	fn pending_transactions(&self) -> Vec<::types::transaction::PendingTransaction> {
 		let miner = match self.miner.as_ref() {
@@ -162,18 +249,55 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 	}
 }
 
+// append lifecycle changes of local transactions to the journal of the local store.
+fn journal_transactions(store: &::local_store::LocalDataStore<FullNodeInfo>, miner: &Miner, statuses: &[(H256, TxStatus)]) {
+	let journal = match store.journal() {
+		Some(journal) => journal,
+		None => return,
+	};
+
+	let mut submissions = None;
+	for &(ref hash, status) in statuses {
+		let result = match status {
+			TxStatus::Added => {
+				let submissions = submissions.get_or_insert_with(|| miner.local_transaction_submissions());
+				match (submissions.get(hash), miner.transaction(hash)) {
+					(Some(submission), Some(tx)) => journal.submitted(::local_store::JournalEntry {
+						transaction: tx.pending().clone(),
+						submitted_at: submission.submitted_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+						resubmissions: submission.resubmissions,
+					}),
+					_ => Ok(()),
+				}
+			},
+			// dropped transactions are resubmitted after a restart, up to `MAX_RESUBMISSIONS` times.
+			TxStatus::Dropped => Ok(()),
+			_ => journal.finalized(hash),
+		};
+
+		if let Err(e) = result {
+			warn!("Error writing local transactions journal: {}", e);
+		}
+	}
+}
+
 type LightClient = ::light::client::Client<::light_helpers::EpochFetch>;
 
 // helper for light execution.
-fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq: Cr) -> Result<RunningClient, String>
-	where Cr: Fn(String) + 'static + Send
+fn execute_light_impl<Cr, Sr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq: Cr, on_shutdown_rq: Sr) -> Result<RunningClient, String>
+	where
+		Cr: Fn(String) + 'static + Send,
+		Sr: Fn() + 'static + Send + Sync
 {
 	use light::client as light_client;
 	use sync::{LightSyncParams, LightSync, ManageNetwork};
 	use parking_lot::{Mutex, RwLock};
 
 	// load spec
-	let spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
+	let spec = cmd.spec.spec(SpecParams {
+		ethash_config: cmd.ethash_conf.clone(),
+		..SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory)
+	})?;
 
 	// load genesis hash
 	let genesis_hash = spec.genesis_header().hash();
@@ -241,7 +365,8 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 			request_backoff_start,
 			request_backoff_max,
 			cmd.on_demand_request_backoff_rounds_max.unwrap_or(::light::on_demand::DEFAULT_MAX_REQUEST_BACKOFF_ROUNDS),
-			cmd.on_demand_request_consecutive_failures.unwrap_or(::light::on_demand::DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS)
+			cmd.on_demand_request_consecutive_failures.unwrap_or(::light::on_demand::DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS),
+			cmd.on_demand_historical_fan_out.unwrap_or(::light::on_demand::DEFAULT_HISTORICAL_FAN_OUT),
 		)
 	});
 
@@ -254,7 +379,7 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	// initialize database.
 	let db = db::open_db_light(
 		&db_dirs.client_path(algorithm).to_str().expect("DB path could not be converted to string."),
-		&cmd.cache_config,
+		cmd.cache_config.blockchain() as usize,
 		&cmd.compaction,
 	).map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -294,6 +419,7 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	// fetch service
 	let fetch = fetch::Client::new(FETCH_LIGHT_NUM_DNS_THREADS).map_err(|e| format!("Error starting fetch client: {:?}", e))?;
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
+	let remote_signer = account_utils::remote_signer(cmd.acc_conf.remote_signer.as_ref(), fetch.clone())?;
 
 	// prepare account provider
 	let account_provider = Arc::new(account_utils::prepare_account_provider(&cmd.spec, &cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
@@ -302,6 +428,13 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	// the dapps server
 	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config));
 
+	// export tracing spans of RPC handling
+	let otlp_service = otlp::start_otlp_exporter(cmd.otlp_conf.clone(), fetch.clone())?;
+
+	// apply configuration changes on SIGHUP or RPC request
+	let reloader = Arc::new(Reloader::new(env::args().collect(), cmd.reload_conf, logger.clone(), light_sync.clone(), None, fetch.clone(), runtime.executor()));
+	let reload_service = reload::start_reload_service(reloader.clone())?;
+
 	// start RPCs
 	let deps_for_rpc_apis = Arc::new(rpc_apis::LightDependencies {
 		signer_service,
@@ -309,6 +442,7 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		sync: light_sync.clone(),
 		net: light_sync.clone(),
 		accounts: account_provider,
+		remote_signer,
 		logger,
 		settings: Arc::new(cmd.net_settings),
 		on_demand,
@@ -321,13 +455,21 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		executor: runtime.executor(),
 		private_tx_service: None, //TODO: add this to client.
 		gas_price_percentile: cmd.gas_price_percentile,
-		poll_lifetime: cmd.poll_lifetime
+		poll_limits: PollLimits {
+			lifetime: cmd.poll_lifetime,
+			max_per_origin: cmd.poll_max_per_origin,
+			max_total: cmd.poll_max_total,
+		},
+		shutdown: Arc::new(on_shutdown_rq),
+		reload: Arc::new(move |dry_run| reloader.reload(dry_run)),
 	});
 
 	let dependencies = rpc::Dependencies {
 		apis: deps_for_rpc_apis.clone(),
 		executor: runtime.executor(),
 		stats: rpc_stats.clone(),
+		tracer: otlp_service.as_ref().map(|s| s.tracer()).unwrap_or_default(),
+		response_cache: None,
 	};
 
 	// start rpc servers
@@ -357,23 +499,34 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 			rpc: rpc_direct,
 			informant,
 			client,
-			keep_alive: Box::new((service, ws_server, http_server, ipc_server, runtime)),
+			rpc_servers: Box::new((ws_server, http_server, ipc_server)),
+			shutdown_conf: cmd.shutdown_conf,
+			keep_alive: Box::new((service, otlp_service, reload_service, runtime)),
 		}
 	})
 }
 
-fn execute_impl<Cr, Rr>(
+fn execute_impl<Cr, Rr, Sr>(
 	cmd: RunCmd,
 	logger: Arc<RotatingLogger>,
 	on_client_rq: Cr,
-	on_updater_rq: Rr
+	on_updater_rq: Rr,
+	on_shutdown_rq: Sr,
 ) -> Result<RunningClient, String>
 	where
 		Cr: Fn(String) + 'static + Send,
-		Rr: Fn() + 'static + Send
+		Rr: Fn() + 'static + Send,
+		Sr: Fn() + 'static + Send + Sync
 {
 	// load spec
-	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let spec = cmd.spec.spec(SpecParams {
+		ethash_config: cmd.ethash_conf.clone(),
+		..SpecParams::from(&cmd.dirs.cache)
+	})?;
+
+	if cmd.state_fork.is_some() {
+		state_fork::ensure_supported(&spec)?;
+	}
 
 	// load genesis hash
 	let genesis_hash = spec.genesis_header().hash();
@@ -449,6 +602,10 @@ fn execute_impl<Cr, Rr>(
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	let mut engine_api_conf = cmd.engine_api_conf.clone();
+	// the consensus client drives the chain once the terminal total difficulty is configured
+	engine_api_conf.enabled |= spec.engine.params().terminal_total_difficulty.is_some();
+
 	let snapshot_supported =
 		if let Snapshotting::Unsupported = spec.engine.snapshot_mode() {
 			false
@@ -477,8 +634,10 @@ fn execute_impl<Cr, Rr>(
 	};
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 	sync_config.serve_light = cmd.serve_light;
+	sync_config.local_transactions_rebroadcast = cmd.local_transactions_rebroadcast;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
+	let remote_signer_conf = cmd.acc_conf.remote_signer.clone();
 
 	// prepare account provider
 	let account_provider = Arc::new(account_utils::prepare_account_provider(&cmd.spec, &cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
@@ -489,6 +648,7 @@ fn execute_impl<Cr, Rr>(
 
 	// fetch service
 	let fetch = fetch::Client::new(FETCH_FULL_NUM_DNS_THREADS).map_err(|e| format!("Error starting fetch client: {:?}", e))?;
+	let remote_signer = account_utils::remote_signer(remote_signer_conf.as_ref(), fetch.clone())?;
 
 	let txpool_size = cmd.miner_options.pool_limits.max_count;
 
@@ -507,14 +667,32 @@ fn execute_impl<Cr, Rr>(
 	miner.set_gas_range_target(cmd.miner_extras.gas_range_target);
 	miner.set_extra_data(cmd.miner_extras.extra_data);
 
+	if let Some(ref path) = cmd.tx_filter_file {
+		let filter = ::miner::pool::FileFilter::new(path)?;
+		miner.set_transaction_filter(Some(Arc::new(filter)));
+	}
+
 	if !cmd.miner_extras.work_notify.is_empty() {
 		miner.add_work_listener(Box::new(
 			WorkPoster::new(&cmd.miner_extras.work_notify, fetch.clone(), runtime.executor())
 		));
 	}
 
+	// the signers set later on with `parity_setEngineSigner` are journalled as well.
+	if spec.engine.uses_sealing_journal() {
+		let journal_path = cmd.miner_extras.sealing_journal.clone().map(PathBuf::from)
+			.unwrap_or_else(|| cmd.dirs.sealing_journal_path(&spec.data_dir));
+		if let Some(dir) = journal_path.parent() {
+			fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+		}
+		let journal = SealingJournal::open(&journal_path)
+			.map_err(|e| format!("Failed to open the sealing journal at {}: {}", journal_path.display(), e))?;
+		spec.engine.set_sealing_journal(Arc::new(journal));
+	}
+
 	let engine_signer = cmd.miner_extras.engine_signer;
 	if engine_signer != Default::default() {
+		// The remote signer can't sign raw hashes, blocks are always sealed with a local key.
 		if let Some(author) = account_utils::miner_author(&cmd.spec, &cmd.dirs, &account_provider, engine_signer, &passwords)? {
 			miner.set_author(author);
 		}
@@ -543,8 +721,21 @@ fn execute_impl<Cr, Rr>(
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.blockchain.compression = cmd.db_compression;
+	client_config.db_rewrite_window = cmd.db_rewrite_window;
+	client_config.call_cache_size = cmd.rpc_call_cache;
+	client_config.state_cache_ratios = cmd.state_cache_ratios;
+	client_config.state_prefetch = cmd.state_prefetch;
+	client_config.commit_pipeline = cmd.commit_pipeline;
+	client_config.commit_batch_blocks = cmd.commit_batch_blocks;
+	client_config.state_checkpoint_interval = cmd.pruning_checkpoints;
+	client_config.state_checkpoints_kept = cmd.pruning_checkpoints_kept;
+	client_config.uncles = cmd.uncle_policy.clone();
+	client_config.root_mismatch_dumps = cmd.root_mismatch_dumps.clone().map(PathBuf::from);
+	client_config.max_reorg_depth = cmd.max_reorg_depth;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
 	client_config.snapshot = cmd.snapshot_conf.clone();
+	client_config.db_statistics = cmd.db_statistics;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -555,10 +746,20 @@ fn execute_impl<Cr, Rr>(
 	// set network path.
 	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
 
-	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+	let db_metrics = Arc::new(db::DatabaseMetrics::default());
+	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, Some(db_metrics.clone()));
 	let client_db = restoration_db_handler.open(&client_path)
 		.map_err(|e| format!("Failed to open database {:?}", e))?;
 
+	// the fork is read from the first block imported, so it's set before the client starts.
+	if let Some(ref fork) = cmd.state_fork {
+		let key_value = client_db.key_value().clone();
+		let new_chain = state_fork::is_new_chain(&*key_value, &spec.genesis_header().hash())?;
+		let source = state_fork::open(fork, &db_dirs.db_path(algorithm), key_value, new_chain)?;
+		info!("Forking the state of {} at block {}", Colour::White.bold().paint(fork.url.as_str()), source.block());
+		client_config.state_fork = Some(StateFork(Arc::new(source)));
+	}
+
 	let private_tx_signer = account_utils::private_tx_signer(account_provider.clone(), &passwords)?;
 
 	// create client service.
@@ -577,7 +778,10 @@ fn execute_impl<Cr, Rr>(
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
 	let forks = spec.hard_forks.clone();
-	let connection_filter_address = spec.params().node_permission_contract;
+	let node_permissions = NodePermissions {
+		contract: cmd.node_permissions.contract.or(spec.params().node_permission_contract),
+		..cmd.node_permissions.clone()
+	};
 	// drop the spec to free up genesis state.
 	drop(spec);
 
@@ -589,7 +793,10 @@ fn execute_impl<Cr, Rr>(
 	// take handle to private transactions service
 	let private_tx_service = service.private_tx_service();
 	let private_tx_provider = private_tx_service.provider();
-	let connection_filter = connection_filter_address.map(|a| Arc::new(NodeFilter::new(Arc::downgrade(&client) as Weak<dyn BlockChainClient>, a)));
+	let connection_filter = match node_permissions.is_restricted() {
+		true => Some(Arc::new(NodeFilter::new(Arc::downgrade(&client) as Weak<dyn BlockChainClient>, node_permissions)?)),
+		false => None,
+	};
 	let snapshot_service = service.snapshot_service();
 	if let Some(filter) = connection_filter.clone() {
 		service.add_notify(filter.clone());
@@ -604,7 +811,8 @@ fn execute_impl<Cr, Rr>(
 			}
 		};
 
-		let store = ::local_store::create(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO, node_info);
+		let mut store = ::local_store::create(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO, node_info);
+		let journal_path = db_dirs.local_transactions_journal_path();
 
 		if cmd.no_persistent_txqueue {
 			info!("Running without a persistent transaction queue.");
@@ -612,14 +820,40 @@ fn execute_impl<Cr, Rr>(
 			if let Err(e) = store.clear() {
 				warn!("Error clearing persistent transaction queue: {}", e);
 			}
+			if journal_path.exists() {
+				if let Err(e) = fs::remove_file(&journal_path) {
+					warn!("Error removing local transactions journal: {}", e);
+				}
+			}
+		} else {
+			match ::local_store::Journal::open(&journal_path) {
+				Ok(journal) => store = store.with_journal(journal),
+				Err(e) => warn!("Error opening local transactions journal at {}: {}", journal_path.display(), e),
+			}
 		}
 
 		// re-queue pending transactions.
-		match store.pending_transactions() {
-			Ok(pending) => {
-				for pending_tx in pending {
-					if let Err(e) = miner.import_own_transaction(&*client, pending_tx) {
-						warn!("Error importing saved transaction: {}", e)
+		match store.transactions_to_resubmit() {
+			Ok(entries) => {
+				for entry in entries {
+					let submission = ::miner::pool::local_transactions::Submission {
+						submitted_at: UNIX_EPOCH + Duration::from_secs(entry.submitted_at),
+						resubmissions: entry.resubmissions + 1,
+					};
+					let hash = entry.transaction.hash();
+					// the journal listener is not running yet, record the outcome directly.
+					let result = match miner.import_resubmitted_transaction(&*client, entry.transaction.clone(), submission) {
+						Ok(_) => store.journal().map_or(Ok(()), |journal| journal.submitted(::local_store::JournalEntry {
+							resubmissions: submission.resubmissions,
+							..entry
+						})),
+						Err(e) => {
+							warn!("Error importing saved transaction: {}", e);
+							store.journal().map_or(Ok(()), |journal| journal.finalized(&hash))
+						}
+					};
+					if let Err(e) = result {
+						warn!("Error writing local transactions journal: {}", e);
 					}
 				}
 			}
@@ -629,17 +863,45 @@ fn execute_impl<Cr, Rr>(
 		Arc::new(store)
 	};
 
+	// keep the journal in sync with the lifecycle of local transactions.
+	if store.journal().is_some() {
+		let store = store.clone();
+		let journal_miner = miner.clone();
+		runtime.executor().spawn(
+			miner.full_transactions_receiver().for_each(move |statuses| {
+				journal_transactions(&store, &*journal_miner, &statuses);
+				Ok(())
+			})
+		);
+	}
+
 	// register it as an IO service to update periodically.
-	service.register_io_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
+	service.register_io_handler(store.clone()).map_err(|_| "Unable to register local store handler".to_owned())?;
+
+	// relock accounts when their unlock session expires.
+	if let Some(relocker) = account_utils::session_relocker(account_provider.clone()) {
+		service.register_io_handler(relocker).map_err(|_| "Unable to register session relocker".to_owned())?;
+	}
 
 	// create external miner
 	let external_miner = Arc::new(ExternalMiner::default());
 
 	// start stratum
-	if let Some(ref stratum_config) = cmd.stratum {
-		stratum::Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
-			.map_err(|e| format!("Stratum start error: {:?}", e))?;
-	}
+	let stratum_workers = match cmd.stratum {
+		Some(ref stratum_config) => Some(
+			stratum::Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
+				.map_err(|e| format!("Stratum start error: {:?}", e))?
+		),
+		None => None,
+	};
+
+	// start gossip messaging
+	let gossip = match cmd.gossip {
+		Some(ref gossip_config) => Some(
+			Arc::new(GossipService::start(gossip_config.clone()).map_err(|e| format!("Gossip start error: {}", e))?) as Arc<dyn GossipProvider>
+		),
+		None => None,
+	};
 
 	let mut private_tx_sync = None;
 	let mut private_state = None;
@@ -650,6 +912,21 @@ fn execute_impl<Cr, Rr>(
 		private_state = Some(private_tx_provider.private_state_db());
 	}
 
+	// persist CHTs so that header proofs served to light clients don't rebuild them
+	let (cht_store, cht_service) = if cmd.serve_light {
+		let store = ChtStore::open(service.db().key_value().clone(), ::ethcore_db::COL_LIGHT_CHAIN)
+			.map_err(|e| format!("Error opening CHT store: {}", e))?;
+		let store = Arc::new(store);
+		let cht_service = cht::start_cht_service(client.clone(), store.clone())?;
+		(Some(store), Some(cht_service))
+	} else {
+		(None, None)
+	};
+	let light_provider: Arc<dyn Provider> = match cht_store {
+		Some(ref store) => Arc::new(StoredChtProvider::new(client.clone(), store.clone())),
+		None => client.clone(),
+	};
+
 	// create sync object
 	let (sync_provider, manage_network, chain_notify, priority_tasks) = modules::sync(
 		sync_config,
@@ -660,7 +937,7 @@ fn execute_impl<Cr, Rr>(
 		snapshot_service.clone(),
 		private_tx_sync,
 		private_state,
-		client.clone(),
+		light_provider,
 		&cmd.logger_config,
 		connection_filter.clone().map(|f| f as Arc<dyn sync::ConnectionFilter + 'static>),
 	).map_err(|e| format!("Sync error: {}", e))?;
@@ -722,6 +999,24 @@ fn execute_impl<Cr, Rr>(
 	let secret_store = account_provider.clone();
 	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config));
 
+	// apply configuration changes on SIGHUP or RPC request
+	let reloader = Arc::new(Reloader::new(env::args().collect(), cmd.reload_conf, logger.clone(), manage_network.clone(), Some(miner.clone()), fetch.clone(), runtime.executor()));
+	let reload_service = reload::start_reload_service(reloader.clone())?;
+
+	// long running requests (traces, logs, debug traces) get their own threads
+	let (request_pools, request_pool_runtimes) = cmd.request_pools.start();
+
+	// filters are shared by all the servers and may be kept across restarts
+	let poll_limits = PollLimits {
+		lifetime: cmd.poll_lifetime,
+		max_per_origin: cmd.poll_max_per_origin,
+		max_total: cmd.poll_max_total,
+	};
+	let filters = Arc::new(match cmd.poll_persist {
+		true => InstalledFilters::persistent(poll_limits, db_dirs.filters_path()),
+		false => InstalledFilters::new(poll_limits),
+	});
+
 	let deps_for_rpc_apis = Arc::new(rpc_apis::FullDependencies {
 		signer_service: signer_service,
 		snapshot: snapshot_service.clone(),
@@ -729,8 +1024,11 @@ fn execute_impl<Cr, Rr>(
 		sync: sync_provider.clone(),
 		net: manage_network.clone(),
 		accounts: secret_store,
+		remote_signer,
 		miner: miner.clone(),
 		external_miner: external_miner.clone(),
+		stratum_workers,
+		gossip,
 		logger: logger.clone(),
 		settings: Arc::new(cmd.net_settings.clone()),
 		net_service: manage_network.clone(),
@@ -740,17 +1038,35 @@ fn execute_impl<Cr, Rr>(
 		ws_address: cmd.ws_conf.address(),
 		fetch: fetch.clone(),
 		executor: runtime.executor(),
+		request_pools,
 		private_tx_service: Some(private_tx_service.clone()),
 		gas_price_percentile: cmd.gas_price_percentile,
-		poll_lifetime: cmd.poll_lifetime,
+		filters,
 		allow_missing_blocks: cmd.allow_missing_blocks,
+		reconstruct_logs: cmd.reconstruct_logs,
 		no_ancient_blocks: !cmd.download_old_blocks,
+		shutdown: Arc::new(on_shutdown_rq),
+		reload: Arc::new(move |dry_run| reloader.reload(dry_run)),
+		cht_store,
+	});
+
+	// export tracing spans of block import and RPC handling
+	let otlp_service = otlp::start_otlp_exporter(cmd.otlp_conf.clone(), fetch.clone())?;
+	let tracer = otlp_service.as_ref().map(|s| s.tracer()).unwrap_or_default();
+	client.set_tracer(tracer.clone());
+
+	// identical read calls at the same chain head are executed once
+	let response_cache = cmd.rpc_dedup_ttl.map(|ttl| {
+		let client = client.clone();
+		Arc::new(ResponseCache::new(response_cache::DEFAULT_METHODS, ttl, move || client.chain_info().best_block_hash))
 	});
 
 	let dependencies = rpc::Dependencies {
 		apis: deps_for_rpc_apis.clone(),
 		executor: runtime.executor(),
 		stats: rpc_stats.clone(),
+		tracer,
+		response_cache: response_cache.clone(),
 	};
 
 	// start rpc servers
@@ -758,6 +1074,25 @@ fn execute_impl<Cr, Rr>(
 	let ws_server = rpc::new_ws(cmd.ws_conf.clone(), &dependencies)?;
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
 	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), &dependencies)?;
+	let engine_api_server = rpc::new_engine_api(engine_api_conf, &dependencies)?;
+	let mut metrics_sources: Vec<Arc<dyn PrometheusMetrics + Send + Sync>> = vec![
+		client.clone(),
+		Arc::new(SyncMetrics(sync_provider.clone())),
+		rpc_stats.clone(),
+		db_metrics,
+	];
+	if let Some(response_cache) = response_cache {
+		metrics_sources.push(response_cache);
+	}
+	let metrics_server = metrics::start_metrics_server(cmd.metrics_conf.clone(), metrics_sources)?;
+	let ipfs_server = ipfs::start_server(cmd.ipfs_conf.clone(), client.clone())?;
+	#[cfg(unix)]
+	let local_sync_server = local_sync::start_server(cmd.local_sync_socket.clone(), client.clone(), snapshot_service.clone())?;
+	#[cfg(not(unix))]
+	let local_sync_server = match cmd.local_sync_socket {
+		Some(_) => return Err("Local sync is only supported on unix platforms".into()),
+		None => (),
+	};
 
 	// secret store key server
 	let secretstore_deps = secretstore::Dependencies {
@@ -783,6 +1118,11 @@ fn execute_impl<Cr, Rr>(
 	service.add_notify(informant.clone());
 	service.register_io_handler(informant.clone()).map_err(|_| "Unable to register informant handler".to_owned())?;
 
+	let webhook_service = webhooks::start_webhooks(cmd.webhook_conf.clone(), &client, fetch.clone())?;
+	if let Some(ref webhook_service) = webhook_service {
+		service.add_notify(webhook_service.notifier());
+	}
+
 	// save user defaults
 	user_defaults.is_first_launch = false;
 	user_defaults.pruning = algorithm;
@@ -826,7 +1166,11 @@ fn execute_impl<Cr, Rr>(
 			informant,
 			client,
 			client_service: Arc::new(service),
-			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, runtime)),
+			store,
+			network: manage_network,
+			rpc_servers: Box::new((ws_server, http_server, ipc_server, engine_api_server, metrics_server, ipfs_server)),
+			shutdown_conf: cmd.shutdown_conf,
+			keep_alive: Box::new((watcher, updater, otlp_service, webhook_service, reload_service, cht_service, secretstore_key_server, local_sync_server, request_pool_runtimes, runtime)),
 		}
 	})
 }
@@ -844,6 +1188,8 @@ enum RunningClientInner {
 		rpc: jsonrpc_core::MetaIoHandler<Metadata, informant::Middleware<rpc_apis::LightClientNotifier>>,
 		informant: Arc<Informant<LightNodeInformantData>>,
 		client: Arc<LightClient>,
+		rpc_servers: Box<dyn Any>,
+		shutdown_conf: ShutdownConfiguration,
 		keep_alive: Box<dyn Any>,
 	},
 	Full {
@@ -851,6 +1197,10 @@ enum RunningClientInner {
 		informant: Arc<Informant<FullNodeInformantData>>,
 		client: Arc<Client>,
 		client_service: Arc<ClientService>,
+		store: Arc<::local_store::LocalDataStore<FullNodeInfo>>,
+		network: Arc<dyn ManageNetwork>,
+		rpc_servers: Box<dyn Any>,
+		shutdown_conf: ShutdownConfiguration,
 		keep_alive: Box<dyn Any>,
 	},
 }
@@ -875,19 +1225,30 @@ impl RunningClient {
 	/// Shuts down the client.
 	pub fn shutdown(self) {
 		match self.inner {
-			RunningClientInner::Light { rpc, informant, client, keep_alive } => {
+			RunningClientInner::Light { rpc, informant, client, rpc_servers, shutdown_conf, keep_alive } => {
+				let deadline = Instant::now() + shutdown_conf.timeout;
 				// Create a weak reference to the client so that we can wait on shutdown
 				// until it is dropped
 				let weak_client = Arc::downgrade(&client);
+				drop(rpc_servers);
 				drop(rpc);
 				drop(keep_alive);
 				informant.shutdown();
 				drop(informant);
 				drop(client);
-				wait_for_drop(weak_client);
+				wait_for_drop(weak_client, deadline);
 			},
-			RunningClientInner::Full { rpc, informant, client, client_service, keep_alive } => {
+			RunningClientInner::Full { rpc, informant, client, client_service, store, network, rpc_servers, shutdown_conf, keep_alive } => {
 				info!("Finishing work, please wait...");
+				let deadline = Instant::now() + shutdown_conf.timeout;
+				// Stop taking requests, blocks and transactions before draining the client
+				drop(rpc_servers);
+				trace!(target: "shutdown", "RPC servers stopped");
+				network.stop_network();
+				trace!(target: "shutdown", "Network stopped");
+				drain_client(&shutdown_conf, deadline, &client, &store);
+				drop(store);
+				drop(network);
 				// Create a weak reference to the client so that we can wait on shutdown
 				// until it is dropped
 				let weak_client = Arc::downgrade(&client);
@@ -910,7 +1271,7 @@ impl RunningClient {
 				drop(client);
 				trace!(target: "shutdown", "Client dropped");
 				trace!(target: "shutdown", "Waiting for refs to Client to shutdown, strong_count={:?}, weak_count={:?}", weak_client.strong_count(), weak_client.weak_count());
-				wait_for_drop(weak_client);
+				wait_for_drop(weak_client, deadline);
 			}
 		}
 	}
@@ -923,22 +1284,31 @@ impl RunningClient {
 ///
 /// `on_updater_rq` is the action to perform when the updater has a new binary to execute.
 ///
+/// `on_shutdown_rq` is the action to perform when the client receives an RPC request to shut down.
+///
 /// On error, returns what to print on stderr.
-pub fn execute<Cr, Rr>(
+pub fn execute<Cr, Rr, Sr>(
 	cmd: RunCmd,
 	logger: Arc<RotatingLogger>,
 	on_client_rq: Cr,
-	on_updater_rq: Rr
+	on_updater_rq: Rr,
+	on_shutdown_rq: Sr,
 ) -> Result<RunningClient, String>
 	where
 		Cr: Fn(String) + 'static + Send,
-		Rr: Fn() + 'static + Send
+		Rr: Fn() + 'static + Send,
+		Sr: Fn() + 'static + Send + Sync
 {
+	if let Some(ref profile) = cmd.profile {
+		profile::register(profile, &cmd.listening_ports(), cmd.ipc_path())?;
+		info!("Running profile {} with ports shifted by {}", profile.name, profile.ports_shift());
+	}
+
 	if cmd.light {
 		warn!("Light client is deprecated and may be removed in a future release. Please see #11681 for details:\nhttps://github.com/openethereum/openethereum/issues/11681");
-		execute_light_impl(cmd, logger, on_client_rq)
+		execute_light_impl(cmd, logger, on_client_rq, on_shutdown_rq)
 	} else {
-		execute_impl(cmd, logger, on_client_rq, on_updater_rq)
+		execute_impl(cmd, logger, on_client_rq, on_updater_rq, on_shutdown_rq)
 	}
 }
 
@@ -948,15 +1318,40 @@ fn print_running_environment(data_dir: &str, dirs: &Directories, db_dirs: &Datab
 	info!("DB path {}", Colour::White.bold().paint(db_dirs.db_root_path().to_string_lossy().into_owned()));
 }
 
-fn wait_for_drop<T>(w: Weak<T>) {
+/// Run the enabled shutdown phases in order, skipping those still pending at `deadline`.
+fn drain_client(conf: &ShutdownConfiguration, deadline: Instant, client: &Client, store: &::local_store::LocalDataStore<FullNodeInfo>) {
+	for phase in ShutdownPhase::ALL.iter().cloned().filter(|phase| conf.runs(*phase)) {
+		if Instant::now() >= deadline {
+			warn!("Shutdown timeout reached, skipping the {:?} phase and the ones after it.", phase);
+			return;
+		}
+
+		trace!(target: "shutdown", "Running the {:?} shutdown phase", phase);
+		match phase {
+			ShutdownPhase::Queue => if client.drain_queue(deadline) {
+				trace!(target: "shutdown", "Verification queue drained");
+			},
+			ShutdownPhase::Journal => if let Err(e) = store.update() {
+				warn!("Error persisting local transactions: {}", e);
+			},
+			ShutdownPhase::Import => if !client.wait_for_import(deadline) {
+				warn!("Shutdown timeout reached while a block was being imported.");
+			},
+			ShutdownPhase::Fsync => if let Err(e) = client.flush_database() {
+				warn!("Error flushing the database: {}", e);
+			},
+		}
+	}
+}
+
+fn wait_for_drop<T>(w: Weak<T>, deadline: Instant) {
 	const SLEEP_DURATION: Duration = Duration::from_secs(1);
 	const WARN_TIMEOUT: Duration = Duration::from_secs(60);
-	const MAX_TIMEOUT: Duration = Duration::from_secs(300);
 
 	let instant = Instant::now();
 	let mut warned = false;
 
-	while instant.elapsed() < MAX_TIMEOUT {
+	while Instant::now() < deadline {
 		if w.upgrade().is_none() {
 			return;
 		}
@@ -0,0 +1,98 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Configuration of the graceful shutdown sequence.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Optional steps taken after the RPC servers and the network have been stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownPhase {
+	/// Import the blocks left in the verification queue.
+	Queue,
+	/// Persist the local transactions and compact their journal.
+	Journal,
+	/// Wait for the block import in progress to finish.
+	Import,
+	/// Flush the databases to disk.
+	Fsync,
+}
+
+impl ShutdownPhase {
+	/// All phases, in the order they are run.
+	pub const ALL: [ShutdownPhase; 4] = [ShutdownPhase::Queue, ShutdownPhase::Journal, ShutdownPhase::Import, ShutdownPhase::Fsync];
+}
+
+impl FromStr for ShutdownPhase {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"queue" => Ok(ShutdownPhase::Queue),
+			"journal" => Ok(ShutdownPhase::Journal),
+			"import" => Ok(ShutdownPhase::Import),
+			"fsync" => Ok(ShutdownPhase::Fsync),
+			other => Err(format!("Invalid shutdown phase: {}. Expected one of: queue, journal, import, fsync.", other)),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShutdownConfiguration {
+	/// Overall time allowed for the shutdown. Phases still pending at the deadline are skipped.
+	pub timeout: Duration,
+	/// Enabled phases.
+	pub phases: Vec<ShutdownPhase>,
+}
+
+impl Default for ShutdownConfiguration {
+	fn default() -> Self {
+		ShutdownConfiguration {
+			timeout: Duration::from_secs(300),
+			phases: ShutdownPhase::ALL.to_vec(),
+		}
+	}
+}
+
+impl ShutdownConfiguration {
+	/// Returns true if the phase should be run.
+	pub fn runs(&self, phase: ShutdownPhase) -> bool {
+		self.phases.contains(&phase)
+	}
+}
+
+/// Parse a comma-separated list of phases. `none` disables all of them.
+pub fn to_shutdown_phases(s: &str) -> Result<Vec<ShutdownPhase>, String> {
+	match s.trim() {
+		"none" | "" => Ok(Vec::new()),
+		s => s.split(',').map(|phase| phase.trim().parse()).collect(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ShutdownPhase, to_shutdown_phases};
+
+	#[test]
+	fn should_parse_shutdown_phases() {
+		assert_eq!(to_shutdown_phases("queue,journal,import,fsync").unwrap(), ShutdownPhase::ALL.to_vec());
+		assert_eq!(to_shutdown_phases("fsync, journal").unwrap(), vec![ShutdownPhase::Fsync, ShutdownPhase::Journal]);
+		assert_eq!(to_shutdown_phases("none").unwrap(), vec![]);
+		assert!(to_shutdown_phases("queue,rpc").is_err());
+	}
+}
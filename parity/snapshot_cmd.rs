@@ -42,6 +42,7 @@ use dir::Directories;
 use user_defaults::UserDefaults;
 use ethcore_private_tx;
 use db;
+use snapshot_storage::{self, ObjectStorage};
 
 /// Kinds of snapshot commands.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -49,7 +50,9 @@ pub enum Kind {
 	/// Take a snapshot.
 	Take,
 	/// Restore a snapshot.
-	Restore
+	Restore,
+	/// Upload the latest local snapshot to object storage.
+	Upload,
 }
 
 /// Command for snapshot creation or restoration.
@@ -65,6 +68,8 @@ pub struct SnapshotCommand {
 	pub fat_db: Switch,
 	pub compaction: DatabaseCompactionProfile,
 	pub file_path: Option<String>,
+	/// Object storage URL to restore from or upload to.
+	pub url: Option<String>,
 	pub kind: Kind,
 	pub block_at: BlockId,
 	pub max_round_blocks_to_import: usize,
@@ -73,7 +78,7 @@ pub struct SnapshotCommand {
 
 // helper for reading chunks from arbitrary reader and feeding them into the
 // service.
-fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService<Client>>, reader: &R, recover: bool) -> Result<(), String> {
+pub fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService<Client>>, reader: &R, recover: bool) -> Result<(), String> {
 	let manifest = reader.manifest();
 
 	info!("Restoring to block #{} (0x{:?})", manifest.block_number, manifest.block_hash);
@@ -191,7 +196,7 @@ impl SnapshotCommand {
 
 		client_config.snapshot = self.snapshot_conf;
 
-		let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
+		let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config, None);
 		let client_db = restoration_db_handler.open(&client_path)
 			.map_err(|e| format!("Failed to open database {:?}", e))?;
 
@@ -216,6 +221,8 @@ impl SnapshotCommand {
 	/// restore from a snapshot
 	pub fn restore(self) -> Result<(), String> {
 		let file = self.file_path.clone();
+		let url = self.url.clone();
+		let download_dir = Path::new(&self.dirs.cache).join("snapshot-download");
 		let service = self.start_service()?;
 
 		warn!("Snapshot restoration is experimental and the format may be subject to change.");
@@ -223,7 +230,17 @@ impl SnapshotCommand {
 
 		let snapshot = service.snapshot_service();
 
-		if let Some(file) = file {
+		if let Some(url) = url {
+			info!("Attempting to restore from snapshot at '{}'", url);
+
+			let storage = ObjectStorage::new(&url)?;
+			let reader = snapshot_storage::download(&storage, &download_dir)?;
+			restore_using(snapshot, &reader, true)?;
+
+			if let Err(e) = ::std::fs::remove_dir_all(&download_dir) {
+				warn!("Failed to remove the downloaded snapshot at {}: {}", download_dir.display(), e);
+			}
+		} else if let Some(file) = file {
 			info!("Attempting to restore from snapshot at '{}'", file);
 
 			let reader = PackedReader::new(Path::new(&file))
@@ -246,6 +263,27 @@ impl SnapshotCommand {
 		Ok(())
 	}
 
+	/// Upload the given snapshot file, or the latest local snapshot, to object storage.
+	pub fn upload(self) -> Result<(), String> {
+		let file = self.file_path.clone();
+		let storage = ObjectStorage::new(self.url.as_ref().ok_or("No snapshot URL provided.".to_owned())?)?;
+
+		if let Some(file) = file {
+			let reader = PackedReader::new(Path::new(&file))
+				.map_err(|e| format!("Couldn't open snapshot file: {}", e))?
+				.ok_or("Snapshot file has invalid format.".to_owned())?;
+			return snapshot_storage::upload(&reader, &storage);
+		}
+
+		let service = self.start_service()?;
+		let snapshot = service.snapshot_service();
+		let reader = snapshot.reader();
+		match *reader {
+			Some(ref reader) => snapshot_storage::upload(reader, &storage),
+			None => Err("No local snapshot found.".into()),
+		}
+	}
+
 	/// Take a snapshot from the head of the chain.
 	pub fn take_snapshot(self) -> Result<(), String> {
 		let file_path = self.file_path.clone().ok_or("No file path provided.".to_owned())?;
@@ -300,6 +338,7 @@ pub fn execute(cmd: SnapshotCommand) -> Result<String, String> {
 	match cmd.kind {
 		Kind::Take => cmd.take_snapshot()?,
 		Kind::Restore => cmd.restore()?,
+		Kind::Upload => cmd.upload()?,
 	}
 
 	Ok(String::new())
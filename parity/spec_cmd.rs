@@ -0,0 +1,925 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generation, validation and conversion of chain specs, see `openethereum spec`.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use ethereum_types::{Address, H256, U256};
+use rustc_hex::ToHex;
+use serde_json::{self, Map, Value};
+
+const DEFAULT_NAME: &str = "Private chain";
+const DEFAULT_ENGINE: &str = "instant-seal";
+const DEFAULT_CHAIN_ID: u64 = 17;
+const DEFAULT_BLOCK_TIME: u64 = 5;
+const DEFAULT_GAS_LIMIT: u64 = 8_000_000;
+const DEFAULT_FORKS: &str = "istanbul=0";
+const CLIQUE_EPOCH: u64 = 30_000;
+/// Vanity and seal bytes in the extra data of Clique headers.
+const CLIQUE_EXTRA_DATA: usize = 32 + 65;
+
+/// Forks a spec can be generated with, in activation order.
+const FORKS: [&str; 8] = [
+	"homestead", "tangerine-whistle", "spurious-dragon", "byzantium", "constantinople", "petersburg", "istanbul", "muir-glacier",
+];
+/// Geth genesis config keys of `FORKS`.
+const GETH_FORKS: [&str; 8] = [
+	"homesteadBlock", "eip150Block", "eip158Block", "byzantiumBlock", "constantinopleBlock", "petersburgBlock", "istanbulBlock", "muirGlacierBlock",
+];
+/// Geth genesis config keys of forks this version doesn't implement.
+const GETH_UNSUPPORTED: [&str; 8] = [
+	"berlinBlock", "londonBlock", "arrowGlacierBlock", "grayGlacierBlock", "mergeNetsplitBlock", "shanghaiTime", "cancunTime", "terminalTotalDifficulty",
+];
+/// Geth genesis header fields and their name in a chain spec.
+const GETH_HEADER: [(&str, &str); 6] = [
+	("coinbase", "author"), ("timestamp", "timestamp"), ("extraData", "extraData"),
+	("gasLimit", "gasLimit"), ("difficulty", "difficulty"), ("parentHash", "parentHash"),
+];
+/// Transitions enabling the EIPs of a fork, checked for consistency.
+const FORK_TRANSITIONS: [(&str, &[&str]); 4] = [
+	("Spurious Dragon", &["eip160Transition", "eip161abcTransition", "eip161dTransition"]),
+	("Byzantium", &["eip140Transition", "eip211Transition", "eip214Transition", "eip658Transition"]),
+	("Constantinople", &["eip145Transition", "eip1014Transition", "eip1052Transition"]),
+	("Istanbul", &["eip1344Transition", "eip1706Transition", "eip1884Transition", "eip2028Transition"]),
+];
+
+const HOMESTEAD: usize = 0;
+const TANGERINE_WHISTLE: usize = 1;
+const SPURIOUS_DRAGON: usize = 2;
+const BYZANTIUM: usize = 3;
+const CONSTANTINOPLE: usize = 4;
+const PETERSBURG: usize = 5;
+const ISTANBUL: usize = 6;
+const MUIR_GLACIER: usize = 7;
+
+/// Options of a new chain spec. Those not set are prompted for in interactive mode, or take their
+/// default value.
+#[derive(Debug, Default, PartialEq)]
+pub struct NewSpec {
+	pub name: Option<String>,
+	pub engine: Option<String>,
+	pub chain_id: Option<u64>,
+	pub premine: Option<String>,
+	pub validators: Option<String>,
+	pub block_time: Option<u64>,
+	pub gas_limit: Option<u64>,
+	pub forks: Option<String>,
+	pub interactive: bool,
+	pub file: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SpecCmd {
+	/// Generate a new chain spec.
+	New(NewSpec),
+	/// Validate a chain spec.
+	Check(String),
+	/// Convert a Geth genesis file into a chain spec.
+	FromGeth { genesis: String, file: Option<String> },
+	/// Convert a chain spec into a Geth genesis file.
+	ToGeth { spec: String, file: Option<String> },
+}
+
+pub fn execute(cmd: SpecCmd) -> Result<String, String> {
+	match cmd {
+		SpecCmd::New(options) => {
+			let stdin = io::stdin();
+			let options = if options.interactive { prompt_missing(options, &mut stdin.lock())? } else { options };
+			output(&new_spec(&options)?, options.file.as_ref())
+		},
+		SpecCmd::Check(file) => check(&file),
+		SpecCmd::FromGeth { genesis, file } => {
+			let name = Path::new(&genesis).file_stem()
+				.map_or_else(|| "Imported chain".to_owned(), |stem| stem.to_string_lossy().into_owned());
+			output(&from_geth(&read_json(&genesis)?, name)?, file.as_ref())
+		},
+		SpecCmd::ToGeth { spec, file } => output(&to_geth(&read_json(&spec)?)?, file.as_ref()),
+	}
+}
+
+fn read_json(file: &str) -> Result<Value, String> {
+	let content = fs::read_to_string(file).map_err(|e| format!("Could not read {}: {}", file, e))?;
+	serde_json::from_str(&content).map_err(|e| format!("{} is not valid JSON: {}", file, e))
+}
+
+fn output(json: &Value, file: Option<&String>) -> Result<String, String> {
+	let json = serde_json::to_string_pretty(json).expect("serializing a json value can't fail; qed");
+	match file {
+		Some(file) => {
+			fs::write(file, json + "\n").map_err(|e| format!("Could not write {}: {}", file, e))?;
+			Ok(format!("Written to {}", file))
+		},
+		None => Ok(json),
+	}
+}
+
+/// Ask for the options not given on the command line.
+fn prompt_missing<R: BufRead>(mut options: NewSpec, input: &mut R) -> Result<NewSpec, String> {
+	fn ask<R: BufRead>(input: &mut R, question: &str, default: &str) -> Result<Option<String>, String> {
+		eprint!("{} [{}]: ", question, default);
+		let _ = io::stderr().flush();
+		let mut line = String::new();
+		input.read_line(&mut line).map_err(|e| format!("Could not read the answer: {}", e))?;
+		let line = line.trim();
+		Ok(if line.is_empty() { None } else { Some(line.to_owned()) })
+	}
+
+	fn ask_number<R: BufRead>(input: &mut R, question: &str, default: u64) -> Result<Option<u64>, String> {
+		ask(input, question, &default.to_string())?
+			.map(|answer| answer.parse().map_err(|_| format!("{} is not a number", answer)))
+			.transpose()
+	}
+
+	if options.name.is_none() {
+		options.name = ask(input, "Chain name", DEFAULT_NAME)?;
+	}
+	if options.engine.is_none() {
+		options.engine = ask(input, "Engine: ethash, clique, authority-round or instant-seal", DEFAULT_ENGINE)?;
+	}
+	if options.chain_id.is_none() {
+		options.chain_id = ask_number(input, "Chain id", DEFAULT_CHAIN_ID)?;
+	}
+	let with_validators = match options.engine.as_ref().map(String::as_str) {
+		Some("clique") | Some("authority-round") => true,
+		_ => false,
+	};
+	if with_validators && options.validators.is_none() {
+		options.validators = ask(input, "Comma separated validator addresses", "")?;
+	}
+	if with_validators && options.block_time.is_none() {
+		options.block_time = ask_number(input, "Block time in seconds", DEFAULT_BLOCK_TIME)?;
+	}
+	if options.premine.is_none() {
+		options.premine = ask(input, "Comma separated ADDRESS=WEI genesis balances", "")?;
+	}
+	if options.gas_limit.is_none() {
+		options.gas_limit = ask_number(input, "Genesis gas limit", DEFAULT_GAS_LIMIT)?;
+	}
+	if options.forks.is_none() {
+		options.forks = ask(input, &format!("Comma separated FORK=BLOCK activations, forks are {}", FORKS.join(", ")), DEFAULT_FORKS)?;
+	}
+	Ok(options)
+}
+
+/// Consensus engine of a generated spec.
+#[derive(Debug, Clone, PartialEq)]
+enum Engine {
+	Ethash,
+	Clique { period: u64, epoch: u64, signers: Vec<Address> },
+	AuthorityRound { step_duration: u64, validators: Vec<Address> },
+	InstantSeal,
+}
+
+/// Activation blocks of `FORKS`, `None` for those which never activate.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Schedule {
+	forks: [Option<u64>; 8],
+	/// Replay protection, part of Spurious Dragon but scheduled on its own by Geth.
+	eip155: Option<u64>,
+}
+
+impl Schedule {
+	/// Parse `FORK=BLOCK` activations. The forks before a listed one activate along with it, those
+	/// after the last listed one never activate.
+	fn parse(schedule: &str) -> Result<Self, String> {
+		let mut listed = BTreeMap::new();
+		for item in schedule.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+			let mut parts = item.splitn(2, '=');
+			let name = parts.next().unwrap_or_default().trim();
+			let index = FORKS.iter().position(|fork| *fork == name)
+				.ok_or_else(|| format!("Unknown fork {}, expected one of {}", name, FORKS.join(", ")))?;
+			let block = parts.next().and_then(|block| block.trim().parse::<u64>().ok())
+				.ok_or_else(|| format!("Invalid fork activation {}, expected FORK=BLOCK", item))?;
+			listed.insert(index, block);
+		}
+		if listed.is_empty() {
+			return Err("The fork schedule is empty".into());
+		}
+
+		let mut forks = [None; 8];
+		let mut next: Option<(usize, u64)> = None;
+		for index in (0..FORKS.len()).rev() {
+			if let Some(&block) = listed.get(&index) {
+				if let Some((later, later_block)) = next {
+					if block > later_block {
+						return Err(format!("{} at block {} activates after {} at block {}", FORKS[index], block, FORKS[later], later_block));
+					}
+				}
+				next = Some((index, block));
+			}
+			forks[index] = next.map(|(_, block)| block);
+		}
+		Ok(Schedule { forks, eip155: forks[SPURIOUS_DRAGON] })
+	}
+
+	fn at(&self, fork: usize) -> Option<u64> {
+		self.forks[fork]
+	}
+}
+
+/// Everything a chain spec is generated from.
+struct Chain {
+	name: String,
+	engine: Engine,
+	chain_id: u64,
+	schedule: Schedule,
+	genesis: Map<String, Value>,
+	accounts: Map<String, Value>,
+}
+
+fn hex<T: Into<U256>>(value: T) -> Value {
+	Value::String(format!("{:#x}", value.into()))
+}
+
+fn bytes_hex(bytes: &[u8]) -> String {
+	format!("0x{}", bytes.to_hex::<String>())
+}
+
+fn hash_hex(value: U256) -> String {
+	let mut bytes = [0u8; 32];
+	value.to_big_endian(&mut bytes);
+	bytes_hex(&bytes)
+}
+
+/// Number given as a hex or decimal string, or as a JSON number.
+fn uint(value: &Value) -> Option<U256> {
+	match *value {
+		Value::Number(ref number) => number.as_u64().map(Into::into),
+		Value::String(ref s) if s.starts_with("0x") => match &s[2..] {
+			"" => Some(U256::zero()),
+			digits => U256::from_str(digits).ok(),
+		},
+		Value::String(ref s) => U256::from_dec_str(s).ok(),
+		_ => None,
+	}
+}
+
+fn block(value: &Value) -> Option<u64> {
+	uint(value).filter(|number| *number <= u64::max_value().into()).map(|number| number.low_u64())
+}
+
+fn parse_address(address: &str) -> Result<Address, String> {
+	let address = address.trim();
+	Address::from_str(address.trim_start_matches("0x")).map_err(|_| format!("Invalid address {}", address))
+}
+
+fn parse_addresses(addresses: &str) -> Result<Vec<Address>, String> {
+	addresses.split(',').map(str::trim).filter(|address| !address.is_empty()).map(parse_address).collect()
+}
+
+fn parse_premine(premine: &str) -> Result<Vec<(Address, U256)>, String> {
+	premine.split(',').map(str::trim).filter(|item| !item.is_empty()).map(|item| {
+		let mut parts = item.splitn(2, '=');
+		let address = parse_address(parts.next().unwrap_or_default())?;
+		let balance = parts.next()
+			.and_then(|balance| uint(&Value::String(balance.trim().to_owned())))
+			.ok_or_else(|| format!("Invalid premine {}, expected ADDRESS=WEI", item))?;
+		Ok((address, balance))
+	}).collect()
+}
+
+fn set_transitions(params: &mut Map<String, Value>, block: Option<u64>, names: &[&str]) {
+	if let Some(block) = block {
+		for name in names {
+			params.insert((*name).into(), hex(block));
+		}
+	}
+}
+
+fn params_json(chain_id: u64, schedule: &Schedule, maximum_extra_data_size: u64) -> Value {
+	let mut params = Map::new();
+	params.insert("gasLimitBoundDivisor".into(), hex(0x400));
+	params.insert("accountStartNonce".into(), hex(0));
+	params.insert("maximumExtraDataSize".into(), hex(maximum_extra_data_size));
+	params.insert("minGasLimit".into(), hex(0x1388));
+	params.insert("networkID".into(), hex(chain_id));
+	params.insert("chainID".into(), hex(chain_id));
+	set_transitions(&mut params, schedule.at(TANGERINE_WHISTLE), &["eip150Transition"]);
+	set_transitions(&mut params, schedule.at(SPURIOUS_DRAGON), &["eip160Transition", "eip161abcTransition", "eip161dTransition", "maxCodeSizeTransition"]);
+	if schedule.at(SPURIOUS_DRAGON).is_some() {
+		params.insert("maxCodeSize".into(), hex(0x6000));
+	}
+	set_transitions(&mut params, schedule.eip155, &["eip155Transition"]);
+	set_transitions(&mut params, schedule.at(BYZANTIUM), &["eip140Transition", "eip211Transition", "eip214Transition", "eip658Transition"]);
+	set_transitions(&mut params, schedule.at(CONSTANTINOPLE), &["eip145Transition", "eip1014Transition", "eip1052Transition", "eip1283Transition"]);
+	set_transitions(&mut params, schedule.at(PETERSBURG), &["eip1283DisableTransition"]);
+	set_transitions(&mut params, schedule.at(ISTANBUL), &["eip1283ReenableTransition", "eip1344Transition", "eip1706Transition", "eip1884Transition", "eip2028Transition"]);
+	Value::Object(params)
+}
+
+/// Ethash with the block rewards and difficulty bomb delays of the Ethereum main net.
+fn ethash_json(schedule: &Schedule) -> Value {
+	let mut rewards = BTreeMap::new();
+	rewards.insert(0, 5_000_000_000_000_000_000u64);
+	let mut delays = BTreeMap::new();
+	if let Some(block) = schedule.at(BYZANTIUM) {
+		rewards.insert(block, 3_000_000_000_000_000_000);
+		*delays.entry(block).or_insert(0) += 3_000_000u64;
+	}
+	if let Some(block) = schedule.at(CONSTANTINOPLE) {
+		rewards.insert(block, 2_000_000_000_000_000_000);
+		*delays.entry(block).or_insert(0) += 2_000_000;
+	}
+	if let Some(block) = schedule.at(MUIR_GLACIER) {
+		*delays.entry(block).or_insert(0) += 4_000_000;
+	}
+	let to_json = |map: BTreeMap<u64, u64>| map.into_iter().map(|(block, value)| (format!("{:#x}", block), hex(value))).collect::<Map<_, _>>();
+
+	let mut params = Map::new();
+	params.insert("minimumDifficulty".into(), hex(0x20000));
+	params.insert("difficultyBoundDivisor".into(), hex(0x800));
+	params.insert("durationLimit".into(), hex(0xd));
+	params.insert("blockReward".into(), Value::Object(to_json(rewards)));
+	set_transitions(&mut params, schedule.at(HOMESTEAD), &["homesteadTransition"]);
+	set_transitions(&mut params, schedule.at(BYZANTIUM), &["eip100bTransition"]);
+	if !delays.is_empty() {
+		params.insert("difficultyBombDelays".into(), Value::Object(to_json(delays)));
+	}
+	serde_json::json!({ "Ethash": { "params": params } })
+}
+
+fn builtin(name: &str, pricing: Value, activate_at: Option<u64>) -> Value {
+	let mut builtin = serde_json::json!({ "name": name, "pricing": pricing });
+	if let Some(block) = activate_at {
+		builtin["activate_at"] = hex(block);
+	}
+	serde_json::json!({ "balance": "0x1", "builtin": builtin })
+}
+
+/// Pricing of the alt_bn128 precompiles, lowered by Istanbul.
+fn bn128_pricing(byzantium: u64, istanbul: Option<u64>, before: Value, after: Value) -> Value {
+	let mut pricing = Map::new();
+	pricing.insert(format!("{:#x}", byzantium), serde_json::json!({ "price": before }));
+	if let Some(istanbul) = istanbul {
+		pricing.insert(format!("{:#x}", istanbul), serde_json::json!({ "price": after }));
+	}
+	Value::Object(pricing)
+}
+
+/// Precompiled contracts activated by the forks of the schedule.
+fn builtins(schedule: &Schedule) -> Map<String, Value> {
+	let linear = |base: u64, word: u64| serde_json::json!({ "linear": { "base": base, "word": word } });
+	let mut builtins = vec![
+		(1, builtin("ecrecover", linear(3000, 0), None)),
+		(2, builtin("sha256", linear(60, 12), None)),
+		(3, builtin("ripemd160", linear(600, 120), None)),
+		(4, builtin("identity", linear(15, 3), None)),
+	];
+	let istanbul = schedule.at(ISTANBUL);
+	if let Some(byzantium) = schedule.at(BYZANTIUM) {
+		let constant = |price: u64| serde_json::json!({ "alt_bn128_const_operations": { "price": price } });
+		let pairing = |base: u64, pair: u64| serde_json::json!({ "alt_bn128_pairing": { "base": base, "pair": pair } });
+		builtins.push((5, builtin("modexp", serde_json::json!({ "modexp": { "divisor": 20 } }), Some(byzantium))));
+		builtins.push((6, builtin("alt_bn128_add", bn128_pricing(byzantium, istanbul, constant(500), constant(150)), None)));
+		builtins.push((7, builtin("alt_bn128_mul", bn128_pricing(byzantium, istanbul, constant(40_000), constant(6000)), None)));
+		builtins.push((8, builtin("alt_bn128_pairing", bn128_pricing(byzantium, istanbul, pairing(100_000, 80_000), pairing(45_000, 34_000)), None)));
+	}
+	if let Some(istanbul) = istanbul {
+		builtins.push((9, builtin("blake2_f", serde_json::json!({ "blake2_f": { "gas_per_round": 1 } }), Some(istanbul))));
+	}
+	builtins.into_iter().map(|(address, account)| (format!("{:#x}", Address::from_low_u64_be(address)), account)).collect()
+}
+
+fn chain_spec(chain: Chain) -> Value {
+	let (engine, maximum_extra_data_size) = match chain.engine {
+		Engine::Ethash => (ethash_json(&chain.schedule), 0x20),
+		Engine::Clique { period, epoch, .. } =>
+			(serde_json::json!({ "clique": { "params": { "period": period, "epoch": epoch } } }), 0xffff),
+		Engine::AuthorityRound { step_duration, ref validators } => {
+			let validators = validators.iter().map(|validator| format!("{:#x}", validator)).collect::<Vec<_>>();
+			(serde_json::json!({ "authorityRound": { "params": { "stepDuration": step_duration, "validators": { "list": validators } } } }), 0x20)
+		},
+		Engine::InstantSeal => (serde_json::json!({ "instantSeal": { "params": {} } }), 0x20),
+	};
+
+	let mut accounts = builtins(&chain.schedule);
+	for (address, account) in chain.accounts {
+		let merged = match (accounts.remove(&address), account) {
+			(Some(Value::Object(mut builtin)), Value::Object(fields)) => {
+				builtin.extend(fields);
+				Value::Object(builtin)
+			},
+			(_, account) => account,
+		};
+		accounts.insert(address, merged);
+	}
+
+	serde_json::json!({
+		"name": chain.name,
+		"engine": engine,
+		"params": params_json(chain.chain_id, &chain.schedule, maximum_extra_data_size),
+		"genesis": chain.genesis,
+		"accounts": accounts,
+	})
+}
+
+fn new_spec(options: &NewSpec) -> Result<Value, String> {
+	let validators = parse_addresses(options.validators.as_ref().map_or("", String::as_str))?;
+	let block_time = options.block_time.unwrap_or(DEFAULT_BLOCK_TIME);
+	let engine = match options.engine.as_ref().map_or(DEFAULT_ENGINE, String::as_str) {
+		"ethash" => Engine::Ethash,
+		"instant-seal" => Engine::InstantSeal,
+		"clique" => Engine::Clique { period: block_time, epoch: CLIQUE_EPOCH, signers: validators.clone() },
+		"authority-round" => Engine::AuthorityRound { step_duration: block_time, validators: validators.clone() },
+		other => return Err(format!("Unknown engine {}, expected one of ethash, clique, authority-round or instant-seal", other)),
+	};
+	match engine {
+		Engine::Clique { .. } | Engine::AuthorityRound { .. } if validators.is_empty() =>
+			return Err("Clique and AuthorityRound chains need at least one validator, set --validators".into()),
+		Engine::Ethash | Engine::InstantSeal if !validators.is_empty() =>
+			return Err("--validators only applies to clique and authority-round chains".into()),
+		_ => {},
+	}
+
+	let zero_hash = format!("{:#x}", H256::zero());
+	let (seal, difficulty, extra_data) = match engine {
+		Engine::Ethash =>
+			(serde_json::json!({ "ethereum": { "nonce": "0x0000000000000042", "mixHash": zero_hash } }), 0x20000, vec![]),
+		Engine::Clique { ref signers, .. } => {
+			let mut extra_data = vec![0u8; 32];
+			for signer in signers {
+				extra_data.extend_from_slice(signer.as_bytes());
+			}
+			extra_data.extend_from_slice(&[0u8; 65]);
+			(serde_json::json!({ "ethereum": { "nonce": "0x0000000000000000", "mixHash": zero_hash } }), 1, extra_data)
+		},
+		Engine::AuthorityRound { .. } =>
+			(serde_json::json!({ "authorityRound": { "step": "0x0", "signature": bytes_hex(&[0u8; 65]) } }), 0x20000, vec![]),
+		Engine::InstantSeal => (serde_json::json!({ "generic": "0x0" }), 0x20000, vec![]),
+	};
+	let mut genesis = Map::new();
+	genesis.insert("seal".into(), seal);
+	genesis.insert("difficulty".into(), hex(difficulty));
+	genesis.insert("author".into(), Value::String(format!("{:#x}", Address::zero())));
+	genesis.insert("timestamp".into(), hex(0));
+	genesis.insert("parentHash".into(), Value::String(zero_hash));
+	genesis.insert("extraData".into(), Value::String(bytes_hex(&extra_data)));
+	genesis.insert("gasLimit".into(), hex(options.gas_limit.unwrap_or(DEFAULT_GAS_LIMIT)));
+
+	let accounts = parse_premine(options.premine.as_ref().map_or("", String::as_str))?
+		.into_iter()
+		.map(|(address, balance)| (format!("{:#x}", address), serde_json::json!({ "balance": hex(balance) })))
+		.collect();
+
+	Ok(chain_spec(Chain {
+		name: options.name.clone().unwrap_or_else(|| DEFAULT_NAME.into()),
+		engine,
+		chain_id: options.chain_id.unwrap_or(DEFAULT_CHAIN_ID),
+		schedule: Schedule::parse(options.forks.as_ref().map_or(DEFAULT_FORKS, String::as_str))?,
+		genesis,
+		accounts,
+	}))
+}
+
+/// Convert a Geth genesis file into a chain spec.
+fn from_geth(genesis: &Value, name: String) -> Result<Value, String> {
+	let config = genesis.get("config").and_then(Value::as_object)
+		.ok_or("The file has no config section, is it a Geth genesis file?")?;
+
+	let mut unsupported = GETH_UNSUPPORTED.iter()
+		.filter(|key| config.get(**key).map_or(false, |value| !value.is_null()))
+		.map(|key| key.to_string())
+		.collect::<Vec<_>>();
+	if config.get("daoForkSupport") == Some(&Value::Bool(true)) {
+		unsupported.push("daoForkSupport".into());
+	}
+	if !unsupported.is_empty() {
+		return Err(format!("{} not supported by this version, remove them from the config to convert the genesis", unsupported.join(", ")));
+	}
+	if genesis.get("number").and_then(uint).map_or(false, |number| !number.is_zero()) {
+		return Err("Only genesis blocks numbered 0 can be converted".into());
+	}
+
+	let chain_id = config.get("chainId").and_then(block).ok_or("config.chainId is missing")?;
+	let mut schedule = Schedule::default();
+	for (index, key) in GETH_FORKS.iter().enumerate() {
+		schedule.forks[index] = config.get(*key).and_then(block);
+	}
+	schedule.eip155 = config.get("eip155Block").and_then(block);
+
+	let engine = match config.get("clique") {
+		Some(clique) if !clique.is_null() => Engine::Clique {
+			period: clique.get("period").and_then(Value::as_u64).unwrap_or(15),
+			epoch: clique.get("epoch").and_then(Value::as_u64).unwrap_or(CLIQUE_EPOCH),
+			// already in the extra data of the genesis
+			signers: vec![],
+		},
+		_ => Engine::Ethash,
+	};
+
+	let mut header = Map::new();
+	for &(geth, spec) in GETH_HEADER.iter() {
+		if let Some(value) = genesis.get(geth) {
+			header.insert(spec.into(), value.clone());
+		}
+	}
+	// Geth defaults
+	header.entry("gasLimit").or_insert_with(|| hex(4_712_388));
+	header.entry("difficulty").or_insert_with(|| hex(0x20000));
+	let nonce = genesis.get("nonce").and_then(block).unwrap_or(0);
+	let mix_hash = genesis.get("mixHash").cloned().unwrap_or_else(|| Value::String(format!("{:#x}", H256::zero())));
+	header.insert("seal".into(), serde_json::json!({ "ethereum": { "nonce": format!("0x{:016x}", nonce), "mixHash": mix_hash } }));
+
+	let mut accounts = Map::new();
+	for (address, account) in genesis.get("alloc").and_then(Value::as_object).into_iter().flatten() {
+		let mut converted = Map::new();
+		for field in &["balance", "nonce"] {
+			if let Some(value) = account.get(*field) {
+				converted.insert((*field).into(), hex(uint(value).ok_or_else(|| format!("Invalid {} of {}", field, address))?));
+			}
+		}
+		for field in &["code", "storage"] {
+			if let Some(value) = account.get(*field) {
+				converted.insert((*field).into(), value.clone());
+			}
+		}
+		accounts.insert(format!("{:#x}", parse_address(address)?), Value::Object(converted));
+	}
+
+	Ok(chain_spec(Chain { name, engine, chain_id, schedule, genesis: header, accounts }))
+}
+
+/// Convert a chain spec into a Geth genesis file.
+fn to_geth(spec: &Value) -> Result<Value, String> {
+	let params = spec.get("params").and_then(Value::as_object).ok_or("The spec has no params section")?;
+	let param = |name: &str| params.get(name).and_then(block);
+	let (engine, engine_params) = spec.get("engine").and_then(Value::as_object).and_then(|engine| engine.iter().next())
+		.ok_or("The spec has no engine")?;
+
+	let mut config = Map::new();
+	let chain_id = param("chainID").or_else(|| param("networkID")).ok_or("params.networkID is missing")?;
+	config.insert("chainId".into(), chain_id.into());
+	match engine.as_str() {
+		"Ethash" => {
+			let homestead = engine_params["params"].get("homesteadTransition").and_then(block).unwrap_or(0);
+			config.insert("homesteadBlock".into(), homestead.into());
+			config.insert("ethash".into(), serde_json::json!({}));
+		},
+		"clique" => {
+			let clique = &engine_params["params"];
+			config.insert("homesteadBlock".into(), 0.into());
+			config.insert("clique".into(), serde_json::json!({
+				"period": clique.get("period").and_then(Value::as_u64).unwrap_or(15),
+				"epoch": clique.get("epoch").and_then(Value::as_u64).unwrap_or(CLIQUE_EPOCH),
+			}));
+		},
+		other => return Err(format!("Geth supports Ethash and Clique chains only, this spec uses {}", other)),
+	}
+	let constantinople = param("eip145Transition");
+	let forks = [
+		("eip150Block", param("eip150Transition")),
+		("eip155Block", param("eip155Transition")),
+		("eip158Block", param("eip161abcTransition")),
+		("byzantiumBlock", param("eip140Transition")),
+		("constantinopleBlock", constantinople),
+		// Geth requires Petersburg to come after Constantinople, which it disables EIP-1283 of
+		("petersburgBlock", param("eip1283DisableTransition").map(|block| cmp::max(block, constantinople.unwrap_or(0)))),
+		("istanbulBlock", param("eip1344Transition")),
+	];
+	for &(key, block) in forks.iter() {
+		if let Some(block) = block {
+			config.insert(key.into(), block.into());
+		}
+	}
+
+	let genesis = spec.get("genesis").and_then(Value::as_object).ok_or("The spec has no genesis section")?;
+	let seal = genesis.get("seal").and_then(|seal| seal.get("ethereum"));
+	let mut result = Map::new();
+	result.insert("config".into(), Value::Object(config));
+	result.insert("nonce".into(), seal.and_then(|seal| seal.get("nonce")).cloned().unwrap_or_else(|| hex(0)));
+	result.insert("mixHash".into(), seal.and_then(|seal| seal.get("mixHash")).cloned()
+		.unwrap_or_else(|| Value::String(format!("{:#x}", H256::zero()))));
+	for &(geth, spec) in GETH_HEADER.iter() {
+		if let Some(value) = genesis.get(spec) {
+			let value = match geth {
+				"timestamp" | "gasLimit" | "difficulty" => hex(uint(value).ok_or_else(|| format!("Invalid genesis.{}", spec))?),
+				_ => value.clone(),
+			};
+			result.insert(geth.into(), value);
+		}
+	}
+	result.insert("number".into(), hex(0));
+	result.insert("gasUsed".into(), hex(0));
+
+	let mut alloc = Map::new();
+	for (address, account) in spec.get("accounts").and_then(Value::as_object).into_iter().flatten() {
+		if account.get("constructor").is_some() {
+			return Err(format!("Account {} is created by a constructor, which Geth genesis files can't express", address));
+		}
+		let mut converted = Map::new();
+		for field in &["balance", "nonce"] {
+			if let Some(value) = account.get(*field) {
+				converted.insert((*field).into(), hex(uint(value).ok_or_else(|| format!("Invalid {} of {}", field, address))?));
+			}
+		}
+		if let Some(code) = account.get("code") {
+			converted.insert("code".into(), code.clone());
+		}
+		if let Some(storage) = account.get("storage").and_then(Value::as_object) {
+			let storage = storage.iter()
+				.map(|(key, value)| match (uint(&Value::String(key.clone())), uint(value)) {
+					(Some(key), Some(value)) => Ok((hash_hex(key), Value::String(hash_hex(value)))),
+					_ => Err(format!("Invalid storage of {}", address)),
+				})
+				.collect::<Result<Map<_, _>, String>>()?;
+			converted.insert("storage".into(), Value::Object(storage));
+		}
+		// builtins without balance are implicit in Geth
+		if converted.is_empty() {
+			continue;
+		}
+		converted.entry("balance").or_insert_with(|| hex(0));
+		alloc.insert(format!("{:#x}", parse_address(address)?), Value::Object(converted));
+	}
+	result.insert("alloc".into(), Value::Object(alloc));
+	Ok(Value::Object(result))
+}
+
+/// Advice for the usual mistakes behind errors of the spec loader.
+fn hint(error: &str) -> &'static str {
+	if error.contains("unknown field") {
+		" Field names are case sensitive, fix or remove the field."
+	} else if error.contains("missing field") {
+		" Add the field to the section at the given position."
+	} else if error.contains("unknown variant") {
+		" Check the name of the engine, seal or validator set."
+	} else {
+		""
+	}
+}
+
+/// Errors and warnings of a spec which loads but is likely not what its author intended.
+fn lint(spec: &Value) -> (Vec<String>, Vec<String>) {
+	let mut errors = vec![];
+	let mut warnings = vec![];
+	let empty = Map::new();
+	let section = |name: &str| spec.get(name).and_then(Value::as_object).unwrap_or(&empty);
+	let params = section("params");
+	let genesis = section("genesis");
+	let param = |name: &str| params.get(name).and_then(uint);
+
+	let mut activations = vec![];
+	if let Some(block) = param("eip150Transition") {
+		activations.push(("Tangerine Whistle", block));
+	}
+	for &(fork, names) in FORK_TRANSITIONS.iter() {
+		let blocks = names.iter().map(|name| (*name, param(name))).collect::<Vec<_>>();
+		let first = match blocks.iter().filter_map(|&(_, block)| block).min() {
+			Some(first) => first,
+			None => continue,
+		};
+		if blocks.iter().any(|&(_, block)| block != Some(first)) {
+			let described = blocks.iter().map(|&(name, block)| match block {
+				Some(block) => format!("{} at {}", name, block),
+				None => format!("{} not set", name),
+			}).collect::<Vec<_>>();
+			warnings.push(format!("{} EIPs activate at different blocks ({}), set them to the same block unless this is intended.", fork, described.join(", ")));
+		}
+		activations.push((fork, first));
+	}
+	for pair in activations.windows(2) {
+		if pair[1].1 < pair[0].1 {
+			warnings.push(format!("{} activates at block {}, before {} at block {}.", pair[1].0, pair[1].1, pair[0].0, pair[0].1));
+		}
+	}
+
+	if let (Some(gas_limit), Some(minimum)) = (genesis.get("gasLimit").and_then(uint), param("minGasLimit")) {
+		if gas_limit < minimum {
+			errors.push(format!("genesis.gasLimit ({}) is below params.minGasLimit ({}), raise it or lower the minimum.", gas_limit, minimum));
+		}
+	}
+
+	if spec.get("engine").and_then(|engine| engine.get("clique")).is_some() {
+		if param("maximumExtraDataSize").map_or(false, |size| size < CLIQUE_EXTRA_DATA.into()) {
+			errors.push(format!("params.maximumExtraDataSize is below the {} bytes of extra data of Clique blocks, set it to \"0xffff\".", CLIQUE_EXTRA_DATA));
+		}
+		let extra_data = genesis.get("extraData").and_then(Value::as_str).map_or(0, |data| data.trim_start_matches("0x").len() / 2);
+		if extra_data < CLIQUE_EXTRA_DATA + 20 || (extra_data - CLIQUE_EXTRA_DATA) % 20 != 0 {
+			errors.push("genesis.extraData must hold 32 vanity bytes, the addresses of the initial signers and 65 zero bytes.".into());
+		}
+	}
+
+	let builtins = section("accounts").values()
+		.filter_map(|account| account.get("builtin")?.get("name")?.as_str())
+		.collect::<Vec<_>>();
+	let precompiles: [(&str, &str, &[&str]); 2] = [
+		("Byzantium", "eip140Transition", &["modexp", "alt_bn128_add", "alt_bn128_mul", "alt_bn128_pairing"]),
+		("Istanbul", "eip1344Transition", &["blake2_f"]),
+	];
+	for &(fork, transition, names) in precompiles.iter() {
+		if let Some(block) = param(transition) {
+			let missing = names.iter().filter(|name| !builtins.contains(*name)).cloned().collect::<Vec<_>>();
+			if !missing.is_empty() {
+				warnings.push(format!("{} activates at block {} but the {} precompiles are missing, contracts calling them will fail.", fork, block, missing.join(", ")));
+			}
+		}
+	}
+
+	(errors, warnings)
+}
+
+/// Validate a chain spec, reporting what to fix.
+fn check(file: &str) -> Result<String, String> {
+	let content = fs::read_to_string(file).map_err(|e| format!("Could not read {}: {}", file, e))?;
+	let json: Value = serde_json::from_str(&content).map_err(|e| format!("{} is not valid JSON: {}", file, e))?;
+
+	let (mut errors, warnings) = lint(&json);
+	let cache_dir = env::temp_dir();
+	let spec_dir = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+	let params = ::spec::SpecParams::from_path(&cache_dir).with_spec_dir(spec_dir);
+	if let Err(e) = ::spec::Spec::load(params, content.as_bytes()) {
+		let e = e.to_string();
+		errors.insert(0, format!("{}{}", e, hint(&e)));
+	}
+
+	let report = |title: &str, problems: &[String]| problems.iter().map(|problem| format!("{}: {}", title, problem)).collect::<Vec<_>>();
+	let mut lines = report("error", &errors);
+	lines.extend(report("warning", &warnings));
+	if errors.is_empty() {
+		lines.push(format!("{} is valid", file));
+		Ok(lines.join("\n"))
+	} else {
+		Err(lines.join("\n"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Cursor;
+	use tempfile::TempDir;
+
+	fn load(spec: &Value) -> ::spec::Spec {
+		let tempdir = TempDir::new().unwrap();
+		::spec::Spec::load(&tempdir.path(), serde_json::to_vec(spec).unwrap().as_slice()).unwrap()
+	}
+
+	#[test]
+	fn generated_specs_load_without_warnings() {
+		let validator = "0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e".to_owned();
+		for (engine, validators) in &[("ethash", None), ("clique", Some(validator.clone())), ("authority-round", Some(validator)), ("instant-seal", None)] {
+			let spec = new_spec(&NewSpec {
+				engine: Some(engine.to_string()),
+				validators: validators.clone(),
+				premine: Some("0x00000000000000000000000000000000000000aa=1000000000000000000".into()),
+				forks: Some("byzantium=0,istanbul=10".into()),
+				..Default::default()
+			}).unwrap();
+
+			assert_eq!(lint(&spec), (vec![], vec![]), "{}", engine);
+			let loaded = load(&spec);
+			assert_eq!(loaded.params().eip140_transition, 0);
+			assert_eq!(loaded.params().eip1344_transition, 10);
+			assert_eq!(spec["accounts"]["0x00000000000000000000000000000000000000aa"]["balance"], "0xde0b6b3a7640000");
+		}
+	}
+
+	#[test]
+	fn clique_signers_go_in_genesis_extra_data() {
+		let spec = new_spec(&NewSpec {
+			engine: Some("clique".into()),
+			validators: Some("0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e".into()),
+			..Default::default()
+		}).unwrap();
+		let extra_data = spec["genesis"]["extraData"].as_str().unwrap();
+		assert_eq!(extra_data.len(), 2 + 2 * (32 + 20 + 65));
+		assert_eq!(&extra_data[66..106], "7d577a597b2742b498cb5cf0c26cdcd726d39e6e");
+
+		assert!(new_spec(&NewSpec { engine: Some("clique".into()), ..Default::default() }).is_err());
+		assert!(new_spec(&NewSpec { engine: Some("pow".into()), ..Default::default() }).is_err());
+	}
+
+	#[test]
+	fn schedule_activates_earlier_forks_with_listed_ones() {
+		let schedule = Schedule::parse("byzantium=10, istanbul=20").unwrap();
+		assert_eq!(schedule.forks, [Some(10), Some(10), Some(10), Some(10), Some(20), Some(20), Some(20), None]);
+		assert_eq!(schedule.eip155, Some(10));
+
+		assert!(Schedule::parse("istanbul=10,byzantium=20").unwrap_err().contains("activates after istanbul"));
+		assert!(Schedule::parse("berlin=0").unwrap_err().contains("Unknown fork"));
+		assert!(Schedule::parse("istanbul").is_err());
+		assert!(Schedule::parse("").is_err());
+	}
+
+	#[test]
+	fn prompts_for_missing_options() {
+		let mut answers = Cursor::new("Testnet\nclique\n0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e\n15\n\n\nbyzantium=0\n");
+		let options = prompt_missing(NewSpec { chain_id: Some(99), ..Default::default() }, &mut answers).unwrap();
+		assert_eq!(options, NewSpec {
+			name: Some("Testnet".into()),
+			engine: Some("clique".into()),
+			chain_id: Some(99),
+			validators: Some("0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e".into()),
+			block_time: Some(15),
+			forks: Some("byzantium=0".into()),
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn converts_geth_genesis_both_ways() {
+		let genesis: Value = serde_json::from_str(r#"{
+			"config": {
+				"chainId": 1337,
+				"homesteadBlock": 0,
+				"eip150Block": 0,
+				"eip155Block": 0,
+				"eip158Block": 0,
+				"byzantiumBlock": 0,
+				"constantinopleBlock": 0,
+				"petersburgBlock": 0,
+				"istanbulBlock": 100,
+				"clique": { "period": 5, "epoch": 30000 }
+			},
+			"nonce": "0x0",
+			"timestamp": "0x5c51a607",
+			"extraData": "0x00000000000000000000000000000000000000000000000000000000000000007d577a597b2742b498cb5cf0c26cdcd726d39e6e0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+			"gasLimit": "0xa00000",
+			"difficulty": "0x1",
+			"mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+			"coinbase": "0x0000000000000000000000000000000000000000",
+			"alloc": {
+				"7d577a597b2742b498cb5cf0c26cdcd726d39e6e": { "balance": "1000000000000000000" },
+				"0x00000000000000000000000000000000000000cc": { "balance": "0x0", "code": "0x6000", "storage": { "0x01": "0x02" } }
+			},
+			"number": "0x0",
+			"gasUsed": "0x0",
+			"parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+		}"#).unwrap();
+
+		let spec = from_geth(&genesis, "test".into()).unwrap();
+		assert_eq!(lint(&spec), (vec![], vec![]));
+		let loaded = load(&spec);
+		assert_eq!(loaded.chain_id(), 1337);
+		assert_eq!(loaded.params().eip1344_transition, 100);
+
+		let converted = to_geth(&spec).unwrap();
+		assert_eq!(converted["config"], genesis["config"]);
+		assert_eq!(converted["alloc"]["0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e"]["balance"], "0xde0b6b3a7640000");
+		assert_eq!(converted["alloc"]["0x00000000000000000000000000000000000000cc"]["storage"][hash_hex(1.into())], hash_hex(2.into()));
+		// precompiles with a balance are kept, like in Geth genesis files of public test nets
+		assert_eq!(converted["alloc"]["0x0000000000000000000000000000000000000001"]["balance"], "0x1");
+		assert_eq!(converted["extraData"], genesis["extraData"]);
+	}
+
+	#[test]
+	fn rejects_unsupported_geth_forks() {
+		let genesis = serde_json::json!({ "config": { "chainId": 1, "berlinBlock": 0, "londonBlock": 0 } });
+		assert!(from_geth(&genesis, "test".into()).unwrap_err().starts_with("berlinBlock, londonBlock not supported"));
+
+		let spec = new_spec(&NewSpec::default()).unwrap();
+		assert!(to_geth(&spec).unwrap_err().contains("instantSeal"));
+	}
+
+	#[test]
+	fn lint_reports_inconsistent_specs() {
+		let mut spec = new_spec(&NewSpec { engine: Some("ethash".into()), ..Default::default() }).unwrap();
+		spec["params"].as_object_mut().unwrap().remove("eip211Transition");
+		spec["params"]["eip1344Transition"] = hex(0x10);
+		spec["params"]["minGasLimit"] = hex(DEFAULT_GAS_LIMIT + 1);
+		spec["accounts"].as_object_mut().unwrap().remove(&format!("{:#x}", Address::from_low_u64_be(9)));
+
+		let (errors, warnings) = lint(&spec);
+		assert_eq!(errors.len(), 1);
+		assert!(errors[0].starts_with("genesis.gasLimit (8000000) is below params.minGasLimit"));
+		assert_eq!(warnings.len(), 3, "{:?}", warnings);
+		assert!(warnings[0].contains("eip211Transition not set"));
+		assert!(warnings[1].contains("eip1344Transition at 16"));
+		assert!(warnings[2].contains("blake2_f precompiles are missing"));
+	}
+
+	#[test]
+	fn check_reports_loader_errors_with_hints() {
+		let tempdir = TempDir::new().unwrap();
+		let file = tempdir.path().join("spec.json");
+		let mut spec = new_spec(&NewSpec::default()).unwrap();
+		fs::write(&file, serde_json::to_string(&spec).unwrap()).unwrap();
+		assert!(check(file.to_str().unwrap()).unwrap().ends_with("is valid"));
+
+		spec["params"]["eip150transition"] = hex(0);
+		fs::write(&file, serde_json::to_string(&spec).unwrap()).unwrap();
+		let error = check(file.to_str().unwrap()).unwrap_err();
+		assert!(error.contains("unknown field `eip150transition`"), "{}", error);
+		assert!(error.contains("Field names are case sensitive"));
+	}
+}
@@ -0,0 +1,416 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dev chains forked off a live network: accounts and storage the local chain never touched are
+//! read on demand over JSON-RPC from the state of the remote network at a pinned block.
+//!
+//! Local blocks are still numbered from the local genesis, so contracts depending on the block
+//! number or on historical block hashes of the remote network may behave differently.
+//!
+//! The values read from the remote state are stored in the database, so each is only requested
+//! once, and the most recently used ones are also kept in memory.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use engine::Engine;
+use ethcore::client::{RemoteAccount, StateForkSource};
+use ethcore_db::{COL_EXTRA, COL_NODE_INFO};
+use ethereum_types::{Address, BigEndianHash, H256, U256};
+use futures::{Future, Stream};
+use hash_fetch::fetch::{self, Fetch};
+use kvdb::{DBTransaction, KeyValueDB};
+use lru_cache::LruCache;
+use parity_rpc::hyper::header::{HeaderValue, CONTENT_TYPE};
+use parking_lot::Mutex;
+use rlp::{Rlp, RlpStream};
+use rustc_hex::FromHex;
+use serde_json::{self, Value};
+use spec::Spec;
+
+/// Name of the file pinning the fork of a database, next to the database itself.
+pub const FORK_FILE: &str = "state_fork.json";
+/// Maximal duration of a single request. Accounts are read while executing transactions, which
+/// wait for the remote node meanwhile.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time during which reads fail at once after a request failed, instead of each waiting for the
+/// remote node again.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+/// Number of accounts kept in memory.
+const ACCOUNT_CACHE_SIZE: usize = 4_096;
+/// Number of storage values kept in memory.
+const STORAGE_CACHE_SIZE: usize = 65_536;
+/// Prefix of the remote accounts stored in the node info column.
+const ACCOUNT_KEY_PREFIX: &[u8] = b"fork-account";
+/// Prefix of the remote storage values stored in the node info column.
+const STORAGE_KEY_PREFIX: &[u8] = b"fork-storage";
+
+/// Remote network given with `--fork-url` and `--fork-block`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateForkConfig {
+	/// JSON-RPC endpoint of a node of the remote network.
+	pub url: String,
+	/// Block the state is forked at, the latest block of the remote node if `None`.
+	pub block: Option<u64>,
+}
+
+/// Fork recorded in a database the first time it is opened.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PinnedFork {
+	url: String,
+	block: u64,
+}
+
+/// Fail unless the chain seals its blocks locally; a forked state can't be verified by peers.
+pub fn ensure_supported(spec: &Spec) -> Result<(), String> {
+	match spec.engine.name() {
+		"InstantSeal" => Ok(()),
+		name => Err(format!("--fork-url requires a chain sealed with InstantSeal, e.g. --chain=dev, {} uses {}", spec.name, name)),
+	}
+}
+
+/// Whether the chain in `db` has no block besides the genesis, before the client is started.
+pub fn is_new_chain(db: &dyn KeyValueDB, genesis_hash: &H256) -> Result<bool, String> {
+	let best = db.get(COL_EXTRA, b"best").map_err(|e| format!("Failed to read the best block: {}", e))?;
+	Ok(best.map_or(true, |best| best == genesis_hash.as_bytes()))
+}
+
+/// Open the fork of the database in `db_path`, pinning it if the database was just created, and
+/// store the remote values in `db`. Once pinned, a database can only be opened with the same
+/// remote URL and block.
+pub fn open(config: &StateForkConfig, db_path: &Path, db: Arc<dyn KeyValueDB>, new_chain: bool) -> Result<RpcForkSource, String> {
+	let path = db_path.join(FORK_FILE);
+	if path.exists() {
+		let file = fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+		let pinned: PinnedFork = serde_json::from_reader(file).map_err(|e| format!("Invalid fork file {}: {}", path.display(), e))?;
+		if pinned.url != config.url || config.block.map_or(false, |block| block != pinned.block) {
+			return Err(format!(
+				"The database was forked off {} at block {}. Use a different --base-path or --db-path to fork another network or block.",
+				pinned.url, pinned.block,
+			));
+		}
+		return RpcForkSource::new(&pinned.url, pinned.block, Some(db));
+	}
+	if !new_chain {
+		return Err("The database already holds blocks, a fork needs an empty database. Use a different --base-path or --db-path.".into());
+	}
+
+	let block = match config.block {
+		Some(block) => block,
+		None => RpcForkSource::latest_block(&config.url)?,
+	};
+	let source = RpcForkSource::new(&config.url, block, Some(db))?;
+	let pinned = PinnedFork { url: config.url.clone(), block };
+	fs::create_dir_all(db_path).map_err(|e| format!("Failed to create {}: {}", db_path.display(), e))?;
+	let json = serde_json::to_vec_pretty(&pinned).map_err(|e| e.to_string())?;
+	fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+	Ok(source)
+}
+
+/// Fork source reading the remote state over JSON-RPC. Values are stored for good, the remote
+/// state at a given block never changing.
+pub struct RpcForkSource {
+	fetch: fetch::Client,
+	url: fetch::Url,
+	block: u64,
+	db: Option<Arc<dyn KeyValueDB>>,
+	accounts: Mutex<LruCache<Address, Option<RemoteAccount>>>,
+	storage: Mutex<LruCache<(Address, H256), H256>>,
+	/// Time of the last failed request.
+	failed_at: Mutex<Option<Instant>>,
+}
+
+impl RpcForkSource {
+	/// Remote state of the node at `url` at the given block, whose values are stored in `db`.
+	pub fn new(url: &str, block: u64, db: Option<Arc<dyn KeyValueDB>>) -> Result<Self, String> {
+		let url = fetch::Url::parse(url).map_err(|e| format!("Invalid fork URL {}: {}", url, e))?;
+		match url.scheme() {
+			"http" | "https" if url.host_str().is_some() => {},
+			_ => return Err(format!("Invalid fork URL {}: expected an http(s) URL", url)),
+		}
+		let fetch = fetch::Client::new(1).map_err(|e| format!("Failed to start the HTTP client: {:?}", e))?;
+
+		Ok(RpcForkSource {
+			fetch,
+			url,
+			block,
+			db,
+			accounts: Mutex::new(LruCache::new(ACCOUNT_CACHE_SIZE)),
+			storage: Mutex::new(LruCache::new(STORAGE_CACHE_SIZE)),
+			failed_at: Mutex::new(None),
+		})
+	}
+
+	/// Latest block of the node at `url`.
+	pub fn latest_block(url: &str) -> Result<u64, String> {
+		let source = Self::new(url, 0, None)?;
+		let mut result = source.call(vec![("eth_blockNumber", vec![])])?;
+		let number: U256 = parse(result.remove(0), "block number")?;
+		if number > U256::from(u64::max_value()) {
+			return Err(format!("Invalid block number {} returned by {}", number, url));
+		}
+		Ok(number.as_u64())
+	}
+
+	/// Block the state is forked at.
+	pub fn block(&self) -> u64 {
+		self.block
+	}
+
+	fn block_param(&self) -> Value {
+		Value::String(format!("0x{:x}", self.block))
+	}
+
+	/// Value stored in the database under `key`.
+	fn stored(&self, key: &[u8]) -> Option<Vec<u8>> {
+		let db = self.db.as_ref()?;
+		match db.get(COL_NODE_INFO, key) {
+			Ok(value) => value,
+			Err(e) => {
+				warn!("Failed to read the forked state from the database: {}", e);
+				None
+			}
+		}
+	}
+
+	fn store(&self, key: &[u8], value: &[u8]) {
+		if let Some(ref db) = self.db {
+			let mut batch = DBTransaction::new();
+			batch.put(COL_NODE_INFO, key, value);
+			if let Err(e) = db.write(batch) {
+				warn!("Failed to store the forked state in the database: {}", e);
+			}
+		}
+	}
+
+	/// Send the given calls in a single batch, returning their results in the same order. Fails at
+	/// once for a while after a request failed.
+	fn call(&self, calls: Vec<(&str, Vec<Value>)>) -> Result<Vec<Value>, String> {
+		if let Some(failed_at) = *self.failed_at.lock() {
+			if failed_at.elapsed() < RETRY_DELAY {
+				return Err(format!("{} is unreachable, retrying in {:?}", self.url, RETRY_DELAY - failed_at.elapsed()));
+			}
+		}
+		let result = self.send(calls);
+		*self.failed_at.lock() = match result {
+			Ok(_) => None,
+			Err(_) => Some(Instant::now()),
+		};
+		result
+	}
+
+	fn send(&self, calls: Vec<(&str, Vec<Value>)>) -> Result<Vec<Value>, String> {
+		let count = calls.len();
+		let batch: Vec<Value> = calls.into_iter().enumerate().map(|(id, (method, params))| serde_json::json!({
+			"jsonrpc": "2.0",
+			"id": id,
+			"method": method,
+			"params": params,
+		})).collect();
+		let body = serde_json::to_vec(&batch).map_err(|e| e.to_string())?;
+
+		let request = fetch::Request::new(self.url.clone(), fetch::Method::POST)
+			.with_header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+			.with_body(body);
+		let abort = fetch::Abort::default().with_max_duration(REQUEST_TIMEOUT);
+		let response = self.fetch.fetch(request, abort).wait().map_err(|e| format!("Request to {} failed: {:?}", self.url, e))?;
+		if !response.status().is_success() {
+			return Err(format!("{} responded with {}", self.url, response.status()));
+		}
+		let body = response.concat2().wait().map_err(|e| format!("Request to {} failed: {:?}", self.url, e))?;
+
+		let responses: Vec<Value> = serde_json::from_slice(&body).map_err(|e| format!("Invalid response from {}: {}", self.url, e))?;
+		let mut results = vec![None; count];
+		for mut response in responses {
+			let id = response["id"].as_u64().map(|id| id as usize).filter(|id| *id < count)
+				.ok_or_else(|| format!("Invalid response from {}: unexpected id {}", self.url, response["id"]))?;
+			if let Some(error) = response.get("error") {
+				return Err(format!("{} returned an error: {}", self.url, error));
+			}
+			results[id] = Some(response["result"].take());
+		}
+		results.into_iter().map(|result| result.ok_or_else(|| format!("Incomplete response from {}", self.url))).collect()
+	}
+}
+
+impl StateForkSource for RpcForkSource {
+	fn account(&self, address: &Address) -> Result<Option<RemoteAccount>, String> {
+		if let Some(account) = self.accounts.lock().get_mut(address) {
+			return Ok(account.clone());
+		}
+		let key = [ACCOUNT_KEY_PREFIX, address.as_bytes()].concat();
+		if let Some(stored) = self.stored(&key) {
+			let account = decode_account(&stored)?;
+			self.accounts.lock().insert(*address, account.clone());
+			return Ok(account);
+		}
+
+		let params = || vec![Value::String(format!("{:?}", address)), self.block_param()];
+		let mut results = self.call(vec![
+			("eth_getBalance", params()),
+			("eth_getTransactionCount", params()),
+			("eth_getCode", params()),
+		])?.into_iter();
+		let balance: U256 = parse(results.next().unwrap_or_default(), "balance")?;
+		let nonce: U256 = parse(results.next().unwrap_or_default(), "nonce")?;
+		let code = match results.next() {
+			Some(Value::String(ref code)) => code.trim_start_matches("0x").from_hex().map_err(|e| format!("Invalid code: {}", e))?,
+			other => return Err(format!("Invalid code: {:?}", other)),
+		};
+
+		// the remote node doesn't tell empty accounts from missing ones, nor does the EVM
+		let account = if balance.is_zero() && nonce.is_zero() && code.is_empty() {
+			None
+		} else {
+			Some(RemoteAccount { balance, nonce, code })
+		};
+		self.store(&key, &encode_account(&account));
+		self.accounts.lock().insert(*address, account.clone());
+		Ok(account)
+	}
+
+	fn storage_at(&self, address: &Address, key: &H256) -> Result<H256, String> {
+		if let Some(value) = self.storage.lock().get_mut(&(*address, *key)) {
+			return Ok(*value);
+		}
+		let db_key = [STORAGE_KEY_PREFIX, address.as_bytes(), key.as_bytes()].concat();
+		if let Some(stored) = self.stored(&db_key) {
+			if stored.len() != 32 {
+				return Err(format!("Invalid stored storage value of {:?}", address));
+			}
+			let value = H256::from_slice(&stored);
+			self.storage.lock().insert((*address, *key), value);
+			return Ok(value);
+		}
+
+		let mut results = self.call(vec![("eth_getStorageAt", vec![
+			Value::String(format!("{:?}", address)),
+			Value::String(format!("{:?}", key)),
+			self.block_param(),
+		])])?;
+		// some nodes return the value without its leading zeroes
+		let value: U256 = parse(results.remove(0), "storage value")?;
+		let value = H256::from_uint(&value);
+		self.store(&db_key, value.as_bytes());
+		self.storage.lock().insert((*address, *key), value);
+		Ok(value)
+	}
+}
+
+/// Encode a remote account, a missing one being empty.
+fn encode_account(account: &Option<RemoteAccount>) -> Vec<u8> {
+	match *account {
+		Some(ref account) => {
+			let mut stream = RlpStream::new_list(3);
+			stream.append(&account.balance).append(&account.nonce).append(&account.code);
+			stream.out()
+		},
+		None => Vec::new(),
+	}
+}
+
+fn decode_account(bytes: &[u8]) -> Result<Option<RemoteAccount>, String> {
+	if bytes.is_empty() {
+		return Ok(None);
+	}
+	let rlp = Rlp::new(bytes);
+	let invalid = |e: ::rlp::DecoderError| format!("Invalid stored account: {}", e);
+	Ok(Some(RemoteAccount {
+		balance: rlp.val_at(0).map_err(invalid)?,
+		nonce: rlp.val_at(1).map_err(invalid)?,
+		code: rlp.val_at(2).map_err(invalid)?,
+	}))
+}
+
+fn parse<T: ::serde::de::DeserializeOwned>(value: Value, what: &str) -> Result<T, String> {
+	serde_json::from_value(value).map_err(|e| format!("Invalid {}: {}", what, e))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+	use std::sync::Arc;
+	use ethcore::client::{RemoteAccount, StateForkSource};
+	use ethereum_types::{Address, H256, U256};
+	use kvdb::KeyValueDB;
+	use kvdb_memorydb;
+	use tempfile::TempDir;
+	use super::{open, RpcForkSource, StateForkConfig, PinnedFork, FORK_FILE};
+
+	fn db() -> Arc<dyn KeyValueDB> {
+		Arc::new(kvdb_memorydb::create(::ethcore_db::NUM_COLUMNS))
+	}
+
+	fn pin(dir: &Path, url: &str, block: u64) {
+		let json = ::serde_json::to_vec(&PinnedFork { url: url.into(), block }).unwrap();
+		::std::fs::write(dir.join(FORK_FILE), json).unwrap();
+	}
+
+	#[test]
+	fn reopens_the_pinned_fork() {
+		let dir = TempDir::new().unwrap();
+		pin(dir.path(), "http://127.0.0.1:8545", 100);
+
+		let config = StateForkConfig { url: "http://127.0.0.1:8545".into(), block: None };
+		assert_eq!(open(&config, dir.path(), db(), false).unwrap().block(), 100);
+		let config = StateForkConfig { url: "http://127.0.0.1:8545".into(), block: Some(100) };
+		assert_eq!(open(&config, dir.path(), db(), false).unwrap().block(), 100);
+	}
+
+	#[test]
+	fn rejects_another_fork_of_a_pinned_database() {
+		let dir = TempDir::new().unwrap();
+		pin(dir.path(), "http://127.0.0.1:8545", 100);
+
+		let config = StateForkConfig { url: "http://127.0.0.1:8545".into(), block: Some(101) };
+		assert!(open(&config, dir.path(), db(), false).is_err());
+		let config = StateForkConfig { url: "http://10.0.0.1:8545".into(), block: None };
+		assert!(open(&config, dir.path(), db(), false).is_err());
+	}
+
+	#[test]
+	fn rejects_forking_a_used_database() {
+		let dir = TempDir::new().unwrap();
+		let config = StateForkConfig { url: "http://127.0.0.1:8545".into(), block: Some(100) };
+		assert!(open(&config, dir.path(), db(), false).is_err());
+		assert!(!dir.path().join(FORK_FILE).exists());
+
+		assert_eq!(open(&config, dir.path(), db(), true).unwrap().block(), 100);
+		assert!(dir.path().join(FORK_FILE).exists());
+	}
+
+	#[test]
+	fn reads_stored_values_without_requests() {
+		let db = db();
+		let address = Address::from_low_u64_be(1);
+		let account = RemoteAccount { balance: U256::from(10), nonce: U256::from(1), code: vec![0x60, 0x00] };
+		let key = H256::from_low_u64_be(2);
+		let value = H256::from_low_u64_be(3);
+		let source = RpcForkSource::new("http://127.0.0.1:1", 100, Some(db.clone())).unwrap();
+		source.store(&[super::ACCOUNT_KEY_PREFIX, address.as_bytes()].concat(), &super::encode_account(&Some(account.clone())));
+		source.store(&[super::ACCOUNT_KEY_PREFIX, Address::from_low_u64_be(4).as_bytes()].concat(), &super::encode_account(&None));
+		source.store(&[super::STORAGE_KEY_PREFIX, address.as_bytes(), key.as_bytes()].concat(), value.as_bytes());
+
+		// nothing listens on the remote URL, the values can only come from the database.
+		let source = RpcForkSource::new("http://127.0.0.1:1", 100, Some(db)).unwrap();
+		assert_eq!(source.account(&address).unwrap(), Some(account));
+		assert_eq!(source.account(&Address::from_low_u64_be(4)).unwrap(), None);
+		assert_eq!(source.storage_at(&address, &key).unwrap(), value);
+		assert!(source.account(&Address::from_low_u64_be(5)).is_err());
+	}
+}
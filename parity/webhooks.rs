@@ -0,0 +1,565 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain events posted as JSON to webhooks.
+
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use client_traits::{BlockChainClient, BlockInfo, ChainInfo, ChainNotify};
+use ethcore::client::Client;
+use ethereum_types::{Address, H256, U64};
+use futures::Future;
+use hash_fetch::fetch::{self, Fetch};
+use parity_crypto::hmac;
+use parity_rpc::hyper::header::{CONTENT_TYPE, HeaderName, HeaderValue};
+use parking_lot::Mutex;
+use rustc_hex::ToHex;
+use serde_json;
+use types::{
+	BlockNumber,
+	chain_notify::NewBlocks,
+	filter::Filter,
+	ids::BlockId,
+	log_entry::LocalizedLogEntry,
+};
+
+/// How often due notifications are sent.
+const SEND_INTERVAL: Duration = Duration::from_secs(1);
+/// Delay of the first retry of a failed notification, doubled for every following one.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+/// Notifications queued for a URL above this limit are dropped.
+const MAX_PENDING_DELIVERIES: usize = 4096;
+/// Imports waiting to be turned into events above this limit are dropped.
+const MAX_PENDING_ROUTES: usize = 1024;
+/// Number of blocks below the best one checked for finality when starting.
+const FINALITY_LOOKBACK: u64 = 128;
+/// Header carrying the hex-encoded HMAC-SHA256 of the body.
+const SIGNATURE_HEADER: &str = "x-signature";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookConfiguration {
+	/// URLs notifications are posted to, webhooks are disabled if empty.
+	pub urls: Vec<String>,
+	/// Key the notifications are signed with, they are not signed if not set.
+	pub secret: Option<String>,
+	/// Contracts whose logs are posted, no logs are posted if empty.
+	pub log_addresses: Vec<Address>,
+	/// Accepted first topics of posted logs, any if empty.
+	pub log_topics: Vec<H256>,
+	/// Number of times a failed notification is retried.
+	pub max_retries: u32,
+}
+
+impl Default for WebhookConfiguration {
+	fn default() -> Self {
+		WebhookConfiguration {
+			urls: Vec::new(),
+			secret: None,
+			log_addresses: Vec::new(),
+			log_topics: Vec::new(),
+			max_retries: 5,
+		}
+	}
+}
+
+/// Chain event posted to the webhooks.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Event {
+	/// A block became the best one.
+	#[serde(rename_all = "camelCase")]
+	NewHead {
+		number: U64,
+		hash: H256,
+		parent_hash: H256,
+		timestamp: U64,
+	},
+	/// Blocks were removed from the canonical chain.
+	Reorg {
+		retracted: Vec<H256>,
+		enacted: Vec<H256>,
+	},
+	/// A branch was kept out of the canon chain for retracting more blocks than allowed.
+	ReorgHeld {
+		head: H256,
+		number: U64,
+		depth: U64,
+	},
+	/// The engine finalized a block and all its ancestors.
+	Finalized {
+		number: U64,
+		hash: H256,
+	},
+	/// An enacted block contains a log matching the configured filter.
+	#[serde(rename_all = "camelCase")]
+	Log {
+		address: Address,
+		topics: Vec<H256>,
+		data: String,
+		block_number: U64,
+		block_hash: H256,
+		transaction_hash: H256,
+		log_index: U64,
+	},
+}
+
+impl From<LocalizedLogEntry> for Event {
+	fn from(log: LocalizedLogEntry) -> Self {
+		Event::Log {
+			address: log.entry.address,
+			topics: log.entry.topics,
+			data: format!("0x{}", log.entry.data.to_hex::<String>()),
+			block_number: log.block_number.into(),
+			block_hash: log.block_hash,
+			transaction_hash: log.transaction_hash,
+			log_index: (log.log_index as u64).into(),
+		}
+	}
+}
+
+/// Notification to be posted to a single URL.
+struct Delivery {
+	body: String,
+	signature: Option<String>,
+	attempts: u32,
+	due: Instant,
+}
+
+/// Queues notifications and posts them, retrying failed ones.
+///
+/// Every URL has its own queue, so that a slow or failing URL doesn't delay the others.
+/// The notifications of a URL are posted in order: a failed one is retried before those following it.
+struct Dispatcher {
+	urls: Vec<fetch::Url>,
+	secret: Option<Vec<u8>>,
+	max_retries: u32,
+	queues: Vec<Mutex<VecDeque<Delivery>>>,
+	stopped: AtomicBool,
+}
+
+impl Dispatcher {
+	fn push(&self, event: &Event) {
+		let body = serde_json::to_string(event).expect("Events are always serializable; qed");
+		let signature = self.secret.as_ref()
+			.map(|secret| hmac::sign(&hmac::SigKey::sha256(secret), body.as_bytes()).to_hex());
+
+		for (url, queue) in self.urls.iter().zip(&self.queues) {
+			let mut queue = queue.lock();
+			if queue.len() >= MAX_PENDING_DELIVERIES {
+				warn!(target: "webhooks", "Dropped a notification to {}, too many are pending", url);
+				continue;
+			}
+			queue.push_back(Delivery {
+				body: body.clone(),
+				signature: signature.clone(),
+				attempts: 0,
+				due: Instant::now(),
+			});
+		}
+	}
+
+	/// Posts the due notifications of a URL in order, until one fails or the dispatcher is stopped.
+	/// A failed notification is scheduled for a retry at the head of the queue.
+	fn send_due<P>(&self, url: usize, post: P) where
+		P: Fn(&fetch::Url, &Delivery) -> Result<(), String>,
+	{
+		while !self.stopped.load(Ordering::SeqCst) {
+			let mut delivery = {
+				let mut queue = self.queues[url].lock();
+				match queue.front() {
+					Some(delivery) if delivery.due <= Instant::now() => queue.pop_front().expect("queue has a front; qed"),
+					_ => return,
+				}
+			};
+
+			let error = match post(&self.urls[url], &delivery) {
+				Ok(()) => continue,
+				Err(error) => error,
+			};
+			if delivery.attempts < self.max_retries {
+				debug!(target: "webhooks", "Failed to post a notification to {}: {}, retrying", self.urls[url], error);
+				delivery.due = Instant::now() + RETRY_DELAY * 2u32.pow(delivery.attempts.min(16));
+				delivery.attempts += 1;
+				self.queues[url].lock().push_front(delivery);
+				return;
+			}
+			warn!(target: "webhooks", "Failed to post a notification to {}: {}, giving up", self.urls[url], error);
+		}
+	}
+}
+
+/// Post a notification, waiting for the response.
+fn post<F: Fetch>(fetch: &F, url: &fetch::Url, delivery: &Delivery) -> Result<(), String> {
+	let mut request = fetch::Request::post(url.clone())
+		.with_header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+		.with_body(delivery.body.clone());
+	if let Some(ref signature) = delivery.signature {
+		let signature = HeaderValue::from_str(signature).expect("Hex is a valid header value; qed");
+		request = request.with_header(HeaderName::from_static(SIGNATURE_HEADER), signature);
+	}
+
+	match fetch.fetch(request, Default::default()).wait() {
+		Ok(ref response) if response.is_success() => Ok(()),
+		Ok(response) => Err(format!("{}", response.status())),
+		Err(e) => Err(format!("{:?}", e)),
+	}
+}
+
+/// Blocks enacted and retracted by an import, turned into events by the events thread.
+struct Route {
+	enacted: Vec<H256>,
+	retracted: Vec<H256>,
+}
+
+/// Turns imported blocks into webhook events, off the import thread.
+struct EventSource {
+	client: Weak<Client>,
+	dispatcher: Arc<Dispatcher>,
+	log_addresses: Vec<Address>,
+	log_topics: Vec<H256>,
+	last_finalized: BlockNumber,
+}
+
+impl EventSource {
+	fn handle(&mut self, route: Route) {
+		let client = match self.client.upgrade() {
+			Some(client) => client,
+			None => return,
+		};
+
+		if !route.retracted.is_empty() {
+			self.dispatcher.push(&Event::Reorg {
+				retracted: route.retracted,
+				enacted: route.enacted.clone(),
+			});
+		}
+
+		for hash in &route.enacted {
+			let header = match client.block_header(BlockId::Hash(*hash)) {
+				Some(header) => header,
+				None => continue,
+			};
+			self.dispatcher.push(&Event::NewHead {
+				number: header.number().into(),
+				hash: *hash,
+				parent_hash: header.parent_hash(),
+				timestamp: header.timestamp().into(),
+			});
+
+			if !self.log_addresses.is_empty() {
+				let filter = Filter {
+					from_block: BlockId::Hash(*hash),
+					to_block: BlockId::Hash(*hash),
+					address: Some(self.log_addresses.clone()),
+					topics: vec![
+						if self.log_topics.is_empty() { None } else { Some(self.log_topics.clone()) },
+						None,
+						None,
+						None,
+					],
+					limit: None,
+				};
+				for log in client.logs(filter).unwrap_or_default() {
+					self.dispatcher.push(&log.into());
+				}
+			}
+		}
+
+		// Finality only ever moves forward along the canonical chain.
+		let best_number = client.chain_info().best_block_number;
+		let mut number = self.last_finalized;
+		while number < best_number && client.block_finalized(BlockId::Number(number + 1)) == Some(true) {
+			number += 1;
+		}
+		if number > self.last_finalized {
+			if let Some(hash) = client.block_hash(BlockId::Number(number)) {
+				self.dispatcher.push(&Event::Finalized {
+					number: number.into(),
+					hash,
+				});
+			}
+			self.last_finalized = number;
+		}
+	}
+}
+
+/// Hands chain notifications over to the events thread.
+pub struct WebhookNotifier {
+	dispatcher: Arc<Dispatcher>,
+	routes: Mutex<mpsc::SyncSender<Route>>,
+}
+
+impl ChainNotify for WebhookNotifier {
+	fn reorg_held(&self, head: &H256, number: BlockNumber, depth: u64) {
+		self.dispatcher.push(&Event::ReorgHeld {
+			head: *head,
+			number: number.into(),
+			depth: depth.into(),
+		});
+	}
+
+	fn new_blocks(&self, new_blocks: NewBlocks) {
+		let route = Route {
+			enacted: new_blocks.route.enacted().to_vec(),
+			retracted: new_blocks.route.retracted().to_vec(),
+		};
+		if let Err(mpsc::TrySendError::Full(_)) = self.routes.lock().try_send(route) {
+			warn!(target: "webhooks", "Dropped the notifications of imported blocks, too many are pending");
+		}
+	}
+}
+
+/// Background threads turning chain notifications into events and posting them. Stop when dropped.
+pub struct WebhookService {
+	notifier: Arc<WebhookNotifier>,
+	dispatcher: Arc<Dispatcher>,
+	handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl WebhookService {
+	/// Notifier to register for chain notifications.
+	pub fn notifier(&self) -> Arc<WebhookNotifier> {
+		self.notifier.clone()
+	}
+}
+
+impl Drop for WebhookService {
+	fn drop(&mut self) {
+		self.dispatcher.stopped.store(true, Ordering::SeqCst);
+		for handle in self.handles.drain(..) {
+			handle.thread().unpark();
+			let _ = handle.join();
+		}
+	}
+}
+
+fn dispatcher(conf: &WebhookConfiguration) -> Result<Dispatcher, String> {
+	let urls = conf.urls.iter()
+		.map(|url| fetch::Url::parse(url).map_err(|e| format!("Invalid webhook URL {}: {}", url, e)))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	Ok(Dispatcher {
+		queues: urls.iter().map(|_| Mutex::new(VecDeque::new())).collect(),
+		urls,
+		secret: conf.secret.as_ref().map(|secret| secret.as_bytes().to_vec()),
+		max_retries: conf.max_retries,
+		stopped: AtomicBool::new(false),
+	})
+}
+
+/// Start posting chain events to the webhooks if any are configured.
+pub fn start_webhooks(conf: WebhookConfiguration, client: &Arc<Client>, fetch: fetch::Client) -> Result<Option<WebhookService>, String> {
+	if conf.urls.is_empty() {
+		return Ok(None);
+	}
+
+	let dispatcher = Arc::new(dispatcher(&conf)?);
+	let spawn_error = |e| format!("Error starting webhooks: {}", e);
+	let mut handles = Vec::new();
+	for url in 0..conf.urls.len() {
+		let dispatcher = dispatcher.clone();
+		let fetch = fetch.clone();
+		handles.push(thread::Builder::new()
+			.name(format!("Webhook #{}", url))
+			.spawn(move || {
+				while !dispatcher.stopped.load(Ordering::SeqCst) {
+					thread::park_timeout(SEND_INTERVAL);
+					dispatcher.send_due(url, |url, delivery| post(&fetch, url, delivery));
+				}
+			})
+			.map_err(spawn_error)?);
+	}
+
+	let (routes, receiver) = mpsc::sync_channel(MAX_PENDING_ROUTES);
+	let mut events = EventSource {
+		client: Arc::downgrade(client),
+		dispatcher: dispatcher.clone(),
+		log_addresses: conf.log_addresses,
+		log_topics: conf.log_topics,
+		last_finalized: client.chain_info().best_block_number.saturating_sub(FINALITY_LOOKBACK),
+	};
+	handles.push(thread::Builder::new()
+		.name("Webhook events".into())
+		.spawn(move || {
+			while !events.dispatcher.stopped.load(Ordering::SeqCst) {
+				match receiver.recv_timeout(SEND_INTERVAL) {
+					Ok(route) => events.handle(route),
+					Err(mpsc::RecvTimeoutError::Timeout) => {},
+					Err(mpsc::RecvTimeoutError::Disconnected) => break,
+				}
+			}
+		})
+		.map_err(spawn_error)?);
+
+	let notifier = Arc::new(WebhookNotifier {
+		dispatcher: dispatcher.clone(),
+		routes: Mutex::new(routes),
+	});
+
+	info!("Posting chain events to {} webhook(s)", conf.urls.len());
+	Ok(Some(WebhookService {
+		notifier,
+		dispatcher,
+		handles,
+	}))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::RefCell;
+	use ethcore::test_helpers::generate_dummy_client;
+
+	#[test]
+	fn should_serialize_events() {
+		let event = Event::NewHead {
+			number: 1.into(),
+			hash: H256::from_low_u64_be(2),
+			parent_hash: H256::from_low_u64_be(1),
+			timestamp: 10.into(),
+		};
+		assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"type":"newHead","number":"0x1","hash":"0x0000000000000000000000000000000000000000000000000000000000000002","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000001","timestamp":"0xa"}"#);
+
+		let event = Event::Finalized {
+			number: 1.into(),
+			hash: H256::from_low_u64_be(2),
+		};
+		assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"type":"finalized","number":"0x1","hash":"0x0000000000000000000000000000000000000000000000000000000000000002"}"#);
+
+		let event = Event::ReorgHeld {
+			head: H256::from_low_u64_be(2),
+			number: 10.into(),
+			depth: 7.into(),
+		};
+		assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"type":"reorgHeld","head":"0x0000000000000000000000000000000000000000000000000000000000000002","number":"0xa","depth":"0x7"}"#);
+	}
+
+	#[test]
+	fn should_sign_and_queue_per_url() {
+		let conf = WebhookConfiguration {
+			urls: vec!["http://localhost:1/a".into(), "http://localhost:1/b".into()],
+			secret: Some("secret".into()),
+			..Default::default()
+		};
+		let dispatcher = dispatcher(&conf).unwrap();
+		dispatcher.push(&Event::Reorg { retracted: vec![], enacted: vec![] });
+
+		for queue in &dispatcher.queues {
+			let queue = queue.lock();
+			assert_eq!(queue.len(), 1);
+			let expected = hmac::sign(&hmac::SigKey::sha256(b"secret"), queue[0].body.as_bytes()).to_hex::<String>();
+			assert_eq!(queue[0].signature, Some(expected));
+		}
+	}
+
+	#[test]
+	fn should_retry_failed_notifications_in_order() {
+		let conf = WebhookConfiguration {
+			urls: vec!["http://localhost:1/a".into()],
+			max_retries: 1,
+			..Default::default()
+		};
+		let dispatcher = dispatcher(&conf).unwrap();
+		dispatcher.push(&Event::Finalized { number: 1.into(), hash: H256::from_low_u64_be(1) });
+		dispatcher.push(&Event::Finalized { number: 2.into(), hash: H256::from_low_u64_be(2) });
+
+		// the first notification fails, the second one waits for its retry.
+		let posted = RefCell::new(Vec::new());
+		dispatcher.send_due(0, |_, delivery| {
+			posted.borrow_mut().push(delivery.body.clone());
+			Err("unavailable".into())
+		});
+		assert_eq!(posted.borrow().len(), 1);
+		{
+			let mut queue = dispatcher.queues[0].lock();
+			assert_eq!(queue.len(), 2);
+			assert_eq!(queue[0].attempts, 1);
+			assert!(queue[0].due > Instant::now());
+			queue[0].due = Instant::now();
+		}
+
+		dispatcher.send_due(0, |_, delivery| {
+			posted.borrow_mut().push(delivery.body.clone());
+			Ok(())
+		});
+		let posted = posted.into_inner();
+		assert_eq!(posted.len(), 3);
+		assert_eq!(posted[0], posted[1]);
+		assert!(posted[1].contains(r#""number":"0x1""#));
+		assert!(posted[2].contains(r#""number":"0x2""#));
+		assert!(dispatcher.queues[0].lock().is_empty());
+	}
+
+	#[test]
+	fn should_give_up_after_max_retries() {
+		let conf = WebhookConfiguration {
+			urls: vec!["http://localhost:1/a".into()],
+			max_retries: 0,
+			..Default::default()
+		};
+		let dispatcher = dispatcher(&conf).unwrap();
+		dispatcher.push(&Event::Finalized { number: 1.into(), hash: H256::from_low_u64_be(1) });
+		dispatcher.push(&Event::Finalized { number: 2.into(), hash: H256::from_low_u64_be(2) });
+
+		let attempts = RefCell::new(0);
+		dispatcher.send_due(0, |_, _| {
+			*attempts.borrow_mut() += 1;
+			Err("unavailable".into())
+		});
+		assert_eq!(*attempts.borrow(), 2);
+		assert!(dispatcher.queues[0].lock().is_empty());
+	}
+
+	#[test]
+	fn should_turn_enacted_blocks_into_events() {
+		let client = generate_dummy_client(2);
+		let conf = WebhookConfiguration {
+			urls: vec!["http://localhost:1/a".into()],
+			..Default::default()
+		};
+		let dispatcher = Arc::new(dispatcher(&conf).unwrap());
+		let mut events = EventSource {
+			client: Arc::downgrade(&client),
+			dispatcher: dispatcher.clone(),
+			log_addresses: Vec::new(),
+			log_topics: Vec::new(),
+			last_finalized: client.chain_info().best_block_number,
+		};
+
+		let enacted: Vec<_> = (1..3).map(|n| client.block_hash(BlockId::Number(n)).unwrap()).collect();
+		let retracted = vec![H256::from_low_u64_be(7)];
+		events.handle(Route { enacted: enacted.clone(), retracted });
+
+		let bodies: Vec<_> = dispatcher.queues[0].lock().iter().map(|delivery| delivery.body.clone()).collect();
+		assert_eq!(bodies.len(), 3);
+		assert!(bodies[0].starts_with(r#"{"type":"reorg""#));
+		assert!(bodies[1].starts_with(r#"{"type":"newHead","number":"0x1""#));
+		assert!(bodies[2].contains(&format!("{:?}", enacted[1])));
+	}
+
+	#[test]
+	fn should_reject_invalid_urls() {
+		let conf = WebhookConfiguration {
+			urls: vec!["not a url".into()],
+			..Default::default()
+		};
+		assert!(dispatcher(&conf).is_err());
+	}
+}
@@ -0,0 +1,270 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JWT authentication of the Engine API endpoint.
+//!
+//! The consensus client signs a token with the shared secret on every request. Tokens are
+//! HS256-signed and only accepted while their `iat` claim is close to the current time.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use base64;
+use crypto::hmac;
+use futures::future;
+use http::{hyper, RequestMiddleware, RequestMiddlewareAction};
+use rustc_hex::{FromHex, ToHex};
+use serde_json::{self, Value};
+
+use authcodes::{DefaultTimeProvider, TimeProvider};
+
+/// Number of seconds the `iat` claim of a token may differ from the current time.
+const IAT_THRESHOLD: u64 = 60;
+/// Length of the shared secret in bytes.
+const SECRET_LENGTH: usize = 32;
+
+/// Secret shared with the consensus client.
+#[derive(Clone, PartialEq)]
+pub struct JwtSecret([u8; SECRET_LENGTH]);
+
+impl JwtSecret {
+	/// Generates a new random secret.
+	pub fn random() -> Self {
+		JwtSecret(rand::random())
+	}
+
+	/// Parses a hex encoded secret, with or without the `0x` prefix.
+	pub fn from_hex(hex: &str) -> Result<Self, String> {
+		let hex = hex.trim();
+		let bytes: Vec<u8> = hex.trim_start_matches("0x").from_hex()
+			.map_err(|e| format!("Invalid JWT secret: {}", e))?;
+		if bytes.len() != SECRET_LENGTH {
+			return Err(format!("Invalid JWT secret: expected {} bytes, got {}", SECRET_LENGTH, bytes.len()));
+		}
+
+		let mut secret = [0u8; SECRET_LENGTH];
+		secret.copy_from_slice(&bytes);
+		Ok(JwtSecret(secret))
+	}
+
+	/// Reads the secret from a file, generating a new one if the file does not exist.
+	pub fn load_or_generate(path: &Path) -> Result<Self, String> {
+		if path.exists() {
+			let hex = fs::read_to_string(path).map_err(|e| format!("Unable to read JWT secret {}: {}", path.display(), e))?;
+			return Self::from_hex(&hex);
+		}
+
+		let secret = Self::random();
+		create_secret_file(path)
+			.and_then(|mut file| file.write_all(secret.to_hex().as_bytes()))
+			.map_err(|e| format!("Unable to write JWT secret {}: {}", path.display(), e))?;
+		info!(target: "rpc", "Generated JWT secret at {}", path.display());
+		Ok(secret)
+	}
+
+	/// Hex encoding of the secret, with the `0x` prefix.
+	pub fn to_hex(&self) -> String {
+		format!("0x{}", self.0.to_hex::<String>())
+	}
+
+	/// Creates a token issued at the given time.
+	pub fn token(&self, iat: u64) -> String {
+		let header = base64::encode_config(r#"{"alg":"HS256","typ":"JWT"}"#, base64::URL_SAFE_NO_PAD);
+		let claims = base64::encode_config(format!(r#"{{"iat":{}}}"#, iat), base64::URL_SAFE_NO_PAD);
+		let message = format!("{}.{}", header, claims);
+		let signature = hmac::sign(&hmac::SigKey::sha256(&self.0), message.as_bytes());
+		format!("{}.{}", message, base64::encode_config(&*signature, base64::URL_SAFE_NO_PAD))
+	}
+
+	/// Checks the signature, algorithm and issue time of a token.
+	pub fn verify(&self, token: &str, now: u64) -> Result<(), String> {
+		let mut parts = token.split('.');
+		let (header, claims, signature) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+			(Some(header), Some(claims), Some(signature), None) => (header, claims, signature),
+			_ => return Err("Malformed token".into()),
+		};
+
+		let decode = |part: &str| base64::decode_config(part, base64::URL_SAFE_NO_PAD).map_err(|_| "Malformed token".to_owned());
+		let signature = decode(signature)?;
+		let message = &token[..header.len() + claims.len() + 1];
+		if !hmac::verify(&hmac::VerifyKey::sha256(&self.0), message.as_bytes(), &signature) {
+			return Err("Invalid token signature".into());
+		}
+
+		let header: Value = serde_json::from_slice(&decode(header)?).map_err(|_| "Malformed token header".to_owned())?;
+		if header["alg"] != "HS256" {
+			return Err("Unsupported token algorithm".into());
+		}
+
+		let claims: Value = serde_json::from_slice(&decode(claims)?).map_err(|_| "Malformed token claims".to_owned())?;
+		let iat = claims["iat"].as_u64().ok_or_else(|| "Missing iat claim".to_owned())?;
+		if iat.saturating_add(IAT_THRESHOLD) < now || iat > now.saturating_add(IAT_THRESHOLD) {
+			return Err("Stale token".into());
+		}
+
+		Ok(())
+	}
+}
+
+/// Creates a new secret file readable by the owner only.
+#[cfg(unix)]
+fn create_secret_file(path: &Path) -> io::Result<fs::File> {
+	use std::os::unix::fs::OpenOptionsExt;
+
+	fs::OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.mode(0o600)
+		.open(path)
+}
+
+/// Creates a new secret file.
+#[cfg(not(unix))]
+fn create_secret_file(path: &Path) -> io::Result<fs::File> {
+	fs::OpenOptions::new()
+		.write(true)
+		.create_new(true)
+		.open(path)
+}
+
+/// HTTP middleware rejecting requests without a valid bearer token.
+pub struct JwtAuth<T: TimeProvider = DefaultTimeProvider> {
+	secret: JwtSecret,
+	time: T,
+}
+
+impl JwtAuth<DefaultTimeProvider> {
+	/// Creates a middleware checking tokens against the given secret.
+	pub fn new(secret: JwtSecret) -> Self {
+		Self::new_with(secret, DefaultTimeProvider)
+	}
+}
+
+impl<T: TimeProvider> JwtAuth<T> {
+	/// Creates a middleware with a custom time provider.
+	pub fn new_with(secret: JwtSecret, time: T) -> Self {
+		JwtAuth { secret, time }
+	}
+
+	fn authorize(&self, request: &hyper::Request<hyper::Body>) -> Result<(), String> {
+		let header = request.headers().get(hyper::header::AUTHORIZATION)
+			.ok_or_else(|| "Missing authorization header".to_owned())?;
+		let header = header.to_str().map_err(|_| "Malformed authorization header".to_owned())?;
+		if !header.starts_with("Bearer ") {
+			return Err("Missing bearer token".into());
+		}
+
+		self.secret.verify(header["Bearer ".len()..].trim(), self.time.now())
+	}
+}
+
+impl<T: TimeProvider + Send + Sync + 'static> RequestMiddleware for JwtAuth<T> {
+	fn on_request(&self, request: hyper::Request<hyper::Body>) -> RequestMiddlewareAction {
+		match self.authorize(&request) {
+			Ok(()) => RequestMiddlewareAction::Proceed {
+				should_continue_on_invalid_cors: false,
+				request,
+			},
+			Err(e) => {
+				debug!(target: "rpc", "Rejected unauthorized Engine API request: {}", e);
+				let response = hyper::Response::builder()
+					.status(hyper::StatusCode::UNAUTHORIZED)
+					.body(hyper::Body::from(e))
+					.expect("status and body are valid; qed");
+				RequestMiddlewareAction::Respond {
+					should_validate_hosts: false,
+					response: Box::new(future::ok(response)),
+				}
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+
+	const NOW: u64 = 1_600_000_000;
+
+	fn request(authorization: Option<String>) -> hyper::Request<hyper::Body> {
+		let mut request = hyper::Request::builder();
+		request.method("POST").uri("/");
+		if let Some(authorization) = authorization {
+			request.header(hyper::header::AUTHORIZATION, authorization);
+		}
+		request.body(hyper::Body::empty()).unwrap()
+	}
+
+	#[test]
+	fn parses_hex_secrets() {
+		let hex = "0x7365637265747365637265747365637265747365637265747365637265747365";
+		assert_eq!(JwtSecret::from_hex(hex).unwrap().to_hex(), hex);
+		assert_eq!(JwtSecret::from_hex(&hex[2..]).unwrap().to_hex(), hex);
+		assert!(JwtSecret::from_hex("0x1234").is_err());
+		assert!(JwtSecret::from_hex("secret").is_err());
+	}
+
+	#[test]
+	fn verifies_tokens() {
+		let secret = JwtSecret::random();
+		assert_eq!(secret.verify(&secret.token(NOW), NOW), Ok(()));
+		assert_eq!(secret.verify(&secret.token(NOW - 60), NOW), Ok(()));
+		assert!(secret.verify(&secret.token(NOW - 61), NOW).is_err());
+		assert!(secret.verify(&secret.token(NOW + 61), NOW).is_err());
+		assert!(secret.verify(&secret.token(u64::MAX), NOW).is_err());
+		assert!(secret.verify(&secret.token(0), u64::MAX).is_err());
+		assert!(secret.verify(&JwtSecret::random().token(NOW), NOW).is_err());
+		assert!(secret.verify("a.b", NOW).is_err());
+
+		let none = format!("{}.{}",
+			base64::encode_config(r#"{"alg":"none","typ":"JWT"}"#, base64::URL_SAFE_NO_PAD),
+			base64::encode_config(format!(r#"{{"iat":{}}}"#, NOW), base64::URL_SAFE_NO_PAD),
+		);
+		assert!(secret.verify(&format!("{}.", none), NOW).is_err());
+	}
+
+	#[test]
+	fn generates_and_reloads_secret() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("jwt.hex");
+
+		let secret = JwtSecret::load_or_generate(&path).unwrap();
+		assert!(JwtSecret::load_or_generate(&path).unwrap() == secret);
+
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+		}
+	}
+
+	#[test]
+	fn rejects_requests_without_valid_token() {
+		let secret = JwtSecret::random();
+		let auth = JwtAuth::new_with(secret.clone(), || NOW);
+
+		let authorized = |authorization| match auth.on_request(request(authorization)) {
+			RequestMiddlewareAction::Proceed { .. } => true,
+			RequestMiddlewareAction::Respond { .. } => false,
+		};
+		assert!(authorized(Some(format!("Bearer {}", secret.token(NOW)))));
+		assert!(!authorized(Some(format!("Bearer {}", secret.token(NOW - 120)))));
+		assert!(!authorized(Some(secret.token(NOW))));
+		assert!(!authorized(None));
+	}
+}
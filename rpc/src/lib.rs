@@ -41,6 +41,7 @@
 extern crate futures;
 
 extern crate ansi_term;
+extern crate base64;
 extern crate itertools;
 extern crate machine;
 extern crate order_stat;
@@ -70,7 +71,9 @@ extern crate ethcore_light as light;
 extern crate ethcore_logger;
 extern crate ethcore_miner as miner;
 extern crate ethcore_network as network;
+extern crate ethcore_network_gossip as gossip;
 extern crate ethcore_private_tx;
+extern crate ethcore_stratum;
 extern crate ethcore_sync as sync;
 extern crate ethereum_types;
 extern crate ethkey;
@@ -88,6 +91,7 @@ extern crate stats;
 extern crate snapshot;
 extern crate tempfile;
 extern crate trace;
+extern crate triehash_ethereum as triehash;
 extern crate vm;
 
 #[cfg(any(test, feature = "ethcore-accounts"))]
@@ -135,6 +139,7 @@ pub extern crate jsonrpc_ws_server as ws;
 
 mod authcodes;
 mod http_common;
+mod jwt;
 pub mod v1;
 
 pub mod tests;
@@ -153,11 +158,12 @@ pub use http::{
 	AccessControlAllowOrigin, Host, DomainsValidation, cors::AccessControlAllowHeaders
 };
 
-pub use v1::{NetworkSettings, Metadata, Origin, informant, dispatch, signer};
+pub use v1::{NetworkSettings, Metadata, Origin, HeavyRequests, RequestPools, ResponseCache, InstalledFilters, PollLimits, response_cache, informant, dispatch, signer};
 pub use v1::block_import::{is_major_importing_or_waiting};
 pub use v1::PubSubSyncStatus;
 pub use v1::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
 pub use authcodes::{AuthCodes, TimeProvider};
+pub use jwt::{JwtAuth, JwtSecret};
 pub use http_common::HttpMetaExtractor;
 
 use std::net::SocketAddr;
@@ -55,8 +55,8 @@ mod signing {
 			false
 		}
 
-		fn default_account(&self) -> Address {
-			Default::default()
+		fn default_account(&self) -> Option<Address> {
+			None
 		}
 
 		fn is_unlocked(&self, _address: &Address) -> bool {
@@ -151,8 +151,8 @@ pub trait Accounts: Send + Sync {
 	/// Returns `true` if the accounts can sign multiple times.
 	fn supports_prospective_signing(&self, address: &Address, password: &SignWith) -> bool;
 
-	/// Returns default account.
-	fn default_account(&self) -> Address;
+	/// Returns default account, if there is any.
+	fn default_account(&self) -> Option<Address>;
 
 	/// Returns true if account is unlocked (i.e. can sign without a password)
 	fn is_unlocked(&self, address: &Address) -> bool;
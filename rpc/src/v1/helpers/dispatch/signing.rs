@@ -93,8 +93,8 @@ impl super::Accounts for Signer {
 		is_unlocked_permanently || has_password
 	}
 
-	fn default_account(&self) -> Address {
-		self.accounts.default_account().ok().unwrap_or_default()
+	fn default_account(&self) -> Option<Address> {
+		self.accounts.default_account().ok()
 	}
 
 	fn is_unlocked(&self, address: &Address) -> bool {
@@ -0,0 +1,114 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Cache of names resolved through ENS.
+
+use std::collections::HashMap;
+
+use ethereum_types::{H160, H256};
+use parking_lot::{Mutex, MutexGuard};
+
+/// Maximal number of names and addresses kept at once.
+const MAX_ENTRIES: usize = 1024;
+
+#[derive(Default)]
+struct Entries {
+	head: H256,
+	addresses: HashMap<String, Option<H160>>,
+	names: HashMap<H160, Option<String>>,
+}
+
+/// Forward and reverse ENS lookups made at the current chain head. ENS records
+/// only change with new blocks, so everything is dropped once the head moves.
+#[derive(Default)]
+pub struct EnsCache {
+	entries: Mutex<Entries>,
+}
+
+impl EnsCache {
+	/// Address `name` resolves to at `head`, looked up with `resolve` unless it's cached.
+	pub fn address<F>(&self, head: H256, name: &str, resolve: F) -> Result<Option<H160>, String> where
+		F: FnOnce() -> Result<Option<H160>, String>,
+	{
+		if let Some(address) = self.entries(head).addresses.get(name) {
+			return Ok(*address);
+		}
+		let address = resolve()?;
+		let mut entries = self.entries(head);
+		if entries.addresses.len() < MAX_ENTRIES {
+			entries.addresses.insert(name.to_owned(), address);
+		}
+		Ok(address)
+	}
+
+	/// Primary name of `address` at `head`, looked up with `resolve` unless it's cached.
+	pub fn name<F>(&self, head: H256, address: &H160, resolve: F) -> Result<Option<String>, String> where
+		F: FnOnce() -> Result<Option<String>, String>,
+	{
+		if let Some(name) = self.entries(head).names.get(address) {
+			return Ok(name.clone());
+		}
+		let name = resolve()?;
+		let mut entries = self.entries(head);
+		if entries.names.len() < MAX_ENTRIES {
+			entries.names.insert(*address, name.clone());
+		}
+		Ok(name)
+	}
+
+	fn entries(&self, head: H256) -> MutexGuard<Entries> {
+		let mut entries = self.entries.lock();
+		if entries.head != head {
+			*entries = Entries { head, ..Default::default() };
+		}
+		entries
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::Cell;
+
+	#[test]
+	fn should_cache_lookups_until_head_changes() {
+		let cache = EnsCache::default();
+		let lookups = Cell::new(0);
+		let resolve = || {
+			lookups.set(lookups.get() + 1);
+			Ok(Some(H160::from_low_u64_be(1)))
+		};
+
+		assert_eq!(cache.address(H256::from_low_u64_be(1), "a.eth", &resolve), Ok(Some(H160::from_low_u64_be(1))));
+		assert_eq!(cache.address(H256::from_low_u64_be(1), "a.eth", &resolve), Ok(Some(H160::from_low_u64_be(1))));
+		assert_eq!(lookups.get(), 1);
+
+		assert_eq!(cache.address(H256::from_low_u64_be(2), "a.eth", &resolve), Ok(Some(H160::from_low_u64_be(1))));
+		assert_eq!(lookups.get(), 2);
+	}
+
+	#[test]
+	fn should_cache_missing_names_but_not_errors() {
+		let cache = EnsCache::default();
+		let head = H256::from_low_u64_be(1);
+		let address = H160::from_low_u64_be(1);
+
+		assert_eq!(cache.name(head, &address, || Err("resolver unavailable".into())), Err("resolver unavailable".into()));
+		assert_eq!(cache.name(head, &address, || Ok(None)), Ok(None));
+		assert_eq!(cache.name(head, &address, || Ok(Some("a.eth".into()))), Ok(None));
+	}
+}
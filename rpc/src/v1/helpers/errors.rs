@@ -21,14 +21,17 @@ use std::fmt;
 use jsonrpc_core::{futures, Result as RpcResult, Error, ErrorCode, Value};
 use rlp::DecoderError;
 use types::transaction::Error as TransactionError;
+use ethcore::miner::BundleError;
 use ethcore_private_tx::Error as PrivateTransactionError;
+use gossip::Error as GossipError;
 use vm::Error as VMError;
 use light::on_demand::error::{Error as OnDemandError};
 use client_traits::BlockChainClient;
 use types::{
 	ids::BlockId,
 	blockchain_info::BlockChainInfo,
-	errors::{EthcoreError},
+	errors::{EngineError, EthcoreError},
+	filter::ReconstructLogsError,
 	transaction::CallError,
 };
 use v1::types::BlockNumber;
@@ -37,6 +40,8 @@ use v1::impls::EthClientOptions;
 mod codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
 	pub const UNSUPPORTED_REQUEST: i64 = -32000;
+	// Same code and message as used by other clients for underpriced replacements.
+	pub const REPLACEMENT_UNDERPRICED: i64 = -32000;
 	pub const NO_WORK: i64 = -32001;
 	pub const NO_AUTHOR: i64 = -32002;
 	pub const NO_NEW_WORK: i64 = -32003;
@@ -54,9 +59,12 @@ mod codes {
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const ACCOUNT_ERROR: i64 = -32023;
 	pub const PRIVATE_ERROR: i64 = -32024;
+	pub const REMOTE_SIGNER_ERROR: i64 = -32025;
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const REQUEST_CANCELLED: i64 = -32043;
+	pub const REQUEST_QUEUE_FULL: i64 = -32044;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	#[cfg(any(test, feature = "accounts"))]
 	pub const ENCODING_ERROR: i64 = -32058;
@@ -66,6 +74,9 @@ mod codes {
 	pub const DEPRECATED: i64 = -32070;
 	pub const EXPERIMENTAL_RPC: i64 = -32071;
 	pub const CANNOT_RESTART: i64 = -32080;
+	// Engine API codes
+	pub const UNKNOWN_PAYLOAD: i64 = -38001;
+	pub const INVALID_FORKCHOICE_STATE: i64 = -38002;
 }
 
 pub fn unimplemented(details: Option<String>) -> Error {
@@ -92,6 +103,22 @@ pub fn unsupported<T: Into<String>>(msg: T, details: Option<T>) -> Error {
 	}
 }
 
+pub fn unknown_payload() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNKNOWN_PAYLOAD),
+		message: "Unknown payload".into(),
+		data: None,
+	}
+}
+
+pub fn invalid_forkchoice_state(details: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::INVALID_FORKCHOICE_STATE),
+		message: "Invalid forkchoice state.".into(),
+		data: Some(Value::String(details)),
+	}
+}
+
 pub fn request_not_found() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_NOT_FOUND),
@@ -116,6 +143,22 @@ pub fn request_rejected_limit() -> Error {
 	}
 }
 
+pub fn request_cancelled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_CANCELLED),
+		message: "Request has been cancelled.".into(),
+		data: None,
+	}
+}
+
+pub fn request_queue_full(pool: &str, limit: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_QUEUE_FULL),
+		message: format!("Too many requests: the {} pool already has {} requests queued. Try again later.", pool, limit),
+		data: None,
+	}
+}
+
 pub fn request_rejected_param_limit(limit: u64, items_desc: &str) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -219,6 +262,34 @@ pub fn no_work_required() -> Error {
 	}
 }
 
+pub fn stratum_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Stratum server is not running. Run Parity with --stratum to enable it.".into(),
+		data: None,
+	}
+}
+
+pub fn gossip_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Gossip messaging is not running. Run Parity with --gossip to enable it.".into(),
+		data: None,
+	}
+}
+
+pub fn gossip(error: GossipError) -> Error {
+	let code = match error {
+		GossipError::NoTopics | GossipError::PayloadTooLarge(_) => ErrorCode::InvalidParams,
+		GossipError::Stopped | GossipError::Transport(_) => ErrorCode::InternalError,
+	};
+	Error {
+		code,
+		message: error.to_string(),
+		data: None,
+	}
+}
+
 pub fn cannot_submit_work(err: EthcoreError) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::CANNOT_SUBMIT_WORK),
@@ -259,6 +330,17 @@ pub fn cannot_submit_block(err: EthcoreError) -> Error {
 	}
 }
 
+pub fn engine_params_update(err: EthcoreError) -> Error {
+	match err {
+		EthcoreError::Engine(EngineError::ParamsReloadUnsupported) =>
+			unsupported("The engine parameters cannot be updated while the node is running", None),
+		EthcoreError::Engine(EngineError::InvalidParamsUpdate(msg)) => invalid_params("update", msg),
+		EthcoreError::Ethkey(e) => invalid_params("signature", e),
+		EthcoreError::StdIo(e) => database(e),
+		e => internal("Failed to update the engine parameters", e),
+	}
+}
+
 pub fn check_block_number_existence<'a, T, C>(
 	client: &'a C,
 	num: BlockNumber,
@@ -401,6 +483,14 @@ pub fn password(error: ::accounts::SignError) -> Error {
 	}
 }
 
+pub fn remote_signer<T: fmt::Display>(error: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REMOTE_SIGNER_ERROR),
+		message: "Remote signer request failed.".into(),
+		data: Some(Value::String(error.to_string())),
+	}
+}
+
 pub fn private_message(error: PrivateTransactionError) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::PRIVATE_ERROR),
@@ -450,6 +540,7 @@ pub fn transaction_message(error: &TransactionError) -> String {
 		SenderBanned => "Sender is banned in local queue.".into(),
 		RecipientBanned => "Recipient is banned in local queue.".into(),
 		CodeBanned => "Code is banned in local queue.".into(),
+		SelectorBanned => "Called function is banned in local queue.".into(),
 		NotAllowed => "Transaction is not permitted.".into(),
 		TooBig => "Transaction is too big, see chain specification for the limit.".into(),
 		InvalidRlp(ref descr) => format!("Invalid RLP data: {}", descr),
@@ -458,7 +549,13 @@ pub fn transaction_message(error: &TransactionError) -> String {
 
 pub fn transaction<T: Into<EthcoreError>>(error: T) -> Error {
 	let error = error.into();
-	if let EthcoreError::Transaction(ref e) = error {
+	if let EthcoreError::Transaction(ref e @ TransactionError::TooCheapToReplace { .. }) = error {
+		Error {
+			code: ErrorCode::ServerError(codes::REPLACEMENT_UNDERPRICED),
+			message: "replacement transaction underpriced".into(),
+			data: Some(Value::String(transaction_message(e))),
+		}
+	} else if let EthcoreError::Transaction(ref e) = error {
 		Error {
 			code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
 			message: transaction_message(e),
@@ -473,6 +570,14 @@ pub fn transaction<T: Into<EthcoreError>>(error: T) -> Error {
 	}
 }
 
+pub fn bundle(error: BundleError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+		message: error.to_string(),
+		data: None,
+	}
+}
+
 pub fn decode<T: Into<EthcoreError>>(error: T) -> Error {
 	match error.into() {
 		EthcoreError::Decoder(ref dec_err) => rlp(dec_err.clone()),
@@ -549,6 +654,30 @@ pub fn filter_not_found() -> Error {
 	}
 }
 
+pub fn too_many_filters() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: "Too many filters installed. Uninstall unused filters or wait for them to expire.".into(),
+		data: None,
+	}
+}
+
+pub fn name_resolution(error: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Couldn't resolve the name through ENS.".into(),
+		data: Some(Value::String(error)),
+	}
+}
+
+pub fn unknown_name(name: &str) -> Error {
+	Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("Name {} doesn't resolve to an address.", name),
+		data: None,
+	}
+}
+
 pub fn filter_block_not_found(id: BlockId) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST), // Specified in EIP-234.
@@ -562,6 +691,17 @@ pub fn filter_block_not_found(id: BlockId) -> Error {
 	}
 }
 
+pub fn reconstruct_logs(error: ReconstructLogsError) -> Error {
+	match error {
+		ReconstructLogsError::UnknownBlock(id) => filter_block_not_found(id),
+		ReconstructLogsError::StatePruned(first, last) => Error {
+			code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+			message: format!("Logs of blocks #{} to #{} can't be reconstructed: their receipts and state are pruned.", first, last),
+			data: None,
+		},
+	}
+}
+
 pub fn on_demand_error(err: OnDemandError) -> Error {
 	match err {
 		OnDemandError::ChannelCanceled(e) => on_demand_cancel(e),
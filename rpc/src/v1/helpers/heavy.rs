@@ -0,0 +1,221 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Execution of expensive requests on a dedicated pool of threads.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use futures::{future, Async, Future, Poll};
+use futures::sync::oneshot;
+use jsonrpc_core::{BoxFuture, Error, Result};
+use parity_runtime::Executor;
+
+use v1::helpers::errors;
+
+/// Tells a request running on the pool that nobody waits for its response anymore.
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+	/// Whether the request was abandoned.
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+
+	/// Fails if the request was abandoned. Long requests call this between units of work.
+	pub fn check(&self) -> Result<()> {
+		match self.is_cancelled() {
+			true => Err(errors::request_cancelled()),
+			false => Ok(()),
+		}
+	}
+
+	fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+}
+
+/// Runs expensive requests (`trace_*`, `eth_getLogs`, `debug_trace*`) on their own threads, so they
+/// don't hold up the threads of the RPC servers, and cancels them once their response is dropped,
+/// e.g. because the client disconnected.
+#[derive(Clone)]
+pub struct HeavyRequests {
+	name: &'static str,
+	executor: Executor,
+	queued: Arc<AtomicUsize>,
+	max_queued: usize,
+}
+
+impl HeavyRequests {
+	/// Run requests on the threads of the given executor.
+	pub fn new(executor: Executor) -> Self {
+		Self::with_queue_limit("heavy", executor, usize::max_value())
+	}
+
+	/// Run requests on the threads of the given executor, rejecting new ones while `max_queued`
+	/// requests are queued or running.
+	pub fn with_queue_limit(name: &'static str, executor: Executor, max_queued: usize) -> Self {
+		HeavyRequests {
+			name,
+			executor,
+			queued: Default::default(),
+			max_queued,
+		}
+	}
+
+	/// Run requests on the calling thread.
+	pub fn new_sync() -> Self {
+		Self::new(Executor::new_sync())
+	}
+
+	/// Number of requests queued or running.
+	pub fn queued(&self) -> usize {
+		self.queued.load(Ordering::SeqCst)
+	}
+
+	/// Run a request on the pool.
+	pub fn run<T, F>(&self, request: F) -> BoxFuture<T> where
+		T: Send + 'static,
+		F: FnOnce(&Cancellation) -> Result<T> + Send + 'static,
+	{
+		if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queued {
+			self.queued.fetch_sub(1, Ordering::SeqCst);
+			return Box::new(future::err(errors::request_queue_full(self.name, self.max_queued)));
+		}
+
+		let cancellation = Cancellation::default();
+		let (sender, receiver) = oneshot::channel();
+
+		let worker = cancellation.clone();
+		let slot = QueueSlot(self.queued.clone());
+		self.executor.spawn(future::lazy(move || {
+			let _slot = slot;
+			if !worker.is_cancelled() {
+				let _ = sender.send(request(&worker));
+			}
+			Ok(())
+		}));
+
+		Box::new(Response { receiver, cancellation })
+	}
+}
+
+/// Place of a request in the queue of a pool, freed once the request is done or dropped.
+struct QueueSlot(Arc<AtomicUsize>);
+
+impl Drop for QueueSlot {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// Pools running the different kinds of expensive requests. Kinds may share a pool.
+#[derive(Clone)]
+pub struct RequestPools {
+	/// Pool of `trace_*` requests.
+	pub traces: HeavyRequests,
+	/// Pool of `eth_getLogs` and filter log requests.
+	pub logs: HeavyRequests,
+	/// Pool of `debug_trace*` requests.
+	pub debug: HeavyRequests,
+	/// Pool of Engine API requests, which wait for payloads to be imported.
+	pub engine: HeavyRequests,
+}
+
+/// Response of a request running on the pool. Cancels the request when dropped.
+struct Response<T> {
+	receiver: oneshot::Receiver<Result<T>>,
+	cancellation: Cancellation,
+}
+
+impl<T> Future for Response<T> {
+	type Item = T;
+	type Error = Error;
+
+	fn poll(&mut self) -> Poll<T, Error> {
+		match self.receiver.poll() {
+			Ok(Async::Ready(Ok(result))) => Ok(Async::Ready(result)),
+			Ok(Async::Ready(Err(err))) => Err(err),
+			Ok(Async::NotReady) => Ok(Async::NotReady),
+			// the request was cancelled before it started or it panicked
+			Err(_) => Err(errors::internal("Request was not completed", "")),
+		}
+	}
+}
+
+impl<T> Drop for Response<T> {
+	fn drop(&mut self) {
+		self.cancellation.cancel();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::mpsc;
+	use std::time::Duration;
+	use parity_runtime::Runtime;
+
+	#[test]
+	fn should_run_requests() {
+		let heavy = HeavyRequests::new_sync();
+		assert_eq!(heavy.run(|_| Ok(5)).wait().unwrap(), 5);
+		assert_eq!(heavy.run::<(), _>(|_| Err(errors::request_cancelled())).wait().unwrap_err(), errors::request_cancelled());
+	}
+
+	#[test]
+	fn should_reject_requests_over_queue_limit() {
+		let runtime = Runtime::with_thread_count(1);
+		let heavy = HeavyRequests::with_queue_limit("traces", runtime.executor(), 1);
+		let (release_tx, release) = mpsc::channel::<()>();
+
+		let running = heavy.run(move |_| {
+			release.recv_timeout(Duration::from_secs(5)).unwrap();
+			Ok(1)
+		});
+		assert_eq!(heavy.run(|_| Ok(2)).wait().unwrap_err(), errors::request_queue_full("traces", 1));
+
+		release_tx.send(()).unwrap();
+		assert_eq!(running.wait().unwrap(), 1);
+		while heavy.queued() > 0 {
+			::std::thread::sleep(Duration::from_millis(1));
+		}
+		assert_eq!(heavy.run(|_| Ok(3)).wait().unwrap(), 3);
+	}
+
+	#[test]
+	fn should_cancel_dropped_requests() {
+		let runtime = Runtime::with_thread_count(1);
+		let heavy = HeavyRequests::new(runtime.executor());
+		let (started_tx, started) = mpsc::channel();
+		let (stopped_tx, stopped) = mpsc::channel();
+
+		let response = heavy.run(move |cancellation| {
+			started_tx.send(()).unwrap();
+			while cancellation.check().is_ok() {
+				::std::thread::sleep(Duration::from_millis(1));
+			}
+			stopped_tx.send(()).unwrap();
+			Ok(())
+		});
+		started.recv_timeout(Duration::from_secs(5)).unwrap();
+		drop(response);
+
+		stopped.recv_timeout(Duration::from_secs(5)).unwrap();
+	}
+}
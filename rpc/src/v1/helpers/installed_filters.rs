@@ -0,0 +1,269 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Filters installed over RPC, shared by all the servers and optionally kept across restarts.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+use serde_json;
+
+use v1::helpers::{PollLimits, PollManager, SyncPollFilter};
+use v1::helpers::poll_filter::StoredPollFilter;
+use v1::helpers::poll_manager::PollId;
+use v1::types::Origin;
+
+/// Time the filters are saved after one was installed or removed, so that bursts of changes
+/// are written once.
+const INSTALL_SAVE_DELAY: Duration = Duration::from_secs(1);
+/// Time the filters are saved after they were polled.
+const POLL_SAVE_DELAY: Duration = Duration::from_secs(30);
+
+type Polls = Mutex<PollManager<SyncPollFilter>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredPoll {
+	id: PollId,
+	origin: Origin,
+	created_at: i64,
+	filter: StoredPollFilter,
+}
+
+/// When the filters have to be saved, shared with the thread saving them.
+#[derive(Default)]
+struct Schedule {
+	/// Next save, `None` if the filters didn't change since the last one.
+	due: Mutex<Option<Instant>>,
+	wake: Condvar,
+	stop: AtomicBool,
+}
+
+/// Thread writing the filters to `path` when they are due.
+struct Saver {
+	path: PathBuf,
+	schedule: Arc<Schedule>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+/// Filters installed over RPC.
+pub struct InstalledFilters {
+	polls: Arc<Polls>,
+	saver: Option<Saver>,
+}
+
+impl InstalledFilters {
+	/// Filters kept in memory only.
+	pub fn new(limits: PollLimits) -> Self {
+		InstalledFilters {
+			polls: Arc::new(Mutex::new(PollManager::new(limits))),
+			saver: None,
+		}
+	}
+
+	/// Filters saved to `path`, starting with the ones saved there by an earlier run.
+	pub fn persistent(limits: PollLimits, path: PathBuf) -> Self {
+		let mut polls = PollManager::new(limits);
+		match load(&path) {
+			Ok(stored) => {
+				let count = stored.len();
+				for poll in stored {
+					polls.restore_poll(poll.id, poll.origin, poll.created_at, SyncPollFilter::new(poll.filter.into()));
+				}
+				debug!(target: "rpc", "Restored {} filters from {}", count, path.display());
+			},
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => {},
+			Err(e) => warn!(target: "rpc", "Error loading filters from {}: {}", path.display(), e),
+		}
+
+		let polls = Arc::new(Mutex::new(polls));
+		let schedule = Arc::new(Schedule::default());
+		let thread = {
+			let (polls, schedule, path) = (polls.clone(), schedule.clone(), path.clone());
+			thread::Builder::new()
+				.name("rpc-filters".into())
+				.spawn(move || run_saver(&polls, &schedule, &path))
+				.map_err(|e| warn!(target: "rpc", "Error starting the thread saving filters: {}", e))
+				.ok()
+		};
+
+		InstalledFilters {
+			polls,
+			saver: Some(Saver { path, schedule, thread }),
+		}
+	}
+
+	/// Installed filters.
+	pub fn polls(&self) -> &Mutex<PollManager<SyncPollFilter>> {
+		&self.polls
+	}
+
+	/// Schedule saving the filters, if they are persistent. They are saved shortly after
+	/// `installed`, i.e. after a filter was installed or removed, and within a while after
+	/// they were polled otherwise. Changes are written together, in the background.
+	pub fn save(&self, installed: bool) {
+		let saver = match self.saver {
+			Some(ref saver) => saver,
+			None => return,
+		};
+		let at = Instant::now() + if installed { INSTALL_SAVE_DELAY } else { POLL_SAVE_DELAY };
+		let mut due = saver.schedule.due.lock();
+		if due.map_or(true, |due| at < due) {
+			*due = Some(at);
+			saver.schedule.wake.notify_one();
+		}
+	}
+}
+
+impl Drop for InstalledFilters {
+	fn drop(&mut self) {
+		let saver = match self.saver.take() {
+			Some(saver) => saver,
+			None => return,
+		};
+		{
+			let _due = saver.schedule.due.lock();
+			saver.schedule.stop.store(true, Ordering::SeqCst);
+			saver.schedule.wake.notify_one();
+		}
+		if let Some(thread) = saver.thread {
+			let _ = thread.join();
+		}
+		save(&self.polls, &saver.path);
+	}
+}
+
+fn run_saver(polls: &Polls, schedule: &Schedule, path: &Path) {
+	loop {
+		{
+			let mut due = schedule.due.lock();
+			loop {
+				if schedule.stop.load(Ordering::SeqCst) {
+					return;
+				}
+				match *due {
+					Some(at) if at <= Instant::now() => break,
+					Some(at) => { schedule.wake.wait_until(&mut due, at); },
+					None => schedule.wake.wait(&mut due),
+				}
+			}
+			*due = None;
+		}
+		save(polls, path);
+	}
+}
+
+fn save(polls: &Polls, path: &Path) {
+	// only the filters are copied under the lock, they are serialized after it's released.
+	let stored: Vec<_> = polls.lock().polls().into_iter().map(|(info, filter)| StoredPoll {
+		id: info.id,
+		origin: info.origin,
+		created_at: info.created_at,
+		filter: filter.modify(|filter| StoredPollFilter::from(&*filter)),
+	}).collect();
+
+	if let Err(e) = write(path, &stored) {
+		warn!(target: "rpc", "Error saving filters to {}: {}", path.display(), e);
+	}
+}
+
+fn load(path: &Path) -> io::Result<Vec<StoredPoll>> {
+	let file = fs::File::open(path)?;
+	serde_json::from_reader(file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(path: &Path, stored: &[StoredPoll]) -> io::Result<()> {
+	// write a temporary file first, so that a crash doesn't leave a truncated file behind
+	let tmp = path.with_extension("tmp");
+	{
+		let mut file = io::BufWriter::new(fs::File::create(&tmp)?);
+		serde_json::to_writer(&mut file, stored).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+		file.flush()?;
+		file.get_ref().sync_all()?;
+	}
+	fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::VecDeque;
+	use ethereum_types::{H160, H256};
+	use tempfile::TempDir;
+	use types::filter::Filter;
+	use types::ids::BlockId;
+	use v1::helpers::{PollFilter, PollLimits, SyncPollFilter};
+	use v1::types::Origin;
+	use super::InstalledFilters;
+
+	#[test]
+	fn should_restore_saved_filters() {
+		let dir = TempDir::new().unwrap();
+		let path = dir.path().join("filters.json");
+		let origin = Origin::Rpc("dapp".into());
+		let log_filter = Filter {
+			from_block: BlockId::Number(5),
+			to_block: BlockId::Latest,
+			address: Some(vec![H160::from_low_u64_be(1)]),
+			topics: vec![Some(vec![H256::from_low_u64_be(2)]), None, None, None],
+			limit: None,
+		};
+
+		{
+			let filters = InstalledFilters::persistent(PollLimits::default(), path.clone());
+			let mut polls = filters.polls().lock();
+			polls.create_poll(&origin, SyncPollFilter::new(PollFilter::Block {
+				last_block_number: 10,
+				recent_reported_hashes: VecDeque::new(),
+			}));
+			polls.create_poll(&origin, SyncPollFilter::new(PollFilter::Logs {
+				block_number: 11,
+				last_block_hash: Some(H256::from_low_u64_be(3)),
+				previous_logs: Default::default(),
+				filter: log_filter.clone(),
+				include_pending: true,
+			}));
+		}
+
+		let filters = InstalledFilters::persistent(PollLimits::default(), path);
+		let mut polls = filters.polls().lock();
+		let restored = polls.polls();
+		assert_eq!(restored.len(), 2);
+		assert_eq!(restored[0].0.origin, origin);
+		restored[0].1.modify(|filter| match *filter {
+			PollFilter::Block { last_block_number, .. } => assert_eq!(last_block_number, 10),
+			_ => panic!("expected a block filter"),
+		});
+		restored[1].1.modify(|filter| match *filter {
+			PollFilter::Logs { block_number, last_block_hash, ref filter, include_pending, .. } => {
+				assert_eq!(block_number, 11);
+				assert_eq!(last_block_hash, Some(H256::from_low_u64_be(3)));
+				assert_eq!(*filter, log_filter);
+				assert!(include_pending);
+			},
+			_ => panic!("expected a log filter"),
+		});
+		drop(polls);
+
+		// ids of restored filters are not reused
+		assert_eq!(filters.polls().lock().create_poll(&origin, SyncPollFilter::new(PollFilter::PendingTransaction(Default::default()))), Some(2));
+	}
+}
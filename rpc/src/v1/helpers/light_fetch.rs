@@ -216,6 +216,24 @@ where
 		}))
 	}
 
+	/// Helper for getting a storage value at a given block.
+	pub fn storage_at(&self, address: Address, key: H256, id: BlockId) -> impl Future<Item = H256, Error = Error> + Send {
+		let mut reqs = Vec::new();
+		let header_ref = match self.make_header_requests(id, &mut reqs) {
+			Ok(r) => r,
+			Err(e) => return Either::A(future::err(e)),
+		};
+
+		reqs.push(request::Account { header: header_ref.clone(), address }.into());
+		let account_idx = reqs.len() - 1;
+		reqs.push(request::Storage { header: header_ref, address, key, storage_root: Field::back_ref(account_idx, 1) }.into());
+
+		Either::B(self.send_requests(reqs, |mut res| match res.pop() {
+			Some(OnDemandResponse::Storage(value)) => value,
+			_ => panic!(WRONG_RESPONSE_AMOUNT_TYPE_PROOF),
+		}))
+	}
+
 	/// Helper for getting account info at a given block.
 	/// `None` indicates the account doesn't exist at the given block.
 	pub fn account(
@@ -257,6 +275,7 @@ where
 		const START_GAS: u64 = 60_000;
 
 		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
+		let fetcher = self.clone();
 		let req: CallRequestHelper = req.into();
 
 		// Note: Here we treat `Pending` as `Latest`.
@@ -301,14 +320,7 @@ where
 			)
 		}).join(header_fut).and_then(move |((gas_known, tx), hdr)| {
 			// then request proved execution.
-			// TODO: get last-hashes from network.
-			let hash = hdr.hash();
-			let env_info = match client.env_info(BlockId::Hash(hash)) {
-				Some(env_info) => env_info,
-				_ => return Either::A(future::err(errors::unknown_block())),
-			};
-
-			Either::B(execute_read_only_tx(gas_known, ExecuteParams {
+			fetcher.env_info(hdr.clone()).and_then(move |env_info| execute_read_only_tx(gas_known, ExecuteParams {
 				from,
 				tx,
 				hdr,
@@ -320,6 +332,41 @@ where
 		}))
 	}
 
+	/// Get the environment to execute transactions in at the given block. The hashes of
+	/// its ancestors are fetched from the network if the block isn't stored locally.
+	fn env_info(&self, hdr: encoded::Header) -> impl Future<Item = ::vm::EnvInfo, Error = Error> + Send {
+		if let Some(env_info) = self.client.env_info(BlockId::Hash(hdr.hash())) {
+			return Either::A(future::ok(env_info));
+		}
+
+		let (number, parent_hash) = (hdr.number(), hdr.parent_hash());
+		let make_env_info = move |last_hashes| ::vm::EnvInfo {
+			number,
+			author: hdr.author(),
+			timestamp: hdr.timestamp(),
+			difficulty: hdr.difficulty(),
+			last_hashes: Arc::new(last_hashes),
+			gas_used: Default::default(),
+			gas_limit: hdr.gas_limit(),
+			base_fee: None,
+		};
+
+		if number == 0 {
+			return Either::A(future::ok(make_env_info(Vec::new())));
+		}
+
+		// the parent and up to 255 of its ancestors.
+		let ancestors = request::HeaderWithAncestors {
+			block_hash: parent_hash.into(),
+			ancestor_count: cmp::min(255, number - 1),
+		};
+
+		Either::B(self.send_requests(vec![ancestors.into()], move |mut res| match res.pop() {
+			Some(OnDemandResponse::HeaderWithAncestors(headers)) => make_env_info(headers.iter().map(encoded::Header::hash).collect()),
+			_ => panic!(WRONG_RESPONSE_AMOUNT_TYPE_PROOF),
+		}))
+	}
+
 	/// Helper to fetch the corpus gas price from 1) the cache 2) the network then it tries to estimate the percentile
 	/// using `gas_price_percentile` if the estimated percentile is zero the `DEFAULT_GAS_PRICE` is returned
 	pub fn gas_price(&self) -> impl Future<Item = U256, Error = Error> + Send {
@@ -20,36 +20,45 @@ pub mod errors;
 pub mod block_import;
 pub mod deprecated;
 pub mod dispatch;
+pub mod ens;
 #[cfg(any(test, feature = "accounts"))]
 pub mod eip191;
 #[cfg(any(test, feature = "accounts"))]
 pub mod engine_signer;
 pub mod external_signer;
 pub mod fake_sign;
+pub mod heavy;
 pub mod light_fetch;
 pub mod nonce;
+pub mod remote_signer;
+pub mod response_cache;
 #[cfg(any(test, feature = "accounts"))]
 pub mod secretstore;
+pub mod tokens;
 
+mod installed_filters;
 mod network_settings;
 mod poll_filter;
 mod poll_manager;
 mod requests;
 mod subscribers;
 mod subscription_manager;
+mod watched_state;
 mod work;
 mod signature;
 
 pub use self::dispatch::{Dispatcher, FullDispatcher, LightDispatcher};
 pub use self::signature::verify_signature;
 pub use self::network_settings::NetworkSettings;
-pub use self::poll_manager::PollManager;
+pub use self::installed_filters::InstalledFilters;
+pub use self::poll_manager::{PollInfo, PollLimits, PollManager};
 pub use self::poll_filter::{PollFilter, SyncPollFilter, limit_logs};
 pub use self::requests::{
 	TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest,
 };
 pub use self::subscribers::Subscribers;
 pub use self::subscription_manager::GenericPollManager;
+pub use self::watched_state::{StateWatcher, WatchedAccounts, WatchedItems, MAX_STATE_SUBSCRIBERS, MAX_WATCHED_ITEMS};
 pub use self::work::submit_work_detail;
 
 pub fn to_url(address: &Option<::Host>) -> Option<String> {
@@ -20,10 +20,11 @@ use std::{
 	collections::{BTreeSet, HashSet, VecDeque},
 	sync::Arc,
 };
-use ethereum_types::H256;
+use ethereum_types::{H160, H256};
 use parking_lot::Mutex;
-use v1::types::Log;
+use v1::types::{BlockNumber as RpcBlockNumber, Log};
 use types::filter::Filter;
+use types::ids::BlockId;
 
 pub type BlockNumber = u64;
 
@@ -68,6 +69,111 @@ pub enum PollFilter {
 
 impl PollFilter {
 	pub (in v1) const MAX_BLOCK_HISTORY_SIZE: usize = 32;
+
+	/// Name of the kind of filter.
+	pub fn kind(&self) -> &'static str {
+		match *self {
+			PollFilter::Block { .. } => "block",
+			PollFilter::PendingTransaction(_) => "pendingTransaction",
+			PollFilter::Logs { .. } => "logs",
+		}
+	}
+}
+
+/// Filter state in the form saved across restarts. Pending logs already reported are not
+/// saved and may be reported again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum StoredPollFilter {
+	/// See `PollFilter::Block`.
+	#[serde(rename_all = "camelCase")]
+	Block {
+		last_block_number: BlockNumber,
+		recent_reported_hashes: Vec<(BlockNumber, H256)>,
+	},
+	/// See `PollFilter::PendingTransaction`.
+	PendingTransaction {
+		hashes: Vec<H256>,
+	},
+	/// See `PollFilter::Logs`.
+	#[serde(rename_all = "camelCase")]
+	Logs {
+		block_number: BlockNumber,
+		last_block_hash: Option<H256>,
+		include_pending: bool,
+		from_block: RpcBlockNumber,
+		to_block: RpcBlockNumber,
+		address: Option<Vec<H160>>,
+		topics: Vec<Option<Vec<H256>>>,
+		limit: Option<usize>,
+	},
+}
+
+fn id_to_number(id: BlockId) -> RpcBlockNumber {
+	match id {
+		BlockId::Hash(hash) => RpcBlockNumber::Hash { hash, require_canonical: false },
+		BlockId::Number(number) => RpcBlockNumber::Num(number),
+		BlockId::Earliest => RpcBlockNumber::Earliest,
+		BlockId::Latest => RpcBlockNumber::Latest,
+	}
+}
+
+fn number_to_id(number: RpcBlockNumber) -> BlockId {
+	match number {
+		RpcBlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+		RpcBlockNumber::Num(number) => BlockId::Number(number),
+		RpcBlockNumber::Earliest => BlockId::Earliest,
+		RpcBlockNumber::Latest | RpcBlockNumber::Pending => BlockId::Latest,
+	}
+}
+
+impl<'a> From<&'a PollFilter> for StoredPollFilter {
+	fn from(filter: &'a PollFilter) -> Self {
+		match *filter {
+			PollFilter::Block { last_block_number, ref recent_reported_hashes } => StoredPollFilter::Block {
+				last_block_number,
+				recent_reported_hashes: recent_reported_hashes.iter().cloned().collect(),
+			},
+			PollFilter::PendingTransaction(ref hashes) => StoredPollFilter::PendingTransaction {
+				hashes: hashes.iter().cloned().collect(),
+			},
+			PollFilter::Logs { block_number, last_block_hash, ref filter, include_pending, .. } => StoredPollFilter::Logs {
+				block_number,
+				last_block_hash,
+				include_pending,
+				from_block: id_to_number(filter.from_block),
+				to_block: id_to_number(filter.to_block),
+				address: filter.address.clone(),
+				topics: filter.topics.clone(),
+				limit: filter.limit,
+			},
+		}
+	}
+}
+
+impl From<StoredPollFilter> for PollFilter {
+	fn from(filter: StoredPollFilter) -> Self {
+		match filter {
+			StoredPollFilter::Block { last_block_number, recent_reported_hashes } => PollFilter::Block {
+				last_block_number,
+				recent_reported_hashes: recent_reported_hashes.into_iter().collect(),
+			},
+			StoredPollFilter::PendingTransaction { hashes } => PollFilter::PendingTransaction(hashes.into_iter().collect()),
+			StoredPollFilter::Logs { block_number, last_block_hash, include_pending, from_block, to_block, address, topics, limit } => PollFilter::Logs {
+				block_number,
+				last_block_hash,
+				previous_logs: Default::default(),
+				filter: Filter {
+					from_block: number_to_id(from_block),
+					to_block: number_to_id(to_block),
+					address,
+					topics,
+					limit,
+				},
+				include_pending,
+			},
+		}
+	}
 }
 
 /// Returns only last `n` logs
@@ -16,71 +16,177 @@
 
 //! Indexes all rpc poll requests.
 
-use transient_hashmap::{TransientHashMap, Timer, StandardTimer};
+use std::collections::BTreeMap;
+
+use transient_hashmap::{Timer, StandardTimer};
+use v1::types::Origin;
 
 pub type PollId = usize;
 
+/// Limits on the polls kept by a `PollManager`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollLimits {
+	/// Seconds an unused poll is kept for. `0` keeps polls until they are removed.
+	pub lifetime: u32,
+	/// Maximal number of polls a single origin may have installed. `None` for no limit.
+	/// HTTP origins come from the request headers, so clients sending different headers get
+	/// separate quotas, and only `max_total` bounds them.
+	pub max_per_origin: Option<usize>,
+	/// Maximal number of polls installed by all origins together.
+	pub max_total: usize,
+}
+
+impl Default for PollLimits {
+	fn default() -> Self {
+		PollLimits {
+			lifetime: 60,
+			max_per_origin: None,
+			max_total: 10_000,
+		}
+	}
+}
+
+/// Description of an installed poll.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PollInfo {
+	/// Poll id.
+	pub id: PollId,
+	/// Origin of the request which created the poll.
+	pub origin: Origin,
+	/// When the poll was created, in seconds since the epoch.
+	pub created_at: i64,
+	/// Seconds since the poll was last used.
+	pub idle: i64,
+}
+
+struct Poll<F> {
+	filter: F,
+	origin: Origin,
+	created_at: i64,
+	last_used: i64,
+}
+
 /// Indexes all poll requests.
 ///
 /// Lazily garbage collects unused polls info.
 pub struct PollManager<F, T = StandardTimer> where T: Timer {
-	polls: TransientHashMap<PollId, F, T>,
+	polls: BTreeMap<PollId, Poll<F>>,
 	next_available_id: PollId,
+	limits: PollLimits,
+	timer: T,
 }
 
 impl<F> PollManager<F, StandardTimer> {
 	/// Creates new instance of indexer
-	pub fn new(lifetime: u32) -> Self {
-		PollManager::new_with_timer(Default::default(), lifetime)
+	pub fn new(limits: PollLimits) -> Self {
+		PollManager::new_with_timer(Default::default(), limits)
 	}
 }
 
 impl<F, T> PollManager<F, T> where T: Timer {
 
-	pub fn new_with_timer(timer: T, lifetime: u32) -> Self {
+	pub fn new_with_timer(timer: T, limits: PollLimits) -> Self {
 		PollManager {
-			polls: TransientHashMap::new_with_timer(lifetime, timer),
+			polls: BTreeMap::new(),
 			next_available_id: 0,
+			limits,
+			timer,
 		}
 	}
 
-	/// Returns id which can be used for new poll.
+	fn prune(&mut self) {
+		if self.limits.lifetime == 0 {
+			return;
+		}
+		let now = self.timer.get_time();
+		let lifetime = i64::from(self.limits.lifetime);
+		self.polls.retain(|_, poll| now - poll.last_used <= lifetime);
+	}
+
+	/// Returns id which can be used for new poll, or `None` if `origin` already has
+	/// as many polls as it is allowed to, or if as many polls as allowed are installed.
 	///
 	/// Stores information when last poll happend.
-	pub fn create_poll(&mut self, filter: F) -> PollId {
-		self.polls.prune();
+	pub fn create_poll(&mut self, origin: &Origin, filter: F) -> Option<PollId> {
+		self.prune();
+
+		if self.polls.len() >= self.limits.max_total {
+			return None;
+		}
+		if let Some(max) = self.limits.max_per_origin {
+			if self.polls.values().filter(|poll| poll.origin == *origin).count() >= max {
+				return None;
+			}
+		}
 
 		let id = self.next_available_id;
-		self.polls.insert(id, filter);
+		let now = self.timer.get_time();
+		self.polls.insert(id, Poll {
+			filter,
+			origin: origin.clone(),
+			created_at: now,
+			last_used: now,
+		});
 
 		self.next_available_id += 1;
-		id
+		Some(id)
+	}
+
+	/// Puts back a poll saved by an earlier instance, keeping its id.
+	pub fn restore_poll(&mut self, id: PollId, origin: Origin, created_at: i64, filter: F) {
+		let now = self.timer.get_time();
+		self.polls.insert(id, Poll {
+			filter,
+			origin,
+			created_at,
+			last_used: now,
+		});
+		if id >= self.next_available_id {
+			self.next_available_id = id + 1;
+		}
 	}
 
 	// Implementation is always using `poll_mut`
 	/// Get a reference to stored poll filter
 	pub fn poll(&mut self, id: &PollId) -> Option<&F> {
-		self.polls.prune();
-		self.polls.get(id)
+		self.poll_mut(id).map(|filter| &*filter)
 	}
 
 	/// Get a mutable reference to stored poll filter
 	pub fn poll_mut(&mut self, id: &PollId) -> Option<&mut F> {
-		self.polls.prune();
-		self.polls.get_mut(id)
+		self.prune();
+		let now = self.timer.get_time();
+		self.polls.get_mut(id).map(|poll| {
+			poll.last_used = now;
+			&mut poll.filter
+		})
 	}
 
 	/// Removes poll info.
 	pub fn remove_poll(&mut self, id: &PollId) -> bool {
 		self.polls.remove(id).is_some()
 	}
+
+	/// Installed polls, ordered by id.
+	pub fn polls(&mut self) -> Vec<(PollInfo, &F)> {
+		self.prune();
+		let now = self.timer.get_time();
+		self.polls.iter().map(|(id, poll)| (PollInfo {
+			id: *id,
+			origin: poll.origin.clone(),
+			created_at: poll.created_at,
+			idle: now - poll.last_used,
+		}, &poll.filter)).collect()
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use std::cell::Cell;
+	use ethereum_types::H256;
 	use transient_hashmap::Timer;
-	use v1::helpers::PollManager;
+	use v1::helpers::{PollLimits, PollManager};
+	use v1::types::Origin;
 
 	struct TestTimer<'a> {
 		time: &'a Cell<i64>,
@@ -92,6 +198,10 @@ mod tests {
 		}
 	}
 
+	fn limits(lifetime: u32, max_per_origin: Option<usize>) -> PollLimits {
+		PollLimits { lifetime, max_per_origin, max_total: 100 }
+	}
+
 	#[test]
 	fn test_poll_indexer() {
 		let time = Cell::new(0);
@@ -99,9 +209,10 @@ mod tests {
 			time: &time,
 		};
 
-		let mut indexer = PollManager::new_with_timer(timer,60);
-		assert_eq!(indexer.create_poll(20), 0);
-		assert_eq!(indexer.create_poll(20), 1);
+		let origin = Origin::Unknown;
+		let mut indexer = PollManager::new_with_timer(timer, limits(60, None));
+		assert_eq!(indexer.create_poll(&origin, 20), Some(0));
+		assert_eq!(indexer.create_poll(&origin, 20), Some(1));
 
 		time.set(10);
 		*indexer.poll_mut(&0).unwrap() = 21;
@@ -120,4 +231,70 @@ mod tests {
 		assert!(indexer.poll(&1).is_none());
 	}
 
+	#[test]
+	fn should_keep_polls_without_lifetime() {
+		let time = Cell::new(0);
+		let mut indexer = PollManager::new_with_timer(TestTimer { time: &time }, limits(0, None));
+		assert_eq!(indexer.create_poll(&Origin::Unknown, 20), Some(0));
+
+		time.set(1_000_000);
+		assert_eq!(*indexer.poll(&0).unwrap(), 20);
+	}
+
+	#[test]
+	fn should_limit_polls_per_origin() {
+		let time = Cell::new(0);
+		let mut indexer = PollManager::new_with_timer(TestTimer { time: &time }, limits(60, Some(2)));
+		let a = Origin::Ws { session: H256::from_low_u64_be(1) };
+		let b = Origin::Ipc(H256::from_low_u64_be(1));
+		assert_eq!(indexer.create_poll(&a, 1), Some(0));
+		assert_eq!(indexer.create_poll(&a, 2), Some(1));
+		assert_eq!(indexer.create_poll(&a, 3), None);
+		assert_eq!(indexer.create_poll(&b, 4), Some(2));
+
+		// expired and removed polls don't count
+		indexer.remove_poll(&0);
+		assert_eq!(indexer.create_poll(&a, 5), Some(3));
+		time.set(100);
+		assert_eq!(indexer.create_poll(&a, 6), Some(4));
+	}
+
+	#[test]
+	fn should_limit_polls_of_all_origins() {
+		let time = Cell::new(0);
+		let mut indexer = PollManager::new_with_timer(TestTimer { time: &time }, PollLimits {
+			lifetime: 0,
+			max_per_origin: Some(2),
+			max_total: 3,
+		});
+		let no_headers = Origin::Rpc("unknown origin / unknown agent".into());
+		assert_eq!(indexer.create_poll(&no_headers, 1), Some(0));
+		assert_eq!(indexer.create_poll(&no_headers, 2), Some(1));
+		assert_eq!(indexer.create_poll(&no_headers, 3), None);
+
+		// HTTP clients sending other headers have their own quota, but all of them together
+		// can't install more polls than allowed.
+		assert_eq!(indexer.create_poll(&Origin::Rpc("http://parity.io / curl".into()), 4), Some(2));
+		assert_eq!(indexer.create_poll(&Origin::Ws { session: H256::from_low_u64_be(1) }, 5), None);
+
+		indexer.remove_poll(&0);
+		assert_eq!(indexer.create_poll(&Origin::Ws { session: H256::from_low_u64_be(1) }, 5), Some(3));
+	}
+
+	#[test]
+	fn should_list_and_restore_polls() {
+		let time = Cell::new(100);
+		let mut indexer = PollManager::new_with_timer(TestTimer { time: &time }, limits(60, None));
+		indexer.restore_poll(7, Origin::Rpc("a".into()), 40, 1);
+		time.set(110);
+		assert_eq!(indexer.create_poll(&Origin::Unknown, 2), Some(8));
+
+		let polls = indexer.polls();
+		assert_eq!(polls.len(), 2);
+		assert_eq!(polls[0].0.id, 7);
+		assert_eq!(polls[0].0.origin, Origin::Rpc("a".into()));
+		assert_eq!(polls[0].0.created_at, 40);
+		assert_eq!(polls[0].0.idle, 10);
+		assert_eq!(*polls[1].1, 2);
+	}
 }
@@ -0,0 +1,448 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Client of an external signer implementing the Clef external API.
+//!
+//! Private keys never enter the node: account listing and signing requests are
+//! forwarded to the signer over HTTP(S), authenticated with a bearer token, or over
+//! a local IPC socket, and the signer decides whether to approve them.
+//!
+//! Only the documented Clef methods are used. Clef can't sign raw hashes, so neither
+//! `SignMessage::Hash` requests nor block sealing are supported through it.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use bytes::Bytes;
+use crypto::publickey::Signature;
+use ethereum_types::{U256, Address};
+use fetch::{self, Fetch, Client as FetchClient, Method, BodyReader, Request, Url};
+use futures::Future;
+use http::hyper::header::{self, HeaderValue};
+use parking_lot::RwLock;
+use rlp;
+use rustc_hex::{FromHex, ToHex};
+use serde_json::{self, json, Value};
+use types::transaction::{Transaction, Action, SignedTransaction, UnverifiedTransaction};
+
+use jsonrpc_core::Result;
+use v1::helpers::{errors, FilledTransactionRequest};
+use v1::helpers::dispatch::{self, WithToken, SignWith, SignMessage};
+
+/// Content type of messages signed with the Ethereum message prefix (`eth_sign`).
+const PLAIN_TEXT_CONTENT_TYPE: &str = "text/plain";
+
+/// Maximum time to wait for the signer, including a manual approval.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum number of requests waiting for the signer at once.
+///
+/// Requests block the calling thread, so they are refused beyond this limit instead
+/// of exhausting the RPC threads.
+const MAX_PENDING_REQUESTS: usize = 4;
+
+/// Channel used to reach the external signer.
+pub trait Transport: Send + Sync {
+	/// Send a serialized JSON-RPC request and return the serialized response.
+	fn request(&self, body: String) -> ::std::result::Result<String, String>;
+}
+
+impl<F> Transport for F where F: Fn(String) -> ::std::result::Result<String, String> + Send + Sync {
+	fn request(&self, body: String) -> ::std::result::Result<String, String> {
+		(self)(body)
+	}
+}
+
+/// HTTP(S) transport authenticated with an optional bearer token.
+pub struct HttpTransport {
+	client: FetchClient,
+	url: Url,
+	token: Option<String>,
+}
+
+impl HttpTransport {
+	/// Create new transport posting requests to `url`.
+	pub fn new(client: FetchClient, url: Url, token: Option<String>) -> Self {
+		HttpTransport { client, url, token }
+	}
+}
+
+impl Transport for HttpTransport {
+	fn request(&self, body: String) -> ::std::result::Result<String, String> {
+		let mut request = Request::new(self.url.clone(), Method::POST)
+			.with_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+			.with_body(body);
+		if let Some(ref token) = self.token {
+			let value = HeaderValue::from_str(&format!("Bearer {}", token))
+				.map_err(|e| format!("Invalid remote signer token: {}", e))?;
+			request.headers_mut().insert(header::AUTHORIZATION, value);
+		}
+
+		let abort = fetch::Abort::default().with_max_duration(REQUEST_TIMEOUT);
+		let response = self.client.fetch(request, abort).wait()
+			.map_err(|e| format!("Remote signer request failed: {:?}", e))?;
+		if !response.is_success() {
+			return Err(format!("Remote signer responded with {}", response.status()));
+		}
+
+		let mut result = String::new();
+		BodyReader::new(response).read_to_string(&mut result)
+			.map_err(|e| format!("Unable to read remote signer response: {}", e))?;
+		Ok(result)
+	}
+}
+
+/// Transport over a local IPC socket, authenticated by the socket's file permissions.
+pub struct IpcTransport {
+	path: PathBuf,
+}
+
+impl IpcTransport {
+	/// Create new transport connecting to the socket at `path`.
+	pub fn new(path: PathBuf) -> Self {
+		IpcTransport { path }
+	}
+}
+
+impl Transport for IpcTransport {
+	#[cfg(unix)]
+	fn request(&self, body: String) -> ::std::result::Result<String, String> {
+		use std::os::unix::net::UnixStream;
+
+		let mut stream = UnixStream::connect(&self.path)
+			.map_err(|e| format!("Unable to connect to remote signer at {}: {}", self.path.display(), e))?;
+		stream.set_read_timeout(Some(REQUEST_TIMEOUT))
+			.and_then(|_| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+			.map_err(|e| format!("Unable to configure remote signer connection: {}", e))?;
+		stream.write_all(body.as_bytes())
+			.and_then(|_| stream.write_all(b"\n"))
+			.map_err(|e| format!("Unable to send request to remote signer: {}", e))?;
+
+		let mut response = String::new();
+		BufReader::new(stream).read_line(&mut response)
+			.map_err(|e| format!("Unable to read remote signer response: {}", e))?;
+		Ok(response)
+	}
+
+	#[cfg(not(unix))]
+	fn request(&self, _body: String) -> ::std::result::Result<String, String> {
+		Err(format!("IPC remote signer at {} is only supported on unix platforms", self.path.display()))
+	}
+}
+
+/// Client of an external signer.
+pub struct RemoteSigner {
+	transport: Box<dyn Transport>,
+	next_id: AtomicUsize,
+	pending: AtomicUsize,
+	accounts: RwLock<Vec<Address>>,
+}
+
+impl RemoteSigner {
+	/// Create new client using given transport.
+	pub fn new(transport: Box<dyn Transport>) -> Self {
+		RemoteSigner {
+			transport,
+			next_id: AtomicUsize::new(1),
+			pending: AtomicUsize::new(0),
+			accounts: RwLock::new(Vec::new()),
+		}
+	}
+
+	/// Create new client for an `http://` or `https://` URL or a path to an IPC socket.
+	pub fn connect(endpoint: &str, token: Option<String>, fetch: FetchClient) -> ::std::result::Result<Self, String> {
+		let transport: Box<dyn Transport> = if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+			let url = Url::from_str(endpoint).map_err(|e| format!("Invalid remote signer URL {}: {}", endpoint, e))?;
+			Box::new(HttpTransport::new(fetch, url, token))
+		} else {
+			if token.is_some() {
+				warn!(target: "remote_signer", "Remote signer token is ignored for IPC endpoint {}", endpoint);
+			}
+			Box::new(IpcTransport::new(endpoint.into()))
+		};
+
+		Ok(RemoteSigner::new(transport))
+	}
+
+	fn call(&self, method: &str, params: Value) -> ::std::result::Result<Value, String> {
+		let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+		let request = json!({
+			"jsonrpc": "2.0",
+			"id": id,
+			"method": method,
+			"params": params,
+		});
+
+		if self.pending.fetch_add(1, Ordering::SeqCst) >= MAX_PENDING_REQUESTS {
+			self.pending.fetch_sub(1, Ordering::SeqCst);
+			return Err(format!("Too many requests are waiting for the remote signer, {} refused", method));
+		}
+
+		trace!(target: "remote_signer", "Sending {} request #{}", method, id);
+		let response = self.transport.request(request.to_string());
+		self.pending.fetch_sub(1, Ordering::SeqCst);
+		let response = response?;
+		let mut response: Value = serde_json::from_str(&response)
+			.map_err(|e| format!("Invalid remote signer response: {}", e))?;
+
+		if let Some(error) = response.get("error") {
+			let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+			return Err(format!("Remote signer rejected {}: {}", method, message));
+		}
+
+		match response.get_mut("result") {
+			Some(result) => Ok(result.take()),
+			None => Err(format!("Remote signer response to {} has no result", method)),
+		}
+	}
+
+	/// Fetch accounts managed by the signer.
+	pub fn accounts(&self) -> ::std::result::Result<Vec<Address>, String> {
+		let result = self.call("account_list", json!([]))?;
+		let accounts: Vec<Address> = serde_json::from_value(result)
+			.map_err(|e| format!("Invalid account list: {}", e))?;
+		*self.accounts.write() = accounts.clone();
+		Ok(accounts)
+	}
+
+	/// Accounts returned by the last successful `account_list` request.
+	pub fn known_accounts(&self) -> Vec<Address> {
+		self.accounts.read().clone()
+	}
+
+	/// Ask the signer to sign a transaction from `from`.
+	///
+	/// The signed transaction is rejected if it doesn't come from `from` or uses a different nonce.
+	pub fn sign_transaction(&self, from: Address, transaction: Transaction, chain_id: Option<u64>) -> ::std::result::Result<SignedTransaction, String> {
+		let mut args = json!({
+			"from": from,
+			"gas": transaction.gas,
+			"gasPrice": transaction.gas_price,
+			"value": transaction.value,
+			"nonce": transaction.nonce,
+			"data": to_hex(&transaction.data),
+		});
+		if let Action::Call(to) = transaction.action {
+			args["to"] = json!(to);
+		}
+		if let Some(chain_id) = chain_id {
+			args["chainId"] = json!(U256::from(chain_id));
+		}
+
+		let result = self.call("account_signTransaction", json!([args]))?;
+		let raw = result.get("raw").and_then(Value::as_str)
+			.ok_or_else(|| "Remote signer response has no raw transaction".to_owned())
+			.and_then(from_hex)?;
+		let unverified: UnverifiedTransaction = rlp::decode(&raw)
+			.map_err(|e| format!("Invalid signed transaction: {}", e))?;
+		let signed = SignedTransaction::new(unverified)
+			.map_err(|e| format!("Invalid transaction signature: {}", e))?;
+
+		if signed.sender() != from {
+			return Err(format!("Transaction was signed by {:?} instead of {:?}", signed.sender(), from));
+		}
+		if signed.nonce != transaction.nonce {
+			return Err(format!("Transaction nonce was changed from {} to {}", transaction.nonce, signed.nonce));
+		}
+		if signed.chain_id() != chain_id {
+			return Err(format!("Transaction was signed for chain {:?} instead of {:?}", signed.chain_id(), chain_id));
+		}
+
+		Ok(signed)
+	}
+
+	/// Ask the signer to sign `data` of given content type with the key of `address`.
+	pub fn sign_data(&self, address: Address, content_type: &str, data: &[u8]) -> ::std::result::Result<Signature, String> {
+		let result = self.call("account_signData", json!([content_type, address, to_hex(data)]))?;
+		let signature = result.as_str()
+			.ok_or_else(|| "Remote signer response is not a signature".to_owned())
+			.and_then(from_hex)?;
+		if signature.len() != 65 {
+			return Err(format!("Invalid signature length: {}", signature.len()));
+		}
+
+		Ok(Signature::from_electrum(&signature))
+	}
+
+	/// Ask the signer to sign a message with the Ethereum prefix.
+	pub fn sign_message(&self, address: Address, data: &[u8]) -> ::std::result::Result<Signature, String> {
+		self.sign_data(address, PLAIN_TEXT_CONTENT_TYPE, data)
+	}
+
+}
+
+fn to_hex(data: &[u8]) -> String {
+	format!("0x{}", data.to_hex::<String>())
+}
+
+fn from_hex(data: &str) -> ::std::result::Result<Vec<u8>, String> {
+	let data = if data.starts_with("0x") { &data[2..] } else { data };
+	data.from_hex().map_err(|e| format!("Invalid hex in remote signer response: {}", e))
+}
+
+impl dispatch::Accounts for RemoteSigner {
+	fn sign_transaction(&self, filled: FilledTransactionRequest, chain_id: Option<u64>, nonce: U256, _password: SignWith) -> Result<WithToken<SignedTransaction>> {
+		let t = Transaction {
+			nonce,
+			action: filled.to.map_or(Action::Create, Action::Call),
+			gas: filled.gas,
+			gas_price: filled.gas_price,
+			value: filled.value,
+			data: filled.data,
+		};
+
+		RemoteSigner::sign_transaction(self, filled.from, t, chain_id)
+			.map(WithToken::No)
+			.map_err(errors::remote_signer)
+	}
+
+	fn sign_message(&self, address: Address, _password: SignWith, message: SignMessage) -> Result<WithToken<Signature>> {
+		match message {
+			SignMessage::Data(data) => RemoteSigner::sign_message(self, address, &data)
+				.map(WithToken::No)
+				.map_err(errors::remote_signer),
+			SignMessage::Hash(_) => Err(errors::unsupported("Signing raw hashes is not supported by the remote signer.", None)),
+		}
+	}
+
+	fn decrypt(&self, _address: Address, _password: SignWith, _data: Bytes) -> Result<WithToken<Bytes>> {
+		Err(errors::unsupported("Decryption is not supported by the remote signer.", None))
+	}
+
+	fn supports_prospective_signing(&self, _address: &Address, _password: &SignWith) -> bool {
+		// Every request may wait for a manual approval, don't hold nonces reserved meanwhile.
+		false
+	}
+
+	fn default_account(&self) -> Option<Address> {
+		self.known_accounts().first().cloned()
+	}
+
+	fn is_unlocked(&self, address: &Address) -> bool {
+		// The signer decides on its own whether to approve requests.
+		self.known_accounts().contains(address)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use crypto::publickey::{self, Generator, Random, recover};
+	use hash::keccak;
+	use v1::helpers::dispatch::{Accounts, eth_data_hash};
+	use super::*;
+
+	fn signer<F>(respond: F) -> RemoteSigner where
+		F: Fn(Value) -> Value + Send + Sync + 'static,
+	{
+		RemoteSigner::new(Box::new(move |request: String| {
+			let request: Value = serde_json::from_str(&request).unwrap();
+			let id = request["id"].clone();
+			let mut response = respond(request);
+			response["jsonrpc"] = json!("2.0");
+			response["id"] = id;
+			Ok(response.to_string())
+		}))
+	}
+
+	#[test]
+	fn should_list_accounts() {
+		let account = Address::from_low_u64_be(5);
+		let calls = Arc::new(AtomicUsize::new(0));
+		let c = calls.clone();
+		let signer = signer(move |request| {
+			c.fetch_add(1, Ordering::SeqCst);
+			assert_eq!(request["method"], "account_list");
+			json!({ "result": [account] })
+		});
+
+		assert_eq!(Accounts::default_account(&signer), None);
+		assert_eq!(signer.accounts(), Ok(vec![account]));
+		assert_eq!(Accounts::default_account(&signer), Some(account));
+		assert!(Accounts::is_unlocked(&signer, &account));
+		assert_eq!(calls.load(Ordering::SeqCst), 1);
+	}
+
+	#[test]
+	fn should_sign_transaction_remotely() {
+		let keypair = Random.generate();
+		let secret = keypair.secret().clone();
+		let signer = signer(move |request| {
+			assert_eq!(request["method"], "account_signTransaction");
+			let args = &request["params"][0];
+			let t = Transaction {
+				nonce: serde_json::from_value(args["nonce"].clone()).unwrap(),
+				action: Action::Call(serde_json::from_value(args["to"].clone()).unwrap()),
+				gas: serde_json::from_value(args["gas"].clone()).unwrap(),
+				gas_price: serde_json::from_value(args["gasPrice"].clone()).unwrap(),
+				value: serde_json::from_value(args["value"].clone()).unwrap(),
+				data: from_hex(args["data"].as_str().unwrap()).unwrap(),
+			};
+			let signed = t.sign(&secret, Some(1));
+			json!({ "result": { "raw": to_hex(&rlp::encode(&signed)) } })
+		});
+		let t = Transaction {
+			nonce: 3.into(),
+			action: Action::Call(Address::from_low_u64_be(1)),
+			gas: 21_000.into(),
+			gas_price: 1.into(),
+			value: 10.into(),
+			data: vec![1, 2],
+		};
+
+		let signed = signer.sign_transaction(keypair.address(), t.clone(), Some(1)).unwrap();
+		assert_eq!(signed.sender(), keypair.address());
+		assert_eq!(signed.as_unsigned(), &t);
+
+		assert!(signer.sign_transaction(Address::from_low_u64_be(2), t, Some(1)).is_err());
+	}
+
+	#[test]
+	fn should_sign_messages_remotely() {
+		let keypair = Random.generate();
+		let secret = keypair.secret().clone();
+		let signer = signer(move |request| {
+			assert_eq!(request["method"], "account_signData");
+			if request["params"][0] != PLAIN_TEXT_CONTENT_TYPE {
+				return json!({ "error": { "code": -32000, "message": "unsupported content type" } });
+			}
+			let data = from_hex(request["params"][2].as_str().unwrap()).unwrap();
+			let signature = publickey::sign(&secret, &eth_data_hash(data)).unwrap();
+			json!({ "result": to_hex(&signature.into_electrum()) })
+		});
+
+		let signature = signer.sign_message(keypair.address(), b"hello").unwrap();
+		assert_eq!(recover(&signature, &eth_data_hash(b"hello".to_vec())).unwrap(), *keypair.public());
+
+		let hash = SignMessage::Hash(keccak("block"));
+		assert!(Accounts::sign_message(&signer, keypair.address(), SignWith::Nothing, hash).is_err());
+		assert!(signer.sign_data(keypair.address(), "data/typed", &[]).is_err());
+	}
+
+	#[test]
+	fn should_refuse_requests_beyond_the_pending_limit() {
+		let signer = signer(|_| json!({ "result": [] }));
+		signer.pending.store(MAX_PENDING_REQUESTS, Ordering::SeqCst);
+		assert!(signer.accounts().is_err());
+
+		signer.pending.store(0, Ordering::SeqCst);
+		assert_eq!(signer.accounts(), Ok(vec![]));
+	}
+}
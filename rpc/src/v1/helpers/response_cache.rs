@@ -0,0 +1,295 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Coalescing of identical read requests.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use ethereum_types::H256;
+use jsonrpc_core::{self as core, Error, MethodCall, Output, Params, Value};
+use jsonrpc_core::futures::Future;
+use jsonrpc_core::futures::future::{Either, Shared};
+use parking_lot::Mutex;
+use serde_json;
+use stats::{PrometheusMetrics, PrometheusRegistry};
+
+/// Read methods whose result depends only on their parameters and the chain head.
+pub const DEFAULT_METHODS: &[&str] = &[
+	"eth_call",
+	"eth_estimateGas",
+	"eth_getBalance",
+	"eth_getCode",
+	"eth_getStorageAt",
+	"eth_getTransactionCount",
+];
+
+/// Maximal number of responses kept at once.
+const MAX_ENTRIES: usize = 4096;
+
+type Execution = Shared<Box<dyn Future<Item = Result<Value, Error>, Error = ()> + Send>>;
+
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+	method: String,
+	params: String,
+	head: H256,
+}
+
+struct Entry {
+	execution: Execution,
+	started: Instant,
+}
+
+/// Runs identical concurrent calls of read methods at the same chain head once and answers
+/// repeats arriving within `ttl` of the first call from its response.
+pub struct ResponseCache {
+	head: Box<dyn Fn() -> H256 + Send + Sync>,
+	methods: HashSet<String>,
+	ttl: Duration,
+	entries: Mutex<HashMap<Key, Entry>>,
+	hits: AtomicU64,
+	misses: AtomicU64,
+}
+
+impl ResponseCache {
+	/// Cache responses of `methods` for `ttl`, keyed by the chain head returned by `head`.
+	/// With a zero `ttl` only concurrent calls are coalesced.
+	pub fn new<F>(methods: &[&str], ttl: Duration, head: F) -> Self where
+		F: Fn() -> H256 + Send + Sync + 'static,
+	{
+		ResponseCache {
+			head: Box::new(head),
+			methods: methods.iter().map(|method| (*method).to_owned()).collect(),
+			ttl,
+			entries: Mutex::new(HashMap::new()),
+			hits: AtomicU64::new(0),
+			misses: AtomicU64::new(0),
+		}
+	}
+
+	/// Answer `call` from a running or recent execution of the same call, or run it with `next`.
+	pub fn on_call<F, X>(&self, call: MethodCall, next: F) -> Either<core::middleware::NoopCallFuture, X> where
+		F: FnOnce(MethodCall) -> X,
+		X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+	{
+		if !self.methods.contains(&call.method) || depends_on_pending(&call.params) {
+			return Either::B(next(call));
+		}
+		let key = match serde_json::to_string(&call.params) {
+			Ok(params) => Key { method: call.method.clone(), params, head: (self.head)() },
+			Err(_) => return Either::B(next(call)),
+		};
+		let (id, jsonrpc) = (call.id.clone(), call.jsonrpc);
+
+		let execution = {
+			let mut entries = self.entries.lock();
+			match entries.get(&key).filter(|entry| self.is_fresh(entry)) {
+				Some(entry) => {
+					self.hits.fetch_add(1, Ordering::Relaxed);
+					entry.execution.clone()
+				},
+				None => {
+					self.misses.fetch_add(1, Ordering::Relaxed);
+					let execution: Box<dyn Future<Item = _, Error = _> + Send> = Box::new(next(call).map(|output| match output {
+						Some(Output::Success(success)) => Ok(success.result),
+						Some(Output::Failure(failure)) => Err(failure.error),
+						None => Err(Error::internal_error()),
+					}));
+					let execution = execution.shared();
+
+					if entries.len() >= MAX_ENTRIES {
+						entries.retain(|_, entry| self.is_fresh(entry));
+					}
+					if entries.len() < MAX_ENTRIES {
+						entries.insert(key, Entry { execution: execution.clone(), started: Instant::now() });
+					}
+					execution
+				},
+			}
+		};
+
+		Either::A(Box::new(execution.then(move |result| match result {
+			Ok(result) => Ok(Some(Output::from((*result).clone(), id, jsonrpc))),
+			Err(_) => Err(()),
+		})))
+	}
+
+	/// Running executions are joined however long they take, finished ones are reused for `ttl`
+	/// unless they failed.
+	fn is_fresh(&self, entry: &Entry) -> bool {
+		match entry.execution.peek() {
+			None => true,
+			Some(Ok(ref result)) => result.is_ok() && entry.started.elapsed() < self.ttl,
+			Some(Err(_)) => false,
+		}
+	}
+}
+
+/// Whether the call refers to the pending block, which changes without a new chain head,
+/// anywhere in its parameters, e.g. in an EIP-1898 block object.
+fn depends_on_pending(params: &Params) -> bool {
+	fn mentions_pending(value: &Value) -> bool {
+		match value {
+			Value::String(value) => value == "pending",
+			Value::Array(values) => values.iter().any(mentions_pending),
+			Value::Object(values) => values.values().any(mentions_pending),
+			_ => false,
+		}
+	}
+
+	match params {
+		Params::Array(params) => params.iter().any(mentions_pending),
+		Params::Map(params) => params.values().any(mentions_pending),
+		Params::None => false,
+	}
+}
+
+impl PrometheusMetrics for ResponseCache {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		r.register_counter("rpc_coalesced_calls", "Read calls answered by another execution of the same call", self.hits.load(Ordering::Relaxed));
+		r.register_counter("rpc_coalescable_calls_executed", "Read calls which could be coalesced but had to be executed", self.misses.load(Ordering::Relaxed));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::Duration;
+	use ethereum_types::H256;
+	use jsonrpc_core::{Error, Id, MethodCall, Output, Params, Success, Value, Version};
+	use jsonrpc_core::futures::{future, Future};
+	use jsonrpc_core::futures::future::Either;
+	use jsonrpc_core::futures::sync::oneshot;
+	use parking_lot::Mutex;
+	use super::ResponseCache;
+
+	fn call(id: u64, method: &str, params: Vec<Value>) -> MethodCall {
+		MethodCall {
+			jsonrpc: Some(Version::V2),
+			method: method.into(),
+			params: Params::Array(params),
+			id: Id::Num(id),
+		}
+	}
+
+	fn respond(executions: &Arc<AtomicUsize>, call: MethodCall) -> future::FutureResult<Option<Output>, ()> {
+		executions.fetch_add(1, Ordering::SeqCst);
+		future::ok(Some(Output::from(Ok(Value::String(call.method)), call.id, call.jsonrpc)))
+	}
+
+	fn result(output: Option<Output>) -> (Id, Value) {
+		match output {
+			Some(Output::Success(Success { id, result, .. })) => (id, result),
+			other => panic!("Unexpected output {:?}", other),
+		}
+	}
+
+	#[test]
+	fn coalesces_concurrent_calls() {
+		let cache = ResponseCache::new(&["eth_call"], Duration::from_secs(0), || H256::zero());
+		let (sender, receiver) = oneshot::channel::<()>();
+		let receiver = Arc::new(Mutex::new(Some(receiver)));
+
+		let first = match cache.on_call(call(1, "eth_call", vec![Value::String("latest".into())]), |call| {
+			let receiver = receiver.lock().take().unwrap();
+			receiver.then(move |_| Ok(Some(Output::from(Ok(Value::Bool(true)), call.id, call.jsonrpc))))
+		}) {
+			Either::A(future) => future,
+			Either::B(_) => panic!("eth_call should be coalesced"),
+		};
+		let second = match cache.on_call(call(2, "eth_call", vec![Value::String("latest".into())]), |_| -> future::FutureResult<Option<Output>, ()> {
+			panic!("the running call should be joined")
+		}) {
+			Either::A(future) => future,
+			Either::B(_) => panic!("eth_call should be coalesced"),
+		};
+
+		sender.send(()).unwrap();
+		assert_eq!(result(second.wait().unwrap()), (Id::Num(2), Value::Bool(true)));
+		assert_eq!(result(first.wait().unwrap()), (Id::Num(1), Value::Bool(true)));
+	}
+
+	#[test]
+	fn reuses_responses_until_head_changes() {
+		let head = Arc::new(Mutex::new(H256::zero()));
+		let cache = {
+			let head = head.clone();
+			ResponseCache::new(&["eth_getBalance"], Duration::from_secs(60), move || *head.lock())
+		};
+		let executions = Arc::new(AtomicUsize::new(0));
+		let balance = |id| call(id, "eth_getBalance", vec![Value::String("0x01".into()), Value::String("latest".into())]);
+		let run = |call: MethodCall| match cache.on_call(call, |call| respond(&executions, call)) {
+			Either::A(future) => future.wait().unwrap(),
+			Either::B(future) => future.wait().unwrap(),
+		};
+
+		assert_eq!(result(run(balance(1))).0, Id::Num(1));
+		assert_eq!(result(run(balance(2))).0, Id::Num(2));
+		assert_eq!(executions.load(Ordering::SeqCst), 1);
+
+		*head.lock() = H256::from_low_u64_be(1);
+		run(balance(3));
+		assert_eq!(executions.load(Ordering::SeqCst), 2);
+	}
+
+	#[test]
+	fn skips_other_methods_and_pending_calls() {
+		let cache = ResponseCache::new(&["eth_call"], Duration::from_secs(60), || H256::zero());
+		let executions = Arc::new(AtomicUsize::new(0));
+
+		for _ in 0..2 {
+			match cache.on_call(call(1, "eth_sendRawTransaction", vec![]), |call| respond(&executions, call)) {
+				Either::A(_) => panic!("eth_sendRawTransaction must not be coalesced"),
+				Either::B(future) => { future.wait().unwrap(); },
+			}
+			match cache.on_call(call(1, "eth_call", vec![Value::String("pending".into())]), |call| respond(&executions, call)) {
+				Either::A(_) => panic!("calls on the pending block must not be coalesced"),
+				Either::B(future) => { future.wait().unwrap(); },
+			}
+			let block = Value::Object(vec![("blockNumber".to_owned(), Value::String("pending".into()))].into_iter().collect());
+			match cache.on_call(call(1, "eth_call", vec![Value::Object(Default::default()), block]), |call| respond(&executions, call)) {
+				Either::A(_) => panic!("calls on the pending block must not be coalesced"),
+				Either::B(future) => { future.wait().unwrap(); },
+			}
+		}
+		assert_eq!(executions.load(Ordering::SeqCst), 6);
+	}
+
+	#[test]
+	fn does_not_reuse_errors() {
+		let cache = ResponseCache::new(&["eth_call"], Duration::from_secs(60), || H256::zero());
+		let executions = Arc::new(AtomicUsize::new(0));
+		let run = |id| match cache.on_call(call(id, "eth_call", vec![Value::String("latest".into())]), |call| {
+			executions.fetch_add(1, Ordering::SeqCst);
+			future::ok(Some(Output::from(Err(Error::internal_error()), call.id, call.jsonrpc)))
+		}) {
+			Either::A(future) => future.wait().unwrap(),
+			Either::B(_) => panic!("eth_call should be coalesced"),
+		};
+
+		for id in 0..2 {
+			match run(id) {
+				Some(Output::Failure(failure)) => assert_eq!(failure.id, Id::Num(id)),
+				other => panic!("Unexpected output {:?}", other),
+			}
+		}
+		assert_eq!(executions.load(Ordering::SeqCst), 2);
+	}
+}
@@ -0,0 +1,169 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Encoding of ERC-20 calls and a cache of token metadata.
+
+use std::collections::HashMap;
+
+use ethereum_types::{H160, U256};
+use parking_lot::Mutex;
+
+/// Selector of `balanceOf(address)`.
+const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+/// Selector of `decimals()`.
+const DECIMALS: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+/// Selector of `symbol()`.
+const SYMBOL: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+
+/// Maximal number of tokens whose metadata is kept at once.
+const MAX_ENTRIES: usize = 4096;
+
+/// Input of a `balanceOf` call for `owner`.
+pub fn balance_of(owner: &H160) -> Vec<u8> {
+	let mut data = Vec::with_capacity(36);
+	data.extend_from_slice(&BALANCE_OF);
+	data.extend_from_slice(&[0u8; 12]);
+	data.extend_from_slice(owner.as_bytes());
+	data
+}
+
+/// Input of a `decimals` call.
+pub fn decimals() -> Vec<u8> {
+	DECIMALS.to_vec()
+}
+
+/// Input of a `symbol` call.
+pub fn symbol() -> Vec<u8> {
+	SYMBOL.to_vec()
+}
+
+/// Decode a single `uint256` output.
+pub fn decode_uint(output: &[u8]) -> Option<U256> {
+	match output.len() {
+		32 => Some(U256::from_big_endian(output)),
+		_ => None,
+	}
+}
+
+/// Decode the output of `decimals`.
+pub fn decode_decimals(output: &[u8]) -> Option<u8> {
+	decode_uint(output).filter(|decimals| *decimals <= U256::from(u8::max_value())).map(|decimals| decimals.low_u32() as u8)
+}
+
+/// Decode the output of `symbol`, returned either as a `string` or, by older tokens, as a
+/// zero-padded `bytes32`.
+pub fn decode_symbol(output: &[u8]) -> Option<String> {
+	let bytes = if output.len() == 32 {
+		let len = output.iter().position(|b| *b == 0).unwrap_or(32);
+		&output[..len]
+	} else {
+		let offset = decode_offset(output, 0)?;
+		let len = decode_offset(output, offset)?;
+		output.get(offset + 32..offset + 32 + len)?
+	};
+	String::from_utf8(bytes.to_vec()).ok().filter(|symbol| !symbol.is_empty())
+}
+
+fn decode_offset(output: &[u8], at: usize) -> Option<usize> {
+	let word = output.get(at..at.checked_add(32)?)?;
+	let value = U256::from_big_endian(word);
+	if value > U256::from(output.len()) {
+		return None;
+	}
+	Some(value.low_u64() as usize)
+}
+
+/// Decimals and symbol of a token.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TokenMetadata {
+	/// Number of decimals of balances.
+	pub decimals: Option<u8>,
+	/// Ticker symbol.
+	pub symbol: Option<String>,
+}
+
+/// Metadata of tokens, which their contracts don't change once deployed.
+#[derive(Default)]
+pub struct TokenMetadataCache {
+	entries: Mutex<HashMap<H160, TokenMetadata>>,
+}
+
+impl TokenMetadataCache {
+	/// Cached metadata of `token`.
+	pub fn get(&self, token: &H160) -> Option<TokenMetadata> {
+		self.entries.lock().get(token).cloned()
+	}
+
+	/// Remember the metadata of `token`. Contracts which don't report their decimals may not be
+	/// deployed yet and are looked up again next time.
+	pub fn insert(&self, token: H160, metadata: TokenMetadata) {
+		if metadata.decimals.is_none() {
+			return;
+		}
+		let mut entries = self.entries.lock();
+		if entries.len() < MAX_ENTRIES {
+			entries.insert(token, metadata);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rustc_hex::FromHex;
+
+	#[test]
+	fn should_encode_balance_of() {
+		let data = balance_of(&H160::from_low_u64_be(0xff));
+		assert_eq!(data.len(), 36);
+		assert_eq!(&data[..4], &BALANCE_OF);
+		assert_eq!(data[35], 0xff);
+	}
+
+	#[test]
+	fn should_decode_symbol() {
+		let string: Vec<u8> = "0000000000000000000000000000000000000000000000000000000000000020\
+			0000000000000000000000000000000000000000000000000000000000000003\
+			5553440000000000000000000000000000000000000000000000000000000000".from_hex().unwrap();
+		let bytes32: Vec<u8> = "4d4b520000000000000000000000000000000000000000000000000000000000".from_hex().unwrap();
+
+		assert_eq!(decode_symbol(&string), Some("USD".into()));
+		assert_eq!(decode_symbol(&bytes32), Some("MKR".into()));
+		assert_eq!(decode_symbol(&[]), None);
+		assert_eq!(decode_symbol(&string[..64]), None);
+	}
+
+	#[test]
+	fn should_decode_decimals() {
+		let mut output = [0u8; 32];
+		output[31] = 18;
+		assert_eq!(decode_decimals(&output), Some(18));
+		output[30] = 1;
+		assert_eq!(decode_decimals(&output), None);
+		assert_eq!(decode_decimals(&output[1..]), None);
+	}
+
+	#[test]
+	fn should_only_cache_tokens_reporting_decimals() {
+		let cache = TokenMetadataCache::default();
+		cache.insert(H160::from_low_u64_be(1), TokenMetadata::default());
+		cache.insert(H160::from_low_u64_be(2), TokenMetadata { decimals: Some(18), symbol: None });
+
+		assert_eq!(cache.get(&H160::from_low_u64_be(1)), None);
+		assert_eq!(cache.get(&H160::from_low_u64_be(2)).unwrap().decimals, Some(18));
+	}
+}
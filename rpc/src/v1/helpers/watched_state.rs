@@ -0,0 +1,264 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Changes of accounts watched by `stateChanges` subscribers.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use client_traits::BlockChainClient;
+use ethereum_types::{Address, H256, U256};
+use hash::KECCAK_EMPTY;
+use types::client_types::StateWrites;
+use types::ids::BlockId;
+use v1::types::pubsub::{AccountChanges, Change, WatchedState};
+
+/// Maximal number of accounts and storage slots a single subscriber may watch.
+pub const MAX_WATCHED_ITEMS: usize = 1024;
+
+/// Maximal number of `stateChanges` subscribers.
+pub const MAX_STATE_SUBSCRIBERS: usize = 64;
+
+/// Accounts watched by any subscriber, each with the storage slots watched in it.
+pub type WatchedItems = BTreeMap<Address, BTreeSet<H256>>;
+
+/// Accounts watched by a subscriber, each with the storage slots watched in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedAccounts {
+	accounts: Vec<(Address, Vec<H256>)>,
+}
+
+/// Balance, nonce and code hash of an account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AccountFields {
+	balance: U256,
+	nonce: U256,
+	code_hash: H256,
+}
+
+/// Watched fields of an account at some block, as far as they were read.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct AccountValues {
+	fields: Option<AccountFields>,
+	storage: BTreeMap<H256, H256>,
+}
+
+/// Values of the watched accounts and storage slots at some block.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateValues(BTreeMap<Address, AccountValues>);
+
+impl StateValues {
+	/// Read the watched items missing from the values at the given block, and forget the
+	/// accounts no longer watched. `None` if the state of the block is not available.
+	fn read<C: BlockChainClient>(&mut self, client: &C, items: &WatchedItems, id: BlockId) -> Option<()> {
+		self.0.retain(|address, _| items.contains_key(address));
+		let addresses: Vec<_> = items.keys()
+			.filter(|address| self.0.get(*address).map_or(true, |values| values.fields.is_none()))
+			.cloned()
+			.collect();
+		let positions: Vec<_> = items.iter()
+			.flat_map(|(address, keys)| keys.iter().map(move |key| (*address, *key)))
+			.filter(|(address, key)| self.0.get(address).map_or(true, |values| !values.storage.contains_key(key)))
+			.collect();
+
+		if !addresses.is_empty() {
+			let balances = client.balances(&addresses, id.into())?.ok()?;
+			for (address, balance) in addresses.iter().zip(balances) {
+				let fields = AccountFields {
+					balance,
+					nonce: client.nonce(address, id)?,
+					code_hash: client.code_hash(address, id).unwrap_or(KECCAK_EMPTY),
+				};
+				self.0.entry(*address).or_default().fields = Some(fields);
+			}
+		}
+		if !positions.is_empty() {
+			let storage = client.storage_at_batch(&positions, id.into())?.ok()?;
+			for ((address, key), value) in positions.into_iter().zip(storage) {
+				self.0.entry(address).or_default().storage.insert(key, value);
+			}
+		}
+		Some(())
+	}
+
+	/// The values left untouched by the given writes. Changed accounts are forgotten as a whole,
+	/// their storage may have been cleared.
+	fn without(&self, writes: &StateWrites) -> StateValues {
+		let mut values = self.clone();
+		for address in &writes.accounts {
+			values.0.remove(address);
+		}
+		for (address, keys) in &writes.storage {
+			if let Some(account) = values.0.get_mut(address) {
+				for key in keys {
+					account.storage.remove(key);
+				}
+			}
+		}
+		values
+	}
+}
+
+/// Reads the watched items block after block, reusing the values of the previous block.
+#[derive(Debug, Default)]
+pub struct StateWatcher {
+	last: Option<(H256, StateValues)>,
+}
+
+impl StateWatcher {
+	/// Values of the watched items at the parent of a block and at the block. Only what the block
+	/// wrote is read again when its writes are known, and the values of the parent are reused if
+	/// it was the previous block. `None` if the state of either block is not available.
+	pub fn values<C: BlockChainClient>(&mut self, client: &C, items: &WatchedItems, hash: H256, parent: H256) -> Option<(StateValues, &StateValues)> {
+		let mut before = match self.last.take() {
+			Some((last, values)) if last == parent => values,
+			_ => StateValues::default(),
+		};
+		before.read(client, items, BlockId::Hash(parent))?;
+
+		let mut after = match client.state_writes(&hash) {
+			Some(writes) => before.without(&writes),
+			None => StateValues::default(),
+		};
+		after.read(client, items, BlockId::Hash(hash))?;
+
+		self.last = Some((hash, after));
+		self.last.as_ref().map(|(_, after)| (before, after))
+	}
+}
+
+impl WatchedAccounts {
+	/// Accounts to watch for a subscription, or `None` if it watches too many items.
+	pub fn new(watched: WatchedState) -> Option<Self> {
+		let mut accounts = BTreeMap::new();
+		for address in watched.addresses {
+			accounts.entry(address).or_insert_with(Vec::new);
+		}
+		for (address, mut keys) in watched.storage {
+			keys.sort();
+			keys.dedup();
+			accounts.insert(address, keys);
+		}
+
+		let items = accounts.values().map(|keys| 1 + keys.len()).sum::<usize>();
+		if items > MAX_WATCHED_ITEMS {
+			return None;
+		}
+		Some(WatchedAccounts { accounts: accounts.into_iter().collect() })
+	}
+
+	/// Add the watched accounts and storage slots to `items`.
+	pub fn add_to(&self, items: &mut WatchedItems) {
+		for (address, keys) in &self.accounts {
+			items.entry(*address).or_default().extend(keys);
+		}
+	}
+
+	/// Changes of the watched accounts between the values of two blocks.
+	pub fn changes(&self, before: &StateValues, after: &StateValues) -> Vec<AccountChanges> {
+		self.accounts.iter()
+			.filter_map(|(address, keys)| {
+				let (before, after) = (before.0.get(address)?, after.0.get(address)?);
+				let (from, to) = (before.fields?, after.fields?);
+				Some(AccountChanges {
+					address: *address,
+					balance: change(from.balance, to.balance),
+					nonce: change(from.nonce, to.nonce),
+					code_hash: change(from.code_hash, to.code_hash),
+					storage: keys.iter()
+						.filter_map(|key| {
+							let (from, to) = (*before.storage.get(key)?, *after.storage.get(key)?);
+							change(from, to).map(|change| (*key, change))
+						})
+						.collect(),
+				})
+			})
+			.filter(|changes| {
+				changes.balance.is_some() || changes.nonce.is_some() || changes.code_hash.is_some() || !changes.storage.is_empty()
+			})
+			.collect()
+	}
+}
+
+fn change<T: PartialEq>(from: T, to: T) -> Option<Change<T>> {
+	if from == to {
+		None
+	} else {
+		Some(Change { from, to })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum_types::H160;
+
+	fn values(accounts: Vec<(u64, u64, u64, Vec<(u64, u64)>)>) -> StateValues {
+		StateValues(accounts.into_iter().map(|(address, balance, nonce, storage)| (H160::from_low_u64_be(address), AccountValues {
+			fields: Some(AccountFields { balance: balance.into(), nonce: nonce.into(), code_hash: KECCAK_EMPTY }),
+			storage: storage.into_iter().map(|(key, value)| (H256::from_low_u64_be(key), H256::from_low_u64_be(value))).collect(),
+		})).collect())
+	}
+
+	fn watched() -> WatchedAccounts {
+		let mut storage = BTreeMap::new();
+		storage.insert(H160::from_low_u64_be(2), vec![H256::from_low_u64_be(5), H256::from_low_u64_be(4), H256::from_low_u64_be(4)]);
+		WatchedAccounts::new(WatchedState {
+			addresses: vec![H160::from_low_u64_be(1), H160::from_low_u64_be(2)],
+			storage,
+		}).unwrap()
+	}
+
+	#[test]
+	fn should_merge_watched_addresses_and_slots() {
+		assert_eq!(watched().accounts, vec![
+			(H160::from_low_u64_be(1), vec![]),
+			(H160::from_low_u64_be(2), vec![H256::from_low_u64_be(4), H256::from_low_u64_be(5)]),
+		]);
+	}
+
+	#[test]
+	fn should_reject_too_many_items() {
+		let addresses = (0..MAX_WATCHED_ITEMS as u64 + 1).map(H160::from_low_u64_be).collect();
+		assert_eq!(WatchedAccounts::new(WatchedState { addresses, storage: BTreeMap::new() }), None);
+	}
+
+	#[test]
+	fn should_report_only_changed_fields() {
+		let watched = watched();
+		let before = values(vec![(1, 10, 1, vec![]), (2, 0, 0, vec![(4, 7), (5, 8)])]);
+		let after = values(vec![(1, 10, 1, vec![]), (2, 0, 0, vec![(4, 7), (5, 9)])]);
+
+		let changes = watched.changes(&before, &after);
+		assert_eq!(changes.len(), 1);
+		assert_eq!(changes[0].address, H160::from_low_u64_be(2));
+		assert_eq!(changes[0].balance, None);
+		assert_eq!(changes[0].storage.len(), 1);
+		assert_eq!(changes[0].storage[&H256::from_low_u64_be(5)], Change { from: H256::from_low_u64_be(8), to: H256::from_low_u64_be(9) });
+
+		assert!(watched.changes(&after, &after).is_empty());
+	}
+
+	#[test]
+	fn should_forget_written_values() {
+		let before = values(vec![(1, 10, 1, vec![]), (2, 0, 0, vec![(4, 7), (5, 8)]), (3, 5, 0, vec![])]);
+		let mut writes = StateWrites::default();
+		writes.accounts.insert(H160::from_low_u64_be(1));
+		writes.storage.insert(H160::from_low_u64_be(2), vec![H256::from_low_u64_be(5)].into_iter().collect());
+
+		assert_eq!(before.without(&writes), values(vec![(2, 0, 0, vec![(4, 7)]), (3, 5, 0, vec![])]));
+	}
+}
@@ -0,0 +1,119 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique rpc implementation.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use client_traits::BlockChainClient;
+use engine::SignerVoting;
+use ethcore::client::EngineInfo;
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::Result;
+use types::header::Header;
+use types::ids::BlockId;
+
+use v1::helpers::errors;
+use v1::traits::Clique;
+use v1::types::{BlockNumber, CliqueStatus, block_number_to_id};
+
+/// Number of most recent blocks `clique_status` reports on.
+const STATUS_BLOCKS: u64 = 64;
+/// Difficulty of a block sealed by its in-turn signer.
+const DIFF_INTURN: u64 = 2;
+
+/// Clique rpc implementation.
+pub struct CliqueClient<C> {
+	client: Arc<C>,
+}
+
+impl<C> CliqueClient<C> {
+	/// Creates new `CliqueClient`.
+	pub fn new(client: Arc<C>) -> Self {
+		CliqueClient { client }
+	}
+}
+
+impl<C: BlockChainClient + EngineInfo> CliqueClient<C> {
+	fn signer_voting(&self) -> Result<&dyn SignerVoting> {
+		self.client.engine().signer_voting()
+			.ok_or_else(|| errors::unsupported("The chain engine does not vote on signers.", None))
+	}
+
+	fn header(&self, id: BlockId) -> Result<Header> {
+		self.client.block_header(id)
+			.ok_or_else(errors::unknown_block)?
+			.decode()
+			.map_err(errors::decode)
+	}
+
+	fn signers_at(&self, id: BlockId) -> Result<Vec<H160>> {
+		let voting = self.signer_voting()?;
+		voting.signers(&self.header(id)?)
+			.map_err(|e| errors::internal("Could not compute the signers", e))
+	}
+}
+
+impl<C: BlockChainClient + EngineInfo + 'static> Clique for CliqueClient<C> {
+	fn signers(&self, number: Option<BlockNumber>) -> Result<Vec<H160>> {
+		let id = match number.unwrap_or_default() {
+			BlockNumber::Pending => BlockId::Latest,
+			number => block_number_to_id(number),
+		};
+		self.signers_at(id)
+	}
+
+	fn signers_at_hash(&self, hash: H256) -> Result<Vec<H160>> {
+		self.signers_at(BlockId::Hash(hash))
+	}
+
+	fn propose(&self, address: H160, authorize: bool) -> Result<bool> {
+		self.signer_voting()?.propose(address, authorize);
+		Ok(true)
+	}
+
+	fn discard(&self, address: H160) -> Result<bool> {
+		Ok(self.signer_voting()?.discard(&address))
+	}
+
+	fn status(&self) -> Result<CliqueStatus> {
+		let mut sealer_activity = self.signers_at(BlockId::Latest)?
+			.into_iter()
+			.map(|signer| (signer, 0))
+			.collect::<BTreeMap<_, _>>();
+
+		let best = self.client.chain_info().best_block_number;
+		let first = best.saturating_sub(STATUS_BLOCKS - 1).max(1);
+		let mut inturn = 0;
+		for number in first..=best {
+			let header = self.header(BlockId::Number(number))?;
+			let signer = self.client.engine().executive_author(&header)
+				.map_err(|e| errors::internal("Could not recover the block signer", e))?;
+			*sealer_activity.entry(signer).or_insert(0) += 1;
+			if *header.difficulty() == U256::from(DIFF_INTURN) {
+				inturn += 1;
+			}
+		}
+
+		let num_blocks = (best + 1).saturating_sub(first);
+		Ok(CliqueStatus {
+			inturn_percent: if num_blocks == 0 { 0.0 } else { inturn as f64 * 100.0 / num_blocks as f64 },
+			num_blocks,
+			sealer_activity,
+		})
+	}
+}
@@ -16,31 +16,115 @@
 
 //! Debug APIs RPC implementation
 
+use std::cmp;
+use std::collections::VecDeque;
+use std::iter::Peekable;
 use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use client_traits::BlockChainClient;
+use account_state::state::StateInfo;
+use client_traits::{BlockChainClient, StateClient};
+use ethcore::client::Call;
+use ethereum_types::H256;
+use parking_lot::Mutex;
+use trace::{StructLog, StructLogger};
 use types::header::Header;
-use types::transaction::LocalizedTransaction;
+use types::ids::{BlockId, TransactionId};
+use types::transaction::{CallError, LocalizedTransaction};
 
-use jsonrpc_core::Result;
+use jsonrpc_core::{BoxFuture, Result};
+use v1::helpers::{errors, fake_sign};
+use v1::helpers::heavy::HeavyRequests;
 use v1::traits::Debug;
-use v1::types::{Block, Bytes, RichBlock, BlockTransactions, Transaction};
+use v1::types::{Block, Bytes, RichBlock, BlockTransactions, Transaction, StructLogs, StructLogRange,
+	GasProfile, CallRequest, BlockNumber, ExecutionWitness};
+
+/// Maximal number of steps returned by a single `debug_traceTransactionSteps` call.
+const MAX_STRUCT_LOGS: usize = 10_000;
+/// Number of steps the replay may run ahead of the consumer.
+const STRUCT_LOG_BUFFER: usize = 1024;
+/// Number of paused replays kept for the next page of `debug_traceTransactionSteps`.
+const MAX_PARKED_REPLAYS: usize = 8;
+/// Time after which a paused replay is dropped, if its next page wasn't requested.
+const PARKED_REPLAY_LIFETIME: Duration = Duration::from_secs(60);
+
+type Replay = JoinHandle<::std::result::Result<usize, CallError>>;
+
+/// Replay a transaction on a separate thread, streaming its executed steps through the returned channel.
+/// Dropping the receiver stops the tracing.
+pub fn replay_struct_logs<C>(client: Arc<C>, hash: H256) -> Result<(Receiver<StructLog>, Replay)>
+	where C: BlockChainClient + 'static
+{
+	let (sender, receiver) = mpsc::sync_channel(STRUCT_LOG_BUFFER);
+	let replay = thread::Builder::new()
+		.name("Struct logs".into())
+		.spawn(move || client.replay_struct_logs(TransactionId::Hash(hash), StructLogger::new(sender)))
+		.map_err(|e| errors::internal("Failed to start the transaction replay", e))?;
+	Ok((receiver, replay))
+}
+
+/// Wait for the replay to finish.
+pub fn join_replay(replay: Replay) -> Result<usize> {
+	replay.join()
+		.map_err(|_| errors::internal("Transaction replay panicked", ""))?
+		.map_err(errors::call)
+}
+
+/// A replay paused after a page of its steps was returned, resumed by the request for the following page.
+struct ParkedReplay {
+	hash: H256,
+	position: usize,
+	steps: Peekable<mpsc::IntoIter<StructLog>>,
+	replay: Replay,
+	parked_at: Instant,
+}
+
+/// Paused replays. The replay threads stay blocked on their full channels until resumed,
+/// and stop once dropped.
+#[derive(Default)]
+struct ParkedReplays(Mutex<VecDeque<ParkedReplay>>);
+
+impl ParkedReplays {
+	fn take(&self, hash: H256, position: usize) -> Option<ParkedReplay> {
+		let mut parked = self.0.lock();
+		parked.retain(|p| p.parked_at.elapsed() < PARKED_REPLAY_LIFETIME);
+		let index = parked.iter().position(|p| p.hash == hash && p.position == position)?;
+		parked.remove(index)
+	}
+
+	fn park(&self, replay: ParkedReplay) {
+		let mut parked = self.0.lock();
+		if parked.len() >= MAX_PARKED_REPLAYS {
+			parked.pop_front();
+		}
+		parked.push_back(replay);
+	}
+}
 
 /// Debug rpc implementation.
 pub struct DebugClient<C> {
 	client: Arc<C>,
+	heavy: HeavyRequests,
+	replays: Arc<ParkedReplays>,
 }
 
 impl<C> DebugClient<C> {
 	/// Creates new debug client.
-	pub fn new(client: Arc<C>) -> Self {
+	pub fn new(client: Arc<C>, heavy: HeavyRequests) -> Self {
 		Self {
 			client,
+			heavy,
+			replays: Default::default(),
 		}
 	}
 }
 
-impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
+impl<C, S> Debug for DebugClient<C> where
+	S: StateInfo + 'static,
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + 'static
+{
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>> {
 		fn cast<O, T: Copy + Into<O>>(t: &T) -> O {
 			(*t).into()
@@ -49,6 +133,7 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 		Ok(self.client.bad_blocks().into_iter().map(|(block, reason)| {
 			let number = block.header.number();
 			let hash = block.header.hash();
+			let diagnostics = self.client.bad_block_diagnostics(&hash);
 			RichBlock {
 				inner: Block {
 					hash: Some(hash),
@@ -86,10 +171,121 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					("reason".to_owned(), reason),
 					("rlp".to_owned(), serialize(&Bytes(block.bytes))),
 					("hash".to_owned(), format!("{:#x}", hash)),
-				].into_iter().collect(),
+				].into_iter().chain(diagnostics.map(|d| ("diagnostics".to_owned(), d))).collect(),
 			}
 		}).collect())
 	}
+
+	fn trace_transaction_steps(&self, hash: H256, range: Option<StructLogRange>) -> BoxFuture<StructLogs> {
+		let client = self.client.clone();
+		let replays = self.replays.clone();
+		self.heavy.run(move |cancellation| {
+			let range = range.unwrap_or_default();
+			let after = range.after.unwrap_or(0);
+			let count = cmp::min(range.count.unwrap_or(MAX_STRUCT_LOGS), MAX_STRUCT_LOGS);
+
+			// resume the replay which returned the previous page, if still around
+			let (mut steps, replay, mut position) = match replays.take(hash, after) {
+				Some(parked) => (parked.steps, parked.replay, parked.position),
+				None => {
+					let (receiver, replay) = replay_struct_logs(client, hash)?;
+					(receiver.into_iter().peekable(), replay, 0)
+				},
+			};
+
+			// stop consuming steps once cancelled, dropping the receiver stops the replay
+			while position < after && steps.next().is_some() {
+				cancellation.check()?;
+				position += 1;
+			}
+			let mut logs = Vec::with_capacity(cmp::min(count, STRUCT_LOG_BUFFER));
+			while logs.len() < count {
+				cancellation.check()?;
+				match steps.next() {
+					Some(log) => logs.push(log.into()),
+					None => break,
+				}
+			}
+
+			let complete = steps.peek().is_none();
+			if complete {
+				join_replay(replay)?;
+			} else {
+				replays.park(ParkedReplay {
+					hash,
+					position: position + logs.len(),
+					steps,
+					replay,
+					parked_at: Instant::now(),
+				});
+			}
+			Ok(StructLogs { logs, complete })
+		})
+	}
+
+	fn trace_transaction_gas_profile(&self, hash: H256) -> BoxFuture<Option<GasProfile>> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			client.replay_gas_profile(TransactionId::Hash(hash))
+				.map(|profiles| profiles.into_iter().next().map(Into::into))
+				.map_err(errors::call)
+		})
+	}
+
+	fn trace_call_gas_profile(&self, request: CallRequest, block: Option<BlockNumber>) -> BoxFuture<Option<GasProfile>> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			let block = block.unwrap_or_default();
+
+			let request = CallRequest::into(request);
+			let signed = fake_sign::sign_call(request)?;
+
+			let id = match block {
+				BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+				BlockNumber::Num(num) => BlockId::Number(num),
+				BlockNumber::Earliest => BlockId::Earliest,
+				BlockNumber::Latest => BlockId::Latest,
+
+				BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+			};
+
+			let mut state = client.state_at(id).ok_or_else(errors::state_pruned)?;
+			let header = client.block_header(id).ok_or_else(errors::state_pruned)?;
+
+			client.call_gas_profile(&signed, &mut state, &header.decode().map_err(errors::decode)?)
+				.map(|profiles| profiles.into_iter().next().map(Into::into))
+				.map_err(errors::call)
+		})
+	}
+
+	fn execution_witness(&self, block: BlockNumber) -> Result<Option<ExecutionWitness>> {
+		let id = match block {
+			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+			BlockNumber::Num(num) => BlockId::Number(num),
+			BlockNumber::Earliest => BlockId::Earliest,
+			BlockNumber::Latest => BlockId::Latest,
+
+			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+		};
+
+		let header = match self.client.block_header(id) {
+			Some(header) => header,
+			None => return Ok(None),
+		};
+		let parent_state_root = match header.number() {
+			0 => header.state_root(),
+			_ => self.client.block_header(BlockId::Hash(header.parent_hash()))
+				.ok_or_else(errors::state_pruned)?
+				.state_root(),
+		};
+
+		let state = self.client.execution_witness(BlockId::Hash(header.hash())).map_err(errors::call)?;
+		Ok(Some(ExecutionWitness {
+			block_hash: header.hash(),
+			parent_state_root,
+			state: state.into_iter().map(Into::into).collect(),
+		}))
+	}
 }
 
 fn serialize<T: ::serde::Serialize>(t: &T) -> String {
@@ -0,0 +1,136 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug PUB-SUB rpc implementation.
+
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use client_traits::BlockChainClient;
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_core::futures::Future;
+use jsonrpc_pubsub::typed::{Sink, Subscriber};
+use jsonrpc_pubsub::SubscriptionId;
+use parking_lot::RwLock;
+use parity_runtime::Executor;
+
+use v1::helpers::Subscribers;
+use v1::helpers::heavy::HeavyRequests;
+use v1::metadata::Metadata;
+use v1::traits::DebugPubSub;
+use v1::types::StructLogs;
+use super::debug::{replay_struct_logs, join_replay};
+
+/// Number of steps sent in a single notification.
+const STRUCT_LOGS_PER_NOTIFICATION: usize = 256;
+
+/// Debug PubSub implementation.
+pub struct DebugPubSubClient<C> {
+	client: Arc<C>,
+	heavy: HeavyRequests,
+	executor: Executor,
+	struct_logs_subscribers: Arc<RwLock<Subscribers<Arc<AtomicBool>>>>,
+}
+
+impl<C> DebugPubSubClient<C> {
+	/// Creates new `DebugPubSubClient`. Traces are streamed from the threads of the given pool,
+	/// which bounds the number of concurrent streams.
+	pub fn new(client: Arc<C>, heavy: HeavyRequests, executor: Executor) -> Self {
+		DebugPubSubClient {
+			client,
+			heavy,
+			executor,
+			struct_logs_subscribers: Default::default(),
+		}
+	}
+}
+
+impl<C: BlockChainClient + 'static> DebugPubSubClient<C> {
+	fn stream_struct_logs(client: Arc<C>, hash: H256, sink: Sink<StructLogs>, active: Arc<AtomicBool>) {
+		let notify = |chunk| sink.notify(Ok(chunk)).wait().is_ok() && active.load(Ordering::SeqCst);
+		let (receiver, replay) = match replay_struct_logs(client, hash) {
+			Ok(replay) => replay,
+			Err(e) => {
+				let _ = sink.notify(Err(e)).wait();
+				return;
+			}
+		};
+
+		let mut logs = Vec::with_capacity(STRUCT_LOGS_PER_NOTIFICATION);
+		let mut streaming = true;
+		for log in receiver {
+			logs.push(log.into());
+			if logs.len() == STRUCT_LOGS_PER_NOTIFICATION {
+				let logs = mem::replace(&mut logs, Vec::with_capacity(STRUCT_LOGS_PER_NOTIFICATION));
+				if !notify(StructLogs { logs, complete: false }) {
+					// dropping the receiver stops the tracing.
+					streaming = false;
+					break;
+				}
+			}
+		}
+
+		match join_replay(replay) {
+			Ok(_) if streaming => { notify(StructLogs { logs, complete: true }); },
+			Ok(_) => {},
+			Err(e) => { let _ = sink.notify(Err(e)).wait(); },
+		}
+	}
+}
+
+impl<C: BlockChainClient + 'static> DebugPubSub for DebugPubSubClient<C> {
+	type Metadata = Metadata;
+
+	fn subscribe_struct_logs(&self, _meta: Metadata, subscriber: Subscriber<StructLogs>, hash: H256) {
+		let active = Arc::new(AtomicBool::new(true));
+		let id = self.struct_logs_subscribers.write().insert(active.clone());
+		let sink = match subscriber.assign_id(id.clone()) {
+			Ok(sink) => sink,
+			Err(_) => {
+				self.struct_logs_subscribers.write().remove(&id);
+				return;
+			},
+		};
+
+		let client = self.client.clone();
+		let subscribers = self.struct_logs_subscribers.clone();
+		let stream_id = id.clone();
+		let stream = self.heavy.run(move |_| {
+			Self::stream_struct_logs(client, hash, sink, active);
+			subscribers.write().remove(&stream_id);
+			Ok(())
+		});
+
+		// the stream is stopped by unsubscribing, not by dropping the response
+		let subscribers = self.struct_logs_subscribers.clone();
+		self.executor.spawn(stream.map_err(move |e| {
+			warn!(target: "rpc", "Unable to stream struct logs: {}", e.message);
+			subscribers.write().remove(&id);
+		}));
+	}
+
+	fn unsubscribe_struct_logs(&self, _: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+		match self.struct_logs_subscribers.write().remove(&id) {
+			Some(active) => {
+				active.store(false, Ordering::SeqCst);
+				Ok(true)
+			},
+			None => Ok(false),
+		}
+	}
+}
@@ -0,0 +1,256 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Engine API rpc implementation.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use client_traits::{BlockChainClient, ChainNotify, ForkChoiceClient};
+use ethcore::client::PrepareOpenBlock;
+use ethcore::miner::{MinerService, PendingOrdering};
+use ethereum_types::{H64, H256};
+use hash::keccak;
+use jsonrpc_core::{BoxFuture, Result};
+use parking_lot::{Condvar, Mutex};
+use rlp::RlpStream;
+use types::{
+	block_status::BlockStatus,
+	chain_notify::NewBlocks,
+	errors::{EthcoreError, ImportError},
+	ids::BlockId,
+};
+
+use v1::helpers::errors;
+use v1::helpers::heavy::HeavyRequests;
+use v1::traits::EngineApi;
+use v1::types::{
+	ExecutionPayload, PayloadValidity, PayloadStatus, ForkchoiceState, PayloadAttributes, ForkchoiceUpdatedResponse,
+};
+
+/// Time to wait for an imported payload to be executed before reporting it as accepted.
+const PAYLOAD_IMPORT_TIMEOUT: Duration = Duration::from_secs(8);
+/// Number of built payloads kept until the consensus client fetches them.
+const MAX_BUILT_PAYLOADS: usize = 16;
+/// Maximum number of transactions of a built payload.
+const MAX_PAYLOAD_TRANSACTIONS: usize = 10_000;
+/// Maximum size of the extra data of a proof-of-stake block.
+const MAX_EXTRA_DATA_SIZE: usize = 32;
+
+/// Wakes up the requests waiting for payloads to be imported whenever the client imported blocks.
+#[derive(Default)]
+pub struct ImportNotifier {
+	rounds: Mutex<u64>,
+	imported: Condvar,
+}
+
+impl ImportNotifier {
+	/// Returns the value of `done` once it has one, checking it again after every import round.
+	/// Gives up at `deadline`.
+	fn wait_for<T, F>(&self, deadline: Instant, mut done: F) -> Option<T> where F: FnMut() -> Option<T> {
+		loop {
+			let round = *self.rounds.lock();
+			if let Some(result) = done() {
+				return Some(result);
+			}
+
+			let mut rounds = self.rounds.lock();
+			while *rounds == round {
+				if self.imported.wait_until(&mut rounds, deadline).timed_out() {
+					return None;
+				}
+			}
+		}
+	}
+}
+
+impl ChainNotify for ImportNotifier {
+	fn new_blocks(&self, _new_blocks: NewBlocks) {
+		*self.rounds.lock() += 1;
+		self.imported.notify_all();
+	}
+}
+
+/// Engine API rpc implementation.
+pub struct EngineApiClient<C, M> {
+	client: Arc<C>,
+	miner: Arc<M>,
+	pool: HeavyRequests,
+	notifier: Arc<ImportNotifier>,
+	payloads: Arc<Mutex<VecDeque<(H64, ExecutionPayload)>>>,
+}
+
+impl<C, M> EngineApiClient<C, M> {
+	/// Creates new `EngineApiClient`. Payloads are imported and built on the given pool.
+	pub fn new(client: Arc<C>, miner: Arc<M>, pool: HeavyRequests) -> Self {
+		EngineApiClient {
+			client,
+			miner,
+			pool,
+			notifier: Default::default(),
+			payloads: Default::default(),
+		}
+	}
+
+	/// Handler which has to be registered with the client to learn about imported payloads.
+	pub fn handler(&self) -> Weak<ImportNotifier> {
+		Arc::downgrade(&self.notifier)
+	}
+}
+
+fn invalid<C: BlockChainClient>(client: &C, hash: H256, latest_valid_hash: Option<H256>) -> PayloadStatus {
+	let reason = client.bad_blocks().into_iter()
+		.find(|(block, _)| block.header.hash() == hash)
+		.map_or_else(|| "Invalid block".to_owned(), |(_, reason)| reason);
+	PayloadStatus::invalid(latest_valid_hash, reason)
+}
+
+/// Waits for a queued payload to be imported or rejected.
+fn wait_for_import<C: BlockChainClient>(client: &C, notifier: &ImportNotifier, hash: H256, parent_hash: H256) -> PayloadStatus {
+	let deadline = Instant::now() + PAYLOAD_IMPORT_TIMEOUT;
+	let status = notifier.wait_for(deadline, || match client.block_status(BlockId::Hash(hash)) {
+		BlockStatus::InChain => Some(PayloadStatus::valid(hash)),
+		BlockStatus::Bad => Some(invalid(client, hash, Some(parent_hash))),
+		BlockStatus::Queued | BlockStatus::Unknown => None,
+	});
+	status.unwrap_or_else(|| PayloadStatus::new(PayloadValidity::Accepted))
+}
+
+fn import_payload<C: BlockChainClient>(client: &C, notifier: &ImportNotifier, payload: ExecutionPayload) -> PayloadStatus {
+	let hash = payload.block_hash;
+	let parent_hash = payload.parent_hash;
+	let block = match payload.into_unverified() {
+		Ok(block) => block,
+		Err(e) => return PayloadStatus::invalid(None, e),
+	};
+	if block.header.hash() != hash {
+		return PayloadStatus::new(PayloadValidity::InvalidBlockHash);
+	}
+
+	match client.block_status(BlockId::Hash(hash)) {
+		BlockStatus::InChain => return PayloadStatus::valid(hash),
+		BlockStatus::Bad => return invalid(client, hash, None),
+		BlockStatus::Queued => return wait_for_import(client, notifier, hash, parent_hash),
+		BlockStatus::Unknown => {},
+	}
+	if client.block_status(BlockId::Hash(parent_hash)) != BlockStatus::InChain {
+		return PayloadStatus::new(PayloadValidity::Syncing);
+	}
+
+	match client.import_block(block) {
+		Ok(_) | Err(EthcoreError::Import(ImportError::AlreadyInChain)) | Err(EthcoreError::Import(ImportError::AlreadyQueued)) => {},
+		Err(e) => return PayloadStatus::invalid(Some(parent_hash), e.to_string()),
+	}
+	wait_for_import(client, notifier, hash, parent_hash)
+}
+
+/// Identifier of the payload built with the given attributes on top of `parent_hash`.
+fn payload_id(parent_hash: H256, attributes: &PayloadAttributes) -> H64 {
+	let mut stream = RlpStream::new_list(4);
+	stream.append(&parent_hash);
+	stream.append(&attributes.timestamp.as_u64());
+	stream.append(&attributes.prev_randao);
+	stream.append(&attributes.suggested_fee_recipient);
+	H64::from_slice(&keccak(stream.out())[..8])
+}
+
+/// Builds a block on top of the best block with the transactions of the pool.
+fn build_payload<C, M>(client: &C, miner: &M, attributes: &PayloadAttributes) -> ::std::result::Result<ExecutionPayload, String> where
+	C: BlockChainClient + PrepareOpenBlock,
+	M: MinerService,
+{
+	let params = miner.authoring_params();
+	let mut extra_data = params.extra_data;
+	extra_data.truncate(MAX_EXTRA_DATA_SIZE);
+	let mut block = client.prepare_open_block(attributes.suggested_fee_recipient, params.gas_range_target, extra_data)
+		.map_err(|e| e.to_string())?;
+	if !block.header.difficulty().is_zero() {
+		return Err("The best block didn't reach the terminal total difficulty".into());
+	}
+	block.set_timestamp(attributes.timestamp.as_u64());
+	let mut seal = block.header.seal().to_vec();
+	seal[0] = rlp::encode(&attributes.prev_randao);
+	block.set_seal_fields(seal);
+
+	for transaction in miner.ready_transactions(client, MAX_PAYLOAD_TRANSACTIONS, PendingOrdering::Priority) {
+		let hash = transaction.hash();
+		if let Err(e) = block.push_transaction(transaction.signed().clone()) {
+			trace!(target: "rpc", "Leaving transaction {:?} out of the payload: {}", hash, e);
+		}
+	}
+
+	let block = block.close_and_lock().map_err(|e| e.to_string())?;
+	Ok(ExecutionPayload::from_block(&block.header, &block.transactions))
+}
+
+impl<C, M> EngineApi for EngineApiClient<C, M> where
+	C: BlockChainClient + ForkChoiceClient + PrepareOpenBlock + 'static,
+	M: MinerService + 'static,
+{
+	fn new_payload(&self, payload: ExecutionPayload) -> BoxFuture<PayloadStatus> {
+		let client = self.client.clone();
+		let notifier = self.notifier.clone();
+		self.pool.run(move |_| Ok(import_payload(&*client, &notifier, payload)))
+	}
+
+	fn forkchoice_updated(&self, state: ForkchoiceState, attributes: Option<PayloadAttributes>) -> BoxFuture<ForkchoiceUpdatedResponse> {
+		let client = self.client.clone();
+		let miner = self.miner.clone();
+		let payloads = self.payloads.clone();
+		self.pool.run(move |_| {
+			let head = state.head_block_hash;
+			let payload_status = match client.block_status(BlockId::Hash(head)) {
+				BlockStatus::InChain => {
+					let finalized = Some(state.finalized_block_hash).filter(|hash| !hash.is_zero());
+					client.set_head(head, finalized).map_err(errors::invalid_forkchoice_state)?;
+					PayloadStatus::valid(head)
+				},
+				BlockStatus::Bad => invalid(&*client, head, None),
+				BlockStatus::Queued | BlockStatus::Unknown => PayloadStatus::new(PayloadValidity::Syncing),
+			};
+
+			// payloads are only built on top of a valid head which became the best block.
+			let build_on_head = payload_status.status == PayloadValidity::Valid && client.chain_info().best_block_hash == head;
+			let payload_id = match attributes {
+				Some(ref attributes) if build_on_head => {
+					let payload = build_payload(&*client, &*miner, attributes).map_err(|e| errors::internal("Failed to build the payload", e))?;
+					let id = payload_id(head, attributes);
+					let mut payloads = payloads.lock();
+					payloads.retain(|(known, _)| *known != id);
+					if payloads.len() == MAX_BUILT_PAYLOADS {
+						payloads.pop_front();
+					}
+					payloads.push_back((id, payload));
+					Some(id)
+				},
+				_ => None,
+			};
+
+			Ok(ForkchoiceUpdatedResponse {
+				payload_status,
+				payload_id,
+			})
+		})
+	}
+
+	fn get_payload(&self, id: H64) -> Result<ExecutionPayload> {
+		self.payloads.lock().iter()
+			.find(|(known, _)| *known == id)
+			.map(|(_, payload)| payload.clone())
+			.ok_or_else(errors::unknown_payload)
+	}
+}
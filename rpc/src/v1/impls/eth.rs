@@ -16,6 +16,7 @@
 
 //! Eth rpc implementation.
 
+use std::cmp;
 use std::thread;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
@@ -40,6 +41,7 @@ use types::{
 	header::Header,
 	ids::{BlockId, TransactionId, UncleId},
 	filter::Filter as EthcoreFilter,
+	log_entry::LocalizedLogEntry,
 	transaction::{SignedTransaction, LocalizedTransaction},
 	snapshot::RestorationStatus,
 };
@@ -49,12 +51,13 @@ use jsonrpc_core::futures::future;
 
 use v1::helpers::{self, errors, limit_logs, fake_sign};
 use v1::helpers::deprecated::{self, DeprecationNotice};
+use v1::helpers::heavy::{Cancellation, HeavyRequests};
 use v1::helpers::dispatch::{FullDispatcher, default_gas_price};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
 	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
-	block_number_to_id
+	block_number_to_id, chain_gaps,
 };
 use v1::metadata::Metadata;
 
@@ -74,6 +77,8 @@ pub struct EthClientOptions {
 	/// Return 'null' instead of an error if ancient block sync is still in
 	/// progress and the block information requested could not be found.
 	pub allow_missing_blocks: bool,
+	/// Re-execute blocks whose receipts were pruned to find their logs.
+	pub reconstruct_logs: bool,
 	/// Enable Experimental RPC-Calls
 	pub allow_experimental_rpcs: bool,
 	/// flag for ancient block sync
@@ -98,6 +103,7 @@ impl Default for EthClientOptions {
 			send_block_number_in_get_work: true,
 			gas_price_percentile: 50,
 			allow_missing_blocks: false,
+			reconstruct_logs: false,
 			allow_experimental_rpcs: false,
 			no_ancient_blocks: false,
 		}
@@ -120,9 +126,16 @@ pub struct EthClient<C, SN: ?Sized, S: ?Sized, M, EM> where
 	external_miner: Arc<EM>,
 	seed_compute: Mutex<SeedHashCompute>,
 	options: EthClientOptions,
+	heavy: HeavyRequests,
 	deprecation_notice: DeprecationNotice,
 }
 
+/// Number of blocks searched for logs between checks for cancellation.
+const LOGS_CHUNK_BLOCKS: u64 = 10_000;
+
+/// Maximal number of reads in a single `eth_getBalances` or `eth_getStorageAtBatch` request.
+pub const MAX_BATCH_STATE_READS: usize = 1024;
+
 #[derive(Debug)]
 enum BlockNumberOrId {
 	Number(BlockNumber),
@@ -156,21 +169,79 @@ enum PendingTransactionId {
 	Location(PendingOrBlock, usize)
 }
 
-pub fn base_logs<C, M, T: StateInfo + 'static> (client: &C, miner: &M, filter: Filter) -> BoxFuture<Vec<Log>> where
+/// Search logs block range by block range, newest first, stopping early once the limit is reached
+/// or the request is cancelled. Ranges which can't be split by number are searched at once.
+pub fn chunked_logs<C: BlockChainClient>(client: &C, filter: EthcoreFilter, cancellation: &Cancellation) -> Result<Vec<LocalizedLogEntry>> {
+	search_chunks(client, filter, cancellation, |client, filter| client.logs(filter).map_err(errors::filter_block_not_found))
+}
+
+/// Reconstruct the logs of pruned blocks block range by block range, like `chunked_logs`.
+pub fn chunked_reconstructed_logs<C: BlockChainClient>(client: &C, filter: EthcoreFilter, cancellation: &Cancellation) -> Result<Vec<LocalizedLogEntry>> {
+	search_chunks(client, filter, cancellation, |client, filter| client.reconstructed_logs(filter).map_err(errors::reconstruct_logs))
+}
+
+fn search_chunks<C, F>(client: &C, filter: EthcoreFilter, cancellation: &Cancellation, search: F) -> Result<Vec<LocalizedLogEntry>> where
+	C: BlockChainClient,
+	F: Fn(&C, EthcoreFilter) -> Result<Vec<LocalizedLogEntry>>,
+{
+	let best_block = client.chain_info().best_block_number;
+	let range = match (filter.from_block, filter.to_block) {
+		(BlockId::Hash(_), _) | (_, BlockId::Hash(_)) => None,
+		(from, to) => client.block_number(from).and_then(|from| client.block_number(to).map(|to| (from, to))),
+	};
+	let (from, to) = match range {
+		Some((from, to)) if from <= to && to <= best_block => (from, to),
+		_ => return search(client, filter),
+	};
+
+	let mut chunks = Vec::new();
+	let mut found = 0;
+	let mut end = to;
+	loop {
+		cancellation.check()?;
+
+		let start = cmp::max(from, end.saturating_sub(LOGS_CHUNK_BLOCKS - 1));
+		let mut chunk = filter.clone();
+		chunk.from_block = BlockId::Number(start);
+		chunk.to_block = BlockId::Number(end);
+		let logs = search(client, chunk)?;
+		found += logs.len();
+		chunks.push(logs);
+
+		if start == from || filter.limit.map_or(false, |limit| found >= limit) {
+			break;
+		}
+		end = start - 1;
+	}
+
+	Ok(chunks.into_iter().rev().flat_map(|logs| logs).collect())
+}
+
+pub fn base_logs<C, M, T: StateInfo + 'static> (client: &C, miner: &M, filter: Filter, reconstruct: bool, cancellation: &Cancellation) -> Result<Vec<Log>> where
 	C: miner::BlockChainClient + BlockChainClient + StateClient<State=T> + Call<State=T>,
 	M: MinerService<State=T> {
 	let include_pending = filter.to_block == Some(BlockNumber::Pending);
-	let filter: EthcoreFilter = match filter.try_into() {
-		Ok(value) => value,
-		Err(err) => return Box::new(future::err(err)),
-	};
-	let mut logs = match client.logs(filter.clone()) {
-		Ok(logs) => logs
-			.into_iter()
-			.map(From::from)
-			.collect::<Vec<Log>>(),
-		Err(id) => return Box::new(future::err(errors::filter_block_not_found(id))),
-	};
+	let filter: EthcoreFilter = filter.try_into()?;
+	let mut entries = chunked_logs(client, filter.clone(), cancellation)?
+		.into_iter()
+		.map(|entry| (entry, false))
+		.collect::<Vec<_>>();
+
+	if reconstruct {
+		let reconstructed = chunked_reconstructed_logs(client, filter.clone(), cancellation)?;
+		entries.extend(reconstructed.into_iter().map(|entry| (entry, true)));
+		entries.sort_by_key(|&(ref entry, _)| (entry.block_number, entry.log_index));
+	}
+
+	let mut logs = entries.into_iter()
+		.map(|(entry, reconstructed)| {
+			let mut log = Log::from(entry);
+			if reconstructed {
+				log.log_type = "reconstructed".into();
+			}
+			log
+		})
+		.collect::<Vec<Log>>();
 
 	if include_pending {
 		let best_block = client.chain_info().best_block_number;
@@ -178,9 +249,7 @@ pub fn base_logs<C, M, T: StateInfo + 'static> (client: &C, miner: &M, filter: F
 		logs.extend(pending);
 	}
 
-	let logs = limit_logs(logs, filter.limit);
-
-	Box::new(future::ok(logs))
+	Ok(limit_logs(logs, filter.limit))
 }
 
 impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
@@ -199,7 +268,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 		accounts: &Arc<dyn Fn() -> Vec<Address> + Send + Sync>,
 		miner: &Arc<M>,
 		em: &Arc<EM>,
-		options: EthClientOptions
+		options: EthClientOptions,
+		heavy: HeavyRequests,
 	) -> Self {
 		EthClient {
 			client: client.clone(),
@@ -210,6 +280,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 			external_miner: em.clone(),
 			seed_compute: Mutex::new(SeedHashCompute::default()),
 			options,
+			heavy,
 			deprecation_notice: Default::default(),
 		}
 	}
@@ -570,6 +641,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				highest_block,
 				warp_chunks_amount: warp_chunks_amount.map(|x| U256::from(x as u64)).map(Into::into),
 				warp_chunks_processed: warp_chunks_processed.map(|x| U256::from(x as u64)).map(Into::into),
+				chain_gaps: chain_gaps(&chain_info, Some(&client.pruning_info())),
 			};
 			Ok(SyncStatus::Info(info))
 		} else {
@@ -684,6 +756,37 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::done(storage))
 	}
 
+	fn balances(&self, addresses: Vec<H160>, num: Option<BlockNumber>) -> BoxFuture<Vec<U256>> {
+		if addresses.len() > MAX_BATCH_STATE_READS {
+			return Box::new(future::err(errors::request_rejected_param_limit(MAX_BATCH_STATE_READS as u64, "reads")));
+		}
+		let num = num.unwrap_or_default();
+
+		try_bf!(check_known(&*self.client, num.clone()));
+		let res = self.client.balances(&addresses, self.get_state(num))
+			.ok_or_else(errors::state_pruned)
+			.and_then(|balances| balances.map_err(errors::database));
+
+		Box::new(future::done(res))
+	}
+
+	fn storage_at_batch(&self, positions: Vec<(H160, U256)>, num: Option<BlockNumber>) -> BoxFuture<Vec<H256>> {
+		if positions.len() > MAX_BATCH_STATE_READS {
+			return Box::new(future::err(errors::request_rejected_param_limit(MAX_BATCH_STATE_READS as u64, "reads")));
+		}
+		let num = num.unwrap_or_default();
+		let positions: Vec<_> = positions.into_iter()
+			.map(|(address, position)| (address, BigEndianHash::from_uint(&position)))
+			.collect();
+
+		try_bf!(check_known(&*self.client, num.clone()));
+		let res = self.client.storage_at_batch(&positions, self.get_state(num))
+			.ok_or_else(errors::state_pruned)
+			.and_then(|values| values.map_err(errors::database));
+
+		Box::new(future::done(res))
+	}
+
 	fn transaction_count(&self, address: H160, num: Option<BlockNumber>) -> BoxFuture<U256> {
 		let res = match num.unwrap_or_default() {
 			BlockNumber::Pending if self.options.pending_nonce_from_queue => {
@@ -870,7 +973,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn logs(&self, filter: Filter) -> BoxFuture<Vec<Log>> {
-		base_logs(&*self.client, &*self.miner, filter)
+		let client = self.client.clone();
+		let miner = self.miner.clone();
+		let reconstruct = self.options.reconstruct_logs;
+		self.heavy.run(move |cancellation| base_logs(&*client, &*miner, filter, reconstruct, cancellation))
 	}
 
 	fn work(&self, no_new_work_timeout: Option<u64>) -> Result<Work> {
@@ -987,7 +1093,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				(state, header)
 			};
 
-		let result = self.client.call(&signed, Default::default(), &mut state, &header);
+		let result = self.client.call_cached(&signed, &mut state, &header);
 
 		Box::new(future::done(result
 			.map_err(errors::call)
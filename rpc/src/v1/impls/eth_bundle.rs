@@ -0,0 +1,100 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Eth bundles rpc implementation.
+
+use std::sync::Arc;
+
+use client_traits::BlockChainClient;
+use ethcore::miner::{self, Bundle, BundleError, MinerService};
+use ethcore::miner::bundle::MAX_BUNDLE_TRANSACTIONS;
+use jsonrpc_core::Result;
+use rlp::Rlp;
+use types::transaction::SignedTransaction;
+
+use v1::helpers::errors;
+use v1::traits::EthBundle;
+use v1::types::{BlockNumber, Bytes, CallBundleRequest, CallBundleResponse, SendBundleRequest, SendBundleResponse};
+
+/// Eth bundles rpc implementation.
+pub struct EthBundleClient<C, M> {
+	client: Arc<C>,
+	miner: Arc<M>,
+}
+
+impl<C, M> EthBundleClient<C, M> {
+	/// Creates new `EthBundleClient`.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		EthBundleClient {
+			client: client.clone(),
+			miner: miner.clone(),
+		}
+	}
+}
+
+fn decode_transactions(txs: Vec<Bytes>) -> Result<Vec<SignedTransaction>> {
+	// checked before decoding, so oversized simulations are refused as cheaply as submissions
+	if txs.len() > MAX_BUNDLE_TRANSACTIONS {
+		return Err(errors::bundle(BundleError::TooManyTransactions(txs.len())));
+	}
+	txs.into_iter()
+		.map(|raw| {
+			Rlp::new(&raw.into_vec()).as_val()
+				.map_err(errors::rlp)
+				.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
+		})
+		.collect()
+}
+
+impl<C, M> EthBundle for EthBundleClient<C, M> where
+	C: miner::BlockChainClient + BlockChainClient + 'static,
+	M: MinerService + 'static,
+{
+	fn send_bundle(&self, request: SendBundleRequest) -> Result<SendBundleResponse> {
+		let bundle = Bundle {
+			transactions: decode_transactions(request.txs)?,
+			block_number: request.block_number.as_u64(),
+			min_timestamp: request.min_timestamp,
+			max_timestamp: request.max_timestamp,
+			reverting_hashes: request.reverting_tx_hashes.into_iter().collect(),
+		};
+
+		self.miner.submit_bundle(&*self.client, bundle)
+			.map(|bundle_hash| SendBundleResponse { bundle_hash })
+			.map_err(errors::bundle)
+	}
+
+	fn call_bundle(&self, request: CallBundleRequest) -> Result<CallBundleResponse> {
+		let best_block = self.client.chain_info().best_block_number;
+		match request.state_block_number {
+			BlockNumber::Latest => {},
+			BlockNumber::Num(number) if number == best_block => {},
+			_ => return Err(errors::unsupported("Bundles can only be executed on top of the latest block.", None)),
+		}
+
+		let bundle = Bundle {
+			transactions: decode_transactions(request.txs)?,
+			block_number: request.block_number.as_u64(),
+			min_timestamp: None,
+			max_timestamp: None,
+			reverting_hashes: Default::default(),
+		};
+
+		self.miner.call_bundle(&*self.client, &bundle, request.timestamp)
+			.map(|simulation| CallBundleResponse::new(simulation, best_block))
+			.map_err(errors::execution)
+	}
+}
@@ -31,10 +31,12 @@ use types::{
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::{future, Future};
 use jsonrpc_core::futures::future::Either;
+use v1::metadata::Metadata;
 use v1::traits::EthFilter;
 use v1::types::{BlockNumber, Index, Filter, FilterChanges, Log};
-use v1::helpers::{errors, SyncPollFilter, PollFilter, PollManager, limit_logs};
-use v1::impls::eth::pending_logs;
+use v1::helpers::{errors, InstalledFilters, SyncPollFilter, PollFilter, PollManager, limit_logs};
+use v1::helpers::heavy::HeavyRequests;
+use v1::impls::eth::{chunked_logs, pending_logs};
 
 /// Something which provides data that can be filtered over.
 pub trait Filterable {
@@ -56,6 +58,10 @@ pub trait Filterable {
 	/// Get a reference to the poll manager.
 	fn polls(&self) -> &Mutex<PollManager<SyncPollFilter>>;
 
+	/// Schedule saving the installed filters, if they are kept across restarts. `installed`
+	/// tells whether a filter was installed or removed, rather than polled.
+	fn save_polls(&self, _installed: bool) {}
+
 	/// Get removed logs within route from the given block to the nearest canon block, not including the canon block. Also returns how many logs have been traversed.
 	fn removed_logs(&self, block_hash: H256, filter: &EthcoreFilter) -> (Vec<Log>, u64);
 }
@@ -64,22 +70,24 @@ pub trait Filterable {
 pub struct EthFilterClient<C, M> {
 	client: Arc<C>,
 	miner: Arc<M>,
-	polls: Mutex<PollManager<SyncPollFilter>>,
+	filters: Arc<InstalledFilters>,
+	heavy: HeavyRequests,
 }
 
 impl<C, M> EthFilterClient<C, M> {
 	/// Creates new Eth filter client.
-	pub fn new(client: Arc<C>, miner: Arc<M>, poll_lifetime: u32) -> Self {
+	pub fn new(client: Arc<C>, miner: Arc<M>, filters: Arc<InstalledFilters>, heavy: HeavyRequests) -> Self {
 		EthFilterClient {
 			client,
 			miner,
-			polls: Mutex::new(PollManager::new(poll_lifetime)),
+			filters,
+			heavy,
 		}
 	}
 }
 
 impl<C, M> Filterable for EthFilterClient<C, M> where
-	C: miner::BlockChainClient + BlockChainClient,
+	C: miner::BlockChainClient + BlockChainClient + 'static,
 	M: MinerService,
 {
 	fn best_block_number(&self) -> u64 {
@@ -95,14 +103,27 @@ impl<C, M> Filterable for EthFilterClient<C, M> where
 	}
 
 	fn logs(&self, filter: EthcoreFilter) -> BoxFuture<Vec<Log>> {
-		Box::new(future::ok(self.client.logs(filter).unwrap_or_default().into_iter().map(Into::into).collect()))
+		let client = self.client.clone();
+		self.heavy.run(move |cancellation| {
+			// unknown blocks yield no logs, cancellation is still reported
+			let logs = match chunked_logs(&*client, filter, cancellation) {
+				Ok(logs) => logs,
+				Err(_) if !cancellation.is_cancelled() => Vec::new(),
+				Err(err) => return Err(err),
+			};
+			Ok(logs.into_iter().map(Into::into).collect())
+		})
 	}
 
 	fn pending_logs(&self, block_number: u64, filter: &EthcoreFilter) -> Vec<Log> {
 		pending_logs(&*self.miner, block_number, filter)
 	}
 
-	fn polls(&self) -> &Mutex<PollManager<SyncPollFilter>> { &self.polls }
+	fn polls(&self) -> &Mutex<PollManager<SyncPollFilter>> { self.filters.polls() }
+
+	fn save_polls(&self, installed: bool) {
+		self.filters.save(installed)
+	}
 
 	fn removed_logs(&self, block_hash: H256, filter: &EthcoreFilter) -> (Vec<Log>, u64) {
 		let inner = || -> Option<Vec<H256>> {
@@ -140,33 +161,36 @@ impl<C, M> Filterable for EthFilterClient<C, M> where
 }
 
 impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
-	fn new_filter(&self, filter: Filter) -> Result<U256> {
-		let mut polls = self.polls().lock();
+	type Metadata = Metadata;
+
+	fn new_filter(&self, meta: Metadata, filter: Filter) -> Result<U256> {
 		let block_number = self.best_block_number();
 		let include_pending = filter.to_block == Some(BlockNumber::Pending);
 		let filter = filter.try_into()?;
-		let id = polls.create_poll(SyncPollFilter::new(PollFilter::Logs {
+		let id = self.polls().lock().create_poll(&meta.origin, SyncPollFilter::new(PollFilter::Logs {
 			block_number, filter, include_pending,
 			last_block_hash: None,
 			previous_logs: Default::default()
-		}));
+		})).ok_or_else(errors::too_many_filters)?;
+		self.save_polls(true);
 		Ok(id.into())
 	}
 
-	fn new_block_filter(&self) -> Result<U256> {
-		let mut polls = self.polls().lock();
+	fn new_block_filter(&self, meta: Metadata) -> Result<U256> {
 		// +1, since we don't want to include the current block
-		let id = polls.create_poll(SyncPollFilter::new(PollFilter::Block {
+		let id = self.polls().lock().create_poll(&meta.origin, SyncPollFilter::new(PollFilter::Block {
 			last_block_number: self.best_block_number(),
 			recent_reported_hashes: VecDeque::with_capacity(PollFilter::MAX_BLOCK_HISTORY_SIZE),
-		}));
+		})).ok_or_else(errors::too_many_filters)?;
+		self.save_polls(true);
 		Ok(id.into())
 	}
 
-	fn new_pending_transaction_filter(&self) -> Result<U256> {
-		let mut polls = self.polls().lock();
+	fn new_pending_transaction_filter(&self, meta: Metadata) -> Result<U256> {
 		let pending_transactions = self.pending_transaction_hashes();
-		let id = polls.create_poll(SyncPollFilter::new(PollFilter::PendingTransaction(pending_transactions)));
+		let id = self.polls().lock().create_poll(&meta.origin, SyncPollFilter::new(PollFilter::PendingTransaction(pending_transactions)))
+			.ok_or_else(errors::too_many_filters)?;
+		self.save_polls(true);
 		Ok(id.into())
 	}
 
@@ -176,7 +200,7 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 			None => return Box::new(future::err(errors::filter_not_found())),
 		};
 
-		Box::new(filter.modify(|filter| match *filter {
+		let changes = filter.modify(|filter| match *filter {
 			PollFilter::Block {
 				ref mut last_block_number,
 				ref mut recent_reported_hashes,
@@ -275,7 +299,9 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 					.map(move |logs| limit_logs(logs, limit)) // limit the logs
 					.map(FilterChanges::Logs))
 			}
-		}))
+		});
+		self.save_polls(false);
+		Box::new(changes)
 	}
 
 	fn filter_logs(&self, index: Index) -> BoxFuture<Vec<Log>> {
@@ -310,6 +336,10 @@ impl<T: Filterable + Send + Sync + 'static> EthFilter for T {
 	}
 
 	fn uninstall_filter(&self, index: Index) -> Result<bool> {
-		Ok(self.polls().lock().remove_poll(&index.value()))
+		let removed = self.polls().lock().remove_poll(&index.value());
+		if removed {
+			self.save_polls(true);
+		}
+		Ok(removed)
 	}
 }
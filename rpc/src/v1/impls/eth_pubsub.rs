@@ -17,14 +17,16 @@
 //! Eth PUB-SUB rpc implementation.
 
 use std::sync::{Arc, Weak};
-use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, Sender};
+use std::collections::{BTreeMap, HashSet};
+use std::thread;
 
 use jsonrpc_core::{BoxFuture, Result, Error};
 use jsonrpc_core::futures::{self, Future, IntoFuture, Stream, sync::mpsc};
 use jsonrpc_pubsub::typed::{Sink, Subscriber};
 use jsonrpc_pubsub::SubscriptionId;
 
-use v1::helpers::{errors, limit_logs, Subscribers, };
+use v1::helpers::{errors, limit_logs, Subscribers, StateWatcher, WatchedAccounts, WatchedItems, MAX_STATE_SUBSCRIBERS, MAX_WATCHED_ITEMS};
 use v1::helpers::light_fetch::LightFetch;
 use v1::metadata::Metadata;
 use v1::traits::EthPubSub;
@@ -57,6 +59,10 @@ pub struct EthPubSubClient<C> {
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	reorgs_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	state_subscribers: Arc<RwLock<Subscribers<(Client, WatchedAccounts)>>>,
+	/// Whether the client can read the state of imported blocks for `stateChanges` subscribers.
+	watch_state: bool,
 }
 
 impl<C> EthPubSubClient<C>
@@ -94,6 +100,8 @@ impl<C> EthPubSubClient<C>
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let sync_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let reorgs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let state_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 
 		let handler = Arc::new(ChainNotificationHandler {
 			client,
@@ -102,6 +110,9 @@ impl<C> EthPubSubClient<C>
 			logs_subscribers: logs_subscribers.clone(),
 			transactions_subscribers: transactions_subscribers.clone(),
 			sync_subscribers: sync_subscribers.clone(),
+			reorgs_subscribers: reorgs_subscribers.clone(),
+			state_subscribers: state_subscribers.clone(),
+			state_changes: Mutex::new(None),
 		});
 		let handler2 = Arc::downgrade(&handler);
 
@@ -121,6 +132,9 @@ impl<C> EthPubSubClient<C>
 			heads_subscribers,
 			logs_subscribers,
 			transactions_subscribers,
+			reorgs_subscribers,
+			state_subscribers,
+			watch_state: true,
 		}
 	}
 
@@ -152,7 +166,9 @@ where
 			cache,
 			gas_price_percentile,
 		};
-		EthPubSubClient::new(Arc::new(fetch), executor, pool_receiver)
+		let mut pubsub = EthPubSubClient::new(Arc::new(fetch), executor, pool_receiver);
+		pubsub.watch_state = false;
+		pubsub
 	}
 }
 
@@ -164,6 +180,10 @@ pub struct ChainNotificationHandler<C> {
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	reorgs_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	state_subscribers: Arc<RwLock<Subscribers<(Client, WatchedAccounts)>>>,
+	/// Routes of imported blocks sent to the thread computing the changes of watched accounts.
+	state_changes: Mutex<Option<Sender<Vec<(H256, ChainRouteType)>>>>,
 }
 
 impl<C> ChainNotificationHandler<C> {
@@ -225,6 +245,25 @@ impl<C> ChainNotificationHandler<C> {
 		}
 	}
 
+	/// Notify all subscribers about a chain reorganization, given the transactions
+	/// of the retracted and enacted blocks.
+	fn notify_reorg(&self, retracted: &[H256], enacted: &[H256], retracted_transactions: Vec<H256>, enacted_transactions: Vec<H256>) {
+		let enacted_transactions = enacted_transactions.into_iter().collect::<HashSet<_>>();
+		let (remined, unmined) = retracted_transactions
+			.into_iter()
+			.partition(|hash| enacted_transactions.contains(hash));
+		let reorg = pubsub::Reorg {
+			retracted: retracted.to_vec(),
+			enacted: enacted.to_vec(),
+			unmined_transactions: unmined,
+			remined_transactions: remined,
+		};
+
+		for subscriber in self.reorgs_subscribers.read().values() {
+			Self::notify(&self.executor, subscriber, pubsub::Result::Reorg(Box::new(reorg.clone())));
+		}
+	}
+
 	/// Notify all subscribers about new transaction hashes.
 	fn notify_new_transactions(&self, hashes: &[H256]) {
 		for subscriber in self.transactions_subscribers.read().values() {
@@ -271,9 +310,94 @@ impl<C: LightClient> LightChainNotify for ChainNotificationHandler<C> {
 	}
 }
 
-impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
+impl<C: BlockChainClient + 'static> ChainNotificationHandler<C> {
+	/// Queue the route for the thread notifying `stateChanges` subscribers, started on first use
+	/// so that reading the state doesn't hold up the import of blocks.
+	fn queue_state_changes(&self, route: &[(H256, ChainRouteType)]) {
+		let mut state_changes = self.state_changes.lock();
+		if state_changes.is_none() {
+			let (sender, receiver) = channel::<Vec<(H256, ChainRouteType)>>();
+			let client = self.client.clone();
+			let executor = self.executor.clone();
+			let subscribers = self.state_subscribers.clone();
+			let spawned = thread::Builder::new().name("rpc-state-changes".into()).spawn(move || {
+				let mut watcher = StateWatcher::default();
+				for route in receiver {
+					Self::notify_state_changes(&*client, &executor, &subscribers, &mut watcher, &route);
+				}
+			});
+			match spawned {
+				Ok(_) => *state_changes = Some(sender),
+				Err(e) => {
+					warn!(target: "rpc", "Unable to start notifying state changes: {}", e);
+					return;
+				},
+			}
+		}
+		if let Some(ref sender) = *state_changes {
+			let _ = sender.send(route.to_vec());
+		}
+	}
+
+	/// Notify subscribers about the changes every block of the route made to the accounts they
+	/// watch. Each block is compared with its parent, retracted blocks are reported as removed.
+	/// The watched items of all subscribers are read once per block.
+	fn notify_state_changes(
+		client: &C,
+		executor: &Executor,
+		subscribers: &RwLock<Subscribers<(Client, WatchedAccounts)>>,
+		watcher: &mut StateWatcher,
+		route: &[(H256, ChainRouteType)],
+	) {
+		let subscribers = subscribers.read().values().cloned().collect::<Vec<_>>();
+		if subscribers.is_empty() {
+			return;
+		}
+		let mut items = WatchedItems::new();
+		for (_, watched) in &subscribers {
+			watched.add_to(&mut items);
+		}
+
+		for &(hash, ref typ) in route {
+			let header = match client.block_header(BlockId::Hash(hash)) {
+				Some(header) => header,
+				None => continue,
+			};
+			let (before, after) = match watcher.values(client, &items, hash, header.parent_hash()) {
+				Some(values) => values,
+				None => {
+					debug!(target: "rpc", "State of block {} is not available for watched accounts", hash);
+					continue;
+				},
+			};
+			for (subscriber, watched) in &subscribers {
+				let accounts = watched.changes(&before, after);
+				if accounts.is_empty() {
+					continue;
+				}
+
+				Self::notify(executor, subscriber, pubsub::Result::StateChanges(Box::new(pubsub::StateChanges {
+					block_hash: hash,
+					block_number: header.number().into(),
+					removed: match *typ {
+						ChainRouteType::Retracted => true,
+						ChainRouteType::Enacted => false,
+					},
+					accounts,
+				})));
+			}
+		}
+	}
+}
+
+impl<C: BlockChainClient + 'static> ChainNotify for ChainNotificationHandler<C> {
 	fn new_blocks(&self, new_blocks: NewBlocks) {
-		if self.heads_subscribers.read().is_empty() && self.logs_subscribers.read().is_empty() { return }
+		let watch_state = !self.state_subscribers.read().is_empty();
+		self.client.record_state_writes(watch_state);
+		if self.heads_subscribers.read().is_empty()
+			&& self.logs_subscribers.read().is_empty()
+			&& self.reorgs_subscribers.read().is_empty()
+			&& !watch_state { return }
 		const EXTRA_INFO_PROOF: &str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 		let headers = new_blocks.route.route()
 			.iter()
@@ -292,6 +416,22 @@ impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
 		// Headers
 		self.notify_heads(&headers);
 
+		// Reorganizations
+		let route = &new_blocks.route;
+		if !route.retracted().is_empty() && !self.reorgs_subscribers.read().is_empty() {
+			let transactions = |blocks: &[H256]| blocks
+				.iter()
+				.filter_map(|hash| self.client.block_body(BlockId::Hash(*hash)))
+				.flat_map(|body| body.transaction_hashes())
+				.collect::<Vec<_>>();
+			self.notify_reorg(route.retracted(), route.enacted(), transactions(route.retracted()), transactions(route.enacted()));
+		}
+
+		// Watched accounts
+		if watch_state {
+			self.queue_state_changes(route.route());
+		}
+
 		// We notify logs enacting and retracting as the order in route.
 		self.notify_logs(new_blocks.route.route(), |filter, ex| {
 			match ex {
@@ -349,6 +489,31 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 			(pubsub::Kind::NewPendingTransactions, _) => {
 				errors::invalid_params("newPendingTransactions", "Expected no parameters.")
 			},
+			(pubsub::Kind::Reorgs, None) => {
+				self.reorgs_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::Reorgs, _) => {
+				errors::invalid_params("reorgs", "Expected no parameters.")
+			},
+			(pubsub::Kind::StateChanges, _) if !self.watch_state => {
+				errors::light_unimplemented(None)
+			},
+			(pubsub::Kind::StateChanges, Some(pubsub::Params::StateChanges(watched))) => {
+				let mut state_subscribers = self.state_subscribers.write();
+				match WatchedAccounts::new(watched) {
+					_ if state_subscribers.len() >= MAX_STATE_SUBSCRIBERS =>
+						errors::request_rejected_param_limit(MAX_STATE_SUBSCRIBERS as u64, "stateChanges subscriptions"),
+					Some(watched) => {
+						state_subscribers.push(subscriber, watched);
+						return;
+					},
+					None => errors::request_rejected_param_limit(MAX_WATCHED_ITEMS as u64, "watched accounts and storage slots"),
+				}
+			},
+			(pubsub::Kind::StateChanges, _) => {
+				errors::invalid_params("stateChanges", "Expected an object with watched addresses.")
+			},
 			_ => {
 				errors::unimplemented(None)
 			},
@@ -362,7 +527,9 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 		let res2 = self.logs_subscribers.write().remove(&id).is_some();
 		let res3 = self.transactions_subscribers.write().remove(&id).is_some();
 		let res4 = self.sync_subscribers.write().remove(&id).is_some();
+		let res5 = self.reorgs_subscribers.write().remove(&id).is_some();
+		let res6 = self.state_subscribers.write().remove(&id).is_some();
 
-		Ok(res || res2 || res3 || res4)
+		Ok(res || res2 || res3 || res4 || res5 || res6)
 	}
 }
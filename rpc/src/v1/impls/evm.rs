@@ -0,0 +1,134 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Development chain rpc implementation.
+
+use std::sync::Arc;
+
+use client_traits::{BlockChainClient, BlockChainReset, ForceUpdateSealing};
+use engine::DevMining;
+use ethcore::client::EngineInfo;
+use ethcore::miner::{self, MinerService};
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+use miner::pool::VerifiedTransaction;
+use parking_lot::Mutex;
+use types::BlockNumber;
+
+use v1::helpers::errors;
+use v1::traits::Evm;
+
+/// State of the development chain restored by `evm_revert`.
+struct Snapshot {
+	best_block: BlockNumber,
+	time_offset: u64,
+	pending: Vec<Arc<VerifiedTransaction>>,
+}
+
+/// Development chain rpc implementation.
+pub struct EvmClient<C, M> {
+	client: Arc<C>,
+	miner: Arc<M>,
+	snapshots: Mutex<Vec<Snapshot>>,
+}
+
+impl<C, M> EvmClient<C, M> {
+	/// Creates new `EvmClient`.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		EvmClient {
+			client: client.clone(),
+			miner: miner.clone(),
+			snapshots: Mutex::new(Vec::new()),
+		}
+	}
+}
+
+impl<C, M> EvmClient<C, M> where
+	C: EngineInfo,
+{
+	fn dev_mining(&self) -> Result<&dyn DevMining> {
+		self.client.engine().dev_mining()
+			.ok_or_else(|| errors::unsupported("The chain engine does not support manual mining.", None))
+	}
+}
+
+impl<C, M> Evm for EvmClient<C, M> where
+	C: miner::BlockChainClient + BlockChainClient + BlockChainReset + EngineInfo + 'static,
+	M: MinerService + 'static,
+{
+	fn mine(&self) -> Result<bool> {
+		self.dev_mining()?.force_seal();
+		self.miner.update_sealing(&*self.client, ForceUpdateSealing::Yes);
+		Ok(true)
+	}
+
+	fn set_automine(&self, automine: bool) -> Result<bool> {
+		self.dev_mining()?.set_automine(automine);
+		Ok(true)
+	}
+
+	fn increase_time(&self, seconds: u64) -> Result<u64> {
+		self.dev_mining()?.increase_time(seconds)
+			.ok_or_else(|| errors::invalid_params("seconds", "the time offset would overflow"))
+	}
+
+	fn snapshot(&self) -> Result<U256> {
+		let time_offset = self.dev_mining()?.time_offset();
+		let mut snapshots = self.snapshots.lock();
+		snapshots.push(Snapshot {
+			best_block: self.client.chain_info().best_block_number,
+			time_offset,
+			pending: self.miner.queued_transactions(),
+		});
+		Ok(U256::from(snapshots.len()))
+	}
+
+	fn revert(&self, id: U256) -> Result<bool> {
+		let dev_mining = self.dev_mining()?;
+		let mut snapshots = self.snapshots.lock();
+		if id.is_zero() || id > U256::from(snapshots.len()) {
+			return Ok(false);
+		}
+
+		let index = id.low_u64() as usize - 1;
+		let snapshot = snapshots.swap_remove(index);
+		snapshots.truncate(index);
+
+		let best = self.client.chain_info().best_block_number;
+		if best > snapshot.best_block {
+			self.client.rewind((best - snapshot.best_block) as u32).map_err(|e| errors::internal("Could not revert the chain", e))?;
+		}
+		dev_mining.set_time_offset(snapshot.time_offset);
+
+		// put back the transactions pending when the snapshot was taken, in place of the current ones.
+		for tx in self.miner.queued_transactions() {
+			self.miner.remove_transaction(&tx.signed().hash());
+		}
+		for tx in snapshot.pending {
+			let pending = tx.pending().clone();
+			let imported = if tx.is_local() {
+				self.miner.import_own_transaction(&*self.client, pending)
+			} else {
+				self.miner.import_external_transactions(&*self.client, vec![pending.transaction.into()])
+					.into_iter().next().unwrap_or(Ok(()))
+			};
+			if let Err(e) = imported {
+				debug!(target: "rpc", "Dropped transaction {:?} pending at the snapshot: {}", tx.signed().hash(), e);
+			}
+		}
+		Ok(true)
+	}
+}
@@ -28,7 +28,7 @@ use light::client::LightChainClient;
 use light::{cht, TransactionQueue};
 use light::on_demand::{request, OnDemandRequester};
 
-use ethereum_types::{Address, H64, H160, H256, U64, U256};
+use ethereum_types::{Address, BigEndianHash, H64, H160, H256, U64, U256};
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY_LIST_RLP};
 use parking_lot::{RwLock, Mutex};
 use rlp::Rlp;
@@ -37,14 +37,16 @@ use types::encoded;
 use types::filter::Filter as EthcoreFilter;
 use types::ids::BlockId;
 
+use v1::impls::eth::MAX_BATCH_STATE_READS;
 use v1::impls::eth_filter::Filterable;
-use v1::helpers::{errors, limit_logs, SyncPollFilter, PollManager};
+use v1::helpers::{errors, limit_logs, SyncPollFilter, PollLimits, PollManager};
 use v1::helpers::deprecated::{self, DeprecationNotice};
 use v1::helpers::light_fetch::{self, LightFetch};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, LightBlockNumber, Bytes, SyncStatus as RpcSyncStatus,
-	SyncInfo as RpcSyncInfo, Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount
+	SyncInfo as RpcSyncInfo, Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount,
+	chain_gaps,
 };
 use v1::metadata::Metadata;
 
@@ -61,7 +63,7 @@ pub struct EthClient<C, S: LightSyncProvider + LightNetworkDispatcher + 'static,
 	accounts: Arc<dyn Fn() -> Vec<Address> + Send + Sync>,
 	cache: Arc<Mutex<LightDataCache>>,
 	polls: Mutex<PollManager<SyncPollFilter>>,
-	poll_lifetime: u32,
+	poll_limits: PollLimits,
 	gas_price_percentile: usize,
 	deprecation_notice: DeprecationNotice,
 }
@@ -80,8 +82,8 @@ where
 			transaction_queue: self.transaction_queue.clone(),
 			accounts: self.accounts.clone(),
 			cache: self.cache.clone(),
-			polls: Mutex::new(PollManager::new(self.poll_lifetime)),
-			poll_lifetime: self.poll_lifetime,
+			polls: Mutex::new(PollManager::new(self.poll_limits)),
+			poll_limits: self.poll_limits,
 			gas_price_percentile: self.gas_price_percentile,
 			deprecation_notice: Default::default(),
 		}
@@ -104,7 +106,7 @@ where
 		accounts: Arc<dyn Fn() -> Vec<Address> + Send + Sync>,
 		cache: Arc<Mutex<LightDataCache>>,
 		gas_price_percentile: usize,
-		poll_lifetime: u32
+		poll_limits: PollLimits,
 	) -> Self {
 		EthClient {
 			sync,
@@ -113,8 +115,8 @@ where
 			transaction_queue,
 			accounts,
 			cache,
-			polls: Mutex::new(PollManager::new(poll_lifetime)),
-			poll_lifetime,
+			polls: Mutex::new(PollManager::new(poll_limits)),
+			poll_limits,
 			gas_price_percentile,
 			deprecation_notice: Default::default(),
 		}
@@ -244,6 +246,7 @@ where
 				highest_block,
 				warp_chunks_amount: None,
 				warp_chunks_processed: None,
+				chain_gaps: chain_gaps(&chain_info, None),
 			}))
 		} else {
 			Ok(RpcSyncStatus::None)
@@ -292,8 +295,31 @@ where
 			.map(|acc| acc.map_or(0.into(), |a| a.balance)))
 	}
 
-	fn storage_at(&self, _address: H160, _key: U256, _num: Option<BlockNumber>) -> BoxFuture<H256> {
-		Box::new(future::err(errors::unimplemented(None)))
+	fn storage_at(&self, address: H160, key: U256, num: Option<BlockNumber>) -> BoxFuture<H256> {
+		Box::new(self.fetcher().storage_at(address, BigEndianHash::from_uint(&key), num.unwrap_or_default().to_block_id()))
+	}
+
+	fn balances(&self, addresses: Vec<H160>, num: Option<BlockNumber>) -> BoxFuture<Vec<U256>> {
+		if addresses.len() > MAX_BATCH_STATE_READS {
+			return Box::new(future::err(errors::request_rejected_param_limit(MAX_BATCH_STATE_READS as u64, "reads")));
+		}
+		let id = num.unwrap_or_default().to_block_id();
+		let fetcher = self.fetcher();
+		Box::new(future::join_all(addresses.into_iter().map(move |address| {
+			fetcher.account(address, id, self.transaction_queue.clone())
+				.map(|acc| acc.map_or(0.into(), |a| a.balance))
+		}).collect::<Vec<_>>()))
+	}
+
+	fn storage_at_batch(&self, positions: Vec<(H160, U256)>, num: Option<BlockNumber>) -> BoxFuture<Vec<H256>> {
+		if positions.len() > MAX_BATCH_STATE_READS {
+			return Box::new(future::err(errors::request_rejected_param_limit(MAX_BATCH_STATE_READS as u64, "reads")));
+		}
+		let id = num.unwrap_or_default().to_block_id();
+		let fetcher = self.fetcher();
+		Box::new(future::join_all(positions.into_iter().map(move |(address, position)| {
+			fetcher.storage_at(address, BigEndianHash::from_uint(&position), id)
+		}).collect::<Vec<_>>()))
 	}
 
 	fn block_by_hash(&self, hash: H256, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
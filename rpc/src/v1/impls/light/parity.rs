@@ -42,11 +42,12 @@ use v1::traits::Parity;
 use v1::types::{
 	Bytes, CallRequest,
 	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
-	LightBlockNumber, ChainStatus, Receipt,
+	TransactionStats, LocalTransaction, LocalTransactionStatus, TransactionAge, TxPoolInspection,
+	LightBlockNumber, ChainStatus, ChainGap, Receipt,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, Header, RichHeader, RecoveredAccount,
-	Log, Filter,
+	Log, Filter, PendingBlockStats, UncleCandidate, StateDiffPage, ConfirmationStatus, ValidatorStatus,
+	EpochValidators, ThreatStatus, GasStats, AddressOrName, TokenBalance, ReceiptProof, AncestryProof, chain_gaps,
 };
 use Host;
 use v1::helpers::errors::light_unimplemented;
@@ -136,7 +137,7 @@ where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String> {
@@ -167,6 +168,14 @@ where
 		Ok(self.light_dispatch.client.engine().params().registrar)
 	}
 
+	fn resolve_ens(&self, _: String) -> Result<Option<H160>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn reverse_ens(&self, _: H160) -> Result<Option<String>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn rpc_settings(&self) -> Result<RpcSettings> {
 		Ok(RpcSettings {
 			enabled: self.settings.rpc_enabled,
@@ -204,10 +213,14 @@ where
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn list_storage_keys(&self, _: H160, _: Option<u64>, _: Option<H256>, _: Option<BlockNumber>) -> Result<Option<Vec<H256>>> {
+	fn list_storage_keys(&self, _: AddressOrName, _: Option<u64>, _: Option<H256>, _: Option<BlockNumber>) -> Result<Option<Vec<H256>>> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn state_diff(&self, _: BlockNumber, _: BlockNumber, _: u64, _: Option<H160>) -> BoxFuture<StateDiffPage> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key, &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -261,18 +274,34 @@ where
 		)
 	}
 
-	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
+	fn transaction_age(&self, _hash: H256) -> Result<Option<TransactionAge>> {
+		Err(light_unimplemented(None))
+	}
+
+	fn txpool_inspect(&self) -> Result<TxPoolInspection> {
+		Err(light_unimplemented(None))
+	}
+
+	fn pending_block_stats(&self) -> Result<PendingBlockStats> {
+		Err(light_unimplemented(None))
+	}
+
+	fn uncle_candidates(&self) -> Result<Vec<UncleCandidate>> {
+		Err(light_unimplemented(None))
+	}
+
+	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransaction>> {
 		let mut map = BTreeMap::new();
 		let chain_info = self.light_dispatch.client.chain_info();
 		let (best_num, best_tm) = (chain_info.best_block_number, chain_info.best_block_timestamp);
 		let txq = self.light_dispatch.transaction_queue.read();
 
 		for pending in txq.ready_transactions(best_num, best_tm) {
-			map.insert(pending.hash(), LocalTransactionStatus::Pending);
+			map.insert(pending.hash(), LocalTransactionStatus::Pending.into());
 		}
 
 		for future in txq.future_transactions(best_num, best_tm) {
-			map.insert(future.hash(), LocalTransactionStatus::Future);
+			map.insert(future.hash(), LocalTransactionStatus::Future.into());
 		}
 
 		// TODO: other types?
@@ -285,8 +314,11 @@ where
 			.ok_or_else(errors::ws_disabled)
 	}
 
-	fn next_nonce(&self, address: H160) -> BoxFuture<U256> {
-		Box::new(self.light_dispatch.next_nonce(address))
+	fn next_nonce(&self, address: AddressOrName) -> BoxFuture<U256> {
+		match address {
+			AddressOrName::Address(address) => Box::new(self.light_dispatch.next_nonce(address)),
+			AddressOrName::Name(_) => Box::new(future::err(errors::light_unimplemented(None))),
+		}
 	}
 
 	fn mode(&self) -> Result<String> {
@@ -324,6 +356,11 @@ where
 		})
 	}
 
+	fn chain_gaps(&self) -> Result<Vec<ChainGap>> {
+		// light clients keep no state to report gaps in.
+		Ok(chain_gaps(&self.light_dispatch.client.chain_info(), None))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -376,6 +413,10 @@ where
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn token_balances(&self, _: AddressOrName, _: Vec<AddressOrName>, _: Option<BlockNumber>) -> Result<Vec<TokenBalance>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn submit_work_detail(&self, _nonce: H64, _pow_hash: H256, _mix_hash: H256) -> Result<H256> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -414,4 +455,36 @@ where
 	fn submit_raw_block(&self, _block: Bytes) -> Result<H256> {
 		Err(light_unimplemented(None))
 	}
+
+	fn cht_root(&self, number: U64) -> Result<Option<H256>> {
+		Ok(self.light_dispatch.client.cht_root(number.as_u64() as usize))
+	}
+
+	fn confirmation_status(&self, _: H256) -> Result<Option<ConfirmationStatus>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn validator_status(&self) -> Result<ValidatorStatus> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn validator_set_at(&self, _: BlockNumber) -> Result<Option<EpochValidators>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn threat_status(&self) -> Result<ThreatStatus> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn gas_stats(&self, _: BlockNumber, _: BlockNumber, _: Option<usize>) -> BoxFuture<GasStats> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
+
+	fn receipt_proof(&self, _: H256) -> Result<Option<ReceiptProof>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn ancestry_proof(&self, _: BlockNumber, _: Option<BlockNumber>) -> Result<Option<AncestryProof>> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -20,7 +20,8 @@
 use std::io;
 use std::sync::Arc;
 
-use ethereum_types::{H160, H256, U256};
+use ethcore_logger::RotatingLogger;
+use ethereum_types::{H256, H520, U256};
 use fetch::{self, Fetch};
 use hash::keccak_buffer;
 use light::client::LightChainClient;
@@ -30,21 +31,34 @@ use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_core::futures::Future;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, ReleaseInfo, Transaction};
+use v1::types::{AddressOrName, Bytes, InstalledFilter, ReleaseInfo, Transaction};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
 	client: Arc<dyn LightChainClient>,
 	net: Arc<dyn ManageNetwork>,
+	logger: Arc<RotatingLogger>,
+	shutdown: Arc<dyn Fn() + Send + Sync>,
+	reload: Arc<dyn Fn(bool) -> ::std::result::Result<Vec<String>, String> + Send + Sync>,
 	fetch: F,
 }
 
 impl<F: Fetch> ParitySetClient<F> {
 	/// Creates new `ParitySetClient` with given `Fetch`.
-	pub fn new(client: Arc<dyn LightChainClient>, net: Arc<dyn ManageNetwork>, fetch: F) -> Self {
+	pub fn new(
+		client: Arc<dyn LightChainClient>,
+		net: Arc<dyn ManageNetwork>,
+		logger: Arc<RotatingLogger>,
+		shutdown: Arc<dyn Fn() + Send + Sync>,
+		reload: Arc<dyn Fn(bool) -> ::std::result::Result<Vec<String>, String> + Send + Sync>,
+		fetch: F,
+	) -> Self {
 		ParitySetClient {
 			client,
 			net,
+			logger,
+			shutdown,
+			reload,
 			fetch,
 		}
 	}
@@ -67,7 +81,7 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn set_author(&self, _author: H160) -> Result<bool> {
+	fn set_author(&self, _author: AddressOrName) -> Result<bool> {
 		Err(errors::light_unimplemented(None))
 	}
 
@@ -129,6 +143,19 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		self.client.set_spec_name(spec_name).map(|_| true).map_err(|()| errors::cannot_restart())
 	}
 
+	fn set_logging_targets(&self, targets: String) -> Result<bool> {
+		self.logger.set_levels(targets).map(|_| true).map_err(|e| errors::invalid_params("targets", e))
+	}
+
+	fn shutdown(&self) -> Result<bool> {
+		(self.shutdown)();
+		Ok(true)
+	}
+
+	fn reload_config(&self, dry_run: Option<bool>) -> Result<Vec<String>> {
+		(self.reload)(dry_run.unwrap_or(false)).map_err(|e| errors::invalid_params("configuration", e))
+	}
+
 	fn hash_content(&self, url: String) -> BoxFuture<H256> {
 		let future = self.fetch.get(&url, Default::default()).then(move |result| {
 			result
@@ -153,4 +180,28 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn reload_engine_params(&self, _update: Bytes, _signature: H520) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn accept_reorg(&self, _hash: H256) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn rewrite_database(&self) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn list_filters(&self) -> Result<Vec<InstalledFilter>> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn pause_sync(&self, _timeout: Option<u64>) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn resume_sync(&self) -> Result<bool> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -17,7 +17,8 @@
 //! Traces api implementation.
 
 use ethereum_types::H256;
-use jsonrpc_core::Result;
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_core::futures::future;
 use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::errors;
@@ -31,12 +32,12 @@ pub struct TracesClient;
 impl Traces for TracesClient {
 	type Metadata = Metadata;
 
-	fn filter(&self, _filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>> {
-		Err(errors::light_unimplemented(None))
+	fn filter(&self, _filter: TraceFilter) -> BoxFuture<Option<Vec<LocalizedTrace>>> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
-	fn block_traces(&self, _block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
-		Err(errors::light_unimplemented(None))
+	fn block_traces(&self, _block_number: BlockNumber) -> BoxFuture<Option<Vec<LocalizedTrace>>> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
 	fn transaction_traces(&self, _transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>> {
@@ -47,23 +48,23 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn call(&self, _request: CallRequest, _flags: TraceOptions, _block: Option<BlockNumber>) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn call(&self, _request: CallRequest, _flags: TraceOptions, _block: Option<BlockNumber>) -> BoxFuture<TraceResults> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
-	fn call_many(&self, _request: Vec<(CallRequest, TraceOptions)>, _block: Option<BlockNumber>) -> Result<Vec<TraceResults>> {
-		Err(errors::light_unimplemented(None))
+	fn call_many(&self, _request: Vec<(CallRequest, TraceOptions)>, _block: Option<BlockNumber>) -> BoxFuture<Vec<TraceResults>> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
-	fn raw_transaction(&self, _raw_transaction: Bytes, _flags: TraceOptions, _block: Option<BlockNumber>) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn raw_transaction(&self, _raw_transaction: Bytes, _flags: TraceOptions, _block: Option<BlockNumber>) -> BoxFuture<TraceResults> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
-	fn replay_transaction(&self, _transaction_hash: H256, _flags: TraceOptions) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn replay_transaction(&self, _transaction_hash: H256, _flags: TraceOptions) -> BoxFuture<TraceResults> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 
-	fn replay_block_transactions(&self,  _block_number: BlockNumber, _flags: TraceOptions) ->  Result<Vec<TraceResultsWithTransactionHash>> {
-		Err(errors::light_unimplemented(None))
+	fn replay_block_transactions(&self,  _block_number: BlockNumber, _flags: TraceOptions) ->  BoxFuture<Vec<TraceResultsWithTransactionHash>> {
+		Box::new(future::err(errors::light_unimplemented(None)))
 	}
 }
@@ -0,0 +1,52 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Miner settings rpc implementation.
+
+use std::sync::Arc;
+
+use ethcore::miner::MinerService;
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+
+use v1::helpers::errors;
+use v1::traits::Miner;
+
+/// Miner settings rpc implementation.
+pub struct MinerClient<M> {
+	miner: Arc<M>,
+}
+
+impl<M> MinerClient<M> {
+	/// Creates new `MinerClient`.
+	pub fn new(miner: &Arc<M>) -> Self {
+		MinerClient {
+			miner: miner.clone(),
+		}
+	}
+}
+
+impl<M> Miner for MinerClient<M> where
+	M: MinerService + 'static,
+{
+	fn set_gas_limit(&self, target: U256) -> Result<bool> {
+		if target.is_zero() {
+			return Err(errors::invalid_params("gasLimit", "Gas limit target must be greater than zero."));
+		}
+		self.miner.set_gas_range_target((target, target));
+		Ok(true)
+	}
+}
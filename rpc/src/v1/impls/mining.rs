@@ -0,0 +1,56 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Mining statistics rpc implementation.
+
+use std::sync::Arc;
+
+use ethcore_stratum::WorkerStatsProvider;
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+
+use v1::helpers::errors;
+use v1::traits::Mining;
+use v1::types::StratumWorker;
+
+/// Mining statistics rpc implementation.
+pub struct MiningClient {
+	workers: Option<Arc<dyn WorkerStatsProvider>>,
+}
+
+impl MiningClient {
+	/// Creates new `MiningClient`, `workers` is `None` if the stratum server is not running.
+	pub fn new(workers: Option<Arc<dyn WorkerStatsProvider>>) -> Self {
+		MiningClient {
+			workers,
+		}
+	}
+
+	fn workers_provider(&self) -> Result<&Arc<dyn WorkerStatsProvider>> {
+		self.workers.as_ref().ok_or_else(errors::stratum_disabled)
+	}
+}
+
+impl Mining for MiningClient {
+	fn workers(&self) -> Result<Vec<StratumWorker>> {
+		Ok(self.workers_provider()?.workers().into_iter().map(Into::into).collect())
+	}
+
+	fn hashrate(&self) -> Result<U256> {
+		Ok(self.workers_provider()?.workers().iter().fold(U256::zero(), |sum, worker| sum.saturating_add(worker.hashrate)))
+	}
+}
@@ -16,10 +16,17 @@
 
 //! Ethereum rpc interface implementation.
 
+mod clique;
 mod debug;
+mod debug_pubsub;
+mod engine_api;
 mod eth;
+mod eth_bundle;
 mod eth_filter;
 mod eth_pubsub;
+mod evm;
+mod miner;
+mod mining;
 mod net;
 mod parity;
 #[cfg(any(test, feature = "accounts"))]
@@ -32,6 +39,7 @@ mod pubsub;
 mod rpc;
 #[cfg(any(test, feature = "accounts"))]
 mod secretstore;
+mod shh;
 mod signer;
 mod signing;
 mod signing_unsafe;
@@ -41,10 +49,17 @@ mod web3;
 
 pub mod light;
 
+pub use self::clique::CliqueClient;
 pub use self::debug::DebugClient;
+pub use self::debug_pubsub::DebugPubSubClient;
+pub use self::engine_api::EngineApiClient;
 pub use self::eth::{EthClient, EthClientOptions};
+pub use self::eth_bundle::EthBundleClient;
 pub use self::eth_filter::EthFilterClient;
 pub use self::eth_pubsub::EthPubSubClient;
+pub use self::evm::EvmClient;
+pub use self::miner::MinerClient;
+pub use self::mining::MiningClient;
 pub use self::transactions_pool::TransactionsPoolClient;
 pub use self::net::NetClient;
 pub use self::parity::ParityClient;
@@ -60,6 +75,7 @@ pub use self::pubsub::PubSubClient;
 pub use self::rpc::RpcClient;
 #[cfg(any(test, feature = "accounts"))]
 pub use self::secretstore::SecretStoreClient;
+pub use self::shh::ShhClient;
 pub use self::signer::SignerClient;
 pub use self::signing::SigningQueueClient;
 pub use self::signing_unsafe::SigningUnsafeClient;
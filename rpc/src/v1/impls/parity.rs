@@ -15,13 +15,14 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Parity-specific rpc implementation.
+use std::cmp;
 use std::sync::Arc;
 use std::collections::BTreeMap;
 
 use crypto::DEFAULT_MAC;
 use ethereum_types::{H64, H160, H256, H512, U64, U256};
-use ethcore::client::Call;
-use client_traits::{BlockChainClient, StateClient};
+use ethcore::client::{Call, EngineInfo};
+use client_traits::{BlockChainClient, ProvingBlockChainClient, StateClient};
 use ethcore::miner::{self, MinerService, FilterOptions};
 use snapshot::SnapshotService;
 use account_state::state::StateInfo;
@@ -31,9 +32,11 @@ use crypto::publickey::{ecies, Generator};
 use ethstore::random_phrase;
 use jsonrpc_core::futures::future;
 use jsonrpc_core::{BoxFuture, Result};
+use light::ChtStore;
 use sync::{SyncProvider, ManageNetwork};
 use types::{
-	ids::BlockId,
+	header::Header,
+	ids::{BlockId, TransactionId},
 	verification::Unverified,
 	snapshot::RestorationStatus,
 };
@@ -41,20 +44,36 @@ use updater::{Service as UpdateService};
 use version::version_data;
 
 use v1::helpers::{self, errors, fake_sign, NetworkSettings, verify_signature};
+use v1::helpers::ens::EnsCache;
+use v1::helpers::heavy::RequestPools;
 use v1::helpers::external_signer::{SigningQueue, SignerService};
+use v1::helpers::tokens::{TokenMetadata, TokenMetadataCache};
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
+	Peers, ForkId, NatStatus, Transaction, RpcSettings, Histogram,
+	TransactionStats, LocalTransaction, TransactionAge, TxPoolInspection,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt, RecoveredAccount,
-	block_number_to_id
+	OperationsInfo, ChainStatus, ChainGap, Log, Filter,
+	RichHeader, Receipt, RecoveredAccount, PendingBlockStats, UncleCandidate, StateDiffPage, ConfirmationStatus,
+	ValidatorStatus, EpochValidators, ThreatStatus, GasStats, AddressOrName, TokenBalance, ReceiptProof, AncestryProof, block_number_to_id, chain_gaps,
 };
 use Host;
 
+/// Maximal number of blocks between the two states compared by `parity_stateDiff`.
+const MAX_STATE_DIFF_BLOCKS: u64 = 1024;
+/// Maximal number of blocks aggregated by `parity_gasStats`. The client caches the statistics
+/// of about as many blocks.
+const MAX_GAS_STATS_BLOCKS: u64 = 2048;
+/// Maximal number of contracts returned by `parity_gasStats`.
+const MAX_GAS_STATS_CONTRACTS: usize = 100;
+/// Maximal number of tokens queried by `parity_tokenBalances`.
+const MAX_TOKEN_BALANCES: usize = 256;
+/// Gas given to every call made by `parity_tokenBalances`, plenty for the view functions of
+/// ERC-20 tokens.
+const TOKEN_CALL_GAS: u64 = 50_000;
+
 /// Parity implementation.
 pub struct ParityClient<C, M, U> {
 	client: Arc<C>,
@@ -67,6 +86,10 @@ pub struct ParityClient<C, M, U> {
 	signer: Option<Arc<SignerService>>,
 	ws_address: Option<Host>,
 	snapshot: Option<Arc<dyn SnapshotService>>,
+	cht_store: Option<Arc<ChtStore>>,
+	ens: EnsCache,
+	token_metadata: TokenMetadataCache,
+	pools: RequestPools,
 }
 
 impl<C, M, U> ParityClient<C, M, U> where
@@ -84,6 +107,8 @@ impl<C, M, U> ParityClient<C, M, U> where
 		signer: Option<Arc<SignerService>>,
 		ws_address: Option<Host>,
 		snapshot: Option<Arc<dyn SnapshotService>>,
+		cht_store: Option<Arc<ChtStore>>,
+		pools: RequestPools,
 	) -> Self {
 		ParityClient {
 			client,
@@ -96,13 +121,61 @@ impl<C, M, U> ParityClient<C, M, U> where
 			signer,
 			ws_address,
 			snapshot,
+			cht_store,
+			ens: EnsCache::default(),
+			token_metadata: TokenMetadataCache::default(),
+			pools,
+		}
+	}
+
+	/// Address `name` resolves to in ENS at the best block, resolved once per block.
+	fn resolve_name(&self, name: &str) -> Result<Option<H160>> {
+		let head = self.client.chain_info().best_block_hash;
+		self.ens.address(head, name, || self.client.ens_address(name, BlockId::Hash(head)))
+			.map_err(errors::name_resolution)
+	}
+
+	fn address_of(&self, address: AddressOrName) -> Result<H160> {
+		match address {
+			AddressOrName::Address(address) => Ok(address),
+			AddressOrName::Name(name) => self.resolve_name(&name)?.ok_or_else(|| errors::unknown_name(&name)),
 		}
 	}
 }
 
-impl<C, M, U, S> Parity for ParityClient<C, M, U> where
+impl<C, M, U, S> ParityClient<C, M, U> where
 	S: StateInfo + 'static,
-	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + 'static,
+	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static,
+	M: MinerService<State=S> + 'static,
+{
+	/// State and header to make calls against at the given block.
+	fn state_and_header(&self, num: BlockNumber) -> Result<(S, Header)> {
+		if num == BlockNumber::Pending {
+			let info = self.client.chain_info();
+			let state = self.miner.pending_state(info.best_block_number).ok_or_else(errors::state_pruned)?;
+			let header = self.miner.pending_block_header(info.best_block_number).ok_or_else(errors::state_pruned)?;
+
+			Ok((state, header))
+		} else {
+			let id = match num {
+				BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+				BlockNumber::Num(num) => BlockId::Number(num),
+				BlockNumber::Earliest => BlockId::Earliest,
+				BlockNumber::Latest => BlockId::Latest,
+				BlockNumber::Pending => unreachable!(), // Already covered
+			};
+
+			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
+			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
+
+			Ok((state, header))
+		}
+	}
+}
+
+impl<C, M, U, S> Parity for ParityClient<C, M, U> where
+	S: StateInfo + Clone + 'static,
+	C: miner::BlockChainClient + BlockChainClient + ProvingBlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static,
 	M: MinerService<State=S> + 'static,
 	U: UpdateService + 'static,
 {
@@ -135,7 +208,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String> {
@@ -172,6 +245,16 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Ok(self.client.registrar_address())
 	}
 
+	fn resolve_ens(&self, name: String) -> Result<Option<H160>> {
+		self.resolve_name(&name.to_lowercase())
+	}
+
+	fn reverse_ens(&self, address: H160) -> Result<Option<String>> {
+		let head = self.client.chain_info().best_block_hash;
+		self.ens.name(head, &address, || self.client.ens_name(address, BlockId::Hash(head)))
+			.map_err(errors::name_resolution)
+	}
+
 	fn rpc_settings(&self) -> Result<RpcSettings> {
 		Ok(RpcSettings {
 			enabled: self.settings.rpc_enabled,
@@ -223,7 +306,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
-	fn list_storage_keys(&self, address: H160, count: Option<u64>, after: Option<H256>, block_number: Option<BlockNumber>) -> Result<Option<Vec<H256>>> {
+	fn list_storage_keys(&self, address: AddressOrName, count: Option<u64>, after: Option<H256>, block_number: Option<BlockNumber>) -> Result<Option<Vec<H256>>> {
+		let address = self.address_of(address)?;
 		let number = match block_number.unwrap_or_default() {
 			BlockNumber::Pending => {
 				warn!("BlockNumber::Pending is unsupported");
@@ -238,6 +322,29 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
+	fn state_diff(&self, from: BlockNumber, to: BlockNumber, count: u64, after: Option<H160>) -> BoxFuture<StateDiffPage> {
+		let client = self.client.clone();
+		self.pools.traces.run(move |_| {
+			if from == BlockNumber::Pending || to == BlockNumber::Pending {
+				return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ()));
+			}
+			let (from, to) = (block_number_to_id(from), block_number_to_id(to));
+
+			let from_number = client.block_number(from).ok_or_else(errors::unknown_block)?;
+			let to_number = client.block_number(to).ok_or_else(errors::unknown_block)?;
+			let distance = cmp::max(from_number, to_number) - cmp::min(from_number, to_number);
+			if distance > MAX_STATE_DIFF_BLOCKS {
+				return Err(errors::invalid_params("blocks", format!("at most {} blocks apart", MAX_STATE_DIFF_BLOCKS)));
+			}
+
+			let (diff, next) = client.state_diff(from, to, after.as_ref(), count).map_err(errors::call)?;
+			Ok(StateDiffPage {
+				diff: diff.into(),
+				next,
+			})
+		})
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key, &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -285,11 +392,29 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		)
 	}
 
-	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
+	fn transaction_age(&self, hash: H256) -> Result<Option<TransactionAge>> {
+		Ok(self.miner.transaction_age(&hash).map(Into::into))
+	}
+
+	fn txpool_inspect(&self) -> Result<TxPoolInspection> {
+		Ok(self.miner.inspect_pool(&*self.client).into())
+	}
+
+	fn pending_block_stats(&self) -> Result<PendingBlockStats> {
+		Ok(self.miner.pending_block_stats().into())
+	}
+
+	fn uncle_candidates(&self) -> Result<Vec<UncleCandidate>> {
+		let author = self.miner.authoring_params().author;
+		Ok(self.client.uncle_candidates(&author).into_iter().map(Into::into).collect())
+	}
+
+	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransaction>> {
 		let transactions = self.miner.local_transactions();
+		let submissions = self.miner.local_transaction_submissions();
 		Ok(transactions
 			.into_iter()
-			.map(|(hash, status)| (hash, LocalTransactionStatus::from(status)))
+			.map(|(hash, status)| (hash, LocalTransaction::new(status, submissions.get(&hash).cloned())))
 			.collect()
 		)
 	}
@@ -299,7 +424,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			.ok_or_else(errors::ws_disabled)
 	}
 
-	fn next_nonce(&self, address: H160) -> BoxFuture<U256> {
+	fn next_nonce(&self, address: AddressOrName) -> BoxFuture<U256> {
+		let address = try_bf!(self.address_of(address));
 		Box::new(future::ok(self.miner.next_nonce(&*self.client, &address)))
 	}
 
@@ -311,6 +437,14 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		self.sync.enode().ok_or_else(errors::network_disabled)
 	}
 
+	fn fork_id(&self) -> Result<ForkId> {
+		self.sync.fork_status().map(Into::into).ok_or_else(errors::network_disabled)
+	}
+
+	fn nat_status(&self) -> Result<NatStatus> {
+		self.net.nat_status().map(Into::into).ok_or_else(errors::network_disabled)
+	}
+
 	fn consensus_capability(&self) -> Result<ConsensusCapability> {
 		Ok(self.updater.capability().into())
 	}
@@ -334,6 +468,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		})
 	}
 
+	fn chain_gaps(&self) -> Result<Vec<ChainGap>> {
+		Ok(chain_gaps(&self.client.chain_info(), Some(&self.client.pruning_info())))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -405,34 +543,69 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			)))
 			.collect::<Result<Vec<_>>>()?;
 
-		let num = num.unwrap_or_default();
-
-		let (mut state, header) = if num == BlockNumber::Pending {
-			let info = self.client.chain_info();
-			let state = self.miner.pending_state(info.best_block_number).ok_or_else(errors::state_pruned)?;
-			let header = self.miner.pending_block_header(info.best_block_number).ok_or_else(errors::state_pruned)?;
-
-			(state, header)
-		} else {
-			let id = match num {
-				BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-				BlockNumber::Num(num) => BlockId::Number(num),
-				BlockNumber::Earliest => BlockId::Earliest,
-				BlockNumber::Latest => BlockId::Latest,
-				BlockNumber::Pending => unreachable!(), // Already covered
-			};
-
-			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
-
-			(state, header)
-		};
+		let (mut state, header) = self.state_and_header(num.unwrap_or_default())?;
 
 		self.client.call_many(&requests, &mut state, &header)
 				.map(|res| res.into_iter().map(|res| res.output.into()).collect())
 				.map_err(errors::call)
 	}
 
+	fn token_balances(&self, owner: AddressOrName, tokens: Vec<AddressOrName>, num: Option<BlockNumber>) -> Result<Vec<TokenBalance>> {
+		if tokens.len() > MAX_TOKEN_BALANCES {
+			return Err(errors::request_rejected_param_limit(MAX_TOKEN_BALANCES as u64, "tokens"));
+		}
+		let owner = self.address_of(owner)?;
+		let tokens = tokens.into_iter().map(|token| self.address_of(token)).collect::<Result<Vec<_>>>()?;
+		let (state, header) = self.state_and_header(num.unwrap_or_default())?;
+
+		// every call runs on its own copy of the state, so that a token changing it while
+		// reporting a balance can't affect what the other tokens report.
+		let token_call = |token: &H160, data: Vec<u8>| -> Result<Vec<u8>> {
+			let transaction = fake_sign::sign_call(helpers::CallRequest {
+				to: Some(*token),
+				gas: Some(TOKEN_CALL_GAS.into()),
+				data: Some(data),
+				..Default::default()
+			})?;
+			let executed = self.client.call(&transaction, Default::default(), &mut state.clone(), &header)
+				.map_err(errors::call)?;
+			Ok(match executed.exception {
+				None => executed.output,
+				Some(_) => Vec::new(),
+			})
+		};
+
+		// metadata of tokens seen before is cached, the others are asked for it next to the balance
+		let cached: Vec<_> = tokens.iter().map(|token| self.token_metadata.get(token)).collect();
+		let mut outputs = Vec::new();
+		for (token, metadata) in tokens.iter().zip(&cached) {
+			outputs.push(token_call(token, helpers::tokens::balance_of(&owner))?);
+			if metadata.is_none() {
+				outputs.push(token_call(token, helpers::tokens::decimals())?);
+				outputs.push(token_call(token, helpers::tokens::symbol())?);
+			}
+		}
+		let mut outputs = outputs.into_iter();
+
+		Ok(tokens.into_iter().zip(cached).map(|(token, metadata)| {
+			let balance = outputs.next().and_then(|output| helpers::tokens::decode_uint(&output));
+			let metadata = metadata.unwrap_or_else(|| {
+				let decimals = outputs.next().and_then(|output| helpers::tokens::decode_decimals(&output));
+				let symbol = outputs.next().and_then(|output| helpers::tokens::decode_symbol(&output));
+				let metadata = TokenMetadata { decimals, symbol };
+				self.token_metadata.insert(token, metadata.clone());
+				metadata
+			});
+
+			TokenBalance {
+				token,
+				balance,
+				decimals: metadata.decimals.map(|decimals| U64::from(decimals as u64)),
+				symbol: metadata.symbol,
+			}
+		}).collect())
+	}
+
 	fn submit_work_detail(&self, nonce: H64, pow_hash: H256, mix_hash: H256) -> Result<H256> {
 		helpers::submit_work_detail(&self.client, &self.miner, nonce, pow_hash, mix_hash)
 	}
@@ -456,7 +629,9 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	fn logs_no_tx_hash(&self, filter: Filter) -> BoxFuture<Vec<Log>> {
 		use v1::impls::eth::base_logs;
 		// only specific impl for lightclient
-		base_logs(&*self.client, &*self.miner, filter)
+		let client = self.client.clone();
+		let miner = self.miner.clone();
+		self.pools.logs.run(move |cancellation| base_logs(&*client, &*miner, filter, false, cancellation))
 	}
 
 	fn verify_signature(&self, is_prefixed: bool, message: Bytes, r: H256, s: H256, v: U64) -> Result<RecoveredAccount> {
@@ -480,4 +655,85 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		);
 		Ok(result.map_err(errors::cannot_submit_block)?)
 	}
+
+	fn cht_root(&self, number: U64) -> Result<Option<H256>> {
+		Ok(self.cht_store.as_ref().and_then(|store| store.root(number.as_u64())))
+	}
+
+	fn confirmation_status(&self, hash: H256) -> Result<Option<ConfirmationStatus>> {
+		Ok(self.client.confirmation_status(TransactionId::Hash(hash)).map(Into::into))
+	}
+
+	fn validator_status(&self) -> Result<ValidatorStatus> {
+		self.client.engine().validator_duties()
+			.map(|duties| duties.validator_status().into())
+			.ok_or_else(|| errors::unsupported("The chain engine does not assign sealing steps to validators.", None))
+	}
+
+	fn validator_set_at(&self, number: BlockNumber) -> Result<Option<EpochValidators>> {
+		let id = match number {
+			BlockNumber::Pending => {
+				warn!("BlockNumber::Pending is unsupported");
+				return Ok(None);
+			},
+
+			num => block_number_to_id(num)
+		};
+
+		Ok(self.client.validator_set_at(id).map(Into::into))
+	}
+
+	fn threat_status(&self) -> Result<ThreatStatus> {
+		Ok(self.client.threat_status().into())
+	}
+
+	fn gas_stats(&self, from: BlockNumber, to: BlockNumber, top: Option<usize>) -> BoxFuture<GasStats> {
+		let client = self.client.clone();
+		self.pools.traces.run(move |_| {
+			let number = |n: BlockNumber| match n {
+				BlockNumber::Pending => Err(errors::invalid_params("block", "pending block is unsupported")),
+				n => client.block_number(block_number_to_id(n)).ok_or_else(errors::unknown_block),
+			};
+			let (from, to) = (number(from)?, number(to)?);
+			if from > to {
+				return Err(errors::invalid_params("to", "range ends before it starts"));
+			}
+			if to - from >= MAX_GAS_STATS_BLOCKS {
+				return Err(errors::request_rejected_param_limit(MAX_GAS_STATS_BLOCKS, "blocks"));
+			}
+			let top = cmp::min(top.unwrap_or(10), MAX_GAS_STATS_CONTRACTS);
+
+			client.gas_stats(from, to, top)
+				.map(Into::into)
+				.ok_or_else(|| errors::unavailable_block(false, false))
+		})
+	}
+
+	fn receipt_proof(&self, hash: H256) -> Result<Option<ReceiptProof>> {
+		Ok(self.client.prove_receipt(hash).map(Into::into))
+	}
+
+	fn ancestry_proof(&self, block: BlockNumber, head: Option<BlockNumber>) -> Result<Option<AncestryProof>> {
+		let head = head.unwrap_or(BlockNumber::Latest);
+		let number = |n: &BlockNumber, param| match *n {
+			BlockNumber::Pending => Err(errors::invalid_params(param, "pending block is unsupported")),
+			_ => Ok(self.client.block_number(block_number_to_id(n.clone()))),
+		};
+		let (number, head_number) = match (number(&block, "block")?, number(&head, "head")?) {
+			(Some(number), Some(head_number)) => (number, head_number),
+			_ => return Ok(None),
+		};
+		if number > head_number {
+			return Err(errors::invalid_params("block", "block is past the head"));
+		}
+
+		// blocks given by hash may be off the canonical chain, whose blocks are proven instead.
+		let is_proven = |n: &BlockNumber, proven: &H256| match *n {
+			BlockNumber::Hash { ref hash, .. } => hash == proven,
+			_ => true,
+		};
+		Ok(self.client.prove_ancestry(number, head_number)
+			.filter(|proof| is_proven(&block, &proof.block_hash) && is_proven(&head, &proof.head_hash))
+			.map(Into::into))
+	}
 }
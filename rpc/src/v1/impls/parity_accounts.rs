@@ -24,13 +24,13 @@ use std::collections::{
 use ethereum_types::{Address, H160, H256, H520};
 use ethkey::{Brain, Password};
 use crypto::publickey::{Generator, Secret};
-use ethstore::KeyFile;
+use ethstore::{KeyFile, KdfParams};
 use accounts::AccountProvider;
 use jsonrpc_core::Result;
 use v1::helpers::deprecated::{self, DeprecationNotice};
 use v1::helpers::errors;
 use v1::traits::{ParityAccounts, ParityAccountsInfo};
-use v1::types::{Derive, DeriveHierarchical, DeriveHash, ExtAccountInfo, AccountInfo};
+use v1::types::{Derive, DeriveHierarchical, DeriveHash, ExtAccountInfo, AccountInfo, KeyDerivation, ReencryptionReport};
 
 /// Account management (personal) rpc implementation.
 pub struct ParityAccountsClient {
@@ -284,6 +284,34 @@ impl ParityAccounts for ParityAccountsClient {
 			.map(|_| true)
 	}
 
+	fn export_vault(&self, name: String, archive_password: Password) -> Result<String> {
+		let archive = self.accounts
+			.export_vault(&name, &archive_password)
+			.map_err(|e| errors::account("Could not export vault.", e))?;
+		String::from_utf8(archive).map_err(|e| errors::account("Could not export vault.", e))
+	}
+
+	fn import_vault(&self, name: String, archive: String, archive_password: Password) -> Result<Vec<H160>> {
+		self.accounts
+			.import_vault(&name, archive.as_bytes(), &archive_password)
+			.map(into_vec)
+			.map_err(|e| errors::account("Could not import vault.", e))
+	}
+
+	fn reencrypt_vault(&self, name: String, passwords: Vec<Password>, kdf: KeyDerivation) -> Result<ReencryptionReport> {
+		let kdf: KdfParams = kdf.into();
+		kdf.validate().map_err(|e| errors::invalid_params("kdf", e))?;
+
+		self.accounts
+			.reencrypt_accounts(&name, &passwords, kdf, |done, total| {
+				if done % 100 == 0 || done == total {
+					info!(target: "rpc", "Re-encrypted {}/{} keys", done, total);
+				}
+			})
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not re-encrypt vault.", e))
+	}
+
 	fn derive_key_index(&self, addr: H160, password: Password, derivation: DeriveHierarchical, save_as_account: bool) -> Result<H160> {
 		self.deprecation_notice("parity_deriveAddressIndex");
 		let addr: Address = addr.into();
@@ -21,9 +21,11 @@ use std::time::Duration;
 
 use client_traits::BlockChainClient;
 use types::client_types::Mode;
+use types::ids::BlockId;
 use ethcore::miner::{self, MinerService};
-use ethereum_types::{H160, H256, U256};
+use ethereum_types::{H256, H520, U256};
 use crypto::publickey::KeyPair;
+use ethcore_logger::RotatingLogger;
 use fetch::{self, Fetch};
 use hash::keccak_buffer;
 use sync::ManageNetwork;
@@ -31,14 +33,20 @@ use updater::{Service as UpdateService};
 
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::Future;
-use v1::helpers::errors;
+use v1::helpers::{errors, InstalledFilters};
 use v1::traits::ParitySet;
-use v1::types::{Bytes, ReleaseInfo, Transaction};
+use v1::types::{AddressOrName, Bytes, InstalledFilter, ReleaseInfo, Transaction};
+
+/// Seconds after which a paused block import resumes, unless told otherwise.
+const DEFAULT_IMPORT_PAUSE_SECS: u64 = 10 * 60;
+/// Longest pause of the block import, in seconds.
+const MAX_IMPORT_PAUSE_SECS: u64 = 24 * 60 * 60;
 
 #[cfg(any(test, feature = "accounts"))]
 pub mod accounts {
 	use super::*;
 	use accounts::AccountProvider;
+	use ethereum_types::H160;
 	use v1::traits::ParitySetAccounts;
 	use v1::helpers::deprecated::DeprecationNotice;
 	use v1::helpers::engine_signer::EngineSigner;
@@ -88,6 +96,10 @@ pub struct ParitySetClient<C, M, U, F = fetch::Client> {
 	miner: Arc<M>,
 	updater: Arc<U>,
 	net: Arc<dyn ManageNetwork>,
+	logger: Arc<RotatingLogger>,
+	shutdown: Arc<dyn Fn() + Send + Sync>,
+	reload: Arc<dyn Fn(bool) -> ::std::result::Result<Vec<String>, String> + Send + Sync>,
+	filters: Arc<InstalledFilters>,
 	fetch: F,
 }
 
@@ -100,6 +112,10 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 		miner: &Arc<M>,
 		updater: &Arc<U>,
 		net: &Arc<dyn ManageNetwork>,
+		logger: &Arc<RotatingLogger>,
+		shutdown: &Arc<dyn Fn() + Send + Sync>,
+		reload: &Arc<dyn Fn(bool) -> ::std::result::Result<Vec<String>, String> + Send + Sync>,
+		filters: &Arc<InstalledFilters>,
 		fetch: F,
 	) -> Self {
 		ParitySetClient {
@@ -107,6 +123,10 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 			miner: miner.clone(),
 			updater: updater.clone(),
 			net: net.clone(),
+			logger: logger.clone(),
+			shutdown: shutdown.clone(),
+			reload: reload.clone(),
+			filters: filters.clone(),
 			fetch,
 		}
 	}
@@ -155,7 +175,13 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
-	fn set_author(&self, address: H160) -> Result<bool> {
+	fn set_author(&self, author: AddressOrName) -> Result<bool> {
+		let address = match author {
+			AddressOrName::Address(address) => address,
+			AddressOrName::Name(name) => self.client.ens_address(&name, BlockId::Latest)
+				.map_err(errors::name_resolution)?
+				.ok_or_else(|| errors::unknown_name(&name))?,
+		};
 		self.miner.set_author(miner::Author::External(address));
 		Ok(true)
 	}
@@ -220,6 +246,19 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		self.client.set_spec_name(spec_name).map(|_| true).map_err(|()| errors::cannot_restart())
 	}
 
+	fn set_logging_targets(&self, targets: String) -> Result<bool> {
+		self.logger.set_levels(targets).map(|_| true).map_err(|e| errors::invalid_params("targets", e))
+	}
+
+	fn shutdown(&self) -> Result<bool> {
+		(self.shutdown)();
+		Ok(true)
+	}
+
+	fn reload_config(&self, dry_run: Option<bool>) -> Result<Vec<String>> {
+		(self.reload)(dry_run.unwrap_or(false)).map_err(|e| errors::invalid_params("configuration", e))
+	}
+
 	fn hash_content(&self, url: String) -> BoxFuture<H256> {
 		let future = self.fetch.get(&url, Default::default()).then(move |result| {
 			result
@@ -246,4 +285,42 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 			.map(|t| Transaction::from_pending(t.pending().clone()))
 		)
 	}
+
+	fn reload_engine_params(&self, update: Bytes, signature: H520) -> Result<bool> {
+		self.client.reload_engine_params(&update.into_vec(), signature)
+			.map(|_| true)
+			.map_err(errors::engine_params_update)
+	}
+
+	fn accept_reorg(&self, hash: H256) -> Result<bool> {
+		self.client.accept_reorg(hash)
+			.map(|_| true)
+			.map_err(|e| errors::invalid_params("hash", e))
+	}
+
+	fn rewrite_database(&self) -> Result<bool> {
+		self.client.rewrite_database()
+			.map(|_| true)
+			.map_err(errors::database)
+	}
+
+	fn list_filters(&self) -> Result<Vec<InstalledFilter>> {
+		Ok(self.filters.polls().lock().polls().into_iter()
+			.map(|(info, filter)| filter.modify(|filter| (info, &*filter).into()))
+			.collect())
+	}
+
+	fn pause_sync(&self, timeout: Option<u64>) -> Result<bool> {
+		let timeout = timeout.unwrap_or(DEFAULT_IMPORT_PAUSE_SECS);
+		if timeout == 0 || timeout > MAX_IMPORT_PAUSE_SECS {
+			return Err(errors::invalid_params("timeout", format!("expected 1 to {} seconds", MAX_IMPORT_PAUSE_SECS)));
+		}
+		self.client.pause_import(Duration::from_secs(timeout))
+			.map(|_| true)
+			.map_err(errors::database)
+	}
+
+	fn resume_sync(&self) -> Result<bool> {
+		Ok(self.client.resume_import())
+	}
 }
@@ -40,11 +40,13 @@ use v1::types::{
 	TransactionRequest,
 	RichRawTransaction as RpcRichRawTransaction,
 	EIP191Version,
+	UnlockSession,
 };
 
 /// Account management (personal) rpc implementation.
 pub struct PersonalClient<D: Dispatcher> {
 	accounts: Arc<AccountProvider>,
+	remote_signer: Option<Arc<dyn dispatch::Accounts>>,
 	dispatcher: D,
 	allow_perm_unlock: bool,
 	allow_experimental_rpcs: bool,
@@ -61,12 +63,28 @@ impl<D: Dispatcher> PersonalClient<D> {
 	) -> Self {
 		PersonalClient {
 			accounts: accounts.clone(),
+			remote_signer: None,
 			dispatcher,
 			allow_perm_unlock,
 			allow_experimental_rpcs,
 			deprecation_notice: DeprecationNotice::default(),
 		}
 	}
+
+	/// Sign with an external signer instead of the local accounts, if given.
+	///
+	/// Passwords are ignored in this case, the external signer approves requests on its own.
+	pub fn with_remote_signer(mut self, signer: Option<Arc<dyn dispatch::Accounts>>) -> Self {
+		self.remote_signer = signer;
+		self
+	}
+
+	fn signer(&self) -> Arc<dyn dispatch::Accounts> {
+		match self.remote_signer {
+			Some(ref signer) => signer.clone(),
+			None => Arc::new(dispatch::Signer::new(self.accounts.clone())),
+		}
+	}
 }
 
 impl<D: Dispatcher + 'static> PersonalClient<D> {
@@ -81,11 +99,14 @@ impl<D: Dispatcher + 'static> PersonalClient<D> {
  		      <P::Out as futures::future::IntoFuture>::Future: Send
 	{
 		let dispatcher = self.dispatcher.clone();
-		let accounts = self.accounts.clone();
+		let accounts = self.signer();
 
-		let default = match request.from.as_ref() {
-			Some(account) => Ok(account.clone().into()),
-			None => accounts
+		let default = match (request.from.as_ref(), self.remote_signer.is_some()) {
+			(Some(account), _) => Ok(account.clone().into()),
+			(None, true) => accounts
+				.default_account()
+				.ok_or_else(|| errors::account("Cannot find default account.", "The remote signer has no accounts.")),
+			(None, false) => self.accounts
 				.default_account()
 				.map_err(|e| errors::account("Cannot find default account.", e)),
 		};
@@ -95,7 +116,6 @@ impl<D: Dispatcher + 'static> PersonalClient<D> {
 			Err(e) => return Box::new(future::err(e)),
 		};
 
-		let accounts = Arc::new(dispatch::Signer::new(accounts)) as _;
 		Box::new(dispatcher.fill_optional_fields(request.into(), default, false)
 			.and_then(move |filled| {
 				dispatcher.sign(filled, &accounts, SignWith::Password(password.into()), post_sign)
@@ -145,7 +165,7 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 			)),
 			(true, Some(0)) => store.unlock_account_permanently(account, account_pass.into()),
 			(true, Some(d)) => store.unlock_account_timed(account, account_pass.into(), Duration::from_secs(d.into())),
-			(true, None) => store.unlock_account_timed(account, account_pass.into(), Duration::from_secs(300)),
+			(true, None) => store.unlock_account_session(account, account_pass.into()),
 		};
 		match r {
 			Ok(_) => Ok(true),
@@ -153,10 +173,20 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 		}
 	}
 
+	fn unlock_sessions(&self) -> Result<Vec<UnlockSession>> {
+		Ok(self.accounts.unlock_sessions().into_iter().map(|session| UnlockSession {
+			address: session.address.into(),
+			one_time: session.one_time,
+			unlocked_for: session.unlocked_for.as_secs(),
+			idle_for: session.idle_for.as_secs(),
+			expires_in: session.expires_in.map(|d| d.as_secs()),
+		}).collect())
+	}
+
 	fn sign(&self, data: RpcBytes, account: H160, password: String) -> BoxFuture<H520> {
 		self.deprecation_notice.print("personal_sign", deprecated::msgs::ACCOUNTS);
 		let dispatcher = self.dispatcher.clone();
-		let accounts = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
+		let accounts = self.signer();
 
 		let payload = RpcConfirmationPayload::EthSignMessage((account.clone(), data).into());
 
@@ -178,7 +208,7 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 
 		let data = try_bf!(eip191::hash_message(version, data));
 		let dispatcher = self.dispatcher.clone();
-		let accounts = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
+		let accounts = self.signer();
 
 		let payload = RpcConfirmationPayload::EIP191SignMessage((account.clone(), data.into()).into());
 
@@ -204,7 +234,7 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 			Err(err) => return Box::new(future::err(errors::invalid_call_data(err.kind()))),
 		};
 		let dispatcher = self.dispatcher.clone();
-		let accounts = Arc::new(dispatch::Signer::new(self.accounts.clone())) as _;
+		let accounts = self.signer();
 
 		let payload = RpcConfirmationPayload::EIP191SignMessage((account.clone(), data.into()).into());
 
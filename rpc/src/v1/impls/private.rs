@@ -127,4 +127,15 @@ impl Private for PrivateClient {
 			.map_err(errors::private_message)
 			.map(Into::into)
 	}
+
+	fn sync_state_history(&self, contract_address: H160, from: BlockNumber, to: BlockNumber) -> Result<Vec<H256>, Error> {
+		let (from, to) = match (from, to) {
+			(BlockNumber::Pending, _) | (_, BlockNumber::Pending) => return Err(errors::private_message_block_id_not_supported()),
+			(from, to) => (block_number_to_id(from), block_number_to_id(to)),
+		};
+
+		self.unwrap_manager()?
+			.sync_private_state_history(&contract_address, from, to)
+			.map_err(errors::private_message)
+	}
 }
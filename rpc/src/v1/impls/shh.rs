@@ -0,0 +1,71 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gossip messaging rpc implementation.
+
+use std::sync::Arc;
+
+use ethereum_types::H256;
+use gossip::GossipProvider;
+use jsonrpc_core::Result;
+
+use v1::helpers::errors;
+use v1::traits::Shh;
+use v1::types::{ShhFilterRequest, ShhInfo, ShhMessage, ShhPostRequest};
+
+/// Gossip messaging rpc implementation.
+pub struct ShhClient {
+	gossip: Option<Arc<dyn GossipProvider>>,
+}
+
+impl ShhClient {
+	/// Creates new `ShhClient`, `gossip` is `None` if the gossip service is not running.
+	pub fn new(gossip: Option<Arc<dyn GossipProvider>>) -> Self {
+		ShhClient {
+			gossip,
+		}
+	}
+
+	fn gossip(&self) -> Result<&Arc<dyn GossipProvider>> {
+		self.gossip.as_ref().ok_or_else(errors::gossip_disabled)
+	}
+}
+
+impl Shh for ShhClient {
+	fn info(&self) -> Result<ShhInfo> {
+		Ok(self.gossip()?.info().into())
+	}
+
+	fn post(&self, request: ShhPostRequest) -> Result<bool> {
+		self.gossip()?.post(request.topics, request.payload.into_vec()).map_err(errors::gossip)?;
+		Ok(true)
+	}
+
+	fn new_message_filter(&self, request: ShhFilterRequest) -> Result<H256> {
+		self.gossip()?.new_filter(request.topics).map_err(errors::gossip)
+	}
+
+	fn filter_messages(&self, id: H256) -> Result<Vec<ShhMessage>> {
+		match self.gossip()?.filter_messages(&id) {
+			Some(messages) => Ok(messages.into_iter().map(Into::into).collect()),
+			None => Err(errors::filter_not_found()),
+		}
+	}
+
+	fn delete_message_filter(&self, id: H256) -> Result<bool> {
+		Ok(self.gossip()?.remove_filter(&id))
+	}
+}
@@ -113,7 +113,7 @@ impl<D: Dispatcher + 'static> SigningQueueClient<D> {
 	}
 
 	fn dispatch(&self, payload: RpcConfirmationPayload, origin: Origin) -> BoxFuture<DispatchResult> {
-		let default_account = self.accounts.default_account();
+		let default_account = self.accounts.default_account().unwrap_or_default();
 		let from = &payload.sender().unwrap_or(&default_account);
 		// bail early if the account isn't unlocked
 		if !self.accounts.is_unlocked(from) && !self.signer.is_enabled() {
@@ -147,7 +147,7 @@ impl<D: Dispatcher + 'static> ParitySigning for SigningQueueClient<D> {
 	type Metadata = Metadata;
 
 	fn compose_transaction(&self, _meta: Metadata, transaction: RpcTransactionRequest) -> BoxFuture<RpcTransactionRequest> {
-		let default_account = self.accounts.default_account();
+		let default_account = self.accounts.default_account().unwrap_or_default();
 		Box::new(self.dispatcher.fill_optional_fields(transaction.into(), default_account, true).map(Into::into))
 	}
 
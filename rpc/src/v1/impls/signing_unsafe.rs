@@ -80,7 +80,7 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 
 	fn send_transaction(&self, _meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<H256> {
 		self.deprecation_notice.print("eth_sendTransaction", deprecated::msgs::ACCOUNTS);
-		Box::new(self.handle(RpcConfirmationPayload::SendTransaction(request), self.accounts.default_account())
+		Box::new(self.handle(RpcConfirmationPayload::SendTransaction(request), self.accounts.default_account().unwrap_or_default())
 			.then(|res| match res {
 				Ok(RpcConfirmationResponse::SendTransaction(hash)) => Ok(hash),
 				Err(e) => Err(e),
@@ -91,7 +91,7 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 	fn sign_transaction(&self, _meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<RpcRichRawTransaction> {
 		self.deprecation_notice.print("eth_signTransaction", deprecated::msgs::ACCOUNTS);
 
-		Box::new(self.handle(RpcConfirmationPayload::SignTransaction(request), self.accounts.default_account())
+		Box::new(self.handle(RpcConfirmationPayload::SignTransaction(request), self.accounts.default_account().unwrap_or_default())
 			.then(|res| match res {
 				Ok(RpcConfirmationResponse::SignTransaction(tx)) => Ok(tx),
 				Err(e) => Err(e),
@@ -105,7 +105,7 @@ impl<D: Dispatcher + 'static> ParitySigning for SigningUnsafeClient<D> {
 
 	fn compose_transaction(&self, _meta: Metadata, transaction: RpcTransactionRequest) -> BoxFuture<RpcTransactionRequest> {
 		let accounts = self.accounts.clone();
-		let default_account = accounts.default_account();
+		let default_account = accounts.default_account().unwrap_or_default();
 		Box::new(self.dispatcher.fill_optional_fields(transaction.into(), default_account, true).map(Into::into))
 	}
 
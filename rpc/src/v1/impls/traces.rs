@@ -16,6 +16,7 @@
 
 //! Traces api implementation.
 
+use std::cmp;
 use std::sync::Arc;
 
 use account_state::state::StateInfo;
@@ -26,16 +27,21 @@ use rlp::Rlp;
 use types::{
 	call_analytics::CallAnalytics,
 	ids::{BlockId, TransactionId, TraceId},
+	trace_filter::Filter as EthcoreTraceFilter,
 	transaction::SignedTransaction,
 };
 
-use jsonrpc_core::Result;
+use jsonrpc_core::{BoxFuture, Result};
 use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::{errors, fake_sign};
+use v1::helpers::heavy::{Cancellation, HeavyRequests};
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults,
 	TraceResultsWithTransactionHash, TraceOptions, block_number_to_id};
 
+/// Number of blocks searched for traces between checks for cancellation.
+const FILTER_CHUNK_BLOCKS: u64 = 1_000;
+
 fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
 	CallAnalytics {
 		transaction_tracing: flags.contains(&("trace".to_owned())),
@@ -47,15 +53,60 @@ fn to_call_analytics(flags: TraceOptions) -> CallAnalytics {
 /// Traces api implementation.
 pub struct TracesClient<C> {
 	client: Arc<C>,
+	heavy: HeavyRequests,
 }
 
 impl<C> TracesClient<C> {
 	/// Creates new Traces client.
-	pub fn new(client: &Arc<C>) -> Self {
+	pub fn new(client: &Arc<C>, heavy: HeavyRequests) -> Self {
 		TracesClient {
 			client: client.clone(),
+			heavy,
+		}
+	}
+}
+
+fn to_block_id(block: BlockNumber) -> Result<BlockId> {
+	match block {
+		BlockNumber::Hash { hash, .. } => Ok(BlockId::Hash(hash)),
+		BlockNumber::Num(num) => Ok(BlockId::Number(num)),
+		BlockNumber::Earliest => Ok(BlockId::Earliest),
+		BlockNumber::Latest => Ok(BlockId::Latest),
+
+		BlockNumber::Pending => Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+	}
+}
+
+/// Search the traces block range by block range, stopping early if the request is cancelled
+/// or enough traces were found.
+fn filter_traces<C: BlockChainClient>(client: &C, filter: TraceFilter, cancellation: &Cancellation) -> Result<Option<Vec<LocalizedTrace>>> {
+	let filter: EthcoreTraceFilter = filter.into();
+	let (start, end) = match (client.block_number(filter.range.start), client.block_number(filter.range.end)) {
+		(Some(start), Some(end)) if start <= end => (start, end),
+		_ => return Ok(client.filter_traces(filter).map(|traces| traces.into_iter().map(LocalizedTrace::from).collect())),
+	};
+	let (after, count) = (filter.after.unwrap_or(0), filter.count.unwrap_or(usize::max_value()));
+
+	let mut traces = Vec::new();
+	let mut from = start;
+	while from <= end && traces.len() < after.saturating_add(count) {
+		cancellation.check()?;
+		let to = cmp::min(from.saturating_add(FILTER_CHUNK_BLOCKS - 1), end);
+		let chunk = EthcoreTraceFilter {
+			range: BlockId::Number(from)..BlockId::Number(to),
+			from_address: filter.from_address.clone(),
+			to_address: filter.to_address.clone(),
+			after: None,
+			count: None,
+		};
+		match client.filter_traces(chunk) {
+			Some(chunk) => traces.extend(chunk),
+			None => return Ok(None),
 		}
+		from = to + 1;
 	}
+
+	Ok(Some(traces.into_iter().skip(after).take(count).map(LocalizedTrace::from).collect()))
 }
 
 impl<C, S> Traces for TracesClient<C> where
@@ -64,19 +115,22 @@ impl<C, S> Traces for TracesClient<C> where
 {
 	type Metadata = Metadata;
 
-	fn filter(&self, filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>> {
-		Ok(self.client.filter_traces(filter.into())
-			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+	fn filter(&self, filter: TraceFilter) -> BoxFuture<Option<Vec<LocalizedTrace>>> {
+		let client = self.client.clone();
+		self.heavy.run(move |cancellation| filter_traces(&*client, filter, cancellation))
 	}
 
-	fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
-		let id = match block_number {
-			BlockNumber::Pending => return Ok(None),
-			num => block_number_to_id(num)
-		};
-
-		Ok(self.client.block_traces(id)
-			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+	fn block_traces(&self, block_number: BlockNumber) -> BoxFuture<Option<Vec<LocalizedTrace>>> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			let id = match block_number {
+				BlockNumber::Pending => return Ok(None),
+				num => block_number_to_id(num)
+			};
+
+			Ok(client.block_traces(id)
+				.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+		})
 	}
 
 	fn transaction_traces(&self, transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>> {
@@ -94,98 +148,79 @@ impl<C, S> Traces for TracesClient<C> where
 			.map(LocalizedTrace::from))
 	}
 
-	fn call(&self, request: CallRequest, flags: TraceOptions, block: Option<BlockNumber>) -> Result<TraceResults> {
-		let block = block.unwrap_or_default();
-
-		let request = CallRequest::into(request);
-		let signed = fake_sign::sign_call(request)?;
+	fn call(&self, request: CallRequest, flags: TraceOptions, block: Option<BlockNumber>) -> BoxFuture<TraceResults> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			let id = to_block_id(block.unwrap_or_default())?;
+			let request = CallRequest::into(request);
+			let signed = fake_sign::sign_call(request)?;
 
-		let id = match block {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(num) => BlockId::Number(num),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
+			let mut state = client.state_at(id).ok_or_else(errors::state_pruned)?;
+			let header = client.block_header(id).ok_or_else(errors::state_pruned)?;
 
-			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
-		};
-
-		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
-
-		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
-			.map(TraceResults::from)
-			.map_err(errors::call)
+			client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
+				.map(TraceResults::from)
+				.map_err(errors::call)
+		})
 	}
 
-	fn call_many(&self, requests: Vec<(CallRequest, TraceOptions)>, block: Option<BlockNumber>) -> Result<Vec<TraceResults>> {
-		let block = block.unwrap_or_default();
-
-		let requests = requests.into_iter()
-			.map(|(request, flags)| {
-				let request = CallRequest::into(request);
-				let signed = fake_sign::sign_call(request)?;
-				Ok((signed, to_call_analytics(flags)))
-			})
-			.collect::<Result<Vec<_>>>()?;
-
-		let id = match block {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(num) => BlockId::Number(num),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
-
-			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
-		};
-
-		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
-
-		self.client.call_many(&requests, &mut state, &header.decode().map_err(errors::decode)?)
-			.map(|results| results.into_iter().map(TraceResults::from).collect())
-			.map_err(errors::call)
+	fn call_many(&self, requests: Vec<(CallRequest, TraceOptions)>, block: Option<BlockNumber>) -> BoxFuture<Vec<TraceResults>> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			let id = to_block_id(block.unwrap_or_default())?;
+			let requests = requests.into_iter()
+				.map(|(request, flags)| {
+					let request = CallRequest::into(request);
+					let signed = fake_sign::sign_call(request)?;
+					Ok((signed, to_call_analytics(flags)))
+				})
+				.collect::<Result<Vec<_>>>()?;
+
+			let mut state = client.state_at(id).ok_or_else(errors::state_pruned)?;
+			let header = client.block_header(id).ok_or_else(errors::state_pruned)?;
+
+			client.call_many(&requests, &mut state, &header.decode().map_err(errors::decode)?)
+				.map(|results| results.into_iter().map(TraceResults::from).collect())
+				.map_err(errors::call)
+		})
 	}
 
-	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Option<BlockNumber>) -> Result<TraceResults> {
-		let block = block.unwrap_or_default();
-
-		let tx = Rlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
-		let signed = SignedTransaction::new(tx).map_err(errors::transaction)?;
-
-		let id = match block {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(num) => BlockId::Number(num),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
-
-			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
-		};
+	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Option<BlockNumber>) -> BoxFuture<TraceResults> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			let id = to_block_id(block.unwrap_or_default())?;
+			let tx = Rlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
+			let signed = SignedTransaction::new(tx).map_err(errors::transaction)?;
 
-		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
+			let mut state = client.state_at(id).ok_or_else(errors::state_pruned)?;
+			let header = client.block_header(id).ok_or_else(errors::state_pruned)?;
 
-		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
-			.map(TraceResults::from)
-			.map_err(errors::call)
+			client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
+				.map(TraceResults::from)
+				.map_err(errors::call)
+		})
 	}
 
-	fn replay_transaction(&self, transaction_hash: H256, flags: TraceOptions) -> Result<TraceResults> {
-		self.client.replay(TransactionId::Hash(transaction_hash), to_call_analytics(flags))
-			.map(TraceResults::from)
-			.map_err(errors::call)
+	fn replay_transaction(&self, transaction_hash: H256, flags: TraceOptions) -> BoxFuture<TraceResults> {
+		let client = self.client.clone();
+		self.heavy.run(move |_| {
+			client.replay(TransactionId::Hash(transaction_hash), to_call_analytics(flags))
+				.map(TraceResults::from)
+				.map_err(errors::call)
+		})
 	}
 
-	fn replay_block_transactions(&self, block_number: BlockNumber, flags: TraceOptions) -> Result<Vec<TraceResultsWithTransactionHash>> {
-		let id = match block_number {
-			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
-			BlockNumber::Num(num) => BlockId::Number(num),
-			BlockNumber::Earliest => BlockId::Earliest,
-			BlockNumber::Latest => BlockId::Latest,
-
-			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
-		};
-
-		self.client.replay_block_transactions(id, to_call_analytics(flags))
-			.map(|results| results.map(TraceResultsWithTransactionHash::from).collect())
-			.map_err(errors::call)
+	fn replay_block_transactions(&self, block_number: BlockNumber, flags: TraceOptions) -> BoxFuture<Vec<TraceResultsWithTransactionHash>> {
+		let client = self.client.clone();
+		self.heavy.run(move |cancellation| {
+			let id = to_block_id(block_number)?;
+			client.replay_block_transactions(id, to_call_analytics(flags))
+				.map_err(errors::call)?
+				.map(|result| {
+					cancellation.check()?;
+					Ok(TraceResultsWithTransactionHash::from(result))
+				})
+				.collect()
+		})
 	}
 }
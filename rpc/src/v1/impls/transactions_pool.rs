@@ -15,6 +15,7 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use jsonrpc_core::Result;
 use jsonrpc_core::futures::Future;
@@ -24,28 +25,41 @@ use v1::helpers::Subscribers;
 use v1::metadata::Metadata;
 use v1::traits::TransactionsPool;
 
-use miner::pool::TxStatus;
+use miner::pool::{PoolEvent, TxStatus};
 use parity_runtime::Executor;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use ethereum_types::H256;
 use futures::{Stream, sync::mpsc};
 
 type Client = Sink<(H256, TxStatus)>;
+type EventsClient = Sink<PoolEvent>;
+type EventsReceiver = mpsc::UnboundedReceiver<Arc<Vec<PoolEvent>>>;
+
+/// Source of pool lifecycle events, only listened to while there are subscribers.
+struct EventsSource {
+	listen: Box<dyn Fn() -> EventsReceiver + Send + Sync>,
+	/// Set while events are forwarded to the subscribers.
+	forwarding: Mutex<Option<Arc<AtomicBool>>>,
+}
 
 /// Transactions pool PubSub implementation.
 pub struct TransactionsPoolClient {
 	handler: Arc<TransactionsNotificationHandler>,
 	transactions_pool_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	events_subscribers: Arc<RwLock<Subscribers<EventsClient>>>,
+	events: Option<EventsSource>,
 }
 
 impl TransactionsPoolClient {
 	/// Creates new `TransactionsPoolClient`.
 	pub fn new(executor: Executor, pool_receiver: mpsc::UnboundedReceiver<Arc<Vec<(H256, TxStatus)>>>) -> Self {
 		let transactions_pool_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let events_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let handler = Arc::new(
 			TransactionsNotificationHandler::new(
 				executor.clone(),
 				transactions_pool_subscribers.clone(),
+				events_subscribers.clone(),
 			)
 		);
 		let handler2 = Arc::downgrade(&handler);
@@ -63,6 +77,58 @@ impl TransactionsPoolClient {
 		TransactionsPoolClient {
 			handler,
 			transactions_pool_subscribers,
+			events_subscribers,
+			events: None,
+		}
+	}
+
+	/// Forwards pool lifecycle events to events subscribers.
+	///
+	/// `listen` registers a new events listener in the pool. It's only called once there are
+	/// subscribers, and the listener is dropped again when the last one unsubscribes, so that
+	/// the pool doesn't track events nobody is interested in.
+	pub fn with_events<F>(mut self, listen: F) -> Self where
+		F: Fn() -> EventsReceiver + Send + Sync + 'static,
+	{
+		self.events = Some(EventsSource {
+			listen: Box::new(listen),
+			forwarding: Mutex::new(None),
+		});
+		self
+	}
+
+	fn start_forwarding_events(&self) {
+		let source = match self.events {
+			Some(ref source) => source,
+			None => return,
+		};
+		let mut forwarding = source.forwarding.lock();
+		if forwarding.is_some() {
+			return;
+		}
+
+		let active = Arc::new(AtomicBool::new(true));
+		let handler = Arc::downgrade(&self.handler);
+		let still_active = active.clone();
+		// the receiver, and with it the pool listener, is dropped with the first events after stopping
+		self.handler.executor.spawn((source.listen)()
+			.take_while(move |_| Ok(still_active.load(Ordering::SeqCst)))
+			.for_each(move |events| {
+				if let Some(handler) = handler.upgrade() {
+					handler.notify_events(events);
+				}
+				Ok(())
+			})
+			.map_err(|e| warn!("Pool events listener error: {:?}", e))
+		);
+		*forwarding = Some(active);
+	}
+
+	fn stop_forwarding_events(&self) {
+		if let Some(ref source) = self.events {
+			if let Some(active) = source.forwarding.lock().take() {
+				active.store(false, Ordering::SeqCst);
+			}
 		}
 	}
 
@@ -76,16 +142,30 @@ impl TransactionsPoolClient {
 pub struct TransactionsNotificationHandler {
 	executor: Executor,
 	transactions_pool_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	events_subscribers: Arc<RwLock<Subscribers<EventsClient>>>,
 }
 
 impl TransactionsNotificationHandler {
-	fn new(executor: Executor, transactions_pool_subscribers: Arc<RwLock<Subscribers<Client>>>) -> Self {
+	fn new(
+		executor: Executor,
+		transactions_pool_subscribers: Arc<RwLock<Subscribers<Client>>>,
+		events_subscribers: Arc<RwLock<Subscribers<EventsClient>>>,
+	) -> Self {
 		TransactionsNotificationHandler {
 			executor,
 			transactions_pool_subscribers,
+			events_subscribers,
 		}
 	}
 
+	fn notify_event(executor: &Executor, subscriber: &EventsClient, event: PoolEvent) {
+		executor.spawn(subscriber
+			.notify(Ok(event))
+			.map(|_| ())
+			.map_err(|e| warn!(target: "rpc", "Unable to send notification: {}", e))
+		);
+	}
+
 	fn notify(executor: &Executor, subscriber: &Client, result: (H256, TxStatus)) {
 		executor.spawn(subscriber
 			.notify(Ok(result))
@@ -101,6 +181,14 @@ impl TransactionsNotificationHandler {
 			}
 		}
 	}
+
+	pub fn notify_events(&self, events: Arc<Vec<PoolEvent>>) {
+		for subscriber in self.events_subscribers.read().values() {
+			for event in events.iter() {
+				Self::notify_event(&self.executor, subscriber, event.clone());
+			}
+		}
+	}
 }
 
 impl TransactionsPool for TransactionsPoolClient {
@@ -114,4 +202,18 @@ impl TransactionsPool for TransactionsPoolClient {
 		let res = self.transactions_pool_subscribers.write().remove(&id).is_some();
 		Ok(res)
 	}
+
+	fn subscribe_events(&self, _meta: Metadata, subscriber: Subscriber<PoolEvent>) {
+		self.events_subscribers.write().push(subscriber);
+		self.start_forwarding_events();
+	}
+
+	fn unsubscribe_events(&self, _meta: Option<Metadata>, id: SubscriptionId) -> Result<bool> {
+		let mut subscribers = self.events_subscribers.write();
+		let res = subscribers.remove(&id).is_some();
+		if subscribers.is_empty() {
+			self.stop_forwarding_events();
+		}
+		Ok(res)
+	}
 }
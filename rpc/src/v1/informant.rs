@@ -16,6 +16,7 @@
 
 //! RPC Requests Statistics
 
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
@@ -25,6 +26,8 @@ use jsonrpc_core as core;
 use jsonrpc_core::futures::future::Either;
 use order_stat;
 use parking_lot::RwLock;
+use stats::{DurationHistogram, PrometheusMetrics, PrometheusRegistry, Tracer};
+use v1::helpers::response_cache::ResponseCache;
 
 pub use self::parity_runtime::Executor;
 
@@ -136,6 +139,7 @@ impl<T: Default + Copy + Ord> StatsCalculator<T> {
 pub struct RpcStats {
 	requests: RwLock<RateCalculator>,
 	roundtrips: RwLock<StatsCalculator<u128>>,
+	methods: RwLock<HashMap<String, DurationHistogram>>,
 	active_sessions: AtomicUsize,
 }
 
@@ -175,6 +179,39 @@ impl RpcStats {
 	pub fn approximated_roundtrip(&self) -> u128 {
 		self.roundtrips.read().approximated_median()
 	}
+
+	/// Register methods whose call times are reported.
+	pub fn register_methods<I: IntoIterator<Item = String>>(&self, methods: I) {
+		let mut registered = self.methods.write();
+		for method in methods {
+			registered.entry(method).or_insert_with(Default::default);
+		}
+	}
+
+	/// Add roundtrip time of a single method call.
+	/// Calls of methods which were not registered are not reported, their names come from clients.
+	pub fn add_method_roundtrip(&self, method: &str, time: time::Duration) {
+		if let Some(histogram) = self.methods.read().get(method) {
+			histogram.observe(time);
+		}
+	}
+}
+
+impl PrometheusMetrics for RpcStats {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		r.register_gauge("rpc_sessions", "Open RPC sessions", self.sessions() as i64);
+		r.register_gauge("rpc_requests_rate", "RPC requests per second", self.requests_rate() as i64);
+
+		let methods = self.methods.read();
+		let mut snapshots: Vec<_> = methods.iter().map(|(method, histogram)| (method.as_str(), histogram.snapshot())).collect();
+		snapshots.sort_by(|a, b| a.0.cmp(b.0));
+		r.register_labelled_histograms(
+			"rpc_call_seconds",
+			"Time to handle an RPC method call",
+			"method",
+			snapshots.iter().map(|(method, snapshot)| (*method, snapshot)),
+		);
+	}
 }
 
 /// Notifies about RPC activity.
@@ -183,18 +220,22 @@ pub trait ActivityNotifier: Send + Sync + 'static {
 	fn active(&self);
 }
 
-/// Stats-counting RPC middleware
+/// Stats-counting RPC middleware, coalescing identical read calls if given a response cache.
 pub struct Middleware<T: ActivityNotifier = ClientNotifier> {
 	stats: Arc<RpcStats>,
 	notifier: T,
+	tracer: Tracer,
+	response_cache: Option<Arc<ResponseCache>>,
 }
 
 impl<T: ActivityNotifier> Middleware<T> {
-	/// Create new Middleware with stats counter and activity notifier.
-	pub fn new(stats: Arc<RpcStats>, notifier: T) -> Self {
+	/// Create new Middleware with stats counter, activity notifier, request tracer and response cache.
+	pub fn new(stats: Arc<RpcStats>, notifier: T, tracer: Tracer, response_cache: Option<Arc<ResponseCache>>) -> Self {
 		Middleware {
 			stats,
 			notifier,
+			tracer,
+			response_cache,
 		}
 	}
 }
@@ -212,23 +253,46 @@ impl<M: core::Metadata, T: ActivityNotifier> core::Middleware<M> for Middleware<
 		self.notifier.active();
 		self.stats.count_request();
 
-		let id = match request {
-			core::Request::Single(core::Call::MethodCall(ref call)) => Some(call.id.clone()),
-			_ => None,
+		let (id, method) = match request {
+			core::Request::Single(core::Call::MethodCall(ref call)) => (Some(call.id.clone()), Some(call.method.clone())),
+			_ => (None, None),
 		};
 		let stats = self.stats.clone();
 
+		let mut span = self.tracer.server_span("rpc.request");
+		if let Some(ref method) = method {
+			span.set_attribute("rpc.method", method);
+		}
+		if let core::Request::Batch(ref calls) = request {
+			span.set_attribute("rpc.batch_size", calls.len());
+		}
+
 		let future = process(request, meta).map(move |res| {
-			let time = start.elapsed().as_micros();
+			let elapsed = start.elapsed();
+			let time = elapsed.as_micros();
 			if time > 10_000 {
 				debug!(target: "rpc", "[{:?}] Took {}ms", id, time / 1_000);
 			}
 			stats.add_roundtrip(time);
+			if let Some(method) = method {
+				stats.add_method_roundtrip(&method, elapsed);
+			}
+			span.end();
 			res
 		});
 
 		Either::A(Box::new(future))
 	}
+
+	fn on_call<F, X>(&self, call: core::Call, meta: M, next: F) -> Either<Self::CallFuture, X> where
+		F: FnOnce(core::Call, M) -> X,
+		X: core::futures::Future<Item=Option<core::Output>, Error=()> + Send + 'static,
+	{
+		match (call, self.response_cache.as_ref()) {
+			(core::Call::MethodCall(call), Some(cache)) => cache.on_call(call, move |call| next(core::Call::MethodCall(call), meta)),
+			(call, _) => Either::B(next(call, meta)),
+		}
+	}
 }
 
 /// Client Notifier
@@ -247,6 +311,7 @@ impl ActivityNotifier for ClientNotifier {
 mod tests {
 
 	use super::{RateCalculator, StatsCalculator, RpcStats};
+	use stats::{PrometheusMetrics, PrometheusRegistry};
 
 	#[test]
 	fn should_calculate_rate() {
@@ -303,6 +368,28 @@ mod tests {
 		assert_eq!(stats.approximated_roundtrip(), 125);
 	}
 
+	#[test]
+	fn should_report_method_timings() {
+		// given
+		let stats = RpcStats::default();
+
+		stats.register_methods(vec!["eth_call".to_owned(), "eth_blockNumber".to_owned()]);
+
+		// when
+		stats.add_method_roundtrip("eth_call", ::std::time::Duration::from_millis(3));
+		stats.add_method_roundtrip("eth_call", ::std::time::Duration::from_millis(30));
+		stats.add_method_roundtrip("eth_blockNumber", ::std::time::Duration::from_millis(1));
+		stats.add_method_roundtrip("made_up", ::std::time::Duration::from_millis(1));
+		let mut registry = PrometheusRegistry::new("");
+		stats.prometheus_metrics(&mut registry);
+		let output = registry.output();
+
+		// then
+		assert!(output.contains("rpc_call_seconds_count{method=\"eth_call\"} 2\n"));
+		assert!(output.contains("rpc_call_seconds_count{method=\"eth_blockNumber\"} 1\n"));
+		assert!(!output.contains("made_up"));
+	}
+
 	#[test]
 	fn should_be_sync_and_send() {
 		let stats = RpcStats::default();
@@ -41,9 +41,11 @@ pub mod informant;
 pub mod metadata;
 pub mod traits;
 
-pub use self::traits::{Debug, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccountsInfo, ParityAccounts, ParitySet, ParitySetAccounts, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, Signer, Traces, Web3};
+pub use self::traits::{Clique, Debug, DebugPubSub, EngineApi, Eth, EthBundle, EthFilter, EthPubSub, EthSigning, Evm, Miner, Mining, Net, Parity, ParityAccountsInfo, ParityAccounts, ParitySet, ParitySetAccounts, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, Shh, Signer, Traces, Web3};
 pub use self::impls::*;
-pub use self::helpers::{NetworkSettings, block_import, dispatch};
+pub use self::helpers::{InstalledFilters, NetworkSettings, PollLimits, block_import, dispatch};
+pub use self::helpers::heavy::{HeavyRequests, RequestPools};
+pub use self::helpers::response_cache::{self as response_cache, ResponseCache};
 pub use self::metadata::Metadata;
 pub use self::types::Origin;
 pub use self::types::pubsub::PubSubSyncStatus;
@@ -54,5 +56,6 @@ pub mod signer {
 	#[cfg(any(test, feature = "accounts"))]
 	pub use super::helpers::engine_signer::EngineSigner;
 	pub use super::helpers::external_signer::{SignerService, ConfirmationsQueue};
+	pub use super::helpers::remote_signer::RemoteSigner;
 	pub use super::types::{ConfirmationRequest, TransactionModification, TransactionCondition};
 }
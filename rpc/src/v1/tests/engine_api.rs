@@ -0,0 +1,174 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Engine API tests driving a client which imports blocks on an io service, like a running node.
+
+use std::sync::{Arc, Weak};
+
+use client_traits::{ChainInfo, ImportBlock};
+use ethcore::client::{Client, ClientConfig};
+use ethcore::miner::Miner;
+use ethcore::test_helpers;
+use ethereum_types::{H160, H256, U256};
+use io::{IoContext, IoHandler as IoMessageHandler, IoService};
+use jsonrpc_core::IoHandler;
+use serde_json::{self, Value};
+use spec;
+use types::io_message::ClientIoMessage;
+
+use v1::{EngineApi, EngineApiClient};
+use v1::helpers::heavy::HeavyRequests;
+use v1::types::ExecutionPayload;
+
+/// Imports the blocks of the queue once they are verified.
+struct BlockImporter(Weak<Client>);
+
+impl IoMessageHandler<ClientIoMessage<Client>> for BlockImporter {
+	fn message(&self, _io: &IoContext<ClientIoMessage<Client>>, message: &ClientIoMessage<Client>) {
+		if let ClientIoMessage::BlockVerified = *message {
+			if let Some(client) = self.0.upgrade() {
+				client.import_verified_blocks();
+			}
+		}
+	}
+}
+
+struct EngineTester {
+	_service: IoService<ClientIoMessage<Client>>,
+	client: Arc<Client>,
+	io: IoHandler,
+}
+
+impl EngineTester {
+	fn new() -> Self {
+		let spec = spec::new_test_merge();
+		let service = IoService::<ClientIoMessage<Client>>::start().unwrap();
+		let miner = Arc::new(Miner::new_for_tests(&spec, None));
+		let client = Client::new(
+			ClientConfig::default(),
+			&spec,
+			test_helpers::new_db(),
+			miner.clone(),
+			service.channel(),
+		).unwrap();
+		client.engine().register_client(Arc::downgrade(&client) as _);
+		service.register_handler(Arc::new(BlockImporter(Arc::downgrade(&client)))).unwrap();
+
+		let engine_api = EngineApiClient::new(client.clone(), miner, HeavyRequests::new_sync());
+		client.add_notify(engine_api.handler().upgrade().unwrap());
+		let mut io = IoHandler::new();
+		io.extend_with(engine_api.to_delegate());
+
+		EngineTester { _service: service, client, io }
+	}
+
+	fn call(&self, method: &str, params: Value) -> Value {
+		let request = serde_json::json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1});
+		let response = self.io.handle_request_sync(&request.to_string()).unwrap();
+		let mut response: Value = serde_json::from_str(&response).unwrap();
+		match response.get("error") {
+			Some(error) => panic!("{} failed: {}", method, error),
+			None => response["result"].take(),
+		}
+	}
+
+	fn forkchoice_updated(&self, head: H256, attributes: Value) -> Value {
+		let state = serde_json::json!({"headBlockHash": head, "safeBlockHash": head, "finalizedBlockHash": H256::zero()});
+		self.call("engine_forkchoiceUpdatedV1", serde_json::json!([state, attributes]))
+	}
+
+	fn new_payload(&self, payload: &ExecutionPayload) -> Value {
+		self.call("engine_newPayloadV1", serde_json::json!([payload]))
+	}
+
+	/// Builds a payload on top of the best block.
+	fn build_payload(&self, timestamp: u64) -> ExecutionPayload {
+		let head = self.client.chain_info().best_block_hash;
+		let attributes = serde_json::json!({
+			"timestamp": format!("{:#x}", timestamp),
+			"prevRandao": H256::repeat_byte(3),
+			"suggestedFeeRecipient": H160::repeat_byte(1),
+		});
+		let response = self.forkchoice_updated(head, attributes);
+		assert_eq!(response["payloadStatus"]["status"], "VALID");
+		let payload = self.call("engine_getPayloadV1", serde_json::json!([response["payloadId"]]));
+		serde_json::from_value(payload).unwrap()
+	}
+}
+
+#[test]
+fn engine_api_builds_imports_and_selects_payloads() {
+	let tester = EngineTester::new();
+	let genesis_hash = tester.client.chain_info().best_block_hash;
+
+	let payload = tester.build_payload(12);
+	assert_eq!(payload.parent_hash, genesis_hash);
+	assert_eq!(payload.fee_recipient, H160::repeat_byte(1));
+	assert_eq!(payload.prev_randao, H256::repeat_byte(3));
+	assert_eq!(payload.base_fee_per_gas, U256::from(1_000_000_000));
+	assert_eq!(payload.timestamp, 12.into());
+
+	// the payload is executed, but only becomes the best block on a fork choice update.
+	let status = tester.new_payload(&payload);
+	assert_eq!(status["status"], "VALID");
+	assert_eq!(status["latestValidHash"], serde_json::json!(payload.block_hash));
+	assert_eq!(tester.client.chain_info().best_block_hash, genesis_hash);
+	assert_eq!(tester.new_payload(&payload)["status"], "VALID");
+
+	let response = tester.forkchoice_updated(payload.block_hash, Value::Null);
+	assert_eq!(response["payloadStatus"]["status"], "VALID");
+	assert_eq!(response["payloadId"], Value::Null);
+	assert_eq!(tester.client.chain_info().best_block_hash, payload.block_hash);
+
+	// pointing the head back at a canonical ancestor keeps the best block.
+	let response = tester.forkchoice_updated(genesis_hash, Value::Null);
+	assert_eq!(response["payloadStatus"]["status"], "VALID");
+	assert_eq!(tester.client.chain_info().best_block_hash, payload.block_hash);
+
+	// the next payload starts from the base fee of its parent.
+	let next = tester.build_payload(24);
+	assert_eq!(next.parent_hash, payload.block_hash);
+	assert_eq!(next.base_fee_per_gas, U256::from(875_000_000));
+	assert_eq!(tester.new_payload(&next)["status"], "VALID");
+}
+
+#[test]
+fn engine_api_rejects_invalid_payloads() {
+	let tester = EngineTester::new();
+	let genesis_hash = tester.client.chain_info().best_block_hash;
+	let payload = tester.build_payload(12);
+
+	let mut wrong_hash = payload.clone();
+	wrong_hash.block_hash = H256::repeat_byte(4);
+	assert_eq!(tester.new_payload(&wrong_hash)["status"], "INVALID_BLOCK_HASH");
+
+	let mut wrong_base_fee = payload.clone();
+	wrong_base_fee.base_fee_per_gas = 7.into();
+	wrong_base_fee.block_hash = wrong_base_fee.clone().into_unverified().unwrap().header.hash();
+	let status = tester.new_payload(&wrong_base_fee);
+	assert_eq!(status["status"], "INVALID");
+	assert_eq!(status["latestValidHash"], serde_json::json!(genesis_hash));
+	assert!(status["validationError"].as_str().unwrap().contains("base fee"));
+
+	let mut unknown_parent = payload.clone();
+	unknown_parent.parent_hash = H256::repeat_byte(5);
+	unknown_parent.block_hash = unknown_parent.clone().into_unverified().unwrap().header.hash();
+	assert_eq!(tester.new_payload(&unknown_parent)["status"], "SYNCING");
+	assert_eq!(tester.forkchoice_updated(unknown_parent.block_hash, Value::Null)["payloadStatus"]["status"], "SYNCING");
+
+	assert_eq!(tester.client.chain_info().best_block_hash, genesis_hash);
+	assert_eq!(tester.new_payload(&payload)["status"], "VALID");
+}
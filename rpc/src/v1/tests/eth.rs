@@ -39,6 +39,7 @@ use types::{
 
 use jsonrpc_core::IoHandler;
 use v1::helpers::dispatch::{self, FullDispatcher};
+use v1::helpers::heavy::HeavyRequests;
 use v1::helpers::nonce;
 use v1::impls::{EthClient, EthClientOptions, SigningUnsafeClient};
 use v1::metadata::Metadata;
@@ -144,8 +145,10 @@ impl EthTester {
 				gas_price_percentile: 50,
 				allow_experimental_rpcs: true,
 				allow_missing_blocks: false,
+				reconstruct_logs: false,
 				no_ancient_blocks: false
 			},
+			HeavyRequests::new_sync(),
 		);
 
 		let reservations = Arc::new(Mutex::new(nonce::Reservations::new(runtime.executor())));
@@ -27,8 +27,8 @@ use ethcore::client::{PrepareOpenBlock, EngineInfo};
 use ethcore::miner::{self, MinerService, AuthoringParams, FilterOptions};
 use ethcore::test_helpers::TestState;
 use ethereum_types::{H256, U256, Address};
-use miner::pool::local_transactions::Status as LocalTransactionStatus;
-use miner::pool::{verifier, VerifiedTransaction, QueueStatus};
+use miner::pool::local_transactions::{Status as LocalTransactionStatus, Submission};
+use miner::pool::{verifier, VerifiedTransaction, QueueStatus, TransactionAge, Inspection};
 use parking_lot::{RwLock, Mutex};
 use txpool;
 use types::{
@@ -38,7 +38,7 @@ use types::{
 	errors::EthcoreError as Error,
 	ids::BlockId,
 	receipt::RichReceipt,
-	transaction::{self, UnverifiedTransaction, SignedTransaction, PendingTransaction},
+	transaction::{self, Action, UnverifiedTransaction, SignedTransaction, PendingTransaction},
 };
 
 /// Test miner service.
@@ -49,14 +49,24 @@ pub struct TestMinerService {
 	pub pending_transactions: Mutex<HashMap<H256, SignedTransaction>>,
 	/// Pre-existed local transactions
 	pub local_transactions: Mutex<BTreeMap<H256, LocalTransactionStatus>>,
+	/// Submission metadata of local transactions.
+	pub local_submissions: Mutex<BTreeMap<H256, Submission>>,
 	/// Pre-existed pending receipts
 	pub pending_receipts: Mutex<Vec<RichReceipt>>,
+	/// Ages of transactions in the pool.
+	pub transaction_ages: Mutex<HashMap<H256, TransactionAge>>,
+	/// Snapshot of the pool returned by `inspect_pool`.
+	pub pool_inspection: Mutex<Inspection>,
 	/// Next nonces.
 	pub next_nonces: RwLock<HashMap<Address, U256>>,
 	/// Minimum gas price
 	pub min_gas_price: RwLock<Option<U256>>,
 	/// Signer (if any)
 	pub signer: RwLock<Option<Box<dyn EngineSigner>>>,
+	/// Submitted bundles
+	pub bundles: Mutex<Vec<miner::Bundle>>,
+	/// Pending block construction statistics
+	pub pending_block_stats: RwLock<miner::PendingBlockStats>,
 
 	authoring_params: RwLock<AuthoringParams>,
 }
@@ -67,7 +77,10 @@ impl Default for TestMinerService {
 			imported_transactions: Default::default(),
 			pending_transactions: Default::default(),
 			local_transactions: Default::default(),
+			local_submissions: Default::default(),
 			pending_receipts: Default::default(),
+			transaction_ages: Default::default(),
+			pool_inspection: Default::default(),
 			next_nonces: Default::default(),
 			min_gas_price: RwLock::new(Some(0.into())),
 			authoring_params: RwLock::new(AuthoringParams {
@@ -76,6 +89,8 @@ impl Default for TestMinerService {
 				extra_data: vec![1, 2, 3, 4],
 			}),
 			signer: RwLock::new(None),
+			bundles: Default::default(),
+			pending_block_stats: Default::default(),
 		}
 	}
 }
@@ -145,6 +160,39 @@ impl MinerService for TestMinerService {
 		self.authoring_params.write().gas_range_target = target;
 	}
 
+	fn submit_bundle<C>(&self, _chain: &C, bundle: miner::Bundle) -> Result<H256, miner::BundleError> {
+		let hash = bundle.hash();
+		self.bundles.lock().push(bundle);
+		Ok(hash)
+	}
+
+	fn call_bundle<C>(&self, _chain: &C, bundle: &miner::Bundle, _timestamp: Option<u64>) -> Result<miner::BundleSimulation, Error> {
+		// lets assume that every transaction is a plain transfer paying its fee to the author
+		let transactions: Vec<_> = bundle.transactions.iter().map(|tx| miner::BundleTransactionResult {
+			hash: tx.hash(),
+			sender: tx.sender(),
+			to: match tx.action {
+				Action::Call(to) => Some(to),
+				Action::Create => None,
+			},
+			gas_price: tx.gas_price,
+			gas_used: 21_000.into(),
+			coinbase_diff: tx.gas_price * U256::from(21_000),
+			success: true,
+			output: vec![],
+		}).collect();
+		let gas_fees = transactions.iter().fold(U256::zero(), |acc, tx| acc + tx.coinbase_diff);
+
+		Ok(miner::BundleSimulation {
+			bundle_hash: bundle.hash(),
+			block_number: bundle.block_number,
+			gas_used: U256::from(21_000 * transactions.len()),
+			gas_fees,
+			coinbase_diff: gas_fees,
+			transactions,
+		})
+	}
+
 	/// Imports transactions to transaction queue.
 	fn import_external_transactions<C: Nonce + Sync>(&self, chain: &C, transactions: Vec<UnverifiedTransaction>)
 		-> Vec<Result<(), transaction::Error>>
@@ -225,6 +273,10 @@ impl MinerService for TestMinerService {
 		self.local_transactions.lock().iter().map(|(hash, stats)| (*hash, stats.clone())).collect()
 	}
 
+	fn local_transaction_submissions(&self) -> BTreeMap<H256, Submission> {
+		self.local_submissions.lock().clone()
+	}
+
 	fn ready_transactions<C>(&self, _chain: &C, _max_len: usize, _ordering: miner::PendingOrdering) -> Vec<Arc<VerifiedTransaction>> {
 		self.queued_transactions()
 	}
@@ -274,6 +326,14 @@ impl MinerService for TestMinerService {
 		false
 	}
 
+	fn transaction_age(&self, hash: &H256) -> Option<TransactionAge> {
+		self.transaction_ages.lock().get(hash).cloned()
+	}
+
+	fn inspect_pool<C>(&self, _chain: &C) -> Inspection {
+		self.pool_inspection.lock().clone()
+	}
+
 	fn queue_status(&self) -> QueueStatus {
 		QueueStatus {
 			options: verifier::Options {
@@ -292,9 +352,14 @@ impl MinerService for TestMinerService {
 				max_per_sender: 16,
 				max_mem_usage: 5_000,
 			},
+			quotas: Default::default(),
 		}
 	}
 
+	fn pending_block_stats(&self) -> miner::PendingBlockStats {
+		self.pending_block_stats.read().clone()
+	}
+
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, _pow_hash: H256, _seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
@@ -21,7 +21,7 @@ use ethereum_types::{H256, H512};
 use parking_lot::RwLock;
 use network::client_version::ClientVersion;
 use futures::sync::mpsc;
-use sync::{SyncProvider, EthProtocolInfo, SyncStatus, PeerInfo, TransactionStats, SyncState};
+use sync::{SyncProvider, EthProtocolInfo, SyncStatus, PeerInfo, TransactionStats, SyncState, ForkStatus, ForkId, ForkHash};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -133,6 +133,14 @@ impl SyncProvider for TestSyncProvider {
 		unimplemented!()
 	}
 
+	fn fork_status(&self) -> Option<ForkStatus> {
+		Some(ForkStatus {
+			fork_id: ForkId { hash: ForkHash(0xfc64ec04), next: 1_150_000 },
+			forks: vec![1_150_000, 1_920_000],
+			rejected_peers: 3,
+		})
+	}
+
 	fn is_major_syncing(&self) -> bool {
 		match (self.status.read().state, *self.is_importing.read()) {
 			(SyncState::Idle, _) => false,
@@ -0,0 +1,79 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ethcore::test_helpers::TestBlockChainClient;
+use jsonrpc_core::IoHandler;
+use spec;
+
+use v1::{Clique, CliqueClient};
+
+fn io(client: TestBlockChainClient) -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(CliqueClient::new(Arc::new(client)).to_delegate());
+	io
+}
+
+fn goerli_client() -> TestBlockChainClient {
+	TestBlockChainClient::new_with_spec(spec::new_goerli(&String::new()))
+}
+
+#[test]
+fn rpc_clique_unsupported_engine() {
+	let io = io(TestBlockChainClient::default());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_getSigners", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The chain engine does not vote on signers."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_clique_get_signers() {
+	let client = goerli_client();
+	let genesis = format!("{:?}", client.genesis_hash);
+	let io = io(client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_getSigners", "params": ["latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0xe0a2bd4258d2768837baa26a28fe71dc079f84c7"],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "clique_getSignersAtHash", "params": ["{}"], "id": 1}}"#, genesis);
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_clique_propose_and_discard() {
+	let io = io(goerli_client());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_propose", "params": ["0x0000000000000000000000000000000000000001", true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_discard", "params": ["0x0000000000000000000000000000000000000001"], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_clique_status() {
+	let io = io(goerli_client());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "clique_status", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"inturnPercent":0.0,"numBlocks":0,"sealerActivity":{"0xe0a2bd4258d2768837baa26a28fe71dc079f84c7":0}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -15,17 +15,21 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
 use ethcore::test_helpers::TestBlockChainClient;
+use types::transaction::CallError;
 
 use jsonrpc_core::IoHandler;
-use v1::{Debug, DebugClient};
+use v1::{Debug, DebugClient, HeavyRequests};
 
 fn io() -> IoHandler {
-	let client = Arc::new(TestBlockChainClient::new());
+	io_with_client(Arc::new(TestBlockChainClient::new()))
+}
 
+fn io_with_client(client: Arc<TestBlockChainClient>) -> IoHandler {
 	let mut io = IoHandler::new();
-	io.extend_with(DebugClient::new(client).to_delegate());
+	io.extend_with(DebugClient::new(client, HeavyRequests::new_sync()).to_delegate());
 	io
 }
 
@@ -35,3 +39,64 @@ fn rpc_debug_get_bad_blocks() {
 	let response = "{\"jsonrpc\":\"2.0\",\"result\":[{\"author\":\"0x0000000000000000000000000000000000000000\",\"difficulty\":\"0x0\",\"extraData\":\"0x\",\"gasLimit\":\"0x0\",\"gasUsed\":\"0x0\",\"hash\":\"0x27bfb37e507ce90da141307204b1c6ba24194380613590ac50ca4b1d7198ff65\",\"logsBloom\":\"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\",\"miner\":\"0x0000000000000000000000000000000000000000\",\"number\":\"0x0\",\"parentHash\":\"0x0000000000000000000000000000000000000000000000000000000000000000\",\"reason\":\"Invalid block\",\"receiptsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"rlp\":\"\\\"0x010203\\\"\",\"sealFields\":[],\"sha3Uncles\":\"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347\",\"size\":\"0x3\",\"stateRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"timestamp\":\"0x0\",\"totalDifficulty\":null,\"transactions\":[],\"transactionsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"uncles\":[]}],\"id\":1}";
 	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_debug_trace_transaction_steps_unknown_transaction() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransactionSteps", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001", {"after": 10, "count": 5}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error occurred: {}, this should not be the case with eth_call, most likely a bug.","data":"TransactionNotFound"},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_steps_resumes_replay_for_next_page() {
+	let client = Arc::new(TestBlockChainClient::new());
+	*client.struct_log_steps.write() = Some(5);
+	let io = io_with_client(client.clone());
+
+	let page = |after: usize| {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "debug_traceTransactionSteps", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001", {{"after": {}, "count": 2}}], "id": 1}}"#, after);
+		let response: ::serde_json::Value = ::serde_json::from_str(&io.handle_request_sync(&request).unwrap()).unwrap();
+		let pcs: Vec<u64> = response["result"]["logs"].as_array().unwrap().iter().map(|log| log["pc"].as_u64().unwrap()).collect();
+		(pcs, response["result"]["complete"].as_bool().unwrap())
+	};
+
+	assert_eq!(page(0), (vec![0, 1], false));
+	assert_eq!(page(2), (vec![2, 3], false));
+	assert_eq!(page(4), (vec![4], true));
+	assert_eq!(client.struct_log_replays.load(Ordering::SeqCst), 1);
+
+	// a page which doesn't follow a paused replay starts a new one
+	assert_eq!(page(1), (vec![1, 2], false));
+	assert_eq!(client.struct_log_replays.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn rpc_debug_trace_transaction_gas_profile_unknown_transaction() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransactionGasProfile", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error occurred: {}, this should not be the case with eth_call, most likely a bug.","data":"TransactionNotFound"},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_call_gas_profile_state_pruned() {
+	let client = Arc::new(TestBlockChainClient::new());
+	*client.execution_result.write() = Some(Err(CallError::StatePruned));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceCallGasProfile", "params": [{}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+	assert_eq!(io_with_client(client).handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_execution_witness_unknown_block() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_executionWitness", "params": ["0x10"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_execution_witness_pending_block() {
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_executionWitness", "params": ["pending"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: `BlockNumber::Pending` is not supported","data":"()"},"id":1}"#;
+	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
+}
@@ -40,7 +40,7 @@ use types::{
 };
 
 use jsonrpc_core::IoHandler;
-use v1::{Eth, EthClient, EthClientOptions, EthFilter, EthFilterClient};
+use v1::{Eth, EthClient, EthClientOptions, EthFilter, EthFilterClient, HeavyRequests, InstalledFilters, PollLimits};
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestSnapshotService};
 use v1::metadata::Metadata;
 
@@ -97,8 +97,9 @@ impl EthTester {
 		let snapshot = snapshot_service();
 		let hashrates = Arc::new(Mutex::new(HashMap::new()));
 		let external_miner = Arc::new(ExternalMiner::new(hashrates.clone()));
-		let eth = EthClient::new(&client, &snapshot, &sync, &opt_ap, &miner, &external_miner, options).to_delegate();
-		let filter = EthFilterClient::new(client.clone(), miner.clone(), 60).to_delegate();
+		let eth = EthClient::new(&client, &snapshot, &sync, &opt_ap, &miner, &external_miner, options, HeavyRequests::new_sync()).to_delegate();
+		let filters = Arc::new(InstalledFilters::new(PollLimits::default()));
+		let filter = EthFilterClient::new(client.clone(), miner.clone(), filters, HeavyRequests::new_sync()).to_delegate();
 
 		let mut io: IoHandler<Metadata> = IoHandler::default();
 		io.extend_with(eth);
@@ -242,6 +243,33 @@ fn rpc_eth_logs() {
 	assert_eq!(tester.io.handle_request_sync(request3), Some(response3.to_owned()));
 }
 
+#[test]
+fn rpc_eth_logs_reconstructed() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|options| {
+		options.reconstruct_logs = true;
+	}));
+	let log = |log_index| LocalizedLogEntry {
+		block_number: 1,
+		block_hash: H256::zero(),
+		entry: LogEntry {
+			address: Address::zero(),
+			topics: vec![],
+			data: vec![1,2,3],
+		},
+		transaction_index: 0,
+		transaction_log_index: log_index,
+		transaction_hash: H256::zero(),
+		log_index,
+	};
+	tester.client.set_logs(vec![log(1)]);
+	tester.client.set_reconstructed_logs(vec![log(0)]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"address":"0x0000000000000000000000000000000000000000","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","blockNumber":"0x1","data":"0x010203","logIndex":"0x0","removed":false,"topics":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x0","type":"reconstructed"},{"address":"0x0000000000000000000000000000000000000000","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","blockNumber":"0x1","data":"0x010203","logIndex":"0x1","removed":false,"topics":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x1","type":"mined"}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_logs_error() {
 	fn h256_from_digit_be(d: u8) -> H256 {
@@ -483,6 +511,50 @@ fn rpc_eth_storage_at() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_balances() {
+	let tester = EthTester::default();
+	tester.client.set_balance(Address::from_low_u64_be(1), U256::from(5));
+	tester.client.set_balance(Address::from_low_u64_be(2), U256::from(6));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getBalances",
+		"params": [["0x0000000000000000000000000000000000000001", "0x0000000000000000000000000000000000000002", "0x0000000000000000000000000000000000000003"], "latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x5","0x6","0x0"],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_storage_at_batch() {
+	let tester = EthTester::default();
+	tester.client.set_storage(Address::from_low_u64_be(1), H256::from_low_u64_be(4), H256::from_low_u64_be(7));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getStorageAtBatch",
+		"params": [[["0x0000000000000000000000000000000000000001", "0x4"], ["0x0000000000000000000000000000000000000001", "0x5"]], "latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x0000000000000000000000000000000000000000000000000000000000000007","0x0000000000000000000000000000000000000000000000000000000000000000"],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_balances_limit() {
+	let tester = EthTester::default();
+	let addresses = vec![r#""0x0000000000000000000000000000000000000001""#; 1025].join(",");
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getBalances", "params": [["#.to_owned() + &addresses + r#"], "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32041,"message":"Requested data size exceeds limit of 1024 reads."},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_transaction_count() {
 	let request = r#"{
@@ -0,0 +1,121 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use crypto::publickey::{Generator, Random};
+use ethcore::test_helpers::TestBlockChainClient;
+use ethereum_types::{Address, U256};
+use jsonrpc_core::IoHandler;
+use rlp;
+use rustc_hex::ToHex;
+use types::transaction::{Action, SignedTransaction, Transaction};
+
+use v1::{EthBundle, EthBundleClient};
+use v1::tests::helpers::TestMinerService;
+
+fn io(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>) -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(EthBundleClient::new(client, miner).to_delegate());
+	io
+}
+
+fn transaction() -> SignedTransaction {
+	Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(10),
+		gas: U256::from(21_000),
+		action: Action::Call(Address::from_low_u64_be(1)),
+		value: U256::zero(),
+		data: vec![],
+	}.sign(Random.generate().secret(), None)
+}
+
+#[test]
+fn rpc_eth_send_bundle() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&client, &miner);
+	let tx = transaction();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_sendBundle", "params": [{"txs": ["0x"#.to_owned()
+		+ &rlp::encode(&tx).to_hex() + r#""], "blockNumber": "0x1", "revertingTxHashes": [""#
+		+ &format!("0x{:x}", tx.hash()) + r#""]}], "id": 1}"#;
+	let response = io.handle_request_sync(&request).unwrap();
+
+	let bundles = miner.bundles.lock();
+	assert_eq!(bundles.len(), 1);
+	assert_eq!(bundles[0].transactions, vec![tx.clone()]);
+	assert_eq!(bundles[0].block_number, 1);
+	assert!(bundles[0].reverting_hashes.contains(&tx.hash()));
+	assert_eq!(response, format!(r#"{{"jsonrpc":"2.0","result":{{"bundleHash":"0x{:x}"}},"id":1}}"#, bundles[0].hash()));
+}
+
+#[test]
+fn rpc_eth_send_bundle_rejects_invalid_transactions() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&client, &miner);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_sendBundle", "params": [{"txs": ["0x01"], "blockNumber": "0x1"}], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""error""#));
+	assert!(miner.bundles.lock().is_empty());
+}
+
+#[test]
+fn rpc_eth_call_bundle() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&client, &miner);
+	let tx = transaction();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_callBundle", "params": [{"txs": ["0x"#.to_owned()
+		+ &rlp::encode(&tx).to_hex() + r#""], "blockNumber": "0x1", "stateBlockNumber": "latest"}], "id": 1}"#;
+	let response = io.handle_request_sync(&request).unwrap();
+
+	assert!(response.contains(r#""bundleGasPrice":"0xa""#));
+	assert!(response.contains(r#""coinbaseDiff":"0x33450""#));
+	assert!(response.contains(r#""ethSentToCoinbase":"0x0""#));
+	assert!(response.contains(r#""stateBlockNumber":"0x0""#));
+	assert!(response.contains(r#""totalGasUsed":"0x5208""#));
+	assert!(response.contains(&format!(r#""txHash":"0x{:x}""#, tx.hash())));
+	assert!(miner.bundles.lock().is_empty());
+}
+
+#[test]
+fn rpc_eth_call_bundle_on_historical_state() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&client, &miner);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_callBundle", "params": [{"txs": [], "blockNumber": "0x1", "stateBlockNumber": "earliest"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Bundles can only be executed on top of the latest block."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_bundle_refuses_too_many_transactions() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&client, &miner);
+
+	let txs = vec![r#""0x01""#; 65].join(",");
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_callBundle", "params": [{"txs": ["#.to_owned()
+		+ &txs + r#"], "blockNumber": "0x1", "stateBlockNumber": "latest"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Bundle has 65 transactions, at most 64 are allowed"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+}
@@ -26,11 +26,13 @@ use v1::{EthPubSub, EthPubSubClient, Metadata};
 use ethcore::test_helpers::{TestBlockChainClient, EachBlockWith};
 use parity_runtime::Runtime;
 use ethereum_types::{Address, H256};
-use client_traits::{BlockInfo, ChainNotify};
+use client_traits::{BlockChainClient, BlockInfo, ChainNotify, ImportBlock};
+use rlp::RlpStream;
 use types::{
 	chain_notify::{NewBlocks, ChainRoute, ChainRouteType},
 	log_entry::{LocalizedLogEntry, LogEntry},
 	ids::BlockId,
+	verification::Unverified,
 };
 
 
@@ -228,3 +230,125 @@ fn eth_subscribe_syncing() {
 	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["syncing"], "id": 1}"#;
 	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
 }
+
+#[test]
+fn should_subscribe_to_reorgs() {
+	// given
+	let el = Runtime::with_thread_count(1);
+	let client = TestBlockChainClient::new();
+	// Insert some blocks
+	client.add_blocks(2, EachBlockWith::Transaction);
+	let h2 = client.block_hash_delta_minus(1);
+	let h1 = client.block_hash_delta_minus(2);
+	let tx1 = client.block_body(BlockId::Hash(h1)).unwrap().transaction_hashes()[0];
+
+	// A sibling of the first block including the same transaction
+	let block = client.block(BlockId::Hash(h1)).unwrap();
+	let mut header = block.decode_header();
+	header.set_timestamp(1);
+	let mut rlp = RlpStream::new_list(3);
+	rlp.append(&header);
+	rlp.append_list(&block.transactions());
+	rlp.begin_list(0);
+	let h1b = client.import_block(Unverified::from_rlp(rlp.out()).unwrap()).unwrap();
+
+	let (_, pool_receiver) = mpsc::unbounded();
+
+	let pubsub = EthPubSubClient::new(Arc::new(client), el.executor(), pool_receiver);
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Fail if params are provided
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["reorgs", {}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: reorgs","data":"\"Expected no parameters.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["reorgs"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x43ca64edf03768e1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// No notification without retracted blocks
+	handler.new_blocks(NewBlocks::new(vec![], vec![], ChainRoute::new(vec![(h2, ChainRouteType::Enacted)]), vec![], vec![], DURATION_ZERO, false));
+
+	// Retracted transaction is unmined
+	handler.new_blocks(NewBlocks::new(vec![], vec![], ChainRoute::new(vec![(h1, ChainRouteType::Retracted), (h2, ChainRouteType::Enacted)]), vec![], vec![], DURATION_ZERO, false));
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{{"enacted":["{:?}"],"reminedTransactions":[],"retracted":["{:?}"],"unminedTransactions":["{:?}"]}},"subscription":"0x43ca64edf03768e1"}}}}"#, h2, h1, tx1);
+	assert_eq!(res, Some(response));
+
+	// Retracted transaction included again is remined
+	handler.new_blocks(NewBlocks::new(vec![], vec![], ChainRoute::new(vec![(h1, ChainRouteType::Retracted), (h1b, ChainRouteType::Enacted)]), vec![], vec![], DURATION_ZERO, false));
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{{"enacted":["{:?}"],"reminedTransactions":["{:?}"],"retracted":["{:?}"],"unminedTransactions":[]}},"subscription":"0x43ca64edf03768e1"}}}}"#, h1b, tx1, h1);
+	assert_eq!(res, Some(response));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x43ca64edf03768e1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
+#[test]
+fn should_subscribe_to_state_changes() {
+	// given
+	let el = Runtime::with_thread_count(1);
+	let client = TestBlockChainClient::new();
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let h1 = client.block_hash_delta_minus(1);
+
+	let genesis = client.genesis_hash;
+	let client = Arc::new(client);
+	let (_, pool_receiver) = mpsc::unbounded();
+
+	let pubsub = EthPubSubClient::new(client.clone(), el.executor(), pool_receiver);
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Fail without watched accounts
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["stateChanges"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: stateChanges","data":"\"Expected an object with watched addresses.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["stateChanges", {"addresses": ["0x0000000000000000000000000000000000000001"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x43ca64edf03768e1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Blocks whose state can't be read are skipped
+	handler.new_blocks(NewBlocks::new(vec![], vec![], ChainRoute::new(vec![(h1, ChainRouteType::Enacted)]), vec![], vec![], DURATION_ZERO, false));
+
+	// Balance changed by the block
+	let address = Address::from_low_u64_be(1);
+	client.set_block_balance(genesis, address, 5.into());
+	client.set_block_balance(h1, address, 7.into());
+	handler.new_blocks(NewBlocks::new(vec![], vec![], ChainRoute::new(vec![(h1, ChainRouteType::Enacted)]), vec![], vec![], DURATION_ZERO, false));
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"result":{{"accounts":[{{"address":"{:?}","balance":{{"from":"0x5","to":"0x7"}}}}],"blockHash":"{:?}","blockNumber":"0x1","removed":false}},"subscription":"0x43ca64edf03768e1"}}}}"#, address, h1);
+	assert_eq!(res, Some(response));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x43ca64edf03768e1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
@@ -0,0 +1,122 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use client_traits::ChainInfo;
+use ethcore::test_helpers::{EachBlockWith, TestBlockChainClient};
+use jsonrpc_core::IoHandler;
+use spec;
+
+use v1::{Evm, EvmClient};
+use v1::tests::helpers::TestMinerService;
+
+fn io(client: &Arc<TestBlockChainClient>) -> IoHandler {
+	let miner = Arc::new(TestMinerService::default());
+	let mut io = IoHandler::new();
+	io.extend_with(EvmClient::new(client, &miner).to_delegate());
+	io
+}
+
+fn dev_client() -> Arc<TestBlockChainClient> {
+	Arc::new(TestBlockChainClient::new_with_spec(spec::new_instant()))
+}
+
+#[test]
+fn rpc_evm_unsupported_engine() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_snapshot", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The chain engine does not support manual mining."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_evm_set_automine_and_increase_time() {
+	let client = dev_client();
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_setAutomine", "params": [false], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(!client.spec.engine.dev_mining().unwrap().automine());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_increaseTime", "params": [60], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":60,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_increaseTime", "params": [40], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":100,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_evm_snapshot_and_revert() {
+	let client = dev_client();
+	client.add_blocks(3, EachBlockWith::Nothing);
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_snapshot", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	client.add_blocks(2, EachBlockWith::Nothing);
+	assert_eq!(client.chain_info().best_block_number, 5);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_revert", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(client.chain_info().best_block_number, 3);
+
+	// the snapshot is discarded once reverted to
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_evm_revert_restores_time_offset() {
+	let client = dev_client();
+	let io = io(&client);
+	let dev_mining = client.spec.engine.dev_mining().unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_increaseTime", "params": [60], "id": 1}"#;
+	io.handle_request_sync(request).unwrap();
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_snapshot", "params": [], "id": 1}"#;
+	io.handle_request_sync(request).unwrap();
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_increaseTime", "params": [40], "id": 1}"#;
+	io.handle_request_sync(request).unwrap();
+	assert_eq!(dev_mining.time_offset(), 100);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_revert", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(dev_mining.time_offset(), 60);
+}
+
+#[test]
+fn rpc_evm_increase_time_overflow() {
+	let client = dev_client();
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_increaseTime", "params": [60], "id": 1}"#;
+	io.handle_request_sync(request).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "evm_increaseTime", "params": [18446744073709551615], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: seconds","data":"\"the time offset would overflow\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(client.spec.engine.dev_mining().unwrap().time_offset(), 60);
+}
@@ -15,8 +15,9 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::ops::RangeInclusive;
+use std::time::{Duration, UNIX_EPOCH};
 use sync::ManageNetwork;
-use self::ethcore_network::{ProtocolId, NetworkContext};
+use self::ethcore_network::{ProtocolId, NetworkContext, NatStatus, NatType};
 
 extern crate ethcore_network;
 
@@ -31,5 +32,17 @@ impl ManageNetwork for TestManageNetwork {
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn num_peers_range(&self) -> RangeInclusive<u32> { 25..=50 }
+	fn set_peer_limits(&self, _min_peers: u32, _max_peers: u32) { }
 	fn with_proto_context(&self, _: ProtocolId, _: &mut dyn FnMut(&dyn NetworkContext)) { }
+	fn nat_status(&self) -> Option<NatStatus> {
+		Some(NatStatus {
+			enabled: true,
+			mapped_with: Some(NatType::UPnP),
+			external_address: Some("1.2.3.4:30303".parse().unwrap()),
+			external_udp_port: Some(30304),
+			last_renewed: Some(UNIX_EPOCH + Duration::from_secs(1_600_000_000)),
+			failed_renewals: 0,
+			address_changes: 1,
+		})
+	}
 }
@@ -0,0 +1,54 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use ethcore::miner::MinerService;
+use ethereum_types::U256;
+use jsonrpc_core::IoHandler;
+
+use v1::{Miner, MinerClient};
+use v1::tests::helpers::TestMinerService;
+
+fn io(miner: &Arc<TestMinerService>) -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(MinerClient::new(miner).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_miner_set_gas_limit() {
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&miner);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "miner_setGasLimit", "params": ["0xb71b00"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	let target = U256::from(12_000_000);
+	assert_eq!(miner.authoring_params().gas_range_target, (target, target));
+}
+
+#[test]
+fn rpc_miner_set_gas_limit_rejects_zero() {
+	let miner = Arc::new(TestMinerService::default());
+	let io = io(&miner);
+	let before = miner.authoring_params().gas_range_target;
+
+	let request = r#"{"jsonrpc": "2.0", "method": "miner_setGasLimit", "params": ["0x0"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.starts_with(r#"{"jsonrpc":"2.0","error":{"code":-32602"#));
+	assert_eq!(miner.authoring_params().gas_range_target, before);
+}
@@ -0,0 +1,90 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethcore_stratum::{WorkerStats, WorkerStatsProvider};
+use jsonrpc_core::IoHandler;
+
+use v1::{Mining, MiningClient};
+
+struct TestWorkers;
+
+impl WorkerStatsProvider for TestWorkers {
+	fn workers(&self) -> Vec<WorkerStats> {
+		vec![
+			WorkerStats {
+				name: "rig1".into(),
+				address: "10.0.0.2:40000".parse().unwrap(),
+				tls: false,
+				difficulty: 1_000_000u64.into(),
+				accepted_shares: 10,
+				rejected_shares: 0,
+				blocks: 1,
+				hashrate: 0x1000u64.into(),
+				connected_for: Duration::from_secs(120),
+				last_share: None,
+			},
+			WorkerStats {
+				name: "rig2".into(),
+				address: "10.0.0.3:40000".parse().unwrap(),
+				tls: true,
+				difficulty: 2_000_000u64.into(),
+				accepted_shares: 5,
+				rejected_shares: 2,
+				blocks: 0,
+				hashrate: 0x2000u64.into(),
+				connected_for: Duration::from_secs(60),
+				last_share: Some(Duration::from_secs(5)),
+			},
+		]
+	}
+}
+
+fn io(workers: Option<Arc<dyn WorkerStatsProvider>>) -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(MiningClient::new(workers).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_mining_workers() {
+	let io = io(Some(Arc::new(TestWorkers)));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "mining_workers", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"acceptedShares":10,"address":"10.0.0.2:40000","blocks":1,"connectedFor":120,"difficulty":"0xf4240","hashrate":"0x1000","lastShare":null,"name":"rig1","rejectedShares":0,"tls":false},{"acceptedShares":5,"address":"10.0.0.3:40000","blocks":0,"connectedFor":60,"difficulty":"0x1e8480","hashrate":"0x2000","lastShare":5,"name":"rig2","rejectedShares":2,"tls":true}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_mining_hashrate() {
+	let io = io(Some(Arc::new(TestWorkers)));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "mining_hashrate", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x3000","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_mining_without_stratum() {
+	let io = io(None);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "mining_workers", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Stratum server is not running. Run Parity with --stratum to enable it."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
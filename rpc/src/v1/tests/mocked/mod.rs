@@ -17,9 +17,14 @@
 //! RPC mocked tests. Most of these test that the RPC server is serializing and forwarding
 //! method calls properly.
 
+mod clique;
 mod debug;
 mod eth;
+mod eth_bundle;
 mod eth_pubsub;
+mod evm;
+mod miner;
+mod mining;
 mod manage_network;
 mod net;
 mod parity;
@@ -32,6 +37,7 @@ mod pubsub;
 mod rpc;
 #[cfg(any(test, feature = "accounts"))]
 mod secretstore;
+mod shh;
 mod signer;
 #[cfg(any(test, feature = "accounts"))]
 mod signing;
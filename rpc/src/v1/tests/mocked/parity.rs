@@ -20,7 +20,7 @@ use ethcore_logger::RotatingLogger;
 use ethereum_types::{Address, U256, H256, BigEndianHash, Bloom};
 use crypto::publickey::{Generator, Random};
 use machine::executed::Executed;
-use miner::pool::local_transactions::Status as LocalTransactionStatus;
+use miner::pool::local_transactions::{Status as LocalTransactionStatus, Submission};
 use sync::ManageNetwork;
 use types::{
 	ids::TransactionId,
@@ -31,6 +31,7 @@ use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::NetworkSettings;
+use v1::helpers::heavy::{HeavyRequests, RequestPools};
 use v1::helpers::external_signer::SignerService;
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
@@ -86,6 +87,13 @@ impl Dependencies {
 			signer,
 			self.ws_address.clone(),
 			None,
+			None,
+			RequestPools {
+				traces: HeavyRequests::new_sync(),
+				logs: HeavyRequests::new_sync(),
+				debug: HeavyRequests::new_sync(),
+				engine: HeavyRequests::new_sync(),
+			},
 		)
 	}
 
@@ -340,6 +348,28 @@ fn rpc_parity_net_port() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_fork_id() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_forkId", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"forks":[1150000,1920000],"hash":"0xfc64ec04","next":1150000,"rejectedPeers":3},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_nat_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_natStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"addressChanges":1,"enabled":true,"externalAddress":"1.2.3.4:30303","externalUdpPort":30304,"failedRenewals":0,"lastRenewed":1600000000,"mappedWith":"upnp"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_rpc_settings() {
 	let deps = Dependencies::new();
@@ -513,6 +543,125 @@ fn rpc_parity_local_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_local_transactions_with_submissions() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let tx = ::types::transaction::Transaction {
+		value: 5.into(),
+		gas: 3.into(),
+		gas_price: 2.into(),
+		action: ::types::transaction::Action::Create,
+		data: vec![1, 2, 3],
+		nonce: 0.into(),
+	}.fake_sign(Address::from_low_u64_be(3));
+	let tx = Arc::new(::miner::pool::VerifiedTransaction::from_pending_block_transaction(tx));
+	deps.miner.local_transactions.lock().insert(H256::from_low_u64_be(10), LocalTransactionStatus::Pending(tx.clone()));
+	deps.miner.local_submissions.lock().insert(H256::from_low_u64_be(10), Submission {
+		submitted_at: ::std::time::UNIX_EPOCH + ::std::time::Duration::from_secs(1_600_000_000),
+		resubmissions: 1,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_localTransactions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x000000000000000000000000000000000000000000000000000000000000000a":{"resubmissions":1,"status":"pending","submittedAt":1600000000}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_transaction_age() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let received_at = ::std::time::UNIX_EPOCH + ::std::time::Duration::from_secs(1_600_000_000);
+	deps.miner.transaction_ages.lock().insert(H256::from_low_u64_be(10), ::miner::pool::TransactionAge {
+		received_at,
+		age: ::std::time::Duration::from_secs(90),
+		expires_at: Some(received_at + ::std::time::Duration::from_secs(600)),
+		is_local: false,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionAge", "params":["0x000000000000000000000000000000000000000000000000000000000000000a"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"age":90,"expiresAt":1600000600,"local":false,"receivedAt":1600000000},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_transactionAge", "params":["0x000000000000000000000000000000000000000000000000000000000000000b"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_txpool_inspect() {
+	use miner::pool::{Inspection, PooledTransaction, DroppedTransaction, TransactionStatus, Priority, DropReason};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let at = ::std::time::UNIX_EPOCH + ::std::time::Duration::from_secs(1_600_000_000);
+	*deps.miner.pool_inspection.lock() = Inspection {
+		transactions: vec![PooledTransaction {
+			hash: H256::from_low_u64_be(10),
+			sender: Address::from_low_u64_be(1),
+			nonce: 2.into(),
+			gas_price: 5.into(),
+			priority_fee: 5.into(),
+			received_at: at,
+			insertion_id: 3,
+			priority: Priority::Local,
+			boost: 15,
+			score: U256::from(5) << 15,
+			status: TransactionStatus::Pending,
+		}],
+		dropped: vec![DroppedTransaction {
+			hash: H256::from_low_u64_be(11),
+			reason: DropReason::Limit,
+			by: None,
+			dropped_at: at,
+		}],
+	};
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_txpoolInspect", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"dropped":{"0x000000000000000000000000000000000000000000000000000000000000000b":{"by":null,"droppedAt":1600000000,"reason":"limit"}},"transactions":{"0x000000000000000000000000000000000000000000000000000000000000000a":{"from":"0x0000000000000000000000000000000000000001","gasPrice":"0x5","insertionId":3,"locality":"local","nonce":"0x2","priorityFee":"0x5","receivedAt":1600000000,"score":"0x28000","scoreBoost":15,"status":"pending"}}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_pending_block_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	*deps.miner.pending_block_stats.write() = ::ethcore::miner::PendingBlockStats {
+		full_builds: 1,
+		incremental_builds: 4,
+		reused: 2,
+		last_build_time: ::std::time::Duration::from_millis(3),
+		total_build_time: ::std::time::Duration::from_millis(40),
+		last_transactions_pushed: 7,
+	};
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingBlockStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"fullBuilds":1,"incrementalBuilds":4,"lastBuildTimeMs":3,"lastTransactionsPushed":7,"reused":2,"totalBuildTimeMs":40},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_uncle_candidates() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	*deps.client.uncle_candidates.write() = vec![::client_traits::UncleCandidate {
+		hash: H256::from_low_u64_be(0xab),
+		number: 41,
+		author: Address::from_low_u64_be(1),
+		age: 2,
+		own: false,
+		included: true,
+		expected_reward: U256::from(0x1bc16d674ec80000u64),
+	}];
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_uncleCandidates", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"age":2,"author":"0x0000000000000000000000000000000000000001","expectedReward":"0x1bc16d674ec80000","hash":"0x00000000000000000000000000000000000000000000000000000000000000ab","included":true,"number":41,"own":false}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_chain_status() {
 	let deps = Dependencies::new();
@@ -527,6 +676,22 @@ fn rpc_parity_chain_status() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_chain_gaps() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_chainGaps", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	*deps.client.ancient_block.write() = Some((H256::zero(), 5));
+	*deps.client.first_block.write() = Some((BigEndianHash::from_uint(&U256::from(1234)), 3333));
+
+	let response = r#"{"jsonrpc":"2.0","result":[{"from":"0x6","missing":"blocks","to":"0xd04"}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_node_kind() {
 	let deps = Dependencies::new();
@@ -678,3 +843,188 @@ fn rpc_parity_verify_signature() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_cht_root_without_store() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_chtRoot", "params": ["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_state_diff_too_many_blocks() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateDiff", "params": ["0x0", "0x500", 10], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: blocks","data":"\"at most 1024 blocks apart\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_state_diff_state_pruned() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateDiff", "params": ["0x0", "latest", 10, null], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_confirmation_status_unknown_transaction() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_confirmationStatus", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_validator_status_unsupported_engine() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_validatorStatus", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The chain engine does not assign sealing steps to validators."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_validator_set_at_without_validator_set() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_validatorSetAt", "params": ["latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_threat_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_threatStatus", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"deepReorgs":"0x0","heldBlocks":"0x0","level":"normal","longestSideBranch":"0x0","maxDifficultySwing":"0x0","maxReorgDepth":"0x0","reasons":[],"sideBlocks":"0x0","window":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_gas_stats_range_limit() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_gasStats", "params": ["0x0", "0x800"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32041,"message":"Requested data size exceeds limit of 2048 blocks."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_gas_stats_reversed_range() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_gasStats", "params": ["0x2", "0x1", 5], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: to","data":"\"range ends before it starts\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_receipt_proof_unknown_transaction() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getReceiptProof", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_ancestry_proof_past_head() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getAncestryProof", "params": ["0x2", "0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: block","data":"\"block is past the head\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_resolve_ens_without_resolver() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_resolveEns", "params": ["gavofyork.eth"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_reverse_ens_without_resolver() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reverseEns", "params": ["0x0000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_next_nonce_by_name() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nextNonce", "params": ["gavofyork.eth"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Name gavofyork.eth doesn't resolve to an address."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_token_balances() {
+	let deps = Dependencies::new();
+	let mut output = vec![0u8; 32];
+	output[31] = 0x12;
+	deps.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0x5208),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output,
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_tokenBalances",
+		"params": ["0x0000000000000000000000000000000000000001", ["0x0000000000000000000000000000000000000002"], "latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"balance":"0x12","decimals":"0x12","symbol":null,"token":"0x0000000000000000000000000000000000000002"}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -21,6 +21,7 @@ use accounts::{AccountProvider, AccountProviderSettings};
 use ethereum_types::Address;
 use ethstore::EthStore;
 use ethstore::accounts_dir::RootDiskDirectory;
+use serde_json;
 use tempfile::TempDir;
 
 use jsonrpc_core::IoHandler;
@@ -480,3 +481,44 @@ fn should_sign_message() {
 	let res = tester.io.handle_request_sync(&request);
 	assert_eq!(res, Some(response.into()));
 }
+
+#[test]
+fn should_export_and_import_vault() {
+	let tester = setup();
+	let address = tester.accounts.new_account(&"password1".into()).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_exportVault", "params": ["", "archive"], "id": 1}"#;
+	let response: serde_json::Value = serde_json::from_str(&tester.io.handle_request_sync(request).unwrap()).unwrap();
+	let archive = response["result"].as_str().unwrap();
+
+	let other = setup();
+	let request = format!(
+		r#"{{"jsonrpc": "2.0", "method": "parity_importVault", "params": ["", {}, "archive"], "id": 1}}"#,
+		serde_json::Value::String(archive.into()),
+	);
+	let response = format!("{{\"jsonrpc\":\"2.0\",\"result\":[\"0x{:x}\"],\"id\":1}}", address);
+	assert_eq!(other.io.handle_request_sync(&request), Some(response));
+	assert!(other.accounts.test_password(&address, &"password1".into()).unwrap());
+}
+
+#[test]
+fn should_reencrypt_vault() {
+	let tester = setup();
+	let address = tester.accounts.new_account(&"password1".into()).unwrap();
+	let locked = tester.accounts.new_account(&"password2".into()).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reencryptVault", "params": ["", ["password1"], {"function": "pbkdf2", "iterations": 1024}], "id": 1}"#;
+	let response = format!("{{\"jsonrpc\":\"2.0\",\"result\":{{\"reencrypted\":[\"0x{:x}\"],\"unchanged\":[],\"locked\":[\"0x{:x}\"]}},\"id\":1}}", address, locked);
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+	assert!(tester.accounts.test_password(&address, &"password1".into()).unwrap());
+
+	for kdf in &[
+		r#"{"function": "scrypt", "n": 1000, "r": 8, "p": 1}"#,
+		r#"{"function": "scrypt", "n": 2147483648, "r": 8, "p": 1}"#,
+		r#"{"function": "pbkdf2", "iterations": 0}"#,
+	] {
+		let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_reencryptVault", "params": ["", [], {}], "id": 1}}"#, kdf);
+		let response: serde_json::Value = serde_json::from_str(&tester.io.handle_request_sync(&request).unwrap()).unwrap();
+		assert_eq!(response["error"]["code"], -32602);
+	}
+}
@@ -15,16 +15,19 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::str::FromStr;
 use rustc_hex::FromHex;
 use ethereum_types::{U256, Address};
 
 use ethcore::miner::MinerService;
 use ethcore::test_helpers::TestBlockChainClient;
+use ethcore_logger::RotatingLogger;
 use sync::ManageNetwork;
 
 use jsonrpc_core::IoHandler;
 use v1::{ParitySet, ParitySetClient};
+use v1::helpers::{InstalledFilters, PollFilter, PollLimits, SyncPollFilter};
 use v1::tests::helpers::{TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 
@@ -46,6 +49,10 @@ fn updater_service() -> Arc<TestUpdater> {
 	Arc::new(TestUpdater::default())
 }
 
+fn logger_service() -> Arc<RotatingLogger> {
+	Arc::new(RotatingLogger::new("rpc=trace".to_owned()))
+}
+
 pub type TestParitySetClient = ParitySetClient<TestBlockChainClient, TestMinerService, TestUpdater, FakeFetch<usize>>;
 
 fn parity_set_client(
@@ -54,11 +61,59 @@ fn parity_set_client(
 	updater: &Arc<TestUpdater>,
 	net: &Arc<TestManageNetwork>,
 ) -> TestParitySetClient {
+	parity_set_client_with_logger(client, miner, updater, net, &logger_service())
+}
+
+fn parity_set_client_with_logger(
+	client: &Arc<TestBlockChainClient>,
+	miner: &Arc<TestMinerService>,
+	updater: &Arc<TestUpdater>,
+	net: &Arc<TestManageNetwork>,
+	logger: &Arc<RotatingLogger>,
+) -> TestParitySetClient {
+	parity_set_client_with_hooks(client, miner, updater, net, logger, Arc::new(|| {}), Arc::new(|_| Ok(vec![])))
+}
+
+fn parity_set_client_with_hooks(
+	client: &Arc<TestBlockChainClient>,
+	miner: &Arc<TestMinerService>,
+	updater: &Arc<TestUpdater>,
+	net: &Arc<TestManageNetwork>,
+	logger: &Arc<RotatingLogger>,
+	shutdown: Arc<dyn Fn() + Send + Sync>,
+	reload: Arc<dyn Fn(bool) -> Result<Vec<String>, String> + Send + Sync>,
+) -> TestParitySetClient {
+	ParitySetClient::new(
+		client,
+		miner,
+		updater,
+		&(net.clone() as Arc<dyn ManageNetwork>),
+		logger,
+		&shutdown,
+		&reload,
+		&Arc::new(InstalledFilters::new(PollLimits::default())),
+		FakeFetch::new(Some(1)),
+	)
+}
+
+fn parity_set_client_with_filters(
+	client: &Arc<TestBlockChainClient>,
+	miner: &Arc<TestMinerService>,
+	updater: &Arc<TestUpdater>,
+	net: &Arc<TestManageNetwork>,
+	filters: &Arc<InstalledFilters>,
+) -> TestParitySetClient {
+	let shutdown: Arc<dyn Fn() + Send + Sync> = Arc::new(|| {});
+	let reload: Arc<dyn Fn(bool) -> Result<Vec<String>, String> + Send + Sync> = Arc::new(|_| Ok(vec![]));
 	ParitySetClient::new(
 		client,
 		miner,
 		updater,
 		&(net.clone() as Arc<dyn ManageNetwork>),
+		&logger_service(),
+		&shutdown,
+		&reload,
+		filters,
 		FakeFetch::new(Some(1)),
 	)
 }
@@ -200,6 +255,94 @@ fn rpc_parity_set_transactions_limit() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_set_logging_targets() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let logger = logger_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client_with_logger(&client, &miner, &updater, &network, &logger).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setLoggingTargets", "params":["sync=debug,rpc=info"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(logger.levels(), "sync=debug,rpc=info");
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setLoggingTargets", "params":["sync=loud"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: targets","data":"\"Invalid logging directive: sync=loud\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(logger.levels(), "sync=debug,rpc=info");
+}
+
+#[test]
+fn rpc_parity_shutdown() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let requested = Arc::new(AtomicBool::new(false));
+	let shutdown = {
+		let requested = requested.clone();
+		Arc::new(move || requested.store(true, Ordering::SeqCst))
+	};
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client_with_hooks(&client, &miner, &updater, &network, &logger_service(), shutdown, Arc::new(|_| Ok(vec![]))).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_shutdown", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(requested.load(Ordering::SeqCst));
+}
+
+#[test]
+fn rpc_parity_reload_config() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let applied = Arc::new(AtomicBool::new(false));
+	let reload = {
+		let applied = applied.clone();
+		Arc::new(move |dry_run: bool| {
+			if !dry_run {
+				applied.store(true, Ordering::SeqCst);
+			}
+			Ok(vec!["max_peers: 50 -> 100".to_owned()])
+		})
+	};
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client_with_hooks(&client, &miner, &updater, &network, &logger_service(), Arc::new(|| {}), reload).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reloadConfig", "params":[true], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["max_peers: 50 -> 100"],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(!applied.load(Ordering::SeqCst));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reloadConfig", "params":[], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(applied.load(Ordering::SeqCst));
+}
+
+#[test]
+fn rpc_parity_reload_config_invalid() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client_with_hooks(&client, &miner, &updater, &network, &logger_service(), Arc::new(|| {}), Arc::new(|_| Err("Invalid logging directive: sync=loud".to_owned()))).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reloadConfig", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: configuration","data":"\"Invalid logging directive: sync=loud\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_hash_content() {
 	let miner = miner_service();
@@ -268,3 +411,123 @@ fn rpc_parity_set_engine_signer() {
 	let signature = miner.signer.read().as_ref().unwrap().sign(::hash::keccak("x")).unwrap().to_vec();
 	assert_eq!(&format!("{}", signature.pretty()), "6f46069ded2154af6e806706e4f7f6fd310ac45f3c6dccb85f11c0059ee20a09245df0a0008bb84a10882b1298284bc93058e7bc5938ea728e77620061687a6401");
 }
+
+#[test]
+fn rpc_parity_reload_engine_params_without_engine_support() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reloadEngineParams", "params":["0x7b7d", "0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+
+	let expected = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The engine parameters cannot be updated while the node is running"},"id":1}"#;
+	assert_eq!(response, expected);
+}
+
+#[test]
+fn rpc_parity_accept_reorg_without_held_reorg() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_acceptReorg", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: hash","data":"\"No reorganization is held back\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_rewrite_database() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_rewriteDatabase", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_list_filters() {
+	use std::collections::VecDeque;
+	use serde_json;
+	use v1::types::Origin;
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let filters = Arc::new(InstalledFilters::new(PollLimits::default()));
+	filters.polls().lock().create_poll(&Origin::Rpc("dapp".into()), SyncPollFilter::new(PollFilter::Block {
+		last_block_number: 4,
+		recent_reported_hashes: VecDeque::new(),
+	}));
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client_with_filters(&client, &miner, &updater, &network, &filters).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listFilters", "params": [], "id": 1}"#;
+	let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let filters = response["result"].as_array().unwrap();
+
+	assert_eq!(filters.len(), 1);
+	assert_eq!(filters[0]["id"], "0x0");
+	assert_eq!(filters[0]["kind"], "block");
+	assert_eq!(filters[0]["origin"]["rpc"], "dapp");
+	assert_eq!(filters[0]["idle"], "0x0");
+	assert_eq!(filters[0]["nextBlock"], "0x5");
+}
+
+#[test]
+fn rpc_parity_pause_and_resume_sync() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let resume = r#"{"jsonrpc": "2.0", "method": "parity_resumeSync", "params":[], "id": 1}"#;
+	let not_paused = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(resume), Some(not_paused.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pauseSync", "params":[60], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(client.import_paused.load(Ordering::Relaxed));
+
+	let resumed = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(resume), Some(resumed.to_owned()));
+	assert!(!client.import_paused.load(Ordering::Relaxed));
+}
+
+#[test]
+fn rpc_parity_pause_sync_timeout_limit() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pauseSync", "params":[0], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: timeout","data":"\"expected 1 to 86400 seconds\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(!client.import_paused.load(Ordering::Relaxed));
+}
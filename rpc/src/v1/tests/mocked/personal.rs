@@ -345,6 +345,20 @@ fn should_unlock_account_permanently() {
 	assert!(tester.accounts.sign(address, None, message).is_ok(), "Should unlock account.");
 }
 
+#[test]
+fn should_list_unlock_sessions() {
+	let tester = setup();
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_listUnlockSessions", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.into()));
+
+	let address = tester.accounts.new_account(&"password123".into()).unwrap();
+	tester.accounts.unlock_account_permanently(address, "password123".into()).unwrap();
+
+	let response = r#"{"jsonrpc":"2.0","result":[{"address":""#.to_owned() + &format!("0x{:x}", address) + r#"","oneTime":false,"unlockedFor":0,"idleFor":0,"expiresIn":null}],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+}
+
 #[test]
 fn sign_eip191_with_validator() {
 	let tester = setup();
@@ -0,0 +1,124 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethereum_types::{H256, H32};
+use gossip::{Error, GossipInfo, GossipProvider, Message};
+use jsonrpc_core::IoHandler;
+use parking_lot::Mutex;
+
+use v1::{Shh, ShhClient};
+
+#[derive(Default)]
+struct TestGossip {
+	filters: Mutex<HashMap<H256, Vec<H32>>>,
+	messages: Mutex<Vec<Message>>,
+}
+
+impl GossipProvider for TestGossip {
+	fn info(&self) -> GossipInfo {
+		GossipInfo {
+			peer_id: "QmLocal".into(),
+			listen_addresses: vec!["/ip4/127.0.0.1/tcp/30305".into()],
+			peers: 2,
+			filters: self.filters.lock().len(),
+			topics: 1,
+		}
+	}
+
+	fn post(&self, topics: Vec<H32>, payload: Vec<u8>) -> Result<(), Error> {
+		if topics.is_empty() {
+			return Err(Error::NoTopics);
+		}
+		self.messages.lock().push(Message { from: "QmLocal".into(), topics, payload, received: 1_600_000_000 });
+		Ok(())
+	}
+
+	fn new_filter(&self, topics: Vec<H32>) -> Result<H256, Error> {
+		let id = H256::from_low_u64_be(self.filters.lock().len() as u64 + 1);
+		self.filters.lock().insert(id, topics);
+		Ok(id)
+	}
+
+	fn filter_messages(&self, id: &H256) -> Option<Vec<Message>> {
+		let topics = self.filters.lock().get(id).cloned()?;
+		Some(self.messages.lock().drain(..).filter(|m| m.topics.iter().any(|t| topics.contains(t))).collect())
+	}
+
+	fn remove_filter(&self, id: &H256) -> bool {
+		self.filters.lock().remove(id).is_some()
+	}
+}
+
+fn io(gossip: Option<Arc<dyn GossipProvider>>) -> IoHandler {
+	let mut io = IoHandler::new();
+	io.extend_with(ShhClient::new(gossip).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_shh_info() {
+	let io = io(Some(Arc::new(TestGossip::default())));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_info", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"filters":0,"listenAddresses":["/ip4/127.0.0.1/tcp/30305"],"peerId":"QmLocal","peers":2,"topics":1},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_post_and_poll() {
+	let io = io(Some(Arc::new(TestGossip::default())));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_newMessageFilter", "params": [{"topics": ["0x01020304"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000001","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"topics": ["0x01020304"], "payload": "0x1234", "ttl": 50}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_getFilterMessages", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"from":"QmLocal","payload":"0x1234","timestamp":1600000000,"topics":["0x01020304"]}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_deleteMessageFilter", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_getFilterMessages", "params": ["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Filter not found"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_post_without_topics() {
+	let io = io(Some(Arc::new(TestGossip::default())));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_post", "params": [{"topics": [], "payload": "0x12"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"At least one topic is required"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_shh_without_gossip() {
+	let io = io(None);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "shh_info", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Gossip messaging is not running. Run Parity with --gossip to enable it."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -27,7 +27,7 @@ use trace::trace::CallType;
 
 use jsonrpc_core::IoHandler;
 use v1::tests::helpers::{TestMinerService};
-use v1::{Metadata, Traces, TracesClient};
+use v1::{HeavyRequests, Metadata, Traces, TracesClient};
 
 struct Tester {
 	client: Arc<TestBlockChainClient>,
@@ -68,7 +68,7 @@ fn io() -> Tester {
 		state_diff: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
-	let traces = TracesClient::new(&client);
+	let traces = TracesClient::new(&client, HeavyRequests::new_sync());
 	let mut io = IoHandler::default();
 	io.extend_with(traces.to_delegate());
 
@@ -54,4 +54,6 @@ macro_rules! register_test {
 #[cfg(test)]
 mod mocked;
 #[cfg(test)]
+mod engine_api;
+#[cfg(test)]
 mod eth;
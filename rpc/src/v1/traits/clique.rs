@@ -0,0 +1,48 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique rpc interface.
+
+use ethereum_types::{H160, H256};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use v1::types::{BlockNumber, CliqueStatus};
+
+/// Clique rpc interface, managing the signer votes of Clique chains.
+#[rpc(server)]
+pub trait Clique {
+	/// Returns the signers authorized after the given block, the latest one by default.
+	#[rpc(name = "clique_getSigners")]
+	fn signers(&self, _: Option<BlockNumber>) -> Result<Vec<H160>>;
+
+	/// Returns the signers authorized after the block with the given hash.
+	#[rpc(name = "clique_getSignersAtHash")]
+	fn signers_at_hash(&self, _: H256) -> Result<Vec<H160>>;
+
+	/// Starts voting on authorizing (`true`) or removing (`false`) the given signer
+	/// in the blocks sealed by this node.
+	#[rpc(name = "clique_propose")]
+	fn propose(&self, _: H160, _: bool) -> Result<bool>;
+
+	/// Stops voting on the given signer. Returns false if there was no such proposal.
+	#[rpc(name = "clique_discard")]
+	fn discard(&self, _: H160) -> Result<bool>;
+
+	/// Returns the signer activity over the most recent blocks.
+	#[rpc(name = "clique_status")]
+	fn status(&self) -> Result<CliqueStatus>;
+}
@@ -16,10 +16,11 @@
 
 //! Debug RPC interface.
 
-use jsonrpc_core::Result;
+use ethereum_types::H256;
+use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
-use v1::types::RichBlock;
+use v1::types::{BlockNumber, CallRequest, ExecutionWitness, GasProfile, RichBlock, StructLogs, StructLogRange};
 
 /// Debug RPC interface.
 #[rpc(server)]
@@ -27,4 +28,21 @@ pub trait Debug {
 	/// Returns recently seen bad blocks.
 	#[rpc(name = "debug_getBadBlocks")]
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+	/// Returns a chunk of the VM steps executed by a transaction. The chunk following
+	/// a returned one is requested with `after` set to the number of steps seen so far.
+	#[rpc(name = "debug_traceTransactionSteps")]
+	fn trace_transaction_steps(&self, _: H256, _: Option<StructLogRange>) -> BoxFuture<StructLogs>;
+
+	/// Returns the gas spent by a transaction, aggregated by call site and opcode class.
+	#[rpc(name = "debug_traceTransactionGasProfile")]
+	fn trace_transaction_gas_profile(&self, _: H256) -> BoxFuture<Option<GasProfile>>;
+
+	/// Executes a call and returns the gas it spent, aggregated by call site and opcode class.
+	#[rpc(name = "debug_traceCallGasProfile")]
+	fn trace_call_gas_profile(&self, _: CallRequest, _: Option<BlockNumber>) -> BoxFuture<Option<GasProfile>>;
+
+	/// Re-executes a block and returns the trie nodes and code it reads from its parent's state.
+	#[rpc(name = "debug_executionWitness")]
+	fn execution_witness(&self, _: BlockNumber) -> Result<Option<ExecutionWitness>>;
 }
@@ -0,0 +1,39 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug PUB-SUB rpc interface.
+
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed, SubscriptionId};
+
+use v1::types::StructLogs;
+
+/// Debug PUB-SUB rpc interface.
+#[rpc(server)]
+pub trait DebugPubSub {
+	/// RPC Metadata
+	type Metadata;
+
+	/// Stream the VM steps executed by a transaction in chunks. The last chunk is marked as complete.
+	#[pubsub(subscription = "debug_structLogs", subscribe, name = "debug_subscribeStructLogs")]
+	fn subscribe_struct_logs(&self, _: Self::Metadata, _: typed::Subscriber<StructLogs>, _: H256);
+
+	/// Stop streaming the steps of a transaction.
+	#[pubsub(subscription = "debug_structLogs", unsubscribe, name = "debug_unsubscribeStructLogs")]
+	fn unsubscribe_struct_logs(&self, _: Option<Self::Metadata>, _: SubscriptionId) -> Result<bool>;
+}
@@ -0,0 +1,41 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Engine API rpc interface.
+
+use ethereum_types::H64;
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+
+use v1::types::{ExecutionPayload, PayloadStatus, ForkchoiceState, PayloadAttributes, ForkchoiceUpdatedResponse};
+
+/// Engine API rpc interface, used by a proof-of-stake consensus client to drive the chain
+/// once it reached the terminal total difficulty.
+#[rpc(server)]
+pub trait EngineApi {
+	/// Imports a block built by the consensus client without making it the best block.
+	#[rpc(name = "engine_newPayloadV1")]
+	fn new_payload(&self, _: ExecutionPayload) -> BoxFuture<PayloadStatus>;
+
+	/// Makes the given block the best block and marks the finalized block. Starts building a
+	/// payload on top of it if attributes are given.
+	#[rpc(name = "engine_forkchoiceUpdatedV1")]
+	fn forkchoice_updated(&self, _: ForkchoiceState, _: Option<PayloadAttributes>) -> BoxFuture<ForkchoiceUpdatedResponse>;
+
+	/// Returns a payload built after a fork choice update.
+	#[rpc(name = "engine_getPayloadV1")]
+	fn get_payload(&self, _: H64) -> Result<ExecutionPayload>;
+}
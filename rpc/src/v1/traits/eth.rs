@@ -78,6 +78,15 @@ pub trait Eth {
 	#[rpc(name = "eth_getStorageAt")]
 	fn storage_at(&self, _: H160, _: U256, _: Option<BlockNumber>) -> BoxFuture<H256>;
 
+	/// Returns balances of the given accounts, all read from the same state.
+	#[rpc(name = "eth_getBalances")]
+	fn balances(&self, _: Vec<H160>, _: Option<BlockNumber>) -> BoxFuture<Vec<U256>>;
+
+	/// Returns contents of the storage at the given `[address, position]` pairs, all read from
+	/// the same state.
+	#[rpc(name = "eth_getStorageAtBatch")]
+	fn storage_at_batch(&self, _: Vec<(H160, U256)>, _: Option<BlockNumber>) -> BoxFuture<Vec<H256>>;
+
 	/// Returns block with given hash.
 	#[rpc(name = "eth_getBlockByHash")]
 	fn block_by_hash(&self, _: H256, _: bool) -> BoxFuture<Option<RichBlock>>;
@@ -191,17 +200,20 @@ pub trait Eth {
 // TODO: do filters api properly
 #[rpc(server)]
 pub trait EthFilter {
+	/// RPC Metadata
+	type Metadata;
+
 	/// Returns id of new filter.
-	#[rpc(name = "eth_newFilter")]
-	fn new_filter(&self, _: Filter) -> Result<U256>;
+	#[rpc(meta, name = "eth_newFilter")]
+	fn new_filter(&self, _: Self::Metadata, _: Filter) -> Result<U256>;
 
 	/// Returns id of new block filter.
-	#[rpc(name = "eth_newBlockFilter")]
-	fn new_block_filter(&self) -> Result<U256>;
+	#[rpc(meta, name = "eth_newBlockFilter")]
+	fn new_block_filter(&self, _: Self::Metadata) -> Result<U256>;
 
 	/// Returns id of new block filter.
-	#[rpc(name = "eth_newPendingTransactionFilter")]
-	fn new_pending_transaction_filter(&self) -> Result<U256>;
+	#[rpc(meta, name = "eth_newPendingTransactionFilter")]
+	fn new_pending_transaction_filter(&self, _: Self::Metadata) -> Result<U256>;
 
 	/// Returns filter changes since last poll.
 	#[rpc(name = "eth_getFilterChanges")]
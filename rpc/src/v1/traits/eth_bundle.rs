@@ -0,0 +1,35 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Eth bundles rpc interface.
+
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use v1::types::{CallBundleRequest, CallBundleResponse, SendBundleRequest, SendBundleResponse};
+
+/// Eth bundles rpc interface, accepting private order flow for block authoring.
+#[rpc(server)]
+pub trait EthBundle {
+	/// Submits a bundle of signed transactions, placed atomically at the top of the block it targets
+	/// if it does not revert and pays at least the minimal gas price.
+	#[rpc(name = "eth_sendBundle")]
+	fn send_bundle(&self, _: SendBundleRequest) -> Result<SendBundleResponse>;
+
+	/// Executes a bundle on top of the latest block and returns the outcome of each transaction.
+	#[rpc(name = "eth_callBundle")]
+	fn call_bundle(&self, _: CallBundleRequest) -> Result<CallBundleResponse>;
+}
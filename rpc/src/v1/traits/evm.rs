@@ -0,0 +1,47 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Development chain rpc interface.
+
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+/// Development chain rpc interface, controlling block production of the instant seal engine.
+#[rpc(server)]
+pub trait Evm {
+	/// Seals a block, even if it has no transactions.
+	#[rpc(name = "evm_mine")]
+	fn mine(&self) -> Result<bool>;
+
+	/// Enables or disables sealing a block on every transaction.
+	#[rpc(name = "evm_setAutomine")]
+	fn set_automine(&self, _: bool) -> Result<bool>;
+
+	/// Moves the timestamp of the next blocks forward by the given number of seconds.
+	/// Returns the total time offset in seconds.
+	#[rpc(name = "evm_increaseTime")]
+	fn increase_time(&self, _: u64) -> Result<u64>;
+
+	/// Records the current best block and returns an id to revert to it.
+	#[rpc(name = "evm_snapshot")]
+	fn snapshot(&self) -> Result<U256>;
+
+	/// Reverts the chain to the given snapshot. The snapshot and all later snapshots are discarded.
+	/// Returns false if the snapshot is unknown.
+	#[rpc(name = "evm_revert")]
+	fn revert(&self, _: U256) -> Result<bool>;
+}
@@ -14,9 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Database-related operations.
+//! Miner settings rpc interface.
 
-#[path="rocksdb/mod.rs"]
-mod impls;
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
 
-pub use self::impls::{open_db_light, restoration_db_handler, migrate};
+/// Miner settings rpc interface, adjusting block production at runtime.
+#[rpc(server)]
+pub trait Miner {
+	/// Sets the block gas limit the miner votes towards. Each authored block moves the
+	/// gas limit at most `parent_gas_limit / 1024` closer to the target.
+	#[rpc(name = "miner_setGasLimit")]
+	fn set_gas_limit(&self, _: U256) -> Result<bool>;
+}
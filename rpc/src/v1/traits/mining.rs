@@ -0,0 +1,36 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Mining statistics rpc interface.
+
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use v1::types::StratumWorker;
+
+/// Mining statistics rpc interface, reporting the workers of the built-in stratum server.
+#[rpc(server)]
+pub trait Mining {
+	/// Returns statistics of every authorized stratum worker, including its estimated hashrate.
+	#[rpc(name = "mining_workers")]
+	fn workers(&self) -> Result<Vec<StratumWorker>>;
+
+	/// Returns the estimated hashrate of all stratum workers combined.
+	#[rpc(name = "mining_hashrate")]
+	fn hashrate(&self) -> Result<U256>;
+}
@@ -16,10 +16,17 @@
 
 //! Ethereum rpc interfaces.
 
+pub mod clique;
 pub mod debug;
+pub mod debug_pubsub;
+pub mod engine_api;
 pub mod eth;
+pub mod eth_bundle;
 pub mod eth_pubsub;
 pub mod eth_signing;
+pub mod evm;
+pub mod miner;
+pub mod mining;
 pub mod net;
 pub mod parity;
 pub mod parity_accounts;
@@ -30,15 +37,23 @@ pub mod private;
 pub mod pubsub;
 pub mod rpc;
 pub mod secretstore;
+pub mod shh;
 pub mod signer;
 pub mod traces;
 pub mod transactions_pool;
 pub mod web3;
 
+pub use self::clique::Clique;
 pub use self::debug::Debug;
+pub use self::debug_pubsub::DebugPubSub;
+pub use self::engine_api::EngineApi;
 pub use self::eth::{Eth, EthFilter};
+pub use self::eth_bundle::EthBundle;
 pub use self::eth_pubsub::EthPubSub;
 pub use self::eth_signing::EthSigning;
+pub use self::evm::Evm;
+pub use self::miner::Miner;
+pub use self::mining::Mining;
 pub use self::net::Net;
 pub use self::parity::Parity;
 pub use self::parity_accounts::{ParityAccounts, ParityAccountsInfo};
@@ -49,6 +64,7 @@ pub use self::private::Private;
 pub use self::pubsub::PubSub;
 pub use self::rpc::Rpc;
 pub use self::secretstore::SecretStore;
+pub use self::shh::Shh;
 pub use self::signer::Signer;
 pub use self::traces::Traces;
 pub use self::transactions_pool::TransactionsPool;
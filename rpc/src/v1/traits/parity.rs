@@ -24,11 +24,12 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 use v1::types::{
 	Bytes, CallRequest,
-	Peers, Transaction, RpcSettings, Histogram, RecoveredAccount,
-	TransactionStats, LocalTransactionStatus,
+	Peers, ForkId, NatStatus, Transaction, RpcSettings, Histogram, RecoveredAccount,
+	TransactionStats, LocalTransaction, TransactionAge, TxPoolInspection,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt,
+	OperationsInfo, ChainStatus, ChainGap, Log, Filter,
+	RichHeader, Receipt, PendingBlockStats, UncleCandidate, StateDiffPage, ConfirmationStatus,
+	ValidatorStatus, EpochValidators, ThreatStatus, GasStats, AddressOrName, TokenBalance, ReceiptProof, AncestryProof,
 };
 
 /// OpenEthereum-specific rpc interface.
@@ -110,21 +111,37 @@ pub trait Parity {
 	#[rpc(name = "parity_registryAddress")]
 	fn registry_address(&self) -> Result<Option<H160>>;
 
+	/// Returns the address an ENS name resolves to at the latest block, or null if the name has
+	/// no resolver or address.
+	#[rpc(name = "parity_resolveEns")]
+	fn resolve_ens(&self, _: String) -> Result<Option<H160>>;
+
+	/// Returns the primary ENS name of an address at the latest block: the name of its reverse
+	/// record, if that name resolves back to the address. Null otherwise.
+	#[rpc(name = "parity_reverseEns")]
+	fn reverse_ens(&self, _: H160) -> Result<Option<String>>;
+
 	/// Returns all addresses if Fat DB is enabled (`--fat-db`), or null if not.
 	#[rpc(name = "parity_listAccounts")]
 	fn list_accounts(&self, _: u64, _: Option<H160>, _: Option<BlockNumber>) -> Result<Option<Vec<H160>>>;
 
-	/// Returns all storage keys of the given address (first parameter) if Fat DB is enabled (`--fat-db`),
-	/// or null if not.
+	/// Returns all storage keys of the given address or ENS name (first parameter) if Fat DB
+	/// is enabled (`--fat-db`), or null if not.
 	#[rpc(name = "parity_listStorageKeys")]
 	fn list_storage_keys(
 		&self,
-		_: H160,
+		_: AddressOrName,
 		_: Option<u64>,
 		_: Option<H256>,
 		_: Option<BlockNumber>,
 	) -> Result<Option<Vec<H256>>>;
 
+	/// Returns the changes to account balances, nonces, code and storage between the states of
+	/// two blocks. At most `count` accounts following `after` (third and fourth parameters) are
+	/// looked at, and the returned `next` address continues the listing.
+	#[rpc(name = "parity_stateDiff")]
+	fn state_diff(&self, _: BlockNumber, _: BlockNumber, _: u64, _: Option<H160>) -> BoxFuture<StateDiffPage>;
+
 	/// Encrypt some data with a public key under ECIES.
 	/// First parameter is the 512-byte destination public key, second is the message.
 	#[rpc(name = "parity_encryptMessage")]
@@ -152,17 +169,37 @@ pub trait Parity {
 	#[rpc(name = "parity_pendingTransactionsStats")]
 	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>>;
 
-	/// Returns a list of current and past local transactions with status details.
+	/// Returns how long a transaction has been in the queue and when it is scheduled to be evicted.
+	#[rpc(name = "parity_transactionAge")]
+	fn transaction_age(&self, _: H256) -> Result<Option<TransactionAge>>;
+
+	/// Returns score components and status of every transaction in the queue,
+	/// and the reasons recently dropped transactions were removed.
+	#[rpc(name = "parity_txpoolInspect")]
+	fn txpool_inspect(&self) -> Result<TxPoolInspection>;
+
+	/// Returns timing statistics of the pending block construction.
+	#[rpc(name = "parity_pendingBlockStats")]
+	fn pending_block_stats(&self) -> Result<PendingBlockStats>;
+
+	/// Returns the possible uncles of the next authored block, whether the uncle
+	/// selection policy includes them and the reward including each one yields.
+	#[rpc(name = "parity_uncleCandidates")]
+	fn uncle_candidates(&self) -> Result<Vec<UncleCandidate>>;
+
+	/// Returns a list of current and past local transactions with status details
+	/// and, when known, the time of their submission and the number of resubmissions.
 	#[rpc(name = "parity_localTransactions")]
-	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>>;
+	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransaction>>;
 
 	/// Returns current WS Server interface and port or an error if ws server is disabled.
 	#[rpc(name = "parity_wsUrl")]
 	fn ws_url(&self) -> Result<String>;
 
-	/// Returns next nonce for particular sender. Should include all transactions in the queue.
+	/// Returns next nonce for particular sender, given as an address or ENS name. Should
+	/// include all transactions in the queue.
 	#[rpc(name = "parity_nextNonce")]
-	fn next_nonce(&self, _: H160) -> BoxFuture<U256>;
+	fn next_nonce(&self, _: AddressOrName) -> BoxFuture<U256>;
 
 	/// Get the mode. Returns one of: "active", "passive", "dark", "offline".
 	#[rpc(name = "parity_mode")]
@@ -176,6 +213,14 @@ pub trait Parity {
 	#[rpc(name = "parity_enode")]
 	fn enode(&self) -> Result<String>;
 
+	/// Returns the fork identifier (EIP-2124) announced to peers.
+	#[rpc(name = "parity_forkId")]
+	fn fork_id(&self) -> Result<ForkId>;
+
+	/// Returns the state of the NAT port mapping.
+	#[rpc(name = "parity_natStatus")]
+	fn nat_status(&self) -> Result<NatStatus>;
+
 	/// Returns information on current consensus capability.
 	#[rpc(name = "parity_consensusCapability")]
 	fn consensus_capability(&self) -> Result<ConsensusCapability>;
@@ -192,6 +237,11 @@ pub trait Parity {
 	#[rpc(name = "parity_chainStatus")]
 	fn chain_status(&self) -> Result<ChainStatus>;
 
+	/// Get the block ranges the node has no headers, bodies or state for, e.g. because of pruning
+	/// or a snapshot restore. Historical queries in these ranges should go to an archive node.
+	#[rpc(name = "parity_chainGaps")]
+	fn chain_gaps(&self) -> Result<Vec<ChainGap>>;
+
 	/// Get node kind info.
 	#[rpc(name = "parity_nodeKind")]
 	fn node_kind(&self) -> Result<::v1::types::NodeKind>;
@@ -211,6 +261,13 @@ pub trait Parity {
 	#[rpc(name = "parity_call")]
 	fn call(&self, _: Vec<CallRequest>, _: Option<BlockNumber>) -> Result<Vec<Bytes>>;
 
+	/// Returns the balances of an address or ENS name (first parameter) in the given ERC-20
+	/// tokens, also given as addresses or ENS names, with their decimals and symbols. Every call
+	/// to `balanceOf` is made against a fresh copy of the same state. Balances the contract failed
+	/// to report are null.
+	#[rpc(name = "parity_tokenBalances")]
+	fn token_balances(&self, _: AddressOrName, _: Vec<AddressOrName>, _: Option<BlockNumber>) -> Result<Vec<TokenBalance>>;
+
 	/// Used for submitting a proof-of-work solution (similar to `eth_submitWork`,
 	/// but returns block hash on success, and returns an explicit error message on failure).
 	#[rpc(name = "parity_submitWorkDetail")]
@@ -243,4 +300,49 @@ pub trait Parity {
 	/// Submit raw block to be published to the network
 	#[rpc(name = "parity_submitRawBlock")]
 	fn submit_raw_block(&self, _: Bytes) -> Result<H256>;
+
+	/// Returns the root of the canonical hash trie with given number,
+	/// or `null` if it hasn't been generated yet.
+	#[rpc(name = "parity_chtRoot")]
+	fn cht_root(&self, _: U64) -> Result<Option<H256>>;
+
+	/// Returns how safely a transaction is confirmed: its depth, the heaviest known branch which
+	/// would revert it and whether it is finalized, or `null` if it isn't in the canonical chain.
+	#[rpc(name = "parity_confirmationStatus")]
+	fn confirmation_status(&self, _: H256) -> Result<Option<ConfirmationStatus>>;
+
+	/// Returns the steps the local validator was expected to seal since the node started and
+	/// whether it sealed them, missed them or sealed them late.
+	#[rpc(name = "parity_validatorStatus")]
+	fn validator_status(&self) -> Result<ValidatorStatus>;
+
+	/// Returns the validator set governing the given block together with the proof of the epoch
+	/// transition which introduced it, or `null` if the block is unknown or the chain engine has no
+	/// validator set. The proof lets light clients and bridges check authority signatures without
+	/// trusting this node.
+	#[rpc(name = "parity_validatorSetAt")]
+	fn validator_set_at(&self, _: BlockNumber) -> Result<Option<EpochValidators>>;
+
+	/// Returns signs of a majority attack in the recently imported blocks: the growth of
+	/// competing branches, sudden difficulty swings and deep or repeated reorganizations.
+	#[rpc(name = "parity_threatStatus")]
+	fn threat_status(&self) -> Result<ThreatStatus>;
+
+	/// Returns the gas used by every block of the range, the average and percentile gas prices
+	/// of its transactions and the contracts which consumed the most gas, at most `top` of them
+	/// (10 by default).
+	#[rpc(name = "parity_gasStats")]
+	fn gas_stats(&self, _: BlockNumber, _: BlockNumber, _: Option<usize>) -> BoxFuture<GasStats>;
+
+	/// Returns Merkle-Patricia proofs of a canonical transaction and its receipt against the
+	/// transactions and receipts roots of the including block, or `null` if the transaction
+	/// is unknown.
+	#[rpc(name = "parity_getReceiptProof")]
+	fn receipt_proof(&self, _: H256) -> Result<Option<ReceiptProof>>;
+
+	/// Returns the proof that a canonical block is an ancestor of the canonical head (latest by
+	/// default), against an accumulator of the block hashes up to the head, or `null` if either
+	/// block is not canonical or the node is still building the accumulator.
+	#[rpc(name = "parity_getAncestryProof")]
+	fn ancestry_proof(&self, _: BlockNumber, _: Option<BlockNumber>) -> Result<Option<AncestryProof>>;
 }
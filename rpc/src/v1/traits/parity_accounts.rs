@@ -22,7 +22,7 @@ use jsonrpc_derive::rpc;
 use ethereum_types::{H160, H256, H520};
 use ethkey::Password;
 use ethstore::KeyFile;
-use v1::types::{DeriveHash, DeriveHierarchical, ExtAccountInfo};
+use v1::types::{DeriveHash, DeriveHierarchical, ExtAccountInfo, KeyDerivation, ReencryptionReport};
 use v1::types::AccountInfo;
 
 /// Parity-specific read-only accounts rpc interface.
@@ -131,6 +131,21 @@ pub trait ParityAccounts {
 	#[rpc(name = "parity_setVaultMeta")]
 	fn set_vault_meta(&self, _: String, _: String) -> Result<bool>;
 
+	/// Export all accounts of the given vault (or the root keystore if the name is empty)
+	/// to an archive encrypted with given password.
+	#[rpc(name = "parity_exportVault")]
+	fn export_vault(&self, _: String, _: Password) -> Result<String>;
+
+	/// Import accounts from an archive created by `parity_exportVault` into the given vault
+	/// (or the root keystore if the name is empty). Returns addresses of imported accounts.
+	#[rpc(name = "parity_importVault")]
+	fn import_vault(&self, _: String, _: String, _: Password) -> Result<Vec<H160>>;
+
+	/// Re-encrypt all accounts of the given vault (or the root keystore if the name is empty)
+	/// with new key derivation parameters, unlocking each account with the first matching password.
+	#[rpc(name = "parity_reencryptVault")]
+	fn reencrypt_vault(&self, _: String, _: Vec<Password>, _: KeyDerivation) -> Result<ReencryptionReport>;
+
 	/// Derive new address from given account address using specific hash.
 	/// Resulting address can be either saved as a new account (with the same password).
 	#[rpc(name = "parity_deriveAddressHash")]
@@ -16,11 +16,11 @@
 
 //! Parity-specific rpc interface for operations altering the settings.
 
-use ethereum_types::{H160, H256, U256};
+use ethereum_types::{H160, H256, H520, U256};
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
-use v1::types::{Bytes, ReleaseInfo, Transaction};
+use v1::types::{AddressOrName, Bytes, InstalledFilter, ReleaseInfo, Transaction};
 
 /// Parity-specific rpc interface for operations altering the account-related settings.
 #[rpc(server)]
@@ -49,9 +49,9 @@ pub trait ParitySet {
 	#[rpc(name = "parity_setExtraData")]
 	fn set_extra_data(&self, _: Bytes) -> Result<bool>;
 
-	/// Sets new author for mined block.
+	/// Sets new author for mined block, given as an address or ENS name.
 	#[rpc(name = "parity_setAuthor")]
-	fn set_author(&self, _: H160) -> Result<bool>;
+	fn set_author(&self, _: AddressOrName) -> Result<bool>;
 
 	/// Sets the secret of engine signer account.
 	#[rpc(name = "parity_setEngineSignerSecret")]
@@ -105,6 +105,20 @@ pub trait ParitySet {
 	#[rpc(name = "parity_setChain")]
 	fn set_spec_name(&self, _: String) -> Result<bool>;
 
+	/// Set the logging targets, using the same syntax as `--logging` (e.g. "sync=debug,rpc=trace").
+	#[rpc(name = "parity_setLoggingTargets")]
+	fn set_logging_targets(&self, _: String) -> Result<bool>;
+
+	/// Shut the node down gracefully. Returns once the shutdown has been requested.
+	#[rpc(name = "parity_shutdown")]
+	fn shutdown(&self) -> Result<bool>;
+
+	/// Re-read the configuration file and apply the reloadable settings (logging, peer limits,
+	/// reserved peers, transaction queue quotas and gas price). Returns the settings that changed.
+	/// With `dry_run` set the configuration is only validated and nothing is applied.
+	#[rpc(name = "parity_reloadConfig")]
+	fn reload_config(&self, _: Option<bool>) -> Result<Vec<String>>;
+
 	/// Hash a file content under given URL.
 	#[rpc(name = "parity_hashContent")]
 	fn hash_content(&self, _: String) -> BoxFuture<H256>;
@@ -125,4 +139,37 @@ pub trait ParitySet {
 	/// Returns `true` when transaction was removed, `false` if it was not found.
 	#[rpc(name = "parity_removeTransaction")]
 	fn remove_transaction(&self, _: H256) -> Result<Option<Transaction>>;
+
+	/// Applies a consensus engine parameters update signed by the key configured in the chain spec
+	/// and re-verifies the blocks already in the verification queue. Applied updates are kept across
+	/// restarts.
+	#[rpc(name = "parity_reloadEngineParams")]
+	fn reload_engine_params(&self, _: Bytes, _: H520) -> Result<bool>;
+
+	/// Switches to the branch containing the given block which was held back for retracting more
+	/// blocks than allowed by `--max-reorg-depth`, and resumes the block import. Fails once the
+	/// state of the branch is pruned.
+	#[rpc(name = "parity_acceptReorg")]
+	fn accept_reorg(&self, _: H256) -> Result<bool>;
+
+	/// Runs the background rewrites of the database now, even outside of the window set with
+	/// `--db-rewrite-window`, until they catch up.
+	#[rpc(name = "parity_rewriteDatabase")]
+	fn rewrite_database(&self) -> Result<bool>;
+
+	/// Returns the filters installed with `eth_newFilter`, `eth_newBlockFilter` and
+	/// `eth_newPendingTransactionFilter` by all clients, with the origin which installed them.
+	#[rpc(name = "parity_listFilters")]
+	fn list_filters(&self) -> Result<Vec<InstalledFilter>>;
+
+	/// Stops importing blocks, keeping the head stable and the database files consistent for
+	/// backups or maintenance while RPC keeps serving. The import resumes with `parity_resumeSync`
+	/// or after the timeout in seconds (10 minutes by default, a day at most).
+	#[rpc(name = "parity_pauseSync")]
+	fn pause_sync(&self, _: Option<u64>) -> Result<bool>;
+
+	/// Resumes the block import paused with `parity_pauseSync` or by a held back reorganization,
+	/// which is rejected. Returns `false` if it wasn't paused.
+	#[rpc(name = "parity_resumeSync")]
+	fn resume_sync(&self) -> Result<bool>;
 }
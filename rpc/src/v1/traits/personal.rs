@@ -20,7 +20,7 @@ use ethereum_types::{H160, H256, H520, U128};
 use jsonrpc_core::types::Value;
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
-use v1::types::{Bytes, TransactionRequest, RichRawTransaction as RpcRichRawTransaction, EIP191Version};
+use v1::types::{Bytes, TransactionRequest, RichRawTransaction as RpcRichRawTransaction, EIP191Version, UnlockSession};
 
 /// Personal rpc interface. Safe (read-only) functions.
 #[rpc(server)]
@@ -41,6 +41,10 @@ pub trait Personal {
 	#[rpc(name = "personal_unlockAccount")]
 	fn unlock_account(&self, _: H160, _: String, _: Option<U128>) -> Result<bool>;
 
+	/// Lists active unlock sessions.
+	#[rpc(name = "personal_listUnlockSessions")]
+	fn unlock_sessions(&self) -> Result<Vec<UnlockSession>>;
+
 	/// Signs the hash of data with given account signature using the given password to unlock the account during
 	/// the request.
 	#[rpc(name = "personal_sign")]
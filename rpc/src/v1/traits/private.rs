@@ -54,4 +54,9 @@ pub trait Private {
 	/// Retrieve log information about private transaction
 	#[rpc(name = "private_log")]
 	fn private_log(&self, _: H256) -> Result<PrivateTransactionLog, Error>;
+
+	/// Retrieve from peers the private states the contract had between the given blocks,
+	/// returns the hashes of the states missing locally
+	#[rpc(name = "private_syncStateHistory")]
+	fn sync_state_history(&self, _: H160, _: BlockNumber, _: BlockNumber) -> Result<Vec<H256>, Error>;
 }
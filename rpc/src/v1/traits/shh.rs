@@ -0,0 +1,47 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gossip messaging rpc interface.
+
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use v1::types::{ShhFilterRequest, ShhInfo, ShhMessage, ShhPostRequest};
+
+/// Node-local messaging over the gossip swarm, served under Whisper's `shh` method names.
+#[rpc(server)]
+pub trait Shh {
+	/// Returns the status of the gossip service.
+	#[rpc(name = "shh_info")]
+	fn info(&self) -> Result<ShhInfo>;
+
+	/// Publishes a message to the gossip swarm.
+	#[rpc(name = "shh_post")]
+	fn post(&self, _: ShhPostRequest) -> Result<bool>;
+
+	/// Installs a filter for messages published under any of the given topics, returning its id.
+	#[rpc(name = "shh_newMessageFilter")]
+	fn new_message_filter(&self, _: ShhFilterRequest) -> Result<H256>;
+
+	/// Returns the messages received by a filter since it was last polled.
+	#[rpc(name = "shh_getFilterMessages")]
+	fn filter_messages(&self, _: H256) -> Result<Vec<ShhMessage>>;
+
+	/// Removes a filter.
+	#[rpc(name = "shh_deleteMessageFilter")]
+	fn delete_message_filter(&self, _: H256) -> Result<bool>;
+}
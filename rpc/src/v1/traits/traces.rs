@@ -17,7 +17,7 @@
 //! Traces specific rpc interface.
 
 use ethereum_types::H256;
-use jsonrpc_core::Result;
+use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults,
 	TraceResultsWithTransactionHash, TraceOptions};
@@ -30,7 +30,7 @@ pub trait Traces {
 
 	/// Returns traces matching given filter.
 	#[rpc(name = "trace_filter")]
-	fn filter(&self, _: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>>;
+	fn filter(&self, _: TraceFilter) -> BoxFuture<Option<Vec<LocalizedTrace>>>;
 
 	/// Returns transaction trace at given index.
 	#[rpc(name = "trace_get")]
@@ -42,23 +42,23 @@ pub trait Traces {
 
 	/// Returns all traces produced at given block.
 	#[rpc(name = "trace_block")]
-	fn block_traces(&self, _: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>>;
+	fn block_traces(&self, _: BlockNumber) -> BoxFuture<Option<Vec<LocalizedTrace>>>;
 
 	/// Executes the given call and returns a number of possible traces for it.
 	#[rpc(name = "trace_call")]
-	fn call(&self, _: CallRequest, _: TraceOptions, _: Option<BlockNumber>) -> Result<TraceResults>;
+	fn call(&self, _: CallRequest, _: TraceOptions, _: Option<BlockNumber>) -> BoxFuture<TraceResults>;
 
 	/// Executes all given calls and returns a number of possible traces for each of it.
 	#[rpc(name = "trace_callMany")]
-	fn call_many(&self, _: Vec<(CallRequest, TraceOptions)>, _: Option<BlockNumber>) -> Result<Vec<TraceResults>>;
+	fn call_many(&self, _: Vec<(CallRequest, TraceOptions)>, _: Option<BlockNumber>) -> BoxFuture<Vec<TraceResults>>;
 
 	/// Executes the given raw transaction and returns a number of possible traces for it.
 	#[rpc(name = "trace_rawTransaction")]
-	fn raw_transaction(&self, _: Bytes, _: TraceOptions, _: Option<BlockNumber>) -> Result<TraceResults>;
+	fn raw_transaction(&self, _: Bytes, _: TraceOptions, _: Option<BlockNumber>) -> BoxFuture<TraceResults>;
 
 	/// Executes the transaction with the given hash and returns a number of possible traces for it.
 	#[rpc(name = "trace_replayTransaction")]
-	fn replay_transaction(&self, _: H256, _: TraceOptions) -> Result<TraceResults>;
+	fn replay_transaction(&self, _: H256, _: TraceOptions) -> BoxFuture<TraceResults>;
 
 	/// Executes all the transactions at the given block and returns a number of possible traces for each transaction.
 	#[rpc(name = "trace_replayBlockTransactions")]
@@ -66,5 +66,5 @@ pub trait Traces {
 		&self,
 		_: BlockNumber,
 		_: TraceOptions
-	) -> Result<Vec<TraceResultsWithTransactionHash>>;
+	) -> BoxFuture<Vec<TraceResultsWithTransactionHash>>;
 }
@@ -19,7 +19,7 @@
 use jsonrpc_core::Result;
 use jsonrpc_pubsub::{typed, SubscriptionId};
 use jsonrpc_derive::rpc;
-use miner::pool::TxStatus;
+use miner::pool::{PoolEvent, TxStatus};
 
 use ethereum_types::H256;
 
@@ -36,4 +36,12 @@ pub trait TransactionsPool {
 	/// Unsubscribe from existing Transactions Pool subscription.
 	#[pubsub(subscription = "parity_watchTransactionsPool", unsubscribe, name = "parity_unwatchTransactionsPool")]
 	fn unsubscribe(&self, _: Option<Self::Metadata>, _: SubscriptionId) -> Result<bool>;
+
+	/// Subscribe to Transactions Pool lifecycle events.
+	#[pubsub(subscription = "parity_watchTransactionsPoolEvents", subscribe, name = "parity_watchTransactionsPoolEvents")]
+	fn subscribe_events(&self, _: Self::Metadata, _: typed::Subscriber<PoolEvent>);
+
+	/// Unsubscribe from existing Transactions Pool lifecycle events subscription.
+	#[pubsub(subscription = "parity_watchTransactionsPoolEvents", unsubscribe, name = "parity_unwatchTransactionsPoolEvents")]
+	fn unsubscribe_events(&self, _: Option<Self::Metadata>, _: SubscriptionId) -> Result<bool>;
 }
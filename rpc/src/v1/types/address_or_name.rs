@@ -0,0 +1,87 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer};
+use serde::de::{Error, Visitor};
+use ethereum_types::H160;
+
+/// An address parameter given either as a hex address or as an ENS name.
+#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+pub enum AddressOrName {
+	/// Plain address.
+	Address(H160),
+	/// Name to resolve, lowercased.
+	Name(String),
+}
+
+impl From<H160> for AddressOrName {
+	fn from(address: H160) -> Self {
+		AddressOrName::Address(address)
+	}
+}
+
+impl<'a> Deserialize<'a> for AddressOrName {
+	fn deserialize<D>(deserializer: D) -> Result<AddressOrName, D::Error> where D: Deserializer<'a> {
+		deserializer.deserialize_str(AddressOrNameVisitor)
+	}
+}
+
+struct AddressOrNameVisitor;
+
+impl<'a> Visitor<'a> for AddressOrNameVisitor {
+	type Value = AddressOrName;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a 0x-prefixed hex address or an ENS name")
+	}
+
+	fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: Error {
+		if value.starts_with("0x") {
+			return H160::from_str(&value[2..])
+				.map(AddressOrName::Address)
+				.map_err(|e| Error::custom(format!("Invalid address: {}", e)));
+		}
+		if value.is_empty() || value.contains(char::is_whitespace) {
+			return Err(Error::custom("Invalid name"));
+		}
+		Ok(AddressOrName::Name(value.to_lowercase()))
+	}
+
+	fn visit_string<E>(self, value: String) -> Result<Self::Value, E> where E: Error {
+		self.visit_str(value.as_ref())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json;
+
+	#[test]
+	fn address_or_name_deserialization() {
+		let s = r#"["0x0000000000000000000000000000000000000001", "Gavofyork.ETH", "", "0x12"]"#;
+		let deserialized: Vec<serde_json::Value> = serde_json::from_str(s).unwrap();
+		let parsed: Vec<Result<AddressOrName, _>> = deserialized.into_iter().map(serde_json::from_value).collect();
+
+		assert_eq!(parsed[0].as_ref().unwrap(), &AddressOrName::Address(H160::from_low_u64_be(1)));
+		assert_eq!(parsed[1].as_ref().unwrap(), &AddressOrName::Name("gavofyork.eth".into()));
+		assert!(parsed[2].is_err());
+		assert!(parsed[3].is_err());
+	}
+}
@@ -0,0 +1,56 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Proof that a block is an ancestor of a head.
+
+use ethereum_types::{H256, U64};
+use types::ancestry_proof;
+
+/// Inclusion proof of a block hash in the header accumulator of a head, a Merkle mountain
+/// range over the canonical block hashes from genesis up to the head.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AncestryProof {
+	/// Number of the proven block.
+	pub block_number: U64,
+	/// Hash of the proven block.
+	pub block_hash: H256,
+	/// Number of the head.
+	pub head_number: U64,
+	/// Hash of the head.
+	pub head_hash: H256,
+	/// Roots of the sibling subtrees on the path from the block to its peak, from the bottom.
+	pub siblings: Vec<H256>,
+	/// Peaks of the accumulator, from the leftmost one.
+	pub peaks: Vec<H256>,
+	/// Root of the accumulator: keccak of the number of blocks (8 bytes, big-endian) and the head hash,
+	/// followed by the peaks.
+	pub root: H256,
+}
+
+impl From<ancestry_proof::AncestryProof> for AncestryProof {
+	fn from(p: ancestry_proof::AncestryProof) -> Self {
+		AncestryProof {
+			block_number: p.block_number.into(),
+			block_hash: p.block_hash,
+			head_number: p.head_number.into(),
+			head_hash: p.head_hash,
+			siblings: p.siblings,
+			peaks: p.peaks,
+			root: p.root,
+		}
+	}
+}
@@ -0,0 +1,196 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction bundles.
+
+use ethcore::miner::{BundleSimulation, BundleTransactionResult};
+use ethereum_types::{H160, H256, U64, U256};
+
+use v1::types::{BlockNumber, Bytes};
+
+/// Bundle submitted with `eth_sendBundle`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct SendBundleRequest {
+	/// Signed raw transactions, in execution order.
+	pub txs: Vec<Bytes>,
+	/// Number of the block the bundle is valid for.
+	pub block_number: U64,
+	/// Earliest block timestamp the bundle is valid for.
+	pub min_timestamp: Option<u64>,
+	/// Latest block timestamp the bundle is valid for.
+	pub max_timestamp: Option<u64>,
+	/// Hashes of transactions that are allowed to revert.
+	#[serde(default)]
+	pub reverting_tx_hashes: Vec<H256>,
+}
+
+/// Bundle simulated with `eth_callBundle`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct CallBundleRequest {
+	/// Signed raw transactions, in execution order.
+	pub txs: Vec<Bytes>,
+	/// Number of the block the bundle would be included in.
+	pub block_number: U64,
+	/// Block whose state the bundle is executed on. Only the latest block is supported.
+	pub state_block_number: BlockNumber,
+	/// Timestamp of the simulated block.
+	pub timestamp: Option<u64>,
+}
+
+/// Result of `eth_sendBundle`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendBundleResponse {
+	/// Hash of the bundle.
+	pub bundle_hash: H256,
+}
+
+/// Result of `eth_callBundle`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResponse {
+	/// Hash of the bundle.
+	pub bundle_hash: H256,
+	/// Payment to the block author per unit of gas.
+	pub bundle_gas_price: U256,
+	/// Change of the block author's balance.
+	pub coinbase_diff: U256,
+	/// Part of `coinbaseDiff` paid directly rather than through gas fees.
+	pub eth_sent_to_coinbase: U256,
+	/// Fees paid for the gas used.
+	pub gas_fees: U256,
+	/// Number of the block the bundle was executed on.
+	pub state_block_number: U64,
+	/// Total gas used by the bundle.
+	pub total_gas_used: U256,
+	/// Per-transaction results.
+	pub results: Vec<CallBundleResult>,
+}
+
+/// Outcome of a single transaction of `eth_callBundle`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResult {
+	/// Transaction hash.
+	pub tx_hash: H256,
+	/// Transaction sender.
+	pub from_address: H160,
+	/// Transaction recipient, `null` for contract creation.
+	pub to_address: Option<H160>,
+	/// Gas price of the transaction.
+	pub gas_price: U256,
+	/// Gas used by the transaction.
+	pub gas_used: U256,
+	/// Fees paid for the gas used.
+	pub gas_fees: U256,
+	/// Change of the block author's balance.
+	pub coinbase_diff: U256,
+	/// Part of `coinbaseDiff` paid directly rather than through gas fees.
+	pub eth_sent_to_coinbase: U256,
+	/// Output of the call, if it succeeded.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<Bytes>,
+	/// Error message, if the transaction reverted.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Revert data, if the transaction reverted.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub revert: Option<Bytes>,
+}
+
+impl From<BundleTransactionResult> for CallBundleResult {
+	fn from(tx: BundleTransactionResult) -> Self {
+		let gas_fees = tx.gas_price * tx.gas_used;
+		let (value, error, revert) = if tx.success {
+			(Some(tx.output.into()), None, None)
+		} else {
+			(None, Some("execution reverted".into()), Some(tx.output.into()))
+		};
+		CallBundleResult {
+			tx_hash: tx.hash,
+			from_address: tx.sender,
+			to_address: tx.to,
+			gas_price: tx.gas_price,
+			gas_used: tx.gas_used,
+			gas_fees,
+			coinbase_diff: tx.coinbase_diff,
+			eth_sent_to_coinbase: tx.coinbase_diff.saturating_sub(gas_fees),
+			value,
+			error,
+			revert,
+		}
+	}
+}
+
+impl CallBundleResponse {
+	/// Creates the response for a bundle executed on top of given block.
+	pub fn new(simulation: BundleSimulation, state_block_number: u64) -> Self {
+		CallBundleResponse {
+			bundle_hash: simulation.bundle_hash,
+			bundle_gas_price: simulation.effective_gas_price(),
+			coinbase_diff: simulation.coinbase_diff,
+			eth_sent_to_coinbase: simulation.coinbase_diff.saturating_sub(simulation.gas_fees),
+			gas_fees: simulation.gas_fees,
+			state_block_number: state_block_number.into(),
+			total_gas_used: simulation.gas_used,
+			results: simulation.transactions.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn send_bundle_request_deserialization() {
+		let s = r#"{"txs":["0x01","0x02"],"blockNumber":"0xa","maxTimestamp":1000,"revertingTxHashes":["0x0000000000000000000000000000000000000000000000000000000000000001"]}"#;
+		let request: SendBundleRequest = serde_json::from_str(s).unwrap();
+		assert_eq!(request.txs, vec![Bytes::new(vec![1]), Bytes::new(vec![2])]);
+		assert_eq!(request.block_number, 10.into());
+		assert_eq!(request.min_timestamp, None);
+		assert_eq!(request.max_timestamp, Some(1000));
+		assert_eq!(request.reverting_tx_hashes, vec![H256::from_low_u64_be(1)]);
+	}
+
+	#[test]
+	fn call_bundle_request_deserialization() {
+		let s = r#"{"txs":["0x01"],"blockNumber":"0xa","stateBlockNumber":"latest"}"#;
+		let request: CallBundleRequest = serde_json::from_str(s).unwrap();
+		assert_eq!(request.block_number, 10.into());
+		assert_eq!(request.state_block_number, BlockNumber::Latest);
+		assert_eq!(request.timestamp, None);
+	}
+
+	#[test]
+	fn reverted_result_serialization() {
+		let result: CallBundleResult = BundleTransactionResult {
+			hash: H256::zero(),
+			sender: H160::zero(),
+			to: None,
+			gas_price: 2.into(),
+			gas_used: 10.into(),
+			coinbase_diff: 25.into(),
+			success: false,
+			output: vec![0xfe],
+		}.into();
+		let serialized = serde_json::to_string(&result).unwrap();
+		assert_eq!(serialized, r#"{"txHash":"0x0000000000000000000000000000000000000000000000000000000000000000","fromAddress":"0x0000000000000000000000000000000000000000","toAddress":null,"gasPrice":"0x2","gasUsed":"0xa","gasFees":"0x14","coinbaseDiff":"0x19","ethSentToCoinbase":"0x5","error":"execution reverted","revert":"0xfe"}"#);
+	}
+}
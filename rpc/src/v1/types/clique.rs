@@ -0,0 +1,50 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique signer activity.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::H160;
+
+/// Signer activity over the most recent blocks.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliqueStatus {
+	/// Percentage of the blocks sealed by their in-turn signer.
+	pub inturn_percent: f64,
+	/// Number of blocks the activity was collected over.
+	pub num_blocks: u64,
+	/// Number of blocks sealed by each current signer.
+	pub sealer_activity: BTreeMap<H160, u64>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn clique_status_serialization() {
+		let status = CliqueStatus {
+			inturn_percent: 50.0,
+			num_blocks: 2,
+			sealer_activity: vec![(H160::from_low_u64_be(1), 2)].into_iter().collect(),
+		};
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"inturnPercent":50.0,"numBlocks":2,"sealerActivity":{"0x0000000000000000000000000000000000000001":2}}"#);
+	}
+}
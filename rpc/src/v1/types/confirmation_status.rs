@@ -0,0 +1,101 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Confirmation status of a transaction.
+
+use ethereum_types::{H256, U64, U256};
+use types::confirmation_status;
+
+/// How safely a transaction is included in the canonical chain.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationStatus {
+	/// Hash of the block including the transaction.
+	pub block_hash: H256,
+	/// Number of the block including the transaction.
+	pub block_number: U64,
+	/// Number of canonical blocks from the including block up to the best one, inclusive.
+	pub depth: U64,
+	/// Total difficulty of the best block.
+	pub total_difficulty: U256,
+	/// Heaviest known branch which doesn't include the transaction.
+	pub competing_branch: Option<CompetingBranch>,
+	/// Total difficulty the competing branch lacks to become canonical.
+	pub total_difficulty_margin: Option<U256>,
+	/// Whether the engine has finalized the including block.
+	pub finalized: bool,
+}
+
+/// A branch forking off the canonical chain below the transaction's block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompetingBranch {
+	/// Hash of the branch's best block.
+	pub hash: H256,
+	/// Number of the canonical block the branch forks off.
+	pub fork_number: U64,
+	/// Number of blocks in the branch.
+	pub length: U64,
+	/// Total difficulty of the branch's best block.
+	pub total_difficulty: U256,
+}
+
+impl From<confirmation_status::ConfirmationStatus> for ConfirmationStatus {
+	fn from(s: confirmation_status::ConfirmationStatus) -> Self {
+		ConfirmationStatus {
+			block_hash: s.block_hash,
+			block_number: s.block_number.into(),
+			depth: s.depth.into(),
+			total_difficulty: s.total_difficulty,
+			total_difficulty_margin: s.competing_branch.as_ref()
+				.map(|branch| s.total_difficulty.saturating_sub(branch.total_difficulty)),
+			competing_branch: s.competing_branch.map(|branch| CompetingBranch {
+				hash: branch.hash,
+				fork_number: branch.fork_number.into(),
+				length: branch.length.into(),
+				total_difficulty: branch.total_difficulty,
+			}),
+			finalized: s.finalized,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::{H256, U256};
+	use types::confirmation_status;
+	use super::ConfirmationStatus;
+
+	#[test]
+	fn confirmation_status_serialization() {
+		let status: ConfirmationStatus = confirmation_status::ConfirmationStatus {
+			block_hash: H256::from_low_u64_be(1),
+			block_number: 5,
+			depth: 3,
+			total_difficulty: U256::from(100),
+			competing_branch: Some(confirmation_status::CompetingBranch {
+				hash: H256::from_low_u64_be(2),
+				fork_number: 4,
+				length: 2,
+				total_difficulty: U256::from(60),
+			}),
+			finalized: false,
+		}.into();
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","blockNumber":"0x5","depth":"0x3","totalDifficulty":"0x64","competingBranch":{"hash":"0x0000000000000000000000000000000000000000000000000000000000000002","forkNumber":"0x4","length":"0x2","totalDifficulty":"0x3c"},"totalDifficultyMargin":"0x28","finalized":false}"#);
+	}
+}
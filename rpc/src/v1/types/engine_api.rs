@@ -0,0 +1,239 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Engine API payloads exchanged with a proof-of-stake consensus client.
+
+use ethereum_types::{H64, H160, H256, U64, U256, Bloom as H2048};
+use rlp::{Encodable, RlpStream};
+use triehash::ordered_trie_root;
+use types::{header::Header, transaction::SignedTransaction, verification::Unverified};
+use v1::types::Bytes;
+
+/// A block built by the consensus client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayload {
+	/// Hash of the parent block.
+	pub parent_hash: H256,
+	/// Beneficiary of the transaction fees.
+	pub fee_recipient: H160,
+	/// State root after the block.
+	pub state_root: H256,
+	/// Receipts root of the block.
+	pub receipts_root: H256,
+	/// Bloom of the block's logs.
+	pub logs_bloom: H2048,
+	/// Randomness of the beacon chain, stored in place of the mix hash.
+	pub prev_randao: H256,
+	/// Block number.
+	pub block_number: U64,
+	/// Gas limit.
+	pub gas_limit: U64,
+	/// Gas used.
+	pub gas_used: U64,
+	/// Timestamp.
+	pub timestamp: U64,
+	/// Extra data.
+	pub extra_data: Bytes,
+	/// Base fee per gas.
+	pub base_fee_per_gas: U256,
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// RLP encoded transactions.
+	pub transactions: Vec<Bytes>,
+}
+
+impl ExecutionPayload {
+	/// Describes a block built by this node.
+	pub fn from_block(header: &Header, transactions: &[SignedTransaction]) -> Self {
+		let seal = header.seal();
+		ExecutionPayload {
+			parent_hash: *header.parent_hash(),
+			fee_recipient: *header.author(),
+			state_root: *header.state_root(),
+			receipts_root: *header.receipts_root(),
+			logs_bloom: *header.log_bloom(),
+			prev_randao: seal.first().and_then(|field| rlp::decode(field).ok()).unwrap_or_default(),
+			block_number: header.number().into(),
+			gas_limit: header.gas_limit().low_u64().into(),
+			gas_used: header.gas_used().low_u64().into(),
+			timestamp: header.timestamp().into(),
+			extra_data: header.extra_data().clone().into(),
+			base_fee_per_gas: seal.get(2).and_then(|field| rlp::decode(field).ok()).unwrap_or_default(),
+			block_hash: header.hash(),
+			transactions: transactions.iter().map(|t| t.rlp_bytes().into()).collect(),
+		}
+	}
+
+	/// Builds the block described by the payload. Its hash is not checked against `block_hash`.
+	/// A zero base fee stands for a block before EIP-1559, as the base fee never drops to zero
+	/// once it is charged.
+	pub fn into_unverified(self) -> Result<Unverified, String> {
+		let transactions = self.transactions.into_iter().map(Bytes::into_vec).collect::<Vec<_>>();
+		let mut header = Header::new();
+		header.set_parent_hash(self.parent_hash);
+		header.set_author(self.fee_recipient);
+		header.set_state_root(self.state_root);
+		header.set_receipts_root(self.receipts_root);
+		header.set_log_bloom(self.logs_bloom);
+		header.set_number(self.block_number.as_u64());
+		header.set_gas_limit(self.gas_limit.as_u64().into());
+		header.set_gas_used(self.gas_used.as_u64().into());
+		header.set_timestamp(self.timestamp.as_u64());
+		header.set_extra_data(self.extra_data.into_vec());
+		header.set_transactions_root(ordered_trie_root(&transactions));
+		// the seal fields complete the header in the order of the London header.
+		let mut seal = vec![rlp::encode(&self.prev_randao), rlp::encode(&H64::zero())];
+		if !self.base_fee_per_gas.is_zero() {
+			seal.push(rlp::encode(&self.base_fee_per_gas));
+		}
+		header.set_seal(seal);
+
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&header);
+		stream.begin_list(transactions.len());
+		for transaction in &transactions {
+			stream.append_raw(transaction, 1);
+		}
+		stream.begin_list(0);
+		Unverified::from_rlp(stream.out()).map_err(|e| format!("Invalid transactions: {}", e))
+	}
+}
+
+/// Validity of a payload or of the head of a fork choice update.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadValidity {
+	/// The block was executed and is valid.
+	Valid,
+	/// The block or one of its ancestors is invalid.
+	Invalid,
+	/// The parent of the block is unknown.
+	Syncing,
+	/// The block was queued for import but not executed yet.
+	Accepted,
+	/// The hash of the block does not match its contents.
+	InvalidBlockHash,
+}
+
+/// Result of processing a payload.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadStatus {
+	/// Validity of the payload.
+	pub status: PayloadValidity,
+	/// Hash of the most recent valid block in the branch of the payload.
+	pub latest_valid_hash: Option<H256>,
+	/// Reason the payload is invalid.
+	pub validation_error: Option<String>,
+}
+
+impl PayloadStatus {
+	/// Status without a valid ancestor or error.
+	pub fn new(status: PayloadValidity) -> Self {
+		PayloadStatus {
+			status,
+			latest_valid_hash: None,
+			validation_error: None,
+		}
+	}
+
+	/// A valid block.
+	pub fn valid(hash: H256) -> Self {
+		PayloadStatus {
+			latest_valid_hash: Some(hash),
+			..Self::new(PayloadValidity::Valid)
+		}
+	}
+
+	/// An invalid block built on the given valid ancestor.
+	pub fn invalid(latest_valid_hash: Option<H256>, error: String) -> Self {
+		PayloadStatus {
+			status: PayloadValidity::Invalid,
+			latest_valid_hash,
+			validation_error: Some(error),
+		}
+	}
+}
+
+/// Blocks chosen by the consensus client.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceState {
+	/// The new best block.
+	pub head_block_hash: H256,
+	/// The most recent block unlikely to be reverted.
+	pub safe_block_hash: H256,
+	/// The most recent finalized block, or zero before the first finalization.
+	pub finalized_block_hash: H256,
+}
+
+/// Attributes of a payload the consensus client asks to be built on top of the new head.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadAttributes {
+	/// Timestamp of the payload.
+	pub timestamp: U64,
+	/// Randomness of the payload.
+	pub prev_randao: H256,
+	/// Beneficiary of the payload.
+	pub suggested_fee_recipient: H160,
+}
+
+/// Result of a fork choice update.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceUpdatedResponse {
+	/// Validity of the new head.
+	pub payload_status: PayloadStatus,
+	/// Identifier of the payload being built, if requested.
+	pub payload_id: Option<H64>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn payload_status_serialization() {
+		let status = PayloadStatus::invalid(Some(H256::zero()), "bad".into());
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"status":"INVALID","latestValidHash":"0x0000000000000000000000000000000000000000000000000000000000000000","validationError":"bad"}"#);
+
+		let response = ForkchoiceUpdatedResponse {
+			payload_status: PayloadStatus::new(PayloadValidity::InvalidBlockHash),
+			payload_id: None,
+		};
+		let serialized = serde_json::to_string(&response).unwrap();
+		assert_eq!(serialized, r#"{"payloadStatus":{"status":"INVALID_BLOCK_HASH","latestValidHash":null,"validationError":null},"payloadId":null}"#);
+	}
+
+	#[test]
+	fn forkchoice_state_deserialization() {
+		let s = r#"{
+			"headBlockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+			"safeBlockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+			"finalizedBlockHash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+		}"#;
+		let state: ForkchoiceState = serde_json::from_str(s).unwrap();
+		assert_eq!(state.head_block_hash, H256::from_low_u64_be(1));
+		assert!(state.finalized_block_hash.is_zero());
+	}
+}
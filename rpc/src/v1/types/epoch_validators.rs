@@ -0,0 +1,66 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator set of an epoch with the proof of its transition.
+
+use ethereum_types::{H160, H256, U64};
+use types::engines::epoch;
+use v1::types::Bytes;
+
+/// The validator set governing a block, with the epoch transition which introduced it.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochValidators {
+	/// Hash of the block at which the epoch started.
+	pub transition_hash: H256,
+	/// Number of the block at which the epoch started.
+	pub transition_number: U64,
+	/// Engine proof of the transition, combined with its finality proof.
+	pub proof: Bytes,
+	/// Validators allowed to seal blocks of the epoch.
+	pub validators: Vec<H160>,
+}
+
+impl From<epoch::EpochValidators> for EpochValidators {
+	fn from(e: epoch::EpochValidators) -> Self {
+		EpochValidators {
+			transition_hash: e.transition_hash,
+			transition_number: e.transition_number.into(),
+			proof: e.proof.into(),
+			validators: e.validators,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::{H160, H256};
+	use types::engines::epoch;
+	use super::EpochValidators;
+
+	#[test]
+	fn epoch_validators_serialization() {
+		let validators: EpochValidators = epoch::EpochValidators {
+			transition_hash: H256::from_low_u64_be(2),
+			transition_number: 16,
+			proof: vec![0xc0],
+			validators: vec![H160::from_low_u64_be(1)],
+		}.into();
+		let serialized = serde_json::to_string(&validators).unwrap();
+		assert_eq!(serialized, r#"{"transitionHash":"0x0000000000000000000000000000000000000000000000000000000000000002","transitionNumber":"0x10","proof":"0xc0","validators":["0x0000000000000000000000000000000000000001"]}"#);
+	}
+}
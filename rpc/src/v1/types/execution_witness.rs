@@ -0,0 +1,51 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Execution witness of a block.
+
+use ethereum_types::H256;
+use v1::types::Bytes;
+
+/// Trie nodes and code read by the execution of a block, which are enough to
+/// re-execute it without access to the rest of the state.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionWitness {
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Root of the state the block is executed on, which the witness nodes belong to.
+	pub parent_state_root: H256,
+	/// Trie nodes and code read during the execution, ordered by hash.
+	pub state: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::H256;
+	use super::ExecutionWitness;
+
+	#[test]
+	fn execution_witness_serialization() {
+		let witness = ExecutionWitness {
+			block_hash: H256::from_low_u64_be(1),
+			parent_state_root: H256::from_low_u64_be(2),
+			state: vec![vec![0x80].into()],
+		};
+		let serialized = serde_json::to_string(&witness).unwrap();
+		assert_eq!(serialized, r#"{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","parentStateRoot":"0x0000000000000000000000000000000000000000000000000000000000000002","state":["0x80"]}"#);
+	}
+}
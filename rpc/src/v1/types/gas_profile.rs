@@ -0,0 +1,90 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas profile of a transaction.
+
+use ethereum_types::{H160, U256};
+use trace as et;
+use v1::types::Bytes;
+
+/// Node of the gas profile tree, in the format consumed by flame graph renderers.
+///
+/// A call is split into a leaf for every opcode class its own instructions belong to,
+/// followed by a node for every subcall.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasProfile {
+	/// Label of the node: the address and selector of a call, or the name of an opcode class.
+	pub name: String,
+	/// Gas spent by the node, including its children.
+	pub value: U256,
+	/// Address of the executed code, if the node is a call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub address: Option<H160>,
+	/// Function selector, if the node is a call with call data.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub selector: Option<Bytes>,
+	/// Opcode classes and subcalls.
+	pub children: Vec<GasProfile>,
+}
+
+impl From<et::GasProfile> for GasProfile {
+	fn from(p: et::GasProfile) -> Self {
+		let name = match (p.is_create, p.selector) {
+			(true, _) => format!("create {:#x}", p.address),
+			(false, Some(selector)) => format!("{:#x}:0x{:08x}", p.address, u32::from_be_bytes(selector)),
+			(false, None) => format!("{:#x}", p.address),
+		};
+
+		let opcodes = p.opcodes.into_iter().map(|(class, gas)| GasProfile {
+			name: class.name().into(),
+			value: gas,
+			address: None,
+			selector: None,
+			children: Vec::new(),
+		});
+
+		GasProfile {
+			name,
+			value: p.gas,
+			address: Some(p.address),
+			selector: p.selector.map(|s| s.to_vec().into()),
+			children: opcodes.chain(p.calls.into_iter().map(Into::into)).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json;
+	use trace::OpcodeClass;
+	use super::*;
+
+	#[test]
+	fn gas_profile_serialization() {
+		let profile = GasProfile::from(et::GasProfile {
+			address: H160::from_low_u64_be(1),
+			selector: Some([0xa9, 0x05, 0x9c, 0xbb]),
+			is_create: false,
+			gas: 811.into(),
+			opcodes: vec![(OpcodeClass::Storage, 800.into()), (OpcodeClass::Call, 11.into())].into_iter().collect::<BTreeMap<_, _>>(),
+			calls: Vec::new(),
+		});
+		let serialized = serde_json::to_string(&profile).unwrap();
+		assert_eq!(serialized, r#"{"name":"0x0000000000000000000000000000000000000001:0xa9059cbb","value":"0x32b","address":"0x0000000000000000000000000000000000000001","selector":"0xa9059cbb","children":[{"name":"storage","value":"0x320","children":[]},{"name":"call","value":"0xb","children":[]}]}"#);
+	}
+}
@@ -0,0 +1,109 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Gas usage and price statistics over a range of blocks.
+
+use ethereum_types::{H160, U64, U256};
+use types::gas_stats;
+
+/// Gas used and gas limit of a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockGas {
+	/// Block number.
+	pub number: U64,
+	/// Gas used by the block.
+	pub gas_used: U256,
+	/// Block gas limit.
+	pub gas_limit: U256,
+}
+
+/// Transaction gas price at a percentile.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasPricePercentile {
+	/// Percentile, between 0 and 100.
+	pub percentile: U64,
+	/// Gas price at that percentile.
+	pub gas_price: U256,
+}
+
+/// Gas consumed by calls to a contract.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractGas {
+	/// Address of the contract.
+	pub address: H160,
+	/// Gas used by transactions calling it.
+	pub gas_used: U256,
+	/// Number of transactions calling it.
+	pub transactions: U64,
+}
+
+/// Gas statistics aggregated over a range of blocks.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasStats {
+	/// First block of the range.
+	pub from: U64,
+	/// Last block of the range.
+	pub to: U64,
+	/// Number of transactions in the range.
+	pub transactions: U64,
+	/// Total gas used.
+	pub gas_used: U256,
+	/// Total gas limit.
+	pub gas_limit: U256,
+	/// Gas used and gas limit of every block.
+	pub blocks: Vec<BlockGas>,
+	/// Average gas price, weighted by the gas each transaction used.
+	pub average_gas_price: U256,
+	/// Transaction gas prices at the 10th, 25th, 50th, 75th and 90th percentiles.
+	pub gas_price_percentiles: Vec<GasPricePercentile>,
+	/// Contracts which consumed the most gas, most expensive first.
+	pub top_contracts: Vec<ContractGas>,
+}
+
+impl From<gas_stats::ContractGas> for ContractGas {
+	fn from(c: gas_stats::ContractGas) -> Self {
+		ContractGas {
+			address: c.address,
+			gas_used: c.gas_used,
+			transactions: c.transactions.into(),
+		}
+	}
+}
+
+impl From<gas_stats::GasStats> for GasStats {
+	fn from(s: gas_stats::GasStats) -> Self {
+		GasStats {
+			from: s.from.into(),
+			to: s.to.into(),
+			transactions: s.transactions.into(),
+			gas_used: s.gas_used,
+			gas_limit: s.gas_limit,
+			blocks: s.blocks.into_iter()
+				.map(|(number, gas_used, gas_limit)| BlockGas { number: number.into(), gas_used, gas_limit })
+				.collect(),
+			average_gas_price: s.average_gas_price,
+			gas_price_percentiles: s.gas_price_percentiles.into_iter()
+				.map(|(percentile, gas_price)| GasPricePercentile { percentile: (percentile as u64).into(), gas_price })
+				.collect(),
+			top_contracts: s.top_contracts.into_iter().map(Into::into).collect(),
+		}
+	}
+}
@@ -0,0 +1,58 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Filters installed with `eth_new*Filter`.
+
+use ethereum_types::{U256, U64};
+
+use v1::helpers::{PollFilter, PollInfo};
+use v1::types::Origin;
+
+/// Filter installed with `eth_newFilter`, `eth_newBlockFilter` or `eth_newPendingTransactionFilter`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledFilter {
+	/// Filter id.
+	pub id: U256,
+	/// Kind of the filter: `logs`, `block` or `pendingTransaction`.
+	pub kind: String,
+	/// Origin of the request which installed the filter.
+	pub origin: Origin,
+	/// When the filter was installed, in seconds since the epoch.
+	pub created_at: U64,
+	/// Seconds since the filter was last polled.
+	pub idle: U64,
+	/// First block the next poll reports, for log and block filters.
+	pub next_block: Option<U64>,
+}
+
+impl<'a> From<(PollInfo, &'a PollFilter)> for InstalledFilter {
+	fn from((info, filter): (PollInfo, &'a PollFilter)) -> Self {
+		let next_block = match *filter {
+			PollFilter::Block { last_block_number, .. } => Some(last_block_number + 1),
+			PollFilter::Logs { block_number, .. } => Some(block_number),
+			PollFilter::PendingTransaction(_) => None,
+		};
+		InstalledFilter {
+			id: info.id.into(),
+			kind: filter.kind().into(),
+			origin: info.origin,
+			created_at: (info.created_at.max(0) as u64).into(),
+			idle: (info.idle.max(0) as u64).into(),
+			next_block: next_block.map(Into::into),
+		}
+	}
+}
@@ -0,0 +1,88 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Keystore re-encryption types.
+
+use ethereum_types::H160;
+use ethstore::{KdfParams, Reencryption, StoreAccountRef};
+
+/// Key derivation function to re-encrypt key files with.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "function", rename_all = "lowercase")]
+pub enum KeyDerivation {
+	/// PBKDF2 with HMAC-SHA256
+	Pbkdf2 {
+		/// Number of iterations
+		iterations: u32,
+	},
+	/// Scrypt
+	Scrypt {
+		/// CPU/memory cost parameter
+		n: u32,
+		/// Block size
+		r: u32,
+		/// Parallelization parameter
+		p: u32,
+	},
+}
+
+impl Into<KdfParams> for KeyDerivation {
+	fn into(self) -> KdfParams {
+		match self {
+			KeyDerivation::Pbkdf2 { iterations } => KdfParams::Pbkdf2 { iterations },
+			KeyDerivation::Scrypt { n, r, p } => KdfParams::Scrypt { n, r, p },
+		}
+	}
+}
+
+/// Outcome of keystore re-encryption.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReencryptionReport {
+	/// Accounts encrypted with new parameters
+	pub reencrypted: Vec<H160>,
+	/// Accounts which were already using requested parameters
+	pub unchanged: Vec<H160>,
+	/// Accounts none of the passwords could unlock
+	pub locked: Vec<H160>,
+}
+
+impl From<Reencryption> for ReencryptionReport {
+	fn from(r: Reencryption) -> Self {
+		let addresses = |accounts: Vec<StoreAccountRef>| accounts.into_iter().map(|a| a.address).collect();
+		ReencryptionReport {
+			reencrypted: addresses(r.reencrypted),
+			unchanged: addresses(r.unchanged),
+			locked: addresses(r.locked),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::KeyDerivation;
+
+	#[test]
+	fn key_derivation_deserialization() {
+		let s = r#"[{"function":"pbkdf2","iterations":10240},{"function":"scrypt","n":262144,"r":8,"p":1}]"#;
+		let deserialized: Vec<KeyDerivation> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, vec![
+			KeyDerivation::Pbkdf2 { iterations: 10240 },
+			KeyDerivation::Scrypt { n: 262144, r: 8, p: 1 },
+		]);
+	}
+}
@@ -20,30 +20,53 @@
 mod eth_types;
 
 mod account_info;
+mod address_or_name;
+mod ancestry_proof;
 mod block;
 mod block_number;
+mod bundle;
 mod bytes;
 mod call_request;
+mod clique;
+mod confirmation_status;
 mod confirmations;
 mod consensus_status;
 mod derivation;
+mod engine_api;
+mod epoch_validators;
+mod execution_witness;
 mod filter;
+mod gas_profile;
+mod gas_stats;
 mod histogram;
 mod index;
+mod installed_filter;
+mod key_derivation;
 mod log;
 mod node_kind;
+mod pending_block_stats;
+mod pool_inspection;
 mod private_receipt;
 mod private_log;
 mod provenance;
 mod receipt;
+mod receipt_proof;
 mod rpc_settings;
 mod secretstore;
+mod shh;
+mod stratum_worker;
+mod struct_log;
 mod sync;
 mod trace;
+mod threat_status;
+mod token_balance;
 mod trace_filter;
 mod transaction;
 mod transaction_request;
 mod transaction_condition;
+mod uncle_candidate;
+mod unlock_session;
+mod validator_status;
 mod work;
 mod eip191;
 
@@ -51,36 +74,62 @@ pub mod pubsub;
 
 pub use self::eip191::{EIP191Version, PresignedTransaction};
 pub use self::account_info::{AccountInfo, ExtAccountInfo, EthAccount, StorageProof, RecoveredAccount};
+pub use self::address_or_name::AddressOrName;
+pub use self::ancestry_proof::AncestryProof;
+pub use self::bundle::{SendBundleRequest, SendBundleResponse, CallBundleRequest, CallBundleResponse, CallBundleResult};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::{BlockNumber, LightBlockNumber, block_number_to_id};
 pub use self::call_request::CallRequest;
+pub use self::clique::CliqueStatus;
+pub use self::confirmation_status::{CompetingBranch, ConfirmationStatus};
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
 	TransactionModification, EIP191SignRequest, EthSignRequest, DecryptRequest, Either
 };
 pub use self::consensus_status::*;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::engine_api::{
+	ExecutionPayload, PayloadValidity, PayloadStatus, ForkchoiceState, PayloadAttributes, ForkchoiceUpdatedResponse,
+};
+pub use self::epoch_validators::EpochValidators;
+pub use self::execution_witness::ExecutionWitness;
 pub use self::filter::{Filter, FilterChanges};
+pub use self::gas_profile::GasProfile;
+pub use self::gas_stats::{BlockGas, ContractGas, GasPricePercentile, GasStats};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
+pub use self::installed_filter::InstalledFilter;
+pub use self::key_derivation::{KeyDerivation, ReencryptionReport};
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::pending_block_stats::PendingBlockStats;
+pub use self::pool_inspection::{TxPoolInspection, PooledTransaction, PooledTransactionStatus, DroppedTransaction, Locality};
 pub use self::private_receipt::{PrivateTransactionReceipt, PrivateTransactionReceiptAndTransaction};
 pub use self::private_log::PrivateTransactionLog;
 pub use self::provenance::Origin;
 pub use self::receipt::Receipt;
+pub use self::receipt_proof::ReceiptProof;
 pub use self::rpc_settings::RpcSettings;
 pub use self::secretstore::EncryptedDocumentKey;
+pub use self::shh::{ShhFilterRequest, ShhInfo, ShhMessage, ShhPostRequest};
+pub use self::stratum_worker::StratumWorker;
+pub use self::struct_log::{StructLog, StructLogs, StructLogRange};
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	TransactionStats, ChainStatus, ChainGap, MissingData, EthProtocolInfo, PipProtocolInfo, ForkId,
+	NatStatus, chain_gaps,
 };
-pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash};
+pub use self::trace::{LocalizedTrace, StateDiffPage, TraceResults, TraceResultsWithTransactionHash};
+pub use self::threat_status::{ThreatLevel, ThreatStatus};
+pub use self::token_balance::TokenBalance;
 pub use self::trace_filter::TraceFilter;
-pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
+pub use self::transaction::{Transaction, RichRawTransaction, LocalTransaction, LocalTransactionStatus, TransactionAge};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
+pub use self::uncle_candidate::UncleCandidate;
+pub use self::unlock_session::UnlockSession;
+pub use self::validator_status::{DutySlot, SlotOutcome, ValidatorStatus};
 pub use self::work::Work;
 
 // TODO [ToDr] Refactor to a proper type Vec of enums?
@@ -0,0 +1,75 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pending block construction statistics.
+
+use ethcore::miner;
+
+/// Timing statistics of pending block construction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingBlockStats {
+	/// Number of pending blocks built from scratch on top of a new parent.
+	pub full_builds: u64,
+	/// Number of times new transactions were appended to the existing pending block.
+	pub incremental_builds: u64,
+	/// Number of times the existing pending block was kept as there were no new transactions.
+	pub reused: u64,
+	/// Time taken by the last build, in milliseconds.
+	pub last_build_time_ms: u64,
+	/// Total time spent building pending blocks, in milliseconds.
+	pub total_build_time_ms: u64,
+	/// Number of transactions pushed by the last build.
+	pub last_transactions_pushed: usize,
+}
+
+fn as_millis(duration: ::std::time::Duration) -> u64 {
+	duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
+
+impl From<miner::PendingBlockStats> for PendingBlockStats {
+	fn from(stats: miner::PendingBlockStats) -> Self {
+		PendingBlockStats {
+			full_builds: stats.full_builds,
+			incremental_builds: stats.incremental_builds,
+			reused: stats.reused,
+			last_build_time_ms: as_millis(stats.last_build_time),
+			total_build_time_ms: as_millis(stats.total_build_time),
+			last_transactions_pushed: stats.last_transactions_pushed,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn pending_block_stats_serialization() {
+		let stats: PendingBlockStats = miner::PendingBlockStats {
+			full_builds: 2,
+			incremental_builds: 5,
+			reused: 1,
+			last_build_time: Duration::from_micros(1_500),
+			total_build_time: Duration::from_secs(2),
+			last_transactions_pushed: 3,
+		}.into();
+		let serialized = serde_json::to_string(&stats).unwrap();
+		assert_eq!(serialized, r#"{"fullBuilds":2,"incrementalBuilds":5,"reused":1,"lastBuildTimeMs":1,"totalBuildTimeMs":2000,"lastTransactionsPushed":3}"#);
+	}
+}
@@ -0,0 +1,175 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool introspection.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethereum_types::{H160, H256, U256};
+use miner::pool::{self, DropReason, Priority};
+
+/// Locality of a pooled transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Locality {
+	/// Received from the network.
+	Regular,
+	/// Re-imported from a retracted block.
+	Retracted,
+	/// Submitted locally.
+	Local,
+}
+
+impl From<Priority> for Locality {
+	fn from(priority: Priority) -> Self {
+		match priority {
+			Priority::Regular => Locality::Regular,
+			Priority::Retracted => Locality::Retracted,
+			Priority::Local => Locality::Local,
+		}
+	}
+}
+
+/// Status of a pooled transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PooledTransactionStatus {
+	/// Ready to be included in the next block.
+	Pending,
+	/// Waiting for a nonce gap to be filled or for its condition to be met.
+	Future,
+}
+
+/// Pooled transaction with the components of its score.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PooledTransaction {
+	/// Transaction sender.
+	pub from: H160,
+	/// Transaction nonce.
+	pub nonce: U256,
+	/// Gas price (fee cap for fee-market transactions).
+	pub gas_price: U256,
+	/// Fee paid to the block author on top of the base fee.
+	pub priority_fee: U256,
+	/// UNIX timestamp of when the transaction entered the pool.
+	pub received_at: u64,
+	/// Insertion order within the pool.
+	pub insertion_id: usize,
+	/// Locality of the transaction.
+	pub locality: Locality,
+	/// Number of bits the gas price is shifted by because of the locality.
+	pub score_boost: usize,
+	/// Score before penalization.
+	pub score: U256,
+	/// Current status.
+	pub status: PooledTransactionStatus,
+}
+
+/// Transaction recently removed from the pool without being mined.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedTransaction {
+	/// Why the transaction was removed.
+	pub reason: DropReason,
+	/// Transaction which pushed this one out, if any.
+	pub by: Option<H256>,
+	/// UNIX timestamp of when the transaction was removed.
+	pub dropped_at: u64,
+}
+
+/// Snapshot of the transaction pool.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPoolInspection {
+	/// Transactions in the pool by hash.
+	pub transactions: BTreeMap<H256, PooledTransaction>,
+	/// Recently dropped transactions by hash.
+	pub dropped: BTreeMap<H256, DroppedTransaction>,
+}
+
+fn timestamp(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+impl From<pool::Inspection> for TxPoolInspection {
+	fn from(inspection: pool::Inspection) -> Self {
+		let transactions = inspection.transactions.into_iter().map(|tx| (tx.hash, PooledTransaction {
+			from: tx.sender,
+			nonce: tx.nonce,
+			gas_price: tx.gas_price,
+			priority_fee: tx.priority_fee,
+			received_at: timestamp(tx.received_at),
+			insertion_id: tx.insertion_id,
+			locality: tx.priority.into(),
+			score_boost: tx.boost,
+			score: tx.score,
+			status: match tx.status {
+				pool::TransactionStatus::Pending => PooledTransactionStatus::Pending,
+				pool::TransactionStatus::Future => PooledTransactionStatus::Future,
+			},
+		})).collect();
+
+		// Later entries win, so a transaction dropped several times reports the last reason.
+		let dropped = inspection.dropped.into_iter().map(|tx| (tx.hash, DroppedTransaction {
+			reason: tx.reason,
+			by: tx.by,
+			dropped_at: timestamp(tx.dropped_at),
+		})).collect();
+
+		TxPoolInspection {
+			transactions,
+			dropped,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn tx_pool_inspection_serialization() {
+		let at = UNIX_EPOCH + Duration::from_secs(1_000);
+		let inspection: TxPoolInspection = pool::Inspection {
+			transactions: vec![pool::PooledTransaction {
+				hash: H256::from_low_u64_be(1),
+				sender: H160::from_low_u64_be(2),
+				nonce: 3.into(),
+				gas_price: 4.into(),
+				priority_fee: 4.into(),
+				received_at: at,
+				insertion_id: 5,
+				priority: Priority::Retracted,
+				boost: 10,
+				score: 4096.into(),
+				status: pool::TransactionStatus::Future,
+			}],
+			dropped: vec![pool::DroppedTransaction {
+				hash: H256::from_low_u64_be(6),
+				reason: DropReason::Replaced,
+				by: Some(H256::from_low_u64_be(7)),
+				dropped_at: at,
+			}],
+		}.into();
+
+		let serialized = serde_json::to_string(&inspection).unwrap();
+		assert_eq!(serialized, r#"{"transactions":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"from":"0x0000000000000000000000000000000000000002","nonce":"0x3","gasPrice":"0x4","priorityFee":"0x4","receivedAt":1000,"insertionId":5,"locality":"retracted","scoreBoost":10,"score":"0x1000","status":"future"}},"dropped":{"0x0000000000000000000000000000000000000000000000000000000000000006":{"reason":"replaced","by":"0x0000000000000000000000000000000000000000000000000000000000000007","droppedAt":1000}}}"#);
+	}
+}
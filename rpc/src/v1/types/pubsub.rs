@@ -16,7 +16,9 @@
 
 //! Pub-Sub types.
 
-use ethereum_types::H256;
+use std::collections::BTreeMap;
+
+use ethereum_types::{H160, H256, U64, U256};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
@@ -32,7 +34,11 @@ pub enum Result {
 	/// Transaction hash
 	TransactionHash(H256),
 	/// SyncStatus
-	SyncState(PubSubSyncStatus)
+	SyncState(PubSubSyncStatus),
+	/// Chain reorganization
+	Reorg(Box<Reorg>),
+	/// Changes of watched accounts
+	StateChanges(Box<StateChanges>),
 }
 
 /// PubSbub sync status
@@ -43,6 +49,63 @@ pub struct PubSubSyncStatus {
 	pub syncing: bool,
 }
 
+/// Chain reorganization, together with the transactions it affected.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all="camelCase")]
+pub struct Reorg {
+	/// Hashes of the blocks removed from the canonical chain.
+	pub retracted: Vec<H256>,
+	/// Hashes of the blocks added to the canonical chain.
+	pub enacted: Vec<H256>,
+	/// Transactions of the retracted blocks which aren't part of any enacted block.
+	pub unmined_transactions: Vec<H256>,
+	/// Transactions of the retracted blocks which are part of an enacted block as well.
+	pub remined_transactions: Vec<H256>,
+}
+
+/// Changes a block made to watched accounts.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all="camelCase")]
+pub struct StateChanges {
+	/// Hash of the block.
+	pub block_hash: H256,
+	/// Number of the block.
+	pub block_number: U64,
+	/// Whether the block was removed from the canonical chain, undoing the changes.
+	pub removed: bool,
+	/// Watched accounts the block changed.
+	pub accounts: Vec<AccountChanges>,
+}
+
+/// Changes a block made to a watched account. Unchanged fields are omitted.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+#[serde(rename_all="camelCase")]
+pub struct AccountChanges {
+	/// Address of the account.
+	pub address: H160,
+	/// Balance change.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<Change<U256>>,
+	/// Nonce change.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<Change<U256>>,
+	/// Code hash change.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code_hash: Option<Change<H256>>,
+	/// Changes of the watched storage slots.
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, Change<H256>>,
+}
+
+/// Value before and after a block.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+pub struct Change<T> {
+	/// Value at the parent block.
+	pub from: T,
+	/// Value at the block.
+	pub to: T,
+}
+
 impl Serialize for Result {
 	fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
 		where S: Serializer
@@ -52,6 +115,8 @@ impl Serialize for Result {
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
 			Result::SyncState(ref sync) => sync.serialize(serializer),
+			Result::Reorg(ref reorg) => reorg.serialize(serializer),
+			Result::StateChanges(ref changes) => changes.serialize(serializer),
 		}
 	}
 }
@@ -69,6 +134,10 @@ pub enum Kind {
 	NewPendingTransactions,
 	/// Node syncing status subscription.
 	Syncing,
+	/// Chain reorganizations subscription.
+	Reorgs,
+	/// Changes of watched accounts subscription.
+	StateChanges,
 }
 
 /// Subscription kind.
@@ -78,6 +147,19 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// Accounts to watch for changes.
+	StateChanges(WatchedState),
+}
+
+/// Accounts and storage slots to watch for changes.
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WatchedState {
+	/// Accounts whose balance, nonce and code hash are watched.
+	pub addresses: Vec<H160>,
+	/// Storage slots to watch, by account.
+	#[serde(default)]
+	pub storage: BTreeMap<H160, Vec<H256>>,
 }
 
 impl Default for Params {
@@ -95,6 +177,10 @@ impl<'a> Deserialize<'a> for Params {
 			return Ok(Params::None);
 		}
 
+		if let Ok(watched) = from_value(v.clone()) {
+			return Ok(Params::StateChanges(watched));
+		}
+
 		from_value(v.clone()).map(Params::Logs)
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
@@ -103,7 +189,9 @@ impl<'a> Deserialize<'a> for Params {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use super::{Result, Kind, Params};
+	use std::collections::BTreeMap;
+	use ethereum_types::{H160, H256};
+	use super::{Result, Kind, Params, Reorg, WatchedState};
 	use v1::types::{RichHeader, Header, Filter};
 	use v1::types::filter::VariadicValue;
 
@@ -113,6 +201,28 @@ mod tests {
 		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
 		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
 		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
+		assert_eq!(serde_json::from_str::<Kind>(r#""reorgs""#).unwrap(), Kind::Reorgs);
+		assert_eq!(serde_json::from_str::<Kind>(r#""stateChanges""#).unwrap(), Kind::StateChanges);
+	}
+
+	#[test]
+	fn should_deserialize_watched_state() {
+		let watched = serde_json::from_str::<Params>(
+			r#"{"addresses":["0x0000000000000000000000000000000000000001"],"storage":{"0x0000000000000000000000000000000000000002":["0x0000000000000000000000000000000000000000000000000000000000000003"]}}"#
+		).unwrap();
+		let mut storage = BTreeMap::new();
+		storage.insert(H160::from_low_u64_be(2), vec![H256::from_low_u64_be(3)]);
+		assert_eq!(watched, Params::StateChanges(WatchedState {
+			addresses: vec![H160::from_low_u64_be(1)],
+			storage,
+		}));
+
+		// a log filter with an address isn't mistaken for watched accounts
+		let logs = serde_json::from_str::<Params>(r#"{"address":"0x0000000000000000000000000000000000000001"}"#).unwrap();
+		match logs {
+			Params::Logs(_) => {},
+			other => panic!("expected a log filter, got {:?}", other),
+		}
 	}
 
 	#[test]
@@ -180,4 +290,16 @@ mod tests {
 		let expected = r#"{"author":"0x0000000000000000000000000000000000000000","difficulty":"0x0","extraData":"0x","gasLimit":"0x0","gasUsed":"0x0","hash":"0x0000000000000000000000000000000000000000000000000000000000000000","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","miner":"0x0000000000000000000000000000000000000000","number":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","sealFields":["0x","0x"],"sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000000","size":"0x45","stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","timestamp":"0x0","transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000"}"#;
 		assert_eq!(serde_json::to_string(&header).unwrap(), expected);
 	}
+
+	#[test]
+	fn should_serialize_reorg() {
+		let reorg = Result::Reorg(Box::new(Reorg {
+			retracted: vec![H256::from_low_u64_be(1)],
+			enacted: vec![H256::from_low_u64_be(2)],
+			unmined_transactions: vec![H256::from_low_u64_be(3)],
+			remined_transactions: vec![],
+		}));
+		let expected = r#"{"retracted":["0x0000000000000000000000000000000000000000000000000000000000000001"],"enacted":["0x0000000000000000000000000000000000000000000000000000000000000002"],"unminedTransactions":["0x0000000000000000000000000000000000000000000000000000000000000003"],"reminedTransactions":[]}"#;
+		assert_eq!(serde_json::to_string(&reorg).unwrap(), expected);
+	}
 }
@@ -0,0 +1,87 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Inclusion proof of a transaction and its receipt.
+
+use ethereum_types::{H256, U64};
+use types::receipt;
+use v1::types::Bytes;
+
+/// Merkle-Patricia proofs of a canonical transaction and its receipt against the
+/// transactions and receipts roots of the including block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptProof {
+	/// Hash of the including block.
+	pub block_hash: H256,
+	/// Number of the including block.
+	pub block_number: U64,
+	/// Index of the transaction; its RLP encoding is the key in both tries.
+	pub transaction_index: U64,
+	/// Receipts root of the block.
+	pub receipts_root: H256,
+	/// RLP-encoded receipt.
+	pub receipt: Bytes,
+	/// Trie nodes proving the receipt, in order from the root.
+	pub receipt_proof: Vec<Bytes>,
+	/// Transactions root of the block.
+	pub transactions_root: H256,
+	/// RLP-encoded transaction.
+	pub transaction: Bytes,
+	/// Trie nodes proving the transaction, in order from the root.
+	pub transaction_proof: Vec<Bytes>,
+}
+
+impl From<receipt::ReceiptProof> for ReceiptProof {
+	fn from(p: receipt::ReceiptProof) -> Self {
+		ReceiptProof {
+			block_hash: p.block_hash,
+			block_number: p.block_number.into(),
+			transaction_index: (p.transaction_index as u64).into(),
+			receipts_root: p.receipts_root,
+			receipt: p.receipt.into(),
+			receipt_proof: p.receipt_proof.into_iter().map(Into::into).collect(),
+			transactions_root: p.transactions_root,
+			transaction: p.transaction.into(),
+			transaction_proof: p.transaction_proof.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::H256;
+	use types::receipt;
+	use super::ReceiptProof;
+
+	#[test]
+	fn receipt_proof_serialization() {
+		let proof: ReceiptProof = receipt::ReceiptProof {
+			block_hash: H256::from_low_u64_be(1),
+			block_number: 16,
+			transaction_index: 2,
+			receipts_root: H256::from_low_u64_be(3),
+			receipt: vec![0xc0],
+			receipt_proof: vec![vec![0xc1, 0x80]],
+			transactions_root: H256::from_low_u64_be(4),
+			transaction: vec![0xc2],
+			transaction_proof: vec![],
+		}.into();
+		let serialized = serde_json::to_string(&proof).unwrap();
+		assert_eq!(serialized, r#"{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","blockNumber":"0x10","transactionIndex":"0x2","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000003","receipt":"0xc0","receiptProof":["0xc180"],"transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000004","transaction":"0xc2","transactionProof":[]}"#);
+	}
+}
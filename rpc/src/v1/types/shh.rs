@@ -0,0 +1,123 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gossip messaging types.
+
+use ethereum_types::H32;
+use gossip::{GossipInfo, Message};
+
+use v1::types::Bytes;
+
+/// Message posted with `shh_post`.
+///
+/// Whisper's `to` and `from` keys are rejected: gossip messages are not encrypted.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ShhPostRequest {
+	/// Topics the message is published under.
+	pub topics: Vec<H32>,
+	/// Message payload.
+	pub payload: Bytes,
+	/// Whisper time to live. Accepted and ignored; gossip messages do not expire.
+	pub ttl: Option<u64>,
+	/// Whisper proof-of-work priority. Accepted and ignored.
+	pub priority: Option<u64>,
+}
+
+/// Filter installed with `shh_newMessageFilter`.
+///
+/// Whisper's `decryptWith` and `from` keys are rejected: gossip messages are not encrypted.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShhFilterRequest {
+	/// Topics to receive messages of.
+	pub topics: Vec<H32>,
+}
+
+/// Message received by a filter.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShhMessage {
+	/// Id of the publishing peer.
+	pub from: String,
+	/// Topics the message was published under.
+	pub topics: Vec<H32>,
+	/// Message payload.
+	pub payload: Bytes,
+	/// Unix timestamp of the message arrival.
+	pub timestamp: u64,
+}
+
+impl From<Message> for ShhMessage {
+	fn from(message: Message) -> Self {
+		ShhMessage {
+			from: message.from,
+			topics: message.topics,
+			payload: message.payload.into(),
+			timestamp: message.received,
+		}
+	}
+}
+
+/// Status of the gossip service.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShhInfo {
+	/// Id of the local peer.
+	pub peer_id: String,
+	/// Addresses the gossip swarm listens on.
+	pub listen_addresses: Vec<String>,
+	/// Number of connected gossip peers.
+	pub peers: usize,
+	/// Number of installed filters.
+	pub filters: usize,
+	/// Number of subscribed topics.
+	pub topics: usize,
+}
+
+impl From<GossipInfo> for ShhInfo {
+	fn from(info: GossipInfo) -> Self {
+		ShhInfo {
+			peer_id: info.peer_id,
+			listen_addresses: info.listen_addresses,
+			peers: info.peers,
+			filters: info.filters,
+			topics: info.topics,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn post_request_deserialization() {
+		let s = r#"{"topics":["0x01020304"],"payload":"0x1234","ttl":100,"priority":50}"#;
+		let request: ShhPostRequest = serde_json::from_str(s).unwrap();
+		assert_eq!(request.topics, vec![H32([1, 2, 3, 4])]);
+		assert_eq!(request.payload, vec![0x12, 0x34].into());
+	}
+
+	#[test]
+	fn encrypted_requests_are_rejected() {
+		let post = r#"{"topics":["0x01020304"],"payload":"0x","to":"0x04ff"}"#;
+		assert!(serde_json::from_str::<ShhPostRequest>(post).is_err());
+		let filter = r#"{"topics":["0x01020304"],"decryptWith":"0x04ff"}"#;
+		assert!(serde_json::from_str::<ShhFilterRequest>(filter).is_err());
+	}
+}
@@ -0,0 +1,91 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Stratum worker statistics.
+
+use std::time::Duration;
+
+use ethcore_stratum::WorkerStats;
+use ethereum_types::U256;
+
+/// Statistics of a worker connected to the built-in stratum server.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StratumWorker {
+	/// Name the worker authorized with.
+	pub name: String,
+	/// Remote address of the worker.
+	pub address: String,
+	/// Whether the worker is connected over TLS.
+	pub tls: bool,
+	/// Current share difficulty.
+	pub difficulty: U256,
+	/// Estimated hashrate in hashes per second.
+	pub hashrate: U256,
+	/// Number of accepted shares.
+	pub accepted_shares: u64,
+	/// Number of rejected shares.
+	pub rejected_shares: u64,
+	/// Number of shares which sealed a block.
+	pub blocks: u64,
+	/// Seconds since the worker authorized.
+	pub connected_for: u64,
+	/// Seconds since the last accepted share.
+	pub last_share: Option<u64>,
+}
+
+impl From<WorkerStats> for StratumWorker {
+	fn from(stats: WorkerStats) -> Self {
+		StratumWorker {
+			name: stats.name,
+			address: stats.address.to_string(),
+			tls: stats.tls,
+			difficulty: stats.difficulty,
+			hashrate: stats.hashrate,
+			accepted_shares: stats.accepted_shares,
+			rejected_shares: stats.rejected_shares,
+			blocks: stats.blocks,
+			connected_for: stats.connected_for.as_secs(),
+			last_share: stats.last_share.as_ref().map(Duration::as_secs),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn stratum_worker_serialization() {
+		let worker: StratumWorker = WorkerStats {
+			name: "0x00a329c0648769a73afac7f9381e08fb43dbea72.rig1".into(),
+			address: "10.0.0.2:40000".parse().unwrap(),
+			tls: true,
+			difficulty: 4_000_000_000u64.into(),
+			accepted_shares: 40,
+			rejected_shares: 1,
+			blocks: 0,
+			hashrate: 250_000_000u64.into(),
+			connected_for: Duration::from_secs(600),
+			last_share: Some(Duration::from_millis(3_500)),
+		}.into();
+		let serialized = serde_json::to_string(&worker).unwrap();
+		assert_eq!(serialized, r#"{"name":"0x00a329c0648769a73afac7f9381e08fb43dbea72.rig1","address":"10.0.0.2:40000","tls":true,"difficulty":"0xee6b2800","hashrate":"0xee6b280","acceptedShares":40,"rejectedShares":1,"blocks":0,"connectedFor":600,"lastShare":3}"#);
+	}
+}
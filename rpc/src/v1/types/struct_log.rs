@@ -0,0 +1,99 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opcode level transaction traces.
+
+use trace as et;
+use v1::types::trace::VMExecutedOperation;
+
+/// A single executed VM step.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// Depth of the call executing the step.
+	pub depth: usize,
+	/// The program counter.
+	pub pc: usize,
+	/// Mnemonic of the executed instruction.
+	pub op: &'static str,
+	/// Gas available before the step.
+	pub gas: u64,
+	/// The gas cost of the instruction.
+	pub gas_cost: u64,
+	/// Stack items pushed and memory and storage written, or `None` if the instruction failed.
+	pub ex: Option<VMExecutedOperation>,
+}
+
+impl From<et::StructLog> for StructLog {
+	fn from(c: et::StructLog) -> Self {
+		StructLog {
+			depth: c.depth,
+			pc: c.pc,
+			op: c.instruction_name(),
+			gas: c.gas.low_u64(),
+			gas_cost: c.gas_cost.low_u64(),
+			ex: c.executed.map(Into::into),
+		}
+	}
+}
+
+/// A chunk of the steps executed by a transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogs {
+	/// The steps, in the order in which they completed.
+	pub logs: Vec<StructLog>,
+	/// True if there are no more steps after this chunk.
+	pub complete: bool,
+}
+
+/// Range of the steps to return.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogRange {
+	/// Number of steps to skip.
+	pub after: Option<usize>,
+	/// Maximal number of steps to return.
+	pub count: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn struct_log_serialization() {
+		let log = StructLog::from(et::StructLog {
+			depth: 1,
+			pc: 2,
+			instruction: 0x01,
+			gas: 100.into(),
+			gas_cost: 3.into(),
+			executed: None,
+		});
+		let serialized = serde_json::to_string(&log).unwrap();
+		assert_eq!(serialized, r#"{"depth":1,"pc":2,"op":"ADD","gas":100,"gasCost":3,"ex":null}"#);
+	}
+
+	#[test]
+	fn struct_log_range_deserialization() {
+		let range: StructLogRange = serde_json::from_str(r#"{"after":10,"count":5}"#).unwrap();
+		assert_eq!(range, StructLogRange { after: Some(10), count: Some(5) });
+		assert!(serde_json::from_str::<StructLogRange>(r#"{"offset":10}"#).is_err());
+	}
+}
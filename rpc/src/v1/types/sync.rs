@@ -15,11 +15,18 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use network::client_version::ClientVersion;
+use std::cmp;
 use std::collections::BTreeMap;
+use std::time::UNIX_EPOCH;
 
-use ethereum_types::{U256, H512};
-use sync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use ethereum_types::{U64, U256, H512};
+use sync::{
+	self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats, ForkStatus,
+	NatStatus as SyncNatStatus, NatType,
+};
 use serde::{Serialize, Serializer};
+use types::{blockchain_info::BlockChainInfo, pruning_info::PruningInfo};
+use v1::types::Bytes;
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -35,6 +42,9 @@ pub struct SyncInfo {
 	pub warp_chunks_amount: Option<U256>,
 	/// Warp sync snpashot chunks processed.
 	pub warp_chunks_processed: Option<U256>,
+	/// Block ranges missing from the local database, if any.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub chain_gaps: Vec<ChainGap>,
 }
 
 /// Peers info
@@ -185,6 +195,72 @@ impl From<SyncTransactionStats> for TransactionStats {
 	}
 }
 
+/// Fork identifier (EIP-2124) of the local chain.
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkId {
+	/// Checksum of the genesis hash and the passed forks.
+	pub hash: Bytes,
+	/// Block number of the next scheduled fork, zero if there is none.
+	pub next: u64,
+	/// Block numbers of all forks, including scheduled ones.
+	pub forks: Vec<u64>,
+	/// Number of peers rejected because of an incompatible fork id.
+	pub rejected_peers: usize,
+}
+
+impl From<ForkStatus> for ForkId {
+	fn from(s: ForkStatus) -> Self {
+		ForkId {
+			hash: Bytes::new(s.fork_id.hash.0.to_be_bytes().to_vec()),
+			next: s.fork_id.next,
+			forks: s.forks,
+			rejected_peers: s.rejected_peers,
+		}
+	}
+}
+
+/// State of the NAT port mapping.
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatStatus {
+	/// Whether the ports should be mapped.
+	pub enabled: bool,
+	/// Method the ports are mapped with, `upnp`, `natpmp` or `pcp`, none if they are not mapped.
+	pub mapped_with: Option<String>,
+	/// External address of the TCP port mapping.
+	pub external_address: Option<String>,
+	/// External UDP port used for discovery.
+	pub external_udp_port: Option<u16>,
+	/// UNIX timestamp of the last successful mapping or renewal.
+	pub last_renewed: Option<u64>,
+	/// Number of failed renewals since then.
+	pub failed_renewals: usize,
+	/// Number of times the external address changed.
+	pub address_changes: usize,
+}
+
+impl From<SyncNatStatus> for NatStatus {
+	fn from(s: SyncNatStatus) -> Self {
+		NatStatus {
+			enabled: s.enabled,
+			mapped_with: s.mapped_with.and_then(|nat_type| match nat_type {
+				NatType::UPnP => Some("upnp".into()),
+				NatType::NatPMP => Some("natpmp".into()),
+				NatType::PCP => Some("pcp".into()),
+				_ => None,
+			}),
+			external_address: s.external_address.map(|a| a.to_string()),
+			external_udp_port: s.external_udp_port,
+			last_renewed: s.last_renewed
+				.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+				.map(|d| d.as_secs()),
+			failed_renewals: s.failed_renewals,
+			address_changes: s.address_changes,
+		}
+	}
+}
+
 /// Chain status.
 #[derive(Default, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -193,9 +269,53 @@ pub struct ChainStatus {
 	pub block_gap: Option<(U256, U256)>,
 }
 
+/// Data the node is missing for a range of blocks.
+#[derive(Debug, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum MissingData {
+	/// Headers, bodies and receipts, e.g. ancient blocks not yet downloaded after a snapshot restore.
+	Blocks,
+	/// State, which was pruned or never imported.
+	State,
+}
+
+/// An inclusive range of blocks the node can't serve some data for.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainGap {
+	/// First block of the range.
+	pub from: U64,
+	/// Last block of the range.
+	pub to: U64,
+	/// The data missing for blocks of the range.
+	pub missing: MissingData,
+}
+
+/// Enumerate the block ranges missing from the local database. State gaps are only reported
+/// when the pruning info is known.
+pub fn chain_gaps(chain_info: &BlockChainInfo, pruning_info: Option<&PruningInfo>) -> Vec<ChainGap> {
+	let mut gaps = Vec::new();
+
+	if let (Some(ancient), Some(first)) = (chain_info.ancient_block_number, chain_info.first_block_number) {
+		if first > ancient + 1 {
+			gaps.push(ChainGap { from: (ancient + 1).into(), to: (first - 1).into(), missing: MissingData::Blocks });
+		}
+	}
+
+	if let Some(pruning_info) = pruning_info {
+		let earliest_state = cmp::min(pruning_info.earliest_state, chain_info.best_block_number);
+		if earliest_state > 0 {
+			gaps.push(ChainGap { from: 0.into(), to: (earliest_state - 1).into(), missing: MissingData::State });
+		}
+	}
+
+	gaps
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, H512};
+	use types::{blockchain_info::BlockChainInfo, pruning_info::PruningInfo};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, H512, chain_gaps};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -234,6 +354,30 @@ mod tests {
 		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
 	}
 
+	#[test]
+	fn test_serialize_chain_gaps() {
+		let chain_info = BlockChainInfo {
+			total_difficulty: 0.into(),
+			pending_total_difficulty: 0.into(),
+			genesis_hash: Default::default(),
+			best_block_hash: Default::default(),
+			best_block_number: 5000,
+			best_block_timestamp: 0,
+			ancient_block_hash: Some(Default::default()),
+			ancient_block_number: Some(9),
+			first_block_hash: Some(Default::default()),
+			first_block_number: Some(4000),
+		};
+		let pruning_info = PruningInfo { earliest_chain: 4000, earliest_state: 4500 };
+
+		let mut t = SyncInfo::default();
+		t.chain_gaps = chain_gaps(&chain_info, Some(&pruning_info));
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null,"chainGaps":[{"from":"0xa","to":"0xf9f","missing":"blocks"},{"from":"0x0","to":"0x1193","missing":"state"}]}"#);
+
+		assert_eq!(chain_gaps(&chain_info, None).len(), 1);
+	}
+
 	#[test]
 	fn test_serialize_transaction_stats() {
 		let stats = TransactionStats {
@@ -0,0 +1,115 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Signs of a majority attack on the chain.
+
+use ethereum_types::U64;
+use types::threat_status;
+
+/// How likely it is that the chain is under a majority attack.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ThreatLevel {
+	/// Nothing unusual was observed.
+	Normal,
+	/// Competing branches or reorganizations beyond what honest forks produce were observed.
+	Elevated,
+	/// An attack is likely, deposits should wait for more confirmations.
+	High,
+}
+
+/// Signs of an attack observed in the most recently imported blocks.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatStatus {
+	/// Overall threat level.
+	pub level: ThreatLevel,
+	/// What raised the level above normal.
+	pub reasons: Vec<String>,
+	/// Number of recently imported blocks the status is computed over.
+	pub window: U64,
+	/// Imported blocks which did not become canon.
+	pub side_blocks: U64,
+	/// Length of the longest competing branch, from its common ancestor with the canon chain.
+	pub longest_side_branch: U64,
+	/// Canon blocks imported per hour.
+	pub canon_rate: U64,
+	/// Blocks imported per hour on the fastest growing competing branch.
+	pub fastest_side_branch_rate: U64,
+	/// Largest difficulty change between a block and its parent, in per mille.
+	pub max_difficulty_swing: U64,
+	/// Reorganizations retracting more blocks than honest forks usually do.
+	pub deep_reorgs: U64,
+	/// Largest number of canon blocks retracted, or held back from being retracted, by a single import.
+	pub max_reorg_depth: U64,
+	/// Blocks kept out of the canon chain for reorganizing it deeper than allowed.
+	pub held_blocks: U64,
+}
+
+impl From<threat_status::ThreatLevel> for ThreatLevel {
+	fn from(level: threat_status::ThreatLevel) -> Self {
+		match level {
+			threat_status::ThreatLevel::Normal => ThreatLevel::Normal,
+			threat_status::ThreatLevel::Elevated => ThreatLevel::Elevated,
+			threat_status::ThreatLevel::High => ThreatLevel::High,
+		}
+	}
+}
+
+impl From<threat_status::ThreatStatus> for ThreatStatus {
+	fn from(s: threat_status::ThreatStatus) -> Self {
+		ThreatStatus {
+			level: s.level.into(),
+			reasons: s.reasons,
+			window: s.window.into(),
+			side_blocks: s.side_blocks.into(),
+			longest_side_branch: s.longest_side_branch.into(),
+			canon_rate: s.canon_rate.into(),
+			fastest_side_branch_rate: s.fastest_side_branch_rate.into(),
+			max_difficulty_swing: s.max_difficulty_swing.into(),
+			deep_reorgs: s.deep_reorgs.into(),
+			max_reorg_depth: s.max_reorg_depth.into(),
+			held_blocks: s.held_blocks.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use types::threat_status::{self, ThreatLevel};
+	use super::ThreatStatus;
+
+	#[test]
+	fn threat_status_serialization() {
+		let status: ThreatStatus = threat_status::ThreatStatus {
+			level: ThreatLevel::High,
+			reasons: vec!["competing branch of 7 blocks".into()],
+			window: 256,
+			side_blocks: 8,
+			longest_side_branch: 7,
+			canon_rate: 240,
+			fastest_side_branch_rate: 0,
+			max_difficulty_swing: 12,
+			deep_reorgs: 0,
+			max_reorg_depth: 1,
+			held_blocks: 0,
+		}.into();
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"level":"high","reasons":["competing branch of 7 blocks"],"window":"0x100","sideBlocks":"0x8","longestSideBranch":"0x7","canonRate":"0xf0","fastestSideBranchRate":"0x0","maxDifficultySwing":"0xc","deepReorgs":"0x0","maxReorgDepth":"0x1","heldBlocks":"0x0"}"#);
+	}
+}
@@ -0,0 +1,34 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Balance of an ERC-20 token.
+
+use ethereum_types::{H160, U64, U256};
+
+/// Balance of an account in an ERC-20 token, with the token's metadata.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+	/// Address of the token contract.
+	pub token: H160,
+	/// Balance, or `None` if the contract failed to report it.
+	pub balance: Option<U256>,
+	/// Number of decimals of the balance, if the token reports it.
+	pub decimals: Option<U64>,
+	/// Ticker symbol, if the token reports it.
+	pub symbol: Option<String>,
+}
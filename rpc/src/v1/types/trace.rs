@@ -211,6 +211,15 @@ impl From<state_diff::StateDiff> for StateDiff {
 	}
 }
 
+/// A page of the state diff between two blocks.
+#[derive(Debug, Serialize)]
+pub struct StateDiffPage {
+	/// Changed accounts within this page.
+	pub diff: StateDiff,
+	/// Address to fetch the next page after, or `None` if this is the last page.
+	pub next: Option<H160>,
+}
+
 /// Create response
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -252,6 +261,8 @@ pub enum CallType {
 	DelegateCall,
 	/// Static call
 	StaticCall,
+	/// Call on behalf of an authorized account
+	AuthCall,
 }
 
 impl From<trace::CallType> for CallType {
@@ -261,6 +272,7 @@ impl From<trace::CallType> for CallType {
 			trace::CallType::CallCode => CallType::CallCode,
 			trace::CallType::DelegateCall => CallType::DelegateCall,
 			trace::CallType::StaticCall => CallType::StaticCall,
+			trace::CallType::AuthCall => CallType::AuthCall,
 		}
 	}
 }
@@ -15,6 +15,7 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Serialize, Serializer};
 use serde::ser::SerializeStruct;
@@ -151,6 +152,71 @@ impl Serialize for LocalTransactionStatus {
 	}
 }
 
+/// Local Transaction together with its submission metadata.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTransaction {
+	/// Current status of the transaction.
+	#[serde(flatten)]
+	pub status: LocalTransactionStatus,
+	/// UNIX timestamp of the first submission to this node.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub submitted_at: Option<u64>,
+	/// Number of times the transaction was resubmitted to the queue.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub resubmissions: Option<u32>,
+}
+
+impl LocalTransaction {
+	/// Convert local transaction status and submission metadata into RPC `LocalTransaction`.
+	pub fn new(
+		status: miner::pool::local_transactions::Status,
+		submission: Option<miner::pool::local_transactions::Submission>,
+	) -> Self {
+		LocalTransaction {
+			status: LocalTransactionStatus::from(status),
+			submitted_at: submission.map(|s| s.submitted_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()),
+			resubmissions: submission.map(|s| s.resubmissions),
+		}
+	}
+}
+
+impl From<LocalTransactionStatus> for LocalTransaction {
+	fn from(status: LocalTransactionStatus) -> Self {
+		LocalTransaction {
+			status,
+			submitted_at: None,
+			resubmissions: None,
+		}
+	}
+}
+
+/// Age of a transaction in the pool and its scheduled eviction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionAge {
+	/// UNIX timestamp of when the transaction entered the pool.
+	pub received_at: u64,
+	/// Number of seconds the transaction has been in the pool.
+	pub age: u64,
+	/// UNIX timestamp of the scheduled eviction, `None` if the transaction never expires.
+	pub expires_at: Option<u64>,
+	/// Whether the transaction is local.
+	pub local: bool,
+}
+
+impl From<miner::pool::TransactionAge> for TransactionAge {
+	fn from(age: miner::pool::TransactionAge) -> Self {
+		let timestamp = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+		TransactionAge {
+			received_at: timestamp(age.received_at),
+			age: age.age.as_secs(),
+			expires_at: age.expires_at.map(timestamp),
+			local: age.is_local,
+		}
+	}
+}
+
 /// Geth-compatible output for eth_signTransaction method
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct RichRawTransaction {
@@ -275,7 +341,7 @@ impl LocalTransactionStatus {
 
 #[cfg(test)]
 mod tests {
-	use super::{Transaction, LocalTransactionStatus};
+	use super::{Transaction, LocalTransaction, LocalTransactionStatus};
 	use serde_json;
 
 	#[test]
@@ -331,4 +397,17 @@ mod tests {
 			r#","hash":"0x000000000000000000000000000000000000000000000000000000000000000a","gasPrice":"0x5"}"#
 		);
 	}
+
+	#[test]
+	fn test_local_transaction_serialize() {
+		let tx = LocalTransaction::from(LocalTransactionStatus::Pending);
+		assert_eq!(serde_json::to_string(&tx).unwrap(), r#"{"status":"pending"}"#);
+
+		let tx = LocalTransaction {
+			status: LocalTransactionStatus::Future,
+			submitted_at: Some(1_600_000_000),
+			resubmissions: Some(2),
+		};
+		assert_eq!(serde_json::to_string(&tx).unwrap(), r#"{"status":"future","submittedAt":1600000000,"resubmissions":2}"#);
+	}
 }
@@ -0,0 +1,76 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Uncle candidates of the next authored block.
+
+use client_traits;
+use ethereum_types::{Address, H256, U256};
+
+/// A block which may be included as an uncle in the next authored block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UncleCandidate {
+	/// Hash of the uncle.
+	pub hash: H256,
+	/// Number of the uncle.
+	pub number: u64,
+	/// Author of the uncle.
+	pub author: Address,
+	/// Number of generations between the uncle and the next block.
+	pub age: u64,
+	/// Whether the uncle was authored by the configured block author.
+	pub own: bool,
+	/// Whether the uncle selection policy includes the uncle in the next block.
+	pub included: bool,
+	/// Increase of the block author's balance when the uncle is included.
+	pub expected_reward: U256,
+}
+
+impl From<client_traits::UncleCandidate> for UncleCandidate {
+	fn from(candidate: client_traits::UncleCandidate) -> Self {
+		UncleCandidate {
+			hash: candidate.hash,
+			number: candidate.number,
+			author: candidate.author,
+			age: candidate.age,
+			own: candidate.own,
+			included: candidate.included,
+			expected_reward: candidate.expected_reward,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::*;
+
+	#[test]
+	fn uncle_candidate_serialization() {
+		let candidate: UncleCandidate = client_traits::UncleCandidate {
+			hash: H256::from_low_u64_be(1),
+			number: 9,
+			author: Address::from_low_u64_be(2),
+			age: 1,
+			own: true,
+			included: false,
+			expected_reward: U256::from(0x10),
+		}.into();
+		let serialized = serde_json::to_string(&candidate).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000001","number":9,"author":"0x0000000000000000000000000000000000000002","age":1,"own":true,"included":false,"expectedReward":"0x10"}"#);
+	}
+}
@@ -0,0 +1,55 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Account unlock session.
+
+use ethereum_types::H160;
+
+/// Active unlock session of an account.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockSession {
+	/// Unlocked account
+	pub address: H160,
+	/// Account is locked after the first signing
+	pub one_time: bool,
+	/// Seconds since the account was unlocked
+	pub unlocked_for: u64,
+	/// Seconds since the account was last used
+	pub idle_for: u64,
+	/// Seconds until the account is relocked, `null` if unlocked permanently
+	pub expires_in: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::UnlockSession;
+
+	#[test]
+	fn unlock_session_serialization() {
+		let session = UnlockSession {
+			address: 10.into(),
+			one_time: false,
+			unlocked_for: 20,
+			idle_for: 5,
+			expires_in: Some(280),
+		};
+		let serialized = serde_json::to_string(&session).unwrap();
+		assert_eq!(serialized, r#"{"address":"0x000000000000000000000000000000000000000a","oneTime":false,"unlockedFor":20,"idleFor":5,"expiresIn":280}"#);
+	}
+}
@@ -0,0 +1,131 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Sealing duties of the local validator.
+
+use ethereum_types::{H160, U64};
+use types::validator_status;
+
+/// What happened in a step the local validator was expected to seal.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SlotOutcome {
+	/// The step is still running.
+	Pending,
+	/// A block was sealed in time.
+	Sealed,
+	/// A block was sealed in the second half of the step.
+	Late,
+	/// An empty step message was broadcast instead of a block.
+	EmptyStep,
+	/// The step passed without a seal.
+	Missed,
+}
+
+/// A step the local validator was expected to seal.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DutySlot {
+	/// The step number.
+	pub step: U64,
+	/// What happened in the step.
+	pub outcome: SlotOutcome,
+	/// Milliseconds into the step a late block was sealed.
+	pub late_by_ms: Option<U64>,
+}
+
+/// Sealing duties of the local validator since the node started.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStatus {
+	/// Address of the local signer, if any.
+	pub signer: Option<H160>,
+	/// The current step.
+	pub current_step: U64,
+	/// The next step after the current one the local signer is expected to seal, if any.
+	pub next_slot: Option<U64>,
+	/// Number of steps the local signer was expected to seal.
+	pub expected: U64,
+	/// Number of expected steps sealed in time, including empty steps.
+	pub sealed: U64,
+	/// Number of expected steps sealed late.
+	pub late: U64,
+	/// Number of expected steps which passed without a seal.
+	pub missed: U64,
+	/// The most recent expected steps, oldest first.
+	pub recent_slots: Vec<DutySlot>,
+}
+
+impl From<validator_status::DutySlot> for DutySlot {
+	fn from(slot: validator_status::DutySlot) -> Self {
+		let (outcome, late_by_ms) = match slot.outcome {
+			validator_status::SlotOutcome::Pending => (SlotOutcome::Pending, None),
+			validator_status::SlotOutcome::Sealed => (SlotOutcome::Sealed, None),
+			validator_status::SlotOutcome::Late(ms) => (SlotOutcome::Late, Some(ms.into())),
+			validator_status::SlotOutcome::EmptyStep => (SlotOutcome::EmptyStep, None),
+			validator_status::SlotOutcome::Missed => (SlotOutcome::Missed, None),
+		};
+		DutySlot {
+			step: slot.step.into(),
+			outcome,
+			late_by_ms,
+		}
+	}
+}
+
+impl From<validator_status::ValidatorStatus> for ValidatorStatus {
+	fn from(s: validator_status::ValidatorStatus) -> Self {
+		ValidatorStatus {
+			signer: s.signer,
+			current_step: s.current_step.into(),
+			next_slot: s.next_slot.map(Into::into),
+			expected: s.expected.into(),
+			sealed: s.sealed.into(),
+			late: s.late.into(),
+			missed: s.missed.into(),
+			recent_slots: s.recent_slots.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::H160;
+	use types::validator_status::{self, DutySlot, SlotOutcome};
+	use super::ValidatorStatus;
+
+	#[test]
+	fn validator_status_serialization() {
+		let status: ValidatorStatus = validator_status::ValidatorStatus {
+			signer: Some(H160::from_low_u64_be(1)),
+			current_step: 10,
+			next_slot: Some(12),
+			expected: 3,
+			sealed: 1,
+			late: 1,
+			missed: 1,
+			recent_slots: vec![
+				DutySlot { step: 6, outcome: SlotOutcome::Late(2500) },
+				DutySlot { step: 8, outcome: SlotOutcome::Missed },
+				DutySlot { step: 10, outcome: SlotOutcome::EmptyStep },
+			],
+		}.into();
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"signer":"0x0000000000000000000000000000000000000001","currentStep":"0xa","nextSlot":"0xc","expected":"0x3","sealed":"0x1","late":"0x1","missed":"0x1","recentSlots":[{"step":"0x6","outcome":"late","lateByMs":"0x9c4"},{"step":"0x8","outcome":"missed","lateByMs":null},{"step":"0xa","outcome":"emptyStep","lateByMs":null}]}"#);
+	}
+}
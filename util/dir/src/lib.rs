@@ -136,6 +136,15 @@ impl Directories {
 		dir.push(data_dir);
 		dir
 	}
+
+	/// Get the default path of the journal of the blocks signed by the engine signers, outside of
+	/// the databases so that restoring them doesn't restore the journal.
+	pub fn sealing_journal_path(&self, data_dir: &str) -> PathBuf {
+		let mut path = PathBuf::from(&self.base);
+		path.push("sealing");
+		path.push(format!("{}.journal", data_dir));
+		path
+	}
 }
 
 #[derive(Debug, PartialEq)]
@@ -224,6 +233,16 @@ impl DatabaseDirectories {
 	pub fn network_path(&self) -> PathBuf {
 		self.spec_root_path().join("network")
 	}
+
+	/// Get the path of the local transactions journal.
+	pub fn local_transactions_journal_path(&self) -> PathBuf {
+		self.spec_root_path().join("local_transactions.journal")
+	}
+
+	/// Get the path of the file keeping the RPC filters across restarts.
+	pub fn filters_path(&self) -> PathBuf {
+		self.spec_root_path().join("filters.json")
+	}
 }
 
 fn default_path(t: AppDataType) -> Option<PathBuf> {
@@ -26,6 +26,7 @@ use crate::{
 	overlayrecentdb::OverlayRecentDB,
 	refcounteddb::RefCountedDB,
 	overlaydb::OverlayDB,
+	recordingdb::RecordingDB,
 };
 
 impl AsHashDB<KeccakHasher, DBValue> for ArchiveDB {
@@ -52,3 +53,8 @@ impl AsHashDB<KeccakHasher, DBValue> for OverlayDB {
 	fn as_hash_db(&self) -> &dyn HashDB<KeccakHasher, DBValue> { self }
 	fn as_hash_db_mut(&mut self) -> &mut dyn HashDB<KeccakHasher, DBValue> { self }
 }
+
+impl AsHashDB<KeccakHasher, DBValue> for RecordingDB {
+	fn as_hash_db(&self) -> &dyn HashDB<KeccakHasher, DBValue> { self }
+	fn as_hash_db_mut(&mut self) -> &mut dyn HashDB<KeccakHasher, DBValue> { self }
+}
@@ -35,6 +35,9 @@ mod refcounteddb;
 mod util;
 mod as_hash_db_impls;
 mod overlaydb;
+mod recordingdb;
+
+pub use recordingdb::{Recorder, RecordingDB};
 
 /// A `HashDB` which can manage a short-term journal potentially containing many forks of mutually
 /// exclusive actions.
@@ -0,0 +1,191 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `JournalDB` wrapper recording the values read through it.
+
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	io,
+	sync::Arc,
+};
+
+use ethereum_types::H256;
+use hash_db::{HashDB, Prefix};
+use keccak_hasher::KeccakHasher;
+use kvdb::{KeyValueDB, DBTransaction, DBValue};
+use parity_bytes::Bytes;
+use parking_lot::Mutex;
+
+use crate::{JournalDB, MemoryDB};
+
+/// Values read through a `RecordingDB`, keyed by their hash.
+#[derive(Clone, Default)]
+pub struct Recorder(Arc<Mutex<BTreeMap<H256, DBValue>>>);
+
+impl Recorder {
+	/// Values read so far, ordered by hash.
+	pub fn values(&self) -> Vec<DBValue> {
+		self.0.lock().values().cloned().collect()
+	}
+}
+
+/// Wraps a `JournalDB` to record every value read from it which wasn't inserted through
+/// the wrapper itself, e.g. the trie nodes and code a block execution depends on.
+pub struct RecordingDB {
+	inner: Box<dyn JournalDB>,
+	recorder: Recorder,
+	inserted: HashSet<H256>,
+}
+
+impl RecordingDB {
+	/// Wrap the given database, recording the reads into a new `Recorder`.
+	pub fn new(inner: Box<dyn JournalDB>) -> (Self, Recorder) {
+		let recorder = Recorder::default();
+		let db = RecordingDB {
+			inner,
+			recorder: recorder.clone(),
+			inserted: HashSet::new(),
+		};
+		(db, recorder)
+	}
+}
+
+impl HashDB<KeccakHasher, DBValue> for RecordingDB {
+	fn get(&self, key: &H256, prefix: Prefix) -> Option<DBValue> {
+		let value = self.inner.get(key, prefix);
+		if let Some(ref value) = value {
+			if !self.inserted.contains(key) {
+				self.recorder.0.lock().entry(*key).or_insert_with(|| value.clone());
+			}
+		}
+		value
+	}
+
+	fn contains(&self, key: &H256, prefix: Prefix) -> bool {
+		self.get(key, prefix).is_some()
+	}
+
+	fn insert(&mut self, prefix: Prefix, value: &[u8]) -> H256 {
+		let key = self.inner.insert(prefix, value);
+		self.inserted.insert(key);
+		key
+	}
+
+	fn emplace(&mut self, key: H256, prefix: Prefix, value: DBValue) {
+		self.inserted.insert(key);
+		self.inner.emplace(key, prefix, value);
+	}
+
+	fn remove(&mut self, key: &H256, prefix: Prefix) {
+		self.inner.remove(key, prefix);
+	}
+}
+
+impl JournalDB for RecordingDB {
+	fn boxed_clone(&self) -> Box<dyn JournalDB> {
+		Box::new(RecordingDB {
+			inner: self.inner.boxed_clone(),
+			recorder: self.recorder.clone(),
+			inserted: self.inserted.clone(),
+		})
+	}
+
+	fn mem_used(&self) -> usize {
+		self.inner.mem_used()
+	}
+
+	fn io_stats(&self) -> kvdb::IoStats {
+		self.inner.io_stats()
+	}
+
+	fn journal_size(&self) -> usize {
+		self.inner.journal_size()
+	}
+
+	fn is_empty(&self) -> bool {
+		self.inner.is_empty()
+	}
+
+	fn earliest_era(&self) -> Option<u64> {
+		self.inner.earliest_era()
+	}
+
+	fn latest_era(&self) -> Option<u64> {
+		self.inner.latest_era()
+	}
+
+	fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, id: &H256) -> io::Result<u32> {
+		self.inner.journal_under(batch, now, id)
+	}
+
+	fn mark_canonical(&mut self, batch: &mut DBTransaction, era: u64, id: &H256) -> io::Result<u32> {
+		self.inner.mark_canonical(batch, era, id)
+	}
+
+	fn drain_transaction_overlay(&mut self) -> io::Result<DBTransaction> {
+		self.inner.drain_transaction_overlay()
+	}
+
+	fn state(&self, id: &H256) -> Option<Bytes> {
+		self.inner.state(id)
+	}
+
+	fn is_prunable(&self) -> bool {
+		self.inner.is_prunable()
+	}
+
+	fn backing(&self) -> &Arc<dyn KeyValueDB> {
+		self.inner.backing()
+	}
+
+	fn flush(&self) {
+		self.inner.flush()
+	}
+
+	fn consolidate(&mut self, overlay: MemoryDB) {
+		self.inner.consolidate(overlay)
+	}
+
+	fn keys(&self) -> HashMap<H256, i32> {
+		self.inner.keys()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use hash_db::{HashDB, EMPTY_PREFIX};
+	use keccak_hash::keccak;
+
+	use crate::{Algorithm, new, commit_batch};
+	use super::RecordingDB;
+
+	#[test]
+	fn records_reads_of_existing_values_only() {
+		let mut jdb = new(Arc::new(kvdb_memorydb::create(1)), Algorithm::Archive, 0);
+		let existing = jdb.insert(EMPTY_PREFIX, b"existing");
+		commit_batch(&mut *jdb, 0, &keccak(b"0"), None).unwrap();
+
+		let (mut db, recorder) = RecordingDB::new(jdb);
+		let fresh = db.insert(EMPTY_PREFIX, b"fresh");
+		assert_eq!(db.get(&fresh, EMPTY_PREFIX), Some(b"fresh".to_vec()));
+		assert_eq!(db.get(&existing, EMPTY_PREFIX), Some(b"existing".to_vec()));
+		assert_eq!(db.get(&keccak(b"missing"), EMPTY_PREFIX), None);
+
+		assert_eq!(recorder.values(), vec![b"existing".to_vec()]);
+	}
+}
@@ -207,6 +207,11 @@ impl Discovery {
 		}
 	}
 
+	/// Update the endpoint announced to other nodes, after the external address changed.
+	pub fn set_public_endpoint(&mut self, endpoint: NodeEndpoint) {
+		self.public_endpoint = endpoint;
+	}
+
 	/// Add a new node to discovery table. Pings the node.
 	pub fn add_node(&mut self, e: NodeEntry) {
 		// If distance returns None, then we are trying to add ourself.
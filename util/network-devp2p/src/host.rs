@@ -23,7 +23,8 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, SystemTime};
 use slab::Slab;
 
 use ethereum_types::H256;
@@ -40,15 +41,15 @@ use rlp::{Encodable, RlpStream};
 use ethcore_io::{IoContext, IoHandler, IoManager, StreamToken, TimerToken};
 use parity_crypto::publickey::{Generator, KeyPair, Random, Secret};
 use network::{
-	client_version::ClientVersion, ConnectionDirection, ConnectionFilter, DisconnectReason, Error,
-	NetworkConfiguration, NetworkContext as NetworkContextTrait, NetworkIoMessage, NetworkProtocolHandler,
-	NonReservedPeerMode, PacketId, PeerId, ProtocolId, SessionInfo
+	client_version::ClientVersion, ConnectionDirection, ConnectionFilter, DisconnectReason, DiversityPeer, Error,
+	NatStatus, NetworkConfiguration, NetworkContext as NetworkContextTrait, NetworkIoMessage,
+	NetworkProtocolHandler, NonReservedPeerMode, PacketId, PeerId, ProtocolId, SessionInfo
 };
 
 use crate::{
 	connection::PAYLOAD_SOFT_LIMIT,
 	discovery::{Discovery, MAX_DATAGRAM_SIZE, NodeEntry, TableUpdates},
-	ip_utils::{map_external_address, select_public_address},
+	ip_utils::{map_external_address, select_public_address, NatMapping, NAT_RENEWAL_INTERVAL},
 	node_record::*,
 	node_table::*,
 	persistence::{save, load},
@@ -69,6 +70,7 @@ const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const FAST_DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 5;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 6;
 const NODE_TABLE: TimerToken = SYS_TIMER + 7;
+const NAT_RENEWAL: TimerToken = SYS_TIMER + 8;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -164,7 +166,11 @@ impl<'s> NetworkContextTrait for NetworkContext<'s> {
 	}
 
 	fn disable_peer(&self, peer: PeerId) {
-		self.io.message(NetworkIoMessage::DisablePeer(peer))
+		self.disable_peer_with_reason(peer, DisconnectReason::DisconnectRequested)
+	}
+
+	fn disable_peer_with_reason(&self, peer: PeerId, reason: DisconnectReason) {
+		self.io.message(NetworkIoMessage::DisablePeer(peer, reason))
 			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
 	}
 
@@ -276,6 +282,9 @@ pub struct Host {
 	reserved_nodes: RwLock<HashSet<NodeId>>,
 	stopping: AtomicBool,
 	filter: Option<Arc<dyn ConnectionFilter>>,
+	nat_status: RwLock<NatStatus>,
+	/// Whether a NAT renewal runs in the background
+	nat_renewing: AtomicBool,
 }
 
 impl Host {
@@ -324,6 +333,11 @@ impl Host {
 		let reserved_nodes = config.reserved_nodes.clone();
 		config.max_handshakes = min(config.max_handshakes, MAX_HANDSHAKES as u32);
 
+		let nat_status = NatStatus {
+			enabled: config.nat_enabled && config.public_address.is_none(),
+			..Default::default()
+		};
+
 		let mut host = Host {
 			info: RwLock::new(HostInfo {
 				keys,
@@ -346,6 +360,8 @@ impl Host {
 			reserved_nodes: RwLock::new(HashSet::new()),
 			stopping: AtomicBool::new(false),
 			filter,
+			nat_status: RwLock::new(nat_status),
+			nat_renewing: AtomicBool::new(false),
 		};
 
 		for n in boot_nodes {
@@ -388,6 +404,12 @@ impl Host {
 		Ok(())
 	}
 
+	pub fn set_peer_limits(&self, min_peers: u32, max_peers: u32) {
+		let mut info = self.info.write();
+		info.config.min_peers = min_peers;
+		info.config.max_peers = max_peers;
+	}
+
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode, io: &IoContext<NetworkIoMessage>) {
 		let mut info = self.info.write();
 
@@ -476,7 +498,8 @@ impl Host {
 				let public_address = select_public_address(local_endpoint.address.port());
 				let public_endpoint = NodeEndpoint { address: public_address, udp_port: local_endpoint.udp_port };
 				if self.info.read().config.nat_enabled {
-					match map_external_address(&local_endpoint, &self.info.read().config.nat_type) {
+					let nat_type = self.info.read().config.nat_type.clone();
+					match self.record_nat_mapping(map_external_address(&local_endpoint, &nat_type, None)) {
 						Some(endpoint) => {
 							info!("NAT mapped to external address {}", endpoint.address);
 							endpoint
@@ -522,10 +545,93 @@ impl Host {
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
 		}
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
+		if self.nat_status.read().enabled {
+			io.register_timer(NAT_RENEWAL, NAT_RENEWAL_INTERVAL)?;
+		}
 		io.register_stream(TCP_ACCEPT)?;
 		Ok(())
 	}
 
+	/// Current state of the NAT port mapping.
+	pub fn nat_status(&self) -> NatStatus {
+		self.nat_status.read().clone()
+	}
+
+	/// Records the outcome of mapping the ports on the gateway.
+	fn record_nat_mapping(&self, mapping: Option<NatMapping>) -> Option<NodeEndpoint> {
+		let mut status = self.nat_status.write();
+		match mapping {
+			Some(mapping) => {
+				if status.external_address.map_or(false, |address| address != mapping.endpoint.address) {
+					status.address_changes += 1;
+				}
+				status.mapped_with = Some(mapping.nat_type);
+				status.external_address = Some(mapping.endpoint.address);
+				status.external_udp_port = Some(mapping.endpoint.udp_port);
+				status.last_renewed = Some(SystemTime::now());
+				status.failed_renewals = 0;
+				Some(mapping.endpoint)
+			},
+			None => {
+				status.mapped_with = None;
+				status.failed_renewals += 1;
+				None
+			},
+		}
+	}
+
+	/// Renews the NAT port mapping before the lease expires. The gateway search can take seconds,
+	/// so it runs on its own thread and reports back with `NetworkIoMessage::NatMappingRenewed`.
+	fn renew_nat_mapping(&self, io: &IoContext<NetworkIoMessage>) {
+		if self.nat_renewing.swap(true, AtomicOrdering::SeqCst) {
+			return;
+		}
+		let (local_endpoint, public_endpoint, nat_type) = {
+			let info = self.info.read();
+			(info.local_endpoint.clone(), info.public_endpoint.clone(), info.config.nat_type.clone())
+		};
+		// Stick to the method which worked as long as it does.
+		let mapped_with = self.nat_status.read().mapped_with.clone();
+		let previous = mapped_with.as_ref().and(public_endpoint);
+		let nat_type = mapped_with.unwrap_or(nat_type);
+
+		let channel = io.channel();
+		let renewal = thread::Builder::new().name("NAT renewal".into()).spawn(move || {
+			let mapping = map_external_address(&local_endpoint, &nat_type, previous.as_ref())
+				.map(|mapping| (mapping.nat_type, mapping.endpoint.address, mapping.endpoint.udp_port));
+			channel.send(NetworkIoMessage::NatMappingRenewed(mapping))
+				.unwrap_or_else(|e| debug!(target: "network", "Error sending NAT renewal: {:?}", e));
+		});
+		if let Err(e) = renewal {
+			warn!(target: "network", "Error spawning NAT renewal thread: {:?}", e);
+			self.nat_renewing.store(false, AtomicOrdering::SeqCst);
+		}
+	}
+
+	/// Records a renewed NAT port mapping and picks up changes of the external address.
+	fn nat_mapping_renewed(&self, io: &IoContext<NetworkIoMessage>, mapping: Option<NatMapping>) {
+		self.nat_renewing.store(false, AtomicOrdering::SeqCst);
+		let public_endpoint = self.info.read().public_endpoint.clone();
+		match self.record_nat_mapping(mapping) {
+			Some(ref endpoint) if public_endpoint.as_ref() != Some(endpoint) => {
+				info!("NAT mapped to new external address {}", endpoint.address);
+				{
+					let mut info = self.info.write();
+					info.public_endpoint = Some(endpoint.clone());
+					info.enr.set_node_endpoint(endpoint);
+				}
+				if let Some(ref mut discovery) = *self.discovery.lock() {
+					discovery.set_public_endpoint(endpoint.clone());
+				}
+				if let Some(url) = self.external_url() {
+					io.message(NetworkIoMessage::NetworkStarted(url)).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
+				}
+			},
+			Some(_) => trace!(target: "network", "Renewed NAT mapping"),
+			None => debug!(target: "network", "Failed to renew NAT mapping"),
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
@@ -550,6 +656,25 @@ impl Host {
 		(handshakes, egress, ingress)
 	}
 
+	/// Addresses and client versions of ready sessions other than `token`, for the peer diversity check.
+	fn diversity_peers(&self, token: Option<StreamToken>) -> Vec<DiversityPeer> {
+		// Sessions are locked one by one after releasing the slab, so that busy sessions are waited for
+		// without holding up the rest of the host.
+		let sessions: Vec<_> = self.sessions.read().iter()
+			.filter(|(t, _)| Some(*t) != token)
+			.map(|(_, s)| s.clone())
+			.collect();
+		sessions.into_iter()
+			.filter_map(|s| {
+				let s = s.lock();
+				match (s.is_ready(), s.remote_addr()) {
+					(true, Ok(address)) => Some(DiversityPeer { address: address.ip(), client_version: s.info.client_version.to_string() }),
+					_ => None,
+				}
+			})
+			.collect()
+	}
+
 	fn connecting_to(&self, id: &NodeId) -> bool {
 		self.sessions.read().iter().any(|(_, e)| e.lock().id() == Some(id))
 	}
@@ -582,17 +707,18 @@ impl Host {
 	}
 
 	fn connect_peers(&self, io: &IoContext<NetworkIoMessage>) {
-		let (min_peers, mut pin, max_handshakes, allow_ips, self_id) = {
+		let (min_peers, mut pin, max_handshakes, allow_ips, peer_diversity, self_id) = {
 			let info = self.info.read();
 			if info.capabilities.is_empty() {
 				return;
 			}
 			let config = &info.config;
 
-			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.ip_filter.clone(), *info.id())
+			(config.min_peers, config.non_reserved_mode == NonReservedPeerMode::Deny, config.max_handshakes as usize, config.ip_filter.clone(), config.peer_diversity.clone(), *info.id())
 		};
 
 		let (handshake_count, egress_count, ingress_count) = self.session_count();
+		let mut diversity_peers = if peer_diversity.is_enabled() { self.diversity_peers(None) } else { Vec::new() };
 		let reserved_nodes = self.reserved_nodes.read();
 		if egress_count + ingress_count >= min_peers as usize + reserved_nodes.len() {
 			// check if all pinned nodes are connected.
@@ -617,13 +743,36 @@ impl Host {
 			Vec::new()
 		});
 
+		// Nodes are not dialed if they would exceed the network limits of the peer diversity along with
+		// the connected peers and the nodes dialed in this round.
+		let mut diversity_allowed = |id: &NodeId| {
+			if !peer_diversity.is_enabled() || reserved_nodes.contains(id) {
+				return true;
+			}
+			let address = match self.nodes.read().get(id) {
+				Some(node) => node.endpoint.address.ip(),
+				None => return true,
+			};
+			match peer_diversity.check_dial(&address, &diversity_peers) {
+				Ok(()) => {
+					diversity_peers.push(DiversityPeer { address, client_version: String::new() });
+					true
+				},
+				Err(reason) => {
+					trace!(target: "network", "Not connecting to {:?}: {}", id, reason);
+					false
+				},
+			}
+		};
+
 		let max_handshakes_per_round = max_handshakes / 2;
 		let mut started: usize = 0;
 		for id in nodes.filter(|id|
 				!self.have_session(id) &&
 				!self.connecting_to(id) &&
 				*id != self_id &&
-				self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Outbound))
+				self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Outbound)) &&
+				diversity_allowed(id)
 			).take(min(max_handshakes_per_round, max_handshakes - handshake_count)) {
 			self.connect_peer(&id, io);
 			started += 1;
@@ -743,7 +892,9 @@ impl Host {
 							let s = session.lock();
 							trace!(target: "network", "Session read error: {}:{:?} ({:?}) {:?}", token, s.id(), s.remote_addr(), e);
 							match e {
-								Error::Disconnect(DisconnectReason::IncompatibleProtocol) | Error::Disconnect(DisconnectReason::UselessPeer) => {
+								Error::Disconnect(DisconnectReason::IncompatibleProtocol) |
+								Error::Disconnect(DisconnectReason::UselessPeer) |
+								Error::Disconnect(DisconnectReason::SubprotocolError) => {
 									if let Some(id) = s.id() {
 										if !reserved_nodes.contains(id) {
 											let mut nodes = self.nodes.write();
@@ -759,6 +910,8 @@ impl Host {
 						},
 						Ok(SessionData::Ready) => {
 							let (_, egress_count, ingress_count) = self.session_count();
+							let peer_diversity = self.info.read().config.peer_diversity.clone();
+							let diversity_peers = if peer_diversity.is_enabled() { self.diversity_peers(Some(token)) } else { Vec::new() };
 							let reserved_nodes = self.reserved_nodes.read();
 							let mut s = session.lock();
 							let (min_peers, mut max_peers, reserved_only, self_id) = {
@@ -793,6 +946,18 @@ impl Host {
 								}
 							}
 
+							if peer_diversity.is_enabled() && !reserved_nodes.contains(&id) {
+								if let Ok(address) = s.remote_addr() {
+									let peer = DiversityPeer { address: address.ip(), client_version: s.info.client_version.to_string() };
+									if let Err(reason) = peer_diversity.check(&peer, &diversity_peers) {
+										trace!(target: "network", "Disconnecting peer {:?}: {}", id, reason);
+										s.disconnect(io, DisconnectReason::TooManyPeers);
+										kill = true;
+										break;
+									}
+								}
+							}
+
 							if !self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Inbound)) {
 								trace!(target: "network", "Inbound connection not allowed for {:?}", id);
 								s.disconnect(io, DisconnectReason::UnexpectedIdentity);
@@ -1091,6 +1256,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 				nodes.clear_useless();
 				nodes.save();
 			},
+			NAT_RENEWAL => self.renew_nat_mapping(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1152,10 +1318,10 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Disconnect requested {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
-			NetworkIoMessage::DisablePeer(ref peer) => {
+			NetworkIoMessage::DisablePeer(ref peer, reason) => {
 				let session = self.sessions.read().get(*peer).cloned();
 				if let Some(session) = session {
-					session.lock().disconnect(io, DisconnectReason::DisconnectRequested);
+					session.lock().disconnect(io, reason);
 					if let Some(id) = session.lock().id() {
 						let mut nodes = self.nodes.write();
 						nodes.note_failure(&id);
@@ -1167,6 +1333,10 @@ impl IoHandler<NetworkIoMessage> for Host {
 			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
+			NetworkIoMessage::NatMappingRenewed(ref mapping) => {
+				let mapping = mapping.clone().map(|(nat_type, address, udp_port)| NatMapping { nat_type, endpoint: NodeEndpoint { address, udp_port } });
+				self.nat_mapping_renewed(io, mapping)
+			},
 			_ => {}	// ignore others.
 		}
 	}
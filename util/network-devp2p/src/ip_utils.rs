@@ -17,7 +17,7 @@
 // Based on original work by David Levy https://raw.githubusercontent.com/dlevy47/rust-interfaces
 
 use std::io;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
 use std::time::Duration;
 
 use igd::{PortMappingProtocol, search_gateway, SearchOptions};
@@ -28,10 +28,25 @@ use network::NatType;
 
 use crate::node_table::NodeEndpoint;
 
-const NAT_PMP_PORT_MAPPING_LIFETIME: u32 = 30;
+// Mappings are renewed every `NAT_RENEWAL_INTERVAL`, well before the lease expires.
+const NAT_PMP_PORT_MAPPING_LIFETIME: u32 = 1200;
+/// Interval at which port mappings are renewed.
+pub const NAT_RENEWAL_INTERVAL: Duration = Duration::from_secs(NAT_PMP_PORT_MAPPING_LIFETIME as u64 / 2);
 // Waiting duration in milliseconds for response from router after sending port mapping request.
 // 50 milliseconds might be enough for low RTT.
 const NAT_PMP_PORT_MAPPING_WAITING_DURATION: u64 = 50;
+// PCP (RFC 6887) is served on the NAT-PMP port of the gateway.
+const PCP_SERVER_PORT: u16 = 5351;
+const PCP_VERSION: u8 = 2;
+const PCP_OPCODE_MAP: u8 = 1;
+const PCP_RESPONSE_BIT: u8 = 0x80;
+const PCP_PROTOCOL_TCP: u8 = 6;
+const PCP_PROTOCOL_UDP: u8 = 17;
+const PCP_MAP_PACKET_SIZE: usize = 60;
+const PCP_MAX_PACKET_SIZE: usize = 1100;
+// Requests are sent again if the gateway doesn't answer in time, gateways speaking only NAT-PMP answer right away.
+const PCP_RESPONSE_TIMEOUT: Duration = Duration::from_millis(500);
+const PCP_ATTEMPTS: usize = 2;
 
 /// Socket address extension for rustc beta. To be replaces with now unstable API
 pub trait SocketAddrExt {
@@ -317,11 +332,23 @@ pub fn select_public_address(port: u16) -> SocketAddr {
 	SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port))
 }
 
-fn search_upnp(local: &NodeEndpoint) -> Option<NodeEndpoint> {
+/// A port mapping obtained from the gateway.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NatMapping {
+	/// Method the ports were mapped with.
+	pub nat_type: NatType,
+	/// External endpoint.
+	pub endpoint: NodeEndpoint,
+}
+
+/// Maps the ports with UPnP. Asks for the external ports of `previous` if given,
+/// so that renewing a mapping keeps it on the same ports.
+fn search_upnp(local: &NodeEndpoint, previous: Option<&NodeEndpoint>) -> Option<NodeEndpoint> {
 	if let SocketAddr::V4(ref local_addr) = local.address {
 		let local_ip = *local_addr.ip();
 		let local_port = local_addr.port();
 		let local_udp_port = local.udp_port;
+		let previous_ports = previous.map(|e| (e.address.port(), e.udp_port));
 
 		let search_options = SearchOptions {
 			timeout: Some(Duration::new(5, 0)),
@@ -339,12 +366,19 @@ fn search_upnp(local: &NodeEndpoint) -> Option<NodeEndpoint> {
 							debug!("IP request error: {}", err);
 						},
 						Ok(external_addr) => {
-							match gateway.add_any_port(PortMappingProtocol::TCP, SocketAddrV4::new(local_ip, local_port), 0, "Parity Node/TCP") {
+							let add_port = |protocol, local_port, previous_port: Option<u16>, description| match previous_port {
+								Some(port) => gateway.add_port(protocol, port, SocketAddrV4::new(local_ip, local_port), 0, description)
+									.map(|_| port)
+									.map_err(|e| e.to_string()),
+								None => gateway.add_any_port(protocol, SocketAddrV4::new(local_ip, local_port), 0, description)
+									.map_err(|e| e.to_string()),
+							};
+							match add_port(PortMappingProtocol::TCP, local_port, previous_ports.map(|p| p.0), "Parity Node/TCP") {
 								Err(ref err) => {
 									debug!("Port mapping error: {}", err);
 								},
 								Ok(tcp_port) => {
-									match gateway.add_any_port(PortMappingProtocol::UDP, SocketAddrV4::new(local_ip, local_udp_port), 0, "Parity Node/UDP") {
+									match add_port(PortMappingProtocol::UDP, local_udp_port, previous_ports.map(|p| p.1), "Parity Node/UDP") {
 										Err(ref err) => {
 											debug!("Port mapping error: {}", err);
 										},
@@ -422,18 +456,98 @@ fn search_natpmp(local: &NodeEndpoint) -> Option<NodeEndpoint> {
 	None
 }
 
-/// Port mapping using ether UPnP or Nat-PMP.
-/// NAT PMP has higher priority than UPnP.
-pub fn map_external_address(local: &NodeEndpoint, nat_type: &NatType) -> Option<NodeEndpoint> {
+/// Encodes a PCP MAP request (RFC 6887, section 11.1) for `internal_port` of `client`, asking for `suggested_port` externally.
+fn pcp_map_request(nonce: &[u8; 12], protocol: u8, client: Ipv4Addr, internal_port: u16, suggested_port: u16, lifetime: u32) -> [u8; PCP_MAP_PACKET_SIZE] {
+	let mut packet = [0u8; PCP_MAP_PACKET_SIZE];
+	packet[0] = PCP_VERSION;
+	packet[1] = PCP_OPCODE_MAP;
+	packet[4..8].copy_from_slice(&lifetime.to_be_bytes());
+	packet[8..24].copy_from_slice(&client.to_ipv6_mapped().octets());
+	packet[24..36].copy_from_slice(nonce);
+	packet[36] = protocol;
+	packet[40..42].copy_from_slice(&internal_port.to_be_bytes());
+	packet[42..44].copy_from_slice(&suggested_port.to_be_bytes());
+	packet[44..60].copy_from_slice(&Ipv4Addr::UNSPECIFIED.to_ipv6_mapped().octets());
+	packet
+}
+
+/// Decodes the response to a PCP MAP request into the assigned external address.
+fn pcp_map_response(packet: &[u8], nonce: &[u8; 12], protocol: u8) -> Result<SocketAddrV4, String> {
+	if packet.len() < PCP_MAP_PACKET_SIZE {
+		// NAT-PMP gateways answer with a short "unsupported version" response.
+		return Err(format!("PCP response of {} bytes, version {:?}", packet.len(), packet.first()));
+	}
+	if packet[0] != PCP_VERSION || packet[1] != PCP_RESPONSE_BIT | PCP_OPCODE_MAP {
+		return Err(format!("Unexpected PCP response version {} opcode {:#x}", packet[0], packet[1]));
+	}
+	if packet[3] != 0 {
+		return Err(format!("PCP mapping refused with result code {}", packet[3]));
+	}
+	if &packet[24..36] != nonce || packet[36] != protocol {
+		return Err("PCP response to another request".into());
+	}
+	let port = u16::from_be_bytes([packet[42], packet[43]]);
+	let mut address = [0u8; 16];
+	address.copy_from_slice(&packet[44..60]);
+	match Ipv6Addr::from(address).to_ipv4() {
+		Some(address) => Ok(SocketAddrV4::new(address, port)),
+		None => Err("PCP mapped to an IPv6 address".into()),
+	}
+}
+
+/// Maps a port with PCP through `socket`, connected to the PCP server of the gateway.
+fn pcp_map(socket: &UdpSocket, protocol: u8, client: Ipv4Addr, internal_port: u16, suggested_port: u16) -> Result<SocketAddrV4, String> {
+	let nonce: [u8; 12] = rand::random();
+	let request = pcp_map_request(&nonce, protocol, client, internal_port, suggested_port, NAT_PMP_PORT_MAPPING_LIFETIME);
+	socket.set_read_timeout(Some(PCP_RESPONSE_TIMEOUT)).map_err(|e| e.to_string())?;
+	let mut response = [0u8; PCP_MAX_PACKET_SIZE];
+	for _ in 0..PCP_ATTEMPTS {
+		socket.send(&request).map_err(|e| e.to_string())?;
+		match socket.recv(&mut response) {
+			Ok(len) => return pcp_map_response(&response[..len], &nonce, protocol),
+			Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => continue,
+			Err(e) => return Err(e.to_string()),
+		}
+	}
+	Err("No PCP response from the gateway".into())
+}
+
+/// Maps the ports with PCP on the default gateway. Asks for the external ports of `previous` if given.
+fn search_pcp(local: &NodeEndpoint, previous: Option<&NodeEndpoint>) -> Option<NodeEndpoint> {
+	let local_addr = match local.address {
+		SocketAddr::V4(ref local_addr) => *local_addr,
+		SocketAddr::V6(_) => return None,
+	};
+	let (tcp_port, udp_port) = previous.map_or((local_addr.port(), local.udp_port), |e| (e.address.port(), e.udp_port));
+	let map = || -> Result<NodeEndpoint, String> {
+		let gateway = natpmp::get_default_gateway().map_err(|e| format!("Gateway search error: {:?}", e))?;
+		let socket = UdpSocket::bind(SocketAddrV4::new(*local_addr.ip(), 0)).map_err(|e| e.to_string())?;
+		socket.connect(SocketAddrV4::new(gateway, PCP_SERVER_PORT)).map_err(|e| e.to_string())?;
+		// The client address must be the one the gateway sees the requests from.
+		let client = match socket.local_addr().map_err(|e| e.to_string())? {
+			SocketAddr::V4(addr) => *addr.ip(),
+			SocketAddr::V6(_) => return Err("PCP socket bound to an IPv6 address".into()),
+		};
+		let tcp = pcp_map(&socket, PCP_PROTOCOL_TCP, client, local_addr.port(), tcp_port)?;
+		let udp = pcp_map(&socket, PCP_PROTOCOL_UDP, client, local.udp_port, udp_port)?;
+		Ok(NodeEndpoint { address: SocketAddr::V4(tcp), udp_port: udp.port() })
+	};
+	map().map_err(|e| debug!(target: "network", "PCP port mapping error: {}", e)).ok()
+}
+
+/// Port mapping using either PCP, Nat-PMP or UPnP.
+/// PCP has higher priority than NAT PMP, which has higher priority than UPnP.
+///
+/// Mapping again renews the leases, `previous` is the endpoint of the mapping being renewed.
+pub fn map_external_address(local: &NodeEndpoint, nat_type: &NatType, previous: Option<&NodeEndpoint>) -> Option<NatMapping> {
+	let pcp = || search_pcp(local, previous).map(|endpoint| NatMapping { nat_type: NatType::PCP, endpoint });
+	let natpmp = || search_natpmp(local).map(|endpoint| NatMapping { nat_type: NatType::NatPMP, endpoint });
+	let upnp = || search_upnp(local, previous).map(|endpoint| NatMapping { nat_type: NatType::UPnP, endpoint });
 	match *nat_type {
-		NatType::Any => {
-			match search_natpmp(local) {
-				Some(end_point) => Some(end_point),
-				None => search_upnp(local),
-			}
-		},
-		NatType::NatPMP => search_natpmp(local),
-		NatType::UPnP => search_upnp(local),
+		NatType::Any => pcp().or_else(natpmp).or_else(upnp),
+		NatType::PCP => pcp(),
+		NatType::NatPMP => natpmp(),
+		NatType::UPnP => upnp(),
 		_ => {
 			trace!(target: "network", "Can't map external address using NAT");
 			None
@@ -451,14 +565,62 @@ fn can_select_public_address() {
 #[test]
 fn can_map_external_address_upnp_or_fail() {
 	let pub_address = select_public_address(40478);
-	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40478 }, &NatType::UPnP);
+	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40478 }, &NatType::UPnP, None);
 }
 
 #[ignore]
 #[test]
 fn can_map_external_address_natpmp_or_fail() {
 	let pub_address = select_public_address(40479);
-	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40479 }, &NatType::NatPMP);
+	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40479 }, &NatType::NatPMP, None);
+}
+
+#[test]
+fn pcp_maps_with_gateway() {
+	let gateway = UdpSocket::bind("127.0.0.1:0").unwrap();
+	let gateway_addr = gateway.local_addr().unwrap();
+	let server = ::std::thread::spawn(move || {
+		let mut request = [0u8; PCP_MAX_PACKET_SIZE];
+		let (len, client) = gateway.recv_from(&mut request).unwrap();
+		assert_eq!(len, PCP_MAP_PACKET_SIZE);
+		assert_eq!(request[0], PCP_VERSION);
+		assert_eq!(request[1], PCP_OPCODE_MAP);
+		assert_eq!(&request[8..24], &Ipv4Addr::LOCALHOST.to_ipv6_mapped().octets());
+		assert_eq!(request[36], PCP_PROTOCOL_TCP);
+		assert_eq!(&request[40..44], &[0x76, 0x5f, 0x76, 0x60]);
+		// Answer with the suggested port plus one on the external address.
+		let mut response = request;
+		response[1] |= PCP_RESPONSE_BIT;
+		response[42..44].copy_from_slice(&30305u16.to_be_bytes());
+		response[44..60].copy_from_slice(&Ipv4Addr::new(203, 0, 113, 7).to_ipv6_mapped().octets());
+		gateway.send_to(&response[..len], client).unwrap();
+		// A refused mapping.
+		let (len, client) = gateway.recv_from(&mut request).unwrap();
+		let mut response = request;
+		response[1] |= PCP_RESPONSE_BIT;
+		response[3] = 2;
+		gateway.send_to(&response[..len], client).unwrap();
+	});
+
+	let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+	socket.connect(gateway_addr).unwrap();
+	let mapped = pcp_map(&socket, PCP_PROTOCOL_TCP, Ipv4Addr::LOCALHOST, 30303, 30304).unwrap();
+	assert_eq!(mapped, SocketAddrV4::new(Ipv4Addr::new(203, 0, 113, 7), 30305));
+	assert!(pcp_map(&socket, PCP_PROTOCOL_UDP, Ipv4Addr::LOCALHOST, 30303, 30303).is_err());
+	server.join().unwrap();
+}
+
+#[test]
+fn pcp_rejects_mismatching_responses() {
+	let nonce = [7u8; 12];
+	let request = pcp_map_request(&nonce, PCP_PROTOCOL_UDP, Ipv4Addr::new(192, 168, 1, 2), 30303, 30303, 1200);
+	let mut response = request;
+	response[1] |= PCP_RESPONSE_BIT;
+	assert_eq!(pcp_map_response(&response, &nonce, PCP_PROTOCOL_UDP), Ok(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 30303)));
+	assert!(pcp_map_response(&response, &[8u8; 12], PCP_PROTOCOL_UDP).is_err());
+	assert!(pcp_map_response(&response, &nonce, PCP_PROTOCOL_TCP).is_err());
+	// NAT-PMP "unsupported version" answer.
+	assert!(pcp_map_response(&[0, 0x81, 0, 1, 0, 0, 0, 0], &nonce, PCP_PROTOCOL_UDP).is_err());
 }
 
 #[test]
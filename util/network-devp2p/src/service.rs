@@ -24,7 +24,7 @@ use parking_lot::RwLock;
 
 use ethcore_io::{IoContext, IoHandler, IoService};
 use network::{
-	ConnectionFilter, Error, NetworkConfiguration, NetworkContext,
+	ConnectionFilter, Error, NatStatus, NetworkConfiguration, NetworkContext,
 	NetworkIoMessage, NetworkProtocolHandler, NonReservedPeerMode, PeerId, ProtocolId,
 
 };
@@ -54,7 +54,7 @@ pub struct NetworkService {
 	host_info: String,
 	host: RwLock<Option<Arc<Host>>>,
 	host_handler: Arc<HostHandler>,
-	config: NetworkConfiguration,
+	config: RwLock<NetworkConfiguration>,
 	filter: Option<Arc<dyn ConnectionFilter>>,
 }
 
@@ -68,7 +68,7 @@ impl NetworkService {
 			io_service,
 			host_info: config.client_version.clone(),
 			host: RwLock::new(None),
-			config,
+			config: RwLock::new(config),
 			host_handler,
 			filter,
 		})
@@ -102,7 +102,21 @@ impl NetworkService {
 
 	/// Returns the number of peers allowed.
 	pub fn num_peers_range(&self) -> RangeInclusive<u32> {
-		self.config.min_peers..=self.config.max_peers
+		let config = self.config.read();
+		config.min_peers..=config.max_peers
+	}
+
+	/// Change the number of peers allowed. Applies to the running host, if any,
+	/// and to any host started afterwards.
+	pub fn set_peer_limits(&self, min_peers: u32, max_peers: u32) {
+		{
+			let mut config = self.config.write();
+			config.min_peers = min_peers;
+			config.max_peers = max_peers;
+		}
+		if let Some(ref host) = *self.host.read() {
+			host.set_peer_limits(min_peers, max_peers);
+		}
 	}
 
 	/// Returns external url if available.
@@ -111,6 +125,12 @@ impl NetworkService {
 		host.as_ref().and_then(|h| h.external_url())
 	}
 
+	/// Returns the state of the NAT port mapping if the network is running.
+	pub fn nat_status(&self) -> Option<NatStatus> {
+		let host = self.host.read();
+		host.as_ref().map(|h| h.nat_status())
+	}
+
 	/// Returns external url if available.
 	pub fn local_url(&self) -> Option<String> {
 		let host = self.host.read();
@@ -122,9 +142,9 @@ impl NetworkService {
 	/// In case of error, also returns the listening address for better error reporting.
 	pub fn start(&self) -> Result<(), (Error, Option<SocketAddr>)> {
 		let mut host = self.host.write();
-		let listen_addr = self.config.listen_address;
+		let listen_addr = self.config.read().listen_address;
 		if host.is_none() {
-			let h = Arc::new(Host::new(self.config.clone(), self.filter.clone())
+			let h = Arc::new(Host::new(self.config.read().clone(), self.filter.clone())
 				.map_err(|err| (err, listen_addr))?);
 			self.io_service.register_handler(h.clone())
 				.map_err(|err| (err.into(), listen_addr))?;
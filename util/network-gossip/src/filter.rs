@@ -0,0 +1,174 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Topic filters buffering received messages until they are polled.
+
+use std::collections::{HashMap, VecDeque};
+
+use ethereum_types::{H256, H32};
+
+use crate::Message;
+
+struct Filter {
+	topics: Vec<H32>,
+	messages: VecDeque<Message>,
+}
+
+/// Installed filters and the reference count of every topic they listen to.
+pub struct Filters {
+	capacity: usize,
+	filters: HashMap<H256, Filter>,
+	topics: HashMap<H32, usize>,
+}
+
+impl Filters {
+	/// Creates an empty set of filters, each buffering at most `capacity` messages.
+	pub fn new(capacity: usize) -> Self {
+		Filters {
+			capacity,
+			filters: HashMap::new(),
+			topics: HashMap::new(),
+		}
+	}
+
+	/// Installs a filter. Returns its id and the topics no other filter listened to.
+	pub fn insert(&mut self, mut topics: Vec<H32>) -> (H256, Vec<H32>) {
+		topics.sort();
+		topics.dedup();
+
+		let mut id = H256::from(rand::random::<[u8; 32]>());
+		while self.filters.contains_key(&id) {
+			id = H256::from(rand::random::<[u8; 32]>());
+		}
+
+		let mut added = Vec::new();
+		for topic in &topics {
+			let count = self.topics.entry(*topic).or_insert(0);
+			if *count == 0 {
+				added.push(*topic);
+			}
+			*count += 1;
+		}
+
+		self.filters.insert(id, Filter { topics, messages: VecDeque::new() });
+		(id, added)
+	}
+
+	/// Removes a filter. Returns the topics no filter listens to anymore, `None` if the filter is unknown.
+	pub fn remove(&mut self, id: &H256) -> Option<Vec<H32>> {
+		let filter = self.filters.remove(id)?;
+		let mut removed = Vec::new();
+		for topic in filter.topics {
+			if let Some(count) = self.topics.get_mut(&topic) {
+				*count -= 1;
+				if *count == 0 {
+					self.topics.remove(&topic);
+					removed.push(topic);
+				}
+			}
+		}
+		Some(removed)
+	}
+
+	/// Takes the messages buffered by a filter.
+	pub fn drain(&mut self, id: &H256) -> Option<Vec<Message>> {
+		self.filters.get_mut(id).map(|filter| filter.messages.drain(..).collect())
+	}
+
+	/// Buffers `message` in every filter listening to one of its topics.
+	pub fn deliver(&mut self, message: &Message) {
+		let capacity = self.capacity;
+		for filter in self.filters.values_mut() {
+			if !filter.topics.iter().any(|topic| message.topics.contains(topic)) {
+				continue;
+			}
+			if filter.messages.len() >= capacity {
+				filter.messages.pop_front();
+			}
+			filter.messages.push_back(message.clone());
+		}
+	}
+
+	/// Number of installed filters.
+	pub fn len(&self) -> usize {
+		self.filters.len()
+	}
+
+	/// Number of distinct topics listened to.
+	pub fn topics(&self) -> usize {
+		self.topics.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn message(topics: &[H32], payload: u8) -> Message {
+		Message {
+			from: "peer".into(),
+			topics: topics.to_vec(),
+			payload: vec![payload],
+			received: 0,
+		}
+	}
+
+	#[test]
+	fn delivers_to_matching_filters() {
+		let (a, b) = (H32([0, 0, 0, 1]), H32([0, 0, 0, 2]));
+		let mut filters = Filters::new(10);
+		let (first, added) = filters.insert(vec![a, a]);
+		assert_eq!(added, vec![a]);
+		let (second, added) = filters.insert(vec![a, b]);
+		assert_eq!(added, vec![b]);
+
+		filters.deliver(&message(&[b], 1));
+		filters.deliver(&message(&[a], 2));
+
+		assert_eq!(filters.drain(&first).unwrap(), vec![message(&[a], 2)]);
+		assert_eq!(filters.drain(&second).unwrap(), vec![message(&[b], 1), message(&[a], 2)]);
+		assert_eq!(filters.drain(&second).unwrap(), vec![]);
+		assert_eq!(filters.drain(&H256::zero()), None);
+	}
+
+	#[test]
+	fn drops_oldest_messages_over_capacity() {
+		let a = H32([0, 0, 0, 1]);
+		let mut filters = Filters::new(2);
+		let (id, _) = filters.insert(vec![a]);
+
+		for payload in 0..3 {
+			filters.deliver(&message(&[a], payload));
+		}
+
+		assert_eq!(filters.drain(&id).unwrap(), vec![message(&[a], 1), message(&[a], 2)]);
+	}
+
+	#[test]
+	fn releases_topics_of_removed_filters() {
+		let (a, b) = (H32([0, 0, 0, 1]), H32([0, 0, 0, 2]));
+		let mut filters = Filters::new(10);
+		let (first, _) = filters.insert(vec![a]);
+		let (second, _) = filters.insert(vec![a, b]);
+		assert_eq!(filters.topics(), 2);
+
+		assert_eq!(filters.remove(&second), Some(vec![b]));
+		assert_eq!(filters.remove(&second), None);
+		assert_eq!(filters.remove(&first), Some(vec![a]));
+		assert_eq!(filters.len(), 0);
+		assert_eq!(filters.topics(), 0);
+	}
+}
@@ -0,0 +1,132 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node-local messaging over a libp2p gossipsub swarm.
+//!
+//! Takes over the role of the removed Whisper subsystem: dapps post payloads under 4-byte
+//! topics and read them back through topic filters. Messages are relayed in plain text by
+//! gossipsub; there is no envelope encryption, proof of work or expiry.
+
+mod filter;
+mod service;
+
+use libp2p::multiaddr::Protocol;
+
+pub use ethereum_types::{H256, H32};
+pub use libp2p::Multiaddr;
+pub use service::GossipService;
+
+/// Prefix of the gossipsub topics carrying messages of a 4-byte topic.
+pub const TOPIC_PREFIX: &str = "/openethereum/shh/";
+
+/// Largest payload accepted for publishing, in bytes.
+pub const MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+
+/// Gossip service configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GossipConfig {
+	/// Address the swarm listens on.
+	pub listen_address: Multiaddr,
+	/// Addresses of the peers dialled at startup.
+	pub boot_nodes: Vec<Multiaddr>,
+	/// Number of unread messages a filter keeps before dropping the oldest.
+	pub filter_capacity: usize,
+}
+
+impl GossipConfig {
+	/// TCP port of the listen address.
+	pub fn listen_port(&self) -> Option<u16> {
+		self.listen_address.iter().find_map(|protocol| match protocol {
+			Protocol::Tcp(port) => Some(port),
+			_ => None,
+		})
+	}
+}
+
+impl Default for GossipConfig {
+	fn default() -> Self {
+		GossipConfig {
+			listen_address: "/ip4/0.0.0.0/tcp/30305".parse().expect("valid multiaddr; qed"),
+			boot_nodes: Vec::new(),
+			filter_capacity: 1024,
+		}
+	}
+}
+
+/// A message delivered to a filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+	/// Id of the peer which published the message.
+	pub from: String,
+	/// Topics the message was published under.
+	pub topics: Vec<H32>,
+	/// Message payload.
+	pub payload: Vec<u8>,
+	/// Unix timestamp (seconds) of the message arrival.
+	pub received: u64,
+}
+
+/// Status of the gossip service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GossipInfo {
+	/// Id of the local peer.
+	pub peer_id: String,
+	/// Addresses the swarm is listening on.
+	pub listen_addresses: Vec<String>,
+	/// Number of connected peers.
+	pub peers: usize,
+	/// Number of installed filters.
+	pub filters: usize,
+	/// Number of topics the node is subscribed to.
+	pub topics: usize,
+}
+
+/// Gossip service error.
+#[derive(Debug, PartialEq, derive_more::Display)]
+pub enum Error {
+	/// A message or filter was given no topics.
+	#[display(fmt = "At least one topic is required")]
+	NoTopics,
+	/// The payload is over `MAX_PAYLOAD_SIZE`.
+	#[display(fmt = "Payload of {} bytes is over the limit of {} bytes", _0, MAX_PAYLOAD_SIZE)]
+	PayloadTooLarge(usize),
+	/// The swarm thread has stopped.
+	#[display(fmt = "Gossip service is not running")]
+	Stopped,
+	/// The swarm could not be started.
+	#[display(fmt = "Gossip transport error: {}", _0)]
+	Transport(String),
+}
+
+impl std::error::Error for Error {}
+
+/// Messaging operations exposed over RPC.
+pub trait GossipProvider: Send + Sync {
+	/// Returns the status of the service.
+	fn info(&self) -> GossipInfo;
+
+	/// Publishes `payload` under `topics`. Local filters matching any of the topics receive it too.
+	fn post(&self, topics: Vec<H32>, payload: Vec<u8>) -> Result<(), Error>;
+
+	/// Installs a filter for messages published under any of `topics` and returns its id.
+	fn new_filter(&self, topics: Vec<H32>) -> Result<H256, Error>;
+
+	/// Drains the messages received by a filter, `None` if the filter does not exist.
+	fn filter_messages(&self, id: &H256) -> Option<Vec<Message>>;
+
+	/// Removes a filter, returning whether it existed.
+	fn remove_filter(&self, id: &H256) -> bool;
+}
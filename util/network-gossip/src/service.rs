@@ -0,0 +1,353 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gossipsub swarm driven on a dedicated thread.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::channel::mpsc;
+use futures::{future, StreamExt};
+use libp2p::core::upgrade;
+use libp2p::gossipsub::{Gossipsub, GossipsubConfigBuilder, GossipsubEvent, GossipsubMessage, Topic, TopicHash};
+use libp2p::{identity, noise, tcp::TcpConfig, yamux, PeerId, Swarm, Transport};
+use log::{debug, info, warn};
+use parking_lot::Mutex;
+
+use crate::filter::Filters;
+use crate::{Error, GossipConfig, GossipInfo, GossipProvider, Message, H256, H32, MAX_PAYLOAD_SIZE, TOPIC_PREFIX};
+
+/// Room left in a gossipsub frame for the message framing and topics.
+const FRAME_OVERHEAD: usize = 4 * 1024;
+
+enum Command {
+	Subscribe(Vec<H32>),
+	Unsubscribe(Vec<H32>),
+	Publish(Vec<H32>, Vec<u8>),
+}
+
+#[derive(Default)]
+struct Status {
+	listen_addresses: Vec<String>,
+	peers: usize,
+}
+
+/// Gossip messaging service.
+///
+/// The swarm runs on its own thread and is stopped when the service is dropped.
+pub struct GossipService {
+	peer_id: String,
+	filters: Arc<Mutex<Filters>>,
+	status: Arc<Mutex<Status>>,
+	commands: mpsc::UnboundedSender<Command>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl GossipService {
+	/// Starts listening on `config.listen_address` and dials the boot nodes.
+	pub fn start(config: GossipConfig) -> Result<Self, Error> {
+		let keypair = identity::Keypair::generate_ed25519();
+		let local_peer_id = PeerId::from(keypair.public());
+		let dh_keys = noise::Keypair::<noise::X25519Spec>::new()
+			.into_authentic(&keypair)
+			.map_err(|e| Error::Transport(e.to_string()))?;
+		let transport = TcpConfig::new()
+			.nodelay(true)
+			.upgrade(upgrade::Version::V1)
+			.authenticate(noise::NoiseConfig::xx(dh_keys).into_authenticated())
+			.multiplex(yamux::Config::default())
+			.timeout(Duration::from_secs(20));
+
+		let gossipsub_config = GossipsubConfigBuilder::new()
+			.max_transmit_size(MAX_PAYLOAD_SIZE + FRAME_OVERHEAD)
+			.build();
+		let mut swarm = Swarm::new(transport, Gossipsub::new(local_peer_id.clone(), gossipsub_config), local_peer_id.clone());
+
+		Swarm::listen_on(&mut swarm, config.listen_address.clone())
+			.map_err(|e| Error::Transport(format!("cannot listen on {}: {}", config.listen_address, e)))?;
+		for node in config.boot_nodes {
+			if let Err(e) = Swarm::dial_addr(&mut swarm, node.clone()) {
+				warn!(target: "gossip", "Cannot dial boot node {}: {:?}", node, e);
+			}
+		}
+
+		let filters = Arc::new(Mutex::new(Filters::new(config.filter_capacity)));
+		let status = Arc::new(Mutex::new(Status::default()));
+		let (commands, receiver) = mpsc::unbounded();
+
+		let thread = {
+			let filters = filters.clone();
+			let status = status.clone();
+			thread::Builder::new()
+				.name("gossip".into())
+				.spawn(move || run(swarm, receiver, filters, status))
+				.map_err(|e| Error::Transport(e.to_string()))?
+		};
+
+		info!(target: "gossip", "Gossip service started with peer id {}", local_peer_id);
+
+		Ok(GossipService {
+			peer_id: local_peer_id.to_base58(),
+			filters,
+			status,
+			commands,
+			thread: Some(thread),
+		})
+	}
+
+	fn send(&self, command: Command) -> Result<(), Error> {
+		self.commands.unbounded_send(command).map_err(|_| Error::Stopped)
+	}
+}
+
+impl Drop for GossipService {
+	fn drop(&mut self) {
+		self.commands.close_channel();
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl GossipProvider for GossipService {
+	fn info(&self) -> GossipInfo {
+		let (filters, topics) = {
+			let filters = self.filters.lock();
+			(filters.len(), filters.topics())
+		};
+		let status = self.status.lock();
+		GossipInfo {
+			peer_id: self.peer_id.clone(),
+			listen_addresses: status.listen_addresses.clone(),
+			peers: status.peers,
+			filters,
+			topics,
+		}
+	}
+
+	fn post(&self, mut topics: Vec<H32>, payload: Vec<u8>) -> Result<(), Error> {
+		if topics.is_empty() {
+			return Err(Error::NoTopics);
+		}
+		if payload.len() > MAX_PAYLOAD_SIZE {
+			return Err(Error::PayloadTooLarge(payload.len()));
+		}
+		topics.sort();
+		topics.dedup();
+
+		let message = Message {
+			from: self.peer_id.clone(),
+			topics: topics.clone(),
+			payload: payload.clone(),
+			received: unix_now(),
+		};
+		self.send(Command::Publish(topics, payload))?;
+		self.filters.lock().deliver(&message);
+		Ok(())
+	}
+
+	fn new_filter(&self, topics: Vec<H32>) -> Result<H256, Error> {
+		if topics.is_empty() {
+			return Err(Error::NoTopics);
+		}
+		// Subscriptions are sent under the lock so they reach the swarm in filter order.
+		let mut filters = self.filters.lock();
+		let (id, added) = filters.insert(topics);
+		if !added.is_empty() {
+			if let Err(e) = self.send(Command::Subscribe(added)) {
+				filters.remove(&id);
+				return Err(e);
+			}
+		}
+		Ok(id)
+	}
+
+	fn filter_messages(&self, id: &H256) -> Option<Vec<Message>> {
+		self.filters.lock().drain(id)
+	}
+
+	fn remove_filter(&self, id: &H256) -> bool {
+		let mut filters = self.filters.lock();
+		match filters.remove(id) {
+			Some(removed) => {
+				if !removed.is_empty() {
+					let _ = self.send(Command::Unsubscribe(removed));
+				}
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+fn run(
+	mut swarm: Swarm<Gossipsub>,
+	mut commands: mpsc::UnboundedReceiver<Command>,
+	filters: Arc<Mutex<Filters>>,
+	status: Arc<Mutex<Status>>,
+) {
+	async_std::task::block_on(future::poll_fn(move |cx: &mut Context| {
+		loop {
+			match commands.poll_next_unpin(cx) {
+				Poll::Ready(Some(Command::Subscribe(topics))) => for topic in &topics {
+					swarm.subscribe(to_gossip_topic(topic));
+				},
+				Poll::Ready(Some(Command::Unsubscribe(topics))) => for topic in &topics {
+					swarm.unsubscribe(to_gossip_topic(topic));
+				},
+				Poll::Ready(Some(Command::Publish(topics, payload))) => {
+					swarm.publish_many(topics.iter().map(to_gossip_topic), payload);
+				}
+				Poll::Ready(None) => return Poll::Ready(()),
+				Poll::Pending => break,
+			}
+		}
+
+		loop {
+			match swarm.poll_next_unpin(cx) {
+				Poll::Ready(Some(GossipsubEvent::Message(_, _, message))) => {
+					if let Some(message) = from_gossip_message(message) {
+						filters.lock().deliver(&message);
+					}
+				}
+				Poll::Ready(Some(GossipsubEvent::Subscribed { peer_id, topic })) => {
+					debug!(target: "gossip", "Peer {} subscribed to {}", peer_id, topic.as_str());
+				}
+				Poll::Ready(Some(GossipsubEvent::Unsubscribed { peer_id, topic })) => {
+					debug!(target: "gossip", "Peer {} unsubscribed from {}", peer_id, topic.as_str());
+				}
+				Poll::Ready(None) | Poll::Pending => break,
+			}
+		}
+
+		let mut status = status.lock();
+		status.listen_addresses = Swarm::listeners(&swarm).map(ToString::to_string).collect();
+		status.peers = Swarm::network_info(&swarm).num_peers;
+		Poll::Pending
+	}));
+	debug!(target: "gossip", "Gossip service stopped");
+}
+
+fn to_gossip_topic(topic: &H32) -> Topic {
+	Topic::new(format!("{}{:08x}", TOPIC_PREFIX, u32::from_be_bytes(topic.0)))
+}
+
+fn from_gossip_topic(topic: &TopicHash) -> Option<H32> {
+	let hex = topic.as_str().strip_prefix(TOPIC_PREFIX)?;
+	if hex.len() != 8 {
+		return None;
+	}
+	u32::from_str_radix(hex, 16).ok().map(|topic| H32(topic.to_be_bytes()))
+}
+
+fn from_gossip_message(message: GossipsubMessage) -> Option<Message> {
+	let topics: Vec<H32> = message.topics.iter().filter_map(from_gossip_topic).collect();
+	if topics.is_empty() || message.data.len() > MAX_PAYLOAD_SIZE {
+		return None;
+	}
+	Some(Message {
+		from: message.source.to_base58(),
+		topics,
+		payload: message.data,
+		received: unix_now(),
+	})
+}
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Instant;
+
+	use super::*;
+
+	fn local_config(boot_nodes: Vec<String>) -> GossipConfig {
+		GossipConfig {
+			listen_address: "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
+			boot_nodes: boot_nodes.into_iter().map(|node| node.parse().unwrap()).collect(),
+			filter_capacity: 16,
+		}
+	}
+
+	fn wait_for<T>(what: &str, mut f: impl FnMut() -> Option<T>) -> T {
+		let deadline = Instant::now() + Duration::from_secs(30);
+		loop {
+			if let Some(value) = f() {
+				return value;
+			}
+			assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+			thread::sleep(Duration::from_millis(100));
+		}
+	}
+
+	#[test]
+	fn maps_topics() {
+		let topic = H32([0xde, 0xad, 0xbe, 0xef]);
+		let gossip_topic = to_gossip_topic(&topic);
+		assert_eq!(gossip_topic.no_hash().as_str(), "/openethereum/shh/deadbeef");
+		assert_eq!(from_gossip_topic(&gossip_topic.no_hash()), Some(topic));
+		assert_eq!(from_gossip_topic(&TopicHash::from_raw("/openethereum/shh/dead")), None);
+		assert_eq!(from_gossip_topic(&TopicHash::from_raw("/other/deadbeef")), None);
+	}
+
+	#[test]
+	fn rejects_invalid_messages() {
+		let service = GossipService::start(local_config(vec![])).unwrap();
+
+		assert_eq!(service.new_filter(vec![]), Err(Error::NoTopics));
+		assert_eq!(service.post(vec![], vec![1]), Err(Error::NoTopics));
+		assert_eq!(
+			service.post(vec![H32::zero()], vec![0; MAX_PAYLOAD_SIZE + 1]),
+			Err(Error::PayloadTooLarge(MAX_PAYLOAD_SIZE + 1))
+		);
+	}
+
+	#[test]
+	fn relays_messages_between_nodes() {
+		let topic = H32([1, 2, 3, 4]);
+		let first = GossipService::start(local_config(vec![])).unwrap();
+		let address = wait_for("listen address", || first.info().listen_addresses.into_iter().next());
+		let second = GossipService::start(local_config(vec![address])).unwrap();
+
+		let first_filter = first.new_filter(vec![topic]).unwrap();
+		let second_filter = second.new_filter(vec![topic]).unwrap();
+		wait_for("peers", || if first.info().peers == 1 && second.info().peers == 1 { Some(()) } else { None });
+
+		// Messages posted before the gossipsub mesh is formed are not relayed, so keep posting.
+		let received = wait_for("relayed message", || {
+			first.post(vec![topic], b"hello".to_vec()).unwrap();
+			let messages = second.filter_messages(&second_filter).unwrap();
+			messages.into_iter().next()
+		});
+		assert_eq!(received.from, first.info().peer_id);
+		assert_eq!(received.topics, vec![topic]);
+		assert_eq!(received.payload, b"hello".to_vec());
+
+		// The publisher's own filters receive its messages as well.
+		let own = first.filter_messages(&first_filter).unwrap();
+		assert!(!own.is_empty());
+		assert!(own.iter().all(|message| message.from == first.info().peer_id));
+
+		assert!(second.remove_filter(&second_filter));
+		assert!(!second.remove_filter(&second_filter));
+		assert_eq!(second.filter_messages(&second_filter), None);
+		assert_eq!(second.info().topics, 0);
+	}
+}
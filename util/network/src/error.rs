@@ -33,6 +33,7 @@ pub enum DisconnectReason
 	UnexpectedIdentity,
 	LocalIdentity,
 	PingTimeout,
+	SubprotocolError = 0x10,
 	Unknown,
 }
 
@@ -51,6 +52,7 @@ impl DisconnectReason {
 			9 => DisconnectReason::UnexpectedIdentity,
 			10 => DisconnectReason::LocalIdentity,
 			11 => DisconnectReason::PingTimeout,
+			16 => DisconnectReason::SubprotocolError,
 			_ => DisconnectReason::Unknown,
 		}
 	}
@@ -73,6 +75,7 @@ impl fmt::Display for DisconnectReason {
 			UnexpectedIdentity => "unexpected identity",
 			LocalIdentity => "local identity",
 			PingTimeout => "ping timeout",
+			SubprotocolError => "subprotocol error",
 			Unknown => "unknown",
 		};
 
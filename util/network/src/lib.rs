@@ -40,10 +40,12 @@ pub mod client_version;
 
 mod connection_filter;
 mod error;
+mod peer_diversity;
 
 pub use connection_filter::{ConnectionFilter, ConnectionDirection};
 pub use io::TimerToken;
 pub use error::{Error, DisconnectReason};
+pub use peer_diversity::{PeerDiversity, DiversityPeer, AsnMap};
 
 use client_version::ClientVersion;
 use std::cmp::Ordering;
@@ -51,7 +53,7 @@ use std::collections::HashMap;
 use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
 use std::str::{self, FromStr};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use ipnetwork::{IpNetwork, IpNetworkError};
 use crypto::publickey::Secret;
 use ethereum_types::H512;
@@ -94,9 +96,12 @@ pub enum NetworkIoMessage {
 	/// Disconnect a peer.
 	Disconnect(PeerId),
 	/// Disconnect and temporary disable peer.
-	DisablePeer(PeerId),
+	DisablePeer(PeerId, DisconnectReason),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
+	/// NAT port mapping renewed in the background, with the method used, the external TCP address
+	/// and the external UDP port, `None` if the renewal failed.
+	NatMappingRenewed(Option<(NatType, SocketAddr, u16)>),
 }
 
 /// Shared session information
@@ -181,6 +186,26 @@ pub enum NatType {
 	Any,
 	UPnP,
 	NatPMP,
+	PCP,
+}
+
+/// State of the NAT port mapping
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NatStatus {
+	/// Whether the ports should be mapped
+	pub enabled: bool,
+	/// Method the ports are currently mapped with, `None` if they are not mapped
+	pub mapped_with: Option<NatType>,
+	/// External address of the TCP port mapping
+	pub external_address: Option<SocketAddr>,
+	/// External UDP port used for discovery
+	pub external_udp_port: Option<u16>,
+	/// When the mapping was last requested or renewed successfully
+	pub last_renewed: Option<SystemTime>,
+	/// Number of failed renewals since then
+	pub failed_renewals: usize,
+	/// Number of times the external address changed
+	pub address_changes: usize,
 }
 
 /// Network service configuration
@@ -220,6 +245,8 @@ pub struct NetworkConfiguration {
 	pub non_reserved_mode: NonReservedPeerMode,
 	/// IP filter
 	pub ip_filter: IpFilter,
+	/// Limits on peers sharing a network or client implementation
+	pub peer_diversity: PeerDiversity,
 	/// Client identifier
 	pub client_version: String,
 }
@@ -249,6 +276,7 @@ impl NetworkConfiguration {
 			max_handshakes: 64,
 			reserved_protocols: HashMap::new(),
 			ip_filter: IpFilter::default(),
+			peer_diversity: PeerDiversity::default(),
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
 			client_version: "Parity-network".into(),
@@ -285,6 +313,9 @@ pub trait NetworkContext {
 	/// Disconnect a peer and prevent it from connecting again.
 	fn disable_peer(&self, peer: PeerId);
 
+	/// Disconnect a peer with the given reason and prevent it from connecting again.
+	fn disable_peer_with_reason(&self, peer: PeerId, reason: DisconnectReason);
+
 	/// Disconnect peer. Reconnect can be attempted later.
 	fn disconnect_peer(&self, peer: PeerId);
 
@@ -330,6 +361,10 @@ impl<'a, T> NetworkContext for &'a T where T: ?Sized + NetworkContext {
 		(**self).disable_peer(peer)
 	}
 
+	fn disable_peer_with_reason(&self, peer: PeerId, reason: DisconnectReason) {
+		(**self).disable_peer_with_reason(peer, reason)
+	}
+
 	fn disconnect_peer(&self, peer: PeerId) {
 		(**self).disconnect_peer(peer)
 	}
@@ -0,0 +1,291 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Limits on how many connected peers may share a network or a client implementation,
+//! making it harder for a single party to surround the node with its own peers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use ipnetwork::IpNetwork;
+
+/// Peer diversity limits. Reserved peers are exempt.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerDiversity {
+	/// Maximum number of peers in the same /24 IPv4 or /48 IPv6 subnet.
+	pub max_per_subnet: Option<usize>,
+	/// Maximum number of peers in the same autonomous system, according to `asn_map`.
+	pub max_per_asn: Option<usize>,
+	/// Networks and the autonomous system they belong to.
+	pub asn_map: AsnMap,
+	/// Maximum number of peers running the same client implementation.
+	pub max_per_client: Option<usize>,
+}
+
+/// A connected peer, as far as diversity is concerned.
+pub struct DiversityPeer {
+	/// Remote address.
+	pub address: IpAddr,
+	/// Client version string the peer announced.
+	pub client_version: String,
+}
+
+impl PeerDiversity {
+	/// Whether any limit is set.
+	pub fn is_enabled(&self) -> bool {
+		self.max_per_subnet.is_some() || self.max_per_asn.is_some() || self.max_per_client.is_some()
+	}
+
+	/// Checks whether `peer` can be connected alongside the already connected `peers`.
+	/// Returns the violated limit otherwise.
+	pub fn check<'a, I>(&self, peer: &DiversityPeer, peers: I) -> Result<(), &'static str>
+		where I: IntoIterator<Item = &'a DiversityPeer>
+	{
+		self.check_limits(&peer.address, Some(&peer.client_version), peers)
+	}
+
+	/// Checks whether a node at `address` can be dialed alongside the already connected `peers`.
+	/// The client implementation is only known after the handshake, so only the network limits apply.
+	pub fn check_dial<'a, I>(&self, address: &IpAddr, peers: I) -> Result<(), &'static str>
+		where I: IntoIterator<Item = &'a DiversityPeer>
+	{
+		self.check_limits(address, None, peers)
+	}
+
+	fn check_limits<'a, I>(&self, address: &IpAddr, client_version: Option<&str>, peers: I) -> Result<(), &'static str>
+		where I: IntoIterator<Item = &'a DiversityPeer>
+	{
+		if !self.is_enabled() {
+			return Ok(());
+		}
+
+		let subnet = subnet(address);
+		let asn = self.asn(address);
+		let client = client_version.map(client_name);
+		let (mut same_subnet, mut same_asn, mut same_client) = (0, 0, 0);
+		for other in peers {
+			if subnet == self::subnet(&other.address) {
+				same_subnet += 1;
+			}
+			if asn.is_some() && asn == self.asn(&other.address) {
+				same_asn += 1;
+			}
+			if client.as_ref().map_or(false, |client| *client == client_name(&other.client_version)) {
+				same_client += 1;
+			}
+		}
+
+		if self.max_per_subnet.map_or(false, |max| same_subnet >= max) {
+			return Err("too many peers in the subnet");
+		}
+		if self.max_per_asn.map_or(false, |max| same_asn >= max) {
+			return Err("too many peers in the autonomous system");
+		}
+		if self.max_per_client.map_or(false, |max| same_client >= max) {
+			return Err("too many peers running the client");
+		}
+		Ok(())
+	}
+
+	/// Autonomous system of an address, from the most specific matching network.
+	pub fn asn(&self, address: &IpAddr) -> Option<u32> {
+		self.asn_map.get(address)
+	}
+
+	/// Parses a map of networks to autonomous systems, one `<network> <asn>` pair per line,
+	/// e.g. `1.1.1.0/24 AS13335`. Empty lines and lines starting with `#` are ignored.
+	pub fn parse_asn_map(content: &str) -> Result<AsnMap, String> {
+		let mut map = HashMap::new();
+		for line in content.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+			let mut parts = line.split_whitespace();
+			let (network, asn) = match (parts.next(), parts.next(), parts.next()) {
+				(Some(network), Some(asn), None) => (network, asn),
+				_ => return Err(format!("Invalid AS map line: {}", line)),
+			};
+			let network = IpNetwork::from_str(network).map_err(|e| format!("Invalid network in AS map line {}: {}", line, e))?;
+			let asn = asn.trim_start_matches("AS").parse().map_err(|_| format!("Invalid AS number in AS map line: {}", line))?;
+			map.insert(network, asn);
+		}
+		Ok(AsnMap::new(map))
+	}
+}
+
+/// Map of address ranges to autonomous systems. Nested networks are flattened into disjoint
+/// ranges belonging to the most specific network, so a lookup is a single range search.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AsnMap {
+	/// Ranges of IPv6 and IPv4-mapped addresses by their first address, with the last address and the AS.
+	ranges: BTreeMap<u128, (u128, u32)>,
+}
+
+impl AsnMap {
+	/// Builds the map of the autonomous systems of `networks`.
+	pub fn new<I>(networks: I) -> Self where I: IntoIterator<Item = (IpNetwork, u32)> {
+		let mut networks: Vec<_> = networks.into_iter().map(|(network, asn)| {
+			let host_bits = match network {
+				IpNetwork::V4(_) => 32 - u32::from(network.prefix()),
+				IpNetwork::V6(_) => 128 - u32::from(network.prefix()),
+			};
+			let first = to_u128(&network.network());
+			let last = first | 1u128.checked_shl(host_bits).map_or(u128::max_value(), |size| size - 1);
+			(last - first, first, last, asn)
+		}).collect();
+		// Larger networks first, so that the networks nested in them override their ranges.
+		networks.sort_by(|a, b| b.0.cmp(&a.0));
+
+		let mut map = AsnMap::default();
+		for (_, first, last, asn) in networks {
+			map.insert(first, last, asn);
+		}
+		map
+	}
+
+	/// Autonomous system of an address.
+	pub fn get(&self, address: &IpAddr) -> Option<u32> {
+		let address = to_u128(address);
+		self.ranges.range(..=address).next_back()
+			.filter(|(_, (last, _))| address <= *last)
+			.map(|(_, (_, asn))| *asn)
+	}
+
+	fn insert(&mut self, first: u128, last: u128, asn: u32) {
+		// Cut the range starting before `first` and overlapping it.
+		if let Some((&start, &(end, other))) = self.ranges.range(..first).next_back() {
+			if end >= first {
+				self.ranges.insert(start, (first - 1, other));
+				if end > last {
+					self.ranges.insert(last + 1, (end, other));
+				}
+			}
+		}
+		// Drop the ranges starting inside, keeping the part of the last one going past `last`.
+		let inside: Vec<_> = self.ranges.range(first..=last).map(|(start, range)| (*start, *range)).collect();
+		for (start, (end, other)) in inside {
+			self.ranges.remove(&start);
+			if end > last {
+				self.ranges.insert(last + 1, (end, other));
+			}
+		}
+		self.ranges.insert(first, (last, asn));
+	}
+}
+
+/// Position of an address in the IPv6 space, IPv4 addresses are mapped to `::ffff:0:0/96`.
+fn to_u128(address: &IpAddr) -> u128 {
+	match *address {
+		IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+		IpAddr::V6(ip) => u128::from(ip),
+	}
+}
+
+/// The /24 subnet of an IPv4 address, or the /48 subnet of an IPv6 address.
+fn subnet(address: &IpAddr) -> IpAddr {
+	match *address {
+		IpAddr::V4(ip) => {
+			let o = ip.octets();
+			IpAddr::from([o[0], o[1], o[2], 0])
+		},
+		IpAddr::V6(ip) => {
+			let s = ip.segments();
+			IpAddr::from([s[0], s[1], s[2], 0, 0, 0, 0, 0])
+		},
+	}
+}
+
+/// Client implementation name, the first part of the version string, e.g. `Geth` for `Geth/v1.9.9/linux/go1.13`.
+fn client_name(client_version: &str) -> String {
+	client_version.split('/').next().unwrap_or(client_version).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn peer(address: &str, client_version: &str) -> DiversityPeer {
+		DiversityPeer { address: address.parse().unwrap(), client_version: client_version.into() }
+	}
+
+	#[test]
+	fn limits_peers_per_subnet_and_client() {
+		let diversity = PeerDiversity {
+			max_per_subnet: Some(2),
+			max_per_client: Some(2),
+			..Default::default()
+		};
+		let peers = || vec![
+			peer("10.0.0.1", "Geth/v1.9.9/linux/go1.13"),
+			peer("10.0.0.2", "OpenEthereum/v3.0.0/x86_64-linux-gnu/rustc1.43.0"),
+			peer("10.0.1.1", "Geth/v1.9.10/linux/go1.13"),
+		];
+
+		assert_eq!(diversity.check(&peer("10.0.0.3", "besu/v1.4"), &peers()), Err("too many peers in the subnet"));
+		assert_eq!(diversity.check(&peer("10.0.2.1", "Geth/v1.9.11"), &peers()), Err("too many peers running the client"));
+		assert_eq!(diversity.check(&peer("10.0.1.2", "besu/v1.4"), &peers()), Ok(()));
+		assert_eq!(PeerDiversity::default().check(&peer("10.0.0.3", "Geth/v1.9.11"), &peers()), Ok(()));
+
+		// dials are only limited by network
+		assert_eq!(diversity.check_dial(&"10.0.0.3".parse().unwrap(), &peers()), Err("too many peers in the subnet"));
+		assert_eq!(diversity.check_dial(&"10.0.2.1".parse().unwrap(), &peers()), Ok(()));
+	}
+
+	#[test]
+	fn limits_peers_per_asn() {
+		let diversity = PeerDiversity {
+			max_per_asn: Some(1),
+			asn_map: PeerDiversity::parse_asn_map("# cloud\n10.0.0.0/8 AS1\n\n10.1.0.0/16 2\n").unwrap(),
+			..Default::default()
+		};
+
+		assert_eq!(diversity.asn(&"10.0.0.1".parse().unwrap()), Some(1));
+		assert_eq!(diversity.asn(&"10.1.0.1".parse().unwrap()), Some(2));
+		assert_eq!(diversity.asn(&"192.168.0.1".parse().unwrap()), None);
+
+		let peers = || vec![peer("10.0.0.1", "Geth"), peer("192.168.0.1", "Geth")];
+		assert!(diversity.check(&peer("10.2.0.1", "Geth"), &peers()).is_err());
+		assert!(diversity.check(&peer("10.1.0.1", "Geth"), &peers()).is_ok());
+		// unknown networks are not limited
+		assert!(diversity.check(&peer("192.168.0.2", "Geth"), &peers()).is_ok());
+	}
+
+	#[test]
+	fn resolves_nested_networks() {
+		let map = PeerDiversity::parse_asn_map("10.0.0.0/8 1\n10.1.0.0/16 2\n10.1.2.0/24 3\n10.200.0.0/16 4\n2001:db8::/32 5\n").unwrap();
+		let asn = |address: &str| map.get(&address.parse().unwrap());
+
+		assert_eq!(asn("10.0.255.255"), Some(1));
+		assert_eq!(asn("10.1.0.0"), Some(2));
+		assert_eq!(asn("10.1.1.255"), Some(2));
+		assert_eq!(asn("10.1.2.0"), Some(3));
+		assert_eq!(asn("10.1.2.255"), Some(3));
+		assert_eq!(asn("10.1.3.0"), Some(2));
+		assert_eq!(asn("10.2.0.0"), Some(1));
+		assert_eq!(asn("10.200.1.1"), Some(4));
+		assert_eq!(asn("10.255.255.255"), Some(1));
+		assert_eq!(asn("11.0.0.0"), None);
+		assert_eq!(asn("9.255.255.255"), None);
+		assert_eq!(asn("2001:db8::1"), Some(5));
+		assert_eq!(asn("2001:db9::1"), None);
+	}
+
+	#[test]
+	fn rejects_invalid_asn_map() {
+		assert!(PeerDiversity::parse_asn_map("10.0.0.0/8").is_err());
+		assert!(PeerDiversity::parse_asn_map("10.0.0.0/33 AS1").is_err());
+		assert!(PeerDiversity::parse_asn_map("10.0.0.0/8 ASX").is_err());
+	}
+}
@@ -0,0 +1,65 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Name hashing of the Ethereum Name Service (EIP-137).
+
+use ethabi::{Address, Hash};
+use keccak_hash::keccak;
+
+/// Address of the ENS registry, the same on the main network and the public test networks.
+pub const ENS_REGISTRY: [u8; 20] = [
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x2e, 0x07, 0x4e, 0xc6,
+	0x9a, 0x0d, 0xfb, 0x29, 0x97, 0xba, 0x6c, 0x7d, 0x2e, 0x1e,
+];
+
+/// Node of `name` in the registry. The name must already be normalized.
+pub fn namehash(name: &str) -> Hash {
+	if name.is_empty() {
+		return Hash::zero();
+	}
+	name.rsplit('.').fold(Hash::zero(), |node, label| {
+		let mut data = [0u8; 64];
+		data[..32].copy_from_slice(node.as_bytes());
+		data[32..].copy_from_slice(keccak(label).as_bytes());
+		keccak(&data[..])
+	})
+}
+
+/// Name under which the reverse record of `address` is kept.
+pub fn reverse_name(address: &Address) -> String {
+	format!("{:x}.addr.reverse", address)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_hash_names() {
+		assert_eq!(namehash(""), Hash::zero());
+		assert_eq!(format!("{:x}", namehash("eth")), "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae");
+		assert_eq!(format!("{:x}", namehash("foo.eth")), "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f");
+	}
+
+	#[test]
+	fn should_name_reverse_records() {
+		assert_eq!(
+			reverse_name(&Address::from_low_u64_be(0xab)),
+			"00000000000000000000000000000000000000ab.addr.reverse"
+		);
+	}
+}
@@ -23,5 +23,7 @@ extern crate types;
 #[macro_use]
 extern crate ethabi_contract;
 
+mod ens;
 mod registrar;
+pub use ens::{ENS_REGISTRY, namehash, reverse_name};
 pub use registrar::RegistrarClient;
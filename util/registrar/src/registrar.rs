@@ -15,11 +15,15 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use call_contract::CallContract;
-use ethabi::Address;
+use ethabi::{Address, Hash};
 use keccak_hash::keccak;
 use types::ids::BlockId;
 
+use ens::{ENS_REGISTRY, namehash, reverse_name};
+
 use_contract!(registrar, "res/registrar.json");
+use_contract!(ens_registry, "res/ens_registry.json");
+use_contract!(ens_resolver, "res/ens_resolver.json");
 
 // Maps a domain name to an Ethereum address
 const DNS_A_RECORD: &'static str = "A";
@@ -52,4 +56,152 @@ pub trait RegistrarClient: CallContract + Send + Sync {
 			Ok(Some(address))
 		}
 	}
+
+	/// Get address of the ENS registry, `None` if the chain has none.
+	fn ens_registry_address(&self) -> Option<Address> {
+		Some(ENS_REGISTRY.into())
+	}
+
+	/// Get the resolver of an ENS node, `None` if it has none.
+	fn ens_resolver(&self, node: Hash, block: BlockId) -> Result<Option<Address>, String> {
+		use registrar::ens_registry::functions::resolver::{encode_input, decode_output};
+
+		let registry_address = match self.ens_registry_address() {
+			Some(address) => address,
+			None => return Err("ENS registry address not defined.".to_owned())
+		};
+
+		// a chain without the registry deployed has no code there, and returns nothing.
+		let resolver_bytes = self.call_contract(block, registry_address, encode_input(node))?;
+		if resolver_bytes.is_empty() {
+			return Ok(None)
+		}
+		let resolver = decode_output(&resolver_bytes).map_err(|e| e.to_string())?;
+		if resolver.is_zero() {
+			Ok(None)
+		} else {
+			Ok(Some(resolver))
+		}
+	}
+
+	/// Get the address an ENS name resolves to. The name must already be normalized.
+	fn ens_address(&self, name: &str, block: BlockId) -> Result<Option<Address>, String> {
+		use registrar::ens_resolver::functions::addr::{encode_input, decode_output};
+
+		let node = namehash(name);
+		let resolver = match self.ens_resolver(node, block)? {
+			Some(resolver) => resolver,
+			None => return Ok(None),
+		};
+
+		let address_bytes = self.call_contract(block, resolver, encode_input(node))?;
+		if address_bytes.is_empty() {
+			return Ok(None)
+		}
+		let address = decode_output(&address_bytes).map_err(|e| e.to_string())?;
+		if address.is_zero() {
+			Ok(None)
+		} else {
+			Ok(Some(address))
+		}
+	}
+
+	/// Get the primary ENS name of `address`: the name of its reverse record, if that name
+	/// resolves back to `address`. Anyone may claim any name in their own reverse record.
+	fn ens_name(&self, address: Address, block: BlockId) -> Result<Option<String>, String> {
+		use registrar::ens_resolver::functions::name::{encode_input, decode_output};
+
+		let node = namehash(&reverse_name(&address));
+		let resolver = match self.ens_resolver(node, block)? {
+			Some(resolver) => resolver,
+			None => return Ok(None),
+		};
+
+		let name_bytes = self.call_contract(block, resolver, encode_input(node))?;
+		if name_bytes.is_empty() {
+			return Ok(None)
+		}
+		let name = decode_output(&name_bytes).map_err(|e| e.to_string())?;
+		if name.is_empty() || self.ens_address(&name, block)? != Some(address) {
+			Ok(None)
+		} else {
+			Ok(Some(name))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use call_contract::CallContract;
+	use ethabi::{encode, Address, Token};
+	use types::ids::BlockId;
+
+	use ens::{ENS_REGISTRY, namehash, reverse_name};
+	use super::{ens_registry, ens_resolver, RegistrarClient};
+
+	/// Answers the calls it knows about with fixed outputs, and the others with nothing.
+	#[derive(Default)]
+	struct FakeEns {
+		outputs: HashMap<(Address, Vec<u8>), Vec<u8>>,
+	}
+
+	impl FakeEns {
+		fn set_resolver(&mut self, name: &str, resolver: Address) {
+			let input = ens_registry::functions::resolver::encode_input(namehash(name));
+			self.outputs.insert((ENS_REGISTRY.into(), input), encode(&[Token::Address(resolver)]));
+		}
+
+		fn set_addr(&mut self, resolver: Address, name: &str, address: Address) {
+			let input = ens_resolver::functions::addr::encode_input(namehash(name));
+			self.outputs.insert((resolver, input), encode(&[Token::Address(address)]));
+		}
+
+		fn set_name(&mut self, resolver: Address, address: Address, name: &str) {
+			let input = ens_resolver::functions::name::encode_input(namehash(&reverse_name(&address)));
+			self.outputs.insert((resolver, input), encode(&[Token::String(name.into())]));
+		}
+	}
+
+	impl CallContract for FakeEns {
+		fn call_contract(&self, _block: BlockId, address: Address, data: Vec<u8>) -> Result<Vec<u8>, String> {
+			Ok(self.outputs.get(&(address, data)).cloned().unwrap_or_default())
+		}
+	}
+
+	impl RegistrarClient for FakeEns {
+		fn registrar_address(&self) -> Option<Address> {
+			None
+		}
+	}
+
+	#[test]
+	fn should_resolve_names_through_their_resolver() {
+		let resolver = Address::from_low_u64_be(1);
+		let owner = Address::from_low_u64_be(2);
+		let mut ens = FakeEns::default();
+		ens.set_resolver("foo.eth", resolver);
+		ens.set_addr(resolver, "foo.eth", owner);
+
+		assert_eq!(ens.ens_address("foo.eth", BlockId::Latest), Ok(Some(owner)));
+		assert_eq!(ens.ens_address("bar.eth", BlockId::Latest), Ok(None));
+	}
+
+	#[test]
+	fn should_only_return_names_resolving_back_to_the_address() {
+		let resolver = Address::from_low_u64_be(1);
+		let owner = Address::from_low_u64_be(2);
+		let impostor = Address::from_low_u64_be(3);
+		let mut ens = FakeEns::default();
+		ens.set_resolver("foo.eth", resolver);
+		ens.set_addr(resolver, "foo.eth", owner);
+		for address in &[owner, impostor] {
+			ens.set_resolver(&reverse_name(address), resolver);
+			ens.set_name(resolver, *address, "foo.eth");
+		}
+
+		assert_eq!(ens.ens_name(owner, BlockId::Latest), Ok(Some("foo.eth".into())));
+		assert_eq!(ens.ens_name(impostor, BlockId::Latest), Ok(None));
+	}
 }
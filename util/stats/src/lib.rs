@@ -21,6 +21,13 @@ use std::ops::{Add, Sub, Deref, Div};
 
 #[macro_use]
 extern crate log;
+extern crate rand;
+
+mod prometheus;
+mod spans;
+
+pub use prometheus::{PrometheusMetrics, PrometheusRegistry, DurationHistogram, HistogramSnapshot};
+pub use spans::{Span, SpanExporter, SpanKind, FinishedSpan, Tracer, otlp_json};
 
 /// Sorted corpus of data.
 #[derive(Debug, Clone, PartialEq)]
@@ -0,0 +1,245 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Metrics in the Prometheus text exposition format.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Default bucket bounds of a `DurationHistogram`, in seconds.
+const DEFAULT_BUCKETS: [f64; 12] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0];
+
+/// Source of metrics rendered by the `/metrics` endpoint.
+pub trait PrometheusMetrics {
+	/// Append current values of the metrics to the registry.
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry);
+}
+
+/// Point-in-time copy of a `DurationHistogram`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramSnapshot {
+	/// Upper bounds of the buckets (in seconds) and cumulative count of observations within each.
+	pub buckets: Vec<(f64, u64)>,
+	/// Total number of observations.
+	pub count: u64,
+	/// Sum of all observations, in seconds.
+	pub sum: f64,
+}
+
+/// Lock-free histogram of durations.
+#[derive(Debug)]
+pub struct DurationHistogram {
+	bounds: Vec<f64>,
+	counts: Vec<AtomicU64>,
+	count: AtomicU64,
+	sum_micros: AtomicU64,
+}
+
+impl Default for DurationHistogram {
+	fn default() -> Self {
+		DurationHistogram::new(&DEFAULT_BUCKETS)
+	}
+}
+
+impl DurationHistogram {
+	/// Create a histogram with given upper bucket bounds, in seconds, sorted ascending.
+	pub fn new(bounds: &[f64]) -> Self {
+		DurationHistogram {
+			bounds: bounds.to_vec(),
+			counts: bounds.iter().map(|_| AtomicU64::new(0)).collect(),
+			count: AtomicU64::new(0),
+			sum_micros: AtomicU64::new(0),
+		}
+	}
+
+	/// Record a single observation.
+	pub fn observe(&self, duration: Duration) {
+		let secs = duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9;
+		if let Some(bucket) = self.bounds.iter().position(|bound| secs <= *bound) {
+			self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+		}
+		self.count.fetch_add(1, Ordering::Relaxed);
+		self.sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+	}
+
+	/// Take a snapshot of the histogram.
+	pub fn snapshot(&self) -> HistogramSnapshot {
+		let mut cumulative = 0;
+		let buckets = self.bounds.iter().zip(&self.counts).map(|(bound, count)| {
+			cumulative += count.load(Ordering::Relaxed);
+			(*bound, cumulative)
+		}).collect();
+
+		HistogramSnapshot {
+			buckets,
+			count: self.count.load(Ordering::Relaxed),
+			sum: self.sum_micros.load(Ordering::Relaxed) as f64 / 1e6,
+		}
+	}
+}
+
+/// Collects metrics and renders them in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct PrometheusRegistry {
+	prefix: String,
+	output: String,
+}
+
+impl PrometheusRegistry {
+	/// Create an empty registry. Every metric name is prefixed with `prefix`.
+	pub fn new(prefix: &str) -> Self {
+		PrometheusRegistry {
+			prefix: prefix.to_owned(),
+			output: String::new(),
+		}
+	}
+
+	/// Add a monotonically increasing counter.
+	pub fn register_counter(&mut self, name: &str, help: &str, value: u64) {
+		self.header(name, help, "counter");
+		let _ = writeln!(self.output, "{}{} {}", self.prefix, name, value);
+	}
+
+	/// Add a family of counters distinguished by the value of `label`.
+	pub fn register_labelled_counters<'a, I>(&mut self, name: &str, help: &str, label: &str, counters: I) where
+		I: IntoIterator<Item=(&'a str, u64)>,
+	{
+		self.header(name, help, "counter");
+		for (value, counter) in counters {
+			let _ = writeln!(self.output, "{}{}{{{}}} {}", self.prefix, name, label_pair(label, value), counter);
+		}
+	}
+
+	/// Add a gauge.
+	pub fn register_gauge(&mut self, name: &str, help: &str, value: i64) {
+		self.header(name, help, "gauge");
+		let _ = writeln!(self.output, "{}{} {}", self.prefix, name, value);
+	}
+
+	/// Add a family of gauges distinguished by the value of `label`.
+	pub fn register_labelled_gauges<'a, I>(&mut self, name: &str, help: &str, label: &str, gauges: I) where
+		I: IntoIterator<Item=(&'a str, i64)>,
+	{
+		self.header(name, help, "gauge");
+		for (value, gauge) in gauges {
+			let _ = writeln!(self.output, "{}{}{{{}}} {}", self.prefix, name, label_pair(label, value), gauge);
+		}
+	}
+
+	/// Add a histogram.
+	pub fn register_histogram(&mut self, name: &str, help: &str, histogram: &HistogramSnapshot) {
+		self.header(name, help, "histogram");
+		self.histogram_samples(name, None, histogram);
+	}
+
+	/// Add a family of histograms distinguished by the value of `label`.
+	pub fn register_labelled_histograms<'a, I>(&mut self, name: &str, help: &str, label: &str, histograms: I) where
+		I: IntoIterator<Item=(&'a str, &'a HistogramSnapshot)>,
+	{
+		self.header(name, help, "histogram");
+		for (value, histogram) in histograms {
+			self.histogram_samples(name, Some((label, value)), histogram);
+		}
+	}
+
+	/// Rendered metrics.
+	pub fn output(self) -> String {
+		self.output
+	}
+
+	fn header(&mut self, name: &str, help: &str, kind: &str) {
+		let _ = writeln!(self.output, "# HELP {}{} {}", self.prefix, name, help);
+		let _ = writeln!(self.output, "# TYPE {}{} {}", self.prefix, name, kind);
+	}
+
+	fn histogram_samples(&mut self, name: &str, label: Option<(&str, &str)>, histogram: &HistogramSnapshot) {
+		let (labels, prefix) = match label {
+			Some((label, value)) => {
+				let labels = label_pair(label, value);
+				let prefix = format!("{},", labels);
+				(format!("{{{}}}", labels), prefix)
+			},
+			None => (String::new(), String::new()),
+		};
+
+		for (bound, count) in &histogram.buckets {
+			let _ = writeln!(self.output, "{}{}_bucket{{{}le=\"{}\"}} {}", self.prefix, name, prefix, bound, count);
+		}
+		let _ = writeln!(self.output, "{}{}_bucket{{{}le=\"+Inf\"}} {}", self.prefix, name, prefix, histogram.count);
+		let _ = writeln!(self.output, "{}{}_sum{} {}", self.prefix, name, labels, histogram.sum);
+		let _ = writeln!(self.output, "{}{}_count{} {}", self.prefix, name, labels, histogram.count);
+	}
+}
+
+fn label_pair(label: &str, value: &str) -> String {
+	format!("{}=\"{}\"", label, value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::{DurationHistogram, PrometheusRegistry};
+
+	#[test]
+	fn should_collect_histogram() {
+		let histogram = DurationHistogram::new(&[0.01, 0.1]);
+		histogram.observe(Duration::from_millis(5));
+		histogram.observe(Duration::from_millis(50));
+		histogram.observe(Duration::from_millis(500));
+
+		let snapshot = histogram.snapshot();
+		assert_eq!(snapshot.buckets, vec![(0.01, 1), (0.1, 2)]);
+		assert_eq!(snapshot.count, 3);
+		assert_eq!(snapshot.sum, 0.555);
+	}
+
+	#[test]
+	fn should_render_text_format() {
+		let histogram = DurationHistogram::new(&[0.5]);
+		histogram.observe(Duration::from_millis(250));
+
+		let mut registry = PrometheusRegistry::new("oe_");
+		registry.register_counter("blocks_imported", "Blocks imported", 3);
+		registry.register_gauge("peers", "Connected peers", 5);
+		registry.register_labelled_histograms("rpc_seconds", "RPC timings", "method", vec![("eth_call", &histogram.snapshot())]);
+		registry.register_labelled_gauges("peer_credits", "Credits of peers", "peer", vec![("1", 10), ("2", 20)]);
+		registry.register_labelled_counters("db_ticker", "Database tickers", "name", vec![("rocksdb.block.cache.hit", 7)]);
+
+		assert_eq!(registry.output(), "\
+# HELP oe_blocks_imported Blocks imported
+# TYPE oe_blocks_imported counter
+oe_blocks_imported 3
+# HELP oe_peers Connected peers
+# TYPE oe_peers gauge
+oe_peers 5
+# HELP oe_rpc_seconds RPC timings
+# TYPE oe_rpc_seconds histogram
+oe_rpc_seconds_bucket{method=\"eth_call\",le=\"0.5\"} 1
+oe_rpc_seconds_bucket{method=\"eth_call\",le=\"+Inf\"} 1
+oe_rpc_seconds_sum{method=\"eth_call\"} 0.25
+oe_rpc_seconds_count{method=\"eth_call\"} 1
+# HELP oe_peer_credits Credits of peers
+# TYPE oe_peer_credits gauge
+oe_peer_credits{peer=\"1\"} 10
+oe_peer_credits{peer=\"2\"} 20
+# HELP oe_db_ticker Database tickers
+# TYPE oe_db_ticker counter
+oe_db_ticker{name=\"rocksdb.block.cache.hit\"} 7
+");
+	}
+}
@@ -0,0 +1,299 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Lightweight tracing spans, exportable in the OTLP/HTTP JSON encoding.
+
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand;
+
+/// Receives spans once they are finished.
+pub trait SpanExporter: Send + Sync {
+	/// Handle a finished span. Must not block.
+	fn export(&self, span: FinishedSpan);
+}
+
+/// Role of a span in a trace, as defined by OpenTelemetry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpanKind {
+	/// Operation internal to the node.
+	Internal,
+	/// Handling of a request of a remote client.
+	Server,
+}
+
+impl SpanKind {
+	fn otlp_code(self) -> u8 {
+		match self {
+			SpanKind::Internal => 1,
+			SpanKind::Server => 2,
+		}
+	}
+}
+
+/// Span which has been ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedSpan {
+	/// Identifier shared by all spans of a single trace.
+	pub trace_id: u128,
+	/// Identifier of this span.
+	pub span_id: u64,
+	/// Identifier of the parent span, `None` for the root of a trace.
+	pub parent_id: Option<u64>,
+	/// Name of the operation.
+	pub name: String,
+	/// Role of the span.
+	pub kind: SpanKind,
+	/// Time the operation started.
+	pub start: SystemTime,
+	/// Time the operation finished.
+	pub end: SystemTime,
+	/// Key-value annotations.
+	pub attributes: Vec<(String, String)>,
+}
+
+/// Creates spans and hands finished ones to an exporter.
+///
+/// A default (disabled) tracer creates no-op spans, so instrumented code
+/// doesn't have to check whether tracing is turned on.
+#[derive(Clone, Default)]
+pub struct Tracer {
+	exporter: Option<Arc<dyn SpanExporter>>,
+}
+
+impl Tracer {
+	/// Create a tracer sending finished spans to `exporter`.
+	pub fn new(exporter: Arc<dyn SpanExporter>) -> Self {
+		Tracer {
+			exporter: Some(exporter),
+		}
+	}
+
+	/// Returns true if spans created by this tracer are exported.
+	pub fn is_enabled(&self) -> bool {
+		self.exporter.is_some()
+	}
+
+	/// Start the root span of a new trace.
+	pub fn span(&self, name: &str) -> Span {
+		self.span_at(name, SystemTime::now())
+	}
+
+	/// Start the root span of a new trace which began at `start`.
+	pub fn span_at(&self, name: &str, start: SystemTime) -> Span {
+		Span::new(self.exporter.clone(), rand::random(), None, name, SpanKind::Internal, start)
+	}
+
+	/// Start the root span of a new trace handling a request of a remote client.
+	pub fn server_span(&self, name: &str) -> Span {
+		Span::new(self.exporter.clone(), rand::random(), None, name, SpanKind::Server, SystemTime::now())
+	}
+}
+
+/// Operation being timed. The span is exported when ended or dropped.
+pub struct Span {
+	exporter: Option<Arc<dyn SpanExporter>>,
+	data: Option<FinishedSpan>,
+}
+
+impl Span {
+	fn new(exporter: Option<Arc<dyn SpanExporter>>, trace_id: u128, parent_id: Option<u64>, name: &str, kind: SpanKind, start: SystemTime) -> Self {
+		let data = exporter.as_ref().map(|_| FinishedSpan {
+			trace_id,
+			span_id: rand::random(),
+			parent_id,
+			name: name.to_owned(),
+			kind,
+			start,
+			end: start,
+			attributes: Vec::new(),
+		});
+
+		Span {
+			exporter,
+			data,
+		}
+	}
+
+	/// Start a child span.
+	pub fn child(&self, name: &str) -> Span {
+		self.child_at(name, SystemTime::now())
+	}
+
+	/// Start a child span which began at `start`.
+	pub fn child_at(&self, name: &str, start: SystemTime) -> Span {
+		match self.data {
+			Some(ref data) => Span::new(self.exporter.clone(), data.trace_id, Some(data.span_id), name, SpanKind::Internal, start),
+			None => Span { exporter: None, data: None },
+		}
+	}
+
+	/// Annotate the span.
+	pub fn set_attribute<T: ToString>(&mut self, key: &str, value: T) {
+		if let Some(ref mut data) = self.data {
+			data.attributes.push((key.to_owned(), value.to_string()));
+		}
+	}
+
+	/// End the span now.
+	pub fn end(self) {
+		self.end_at(SystemTime::now())
+	}
+
+	/// End the span at given time.
+	pub fn end_at(mut self, end: SystemTime) {
+		self.finish(end);
+	}
+
+	fn finish(&mut self, end: SystemTime) {
+		if let (Some(exporter), Some(mut data)) = (self.exporter.as_ref(), self.data.take()) {
+			data.end = end;
+			exporter.export(data);
+		}
+	}
+}
+
+impl Drop for Span {
+	fn drop(&mut self) {
+		self.finish(SystemTime::now());
+	}
+}
+
+/// Encode spans as an OTLP `ExportTraceServiceRequest` in the protobuf JSON mapping.
+pub fn otlp_json(service_name: &str, spans: &[FinishedSpan]) -> String {
+	let mut out = String::new();
+	let _ = write!(
+		out,
+		r#"{{"resourceSpans":[{{"resource":{{"attributes":[{}]}},"scopeSpans":[{{"scope":{{"name":"openethereum"}},"spans":["#,
+		attribute_json("service.name", service_name),
+	);
+
+	for (i, span) in spans.iter().enumerate() {
+		if i > 0 {
+			out.push(',');
+		}
+		let _ = write!(out, r#"{{"traceId":"{:032x}","spanId":"{:016x}","#, span.trace_id, span.span_id);
+		if let Some(parent_id) = span.parent_id {
+			let _ = write!(out, r#""parentSpanId":"{:016x}","#, parent_id);
+		}
+		let attributes = span.attributes.iter().map(|(k, v)| attribute_json(k, v)).collect::<Vec<_>>().join(",");
+		let _ = write!(
+			out,
+			r#""name":{},"kind":{},"startTimeUnixNano":"{}","endTimeUnixNano":"{}","attributes":[{}]}}"#,
+			json_string(&span.name),
+			span.kind.otlp_code(),
+			unix_nanos(span.start),
+			unix_nanos(span.end),
+			attributes,
+		);
+	}
+
+	out.push_str("]}]}]}");
+	out
+}
+
+fn attribute_json(key: &str, value: &str) -> String {
+	format!(r#"{{"key":{},"value":{{"stringValue":{}}}}}"#, json_string(key), json_string(value))
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+	time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0)
+}
+
+fn json_string(s: &str) -> String {
+	let mut out = String::with_capacity(s.len() + 2);
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); },
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+	use std::time::{Duration, UNIX_EPOCH};
+	use super::{FinishedSpan, SpanExporter, SpanKind, Tracer, otlp_json};
+
+	#[derive(Default)]
+	struct Collector(Mutex<Vec<FinishedSpan>>);
+
+	impl SpanExporter for Collector {
+		fn export(&self, span: FinishedSpan) {
+			self.0.lock().unwrap().push(span);
+		}
+	}
+
+	#[test]
+	fn should_export_nested_spans() {
+		let collector = Arc::new(Collector::default());
+		let tracer = Tracer::new(collector.clone());
+
+		let mut root = tracer.span("block.import");
+		root.set_attribute("number", 42);
+		root.child("block.execute").end();
+		drop(root);
+
+		let spans = collector.0.lock().unwrap();
+		assert_eq!(spans.len(), 2);
+		assert_eq!(spans[0].name, "block.execute");
+		assert_eq!(spans[0].parent_id, Some(spans[1].span_id));
+		assert_eq!(spans[0].trace_id, spans[1].trace_id);
+		assert_eq!(spans[1].parent_id, None);
+		assert_eq!(spans[1].kind, SpanKind::Internal);
+		assert_eq!(spans[1].attributes, vec![("number".to_owned(), "42".to_owned())]);
+	}
+
+	#[test]
+	fn disabled_tracer_should_not_create_spans() {
+		let tracer = Tracer::default();
+		assert!(!tracer.is_enabled());
+		let mut span = tracer.span("rpc.request");
+		span.set_attribute("method", "eth_call");
+		span.child("child").end();
+	}
+
+	#[test]
+	fn should_encode_otlp_json() {
+		let span = FinishedSpan {
+			trace_id: 1,
+			span_id: 2,
+			parent_id: Some(3),
+			name: "rpc.request".into(),
+			kind: SpanKind::Server,
+			start: UNIX_EPOCH + Duration::from_secs(1),
+			end: UNIX_EPOCH + Duration::from_secs(2),
+			attributes: vec![("method".into(), "eth_call".into())],
+		};
+
+		assert_eq!(
+			otlp_json("oe", &[span]),
+			r#"{"resourceSpans":[{"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"oe"}}]},"scopeSpans":[{"scope":{"name":"openethereum"},"spans":[{"traceId":"00000000000000000000000000000001","spanId":"0000000000000002","parentSpanId":"0000000000000003","name":"rpc.request","kind":2,"startTimeUnixNano":"1000000000","endTimeUnixNano":"2000000000","attributes":[{"key":"method","value":{"stringValue":"eth_call"}}]}]}]}]}"#
+		);
+	}
+}